@@ -20,17 +20,28 @@ use clap::Parser;
 use clap_complete::generate;
 use clap_complete::Shell;
 use itertools::Itertools;
+use neptune_cash::api::export::Checkpoint;
+use neptune_cash::api::export::ComposerPayout;
+use neptune_cash::api::export::MembershipBundle;
+use neptune_cash::api::export::SignedReserveReport;
 use neptune_cash::api::export::TransactionKernelId;
+use neptune_cash::api::export::UtxoOwnershipProof;
+use neptune_cash::api::export::UtxoTriple;
 use neptune_cash::api::tx_initiation::builder::tx_output_list_builder::OutputFormat;
+use neptune_cash::api::wallet::WalletMigration;
 use neptune_cash::application::config::data_directory::DataDirectory;
 use neptune_cash::application::config::network::Network;
 use neptune_cash::application::rpc::auth;
 use neptune_cash::application::rpc::server::coinbase_output_readable::CoinbaseOutputReadable;
 use neptune_cash::application::rpc::server::error::RpcError;
 use neptune_cash::application::rpc::server::RPCClient;
+use neptune_cash::application::rpc::spending_pin::SpendingPinKey;
+use neptune_cash::application::rpc::spending_pin::AUTHORIZATION_LIFETIME;
 use neptune_cash::protocol::consensus::block::block_selector::BlockSelector;
 use neptune_cash::protocol::consensus::block::block_selector::BlockSelectorLiteral;
 use neptune_cash::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
+use neptune_cash::state::proof_upgrade_schedule::ProofUpgradeSchedule;
+use neptune_cash::state::proof_upgrade_schedule::UpgradeWindow;
 use neptune_cash::state::wallet::address::KeyType;
 use neptune_cash::state::wallet::address::ReceivingAddress;
 use neptune_cash::state::wallet::change_policy::ChangePolicy;
@@ -65,6 +76,10 @@ enum Command {
     /// retrieve network that neptune-core is running on
     Network,
 
+    /// retrieve the consensus constants of the network neptune-core is running
+    /// on
+    ChainParameters,
+
     /// retrieve address for peers to contact this neptune-core node
     OwnListenAddressForPeers,
 
@@ -74,6 +89,13 @@ enum Command {
     /// retrieve current block height
     BlockHeight,
 
+    /// show the progress of catching up with the network, stage by stage
+    SyncStatus,
+
+    /// show statistics on chain usage: mutator set size, anonymity set, and
+    /// additions and removals per block
+    MutatorSetStatistics,
+
     /// retrieve information about a block
     BlockInfo {
         /// one of: `genesis, tip, height/<n>, digest/<hex>`
@@ -85,6 +107,18 @@ enum Command {
         height: u64,
     },
 
+    /// export block headers, transaction kernels, and fees of a range of
+    /// blocks to CSV files in the node's data directory
+    ExportChainData {
+        /// height of the first block to export
+        #[clap(long, default_value_t = 0)]
+        from: u64,
+
+        /// height of the last block to export. the export stops at the tip.
+        #[clap(long)]
+        to: u64,
+    },
+
     /// get information about the current best block proposal
     BestBlockProposal,
 
@@ -97,6 +131,22 @@ enum Command {
     /// retrieve list of punished peers
     AllPunishedPeers,
 
+    /// retrieve the share of peers signaling readiness for upcoming consensus
+    /// changes
+    ForkReadiness,
+
+    /// write a bundle of diagnostics to attach to bug reports. Secrets and
+    /// local paths are redacted, as are network addresses unless included.
+    Diagnostics {
+        /// include the addresses of peers and of this node
+        #[clap(long)]
+        include_addresses: bool,
+
+        /// file to write the bundle to
+        #[clap(long, value_parser)]
+        file: PathBuf,
+    },
+
     /// retrieve digest/hash of newest block
     TipDigest,
     LatestTipDigests {
@@ -137,6 +187,24 @@ enum Command {
     /// Get next unused generation receiving address
     NextReceivingAddress,
 
+    /// Get a symmetric-key address for sending funds to this wallet itself.
+    ///
+    /// Outputs to symmetric-key addresses are cheaper than outputs to
+    /// generation addresses. The printed address is also the spending key:
+    /// anyone who knows it can spend the funds sent to it, so never share it.
+    NextSymmetricAddress,
+
+    /// List the symmetric-key addresses of this wallet.
+    ///
+    /// Shows the display form of each address, which is safe to show but
+    /// cannot be sent to.
+    SymmetricAddresses {
+        /// Show the full addresses, which can be sent to but which are also
+        /// the spending keys.
+        #[clap(long)]
+        reveal: bool,
+    },
+
     /// Get the nth generation receiving address.
     ///
     /// Ignoring the ones that have been generated in the past; re-generate them
@@ -160,6 +228,48 @@ enum Command {
     /// list known coins
     ListCoins,
 
+    /// prove to an auditor that the wallet controlled UTXOs at a block,
+    /// without disclosing keys. Verify with `verify-utxo-ownership-proof`.
+    ProveUtxoOwnership {
+        /// one of: `genesis, tip, height/<n>, digest/<hex>`
+        #[clap(long, default_value = "tip")]
+        block: BlockSelector,
+
+        /// AOCL leaf indices of the UTXOs to disclose. Discloses all unspent
+        /// UTXOs if omitted.
+        #[clap(long, value_delimiter = ',')]
+        aocl_leaf_indices: Vec<u64>,
+
+        /// file to write the proof to
+        #[clap(long, value_parser)]
+        file: PathBuf,
+    },
+
+    /// produce a signed proof-of-reserve report of all unspent UTXOs at a
+    /// block. Verify with `verify-reserve-report`.
+    ProofOfReserve {
+        /// one of: `genesis, tip, height/<n>, digest/<hex>`
+        #[clap(long, default_value = "tip")]
+        block: BlockSelector,
+
+        /// file to write the report to
+        #[clap(long, value_parser)]
+        file: PathBuf,
+    },
+
+    /// export the off-chain composer payouts to foreign addresses, e.g. the
+    /// members of a mining pool, that were mined. Recipients import them with
+    /// `import-utxo-claims`.
+    ExportComposerPayouts {
+        /// lowest block height to export payouts for
+        #[clap(long, default_value = "0")]
+        from_height: u64,
+
+        /// file to write the payouts to
+        #[clap(long, value_parser)]
+        file: PathBuf,
+    },
+
     /// retrieve count of transactions in the mempool
     MempoolTxCount,
 
@@ -235,6 +345,48 @@ enum Command {
         max_search_depth: Option<u64>,
     },
 
+    /// import composer payouts exported with `export-composer-payouts`.
+    /// Payouts to addresses of other wallets are skipped.
+    ImportUtxoClaims {
+        /// file to read the payouts from
+        #[clap(long, value_parser)]
+        file: PathBuf,
+
+        /// Indicates how many blocks to look back for the block that mined
+        /// the UTXOs.
+        max_search_depth: Option<u64>,
+    },
+
+    /// export, from an archival node, a bundle from which a wallet-only node
+    /// can assemble the membership proofs of its UTXOs. The wallet-only node
+    /// imports it with `import-membership-bundle`.
+    ExportMembershipBundle {
+        /// file to read the UTXOs from, as a JSON list of UTXO triples
+        #[clap(long, value_parser)]
+        utxos: PathBuf,
+
+        /// file to write the bundle to
+        #[clap(long, value_parser)]
+        file: PathBuf,
+    },
+
+    /// import a bundle exported with `export-membership-bundle`, and monitor
+    /// the UTXOs it was exported for.
+    ImportMembershipBundle {
+        /// file to read the UTXOs from, as a JSON list of UTXO triples
+        #[clap(long, value_parser)]
+        utxos: PathBuf,
+
+        /// file to read the bundle from
+        #[clap(long, value_parser)]
+        file: PathBuf,
+    },
+
+    /// answer a spending-PIN challenge, authorizing the next spend within a
+    /// minute. Required before every spend if neptune-core runs with
+    /// `--spending-pin-file`.
+    UnlockSpending,
+
     /// send a payment to a single recipient
     Send {
         /// recipient's address
@@ -289,6 +441,36 @@ enum Command {
         tx_kernel_id: TransactionKernelId,
     },
 
+    /// Cancel an own transaction that is still being proven, ie that was not
+    /// broadcast yet.
+    CancelTransaction {
+        tx_kernel_id: TransactionKernelId,
+    },
+
+    /// show when the node may upgrade proofs of 3rd party transactions, and
+    /// whether it currently does
+    ProofUpgradeSchedule,
+
+    /// set when the node may upgrade proofs of 3rd party transactions. the
+    /// schedule is not persisted across restarts.
+    SetProofUpgradeSchedule {
+        /// daily window, in the node's local time, within which upgrading may
+        /// start. may be given multiple times. if none is given, upgrading may
+        /// start at any time of day.
+        ///
+        /// E.g. --window 01:00-06:00
+        #[clap(long, value_name = "HH:MM-HH:MM")]
+        window: Vec<UpgradeWindow>,
+
+        /// CPU usage, in percent, at or above which upgrading is deferred
+        #[clap(long, value_name = "PERCENT")]
+        max_cpu: Option<u8>,
+
+        /// pause upgrading altogether
+        #[clap(long)]
+        paused: bool,
+    },
+
     /// Sends a command to the client to delete all transactions from the
     /// mempool.
     ClearMempool,
@@ -338,6 +520,20 @@ enum Command {
         num_blocks: u32,
     },
 
+    /// mark a stored block invalid, switching to the most canonical chain
+    /// without it. (regtest network only)
+    InvalidateBlock {
+        #[arg(value_parser = HexDigest::from_str)]
+        digest: HexDigest,
+    },
+
+    /// remove the invalid mark from a block, switching to the most canonical
+    /// chain. (regtest network only)
+    ReconsiderBlock {
+        #[arg(value_parser = HexDigest::from_str)]
+        digest: HexDigest,
+    },
+
     /******** WALLET -- offline actions ********/
     /// generate a new wallet
     GenerateWallet {
@@ -363,6 +559,26 @@ enum Command {
         network: Network,
     },
 
+    /// migrate the wallet into a fresh data directory, eg on removable media
+    /// or another disk.
+    ///
+    /// Copies keys, key counters, expected UTXOs, sent transactions and
+    /// off-chain utxo-transfer files, then verifies the copy. neptune-core must
+    /// not be running against either data directory.
+    MigrateWallet {
+        #[clap(long, default_value_t)]
+        network: Network,
+
+        /// data directory to migrate the wallet into
+        #[clap(long, value_parser)]
+        to_data_dir: PathBuf,
+
+        /// network of the destination. defaults to --network. Migrating
+        /// across networks is refused.
+        #[clap(long)]
+        to_network: Option<Network>,
+    },
+
     /// Combine shares from a t-out-of-n Shamir secret sharing scheme; reproduce
     /// the original secret and save it as a wallet secret.
     ShamirCombine {
@@ -380,6 +596,36 @@ enum Command {
         #[clap(long, default_value_t)]
         network: Network,
     },
+
+    /// derive a key from a spending PIN and store it in a new file, for use
+    /// with neptune-core's `--spending-pin-file`. Store it apart from the
+    /// wallet.
+    CreateSpendingPinFile {
+        #[clap(value_parser)]
+        file: PathBuf,
+    },
+
+    /// verify a proof produced by `prove-utxo-ownership`. Does not require a
+    /// running node.
+    VerifyUtxoOwnershipProof {
+        #[clap(value_parser)]
+        file: PathBuf,
+    },
+
+    /// verify a report produced by `proof-of-reserve` against a trusted
+    /// checkpoint of the chain. Does not require a running node.
+    VerifyReserveReport {
+        #[clap(value_parser)]
+        file: PathBuf,
+
+        /// JSON file holding a checkpoint of the block the report is for
+        #[clap(long, value_parser)]
+        snapshot: PathBuf,
+
+        /// network the report is for
+        #[clap(long, default_value_t)]
+        network: Network,
+    },
 }
 
 /// represents top-level cli args
@@ -387,8 +633,16 @@ enum Command {
 #[clap(name = "neptune-cli", about = "An RPC client")]
 struct Config {
     /// Sets the neptune-core rpc server localhost port to connect to.
-    #[clap(short, long, default_value = "9799", value_name = "port")]
-    port: u16,
+    ///
+    /// Defaults to the default rpc port of --network.
+    #[clap(short, long, value_name = "port")]
+    port: Option<u16>,
+
+    /// network of the neptune-core instance to connect to.
+    ///
+    /// Only determines the default --port.
+    #[clap(long, default_value_t)]
+    network: Network,
 
     /// neptune-core data directory containing wallet and blockchain state
     #[clap(long)]
@@ -424,6 +678,20 @@ async fn main() -> Result<()> {
             println!("{}", wallet_file.display());
             return Ok(());
         }
+        Command::CreateSpendingPinFile { file } => {
+            let pin = enter_spending_pin_dialog()?;
+            print!("Repeat PIN: ");
+            io::stdout().flush()?;
+            let mut buffer = "".to_string();
+            std::io::stdin().read_line(&mut buffer)?;
+            ensure!(pin == buffer.trim(), "PINs do not match.");
+
+            SpendingPinKey::generate(&pin).write_to_file(file)?;
+            println!("Spending PIN key stored in: {}", file.display());
+            println!("Start neptune-core with `--spending-pin-file` pointing to it.");
+
+            return Ok(());
+        }
         Command::GenerateWallet { network } => {
             let wallet_dir =
                 DataDirectory::get(args.data_dir.clone(), *network)?.wallet_directory_path();
@@ -527,6 +795,42 @@ async fn main() -> Result<()> {
             print_seed_phrase_dialog(wallet_secret.secret_key());
             return Ok(());
         }
+        Command::MigrateWallet {
+            network,
+            to_data_dir,
+            to_network,
+        } => {
+            let to_network = to_network.unwrap_or(*network);
+            let from = DataDirectory::get(args.data_dir.clone(), *network)?;
+            let to = DataDirectory::get(Some(to_data_dir.clone()), to_network)?;
+
+            println!("Migrating wallet from {from} to {to} ...");
+            let report = WalletMigration::new(from, *network, to, to_network)
+                .migrate()
+                .await?;
+
+            println!(
+                "Success. Verified {} files ({} bytes).",
+                report.files_verified, report.bytes_verified
+            );
+            match report.wallet_db {
+                Some(db) => println!(
+                    "Wallet database: {} monitored UTXOs, {} expected UTXOs, \
+                    {} sent transactions, synced to block {:x}.",
+                    db.num_monitored_utxos,
+                    db.num_expected_utxos,
+                    db.num_sent_transactions,
+                    db.sync_label
+                ),
+                None => println!("No wallet database found; only the wallet secret was migrated."),
+            }
+            println!(
+                "Start neptune-core with `--data-dir {}` to use the migrated wallet.",
+                to_data_dir.display()
+            );
+
+            return Ok(());
+        }
         Command::NthReceivingAddress { network, index } => {
             return get_nth_receiving_address(*network, args.data_dir.clone(), *index);
         }
@@ -718,11 +1022,69 @@ async fn main() -> Result<()> {
 
             return Ok(());
         }
+        Command::VerifyUtxoOwnershipProof { file } => {
+            let proof: UtxoOwnershipProof = serde_json::from_str(&std::fs::read_to_string(file)?)?;
+            let report = proof.verify()?;
+
+            println!("Proof is valid.");
+            println!("block: {:x}", report.block_digest);
+            println!("height: {}", report.block_height);
+            println!("mutator set hash: {:x}", report.mutator_set_hash);
+            for utxo in &report.utxos {
+                let release_date = utxo
+                    .release_date
+                    .map(|date| format!(", time-locked until {}", date.standard_format()))
+                    .unwrap_or_default();
+                println!(
+                    "AOCL leaf index {}: {}, lock script hash {:x}, receiver digest {:x}{release_date}",
+                    utxo.aocl_leaf_index, utxo.amount, utxo.lock_script_hash, utxo.receiver_digest,
+                );
+            }
+            println!("total: {}", report.total);
+            println!(
+                "\nCheck that the block belongs to the canonical chain and that the \
+                 mutator set after it has the hash above."
+            );
+
+            return Ok(());
+        }
+        Command::VerifyReserveReport {
+            file,
+            snapshot,
+            network,
+        } => {
+            let signed_report: SignedReserveReport =
+                serde_json::from_str(&std::fs::read_to_string(file)?)?;
+            let snapshot: Checkpoint = serde_json::from_str(&std::fs::read_to_string(snapshot)?)?;
+            let report = signed_report.verify(network, &snapshot).await?;
+
+            println!("Report is valid.");
+            println!("signer: {:x}", signed_report.signer);
+            println!(
+                "created: {}",
+                signed_report.report.created_at.standard_format()
+            );
+            println!("block: {:x}", report.block_digest);
+            println!("height: {}", report.block_height);
+            for (utxo, commitment) in report.utxos.iter().zip(&signed_report.report.commitments) {
+                println!(
+                    "AOCL leaf index {}: {}, commitment {commitment}",
+                    utxo.aocl_leaf_index, utxo.amount,
+                );
+            }
+            println!("total: {}", report.total);
+            println!("\nCheck that the signer is the wallet being audited.");
+
+            return Ok(());
+        }
         _ => {}
     }
 
     // all other operations need a connection to the server
-    let server_socket = SocketAddr::new(std::net::IpAddr::V4(Ipv4Addr::LOCALHOST), args.port);
+    let server_socket = SocketAddr::new(
+        std::net::IpAddr::V4(Ipv4Addr::LOCALHOST),
+        args.port.unwrap_or_else(|| args.network.default_rpc_port()),
+    );
     let Ok(transport) = tarpc::serde_transport::tcp::connect(server_socket, Json::default).await
     else {
         eprintln!("This command requires a connection to `neptune-core`, but that connection could not be established. Is `neptune-core` running?");
@@ -760,10 +1122,14 @@ async fn main() -> Result<()> {
         | Command::WhichWallet { .. }
         | Command::ExportSeedPhrase { .. }
         | Command::ImportSeedPhrase { .. }
+        | Command::MigrateWallet { .. }
         | Command::ShamirCombine { .. }
         | Command::ShamirShare { .. }
         | Command::NthReceivingAddress { .. }
-        | Command::PremineReceivingAddress { .. } => {
+        | Command::PremineReceivingAddress { .. }
+        | Command::CreateSpendingPinFile { .. }
+        | Command::VerifyUtxoOwnershipProof { .. }
+        | Command::VerifyReserveReport { .. } => {
             unreachable!("Case should be handled earlier.")
         }
 
@@ -772,10 +1138,64 @@ async fn main() -> Result<()> {
             let list = client.list_own_coins(ctx, token).await??;
             println!("{}", CoinWithPossibleTimeLock::report(&list));
         }
+        Command::ProveUtxoOwnership {
+            block,
+            aocl_leaf_indices,
+            file,
+        } => {
+            let Some(proof) = client
+                .prove_utxo_ownership(ctx, token, block, aocl_leaf_indices)
+                .await??
+            else {
+                bail!("Unknown block: {block}");
+            };
+
+            let mut writer = std::io::BufWriter::new(std::fs::File::create_new(&file)?);
+            serde_json::to_writer_pretty(&mut writer, &proof)?;
+            writer.flush()?;
+            println!(
+                "Wrote proof of ownership of {} UTXOs to {}",
+                proof.utxos.len(),
+                file.display()
+            );
+        }
+        Command::ExportComposerPayouts { from_height, file } => {
+            let payouts = client
+                .composer_payouts(ctx, token, from_height.into())
+                .await??;
+
+            let mut writer = std::io::BufWriter::new(std::fs::File::create_new(&file)?);
+            serde_json::to_writer_pretty(&mut writer, &payouts)?;
+            writer.flush()?;
+            println!(
+                "Wrote {} composer payouts to {}",
+                payouts.len(),
+                file.display()
+            );
+        }
+        Command::ProofOfReserve { block, file } => {
+            let Some(signed_report) = client.proof_of_reserve(ctx, token, block).await?? else {
+                bail!("Unknown block: {block}");
+            };
+
+            let mut writer = std::io::BufWriter::new(std::fs::File::create_new(&file)?);
+            serde_json::to_writer_pretty(&mut writer, &signed_report)?;
+            writer.flush()?;
+            println!(
+                "Wrote proof-of-reserve report of {} in {} UTXOs to {}",
+                signed_report.report.total,
+                signed_report.report.commitments.len(),
+                file.display()
+            );
+        }
         Command::Network => {
             // we already queries the network above.
             println!("{network}")
         }
+        Command::ChainParameters => {
+            let parameters = client.chain_parameters(ctx, token).await??;
+            print!("{parameters}");
+        }
         Command::OwnListenAddressForPeers => {
             let own_listen_address = client.own_listen_address_for_peers(ctx, token).await??;
             match own_listen_address {
@@ -791,6 +1211,14 @@ async fn main() -> Result<()> {
             let block_height = client.block_height(ctx, token).await??;
             println!("Block height: {block_height}")
         }
+        Command::SyncStatus => {
+            let status = client.sync_status(ctx, token).await??;
+            print!("{status}");
+        }
+        Command::MutatorSetStatistics => {
+            let statistics = client.mutator_set_statistics(ctx, token).await??;
+            print!("{statistics}");
+        }
         Command::BlockInfo { block_selector } => {
             let data = client.block_info(ctx, token, block_selector).await??;
             match data {
@@ -806,6 +1234,19 @@ async fn main() -> Result<()> {
                 println!("{digest:x}");
             }
         }
+        Command::ExportChainData { from, to } => {
+            let report = client
+                .export_chain_data(ctx, token, from.into(), to.into())
+                .await??;
+            println!(
+                "Exported {} blocks to {}",
+                report.num_blocks,
+                report.directory.display()
+            );
+            for file in report.files {
+                println!("{}", file.display());
+            }
+        }
         Command::BestBlockProposal => {
             let best_proposal = client.best_proposal(ctx, token).await??;
             match best_proposal {
@@ -836,6 +1277,52 @@ async fn main() -> Result<()> {
                 println!("{ip}\nstanding: {standing}\nlatest sanction: {latest_sanction_str} \n\n");
             }
         }
+        Command::ForkReadiness => {
+            let report = client.fork_readiness(ctx, token).await??;
+            println!(
+                "tip height: {}, connected peers: {}, recent peers: {}",
+                report.tip_height, report.connected_peers, report.recent_peers
+            );
+            for readiness in report.rule_sets {
+                let activation_height = readiness
+                    .activation_height
+                    .map_or("unknown".to_string(), |height| height.to_string());
+                let signaled_by_self = if readiness.signaled_by_self {
+                    "yes"
+                } else {
+                    "no"
+                };
+                println!(
+                    "{}\nactivation height: {activation_height}\nsignaled by self: {signaled_by_self}\n\
+                     connected peers ready: {} ({:.1}%)\nrecent peers ready: {} ({:.1}%)\n",
+                    readiness.rule_set,
+                    readiness.connected_peers_signaling,
+                    readiness.connected_peers_ready_percent,
+                    readiness.recent_peers_signaling,
+                    readiness.recent_peers_ready_percent,
+                );
+            }
+        }
+        Command::Diagnostics {
+            include_addresses,
+            file,
+        } => {
+            let bundle = client.diagnostics(ctx, token, include_addresses).await??;
+
+            let mut writer = std::io::BufWriter::new(std::fs::File::create_new(&file)?);
+            serde_json::to_writer_pretty(&mut writer, &bundle)?;
+            writer.flush()?;
+            println!("Wrote diagnostics to {}", file.display());
+            if !bundle.redactions.config_keys.is_empty() {
+                println!(
+                    "Redacted configuration: {}",
+                    bundle.redactions.config_keys.join(", ")
+                );
+            }
+            if bundle.redactions.addresses {
+                println!("Redacted network addresses");
+            }
+        }
         Command::TipDigest => {
             let head_hash = client
                 .block_digest(
@@ -901,6 +1388,30 @@ async fn main() -> Result<()> {
                 .await??;
             println!("{}", receiving_address.to_display_bech32m(network).unwrap())
         }
+        Command::NextSymmetricAddress => {
+            let address_data = client.next_symmetric_address(ctx, token).await??;
+            eprintln!(
+                "Warning: anyone who knows this address can spend the funds sent to it. \
+                Only use it for sending funds to this wallet."
+            );
+            println!("{}", address_data.address);
+        }
+        Command::SymmetricAddresses { reveal } => {
+            let addresses = client.symmetric_addresses(ctx, token).await??;
+            for address_data in addresses {
+                let address = if reveal {
+                    address_data.address
+                } else {
+                    address_data.display_address
+                };
+                let active = if address_data.is_active {
+                    " (active)"
+                } else {
+                    ""
+                };
+                println!("{}: {address}{active}", address_data.derivation_index);
+            }
+        }
         Command::MempoolTxCount => {
             let count: usize = client.mempool_tx_count(ctx, token).await??;
             println!("{count}");
@@ -1095,6 +1606,78 @@ async fn main() -> Result<()> {
                 println!("This claim has already been registered.");
             }
         }
+        Command::ImportUtxoClaims {
+            file,
+            max_search_depth,
+        } => {
+            let payouts: Vec<ComposerPayout> =
+                serde_json::from_str(&std::fs::read_to_string(file)?)?;
+
+            let mut num_imported = 0;
+            let mut num_known = 0;
+            let mut num_skipped = 0;
+            for payout in payouts {
+                match client
+                    .import_utxo_claim(ctx, token, payout.utxo_triple, max_search_depth)
+                    .await?
+                {
+                    Ok(true) => num_imported += 1,
+                    Ok(false) => num_known += 1,
+                    Err(e) => {
+                        println!("Skipping payout at height {}: {e}", payout.block_height);
+                        num_skipped += 1;
+                    }
+                }
+            }
+
+            println!(
+                "Imported {num_imported} UTXOs. {num_known} were already registered, \
+                 {num_skipped} were skipped."
+            );
+        }
+        Command::ExportMembershipBundle { utxos, file } => {
+            let utxo_triples: Vec<UtxoTriple> =
+                serde_json::from_str(&std::fs::read_to_string(utxos)?)?;
+            let addition_records = utxo_triples
+                .iter()
+                .map(UtxoTriple::addition_record)
+                .collect_vec();
+
+            let bundle = client
+                .utxo_membership_bundle(ctx, token, addition_records)
+                .await??;
+
+            let mut writer = std::io::BufWriter::new(std::fs::File::create_new(&file)?);
+            serde_json::to_writer_pretty(&mut writer, &bundle)?;
+            writer.flush()?;
+            println!(
+                "Wrote bundle for {} UTXOs at height {} to {}. {} UTXOs were not found.",
+                bundle.utxos.len(),
+                bundle.tip_height,
+                file.display(),
+                bundle.missing.len()
+            );
+        }
+        Command::ImportMembershipBundle { utxos, file } => {
+            let utxo_triples: Vec<UtxoTriple> =
+                serde_json::from_str(&std::fs::read_to_string(utxos)?)?;
+            let bundle: MembershipBundle = serde_json::from_str(&std::fs::read_to_string(file)?)?;
+
+            let num_imported = client
+                .import_membership_bundle(ctx, token, bundle, utxo_triples)
+                .await??;
+            println!("Imported {num_imported} UTXOs.");
+        }
+        Command::UnlockSpending => {
+            let pin = enter_spending_pin_dialog()?;
+            let challenge = client.spending_pin_challenge(ctx, token).await??;
+            let response = SpendingPinKey::derive(&pin, challenge.salt).respond(&challenge);
+            client.unlock_spending(ctx, token, response).await??;
+            println!(
+                "Spending unlocked. The next spend within {} seconds is authorized.",
+                AUTHORIZATION_LIFETIME.as_secs()
+            );
+        }
         Command::Send {
             address,
             amount,
@@ -1260,6 +1843,49 @@ async fn main() -> Result<()> {
                 println!("Found no transaction in need of upgrading");
             }
         }
+        Command::CancelTransaction { tx_kernel_id } => {
+            client
+                .cancel_transaction(ctx, token, tx_kernel_id)
+                .await??;
+            println!("Cancelled transaction {tx_kernel_id}");
+        }
+        Command::ProofUpgradeSchedule => {
+            let status = client.proof_upgrade_schedule(ctx, token).await??;
+            let windows = if status.schedule.windows.is_empty() {
+                "any time".to_string()
+            } else {
+                status.schedule.windows.iter().join(", ")
+            };
+            let max_cpu = match status.schedule.max_cpu_percent {
+                Some(percent) => format!("{percent}%"),
+                None => "unlimited".to_string(),
+            };
+            println!("windows: {windows}");
+            println!("max cpu: {max_cpu}");
+            println!("paused: {}", status.schedule.paused);
+            if let Some(cpu_percent) = status.cpu_percent {
+                println!("cpu usage: {cpu_percent:.1}%");
+            }
+            match status.deferral {
+                Some(deferral) => println!("upgrading deferred: {deferral}"),
+                None => println!("upgrading permitted by schedule"),
+            }
+        }
+        Command::SetProofUpgradeSchedule {
+            window,
+            max_cpu,
+            paused,
+        } => {
+            let schedule = ProofUpgradeSchedule {
+                windows: window,
+                max_cpu_percent: max_cpu,
+                paused,
+            };
+            client
+                .set_proof_upgrade_schedule(ctx, token, schedule)
+                .await??;
+            println!("Proof-upgrade schedule set");
+        }
         Command::ClearMempool => {
             println!("Sending command to delete all commands from the mempool.");
             client.clear_mempool(ctx, token).await??;
@@ -1321,6 +1947,14 @@ async fn main() -> Result<()> {
                 .await??;
             println!("Command completed successfully");
         }
+        Command::InvalidateBlock { digest } => {
+            let new_tip = client.invalidate_block(ctx, token, digest.0).await??;
+            println!("tip: {new_tip:x}");
+        }
+        Command::ReconsiderBlock { digest } => {
+            let new_tip = client.reconsider_block(ctx, token, digest.0).await??;
+            println!("tip: {new_tip:x}");
+        }
     }
 
     Ok(())
@@ -1490,6 +2124,17 @@ or use equivalent claim functionality of your chosen wallet software.
     Ok(())
 }
 
+fn enter_spending_pin_dialog() -> Result<String> {
+    print!("Spending PIN: ");
+    io::stdout().flush()?;
+    let mut buffer = "".to_string();
+    std::io::stdin().read_line(&mut buffer)?;
+    let pin = buffer.trim();
+    ensure!(!pin.is_empty(), "PIN must not be empty.");
+
+    Ok(pin.to_string())
+}
+
 fn enter_seed_phrase_dialog() -> Result<SecretKeyMaterial> {
     let mut phrase = vec![];
     let mut i = 1;
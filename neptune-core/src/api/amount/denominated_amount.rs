@@ -0,0 +1,96 @@
+//! provides an amount together with the denomination it is expressed in.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use super::error::AmountError;
+use super::Denomination;
+use crate::api::export::NativeCurrencyAmount;
+
+/// an amount of the native currency, expressed in a particular
+/// [Denomination].
+///
+/// parses from a decimal number optionally followed by a unit, eg `"1.5"`,
+/// `"1.5 NPT"`, or `"1500mNPT"`.  numbers without a unit are coins.  parsing
+/// fails rather than round, see [Denomination::parse()].
+///
+/// displays as the exact number followed by the unit, eg `"1.5 NPT"`.
+///
+/// ```
+/// use neptune_cash::api::amount::DenominatedAmount;
+/// use neptune_cash::api::amount::Denomination;
+/// use neptune_cash::api::export::NativeCurrencyAmount;
+///
+/// let fee: DenominatedAmount = "500 uNPT".parse()?;
+/// assert_eq!(Denomination::Millicoin.parse("0.5")?, fee.amount());
+///
+/// let in_coins = fee.with_denomination(Denomination::Coin);
+/// assert_eq!("0.0005 NPT", in_coins.to_string());
+/// # Ok::<(), neptune_cash::api::amount::error::AmountError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DenominatedAmount {
+    amount: NativeCurrencyAmount,
+    denomination: Denomination,
+}
+
+impl DenominatedAmount {
+    /// express `amount` in `denomination`.
+    pub fn new(amount: NativeCurrencyAmount, denomination: Denomination) -> Self {
+        Self {
+            amount,
+            denomination,
+        }
+    }
+
+    /// the amount, independent of its denomination.
+    pub fn amount(&self) -> NativeCurrencyAmount {
+        self.amount
+    }
+
+    /// the denomination the amount is expressed in.
+    pub fn denomination(&self) -> Denomination {
+        self.denomination
+    }
+
+    /// express the same amount in another denomination.
+    pub fn with_denomination(self, denomination: Denomination) -> Self {
+        Self::new(self.amount, denomination)
+    }
+}
+
+impl From<DenominatedAmount> for NativeCurrencyAmount {
+    fn from(amount: DenominatedAmount) -> Self {
+        amount.amount
+    }
+}
+
+impl Display for DenominatedAmount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}",
+            self.denomination.format(self.amount),
+            self.denomination
+        )
+    }
+}
+
+impl FromStr for DenominatedAmount {
+    type Err = AmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let unit_start = s.find(|c: char| c.is_alphabetic()).unwrap_or(s.len());
+        let (number, unit) = s.split_at(unit_start);
+
+        let denomination = if unit.is_empty() {
+            Denomination::Coin
+        } else {
+            unit.parse()?
+        };
+        let amount = denomination.parse(number.trim_end())?;
+
+        Ok(Self::new(amount, denomination))
+    }
+}
@@ -0,0 +1,220 @@
+//! provides the units in which amounts of the native currency are expressed.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use num_bigint::BigInt;
+use num_traits::Signed;
+use num_traits::Zero;
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::error::AmountError;
+use crate::api::export::NativeCurrencyAmount;
+
+/// a unit in which amounts of the native currency are expressed.
+///
+/// see the [module docs](super) for usage.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Denomination {
+    /// a whole coin.  symbol: `NPT`
+    #[default]
+    Coin,
+
+    /// a thousandth of a coin.  symbol: `mNPT`
+    Millicoin,
+
+    /// a millionth of a coin.  symbol: `uNPT`
+    Microcoin,
+
+    /// the atomic unit, 1 / (4 * 10^30) of a coin.  symbol: `nau`
+    Nau,
+}
+
+impl Denomination {
+    /// all denominations, from largest to smallest.
+    pub const ALL: [Self; 4] = [Self::Coin, Self::Millicoin, Self::Microcoin, Self::Nau];
+
+    /// the symbol with which amounts in this denomination are displayed.
+    ///
+    /// ```
+    /// use neptune_cash::api::amount::Denomination;
+    ///
+    /// assert_eq!("mNPT", Denomination::Millicoin.symbol());
+    /// ```
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Coin => "NPT",
+            Self::Millicoin => "mNPT",
+            Self::Microcoin => "uNPT",
+            Self::Nau => "nau",
+        }
+    }
+
+    /// the number of nau in one unit of this denomination.
+    ///
+    /// ```
+    /// use neptune_cash::api::amount::Denomination;
+    ///
+    /// assert_eq!(1, Denomination::Nau.nau_per_unit());
+    /// assert_eq!(
+    ///     1000 * Denomination::Millicoin.nau_per_unit(),
+    ///     Denomination::Coin.nau_per_unit()
+    /// );
+    /// ```
+    pub fn nau_per_unit(&self) -> i128 {
+        let nau_per_coin = NativeCurrencyAmount::conversion_factor();
+        match self {
+            Self::Coin => nau_per_coin,
+            Self::Millicoin => nau_per_coin / 1_000,
+            Self::Microcoin => nau_per_coin / 1_000_000,
+            Self::Nau => 1,
+        }
+    }
+
+    /// parse a decimal number of units of this denomination, eg `"-1.25"`.
+    ///
+    /// unlike
+    /// [coins_from_str()](NativeCurrencyAmount::coins_from_str), this never
+    /// rounds:  numbers that do not correspond to a whole number of nau are
+    /// rejected.
+    ///
+    /// ```
+    /// use neptune_cash::api::amount::Denomination;
+    /// use neptune_cash::api::export::NativeCurrencyAmount;
+    ///
+    /// assert_eq!(
+    ///     NativeCurrencyAmount::coins(3),
+    ///     Denomination::Millicoin.parse("3000")?
+    /// );
+    /// assert!(Denomination::Nau.parse("1.5").is_err());
+    /// # Ok::<(), neptune_cash::api::amount::error::AmountError>(())
+    /// ```
+    pub fn parse(&self, number: &str) -> Result<NativeCurrencyAmount, AmountError> {
+        let (units, num_decimals) = parse_decimal(number)?;
+        let scale = BigInt::from(10u8).pow(num_decimals);
+        let nau = units * BigInt::from(self.nau_per_unit());
+        if !(&nau % &scale).is_zero() {
+            return Err(AmountError::PrecisionLoss {
+                amount: number.to_owned(),
+                denomination: *self,
+            });
+        }
+
+        let nau = nau / scale;
+        if nau.abs() > BigInt::from(NativeCurrencyAmount::max().to_nau()) {
+            return Err(AmountError::OutOfRange(format!("{number} {self}")));
+        }
+        i128::try_from(nau)
+            .map(NativeCurrencyAmount::from_nau)
+            .map_err(|_| AmountError::OutOfRange(format!("{number} {self}")))
+    }
+
+    /// format the amount as a decimal number of units of this denomination,
+    /// without losing precision.  trailing zeros are omitted.
+    ///
+    /// ```
+    /// use neptune_cash::api::amount::Denomination;
+    /// use neptune_cash::api::export::NativeCurrencyAmount;
+    ///
+    /// let amount = NativeCurrencyAmount::coins(2).half();
+    /// assert_eq!("1", Denomination::Coin.format(amount));
+    /// assert_eq!("1000000", Denomination::Microcoin.format(amount));
+    /// ```
+    pub fn format(&self, amount: NativeCurrencyAmount) -> String {
+        let nau_per_unit = self.nau_per_unit();
+        let nau = amount.to_nau();
+        let sign = if nau.is_negative() { "-" } else { "" };
+        let nau = nau.unsigned_abs();
+        let nau_per_unit = nau_per_unit.unsigned_abs();
+
+        let integer_part = nau / nau_per_unit;
+        let mut remainder = nau % nau_per_unit;
+        let mut decimals = String::new();
+        while remainder != 0 {
+            remainder *= 10;
+            decimals.push(char::from(b'0' + (remainder / nau_per_unit) as u8));
+            remainder %= nau_per_unit;
+        }
+
+        if decimals.is_empty() {
+            format!("{sign}{integer_part}")
+        } else {
+            format!("{sign}{integer_part}.{decimals}")
+        }
+    }
+
+    /// convert a whole number of units of this denomination into an amount.
+    ///
+    /// fails if the result is outside the range of valid amounts.
+    pub fn from_units(&self, units: i128) -> Result<NativeCurrencyAmount, AmountError> {
+        units
+            .checked_mul(self.nau_per_unit())
+            .filter(|nau| nau.unsigned_abs() <= NativeCurrencyAmount::max().to_nau().unsigned_abs())
+            .map(NativeCurrencyAmount::from_nau)
+            .ok_or_else(|| AmountError::OutOfRange(format!("{units} {self}")))
+    }
+
+    /// convert the amount into a whole number of units of this denomination.
+    ///
+    /// fails if the amount is not a whole number of units.
+    pub fn to_units(&self, amount: NativeCurrencyAmount) -> Result<i128, AmountError> {
+        let nau = amount.to_nau();
+        if nau % self.nau_per_unit() != 0 {
+            return Err(AmountError::FractionalUnits {
+                nau,
+                denomination: *self,
+            });
+        }
+
+        Ok(nau / self.nau_per_unit())
+    }
+}
+
+/// parse `[-]digits[.digits]` into its digits as an integer, and the number
+/// of digits after the decimal point.
+fn parse_decimal(number: &str) -> Result<(BigInt, u32), AmountError> {
+    let invalid = || AmountError::InvalidFormat(number.to_owned());
+
+    let (is_negative, unsigned) = match number.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, number),
+    };
+    let (integer_part, fractional_part) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if integer_part.is_empty() && fractional_part.is_empty()
+        || !is_digits(integer_part)
+        || !is_digits(fractional_part)
+    {
+        return Err(invalid());
+    }
+
+    let digits = format!("{integer_part}{fractional_part}");
+    let magnitude = BigInt::from_str(&digits).map_err(|_| invalid())?;
+    let num_decimals = u32::try_from(fractional_part.len()).map_err(|_| invalid())?;
+    let units = if is_negative { -magnitude } else { magnitude };
+
+    Ok((units, num_decimals))
+}
+
+impl Display for Denomination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+impl FromStr for Denomination {
+    type Err = AmountError;
+
+    /// parse a denomination from its symbol, case-insensitively.  `µNPT` is
+    /// accepted for microcoins.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "npt" => Ok(Self::Coin),
+            "mnpt" => Ok(Self::Millicoin),
+            "unpt" | "µnpt" | "μnpt" => Ok(Self::Microcoin),
+            "nau" => Ok(Self::Nau),
+            _ => Err(AmountError::UnknownDenomination(s.to_owned())),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+//! provides error types related to parsing and converting amounts.
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::Denomination;
+
+/// enumerates possible amount errors
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum AmountError {
+    #[error("invalid amount {0:?}.  expected a decimal number, optionally followed by a unit")]
+    InvalidFormat(String),
+
+    #[error("unknown denomination {0:?}.  expected one of NPT, mNPT, uNPT, nau")]
+    UnknownDenomination(String),
+
+    #[error("{amount} {denomination} is not a whole number of nau, the smallest unit")]
+    PrecisionLoss {
+        amount: String,
+        denomination: Denomination,
+    },
+
+    #[error("{nau} nau is not a whole number of {denomination}")]
+    FractionalUnits {
+        nau: i128,
+        denomination: Denomination,
+    },
+
+    #[error("{0} is outside the range of valid amounts")]
+    OutOfRange(String),
+
+    #[error("total amount exceeds the maximum supply")]
+    TotalExceedsSupply,
+}
@@ -0,0 +1,44 @@
+//! provides exact parsing and formatting of amounts of the native currency.
+//!
+//! A [NativeCurrencyAmount](crate::api::export::NativeCurrencyAmount) counts
+//! nau, the atomic unit of the native currency.  One coin is 4 * 10^30 nau,
+//! so fractions of coins are easily mangled by integrators, eg when passing
+//! through an `f64`.
+//!
+//! The helpers in this module work on decimal strings instead.  Amounts can be
+//! expressed in any [Denomination], from whole coins down to nau.  Parsing
+//! never rounds: an input that does not correspond to a whole number of nau is
+//! rejected with [AmountError::PrecisionLoss](error::AmountError::PrecisionLoss).
+//!
+//! ```
+//! use neptune_cash::api::amount::DenominatedAmount;
+//! use neptune_cash::api::amount::Denomination;
+//! use neptune_cash::api::export::NativeCurrencyAmount;
+//!
+//! // amounts entered by users may carry a unit.  coins are the default.
+//! let price: DenominatedAmount = "2.5 mNPT".parse()?;
+//! assert_eq!(Denomination::Millicoin, price.denomination());
+//! assert_eq!("0.0025", Denomination::Coin.format(price.amount()));
+//! assert_eq!("2.5 mNPT", price.to_string());
+//!
+//! // conversion to and from whole units is checked.
+//! let one_nau = Denomination::Nau.from_units(1)?;
+//! assert!(Denomination::Microcoin.to_units(one_nau).is_err());
+//! assert_eq!(1, Denomination::Nau.to_units(one_nau)?);
+//!
+//! // inputs that would be rounded are rejected.
+//! assert!("0.5 nau".parse::<DenominatedAmount>().is_err());
+//! # Ok::<(), neptune_cash::api::amount::error::AmountError>(())
+//! ```
+//!
+//! Amounts received from clients, eg over RPC, can be checked against the
+//! supply with [ValidatedAmount], whose arithmetic cannot overflow.
+mod denominated_amount;
+mod denomination;
+mod validated_amount;
+
+// these represent the public API
+pub mod error;
+pub use denominated_amount::DenominatedAmount;
+pub use denomination::Denomination;
+pub use validated_amount::ValidatedAmount;
@@ -0,0 +1,174 @@
+//! provides amounts that are checked to be within the supply.
+
+use std::fmt::Display;
+
+use num_traits::CheckedAdd;
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::error::AmountError;
+use crate::api::export::NativeCurrencyAmount;
+
+/// a non-negative amount of the native currency that does not exceed the
+/// maximum supply of 42 000 000 coins.
+///
+/// a [NativeCurrencyAmount] received from a client can hold any `i128`, so
+/// summing such amounts with `+` can overflow, or yield a total that no
+/// transaction could ever balance.  a [ValidatedAmount] can only be created
+/// from a valid amount, and its arithmetic is checked.
+///
+/// serializes like the [NativeCurrencyAmount] it wraps.  deserializing an
+/// invalid amount fails.
+///
+/// ```
+/// use neptune_cash::api::amount::ValidatedAmount;
+/// use neptune_cash::api::export::NativeCurrencyAmount;
+///
+/// let fee = ValidatedAmount::new(NativeCurrencyAmount::coins(1))?;
+/// let output = ValidatedAmount::new(NativeCurrencyAmount::coins(41_999_999))?;
+/// assert_eq!(NativeCurrencyAmount::coins(42_000_000), fee.checked_add(output)?.amount());
+///
+/// // negative amounts, and totals exceeding the supply, are rejected.
+/// assert!(ValidatedAmount::new(-NativeCurrencyAmount::coins(1)).is_err());
+/// assert!(output.checked_add(output).is_err());
+/// # Ok::<(), neptune_cash::api::amount::error::AmountError>(())
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "NativeCurrencyAmount", into = "NativeCurrencyAmount")]
+pub struct ValidatedAmount(NativeCurrencyAmount);
+
+impl ValidatedAmount {
+    /// validate `amount`.  fails if it is negative or exceeds the supply.
+    pub fn new(amount: NativeCurrencyAmount) -> Result<Self, AmountError> {
+        if amount.is_negative() || amount > NativeCurrencyAmount::max() {
+            return Err(AmountError::OutOfRange(format!("{} nau", amount.to_nau())));
+        }
+
+        Ok(Self(amount))
+    }
+
+    /// the validated amount.
+    pub fn amount(&self) -> NativeCurrencyAmount {
+        self.0
+    }
+
+    /// add two amounts.  fails if the total exceeds the supply.
+    pub fn checked_add(self, other: Self) -> Result<Self, AmountError> {
+        self.0
+            .checked_add(&other.0)
+            .map(Self)
+            .ok_or(AmountError::TotalExceedsSupply)
+    }
+
+    /// validate and add up `amounts`.  fails if any of them is invalid, or if
+    /// their total exceeds the supply.
+    pub fn checked_sum(
+        amounts: impl IntoIterator<Item = NativeCurrencyAmount>,
+    ) -> Result<Self, AmountError> {
+        amounts
+            .into_iter()
+            .try_fold(Self::default(), |total, amount| {
+                total.checked_add(Self::new(amount)?)
+            })
+    }
+}
+
+impl TryFrom<NativeCurrencyAmount> for ValidatedAmount {
+    type Error = AmountError;
+
+    fn try_from(amount: NativeCurrencyAmount) -> Result<Self, Self::Error> {
+        Self::new(amount)
+    }
+}
+
+impl From<ValidatedAmount> for NativeCurrencyAmount {
+    fn from(amount: ValidatedAmount) -> Self {
+        amount.0
+    }
+}
+
+impl Display for ValidatedAmount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    fn nau(num_nau: i128) -> NativeCurrencyAmount {
+        NativeCurrencyAmount::from_nau(num_nau)
+    }
+
+    #[test]
+    fn only_amounts_within_supply_are_valid() {
+        let max = NativeCurrencyAmount::MAX_NAU;
+        for valid in [0, 1, max - 1, max] {
+            assert_eq!(
+                nau(valid),
+                ValidatedAmount::new(nau(valid)).unwrap().amount()
+            );
+        }
+        for invalid in [-1, -max, max + 1, i128::MAX, i128::MIN] {
+            assert!(matches!(
+                ValidatedAmount::new(nau(invalid)),
+                Err(AmountError::OutOfRange(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn totals_exceeding_supply_are_rejected() {
+        let max = ValidatedAmount::new(NativeCurrencyAmount::max()).unwrap();
+        let one = ValidatedAmount::new(nau(1)).unwrap();
+        let zero = ValidatedAmount::default();
+
+        assert_eq!(Ok(max), max.checked_add(zero));
+        assert_eq!(Err(AmountError::TotalExceedsSupply), max.checked_add(one));
+
+        // would overflow i128 if added unchecked
+        assert_eq!(Err(AmountError::TotalExceedsSupply), max.checked_add(max));
+
+        let half = NativeCurrencyAmount::MAX_NAU / 2;
+        let rest = NativeCurrencyAmount::MAX_NAU - half;
+        assert_eq!(
+            Ok(max),
+            ValidatedAmount::checked_sum([nau(half), nau(rest)])
+        );
+        assert_eq!(
+            Err(AmountError::TotalExceedsSupply),
+            ValidatedAmount::checked_sum([nau(half), nau(rest), nau(1)])
+        );
+        assert_eq!(Ok(zero), ValidatedAmount::checked_sum([]));
+    }
+
+    #[test]
+    fn invalid_summands_are_rejected_even_if_total_is_valid() {
+        let total = ValidatedAmount::checked_sum([nau(-1), nau(2)]);
+        assert!(matches!(total, Err(AmountError::OutOfRange(_))));
+
+        let total = ValidatedAmount::checked_sum([nau(i128::MAX), nau(i128::MIN)]);
+        assert!(matches!(total, Err(AmountError::OutOfRange(_))));
+    }
+
+    #[test]
+    fn serializes_like_native_currency_amount() {
+        let amount = NativeCurrencyAmount::coins(42);
+        let validated = ValidatedAmount::new(amount).unwrap();
+        assert_eq!(
+            serde_json::to_string(&amount).unwrap(),
+            serde_json::to_string(&validated).unwrap()
+        );
+        assert_eq!(
+            validated,
+            serde_json::from_str(&serde_json::to_string(&amount).unwrap()).unwrap()
+        );
+
+        for invalid in [nau(-1), nau(NativeCurrencyAmount::MAX_NAU + 1)] {
+            let json = serde_json::to_string(&invalid).unwrap();
+            assert!(serde_json::from_str::<ValidatedAmount>(&json).is_err());
+        }
+    }
+}
@@ -57,6 +57,11 @@ impl Api {
         self.global_state_lock.clone().into()
     }
 
+    /// retrieve a blockchain reader.
+    pub fn chain(&self) -> api::chain::Chain {
+        self.global_state_lock.clone().into()
+    }
+
     /// retrieve a transaction recipient in mutable context.
     pub fn wallet_mut(&mut self) -> api::wallet::Wallet<'_> {
         self.global_state_lock.clone().into()
@@ -0,0 +1,125 @@
+// private module.  no need for module docs.
+
+use futures::Stream;
+
+use super::error::ChainError;
+use crate::api::export::BlockHeight;
+use crate::api::export::Digest;
+use crate::api::export::Timestamp;
+use crate::protocol::consensus::block::block_header::BlockHeader;
+use crate::protocol::consensus::block::block_selector::BlockSelector;
+use crate::protocol::consensus::block::Block;
+use crate::GlobalStateLock;
+
+/// a block of the canonical chain, as yielded by [CanonicalBlocks].
+#[derive(Debug, Clone)]
+pub struct BlockView {
+    digest: Digest,
+    block: Block,
+}
+
+impl BlockView {
+    /// the block's digest
+    pub fn digest(&self) -> Digest {
+        self.digest
+    }
+
+    /// the block's height
+    pub fn height(&self) -> BlockHeight {
+        self.block.header().height
+    }
+
+    /// the block's header
+    pub fn header(&self) -> &BlockHeader {
+        self.block.header()
+    }
+
+    /// the block's timestamp
+    pub fn timestamp(&self) -> Timestamp {
+        self.block.header().timestamp
+    }
+
+    /// the full block
+    pub fn block(&self) -> &Block {
+        &self.block
+    }
+
+    /// convert into the full block
+    pub fn into_block(self) -> Block {
+        self.block
+    }
+}
+
+/// an async iterator over blocks of the canonical chain, in ascending order
+/// of height.
+///
+/// Instances are obtained via [Chain](super::Chain).
+///
+/// Each call to [next()](Self::next()) acquires the global state lock only
+/// for as long as it takes to load a single block, so iterating does not
+/// block the node from making progress.
+///
+/// Iteration ends after the last requested height, or when the tip has been
+/// reached, or after the first error.
+#[derive(Debug)]
+pub struct CanonicalBlocks {
+    global_state_lock: GlobalStateLock,
+    next_height: BlockHeight,
+    last_height: BlockHeight,
+    done: bool,
+}
+
+impl CanonicalBlocks {
+    pub(super) fn new(
+        global_state_lock: GlobalStateLock,
+        first_height: BlockHeight,
+        last_height: BlockHeight,
+    ) -> Self {
+        Self {
+            global_state_lock,
+            next_height: first_height,
+            last_height,
+            done: false,
+        }
+    }
+
+    /// yield the next canonical block, or `None` if iteration has ended.
+    pub async fn next(&mut self) -> Option<Result<BlockView, ChainError>> {
+        if self.done || self.next_height > self.last_height {
+            return None;
+        }
+
+        let item = self.load(self.next_height).await.transpose();
+        match item {
+            Some(Ok(_)) => self.next_height = self.next_height.next(),
+            _ => self.done = true,
+        }
+
+        item
+    }
+
+    /// convert into a [Stream], for use with stream combinators.
+    pub fn into_stream(self) -> impl Stream<Item = Result<BlockView, ChainError>> + Send {
+        futures::stream::unfold(self, |mut blocks| async move {
+            blocks.next().await.map(|item| (item, blocks))
+        })
+    }
+
+    /// load the canonical block at the given height, or `None` if the height
+    /// exceeds that of the tip.
+    async fn load(&self, height: BlockHeight) -> Result<Option<BlockView>, ChainError> {
+        let state = self.global_state_lock.lock_guard().await;
+        let Some(digest) = BlockSelector::Height(height).as_digest(&state).await else {
+            return Ok(None);
+        };
+
+        let block = state
+            .chain
+            .archival_state()
+            .get_block(digest)
+            .await?
+            .ok_or(ChainError::MissingBlock(height))?;
+
+        Ok(Some(BlockView { digest, block }))
+    }
+}
@@ -0,0 +1,173 @@
+// private module.  no need for module docs.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::fs::File;
+use tokio::io::AsyncWriteExt;
+use tokio::io::BufWriter;
+
+use super::canonical_blocks::BlockView;
+use crate::api::export::BlockHeight;
+
+const HEADERS_FILE_NAME: &str = "headers.csv";
+const HEADERS_COLUMNS: &[&str] = &[
+    "height",
+    "digest",
+    "prev_block_digest",
+    "timestamp_ms",
+    "version",
+    "difficulty",
+    "cumulative_proof_of_work",
+];
+
+const KERNELS_FILE_NAME: &str = "transaction_kernels.csv";
+const KERNELS_COLUMNS: &[&str] = &[
+    "height",
+    "txid",
+    "timestamp_ms",
+    "num_inputs",
+    "num_outputs",
+    "num_announcements",
+    "merge_bit",
+    "mutator_set_hash",
+];
+
+const FEES_FILE_NAME: &str = "fees.csv";
+const FEES_COLUMNS: &[&str] = &["height", "fee_nau", "coinbase_nau", "block_size"];
+
+/// describes the files written by
+/// [Chain::export_csv()](super::Chain::export_csv()).
+///
+/// ```
+/// # use neptune_cash::api::export::GlobalStateLock;
+/// # use neptune_cash::api::export::BlockHeight;
+/// # use neptune_cash::api::chain::error::ChainError;
+/// #
+/// # async fn example(gsl: GlobalStateLock) -> Result<(), ChainError> {
+/// let heights = BlockHeight::genesis()..=BlockHeight::from(100u64);
+/// let report = gsl.api().chain().export_csv(heights).await?;
+///
+/// for file in &report.files {
+///     println!("wrote {}", file.display());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainExportReport {
+    /// the directory the files were written to
+    pub directory: PathBuf,
+
+    /// the files written, one per table: block headers, transaction kernels,
+    /// and fees
+    pub files: Vec<PathBuf>,
+
+    /// the number of blocks exported
+    pub num_blocks: u64,
+
+    /// the height of the last exported block, or `None` if no block was
+    /// exported
+    pub last_height: Option<BlockHeight>,
+}
+
+/// a CSV file that is written one row at a time.
+struct CsvFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl CsvFile {
+    async fn create(directory: &Path, name: &str, columns: &[&str]) -> std::io::Result<Self> {
+        let path = directory.join(name);
+        let writer = BufWriter::new(File::create_new(&path).await?);
+        let mut csv_file = Self { path, writer };
+        csv_file.write_row(columns).await?;
+
+        Ok(csv_file)
+    }
+
+    async fn write_row<T: AsRef<str>>(&mut self, fields: &[T]) -> std::io::Result<()> {
+        let row = fields
+            .iter()
+            .map(|field| field.as_ref())
+            .collect::<Vec<_>>();
+        self.writer
+            .write_all(format!("{}\n", row.join(",")).as_bytes())
+            .await
+    }
+
+    async fn finish(mut self) -> std::io::Result<PathBuf> {
+        self.writer.flush().await?;
+        Ok(self.path)
+    }
+}
+
+/// the tables of an export in progress.
+pub(super) struct CsvTables {
+    headers: CsvFile,
+    kernels: CsvFile,
+    fees: CsvFile,
+}
+
+impl CsvTables {
+    /// create the tables in `directory`, which must not contain them yet.
+    pub(super) async fn create(directory: &Path) -> std::io::Result<Self> {
+        Ok(Self {
+            headers: CsvFile::create(directory, HEADERS_FILE_NAME, HEADERS_COLUMNS).await?,
+            kernels: CsvFile::create(directory, KERNELS_FILE_NAME, KERNELS_COLUMNS).await?,
+            fees: CsvFile::create(directory, FEES_FILE_NAME, FEES_COLUMNS).await?,
+        })
+    }
+
+    /// append a row for `block` to each table.
+    pub(super) async fn write_block(&mut self, block: &BlockView) -> std::io::Result<()> {
+        let header = block.header();
+        let height = block.height().to_string();
+        self.headers
+            .write_row(&[
+                height.clone(),
+                block.digest().to_hex(),
+                header.prev_block_digest.to_hex(),
+                header.timestamp.to_millis().to_string(),
+                header.version.to_string(),
+                header.difficulty.to_string(),
+                header.cumulative_proof_of_work.to_string(),
+            ])
+            .await?;
+
+        let kernel = &block.block().body().transaction_kernel;
+        self.kernels
+            .write_row(&[
+                height.clone(),
+                kernel.txid().to_string(),
+                kernel.timestamp.to_millis().to_string(),
+                kernel.inputs.len().to_string(),
+                kernel.outputs.len().to_string(),
+                kernel.announcements.len().to_string(),
+                kernel.merge_bit.to_string(),
+                kernel.mutator_set_hash.to_hex(),
+            ])
+            .await?;
+
+        self.fees
+            .write_row(&[
+                height,
+                kernel.fee.to_nau().to_string(),
+                kernel.coinbase.unwrap_or_default().to_nau().to_string(),
+                block.block().size().to_string(),
+            ])
+            .await
+    }
+
+    /// flush all tables and return their paths.
+    pub(super) async fn finish(self) -> std::io::Result<Vec<PathBuf>> {
+        Ok(vec![
+            self.headers.finish().await?,
+            self.kernels.finish().await?,
+            self.fees.finish().await?,
+        ])
+    }
+}
@@ -0,0 +1,470 @@
+// private module.  no need for module docs.
+
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::path::PathBuf;
+
+use tasm_lib::twenty_first::util_types::mmr::mmr_trait::Mmr;
+
+use super::canonical_blocks::CanonicalBlocks;
+use super::chain_export::ChainExportReport;
+use super::chain_export::CsvTables;
+use super::chain_work::ChainWork;
+use super::chain_work::ForkChoiceAudit;
+use super::data_commitment::DataCommitment;
+use super::data_commitment::DataCommitmentProof;
+use super::error::ChainError;
+use crate::api::export::BlockHeight;
+use crate::api::export::Digest;
+use crate::GlobalStateLock;
+
+/// provides an API for reading the blockchain.
+///
+/// Instances are obtained via [Api::chain()](crate::api::Api::chain()).
+///
+/// Methods that return block data require an archival node, and return
+/// [ChainError::NotArchival] otherwise.
+#[derive(Debug)]
+pub struct Chain {
+    worker: ChainPrivate,
+}
+
+impl From<GlobalStateLock> for Chain {
+    fn from(gsl: GlobalStateLock) -> Self {
+        Self {
+            worker: ChainPrivate::new(gsl),
+        }
+    }
+}
+
+// these methods just call a worker method, so the public API
+// is easy to read and digest.  Please keep it that way.
+impl Chain {
+    /// walk the canonical blocks with heights in the given range.
+    ///
+    /// Blocks are yielded in ascending order of height. Iteration stops early
+    /// if the end of the range lies beyond the tip.
+    ///
+    /// The returned [CanonicalBlocks] does not hold the global state lock
+    /// in-between blocks. If a reorganization happens while iterating, later
+    /// blocks are taken from the new canonical chain.
+    ///
+    /// ```
+    /// # use neptune_cash::api::export::GlobalStateLock;
+    /// # use neptune_cash::api::export::BlockHeight;
+    /// # use neptune_cash::api::chain::error::ChainError;
+    /// #
+    /// # async fn example(gsl: GlobalStateLock) -> Result<(), ChainError> {
+    /// let heights = BlockHeight::genesis()..=BlockHeight::from(100u64);
+    /// let mut blocks = gsl.api().chain().canonical_blocks(heights).await?;
+    ///
+    /// while let Some(block) = blocks.next().await {
+    ///     let block = block?;
+    ///     println!("{}: {}", block.height(), block.digest());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn canonical_blocks(
+        &self,
+        heights: RangeInclusive<BlockHeight>,
+    ) -> Result<CanonicalBlocks, ChainError> {
+        self.worker.canonical_blocks(heights).await
+    }
+
+    /// walk the canonical chain from the block with the given digest up to
+    /// the tip.
+    ///
+    /// The block with the given digest is the first block yielded. The tip is
+    /// determined when this method is called; blocks added afterwards are not
+    /// yielded.
+    ///
+    /// Returns [ChainError::UnknownBlock] if the block is not known and
+    /// [ChainError::NotCanonical] if it has been orphaned.
+    ///
+    /// ```
+    /// # use neptune_cash::api::export::GlobalStateLock;
+    /// # use neptune_cash::api::export::Digest;
+    /// # use neptune_cash::api::chain::error::ChainError;
+    /// #
+    /// # async fn example(gsl: GlobalStateLock, last_indexed: Digest) -> Result<(), ChainError> {
+    /// let mut blocks = gsl.api().chain().canonical_blocks_from(last_indexed).await?;
+    ///
+    /// while let Some(block) = blocks.next().await {
+    ///     let block = block?;
+    ///     println!("{} mined at {}", block.digest(), block.timestamp());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn canonical_blocks_from(
+        &self,
+        digest: Digest,
+    ) -> Result<CanonicalBlocks, ChainError> {
+        self.worker.canonical_blocks_from(digest).await
+    }
+
+    /// produce a [DataCommitmentProof] that the given block contains the
+    /// given commitment.
+    ///
+    /// The proof is anchored to the current tip, ie it verifies against the
+    /// block MMR accumulator in the tip's body. As a consequence, the block
+    /// containing the commitment must not be the tip itself.
+    ///
+    /// Returns [ChainError::CommitmentNotInBlock] if the block does not contain
+    /// the commitment, and [ChainError::NoSuccessor] if the block is the tip.
+    pub async fn prove_data_commitment(
+        &self,
+        commitment: DataCommitment,
+        block_digest: Digest,
+    ) -> Result<DataCommitmentProof, ChainError> {
+        self.worker
+            .prove_data_commitment(commitment, block_digest)
+            .await
+    }
+
+    /// search the canonical blocks with heights in the given range for the
+    /// given commitment and produce a [DataCommitmentProof] for the first
+    /// block that contains it.
+    ///
+    /// Returns `Ok(None)` if no block in the range contains the commitment.
+    ///
+    /// see [prove_data_commitment()](Self::prove_data_commitment()) for
+    /// details.
+    ///
+    /// ```
+    /// # use neptune_cash::api::export::GlobalStateLock;
+    /// # use neptune_cash::api::export::BlockHeight;
+    /// # use neptune_cash::api::chain::DataCommitment;
+    /// # use neptune_cash::api::chain::error::ChainError;
+    /// #
+    /// # async fn example(gsl: GlobalStateLock, submitted_at: BlockHeight) -> Result<(), ChainError> {
+    /// let commitment = DataCommitment::from_bytes(b"my important document");
+    /// let heights = submitted_at..=submitted_at + 100;
+    ///
+    /// if let Some(proof) = gsl.api().chain().find_data_commitment(commitment, heights).await? {
+    ///     println!("document existed at {}", proof.timestamp());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn find_data_commitment(
+        &self,
+        commitment: DataCommitment,
+        heights: RangeInclusive<BlockHeight>,
+    ) -> Result<Option<DataCommitmentProof>, ChainError> {
+        self.worker.find_data_commitment(commitment, heights).await
+    }
+
+    /// export the canonical blocks with heights in the given range to CSV
+    /// files, for offline analytics.
+    ///
+    /// Three files are written to a new directory below
+    /// [DataDirectory::chain_exports_dir_path()](crate::application::config::data_directory::DataDirectory::chain_exports_dir_path()),
+    /// named after the range:
+    ///
+    /// - `headers.csv`: height, digest, predecessor, timestamp, version,
+    ///   difficulty, and cumulative proof-of-work of each block.
+    /// - `transaction_kernels.csv`: id, timestamp, number of inputs, outputs
+    ///   and announcements, merge bit, and mutator set hash of each block's
+    ///   transaction.
+    /// - `fees.csv`: fee and coinbase, in nau, and size, in field elements,
+    ///   of each block.
+    ///
+    /// Blocks are written as they are read, so memory usage does not grow
+    /// with the size of the range. Like
+    /// [canonical_blocks()](Self::canonical_blocks()), the export stops at
+    /// the tip.
+    ///
+    /// Returns [ChainError::ExportFailed] if the directory exists already,
+    /// so earlier exports are never overwritten. The files are written to a
+    /// temporary directory that is renamed once the export succeeded, so a
+    /// failed export can be retried.
+    ///
+    /// see [ChainExportReport] for example usage.
+    pub async fn export_csv(
+        &self,
+        heights: RangeInclusive<BlockHeight>,
+    ) -> Result<ChainExportReport, ChainError> {
+        self.worker.export_csv(heights).await
+    }
+
+    /// the proof-of-work accumulated by the chain up to the block with the
+    /// given digest.
+    ///
+    /// The tip's chain work is always available. For other blocks, an
+    /// archival node is required. Blocks that are stored but not canonical,
+    /// eg the tips of abandoned forks, are reported too.
+    ///
+    /// Returns [ChainError::UnknownBlock] if the block is not stored.
+    ///
+    /// ```
+    /// # use neptune_cash::api::export::GlobalStateLock;
+    /// # use neptune_cash::api::export::Digest;
+    /// # use neptune_cash::api::chain::error::ChainError;
+    /// #
+    /// # async fn example(gsl: GlobalStateLock, digest: Digest) -> Result<(), ChainError> {
+    /// let work = gsl.api().chain().chain_work(digest).await?;
+    /// println!(
+    ///     "block {} at height {} has cumulative work {}",
+    ///     work.digest, work.height, work.cumulative_proof_of_work
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn chain_work(&self, digest: Digest) -> Result<ChainWork, ChainError> {
+        self.worker.chain_work(digest).await
+    }
+
+    /// explain the current fork-choice decision.
+    ///
+    /// Searches the stored blocks from `depth` heights below the tip upwards
+    /// for competing tips, ie non-canonical blocks without a stored
+    /// successor, and compares their cumulative proof-of-work with the
+    /// tip's. `depth` is capped at
+    /// [MAX_FORK_CHOICE_AUDIT_DEPTH](super::MAX_FORK_CHOICE_AUDIT_DEPTH).
+    ///
+    /// Requires an archival node.
+    ///
+    /// ```
+    /// # use neptune_cash::api::export::GlobalStateLock;
+    /// # use neptune_cash::api::chain::ForkChoiceVerdict;
+    /// # use neptune_cash::api::chain::error::ChainError;
+    /// #
+    /// # async fn example(gsl: GlobalStateLock) -> Result<(), ChainError> {
+    /// let audit = gsl.api().chain().fork_choice_audit(100).await?;
+    ///
+    /// for competing_tip in audit.competing_tips {
+    ///     if competing_tip.verdict == ForkChoiceVerdict::MoreWork {
+    ///         println!("not following heavier tip {}", competing_tip.work.digest);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fork_choice_audit(&self, depth: u64) -> Result<ForkChoiceAudit, ChainError> {
+        self.worker.fork_choice_audit(depth).await
+    }
+}
+
+#[derive(Debug)]
+struct ChainPrivate {
+    global_state_lock: GlobalStateLock,
+}
+
+impl ChainPrivate {
+    fn new(global_state_lock: GlobalStateLock) -> Self {
+        Self { global_state_lock }
+    }
+
+    async fn canonical_blocks(
+        &self,
+        heights: RangeInclusive<BlockHeight>,
+    ) -> Result<CanonicalBlocks, ChainError> {
+        if !self
+            .global_state_lock
+            .lock_guard()
+            .await
+            .chain
+            .is_archival_node()
+        {
+            return Err(ChainError::NotArchival);
+        }
+
+        let (first, last) = heights.into_inner();
+        Ok(CanonicalBlocks::new(
+            self.global_state_lock.clone(),
+            first,
+            last,
+        ))
+    }
+
+    async fn canonical_blocks_from(&self, digest: Digest) -> Result<CanonicalBlocks, ChainError> {
+        let state = self.global_state_lock.lock_guard().await;
+        if !state.chain.is_archival_node() {
+            return Err(ChainError::NotArchival);
+        }
+
+        let archival_state = state.chain.archival_state();
+        let header = archival_state
+            .get_block_header(digest)
+            .await
+            .ok_or(ChainError::UnknownBlock(digest))?;
+        if !archival_state
+            .block_belongs_to_canonical_chain(digest)
+            .await
+        {
+            return Err(ChainError::NotCanonical(digest));
+        }
+
+        let tip_height = state.chain.light_state().header().height;
+        Ok(CanonicalBlocks::new(
+            self.global_state_lock.clone(),
+            header.height,
+            tip_height,
+        ))
+    }
+
+    async fn prove_data_commitment(
+        &self,
+        commitment: DataCommitment,
+        block_digest: Digest,
+    ) -> Result<DataCommitmentProof, ChainError> {
+        let state = self.global_state_lock.lock_guard().await;
+        if !state.chain.is_archival_node() {
+            return Err(ChainError::NotArchival);
+        }
+
+        let archival_state = state.chain.archival_state();
+        let block = archival_state
+            .get_block(block_digest)
+            .await?
+            .ok_or(ChainError::UnknownBlock(block_digest))?;
+        if !archival_state
+            .block_belongs_to_canonical_chain(block_digest)
+            .await
+        {
+            return Err(ChainError::NotCanonical(block_digest));
+        }
+
+        // the tip's block MMR accumulator contains all blocks but the tip.
+        let anchor = state.chain.light_state();
+        if anchor.hash() == block_digest {
+            return Err(ChainError::NoSuccessor(block_digest));
+        }
+
+        let block_mmr_membership_proof = archival_state
+            .archival_block_mmr
+            .ammr()
+            .prove_membership_relative_to_smaller_mmr(
+                block.header().height.into(),
+                anchor.body().block_mmr_accumulator.num_leafs(),
+            )
+            .await;
+
+        DataCommitmentProof::new(
+            commitment,
+            &block,
+            block_mmr_membership_proof,
+            anchor.hash(),
+            anchor.header().height,
+        )
+        .ok_or(ChainError::CommitmentNotInBlock {
+            commitment: commitment.digest(),
+            block: block_digest,
+        })
+    }
+
+    async fn export_csv(
+        &self,
+        heights: RangeInclusive<BlockHeight>,
+    ) -> Result<ChainExportReport, ChainError> {
+        let directory = self.export_directory(&heights).await?;
+
+        // write to a temporary directory first, so that a failed export does
+        // not leave behind a directory that blocks retries.
+        let partial_directory = directory.with_extension("partial");
+        if tokio::fs::try_exists(&partial_directory).await? {
+            tokio::fs::remove_dir_all(&partial_directory).await?;
+        }
+        tokio::fs::create_dir(&partial_directory).await?;
+        let report = match self.write_csv(&partial_directory, heights).await {
+            Ok(report) => report,
+            Err(e) => {
+                let _ = tokio::fs::remove_dir_all(&partial_directory).await;
+                return Err(e);
+            }
+        };
+        tokio::fs::rename(&partial_directory, &directory).await?;
+
+        Ok(ChainExportReport {
+            files: report
+                .files
+                .iter()
+                .filter_map(|file| file.file_name())
+                .map(|file_name| directory.join(file_name))
+                .collect(),
+            directory,
+            ..report
+        })
+    }
+
+    /// write the CSV tables of the given range to `directory`.
+    async fn write_csv(
+        &self,
+        directory: &Path,
+        heights: RangeInclusive<BlockHeight>,
+    ) -> Result<ChainExportReport, ChainError> {
+        let mut blocks = self.canonical_blocks(heights).await?;
+        let mut tables = CsvTables::create(directory).await?;
+
+        let (mut num_blocks, mut last_height) = (0, None);
+        while let Some(block) = blocks.next().await {
+            let block = block?;
+            tables.write_block(&block).await?;
+            num_blocks += 1;
+            last_height = Some(block.height());
+        }
+
+        Ok(ChainExportReport {
+            files: tables.finish().await?,
+            directory: directory.to_path_buf(),
+            num_blocks,
+            last_height,
+        })
+    }
+
+    /// the directory for exporting the given range, which must not exist yet.
+    async fn export_directory(
+        &self,
+        heights: &RangeInclusive<BlockHeight>,
+    ) -> Result<PathBuf, ChainError> {
+        let exports_dir = self
+            .global_state_lock
+            .lock_guard()
+            .await
+            .wallet_state
+            .configuration
+            .data_directory()
+            .chain_exports_dir_path();
+        tokio::fs::create_dir_all(&exports_dir).await?;
+
+        let directory = exports_dir.join(format!("{}-{}", heights.start(), heights.end()));
+        if tokio::fs::try_exists(&directory).await? {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{} already exists", directory.display()),
+            )
+            .into());
+        }
+
+        Ok(directory)
+    }
+
+    async fn find_data_commitment(
+        &self,
+        commitment: DataCommitment,
+        heights: RangeInclusive<BlockHeight>,
+    ) -> Result<Option<DataCommitmentProof>, ChainError> {
+        let mut blocks = self.canonical_blocks(heights).await?;
+        while let Some(block) = blocks.next().await {
+            let block = block?;
+            if commitment.is_in_block(block.block()) {
+                return self
+                    .prove_data_commitment(commitment, block.digest())
+                    .await
+                    .map(Some);
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn chain_work(&self, digest: Digest) -> Result<ChainWork, ChainError> {
+        ChainWork::from_global_state(&*self.global_state_lock.lock_guard().await, digest).await
+    }
+
+    async fn fork_choice_audit(&self, depth: u64) -> Result<ForkChoiceAudit, ChainError> {
+        ForkChoiceAudit::from_global_state(&*self.global_state_lock.lock_guard().await, depth).await
+    }
+}
@@ -0,0 +1,245 @@
+// private module.  no need for module docs.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::error::ChainError;
+use crate::api::export::BlockHeight;
+use crate::api::export::Digest;
+use crate::protocol::consensus::block::block_header::BlockHeader;
+use crate::protocol::consensus::block::difficulty_control::Difficulty;
+use crate::protocol::consensus::block::difficulty_control::ProofOfWork;
+use crate::state::GlobalState;
+
+/// most heights below the tip that a [ForkChoiceAudit] searches for
+/// competing tips.
+pub const MAX_FORK_CHOICE_AUDIT_DEPTH: u64 = 1000;
+
+/// the proof-of-work accumulated by the chain up to a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainWork {
+    /// the block's digest
+    pub digest: Digest,
+
+    /// the block's height
+    pub height: BlockHeight,
+
+    /// proof-of-work of all of the block's ancestors, as declared in its
+    /// header. this is the quantity the fork-choice rule compares.
+    pub cumulative_proof_of_work: ProofOfWork,
+
+    /// the block's own difficulty
+    pub difficulty: Difficulty,
+
+    /// proof-of-work of the block and all of its ancestors
+    pub total_proof_of_work: ProofOfWork,
+
+    /// whether the block belongs to the canonical chain
+    pub is_canonical: bool,
+}
+
+impl ChainWork {
+    pub(super) fn from_header(digest: Digest, header: &BlockHeader, is_canonical: bool) -> Self {
+        Self {
+            digest,
+            height: header.height,
+            cumulative_proof_of_work: header.cumulative_proof_of_work,
+            difficulty: header.difficulty,
+            total_proof_of_work: header.cumulative_proof_of_work + header.difficulty,
+            is_canonical,
+        }
+    }
+
+    /// the chain work of the block with the given digest.
+    ///
+    /// the tip is always known. other blocks require an archival node.
+    pub(super) async fn from_global_state(
+        gs: &GlobalState,
+        digest: Digest,
+    ) -> Result<Self, ChainError> {
+        let tip = gs.chain.light_state();
+        if tip.hash() == digest {
+            return Ok(Self::from_header(digest, tip.header(), true));
+        }
+
+        if !gs.chain.is_archival_node() {
+            return Err(ChainError::NotArchival);
+        }
+        let archival_state = gs.chain.archival_state();
+        let header = archival_state
+            .get_block_header(digest)
+            .await
+            .ok_or(ChainError::UnknownBlock(digest))?;
+        let is_canonical = archival_state
+            .block_belongs_to_canonical_chain(digest)
+            .await;
+
+        Ok(Self::from_header(digest, &header, is_canonical))
+    }
+}
+
+/// why the fork-choice rule keeps the current tip over a competing tip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ForkChoiceVerdict {
+    /// the competing tip declares less cumulative proof-of-work.
+    LessWork,
+
+    /// the competing tip declares as much cumulative proof-of-work as the
+    /// current tip, which was seen first and is kept.
+    EqualWork,
+
+    /// the competing tip has the same height as the current tip, which was
+    /// seen first and is kept unless its transaction has no inputs.
+    SameHeight,
+
+    /// the competing tip declares more cumulative proof-of-work, yet is not
+    /// canonical. the node should have switched to it, unless it or one of
+    /// its ancestors is invalid or incomplete. this points at a sync problem.
+    MoreWork,
+}
+
+impl ForkChoiceVerdict {
+    /// apply the fork-choice rule to the headers of the current tip and a
+    /// competing tip.
+    ///
+    /// mirrors [Block::fork_choice_rule()](crate::protocol::consensus::block::Block::fork_choice_rule()),
+    /// which also considers the transaction of blocks at the same height.
+    fn of(tip: &BlockHeader, competitor: &BlockHeader) -> Self {
+        if tip.height == competitor.height {
+            return Self::SameHeight;
+        }
+
+        match competitor
+            .cumulative_proof_of_work
+            .cmp(&tip.cumulative_proof_of_work)
+        {
+            std::cmp::Ordering::Less => Self::LessWork,
+            std::cmp::Ordering::Equal => Self::EqualWork,
+            std::cmp::Ordering::Greater => Self::MoreWork,
+        }
+    }
+}
+
+/// a stored block that is not canonical and has no stored successor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompetingTip {
+    /// the competing tip's chain work
+    pub work: ChainWork,
+
+    /// height of the last canonical ancestor of the competing tip, or `None`
+    /// if its ancestry is not stored.
+    pub fork_height: Option<BlockHeight>,
+
+    /// why the current tip is kept over the competing tip
+    pub verdict: ForkChoiceVerdict,
+}
+
+/// explains the current fork-choice decision: the tip, the competing tips the
+/// node knows of, and how they compare.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForkChoiceAudit {
+    /// the current tip's chain work
+    pub tip: ChainWork,
+
+    /// how many heights below the tip were searched for competing tips
+    pub depth: u64,
+
+    /// the competing tips found, most cumulative proof-of-work first
+    pub competing_tips: Vec<CompetingTip>,
+
+    /// cumulative proof-of-work claimed by the peer the node is syncing
+    /// towards, if it is syncing
+    pub sync_target_proof_of_work: Option<ProofOfWork>,
+}
+
+impl ForkChoiceAudit {
+    /// search the stored blocks from `depth` heights below the tip upwards for
+    /// competing tips.
+    pub(super) async fn from_global_state(
+        gs: &GlobalState,
+        depth: u64,
+    ) -> Result<Self, ChainError> {
+        if !gs.chain.is_archival_node() {
+            return Err(ChainError::NotArchival);
+        }
+
+        let depth = depth.min(MAX_FORK_CHOICE_AUDIT_DEPTH);
+        let tip_block = gs.chain.light_state();
+        let tip_header = *tip_block.header();
+        let tip = ChainWork::from_header(tip_block.hash(), &tip_header, true);
+        let tip_height = u64::from(tip_header.height);
+
+        // collect the stored headers from the lowest searched height up to
+        // the highest height with stored blocks, which may exceed the tip's.
+        let archival_state = gs.chain.archival_state();
+        let mut headers = HashMap::new();
+        let mut height = tip_height.saturating_sub(depth).max(1);
+        loop {
+            let digests = archival_state
+                .block_height_to_block_digests(height.into())
+                .await;
+            if digests.is_empty() && height > tip_height {
+                break;
+            }
+            for digest in digests {
+                if let Some(header) = archival_state.get_block_header(digest).await {
+                    headers.insert(digest, header);
+                }
+            }
+            height += 1;
+        }
+
+        let predecessors = headers
+            .values()
+            .map(|header| header.prev_block_digest)
+            .collect::<HashSet<_>>();
+        let mut competing_tips = vec![];
+        for (digest, header) in &headers {
+            if predecessors.contains(digest)
+                || archival_state
+                    .block_belongs_to_canonical_chain(*digest)
+                    .await
+            {
+                continue;
+            }
+
+            let mut fork_height = None;
+            let mut ancestor = header.prev_block_digest;
+            while let Some(ancestor_header) = archival_state.get_block_header(ancestor).await {
+                if archival_state
+                    .block_belongs_to_canonical_chain(ancestor)
+                    .await
+                {
+                    fork_height = Some(ancestor_header.height);
+                    break;
+                }
+                ancestor = ancestor_header.prev_block_digest;
+            }
+
+            competing_tips.push(CompetingTip {
+                work: ChainWork::from_header(*digest, header, false),
+                fork_height,
+                verdict: ForkChoiceVerdict::of(&tip_header, header),
+            });
+        }
+        competing_tips.sort_by(|a, b| {
+            b.work
+                .cumulative_proof_of_work
+                .cmp(&a.work.cumulative_proof_of_work)
+        });
+
+        Ok(Self {
+            tip,
+            depth,
+            competing_tips,
+            sync_target_proof_of_work: gs
+                .net
+                .sync_anchor
+                .as_ref()
+                .map(|anchor| anchor.cumulative_proof_of_work),
+        })
+    }
+}
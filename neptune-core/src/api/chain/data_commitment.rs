@@ -0,0 +1,325 @@
+// private module.  no need for module docs.
+
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::triton_vm::prelude::BFieldCodec;
+use tasm_lib::triton_vm::prelude::BFieldElement;
+use tasm_lib::twenty_first::prelude::MerkleTreeInclusionProof;
+use tasm_lib::twenty_first::util_types::mmr::mmr_accumulator::MmrAccumulator;
+use tasm_lib::twenty_first::util_types::mmr::mmr_membership_proof::MmrMembershipProof;
+use tasm_lib::twenty_first::util_types::mmr::mmr_trait::Mmr;
+
+use crate::api::export::Announcement;
+use crate::api::export::Block;
+use crate::api::export::BlockHeight;
+use crate::api::export::Digest;
+use crate::api::export::Timestamp;
+use crate::api::export::Tip5;
+use crate::protocol::consensus::block::block_body::BlockBody;
+use crate::protocol::consensus::block::block_body::BlockBodyField;
+use crate::protocol::consensus::block::block_header::BlockHeader;
+use crate::protocol::consensus::block::block_header::BlockHeaderField;
+use crate::protocol::consensus::block::block_kernel::BlockKernel;
+use crate::protocol::consensus::block::block_kernel::BlockKernelField;
+use crate::protocol::consensus::block::BlockField;
+use crate::protocol::consensus::transaction::transaction_kernel::TransactionKernel;
+use crate::protocol::consensus::transaction::transaction_kernel::TransactionKernelField;
+use crate::protocol::proof_abstractions::mast_hash::HasDiscriminant;
+use crate::protocol::proof_abstractions::mast_hash::MastHash;
+
+/// identifies an [Announcement] as a data commitment.
+///
+/// Announcements carrying UTXO notifications start with a key-type flag
+/// instead, so data commitments are ignored by wallets.
+pub const DATA_COMMITMENT_FLAG: BFieldElement = BFieldElement::new(68);
+
+/// a commitment to external data, stored on-chain in an [Announcement].
+///
+/// Only the hash of the data is published. Anyone holding the data can later
+/// demonstrate, via a [DataCommitmentProof], that the data existed no later
+/// than the timestamp of the block that includes the commitment.
+///
+/// ```
+/// use neptune_cash::api::chain::DataCommitment;
+///
+/// let commitment = DataCommitment::from_bytes(b"my important document");
+/// let announcement = commitment.to_announcement();
+/// assert_eq!(Some(commitment), DataCommitment::try_from_announcement(&announcement));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DataCommitment(Digest);
+
+impl DataCommitment {
+    /// commit to a digest computed by the caller.
+    pub fn new(digest: Digest) -> Self {
+        Self(digest)
+    }
+
+    /// commit to arbitrary bytes, by hashing them.
+    pub fn from_bytes(data: &[u8]) -> Self {
+        let elements = data
+            .chunks(4)
+            .map(|chunk| {
+                let mut word = [0u8; 4];
+                word[..chunk.len()].copy_from_slice(chunk);
+                BFieldElement::new(u32::from_le_bytes(word).into())
+            })
+            .collect::<Vec<_>>();
+
+        // the length is hashed as well, so that trailing zero bytes are
+        // not lost to the padding above.
+        let length = BFieldElement::new(data.len() as u64);
+        Self(Tip5::hash_varlen(&[vec![length], elements].concat()))
+    }
+
+    /// the committed digest
+    pub fn digest(&self) -> Digest {
+        self.0
+    }
+
+    /// convert into an [Announcement], for inclusion in a transaction.
+    pub fn to_announcement(&self) -> Announcement {
+        Announcement::new([vec![DATA_COMMITMENT_FLAG], self.0.encode()].concat())
+    }
+
+    /// interpret an [Announcement] as a data commitment, if it is one.
+    pub fn try_from_announcement(announcement: &Announcement) -> Option<Self> {
+        match announcement.message.split_first() {
+            Some((flag, rest)) if *flag == DATA_COMMITMENT_FLAG => {
+                Digest::decode(rest).ok().map(|d| Self(*d))
+            }
+            _ => None,
+        }
+    }
+
+    /// returns true if the block's transaction contains this commitment.
+    pub fn is_in_block(&self, block: &Block) -> bool {
+        block
+            .body()
+            .transaction_kernel
+            .announcements
+            .contains(&self.to_announcement())
+    }
+}
+
+impl From<Digest> for DataCommitment {
+    fn from(digest: Digest) -> Self {
+        Self(digest)
+    }
+}
+
+/// proves that a [DataCommitment] is included in a block of the canonical
+/// chain, and at what time that block was mined.
+///
+/// The proof consists of:
+///  1. the announcements of the block's transaction, one of which is the
+///     commitment.
+///  2. Merkle authentication paths from the announcements up to the block
+///     digest, and from the block timestamp up to the block digest.
+///  3. an MMR membership proof of the block digest in the block MMR of a
+///     later block, the *anchor*.
+///
+/// The proof is verified against the block MMR accumulator of the anchor,
+/// which is found in the anchor's
+/// [body](crate::protocol::consensus::block::block_body::BlockBody::block_mmr_accumulator).
+/// Because the anchor must be a later block, a proof can only be produced
+/// once the block containing the commitment has at least one successor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataCommitmentProof {
+    commitment: DataCommitment,
+
+    // transaction kernel
+    announcements: Vec<Announcement>,
+    announcements_path: Vec<Digest>,
+    transaction_kernel_digest: Digest,
+
+    // block body
+    transaction_kernel_path: Vec<Digest>,
+    body_digest: Digest,
+
+    // block header
+    timestamp: Timestamp,
+    timestamp_path: Vec<Digest>,
+    header_digest: Digest,
+
+    // block kernel
+    kernel_auth_structure: Vec<Digest>,
+    kernel_digest: Digest,
+
+    // block
+    kernel_path: Vec<Digest>,
+    block_digest: Digest,
+    block_height: BlockHeight,
+
+    // block mmr
+    block_mmr_membership_proof: MmrMembershipProof,
+    anchor_digest: Digest,
+    anchor_height: BlockHeight,
+}
+
+impl DataCommitmentProof {
+    /// assemble a proof from the block containing the commitment and an
+    /// MMR membership proof of that block relative to the anchor.
+    ///
+    /// returns `None` if the block does not contain the commitment.
+    pub(super) fn new(
+        commitment: DataCommitment,
+        block: &Block,
+        block_mmr_membership_proof: MmrMembershipProof,
+        anchor_digest: Digest,
+        anchor_height: BlockHeight,
+    ) -> Option<Self> {
+        if !commitment.is_in_block(block) {
+            return None;
+        }
+
+        let kernel = &block.kernel;
+        let body = block.body();
+        let header = block.header();
+        let transaction_kernel = &body.transaction_kernel;
+
+        let kernel_auth_structure = kernel
+            .merkle_tree()
+            .authentication_structure(&[
+                BlockKernelField::Header.discriminant(),
+                BlockKernelField::Body.discriminant(),
+            ])
+            .ok()?;
+
+        Some(Self {
+            commitment,
+            announcements: transaction_kernel.announcements.clone(),
+            announcements_path: transaction_kernel.mast_path(TransactionKernelField::Announcements),
+            transaction_kernel_digest: transaction_kernel.mast_hash(),
+            transaction_kernel_path: body.mast_path(BlockBodyField::TransactionKernel),
+            body_digest: body.mast_hash(),
+            timestamp: header.timestamp,
+            timestamp_path: header.mast_path(BlockHeaderField::Timestamp),
+            header_digest: header.mast_hash(),
+            kernel_auth_structure,
+            kernel_digest: kernel.mast_hash(),
+            kernel_path: block.mast_path(BlockField::Kernel),
+            block_digest: block.hash(),
+            block_height: header.height,
+            block_mmr_membership_proof,
+            anchor_digest,
+            anchor_height,
+        })
+    }
+
+    /// the proven commitment
+    pub fn commitment(&self) -> DataCommitment {
+        self.commitment
+    }
+
+    /// digest of the block containing the commitment
+    pub fn block_digest(&self) -> Digest {
+        self.block_digest
+    }
+
+    /// height of the block containing the commitment
+    pub fn block_height(&self) -> BlockHeight {
+        self.block_height
+    }
+
+    /// timestamp of the block containing the commitment
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    /// digest of the anchor block, whose block MMR accumulator the proof is
+    /// verified against
+    pub fn anchor_digest(&self) -> Digest {
+        self.anchor_digest
+    }
+
+    /// height of the anchor block
+    pub fn anchor_height(&self) -> BlockHeight {
+        self.anchor_height
+    }
+
+    /// verify the proof against the block MMR accumulator of the anchor block.
+    ///
+    /// The caller is responsible for obtaining `block_mmr` from a trusted
+    /// source, eg the body of the anchor block as known to their own node.
+    pub fn verify(&self, block_mmr: &MmrAccumulator) -> bool {
+        let commitment_is_announced = self
+            .announcements
+            .contains(&self.commitment.to_announcement());
+
+        let announcements_are_in_transaction_kernel = verify_mast_leaf::<TransactionKernel>(
+            TransactionKernelField::Announcements,
+            &self.announcements.encode(),
+            &self.announcements_path,
+            self.transaction_kernel_digest,
+        );
+
+        let transaction_kernel_is_in_body = verify_mast_leaf::<BlockBody>(
+            BlockBodyField::TransactionKernel,
+            &self.transaction_kernel_digest.encode(),
+            &self.transaction_kernel_path,
+            self.body_digest,
+        );
+
+        let timestamp_is_in_header = verify_mast_leaf::<BlockHeader>(
+            BlockHeaderField::Timestamp,
+            &self.timestamp.encode(),
+            &self.timestamp_path,
+            self.header_digest,
+        );
+
+        let header_and_body_are_in_kernel = MerkleTreeInclusionProof {
+            tree_height: BlockKernel::MAST_HEIGHT as u32,
+            indexed_leafs: vec![
+                (
+                    BlockKernelField::Header.discriminant(),
+                    Tip5::hash_varlen(&self.header_digest.encode()),
+                ),
+                (
+                    BlockKernelField::Body.discriminant(),
+                    Tip5::hash_varlen(&self.body_digest.encode()),
+                ),
+            ],
+            authentication_structure: self.kernel_auth_structure.clone(),
+        }
+        .verify(self.kernel_digest);
+
+        let kernel_is_in_block = verify_mast_leaf::<Block>(
+            BlockField::Kernel,
+            &self.kernel_digest.encode(),
+            &self.kernel_path,
+            self.block_digest,
+        );
+
+        let block_is_in_block_mmr = self.block_mmr_membership_proof.verify(
+            self.block_height.into(),
+            self.block_digest,
+            &block_mmr.peaks(),
+            block_mmr.num_leafs(),
+        );
+
+        commitment_is_announced
+            && announcements_are_in_transaction_kernel
+            && transaction_kernel_is_in_body
+            && timestamp_is_in_header
+            && header_and_body_are_in_kernel
+            && kernel_is_in_block
+            && block_is_in_block_mmr
+    }
+}
+
+// verify that the encoded field is a leaf of the MAST of type `T` with the
+// given root.
+fn verify_mast_leaf<T: MastHash>(
+    field: T::FieldEnum,
+    field_encoding: &[BFieldElement],
+    path: &[Digest],
+    root: Digest,
+) -> bool {
+    MerkleTreeInclusionProof {
+        tree_height: T::MAST_HEIGHT as u32,
+        indexed_leafs: vec![(field.discriminant(), Tip5::hash_varlen(field_encoding))],
+        authentication_structure: path.to_vec(),
+    }
+    .verify(root)
+}
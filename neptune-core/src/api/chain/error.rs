@@ -0,0 +1,53 @@
+//! provides error types related to the chain api.
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::api::export::BlockHeight;
+use crate::api::export::Digest;
+
+/// enumerates possible chain api errors
+#[derive(Debug, Clone, thiserror::Error, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum ChainError {
+    #[error("node is not archival.  historical blocks are not available")]
+    NotArchival,
+
+    #[error("block {0} is not known")]
+    UnknownBlock(Digest),
+
+    #[error("block {0} does not belong to the canonical chain")]
+    NotCanonical(Digest),
+
+    #[error("canonical block at height {0} could not be loaded")]
+    MissingBlock(BlockHeight),
+
+    #[error("data commitment {commitment} not found in block {block}")]
+    CommitmentNotInBlock { commitment: Digest, block: Digest },
+
+    #[error("block {0} is the tip.  a proof requires at least one successor block")]
+    NoSuccessor(Digest),
+
+    #[error("export failed.  reason: {0}")]
+    ExportFailed(String),
+
+    // catch-all error, eg for anyhow errors
+    #[error("operation failed.  reason: {0}")]
+    Failed(String),
+}
+
+// convert io errors during export to a ChainError::ExportFailed.
+// note that io Error is not serializable.
+impl From<std::io::Error> for ChainError {
+    fn from(e: std::io::Error) -> Self {
+        Self::ExportFailed(e.to_string())
+    }
+}
+
+// convert anyhow::Error to a ChainError::Failed.
+// note that anyhow Error is not serializable.
+impl From<anyhow::Error> for ChainError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::Failed(e.to_string())
+    }
+}
@@ -0,0 +1,42 @@
+//! provides public API for reading the blockchain.
+//!
+//! This module enables library consumers, eg an indexer embedded in a
+//! downstream binary, to walk the canonical chain without knowledge of
+//! neptune-core's internal storage.
+//!
+//! It also provides [DataCommitment]s, with which a hash of external data can
+//! be committed on-chain, and [DataCommitmentProof]s, with which the
+//! commitment's inclusion in a block can later be demonstrated to a third
+//! party, eg by a timestamping service.
+//!
+//! Block headers, transaction kernel metadata, and fees over a range of
+//! heights can be exported to CSV files for offline analytics, see
+//! [Chain::export_csv()].
+//!
+//! The proof-of-work accumulated up to any block is reported by
+//! [Chain::chain_work()], and [Chain::fork_choice_audit()] explains why the
+//! node prefers its tip over the competing tips it knows of, which helps
+//! debugging sync disagreements between nodes.
+//!
+//! Walking the chain requires an archival node, as light nodes do not store
+//! historical blocks.
+mod canonical_blocks;
+mod chain_export;
+mod chain_impl;
+mod chain_work;
+mod data_commitment;
+
+// these represent the public API
+pub mod error;
+pub use canonical_blocks::BlockView;
+pub use canonical_blocks::CanonicalBlocks;
+pub use chain_export::ChainExportReport;
+pub use chain_impl::Chain;
+pub use chain_work::ChainWork;
+pub use chain_work::CompetingTip;
+pub use chain_work::ForkChoiceAudit;
+pub use chain_work::ForkChoiceVerdict;
+pub use chain_work::MAX_FORK_CHOICE_AUDIT_DEPTH;
+pub use data_commitment::DataCommitment;
+pub use data_commitment::DataCommitmentProof;
+pub use data_commitment::DATA_COMMITMENT_FLAG;
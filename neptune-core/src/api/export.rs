@@ -2,13 +2,17 @@
 //!
 //! They are exported here in one location for convenience.
 
+pub use crate::api::chain::ChainExportReport;
+pub use crate::api::chain::DataCommitment;
 pub use crate::api::tx_initiation::builder::tx_input_list_builder::InputSelectionPolicy;
 pub use crate::api::tx_initiation::builder::tx_output_list_builder::OutputFormat;
 pub use crate::api::wallet::WalletBalances;
 pub use crate::application::config::cli_args::Args;
 pub use crate::application::config::network::Network;
 pub use crate::application::triton_vm_job_queue::TritonVmJobPriority;
+pub use crate::protocol::consensus::block::block_header::BlockHeader;
 pub use crate::protocol::consensus::block::block_height::BlockHeight;
+pub use crate::protocol::consensus::block::Block;
 pub use crate::protocol::consensus::transaction::announcement::Announcement;
 pub use crate::protocol::consensus::transaction::primitive_witness::WitnessValidationError;
 pub use crate::protocol::consensus::transaction::transaction_proof::TransactionProof;
@@ -21,6 +25,8 @@ pub use crate::protocol::consensus::transaction::validity::neptune_proof::Neptun
 pub use crate::protocol::consensus::transaction::Transaction;
 pub use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
 pub use crate::protocol::proof_abstractions::timestamp::Timestamp;
+pub use crate::state::checkpoint::Checkpoint;
+pub use crate::state::event_log::SequencedEvent;
 pub use crate::state::transaction::transaction_details::TransactionDetails;
 pub use crate::state::transaction::transaction_kernel_id::TransactionKernelId;
 pub use crate::state::transaction::tx_creation_artifacts::TxCreationArtifacts;
@@ -31,9 +37,17 @@ pub use crate::state::wallet::address::KeyType;
 pub use crate::state::wallet::address::ReceivingAddress;
 pub use crate::state::wallet::address::SpendingKey;
 pub use crate::state::wallet::change_policy::ChangePolicy;
+pub use crate::state::wallet::composer_payout::ComposerPayout;
+pub use crate::state::wallet::derivation_vectors::DerivationVectors;
+pub use crate::state::wallet::memo::Memo;
+pub use crate::state::wallet::reserve_report::LockScriptSignature;
+pub use crate::state::wallet::reserve_report::ReserveReport;
+pub use crate::state::wallet::reserve_report::SignedReserveReport;
 pub use crate::state::wallet::transaction_input::TxInput;
 pub use crate::state::wallet::transaction_input::TxInputList;
 pub use crate::state::wallet::transaction_output::TxOutputList;
+pub use crate::state::wallet::utxo_ownership_proof::UtxoOwnershipProof;
+pub use crate::state::wallet::utxo_ownership_proof::UtxoOwnershipReport;
 pub use crate::state::GlobalStateLock;
 pub use crate::state::RecordTransactionError;
 pub use crate::state::StateLock;
@@ -43,3 +57,5 @@ pub use crate::triton_vm::prelude::Program;
 pub use crate::triton_vm::proof::Claim;
 pub use crate::triton_vm::vm::NonDeterminism;
 pub use crate::util_types::mutator_set::addition_record::AdditionRecord;
+pub use crate::util_types::mutator_set::membership_bundle::MembershipBundle;
+pub use crate::util_types::mutator_set::ms_membership_proof::MsMembershipProof;
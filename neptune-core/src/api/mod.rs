@@ -14,40 +14,42 @@
 //! point.
 //!
 //! Starting out, it is necessary to understand how to start a node
-//! and obtain a GlobalStateLock handle.  here's how:
+//! and obtain a GlobalStateLock handle.  The [node] module is the entry point
+//! for embedding a node in a rust application.  here's how:
 //!
 //! ```no_run
 //! use neptune_cash::api::export;
-//! use export::GlobalStateLock;
-//! use export::Args;
+//! use neptune_cash::api::node::NodeBuilder;
+//! use export::Network;
 //! use export::Timestamp;
 //!
 //! #[tokio::main]
 //! async fn main() -> anyhow::Result<()> {
 //!
-//!     let args = Args::default();
-//!
-//!     // initialize
-//!     let mut main_loop_handler = neptune_cash::initialize(args).await?;
-//!     let gsl = main_loop_handler.global_state_lock();
-//!
-//!     // spawn tokio task to start the node running
-//!     let main_loop_join_handle =
-//!         tokio::task::spawn(async move { main_loop_handler.run().await });
+//!     // initialize and start the node running
+//!     let node = NodeBuilder::new(Network::Main).build().await?;
+//!     let gsl = node.global_state_lock();
 //!
 //!     // use the API ...
 //!     println!("wallet balances:\n\n{}", gsl.api().wallet().balances(Timestamp::now()).await);
 //!
+//!     // stop the node
+//!     node.shutdown().await?;
+//!
 //!     Ok(())
 //! }
 //! ```
 //!
 //! Please read the [GlobalStateLock](crate::GlobalStateLock) docs carefully because it is critical
 //! not to hold the lock too long or cause a deadlock situation.
+pub mod amount;
 mod api_impl;
+pub mod chain;
 pub mod export;
+pub mod node;
 pub mod regtest;
 pub mod tx_initiation;
+pub mod types;
 pub mod wallet;
 
 pub use api_impl::Api;
@@ -0,0 +1,31 @@
+//! provides error types related to embedding a node.
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// enumerates possible errors when starting or interacting with an embedded
+/// node
+#[derive(Debug, Clone, thiserror::Error, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum NodeError {
+    #[error("node could not be started.  reason: {0}")]
+    Initialization(String),
+
+    #[error("could not connect to the node's RPC server.  reason: {0}")]
+    RpcConnection(String),
+
+    #[error("node has stopped")]
+    Stopped,
+
+    // catch-all error, eg for anyhow errors
+    #[error("operation failed.  reason: {0}")]
+    Failed(String),
+}
+
+// convert anyhow::Error to a NodeError::Failed.
+// note that anyhow Error is not serializable.
+impl From<anyhow::Error> for NodeError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::Failed(e.to_string())
+    }
+}
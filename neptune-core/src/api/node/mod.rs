@@ -0,0 +1,40 @@
+//! provides the entry point for embedding a neptune-core node in a rust
+//! application.
+//!
+//! The neptune-core binary parses its configuration from the command line and
+//! runs until it is told to stop. An application that embeds the node instead
+//! configures it with a [NodeBuilder] and receives a [Node] with handles to
+//! interact with the running node:
+//!
+//! 1. the [GlobalStateLock](crate::GlobalStateLock) and public [Api](crate::api::Api).
+//! 2. an [RpcConnection] to the node's RPC server, for code that is written
+//!    against the RPC interface.
+//! 3. a stream of [NodeEvents] caused by new tips.
+//! 4. a [ShutdownHandle] to stop the node gracefully.
+//!
+//! ```no_run
+//! use neptune_cash::api::export::Network;
+//! use neptune_cash::api::node::NodeBuilder;
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let node = NodeBuilder::new(Network::RegTest)
+//!     .max_num_peers(0)
+//!     .build()
+//!     .await?;
+//!
+//! let mut events = node.events("my-app");
+//! let new_events = events.next().await?;
+//!
+//! let exit_code = node.shutdown().await?;
+//! # Ok(())
+//! # }
+//! ```
+mod node_builder;
+mod node_impl;
+
+pub mod error;
+pub use node_builder::NodeBuilder;
+pub use node_impl::Node;
+pub use node_impl::NodeEvents;
+pub use node_impl::RpcConnection;
+pub use node_impl::ShutdownHandle;
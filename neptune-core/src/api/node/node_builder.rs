@@ -0,0 +1,275 @@
+// private module.  no need for module docs.
+
+use std::net::IpAddr;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use super::error::NodeError;
+use super::node_impl::Node;
+use crate::api::export::Args;
+use crate::api::export::Network;
+use crate::api::export::TxProvingCapability;
+
+/// configures and starts a neptune-core node embedded in a rust application.
+///
+/// The builder starts out with the same defaults as the neptune-core binary,
+/// except for the network, and provides typed setters for the settings an
+/// embedding application typically needs. Settings without a setter can be
+/// configured by building from [Args] directly.
+///
+/// Which subsystems the node starts follows from the configuration:
+///
+/// 1. the RPC server is always started. it serves [Node::rpc_connection()].
+/// 2. incoming peer connections are accepted unless
+///    [max_num_peers()](Self::max_num_peers) is zero.
+/// 3. outgoing peer connections are made to each of [peers()](Self::peers).
+/// 4. composing and guessing are started if [compose()](Self::compose) or
+///    [guess()](Self::guess) is set.
+/// 5. the HTTP-JSON RPC server is started if
+///    [json_rpc_address()](Self::json_rpc_address) is set.
+///
+/// The node does not set up logging. Embedding applications install a
+/// [tracing] subscriber of their own, if they want the node's log output.
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use neptune_cash::api::export::Network;
+/// use neptune_cash::api::export::Timestamp;
+/// use neptune_cash::api::node::NodeBuilder;
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let node = NodeBuilder::new(Network::Testnet(0))
+///     .data_dir(PathBuf::from("/var/lib/my-app/neptune"))
+///     .rpc_port(19799)
+///     .compose(true)
+///     .build()
+///     .await?;
+///
+/// let balances = node.api().wallet().balances(Timestamp::now()).await;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct NodeBuilder {
+    args: Args,
+}
+
+impl From<Args> for NodeBuilder {
+    fn from(args: Args) -> Self {
+        Self { args }
+    }
+}
+
+impl NodeBuilder {
+    /// instantiate a builder for a node on the given network, with all other
+    /// settings at their defaults.
+    ///
+    /// ```
+    /// use neptune_cash::api::export::Network;
+    /// use neptune_cash::api::node::NodeBuilder;
+    ///
+    /// let builder = NodeBuilder::new(Network::RegTest);
+    /// assert_eq!(Network::RegTest, builder.args().network);
+    /// ```
+    pub fn new(network: Network) -> Self {
+        Args {
+            network,
+            ..Default::default()
+        }
+        .into()
+    }
+
+    /// the configuration the node will be started with.
+    ///
+    /// ```
+    /// use neptune_cash::api::export::Network;
+    /// use neptune_cash::api::node::NodeBuilder;
+    ///
+    /// let builder = NodeBuilder::new(Network::RegTest).rpc_port(19799);
+    /// assert_eq!(19799, builder.args().rpc_port());
+    /// ```
+    pub fn args(&self) -> &Args {
+        &self.args
+    }
+
+    /// set the directory holding the node's databases and wallet.
+    ///
+    /// A directory for the network is created inside it, if it does not exist.
+    /// Defaults to the platform's data directory for neptune-core, which is
+    /// shared with the neptune-core binary.
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    ///
+    /// use neptune_cash::api::export::Network;
+    /// use neptune_cash::api::node::NodeBuilder;
+    ///
+    /// let builder = NodeBuilder::new(Network::RegTest).data_dir(PathBuf::from("/tmp/neptune"));
+    /// ```
+    pub fn data_dir(mut self, data_dir: PathBuf) -> Self {
+        self.args.data_dir = Some(data_dir);
+        self
+    }
+
+    /// set the localhost port of the node's RPC server.
+    ///
+    /// Defaults to the network's default RPC port.
+    ///
+    /// ```
+    /// use neptune_cash::api::export::Network;
+    /// use neptune_cash::api::node::NodeBuilder;
+    ///
+    /// let builder = NodeBuilder::new(Network::RegTest).rpc_port(19799);
+    /// ```
+    pub fn rpc_port(mut self, port: u16) -> Self {
+        self.args.rpc_port = Some(port);
+        self
+    }
+
+    /// set the port on which to listen for peer connections.
+    ///
+    /// Defaults to the network's default peer port.
+    ///
+    /// ```
+    /// use neptune_cash::api::export::Network;
+    /// use neptune_cash::api::node::NodeBuilder;
+    ///
+    /// let builder = NodeBuilder::new(Network::RegTest).peer_port(19798);
+    /// ```
+    pub fn peer_port(mut self, port: u16) -> Self {
+        self.args.peer_port = Some(port);
+        self
+    }
+
+    /// set the IP on which to listen for peer connections.
+    ///
+    /// Defaults to all network interfaces.
+    ///
+    /// ```
+    /// use std::net::IpAddr;
+    /// use std::net::Ipv4Addr;
+    ///
+    /// use neptune_cash::api::export::Network;
+    /// use neptune_cash::api::node::NodeBuilder;
+    ///
+    /// let builder =
+    ///     NodeBuilder::new(Network::RegTest).peer_listen_addr(IpAddr::V4(Ipv4Addr::LOCALHOST));
+    /// ```
+    pub fn peer_listen_addr(mut self, addr: IpAddr) -> Self {
+        self.args.peer_listen_addr = addr;
+        self
+    }
+
+    /// set the peers the node connects to on startup.
+    ///
+    /// ```
+    /// use neptune_cash::api::export::Network;
+    /// use neptune_cash::api::node::NodeBuilder;
+    ///
+    /// let builder = NodeBuilder::new(Network::RegTest).peers(vec!["127.0.0.1:9798".parse()?]);
+    /// # Ok::<(), std::net::AddrParseError>(())
+    /// ```
+    pub fn peers(mut self, peers: Vec<SocketAddr>) -> Self {
+        self.args.peers = peers;
+        self
+    }
+
+    /// set the maximum number of peers the node connects to.
+    ///
+    /// Zero disables incoming peer connections, and with them the peer
+    /// listener.
+    ///
+    /// ```
+    /// use neptune_cash::api::export::Network;
+    /// use neptune_cash::api::node::NodeBuilder;
+    ///
+    /// let builder = NodeBuilder::new(Network::RegTest).max_num_peers(0);
+    /// ```
+    pub fn max_num_peers(mut self, max_num_peers: usize) -> Self {
+        self.args.max_num_peers = max_num_peers;
+        self
+    }
+
+    /// set whether the node composes block proposals.
+    ///
+    /// Composing requires substantial memory and CPU. See the `--compose` flag
+    /// of the neptune-core binary.
+    ///
+    /// ```
+    /// use neptune_cash::api::export::Network;
+    /// use neptune_cash::api::node::NodeBuilder;
+    ///
+    /// let builder = NodeBuilder::new(Network::Testnet(0)).compose(true);
+    /// ```
+    pub fn compose(mut self, compose: bool) -> Self {
+        self.args.compose = compose;
+        self
+    }
+
+    /// set whether the node guesses on block proposals.
+    ///
+    /// ```
+    /// use neptune_cash::api::export::Network;
+    /// use neptune_cash::api::node::NodeBuilder;
+    ///
+    /// let builder = NodeBuilder::new(Network::Testnet(0)).guess(true);
+    /// ```
+    pub fn guess(mut self, guess: bool) -> Self {
+        self.args.guess = guess;
+        self
+    }
+
+    /// set the most expensive type of proof the node produces.
+    ///
+    /// Defaults to an estimate based on the machine's resources.
+    ///
+    /// ```
+    /// use neptune_cash::api::export::Network;
+    /// use neptune_cash::api::export::TxProvingCapability;
+    /// use neptune_cash::api::node::NodeBuilder;
+    ///
+    /// let builder = NodeBuilder::new(Network::RegTest)
+    ///     .tx_proving_capability(TxProvingCapability::PrimitiveWitness);
+    /// ```
+    pub fn tx_proving_capability(mut self, capability: TxProvingCapability) -> Self {
+        self.args.tx_proving_capability = Some(capability);
+        self
+    }
+
+    /// start the HTTP-JSON RPC server on the given address.
+    ///
+    /// ```
+    /// use neptune_cash::api::export::Network;
+    /// use neptune_cash::api::node::NodeBuilder;
+    ///
+    /// let builder = NodeBuilder::new(Network::RegTest).json_rpc_address("127.0.0.1:9797".parse()?);
+    /// # Ok::<(), std::net::AddrParseError>(())
+    /// ```
+    pub fn json_rpc_address(mut self, addr: SocketAddr) -> Self {
+        self.args.listen_rpc = Some(addr);
+        self
+    }
+
+    /// start the node.
+    ///
+    /// Loads or creates the node's state in the data directory, starts the
+    /// configured subsystems and runs the node's main loop in a tokio task.
+    /// Must be called from within a multi-threaded tokio runtime.
+    ///
+    /// Fails with [NodeError::Initialization] if the node cannot be started,
+    /// eg because another node holds the data directory or a port is in use.
+    ///
+    /// ```no_run
+    /// use neptune_cash::api::export::Network;
+    /// use neptune_cash::api::node::NodeBuilder;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let node = NodeBuilder::new(Network::RegTest).build().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn build(self) -> Result<Node, NodeError> {
+        Node::start(self.args).await
+    }
+}
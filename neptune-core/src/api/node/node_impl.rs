@@ -0,0 +1,452 @@
+// private module.  no need for module docs.
+
+use std::net::Ipv4Addr;
+use std::net::SocketAddr;
+
+use tarpc::client;
+use tarpc::tokio_serde::formats::Json;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+use super::error::NodeError;
+use crate::api::export::Args;
+use crate::api::Api;
+use crate::application::config::data_directory::DataDirectory;
+use crate::application::rpc::auth::Cookie;
+use crate::application::rpc::auth::Token;
+use crate::application::rpc::server::RPCClient;
+use crate::state::event_log::SequencedEvent;
+use crate::GlobalStateLock;
+use crate::RPCServerToMain;
+
+/// a neptune-core node running inside this process.
+///
+/// Obtained from [NodeBuilder::build()](super::NodeBuilder::build). The node
+/// keeps running until it is shut down, either through [Node::shutdown()],
+/// a [ShutdownHandle], the `shutdown` RPC endpoint or ctrl-c. Dropping the
+/// `Node` does not stop it.
+///
+/// ```no_run
+/// use neptune_cash::api::export::Network;
+/// use neptune_cash::api::node::NodeBuilder;
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let node = NodeBuilder::new(Network::RegTest).build().await?;
+///
+/// // use the node ...
+/// let mut events = node.events("my-app");
+/// let new_events = events.next().await?;
+///
+/// let exit_code = node.shutdown().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Node {
+    worker: NodePrivate,
+}
+
+impl Node {
+    pub(super) async fn start(args: Args) -> Result<Self, NodeError> {
+        Ok(Self {
+            worker: NodePrivate::start(args).await?,
+        })
+    }
+
+    /// the lock around the node's global state.
+    ///
+    /// Please read the [GlobalStateLock] docs carefully because it is critical
+    /// not to hold the lock too long or cause a deadlock situation.
+    ///
+    /// ```no_run
+    /// use neptune_cash::api::export::Network;
+    /// use neptune_cash::api::node::NodeBuilder;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let node = NodeBuilder::new(Network::RegTest).build().await?;
+    /// let network = node.global_state_lock().cli().network;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn global_state_lock(&self) -> GlobalStateLock {
+        self.worker.global_state_lock.clone()
+    }
+
+    /// the public [Api] of the node.
+    ///
+    /// ```no_run
+    /// use neptune_cash::api::export::Network;
+    /// use neptune_cash::api::export::Timestamp;
+    /// use neptune_cash::api::node::NodeBuilder;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let node = NodeBuilder::new(Network::RegTest).build().await?;
+    /// let balances = node.api().wallet().balances(Timestamp::now()).await;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn api(&self) -> Api {
+        self.worker.global_state_lock.api()
+    }
+
+    /// connect to the node's RPC server.
+    ///
+    /// Useful for code that is written against the RPC interface, eg because it
+    /// also talks to nodes running in other processes.
+    ///
+    /// ```no_run
+    /// use neptune_cash::api::export::Network;
+    /// use neptune_cash::api::node::NodeBuilder;
+    /// use tarpc::context;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let node = NodeBuilder::new(Network::RegTest).build().await?;
+    /// let rpc = node.rpc_connection().await?;
+    /// let height = rpc.client.block_height(context::current(), rpc.token).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn rpc_connection(&self) -> Result<RpcConnection, NodeError> {
+        self.worker.rpc_connection().await
+    }
+
+    /// the events caused by new tips, as received by the given subscriber.
+    ///
+    /// Each event is delivered to a subscriber until the subscriber
+    /// acknowledges it, also across restarts of the node. Subscribers share
+    /// their position in the event log with the subscribers of the
+    /// `replay_events` RPC endpoint of the same name.
+    ///
+    /// ```no_run
+    /// use neptune_cash::api::export::Network;
+    /// use neptune_cash::api::node::NodeBuilder;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let node = NodeBuilder::new(Network::RegTest).build().await?;
+    /// let mut events = node.events("my-app");
+    /// loop {
+    ///     let new_events = events.next().await?;
+    ///     for event in &new_events {
+    ///         println!("{}: {:?}", event.sequence_number, event.event);
+    ///     }
+    ///     if let Some(last) = new_events.last() {
+    ///         events.acknowledge(last.sequence_number).await?;
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn events(&self, subscriber: impl Into<String>) -> NodeEvents {
+        NodeEvents::new(self.global_state_lock(), subscriber.into())
+    }
+
+    /// a handle that stops the node, for use by other tasks.
+    ///
+    /// ```no_run
+    /// use neptune_cash::api::export::Network;
+    /// use neptune_cash::api::node::NodeBuilder;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let node = NodeBuilder::new(Network::RegTest).build().await?;
+    /// let shutdown_handle = node.shutdown_handle();
+    /// tokio::spawn(async move {
+    ///     tokio::time::sleep(std::time::Duration::from_secs(3600)).await;
+    ///     shutdown_handle.shutdown().await
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle::new(self.worker.global_state_lock.rpc_server_to_main_tx())
+    }
+
+    /// wait until the node has stopped, and return its exit code.
+    ///
+    /// ```no_run
+    /// use neptune_cash::api::export::Network;
+    /// use neptune_cash::api::node::NodeBuilder;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let node = NodeBuilder::new(Network::RegTest).build().await?;
+    /// let exit_code = node.wait().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn wait(self) -> Result<i32, NodeError> {
+        self.worker.wait().await
+    }
+
+    /// stop the node gracefully, and return its exit code.
+    ///
+    /// ```no_run
+    /// use neptune_cash::api::export::Network;
+    /// use neptune_cash::api::node::NodeBuilder;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let node = NodeBuilder::new(Network::RegTest).build().await?;
+    /// let exit_code = node.shutdown().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn shutdown(self) -> Result<i32, NodeError> {
+        self.worker.shutdown().await
+    }
+}
+
+#[derive(Debug)]
+struct NodePrivate {
+    global_state_lock: GlobalStateLock,
+    data_directory: DataDirectory,
+    main_loop_join_handle: JoinHandle<anyhow::Result<i32>>,
+}
+
+impl NodePrivate {
+    async fn start(args: Args) -> Result<Self, NodeError> {
+        let data_directory = DataDirectory::get(args.data_dir.clone(), args.network)
+            .map_err(|e| NodeError::Initialization(e.to_string()))?;
+        let mut main_loop_handler = crate::initialize(args)
+            .await
+            .map_err(|e| NodeError::Initialization(format!("{e:#}")))?;
+        let global_state_lock = main_loop_handler.global_state_lock();
+        let main_loop_join_handle =
+            tokio::task::spawn(async move { main_loop_handler.run().await });
+
+        Ok(Self {
+            global_state_lock,
+            data_directory,
+            main_loop_join_handle,
+        })
+    }
+
+    async fn rpc_connection(&self) -> Result<RpcConnection, NodeError> {
+        let rpc_port = self.global_state_lock.cli().rpc_port();
+        let server_socket = SocketAddr::from((Ipv4Addr::LOCALHOST, rpc_port));
+        let transport = tarpc::serde_transport::tcp::connect(server_socket, Json::default)
+            .await
+            .map_err(|e| NodeError::RpcConnection(e.to_string()))?;
+        let cookie = Cookie::try_load(&self.data_directory)
+            .await
+            .map_err(|e| NodeError::RpcConnection(e.to_string()))?;
+
+        Ok(RpcConnection {
+            client: RPCClient::new(client::Config::default(), transport).spawn(),
+            token: cookie.into(),
+        })
+    }
+
+    async fn wait(self) -> Result<i32, NodeError> {
+        self.main_loop_join_handle
+            .await
+            .map_err(|e| NodeError::Failed(e.to_string()))?
+            .map_err(NodeError::from)
+    }
+
+    async fn shutdown(self) -> Result<i32, NodeError> {
+        // The node may have stopped already. Its exit code is reported either
+        // way.
+        let shutdown_handle = ShutdownHandle::new(self.global_state_lock.rpc_server_to_main_tx());
+        if let Err(e) = shutdown_handle.shutdown().await {
+            debug!("Node was stopped before shutdown: {e}");
+        }
+
+        self.wait().await
+    }
+}
+
+/// a connection to the RPC server of a [Node].
+///
+/// ```no_run
+/// use neptune_cash::api::export::Network;
+/// use neptune_cash::api::node::NodeBuilder;
+/// use tarpc::context;
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let node = NodeBuilder::new(Network::RegTest).build().await?;
+/// let rpc = node.rpc_connection().await?;
+/// let network = rpc.client.network(context::current()).await??;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RpcConnection {
+    /// the RPC client, connected to the node.
+    pub client: RPCClient,
+
+    /// the token that authenticates requests to the node.
+    pub token: Token,
+}
+
+/// the events caused by new tips, as received by one subscriber of a [Node].
+///
+/// Obtained from [Node::events()].
+///
+/// ```no_run
+/// use neptune_cash::api::export::Network;
+/// use neptune_cash::api::node::NodeBuilder;
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let node = NodeBuilder::new(Network::RegTest).build().await?;
+/// let mut events = node.events("my-app");
+/// let new_events = events.next().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct NodeEvents {
+    worker: NodeEventsPrivate,
+}
+
+impl NodeEvents {
+    fn new(global_state_lock: GlobalStateLock, subscriber: String) -> Self {
+        Self {
+            worker: NodeEventsPrivate {
+                global_state_lock,
+                subscriber,
+            },
+        }
+    }
+
+    /// wait for events the subscriber has not acknowledged yet, and return
+    /// them oldest first.
+    ///
+    /// Returns at most
+    /// [MAX_NUM_REPLAYED_EVENTS](crate::state::event_log::MAX_NUM_REPLAYED_EVENTS)
+    /// events. The same events are returned again until they are
+    /// acknowledged with [NodeEvents::acknowledge()].
+    ///
+    /// ```no_run
+    /// use neptune_cash::api::export::Network;
+    /// use neptune_cash::api::node::NodeBuilder;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let node = NodeBuilder::new(Network::RegTest).build().await?;
+    /// let mut events = node.events("my-app");
+    /// for event in events.next().await? {
+    ///     println!("{:?}", event.event);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn next(&mut self) -> Result<Vec<SequencedEvent>, NodeError> {
+        self.worker.next().await
+    }
+
+    /// acknowledge the events up to and including `sequence_number`, such
+    /// that they are not returned to the subscriber again.
+    ///
+    /// Returns once the acknowledgement is persisted. Events that were not
+    /// acknowledged, eg because the application stopped while handling them,
+    /// are returned again, also after a restart.
+    ///
+    /// ```no_run
+    /// use neptune_cash::api::export::Network;
+    /// use neptune_cash::api::node::NodeBuilder;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let node = NodeBuilder::new(Network::RegTest).build().await?;
+    /// let mut events = node.events("my-app");
+    /// for event in events.next().await? {
+    ///     println!("{:?}", event.event);
+    ///     events.acknowledge(event.sequence_number).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn acknowledge(&mut self, sequence_number: u64) -> Result<(), NodeError> {
+        self.worker.acknowledge(sequence_number).await
+    }
+}
+
+#[derive(Debug)]
+struct NodeEventsPrivate {
+    global_state_lock: GlobalStateLock,
+    subscriber: String,
+}
+
+impl NodeEventsPrivate {
+    async fn next(&mut self) -> Result<Vec<SequencedEvent>, NodeError> {
+        let recorded = self.global_state_lock.events_recorded().await;
+        loop {
+            // Waiting starts before the replay, so that events recorded in
+            // between are not missed.
+            let notified = recorded.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let events = self.replay().await;
+            if !events.is_empty() {
+                return Ok(events);
+            }
+
+            notified.await;
+        }
+    }
+
+    async fn replay(&mut self) -> Vec<SequencedEvent> {
+        // The subscriber's cursor determines which events are new.
+        let since = 0;
+        self.global_state_lock
+            .replay_events(&self.subscriber, since)
+            .await
+    }
+
+    async fn acknowledge(&mut self, sequence_number: u64) -> Result<(), NodeError> {
+        Ok(self
+            .global_state_lock
+            .acknowledge_events(&self.subscriber, sequence_number)
+            .await?)
+    }
+}
+
+/// stops a [Node] gracefully.
+///
+/// Obtained from [Node::shutdown_handle()]. Handles can be cloned and moved
+/// into other tasks.
+///
+/// ```no_run
+/// use neptune_cash::api::export::Network;
+/// use neptune_cash::api::node::NodeBuilder;
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let node = NodeBuilder::new(Network::RegTest).build().await?;
+/// node.shutdown_handle().shutdown().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ShutdownHandle {
+    rpc_server_to_main_tx: mpsc::Sender<RPCServerToMain>,
+}
+
+impl ShutdownHandle {
+    fn new(rpc_server_to_main_tx: mpsc::Sender<RPCServerToMain>) -> Self {
+        Self {
+            rpc_server_to_main_tx,
+        }
+    }
+
+    /// request the node to stop.
+    ///
+    /// Returns as soon as the request is delivered. Use [Node::wait()] to
+    /// wait until the node has stopped.
+    ///
+    /// Fails with [NodeError::Stopped] if the node has stopped already.
+    ///
+    /// ```no_run
+    /// use neptune_cash::api::export::Network;
+    /// use neptune_cash::api::node::NodeBuilder;
+    ///
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let node = NodeBuilder::new(Network::RegTest).build().await?;
+    /// node.shutdown_handle().shutdown().await?;
+    /// let exit_code = node.wait().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn shutdown(&self) -> Result<(), NodeError> {
+        self.rpc_server_to_main_tx
+            .send(RPCServerToMain::Shutdown)
+            .await
+            .map_err(|_| NodeError::Stopped)
+    }
+}
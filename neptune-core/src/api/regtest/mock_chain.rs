@@ -0,0 +1,267 @@
+use std::sync::Arc;
+
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+
+use super::error::RegTestError;
+use crate::api::export::Block;
+use crate::api::export::Network;
+use crate::api::export::Timestamp;
+use crate::api::export::Transaction;
+use crate::application::loops::mine_loop::coinbase_distribution::CoinbaseDistribution;
+use crate::application::loops::mine_loop::composer_parameters::ComposerParameters;
+use crate::protocol::consensus::block::mock_block_generator::MockBlockGenerator;
+use crate::protocol::consensus::block::BlockProof;
+use crate::state::wallet::wallet_entropy::WalletEntropy;
+
+/// the fraction of the block subsidy that goes to the guesser of a mock
+/// block.  matches the default of the `--guesser-fraction` flag.
+const GUESSER_FRACTION: f64 = 0.5;
+
+/// options for a single block generated by [MockChainGenerator].
+///
+/// the default options produce a block with a valid mock proof, one
+/// nop-transaction merged into the coinbase transaction, and a timestamp one
+/// target block interval after its predecessor.
+#[derive(Debug, Clone, Default)]
+pub struct MockBlockOptions {
+    /// number of nop-transactions to merge into the block's coinbase
+    /// transaction.
+    ///
+    /// a block transaction must be the result of at least one merge, so a
+    /// single nop-transaction is merged if this is zero and `transactions`
+    /// is empty.
+    pub num_nop_transactions: usize,
+
+    /// transactions to merge into the block's coinbase transaction.
+    ///
+    /// each must be backed by a single proof, which on regtest may be a
+    /// mock proof.
+    pub transactions: Vec<Transaction>,
+
+    /// timestamp of the block.  defaults to the timestamp of the predecessor
+    /// plus the network's target block interval.
+    pub timestamp: Option<Timestamp>,
+
+    /// replace the block's proof with an invalid one.
+    ///
+    /// the block is otherwise well-formed and satisfies mock proof-of-work,
+    /// so it exercises the receiver's proof verification specifically.
+    pub invalid_proof: bool,
+}
+
+/// generates chains of mock blocks, reproducibly, for downstream test
+/// frameworks. (regtest network only)
+///
+/// The generated blocks have mock proofs and satisfy mock proof-of-work,
+/// so they are accepted by a regtest node just like blocks mined with
+/// [RegTest::mine_blocks_to_wallet()](super::RegTest::mine_blocks_to_wallet()).
+/// Unlike those, generating them does not require a running node, and the
+/// same network, wallet entropy, seed and sequence of calls always produce
+/// the same blocks.
+///
+/// Composer rewards are paid to the prover fee address of the wallet entropy
+/// and guesser rewards to its guesser fee key, both with on-chain
+/// notifications, so a wallet with the same entropy discovers its rewards
+/// when processing the blocks.
+///
+/// Forks are created by generating a successor of any block other than the
+/// tip of an existing chain.
+///
+/// ```no_run
+/// # use neptune_cash::api::export::Block;
+/// # use neptune_cash::api::export::Network;
+/// # use neptune_cash::api::regtest::error::RegTestError;
+/// # use neptune_cash::api::regtest::MockBlockOptions;
+/// # use neptune_cash::api::regtest::MockChainGenerator;
+/// # use neptune_cash::state::wallet::wallet_entropy::WalletEntropy;
+/// #
+/// # fn example() -> Result<(), RegTestError> {
+/// let network = Network::RegTest;
+/// let genesis = Block::genesis(network);
+/// let mut generator =
+///     MockChainGenerator::new(network, WalletEntropy::devnet_wallet(), [7u8; 32])?;
+///
+/// // a chain of 10 blocks on top of genesis
+/// let chain = generator.chain(&genesis, 10, MockBlockOptions::default())?;
+///
+/// // a competing block at height 6, with three merged transactions
+/// let options = MockBlockOptions {
+///     num_nop_transactions: 3,
+///     ..Default::default()
+/// };
+/// let fork = generator.successor(&chain[4], options)?;
+///
+/// // a block at height 11 that receivers must reject
+/// let options = MockBlockOptions {
+///     invalid_proof: true,
+///     ..Default::default()
+/// };
+/// let invalid = generator.successor(&chain[9], options)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MockChainGenerator {
+    worker: MockChainGeneratorPrivate,
+}
+
+impl MockChainGenerator {
+    /// instantiate a generator paying rewards to `wallet_entropy` and
+    /// drawing all randomness from `seed`.
+    ///
+    /// fails if the network does not accept mock proofs and mock
+    /// proof-of-work.
+    pub fn new(
+        network: Network,
+        wallet_entropy: WalletEntropy,
+        seed: [u8; 32],
+    ) -> Result<Self, RegTestError> {
+        Ok(Self {
+            worker: MockChainGeneratorPrivate::new(network, wallet_entropy, seed)?,
+        })
+    }
+
+    /// generate a successor of `predecessor` according to `options`.
+    pub fn successor(
+        &mut self,
+        predecessor: &Block,
+        options: MockBlockOptions,
+    ) -> Result<Block, RegTestError> {
+        self.worker.successor(predecessor, options)
+    }
+
+    /// generate `num_blocks` blocks extending `start`, each generated
+    /// according to `options`.
+    ///
+    /// all blocks share the same options, except that `transactions` and an
+    /// explicit timestamp only apply to the first block.  subsequent blocks
+    /// follow at the network's target block interval.
+    ///
+    /// the first block of the returned list is the successor of `start`.
+    pub fn chain(
+        &mut self,
+        start: &Block,
+        num_blocks: usize,
+        options: MockBlockOptions,
+    ) -> Result<Vec<Block>, RegTestError> {
+        self.worker.chain(start, num_blocks, options)
+    }
+}
+
+#[derive(Debug)]
+struct MockChainGeneratorPrivate {
+    network: Network,
+    wallet_entropy: WalletEntropy,
+    rng: StdRng,
+}
+
+impl MockChainGeneratorPrivate {
+    fn new(
+        network: Network,
+        wallet_entropy: WalletEntropy,
+        seed: [u8; 32],
+    ) -> Result<Self, RegTestError> {
+        if !network.use_mock_proof() || !network.allows_mock_pow() {
+            return Err(RegTestError::WrongNetwork);
+        }
+
+        Ok(Self {
+            network,
+            wallet_entropy,
+            rng: StdRng::from_seed(seed),
+        })
+    }
+
+    fn successor(
+        &mut self,
+        predecessor: &Block,
+        options: MockBlockOptions,
+    ) -> Result<Block, RegTestError> {
+        let timestamp = options.timestamp.unwrap_or_else(|| {
+            predecessor.header().timestamp + self.network.target_block_interval()
+        });
+
+        if let Some(index) = options
+            .transactions
+            .iter()
+            .position(|tx| !tx.proof.is_single_proof())
+        {
+            return Err(RegTestError::Failed(format!(
+                "transaction {index} is not backed by a single proof"
+            )));
+        }
+
+        let mut transactions = options.transactions;
+        transactions.extend(
+            MockBlockGenerator::mock_nop_transactions(
+                self.network,
+                predecessor,
+                timestamp,
+                options.num_nop_transactions,
+            )
+            .map_err(|e| RegTestError::Failed(e.to_string()))?,
+        );
+
+        let (block, _) = MockBlockGenerator::mock_successor_no_pow(
+            Arc::new(predecessor.clone()),
+            self.composer_parameters(predecessor),
+            self.wallet_entropy.guesser_fee_key().to_address().into(),
+            timestamp,
+            self.rng.random(),
+            transactions,
+            self.network,
+        );
+
+        let mut block = if options.invalid_proof {
+            Block::new(
+                *block.header(),
+                block.body().clone(),
+                block.appendix().clone(),
+                BlockProof::Invalid,
+            )
+        } else {
+            block
+        };
+        block.satisfy_mock_pow(predecessor.header().difficulty, self.rng.random());
+
+        Ok(block)
+    }
+
+    fn chain(
+        &mut self,
+        start: &Block,
+        num_blocks: usize,
+        mut options: MockBlockOptions,
+    ) -> Result<Vec<Block>, RegTestError> {
+        let mut blocks: Vec<Block> = Vec::with_capacity(num_blocks);
+        for _ in 0..num_blocks {
+            let predecessor = blocks.last().unwrap_or(start);
+            let block = self.successor(predecessor, options.clone())?;
+            options.transactions.clear();
+            options.timestamp = None;
+            blocks.push(block);
+        }
+
+        Ok(blocks)
+    }
+
+    // pays the composer reward to this wallet, as
+    // WalletState::composer_parameters() does for a node's own wallet.
+    fn composer_parameters(&self, predecessor: &Block) -> ComposerParameters {
+        let next_block_height = predecessor.header().height.next();
+        let reward_address = self.wallet_entropy.prover_fee_address();
+        let sender_randomness = self
+            .wallet_entropy
+            .generate_sender_randomness(next_block_height, reward_address.privacy_digest());
+
+        ComposerParameters::new(
+            CoinbaseDistribution::solo(reward_address),
+            sender_randomness,
+            Some(self.wallet_entropy.composer_fee_key().receiver_preimage()),
+            GUESSER_FRACTION,
+            Default::default(),
+        )
+    }
+}
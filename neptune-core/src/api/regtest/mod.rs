@@ -6,8 +6,25 @@
 //! regtest mode:
 //! 1. provides a locally controlled network without peer discovery.
 //! 2. enables blocks to be generated quickly without real proofs.
+//!
+//! [MockChainGenerator] generates such blocks reproducibly and without a
+//! running node, for test frameworks that construct chains, forks and
+//! invalid blocks of their own.
+//!
+//! [DevnetPremine] provides the devnet premine wallet and the genesis UTXOs
+//! it can claim as fixtures, for test frameworks that fund accounts. It is
+//! only available with the `regtest-fixtures` feature.
+mod mock_chain;
+#[cfg(feature = "regtest-fixtures")]
+mod premine_fixture;
 mod regtest_impl;
 
 // these represent the public tx_initiator API
 pub mod error;
+pub use mock_chain::MockBlockOptions;
+pub use mock_chain::MockChainGenerator;
+#[cfg(feature = "regtest-fixtures")]
+pub use premine_fixture::DevnetPremine;
+#[cfg(feature = "regtest-fixtures")]
+pub use premine_fixture::PremineUtxo;
 pub use regtest_impl::RegTest;
@@ -0,0 +1,218 @@
+use tasm_lib::prelude::Digest;
+
+use super::error::RegTestError;
+use crate::api::export::AdditionRecord;
+use crate::api::export::Block;
+use crate::api::export::GenerationSpendingKey;
+use crate::api::export::NativeCurrencyAmount;
+use crate::api::export::Network;
+use crate::api::export::ReceivingAddress;
+use crate::api::export::Timestamp;
+use crate::api::export::UtxoTriple;
+use crate::state::wallet::wallet_entropy::WalletEntropy;
+
+/// number of generation keys of the devnet wallet that are scanned for
+/// premine UTXOs.  matches the number of keys a wallet scans when it is
+/// initialized with the genesis block.
+const NUM_PREMINE_KEYS: u64 = 10;
+
+/// a premine UTXO in the genesis block that the devnet wallet can claim.
+#[derive(Debug, Clone)]
+pub struct PremineUtxo {
+    /// the UTXO, the premine sender randomness of the network, and the
+    /// receiver digest of the key it is locked to.
+    pub utxo_triple: UtxoTriple,
+
+    /// preimage of the receiver digest, required to spend the UTXO.
+    pub receiver_preimage: Digest,
+
+    /// generation-key derivation index of the key the UTXO is locked to.
+    pub derivation_index: u64,
+
+    /// index of the UTXO's leaf in the append-only commitment list of the
+    /// genesis mutator set.
+    pub aocl_leaf_index: u64,
+
+    /// the date before which the UTXO cannot be spent, if timelocked.
+    pub release_date: Option<Timestamp>,
+}
+
+impl PremineUtxo {
+    /// the UTXO's commitment in the genesis block.
+    pub fn addition_record(&self) -> AdditionRecord {
+        self.utxo_triple.addition_record()
+    }
+
+    /// the amount of native currency in the UTXO.
+    pub fn amount(&self) -> NativeCurrencyAmount {
+        self.utxo_triple.utxo.get_native_currency_amount()
+    }
+}
+
+/// the devnet premine wallet and the genesis UTXOs it can claim, as fixtures
+/// for downstream integration test suites. (regtest network only)
+///
+/// The genesis block of every network pays part of the premine to keys of
+/// [WalletEntropy::devnet_wallet()], which anyone can derive.  This fixture
+/// re-derives those keys and matches them against the genesis outputs, so
+/// test suites can fund accounts without copying address constants that
+/// change whenever the premine distribution does.
+///
+/// A node started with the devnet wallet discovers the premine UTXOs when
+/// its wallet is initialized, so spending from it funds other accounts.
+/// Alternatively, a UTXO can be handed to a wallet as a [UtxoTriple].
+///
+/// only available with the `regtest-fixtures` feature.
+///
+/// ```
+/// # use neptune_cash::api::export::Network;
+/// # use neptune_cash::api::regtest::error::RegTestError;
+/// # use neptune_cash::api::regtest::DevnetPremine;
+/// #
+/// # fn example() -> Result<(), RegTestError> {
+/// let premine = DevnetPremine::new(Network::RegTest)?;
+///
+/// // the wallet to start a funded regtest node with
+/// let wallet_entropy = premine.wallet_entropy();
+///
+/// for utxo in premine.utxos() {
+///     println!(
+///         "{} at key {} in aocl leaf {}",
+///         utxo.amount(),
+///         utxo.derivation_index,
+///         utxo.aocl_leaf_index,
+///     );
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DevnetPremine {
+    worker: DevnetPreminePrivate,
+}
+
+impl DevnetPremine {
+    /// derive the fixture for the genesis block of `network`.
+    ///
+    /// fails if the network does not accept mock proofs and mock
+    /// proof-of-work, or if the genesis block pays no premine to the devnet
+    /// wallet.
+    pub fn new(network: Network) -> Result<Self, RegTestError> {
+        Ok(Self {
+            worker: DevnetPreminePrivate::new(network)?,
+        })
+    }
+
+    /// the network whose genesis block the fixture was derived from.
+    pub fn network(&self) -> Network {
+        self.worker.network
+    }
+
+    /// the devnet wallet's entropy, with which a wallet can claim the
+    /// premine UTXOs.
+    pub fn wallet_entropy(&self) -> WalletEntropy {
+        WalletEntropy::devnet_wallet()
+    }
+
+    /// the devnet wallet's spending keys to which premine UTXOs are locked,
+    /// ordered by derivation index.
+    pub fn spending_keys(&self) -> Vec<GenerationSpendingKey> {
+        self.worker.spending_keys()
+    }
+
+    /// the receiving addresses of [Self::spending_keys()].
+    pub fn receiving_addresses(&self) -> Vec<ReceivingAddress> {
+        self.worker.receiving_addresses()
+    }
+
+    /// the premine UTXOs the devnet wallet can claim, ordered by their leaf
+    /// index in the genesis mutator set.
+    pub fn utxos(&self) -> &[PremineUtxo] {
+        &self.worker.utxos
+    }
+
+    /// the total amount of the premine UTXOs the devnet wallet can claim.
+    pub fn total_amount(&self) -> NativeCurrencyAmount {
+        self.worker.utxos.iter().map(|utxo| utxo.amount()).sum()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct DevnetPreminePrivate {
+    network: Network,
+    utxos: Vec<PremineUtxo>,
+}
+
+impl DevnetPreminePrivate {
+    fn new(network: Network) -> Result<Self, RegTestError> {
+        if !network.use_mock_proof() || !network.allows_mock_pow() {
+            return Err(RegTestError::WrongNetwork);
+        }
+
+        let entropy = WalletEntropy::devnet_wallet();
+        let keys = (0..NUM_PREMINE_KEYS)
+            .map(|index| {
+                let key = entropy.nth_generation_spending_key(index);
+                let address = ReceivingAddress::from(key.to_address());
+                (index, key, address)
+            })
+            .collect::<Vec<_>>();
+
+        let sender_randomness = Block::premine_sender_randomness(network);
+        let mut utxos = vec![];
+        for (aocl_leaf_index, utxo) in (0u64..).zip(Block::premine_utxos()) {
+            let Some((derivation_index, key, address)) = keys
+                .iter()
+                .find(|(_, _, address)| address.lock_script_hash() == utxo.lock_script_hash())
+            else {
+                continue;
+            };
+
+            utxos.push(PremineUtxo {
+                release_date: utxo.release_date(),
+                utxo_triple: UtxoTriple {
+                    utxo,
+                    sender_randomness,
+                    receiver_digest: address.privacy_digest(),
+                },
+                receiver_preimage: key.receiver_preimage(),
+                derivation_index: *derivation_index,
+                aocl_leaf_index,
+            });
+        }
+
+        if utxos.is_empty() {
+            return Err(RegTestError::Failed(
+                "genesis block pays no premine to the devnet wallet".to_string(),
+            ));
+        }
+
+        Ok(Self { network, utxos })
+    }
+
+    fn derivation_indices(&self) -> Vec<u64> {
+        let mut indices = self
+            .utxos
+            .iter()
+            .map(|utxo| utxo.derivation_index)
+            .collect::<Vec<_>>();
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+
+    fn spending_keys(&self) -> Vec<GenerationSpendingKey> {
+        let entropy = WalletEntropy::devnet_wallet();
+        self.derivation_indices()
+            .into_iter()
+            .map(|index| entropy.nth_generation_spending_key(index))
+            .collect()
+    }
+
+    fn receiving_addresses(&self) -> Vec<ReceivingAddress> {
+        self.spending_keys()
+            .into_iter()
+            .map(|key| key.to_address().into())
+            .collect()
+    }
+}
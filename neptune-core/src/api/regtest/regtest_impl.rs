@@ -2,6 +2,7 @@ use tasm_lib::prelude::Digest;
 
 use super::error::RegTestError;
 use crate::api::export::Timestamp;
+use crate::application::config::inclusion_policy::InclusionPolicy;
 use crate::protocol::consensus::block::mock_block_generator::MockBlockGenerator;
 use crate::protocol::consensus::block::Block;
 use crate::protocol::shared::SIZE_20MB_IN_BYTES;
@@ -37,8 +38,6 @@ impl From<GlobalStateLock> for RegTest {
 // generate: This RPC mines a specified number of blocks, but offers less control over the recipient address compared to generatetoaddress.
 // generateblock: This RPC mines a block and allows the caller to specify the block template.
 // setmocktime: This RPC allows manual manipulation of the blockchain's apparent timestamp, facilitating testing of time-sensitive consensus rules.
-// invalidateblock: This RPC removes a block from the current best chain, enabling the simulation of blockchain reorganizations.
-// reconsiderblock: This RPC reconsiders whether a block should be part of the best chain, often used in conjunction with invalidateblock to test chain selection logic.
 //
 impl RegTest {
     /// mine a series of blocks to the node's wallet. (regtest network only)
@@ -81,6 +80,28 @@ impl RegTest {
             .await
     }
 
+    /// mark a stored block invalid, forcing the node onto the most canonical
+    /// stored chain without it. (regtest network only)
+    ///
+    /// The block and its descendants are rejected, also when received from
+    /// peers, until [reconsidered](Self::reconsider_block). If the block is in
+    /// the canonical chain, the node reorganizes: the mutator set and wallet
+    /// are rolled back and the mempool is updated, as for any other
+    /// reorganization.
+    ///
+    /// Returns the digest of the resulting tip.
+    pub async fn invalidate_block(&mut self, block_digest: Digest) -> Result<Digest, RegTestError> {
+        self.worker.invalidate_block(block_digest).await
+    }
+
+    /// remove the mark set by [Self::invalidate_block()] from a block, and
+    /// switch to the most canonical stored chain. (regtest network only)
+    ///
+    /// Returns the digest of the resulting tip.
+    pub async fn reconsider_block(&mut self, block_digest: Digest) -> Result<Digest, RegTestError> {
+        self.worker.reconsider_block(block_digest).await
+    }
+
     /// Compose a block with a mocked proof and set it as current block
     /// proposal
     pub async fn set_self_composed_proposal(&mut self, timestamp: Timestamp, seed: [u8; 32]) {
@@ -145,18 +166,22 @@ impl RegTestPrivate {
 
         let guesser_key = gs.wallet_state.wallet_entropy.guesser_fee_key();
 
-        // retrieve selected tx from mempool for block inclusion.
-        let txs_from_mempool = if include_mempool_txs {
-            gs.mempool.get_transactions_for_block_composition(
+        // select tx from mempool for block inclusion.
+        let selection = include_mempool_txs.then(|| {
+            gs.mempool.select_for_block_composition(
                 SIZE_20MB_IN_BYTES,
                 Some(gsl.cli().max_num_compose_mergers.get()),
+                &InclusionPolicy::default(),
             )
-        } else {
-            vec![]
-        };
+        });
 
         drop(gs);
 
+        let txs_from_mempool = match selection {
+            Some(selection) => selection.read().await.0,
+            None => vec![],
+        };
+
         let (mut block, composer_tx_outputs) = MockBlockGenerator::mock_successor_no_pow(
             tip_block.clone(),
             composer_parameters.clone(),
@@ -234,6 +259,67 @@ impl RegTestPrivate {
         Ok(block_hash)
     }
 
+    // see description in [RegTest]
+    async fn invalidate_block(&mut self, block_digest: Digest) -> Result<Digest, RegTestError> {
+        self.ensure_regtest()?;
+        let new_tip = self
+            .global_state_lock
+            .lock_guard_mut()
+            .await
+            .invalidate_block(block_digest)
+            .await?;
+        self.set_tip(new_tip).await?;
+
+        Ok(new_tip)
+    }
+
+    // see description in [RegTest]
+    async fn reconsider_block(&mut self, block_digest: Digest) -> Result<Digest, RegTestError> {
+        self.ensure_regtest()?;
+        let new_tip = self
+            .global_state_lock
+            .lock_guard_mut()
+            .await
+            .reconsider_block(block_digest)
+            .await?;
+        self.set_tip(new_tip).await?;
+
+        Ok(new_tip)
+    }
+
+    fn ensure_regtest(&self) -> Result<(), RegTestError> {
+        if !self.global_state_lock.cli().network.is_reg_test() {
+            return Err(RegTestError::WrongNetwork);
+        }
+        Ok(())
+    }
+
+    // has the main-loop set a stored block as tip, so that the reorganization
+    // goes through the same code path as those caused by peers.
+    async fn set_tip(&self, new_tip: Digest) -> Result<(), RegTestError> {
+        if self
+            .global_state_lock
+            .lock_guard()
+            .await
+            .chain
+            .light_state()
+            .hash()
+            == new_tip
+        {
+            return Ok(());
+        }
+
+        self.global_state_lock.rpc_server_to_main_tx()
+            .send(RPCServerToMain::SetTipToStoredBlock(new_tip))
+            .await
+            .map_err(|_| {
+                tracing::warn!("channel send failed. channel 'rpc_server_to_main' closed unexpectedly. main_loop may have terminated prematurely.");
+                RegTestError::Failed("internal error. tip not set".into())
+            })?;
+
+        Self::wait_until_block_in_chain(&self.global_state_lock, new_tip).await
+    }
+
     // waits (polls) until block is found in canonical chain or 5 second timeout occurs.
     //
     // note: temporary until listener approach is implemented.
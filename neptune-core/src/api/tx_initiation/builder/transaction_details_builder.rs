@@ -209,9 +209,13 @@ impl TransactionDetailsBuilder {
                         key_type: KeyType,
                         change_amount: NativeCurrencyAmount,
                         medium: UtxoNotificationMedium,
+                        timestamp: Timestamp,
                     ) -> Result<(TxOutput, Arc<Block>), CreateTxError> {
                         let tip = gsm.chain.light_state_clone();
-                        let key = gsm.wallet_state.next_unused_spending_key(key_type).await;
+                        let key = gsm
+                            .wallet_state
+                            .spending_key_for_reception(key_type, timestamp)
+                            .await;
 
                         Ok((
                             TransactionDetailsBuilder::create_change_output(
@@ -232,11 +236,13 @@ impl TransactionDetailsBuilder {
                                 key_type,
                                 change_amount,
                                 medium,
+                                timestamp,
                             )
                             .await?
                         }
                         StateLock::WriteGuard(ref mut gsm) => {
-                            create_change(&mut *gsm, key_type, change_amount, medium).await?
+                            create_change(&mut *gsm, key_type, change_amount, medium, timestamp)
+                                .await?
                         }
                         StateLock::ReadGuard(_) => {
                             return Err(CreateTxError::CantGenChangeKeyForImmutableWallet)
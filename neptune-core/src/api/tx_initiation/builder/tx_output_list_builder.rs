@@ -7,6 +7,8 @@
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::api::amount::error::AmountError;
+use crate::api::amount::ValidatedAmount;
 use crate::api::export::Timestamp;
 use crate::protocol::consensus::block::block_height::BlockHeight;
 use crate::protocol::consensus::transaction::utxo::Utxo;
@@ -59,6 +61,22 @@ impl OutputFormat {
         }
     }
 
+    /// returns the native currency amount, checked to be within the supply.
+    ///
+    /// unlike [Self::native_currency_amount()] this does not overflow if a
+    /// utxo holds several native currency coins.
+    pub fn validated_amount(&self) -> Result<ValidatedAmount, AmountError> {
+        match self {
+            Self::AddressAndAmount(_, amt) => ValidatedAmount::new(*amt),
+            Self::AddressAndAmountAndMedium(_, amt, _) => ValidatedAmount::new(*amt),
+            Self::AddressAndAmountAndReleaseDate(_, amt, _) => ValidatedAmount::new(*amt),
+            Self::AddressAndUtxo(_, u) => ValidatedAmount::checked_sum(u.native_currency_amounts()),
+            Self::AddressAndUtxoAndMedium(_, u, _) => {
+                ValidatedAmount::checked_sum(u.native_currency_amounts())
+            }
+        }
+    }
+
     // ##multicoin## : maybe something like
     // pub fn amount(&self, coint: Coin) -> CoinAmount;
 
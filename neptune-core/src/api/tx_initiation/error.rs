@@ -1,10 +1,15 @@
 //! provides error types related to initiating transactions.
 
+use serde::Deserialize;
+use serde::Serialize;
 use tasm_lib::prelude::Digest;
 
 use crate::api::export::BlockHeight;
 use crate::api::export::NativeCurrencyAmount;
+use crate::api::export::Network;
 use crate::api::export::RecordTransactionError;
+use crate::api::export::Timestamp;
+use crate::api::tx_initiation::raw_transaction::RawTransactionSpecError;
 use crate::application::job_queue::errors::AddJobError;
 use crate::application::job_queue::errors::JobHandleError;
 use crate::protocol::consensus::transaction::transaction_proof::TransactionProofType;
@@ -45,6 +50,50 @@ pub enum CreateTxError {
 
     #[error("tip does not have mutator-set-after")]
     NoMutatorSetAccumulatorAfter,
+
+    #[error("payment requires more than the maximum of {max} inputs per transaction")]
+    TooManyInputs { max: usize },
+
+    #[error("input {aocl_leaf_index} is not a synced, unspent UTXO of this wallet")]
+    UnknownInput { aocl_leaf_index: u64 },
+
+    #[error("input {aocl_leaf_index} was selected more than once")]
+    DuplicateInput { aocl_leaf_index: u64 },
+
+    #[error("input {aocl_leaf_index} is time-locked until {release_date}")]
+    TimeLockedInput {
+        aocl_leaf_index: u64,
+        release_date: Timestamp,
+    },
+
+    #[error("input {aocl_leaf_index} is already spent by a transaction in the mempool")]
+    InputSpentInMempool { aocl_leaf_index: u64 },
+
+    #[error("no spending key found for input {aocl_leaf_index}")]
+    NoSpendingKeyForInput { aocl_leaf_index: u64 },
+
+    #[error("fee {fee} is below the minimum send fee of {floor}")]
+    BelowFeeFloor {
+        fee: NativeCurrencyAmount,
+        floor: NativeCurrencyAmount,
+    },
+
+    #[error("transaction has {count} {component}, exceeding the maximum of {max}")]
+    ExceedsSize {
+        component: TxComponent,
+        count: usize,
+        max: usize,
+    },
+}
+
+/// a part of a transaction that is subject to a consensus size limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display)]
+#[strum(serialize_all = "lowercase")]
+#[non_exhaustive]
+pub enum TxComponent {
+    Inputs,
+    Outputs,
+    Announcements,
 }
 
 #[derive(Debug, Clone, thiserror::Error, strum::Display)]
@@ -103,6 +152,17 @@ pub enum UpgradeProofError {
     InvalidProof,
 }
 
+/// enumerates possible errors cancelling an own transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum CancelTxError {
+    #[error("transaction is not an own transaction in the mempool")]
+    NotOwnTransaction,
+
+    #[error("transaction was proven and broadcast already")]
+    AlreadyBroadcast,
+}
+
 /// enumerates possible transaction send errors
 #[derive(Debug, thiserror::Error)]
 #[non_exhaustive]
@@ -113,6 +173,9 @@ pub enum SendError {
     #[error("transaction could not be broadcast.")]
     NotBroadcast,
 
+    #[error("this node is a standby and cannot spend until promoted")]
+    Standby,
+
     #[error(transparent)]
     Tx(#[from] CreateTxError),
 
@@ -128,4 +191,115 @@ pub enum SendError {
         tip_digest: Digest,
         max: usize,
     },
+
+    #[error(transparent)]
+    RecipientDenied(#[from] RecipientDeniedError),
+
+    #[error(transparent)]
+    InvalidSpec(#[from] RawTransactionSpecError),
+}
+
+/// classifies a failed transaction initiation.
+///
+/// [SendError] and the errors it wraps describe failures in detail but are not
+/// serializable, and so reach RPC clients only as text. This type is what RPC
+/// clients receive instead: one variant per kind of failure a user can act
+/// upon, so that eg a GUI can tell an insufficient balance apart from a fee
+/// that is too low without parsing messages.
+///
+/// Failures without a variant of their own are reported as
+/// [TxInitiationError::Other] along with their message.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum TxInitiationError {
+    #[error("insufficient funds. requested: {requested}, available: {available}")]
+    InsufficientFunds {
+        requested: NativeCurrencyAmount,
+        available: NativeCurrencyAmount,
+    },
+
+    #[error("fee {fee} is below the minimum send fee of {floor}")]
+    BelowFeeFloor {
+        fee: NativeCurrencyAmount,
+        floor: NativeCurrencyAmount,
+    },
+
+    #[error("transaction exceeds the maximum of {max} {component}")]
+    ExceedsSize { component: TxComponent, max: usize },
+
+    #[error("proving failed: {0}")]
+    ProvingFailed(String),
+
+    #[error("wallet cannot spend: {0}")]
+    WalletLocked(String),
+
+    #[error("output {index} is an address for another network than {network}")]
+    AddressWrongNetwork { index: usize, network: Network },
+
+    #[error("{0}")]
+    Other(String),
+}
+
+impl From<&CreateTxError> for TxInitiationError {
+    fn from(err: &CreateTxError) -> Self {
+        match *err {
+            CreateTxError::InsufficientFunds {
+                requested,
+                available,
+            } => Self::InsufficientFunds {
+                requested,
+                available,
+            },
+            CreateTxError::BelowFeeFloor { fee, floor } => Self::BelowFeeFloor { fee, floor },
+            CreateTxError::ExceedsSize { component, max, .. } => {
+                Self::ExceedsSize { component, max }
+            }
+            CreateTxError::TooManyInputs { max } => Self::ExceedsSize {
+                component: TxComponent::Inputs,
+                max,
+            },
+            CreateTxError::CantGenChangeKeyForImmutableWallet => {
+                Self::WalletLocked(err.to_string())
+            }
+            _ => Self::Other(err.to_string()),
+        }
+    }
+}
+
+impl From<&SendError> for TxInitiationError {
+    fn from(err: &SendError) -> Self {
+        match err {
+            SendError::Tx(e) => e.into(),
+            SendError::Proof(e) => Self::ProvingFailed(e.to_string()),
+            SendError::Standby | SendError::Unsupported => Self::WalletLocked(err.to_string()),
+            SendError::InvalidSpec(RawTransactionSpecError::AddressWrongNetwork {
+                index,
+                network,
+            }) => Self::AddressWrongNetwork {
+                index: *index,
+                network: *network,
+            },
+            _ => Self::Other(err.to_string()),
+        }
+    }
+}
+
+/// describes a recipient that was denied by the active
+/// [RecipientPolicy](super::recipient_policy::RecipientPolicy).
+///
+/// The same record is appended to the send-policy audit file.
+#[derive(Debug, Clone, thiserror::Error, Serialize, Deserialize)]
+#[error("recipient {address} denied by send policy: {reason}")]
+pub struct RecipientDeniedError {
+    /// bech32m encoding of the denied address
+    pub address: String,
+
+    /// amount that was to be sent to the address
+    pub amount: NativeCurrencyAmount,
+
+    /// reason given by the policy
+    pub reason: String,
+
+    /// time at which the send was attempted
+    pub timestamp: Timestamp,
 }
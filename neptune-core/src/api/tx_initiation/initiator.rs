@@ -14,6 +14,8 @@
 use std::sync::Arc;
 
 use super::error;
+use crate::api::chain::DataCommitment;
+use crate::api::export::Announcement;
 use crate::api::export::Timestamp;
 use crate::api::tx_initiation::builder::transaction_builder::TransactionBuilder;
 use crate::api::tx_initiation::builder::transaction_details_builder::TransactionDetailsBuilder;
@@ -24,7 +26,14 @@ use crate::api::tx_initiation::builder::tx_input_list_builder::InputSelectionPol
 use crate::api::tx_initiation::builder::tx_input_list_builder::TxInputListBuilder;
 use crate::api::tx_initiation::builder::tx_output_list_builder::OutputFormat;
 use crate::api::tx_initiation::builder::tx_output_list_builder::TxOutputListBuilder;
+use crate::api::tx_initiation::planner::TransferPlan;
+use crate::api::tx_initiation::planner::TransferPlanner;
+use crate::api::tx_initiation::private::RATE_LIMIT;
+use crate::api::tx_initiation::private::RATE_LIMIT_UNTIL_HEIGHT;
+use crate::api::tx_initiation::raw_transaction::RawTransactionSpec;
+use crate::api::tx_initiation::recipient_policy::RecipientPolicy;
 use crate::application::triton_vm_job_queue::vm_job_queue;
+use crate::application::triton_vm_job_queue::TritonVmJobPriority;
 use crate::protocol::consensus::consensus_rule_set::ConsensusRuleSet;
 use crate::protocol::consensus::transaction::primitive_witness::PrimitiveWitness;
 use crate::protocol::consensus::transaction::transaction_proof::TransactionProofType;
@@ -85,6 +94,68 @@ impl TransactionInitiator {
             .build()
     }
 
+    /// retrieve exactly the wallet's inputs with the given AOCL leaf indices,
+    /// in the given order, bypassing automatic input selection.
+    ///
+    /// fails with an error identifying the first input that is not a synced,
+    /// unspent UTXO of the wallet, is selected more than once, is time-locked
+    /// at `timestamp`, is already spent by a mempool transaction, or has no
+    /// spending key in the wallet.
+    pub async fn inputs_by_aocl_leaf_index(
+        &self,
+        aocl_leaf_indices: &[u64],
+        timestamp: Timestamp,
+    ) -> Result<TxInputList, error::CreateTxError> {
+        Ok(self
+            .global_state_lock
+            .lock_guard()
+            .await
+            .wallet_inputs_by_aocl_leaf_index(aocl_leaf_indices, timestamp)
+            .await?
+            .into())
+    }
+
+    /// report how many transactions, fees, confirmation rounds, and how much
+    /// proving time are needed to make the given payments from the wallet's
+    /// spendable inputs, paying `fee` for each transaction.
+    ///
+    /// Consensus limits and, while it applies, the send rate-limit are taken
+    /// into account. Nothing is built, proven, or sent.
+    ///
+    /// See [TransferPlanner] for details and for customizing the planning.
+    pub async fn plan_transfer(
+        &self,
+        outputs: impl IntoIterator<Item = impl Into<OutputFormat>>,
+        fee: NativeCurrencyAmount,
+        timestamp: Timestamp,
+    ) -> Result<TransferPlan, error::CreateTxError> {
+        let payments = outputs
+            .into_iter()
+            .map(|output| output.into().native_currency_amount())
+            .collect::<Vec<_>>();
+        let spendable = self
+            .spendable_inputs(timestamp)
+            .await
+            .iter()
+            .map(|input| input.native_currency_amount())
+            .collect::<Vec<_>>();
+
+        let state = self.global_state_lock.lock_guard().await;
+        let tip = state.chain.light_state();
+        let network = state.cli().network;
+        let consensus_rule_set = ConsensusRuleSet::infer_from(network, tip.header().height);
+        let mut planner = TransferPlanner::new(spendable, consensus_rule_set);
+        if tip.header().height < RATE_LIMIT_UNTIL_HEIGHT.into() {
+            let sent_at_tip = state
+                .wallet_state
+                .count_sent_transactions_at_block(tip.hash())
+                .await;
+            planner = planner.rate_limit(RATE_LIMIT, sent_at_tip);
+        }
+
+        planner.plan(&payments, fee)
+    }
+
     /// generate a list of outputs from a list of [OutputFormat].
     ///
     /// note that the outputs can be expressed in tuple format, so long
@@ -103,6 +174,51 @@ impl TransactionInitiator {
             .await
     }
 
+    /// check all outputs against the active
+    /// [RecipientPolicy](super::recipient_policy::RecipientPolicy), if any.
+    ///
+    /// This check is performed automatically by [send()](Self::send()). Callers
+    /// assembling transactions via the builder API may call it before
+    /// [record_and_broadcast_transaction()](Self::record_and_broadcast_transaction()).
+    ///
+    /// Returns [SendError::RecipientDenied](error::SendError::RecipientDenied)
+    /// for the first denied output. Each denial is appended to the audit file at
+    /// [DataDirectory::send_policy_audit_file_path()](crate::application::config::data_directory::DataDirectory::send_policy_audit_file_path()).
+    pub async fn check_recipients(
+        &self,
+        outputs: &[OutputFormat],
+        timestamp: Timestamp,
+    ) -> Result<(), error::SendError> {
+        self.private().check_recipients(outputs, timestamp).await
+    }
+
+    /// install a [RecipientPolicy](super::recipient_policy::RecipientPolicy)
+    /// to be consulted before sending, replacing any policy active before,
+    /// including one read from `--denied-addresses-file`.
+    ///
+    /// `None` disables the policy check.
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use neptune_cash::api::export::GlobalStateLock;
+    /// # use neptune_cash::api::export::ReceivingAddress;
+    /// # use neptune_cash::api::tx_initiation::recipient_policy::AddressDenylist;
+    /// #
+    /// # async fn example(mut gsl: GlobalStateLock, denied: Vec<ReceivingAddress>) {
+    /// let denylist = AddressDenylist::new(&denied);
+    /// gsl.api_mut()
+    ///     .tx_initiator_mut()
+    ///     .set_recipient_policy(Some(Arc::new(denylist)))
+    ///     .await;
+    /// # }
+    /// ```
+    pub async fn set_recipient_policy(&mut self, policy: Option<Arc<dyn RecipientPolicy>>) {
+        self.global_state_lock
+            .lock_guard_mut()
+            .await
+            .recipient_policy = policy;
+    }
+
     /// generates [TransactionDetails] from inputs and outputs
     ///
     /// see [TransactionDetailsBuilder] for details.
@@ -193,6 +309,7 @@ impl TransactionInitiator {
         tx: &TxCreationArtifacts,
     ) -> Result<(), error::SendError> {
         // may have been checked before, but just in case.
+        self.worker().check_spend_enabled().await?;
         self.worker().check_rate_limit().await?;
 
         // note: acquires write-lock.
@@ -229,11 +346,37 @@ impl TransactionInitiator {
             .ok_or(error::UpgradeProofError::TxNotInMempool)
     }
 
+    /// Cancel an own transaction that is still being proven.
+    ///
+    /// The send methods return the transaction before it is proven, and the
+    /// proving happens in the background. Until the transaction is proven and
+    /// broadcast, it can be cancelled by its id: the proving job is aborted,
+    /// the transaction is removed from the mempool, and its inputs can be
+    /// spent by other transactions again. A
+    /// [`HookEvent::TxCancelled`](crate::application::plugin_hooks::HookEvent::TxCancelled)
+    /// event is published.
+    pub async fn cancel(&mut self, txid: TransactionKernelId) -> Result<(), error::CancelTxError> {
+        self.global_state_lock
+            .lock_guard_mut()
+            .await
+            .cancel_own_transaction(txid)
+            .await
+    }
+
     fn worker(&self) -> super::private::TransactionInitiatorPrivate {
         super::private::TransactionInitiatorPrivate::new(self.global_state_lock.clone())
     }
 
     /// Build and broadcast a regular transaction.
+    ///
+    /// The transaction is returned as soon as it is recorded, and proven in
+    /// the background. Until it is broadcast, it can be cancelled with
+    /// [Self::cancel], using the id of the returned transaction.
+    ///
+    /// If `--tx-timestamp-fuzz` or `--tx-timestamp-granularity` is set, the
+    /// transaction is timestamped somewhat before `timestamp`, so as not to
+    /// reveal the time of initiation. The same holds for the other send
+    /// methods, except for [Self::send_raw] with an explicit timestamp.
     pub async fn send(
         &mut self,
         outputs: impl IntoIterator<Item = impl Into<OutputFormat>>,
@@ -241,8 +384,71 @@ impl TransactionInitiator {
         fee: NativeCurrencyAmount,
         timestamp: Timestamp,
     ) -> Result<TxCreationArtifacts, error::SendError> {
-        self.send_inner(outputs, change_policy, fee, timestamp, false)
-            .await
+        self.send_inner(
+            outputs,
+            vec![],
+            None,
+            change_policy,
+            fee,
+            timestamp,
+            true,
+            false,
+        )
+        .await
+    }
+
+    /// Build and broadcast a transaction that spends exactly the wallet's
+    /// UTXOs with the given AOCL leaf indices, instead of automatically
+    /// selected ones.
+    ///
+    /// Fails with [InsufficientFunds](error::CreateTxError::InsufficientFunds)
+    /// if the selected UTXOs do not cover the outputs and fee. Any excess is
+    /// returned as change. See [Self::inputs_by_aocl_leaf_index] for the
+    /// validation of the selected UTXOs.
+    pub async fn send_with_inputs(
+        &mut self,
+        outputs: impl IntoIterator<Item = impl Into<OutputFormat>>,
+        aocl_leaf_indices: &[u64],
+        change_policy: ChangePolicy,
+        fee: NativeCurrencyAmount,
+        timestamp: Timestamp,
+    ) -> Result<TxCreationArtifacts, error::SendError> {
+        self.send_inner(
+            outputs,
+            vec![],
+            Some(aocl_leaf_indices),
+            change_policy,
+            fee,
+            timestamp,
+            true,
+            false,
+        )
+        .await
+    }
+
+    /// Build and broadcast a transaction that is described completely by
+    /// `spec`: inputs, outputs, fee, timestamp, and announcements.
+    ///
+    /// The spec is validated with [RawTransactionSpec::validate()], and the
+    /// inputs as by [Self::send_with_inputs]. The transaction is then proven
+    /// and broadcast like any other.
+    pub async fn send_raw(
+        &mut self,
+        spec: &RawTransactionSpec,
+    ) -> Result<TxCreationArtifacts, error::SendError> {
+        let network = self.global_state_lock.cli().network;
+        let raw = spec.validate(network, Timestamp::now())?;
+        self.send_inner(
+            raw.outputs,
+            raw.announcements,
+            Some(&raw.inputs),
+            raw.change_policy,
+            raw.fee,
+            raw.timestamp,
+            spec.timestamp.is_none(),
+            false,
+        )
+        .await
     }
 
     /// Build and broadcast a *transparent* transaction.
@@ -258,8 +464,50 @@ impl TransactionInitiator {
         fee: NativeCurrencyAmount,
         timestamp: Timestamp,
     ) -> Result<TxCreationArtifacts, error::SendError> {
-        self.send_inner(outputs, change_policy, fee, timestamp, true)
-            .await
+        self.send_inner(
+            outputs,
+            vec![],
+            None,
+            change_policy,
+            fee,
+            timestamp,
+            true,
+            true,
+        )
+        .await
+    }
+
+    /// Build and broadcast a transaction that commits to external data.
+    ///
+    /// The transaction has no outputs other than change. It carries one
+    /// announcement per [DataCommitment], and is otherwise a regular
+    /// transaction.
+    ///
+    /// Once the transaction is confirmed, a proof of inclusion can be
+    /// retrieved via
+    /// [Chain::find_data_commitment()](crate::api::chain::Chain::find_data_commitment()).
+    pub async fn send_data_commitments(
+        &mut self,
+        commitments: impl IntoIterator<Item = DataCommitment>,
+        change_policy: ChangePolicy,
+        fee: NativeCurrencyAmount,
+        timestamp: Timestamp,
+    ) -> Result<TxCreationArtifacts, error::SendError> {
+        let announcements = commitments
+            .into_iter()
+            .map(|c| c.to_announcement())
+            .collect();
+        self.send_inner(
+            Vec::<OutputFormat>::new(),
+            announcements,
+            None,
+            change_policy,
+            fee,
+            timestamp,
+            true,
+            false,
+        )
+        .await
     }
 
     /// Build a transaction and broadcast it.
@@ -269,16 +517,23 @@ impl TransactionInitiator {
     // afterwards. As a result, no single lock is held over the bulk of the
     // function's duration, potentially leading to funky race conditions if
     // multiple invocations are made in parallel.
+    #[allow(clippy::too_many_arguments)]
     async fn send_inner(
         &mut self,
         outputs: impl IntoIterator<Item = impl Into<OutputFormat>>,
+        announcements: Vec<Announcement>,
+        aocl_leaf_indices: Option<&[u64]>,
         change_policy: ChangePolicy,
         fee: NativeCurrencyAmount,
         timestamp: Timestamp,
+        fuzz_timestamp: bool,
         transparent: bool,
     ) -> Result<TxCreationArtifacts, error::SendError> {
         self.private().check_proceed_with_send(fee).await?;
 
+        let outputs = outputs.into_iter().map(Into::into).collect::<Vec<_>>();
+        self.check_recipients(&outputs, timestamp).await?;
+
         tracing::debug!("tx send initiated.");
 
         // The target proof-type is set to the lowest possible value here,
@@ -290,22 +545,35 @@ impl TransactionInitiator {
         // generate outputs
         let tx_outputs = self.generate_tx_outputs(outputs).await;
 
-        // select inputs
-        let spend_amount = tx_outputs.total_native_coins() + fee;
-        let policy = InputSelectionPolicy::Random;
-        let tx_inputs = self
-            .select_spendable_inputs(policy, spend_amount, timestamp)
-            .await
-            .into_iter()
-            .collect::<Vec<_>>();
+        // select inputs, unless the caller chose them
+        let tx_inputs = match aocl_leaf_indices {
+            Some(aocl_leaf_indices) => {
+                self.inputs_by_aocl_leaf_index(aocl_leaf_indices, timestamp)
+                    .await?
+            }
+            None => {
+                let spend_amount = tx_outputs.total_native_coins() + fee;
+                let policy = InputSelectionPolicy::Random;
+                self.select_spendable_inputs(policy, spend_amount, timestamp)
+                    .await
+                    .into()
+            }
+        };
+
+        // obscure the time of initiation, if so configured
+        let timestamp = match fuzz_timestamp {
+            true => self.private().fuzz_timestamp(timestamp, &tx_inputs),
+            false => timestamp,
+        };
 
         // generate tx details (may add change output)
         let tx_details = TransactionDetailsBuilder::new()
             .timestamp(timestamp)
-            .inputs(tx_inputs.into())
+            .inputs(tx_inputs)
             .outputs(tx_outputs)
             .fee(fee)
             .change_policy(change_policy)
+            .custom_announcements(announcements)
             .transparent(transparent)
             .build(&mut self.global_state_lock.clone().into())
             .await?;
@@ -323,12 +591,16 @@ impl TransactionInitiator {
         let consensus_rule_set = ConsensusRuleSet::infer_from(network, block_height);
         // drop(state_lock); // release lock asap.
 
+        self.private().check_size(&tx_details, consensus_rule_set)?;
+
         tracing::info!("send: proving tx:\n{}", tx_details);
 
-        // use cli options for building proof, but override proof-type
+        // use cli options for building proof, but override proof-type, and
+        // let the proof of this own transaction jump the queue.
         let options = TritonVmProofJobOptionsBuilder::new()
             .template(&cli_args.as_proof_job_options())
             .proof_type(target_proof_type)
+            .job_priority(cli_args.own_tx_job_priority(TritonVmJobPriority::default()))
             .build();
 
         // generate proof
@@ -9,6 +9,9 @@
 //! 2. [initiator] provides [TransactionInitiator](initiator) that wraps the builders and broadcasts the tx.
 //! 3. [send] provides [TransactionSender](initiator) with a single, simple `send()` method.
 //!
+//! Additionally, [planner] reports what a large transfer requires before
+//! anything is built.
+//!
 //! The RPC layer wraps and mirrors this API, except for the builders.
 //!
 //! note: The only API that neptune-core truly needs for transaction initiation
@@ -68,6 +71,9 @@
 pub mod builder;
 pub mod error;
 pub mod initiator;
+pub mod planner;
+pub mod raw_transaction;
+pub mod recipient_policy;
 pub mod send;
 
 #[cfg(test)]
@@ -0,0 +1,443 @@
+//! provides a planner that reports what a large transfer requires, before
+//! anything is built.
+//!
+//! A set of payments may not fit in a single transaction, eg because of
+//! consensus limits on the number of inputs and outputs, or because the
+//! wallet's spendable UTXOs only suffice once the change of earlier
+//! transactions is confirmed. [TransferPlanner] splits the payments into
+//! transactions and reports, for each of them, how many inputs and outputs it
+//! has, what it costs in fees, how long it takes to prove, and how many
+//! confirmation rounds must pass before it can be sent.
+//!
+//! Planning does not reserve any UTXOs and does not build, prove, or send any
+//! transaction. The actual transactions selected by
+//! [send()](super::initiator::TransactionInitiator::send()) may differ, as it
+//! selects inputs at random.
+//!
+//! see [tx_initiation](super) for other available API.
+
+use std::time::Duration;
+
+use num_traits::Zero;
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::error::CreateTxError;
+use crate::protocol::consensus::consensus_rule_set::ConsensusRuleSet;
+use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
+
+/// a coarse model of the time it takes to prove a transaction, linear in the
+/// number of inputs and outputs.
+///
+/// The default figures are rough approximations for producing a
+/// `ProofCollection` on a machine meeting the minimum requirements for doing
+/// so. Callers that have measured their own hardware should supply their own
+/// figures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProvingTimeModel {
+    /// fixed time per transaction
+    pub per_transaction: Duration,
+
+    /// additional time per input
+    pub per_input: Duration,
+
+    /// additional time per output
+    pub per_output: Duration,
+}
+
+impl Default for ProvingTimeModel {
+    fn default() -> Self {
+        Self {
+            per_transaction: Duration::from_secs(20),
+            per_input: Duration::from_secs(10),
+            per_output: Duration::from_secs(2),
+        }
+    }
+}
+
+impl ProvingTimeModel {
+    /// estimate the proving time of a transaction with the given number of
+    /// inputs and outputs.
+    pub fn estimate(&self, num_inputs: usize, num_outputs: usize) -> Duration {
+        self.per_transaction
+            + self.per_input * num_inputs as u32
+            + self.per_output * num_outputs as u32
+    }
+}
+
+/// one transaction of a [TransferPlan].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannedTransaction {
+    /// number of confirmation rounds (blocks) that must pass before this
+    /// transaction can be sent. Zero means it can be sent right away.
+    pub round: usize,
+
+    /// number of inputs
+    pub num_inputs: usize,
+
+    /// number of outputs, including change
+    pub num_outputs: usize,
+
+    /// number of payments made by this transaction
+    pub num_payments: usize,
+
+    /// total amount paid, excluding fee and change
+    pub amount: NativeCurrencyAmount,
+
+    /// fee paid by this transaction
+    pub fee: NativeCurrencyAmount,
+
+    /// change returned to the wallet
+    pub change: NativeCurrencyAmount,
+
+    /// estimated time to prove this transaction
+    pub estimated_proving_time: Duration,
+}
+
+/// the result of planning a transfer. see [TransferPlanner].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferPlan {
+    /// transactions needed to make all payments, in the order they are to be
+    /// sent.
+    pub transactions: Vec<PlannedTransaction>,
+
+    /// total amount paid, excluding fees
+    pub total_amount: NativeCurrencyAmount,
+
+    /// total fees paid
+    pub total_fee: NativeCurrencyAmount,
+
+    /// number of confirmation rounds spanned by the transfer. A transfer
+    /// whose transactions can all be sent right away spans one round.
+    pub num_rounds: usize,
+
+    /// estimated time to prove all transactions
+    pub estimated_proving_time: Duration,
+}
+
+impl TransferPlan {
+    /// number of transactions needed
+    pub fn num_transactions(&self) -> usize {
+        self.transactions.len()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Coin {
+    amount: NativeCurrencyAmount,
+    round: usize,
+}
+
+/// plans a transfer of a set of payments. see the [module docs](self).
+///
+/// Payments are assigned to transactions in the given order, as many per
+/// transaction as the output limit allows while leaving room for change. Each
+/// transaction spends the largest UTXOs that are available earliest, and its
+/// change becomes available one round after it is sent.
+#[derive(Debug, Clone)]
+pub struct TransferPlanner {
+    spendable: Vec<NativeCurrencyAmount>,
+    max_inputs_per_transaction: usize,
+    max_outputs_per_transaction: usize,
+    max_transactions_per_round: Option<usize>,
+    num_transactions_already_sent: usize,
+    proving_time_model: ProvingTimeModel,
+}
+
+impl TransferPlanner {
+    /// instantiate a planner for spending the given UTXO amounts under the
+    /// limits of the given consensus rules.
+    pub fn new(
+        spendable: impl IntoIterator<Item = NativeCurrencyAmount>,
+        consensus_rule_set: ConsensusRuleSet,
+    ) -> Self {
+        Self {
+            spendable: spendable.into_iter().collect(),
+            max_inputs_per_transaction: consensus_rule_set.max_num_inputs(),
+            max_outputs_per_transaction: consensus_rule_set
+                .max_num_outputs()
+                .min(consensus_rule_set.max_num_announcements()),
+            max_transactions_per_round: None,
+            num_transactions_already_sent: 0,
+            proving_time_model: ProvingTimeModel::default(),
+        }
+    }
+
+    /// lower the number of inputs per transaction, eg to keep proving
+    /// within the capability of the machine.
+    pub fn max_inputs_per_transaction(mut self, max: usize) -> Self {
+        self.max_inputs_per_transaction = self.max_inputs_per_transaction.min(max.max(1));
+        self
+    }
+
+    /// lower the number of outputs per transaction, including change.
+    pub fn max_outputs_per_transaction(mut self, max: usize) -> Self {
+        self.max_outputs_per_transaction = self.max_outputs_per_transaction.min(max.max(2));
+        self
+    }
+
+    /// limit the number of transactions sent per round, of which
+    /// `already_sent` have been sent in the current round.
+    pub fn rate_limit(mut self, max_per_round: usize, already_sent: usize) -> Self {
+        self.max_transactions_per_round = Some(max_per_round.max(1));
+        self.num_transactions_already_sent = already_sent;
+        self
+    }
+
+    /// set the model used to estimate proving times.
+    pub fn proving_time_model(mut self, model: ProvingTimeModel) -> Self {
+        self.proving_time_model = model;
+        self
+    }
+
+    /// plan the payment of `payments`, paying `fee` for each transaction.
+    pub fn plan(
+        &self,
+        payments: &[NativeCurrencyAmount],
+        fee: NativeCurrencyAmount,
+    ) -> Result<TransferPlan, CreateTxError> {
+        if fee.is_negative() {
+            return Err(CreateTxError::NegativeFee);
+        }
+
+        // leave room for a change output in each transaction.
+        let payments_per_transaction = self.max_outputs_per_transaction - 1;
+        let num_transactions = payments.len().div_ceil(payments_per_transaction);
+        let total_amount: NativeCurrencyAmount = payments.iter().copied().sum();
+        let total_fee = fee.scalar_mul(num_transactions as u32);
+        let requested = total_amount + total_fee;
+        let available: NativeCurrencyAmount = self.spendable.iter().copied().sum();
+        if requested > available {
+            return Err(CreateTxError::InsufficientFunds {
+                requested,
+                available,
+            });
+        }
+
+        let mut pool = self
+            .spendable
+            .iter()
+            .map(|&amount| Coin { amount, round: 0 })
+            .collect::<Vec<_>>();
+        let mut num_transactions_per_round = vec![self.num_transactions_already_sent];
+        let mut transactions = Vec::with_capacity(num_transactions);
+        for chunk in payments.chunks(payments_per_transaction) {
+            let amount: NativeCurrencyAmount = chunk.iter().copied().sum();
+            let required = amount + fee;
+
+            // prefer coins that are available earliest, then the largest.
+            pool.sort_by(|a, b| a.round.cmp(&b.round).then(b.amount.cmp(&a.amount)));
+            let mut num_inputs = 0;
+            let mut total_input = NativeCurrencyAmount::zero();
+            let mut round = 0;
+            while total_input < required {
+                let Some(coin) = pool.get(num_inputs) else {
+                    return Err(CreateTxError::InsufficientFunds {
+                        requested,
+                        available,
+                    });
+                };
+                if num_inputs == self.max_inputs_per_transaction {
+                    return Err(CreateTxError::TooManyInputs {
+                        max: self.max_inputs_per_transaction,
+                    });
+                }
+
+                total_input += coin.amount;
+                round = round.max(coin.round);
+                num_inputs += 1;
+            }
+            pool.drain(..num_inputs);
+
+            if let Some(max_per_round) = self.max_transactions_per_round {
+                while num_transactions_per_round
+                    .get(round)
+                    .is_some_and(|&n| n >= max_per_round)
+                {
+                    round += 1;
+                }
+            }
+            if num_transactions_per_round.len() <= round {
+                num_transactions_per_round.resize(round + 1, 0);
+            }
+            num_transactions_per_round[round] += 1;
+
+            let change = total_input - required;
+            let num_outputs = if change.is_zero() {
+                chunk.len()
+            } else {
+                pool.push(Coin {
+                    amount: change,
+                    round: round + 1,
+                });
+                chunk.len() + 1
+            };
+
+            transactions.push(PlannedTransaction {
+                round,
+                num_inputs,
+                num_outputs,
+                num_payments: chunk.len(),
+                amount,
+                fee,
+                change,
+                estimated_proving_time: self.proving_time_model.estimate(num_inputs, num_outputs),
+            });
+        }
+
+        Ok(TransferPlan {
+            num_rounds: transactions
+                .iter()
+                .map(|tx| tx.round + 1)
+                .max()
+                .unwrap_or(0),
+            estimated_proving_time: transactions
+                .iter()
+                .map(|tx| tx.estimated_proving_time)
+                .sum(),
+            transactions,
+            total_amount,
+            total_fee,
+        })
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    fn coins(amounts: &[u32]) -> Vec<NativeCurrencyAmount> {
+        amounts
+            .iter()
+            .map(|&a| NativeCurrencyAmount::coins(a))
+            .collect()
+    }
+
+    fn planner(spendable: &[u32]) -> TransferPlanner {
+        TransferPlanner::new(coins(spendable), ConsensusRuleSet::HardforkAlpha)
+    }
+
+    #[test]
+    fn single_transaction_suffices_for_few_payments() {
+        let plan = planner(&[10, 50])
+            .plan(&coins(&[3, 4]), NativeCurrencyAmount::coins(1))
+            .unwrap();
+
+        assert_eq!(1, plan.num_transactions());
+        assert_eq!(1, plan.num_rounds);
+        assert_eq!(NativeCurrencyAmount::coins(7), plan.total_amount);
+        assert_eq!(NativeCurrencyAmount::coins(1), plan.total_fee);
+
+        let tx = plan.transactions[0];
+        assert_eq!(0, tx.round);
+        assert_eq!(1, tx.num_inputs, "largest UTXO must be spent first");
+        assert_eq!(3, tx.num_outputs, "two payments and change");
+        assert_eq!(NativeCurrencyAmount::coins(42), tx.change);
+    }
+
+    #[test]
+    fn output_limit_splits_payments_across_transactions() {
+        let plan = planner(&[100, 100, 100])
+            .max_outputs_per_transaction(3)
+            .plan(&coins(&[1, 1, 1, 1, 1]), NativeCurrencyAmount::zero())
+            .unwrap();
+
+        assert_eq!(3, plan.num_transactions());
+        assert_eq!(
+            vec![2, 2, 1],
+            plan.transactions
+                .iter()
+                .map(|tx| tx.num_payments)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(1, plan.num_rounds, "enough UTXOs to send all at once");
+    }
+
+    #[test]
+    fn spending_change_requires_another_round() {
+        let plan = planner(&[100])
+            .max_outputs_per_transaction(2)
+            .plan(&coins(&[10, 10, 10]), NativeCurrencyAmount::coins(1))
+            .unwrap();
+
+        assert_eq!(
+            vec![0, 1, 2],
+            plan.transactions
+                .iter()
+                .map(|tx| tx.round)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(3, plan.num_rounds);
+        assert_eq!(NativeCurrencyAmount::coins(3), plan.total_fee);
+    }
+
+    #[test]
+    fn rate_limit_postpones_transactions() {
+        let plan = planner(&[10, 10, 10])
+            .max_outputs_per_transaction(2)
+            .rate_limit(2, 1)
+            .plan(&coins(&[5, 5, 5]), NativeCurrencyAmount::zero())
+            .unwrap();
+
+        assert_eq!(
+            vec![0, 1, 1],
+            plan.transactions
+                .iter()
+                .map(|tx| tx.round)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn proving_time_is_summed_over_transactions() {
+        let model = ProvingTimeModel {
+            per_transaction: Duration::from_secs(100),
+            per_input: Duration::from_secs(10),
+            per_output: Duration::from_secs(1),
+        };
+        let plan = planner(&[5, 5, 5])
+            .max_outputs_per_transaction(2)
+            .proving_time_model(model)
+            .plan(&coins(&[5, 5]), NativeCurrencyAmount::zero())
+            .unwrap();
+
+        // two transactions, each with one input and one output
+        assert_eq!(Duration::from_secs(2 * 111), plan.estimated_proving_time);
+    }
+
+    #[test]
+    fn insufficient_funds_account_for_fees_of_all_transactions() {
+        let result = planner(&[10])
+            .max_outputs_per_transaction(2)
+            .plan(&coins(&[4, 4]), NativeCurrencyAmount::coins(2));
+
+        assert!(matches!(
+            result,
+            Err(CreateTxError::InsufficientFunds { requested, .. })
+                if requested == NativeCurrencyAmount::coins(12)
+        ));
+    }
+
+    #[test]
+    fn input_limit_is_respected() {
+        let result = planner(&[1, 1, 1, 1])
+            .max_inputs_per_transaction(2)
+            .plan(&coins(&[3]), NativeCurrencyAmount::zero());
+
+        assert!(matches!(
+            result,
+            Err(CreateTxError::TooManyInputs { max: 2 })
+        ));
+    }
+
+    #[test]
+    fn empty_payment_set_needs_no_transactions() {
+        let plan = planner(&[])
+            .plan(&[], NativeCurrencyAmount::coins(1))
+            .unwrap();
+        assert_eq!(0, plan.num_transactions());
+        assert_eq!(0, plan.num_rounds);
+        assert!(plan.total_fee.is_zero());
+    }
+}
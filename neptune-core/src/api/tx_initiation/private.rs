@@ -1,13 +1,28 @@
 // private module. module docs not needed.
 use std::sync::Arc;
 
+use tokio::io::AsyncWriteExt;
+
 use super::error;
+use crate::api::export::Timestamp;
+use crate::api::tx_initiation::builder::tx_output_list_builder::OutputFormat;
+use crate::protocol::consensus::consensus_rule_set::ConsensusRuleSet;
 use crate::protocol::consensus::transaction::transaction_proof::TransactionProofType;
 use crate::protocol::consensus::transaction::Transaction;
 use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
+use crate::state::transaction::transaction_details::TransactionDetails;
+use crate::state::wallet::transaction_input::TxInputList;
 use crate::GlobalStateLock;
 use crate::RPCServerToMain;
 
+// send rate limiting only applies below height 25000
+// which is approx 5.6 months after launch.
+// after that, the training wheel come off.
+pub(super) const RATE_LIMIT_UNTIL_HEIGHT: u64 = 25000;
+
+// max number of tx that may be sent per block while rate limiting applies.
+pub(super) const RATE_LIMIT: usize = 2;
+
 pub(super) struct TransactionInitiatorPrivate {
     global_state_lock: GlobalStateLock,
 }
@@ -38,6 +53,20 @@ impl TransactionInitiatorPrivate {
         Ok(())
     }
 
+    // moves the timestamp of a tx initiated at `timestamp` into the past as
+    // per --tx-timestamp-fuzz and --tx-timestamp-granularity, but not before
+    // the release date of any time-locked input.
+    pub(super) fn fuzz_timestamp(&self, timestamp: Timestamp, inputs: &TxInputList) -> Timestamp {
+        let not_before = inputs
+            .iter()
+            .filter_map(|input| input.utxo.release_date())
+            .max();
+        self.global_state_lock
+            .cli()
+            .tx_timestamp_fuzzing_policy()
+            .fuzz(timestamp, not_before, &mut rand::rng())
+    }
+
     pub(super) async fn check_proceed_with_send(
         &self,
         fee: NativeCurrencyAmount,
@@ -49,12 +78,20 @@ impl TransactionInitiatorPrivate {
             return Err(error::SendError::Unsupported);
         }
 
+        self.check_spend_enabled().await?;
+
         // abort early on negative fee
         if fee.is_negative() {
             tracing::warn!("Cannot send negative-fee transaction.");
             return Err(error::CreateTxError::NegativeFee.into());
         }
 
+        let floor = self.global_state_lock.cli().min_send_fee;
+        if fee < floor {
+            tracing::warn!("Cannot send transaction with fee {fee} below minimum of {floor}.");
+            return Err(error::CreateTxError::BelowFeeFloor { fee, floor }.into());
+        }
+
         let capability = self.global_state_lock.cli().proving_capability();
         let proof_type = TransactionProofType::ProofCollection;
         let network = self.global_state_lock.cli().network;
@@ -72,16 +109,128 @@ impl TransactionInitiatorPrivate {
         self.check_rate_limit().await
     }
 
+    // a transaction exceeding any consensus limit can never be confirmed, so
+    // check before spending resources on proving it.
+    pub(super) fn check_size(
+        &self,
+        details: &TransactionDetails,
+        consensus_rule_set: ConsensusRuleSet,
+    ) -> Result<(), error::CreateTxError> {
+        let limits = [
+            (
+                error::TxComponent::Inputs,
+                details.tx_inputs.len(),
+                consensus_rule_set.max_num_inputs(),
+            ),
+            (
+                error::TxComponent::Outputs,
+                details.tx_outputs.len(),
+                consensus_rule_set.max_num_outputs(),
+            ),
+            (
+                error::TxComponent::Announcements,
+                details.announcements().len(),
+                consensus_rule_set.max_num_announcements(),
+            ),
+        ];
+
+        match limits.into_iter().find(|(_, count, max)| count > max) {
+            Some((component, count, max)) => Err(error::CreateTxError::ExceedsSize {
+                component,
+                count,
+                max,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    pub(super) async fn check_recipients(
+        &self,
+        outputs: &[OutputFormat],
+        timestamp: Timestamp,
+    ) -> Result<(), error::SendError> {
+        let Some(policy) = self
+            .global_state_lock
+            .lock_guard()
+            .await
+            .recipient_policy
+            .clone()
+        else {
+            return Ok(());
+        };
+
+        let network = self.global_state_lock.cli().network;
+        for output in outputs {
+            let amount = output.native_currency_amount();
+            if let Err(reason) = policy.check(output.address(), amount, network) {
+                let denial = error::RecipientDeniedError {
+                    address: output
+                        .address()
+                        .to_bech32m(network)
+                        .unwrap_or_else(|_| output.address().privacy_digest().to_hex()),
+                    amount,
+                    reason,
+                    timestamp,
+                };
+                tracing::warn!("{}", denial);
+                self.append_to_audit_file(&denial).await;
+                return Err(denial.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    // failure to write the audit record is logged but does not change the
+    // outcome: the send is denied either way.
+    async fn append_to_audit_file(&self, denial: &error::RecipientDeniedError) {
+        let path = self
+            .global_state_lock
+            .lock_guard()
+            .await
+            .wallet_state
+            .configuration
+            .data_directory()
+            .send_policy_audit_file_path();
+
+        let result = async {
+            let mut line = serde_json::to_string(denial)?;
+            line.push('\n');
+            tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .await?
+                .write_all(line.as_bytes())
+                .await?;
+            anyhow::Ok(())
+        }
+        .await;
+
+        if let Err(e) = result {
+            tracing::error!(
+                "Could not write send-policy audit record to {}: {e}",
+                path.display()
+            );
+        }
+    }
+
+    // a standby shares its wallet with its primary, so it must not spend
+    // until promoted.
+    pub(super) async fn check_spend_enabled(&self) -> Result<(), error::SendError> {
+        let state = self.global_state_lock.lock_guard().await;
+        if !state.replication.is_spend_enabled() {
+            tracing::warn!("Cannot initiate transaction because this node is a standby.");
+            return Err(error::SendError::Standby);
+        }
+        Ok(())
+    }
+
     // check if send would exceed the send rate-limit (per block)
     pub(super) async fn check_rate_limit(&self) -> Result<(), error::SendError> {
-        // send rate limiting only applies below height 25000
-        // which is approx 5.6 months after launch.
-        // after that, the training wheel come off.
-        const RATE_LIMIT_UNTIL_HEIGHT: u64 = 25000;
         let state = self.global_state_lock.lock_guard().await;
 
         if state.chain.light_state().header().height < RATE_LIMIT_UNTIL_HEIGHT.into() {
-            const RATE_LIMIT: usize = 2;
             let tip_digest = state.chain.light_state().hash();
             let send_count_at_tip = state
                 .wallet_state
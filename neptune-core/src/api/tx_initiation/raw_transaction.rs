@@ -0,0 +1,258 @@
+//! provides a complete, JSON-serializable description of a transaction, for
+//! power users and scripts.
+//!
+//! Unlike [send()](super::initiator::TransactionInitiator::send()), which
+//! selects inputs and sets the timestamp automatically, a
+//! [RawTransactionSpec] states every part of the transaction explicitly:
+//!
+//! - the inputs, as AOCL leaf indices of the wallet's UTXOs,
+//! - the outputs, as bech32m addresses and amounts,
+//! - the fee,
+//! - the timestamp, in milliseconds since the unix epoch, and
+//! - the payloads of any announcements, as lists of field elements.
+//!
+//! Amounts are decimal numbers of coins, or of another
+//! [Denomination](crate::api::amount::Denomination) if followed by its unit,
+//! eg `"2500 mNPT"`. Amounts that are not a whole number of nau are rejected
+//! rather than rounded.
+//!
+//! The spec is validated before anything is built. Once valid, the
+//! transaction is built, proven, and broadcast like any other; see
+//! [send_raw()](super::initiator::TransactionInitiator::send_raw()).
+//!
+//! Example spec:
+//!
+//! ```json
+//! {
+//!     "inputs": [12, 14],
+//!     "outputs": [{ "address": "nolgam1...", "amount": "2.5" }],
+//!     "fee": "0.01",
+//!     "timestamp": 1754049600000,
+//!     "announcements": [[1, 2, 3]]
+//! }
+//! ```
+//!
+//! see [tx_initiation](super) for other available API.
+
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::triton_vm::prelude::BFieldElement;
+
+use crate::api::amount::DenominatedAmount;
+use crate::api::export::Announcement;
+use crate::api::export::Network;
+use crate::api::export::ReceivingAddress;
+use crate::api::export::Timestamp;
+use crate::api::tx_initiation::builder::tx_output_list_builder::OutputFormat;
+use crate::protocol::consensus::block::FUTUREDATING_LIMIT;
+use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
+use crate::state::wallet::change_policy::ChangePolicy;
+
+/// enumerates the ways in which a [RawTransactionSpec] can be invalid.
+#[derive(Debug, Clone, thiserror::Error)]
+#[non_exhaustive]
+pub enum RawTransactionSpecError {
+    #[error("invalid transaction spec: {0}")]
+    Json(String),
+
+    #[error("transaction spec has no inputs")]
+    NoInputs,
+
+    #[error("transaction spec has neither outputs nor announcements")]
+    NoOutputsOrAnnouncements,
+
+    #[error("output {index} has invalid address: {reason}")]
+    InvalidAddress { index: usize, reason: String },
+
+    #[error("output {index} is an address for another network than {network}")]
+    AddressWrongNetwork { index: usize, network: Network },
+
+    #[error("invalid amount {amount:?}: {reason}")]
+    InvalidAmount { amount: String, reason: String },
+
+    #[error("amounts must not be negative, got {0}")]
+    NegativeAmount(NativeCurrencyAmount),
+
+    #[error("transaction spec timestamp {timestamp} is later than {limit}")]
+    TimestampInFuture {
+        timestamp: Timestamp,
+        limit: Timestamp,
+    },
+
+    #[error("announcement {index} contains {value}, which is not a field element")]
+    InvalidAnnouncement { index: usize, value: u64 },
+}
+
+/// an output of a [RawTransactionSpec].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RawOutput {
+    /// bech32m encoding of the receiving address
+    pub address: String,
+
+    /// amount, eg "2.5" or "2500 mNPT"
+    pub amount: String,
+}
+
+/// a complete description of a transaction.
+///
+/// see the [module docs](self) for details.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawTransactionSpec {
+    /// AOCL leaf indices of the wallet's UTXOs to spend
+    pub inputs: Vec<u64>,
+
+    /// the outputs, not including change
+    #[serde(default)]
+    pub outputs: Vec<RawOutput>,
+
+    /// the fee, eg "0.01" or "10 mNPT"
+    pub fee: String,
+
+    /// milliseconds since the unix epoch. `None` means the time at which the
+    /// transaction is built.
+    #[serde(default)]
+    pub timestamp: Option<u64>,
+
+    /// the payloads of announcements, eg for application-specific data
+    #[serde(default)]
+    pub announcements: Vec<Vec<u64>>,
+
+    /// what to do with the difference between the inputs and the outputs plus
+    /// fee
+    #[serde(default)]
+    pub change_policy: ChangePolicy,
+}
+
+/// a [RawTransactionSpec] that passed validation, in terms of neptune-core
+/// types.
+#[derive(Debug, Clone)]
+pub struct ValidRawTransaction {
+    /// AOCL leaf indices of the wallet's UTXOs to spend
+    pub inputs: Vec<u64>,
+
+    /// the outputs, not including change
+    pub outputs: Vec<OutputFormat>,
+
+    /// the fee
+    pub fee: NativeCurrencyAmount,
+
+    /// the timestamp
+    pub timestamp: Timestamp,
+
+    /// the announcements
+    pub announcements: Vec<Announcement>,
+
+    /// what to do with the difference between the inputs and the outputs plus
+    /// fee
+    pub change_policy: ChangePolicy,
+}
+
+impl RawTransactionSpec {
+    /// parse a spec from its JSON representation.
+    ///
+    /// The parsed spec is not yet validated.
+    pub fn from_json(json: &str) -> Result<Self, RawTransactionSpecError> {
+        serde_json::from_str(json).map_err(|e| RawTransactionSpecError::Json(e.to_string()))
+    }
+
+    /// check the spec for errors that do not depend on the wallet, and
+    /// convert it into neptune-core types.
+    ///
+    /// Whether the inputs are spendable and suffice to cover the outputs and
+    /// fee is checked when the transaction is built.
+    pub fn validate(
+        &self,
+        network: Network,
+        now: Timestamp,
+    ) -> Result<ValidRawTransaction, RawTransactionSpecError> {
+        if self.inputs.is_empty() {
+            return Err(RawTransactionSpecError::NoInputs);
+        }
+        if self.outputs.is_empty() && self.announcements.is_empty() {
+            return Err(RawTransactionSpecError::NoOutputsOrAnnouncements);
+        }
+
+        let outputs = self
+            .outputs
+            .iter()
+            .enumerate()
+            .map(|(index, output)| output.to_output_format(index, network))
+            .collect::<Result<Vec<_>, _>>()?;
+        let fee = parse_amount(&self.fee)?;
+        let timestamp = self.timestamp.map_or(now, Timestamp::millis);
+        let limit = now + FUTUREDATING_LIMIT;
+        if timestamp > limit {
+            return Err(RawTransactionSpecError::TimestampInFuture { timestamp, limit });
+        }
+        let announcements = self
+            .announcements
+            .iter()
+            .enumerate()
+            .map(|(index, message)| parse_announcement(index, message))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ValidRawTransaction {
+            inputs: self.inputs.clone(),
+            outputs,
+            fee,
+            timestamp,
+            announcements,
+            change_policy: self.change_policy.clone(),
+        })
+    }
+}
+
+impl RawOutput {
+    fn to_output_format(
+        &self,
+        index: usize,
+        network: Network,
+    ) -> Result<OutputFormat, RawTransactionSpecError> {
+        let address = ReceivingAddress::from_bech32m(&self.address, network).map_err(|e| {
+            if ReceivingAddress::is_bech32m_for_other_network(&self.address, network) {
+                RawTransactionSpecError::AddressWrongNetwork { index, network }
+            } else {
+                RawTransactionSpecError::InvalidAddress {
+                    index,
+                    reason: e.to_string(),
+                }
+            }
+        })?;
+        let amount = parse_amount(&self.amount)?;
+
+        Ok((address, amount).into())
+    }
+}
+
+fn parse_amount(amount: &str) -> Result<NativeCurrencyAmount, RawTransactionSpecError> {
+    let parsed = amount
+        .parse::<DenominatedAmount>()
+        .map_err(|e| RawTransactionSpecError::InvalidAmount {
+            amount: amount.to_owned(),
+            reason: e.to_string(),
+        })?
+        .amount();
+    if parsed.is_negative() {
+        return Err(RawTransactionSpecError::NegativeAmount(parsed));
+    }
+
+    Ok(parsed)
+}
+
+fn parse_announcement(
+    index: usize,
+    message: &[u64],
+) -> Result<Announcement, RawTransactionSpecError> {
+    let message = message
+        .iter()
+        .map(|&value| {
+            if value < BFieldElement::P {
+                Ok(BFieldElement::new(value))
+            } else {
+                Err(RawTransactionSpecError::InvalidAnnouncement { index, value })
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Announcement::new(message))
+}
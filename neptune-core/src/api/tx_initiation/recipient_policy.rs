@@ -0,0 +1,151 @@
+//! provides a pre-send policy hook for transaction recipients.
+//!
+//! Operators with regulatory obligations may need to refuse sending funds to
+//! certain addresses. A [RecipientPolicy] is consulted for every output of a
+//! transaction initiated via
+//! [TransactionSender::send()](super::send::TransactionSender::send()) (and
+//! thus the RPC and CLI send commands). If the policy denies any recipient,
+//! the send is aborted with
+//! [SendError::RecipientDenied](super::error::SendError::RecipientDenied) and
+//! the denial is appended to the audit file at
+//! [DataDirectory::send_policy_audit_file_path()](crate::application::config::data_directory::DataDirectory::send_policy_audit_file_path()).
+//!
+//! No policy is active by default. Two ways exist to activate one:
+//!
+//! 1. start neptune-core with `--denied-addresses-file <PATH>`, which
+//!    installs an [AddressDenylist] read from that file.
+//! 2. install a custom policy via
+//!    [TransactionInitiator::set_recipient_policy()](super::initiator::TransactionInitiator::set_recipient_policy()).
+//!
+//! note: the policy applies to the high-level send API only. Callers who
+//! assemble transactions with the [builder](super::builder) API are
+//! responsible for applying their own checks, eg via
+//! [TransactionInitiator::check_recipients()](super::initiator::TransactionInitiator::check_recipients()).
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::api::export::Digest;
+use crate::api::export::NativeCurrencyAmount;
+use crate::api::export::Network;
+use crate::api::export::ReceivingAddress;
+
+/// a policy deciding whether funds may be sent to a recipient.
+///
+/// Implementations must be cheap to call, as the policy is consulted
+/// while a send is in progress.
+///
+/// ```
+/// use neptune_cash::api::export::NativeCurrencyAmount;
+/// use neptune_cash::api::export::Network;
+/// use neptune_cash::api::export::ReceivingAddress;
+/// use neptune_cash::api::tx_initiation::recipient_policy::RecipientPolicy;
+///
+/// /// refuses any single output above a fixed amount.
+/// #[derive(Debug)]
+/// struct MaxOutputAmount(NativeCurrencyAmount);
+///
+/// impl RecipientPolicy for MaxOutputAmount {
+///     fn check(
+///         &self,
+///         _recipient: &ReceivingAddress,
+///         amount: NativeCurrencyAmount,
+///         _network: Network,
+///     ) -> Result<(), String> {
+///         match amount > self.0 {
+///             true => Err(format!("amount exceeds {}", self.0)),
+///             false => Ok(()),
+///         }
+///     }
+/// }
+/// ```
+pub trait RecipientPolicy: std::fmt::Debug + Send + Sync {
+    /// returns `Err(reason)` if `amount` may not be sent to `recipient`.
+    fn check(
+        &self,
+        recipient: &ReceivingAddress,
+        amount: NativeCurrencyAmount,
+        network: Network,
+    ) -> Result<(), String>;
+}
+
+/// a [RecipientPolicy] that denies a fixed set of addresses.
+///
+/// Addresses are identified by their
+/// [privacy_digest()](ReceivingAddress::privacy_digest()), so an address
+/// is denied regardless of how it is encoded.
+#[derive(Debug, Clone, Default)]
+pub struct AddressDenylist {
+    denied: HashSet<Digest>,
+}
+
+impl AddressDenylist {
+    /// create a denylist from a set of addresses.
+    pub fn new<'a>(addresses: impl IntoIterator<Item = &'a ReceivingAddress>) -> Self {
+        Self {
+            denied: addresses.into_iter().map(|a| a.privacy_digest()).collect(),
+        }
+    }
+
+    /// read a denylist from a file.
+    ///
+    /// The file contains one bech32m-encoded address per line. Blank lines
+    /// and lines starting with `#` are ignored.
+    ///
+    /// Returns an error if the file cannot be read or if any line is not a
+    /// valid address for `network`, such that a typo cannot silently disable
+    /// the denylist.
+    pub fn read_from_file(path: &Path, network: Network) -> Result<Self, DenylistError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| DenylistError::Read(path.into(), e.to_string()))?;
+
+        let addresses = contents
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(line_number, line)| {
+                ReceivingAddress::from_bech32m(line, network)
+                    .map_err(|_| DenylistError::InvalidAddress(path.into(), line_number))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self::new(&addresses))
+    }
+
+    /// the number of denied addresses
+    pub fn len(&self) -> usize {
+        self.denied.len()
+    }
+
+    /// true if no address is denied
+    pub fn is_empty(&self) -> bool {
+        self.denied.is_empty()
+    }
+}
+
+impl RecipientPolicy for AddressDenylist {
+    fn check(
+        &self,
+        recipient: &ReceivingAddress,
+        _amount: NativeCurrencyAmount,
+        _network: Network,
+    ) -> Result<(), String> {
+        match self.denied.contains(&recipient.privacy_digest()) {
+            true => Err("address is on the denylist".to_string()),
+            false => Ok(()),
+        }
+    }
+}
+
+/// enumerates possible errors when reading an [AddressDenylist]
+#[derive(Debug, Clone, thiserror::Error)]
+#[non_exhaustive]
+pub enum DenylistError {
+    #[error("could not read denylist file {}: {1}", .0.display())]
+    Read(PathBuf, String),
+
+    #[error("invalid address in denylist file {} on line {1}", .0.display())]
+    InvalidAddress(PathBuf, usize),
+}
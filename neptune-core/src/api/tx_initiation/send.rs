@@ -34,6 +34,7 @@
 use super::error;
 use crate::api::tx_initiation::builder::tx_output_list_builder::OutputFormat;
 use crate::api::tx_initiation::initiator::TransactionInitiator;
+use crate::api::tx_initiation::raw_transaction::RawTransactionSpec;
 use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
 use crate::protocol::proof_abstractions::timestamp::Timestamp;
 use crate::state::transaction::tx_creation_artifacts::TxCreationArtifacts;
@@ -68,4 +69,38 @@ impl TransactionSender {
         .send(outputs, change_policy, fee, timestamp)
         .await
     }
+
+    /// Like [Self::send], but spends exactly the wallet's UTXOs with the given
+    /// AOCL leaf indices instead of automatically selected ones.
+    ///
+    /// See [TransactionInitiator::send_with_inputs] for details.
+    pub async fn send_with_inputs(
+        &mut self,
+        outputs: impl IntoIterator<Item = impl Into<OutputFormat>>,
+        aocl_leaf_indices: &[u64],
+        change_policy: ChangePolicy,
+        fee: NativeCurrencyAmount,
+        timestamp: Timestamp,
+    ) -> Result<TxCreationArtifacts, error::SendError> {
+        TransactionInitiator {
+            global_state_lock: self.global_state_lock.clone(),
+        }
+        .send_with_inputs(outputs, aocl_leaf_indices, change_policy, fee, timestamp)
+        .await
+    }
+
+    /// Like [Self::send], but for a transaction that is described completely
+    /// by `spec`.
+    ///
+    /// See [TransactionInitiator::send_raw] for details.
+    pub async fn send_raw(
+        &mut self,
+        spec: &RawTransactionSpec,
+    ) -> Result<TxCreationArtifacts, error::SendError> {
+        TransactionInitiator {
+            global_state_lock: self.global_state_lock.clone(),
+        }
+        .send_raw(spec)
+        .await
+    }
 }
@@ -0,0 +1,48 @@
+// private module.  no need for module docs.
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::api::wallet::WalletBalances;
+
+/// the wallet balances, stable across releases of neptune-core.
+///
+/// see [WalletBalances] for the meaning of each balance, and the
+/// [module docs](super) for the stability guarantees.
+///
+/// ```no_run
+/// use neptune_cash::api::export::GlobalStateLock;
+/// use neptune_cash::api::export::Timestamp;
+/// use neptune_cash::api::types::BalanceReport;
+///
+/// async fn print_balances(gsl: &GlobalStateLock) {
+///     let balances = gsl.api().wallet().balances(Timestamp::now()).await;
+///     let report = BalanceReport::from(balances);
+///     println!("available: {}", report.confirmed_available);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceReport {
+    /// balance of confirmed, available utxos
+    pub confirmed_available: String,
+
+    /// balance of all confirmed utxos.  (available and time-locked)
+    pub confirmed_total: String,
+
+    /// balance of unconfirmed, available utxos
+    pub unconfirmed_available: String,
+
+    /// balance of all unconfirmed utxos. (available and time-locked)
+    pub unconfirmed_total: String,
+}
+
+impl From<WalletBalances> for BalanceReport {
+    fn from(balances: WalletBalances) -> Self {
+        Self {
+            confirmed_available: balances.confirmed_available.to_nau().to_string(),
+            confirmed_total: balances.confirmed_total.to_nau().to_string(),
+            unconfirmed_available: balances.unconfirmed_available.to_nau().to_string(),
+            unconfirmed_total: balances.unconfirmed_total.to_nau().to_string(),
+        }
+    }
+}
@@ -0,0 +1,68 @@
+// private module.  no need for module docs.
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::consensus::block::Block;
+
+/// a summary of a block, stable across releases of neptune-core.
+///
+/// see the [module docs](super) for the stability guarantees.
+///
+/// ```
+/// use neptune_cash::api::export::Block;
+/// use neptune_cash::api::export::Network;
+/// use neptune_cash::api::types::BlockSummary;
+///
+/// let genesis = Block::genesis(Network::Main);
+/// let summary = BlockSummary::from(&genesis);
+/// assert_eq!(genesis.hash().to_hex(), summary.digest);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockSummary {
+    /// the block's digest, as hex
+    pub digest: String,
+
+    /// the block's height
+    pub height: u64,
+
+    /// the digest of the block's predecessor, as hex
+    pub prev_block_digest: String,
+
+    /// the block's timestamp, in milliseconds since the unix epoch
+    pub timestamp: u64,
+
+    /// the number of inputs of the block's transaction
+    pub num_inputs: usize,
+
+    /// the number of outputs of the block's transaction
+    pub num_outputs: usize,
+
+    /// the number of announcements of the block's transaction
+    pub num_announcements: usize,
+
+    /// the fee of the block's transaction, which goes to the guesser
+    pub fee: String,
+
+    /// the coinbase of the block's transaction
+    pub coinbase: String,
+}
+
+impl From<&Block> for BlockSummary {
+    fn from(block: &Block) -> Self {
+        let header = block.header();
+        let kernel = &block.body().transaction_kernel;
+
+        Self {
+            digest: block.hash().to_hex(),
+            height: header.height.into(),
+            prev_block_digest: header.prev_block_digest.to_hex(),
+            timestamp: header.timestamp.to_millis(),
+            num_inputs: kernel.inputs.len(),
+            num_outputs: kernel.outputs.len(),
+            num_announcements: kernel.announcements.len(),
+            fee: kernel.fee.to_nau().to_string(),
+            coinbase: kernel.coinbase.unwrap_or_default().to_nau().to_string(),
+        }
+    }
+}
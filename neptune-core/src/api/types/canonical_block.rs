@@ -0,0 +1,131 @@
+// private module.  no need for module docs.
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::CanonicalTransactionKernel;
+use crate::protocol::consensus::block::block_header::BlockHeader;
+use crate::protocol::consensus::block::Block;
+
+/// a block's header and transaction kernel, in the stable JSON encoding for
+/// block explorers.
+///
+/// The proofs and the mutator set accumulator after the block are omitted.
+///
+/// see the [module docs](super) for the stability guarantees.
+///
+/// ```
+/// use neptune_cash::api::export::Block;
+/// use neptune_cash::api::export::Network;
+/// use neptune_cash::api::types::CanonicalBlock;
+///
+/// let genesis = Block::genesis(Network::Main);
+/// let canonical = CanonicalBlock::from(&genesis);
+/// assert_eq!(genesis.hash().to_hex(), canonical.digest);
+/// assert_eq!(0, canonical.header.height);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CanonicalBlock {
+    /// the block's digest, as hex
+    pub digest: String,
+
+    /// the block's header
+    pub header: CanonicalBlockHeader,
+
+    /// the kernel of the block's transaction
+    pub transaction_kernel: CanonicalTransactionKernel,
+}
+
+impl From<&Block> for CanonicalBlock {
+    fn from(block: &Block) -> Self {
+        Self {
+            digest: block.hash().to_hex(),
+            header: block.header().into(),
+            transaction_kernel: (&block.body().transaction_kernel).into(),
+        }
+    }
+}
+
+/// a block header, in the stable JSON encoding for block explorers.
+///
+/// see the [module docs](super) for the stability guarantees.
+///
+/// ```
+/// use neptune_cash::api::export::Block;
+/// use neptune_cash::api::export::Network;
+/// use neptune_cash::api::types::CanonicalBlockHeader;
+///
+/// let genesis = Block::genesis(Network::Main);
+/// let header = CanonicalBlockHeader::from(genesis.header());
+/// assert_eq!(genesis.header().timestamp.to_millis(), header.timestamp);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CanonicalBlockHeader {
+    /// the version of the block header format
+    pub version: u64,
+
+    /// the block's height
+    pub height: u64,
+
+    /// the digest of the block's predecessor, as hex
+    pub prev_block_digest: String,
+
+    /// the block's timestamp, in milliseconds since the unix epoch
+    pub timestamp: u64,
+
+    /// the proof-of-work solution
+    pub pow: CanonicalPow,
+
+    /// the proof-of-work accumulated by the chain up to and including the
+    /// block, as a decimal string of expected hashes
+    pub cumulative_proof_of_work: String,
+
+    /// the difficulty of the block's successor, as a decimal string of
+    /// expected hashes
+    pub difficulty: String,
+
+    /// the receiver digest of the guesser's UTXOs, as hex
+    pub guesser_receiver_digest: String,
+
+    /// the lock script hash of the guesser's UTXOs, as hex
+    pub guesser_lock_script_hash: String,
+}
+
+impl From<&BlockHeader> for CanonicalBlockHeader {
+    fn from(header: &BlockHeader) -> Self {
+        Self {
+            version: header.version.value(),
+            height: header.height.into(),
+            prev_block_digest: header.prev_block_digest.to_hex(),
+            timestamp: header.timestamp.to_millis(),
+            pow: CanonicalPow {
+                root: header.pow.root.to_hex(),
+                path_a: header.pow.path_a.iter().map(|d| d.to_hex()).collect(),
+                path_b: header.pow.path_b.iter().map(|d| d.to_hex()).collect(),
+                nonce: header.pow.nonce.to_hex(),
+            },
+            cumulative_proof_of_work: header.cumulative_proof_of_work.to_string(),
+            difficulty: header.difficulty.to_string(),
+            guesser_receiver_digest: header.guesser_receiver_data.receiver_digest.to_hex(),
+            guesser_lock_script_hash: header.guesser_receiver_data.lock_script_hash.to_hex(),
+        }
+    }
+}
+
+/// the proof-of-work solution of a block header. all digests are hex.
+///
+/// see the [module docs](super) for the stability guarantees.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CanonicalPow {
+    /// the root of the memory tree
+    pub root: String,
+
+    /// the authentication path of the first memory leaf
+    pub path_a: Vec<String>,
+
+    /// the authentication path of the second memory leaf
+    pub path_b: Vec<String>,
+
+    /// the nonce
+    pub nonce: String,
+}
@@ -0,0 +1,120 @@
+// private module.  no need for module docs.
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::consensus::transaction::announcement::Announcement;
+use crate::protocol::consensus::transaction::transaction_kernel::TransactionKernel;
+use crate::util_types::mutator_set::removal_record::RemovalRecord;
+
+/// a transaction kernel, in the stable JSON encoding for block explorers.
+///
+/// see the [module docs](super) for the stability guarantees.
+///
+/// ```
+/// use neptune_cash::api::export::Block;
+/// use neptune_cash::api::export::Network;
+/// use neptune_cash::api::types::CanonicalTransactionKernel;
+///
+/// let genesis = Block::genesis(Network::Main);
+/// let kernel = CanonicalTransactionKernel::from(&genesis.body().transaction_kernel);
+/// assert!(kernel.inputs.is_empty());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CanonicalTransactionKernel {
+    /// the id of the transaction, as hex
+    pub txid: String,
+
+    /// the removal records of the inputs
+    pub inputs: Vec<CanonicalRemovalRecord>,
+
+    /// the canonical commitments of the outputs' addition records, as hex
+    pub outputs: Vec<String>,
+
+    /// the announcements
+    pub announcements: Vec<CanonicalAnnouncement>,
+
+    /// the fee
+    pub fee: String,
+
+    /// the coinbase, if any
+    #[serde(default)]
+    pub coinbase: Option<String>,
+
+    /// the transaction's timestamp, in milliseconds since the unix epoch
+    pub timestamp: u64,
+
+    /// the hash of the mutator set the transaction was built against, as hex
+    pub mutator_set_hash: String,
+
+    /// whether the transaction is the result of merging transactions
+    pub merge_bit: bool,
+}
+
+impl From<&TransactionKernel> for CanonicalTransactionKernel {
+    fn from(kernel: &TransactionKernel) -> Self {
+        Self {
+            txid: kernel.txid().to_string(),
+            inputs: kernel.inputs.iter().map(Into::into).collect(),
+            outputs: kernel
+                .outputs
+                .iter()
+                .map(|output| output.canonical_commitment.to_hex())
+                .collect(),
+            announcements: kernel.announcements.iter().map(Into::into).collect(),
+            fee: kernel.fee.to_nau().to_string(),
+            coinbase: kernel
+                .coinbase
+                .map(|coinbase| coinbase.to_nau().to_string()),
+            timestamp: kernel.timestamp.to_millis(),
+            mutator_set_hash: kernel.mutator_set_hash.to_hex(),
+            merge_bit: kernel.merge_bit,
+        }
+    }
+}
+
+/// the removal record of a transaction input, without the witness data
+/// (target chunks) that changes with every block.
+///
+/// see the [module docs](super) for the stability guarantees.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CanonicalRemovalRecord {
+    /// the absolute indices into the mutator set's bloom filter that the
+    /// input sets, as decimal strings. They identify the spent UTXO without
+    /// revealing it.
+    pub absolute_indices: Vec<String>,
+}
+
+impl From<&RemovalRecord> for CanonicalRemovalRecord {
+    fn from(record: &RemovalRecord) -> Self {
+        Self {
+            absolute_indices: record
+                .absolute_indices
+                .to_vec()
+                .iter()
+                .map(|index| index.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// an announcement of a transaction.
+///
+/// see the [module docs](super) for the stability guarantees.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CanonicalAnnouncement {
+    /// the announced field elements, as decimal strings
+    pub message: Vec<String>,
+}
+
+impl From<&Announcement> for CanonicalAnnouncement {
+    fn from(announcement: &Announcement) -> Self {
+        Self {
+            message: announcement
+                .message
+                .iter()
+                .map(|element| element.value().to_string())
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! provides stable data transfer types for downstream software.
+//!
+//! The types in [export](super::export) are neptune-core's internal types,
+//! which change whenever the consensus rules or the node's internals change.
+//! Wallets and other downstream software that store or transmit those types
+//! break on such changes.
+//!
+//! The types in this module are decoupled from the internal types. They
+//! consist only of primitive types and strings, and are obtained from the
+//! internal types via `From` impls. Their serialized form is guarded by
+//! [API_TYPES_VERSION], which follows semver:
+//!
+//! - patch: no change to the serialized form.
+//! - minor: fields added. Added fields are optional when deserializing, so
+//!   data serialized by an older minor version still deserializes.
+//! - major: fields removed, renamed, or changed in meaning or format.
+//!
+//! Amounts are decimal strings of nau, the smallest unit of the native
+//! currency. Digests are hex strings, and timestamps are milliseconds since
+//! the unix epoch. Other numbers that may exceed 2^53, and so cannot be
+//! represented exactly by JSON parsers that use doubles, are decimal strings.
+//!
+//! Besides summaries, the module provides canonical encodings of consensus
+//! types for block explorers: [CanonicalBlock], [CanonicalBlockHeader], and
+//! [CanonicalTransactionKernel]. These mirror the consensus types field by
+//! field, but are not affected by changes to the consensus types' serde
+//! derives.
+//!
+//! ```
+//! use neptune_cash::api::export::Block;
+//! use neptune_cash::api::export::Network;
+//! use neptune_cash::api::types::BlockSummary;
+//!
+//! let genesis = Block::genesis(Network::Main);
+//! let summary = BlockSummary::from(&genesis);
+//! assert_eq!(0, summary.height);
+//! ```
+mod balance_report;
+mod block_summary;
+mod canonical_block;
+mod canonical_transaction_kernel;
+mod transaction_summary;
+
+// these represent the public API
+pub use balance_report::BalanceReport;
+pub use block_summary::BlockSummary;
+pub use canonical_block::CanonicalBlock;
+pub use canonical_block::CanonicalBlockHeader;
+pub use canonical_block::CanonicalPow;
+pub use canonical_transaction_kernel::CanonicalAnnouncement;
+pub use canonical_transaction_kernel::CanonicalRemovalRecord;
+pub use canonical_transaction_kernel::CanonicalTransactionKernel;
+pub use transaction_summary::TransactionSummary;
+
+/// semver version of the serialized form of the types in this module.
+///
+/// see the [module docs](self) for when each component is bumped.
+pub const API_TYPES_VERSION: &str = "1.1.0";
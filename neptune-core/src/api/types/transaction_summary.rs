@@ -0,0 +1,62 @@
+// private module.  no need for module docs.
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::consensus::transaction::Transaction;
+
+/// a summary of a transaction, stable across releases of neptune-core.
+///
+/// see the [module docs](super) for the stability guarantees.
+///
+/// ```no_run
+/// use neptune_cash::api::export::Transaction;
+/// use neptune_cash::api::types::TransactionSummary;
+///
+/// fn print_summary(transaction: &Transaction) {
+///     let summary = TransactionSummary::from(transaction);
+///     println!("{}: fee {}", summary.txid, summary.fee);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionSummary {
+    /// the transaction's id, as hex
+    pub txid: String,
+
+    /// the transaction's timestamp, in milliseconds since the unix epoch
+    pub timestamp: u64,
+
+    /// the number of inputs
+    pub num_inputs: usize,
+
+    /// the number of outputs
+    pub num_outputs: usize,
+
+    /// the number of announcements
+    pub num_announcements: usize,
+
+    /// the fee
+    pub fee: String,
+
+    /// the coinbase, if any
+    #[serde(default)]
+    pub coinbase: Option<String>,
+}
+
+impl From<&Transaction> for TransactionSummary {
+    fn from(transaction: &Transaction) -> Self {
+        let kernel = &transaction.kernel;
+
+        Self {
+            txid: transaction.txid().to_string(),
+            timestamp: kernel.timestamp.to_millis(),
+            num_inputs: kernel.inputs.len(),
+            num_outputs: kernel.outputs.len(),
+            num_announcements: kernel.announcements.len(),
+            fee: kernel.fee.to_nau().to_string(),
+            coinbase: kernel
+                .coinbase
+                .map(|coinbase| coinbase.to_nau().to_string()),
+        }
+    }
+}
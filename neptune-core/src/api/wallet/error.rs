@@ -3,10 +3,34 @@
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::api::export::BlockHeight;
+use crate::api::export::Network;
+
 /// enumerates possible wallet errors
 #[derive(Debug, Clone, thiserror::Error, Serialize, Deserialize)]
 #[non_exhaustive]
 pub enum WalletError {
+    #[error("the utxo cannot be unlocked by any key of this wallet")]
+    UtxoNotOwned,
+
+    #[error("the membership proof is not valid for the tip, or the utxo was spent")]
+    InvalidMembershipProof,
+
+    #[error("the membership bundle was not produced at the tip")]
+    MembershipBundleNotAtTip,
+
+    #[error("node is not archival.  historical balances are not available")]
+    NotArchival,
+
+    #[error("height {height} is above the tip at height {tip_height}")]
+    HeightAboveTip {
+        height: BlockHeight,
+        tip_height: BlockHeight,
+    },
+
+    #[error("height {height} is more than {max_depth} blocks below the tip")]
+    HeightTooDeep { height: BlockHeight, max_depth: u64 },
+
     // catch-all error, eg for anyhow errors
     #[error("operation failed.  reason: {0}")]
     Failed(String),
@@ -19,3 +43,54 @@ impl From<anyhow::Error> for WalletError {
         Self::Failed(e.to_string())
     }
 }
+
+/// enumerates possible errors when migrating a wallet between data directories.
+#[derive(Debug, Clone, thiserror::Error, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum WalletMigrationError {
+    #[error("cannot migrate a wallet from network {from} to network {to}")]
+    IncompatibleNetworks { from: Network, to: Network },
+
+    #[error("data directory {data_dir} belongs to network {recorded}, not {expected}")]
+    WrongNetwork {
+        data_dir: String,
+        recorded: Network,
+        expected: Network,
+    },
+
+    #[error("source and destination are the same data directory")]
+    SameDataDirectory,
+
+    #[error("no wallet found at {0}")]
+    SourceWalletMissing(String),
+
+    #[error("destination is not a fresh data directory; {0} already exists")]
+    DestinationNotEmpty(String),
+
+    #[error(
+        "could not open wallet database at {path}.  Is neptune-core running?  reason: {reason}"
+    )]
+    Database { path: String, reason: String },
+
+    #[error("migrated wallet does not match its source: {0}")]
+    VerificationFailed(String),
+
+    // catch-all error, eg for anyhow errors
+    #[error("wallet migration failed.  reason: {0}")]
+    Failed(String),
+}
+
+// convert anyhow::Error to a WalletMigrationError::Failed.
+// note that anyhow Error is not serializable.
+impl From<anyhow::Error> for WalletMigrationError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::Failed(e.to_string())
+    }
+}
+
+// note that std::io::Error is not serializable.
+impl From<std::io::Error> for WalletMigrationError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Failed(e.to_string())
+    }
+}
@@ -0,0 +1,242 @@
+// private module.  no need for module docs.
+
+use std::collections::HashSet;
+
+use itertools::Itertools;
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Digest;
+use tasm_lib::prelude::Tip5;
+
+use super::error::WalletError;
+use crate::application::database::storage::storage_vec::traits::*;
+use crate::macros::state_lock_call_async;
+use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::protocol::consensus::block::block_selector::BlockSelector;
+use crate::protocol::consensus::block::Block;
+use crate::protocol::consensus::transaction::utxo::Utxo;
+use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+use crate::state::GlobalState;
+use crate::state::StateLock;
+use crate::util_types::mutator_set::addition_record::AdditionRecord;
+use crate::util_types::mutator_set::removal_record::absolute_index_set::AbsoluteIndexSet;
+
+/// the wallet's confirmed balances and utxos as of a historical block.
+///
+/// Only canonical blocks count: a utxo belongs to the historical view if it
+/// was confirmed in a canonical block at or below the queried height, and had
+/// not been spent in a canonical block at or below that height.
+///
+/// `available` excludes utxos that were time-locked as of the block's
+/// timestamp. `total` includes them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoricalBalance {
+    /// height of the queried block
+    pub block_height: BlockHeight,
+
+    /// digest of the canonical block at the queried height
+    pub block_digest: Digest,
+
+    /// timestamp of the canonical block at the queried height
+    pub block_timestamp: Timestamp,
+
+    /// balance of confirmed utxos that were not time-locked as of the block
+    pub confirmed_available: NativeCurrencyAmount,
+
+    /// balance of all confirmed utxos as of the block
+    pub confirmed_total: NativeCurrencyAmount,
+
+    /// the unspent utxos as of the block, in order of confirmation
+    pub utxos: Vec<HistoricalUtxo>,
+}
+
+/// an unspent utxo of the wallet as of a historical block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoricalUtxo {
+    /// the utxo itself
+    pub utxo: Utxo,
+
+    /// the utxo's position in the append-only commitment list
+    pub aocl_leaf_index: u64,
+
+    /// height of the canonical block the utxo was confirmed in
+    pub confirmed_at: BlockHeight,
+
+    /// native-currency amount of the utxo
+    pub amount: NativeCurrencyAmount,
+
+    /// the utxo's release date, if it is time-locked
+    pub release_date: Option<Timestamp>,
+}
+
+/// the wallet's utxos as of the tip, and the canonical blocks that must be
+/// replayed to roll them back to the queried height.
+///
+/// collected under the global read lock by [Self::from_global_state], which
+/// is released before the blocks are replayed by [Self::replay].
+#[derive(Debug, Clone)]
+pub(super) struct HistoricalReplay {
+    block_height: BlockHeight,
+    block_digest: Digest,
+    block_timestamp: Timestamp,
+
+    /// the canonical tip at the time the utxos were collected. blocks are
+    /// replayed from here, so later tip changes do not affect the result.
+    tip_digest: Digest,
+
+    /// number of blocks between the queried height and the tip
+    num_blocks: u64,
+
+    utxos: Vec<ReplayedUtxo>,
+}
+
+/// a utxo the wallet has a membership proof for, as of the tip
+#[derive(Debug, Clone)]
+struct ReplayedUtxo {
+    utxo: HistoricalUtxo,
+    addition_record: AdditionRecord,
+    absolute_indices: AbsoluteIndexSet,
+    unspent_at_tip: bool,
+}
+
+impl HistoricalReplay {
+    /// collect the wallet's utxos as of the tip. `height` may lie at most
+    /// `--max-historical-balance-depth` blocks below the tip.
+    pub(super) async fn from_global_state(
+        gs: &GlobalState,
+        height: BlockHeight,
+    ) -> Result<Self, WalletError> {
+        if !gs.chain.is_archival_node() {
+            return Err(WalletError::NotArchival);
+        }
+
+        let tip = gs.chain.light_state();
+        let tip_height = tip.header().height;
+        if height > tip_height {
+            return Err(WalletError::HeightAboveTip { height, tip_height });
+        }
+        let num_blocks = u64::from(tip_height) - u64::from(height);
+        let max_depth = gs.cli().max_historical_balance_depth;
+        if num_blocks > max_depth {
+            return Err(WalletError::HeightTooDeep { height, max_depth });
+        }
+
+        let block_digest = BlockSelector::Height(height)
+            .as_digest(gs)
+            .await
+            .ok_or_else(|| WalletError::Failed(format!("no canonical block at height {height}")))?;
+        let block_header = gs
+            .chain
+            .archival_state()
+            .get_block_header(block_digest)
+            .await
+            .ok_or_else(|| WalletError::Failed(format!("no header for block {block_digest}")))?;
+
+        // like the wallet's status at the tip, only utxos with a membership
+        // proof for the tip count. those are confirmed in the canonical chain.
+        let tip_digest = tip.hash();
+        let mutator_set_accumulator = tip
+            .mutator_set_accumulator_after()
+            .map_err(|e| WalletError::Failed(e.to_string()))?;
+        let mut utxos = vec![];
+        let monitored_utxos = gs.wallet_state.wallet_db.monitored_utxos();
+        let stream = monitored_utxos.stream_values().await;
+        pin_mut!(stream); // needed for iteration
+        while let Some(monitored_utxo) = stream.next().await {
+            let Some(membership_proof) = monitored_utxo.get_membership_proof_for_block(tip_digest)
+            else {
+                continue;
+            };
+
+            let unspent_at_tip =
+                mutator_set_accumulator.verify(Tip5::hash(&monitored_utxo.utxo), &membership_proof);
+            utxos.push(ReplayedUtxo {
+                addition_record: monitored_utxo.addition_record(),
+                absolute_indices: monitored_utxo.absolute_indices(),
+                unspent_at_tip,
+                utxo: HistoricalUtxo {
+                    amount: monitored_utxo.utxo.get_native_currency_amount(),
+                    release_date: monitored_utxo.utxo.release_date(),
+                    aocl_leaf_index: monitored_utxo.aocl_leaf_index,
+                    confirmed_at: monitored_utxo.confirmed_in_block.2,
+                    utxo: monitored_utxo.utxo,
+                },
+            });
+        }
+
+        Ok(Self {
+            block_height: height,
+            block_digest,
+            block_timestamp: block_header.timestamp,
+            tip_digest,
+            num_blocks,
+            utxos,
+        })
+    }
+
+    /// replay the canonical blocks from the tip down to, but excluding, the
+    /// queried height: utxos created in them did not exist yet, and utxos
+    /// spent in them were still unspent.
+    ///
+    /// Locking:
+    ///  * acquires the global read lock once per replayed block, if
+    ///    `state_lock` is not a guard already
+    pub(super) async fn replay(
+        self,
+        state_lock: &StateLock<'_>,
+    ) -> Result<HistoricalBalance, WalletError> {
+        let mut created = HashSet::new();
+        let mut spent = HashSet::new();
+        let mut block_digest = self.tip_digest;
+        for _ in 0..self.num_blocks {
+            let block = state_lock_call_async!(state_lock, archived_block, block_digest).await?;
+            let mutator_set_update = block
+                .mutator_set_update()
+                .map_err(|e| WalletError::Failed(e.to_string()))?;
+            created.extend(mutator_set_update.additions);
+            spent.extend(
+                mutator_set_update
+                    .removals
+                    .into_iter()
+                    .map(|removal_record| removal_record.absolute_indices),
+            );
+            block_digest = block.header().prev_block_digest;
+        }
+
+        let mut utxos = self
+            .utxos
+            .into_iter()
+            .filter(|replayed| !created.contains(&replayed.addition_record))
+            .filter(|replayed| {
+                replayed.unspent_at_tip || spent.contains(&replayed.absolute_indices)
+            })
+            .map(|replayed| replayed.utxo)
+            .collect_vec();
+        utxos.sort_by_key(|utxo| utxo.aocl_leaf_index);
+
+        let confirmed_total = utxos.iter().map(|utxo| utxo.amount).sum();
+        let confirmed_available = utxos
+            .iter()
+            .filter(|utxo| utxo.utxo.can_spend_at(self.block_timestamp))
+            .map(|utxo| utxo.amount)
+            .sum();
+
+        Ok(HistoricalBalance {
+            block_height: self.block_height,
+            block_digest: self.block_digest,
+            block_timestamp: self.block_timestamp,
+            confirmed_available,
+            confirmed_total,
+            utxos,
+        })
+    }
+}
+
+async fn archived_block(gs: &GlobalState, block_digest: Digest) -> Result<Block, WalletError> {
+    gs.chain
+        .archival_state()
+        .get_block(block_digest)
+        .await?
+        .ok_or_else(|| WalletError::Failed(format!("no block {block_digest}")))
+}
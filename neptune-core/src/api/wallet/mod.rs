@@ -1,8 +1,17 @@
 //! provides public API for the neptune-core wallet.
+mod historical_balance;
+mod utxo_memos;
 mod wallet_balances;
 mod wallet_impl;
+mod wallet_migration;
 
 // these represent the public API
 pub mod error;
+pub use historical_balance::HistoricalBalance;
+pub use historical_balance::HistoricalUtxo;
+pub use utxo_memos::UtxoMemos;
 pub use wallet_balances::WalletBalances;
 pub use wallet_impl::Wallet;
+pub use wallet_migration::WalletDbSnapshot;
+pub use wallet_migration::WalletMigration;
+pub use wallet_migration::WalletMigrationReport;
@@ -0,0 +1,69 @@
+// private module.  no need for module docs.
+
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Digest;
+
+use crate::application::database::storage::storage_vec::traits::*;
+use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
+use crate::state::wallet::memo::Memo;
+use crate::state::GlobalState;
+use crate::util_types::mutator_set::addition_record::AdditionRecord;
+
+/// the decoded memos that senders announced along with a utxo received by the
+/// wallet.
+///
+/// memos are unauthenticated: anyone can announce a memo to the receiving
+/// address in the block that pays it. see [memo](crate::state::wallet::memo).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UtxoMemos {
+    /// the utxo's commitment in the mutator set
+    pub addition_record: AdditionRecord,
+
+    /// the utxo's position in the append-only commitment list
+    pub aocl_leaf_index: u64,
+
+    /// digest of the block the utxo was confirmed in
+    pub confirmed_in_block: Digest,
+
+    /// height of the block the utxo was confirmed in
+    pub confirmed_at: BlockHeight,
+
+    /// native-currency amount of the utxo
+    pub amount: NativeCurrencyAmount,
+
+    /// the decoded memos, in order of receipt. unauthenticated.
+    pub memos: Vec<Memo>,
+}
+
+impl UtxoMemos {
+    /// collect the memos of all monitored utxos that have any, in order of
+    /// confirmation.
+    pub(super) async fn all_from_global_state(gs: &GlobalState) -> Vec<Self> {
+        let mut all_memos = vec![];
+        let monitored_utxos = gs.wallet_state.wallet_db.monitored_utxos();
+        let stream = monitored_utxos.stream_values().await;
+        pin_mut!(stream); // needed for iteration
+        while let Some(monitored_utxo) = stream.next().await {
+            let addition_record = monitored_utxo.addition_record();
+            let memos = gs.wallet_state.wallet_db.utxo_memos(&addition_record).await;
+            if memos.is_empty() {
+                continue;
+            }
+
+            let (confirmed_in_block, _, confirmed_at) = monitored_utxo.confirmed_in_block;
+            all_memos.push(Self {
+                addition_record,
+                aocl_leaf_index: monitored_utxo.aocl_leaf_index,
+                confirmed_in_block,
+                confirmed_at,
+                amount: monitored_utxo.utxo.get_native_currency_amount(),
+                memos,
+            });
+        }
+        all_memos.sort_by_key(|utxo_memos| utxo_memos.aocl_leaf_index);
+
+        all_memos
+    }
+}
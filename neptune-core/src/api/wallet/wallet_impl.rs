@@ -1,16 +1,26 @@
 // private module.  no need for module docs.
 
 use super::error::WalletError;
+use super::historical_balance::HistoricalBalance;
+use super::historical_balance::HistoricalReplay;
+use super::utxo_memos::UtxoMemos;
 use super::wallet_balances::WalletBalances;
+use crate::api::export::BlockHeight;
+use crate::api::export::Tip5;
 use crate::macros::state_lock_call_async;
 use crate::macros::state_lock_call_mut_async;
+use crate::protocol::consensus::transaction::utxo::Utxo;
+use crate::protocol::consensus::transaction::utxo_triple::UtxoTriple;
 use crate::protocol::proof_abstractions::timestamp::Timestamp;
 use crate::state::wallet::address::KeyType;
 use crate::state::wallet::address::ReceivingAddress;
 use crate::state::wallet::address::SpendingKey;
+use crate::state::wallet::monitored_utxo::MonitoredUtxo;
 use crate::state::wallet::transaction_input::TxInputList;
 use crate::state::GlobalState;
 use crate::state::StateLock;
+use crate::util_types::mutator_set::membership_bundle::MembershipBundle;
+use crate::util_types::mutator_set::ms_membership_proof::MsMembershipProof;
 use crate::GlobalStateLock;
 
 /// provides an API for interacting with the neptune-core wallet.
@@ -145,11 +155,23 @@ impl<'a> Wallet<'a> {
     /// transaction individually not the transaction as a whole.
     ///
     /// If in any doubt, just use [KeyType::Generation].
+    ///
+    /// If a symmetric-key rotation policy is configured, requesting a
+    /// [KeyType::Symmetric] address returns the active symmetric key until it
+    /// is rotated, rather than a new key every time.
     pub async fn next_receiving_address(
         &mut self,
         key_type: KeyType,
     ) -> Result<ReceivingAddress, WalletError> {
-        Ok(self.next_unused_spending_key(key_type).await?.to_address())
+        let key = state_lock_call_mut_async!(
+            &mut self.state_lock,
+            worker::spending_key_for_reception,
+            key_type,
+            Timestamp::now()
+        )
+        .await?;
+
+        Ok(key.to_address())
     }
 
     /// get wallet balances as of timestamp
@@ -175,6 +197,102 @@ impl<'a> Wallet<'a> {
     pub async fn spendable_inputs(&self, timestamp: Timestamp) -> TxInputList {
         state_lock_call_async!(&self.state_lock, worker::spendable_inputs, timestamp).await
     }
+
+    /// returns the wallet's confirmed balances and unspent utxos as of the
+    /// canonical block at `height`.
+    ///
+    /// a utxo counts if it was confirmed in a canonical block at or below
+    /// `height` and was not spent in a canonical block at or below `height`.
+    /// the available balance excludes utxos that were time-locked as of that
+    /// block's timestamp.
+    ///
+    /// the view is built by replaying the canonical blocks between `height`
+    /// and the tip against the utxos the wallet monitors as of the tip, so
+    /// utxos the wallet never learned of are not included, even if they were
+    /// owned at the time. the global read lock is released between blocks.
+    ///
+    /// fails with [WalletError::NotArchival] on a non-archival node, with
+    /// [WalletError::HeightAboveTip] if `height` exceeds the tip, and with
+    /// [WalletError::HeightTooDeep] if `height` lies more than
+    /// `--max-historical-balance-depth` blocks below the tip.
+    pub async fn balance_at_height(
+        &self,
+        height: BlockHeight,
+    ) -> Result<HistoricalBalance, WalletError> {
+        let replay =
+            state_lock_call_async!(&self.state_lock, worker::historical_replay, height).await?;
+        replay.replay(&self.state_lock).await
+    }
+
+    /// returns the memos that senders announced along with utxos received by
+    /// this wallet, for every monitored utxo that has any.
+    ///
+    /// memos of recognized formats, such as amount hints and invoice ids, are
+    /// decoded. others are returned raw. see [memo](crate::state::wallet::memo)
+    /// for how memos are announced and attached to utxos.
+    ///
+    /// memos are unauthenticated, so anyone who knows the receiving address
+    /// can attach memos to a payment to it.
+    pub async fn utxo_memos(&self) -> Vec<UtxoMemos> {
+        state_lock_call_async!(&self.state_lock, worker::utxo_memos).await
+    }
+
+    /// add a membership proof for a utxo of this wallet, obtained elsewhere.
+    ///
+    /// this is how a node running in headers-only mode learns of utxos that
+    /// were confirmed in blocks whose bodies it no longer stores, and so cannot
+    /// scan. the membership proof can be obtained from an archival node, eg
+    /// with the `restore_membership_proof_privacy_preserving` RPC.
+    ///
+    /// the proof must be valid for the current tip. the utxo is then monitored
+    /// like any other, and its membership proof kept up to date with new
+    /// blocks. if the wallet already monitors the utxo, the proof is added to
+    /// it.
+    ///
+    /// fails with [WalletError::UtxoNotOwned] if no key of the wallet can
+    /// unlock the utxo, and with [WalletError::InvalidMembershipProof] if the
+    /// proof is not valid for the tip, which includes the case that the utxo
+    /// was spent.
+    pub async fn submit_membership_proof(
+        &mut self,
+        utxo: Utxo,
+        membership_proof: MsMembershipProof,
+    ) -> Result<(), WalletError> {
+        state_lock_call_mut_async!(
+            &mut self.state_lock,
+            worker::submit_membership_proof,
+            utxo,
+            membership_proof
+        )
+        .await
+    }
+
+    /// add the membership proofs of utxos of this wallet, assembled from a
+    /// membership bundle obtained from an archival node, eg with the
+    /// `utxo_membership_bundle` RPC.
+    ///
+    /// this is how a wallet-only node that learned of its utxos out-of-band
+    /// becomes able to spend them. each utxo is then handled as by
+    /// [Wallet::submit_membership_proof()]. returns the number of utxos.
+    ///
+    /// fails with [WalletError::MembershipBundleNotAtTip] if the bundle was
+    /// not produced at the current tip, with [WalletError::UtxoNotOwned] if
+    /// a receiver digest does not belong to a key of the wallet, and with
+    /// [WalletError::InvalidMembershipProof] if a utxo is not contained in the
+    /// bundle or was spent. utxos preceding the failing one are imported.
+    pub async fn import_membership_bundle(
+        &mut self,
+        bundle: MembershipBundle,
+        utxo_triples: Vec<UtxoTriple>,
+    ) -> Result<usize, WalletError> {
+        state_lock_call_mut_async!(
+            &mut self.state_lock,
+            worker::import_membership_bundle,
+            bundle,
+            utxo_triples
+        )
+        .await
+    }
 }
 
 mod worker {
@@ -192,6 +310,22 @@ mod worker {
         Ok(address)
     }
 
+    pub async fn spending_key_for_reception(
+        gsm: &mut GlobalState,
+        key_type: KeyType,
+        now: Timestamp,
+    ) -> Result<SpendingKey, WalletError> {
+        let key = gsm
+            .wallet_state
+            .spending_key_for_reception(key_type, now)
+            .await;
+
+        // persist wallet state to disk
+        gsm.persist_wallet().await?;
+
+        Ok(key)
+    }
+
     pub async fn balances(gs: &GlobalState, timestamp: Timestamp) -> WalletBalances {
         WalletBalances::from_global_state(gs, timestamp).await
     }
@@ -203,4 +337,107 @@ mod worker {
             .into_iter()
             .into()
     }
+
+    pub async fn historical_replay(
+        gs: &GlobalState,
+        height: BlockHeight,
+    ) -> Result<HistoricalReplay, WalletError> {
+        HistoricalReplay::from_global_state(gs, height).await
+    }
+
+    pub async fn utxo_memos(gs: &GlobalState) -> Vec<UtxoMemos> {
+        UtxoMemos::all_from_global_state(gs).await
+    }
+
+    pub async fn submit_membership_proof(
+        gsm: &mut GlobalState,
+        utxo: Utxo,
+        membership_proof: MsMembershipProof,
+    ) -> Result<(), WalletError> {
+        if !gsm.wallet_state.can_unlock(&utxo) {
+            return Err(WalletError::UtxoNotOwned);
+        }
+
+        let tip = gsm.chain.light_state();
+        let tip_digest = tip.hash();
+        let item = Tip5::hash(&utxo);
+        let mutator_set_accumulator = tip
+            .mutator_set_accumulator_after()
+            .map_err(|e| WalletError::Failed(e.to_string()))?;
+        if !mutator_set_accumulator.verify(item, &membership_proof) {
+            return Err(WalletError::InvalidMembershipProof);
+        }
+
+        let index_set = membership_proof.compute_indices(item);
+        if let Some((_, list_index)) = gsm
+            .wallet_state
+            .wallet_db
+            .monitored_utxo_by_index_set(&index_set)
+            .await
+        {
+            gsm.wallet_state
+                .wallet_db
+                .add_msmp_to_monitored_utxo(list_index, tip_digest, membership_proof)
+                .await;
+        } else {
+            // headers suffice to find the block that confirmed the utxo.
+            let archival_state = gsm.chain.archival_state();
+            let confirmed_in = archival_state
+                .canonical_block_digest_of_aocl_index(membership_proof.aocl_leaf_index)
+                .await?
+                .ok_or(WalletError::InvalidMembershipProof)?;
+            let header = archival_state
+                .get_block_header(confirmed_in)
+                .await
+                .ok_or(WalletError::InvalidMembershipProof)?;
+
+            let mut monitored_utxo = MonitoredUtxo::new_from_block_hash(
+                utxo,
+                gsm.cli().number_of_mps_per_utxo,
+                membership_proof.aocl_leaf_index,
+                membership_proof.sender_randomness,
+                membership_proof.receiver_preimage,
+                (confirmed_in, header.timestamp, header.height),
+            );
+            monitored_utxo.add_membership_proof_for_tip(tip_digest, membership_proof);
+            gsm.wallet_state
+                .register_incoming_utxo(monitored_utxo)
+                .await?;
+        }
+
+        // persist wallet state to disk
+        gsm.persist_wallet().await?;
+
+        Ok(())
+    }
+
+    pub async fn import_membership_bundle(
+        gsm: &mut GlobalState,
+        bundle: MembershipBundle,
+        utxo_triples: Vec<UtxoTriple>,
+    ) -> Result<usize, WalletError> {
+        if bundle.tip_hash != gsm.chain.light_state().hash() {
+            return Err(WalletError::MembershipBundleNotAtTip);
+        }
+
+        let num_utxos = utxo_triples.len();
+        for utxo_triple in utxo_triples {
+            let receiver_preimage = gsm
+                .wallet_state
+                .find_known_spending_key_for_receiver_digest(utxo_triple.receiver_digest)
+                .ok_or(WalletError::UtxoNotOwned)?
+                .privacy_preimage();
+            let membership_proof = bundle
+                .membership_proof(
+                    Tip5::hash(&utxo_triple.utxo),
+                    utxo_triple.sender_randomness,
+                    receiver_preimage,
+                )
+                .map_err(|_| WalletError::InvalidMembershipProof)?;
+
+            submit_membership_proof(gsm, utxo_triple.utxo, membership_proof).await?;
+        }
+
+        Ok(num_utxos)
+    }
 }
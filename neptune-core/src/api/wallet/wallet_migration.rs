@@ -0,0 +1,392 @@
+// private module.  no need for module docs.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Digest;
+
+use super::error::WalletMigrationError;
+use crate::application::config::data_directory::DataDirectory;
+use crate::application::config::network::Network;
+use crate::application::database::open_files::db_options;
+use crate::application::database::open_files::DbStore;
+use crate::application::database::storage::storage_vec::traits::StorageVecBase;
+use crate::application::database::NeptuneLevelDb;
+use crate::state::wallet::rusty_wallet_database::RustyWalletDatabase;
+use crate::state::wallet::wallet_file::WalletFile;
+use crate::state::wallet::wallet_file::WalletFileContext;
+
+/// migrates a wallet from one data directory into a fresh data directory.
+///
+/// Everything the wallet knows is moved: the wallet secret (keys), the
+/// incoming and outgoing randomness files, the wallet database (key-derivation
+/// counters, monitored and expected UTXOs, sent transactions and their
+/// labels) and any off-chain utxo-transfer files.
+///
+/// The destination may be on another disk, eg removable media, which makes
+/// cross-machine moves a matter of two migrations: one onto the medium and one
+/// from the medium into the data directory of the new machine.
+///
+/// A migration:
+///
+/// 1. refuses to move a wallet between different networks, since the wallet
+///    database describes UTXOs of one specific chain.
+/// 2. refuses to touch a data directory whose network marker names another
+///    network than the one specified.
+/// 3. refuses to overwrite any wallet data already present at the destination.
+/// 4. verifies, after copying, that every copied file is byte-identical to its
+///    source, that the wallet secret reads back unchanged and that the
+///    migrated wallet database has the same counters, sync label and number of
+///    UTXOs and transactions as its source.
+///
+/// If anything fails after copying has begun, the partially migrated data is
+/// removed from the destination again. The source is never modified.
+///
+/// neptune-core must not be running against the source data directory while
+/// migrating; the wallet database is locked by a running node and the
+/// migration fails with [WalletMigrationError::Database].
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use neptune_cash::api::export::Network;
+/// use neptune_cash::api::wallet::WalletMigration;
+/// use neptune_cash::application::config::data_directory::DataDirectory;
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let from = DataDirectory::get(None, Network::Main)?;
+/// let to = DataDirectory::get(Some(PathBuf::from("/mnt/usb/neptune")), Network::Main)?;
+///
+/// let report = WalletMigration::new(from, Network::Main, to, Network::Main)
+///     .migrate()
+///     .await?;
+/// println!("verified {} files", report.files_verified);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct WalletMigration {
+    from: DataDirectory,
+    from_network: Network,
+    to: DataDirectory,
+    to_network: Network,
+}
+
+/// summary of a wallet database, used to verify a migration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletDbSnapshot {
+    /// schema version of the wallet database
+    pub schema_version: u16,
+
+    /// digest of the block the wallet database is synced to
+    pub sync_label: Digest,
+
+    /// number of output UTXOs generated by the wallet
+    pub output_counter: u64,
+
+    /// derivation index of the next unused generation key
+    pub generation_key_counter: u64,
+
+    /// derivation index of the next unused symmetric key
+    pub symmetric_key_counter: u64,
+
+    /// number of monitored UTXOs
+    pub num_monitored_utxos: u64,
+
+    /// number of expected UTXOs
+    pub num_expected_utxos: u64,
+
+    /// number of sent transactions
+    pub num_sent_transactions: u64,
+}
+
+/// outcome of a successful wallet migration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletMigrationReport {
+    /// network the wallet belongs to
+    pub network: Network,
+
+    /// wallet directory the wallet was migrated from
+    pub from_wallet_dir: PathBuf,
+
+    /// wallet directory the wallet was migrated to
+    pub to_wallet_dir: PathBuf,
+
+    /// summary of the migrated wallet database, if the source had one.
+    ///
+    /// a wallet that has never been used by neptune-core has no database.
+    pub wallet_db: Option<WalletDbSnapshot>,
+
+    /// number of files verified to be byte-identical to their source
+    pub files_verified: usize,
+
+    /// number of bytes verified
+    pub bytes_verified: u64,
+}
+
+// these methods just call a worker method, so the public API
+// is easy to read and digest.  Please keep it that way.
+impl WalletMigration {
+    /// prepare migration of the wallet in data directory `from` into the
+    /// fresh data directory `to`.
+    ///
+    /// nothing is read or written until [WalletMigration::migrate()] is
+    /// called.
+    pub fn new(
+        from: DataDirectory,
+        from_network: Network,
+        to: DataDirectory,
+        to_network: Network,
+    ) -> Self {
+        Self {
+            from,
+            from_network,
+            to,
+            to_network,
+        }
+    }
+
+    /// perform the migration and verify its integrity.
+    ///
+    /// see [WalletMigration] for the checks performed.
+    pub async fn migrate(&self) -> Result<WalletMigrationReport, WalletMigrationError> {
+        worker::migrate(self).await
+    }
+}
+
+mod worker {
+    use super::*;
+
+    // files leveldb rewrites on its own when a database is opened.  they
+    // carry no wallet data, so they are exempt from byte comparison.
+    const LEVELDB_VOLATILE_FILES: [&str; 3] = ["LOCK", "LOG", "LOG.old"];
+
+    pub(super) async fn migrate(
+        m: &WalletMigration,
+    ) -> Result<WalletMigrationReport, WalletMigrationError> {
+        check_networks(m).await?;
+        check_locations(m)?;
+
+        let source_db = open_wallet_db(&m.from.wallet_database_dir_path()).await?;
+        let wallet_db = match &source_db {
+            Some(db) => Some(WalletDbSnapshot::read(db).await),
+            None => None,
+        };
+
+        // leveldb's lock on the source would make windows refuse the copy.
+        #[cfg(target_os = "windows")]
+        drop(source_db);
+
+        let result = copy_and_verify(m, wallet_db).await;
+        if result.is_err() {
+            remove_copies(m);
+        }
+        result
+    }
+
+    async fn check_networks(m: &WalletMigration) -> Result<(), WalletMigrationError> {
+        if m.from_network != m.to_network {
+            return Err(WalletMigrationError::IncompatibleNetworks {
+                from: m.from_network,
+                to: m.to_network,
+            });
+        }
+        check_recorded_network(&m.from, m.from_network).await?;
+        check_recorded_network(&m.to, m.to_network).await
+    }
+
+    async fn check_recorded_network(
+        data_dir: &DataDirectory,
+        expected: Network,
+    ) -> Result<(), WalletMigrationError> {
+        match data_dir.recorded_network().await? {
+            Some(recorded) if recorded != expected => Err(WalletMigrationError::WrongNetwork {
+                data_dir: data_dir.to_string(),
+                recorded,
+                expected,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_locations(m: &WalletMigration) -> Result<(), WalletMigrationError> {
+        if m.from.root_dir_path() == m.to.root_dir_path() {
+            return Err(WalletMigrationError::SameDataDirectory);
+        }
+
+        let wallet_secret_path =
+            WalletFileContext::wallet_secret_path(&m.from.wallet_directory_path());
+        if !wallet_secret_path.exists() {
+            return Err(WalletMigrationError::SourceWalletMissing(
+                wallet_secret_path.display().to_string(),
+            ));
+        }
+
+        match copied_dirs(m).into_iter().find(|(_, to)| to.exists()) {
+            Some((_, to)) => Err(WalletMigrationError::DestinationNotEmpty(
+                to.display().to_string(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    // (source, destination) of every directory that is part of a wallet.
+    fn copied_dirs(m: &WalletMigration) -> [(PathBuf, PathBuf); 3] {
+        [
+            (m.from.wallet_directory_path(), m.to.wallet_directory_path()),
+            (
+                m.from.wallet_database_dir_path(),
+                m.to.wallet_database_dir_path(),
+            ),
+            (
+                m.from.utxo_transfer_directory_path(),
+                m.to.utxo_transfer_directory_path(),
+            ),
+        ]
+    }
+
+    async fn open_wallet_db(
+        path: &Path,
+    ) -> Result<Option<RustyWalletDatabase>, WalletMigrationError> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let database_error = |reason: String| WalletMigrationError::Database {
+            path: path.display().to_string(),
+            reason,
+        };
+        // only ever read an existing database, never create an empty one.
+        let mut options = db_options(DbStore::Wallet);
+        options.create_if_missing = false;
+        let db = NeptuneLevelDb::new(path, &options)
+            .await
+            .map_err(|e| database_error(e.to_string()))?;
+        let wallet_db = RustyWalletDatabase::try_connect(db)
+            .await
+            .map_err(|e| database_error(e.to_string()))?;
+
+        Ok(Some(wallet_db))
+    }
+
+    async fn copy_and_verify(
+        m: &WalletMigration,
+        wallet_db: Option<WalletDbSnapshot>,
+    ) -> Result<WalletMigrationReport, WalletMigrationError> {
+        let mut report = WalletMigrationReport {
+            network: m.to_network,
+            from_wallet_dir: m.from.wallet_directory_path(),
+            to_wallet_dir: m.to.wallet_directory_path(),
+            wallet_db,
+            files_verified: 0,
+            bytes_verified: 0,
+        };
+
+        for (from, to) in copied_dirs(m).into_iter().filter(|(from, _)| from.exists()) {
+            tracing::info!("migrating {} to {}", from.display(), to.display());
+            crate::copy_dir_recursive(&from, &to)?;
+            verify_files(&from, &to, &mut report)?;
+        }
+
+        verify_wallet_secret(m)?;
+        verify_wallet_db(m, wallet_db).await?;
+
+        DataDirectory::create_dir_if_not_exists(&m.to.root_dir_path()).await?;
+        m.to.ensure_network(m.to_network).await?;
+
+        Ok(report)
+    }
+
+    fn verify_files(
+        from: &Path,
+        to: &Path,
+        report: &mut WalletMigrationReport,
+    ) -> Result<(), WalletMigrationError> {
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            let (from, to) = (entry.path(), to.join(entry.file_name()));
+            if from.is_dir() {
+                verify_files(&from, &to, report)?;
+            } else if !is_leveldb_volatile(&from) {
+                report.bytes_verified += verify_file(&from, &to)?;
+                report.files_verified += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn is_leveldb_volatile(path: &Path) -> bool {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| LEVELDB_VOLATILE_FILES.contains(&name))
+    }
+
+    fn verify_file(from: &Path, to: &Path) -> Result<u64, WalletMigrationError> {
+        let contents = std::fs::read(from)?;
+        if std::fs::read(to)? != contents {
+            return Err(WalletMigrationError::VerificationFailed(format!(
+                "{} differs from {}",
+                to.display(),
+                from.display()
+            )));
+        }
+        Ok(contents.len() as u64)
+    }
+
+    fn verify_wallet_secret(m: &WalletMigration) -> Result<(), WalletMigrationError> {
+        let from = WalletFile::read_from_file(&WalletFileContext::wallet_secret_path(
+            &m.from.wallet_directory_path(),
+        ))?;
+        let to = WalletFile::read_from_file(&WalletFileContext::wallet_secret_path(
+            &m.to.wallet_directory_path(),
+        ))?;
+        if from != to {
+            return Err(WalletMigrationError::VerificationFailed(
+                "wallet secret differs".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn verify_wallet_db(
+        m: &WalletMigration,
+        expected: Option<WalletDbSnapshot>,
+    ) -> Result<(), WalletMigrationError> {
+        let migrated = match open_wallet_db(&m.to.wallet_database_dir_path()).await? {
+            Some(db) => Some(WalletDbSnapshot::read(&db).await),
+            None => None,
+        };
+        if migrated != expected {
+            return Err(WalletMigrationError::VerificationFailed(format!(
+                "wallet database differs. expected {expected:?}, found {migrated:?}"
+            )));
+        }
+        Ok(())
+    }
+
+    // only removes what check_locations() guaranteed was absent beforehand.
+    fn remove_copies(m: &WalletMigration) {
+        for (_, to) in copied_dirs(m).into_iter().filter(|(_, to)| to.exists()) {
+            if let Err(e) = std::fs::remove_dir_all(&to) {
+                tracing::warn!("could not remove partial migration {}: {e}", to.display());
+            }
+        }
+    }
+
+    impl WalletDbSnapshot {
+        pub(super) async fn read(db: &RustyWalletDatabase) -> Self {
+            Self {
+                schema_version: db.schema_version(),
+                sync_label: db.get_sync_label(),
+                output_counter: db.get_counter(),
+                generation_key_counter: db.get_generation_key_counter(),
+                symmetric_key_counter: db.get_symmetric_key_counter(),
+                num_monitored_utxos: db.monitored_utxos().len().await,
+                num_expected_utxos: db.num_expected_utxos().await,
+                num_sent_transactions: db.sent_transactions().len().await,
+            }
+        }
+    }
+}
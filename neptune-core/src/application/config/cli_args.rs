@@ -1,3 +1,4 @@
+use std::ffi::OsString;
 use std::net::IpAddr;
 use std::net::SocketAddr;
 use std::num::NonZero;
@@ -9,25 +10,40 @@ use std::time::Duration;
 use bytesize::ByteSize;
 use clap::builder::RangedI64ValueParser;
 use clap::builder::TypedValueParser;
+use clap::CommandFactory;
+use clap::FromArgMatches;
 use clap::Parser;
 use itertools::Itertools;
 use num_traits::Zero;
 use sysinfo::System;
+use tasm_lib::prelude::Digest;
 use tracing::error;
 
+use super::config_file;
+use super::config_file::ConfigFile;
+use super::config_file::CONFIG_FILE_ARG;
 use super::fee_notification_policy::FeeNotificationPolicy;
 use super::network::Network;
 use crate::application::config::triton_vm_env_vars::TritonVmEnvVars;
 use crate::application::config::tx_upgrade_filter::TxUpgradeFilter;
+use crate::application::database::open_files::StoreOpenFiles;
 use crate::application::json_rpc::core::api::ops::Namespace;
+use crate::application::loops::channel::backpressure::OverflowPolicy;
+use crate::application::plugin_hooks::PluginHook;
+use crate::application::rpc::request_limiter::RpcMethodTimeout;
 use crate::application::triton_vm_job_queue::TritonVmJobPriority;
 use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
 use crate::protocol::peer::transfer_transaction::TransactionProofQuality;
 use crate::protocol::proof_abstractions::tasm::program::TritonVmProofJobOptions;
 use crate::protocol::proof_abstractions::tasm::prover_job::ProverJobSettings;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+use crate::state::mempool::orphan_pool::OrphanPoolLimits;
 use crate::state::mining::block_proposal::BlockProposalRejectError;
+use crate::state::proof_upgrade_schedule::UpgradeWindow;
 use crate::state::transaction::tx_proving_capability::TxProvingCapability;
 use crate::state::wallet::scan_mode_configuration::ScanModeConfiguration;
+use crate::state::wallet::symmetric_key_rotation::SymmetricKeyRotationPolicy;
+use crate::state::wallet::timestamp_fuzzing::TimestampFuzzingPolicy;
 
 const MAX_NUM_INPUTS_FOR_PC_BACKED_TXS: u64 = 200;
 
@@ -35,6 +51,15 @@ const MAX_NUM_INPUTS_FOR_PC_BACKED_TXS: u64 = 200;
 #[derive(Parser, Debug, Clone)]
 #[clap(author, version, about)]
 pub struct Args {
+    /// A TOML file with settings for the other command-line arguments
+    ///
+    /// Every key is the long name of an argument, with dashes or underscores,
+    /// e.g. `max_num_peers = 12`. Flags are enabled with `true`, and arguments
+    /// that can be repeated take an array, e.g. `peers = ["1.2.3.4:9798"]`.
+    /// Arguments given on the command line take precedence over the file.
+    #[clap(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
     /// The data directory that contains the wallet and blockchain state
     ///
     /// The default varies by operating system, and includes the network, e.g.
@@ -80,6 +105,60 @@ pub struct Args {
     /// used here may not contain spaces.
     pub(crate) block_notify: Option<String>,
 
+    /// Run an executable when an event occurs. May be given multiple times.
+    ///
+    /// Events are `new-block`, `wallet-receive`, `tx-confirmed`, `tx-cancelled`,
+    /// and `alert`. The event is written as JSON to the executable's stdin.
+    /// Executables run in the background, with only `PATH` in their
+    /// environment and the system's temporary directory as working directory.
+    /// Their exit codes are logged but otherwise ignored.
+    ///
+    /// E.g. --plugin-hook wallet-receive=/usr/local/bin/on-receive
+    #[clap(long = "plugin-hook", value_name = "EVENT=PATH")]
+    pub(crate) plugin_hooks: Vec<PluginHook>,
+
+    /// Kill plugin hooks that run for longer than this many seconds.
+    #[clap(long, default_value = "10", value_name = "SECONDS", value_parser = duration_from_seconds_str)]
+    pub(crate) plugin_hook_timeout: Duration,
+
+    /// Maximum number of plugin hooks that run at a time. Events that occur
+    /// while this many hooks are running do not trigger any hooks.
+    #[clap(long, default_value = "4")]
+    pub(crate) plugin_hook_max_concurrency: NonZero<usize>,
+
+    /// Raise an alert when no new block was received for this many minutes.
+    ///
+    /// Alert rules are evaluated periodically and are disabled unless
+    /// configured. Fired alerts are logged, passed to `alert` plugin hooks,
+    /// and recorded in the event log. Active alerts are listed, and can be
+    /// silenced, over RPC. The `tip-stall` alert is raised regardless of the
+    /// rules, and the `update-available` alert if release checks are enabled.
+    #[clap(
+        long,
+        value_name = "MINUTES",
+        value_parser = clap::value_parser!(u64).range(1..),
+    )]
+    pub(crate) alert_no_block_minutes: Option<u64>,
+
+    /// Raise an alert when the difficulty of the tip is more than this many
+    /// percent below the highest difficulty of the 100 most recent tips.
+    #[clap(
+        long,
+        value_name = "PERCENT",
+        value_parser = clap::value_parser!(u8).range(1..=100),
+    )]
+    pub(crate) alert_difficulty_drop_percent: Option<u8>,
+
+    /// Raise an alert when the node is connected to fewer than this many
+    /// peers.
+    #[clap(long, value_name = "COUNT")]
+    pub(crate) alert_min_peers: Option<usize>,
+
+    /// Raise an alert when the mempool holds more than this many
+    /// transactions.
+    #[clap(long, value_name = "COUNT")]
+    pub(crate) alert_max_mempool_txs: Option<usize>,
+
     /// Ban connections to this node from IP address.
     ///
     /// This node can still make outgoing connections to IP address.
@@ -133,13 +212,136 @@ pub struct Args {
 
     /// Handshake timeout in seconds.
     ///
-    /// The timeout used for all messages received and sent during the handshake
-    /// protocol for incoming connections. Can be lowered if the client is
-    /// attacked by many connection attempts. Can be raised if client is running
-    /// on a machine with a bad internet connection.
+    /// The time allowed for establishing the TCP connection of an outgoing
+    /// connection, and separately for completing the entire handshake protocol
+    /// of any connection. Can be lowered if the client is attacked by many
+    /// connection attempts. Can be raised if client is running on a machine
+    /// with a bad internet connection.
     #[clap(long, default_value = "4", value_name = "SECONDS")]
     pub(crate) handshake_timeout: u8,
 
+    /// Maximum number of incoming connections that may be handshaking at the
+    /// same time.
+    ///
+    /// Connections that never complete the handshake occupy a slot until
+    /// `--handshake-timeout` passes. Further incoming connections wait for a
+    /// free slot for at most the handshake timeout, and are dropped otherwise.
+    /// Defaults to twice `--max-num-peers`, plus 4.
+    #[clap(long, value_name = "COUNT")]
+    pub(crate) max_pending_handshakes: Option<NonZero<usize>>,
+
+    /// Maximum number of incoming connections from the same IP address that
+    /// may be handshaking at the same time. Further incoming connections from
+    /// that IP address are dropped immediately.
+    ///
+    /// Prevents a single host from occupying all handshake slots with
+    /// connections that send their handshake slowly, or not at all.
+    #[clap(long, default_value = "2", value_name = "COUNT")]
+    pub(crate) max_pending_handshakes_per_ip: NonZero<usize>,
+
+    /// Disable zstd compression of peer messages.
+    ///
+    /// By default, compression is offered in the handshake and used on every
+    /// connection where the peer offers it too. Compression reduces the
+    /// bandwidth spent on blocks and transactions at the cost of some CPU.
+    #[clap(long)]
+    pub(crate) disable_peer_compression: bool,
+
+    /// Minimum size of a peer message for it to be compressed.
+    ///
+    /// Messages smaller than this are sent uncompressed even if compression
+    /// was negotiated, since the savings would not be worth the CPU cost.
+    ///
+    /// Units: B (bytes), K (kilobytes), M (megabytes), G (gigabytes)
+    #[clap(long, default_value = "4K", value_name = "SIZE")]
+    pub(crate) peer_compression_threshold: ByteSize,
+
+    /// Isolate the node's own activity from what it reveals to peers, so that
+    /// peers cannot tell transactions that originate from this node apart from
+    /// relayed ones.
+    ///
+    /// In this mode, handshakes carry no identifier that peers could use to
+    /// link connections to this node, and nodes that do not mine do not reveal
+    /// their tip. Every peer is assigned to relay either transactions or
+    /// blocks, and never learns of the other kind from this node.
+    ///
+    /// The listen port is still advertised, so for the strongest isolation
+    /// combine this flag with `--max-num-peers 0`, which refuses incoming
+    /// connections and stops advertising it.
+    #[clap(long)]
+    pub(crate) plausible_deniability: bool,
+
+    /// Number of peers that a new block is relayed to at once.
+    ///
+    /// Peers are ranked by how often they were the first to announce a new
+    /// block, which indicates that they are well connected. The best-ranked
+    /// peers are relayed to first, and every following batch of peers
+    /// `--block-relay-stagger` later.
+    #[clap(long, default_value = "8", value_name = "PEERS")]
+    pub(crate) block_relay_fan_out: NonZero<usize>,
+
+    /// Time in milliseconds between relaying a new block to consecutive
+    /// batches of peers. Zero relays to all peers at once.
+    #[clap(long, default_value = "100", value_name = "MILLISECONDS", value_parser = duration_from_millis_str)]
+    pub(crate) block_relay_stagger: Duration,
+
+    /// Time in seconds between pings to each peer, for measuring round-trip
+    /// times and clock offsets. Zero disables pinging, but pings from peers
+    /// are still answered.
+    ///
+    /// Among peers that announced equally many new blocks first, those with
+    /// the shortest round-trip times are relayed new blocks first.
+    #[clap(long, default_value = "60", value_name = "SECONDS", value_parser = duration_from_seconds_str)]
+    pub(crate) peer_ping_interval: Duration,
+
+    /// Time in seconds after which a connected peer that has not sent any
+    /// message is disconnected. Zero disables the timeout.
+    ///
+    /// Only applies to peers that are pinged, which are expected to answer
+    /// within every ping interval, so the timeout should be well above
+    /// `--peer-ping-interval`.
+    #[clap(long, default_value = "300", value_name = "SECONDS", value_parser = duration_from_seconds_str)]
+    pub(crate) peer_idle_timeout: Duration,
+
+    /// Ask peers that relay introductions to introduce this node to other
+    /// nodes that cannot accept incoming connections either, eg because they
+    /// are behind NAT, and connect to the introduced nodes by simultaneous
+    /// open.
+    ///
+    /// Only peers that set `--rendezvous-relay` are asked, and only
+    /// introductions to peers that set this flag are accepted. All outgoing
+    /// peer connections are made from `--rendezvous-port`, so that the
+    /// address relays observe for this node is also the one introduced peers
+    /// can reach it on.
+    #[clap(long)]
+    pub(crate) rendezvous: bool,
+
+    /// Local port from which outgoing peer connections are made if
+    /// `--rendezvous` is set. Must differ from the peer port.
+    #[clap(long, default_value = "9801", value_name = "PORT")]
+    pub(crate) rendezvous_port: u16,
+
+    /// Introduce peers that set `--rendezvous` to each other, on their
+    /// request. Introduced peers learn each other's address as observed by
+    /// this node.
+    #[clap(long)]
+    pub(crate) rendezvous_relay: bool,
+
+    /// Trusted peers to exchange encrypted wallet backups with. Disabled if
+    /// empty.
+    ///
+    /// The data needed to recover received UTXOs, which cannot be derived
+    /// from the seed phrase, is encrypted with a key derived from the seed
+    /// and replicated to each listed peer that is connected and lists this
+    /// node in turn. After losing the data directory, restoring the wallet
+    /// from its seed phrase and connecting to any of these peers fetches the
+    /// data back. Keys are never sent. In return, this node holds the
+    /// backups of the listed peers.
+    ///
+    /// Example: `--wallet-backup-peer=8.8.8.8 --wallet-backup-peer=8.8.4.4`
+    #[clap(long = "wallet-backup-peer", value_name = "IP")]
+    pub(crate) wallet_backup_peers: Vec<IpAddr>,
+
     /// Whether to act as bootstrapper node.
     ///
     /// Bootstrapper nodes ensure that the maximum number of peers is never
@@ -159,6 +361,55 @@ pub struct Args {
     #[clap(long, alias = "notx")]
     pub(crate) no_transaction_initiation: bool,
 
+    /// Minimum fee of transactions initiated by this node.
+    ///
+    /// Sends paying a lower fee are refused, rather than broadcast as
+    /// transactions that peers may not relay. Defaults to no minimum.
+    #[clap(long, default_value = "0", value_parser = NativeCurrencyAmount::coins_from_str)]
+    pub(crate) min_send_fee: NativeCurrencyAmount,
+
+    /// Path to a file of receiving addresses that this node refuses to send
+    /// funds to, one bech32m-encoded address per line. Lines starting with `#`
+    /// are ignored.
+    ///
+    /// Denied send attempts are rejected and recorded in
+    /// `send_policy_audit.jsonl` in the data directory. Disabled by default.
+    #[clap(long, value_name = "PATH")]
+    pub(crate) denied_addresses_file: Option<PathBuf>,
+
+    /// Path to a file holding a key derived from a spending PIN, as created by
+    /// `neptune-cli create-spending-pin-file`.
+    ///
+    /// If set, RPC methods that spend funds are refused until the client
+    /// answers a challenge with the PIN. Intended for hosts shared by users
+    /// who can read the RPC cookie, so store the file where only neptune-core
+    /// can read it, apart from the wallet. Responses and spend attempts are
+    /// recorded in `spending_pin_audit.jsonl` in the data directory. Disabled
+    /// by default.
+    #[clap(long, value_name = "PATH")]
+    pub(crate) spending_pin_file: Option<PathBuf>,
+
+    /// Size in bytes above which logs in the data directory, such as
+    /// `send_policy_audit.jsonl` and `spending_pin_audit.jsonl`, are rotated.
+    ///
+    /// The data directory is cleaned up hourly, and on request over RPC.
+    #[clap(long, default_value = "10485760", value_name = "BYTES")]
+    pub(crate) max_log_file_size: u64,
+
+    /// Time in seconds that rotated logs are kept.
+    #[clap(long, default_value = "2592000", value_name = "SECONDS", value_parser = duration_from_seconds_str)]
+    pub(crate) log_retention: Duration,
+
+    /// Time in seconds that backups of databases, made before migrating them
+    /// to a new schema, are kept. Kept forever if not set.
+    #[clap(long, value_name = "SECONDS", value_parser = duration_from_seconds_str)]
+    pub(crate) migration_backup_retention: Option<Duration>,
+
+    /// Time in seconds after which temporary files orphaned by interrupted
+    /// writes are deleted.
+    #[clap(long, default_value = "86400", value_name = "SECONDS", value_parser = duration_from_seconds_str)]
+    pub(crate) temporary_file_retention: Duration,
+
     /// Specify environment variables for Triton VM for a given (log2 of) the
     /// padded height. Can be used to control the environment variables
     /// `TVM_LDE_TRACE` and `RAYON_NUM_THREADS` as a function of the proof's
@@ -202,6 +453,41 @@ pub struct Args {
     #[arg(long, default_value = "1:0")]
     pub(crate) tx_upgrade_filter: TxUpgradeFilter,
 
+    /// If [`Self::tx_proof_upgrading`] is set, only start upgrading proofs of
+    /// 3rd party transactions within this daily window, in local time. May be
+    /// given multiple times. A window may wrap around midnight.
+    ///
+    /// E.g. --proof-upgrade-window 01:00-06:00
+    ///
+    /// If not set, upgrading may start at any time of day. Can be changed at
+    /// runtime with the `set_proof_upgrade_schedule` RPC.
+    #[clap(long, value_name = "HH:MM-HH:MM")]
+    pub(crate) proof_upgrade_window: Vec<UpgradeWindow>,
+
+    /// If [`Self::tx_proof_upgrading`] is set, do not start upgrading proofs of
+    /// 3rd party transactions while the CPU usage of this node, in percent
+    /// across all cores, is at or above this value.
+    ///
+    /// CPU usage is sampled every 20 seconds, and includes the usage of this
+    /// node's own proving. So a low value may leave a gap between consecutive
+    /// upgrades. Can be changed at runtime with the
+    /// `set_proof_upgrade_schedule` RPC.
+    #[clap(
+        long,
+        value_name = "PERCENT",
+        value_parser = clap::value_parser!(u8).range(1..=100),
+    )]
+    pub(crate) proof_upgrade_max_cpu: Option<u8>,
+
+    /// By default, transactions initiated by this node's wallet take a
+    /// priority lane: their proving jobs jump ahead of all other jobs in the
+    /// proving queue, and a node that can produce single proofs upgrades and
+    /// relays them as soon as they are initiated, regardless of
+    /// `--tx-proof-upgrading`. If this flag is set, they are queued like
+    /// other transactions.
+    #[clap(long)]
+    pub(crate) no_own_tx_priority: bool,
+
     /// Determines the fraction of the transaction fee consumed by this node as
     /// a reward either for upgrading transaction proofs. Ignored unless
     /// proof upgrading is activated.
@@ -226,6 +512,231 @@ pub struct Args {
     #[clap(long, default_value = "0.0005", value_parser = NativeCurrencyAmount::coins_from_str)]
     pub(crate) min_relay_pctx_fee_per_input: NativeCurrencyAmount,
 
+    /// Start in safe mode, in which the node neither composes, guesses, nor
+    /// upgrades transaction proofs, and maintains at most 2 peers. The RPC
+    /// server runs as usual, for diagnosing the node.
+    ///
+    /// Safe mode is also entered automatically after repeated crashes, see
+    /// `--safe-mode-crash-threshold`.
+    #[clap(long)]
+    pub(crate) safe_mode: bool,
+
+    /// Number of consecutive runs that end without a clean run, after which
+    /// the node starts in safe mode.
+    ///
+    /// A run is clean once the node has run for 10 minutes, or shut down
+    /// gracefully. A clean run in safe mode ends safe mode at the next
+    /// restart.
+    #[clap(
+        long,
+        default_value = "3",
+        value_name = "COUNT",
+        value_parser = clap::value_parser!(u32).range(1..),
+    )]
+    pub(crate) safe_mode_crash_threshold: u32,
+
+    /// Enter load-shedding mode under resource pressure.
+    ///
+    /// If set, the node enters load-shedding mode when its own CPU or memory
+    /// usage, or the usage of the disk holding its data directory, exceeds the
+    /// thresholds set with `--load-shedding-*-threshold`. In this mode, the
+    /// node pauses transaction-proof upgrading, maintains at most
+    /// `--load-shedding-max-num-peers` peers, and rejects transactions paying
+    /// less than `--load-shedding-min-fee`. The mode is exited when the
+    /// pressure subsides.
+    #[clap(long)]
+    pub(crate) load_shedding: bool,
+
+    /// CPU usage of this node, in percent across all cores, above which the
+    /// node enters load-shedding mode.
+    #[clap(
+        long,
+        default_value = "95",
+        value_name = "PERCENT",
+        value_parser = clap::value_parser!(u8).range(1..=100),
+    )]
+    pub(crate) load_shedding_cpu_threshold: u8,
+
+    /// Memory usage of this node, in percent of `--load-shedding-memory-limit`
+    /// or, if no limit is set, of total memory, above which the node enters
+    /// load-shedding mode.
+    #[clap(
+        long,
+        default_value = "90",
+        value_name = "PERCENT",
+        value_parser = clap::value_parser!(u8).range(1..=100),
+    )]
+    pub(crate) load_shedding_memory_threshold: u8,
+
+    /// Memory budget of this node, relative to which
+    /// `--load-shedding-memory-threshold` applies. Defaults to total memory.
+    ///
+    /// Units: B (bytes), K (kilobytes), M (megabytes), G (gigabytes)
+    ///
+    /// E.g. --load-shedding-memory-limit 4G
+    #[clap(long, value_name = "SIZE")]
+    pub(crate) load_shedding_memory_limit: Option<ByteSize>,
+
+    /// Usage of the disk holding the data directory, in percent of its
+    /// capacity, above which the node enters load-shedding mode.
+    #[clap(
+        long,
+        default_value = "95",
+        value_name = "PERCENT",
+        value_parser = clap::value_parser!(u8).range(1..=100),
+    )]
+    pub(crate) load_shedding_disk_threshold: u8,
+
+    /// Maximum number of peers to maintain while in load-shedding mode.
+    ///
+    /// Surplus connections are closed when the mode is entered, incoming ones
+    /// first. Connections to peers specified with `--peer` are kept.
+    #[clap(long, default_value = "4", value_name = "COUNT")]
+    pub(crate) load_shedding_max_num_peers: usize,
+
+    /// Minimum fee of transactions accepted from peers while in load-shedding
+    /// mode.
+    #[clap(long, default_value = "0.01", value_parser = NativeCurrencyAmount::coins_from_str)]
+    pub(crate) load_shedding_min_fee: NativeCurrencyAmount,
+
+    /// Publish a signed checkpoint of the canonical chain every this many
+    /// blocks.
+    ///
+    /// A checkpoint consists of the digest, height, cumulative proof-of-work,
+    /// and mutator set hash of the canonical block at every height that is a
+    /// multiple of the interval. Light clients can fetch checkpoints over RPC
+    /// and compare them across independent nodes. Signing produces a small
+    /// proof. Disabled by default.
+    #[clap(long, value_name = "BLOCKS")]
+    pub(crate) checkpoint_interval: Option<NonZero<u64>>,
+
+    /// Periodically fetch the signed release manifest at this URL and report
+    /// whether a newer version is available, and whether it must be installed
+    /// before some block height.
+    ///
+    /// Only `http://` URLs are supported. Manifests are only trusted if signed
+    /// by `--release-manifest-signer`, and if they are for the node's network.
+    /// The node never updates itself. The outcome is logged, raised as an
+    /// alert, and available over RPC. Disabled by default.
+    #[clap(long, value_name = "URL", requires = "release_manifest_signer")]
+    pub(crate) release_manifest_url: Option<String>,
+
+    /// Hex-encoded public key of the signer of the release manifests fetched
+    /// from `--release-manifest-url`, as published by the release maintainers.
+    #[clap(long, value_name = "DIGEST", value_parser = Digest::try_from_hex)]
+    pub(crate) release_manifest_signer: Option<Digest>,
+
+    /// Accept connections from standby nodes on this address, and serve them
+    /// validated blocks and expected UTXOs.
+    ///
+    /// Standbys must prove knowledge of the secret in
+    /// `--replication-secret-file`.
+    #[clap(long, value_name = "ADDRESS", requires = "replication_secret_file")]
+    pub(crate) replication_listen_addr: Option<SocketAddr>,
+
+    /// Run as a standby of the primary node whose replication listener is at
+    /// this address.
+    ///
+    /// A standby continuously replicates blocks and expected UTXOs from its
+    /// primary, and refuses to initiate transactions until it is promoted
+    /// over RPC. Promotion demotes the primary first, so that the two nodes,
+    /// which share a wallet, never both spend.
+    #[clap(long, value_name = "ADDRESS", requires = "replication_secret_file")]
+    pub(crate) standby_of: Option<SocketAddr>,
+
+    /// File containing the secret shared by a primary and its standbys, of at
+    /// least 16 bytes.
+    #[clap(long, value_name = "PATH")]
+    pub(crate) replication_secret_file: Option<PathBuf>,
+
+    /// Delete the bodies and proofs of abandoned fork blocks that are more
+    /// than this many blocks below the tip, retaining their headers.
+    ///
+    /// Fork blocks are stored blocks that do not belong to the canonical
+    /// chain. Without pruning, they are kept forever. Pruned blocks can no
+    /// longer be served to peers, so the depth should exceed any expected
+    /// reorganization. Only applies to archival nodes. Disabled by default.
+    #[clap(long, value_name = "BLOCKS")]
+    pub(crate) fork_pruning_depth: Option<NonZero<u64>>,
+
+    /// Retain only the headers of blocks, and the bodies and proofs of the
+    /// most recent blocks, for deployments with little disk space.
+    ///
+    /// Blocks are still downloaded and validated in full, but their bodies
+    /// and proofs are deleted once they are more than 100 blocks below the
+    /// tip. Deeper reorganizations cannot be followed, old blocks cannot be
+    /// served to peers, and the wallet cannot find its UTXOs in blocks whose
+    /// bodies were deleted. Membership proofs for such UTXOs, eg obtained from
+    /// an archival node, can be submitted to the wallet instead.
+    #[clap(long)]
+    pub(crate) headers_only: bool,
+
+    /// Store block files in this directory instead of in the data directory,
+    /// so that several nodes on the same host can share them.
+    ///
+    /// Exactly one node writes to a shared block directory, and locks it for
+    /// as long as it runs. All other nodes sharing the directory must be
+    /// started with `--shared-block-dir-read-only`. Every node still
+    /// validates blocks and keeps its own databases; only the block files,
+    /// which make up most of the disk usage, are shared. A node must use the
+    /// same block directory for as long as it keeps its data directory.
+    #[clap(long, value_name = "DIR", conflicts_with = "headers_only")]
+    pub(crate) shared_block_dir: Option<PathBuf>,
+
+    /// Only read from the shared block directory, which the node writing to
+    /// it must have created.
+    ///
+    /// Blocks received by this node are indexed once the writing node has
+    /// stored them, so the writing node should be kept in sync. Nothing is
+    /// written to the directory, so it can be mounted read-only.
+    #[clap(
+        long,
+        requires = "shared_block_dir",
+        conflicts_with = "fork_pruning_depth"
+    )]
+    pub(crate) shared_block_dir_read_only: bool,
+
+    /// How far below the tip, in blocks, the wallet's historical balance may
+    /// be queried.
+    ///
+    /// Each query replays the canonical blocks between the queried height and
+    /// the tip, so the bound keeps accounting queries from reaching
+    /// arbitrarily far back. Only applies to archival nodes.
+    #[clap(long, default_value = "100000", value_name = "BLOCKS")]
+    pub(crate) max_historical_balance_depth: u64,
+
+    /// Total number of files the databases may keep open.
+    ///
+    /// The budget is split among the databases, with the largest shares going
+    /// to the block index and the mutator set. At startup, the process's soft
+    /// limit on open files is raised as far as the hard limit permits to
+    /// accommodate the budget, peer connections, and other files. If not set,
+    /// the databases get up to 2048 files, or less if the limit on open files
+    /// does not permit that.
+    #[clap(long, value_name = "FILES")]
+    pub(crate) max_open_files: Option<u64>,
+
+    /// Number of files a single database may keep open, taken from the total
+    /// budget of `--max-open-files`.
+    ///
+    /// Databases are `block-index`, `mutator-set`, `archival-block-mmr`,
+    /// `wallet`, and `peer-standings`. May be given once per database. At
+    /// least 74 files are needed.
+    ///
+    /// E.g. --db-max-open-files wallet=200
+    #[clap(long, value_name = "STORE=FILES")]
+    pub(crate) db_max_open_files: Vec<StoreOpenFiles>,
+
+    /// Memory, in MiB, for caching recently read mutator set chunks and MMR
+    /// nodes.
+    ///
+    /// Applying a block reads the same Bloom filter chunks and MMR nodes
+    /// repeatedly. The memory is split among the cached tables, with the
+    /// largest share going to the chunks of the mutator set. Hit ratios are
+    /// available over RPC. Set to 0 to disable caching.
+    #[clap(long, default_value = "64", value_name = "MIB")]
+    pub(crate) db_read_cache_size: usize,
+
     /// Whether to produce block proposals, which is the 2nd step of three-step
     /// mining. Note that composing block proposals involves the computationally
     /// expensive task of producing STARK proofs. You should have plenty of
@@ -246,6 +757,68 @@ pub struct Args {
     #[clap(long)]
     pub(crate) secret_compositions: bool,
 
+    /// When composing, the number of target block intervals after which an
+    /// own block proposal that has not resulted in a block is considered
+    /// expired.
+    ///
+    /// An expired proposal may indicate that guessers withhold the solution.
+    /// When a proposal expires, a warning is logged and the proposal is
+    /// re-broadcast to peers, unless `--secret-compositions` is set. This is
+    /// repeated every horizon until a new block arrives.
+    #[clap(long, default_value = "3", value_name = "INTERVALS")]
+    pub(crate) proposal_expiry_horizon: NonZero<u32>,
+
+    /// When composing, the number of reorganizations that orphan the block
+    /// being composed on that are tolerated within `--reorg-compose-cooldown`
+    /// before composition is suspended.
+    ///
+    /// Every such reorganization wastes the proving effort of the current
+    /// composition. Suspending composition prevents the composer from
+    /// thrashing while competing chains keep replacing each other. With a
+    /// budget of zero, composition is suspended after every reorganization.
+    #[clap(long, default_value = "2", value_name = "COUNT")]
+    pub(crate) reorg_compose_budget: usize,
+
+    /// The period in seconds over which `--reorg-compose-budget` applies.
+    /// Suspended composition resumes once no more reorganizations than the
+    /// budget occurred within this period. Zero disables the policy.
+    #[clap(long, default_value = "600", value_name = "SECONDS", value_parser = duration_from_seconds_str)]
+    pub(crate) reorg_compose_cooldown: Duration,
+
+    /// A directory shared by the composers of a cluster, eg on a network file
+    /// system, through which they agree on which of them composes.
+    ///
+    /// Before composing on a block, a composer acquires a lease on that block
+    /// in this directory. Only the holder of the lease composes, the others
+    /// stand by. If the holder fails to renew its lease, eg because it
+    /// crashed, one of the standbys takes over once the lease expires. The
+    /// clocks of the composers must be synchronized.
+    #[clap(long, value_name = "DIR")]
+    pub(crate) composer_lease_dir: Option<PathBuf>,
+
+    /// The number of seconds a composer lease lasts unless renewed. A standby
+    /// takes over from a failed composer after at most this long.
+    #[clap(long, default_value = "60", value_name = "SECONDS", value_parser = duration_from_seconds_str)]
+    pub(crate) composer_lease_duration: Duration,
+
+    /// When composing, exclude mempool transactions carrying an announcement
+    /// longer than this number of field elements.
+    ///
+    /// Excluded transactions are recorded in `composer_inclusion_audit.jsonl`
+    /// in the data directory. Disabled by default.
+    #[clap(long, value_name = "ELEMENTS")]
+    pub(crate) max_composed_announcement_size: Option<usize>,
+
+    /// Path to a file of announcement tags that, when composing, mempool
+    /// transactions must not carry. Each line holds either a receiver
+    /// identifier or a bech32m-encoded address, whose receiver identifier is
+    /// excluded. Lines starting with `#` are ignored.
+    ///
+    /// Excluded transactions are recorded in `composer_inclusion_audit.jsonl`
+    /// in the data directory. Disabled by default.
+    #[clap(long, value_name = "PATH")]
+    pub(crate) excluded_announcements_file: Option<PathBuf>,
+
     /// If set, node will only accept block proposals from these IP addresses.
     ///
     /// Multiple IP address can be set in which case the node will accept
@@ -343,13 +916,53 @@ pub struct Args {
     #[clap(long, default_value = "1G", value_name = "SIZE")]
     pub(crate) max_mempool_size: ByteSize,
 
+    /// Keep transactions that do not fit within the mempool's RAM budget on
+    /// disk, up to this size, instead of forgetting them.
+    ///
+    /// Only single-proof backed transactions that do not concern this node's
+    /// wallet are kept on disk. They are read back when selected for a block
+    /// or requested by a peer, and moved back into RAM once there is room.
+    /// Zero disables the disk tier.
+    ///
+    /// Units: B (bytes), K (kilobytes), M (megabytes), G (gigabytes)
+    ///
+    /// E.g. --max-mempool-spillover-size 5G
+    #[clap(long, default_value = "0", value_name = "SIZE")]
+    pub(crate) max_mempool_spillover_size: ByteSize,
+
+    /// Maximum number of transactions kept while waiting for the block they
+    /// are synced to.
+    ///
+    /// Peers that have applied a block this node has not seen yet send
+    /// transactions synced to the mutator set after that block. Such
+    /// transactions are kept until the block arrives, and then moved into the
+    /// mempool. Zero drops them instead.
+    #[clap(long, default_value = "100", value_name = "COUNT")]
+    pub(crate) max_orphan_transactions: usize,
+
+    /// Maximum number of transactions kept from any one peer while waiting
+    /// for the block they are synced to.
+    #[clap(long, default_value = "10", value_name = "COUNT")]
+    pub(crate) max_orphan_transactions_per_peer: usize,
+
+    /// Time in seconds after which a transaction waiting for the block it is
+    /// synced to is dropped.
+    #[clap(long, default_value = "600", value_name = "SECONDS", value_parser = duration_from_seconds_str)]
+    pub(crate) orphan_transaction_expiry: Duration,
+
     /// Port on which to listen for peer connections.
-    #[clap(long, default_value = "9798", value_name = "PORT")]
-    pub peer_port: u16,
+    ///
+    /// Defaults to 9798 on main net. Other networks default to distinct ports,
+    /// so that nodes on several networks can run side by side.
+    #[clap(long, value_name = "PORT")]
+    pub peer_port: Option<u16>,
 
     /// Port on which to listen for RPC connections.
-    #[clap(long, default_value = "9799", value_name = "PORT")]
-    pub rpc_port: u16,
+    ///
+    /// Defaults to 9799 on main net. Other networks default to distinct ports,
+    /// so that nodes on several networks can run side by side.
+    #[clap(long, value_name = "PORT")]
+    pub rpc_port: Option<u16>,
 
     /// IP on which to listen for peer connections. Will default to all network interfaces, IPv4 and IPv6.
     #[clap(short, long, default_value = "::")]
@@ -382,6 +995,47 @@ pub struct Args {
     #[structopt(long, default_value = "3")]
     pub(crate) number_of_mps_per_utxo: usize,
 
+    /// Reuse the symmetric key handed out for receiving funds, eg for change
+    /// outputs, until it has been handed out this many times. Then derive its
+    /// successor.
+    ///
+    /// Retired keys are kept for scanning only. If neither this nor
+    /// `--symmetric-key-max-age` is set, a new symmetric key is derived every
+    /// time one is needed.
+    #[clap(long, value_name = "COUNT")]
+    pub(crate) symmetric_key_max_uses: Option<NonZero<u64>>,
+
+    /// Reuse the symmetric key handed out for receiving funds, eg for change
+    /// outputs, until it is this many days old. Then derive its successor.
+    ///
+    /// Retired keys are kept for scanning only. If neither this nor
+    /// `--symmetric-key-max-uses` is set, a new symmetric key is derived every
+    /// time one is needed.
+    #[clap(long, value_name = "DAYS")]
+    pub(crate) symmetric_key_max_age: Option<NonZero<u16>>,
+
+    /// Timestamp transactions initiated by this node at a random time within
+    /// this many seconds before initiation, instead of the time of initiation,
+    /// such that the timestamp does not reveal when the sender was active.
+    /// At most 21600 (6 hours); larger values are capped.
+    ///
+    /// Timestamps are never moved into the future, nor before the release
+    /// date of a time-locked input. Since the mempool drops transactions
+    /// older than 72 hours, a fuzzed transaction is dropped earlier by the
+    /// amount it was moved into the past. Disabled by default.
+    #[clap(long, value_name = "SECONDS", value_parser = duration_from_seconds_str)]
+    pub(crate) tx_timestamp_fuzz: Option<Duration>,
+
+    /// Round the timestamps of transactions initiated by this node down to a
+    /// multiple of this many seconds, after fuzzing them by
+    /// `--tx-timestamp-fuzz`. At most 21600 (6 hours); larger values are
+    /// capped.
+    ///
+    /// Rounding only hides the sender among others that round alike. Disabled
+    /// by default.
+    #[clap(long, value_name = "SECONDS", value_parser = duration_from_seconds_str)]
+    pub(crate) tx_timestamp_granularity: Option<Duration>,
+
     /// Configure how complicated proofs this machine is capable of producing.
     /// If no value is set, this parameter is estimated. For privacy, this level
     /// must not be set to [`TxProvingCapability::LockScript`], as this leaks
@@ -402,6 +1056,12 @@ pub struct Args {
     #[clap(skip)]
     pub(crate) tx_proving_capability_cache: OnceLock<TxProvingCapability>,
 
+    /// The settings the node was started with, including defaults and the
+    /// contents of the configuration file, as a configuration file. Set when
+    /// parsed with [`Args::parse_with_config_file`].
+    #[clap(skip)]
+    pub(crate) effective_config: Option<String>,
+
     /// Enable tokio tracing for consumption by the tokio-console application
     /// note: this will attempt to connect to localhost:6669
     #[structopt(long, name = "tokio-console", default_value = "false")]
@@ -430,6 +1090,17 @@ pub struct Args {
     #[clap(long, default_value = "16")]
     pub(crate) max_num_proofs: usize,
 
+    /// Sets the number of Triton VM proofs this machine produces
+    /// simultaneously.
+    ///
+    /// Every proof runs in its own prover process with its own RAM and CPU
+    /// requirements, so only increase this on machines with resources for
+    /// several proofs at once. Proofs that do not depend on each other, like
+    /// the coinbase proof and the transaction merges of a block proposal, are
+    /// then produced in parallel.
+    #[clap(long, default_value = "1")]
+    pub proof_job_workers: NonZero<usize>,
+
     /// Disables the cookie_hint RPC API
     ///
     /// client software can ask for a cookie hint to automatically determine the
@@ -536,6 +1207,16 @@ pub struct Args {
     #[clap(long)]
     pub(crate) scan_keys: Option<usize>,
 
+    /// Scan blocks created before the wallet's birthday.
+    ///
+    /// Wallet files record when their secret seed was generated. In scan mode,
+    /// blocks created more than a day before that time are not scanned, since
+    /// they cannot contain UTXOs belonging to the wallet. Set this flag if the
+    /// seed was in use before its recorded birthday, for instance because the
+    /// wallet file was recreated.
+    #[clap(long)]
+    pub(crate) ignore_wallet_birthday: bool,
+
     /// Enable JSON/HTTP RPC.
     /// You can optionally specify an address and port (default: 127.0.0.1:9797).
     /// If not given, RPC is disabled.
@@ -570,6 +1251,44 @@ pub struct Args {
     /// - Misconfigurations leading to unexpected or unsafe behavior.
     #[clap(long)]
     pub unsafe_rpc: bool,
+
+    /// Maximum number of RPC requests that are executed concurrently, across
+    /// all RPC transports. Requests beyond this limit are rejected rather than
+    /// queued.
+    #[clap(long, default_value = "16", value_name = "COUNT")]
+    pub(crate) rpc_max_concurrent_requests: usize,
+
+    /// Time in seconds after which an RPC request is aborted.
+    ///
+    /// Can be overridden for individual methods with `--rpc-method-timeouts`.
+    #[clap(long, default_value = "60", value_name = "SECONDS", value_parser = duration_from_seconds_str)]
+    pub(crate) rpc_request_timeout: Duration,
+
+    /// Per-method overrides of `--rpc-request-timeout`, as a comma-separated
+    /// list of `METHOD=SECONDS` pairs.
+    ///
+    /// E.g. --rpc-method-timeouts history=300,wallet_getBlocks=120
+    #[clap(long, use_value_delimiter = true, value_name = "METHOD=SECONDS")]
+    pub(crate) rpc_method_timeouts: Vec<RpcMethodTimeout>,
+
+    /// What peer tasks do with messages for the main task when the channel
+    /// to it is full, as it is when the main task falls behind.
+    ///
+    /// `block` waits for capacity. `block-with-timeout` drops the message if
+    /// no capacity frees up within `--channel-send-timeout`.
+    /// `shed-lowest-priority` additionally drops low-priority messages, like
+    /// relayed transactions, immediately. `spill-to-disk` additionally writes
+    /// blocks to the data directory, to be delivered once the channel has
+    /// drained.
+    ///
+    /// Congestion is reported by the `channel_metrics` RPC endpoint.
+    #[clap(long, value_enum, default_value_t = OverflowPolicy::Block, value_name = "POLICY")]
+    pub(crate) peer_channel_overflow_policy: OverflowPolicy,
+
+    /// Time in seconds after which a message to the main task is dropped,
+    /// under overflow policies other than `block`.
+    #[clap(long, default_value = "10", value_name = "SECONDS", value_parser = duration_from_seconds_str)]
+    pub(crate) channel_send_timeout: Duration,
 }
 
 impl Default for Args {
@@ -579,6 +1298,72 @@ impl Default for Args {
     }
 }
 
+impl Args {
+    /// Parse the arguments of the process, merged with the configuration file
+    /// given by `--config`, if any. Exits the process on error, like
+    /// [`Parser::parse`].
+    pub fn parse_with_config_file() -> Self {
+        Self::try_parse_with_config_file_from(std::env::args_os()).unwrap_or_else(|err| err.exit())
+    }
+
+    /// Like [`Args::parse_with_config_file`], but parses the given arguments
+    /// and returns errors instead of exiting.
+    ///
+    /// Arguments on the command line take precedence over the configuration
+    /// file, which takes precedence over the defaults.
+    pub fn try_parse_with_config_file_from<I, T>(itr: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let command = Self::command();
+        let cli_tokens: Vec<OsString> = itr.into_iter().map(Into::into).collect();
+        let cli_matches = command.clone().try_get_matches_from(&cli_tokens)?;
+
+        let matches = match cli_matches.get_one::<PathBuf>(CONFIG_FILE_ARG) {
+            Some(path) => {
+                let config_file = ConfigFile::load(path, &command).map_err(|err| {
+                    command
+                        .clone()
+                        .error(clap::error::ErrorKind::InvalidValue, err)
+                })?;
+                let file_tokens =
+                    config_file.tokens(|id| config_file::is_set_on_command_line(&cli_matches, id));
+
+                // file settings go right after the binary name, such that
+                // they cannot be mistaken for values of positional arguments
+                let tokens = cli_tokens
+                    .iter()
+                    .take(1)
+                    .cloned()
+                    .chain(file_tokens)
+                    .chain(cli_tokens.iter().skip(1).cloned());
+
+                // Constraints between arguments are only checked here, for the
+                // file and the command line together.
+                command
+                    .clone()
+                    .try_get_matches_from(tokens)
+                    .map_err(|err| {
+                        let is_overridden =
+                            |id: &str| config_file::is_set_on_command_line(&cli_matches, id);
+                        match config_file.locate(&err, path, is_overridden) {
+                            Some(located) => command
+                                .clone()
+                                .error(clap::error::ErrorKind::InvalidValue, located),
+                            None => err,
+                        }
+                    })?
+            }
+            None => cli_matches,
+        };
+
+        let mut args = Self::from_arg_matches(&matches)?;
+        args.effective_config = Some(config_file::effective_config(&command, &matches));
+        Ok(args)
+    }
+}
+
 fn fraction_validator(s: &str) -> Result<f64, String> {
     let value = s
         .parse::<f64>()
@@ -594,6 +1379,10 @@ fn duration_from_seconds_str(s: &str) -> Result<Duration, std::num::ParseIntErro
     Ok(Duration::from_secs(s.parse()?))
 }
 
+fn duration_from_millis_str(s: &str) -> Result<Duration, std::num::ParseIntError> {
+    Ok(Duration::from_millis(s.parse()?))
+}
+
 /// Parses strings that represent ranges of non-negative integers, in either
 /// rust or python (index-range) format.
 ///
@@ -660,15 +1449,51 @@ impl Args {
         if self.disallow_all_incoming_peer_connections() {
             None
         } else {
-            Some(self.peer_port)
+            Some(self.peer_port())
         }
     }
 
+    /// The port on which to listen for peer connections, defaulting to that of
+    /// the network.
+    pub fn peer_port(&self) -> u16 {
+        self.peer_port
+            .unwrap_or_else(|| self.network.default_peer_port())
+    }
+
+    /// The port on which to listen for RPC connections, defaulting to that of
+    /// the network.
+    pub fn rpc_port(&self) -> u16 {
+        self.rpc_port
+            .unwrap_or_else(|| self.network.default_rpc_port())
+    }
+
     /// Whether to engage in mining (composing or guessing or both)
     pub(crate) fn mine(&self) -> bool {
         self.guess || self.compose
     }
 
+    /// The policy for rotating the symmetric key handed out for receiving
+    /// funds.
+    pub(crate) fn symmetric_key_rotation_policy(&self) -> SymmetricKeyRotationPolicy {
+        SymmetricKeyRotationPolicy {
+            max_uses: self.symmetric_key_max_uses.map(|max_uses| max_uses.get()),
+            max_age: self
+                .symmetric_key_max_age
+                .map(|days| Timestamp::days(days.get().into())),
+        }
+    }
+
+    /// The policy for obscuring the timestamps of transactions initiated by
+    /// this node.
+    pub(crate) fn tx_timestamp_fuzzing_policy(&self) -> TimestampFuzzingPolicy {
+        let to_timestamp =
+            |d: Duration| Timestamp::millis(u64::try_from(d.as_millis()).unwrap_or(u64::MAX));
+        TimestampFuzzingPolicy {
+            window: self.tx_timestamp_fuzz.map(to_timestamp),
+            granularity: self.tx_timestamp_granularity.map(to_timestamp),
+        }
+    }
+
     pub(crate) fn proof_job_options(
         &self,
         job_priority: TritonVmJobPriority,
@@ -680,6 +1505,15 @@ impl Args {
         }
     }
 
+    /// The priority of proving jobs for transactions initiated by this node's
+    /// wallet, which would otherwise have priority `default`.
+    pub(crate) fn own_tx_job_priority(&self, default: TritonVmJobPriority) -> TritonVmJobPriority {
+        match self.no_own_tx_priority {
+            true => default,
+            false => TritonVmJobPriority::Highest,
+        }
+    }
+
     /// Get the proving capability CLI argument or estimate it if it is not set.
     /// Cache the result so we don't estimate more than once.
     pub fn proving_capability(&self) -> TxProvingCapability {
@@ -719,6 +1553,20 @@ impl Args {
         Ok(())
     }
 
+    /// Check if wallet backups are exchanged with the peer at this IP
+    /// address.
+    pub(crate) fn is_wallet_backup_peer(&self, ip_address: IpAddr) -> bool {
+        let ip_address = match ip_address {
+            IpAddr::V4(_) => ip_address,
+            IpAddr::V6(v6) => match v6.to_ipv4() {
+                Some(v4) => std::net::IpAddr::V4(v4),
+                None => ip_address,
+            },
+        };
+
+        self.wallet_backup_peers.contains(&ip_address)
+    }
+
     fn estimate_proving_capability() -> TxProvingCapability {
         const SINGLE_PROOF_CORE_REQ: usize = 19;
         // see https://github.com/Neptune-Crypto/neptune-core/issues/426
@@ -752,6 +1600,16 @@ impl Args {
         self.into()
     }
 
+    /// Bounds on the transactions kept while waiting for the block they are
+    /// synced to.
+    pub(crate) fn orphan_pool_limits(&self) -> OrphanPoolLimits {
+        OrphanPoolLimits {
+            max_num_transactions: self.max_orphan_transactions,
+            max_num_transactions_per_peer: self.max_orphan_transactions_per_peer,
+            expiry: self.orphan_transaction_expiry,
+        }
+    }
+
     /// Check if a transaction should be inserted into the mempool and relayed
     /// to peers. Proofcollection-backed transactions that pay too small fees
     /// are not relayed.
@@ -849,14 +1707,100 @@ mod tests {
 
         assert_eq!(1000, default_args.peer_tolerance);
         assert_eq!(10, default_args.max_num_peers);
-        assert_eq!(9798, default_args.peer_port);
-        assert_eq!(9799, default_args.rpc_port);
+        assert_eq!(9798, default_args.peer_port());
+        assert_eq!(9799, default_args.rpc_port());
         assert_eq!(
             IpAddr::from(Ipv6Addr::UNSPECIFIED),
             default_args.peer_listen_addr
         );
         assert_eq!(1, default_args.max_num_compose_mergers.get());
+        assert_eq!(1, default_args.proof_job_workers.get());
+        assert_eq!(3, default_args.proposal_expiry_horizon.get());
+        assert_eq!(2, default_args.reorg_compose_budget);
+        assert_eq!(
+            Duration::from_secs(600),
+            default_args.reorg_compose_cooldown
+        );
+        assert!(default_args.composer_lease_dir.is_none());
+        assert_eq!(
+            Duration::from_secs(60),
+            default_args.composer_lease_duration
+        );
+        assert!(!default_args.no_own_tx_priority);
+        assert!(default_args.max_composed_announcement_size.is_none());
+        assert!(default_args.excluded_announcements_file.is_none());
+        assert!(default_args.checkpoint_interval.is_none());
+        assert!(default_args.release_manifest_url.is_none());
+        assert!(default_args.release_manifest_signer.is_none());
+        assert!(default_args.plugin_hooks.is_empty());
+        assert!(default_args.alert_no_block_minutes.is_none());
+        assert!(default_args.alert_difficulty_drop_percent.is_none());
+        assert!(default_args.alert_min_peers.is_none());
+        assert!(default_args.alert_max_mempool_txs.is_none());
+        assert!(default_args.standby_of.is_none());
+        assert!(default_args.fork_pruning_depth.is_none());
+        assert!(!default_args.headers_only);
+        assert!(default_args.shared_block_dir.is_none());
+        assert!(!default_args.shared_block_dir_read_only);
+        assert_eq!(100_000, default_args.max_historical_balance_depth);
+        assert!(default_args.max_open_files.is_none());
+        assert!(default_args.db_max_open_files.is_empty());
+        assert_eq!(64, default_args.db_read_cache_size);
+        assert!(!default_args.symmetric_key_rotation_policy().is_enabled());
+        assert!(!default_args.tx_timestamp_fuzzing_policy().is_enabled());
+        assert_eq!(
+            OverflowPolicy::Block,
+            default_args.peer_channel_overflow_policy
+        );
         assert_eq!(TxUpgradeFilter::match_all(), default_args.tx_upgrade_filter);
+        assert!(default_args.proof_upgrade_window.is_empty());
+        assert!(default_args.proof_upgrade_max_cpu.is_none());
+        assert!(!default_args.safe_mode);
+        assert_eq!(3, default_args.safe_mode_crash_threshold);
+        assert_eq!(8, default_args.block_relay_fan_out.get());
+        assert_eq!(Duration::from_millis(100), default_args.block_relay_stagger);
+        assert_eq!(Duration::from_secs(60), default_args.peer_ping_interval);
+        assert_eq!(Duration::from_secs(300), default_args.peer_idle_timeout);
+        assert!(default_args.max_pending_handshakes.is_none());
+        assert_eq!(2, default_args.max_pending_handshakes_per_ip.get());
+        assert!(!default_args.rendezvous);
+        assert_eq!(9801, default_args.rendezvous_port);
+        assert!(!default_args.rendezvous_relay);
+        assert!(default_args.wallet_backup_peers.is_empty());
+        assert_eq!(10 * 1024 * 1024, default_args.max_log_file_size);
+        assert_eq!(
+            Duration::from_secs(30 * 24 * 60 * 60),
+            default_args.log_retention
+        );
+        assert!(default_args.migration_backup_retention.is_none());
+        assert_eq!(
+            Duration::from_secs(24 * 60 * 60),
+            default_args.temporary_file_retention
+        );
+        assert_eq!(
+            OrphanPoolLimits::default(),
+            default_args.orphan_pool_limits()
+        );
+    }
+
+    #[test]
+    fn ports_default_to_those_of_network() {
+        let network = Network::Testnet(3);
+        let args = Args {
+            network,
+            ..Default::default()
+        };
+        assert_eq!(network.default_peer_port(), args.peer_port());
+        assert_eq!(network.default_rpc_port(), args.rpc_port());
+
+        let args = Args {
+            network,
+            peer_port: Some(1337),
+            rpc_port: Some(1338),
+            ..Default::default()
+        };
+        assert_eq!(1337, args.peer_port());
+        assert_eq!(1338, args.rpc_port());
     }
 
     #[test]
@@ -868,6 +1812,24 @@ mod tests {
         assert!(args.disallow_all_incoming_peer_connections());
     }
 
+    #[test]
+    fn own_transactions_jump_the_proving_queue_unless_disabled() {
+        let args = Args::default();
+        assert_eq!(
+            TritonVmJobPriority::Highest,
+            args.own_tx_job_priority(TritonVmJobPriority::Normal)
+        );
+
+        let args = Args {
+            no_own_tx_priority: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            TritonVmJobPriority::Normal,
+            args.own_tx_job_priority(TritonVmJobPriority::Normal)
+        );
+    }
+
     #[test]
     fn estimate_own_proving_capability() {
         // doubles as a no-crash test
@@ -973,4 +1935,47 @@ mod tests {
         assert_range_eq!(0u64..10, parse_range(":10").unwrap());
         assert_range_eq!(0u64..=u64::MAX, parse_range(":").unwrap());
     }
+
+    #[test]
+    fn command_line_overrides_config_file_overrides_defaults() {
+        let data_dir = crate::tests::shared::files::unit_test_data_directory(Network::Main)
+            .unwrap()
+            .root_dir_path();
+        std::fs::create_dir_all(&data_dir).unwrap();
+        let config_path = data_dir.join("neptune.toml");
+        std::fs::write(
+            &config_path,
+            "max_num_peers = 3\npeer_tolerance = 7\npeers = [\"127.0.0.1:9798\"]\n",
+        )
+        .unwrap();
+
+        let args = Args::try_parse_with_config_file_from([
+            "neptune-core".into(),
+            "--config".into(),
+            config_path.clone().into_os_string(),
+            "--max-num-peers".into(),
+            "5".into(),
+        ])
+        .unwrap();
+        assert_eq!(5, args.max_num_peers);
+        assert_eq!(7, args.peer_tolerance);
+        assert_eq!(
+            vec!["127.0.0.1:9798".parse::<SocketAddr>().unwrap()],
+            args.peers
+        );
+        assert_eq!(Args::default().network, args.network);
+
+        let effective_config = args.effective_config.unwrap();
+        assert!(effective_config.contains("max_num_peers = \"5\""));
+        assert!(effective_config.contains("peer_tolerance = \"7\""));
+
+        std::fs::write(&config_path, "max_num_peers = \"many\"\n").unwrap();
+        let err = Args::try_parse_with_config_file_from([
+            "neptune-core".into(),
+            "--config".into(),
+            config_path.into_os_string(),
+        ])
+        .unwrap_err();
+        assert!(err.to_string().contains("line 1"));
+    }
 }
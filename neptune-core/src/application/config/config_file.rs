@@ -0,0 +1,403 @@
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::Path;
+use std::path::PathBuf;
+
+use clap::error::ContextKind;
+use clap::error::ContextValue;
+use clap::parser::ValueSource;
+use clap::ArgAction;
+use clap::ArgMatches;
+use clap::Command;
+use itertools::Itertools;
+use toml::Spanned;
+use toml::Value;
+
+/// The id of the command-line argument that points to the configuration
+/// file. It cannot be set from within the file.
+pub(crate) const CONFIG_FILE_ARG: &str = "config";
+
+/// Ways in which a configuration file can be unusable.
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum ConfigFileError {
+    #[error("could not read config file {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("could not parse config file {}: {message}", path.display())]
+    Syntax { path: PathBuf, message: String },
+
+    #[error("{}, line {line}: `{key}`: {message}", path.display())]
+    InvalidEntry {
+        path: PathBuf,
+        line: usize,
+        key: String,
+        message: String,
+    },
+}
+
+/// A single setting of a configuration file, in the form of the command-line
+/// argument(s) it is equivalent to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ConfigEntry {
+    /// The id of the command-line argument.
+    id: String,
+
+    /// The key as written in the file, and the line it is on.
+    key: String,
+    line: usize,
+
+    /// The tokens to append to the command line, e.g. `["--peers", "a",
+    /// "--peers", "b"]`.
+    tokens: Vec<OsString>,
+}
+
+/// A TOML configuration file for the command-line arguments of a [`Command`].
+///
+/// Every top-level key is the long name of a command-line argument, with
+/// either dashes or underscores, and its value is what would be passed on the
+/// command line: a string, number, or boolean, or an array of such for
+/// arguments that can be repeated. Flags are enabled with `true`.
+///
+/// The file is not deserialized into a struct of its own. Instead, every entry
+/// is translated into the command-line tokens it stands for, and these are
+/// parsed by clap together with the actual command line, e.g. into
+/// [`Args`](super::cli_args::Args). Each value is validated on load with the
+/// value parser of its argument, so the file accepts exactly what the command
+/// line accepts.
+///
+/// Constraints between arguments, such as one argument requiring another, can
+/// only be checked once the file is merged with the command line. Errors of
+/// the merged arguments are attributed to the file with [`Self::locate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ConfigFile {
+    entries: Vec<ConfigEntry>,
+}
+
+impl ConfigFile {
+    /// Read and validate the configuration file at `path` against `command`.
+    pub(crate) fn load(path: &Path, command: &Command) -> Result<Self, ConfigFileError> {
+        let contents = std::fs::read_to_string(path).map_err(|source| ConfigFileError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+
+        Self::parse(&contents, path, command)
+    }
+
+    fn parse(contents: &str, path: &Path, command: &Command) -> Result<Self, ConfigFileError> {
+        let table: BTreeMap<Spanned<String>, Spanned<Value>> =
+            toml::from_str(contents).map_err(|err| ConfigFileError::Syntax {
+                path: path.to_owned(),
+                message: err.to_string(),
+            })?;
+
+        let mut entries = vec![];
+        for (key, value) in table {
+            let line = contents[..key.span().start].matches('\n').count() + 1;
+            let invalid = |message: String| ConfigFileError::InvalidEntry {
+                path: path.to_owned(),
+                line,
+                key: key.get_ref().clone(),
+                message,
+            };
+
+            let long = key.get_ref().replace('_', "-");
+            let Some(arg) = command
+                .get_arguments()
+                .find(|arg| arg.get_long() == Some(long.as_str()))
+                .filter(|arg| arg.get_id().as_str() != CONFIG_FILE_ARG)
+                .filter(|arg| {
+                    matches!(
+                        arg.get_action(),
+                        ArgAction::Set | ArgAction::Append | ArgAction::SetTrue
+                    )
+                })
+            else {
+                return Err(invalid("unknown setting".to_owned()));
+            };
+
+            let flag = format!("--{long}");
+            let tokens: Vec<OsString> = match (arg.get_action(), value.get_ref()) {
+                (ArgAction::SetTrue, Value::Boolean(true)) => vec![flag.into()],
+                (ArgAction::SetTrue, Value::Boolean(false)) => vec![],
+                (ArgAction::SetTrue, _) => return Err(invalid("expected a boolean".to_owned())),
+                (ArgAction::Append, Value::Array(values)) => values
+                    .iter()
+                    .map(scalar_to_string)
+                    .map_ok(|value| [OsString::from(&flag), OsString::from(value)])
+                    .flatten_ok()
+                    .collect::<Result<_, _>>()
+                    .map_err(invalid)?,
+                (_, Value::Array(_)) => {
+                    return Err(invalid("does not take multiple values".to_owned()))
+                }
+                (_, value) => vec![
+                    flag.into(),
+                    scalar_to_string(value).map_err(invalid)?.into(),
+                ],
+            };
+
+            // Validate the values on their own, to report errors with the
+            // line they stem from.
+            if !matches!(arg.get_action(), ArgAction::SetTrue) {
+                let value_parser = arg.get_value_parser();
+                for value in tokens.iter().skip(1).step_by(2) {
+                    value_parser
+                        .parse_ref(command, Some(arg), value)
+                        .map_err(|err| invalid(clap_error_message(&err)))?;
+                }
+            }
+
+            entries.push(ConfigEntry {
+                id: arg.get_id().to_string(),
+                key: key.get_ref().clone(),
+                line,
+                tokens,
+            });
+        }
+
+        Ok(Self { entries })
+    }
+
+    /// The command-line tokens equivalent to the file, excluding the settings
+    /// of arguments for which `is_overridden` returns true.
+    pub(crate) fn tokens(&self, is_overridden: impl Fn(&str) -> bool) -> Vec<OsString> {
+        self.entries
+            .iter()
+            .filter(|entry| !is_overridden(&entry.id))
+            .flat_map(|entry| entry.tokens.clone())
+            .collect()
+    }
+
+    /// Attribute an error of the command line merged with [`Self::tokens`] to
+    /// the line of the file that caused it, if any. Settings of arguments for
+    /// which `is_overridden` returns true are not in the merged command line,
+    /// and cannot have caused the error.
+    pub(crate) fn locate(
+        &self,
+        err: &clap::Error,
+        path: &Path,
+        is_overridden: impl Fn(&str) -> bool,
+    ) -> Option<ConfigFileError> {
+        // Missing arguments are reported as several, and cannot stem from
+        // the file.
+        let Some(ContextValue::String(invalid_arg)) = err.get(ContextKind::InvalidArg) else {
+            return None;
+        };
+
+        // e.g. `--fork-pruning-depth <BLOCKS>`
+        let flag = invalid_arg.split_whitespace().next()?;
+        let entry = self
+            .entries
+            .iter()
+            .filter(|entry| !is_overridden(&entry.id))
+            .find(|entry| entry.tokens.first().is_some_and(|token| token == flag))?;
+
+        Some(ConfigFileError::InvalidEntry {
+            path: path.to_owned(),
+            line: entry.line,
+            key: entry.key.clone(),
+            message: clap_error_message(err),
+        })
+    }
+}
+
+fn scalar_to_string(value: &Value) -> Result<String, String> {
+    match value {
+        Value::String(string) => Ok(string.clone()),
+        Value::Integer(integer) => Ok(integer.to_string()),
+        Value::Float(float) => Ok(float.to_string()),
+        Value::Boolean(boolean) => Ok(boolean.to_string()),
+        Value::Datetime(datetime) => Ok(datetime.to_string()),
+        Value::Array(_) | Value::Table(_) => {
+            Err("expected a string, number, or boolean".to_owned())
+        }
+    }
+}
+
+/// The first line of a clap error, without the `error: ` prefix and usage
+/// hints, which refer to the command line rather than the file.
+fn clap_error_message(err: &clap::Error) -> String {
+    let rendered = err.to_string();
+    let first_line = rendered.lines().next().unwrap_or_default();
+    first_line
+        .strip_prefix("error: ")
+        .unwrap_or(first_line)
+        .to_owned()
+}
+
+/// Render the values of all arguments in `matches`, including defaults, as a
+/// configuration file that [`ConfigFile`] accepts.
+///
+/// Arguments without value, such as unset optional arguments, are omitted.
+pub(crate) fn effective_config(command: &Command, matches: &ArgMatches) -> String {
+    let mut table = toml::Table::new();
+    for arg in command.get_arguments() {
+        let id = arg.get_id().as_str();
+        let Some(long) = arg.get_long() else {
+            continue;
+        };
+        if id == CONFIG_FILE_ARG || matches.value_source(id).is_none() {
+            continue;
+        }
+
+        let key = long.replace('-', "_");
+        match arg.get_action() {
+            ArgAction::SetTrue => {
+                table.insert(key, Value::Boolean(matches.get_flag(id)));
+            }
+            ArgAction::Append | ArgAction::Set => {
+                let Some(raw) = matches.get_raw(id) else {
+                    continue;
+                };
+                let mut values = raw
+                    .map(|value| Value::String(value.to_string_lossy().into_owned()))
+                    .collect_vec();
+                if matches!(arg.get_action(), ArgAction::Append) {
+                    table.insert(key, Value::Array(values));
+                } else if let Some(value) = values.pop() {
+                    table.insert(key, value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    toml::to_string(&table).unwrap_or_default()
+}
+
+/// True iff the argument with the given id was set on the command line.
+pub(crate) fn is_set_on_command_line(matches: &ArgMatches, id: &str) -> bool {
+    matches.value_source(id) == Some(ValueSource::CommandLine)
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+    use crate::application::config::cli_args::Args;
+
+    #[test]
+    fn entries_translate_to_command_line_tokens() {
+        let contents = r#"
+            max_num_peers = 3
+            peers = ["127.0.0.1:9798", "127.0.0.1:9799"]
+            guess = true
+            compose = false
+            guesser-fraction = 0.5
+        "#;
+        let config =
+            ConfigFile::parse(contents, Path::new("neptune.toml"), &Args::command()).unwrap();
+
+        let tokens = config
+            .tokens(|id| id == "guesser_fraction")
+            .into_iter()
+            .map(|token| token.into_string().unwrap())
+            .collect_vec();
+        assert_eq!(
+            vec![
+                "--guess",
+                "--max-num-peers",
+                "3",
+                "--peers",
+                "127.0.0.1:9798",
+                "--peers",
+                "127.0.0.1:9799",
+            ],
+            tokens
+        );
+    }
+
+    #[test]
+    fn invalid_entries_are_reported_with_line() {
+        let invalid = |contents: &str| match ConfigFile::parse(
+            contents,
+            Path::new("neptune.toml"),
+            &Args::command(),
+        ) {
+            Err(ConfigFileError::InvalidEntry { line, key, .. }) => (line, key),
+            other => panic!("expected invalid entry, got {other:?}"),
+        };
+
+        assert_eq!(
+            (3, "max_num_peerz".to_owned()),
+            invalid("network = \"main\"\n\nmax_num_peerz = 3\n")
+        );
+        assert_eq!(
+            (2, "guesser_fraction".to_owned()),
+            invalid("\nguesser_fraction = 1.5\n")
+        );
+        assert_eq!((1, "guess".to_owned()), invalid("guess = \"yes\"\n"));
+        assert_eq!(
+            (1, "config".to_owned()),
+            invalid("config = \"other.toml\"\n")
+        );
+        assert_eq!(
+            (1, "network".to_owned()),
+            invalid("network = [\"main\", \"testnet\"]\n")
+        );
+
+        assert!(matches!(
+            ConfigFile::parse(
+                "max_num_peers = ",
+                Path::new("neptune.toml"),
+                &Args::command()
+            ),
+            Err(ConfigFileError::Syntax { .. })
+        ));
+    }
+
+    #[test]
+    fn entries_are_validated_together() {
+        let path = Path::new("neptune.toml");
+        let command = Args::command();
+        let merged = |config: &ConfigFile| {
+            command
+                .clone()
+                .no_binary_name(true)
+                .try_get_matches_from(config.tokens(|_| false))
+        };
+
+        // The second setting requires the first.
+        let contents = "shared_block_dir = \"/blocks\"\nshared_block_dir_read_only = true\n";
+        let config = ConfigFile::parse(contents, path, &command).unwrap();
+        assert!(merged(&config).is_ok());
+
+        // The last two settings conflict.
+        let contents = "max_num_peers = 3\nshared_block_dir = \"/blocks\"\n\
+            shared_block_dir_read_only = true\nfork_pruning_depth = 100\n";
+        let config = ConfigFile::parse(contents, path, &command).unwrap();
+        let err = merged(&config).unwrap_err();
+        assert!(matches!(
+            config.locate(&err, path, |_| false),
+            Some(ConfigFileError::InvalidEntry { line: 3 | 4, .. })
+        ));
+        assert!(config
+            .locate(&err, path, |id| id == "shared_block_dir_read_only"
+                || id == "fork_pruning_depth")
+            .is_none());
+    }
+
+    #[test]
+    fn effective_config_round_trips() {
+        let command = Args::command();
+        let matches = command
+            .clone()
+            .try_get_matches_from(["neptune-core", "--guess", "--peers", "127.0.0.1:9798"])
+            .unwrap();
+        let dumped = effective_config(&command, &matches);
+
+        let config = ConfigFile::parse(&dumped, Path::new("neptune.toml"), &command).unwrap();
+        let reparsed = command
+            .clone()
+            .no_binary_name(true)
+            .try_get_matches_from(config.tokens(|_| false))
+            .unwrap();
+        assert_eq!(dumped, effective_config(&command, &reparsed));
+    }
+}
@@ -1,5 +1,6 @@
 use std::path::Path;
 use std::path::PathBuf;
+use std::str::FromStr;
 
 use anyhow::Context;
 use anyhow::Result;
@@ -23,6 +24,21 @@ use crate::state::wallet::wallet_file::WALLET_OUTPUT_COUNT_DB_NAME;
 const UTXO_TRANSFER_DIRECTORY: &str = "utxo-transfer";
 const RPC_COOKIE_FILE_NAME: &str = ".cookie"; // matches bitcoin-core name.
 const DB_MIGRATION_BACKUPS_DIR: &str = "migration_backups";
+const SEND_POLICY_AUDIT_FILE_NAME: &str = "send_policy_audit.jsonl";
+const SPENDING_PIN_AUDIT_FILE_NAME: &str = "spending_pin_audit.jsonl";
+const COMPOSER_INCLUSION_AUDIT_FILE_NAME: &str = "composer_inclusion_audit.jsonl";
+const NETWORK_MARKER_FILE_NAME: &str = "network";
+const CHANNEL_SPILL_DIRECTORY: &str = "channel_spill";
+const MEMPOOL_SPILLOVER_DIRECTORY: &str = "mempool_spillover";
+const REPLICATION_ROLE_FILE_NAME: &str = "replication_role";
+const WALLET_EVENTS_FILE_NAME: &str = "wallet_events.jsonl";
+const EVENT_CURSORS_FILE_NAME: &str = "event_cursors.json";
+const CHAIN_EXPORTS_DIRECTORY: &str = "exports";
+const CRASH_REPORTS_DIRECTORY: &str = "crash_reports";
+const CRASH_COUNTER_FILE_NAME: &str = "crash_counter";
+const COMPOSER_PAYOUTS_FILE_NAME: &str = "composer_payouts.jsonl";
+const WALLET_BACKUP_FILE_NAME: &str = "wallet_backup.jsonl";
+const HELD_WALLET_BACKUPS_DIRECTORY: &str = "wallet_backups";
 
 // TODO: Add `rusty_leveldb::Options` and `fs::OpenOptions` here too, since they keep being repeated.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -77,6 +93,44 @@ impl DataDirectory {
             .context("open_ensure_parent_dir_exists")
     }
 
+    /// Ensure the data directory belongs to `network`.
+    ///
+    /// The network is recorded in a marker file the first time the directory
+    /// is used. Fails if the directory was previously used by a different
+    /// network, since mixing the state of two networks corrupts both.
+    pub async fn ensure_network(&self, network: Network) -> Result<()> {
+        match self.recorded_network().await? {
+            Some(recorded) => {
+                anyhow::ensure!(
+                    recorded == network,
+                    "Data directory {self} belongs to network {recorded}, not {network}. \
+                    Use a different --data-dir or --network."
+                );
+                Ok(())
+            }
+            None => {
+                let path = self.network_marker_file_path();
+                tokio::fs::write(&path, network.to_string())
+                    .await
+                    .with_context(|| format!("Failed to write {}", path.display()))
+            }
+        }
+    }
+
+    /// The network recorded in the marker file, if the directory has been
+    /// used before.
+    pub async fn recorded_network(&self) -> Result<Option<Network>> {
+        let path = self.network_marker_file_path();
+        match tokio::fs::read_to_string(&path).await {
+            Ok(contents) => Network::from_str(contents.trim())
+                .map(Some)
+                .map_err(anyhow::Error::msg)
+                .with_context(|| format!("Invalid network marker file {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
     ///////////////////////////////////////////////////////////////////////////
     ///
     /// The root data directory path
@@ -117,6 +171,135 @@ impl DataDirectory {
         self.data_dir.join(Path::new(UTXO_TRANSFER_DIRECTORY))
     }
 
+    ///////////////////////////////////////////////////////////////////////////
+    ///
+    /// network marker file path
+    ///
+    /// records the network the data directory belongs to.
+    pub fn network_marker_file_path(&self) -> PathBuf {
+        self.data_dir.join(Path::new(NETWORK_MARKER_FILE_NAME))
+    }
+
+    ///////////////////////////////////////////////////////////////////////////
+    ///
+    /// send-policy audit file path
+    ///
+    /// records sends denied by the recipient policy, one JSON object per line.
+    pub fn send_policy_audit_file_path(&self) -> PathBuf {
+        self.data_dir.join(Path::new(SEND_POLICY_AUDIT_FILE_NAME))
+    }
+
+    ///////////////////////////////////////////////////////////////////////////
+    ///
+    /// spending-PIN audit file path
+    ///
+    /// records spending-PIN responses and spend attempts, one JSON object per
+    /// line.
+    pub fn spending_pin_audit_file_path(&self) -> PathBuf {
+        self.data_dir.join(Path::new(SPENDING_PIN_AUDIT_FILE_NAME))
+    }
+
+    ///////////////////////////////////////////////////////////////////////////
+    ///
+    /// composer inclusion audit file path
+    ///
+    /// records transactions excluded from block proposals by the composer's
+    /// inclusion policy, one JSON object per line.
+    pub fn composer_inclusion_audit_file_path(&self) -> PathBuf {
+        self.data_dir
+            .join(Path::new(COMPOSER_INCLUSION_AUDIT_FILE_NAME))
+    }
+
+    ///////////////////////////////////////////////////////////////////////////
+    ///
+    /// replication role file path
+    ///
+    /// records whether the node is a primary or a standby, once it has been
+    /// promoted or demoted.
+    pub fn replication_role_file_path(&self) -> PathBuf {
+        self.data_dir.join(Path::new(REPLICATION_ROLE_FILE_NAME))
+    }
+
+    ///////////////////////////////////////////////////////////////////////////
+    ///
+    /// wallet events file path
+    ///
+    /// records numbered events caused by new tips, one JSON object per line,
+    /// for replay to subscribers.
+    pub fn wallet_events_file_path(&self) -> PathBuf {
+        self.data_dir.join(Path::new(WALLET_EVENTS_FILE_NAME))
+    }
+
+    ///////////////////////////////////////////////////////////////////////////
+    ///
+    /// event cursors file path
+    ///
+    /// records, per subscriber, the sequence number of the last event replayed
+    /// to it.
+    pub fn event_cursors_file_path(&self) -> PathBuf {
+        self.data_dir.join(Path::new(EVENT_CURSORS_FILE_NAME))
+    }
+
+    ///////////////////////////////////////////////////////////////////////////
+    ///
+    /// held wallet backups directory path
+    ///
+    /// holds the encrypted wallet backups of trusted peers, one file per
+    /// backup.
+    pub fn held_wallet_backups_dir_path(&self) -> PathBuf {
+        self.data_dir.join(Path::new(HELD_WALLET_BACKUPS_DIRECTORY))
+    }
+
+    ///////////////////////////////////////////////////////////////////////////
+    ///
+    /// channel spill directory path
+    ///
+    /// holds block messages from peer tasks that did not fit in the channel to
+    /// the main task. emptied on startup.
+    pub fn channel_spill_dir_path(&self) -> PathBuf {
+        self.data_dir.join(Path::new(CHANNEL_SPILL_DIRECTORY))
+    }
+
+    ///////////////////////////////////////////////////////////////////////////
+    ///
+    /// mempool spillover directory path
+    ///
+    /// holds transactions that did not fit within the mempool's RAM budget.
+    /// emptied on startup.
+    pub fn mempool_spillover_dir_path(&self) -> PathBuf {
+        self.data_dir.join(Path::new(MEMPOOL_SPILLOVER_DIRECTORY))
+    }
+
+    ///////////////////////////////////////////////////////////////////////////
+    ///
+    /// chain exports directory path
+    ///
+    /// holds CSV exports of chain data for offline analytics, one
+    /// subdirectory per exported range of heights.
+    pub fn chain_exports_dir_path(&self) -> PathBuf {
+        self.data_dir.join(Path::new(CHAIN_EXPORTS_DIRECTORY))
+    }
+
+    ///////////////////////////////////////////////////////////////////////////
+    ///
+    /// crash reports directory path
+    ///
+    /// holds one JSON bundle per panic, with a backtrace, recent log lines,
+    /// and the configuration and tip at the time of the panic.
+    pub fn crash_reports_dir_path(&self) -> PathBuf {
+        self.data_dir.join(Path::new(CRASH_REPORTS_DIRECTORY))
+    }
+
+    ///////////////////////////////////////////////////////////////////////////
+    ///
+    /// crash counter file path
+    ///
+    /// records the number of consecutive runs of the node that did not end
+    /// cleanly, to detect crash loops.
+    pub fn crash_counter_file_path(&self) -> PathBuf {
+        self.data_dir.join(Path::new(CRASH_COUNTER_FILE_NAME))
+    }
+
     ///////////////////////////////////////////////////////////////////////////
     ///
     /// The wallet file path
@@ -124,6 +307,27 @@ impl DataDirectory {
         self.data_dir.join(Path::new(WALLET_DIRECTORY))
     }
 
+    /// composer payouts file path
+    ///
+    /// records the composer's off-chain payouts to foreign addresses, one JSON
+    /// object per line. This file lives within
+    /// `DataDirectory::wallet_directory_path()`, as it is required to claim the
+    /// payouts.
+    pub fn composer_payouts_file_path(&self) -> PathBuf {
+        self.wallet_directory_path()
+            .join(Path::new(COMPOSER_PAYOUTS_FILE_NAME))
+    }
+
+    /// wallet backup file path
+    ///
+    /// records the encrypted increments of the wallet's backup to trusted
+    /// peers, one JSON object per line. This file lives within
+    /// `DataDirectory::wallet_directory_path()`.
+    pub fn wallet_backup_file_path(&self) -> PathBuf {
+        self.wallet_directory_path()
+            .join(Path::new(WALLET_BACKUP_FILE_NAME))
+    }
+
     /// The wallet database directory path.
     ///
     /// This directory lives within `DataDirectory::database_dir_path()`.
@@ -235,3 +439,28 @@ impl std::fmt::Display for DataDirectory {
         write!(f, "{}", self.data_dir.display())
     }
 }
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use macro_rules_attr::apply;
+
+    use super::*;
+    use crate::tests::shared::files::unit_test_data_directory;
+    use crate::tests::shared_tokio_runtime;
+
+    #[apply(shared_tokio_runtime)]
+    async fn data_directory_cannot_be_reused_across_networks() {
+        let data_dir = unit_test_data_directory(Network::Main).unwrap();
+        DataDirectory::create_dir_if_not_exists(&data_dir.root_dir_path())
+            .await
+            .unwrap();
+
+        // first use records the network, subsequent uses verify it.
+        data_dir.ensure_network(Network::Main).await.unwrap();
+        data_dir.ensure_network(Network::Main).await.unwrap();
+
+        assert!(data_dir.ensure_network(Network::Testnet(0)).await.is_err());
+        assert!(data_dir.ensure_network(Network::RegTest).await.is_err());
+    }
+}
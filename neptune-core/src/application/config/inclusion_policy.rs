@@ -0,0 +1,304 @@
+//! Rules by which a composer excludes mempool transactions from its block
+//! proposals.
+//!
+//! A transaction is excluded if any of its announcements
+//!  - is longer than `--max-composed-announcement-size`, or
+//!  - is tagged with a receiver identifier listed in
+//!    `--excluded-announcements-file`.
+//!
+//! Excluded candidates are logged and appended to the audit file at
+//! [DataDirectory::composer_inclusion_audit_file_path()](super::data_directory::DataDirectory::composer_inclusion_audit_file_path()).
+//! They remain in the mempool, so other composers may include them.
+
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::twenty_first::math::b_field_element::BFieldElement;
+use tokio::io::AsyncWriteExt;
+
+use super::cli_args::Args;
+use crate::api::export::Network;
+use crate::api::export::ReceivingAddress;
+use crate::api::export::Timestamp;
+use crate::api::export::TransactionKernelId;
+use crate::protocol::consensus::transaction::transaction_kernel::TransactionKernel;
+use crate::state::wallet::address::receiver_identifier_from_announcement;
+
+/// The rules a composer applies to candidate transactions. Includes every
+/// transaction by default.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct InclusionPolicy {
+    /// Maximum length of any announcement, in number of field elements.
+    max_announcement_size: Option<usize>,
+
+    /// Receiver identifiers that no announcement may be tagged with.
+    excluded_receiver_ids: HashSet<BFieldElement>,
+}
+
+impl InclusionPolicy {
+    pub(crate) fn new(
+        max_announcement_size: Option<usize>,
+        excluded_receiver_ids: impl IntoIterator<Item = BFieldElement>,
+    ) -> Self {
+        Self {
+            max_announcement_size,
+            excluded_receiver_ids: excluded_receiver_ids.into_iter().collect(),
+        }
+    }
+
+    /// The policy configured by the command-line arguments.
+    pub(crate) fn from_cli(cli: &Args) -> Result<Self, InclusionPolicyError> {
+        let excluded_receiver_ids = match &cli.excluded_announcements_file {
+            Some(path) => read_receiver_ids_from_file(path, cli.network)?,
+            None => vec![],
+        };
+
+        Ok(Self::new(
+            cli.max_composed_announcement_size,
+            excluded_receiver_ids,
+        ))
+    }
+
+    /// Whether the policy may exclude any transaction.
+    pub(crate) fn is_active(&self) -> bool {
+        self.max_announcement_size.is_some() || !self.excluded_receiver_ids.is_empty()
+    }
+
+    /// The number of excluded receiver identifiers.
+    pub(crate) fn num_excluded_receiver_ids(&self) -> usize {
+        self.excluded_receiver_ids.len()
+    }
+
+    /// Returns `Err(reason)` if the transaction must not be included in a
+    /// block proposal.
+    pub(crate) fn check(&self, kernel: &TransactionKernel) -> Result<(), ExclusionReason> {
+        for announcement in &kernel.announcements {
+            let size = announcement.message.len();
+            if let Some(max) = self.max_announcement_size {
+                if size > max {
+                    return Err(ExclusionReason::AnnouncementTooLarge { size, max });
+                }
+            }
+
+            if let Ok(receiver_id) = receiver_identifier_from_announcement(announcement) {
+                if self.excluded_receiver_ids.contains(&receiver_id) {
+                    return Err(ExclusionReason::ExcludedReceiverId(receiver_id));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Read the receiver identifiers listed in a file.
+///
+/// Each line holds either a receiver identifier, as a decimal integer, or a
+/// bech32m-encoded address whose receiver identifier is excluded. Blank lines
+/// and lines starting with `#` are ignored.
+///
+/// Returns an error if the file cannot be read or if any line is invalid, such
+/// that a typo cannot silently disable an exclusion.
+fn read_receiver_ids_from_file(
+    path: &Path,
+    network: Network,
+) -> Result<Vec<BFieldElement>, InclusionPolicyError> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| InclusionPolicyError::Read(path.into(), e.to_string()))?;
+
+    contents
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line_number, line)| {
+            if let Ok(receiver_id) = line.parse::<u64>() {
+                return Ok(BFieldElement::new(receiver_id));
+            }
+
+            ReceivingAddress::from_bech32m(line, network)
+                .map(|address| address.receiver_identifier())
+                .map_err(|_| InclusionPolicyError::InvalidEntry(path.into(), line_number))
+        })
+        .collect()
+}
+
+/// Why a candidate transaction was excluded from a block proposal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum ExclusionReason {
+    AnnouncementTooLarge { size: usize, max: usize },
+    ExcludedReceiverId(BFieldElement),
+}
+
+impl Display for ExclusionReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AnnouncementTooLarge { size, max } => write!(
+                f,
+                "announcement of {size} elements exceeds maximum of {max}"
+            ),
+            Self::ExcludedReceiverId(receiver_id) => {
+                write!(
+                    f,
+                    "announcement tagged with excluded receiver ID {receiver_id}"
+                )
+            }
+        }
+    }
+}
+
+/// A transaction excluded from a block proposal, as recorded in the audit
+/// file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ExcludedCandidate {
+    pub(crate) txid: TransactionKernelId,
+    pub(crate) reason: ExclusionReason,
+
+    /// time at which the block proposal was composed
+    pub(crate) timestamp: Timestamp,
+}
+
+impl ExcludedCandidate {
+    /// Log the exclusion and append it to the audit file at `path`.
+    ///
+    /// Failure to write the audit record is logged, but does not change the
+    /// outcome: the transaction is excluded either way.
+    pub(crate) async fn record(&self, path: &Path) {
+        tracing::info!(
+            "Excluding transaction {} from block proposal: {}",
+            self.txid,
+            self.reason
+        );
+
+        let result = async {
+            let mut line = serde_json::to_string(self)?;
+            line.push('\n');
+            tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .await?
+                .write_all(line.as_bytes())
+                .await?;
+            anyhow::Ok(())
+        }
+        .await;
+
+        if let Err(e) = result {
+            tracing::error!(
+                "Could not write composer inclusion audit record to {}: {e}",
+                path.display()
+            );
+        }
+    }
+}
+
+/// enumerates possible errors when reading an [InclusionPolicy]
+#[derive(Debug, Clone, thiserror::Error)]
+#[non_exhaustive]
+pub(crate) enum InclusionPolicyError {
+    #[error("could not read excluded announcements file {}: {1}", .0.display())]
+    Read(PathBuf, String),
+
+    #[error("invalid entry in excluded announcements file {} on line {1}", .0.display())]
+    InvalidEntry(PathBuf, usize),
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use rand::random;
+
+    use super::*;
+    use crate::api::export::GenerationSpendingKey;
+    use crate::api::export::NativeCurrencyAmount;
+    use crate::protocol::consensus::transaction::announcement::Announcement;
+    use crate::protocol::consensus::transaction::transaction_kernel::TransactionKernelProxy;
+    use crate::tests::shared::files::unit_test_data_directory;
+
+    fn kernel_with_announcements(announcements: Vec<Announcement>) -> TransactionKernel {
+        TransactionKernelProxy {
+            inputs: vec![],
+            outputs: vec![],
+            announcements,
+            fee: NativeCurrencyAmount::zero(),
+            coinbase: None,
+            timestamp: Timestamp::now(),
+            mutator_set_hash: Default::default(),
+            merge_bit: false,
+        }
+        .into_kernel()
+    }
+
+    fn tagged_announcement(receiver_id: u64, size: usize) -> Announcement {
+        let mut message = vec![BFieldElement::new(0), BFieldElement::new(receiver_id)];
+        message.resize(size, BFieldElement::new(1));
+        Announcement::new(message)
+    }
+
+    #[test]
+    fn default_policy_includes_everything() {
+        let policy = InclusionPolicy::default();
+        assert!(!policy.is_active());
+
+        let kernel = kernel_with_announcements(vec![tagged_announcement(7, 1_000)]);
+        assert!(policy.check(&kernel).is_ok());
+    }
+
+    #[test]
+    fn large_announcements_and_excluded_receiver_ids_are_excluded() {
+        let policy = InclusionPolicy::new(Some(10), [BFieldElement::new(7)]);
+        assert!(policy.is_active());
+
+        let acceptable = kernel_with_announcements(vec![tagged_announcement(8, 10)]);
+        assert!(policy.check(&acceptable).is_ok());
+
+        let too_large = kernel_with_announcements(vec![tagged_announcement(8, 11)]);
+        assert_eq!(
+            Err(ExclusionReason::AnnouncementTooLarge { size: 11, max: 10 }),
+            policy.check(&too_large)
+        );
+
+        let excluded =
+            kernel_with_announcements(vec![tagged_announcement(8, 5), tagged_announcement(7, 5)]);
+        assert_eq!(
+            Err(ExclusionReason::ExcludedReceiverId(BFieldElement::new(7))),
+            policy.check(&excluded)
+        );
+    }
+
+    #[test]
+    fn receiver_ids_are_read_from_ids_and_addresses() {
+        let network = Network::Main;
+        let address: ReceivingAddress = GenerationSpendingKey::derive_from_seed(random())
+            .to_address()
+            .into();
+        let data_dir = unit_test_data_directory(network).unwrap();
+        std::fs::create_dir_all(data_dir.root_dir_path()).unwrap();
+        let path = data_dir.root_dir_path().join("excluded_announcements");
+        std::fs::write(
+            &path,
+            format!(
+                "# excluded\n\n42\n{}\n",
+                address.to_bech32m(network).unwrap()
+            ),
+        )
+        .unwrap();
+
+        let receiver_ids = read_receiver_ids_from_file(&path, network).unwrap();
+        assert_eq!(
+            vec![BFieldElement::new(42), address.receiver_identifier()],
+            receiver_ids
+        );
+
+        std::fs::write(&path, "42\nnot an id\n").unwrap();
+        assert!(matches!(
+            read_receiver_ids_from_file(&path, network),
+            Err(InclusionPolicyError::InvalidEntry(_, 2))
+        ));
+    }
+}
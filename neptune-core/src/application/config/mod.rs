@@ -1,6 +1,9 @@
 pub mod cli_args;
+pub(crate) mod config_file;
 pub mod data_directory;
 pub(crate) mod fee_notification_policy;
+pub(crate) mod inclusion_policy;
 pub mod network;
+pub mod retention_policy;
 pub mod triton_vm_env_vars;
 pub mod tx_upgrade_filter;
@@ -49,6 +49,34 @@ impl Network {
         }
     }
 
+    /// default port on which to listen for peer connections.
+    ///
+    /// Each network has its own port, so that nodes on different networks can
+    /// run side by side on the same machine without explicit configuration.
+    ///
+    /// - mainnet: 9798
+    /// - others: 9798 + 2 * network id
+    pub fn default_peer_port(&self) -> u16 {
+        Self::MAIN_PEER_PORT + self.port_offset()
+    }
+
+    /// default port on which to listen for RPC connections.
+    ///
+    /// - mainnet: 9799
+    /// - others: 9799 + 2 * network id
+    pub fn default_rpc_port(&self) -> u16 {
+        Self::MAIN_RPC_PORT + self.port_offset()
+    }
+
+    const MAIN_PEER_PORT: u16 = 9798;
+    const MAIN_RPC_PORT: u16 = 9799;
+
+    // peer and rpc ports are interleaved, so each network occupies two ports.
+    fn port_offset(&self) -> u16 {
+        // ids are at most 3 + u8::MAX, so this cannot overflow.
+        2 * self.id() as u16
+    }
+
     pub fn launch_date(&self) -> Timestamp {
         // 5 August 2025, 19:00:00 UTC
         Timestamp(BFieldElement::new(1754420400000u64))
@@ -229,6 +257,27 @@ mod tests {
         assert!(Network::from_str("0").is_err());
     }
 
+    #[test]
+    fn mainnet_default_ports_are_unchanged() {
+        assert_eq!(9798, Network::Main.default_peer_port());
+        assert_eq!(9799, Network::Main.default_rpc_port());
+    }
+
+    #[test]
+    fn all_default_ports_unique() {
+        let mut seen: HashSet<u16, _> = HashSet::new();
+        for network in Network::all_networks() {
+            assert!(
+                seen.insert(network.default_peer_port()),
+                "network {network} has non-unique peer port."
+            );
+            assert!(
+                seen.insert(network.default_rpc_port()),
+                "network {network} has non-unique rpc port."
+            );
+        }
+    }
+
     #[test]
     fn all_ids_unique() {
         let mut seen: HashSet<u32, _> = HashSet::new();
@@ -0,0 +1,268 @@
+//! Retention of the files that accumulate in the [DataDirectory].
+//!
+//! Three tiers of files are cleaned up:
+//!
+//!  - logs, which are rotated once they grow too large, and deleted some time
+//!    after rotation, and crash reports, which are deleted after the same
+//!    time,
+//!  - backups of databases made before schema migrations, which are kept
+//!    forever unless configured otherwise, and
+//!  - temporary files orphaned by interrupted writes.
+//!
+//! Databases, block files, and wallet files are never touched.
+
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::debug;
+use tracing::warn;
+
+use super::cli_args;
+use super::data_directory::DataDirectory;
+
+/// Extension of files written under a temporary name before being renamed.
+pub(crate) const TEMPORARY_FILE_EXTENSION: &str = "tmp";
+
+/// How long the files that accumulate in the [DataDirectory] are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RetentionPolicy {
+    /// Size in bytes above which logs are rotated.
+    pub(crate) max_log_file_size: u64,
+    pub(crate) log_retention: Duration,
+
+    /// `None` keeps backups forever.
+    pub(crate) migration_backup_retention: Option<Duration>,
+    pub(crate) temporary_file_retention: Duration,
+}
+
+/// What a cleanup of the [DataDirectory] did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CleanupReport {
+    pub num_logs_rotated: usize,
+    pub num_files_removed: usize,
+    pub num_bytes_reclaimed: u64,
+}
+
+impl RetentionPolicy {
+    pub(crate) fn from_cli(cli: &cli_args::Args) -> Self {
+        Self {
+            max_log_file_size: cli.max_log_file_size,
+            log_retention: cli.log_retention,
+            migration_backup_retention: cli.migration_backup_retention,
+            temporary_file_retention: cli.temporary_file_retention,
+        }
+    }
+
+    /// Rotate and delete files in `data_dir` as the policy prescribes.
+    ///
+    /// Files that cannot be inspected or removed are skipped. Performs
+    /// blocking file system operations.
+    pub(crate) fn enforce(&self, data_dir: &DataDirectory, now: SystemTime) -> CleanupReport {
+        let mut report = CleanupReport::default();
+
+        for log in [
+            data_dir.send_policy_audit_file_path(),
+            data_dir.spending_pin_audit_file_path(),
+            data_dir.composer_inclusion_audit_file_path(),
+        ] {
+            self.rotate_log(&log, now, &mut report);
+            for rotated_log in rotated_logs(&log) {
+                remove_if_older(&rotated_log, self.log_retention, now, &mut report);
+            }
+        }
+
+        for crash_report in dir_entries(&data_dir.crash_reports_dir_path()) {
+            remove_if_older(&crash_report, self.log_retention, now, &mut report);
+        }
+
+        if let Some(retention) = self.migration_backup_retention {
+            for backup in dir_entries(&data_dir.db_migration_backups_dir_path()) {
+                remove_if_older(&backup, retention, now, &mut report);
+            }
+        }
+
+        for file in dir_entries(&data_dir.channel_spill_dir_path())
+            .chain(dir_entries(&data_dir.mempool_spillover_dir_path()))
+        {
+            if file
+                .extension()
+                .is_some_and(|extension| extension == TEMPORARY_FILE_EXTENSION)
+            {
+                remove_if_older(&file, self.temporary_file_retention, now, &mut report);
+            }
+        }
+
+        report
+    }
+
+    fn rotate_log(&self, log: &Path, now: SystemTime, report: &mut CleanupReport) {
+        let Ok(metadata) = fs::metadata(log) else {
+            return;
+        };
+        if metadata.len() <= self.max_log_file_size {
+            return;
+        }
+
+        let rotated_log = rotated_log_path(log, now);
+        match fs::rename(log, &rotated_log) {
+            Ok(()) => {
+                debug!("Rotated {} to {}", log.display(), rotated_log.display());
+                report.num_logs_rotated += 1;
+            }
+            Err(e) => warn!("Failed to rotate {}: {e}", log.display()),
+        }
+    }
+}
+
+/// `<stem>.<seconds since epoch>.<extension>`, next to `log`.
+fn rotated_log_path(log: &Path, now: SystemTime) -> PathBuf {
+    let seconds = now
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let stem = log.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = log.extension().unwrap_or_default().to_string_lossy();
+
+    log.with_file_name(format!("{stem}.{seconds}.{extension}"))
+}
+
+fn rotated_logs(log: &Path) -> impl Iterator<Item = PathBuf> {
+    let stem = log.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = log.extension().unwrap_or_default().to_string_lossy();
+    let prefix = format!("{stem}.");
+    let suffix = format!(".{extension}");
+
+    dir_entries(log.parent().unwrap_or(Path::new(""))).filter(move |path| {
+        path.file_name()
+            .map(|name| name.to_string_lossy())
+            .and_then(|name| {
+                name.strip_prefix(&prefix)
+                    .and_then(|name| name.strip_suffix(&suffix))
+                    .map(|seconds| seconds.parse::<u64>().is_ok())
+            })
+            .unwrap_or(false)
+    })
+}
+
+fn dir_entries(dir: &Path) -> impl Iterator<Item = PathBuf> {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+}
+
+/// Size in bytes of the file or directory at `path`.
+fn size(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if metadata.is_dir() {
+        dir_entries(path).map(|entry| size(&entry)).sum()
+    } else {
+        metadata.len()
+    }
+}
+
+fn remove_if_older(path: &Path, max_age: Duration, now: SystemTime, report: &mut CleanupReport) {
+    let Ok(modified) = fs::symlink_metadata(path).and_then(|metadata| metadata.modified()) else {
+        return;
+    };
+    if now.duration_since(modified).unwrap_or_default() < max_age {
+        return;
+    }
+
+    let num_bytes = size(path);
+    let result = if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+    match result {
+        Ok(()) => {
+            debug!("Removed {}", path.display());
+            report.num_files_removed += 1;
+            report.num_bytes_reclaimed += num_bytes;
+        }
+        Err(e) => warn!("Failed to remove {}: {e}", path.display()),
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+    use crate::application::config::network::Network;
+    use crate::tests::shared::files::unit_test_data_directory;
+
+    fn policy() -> RetentionPolicy {
+        RetentionPolicy {
+            max_log_file_size: 10,
+            log_retention: Duration::from_secs(60),
+            migration_backup_retention: None,
+            temporary_file_retention: Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn logs_are_rotated_then_removed() {
+        let data_dir = unit_test_data_directory(Network::Main).unwrap();
+        fs::create_dir_all(data_dir.root_dir_path()).unwrap();
+        let log = data_dir.send_policy_audit_file_path();
+        fs::write(&log, [0u8; 11]).unwrap();
+
+        let now = SystemTime::now();
+        let report = policy().enforce(&data_dir, now);
+        assert_eq!(1, report.num_logs_rotated);
+        assert!(!log.exists());
+        assert_eq!(1, rotated_logs(&log).count());
+
+        // The rotated log is kept until it expires.
+        assert_eq!(
+            CleanupReport::default(),
+            policy().enforce(&data_dir, now + Duration::from_secs(30))
+        );
+        let report = policy().enforce(&data_dir, now + Duration::from_secs(61));
+        assert_eq!(1, report.num_files_removed);
+        assert_eq!(11, report.num_bytes_reclaimed);
+        assert_eq!(0, rotated_logs(&log).count());
+    }
+
+    #[test]
+    fn only_expired_temporary_files_and_opted_in_backups_are_removed() {
+        let data_dir = unit_test_data_directory(Network::Main).unwrap();
+        let spill_dir = data_dir.channel_spill_dir_path();
+        fs::create_dir_all(&spill_dir).unwrap();
+        let temporary_file = spill_dir.join(format!("0.{TEMPORARY_FILE_EXTENSION}"));
+        let spilled_file = spill_dir.join("1.spill");
+        fs::write(&temporary_file, [0u8; 3]).unwrap();
+        fs::write(&spilled_file, [0u8; 5]).unwrap();
+        let backup = data_dir
+            .db_migration_backups_dir_path()
+            .join("wallet.bak.1");
+        fs::create_dir_all(&backup).unwrap();
+        fs::write(backup.join("data"), [0u8; 7]).unwrap();
+
+        let later = SystemTime::now() + Duration::from_secs(61);
+        let report = policy().enforce(&data_dir, later);
+        assert_eq!(1, report.num_files_removed);
+        assert_eq!(3, report.num_bytes_reclaimed);
+        assert!(!temporary_file.exists());
+        assert!(spilled_file.exists());
+        assert!(backup.exists());
+
+        let policy = RetentionPolicy {
+            migration_backup_retention: Some(Duration::from_secs(60)),
+            ..policy()
+        };
+        let report = policy.enforce(&data_dir, later);
+        assert_eq!(1, report.num_files_removed);
+        assert_eq!(7, report.num_bytes_reclaimed);
+        assert!(!backup.exists());
+    }
+}
@@ -0,0 +1,292 @@
+//! Crash report bundles, written when any task of the node panics.
+//!
+//! [install()] sets a panic hook that, after the previously installed hook
+//! has run, writes a JSON [CrashReport] to
+//! [DataDirectory::crash_reports_dir_path()]. The report holds the panic
+//! message and location, a backtrace, the most recent log lines, the
+//! configuration, and the tip at the time of the panic. Secret values, paths,
+//! and network addresses are redacted from the configuration like they are
+//! from [diagnostics bundles](crate::application::rpc::server::diagnostics).
+//!
+//! Recent log lines are only available if the logger writes to
+//! [RecentLogWriter] in addition to its usual output.
+//!
+//! If the panicking task is supervised, the report names the task as the
+//! crashed subsystem, and the supervisor reports the path of the bundle as
+//! part of the task's [TaskStatus](crate::application::loops::task_supervisor::TaskStatus).
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::Once;
+use std::sync::OnceLock;
+use std::sync::PoisonError;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Digest;
+
+use crate::application::config::cli_args;
+use crate::application::config::data_directory::DataDirectory;
+use crate::application::loops::task_supervisor::panic_message;
+use crate::application::loops::task_supervisor::SupervisedTask;
+use crate::application::rpc::server::diagnostics;
+use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+
+/// Number of log lines kept for inclusion in crash reports.
+const MAX_RECENT_LOG_LINES: usize = 200;
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+static RECENT_LOGS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+static TIP: Mutex<Option<TipSummary>> = Mutex::new(None);
+static CONTEXT: OnceLock<CrashContext> = OnceLock::new();
+static INSTALL_HOOK: Once = Once::new();
+
+tokio::task_local! {
+    static SUBSYSTEM: SupervisedTask;
+}
+
+thread_local! {
+    /// The report written for the most recent panic on this thread.
+    static LAST_REPORT: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
+/// What a crash report knows of the node, independent of the panic.
+#[derive(Debug, Clone)]
+struct CrashContext {
+    crash_reports_dir: PathBuf,
+    config: String,
+}
+
+/// The tip of the node at the time of a panic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TipSummary {
+    pub height: BlockHeight,
+    pub digest: Digest,
+}
+
+/// Everything recorded about a panic, as written to a crash report bundle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub timestamp: Timestamp,
+    pub version: String,
+
+    /// The supervised task that panicked, or `None` if the panic occurred
+    /// outside of supervised tasks.
+    pub subsystem: Option<SupervisedTask>,
+
+    /// The name of the panicking thread, if it has one.
+    pub thread: Option<String>,
+    pub message: String,
+
+    /// The source location of the panic, as `file:line:column`.
+    pub location: Option<String>,
+    pub backtrace: String,
+    pub tip: Option<TipSummary>,
+
+    /// The configuration the node was started with, as TOML, with secret
+    /// values, paths, and network addresses redacted.
+    pub config: String,
+
+    /// The most recent log lines, oldest first.
+    pub recent_logs: Vec<String>,
+}
+
+impl CrashReport {
+    /// Capture the state of the node for a panic that is occurring now.
+    fn capture(message: String, location: Option<String>, config: String) -> Self {
+        Self {
+            timestamp: Timestamp::now(),
+            version: VERSION.to_string(),
+            subsystem: SUBSYSTEM.try_with(|task| *task).ok(),
+            thread: std::thread::current().name().map(|name| name.to_string()),
+            message,
+            location,
+            backtrace: Backtrace::force_capture().to_string(),
+            tip: *TIP.lock().unwrap_or_else(PoisonError::into_inner),
+            config,
+            recent_logs: recent_logs(),
+        }
+    }
+
+    /// Write the report to a new file in `dir`, and return its path.
+    fn write_to(&self, dir: &Path) -> io::Result<PathBuf> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("crash-{}.json", self.timestamp.to_millis()));
+        let contents = serde_json::to_vec_pretty(self).map_err(io::Error::other)?;
+        std::fs::write(&path, contents)?;
+
+        Ok(path)
+    }
+}
+
+/// A log writer that keeps the most recent log lines in memory, for inclusion
+/// in crash reports.
+///
+/// Combine it with the usual output of the logger, e.g. with
+/// `std::io::stdout.and(RecentLogWriter::default)`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RecentLogWriter;
+
+impl io::Write for RecentLogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut logs = RECENT_LOGS.lock().unwrap_or_else(PoisonError::into_inner);
+        for line in String::from_utf8_lossy(buf).lines() {
+            if logs.len() == MAX_RECENT_LOG_LINES {
+                logs.pop_front();
+            }
+            logs.push_back(line.to_string());
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The most recent log lines, oldest first.
+pub(crate) fn recent_logs() -> Vec<String> {
+    // A panic while the buffer is being written to must not deadlock.
+    match RECENT_LOGS.try_lock() {
+        Ok(logs) => logs.iter().cloned().collect(),
+        Err(_) => vec![],
+    }
+}
+
+/// Set the panic hook that writes crash reports to the data directory.
+///
+/// Only the first call installs the hook; the data directory and
+/// configuration of later calls are ignored.
+pub(crate) fn install(data_directory: &DataDirectory, cli_args: &cli_args::Args) {
+    let context = CrashContext {
+        crash_reports_dir: data_directory.crash_reports_dir_path(),
+        config: redacted_config(cli_args),
+    };
+    if CONTEXT.set(context).is_err() {
+        return;
+    }
+
+    INSTALL_HOOK.call_once(|| {
+        let previous_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            previous_hook(info);
+
+            let Some(context) = CONTEXT.get() else {
+                return;
+            };
+            let message = panic_message(info.payload());
+            let location = info.location().map(|location| location.to_string());
+            let report = CrashReport::capture(message, location, context.config.clone());
+            match report.write_to(&context.crash_reports_dir) {
+                Ok(path) => {
+                    eprintln!("Crash report written to {}", path.display());
+                    LAST_REPORT.with(|last| *last.borrow_mut() = Some(path));
+                }
+                Err(e) => eprintln!("Could not write crash report: {e}"),
+            }
+        }));
+    });
+}
+
+/// The configuration for inclusion in crash reports, redacted like it is for
+/// diagnostics bundles without network addresses.
+fn redacted_config(cli_args: &cli_args::Args) -> String {
+    cli_args
+        .effective_config
+        .as_deref()
+        .map(|config| {
+            let paths = diagnostics::local_paths(cli_args.data_dir.clone());
+            diagnostics::redact_config(config, false, &paths, &mut vec![])
+        })
+        .unwrap_or_default()
+}
+
+/// Record the new tip, for inclusion in crash reports.
+pub(crate) fn record_tip(height: BlockHeight, digest: Digest) {
+    *TIP.lock().unwrap_or_else(PoisonError::into_inner) = Some(TipSummary { height, digest });
+}
+
+/// Run `future` as the given supervised task, such that crash reports of
+/// panics within it name the task.
+pub(crate) fn in_subsystem<F: Future>(
+    task: SupervisedTask,
+    future: F,
+) -> impl Future<Output = F::Output> {
+    SUBSYSTEM.scope(task, future)
+}
+
+/// Take the path of the report written for the most recent panic on the
+/// current thread.
+///
+/// Called where the panic is caught, which is on the panicking thread.
+pub(crate) fn take_last_report() -> Option<PathBuf> {
+    LAST_REPORT.with(|last| last.borrow_mut().take())
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::io::Write;
+
+    use macro_rules_attr::apply;
+
+    use super::*;
+    use crate::application::config::network::Network;
+    use crate::tests::shared::files::unit_test_data_directory;
+    use crate::tests::shared_tokio_runtime;
+
+    #[test]
+    fn recent_log_writer_keeps_most_recent_lines() {
+        for i in 0..MAX_RECENT_LOG_LINES + 10 {
+            writeln!(RecentLogWriter, "line {i}").unwrap();
+        }
+
+        // other tests may log concurrently, so only check the bound and that
+        // old lines were dropped.
+        let logs = recent_logs();
+        assert_eq!(MAX_RECENT_LOG_LINES, logs.len());
+        assert!(!logs.contains(&"line 0".to_string()));
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn report_names_subsystem_and_is_written_to_file() {
+        let report = in_subsystem(SupervisedTask::Miner, async {
+            CrashReport::capture("boom".to_string(), None, "config".to_string())
+        })
+        .await;
+        assert_eq!(Some(SupervisedTask::Miner), report.subsystem);
+        assert!(!report.backtrace.is_empty());
+
+        let data_dir = unit_test_data_directory(Network::Main).unwrap();
+        let path = report.write_to(&data_dir.crash_reports_dir_path()).unwrap();
+        let written: CrashReport = serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+        assert_eq!(report, written);
+
+        let outside = CrashReport::capture("boom".to_string(), None, "config".to_string());
+        assert!(outside.subsystem.is_none());
+    }
+
+    #[test]
+    fn config_is_redacted() {
+        let mut cli_args = cli_args::Args::default();
+        cli_args.effective_config = Some(
+            "data_dir = \"/home/alice/.neptune\"\n\
+             peer = [\"192.0.2.1:9798\"]\n\
+             max_num_peers = \"5\"\n"
+                .to_string(),
+        );
+
+        let config = redacted_config(&cli_args);
+        assert!(!config.contains("/home/alice"));
+        assert!(!config.contains("192.0.2.1"));
+        assert!(config.contains("max_num_peers = \"5\""));
+    }
+}
@@ -1,5 +1,7 @@
 pub mod leveldb;
 mod neptune_leveldb;
+pub mod open_files;
+pub mod read_cache_budget;
 pub mod storage;
 
 pub use neptune_leveldb::create_db_if_missing;
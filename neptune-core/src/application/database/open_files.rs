@@ -0,0 +1,317 @@
+//! Budgeting of file descriptors across the LevelDB stores.
+//!
+//! By default, LevelDB keeps up to 1000 table files open per database. With
+//! several databases open, plus peer connections and block files, that easily
+//! exceeds the process's limit on open files, at which point the node crashes
+//! with `EMFILE`.
+//!
+//! At startup, [OpenFileBudget::from_cli()] raises the soft limit on open
+//! files as far as needed and permitted, and splits what is available to the
+//! databases among the stores. Stores opened through [db_options()] are then
+//! confined to their share.
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+
+use leveldb::options::Options;
+use tracing::info;
+use tracing::warn;
+
+use super::create_db_if_missing;
+use crate::application::config::cli_args;
+
+/// LevelDB's own default, and the most files any store is given.
+pub(crate) const MAX_OPEN_FILES_PER_STORE: u64 = 1000;
+
+/// LevelDB silently raises smaller limits to this value.
+pub(crate) const MIN_OPEN_FILES_PER_STORE: u64 = 74;
+
+/// File descriptors kept for everything but the databases, such as block
+/// files, RPC connections, and log files. Peer connections come on top.
+const RESERVED_FILE_DESCRIPTORS: u64 = 256;
+
+/// Total number of file descriptors given to the databases unless configured
+/// otherwise and permitted by the process's limit.
+const DEFAULT_DATABASE_FILE_DESCRIPTORS: u64 = 2048;
+
+static BUDGET: RwLock<Option<OpenFileBudget>> = RwLock::new(None);
+
+/// The LevelDB stores that are open while the node runs.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    strum::Display,
+    strum::EnumString,
+    strum::EnumIter,
+)]
+#[strum(serialize_all = "kebab-case")]
+pub enum DbStore {
+    BlockIndex,
+    MutatorSet,
+    ArchivalBlockMmr,
+    Wallet,
+    PeerStandings,
+}
+
+impl DbStore {
+    /// The store's share of the budget, relative to the other stores.
+    fn weight(self) -> u64 {
+        match self {
+            // read on every block and every block request
+            Self::BlockIndex | Self::MutatorSet => 4,
+            Self::ArchivalBlockMmr | Self::Wallet => 2,
+            Self::PeerStandings => 1,
+        }
+    }
+}
+
+/// A limit on the open files of one store, as given on the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StoreOpenFiles {
+    pub store: DbStore,
+    pub max_open_files: u64,
+}
+
+impl FromStr for StoreOpenFiles {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((store, max_open_files)) = s.split_once('=') else {
+            return Err(format!("Expected `STORE=FILES`, got `{s}`"));
+        };
+        let store = store
+            .trim()
+            .parse()
+            .map_err(|_| format!("Unknown store in `{s}`"))?;
+        let max_open_files = max_open_files
+            .trim()
+            .parse::<u64>()
+            .map_err(|_| format!("Invalid number of files in `{s}`"))?;
+        if max_open_files < MIN_OPEN_FILES_PER_STORE {
+            return Err(format!(
+                "At least {MIN_OPEN_FILES_PER_STORE} files are needed per store, got `{s}`"
+            ));
+        }
+
+        Ok(Self {
+            store,
+            max_open_files,
+        })
+    }
+}
+
+/// The number of files each store may keep open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct OpenFileBudget(BTreeMap<DbStore, u64>);
+
+impl OpenFileBudget {
+    /// Split `total` among the stores by their weights, except for the
+    /// stores in `overrides`, which get the given number of files.
+    ///
+    /// Every store gets at least [MIN_OPEN_FILES_PER_STORE] and, unless
+    /// overridden, at most [MAX_OPEN_FILES_PER_STORE] files, so the budget
+    /// may exceed `total` if `total` is too small.
+    pub(crate) fn new(total: u64, overrides: &[StoreOpenFiles]) -> Self {
+        use strum::IntoEnumIterator;
+
+        let mut limits: BTreeMap<DbStore, u64> = overrides
+            .iter()
+            .map(|limit| (limit.store, limit.max_open_files))
+            .collect();
+        let remaining = total.saturating_sub(limits.values().sum());
+        let remaining_stores = DbStore::iter()
+            .filter(|store| !limits.contains_key(store))
+            .collect::<Vec<_>>();
+        let total_weight: u64 = remaining_stores.iter().map(|store| store.weight()).sum();
+        for store in remaining_stores {
+            let share = remaining.saturating_mul(store.weight()) / total_weight;
+            limits.insert(
+                store,
+                share.clamp(MIN_OPEN_FILES_PER_STORE, MAX_OPEN_FILES_PER_STORE),
+            );
+        }
+
+        Self(limits)
+    }
+
+    /// The budget configured by the command-line arguments and permitted by
+    /// the process's limit on open files, which is raised if necessary.
+    ///
+    /// Warns if the limit cannot be raised far enough for the budget.
+    pub(crate) fn from_cli(cli: &cli_args::Args) -> Self {
+        let reserved = RESERVED_FILE_DESCRIPTORS + cli.max_num_peers as u64;
+        let wanted = cli
+            .max_open_files
+            .unwrap_or(DEFAULT_DATABASE_FILE_DESCRIPTORS);
+
+        let Some(limit) = raise_open_file_limit(wanted.saturating_add(reserved)) else {
+            return Self::new(wanted, &cli.db_max_open_files);
+        };
+
+        let available = limit.saturating_sub(reserved);
+        let total = if cli.max_open_files.is_some() {
+            wanted
+        } else {
+            wanted.min(available)
+        };
+        let budget = Self::new(total, &cli.db_max_open_files);
+
+        if budget.total() > available {
+            warn!(
+                "The limit of {limit} open files leaves {available} for databases, \
+                 but they are configured to use up to {}. The node may crash with \
+                 \"too many open files\". Raise the limit, e.g. with `ulimit -n`, or \
+                 lower --max-open-files or --db-max-open-files.",
+                budget.total()
+            );
+        }
+
+        budget
+    }
+
+    pub(crate) fn limit(&self, store: DbStore) -> u64 {
+        self.0
+            .get(&store)
+            .copied()
+            .unwrap_or(MAX_OPEN_FILES_PER_STORE)
+    }
+
+    pub(crate) fn total(&self) -> u64 {
+        self.0.values().sum()
+    }
+
+    /// Make this the budget applied by [db_options()].
+    pub(crate) fn apply(self) {
+        info!(
+            "Database open-file budget: {}",
+            self.0
+                .iter()
+                .map(|(store, limit)| format!("{store}={limit}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        *BUDGET.write().unwrap_or_else(PoisonError::into_inner) = Some(self);
+    }
+}
+
+/// Options for opening or creating `store`, with its share of the open-file
+/// budget.
+///
+/// Without an applied budget, LevelDB's defaults are used.
+pub(crate) fn db_options(store: DbStore) -> Options {
+    let mut options = create_db_if_missing();
+    options.max_open_files = BUDGET
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+        .map(|budget| i32::try_from(budget.limit(store)).unwrap_or(i32::MAX));
+
+    options
+}
+
+/// Raise the soft limit on open files to `wanted`, or as close as the hard
+/// limit permits. Returns the resulting soft limit, or `None` if the limit
+/// cannot be read on this platform.
+#[cfg(unix)]
+fn raise_open_file_limit(wanted: u64) -> Option<u64> {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+
+    // SAFETY: `limit` is a valid, writable `rlimit`.
+    if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) } != 0 {
+        warn!(
+            "Could not read limit on open files: {}",
+            std::io::Error::last_os_error()
+        );
+        return None;
+    }
+
+    let soft = limit.rlim_cur as u64;
+    let hard = limit.rlim_max as u64;
+    if soft >= wanted {
+        return Some(soft);
+    }
+
+    let raised = wanted.min(hard);
+    if raised <= soft {
+        return Some(soft);
+    }
+    limit.rlim_cur = raised as libc::rlim_t;
+
+    // SAFETY: `limit` is a valid `rlimit`, and does not exceed the hard limit.
+    if unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &limit) } != 0 {
+        warn!(
+            "Could not raise limit on open files from {soft} to {raised}: {}",
+            std::io::Error::last_os_error()
+        );
+        return Some(soft);
+    }
+
+    info!("Raised limit on open files from {soft} to {raised}");
+    Some(raised)
+}
+
+#[cfg(not(unix))]
+fn raise_open_file_limit(_wanted: u64) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    #[test]
+    fn budget_is_split_by_weight_within_bounds() {
+        let budget = OpenFileBudget::new(1300, &[]);
+        assert_eq!(400, budget.limit(DbStore::BlockIndex));
+        assert_eq!(400, budget.limit(DbStore::MutatorSet));
+        assert_eq!(200, budget.limit(DbStore::Wallet));
+        assert_eq!(100, budget.limit(DbStore::PeerStandings));
+        assert_eq!(1300, budget.total());
+
+        let small = OpenFileBudget::new(0, &[]);
+        for store in DbStore::iter() {
+            assert_eq!(MIN_OPEN_FILES_PER_STORE, small.limit(store));
+        }
+
+        let large = OpenFileBudget::new(u64::MAX / 8, &[]);
+        for store in DbStore::iter() {
+            assert_eq!(MAX_OPEN_FILES_PER_STORE, large.limit(store));
+        }
+    }
+
+    #[test]
+    fn overrides_are_taken_from_the_budget() {
+        let overrides = ["wallet=500".parse().unwrap()];
+        let budget = OpenFileBudget::new(1600, &overrides);
+        assert_eq!(500, budget.limit(DbStore::Wallet));
+        assert_eq!(400, budget.limit(DbStore::BlockIndex));
+        assert_eq!(100, budget.limit(DbStore::PeerStandings));
+        assert_eq!(1600, budget.total());
+    }
+
+    #[test]
+    fn store_limits_are_parsed() {
+        assert_eq!(
+            Ok(StoreOpenFiles {
+                store: DbStore::ArchivalBlockMmr,
+                max_open_files: 128,
+            }),
+            "archival-block-mmr=128".parse()
+        );
+        assert!("wallet".parse::<StoreOpenFiles>().is_err());
+        assert!("blocks=128".parse::<StoreOpenFiles>().is_err());
+        assert!("wallet=10".parse::<StoreOpenFiles>().is_err());
+    }
+}
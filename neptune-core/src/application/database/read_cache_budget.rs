@@ -0,0 +1,158 @@
+//! Budgeting of memory across the read caches of the databases.
+//!
+//! Applying a block reads the same Bloom filter chunks and MMR nodes of the
+//! mutator set over and over, and every read fetches and decodes the value
+//! from LevelDB. The hottest tables therefore keep recently read values in
+//! memory.
+//!
+//! At startup, [ReadCacheBudget::from_cli()] splits the configured memory
+//! among the cached tables. Tables opened afterwards get their share through
+//! [read_cache_capacity()].
+use std::collections::BTreeMap;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::info;
+
+use crate::application::config::cli_args;
+use crate::application::database::storage::storage_schema::ReadCacheMetrics;
+
+static BUDGET: RwLock<Option<ReadCacheBudget>> = RwLock::new(None);
+
+/// The database tables whose recently read values are cached in memory.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    strum::Display,
+    strum::EnumIter,
+)]
+#[strum(serialize_all = "kebab-case")]
+pub enum CachedTable {
+    /// The inactive chunks of the mutator set's sliding-window Bloom filter.
+    MutatorSetChunks,
+
+    /// The MMR nodes of the mutator set's append-only commitment list.
+    MutatorSetAocl,
+
+    /// The MMR nodes of the mutator set's inactive Bloom filter chunks.
+    MutatorSetSwbfInactive,
+
+    /// The MMR nodes of the archival block MMR.
+    ArchivalBlockMmr,
+}
+
+impl CachedTable {
+    /// The table's share of the budget, relative to the other tables.
+    fn weight(self) -> usize {
+        match self {
+            // read for every input of every block
+            Self::MutatorSetChunks => 8,
+            Self::MutatorSetAocl => 4,
+            Self::MutatorSetSwbfInactive | Self::ArchivalBlockMmr => 2,
+        }
+    }
+}
+
+/// The effectiveness of the read cache of one table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableReadCacheMetrics {
+    /// The cached table.
+    pub table: CachedTable,
+
+    /// Counters describing the table's read cache.
+    pub metrics: ReadCacheMetrics,
+}
+
+/// The number of bytes each table may cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ReadCacheBudget(BTreeMap<CachedTable, usize>);
+
+impl ReadCacheBudget {
+    /// Split `total` bytes among the tables by their weights.
+    pub(crate) fn new(total: usize) -> Self {
+        use strum::IntoEnumIterator;
+
+        let total_weight: usize = CachedTable::iter().map(CachedTable::weight).sum();
+        let capacities = CachedTable::iter()
+            .map(|table| (table, total / total_weight * table.weight()))
+            .collect();
+
+        Self(capacities)
+    }
+
+    /// The budget configured by the command-line arguments.
+    pub(crate) fn from_cli(cli: &cli_args::Args) -> Self {
+        Self::new(cli.db_read_cache_size.saturating_mul(1024 * 1024))
+    }
+
+    pub(crate) fn capacity(&self, table: CachedTable) -> usize {
+        self.0.get(&table).copied().unwrap_or_default()
+    }
+
+    /// Make this the budget applied by [read_cache_capacity()].
+    pub(crate) fn apply(self) {
+        info!(
+            "Database read-cache budget in bytes: {}",
+            self.0
+                .iter()
+                .map(|(table, capacity)| format!("{table}={capacity}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        *BUDGET.write().unwrap_or_else(PoisonError::into_inner) = Some(self);
+    }
+}
+
+/// The number of bytes `table` may cache.
+///
+/// Without an applied budget, nothing is cached.
+pub(crate) fn read_cache_capacity(table: CachedTable) -> usize {
+    BUDGET
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+        .map(|budget| budget.capacity(table))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    #[test]
+    fn budget_is_split_by_weight() {
+        let total = 64 * 1024 * 1024;
+        let budget = ReadCacheBudget::new(total);
+
+        let sum: usize = CachedTable::iter()
+            .map(|table| budget.capacity(table))
+            .sum();
+        assert!(sum <= total);
+        assert_eq!(
+            2 * budget.capacity(CachedTable::MutatorSetAocl),
+            budget.capacity(CachedTable::MutatorSetChunks)
+        );
+        assert_eq!(
+            budget.capacity(CachedTable::MutatorSetSwbfInactive),
+            budget.capacity(CachedTable::ArchivalBlockMmr)
+        );
+    }
+
+    #[test]
+    fn empty_budget_disables_caching() {
+        let budget = ReadCacheBudget::new(0);
+        assert!(CachedTable::iter().all(|table| budget.capacity(table) == 0));
+    }
+}
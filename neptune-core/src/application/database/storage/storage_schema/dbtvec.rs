@@ -8,6 +8,7 @@ use serde::Serialize;
 use super::super::storage_vec::traits::*;
 use super::super::storage_vec::Index;
 use super::dbtvec_private::DbtVecPrivate;
+use super::read_cache::ReadCacheMetrics;
 use super::traits::*;
 use super::PendingWrites;
 use super::SimpleRustyReader;
@@ -44,6 +45,20 @@ where
     pub(crate) async fn delete_cache(&mut self) {
         self.inner.delete_cache().await;
     }
+
+    /// Keep up to `capacity` bytes of recently read elements in memory, such
+    /// that repeatedly read elements are not fetched from the database every
+    /// time. A capacity of zero disables the read cache, which is the
+    /// default.
+    pub(crate) fn with_read_cache(mut self, capacity: usize) -> Self {
+        self.inner.set_read_cache_capacity(capacity);
+        self
+    }
+
+    /// Counters describing the effectiveness of the read cache, if enabled.
+    pub(crate) fn read_cache_metrics(&self) -> Option<ReadCacheMetrics> {
+        self.inner.read_cache_metrics()
+    }
 }
 
 #[async_trait::async_trait]
@@ -212,6 +227,57 @@ mod tests {
         assert_eq!(2, vec.len().await);
     }
 
+    #[apply(shared_tokio_runtime)]
+    async fn read_cache_never_serves_overwritten_values() {
+        let db = NeptuneLevelDb::open_new_test_database(true, None, None, None)
+            .await
+            .unwrap();
+        let mut rusty_storage = SimpleRustyStorage::new(db);
+        let mut vec = rusty_storage
+            .schema
+            .new_vec::<u64>("test-vector")
+            .await
+            .with_read_cache(1000);
+
+        vec.push(1).await;
+        vec.push(2).await;
+        rusty_storage.persist().await;
+
+        // values of pending writes are not read from the database
+        assert_eq!(vec![1, 2], vec.get_many(&[0, 1]).await);
+        assert_eq!(0, vec.read_cache_metrics().unwrap().misses);
+
+        // once persisted, reads go through the read cache
+        vec.push(3).await;
+        assert_eq!(1, vec.get(0).await);
+        assert_eq!(1, vec.get(0).await);
+        assert_eq!(vec![1, 2], vec.get_many(&[0, 1]).await);
+        let metrics = vec.read_cache_metrics().unwrap();
+        assert_eq!(2, metrics.hits);
+        assert_eq!(2, metrics.misses);
+        assert_eq!(2, metrics.len);
+
+        // overwritten values are not served, neither before nor after
+        // persisting, nor after the write is dropped
+        vec.set(0, 10).await;
+        assert_eq!(10, vec.get(0).await);
+        rusty_storage.drop_unpersisted().await;
+        vec.delete_cache().await;
+        assert_eq!(1, vec.get(0).await);
+
+        vec.set(1, 20).await;
+        rusty_storage.persist().await;
+        vec.push(4).await;
+        assert_eq!(20, vec.get(1).await);
+
+        assert!(rusty_storage
+            .schema
+            .new_vec::<u64>("uncached-vector")
+            .await
+            .read_cache_metrics()
+            .is_none());
+    }
+
     pub mod streams {
         use macro_rules_attr::apply;
 
@@ -2,12 +2,16 @@ use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
 
 use itertools::Itertools;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
 use super::super::storage_vec::Index;
+use super::read_cache::ReadCache;
+use super::read_cache::ReadCacheMetrics;
 use super::traits::StorageReader;
 use super::PendingWrites;
 use super::RustyKey;
@@ -22,6 +26,10 @@ pub(super) struct DbtVecPrivate<V> {
     pub(super) current_length: Option<Index>,
     pub(super) key_prefix: u8,
     pub(super) cache: HashMap<Index, V>,
+
+    /// Recently read persisted values, if enabled. Unlike `cache`, which
+    /// holds the values of pending writes, it survives persisting.
+    read_cache: Option<Mutex<ReadCache<V>>>,
     persist_count: usize,
     pub(super) name: String,
     phantom: std::marker::PhantomData<V>,
@@ -37,6 +45,7 @@ where
             .field("current_length", &self.current_length)
             .field("key_prefix", &self.key_prefix)
             .field("cache", &self.cache)
+            .field("read_cache", &self.read_cache)
             .field("name", &self.name)
             .finish()
     }
@@ -63,6 +72,11 @@ impl<V: Clone + Serialize + DeserializeOwned> DbtVecPrivate<V> {
                 .clone();
         }
 
+        // then try recently read values
+        if let Some(value) = self.read_cache_get(index) {
+            return value;
+        }
+
         // then try persistent storage
         let key: RustyKey = self.get_index_key(index);
         let val = self.reader.get(key).await.unwrap_or_else(|| {
@@ -71,7 +85,10 @@ impl<V: Clone + Serialize + DeserializeOwned> DbtVecPrivate<V> {
                 self.name
             )
         });
-        val.into_any()
+        let value: V = val.into_any();
+        self.read_cache_insert(index, &value, &val);
+
+        value
     }
 
     #[inline]
@@ -141,14 +158,59 @@ where
             reader,
             current_length: length,
             cache,
+            read_cache: None,
             persist_count,
             name: name.to_string(),
             phantom: Default::default(),
         }
     }
 
+    /// Keep up to `capacity` bytes of recently read persisted values in
+    /// memory. A capacity of zero disables the read cache.
+    pub(super) fn set_read_cache_capacity(&mut self, capacity: usize) {
+        self.read_cache = (capacity > 0).then(|| Mutex::new(ReadCache::new(capacity)));
+    }
+
+    pub(super) fn read_cache_metrics(&self) -> Option<ReadCacheMetrics> {
+        self.read_cache.as_ref().map(|read_cache| {
+            read_cache
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .metrics()
+        })
+    }
+
+    fn read_cache_get(&self, index: Index) -> Option<V> {
+        self.read_cache
+            .as_ref()?
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(index)
+    }
+
+    fn read_cache_insert(&self, index: Index, value: &V, encoding: &RustyValue) {
+        if let Some(read_cache) = &self.read_cache {
+            read_cache
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .insert(index, value.clone(), encoding.0.len());
+        }
+    }
+
+    /// Forget the read value at `index`, which is about to be overwritten or
+    /// deleted.
+    fn read_cache_remove(&mut self, index: Index) {
+        if let Some(read_cache) = &mut self.read_cache {
+            read_cache
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner)
+                .remove(index);
+        }
+    }
+
     #[inline]
     async fn write_op_overwrite(&mut self, index: Index, value: V) {
+        self.read_cache_remove(index);
         let index_key = self.get_index_key(index);
 
         let persist_count = {
@@ -227,6 +289,15 @@ where
             fetched_elements.insert(index_position, value);
         }
 
+        let mut indices_of_elements_not_in_cache = indices_of_elements_not_in_cache;
+        indices_of_elements_not_in_cache.retain(|&(index_position, index)| {
+            let Some(value) = self.read_cache_get(index) else {
+                return true;
+            };
+            fetched_elements.insert(index_position, value);
+            false
+        });
+
         let no_need_to_lock_database = indices_of_elements_not_in_cache.is_empty();
         if no_need_to_lock_database {
             return sort_to_match_requested_index_order(fetched_elements);
@@ -241,13 +312,16 @@ where
             .get_many(keys_for_indices_not_in_cache)
             .await
             .into_iter()
-            .map(|x| x.expect("there should be some value").into_any());
+            .map(|x| x.expect("there should be some value"));
 
-        let indexed_fetched_elements_from_db = indices_of_elements_not_in_cache
+        for (&(index_position, index), encoding) in indices_of_elements_not_in_cache
             .iter()
-            .map(|&(index_position, _)| index_position)
-            .zip_eq(elements_fetched_from_db);
-        fetched_elements.extend(indexed_fetched_elements_from_db);
+            .zip_eq(elements_fetched_from_db)
+        {
+            let value: V = encoding.into_any();
+            self.read_cache_insert(index, &value, &encoding);
+            fetched_elements.insert(index_position, value);
+        }
 
         sort_to_match_requested_index_order(fetched_elements)
     }
@@ -339,6 +413,7 @@ where
         *current_length -= 1;
 
         let new_length = *current_length;
+        self.read_cache_remove(new_length);
         let index_key = self.get_index_key(new_length);
 
         let persist_count = {
@@ -371,6 +446,7 @@ where
         // record in cache
         let current_length = self.len().await;
         let new_length = current_length + 1;
+        self.read_cache_remove(current_length);
         let index_key = self.get_index_key(current_length);
 
         let persist_count = {
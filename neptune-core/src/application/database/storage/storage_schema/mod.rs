@@ -27,6 +27,7 @@ mod dbtvec;
 mod dbtvec_private;
 mod enums;
 mod pending_writes;
+mod read_cache;
 mod rusty_key;
 mod rusty_reader;
 mod rusty_value;
@@ -40,6 +41,7 @@ pub use dbtsingleton::*;
 pub use dbtvec::*;
 pub use enums::*;
 use pending_writes::*;
+pub use read_cache::ReadCacheMetrics;
 pub use rusty_key::*;
 pub use rusty_reader::*;
 pub use rusty_value::*;
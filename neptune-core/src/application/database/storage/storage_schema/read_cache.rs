@@ -0,0 +1,218 @@
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::super::storage_vec::Index;
+
+/// Counters describing the effectiveness of a [`ReadCache`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ReadCacheMetrics {
+    /// Number of reads served from the cache.
+    pub hits: u64,
+
+    /// Number of reads that went to the database.
+    pub misses: u64,
+
+    /// Number of elements removed to make room for new ones.
+    pub evictions: u64,
+
+    /// Number of elements currently cached.
+    pub len: usize,
+
+    /// Encoded size, in bytes, of the elements currently cached.
+    pub size: usize,
+
+    /// Maximum encoded size, in bytes, of the cached elements.
+    pub capacity: usize,
+}
+
+impl ReadCacheMetrics {
+    /// The fraction of reads served from the cache, or `None` if nothing was
+    /// read yet.
+    pub fn hit_ratio(&self) -> Option<f64> {
+        let num_reads = self.hits + self.misses;
+        (num_reads != 0).then(|| self.hits as f64 / num_reads as f64)
+    }
+}
+
+/// A cached element, along with its encoded size and the time it was last
+/// read.
+#[derive(Debug, Clone)]
+struct CachedElement<V> {
+    value: V,
+    size: usize,
+    last_read: u64,
+}
+
+/// Keeps the most recently read elements of a persisted vector in memory, up
+/// to a total encoded size, so that hot elements are not repeatedly fetched
+/// from and decoded by the database.
+///
+/// Only persisted values may be cached. Callers must [remove](Self::remove)
+/// an element whenever it is overwritten, so that a stale value is never
+/// served, even if the write is dropped before it is persisted.
+///
+/// When full, the least recently read element is evicted.
+#[derive(Debug, Clone)]
+pub(crate) struct ReadCache<V> {
+    elements: HashMap<Index, CachedElement<V>>,
+    read_order: BTreeMap<u64, Index>,
+    clock: u64,
+    size: usize,
+    capacity: usize,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl<V: Clone> ReadCache<V> {
+    /// A cache for elements of a total encoded size of up to `capacity`
+    /// bytes.
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            elements: HashMap::new(),
+            read_order: BTreeMap::new(),
+            clock: 0,
+            size: 0,
+            capacity,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    /// Return the cached element at `index`, if any, and mark it as recently
+    /// read.
+    pub(crate) fn get(&mut self, index: Index) -> Option<V> {
+        let now = self.tick();
+        let Some(element) = self.elements.get_mut(&index) else {
+            self.misses += 1;
+            return None;
+        };
+
+        self.hits += 1;
+        self.read_order.remove(&element.last_read);
+        self.read_order.insert(now, index);
+        element.last_read = now;
+
+        Some(element.value.clone())
+    }
+
+    /// Cache the persisted element at `index`, whose encoding is `size`
+    /// bytes long. Elements larger than the capacity are not cached.
+    pub(crate) fn insert(&mut self, index: Index, value: V, size: usize) {
+        if size > self.capacity {
+            return;
+        }
+
+        self.remove(index);
+        while self.size + size > self.capacity {
+            let Some((_, evicted)) = self.read_order.pop_first() else {
+                break;
+            };
+            if let Some(element) = self.elements.remove(&evicted) {
+                self.size -= element.size;
+                self.evictions += 1;
+            }
+        }
+
+        let now = self.tick();
+        self.read_order.insert(now, index);
+        self.elements.insert(
+            index,
+            CachedElement {
+                value,
+                size,
+                last_read: now,
+            },
+        );
+        self.size += size;
+    }
+
+    /// Forget the element at `index`, if cached.
+    pub(crate) fn remove(&mut self, index: Index) {
+        if let Some(element) = self.elements.remove(&index) {
+            self.read_order.remove(&element.last_read);
+            self.size -= element.size;
+        }
+    }
+
+    pub(crate) fn metrics(&self) -> ReadCacheMetrics {
+        ReadCacheMetrics {
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+            len: self.elements.len(),
+            size: self.size,
+            capacity: self.capacity,
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cached_elements_are_returned_and_counted() {
+        let mut cache = ReadCache::new(100);
+        assert_eq!(None, cache.get(1));
+        assert_eq!(Some(0.0), cache.metrics().hit_ratio());
+
+        cache.insert(1, "one", 10);
+        assert_eq!(Some("one"), cache.get(1));
+
+        let metrics = cache.metrics();
+        assert_eq!(1, metrics.hits);
+        assert_eq!(1, metrics.misses);
+        assert_eq!(1, metrics.len);
+        assert_eq!(10, metrics.size);
+        assert_eq!(Some(0.5), metrics.hit_ratio());
+        assert_eq!(None, ReadCache::<u8>::new(100).metrics().hit_ratio());
+    }
+
+    #[test]
+    fn least_recently_read_elements_are_evicted_when_full() {
+        let mut cache = ReadCache::new(30);
+        cache.insert(1, 'a', 10);
+        cache.insert(2, 'b', 10);
+        cache.insert(3, 'c', 10);
+
+        // reading 1 makes 2 the least recently read element
+        assert_eq!(Some('a'), cache.get(1));
+        cache.insert(4, 'd', 10);
+
+        assert_eq!(None, cache.get(2));
+        assert_eq!(Some('a'), cache.get(1));
+        assert_eq!(Some('c'), cache.get(3));
+        assert_eq!(Some('d'), cache.get(4));
+        assert_eq!(1, cache.metrics().evictions);
+        assert_eq!(30, cache.metrics().size);
+
+        // elements exceeding the capacity are never cached
+        cache.insert(5, 'e', 31);
+        assert_eq!(None, cache.get(5));
+        assert_eq!(3, cache.metrics().len);
+    }
+
+    #[test]
+    fn removed_and_replaced_elements_release_their_size() {
+        let mut cache = ReadCache::new(30);
+        cache.insert(1, 'a', 10);
+        cache.insert(1, 'b', 20);
+        assert_eq!(20, cache.metrics().size);
+        assert_eq!(Some('b'), cache.get(1));
+
+        cache.remove(1);
+        assert_eq!(0, cache.metrics().size);
+        assert_eq!(None, cache.get(1));
+    }
+}
@@ -1,5 +1,5 @@
 /// A unique identifier for a [Job](super::traits::Job)
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct JobId([u8; 12]);
 
 impl std::fmt::Display for JobId {
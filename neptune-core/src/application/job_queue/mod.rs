@@ -21,6 +21,11 @@
 //!
 //! There is no upper limit on the number of jobs. (except RAM).
 //!
+//! By default one job runs at a time. A queue started with
+//! [JobQueue::start_with_workers()] runs up to that many jobs simultaneously,
+//! and a job added with [JobQueue::add_job_with_dependencies()] does not start
+//! before the jobs it depends on have ended.
+//!
 //! Jobs may be of mixed (heterogenous) types in a single [JobQueue] instance.
 //! Any type that implements the [Job](traits::Job) trait may be a job.
 //!
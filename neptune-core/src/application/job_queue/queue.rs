@@ -1,5 +1,6 @@
 use std::collections::VecDeque;
 use std::fmt;
+use std::num::NonZero;
 use std::sync::Arc;
 use std::sync::Mutex;
 
@@ -7,6 +8,7 @@ use tokio::sync::mpsc;
 use tokio::sync::oneshot;
 use tokio::sync::watch;
 use tokio::task::JoinHandle;
+use tokio::task::JoinSet;
 
 use super::channels::JobCancelReceiver;
 use super::channels::JobCancelSender;
@@ -50,12 +52,23 @@ impl<P: Ord + Send + Sync + 'static> Drop for JobQueue<P> {
 impl<P: Ord + Send + Sync + 'static> JobQueue<P> {
     /// creates job queue and starts it processing.
     ///
+    /// the queue runs one job at a time. See [Self::start_with_workers()].
+    ///
     /// returns immediately.
     pub fn start() -> Self {
+        Self::start_with_workers(NonZero::<usize>::MIN)
+    }
+
+    /// creates job queue that runs up to `num_workers` jobs simultaneously
+    /// and starts it processing.
+    ///
+    /// returns immediately.
+    pub fn start_with_workers(num_workers: NonZero<usize>) -> Self {
         // create a SharedQueue that is shared between tokio tasks.
         let shared_queue = SharedQueue {
             jobs: VecDeque::new(),
-            current_job: None,
+            current_jobs: vec![],
+            num_workers,
         };
         let shared_queue: Arc<Mutex<SharedQueue<P>>> = Arc::new(Mutex::new(shared_queue));
 
@@ -67,7 +80,7 @@ impl<P: Ord + Send + Sync + 'static> JobQueue<P> {
         let process_jobs_task_handle =
             tokio::spawn(process_jobs(shared_queue.clone(), rx_stop, rx_job_added));
 
-        tracing::debug!("JobQueue: started new queue.");
+        tracing::debug!("JobQueue: started new queue with {num_workers} worker(s).");
 
         // construct and return JobQueue
         Self {
@@ -112,6 +125,24 @@ impl<P: Ord + Send + Sync + 'static> JobQueue<P> {
         &self,
         job: impl Into<Box<dyn Job>>,
         priority: P,
+    ) -> Result<JobHandle, AddJobError> {
+        self.add_job_with_dependencies(job, priority, vec![])
+    }
+
+    /// adds job to job-queue that must not start before the jobs identified
+    /// by `dependencies` have ended.
+    ///
+    /// Dependencies that are neither queued nor running, eg because they have
+    /// already ended, are ignored. A job whose dependencies are pending does
+    /// not occupy a worker, so other jobs, even of lower priority, run in the
+    /// meantime.
+    ///
+    /// returns a [`JobHandle`] that can be used to await or cancel the job.
+    pub fn add_job_with_dependencies(
+        &self,
+        job: impl Into<Box<dyn Job>>,
+        priority: P,
+        dependencies: Vec<JobId>,
     ) -> Result<JobHandle, AddJobError> {
         let (result_tx, result_rx) = oneshot::channel();
         let (cancel_tx, cancel_rx) = watch::channel::<()>(());
@@ -127,21 +158,18 @@ impl<P: Ord + Send + Sync + 'static> JobQueue<P> {
             cancel_tx: cancel_tx.clone(),
             cancel_rx,
             priority,
+            dependencies,
         };
 
-        // add job to queue and obtain number of jobs in queue and current-job (if any)
-        let (num_jobs, job_running) = {
+        // add job to queue and obtain number of jobs in queue and current-jobs (if any)
+        let (num_jobs, jobs_running) = {
             // acquire mutex lock
             let mut guard = self.shared_queue.lock().unwrap();
 
             // add job to job-queue
             guard.jobs.push_back(m);
 
-            let job_running = match &guard.current_job {
-                Some(j) => format!("#{} - {}", j.job_num, j.job_id),
-                None => "none".to_string(),
-            };
-            (guard.jobs.len(), job_running)
+            (guard.jobs.len(), guard.describe_current_jobs())
         }; // mutex lock released on drop
 
         // notify process_jobs task that a job was added.
@@ -149,10 +177,10 @@ impl<P: Ord + Send + Sync + 'static> JobQueue<P> {
 
         // log that job is added to the queue
         tracing::debug!(
-            "JobQueue: job added - {}  {} queued job(s).  job running: {}",
+            "JobQueue: job added - {}  {} queued job(s).  job(s) running: {}",
             job_id,
             num_jobs,
-            job_running
+            jobs_running
         );
 
         // create and return JobHandle
@@ -188,34 +216,58 @@ impl<P: Ord + Send + Sync + 'static> JobQueue<P> {
     /// returns total number of jobs, queued plus running.
     pub fn num_jobs(&self) -> usize {
         let guard = self.shared_queue.lock().unwrap();
-        guard.jobs.len() + guard.current_job.as_ref().map(|_| 1).unwrap_or(0)
+        guard.jobs.len() + guard.current_jobs.len()
     }
 
     /// returns number of queued jobs
     pub fn num_queued_jobs(&self) -> usize {
         self.shared_queue.lock().unwrap().jobs.len()
     }
+
+    /// returns the maximum number of jobs that run simultaneously.
+    pub fn num_workers(&self) -> NonZero<usize> {
+        self.shared_queue.lock().unwrap().num_workers
+    }
+
+    /// sets the maximum number of jobs that run simultaneously.
+    ///
+    /// Lowering the number does not interrupt running jobs; no new job starts
+    /// until fewer than `num_workers` jobs are running.
+    pub fn set_num_workers(&self, num_workers: NonZero<usize>) {
+        self.shared_queue.lock().unwrap().num_workers = num_workers;
+
+        // wake up the process_jobs task, which may now start more jobs.
+        if let Err(e) = self.tx_job_added.send(()) {
+            tracing::debug!("JobQueue: not processing jobs. {}", e);
+        }
+        tracing::debug!("JobQueue: now running up to {num_workers} job(s) at a time.");
+    }
 }
 
 /// implements the process_jobs task, spawned by JobQueue::start().
 ///
-/// this fn calls tokio::select!{} in a loop.  The select has two branches:
+/// this fn calls tokio::select!{} in a loop.  The select has three branches:
 /// 1. receive 'job_added' message over mpsc channel (unbounded)
-/// 2. receive 'stop' message over watch channel
+/// 2. a running job ends
+/// 3. receive 'stop' message over watch channel
+///
+/// Before each select, idle workers pick the highest priority queued jobs
+/// whose dependencies have ended.  Each picked job runs in its own task.
 ///
 /// job_added:
 ///
-/// When a 'job_added' msg is received, the highest priority queued job is picked
-/// to run next.  We await the job, and then send results to the JobHandle.
+/// A 'job_added' msg only wakes the loop, so that an idle worker can pick up
+/// the new job.  These messages are of type "()" so are as small as possible.
 ///
-/// Note that jobs can take a long time to run and thus msgs can pile up in the
-/// job_added channel, which is unbounded. These messages are of type "()" so
-/// are as small as possible.
+/// job ended:
+///
+/// The results of the job are sent to its JobHandle. Its worker becomes idle,
+/// and jobs depending on it may become eligible to run.
 ///
 /// stop:
 ///
-/// When a 'stop' msg is received we send a cancel msg to the current job (if any) and
-/// wait for it to complete. Then we exit the loop and return.
+/// When a 'stop' msg is received we send a cancel msg to the current jobs (if
+/// any) and wait for them to complete. Then we exit the loop and return.
 async fn process_jobs<P: Ord + Send + Sync + 'static>(
     shared_queue: Arc<Mutex<SharedQueue<P>>>,
     mut rx_stop: watch::Receiver<()>,
@@ -226,59 +278,65 @@ async fn process_jobs<P: Ord + Send + Sync + 'static>(
     // are added due to job priorities.
     let mut job_num: usize = 1;
 
-    // loop until 'stop' msg is received or job_added channel is closed.
-    //
-    // note:  this unbounded channel will grow in size as new job(s) are
-    // added while an existing job is running.  ie, we read from the
-    // channel after each job completes.
-    while rx_job_added.recv().await.is_some() {
-        // Find the next job to run, and the number of jobs left in queue
-        tracing::debug!("task process_jobs received JobAdded message.");
-        let (next_job, num_pending) = {
-            // acquire mutex lock
-            let mut guard = shared_queue.lock().unwrap();
+    // tasks of the jobs that are presently running.
+    let mut running_jobs = JoinSet::new();
 
-            // pick the highest priority job
-            guard
-                .jobs
-                .make_contiguous()
-                .sort_by(|a, b| b.priority.cmp(&a.priority));
-            let job = guard.jobs.pop_front().unwrap();
+    // loop until 'stop' msg is received or job_added channel is closed.
+    loop {
+        // keep idle workers busy with eligible jobs.
+        loop {
+            // Find the next job to run, and the number of jobs left in queue
+            let (next_job, num_pending) = {
+                // acquire mutex lock
+                let mut guard = shared_queue.lock().unwrap();
+                let Some(job) = guard.pop_next_eligible_job(job_num) else {
+                    break;
+                };
+                (job, guard.jobs.len())
+            }; // mutex lock is released when guard drops.
 
-            // set highest priority job as the current job
-            guard.current_job = Some(CurrentJob {
+            // log that we are starting a job
+            tracing::debug!(
+                "  *** JobQueue: begin job #{} - {} - {} queued job(s) ***",
                 job_num,
-                job_id: job.job_id,
-                cancel_tx: job.cancel_tx.clone(),
-            });
-
-            (job, guard.jobs.len())
-        }; // mutex lock is released when guard drops.
-
-        // log that we are starting a job
-        tracing::debug!(
-            "  *** JobQueue: begin job #{} - {} - {} queued job(s) ***",
-            job_num,
-            next_job.job_id,
-            num_pending
-        );
+                next_job.job_id,
+                num_pending
+            );
+
+            let QueuedJob {
+                job,
+                job_id,
+                result_tx,
+                cancel_rx,
+                ..
+            } = next_job;
+            let running_job = RunningJob {
+                job_num,
+                job_id,
+                result_tx,
+                timer: tokio::time::Instant::now(),
+            };
+            running_jobs.spawn(async move { (running_job, run_job(job, cancel_rx).await) });
 
-        // record time that job starts
-        let timer = tokio::time::Instant::now();
+            job_num += 1;
+        }
 
-        // spawn task that performs the job, either async or blocking.
-        let job_task_handle = if next_job.job.is_async() {
-            tokio::spawn(
-                async move { next_job.job.run_async_cancellable(next_job.cancel_rx).await },
-            )
-        } else {
-            tokio::task::spawn_blocking(move || next_job.job.run(next_job.cancel_rx))
-        };
+        let (running_job, job_completion) = tokio::select! {
+            // wake up, a job may have been added.
+            msg = rx_job_added.recv() => match msg {
+                Some(()) => continue,
+                None => break,
+            },
 
-        // execute job task and simultaneously listen for a 'stop' message.
-        let job_task_result = tokio::select! {
-            // execute the job task
-            job_task_result = job_task_handle => job_task_result,
+            // a running job ended.
+            Some(joined) = running_jobs.join_next() => match joined {
+                Ok(ended) => ended,
+                Err(e) => {
+                    // the task only awaits the job task, which catches panics.
+                    tracing::error!("JobQueue: job task failed. {}", e);
+                    continue;
+                }
+            },
 
             // handle msg over 'stop' channel which indicates we must exit the loop.
             _ = rx_stop.changed() => {
@@ -290,58 +348,72 @@ async fn process_jobs<P: Ord + Send + Sync + 'static>(
             },
         };
 
-        // create JobCompletion from task results
-        let job_completion = match job_task_result {
-            Ok(jc) => jc,
-            Err(e) => {
-                if e.is_panic() {
-                    JobCompletion::Panicked(e.into_panic())
-                } else if e.is_cancelled() {
-                    JobCompletion::Cancelled
-                } else {
-                    unreachable!()
-                }
-            }
-        };
-
         // log that job has ended.
         tracing::debug!(
             "  *** JobQueue: ended job #{} - {} - Completion: {} - {} secs ***",
-            job_num,
-            next_job.job_id,
+            running_job.job_num,
+            running_job.job_id,
             job_completion,
-            timer.elapsed().as_secs_f32()
+            running_job.timer.elapsed().as_secs_f32()
         );
-        job_num += 1;
 
-        // obtain mutex lock and set current-job to None
-        shared_queue.lock().unwrap().current_job = None;
+        // obtain mutex lock and remove job from current-jobs
+        shared_queue
+            .lock()
+            .unwrap()
+            .current_jobs
+            .retain(|j| j.job_id != running_job.job_id);
 
         // send job results to the JobHandle receiver
-        if let Err(e) = next_job.result_tx.send(job_completion) {
+        if let Err(e) = running_job.result_tx.send(job_completion) {
             tracing::warn!("job-handle dropped? {}", e);
         }
     }
     tracing::debug!("task process_jobs exiting");
 }
 
+/// runs a job in a task, either async or blocking, and awaits it.
+async fn run_job(job: Box<dyn Job>, cancel_rx: JobCancelReceiver) -> JobCompletion {
+    // spawn task that performs the job, either async or blocking.
+    let job_task_handle = if job.is_async() {
+        tokio::spawn(async move { job.run_async_cancellable(cancel_rx).await })
+    } else {
+        tokio::task::spawn_blocking(move || job.run(cancel_rx))
+    };
+
+    // create JobCompletion from task results
+    match job_task_handle.await {
+        Ok(jc) => jc,
+        Err(e) => {
+            if e.is_panic() {
+                JobCompletion::Panicked(e.into_panic())
+            } else if e.is_cancelled() {
+                JobCompletion::Cancelled
+            } else {
+                unreachable!()
+            }
+        }
+    }
+}
+
 /// handles the 'stop' branch of tokio::select!{} in process_job() task
 async fn handle_stop_signal<P: Ord + Send + Sync + 'static>(
     shared_queue: &Arc<Mutex<SharedQueue<P>>>,
 ) {
     tracing::debug!("task process_jobs received Stop message.");
 
-    // acquire mutex lock and obtain current_job info, if any.
-    let maybe_info = shared_queue
+    // acquire mutex lock and obtain current_jobs info, if any.
+    let current_jobs = shared_queue
         .lock()
         .unwrap()
-        .current_job
-        .as_ref()
-        .map(|cj| (cj.job_id, cj.cancel_tx.clone()));
-
-    // if there is a presently executing job we need to cancel it
-    // and wait for it to complete.
-    if let Some((job_id, cancel_tx)) = maybe_info {
+        .current_jobs
+        .iter()
+        .map(|cj| (cj.job_id, cj.cancel_tx.clone()))
+        .collect::<Vec<_>>();
+
+    // if there are presently executing jobs we need to cancel them
+    // and wait for them to complete.
+    for (job_id, cancel_tx) in current_jobs {
         match cancel_tx.send(()) {
             Ok(()) => {
                 // wait for channel to close, indicating job has cancelled (or otherwise completed)
@@ -371,6 +443,7 @@ pub(super) struct QueuedJob<P> {
     cancel_tx: JobCancelSender,
     cancel_rx: JobCancelReceiver,
     priority: P,
+    dependencies: Vec<JobId>,
 }
 
 impl<P: fmt::Debug> fmt::Debug for QueuedJob<P> {
@@ -382,11 +455,12 @@ impl<P: fmt::Debug> fmt::Debug for QueuedJob<P> {
             .field("cancel_tx", &"JobCancelSender")
             .field("cancel_rx", &"JobCancelReceiver")
             .field("priority", &self.priority)
+            .field("dependencies", &self.dependencies)
             .finish()
     }
 }
 
-/// represents the currently executing job
+/// represents a currently executing job
 #[derive(Debug)]
 pub(super) struct CurrentJob {
     job_num: usize,
@@ -394,11 +468,70 @@ pub(super) struct CurrentJob {
     cancel_tx: JobCancelSender,
 }
 
+/// the part of a running job that the process_jobs task needs once the job
+/// ends.
+struct RunningJob {
+    job_num: usize,
+    job_id: JobId,
+    result_tx: JobResultSender,
+    timer: tokio::time::Instant,
+}
+
 /// represents data shared between tasks/threads
 #[derive(Debug)]
 pub(super) struct SharedQueue<P: Ord> {
     jobs: VecDeque<QueuedJob<P>>,
-    current_job: Option<CurrentJob>,
+    current_jobs: Vec<CurrentJob>,
+    num_workers: NonZero<usize>,
+}
+
+impl<P: Ord> SharedQueue<P> {
+    /// returns true if the job is queued or running.
+    fn is_pending(&self, job_id: JobId) -> bool {
+        self.jobs.iter().any(|j| j.job_id == job_id)
+            || self.current_jobs.iter().any(|j| j.job_id == job_id)
+    }
+
+    /// removes the highest priority job whose dependencies have all ended and
+    /// sets it as a current job, if a worker is idle.
+    ///
+    /// when multiple jobs have the same priority, the oldest one is picked.
+    fn pop_next_eligible_job(&mut self, job_num: usize) -> Option<QueuedJob<P>> {
+        if self.current_jobs.len() >= self.num_workers.get() {
+            return None;
+        }
+
+        // stable sort, so FIFO order is preserved within a priority level.
+        self.jobs
+            .make_contiguous()
+            .sort_by(|a, b| b.priority.cmp(&a.priority));
+        let index = self
+            .jobs
+            .iter()
+            .position(|job| !job.dependencies.iter().any(|d| self.is_pending(*d)))?;
+        let job = self.jobs.remove(index)?;
+
+        self.current_jobs.push(CurrentJob {
+            job_num,
+            job_id: job.job_id,
+            cancel_tx: job.cancel_tx.clone(),
+        });
+
+        Some(job)
+    }
+
+    /// describes the current jobs, for logging.
+    fn describe_current_jobs(&self) -> String {
+        if self.current_jobs.is_empty() {
+            return "none".to_string();
+        }
+
+        self.current_jobs
+            .iter()
+            .map(|j| format!("#{} - {}", j.job_num, j.job_id))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
 }
 
 #[cfg(test)]
@@ -512,6 +645,24 @@ mod tests {
         workers::job_result_wrapper().await
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    #[traced_test]
+    async fn run_sync_jobs_in_parallel() -> anyhow::Result<()> {
+        workers::run_jobs_in_parallel(false).await
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[traced_test]
+    async fn run_async_jobs_in_parallel() -> anyhow::Result<()> {
+        workers::run_jobs_in_parallel(true).await
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[traced_test]
+    async fn dependent_job_waits_for_dependencies() -> anyhow::Result<()> {
+        workers::dependent_job_waits_for_dependencies().await
+    }
+
     mod workers {
         use super::*;
         use crate::application::job_queue::errors::JobHandleError;
@@ -1110,5 +1261,102 @@ mod tests {
 
             Ok(())
         }
+
+        type IntervalJobResult = JobResultWrapper<(Instant, Instant)>;
+
+        // a job that reports when it started and ended.
+        #[derive(Debug)]
+        struct IntervalJob {
+            duration: std::time::Duration,
+            is_async: bool,
+        }
+
+        #[async_trait::async_trait]
+        impl Job for IntervalJob {
+            fn is_async(&self) -> bool {
+                self.is_async
+            }
+
+            fn run(&self, _cancel_rx: JobCancelReceiver) -> JobCompletion {
+                let start = Instant::now();
+                std::thread::sleep(self.duration);
+                JobCompletion::Finished(IntervalJobResult::new((start, Instant::now())).into())
+            }
+
+            async fn run_async(&self) -> Box<dyn JobResult> {
+                let start = Instant::now();
+                tokio::time::sleep(self.duration).await;
+                IntervalJobResult::new((start, Instant::now())).into()
+            }
+        }
+
+        async fn interval(job_handle: JobHandle) -> anyhow::Result<(Instant, Instant)> {
+            Ok(IntervalJobResult::try_from(job_handle.await?)?.into_inner())
+        }
+
+        // tests that a queue with n workers runs n jobs simultaneously, and
+        // no more.
+        pub(super) async fn run_jobs_in_parallel(is_async: bool) -> anyhow::Result<()> {
+            let job_queue = JobQueue::start_with_workers(NonZero::new(3).unwrap());
+            let duration = std::time::Duration::from_millis(200);
+
+            let handles = (0..4)
+                .map(|_| {
+                    let job = IntervalJob { duration, is_async };
+                    job_queue.add_job(job, DoubleJobPriority::Low)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut intervals = vec![];
+            for handle in handles {
+                intervals.push(interval(handle).await?);
+            }
+
+            // the first three jobs overlap, the fourth waits for a worker.
+            let first_end = intervals[..3].iter().map(|(_, end)| *end).min().unwrap();
+            assert!(intervals[..3].iter().all(|(start, _)| *start < first_end));
+            assert!(intervals[3].0 >= first_end);
+
+            Ok(())
+        }
+
+        // tests that a job does not start before its dependencies have ended,
+        // and that other jobs run in the meantime.
+        pub(super) async fn dependent_job_waits_for_dependencies() -> anyhow::Result<()> {
+            let job_queue = JobQueue::start_with_workers(NonZero::new(2).unwrap());
+            let long = std::time::Duration::from_millis(200);
+            let short = std::time::Duration::from_millis(10);
+
+            let dependency = job_queue.add_job(
+                IntervalJob {
+                    duration: long,
+                    is_async: true,
+                },
+                DoubleJobPriority::Low,
+            )?;
+            let dependent = job_queue.add_job_with_dependencies(
+                IntervalJob {
+                    duration: short,
+                    is_async: true,
+                },
+                DoubleJobPriority::High,
+                vec![dependency.job_id()],
+            )?;
+            let independent = job_queue.add_job(
+                IntervalJob {
+                    duration: short,
+                    is_async: true,
+                },
+                DoubleJobPriority::Low,
+            )?;
+
+            let (_, dependency_end) = interval(dependency).await?;
+            let (dependent_start, _) = interval(dependent).await?;
+            let (independent_start, _) = interval(independent).await?;
+
+            assert!(dependent_start >= dependency_end);
+            assert!(independent_start < dependency_end);
+
+            Ok(())
+        }
     }
 }
@@ -33,6 +33,15 @@ pub enum RpcMethods {
     #[namespace(Namespace::Node)]
     Network,
 
+    #[namespace(Namespace::Node)]
+    RpcMetrics,
+
+    #[namespace(Namespace::Node)]
+    Health,
+
+    #[namespace(Namespace::Node)]
+    BlockValidationCacheMetrics,
+
     #[namespace(Namespace::Chain)]
     Height,
 
@@ -60,6 +69,12 @@ pub enum RpcMethods {
     #[namespace(Namespace::Chain)]
     TipAnnouncements,
 
+    #[namespace(Namespace::Chain)]
+    Checkpoints,
+
+    #[namespace(Namespace::Chain)]
+    MutatorSetStatistics,
+
     #[namespace(Namespace::Archival)]
     GetBlockDigest,
 
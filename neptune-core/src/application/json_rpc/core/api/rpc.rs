@@ -82,6 +82,27 @@ pub trait RpcApi: Sync + Send {
     }
     async fn network_call(&self, request: NetworkRequest) -> RpcResult<NetworkResponse>;
 
+    async fn rpc_metrics(&self) -> RpcResult<RpcMetricsResponse> {
+        self.rpc_metrics_call(RpcMetricsRequest {}).await
+    }
+    async fn rpc_metrics_call(&self, request: RpcMetricsRequest) -> RpcResult<RpcMetricsResponse>;
+
+    async fn health(&self) -> RpcResult<HealthResponse> {
+        self.health_call(HealthRequest {}).await
+    }
+    async fn health_call(&self, request: HealthRequest) -> RpcResult<HealthResponse>;
+
+    async fn block_validation_cache_metrics(
+        &self,
+    ) -> RpcResult<BlockValidationCacheMetricsResponse> {
+        self.block_validation_cache_metrics_call(BlockValidationCacheMetricsRequest {})
+            .await
+    }
+    async fn block_validation_cache_metrics_call(
+        &self,
+        request: BlockValidationCacheMetricsRequest,
+    ) -> RpcResult<BlockValidationCacheMetricsResponse>;
+
     /* Chain */
 
     async fn height(&self) -> RpcResult<HeightResponse> {
@@ -137,6 +158,21 @@ pub trait RpcApi: Sync + Send {
         request: TipAnnouncementsRequest,
     ) -> RpcResult<TipAnnouncementsResponse>;
 
+    async fn checkpoints(&self) -> RpcResult<CheckpointsResponse> {
+        self.checkpoints_call(CheckpointsRequest {}).await
+    }
+    async fn checkpoints_call(&self, request: CheckpointsRequest)
+        -> RpcResult<CheckpointsResponse>;
+
+    async fn mutator_set_statistics(&self) -> RpcResult<MutatorSetStatisticsResponse> {
+        self.mutator_set_statistics_call(MutatorSetStatisticsRequest {})
+            .await
+    }
+    async fn mutator_set_statistics_call(
+        &self,
+        request: MutatorSetStatisticsRequest,
+    ) -> RpcResult<MutatorSetStatisticsResponse>;
+
     /* Archival */
 
     async fn get_block_digests(&self, height: BFieldElement) -> RpcResult<GetBlockDigestsResponse> {
@@ -7,6 +7,7 @@ use serde_json::Value;
 use thiserror::Error;
 
 use crate::application::json_rpc::core::api::rpc::RpcError;
+use crate::application::rpc::request_limiter::RpcRequestRejection;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JsonRequest {
@@ -29,6 +30,10 @@ pub enum JsonError {
     InvalidParams,
     #[error("Internal error")]
     InternalError,
+    #[error("Server busy")]
+    ServerBusy,
+    #[error("Request timed out")]
+    RequestTimeout,
     #[error("Server error")]
     Custom {
         code: i32,
@@ -45,6 +50,8 @@ impl JsonError {
             Self::MethodNotFound => -32601,
             Self::InvalidParams => -32602,
             Self::InternalError => -32603,
+            Self::ServerBusy => -32001,
+            Self::RequestTimeout => -32002,
             Self::Custom { code, .. } => *code,
         }
     }
@@ -71,6 +78,15 @@ impl From<RpcError> for JsonError {
     }
 }
 
+impl From<RpcRequestRejection> for JsonError {
+    fn from(rejection: RpcRequestRejection) -> Self {
+        match rejection {
+            RpcRequestRejection::Busy => JsonError::ServerBusy,
+            RpcRequestRejection::TimedOut(_) => JsonError::RequestTimeout,
+        }
+    }
+}
+
 impl From<JsonError> for RpcError {
     fn from(err: JsonError) -> Self {
         match err {
@@ -119,6 +135,8 @@ impl<'de> Deserialize<'de> for JsonError {
             -32601 => Ok(JsonError::MethodNotFound),
             -32602 => Ok(JsonError::InvalidParams),
             -32603 => Ok(JsonError::InternalError),
+            -32001 => Ok(JsonError::ServerBusy),
+            -32002 => Ok(JsonError::RequestTimeout),
             code => Ok(JsonError::Custom {
                 code,
                 message: err.message,
@@ -1,10 +1,12 @@
 use std::collections::HashSet;
+use std::sync::Arc;
 
 use tokio::sync::mpsc;
 use tracing::warn;
 
 use crate::application::json_rpc::core::api::ops::Namespace;
 use crate::application::loops::channel::RPCServerToMain;
+use crate::application::rpc::request_limiter::RpcRequestLimiter;
 use crate::state::GlobalStateLock;
 
 #[derive(Clone, Debug)]
@@ -12,20 +14,30 @@ pub struct RpcServer {
     pub(crate) state: GlobalStateLock,
     pub(crate) to_main_tx: mpsc::Sender<RPCServerToMain>,
     pub(crate) unrestricted: bool,
+    pub(crate) request_limiter: Arc<RpcRequestLimiter>,
 }
 
 impl RpcServer {
     pub fn new(state: GlobalStateLock, unrestricted: Option<bool>) -> Self {
         let unrestricted = unrestricted.unwrap_or(state.cli().unsafe_rpc);
         let to_main_tx = state.rpc_server_to_main_tx();
+        let request_limiter = Arc::new(RpcRequestLimiter::from_cli(state.cli()));
 
         Self {
             state,
             to_main_tx,
             unrestricted,
+            request_limiter,
         }
     }
 
+    /// Use the given limiter instead of a dedicated one, such that the limits
+    /// are shared with other RPC servers.
+    pub fn with_request_limiter(mut self, request_limiter: Arc<RpcRequestLimiter>) -> Self {
+        self.request_limiter = request_limiter;
+        self
+    }
+
     /// Returns the enabled set of RPC namespaces with node configuration check.
     pub async fn enabled_namespaces(&self) -> HashSet<Namespace> {
         let state = self.state.lock_guard().await;
@@ -6,12 +6,14 @@ use crate::api::export::Timestamp;
 use crate::api::export::Transaction;
 use crate::application::json_rpc::core::api::rpc::*;
 use crate::application::json_rpc::core::model::block::RpcBlock;
+use crate::application::json_rpc::core::model::json::JsonError;
 use crate::application::json_rpc::core::model::message::*;
 use crate::application::json_rpc::core::model::mining::template::RpcBlockTemplate;
 use crate::application::json_rpc::core::model::mining::template::RpcBlockTemplateMetadata;
 use crate::application::json_rpc::core::model::wallet::mutator_set::RpcMsMembershipSnapshot;
 use crate::application::json_rpc::server::rpc::RpcServer;
 use crate::application::loops::channel::RPCServerToMain;
+use crate::application::rpc::server::node_health::NodeHealth;
 use crate::protocol::consensus::block::block_selector::BlockSelector;
 use crate::protocol::consensus::block::Block;
 use crate::protocol::consensus::block::FUTUREDATING_LIMIT;
@@ -24,6 +26,32 @@ impl RpcApi for RpcServer {
         })
     }
 
+    async fn rpc_metrics_call(&self, _: RpcMetricsRequest) -> RpcResult<RpcMetricsResponse> {
+        Ok(RpcMetricsResponse {
+            metrics: self.request_limiter.metrics(),
+        })
+    }
+
+    async fn health_call(&self, _: HealthRequest) -> RpcResult<HealthResponse> {
+        Ok(HealthResponse {
+            health: NodeHealth::from(&*self.state.lock_guard().await),
+        })
+    }
+
+    async fn block_validation_cache_metrics_call(
+        &self,
+        _: BlockValidationCacheMetricsRequest,
+    ) -> RpcResult<BlockValidationCacheMetricsResponse> {
+        Ok(BlockValidationCacheMetricsResponse {
+            metrics: self
+                .state
+                .lock_guard()
+                .await
+                .block_validation_cache
+                .metrics(),
+        })
+    }
+
     async fn height_call(&self, _: HeightRequest) -> RpcResult<HeightResponse> {
         let state = self.state.lock_guard().await;
 
@@ -114,6 +142,26 @@ impl RpcApi for RpcServer {
         })
     }
 
+    async fn checkpoints_call(&self, _: CheckpointsRequest) -> RpcResult<CheckpointsResponse> {
+        Ok(CheckpointsResponse {
+            checkpoints: self.state.lock_guard().await.checkpoints.all(),
+        })
+    }
+
+    async fn mutator_set_statistics_call(
+        &self,
+        _: MutatorSetStatisticsRequest,
+    ) -> RpcResult<MutatorSetStatisticsResponse> {
+        let statistics = self
+            .state
+            .lock_guard()
+            .await
+            .mutator_set_statistics()
+            .map_err(|_| RpcError::Server(JsonError::InternalError))?;
+
+        Ok(MutatorSetStatisticsResponse { statistics })
+    }
+
     async fn get_block_digest_call(
         &self,
         request: GetBlockDigestRequest,
@@ -594,6 +642,57 @@ pub mod tests {
         assert_eq!("main", rpc_server.network().await.unwrap().network);
     }
 
+    #[apply(shared_tokio_runtime)]
+    async fn rpc_metrics_reflect_limiter() {
+        let rpc_server = test_rpc_server().await;
+        let _ = rpc_server
+            .request_limiter
+            .run("node_network", rpc_server.network())
+            .await;
+
+        let metrics = rpc_server.rpc_metrics().await.unwrap().metrics;
+        assert_eq!(1, metrics.completed);
+        assert_eq!(0, metrics.in_flight);
+        assert_eq!(
+            rpc_server.state.cli().rpc_max_concurrent_requests,
+            metrics.max_concurrent
+        );
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn mutator_set_statistics_include_premine() {
+        let rpc_server = test_rpc_server().await;
+        let statistics = rpc_server
+            .mutator_set_statistics()
+            .await
+            .unwrap()
+            .statistics;
+        assert!(statistics.aocl_leaf_count > 0);
+        assert_eq!(BlockHeight::genesis(), statistics.tip_height);
+        assert_eq!(0, statistics.num_sampled_blocks);
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn health_reports_no_load_shedding_initially() {
+        let rpc_server = test_rpc_server().await;
+        let health = rpc_server.health().await.unwrap().health;
+        assert_eq!(Network::Main, health.network);
+        assert!(!health.load_shedding.active);
+        assert!(health.load_shedding.since.is_none());
+        assert!(!health.syncing);
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn no_checkpoints_are_published_by_default() {
+        let rpc_server = test_rpc_server().await;
+        assert!(rpc_server
+            .checkpoints()
+            .await
+            .unwrap()
+            .checkpoints
+            .is_empty());
+    }
+
     #[apply(shared_tokio_runtime)]
     async fn height_is_correct() {
         let rpc_server = test_rpc_server().await;
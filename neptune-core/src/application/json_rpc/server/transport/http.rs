@@ -14,6 +14,15 @@ use crate::application::json_rpc::core::model::json::JsonError;
 use crate::application::json_rpc::core::model::json::JsonRequest;
 use crate::application::json_rpc::core::model::json::JsonResponse;
 use crate::application::json_rpc::server::rpc::RpcServer;
+use crate::application::rpc::request_limiter::RpcRequestLimiter;
+
+/// State shared by all HTTP request handlers.
+#[derive(Clone)]
+#[allow(missing_debug_implementations)]
+struct HttpState {
+    router: Arc<RpcRouter>,
+    request_limiter: Arc<RpcRequestLimiter>,
+}
 
 impl RpcServer {
     /// Starts the HTTP RPC server.
@@ -26,9 +35,13 @@ impl RpcServer {
         let namespaces = self.enabled_namespaces().await;
         let router = RpcMethods::new_router(api, namespaces);
 
+        let state = HttpState {
+            router: Arc::new(router),
+            request_limiter: self.request_limiter.clone(),
+        };
         let app = Router::new()
             .route("/", post(Self::rpc_handler))
-            .with_state(Arc::new(router));
+            .with_state(state);
 
         axum::serve(listener, app).await.unwrap();
     }
@@ -95,8 +108,13 @@ impl RpcServer {
     ///     }
     /// }
     /// ```
+    ///
+    /// Requests are subject to the server's [`RpcRequestLimiter`]. A request
+    /// is rejected with [`JsonError::ServerBusy`] if too many requests are
+    /// executing already, and aborted with [`JsonError::RequestTimeout`] if it
+    /// takes too long. If the client disconnects, the request is cancelled.
     async fn rpc_handler(
-        State(router): State<Arc<RpcRouter>>,
+        State(state): State<HttpState>,
         // An optimization to avoid deserializing 2 times
         body: Result<Json<JsonRequest>, JsonRejection>,
     ) -> Json<JsonResponse> {
@@ -104,7 +122,14 @@ impl RpcServer {
             return Json(JsonResponse::error(None, JsonError::ParseError));
         };
 
-        let res = router.dispatch(&request.method, request.params).await;
+        let res = state
+            .request_limiter
+            .run(
+                &request.method,
+                state.router.dispatch(&request.method, request.params),
+            )
+            .await
+            .unwrap_or_else(|rejection| Err(rejection.into()));
         let response = match res {
             Ok(result) => JsonResponse::success(request.id, result),
             Err(error) => JsonResponse::error(request.id, error),
@@ -118,12 +143,14 @@ impl RpcServer {
 #[cfg_attr(coverage_nightly, coverage(off))]
 mod tests {
     use std::sync::Arc;
+    use std::time::Duration;
 
     use axum::extract::State;
     use axum::Json;
     use macro_rules_attr::apply;
     use serde_json::json;
 
+    use super::HttpState;
     use crate::application::json_rpc::core::api::ops::Namespace;
     use crate::application::json_rpc::core::api::ops::RpcMethods;
     use crate::application::json_rpc::core::api::server::router::RpcRouter;
@@ -132,12 +159,17 @@ mod tests {
     use crate::application::json_rpc::core::model::json::JsonResponse;
     use crate::application::json_rpc::server::rpc::RpcServer;
     use crate::application::json_rpc::server::service::tests::test_rpc_server;
+    use crate::application::rpc::request_limiter::RpcRequestLimiter;
     use crate::tests::shared_tokio_runtime;
 
-    async fn test_router() -> Arc<RpcRouter> {
+    async fn test_state(request_limiter: RpcRequestLimiter) -> HttpState {
         let api = Arc::new(test_rpc_server().await);
+        let router: RpcRouter = RpcMethods::new_router(api, [Namespace::Node].into());
 
-        Arc::new(RpcMethods::new_router(api, [Namespace::Node].into()))
+        HttpState {
+            router: Arc::new(router),
+            request_limiter: Arc::new(request_limiter),
+        }
     }
 
     #[apply(shared_tokio_runtime)]
@@ -145,7 +177,7 @@ mod tests {
         const TEST_METHOD: &str = "node_network";
         const UNKNOWN_TEST_METHOD: &str = "node_crash";
 
-        let router = test_router().await;
+        let state = test_state(RpcRequestLimiter::new(1, Duration::from_secs(10), [])).await;
 
         // 1. Valid -> Success
         let valid_req = JsonRequest {
@@ -155,7 +187,7 @@ mod tests {
             id: Some(json!(1)),
         };
         let Json(valid_res) =
-            RpcServer::rpc_handler(State(router.clone()), Ok(Json(valid_req))).await;
+            RpcServer::rpc_handler(State(state.clone()), Ok(Json(valid_req))).await;
         assert!(
             matches!(valid_res, JsonResponse::Success { id: Some(id), result, .. }
                 if id == json!(1) && result.is_object() // shouldn't be null
@@ -169,7 +201,7 @@ mod tests {
             params: json!([1, "x"]),
             id: Some(json!(2)),
         };
-        let Json(bad_res) = RpcServer::rpc_handler(State(router.clone()), Ok(Json(bad_req))).await;
+        let Json(bad_res) = RpcServer::rpc_handler(State(state.clone()), Ok(Json(bad_req))).await;
         assert!(
             matches!(bad_res, JsonResponse::Error { id: Some(id), error: JsonError::InvalidParams, .. }
                 if id == json!(2)
@@ -183,11 +215,30 @@ mod tests {
             params: json!([]),
             id: Some(json!(3)),
         };
-        let Json(unknown_res) = RpcServer::rpc_handler(State(router), Ok(Json(unknown_req))).await;
+        let Json(unknown_res) = RpcServer::rpc_handler(State(state), Ok(Json(unknown_req))).await;
         assert!(
             matches!(unknown_res, JsonResponse::Error { id: Some(id), error: JsonError::MethodNotFound, .. }
                 if id == json!(3)
             )
         );
     }
+
+    #[apply(shared_tokio_runtime)]
+    async fn rejects_requests_when_busy() {
+        let state = test_state(RpcRequestLimiter::new(0, Duration::from_secs(10), [])).await;
+
+        let request = JsonRequest {
+            jsonrpc: Some("2.0".into()),
+            method: "node_network".into(),
+            params: json!([]),
+            id: Some(json!(1)),
+        };
+        let Json(response) = RpcServer::rpc_handler(State(state.clone()), Ok(Json(request))).await;
+        assert!(
+            matches!(response, JsonResponse::Error { id: Some(id), error: JsonError::ServerBusy, .. }
+                if id == json!(1)
+            )
+        );
+        assert_eq!(1, state.request_limiter.metrics().rejected_busy);
+    }
 }
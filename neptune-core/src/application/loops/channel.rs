@@ -1,3 +1,5 @@
+pub mod backpressure;
+
 use std::net::SocketAddr;
 use std::sync::Arc;
 
@@ -7,13 +9,18 @@ use serde::Serialize;
 use tasm_lib::triton_vm::prelude::Digest;
 use tasm_lib::twenty_first::util_types::mmr::mmr_accumulator::MmrAccumulator;
 
+use crate::application::loops::channel::backpressure::ChannelMessage;
+use crate::application::loops::channel::backpressure::MessagePriority;
 use crate::application::loops::main_loop::proof_upgrader::UpgradeJob;
 use crate::protocol::consensus::block::block_height::BlockHeight;
 use crate::protocol::consensus::block::difficulty_control::ProofOfWork;
 use crate::protocol::consensus::block::Block;
 use crate::protocol::consensus::transaction::Transaction;
 use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
+use crate::protocol::peer::rendezvous::RendezvousIntroduction;
+use crate::protocol::peer::rendezvous::RendezvousRequest;
 use crate::protocol::peer::transaction_notification::TransactionNotification;
+use crate::protocol::peer::wallet_backup::WalletBackupMessage;
 use crate::protocol::proof_abstractions::mast_hash::MastHash;
 use crate::state::wallet::expected_utxo::ExpectedUtxo;
 use crate::state::wallet::monitored_utxo::MonitoredUtxo;
@@ -72,11 +79,26 @@ pub(crate) enum MinerToMain {
     NewBlockFound(NewBlockFound),
     BlockProposal(Box<(Block, Vec<ExpectedUtxo>)>),
 
+    /// Own block proposal with the indicated digest has not resulted in a
+    /// block within the expected horizon, possibly because guessers withhold
+    /// the solution.
+    ProposalExpired(Digest),
+
     /// Request main loop to shut down entire application and return the
     /// indicated exit code.
     Shutdown(i32),
 }
 
+impl ChannelMessage for MinerToMain {
+    fn priority(&self) -> MessagePriority {
+        match self {
+            MinerToMain::NewBlockFound(_) | MinerToMain::Shutdown(_) => MessagePriority::High,
+            MinerToMain::BlockProposal(_) => MessagePriority::Normal,
+            MinerToMain::ProposalExpired(_) => MessagePriority::Low,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct MainToPeerTaskBatchBlockRequest {
     /// The peer to whom this request should be directed.
@@ -121,6 +143,9 @@ pub(crate) enum MainToPeerTask {
     /// Request peer list from connected peers
     MakePeerDiscoveryRequest,
 
+    /// Ask connected peers for their tips, to fetch the blocks leading to them
+    RequestBlockNotification,
+
     /// Request peers from a specific peer to get peers further away
     MakeSpecificPeerDiscoveryRequest(SocketAddr),
 
@@ -132,6 +157,15 @@ pub(crate) enum MainToPeerTask {
 
     /// Disconnect from all peers
     DisconnectAll(),
+
+    /// Ask a specific peer for an introduction to another peer
+    RequestIntroduction(SocketAddr, RendezvousRequest),
+
+    /// Introduce a specific peer to another peer
+    Introduce(SocketAddr, RendezvousIntroduction),
+
+    /// Send a wallet backup message to a specific trusted peer
+    WalletBackup(SocketAddr, Box<WalletBackupMessage>),
 }
 
 impl MainToPeerTask {
@@ -141,6 +175,7 @@ impl MainToPeerTask {
             MainToPeerTask::RequestBlockBatch(_) => "req block batch",
             MainToPeerTask::PeerSynchronizationTimeout(_) => "peer sync timeout",
             MainToPeerTask::MakePeerDiscoveryRequest => "make peer discovery req",
+            MainToPeerTask::RequestBlockNotification => "req block notification",
             MainToPeerTask::MakeSpecificPeerDiscoveryRequest(_) => {
                 "make specific peer discovery req"
             }
@@ -148,6 +183,9 @@ impl MainToPeerTask {
             MainToPeerTask::Disconnect(_) => "disconnect",
             MainToPeerTask::DisconnectAll() => "disconnect all",
             MainToPeerTask::BlockProposalNotification(_) => "block proposal notification",
+            MainToPeerTask::RequestIntroduction(..) => "request introduction",
+            MainToPeerTask::Introduce(..) => "introduce",
+            MainToPeerTask::WalletBackup(..) => "wallet backup",
         }
         .to_string()
     }
@@ -161,10 +199,14 @@ impl MainToPeerTask {
             MainToPeerTask::RequestBlockBatch(_) => true,
             MainToPeerTask::PeerSynchronizationTimeout(_) => true,
             MainToPeerTask::MakePeerDiscoveryRequest => false,
+            MainToPeerTask::RequestBlockNotification => true,
             MainToPeerTask::MakeSpecificPeerDiscoveryRequest(_) => false,
             MainToPeerTask::TransactionNotification(_) => true,
             MainToPeerTask::Disconnect(_) => false,
             MainToPeerTask::DisconnectAll() => false,
+            MainToPeerTask::RequestIntroduction(..) => false,
+            MainToPeerTask::Introduce(..) => false,
+            MainToPeerTask::WalletBackup(..) => true,
         }
     }
 }
@@ -189,6 +231,22 @@ pub(crate) enum PeerTaskToMain {
     Transaction(Box<PeerTaskToMainTransaction>),
     BlockProposal(Box<Block>),
     DisconnectFromLongestLivedPeer,
+
+    /// Disconnect from the peer at this address, because a newer connection
+    /// to the same peer instance is kept instead.
+    DisconnectDuplicate(SocketAddr),
+
+    /// Introduce the recipient to the peer that asked for it, as a relay.
+    Introduce {
+        recipient: SocketAddr,
+        introduction: RendezvousIntroduction,
+    },
+
+    /// A relay introduced this node to a peer to connect to.
+    Introduced(RendezvousIntroduction),
+
+    /// A trusted peer reported on, or returned, this node's wallet backup.
+    WalletBackup(SocketAddr, Box<WalletBackupMessage>),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -207,11 +265,48 @@ impl PeerTaskToMain {
             PeerTaskToMain::Transaction(_) => "transaction",
             PeerTaskToMain::BlockProposal(_) => "block proposal",
             PeerTaskToMain::DisconnectFromLongestLivedPeer => "disconnect from longest lived peer",
+            PeerTaskToMain::DisconnectDuplicate(_) => "disconnect duplicate",
+            PeerTaskToMain::Introduce { .. } => "introduce",
+            PeerTaskToMain::Introduced(_) => "introduced",
+            PeerTaskToMain::WalletBackup(..) => "wallet backup",
         }
         .to_string()
     }
 }
 
+impl ChannelMessage for PeerTaskToMain {
+    fn priority(&self) -> MessagePriority {
+        match self {
+            PeerTaskToMain::NewBlocks(_) => MessagePriority::High,
+            PeerTaskToMain::AddPeerMaxBlockHeight { .. }
+            | PeerTaskToMain::RemovePeerMaxBlockHeight(_)
+            | PeerTaskToMain::BlockProposal(_)
+            | PeerTaskToMain::DisconnectDuplicate(_)
+            | PeerTaskToMain::DisconnectFromLongestLivedPeer => MessagePriority::Normal,
+
+            // Transactions and peer lists are announced repeatedly, and
+            // introductions are only hints. Wallet backups are reconciled
+            // periodically.
+            PeerTaskToMain::PeerDiscoveryAnswer(_)
+            | PeerTaskToMain::Transaction(_)
+            | PeerTaskToMain::Introduce { .. }
+            | PeerTaskToMain::Introduced(_)
+            | PeerTaskToMain::WalletBackup(..) => MessagePriority::Low,
+        }
+    }
+
+    fn into_spillable(self) -> Result<Vec<Block>, Self> {
+        match self {
+            PeerTaskToMain::NewBlocks(blocks) => Ok(blocks),
+            other => Err(other),
+        }
+    }
+
+    fn from_spilled(blocks: Vec<Block>) -> Option<Self> {
+        Some(PeerTaskToMain::NewBlocks(blocks))
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub(crate) struct ClaimUtxoData {
     /// Some(mutxo) if UTXO has already been mined. Otherwise, None.
@@ -237,6 +332,9 @@ pub enum RPCServerToMain {
     RestartMiner,
     SetTipToStoredBlock(Digest),
 
+    // Used by the replication task of standbys
+    ReplicatedBlocks(Vec<Block>),
+
     // Used by JSON-RPC
     SubmitTx(Box<Transaction>),
 }
@@ -0,0 +1,623 @@
+//! Bounded channels to the main task that apply an explicit policy when they
+//! are full.
+//!
+//! A plain [`mpsc::Sender::send`] waits indefinitely for capacity, so a main
+//! task that falls behind stalls every sender, and may deadlock if the main
+//! task itself waits on one of them. [`BackpressureSender`] instead applies an
+//! [`OverflowPolicy`] and records what happened in [`ChannelMetrics`].
+
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::SendError;
+use tokio::sync::mpsc::error::SendTimeoutError;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::time::Instant;
+use tracing::debug;
+use tracing::warn;
+
+use crate::application::config::retention_policy::TEMPORARY_FILE_EXTENSION;
+use crate::protocol::consensus::block::Block;
+
+const SPILL_FILE_EXTENSION: &str = "spill";
+
+/// What a [`BackpressureSender`] does with a message when the channel is
+/// full. Each policy extends the previous one.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    clap::ValueEnum,
+    strum::Display,
+)]
+pub enum OverflowPolicy {
+    /// Wait until the channel has capacity.
+    #[default]
+    Block,
+
+    /// Wait until the channel has capacity, but drop the message after a
+    /// timeout.
+    BlockWithTimeout,
+
+    /// Drop low-priority messages immediately, and wait with a timeout for
+    /// all others.
+    ShedLowestPriority,
+
+    /// Write messages carrying blocks to disk, to be delivered once the
+    /// channel has drained. Otherwise like `ShedLowestPriority`.
+    SpillToDisk,
+}
+
+/// How important it is that a message reaches the main task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum MessagePriority {
+    /// May be dropped when the channel is congested, e.g. because the sender
+    /// can repeat it or because it is only a hint. Never applies to control
+    /// messages, such as requests to disconnect peers.
+    Low,
+    Normal,
+    High,
+}
+
+/// A message that can be sent over a [`BackpressureSender`].
+pub(crate) trait ChannelMessage: Send + Debug + 'static {
+    fn priority(&self) -> MessagePriority;
+
+    /// Returns the blocks this message carries if it may be spilled to disk,
+    /// and the message itself otherwise.
+    fn into_spillable(self) -> Result<Vec<Block>, Self>
+    where
+        Self: Sized,
+    {
+        Err(self)
+    }
+
+    /// Reconstructs a message spilled to disk.
+    fn from_spilled(_blocks: Vec<Block>) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+}
+
+/// A snapshot of the congestion of a channel to the main task.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelMetrics {
+    pub name: String,
+    pub policy: OverflowPolicy,
+    pub capacity: usize,
+
+    /// Number of messages waiting in the channel.
+    pub num_queued: usize,
+
+    /// Number of messages that reached the channel, possibly after waiting.
+    pub num_sent: u64,
+
+    /// Number of sends that found the channel full.
+    pub num_full: u64,
+
+    /// Number of low-priority messages dropped because the channel was full.
+    pub num_shed: u64,
+
+    /// Number of messages dropped because the channel stayed full for too
+    /// long.
+    pub num_timed_out: u64,
+
+    /// Number of messages written to disk because the channel was full.
+    pub num_spilled: u64,
+
+    /// Number of spilled messages not yet delivered to the main task.
+    pub num_pending_spilled: u64,
+
+    /// Longest time a send waited for capacity.
+    pub max_wait: Duration,
+}
+
+/// Counters shared by all clones of a [`BackpressureSender`].
+struct ChannelStats {
+    name: &'static str,
+    policy: OverflowPolicy,
+    capacity: usize,
+    num_queued: Box<dyn Fn() -> usize + Send + Sync>,
+    spill_queue: Option<Arc<SpillQueue>>,
+    num_sent: AtomicU64,
+    num_full: AtomicU64,
+    num_shed: AtomicU64,
+    num_timed_out: AtomicU64,
+    num_spilled: AtomicU64,
+    max_wait_micros: AtomicU64,
+}
+
+impl Debug for ChannelStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChannelStats")
+            .field("name", &self.name)
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ChannelStats {
+    fn record_wait(&self, wait: Duration) {
+        let micros = u64::try_from(wait.as_micros()).unwrap_or(u64::MAX);
+        self.max_wait_micros.fetch_max(micros, Ordering::Relaxed);
+    }
+
+    fn metrics(&self) -> ChannelMetrics {
+        ChannelMetrics {
+            name: self.name.to_string(),
+            policy: self.policy,
+            capacity: self.capacity,
+            num_queued: (self.num_queued)(),
+            num_sent: self.num_sent.load(Ordering::Relaxed),
+            num_full: self.num_full.load(Ordering::Relaxed),
+            num_shed: self.num_shed.load(Ordering::Relaxed),
+            num_timed_out: self.num_timed_out.load(Ordering::Relaxed),
+            num_spilled: self.num_spilled.load(Ordering::Relaxed),
+            num_pending_spilled: self
+                .spill_queue
+                .as_ref()
+                .map(|spill_queue| spill_queue.len())
+                .unwrap_or_default(),
+            max_wait: Duration::from_micros(self.max_wait_micros.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// The channels to the main task whose congestion is reported over RPC.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ChannelMetricsRegistry(Vec<Arc<ChannelStats>>);
+
+impl ChannelMetricsRegistry {
+    pub(crate) fn register<T: ChannelMessage>(&mut self, sender: &BackpressureSender<T>) {
+        self.0.push(sender.stats.clone());
+    }
+
+    pub(crate) fn metrics(&self) -> Vec<ChannelMetrics> {
+        self.0.iter().map(|stats| stats.metrics()).collect()
+    }
+}
+
+/// Messages written to disk in the order they were spilled. Supports many
+/// writers and a single reader, the main task.
+#[derive(Debug)]
+pub(crate) struct SpillQueue {
+    directory: PathBuf,
+    next_write: AtomicU64,
+    next_read: AtomicU64,
+
+    /// Indices reserved by writes that failed, which the reader skips.
+    failed_writes: Mutex<HashSet<u64>>,
+}
+
+impl SpillQueue {
+    /// Creates a spill queue in `directory`, discarding messages spilled by a
+    /// previous run. Blocks lost this way are fetched again by
+    /// synchronization.
+    pub(crate) async fn new(directory: PathBuf) -> std::io::Result<Self> {
+        if tokio::fs::try_exists(&directory).await? {
+            tokio::fs::remove_dir_all(&directory).await?;
+        }
+        tokio::fs::create_dir_all(&directory).await?;
+
+        Ok(Self {
+            directory,
+            next_write: AtomicU64::new(0),
+            next_read: AtomicU64::new(0),
+            failed_writes: Mutex::new(HashSet::new()),
+        })
+    }
+
+    fn path(&self, index: u64) -> PathBuf {
+        self.directory
+            .join(format!("{index:020}.{SPILL_FILE_EXTENSION}"))
+    }
+
+    /// Number of spilled messages not yet read, including any that are still
+    /// being written.
+    fn len(&self) -> u64 {
+        self.next_write
+            .load(Ordering::SeqCst)
+            .saturating_sub(self.next_read.load(Ordering::SeqCst))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    async fn push(&self, blocks: &[Block]) -> anyhow::Result<()> {
+        let serialized = bincode::serialize(blocks)?;
+        let index = self.next_write.fetch_add(1, Ordering::SeqCst);
+
+        // Write under a temporary name so the reader never sees a partially
+        // written file.
+        let path = self.path(index);
+        let temporary_path = path.with_extension(TEMPORARY_FILE_EXTENSION);
+        let result = async {
+            tokio::fs::write(&temporary_path, serialized).await?;
+            tokio::fs::rename(&temporary_path, &path).await
+        }
+        .await;
+
+        if let Err(error) = result {
+            // The index is reserved, so the reader must be able to skip it.
+            warn!("Failed to spill message to {}: {error}", path.display());
+            let _ = tokio::fs::remove_file(&temporary_path).await;
+            self.failed_writes.lock().unwrap().insert(index);
+            return Err(error.into());
+        }
+
+        Ok(())
+    }
+
+    /// Returns the oldest spilled blocks, or `None` if there are none, or the
+    /// oldest is still being written.
+    async fn pop(&self) -> Option<Vec<Block>> {
+        loop {
+            if self.is_empty() {
+                return None;
+            }
+
+            let index = self.next_read.load(Ordering::SeqCst);
+            let write_failed = self.failed_writes.lock().unwrap().remove(&index);
+            if write_failed {
+                self.next_read.fetch_add(1, Ordering::SeqCst);
+                continue;
+            }
+
+            let path = self.path(index);
+            let serialized = tokio::fs::read(&path).await.ok()?;
+            let _ = tokio::fs::remove_file(&path).await;
+            self.next_read.fetch_add(1, Ordering::SeqCst);
+
+            match bincode::deserialize(&serialized) {
+                Ok(blocks) => return Some(blocks),
+                Err(error) => {
+                    debug!("Skipping unreadable spilled message {index}: {error}");
+                }
+            }
+        }
+    }
+}
+
+/// A bounded sender to the main task that applies an [`OverflowPolicy`] when
+/// the channel is full.
+///
+/// Dropping a message is not an error. Like [`mpsc::Sender::send`], sending
+/// only fails if the main task has closed the channel.
+#[derive(Debug)]
+pub(crate) struct BackpressureSender<T> {
+    sender: mpsc::Sender<T>,
+    timeout: Duration,
+    stats: Arc<ChannelStats>,
+}
+
+impl<T> Clone for BackpressureSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            timeout: self.timeout,
+            stats: self.stats.clone(),
+        }
+    }
+}
+
+impl<T: ChannelMessage> BackpressureSender<T> {
+    /// Wraps `sender`. `spill_queue` is required by, and only used with,
+    /// [`OverflowPolicy::SpillToDisk`].
+    pub(crate) fn new(
+        name: &'static str,
+        sender: mpsc::Sender<T>,
+        policy: OverflowPolicy,
+        timeout: Duration,
+        spill_queue: Option<SpillQueue>,
+    ) -> Self {
+        let policy = if policy == OverflowPolicy::SpillToDisk && spill_queue.is_none() {
+            warn!("No spill queue for channel \"{name}\". Not spilling messages to disk.");
+            OverflowPolicy::ShedLowestPriority
+        } else {
+            policy
+        };
+
+        let weak_sender = sender.downgrade();
+        let num_queued = move || {
+            weak_sender
+                .upgrade()
+                .map(|sender| sender.max_capacity() - sender.capacity())
+                .unwrap_or_default()
+        };
+
+        let stats = ChannelStats {
+            name,
+            policy,
+            capacity: sender.max_capacity(),
+            num_queued: Box::new(num_queued),
+            spill_queue: spill_queue
+                .filter(|_| policy == OverflowPolicy::SpillToDisk)
+                .map(Arc::new),
+            num_sent: AtomicU64::new(0),
+            num_full: AtomicU64::new(0),
+            num_shed: AtomicU64::new(0),
+            num_timed_out: AtomicU64::new(0),
+            num_spilled: AtomicU64::new(0),
+            max_wait_micros: AtomicU64::new(0),
+        };
+
+        Self {
+            sender,
+            timeout,
+            stats: Arc::new(stats),
+        }
+    }
+
+    /// Wraps `sender` with [`OverflowPolicy::Block`], which preserves the
+    /// behavior of the unwrapped sender.
+    pub(crate) fn blocking(name: &'static str, sender: mpsc::Sender<T>) -> Self {
+        Self::new(name, sender, OverflowPolicy::Block, Duration::MAX, None)
+    }
+
+    pub(crate) fn metrics(&self) -> ChannelMetrics {
+        self.stats.metrics()
+    }
+
+    /// Send a message, applying the overflow policy if the channel is full.
+    pub(crate) async fn send(&self, message: T) -> Result<(), SendError<T>> {
+        let stats = &self.stats;
+
+        // Once a message is spilled, subsequent spillable messages are
+        // spilled too, so that they are delivered in order.
+        let message = match &stats.spill_queue {
+            Some(spill_queue) if !spill_queue.is_empty() => match self.spill(message).await {
+                Ok(()) => return Ok(()),
+                Err(message) => message,
+            },
+            _ => message,
+        };
+
+        let message = match self.sender.try_send(message) {
+            Ok(()) => {
+                stats.num_sent.fetch_add(1, Ordering::Relaxed);
+                return Ok(());
+            }
+            Err(TrySendError::Closed(message)) => return Err(SendError(message)),
+            Err(TrySendError::Full(message)) => message,
+        };
+        stats.num_full.fetch_add(1, Ordering::Relaxed);
+
+        let sheds = matches!(
+            stats.policy,
+            OverflowPolicy::ShedLowestPriority | OverflowPolicy::SpillToDisk
+        );
+        if sheds && message.priority() == MessagePriority::Low {
+            debug!(
+                "Channel \"{}\" is full. Dropping low-priority message.",
+                stats.name
+            );
+            stats.num_shed.fetch_add(1, Ordering::Relaxed);
+            return Ok(());
+        }
+
+        let message = if stats.spill_queue.is_some() {
+            match self.spill(message).await {
+                Ok(()) => return Ok(()),
+                Err(message) => message,
+            }
+        } else {
+            message
+        };
+
+        let start = Instant::now();
+        let result = if stats.policy == OverflowPolicy::Block {
+            self.sender.send(message).await
+        } else {
+            match self.sender.send_timeout(message, self.timeout).await {
+                Ok(()) => Ok(()),
+                Err(SendTimeoutError::Closed(message)) => Err(SendError(message)),
+                Err(SendTimeoutError::Timeout(_)) => {
+                    warn!(
+                        "Channel \"{}\" stayed full for {:?}. Dropping message.",
+                        stats.name, self.timeout,
+                    );
+                    stats.num_timed_out.fetch_add(1, Ordering::Relaxed);
+                    stats.record_wait(start.elapsed());
+                    return Ok(());
+                }
+            }
+        };
+        stats.record_wait(start.elapsed());
+        if result.is_ok() {
+            stats.num_sent.fetch_add(1, Ordering::Relaxed);
+        }
+
+        result
+    }
+
+    /// Write the message to disk if it carries blocks. Returns the message if
+    /// it was not spilled.
+    async fn spill(&self, message: T) -> Result<(), T> {
+        let Some(spill_queue) = &self.stats.spill_queue else {
+            return Err(message);
+        };
+        let blocks = message.into_spillable()?;
+
+        match spill_queue.push(&blocks).await {
+            Ok(()) => {
+                self.stats.num_spilled.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            }
+            Err(_) => Err(T::from_spilled(blocks)
+                .expect("spillable messages must be reconstructible from their blocks")),
+        }
+    }
+
+    /// Returns the oldest message spilled to disk, if any. Must only be called
+    /// by the receiving task.
+    pub(crate) async fn take_spilled(&self) -> Option<T> {
+        let blocks = self.stats.spill_queue.as_ref()?.pop().await?;
+        T::from_spilled(blocks)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::net::SocketAddr;
+
+    use macro_rules_attr::apply;
+
+    use super::*;
+    use crate::application::config::network::Network;
+    use crate::application::loops::channel::PeerTaskToMain;
+    use crate::tests::shared::files::unit_test_data_directory;
+    use crate::tests::shared_tokio_runtime;
+
+    fn low_priority_message() -> PeerTaskToMain {
+        PeerTaskToMain::PeerDiscoveryAnswer((vec![], "127.0.0.1:9798".parse().unwrap(), 0))
+    }
+
+    fn normal_priority_message() -> PeerTaskToMain {
+        let peer_address: SocketAddr = "127.0.0.1:9798".parse().unwrap();
+        PeerTaskToMain::RemovePeerMaxBlockHeight(peer_address)
+    }
+
+    fn block_message(network: Network) -> PeerTaskToMain {
+        PeerTaskToMain::NewBlocks(vec![Block::genesis(network)])
+    }
+
+    async fn spill_queue(network: Network) -> SpillQueue {
+        let data_dir = unit_test_data_directory(network).unwrap();
+        SpillQueue::new(data_dir.channel_spill_dir_path())
+            .await
+            .unwrap()
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn congested_channel_times_out_instead_of_blocking_forever() {
+        let (sender, _receiver) = mpsc::channel(1);
+        let timeout = Duration::from_millis(50);
+        let sender = BackpressureSender::new(
+            "test",
+            sender,
+            OverflowPolicy::BlockWithTimeout,
+            timeout,
+            None,
+        );
+
+        sender.send(normal_priority_message()).await.unwrap();
+        sender.send(normal_priority_message()).await.unwrap();
+
+        let metrics = sender.metrics();
+        assert_eq!(1, metrics.num_sent);
+        assert_eq!(1, metrics.num_full);
+        assert_eq!(1, metrics.num_timed_out);
+        assert_eq!(1, metrics.num_queued);
+        assert!(metrics.max_wait >= timeout);
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn congested_channel_sheds_low_priority_messages_only() {
+        let (sender, mut receiver) = mpsc::channel(1);
+        let sender = BackpressureSender::new(
+            "test",
+            sender,
+            OverflowPolicy::ShedLowestPriority,
+            Duration::from_secs(10),
+            None,
+        );
+
+        sender.send(low_priority_message()).await.unwrap();
+        sender.send(low_priority_message()).await.unwrap();
+        assert_eq!(1, sender.metrics().num_shed);
+
+        // A normal-priority message waits for capacity.
+        let blocked_sender = sender.clone();
+        let send =
+            tokio::spawn(async move { blocked_sender.send(normal_priority_message()).await });
+        assert_eq!(low_priority_message(), receiver.recv().await.unwrap());
+        send.await.unwrap().unwrap();
+        assert_eq!(normal_priority_message(), receiver.recv().await.unwrap());
+
+        let metrics = sender.metrics();
+        assert_eq!(2, metrics.num_sent);
+        assert_eq!(1, metrics.num_shed);
+        assert_eq!(0, metrics.num_timed_out);
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn congested_channel_spills_blocks_to_disk_in_order() {
+        let network = Network::Main;
+        let (sender, mut receiver) = mpsc::channel(1);
+        let sender = BackpressureSender::new(
+            "test",
+            sender,
+            OverflowPolicy::SpillToDisk,
+            Duration::from_secs(10),
+            Some(spill_queue(network).await),
+        );
+
+        sender.send(normal_priority_message()).await.unwrap();
+        sender.send(block_message(network)).await.unwrap();
+        assert_eq!(1, sender.metrics().num_spilled);
+
+        // Blocks are spilled while earlier blocks are pending, even if the
+        // channel has capacity.
+        assert_eq!(normal_priority_message(), receiver.recv().await.unwrap());
+        sender.send(block_message(network)).await.unwrap();
+        assert_eq!(2, sender.metrics().num_spilled);
+        assert_eq!(2, sender.metrics().num_pending_spilled);
+
+        assert_eq!(Some(block_message(network)), sender.take_spilled().await);
+        assert_eq!(Some(block_message(network)), sender.take_spilled().await);
+        assert_eq!(None, sender.take_spilled().await);
+        assert_eq!(0, sender.metrics().num_pending_spilled);
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn failed_spills_are_skipped() {
+        let network = Network::Main;
+        let spill_queue = spill_queue(network).await;
+        spill_queue.push(&[Block::genesis(network)]).await.unwrap();
+
+        // Writes fail once the directory is gone.
+        let directory = spill_queue.directory.clone();
+        tokio::fs::rename(&directory, directory.with_extension("moved"))
+            .await
+            .unwrap();
+        assert!(spill_queue.push(&[Block::genesis(network)]).await.is_err());
+        tokio::fs::rename(directory.with_extension("moved"), &directory)
+            .await
+            .unwrap();
+        spill_queue.push(&[Block::genesis(network)]).await.unwrap();
+
+        assert_eq!(3, spill_queue.len());
+        assert!(spill_queue.pop().await.is_some());
+        assert!(spill_queue.pop().await.is_some());
+        assert!(spill_queue.pop().await.is_none());
+        assert!(spill_queue.is_empty());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn closed_channel_is_an_error() {
+        let (sender, receiver) = mpsc::channel(1);
+        let sender = BackpressureSender::blocking("test", sender);
+        drop(receiver);
+
+        assert!(sender.send(normal_priority_message()).await.is_err());
+    }
+}
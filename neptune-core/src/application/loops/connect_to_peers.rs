@@ -1,14 +1,12 @@
 use std::fmt::Debug;
 use std::net::IpAddr;
 use std::net::SocketAddr;
-use std::time::Duration;
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
 
 use anyhow::bail;
 use anyhow::ensure;
 use anyhow::Result;
-use bincode::Options;
 use chrono::DateTime;
 use chrono::Utc;
 use futures::FutureExt;
@@ -16,23 +14,34 @@ use futures::SinkExt;
 use futures::TryStreamExt;
 use tokio::io::AsyncRead;
 use tokio::io::AsyncWrite;
+use tokio::net::TcpStream;
 use tokio::sync::broadcast;
-use tokio::sync::mpsc;
-use tokio::sync::OwnedSemaphorePermit;
-use tokio::time::timeout;
-use tokio_serde::formats::Bincode;
 use tokio_serde::SymmetricallyFramed;
 use tokio_util::codec::Framed;
-use tokio_util::codec::LengthDelimitedCodec;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
 use tracing::warn;
 
 use crate::application::config::cli_args;
+use crate::application::loops::channel::backpressure::BackpressureSender;
 use crate::application::loops::channel::MainToPeerTask;
 use crate::application::loops::channel::PeerTaskToMain;
 use crate::application::loops::peer_loop::PeerLoopHandler;
+use crate::protocol::peer::connection_lifecycle::ConnectionLifecycle;
+use crate::protocol::peer::connection_lifecycle::ConnectionState;
+use crate::protocol::peer::connection_lifecycle::HandshakePermit;
+use crate::protocol::peer::handshake_data::MESSAGE_SEQUENCE_CAPABILITY;
+use crate::protocol::peer::handshake_data::ZSTD_COMPRESSION_CAPABILITY;
+use crate::protocol::peer::message_sequence::SequenceViolations;
+use crate::protocol::peer::peer_codec::PeerCodec;
+use crate::protocol::peer::peer_message_format;
+use crate::protocol::peer::peer_message_format::PeerMessageFormat;
+use crate::protocol::peer::plausible_deniability::is_own_instance_id;
+use crate::protocol::peer::rendezvous;
+use crate::protocol::peer::rendezvous::RendezvousIntroduction;
+use crate::protocol::peer::rendezvous::RENDEZVOUS_ATTEMPT_INTERVAL;
+use crate::protocol::peer::rendezvous::RENDEZVOUS_NUM_ATTEMPTS;
 use crate::protocol::peer::ConnectionRefusedReason;
 use crate::protocol::peer::InternalConnectionStatus;
 use crate::protocol::peer::NegativePeerSanction;
@@ -40,6 +49,7 @@ use crate::protocol::peer::PeerMessage;
 use crate::protocol::peer::PeerSanction;
 use crate::protocol::peer::PeerStanding;
 use crate::protocol::peer::TransferConnectionStatus;
+use crate::state::GlobalState;
 use crate::state::GlobalStateLock;
 use crate::HandshakeData;
 use crate::MAGIC_STRING_REQUEST;
@@ -55,28 +65,52 @@ const PEER_TIME_DIFFERENCE_THRESHOLD_IN_SECONDS: u128 = 90;
 
 /// Use this function to ensure that the same rules apply for both
 /// ingoing and outgoing connections. This limits the size of messages
-/// peers can send.
-fn get_codec_rules() -> LengthDelimitedCodec {
-    let mut codec_rules = LengthDelimitedCodec::new();
-    codec_rules.set_max_frame_length(MAX_PEER_FRAME_LENGTH_IN_BYTES);
-    codec_rules
+/// peers can send, per message type.
+fn get_codec_rules() -> PeerCodec {
+    PeerCodec::new(MAX_PEER_FRAME_LENGTH_IN_BYTES)
+        .with_message_size_limit(peer_message_format::max_message_size_from_prefix)
 }
 
-/// Returns a bincode codec with allocation limits to prevent OOM attacks.
-///
-/// This prevents "length bomb" attacks where a malicious peer sends a tiny
-/// frame claiming to contain a Vec with billions of elements. Without limits,
-/// bincode would pre-allocate memory based on the claimed length, causing OOM.
+/// Switch the connection to compressed frames if both parties advertised
+/// support for it in the handshake. Must be called by both parties after the
+/// connection status has been exchanged, and before any other message is sent.
+fn negotiate_compression(
+    codec: &mut PeerCodec,
+    cli: &cli_args::Args,
+    own_handshake: &HandshakeData,
+    other_handshake: &HandshakeData,
+    peer_address: SocketAddr,
+) {
+    if own_handshake.has_capability(ZSTD_COMPRESSION_CAPABILITY)
+        && other_handshake.has_capability(ZSTD_COMPRESSION_CAPABILITY)
+    {
+        let threshold =
+            usize::try_from(cli.peer_compression_threshold.as_u64()).unwrap_or(usize::MAX);
+        codec.enable_compression(threshold);
+        debug!("Enabled zstd compression on connection to {peer_address}");
+    }
+}
+
+/// Switch the connection to sequence-numbered frames if both parties
+/// advertised support for it in the handshake. Must be called by both parties
+/// right after [`negotiate_compression`].
 ///
-/// The limit is set to match MAX_PEER_FRAME_LENGTH_IN_BYTES (500MB).
-fn get_bincode_codec<Item, SinkItem>() -> Bincode<Item, SinkItem, impl Options + Copy>
-where
-    Item: serde::de::DeserializeOwned,
-    SinkItem: serde::Serialize,
-{
-    bincode::DefaultOptions::new()
-        .with_limit(MAX_PEER_FRAME_LENGTH_IN_BYTES as u64)
-        .into()
+/// Returns the violations of the peer's numbering, which remain zero if
+/// sequencing was not negotiated.
+fn negotiate_sequencing(
+    codec: &mut PeerCodec,
+    own_handshake: &HandshakeData,
+    other_handshake: &HandshakeData,
+    peer_address: SocketAddr,
+) -> SequenceViolations {
+    if own_handshake.has_capability(MESSAGE_SEQUENCE_CAPABILITY)
+        && other_handshake.has_capability(MESSAGE_SEQUENCE_CAPABILITY)
+    {
+        debug!("Enabled message sequencing on connection to {peer_address}");
+        return codec.enable_sequencing();
+    }
+
+    SequenceViolations::default()
 }
 
 /// Returns true iff version numbers are compatible. Returns false otherwise.
@@ -157,6 +191,19 @@ pub(crate) fn precheck_incoming_connection_is_allowed(
     true
 }
 
+/// Returns true iff the handshake was made by this node itself.
+///
+/// The handshake's instance ID is a random nonce drawn at startup or, in
+/// plausible deniability mode, derived per connection from such a nonce.
+fn is_self_connection(
+    global_state: &GlobalState,
+    own_handshake: &HandshakeData,
+    other_handshake: &HandshakeData,
+) -> bool {
+    own_handshake.instance_id == other_handshake.instance_id
+        || is_own_instance_id(global_state.net.instance_id, other_handshake.instance_id)
+}
+
 /// Check if connection is allowed. Used for both ingoing and outgoing connections.
 ///
 /// Locking:
@@ -170,6 +217,12 @@ async fn check_if_connection_is_allowed(
     let cli_arguments = global_state_lock.cli();
     let global_state = global_state_lock.lock_guard().await;
 
+    // Disallow connection to self. Checked first, such that no other reason
+    // masks it.
+    if is_self_connection(&global_state, own_handshake, other_handshake) {
+        return InternalConnectionStatus::Refused(ConnectionRefusedReason::SelfConnect);
+    }
+
     // Disallow connection if peer is banned via CLI arguments
     if cli_arguments.ban.contains(&peer_address.ip()) {
         let ip = peer_address.ip();
@@ -262,11 +315,6 @@ async fn check_if_connection_is_allowed(
         }
     }
 
-    // Disallow connection to self
-    if own_handshake.instance_id == other_handshake.instance_id {
-        return InternalConnectionStatus::Refused(ConnectionRefusedReason::SelfConnect);
-    }
-
     // Disallow connection if versions are incompatible
     if !versions_are_compatible(&own_handshake.version, &other_handshake.version) {
         warn!(
@@ -294,16 +342,16 @@ async fn check_if_connection_is_allowed(
 /// All incoming connections from peers must go through this function.
 ///
 /// The `handshake_permit` is released after the handshake completes (success or
-/// failure), not when the connection closes. This prevents semaphore starvation
-/// attacks where an attacker holds connections idle to exhaust permits.
+/// failure), not when the connection closes. This prevents starvation attacks
+/// where an attacker holds connections idle to exhaust permits.
 pub(crate) async fn answer_peer<S>(
     stream: S,
     state_lock: GlobalStateLock,
     peer_address: std::net::SocketAddr,
     main_to_peer_task_rx: broadcast::Receiver<MainToPeerTask>,
-    peer_task_to_main_tx: mpsc::Sender<PeerTaskToMain>,
+    peer_task_to_main_tx: BackpressureSender<PeerTaskToMain>,
     own_handshake_data: HandshakeData,
-    handshake_permit: Option<OwnedSemaphorePermit>,
+    handshake_permit: Option<HandshakePermit>,
 ) -> Result<()>
 where
     S: AsyncRead + AsyncWrite + std::fmt::Debug + std::marker::Unpin,
@@ -362,23 +410,33 @@ async fn answer_peer_inner<S>(
     state: GlobalStateLock,
     peer_address: SocketAddr,
     main_to_peer_task_rx: broadcast::Receiver<MainToPeerTask>,
-    peer_task_to_main_tx: mpsc::Sender<PeerTaskToMain>,
+    peer_task_to_main_tx: BackpressureSender<PeerTaskToMain>,
     own_handshake_data: HandshakeData,
-    handshake_permit: Option<OwnedSemaphorePermit>,
+    handshake_permit: Option<HandshakePermit>,
 ) -> Result<()>
 where
     S: AsyncRead + AsyncWrite + Debug + Unpin,
 {
     debug!("Established incoming TCP connection with {peer_address}");
+    let (bandwidth, mut lifecycle) = state
+        .lock(|s| {
+            (
+                s.bandwidth.register(peer_address),
+                s.connection_lifecycles.track(peer_address),
+            )
+        })
+        .await;
+    lifecycle.transition(ConnectionState::Handshaking)?;
 
     // Build the communication/serialization/frame handler
     let length_delimited = Framed::new(stream, get_codec_rules());
-    let mut peer = SymmetricallyFramed::new(length_delimited, get_bincode_codec());
+    let mut peer = SymmetricallyFramed::new(
+        length_delimited,
+        PeerMessageFormat::with_bandwidth(bandwidth),
+    );
 
     // Complete Neptune handshake
-    let handshake_timeout: u64 = state.cli().handshake_timeout.into();
-    let handshake_timeout = Duration::from_secs(handshake_timeout);
-    let maybe_msg = timeout(handshake_timeout, peer.try_next()).await;
+    let maybe_msg = lifecycle.within(peer.try_next()).await;
     let (magic_value, peer_handshake) = match maybe_msg {
         Ok(Ok(Some(PeerMessage::Handshake { magic_value, data }))) => (magic_value, data),
         Ok(Ok(_)) => {
@@ -392,8 +450,12 @@ where
             return Ok(());
         }
         Err(_) => {
-            // no heavy anyhow::Error, just close
-            tracing::warn!(%peer_address, "handshake timed out");
+            // no heavy anyhow::Error, just close. timeout is logged by
+            // lifecycle. a partially received handshake indicates a peer that
+            // trickles its bytes to keep the connection open.
+            if !peer.get_ref().read_buffer().is_empty() {
+                lifecycle.record_slow_handshake();
+            }
             return Ok(());
         }
     };
@@ -408,7 +470,7 @@ where
         magic_value: *MAGIC_STRING_RESPONSE,
         data: Box::new(own_handshake_data),
     };
-    timeout(handshake_timeout, peer.send(handshake_response)).await??;
+    lifecycle.within(peer.send(handshake_response)).await??;
 
     // Verify peer network before moving on
     let peer_network = peer_handshake.network;
@@ -427,11 +489,9 @@ where
         &peer_address,
     )
     .await;
-    timeout(
-        handshake_timeout,
-        peer.send(PeerMessage::ConnectionStatus(connection_status.into())),
-    )
-    .await??;
+    lifecycle
+        .within(peer.send(PeerMessage::ConnectionStatus(connection_status.into())))
+        .await??;
     if let InternalConnectionStatus::Refused(reason) = connection_status {
         let reason = format!("Refusing incoming connection. Reason: {reason:?}");
         debug!("{reason}");
@@ -448,6 +508,20 @@ where
     // The connection is now governed by max_num_peers, not the semaphore.
     drop(handshake_permit);
 
+    negotiate_compression(
+        peer.get_mut().codec_mut(),
+        state.cli(),
+        &own_handshake_data,
+        &peer_handshake,
+        peer_address,
+    );
+    let sequence_violations = negotiate_sequencing(
+        peer.get_mut().codec_mut(),
+        &own_handshake_data,
+        &peer_handshake,
+        peer_address,
+    );
+
     // If necessary, disconnect from another, existing peer.
     if connection_status == InternalConnectionStatus::AcceptedMaxReached && state.cli().bootstrap {
         info!("Maximum # peers reached, so disconnecting from an existing peer.");
@@ -464,7 +538,9 @@ where
         *peer_handshake,
         true,
         peer_distance,
-    );
+    )
+    .with_lifecycle(lifecycle)
+    .with_sequence_violations(sequence_violations);
 
     peer_loop_handler
         .run_wrapper(peer, main_to_peer_task_rx)
@@ -481,15 +557,138 @@ pub(crate) async fn call_peer(
     peer_address: std::net::SocketAddr,
     state: GlobalStateLock,
     main_to_peer_task_rx: broadcast::Receiver<MainToPeerTask>,
-    peer_task_to_main_tx: mpsc::Sender<PeerTaskToMain>,
+    peer_task_to_main_tx: BackpressureSender<PeerTaskToMain>,
     own_handshake_data: HandshakeData,
     peer_distance: u8,
+) {
+    call_peer_with_attempts(
+        peer_address,
+        state,
+        main_to_peer_task_rx,
+        peer_task_to_main_tx,
+        own_handshake_data,
+        peer_distance,
+        1,
+    )
+    .await;
+}
+
+/// Connect to a peer that a relay introduced this node to, see
+/// [rendezvous](crate::protocol::peer::rendezvous).
+///
+/// Both introduced peers connect to each other at the same time, and the
+/// resulting connection is handled as outgoing by the one that asked for the
+/// introduction and as incoming by the other.
+pub(crate) async fn rendezvous_with_peer(
+    introduction: RendezvousIntroduction,
+    state: GlobalStateLock,
+    main_to_peer_task_rx: broadcast::Receiver<MainToPeerTask>,
+    peer_task_to_main_tx: BackpressureSender<PeerTaskToMain>,
+    own_handshake_data: HandshakeData,
+) {
+    let peer_address = introduction.peer_address;
+    if introduction.initiator {
+        // The introduced peer is a peer of one of this node's peers.
+        let peer_distance = 2;
+        call_peer_with_attempts(
+            peer_address,
+            state,
+            main_to_peer_task_rx,
+            peer_task_to_main_tx,
+            own_handshake_data,
+            peer_distance,
+            RENDEZVOUS_NUM_ATTEMPTS,
+        )
+        .await;
+        return;
+    }
+
+    let stream = match open_stream(peer_address, state.cli(), RENDEZVOUS_NUM_ATTEMPTS).await {
+        Ok(stream) => stream,
+        Err(e) => {
+            debug!("Failed to establish TCP connection to introduced peer {peer_address}: {e}");
+            return;
+        }
+    };
+    if let Err(e) = answer_peer(
+        stream,
+        state,
+        peer_address,
+        main_to_peer_task_rx,
+        peer_task_to_main_tx,
+        own_handshake_data,
+        None,
+    )
+    .await
+    {
+        debug!("Connection to introduced peer {peer_address} failed: {e}");
+    }
+}
+
+/// Open a TCP connection to a peer, retrying up to `num_attempts` times in
+/// total.
+///
+/// Nodes that take part in rendezvous make all outgoing connections from the
+/// same local port, such that their peers observe the address that introduced
+/// peers must connect to.
+async fn open_stream(
+    peer_address: SocketAddr,
+    cli: &cli_args::Args,
+    num_attempts: usize,
+) -> std::io::Result<TcpStream> {
+    let mut attempt = 1;
+    loop {
+        let stream = if cli.rendezvous {
+            rendezvous::connect_from_port(peer_address, cli.rendezvous_port).await
+        } else {
+            TcpStream::connect(peer_address).await
+        };
+        match stream {
+            Err(e) if attempt < num_attempts => {
+                debug!("Attempt {attempt} to connect to {peer_address} failed: {e}");
+                attempt += 1;
+                tokio::time::sleep(RENDEZVOUS_ATTEMPT_INTERVAL).await;
+            }
+            stream => return stream,
+        }
+    }
+}
+
+async fn call_peer_with_attempts(
+    peer_address: std::net::SocketAddr,
+    state: GlobalStateLock,
+    main_to_peer_task_rx: broadcast::Receiver<MainToPeerTask>,
+    peer_task_to_main_tx: BackpressureSender<PeerTaskToMain>,
+    own_handshake_data: HandshakeData,
+    peer_distance: u8,
+    num_attempts: usize,
 ) {
     let state_clone = state.clone();
     let peer_task_to_main_tx_clone = peer_task_to_main_tx.clone();
     let panic_result = std::panic::AssertUnwindSafe(async {
+        if state
+            .lock_guard()
+            .await
+            .net
+            .self_addresses
+            .contains(&peer_address)
+        {
+            debug!("Not connecting to {peer_address}, which leads to this node itself");
+            return;
+        }
+
         debug!("Attempting to initiate connection to {peer_address}");
-        match tokio::net::TcpStream::connect(peer_address).await {
+        let lifecycle = state
+            .lock_guard()
+            .await
+            .connection_lifecycles
+            .track(peer_address);
+        let stream = lifecycle
+            .within(open_stream(peer_address, state.cli(), num_attempts))
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|connected| connected.map_err(anyhow::Error::from));
+        match stream {
             Err(e) => {
                 let msg = format!("Failed to establish TCP connection to {peer_address}: {e}");
                 if peer_distance == 1 {
@@ -510,6 +709,7 @@ pub(crate) async fn call_peer(
                     peer_task_to_main_tx,
                     &own_handshake_data,
                     peer_distance,
+                    lifecycle,
                 )
                 .await
                 {
@@ -549,31 +749,37 @@ async fn call_peer_inner<S>(
     state: GlobalStateLock,
     peer_address: std::net::SocketAddr,
     main_to_peer_task_rx: broadcast::Receiver<MainToPeerTask>,
-    peer_task_to_main_tx: mpsc::Sender<PeerTaskToMain>,
+    peer_task_to_main_tx: BackpressureSender<PeerTaskToMain>,
     own_handshake: &HandshakeData,
     peer_distance: u8,
+    mut lifecycle: ConnectionLifecycle,
 ) -> Result<()>
 where
     S: AsyncRead + AsyncWrite + Debug + Unpin,
 {
     debug!("Established outgoing TCP connection with {peer_address}");
+    lifecycle.transition(ConnectionState::Handshaking)?;
 
     // Build the communication/serialization/frame handler
     let length_delimited = Framed::new(stream, get_codec_rules());
-    let mut peer = SymmetricallyFramed::new(length_delimited, get_bincode_codec());
+    let bandwidth = state.lock_guard().await.bandwidth.register(peer_address);
+    let mut peer = SymmetricallyFramed::new(
+        length_delimited,
+        PeerMessageFormat::with_bandwidth(bandwidth),
+    );
 
     // Make Neptune handshake
     let outgoing_handshake = PeerMessage::Handshake {
         magic_value: *MAGIC_STRING_REQUEST,
         data: Box::new(own_handshake.to_owned()),
     };
-    peer.send(outgoing_handshake).await?;
+    lifecycle.within(peer.send(outgoing_handshake)).await??;
     debug!("Awaiting connection status response from {peer_address}");
 
     let Some(PeerMessage::Handshake {
         magic_value,
         data: other_handshake,
-    }) = peer.try_next().await?
+    }) = lifecycle.within(peer.try_next()).await??
     else {
         bail!("Didn't get handshake response from {peer_address}");
     };
@@ -589,7 +795,19 @@ where
         bail!("Cannot connect with {peer_address}: Peer runs {other}, this client runs {own}.");
     }
 
-    match peer.try_next().await? {
+    // Hang up right away if the address leads back to this node, and do not
+    // dial it again.
+    {
+        let mut global_state = state.lock_guard_mut().await;
+        if is_self_connection(&global_state, own_handshake, &other_handshake) {
+            global_state.net.self_addresses.insert(peer_address);
+            drop(global_state);
+            lifecycle.transition(ConnectionState::Draining)?;
+            bail!("Address {peer_address} leads to this node itself. Not dialing it again.");
+        }
+    }
+
+    match lifecycle.within(peer.try_next()).await?? {
         Some(PeerMessage::ConnectionStatus(TransferConnectionStatus::Accepted)) => {
             debug!("Outgoing connection accepted by {peer_address}");
         }
@@ -603,6 +821,22 @@ where
         }
     }
 
+    // The peer switches to compressed frames right after accepting us, so we
+    // must do the same before sending anything else, including a `Bye`.
+    negotiate_compression(
+        peer.get_mut().codec_mut(),
+        state.cli(),
+        own_handshake,
+        &other_handshake,
+        peer_address,
+    );
+    let sequence_violations = negotiate_sequencing(
+        peer.get_mut().codec_mut(),
+        own_handshake,
+        &other_handshake,
+        peer_address,
+    );
+
     // Peer accepted us. Check if we accept the peer. Note that the protocol does not stipulate
     // that we answer with a connection status here, so if the connection is *not* accepted, we
     // simply hang up but log the reason for the refusal.
@@ -618,7 +852,8 @@ where
             "Outgoing connection to {peer_address} refused. Reason: {:?}\nNow hanging up.",
             refused_reason
         );
-        peer.send(PeerMessage::Bye).await?;
+        lifecycle.transition(ConnectionState::Draining)?;
+        lifecycle.within(peer.send(PeerMessage::Bye)).await??;
         bail!("Attempted to connect to peer ({peer_address}) that was not allowed. This connection attempt should not have been made.");
     }
 
@@ -626,7 +861,9 @@ where
     // context, we want the network topology to be as robust as possible.
     // Blockchain data can be obtained from other peers, if this connection
     // fails.
-    peer.send(PeerMessage::PeerListRequest).await?;
+    lifecycle
+        .within(peer.send(PeerMessage::PeerListRequest))
+        .await??;
 
     let mut peer_loop_handler = PeerLoopHandler::new(
         peer_task_to_main_tx,
@@ -635,7 +872,9 @@ where
         *other_handshake,
         false,
         peer_distance,
-    );
+    )
+    .with_lifecycle(lifecycle)
+    .with_sequence_violations(sequence_violations);
 
     info!("Established outgoing connection to {peer_address}");
     peer_loop_handler
@@ -654,7 +893,7 @@ where
 pub(crate) async fn close_peer_connected_callback(
     mut global_state_lock: GlobalStateLock,
     peer_address: SocketAddr,
-    to_main_tx: &mpsc::Sender<PeerTaskToMain>,
+    to_main_tx: &BackpressureSender<PeerTaskToMain>,
 ) {
     let cli_arguments = global_state_lock.cli().clone();
     let mut global_state_mut = global_state_lock.lock_guard_mut().await;
@@ -681,7 +920,7 @@ pub(crate) async fn close_peer_connected_callback(
         .net
         .write_peer_standing_on_decrease(peer_address.ip(), new_standing)
         .await;
-    drop(global_state_mut); // avoid holding across mpsc::Sender::send()
+    drop(global_state_mut); // avoid holding across BackpressureSender::send()
     debug!("Stored peer info standing {new_standing} for peer {peer_address}");
 
     // This message is used to determine if we are to exit synchronization mode
@@ -694,21 +933,28 @@ pub(crate) async fn close_peer_connected_callback(
 #[cfg(test)]
 #[cfg_attr(coverage_nightly, coverage(off))]
 mod tests {
+    use std::pin::Pin;
     use std::str::FromStr;
     use std::time::Duration;
     use std::time::SystemTime;
 
     use anyhow::bail;
     use anyhow::Result;
+    use bytes::Bytes;
+    use bytes::BytesMut;
     use macro_rules_attr::apply;
     use tasm_lib::twenty_first::tip5::digest::Digest;
     use test_strategy::proptest;
+    use tokio_serde::formats::SymmetricalBincode;
+    use tokio_serde::Serializer;
     use tokio_test::io::Builder;
+    use tokio_util::codec::Encoder;
     use tracing_test::traced_test;
 
     use super::*;
     use crate::application::config::cli_args;
     use crate::application::config::network::Network;
+    use crate::protocol::peer::connection_lifecycle::ConnectionLifecycleError;
     use crate::protocol::peer::handshake_data::VersionString;
     use crate::protocol::peer::peer_info::PeerInfo;
     use crate::protocol::peer::InternalConnectionStatus;
@@ -761,14 +1007,19 @@ mod tests {
 
         let (_peer_broadcast_tx, from_main_rx_clone, to_main_tx, _to_main_rx1, state, _hsd) =
             get_test_genesis_setup(network, 0, cli_args::Args::default()).await?;
+        let peer_address = get_dummy_socket_address(0);
+        let lifecycle = state
+            .lock(|s| s.connection_lifecycles.track(peer_address))
+            .await;
         call_peer_inner(
             mock,
             state.clone(),
-            get_dummy_socket_address(0),
+            peer_address,
             from_main_rx_clone,
             to_main_tx,
             &own_handshake,
             1,
+            lifecycle,
         )
         .await?;
 
@@ -781,6 +1032,162 @@ mod tests {
         Ok(())
     }
 
+    #[traced_test]
+    #[apply(shared_tokio_runtime)]
+    async fn outgoing_connection_switches_to_compression_when_both_support_it() -> Result<()> {
+        fn to_compressed_bytes(message: &PeerMessage) -> Result<Bytes> {
+            let mut codec = get_codec_rules();
+            codec.enable_compression(0);
+            let mut formatting = SymmetricalBincode::<PeerMessage>::default();
+            let mut buf = BytesMut::new();
+            codec.encode(Pin::new(&mut formatting).serialize(message)?, &mut buf)?;
+            Ok(buf.freeze())
+        }
+
+        let network = Network::Main;
+        let mut other_handshake = get_dummy_handshake_data_for_genesis(network);
+        let mut own_handshake = get_dummy_handshake_data_for_genesis(network);
+        other_handshake.add_capability(ZSTD_COMPRESSION_CAPABILITY);
+        own_handshake.add_capability(ZSTD_COMPRESSION_CAPABILITY);
+
+        let mock = Builder::new()
+            .write(&to_bytes(&PeerMessage::Handshake {
+                magic_value: *MAGIC_STRING_REQUEST,
+                data: Box::new(own_handshake),
+            })?)
+            .read(&to_bytes(&PeerMessage::Handshake {
+                magic_value: *MAGIC_STRING_RESPONSE,
+                data: Box::new(other_handshake),
+            })?)
+            .read(&to_bytes(&PeerMessage::ConnectionStatus(
+                TransferConnectionStatus::Accepted,
+            ))?)
+            .write(&to_compressed_bytes(&PeerMessage::PeerListRequest)?)
+            .read(&to_compressed_bytes(&PeerMessage::Bye)?)
+            .build();
+
+        let (_peer_broadcast_tx, from_main_rx_clone, to_main_tx, _to_main_rx1, state, _hsd) =
+            get_test_genesis_setup(network, 0, cli_args::Args::default()).await?;
+        let peer_address = get_dummy_socket_address(0);
+        let lifecycle = state
+            .lock(|s| s.connection_lifecycles.track(peer_address))
+            .await;
+        call_peer_inner(
+            mock,
+            state.clone(),
+            peer_address,
+            from_main_rx_clone,
+            to_main_tx,
+            &own_handshake,
+            1,
+            lifecycle,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    #[traced_test]
+    #[apply(shared_tokio_runtime)]
+    async fn stalled_outgoing_handshake_times_out() -> Result<()> {
+        let network = Network::Main;
+        let own_handshake = get_dummy_handshake_data_for_genesis(network);
+        let mock = Builder::new()
+            .write(&to_bytes(&PeerMessage::Handshake {
+                magic_value: *MAGIC_STRING_REQUEST,
+                data: Box::new(own_handshake),
+            })?)
+            .wait(Duration::from_secs(3))
+            .build();
+
+        let cli = cli_args::Args {
+            handshake_timeout: 1,
+            ..Default::default()
+        };
+        let (_peer_broadcast_tx, from_main_rx_clone, to_main_tx, _to_main_rx1, state, _hsd) =
+            get_test_genesis_setup(network, 0, cli).await?;
+        let peer_address = get_dummy_socket_address(0);
+        let lifecycle = state
+            .lock(|s| s.connection_lifecycles.track(peer_address))
+            .await;
+        let err = call_peer_inner(
+            mock,
+            state.clone(),
+            peer_address,
+            from_main_rx_clone,
+            to_main_tx,
+            &own_handshake,
+            1,
+            lifecycle,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<ConnectionLifecycleError>(),
+            Some(ConnectionLifecycleError::TimedOut {
+                state: ConnectionState::Handshaking,
+                ..
+            })
+        ));
+
+        let metrics = state.lock(|s| s.connection_lifecycles.metrics()).await;
+        let handshaking = metrics.states[ConnectionState::Handshaking as usize];
+        assert_eq!(1, handshaking.timed_out);
+        assert_eq!(0, handshaking.current);
+
+        Ok(())
+    }
+
+    #[traced_test]
+    #[apply(shared_tokio_runtime)]
+    async fn partial_incoming_handshake_is_detected_as_slow() -> Result<()> {
+        let network = Network::Main;
+        let peer_handshake = get_dummy_handshake_data_for_genesis(network);
+        let handshake_bytes = to_bytes(&PeerMessage::Handshake {
+            magic_value: *MAGIC_STRING_REQUEST,
+            data: Box::new(peer_handshake),
+        })?;
+
+        // the peer trickles half of its handshake, then stalls.
+        let mock = Builder::new()
+            .read(&handshake_bytes[..handshake_bytes.len() / 2])
+            .wait(Duration::from_secs(3))
+            .build();
+
+        let cli = cli_args::Args {
+            handshake_timeout: 1,
+            ..Default::default()
+        };
+        let (
+            _peer_broadcast_tx,
+            from_main_rx_clone,
+            to_main_tx,
+            _to_main_rx1,
+            state,
+            own_handshake,
+        ) = get_test_genesis_setup(network, 0, cli).await?;
+        answer_peer(
+            mock,
+            state.clone(),
+            get_dummy_socket_address(0),
+            from_main_rx_clone,
+            to_main_tx,
+            own_handshake,
+            None,
+        )
+        .await?;
+
+        let metrics = state.lock(|s| s.connection_lifecycles.metrics()).await;
+        assert_eq!(1, metrics.slow_handshakes);
+        assert_eq!(
+            1,
+            metrics.states[ConnectionState::Handshaking as usize].timed_out
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn malformed_version_from_peer_doesnt_crash() {
         let version_numbers = ["potato", "&&&&"];
@@ -931,6 +1338,20 @@ mod tests {
             status,
         );
 
+        // Connections to self are recognized as such, whatever else is wrong
+        // with them.
+        status = check_if_connection_is_allowed(
+            state_lock.clone(),
+            &own_handshake,
+            &own_handshake,
+            &peer_sa,
+        )
+        .await;
+        assert_eq!(
+            InternalConnectionStatus::Refused(ConnectionRefusedReason::SelfConnect),
+            status,
+        );
+
         // pretend --ban ""
         cli.ban.pop();
         state_lock.set_cli(cli.clone()).await;
@@ -1,7 +1,13 @@
+pub mod message_latency;
 pub mod proof_upgrader;
+pub(crate) mod resource_monitor;
+mod sync_requests;
+mod tip_stall;
 pub(crate) mod upgrade_incentive;
 
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::future::Future;
 use std::net::SocketAddr;
 use std::process::Command;
 use std::process::Stdio;
@@ -10,6 +16,7 @@ use std::time::Duration;
 use std::time::SystemTime;
 
 use anyhow::Result;
+use chrono::Local;
 use itertools::Itertools;
 use proof_upgrader::get_upgrade_task_from_mempool;
 use proof_upgrader::UpgradeJob;
@@ -21,7 +28,6 @@ use tokio::select;
 use tokio::signal;
 use tokio::sync::broadcast;
 use tokio::sync::mpsc;
-use tokio::sync::Semaphore;
 use tokio::task::JoinHandle;
 use tokio::time;
 use tokio::time::Instant;
@@ -32,6 +38,7 @@ use tracing::info;
 use tracing::trace;
 use tracing::warn;
 
+use crate::application::loops::channel::backpressure::BackpressureSender;
 use crate::application::loops::channel::MainToMiner;
 use crate::application::loops::channel::MainToPeerTask;
 use crate::application::loops::channel::MainToPeerTaskBatchBlockRequest;
@@ -41,9 +48,18 @@ use crate::application::loops::channel::RPCServerToMain;
 use crate::application::loops::connect_to_peers::answer_peer;
 use crate::application::loops::connect_to_peers::call_peer;
 use crate::application::loops::connect_to_peers::precheck_incoming_connection_is_allowed;
+use crate::application::loops::connect_to_peers::rendezvous_with_peer;
+use crate::application::loops::main_loop::message_latency::MessageSource;
 use crate::application::loops::main_loop::proof_upgrader::PrimitiveWitnessToProofCollection;
 use crate::application::loops::main_loop::proof_upgrader::SEARCH_DEPTH_FOR_BLOCKS_FOR_MS_UPDATE;
+use crate::application::loops::main_loop::resource_monitor::ResourceMonitor;
+use crate::application::loops::main_loop::sync_requests::SyncPeerQuality;
+use crate::application::loops::main_loop::sync_requests::SyncRequest;
+use crate::application::loops::main_loop::tip_stall::TipStallTransition;
+use crate::application::loops::main_loop::tip_stall::TipStallWatchdog;
+use crate::application::loops::main_loop::tip_stall::TIP_STALL_TARGET_INTERVALS;
 use crate::application::loops::main_loop::upgrade_incentive::UpgradeIncentive;
+use crate::application::safe_mode::CrashCounter;
 use crate::application::triton_vm_job_queue::vm_job_queue;
 use crate::application::triton_vm_job_queue::TritonVmJobPriority;
 use crate::application::triton_vm_job_queue::TritonVmJobQueue;
@@ -51,21 +67,39 @@ use crate::macros::fn_name;
 use crate::macros::log_slow_scope;
 use crate::protocol::consensus::block::block_header::BlockHeader;
 use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::protocol::consensus::block::block_selector::BlockSelector;
 use crate::protocol::consensus::block::difficulty_control::ProofOfWork;
 use crate::protocol::consensus::block::Block;
 use crate::protocol::consensus::transaction::Transaction;
 use crate::protocol::consensus::transaction::TransactionProof;
 use crate::protocol::peer::handshake_data::HandshakeData;
 use crate::protocol::peer::peer_info::PeerInfo;
+use crate::protocol::peer::plausible_deniability::is_own_instance_id;
+use crate::protocol::peer::rendezvous::RendezvousIntroduction;
+use crate::protocol::peer::rendezvous::RendezvousRequest;
+use crate::protocol::peer::rendezvous::MAX_NUM_CONNECTED_PEERS_IN_RENDEZVOUS_REQUEST;
 use crate::protocol::peer::transaction_notification::TransactionNotification;
+use crate::protocol::peer::wallet_backup::WalletBackupMessage;
 use crate::protocol::peer::PeerSynchronizationState;
 use crate::protocol::proof_abstractions::tasm::program::TritonVmProofJobOptions;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+use crate::state::alerts::AlertKind;
+use crate::state::archival_state::headers_only::HEADERS_ONLY_RETENTION_DEPTH;
+use crate::state::block_application_progress::BlockApplicationSource;
+use crate::state::checkpoint::checkpoint_height;
+use crate::state::checkpoint::Checkpoint;
+use crate::state::checkpoint::SignedCheckpoint;
+use crate::state::load_shedding::LoadSheddingThresholds;
+use crate::state::load_shedding::LoadSheddingTransition;
 use crate::state::mempool::mempool_update_job::MempoolUpdateJob;
 use crate::state::mempool::mempool_update_job_result::MempoolUpdateJobResult;
 use crate::state::mempool::upgrade_priority::UpgradePriority;
 use crate::state::mining::block_proposal::BlockProposal;
 use crate::state::networking_state::SyncAnchor;
+use crate::state::release_manifest::fetch_release_manifest;
+use crate::state::release_manifest::UpdateNotice;
 use crate::state::transaction::tx_proving_capability::TxProvingCapability;
+use crate::state::wallet::wallet_backup::WalletBackupKey;
 use crate::state::GlobalState;
 use crate::state::GlobalStateLock;
 use crate::SUCCESS_EXIT_CODE;
@@ -76,6 +110,21 @@ const MEMPOOL_PRUNE_INTERVAL: Duration = Duration::from_secs(30 * 60);
 const MP_RESYNC_INTERVAL: Duration = Duration::from_secs(59);
 const PROOF_UPGRADE_INTERVAL: Duration = Duration::from_secs(10);
 const EXPECTED_UTXOS_PRUNE_INTERVAL: Duration = Duration::from_secs(19 * 60);
+const LOAD_SHEDDING_SAMPLE_INTERVAL: Duration = Duration::from_secs(20);
+const ALERT_EVALUATION_INTERVAL: Duration = Duration::from_secs(30);
+const TIP_STALL_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const WALLET_BACKUP_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(60);
+const RELEASE_CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+const FORK_PRUNING_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const CANONICAL_PRUNING_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const SPILL_DRAIN_INTERVAL: Duration = Duration::from_secs(1);
+const DATA_DIRECTORY_CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Warn if the clocks of peers deviate from the own clock by more than this.
+/// Well below the tolerance for futuredated blocks, so that the operator can
+/// react before composed blocks are rejected.
+const CLOCK_OFFSET_WARNING_THRESHOLD: Duration = Duration::from_secs(30);
 
 const SANCTION_PEER_TIMEOUT_FACTOR: u64 = 40;
 
@@ -125,9 +174,9 @@ pub struct MainLoopHandler {
     // note: broadcast::Sender::send() does not block
     main_to_peer_broadcast_tx: broadcast::Sender<MainToPeerTask>,
 
-    // note: mpsc::Sender::send() blocks if channel full.
-    // locks should not be held across it.
-    peer_task_to_main_tx: mpsc::Sender<PeerTaskToMain>,
+    // note: BackpressureSender::send() may block if channel full, depending
+    // on the overflow policy. locks should not be held across it.
+    peer_task_to_main_tx: BackpressureSender<PeerTaskToMain>,
 
     // note: MainToMinerChannel::send() does not block.  might log error.
     main_to_miner_tx: MainToMinerChannel,
@@ -146,6 +195,9 @@ struct MutableMainLoopState {
     /// Information used to batch-download blocks.
     sync_state: SyncState,
 
+    /// Tracks when the tip last advanced, to detect stalls.
+    tip_stall_watchdog: TipStallWatchdog,
+
     /// Information about potential peers for new connections.
     potential_peers: PotentialPeersState,
 
@@ -155,12 +207,22 @@ struct MutableMainLoopState {
     /// A join-handle to a task performing transaction-proof upgrades.
     proof_upgrader_task: Option<JoinHandle<()>>,
 
+    /// A join-handle to a task signing a chain checkpoint.
+    checkpoint_task: Option<JoinHandle<()>>,
+
+    /// A join-handle to a task fetching the release manifest.
+    release_check_task: Option<JoinHandle<()>>,
+
     /// A join-handle to a task running the update of the mempool transactions.
     update_mempool_txs_handle: Option<JoinHandle<()>>,
 
     /// A channel that the task updating mempool transactions can use to
     /// communicate its result.
     update_mempool_receiver: mpsc::Receiver<Vec<MempoolUpdateJobResult>>,
+
+    /// Samples resource usage to decide on load shedding and on deferring
+    /// proof upgrades. `None` until the main loop runs.
+    resource_monitor: Option<ResourceMonitor>,
 }
 
 impl MutableMainLoopState {
@@ -169,11 +231,15 @@ impl MutableMainLoopState {
             mpsc::channel::<Vec<MempoolUpdateJobResult>>(TX_UPDATER_CHANNEL_CAPACITY);
         Self {
             sync_state: SyncState::default(),
+            tip_stall_watchdog: TipStallWatchdog::default(),
             potential_peers: PotentialPeersState::default(),
             task_handles,
             proof_upgrader_task: None,
+            checkpoint_task: None,
+            release_check_task: None,
             update_mempool_txs_handle: None,
             update_mempool_receiver: dummy_receiver,
+            resource_monitor: None,
         }
     }
 }
@@ -182,17 +248,86 @@ impl MutableMainLoopState {
 #[derive(Default, Debug)]
 struct SyncState {
     peer_sync_states: HashMap<SocketAddr, PeerSynchronizationState>,
-    last_sync_request: Option<(SystemTime, BlockHeight, SocketAddr)>,
+    last_sync_request: Option<SyncRequest>,
+
+    /// How well peers served earlier synchronization requests.
+    peer_quality: HashMap<SocketAddr, SyncPeerQuality>,
+
+    /// Peers that failed to serve the blocks following the synced height. They
+    /// are only asked for these blocks again if no other peer holds them.
+    stalled_peers: HashSet<SocketAddr>,
 }
 
 impl SyncState {
+    /// Record that a request for the blocks from `requested_block_height`
+    /// onwards was sent to `peer`, with a deadline according to the peer's
+    /// earlier response times.
     fn record_request(
         &mut self,
         requested_block_height: BlockHeight,
         peer: SocketAddr,
         now: SystemTime,
     ) {
-        self.last_sync_request = Some((now, requested_block_height, peer));
+        let timeout = self
+            .peer_quality
+            .get(&peer)
+            .copied()
+            .unwrap_or_default()
+            .timeout(INDIVIDUAL_PEER_SYNCHRONIZATION_TIMEOUT);
+        self.last_sync_request = Some(SyncRequest {
+            peer,
+            requested_height: requested_block_height,
+            sent: now,
+            deadline: now + timeout,
+        });
+    }
+
+    /// Forget everything about a peer that disconnected.
+    fn remove_peer(&mut self, peer: SocketAddr) {
+        self.peer_sync_states.remove(&peer);
+        self.peer_quality.remove(&peer);
+        self.stalled_peers.remove(&peer);
+    }
+
+    /// Return the peers that failed to serve the blocks following the synced
+    /// height, including the peer of the outstanding request, if any. The
+    /// outstanding request is dropped, such that the next one goes to another
+    /// peer.
+    ///
+    /// If no peer failed yet and no request is outstanding, only the least
+    /// reliable of the peers that have reported to be in possession of blocks
+    /// with a PoW above the threshold is returned. The others are kept, since
+    /// they are the ones that can serve the missing blocks.
+    fn take_stalled_peers(&mut self, threshold_pow: ProofOfWork) -> Vec<SocketAddr> {
+        let mut stalled = self.stalled_peers.drain().collect_vec();
+        stalled.extend(self.last_sync_request.take().map(|request| request.peer));
+        if stalled.is_empty() {
+            let rank = |peer: &SocketAddr| {
+                self.peer_quality
+                    .get(peer)
+                    .copied()
+                    .unwrap_or_default()
+                    .rank()
+            };
+            stalled.extend(
+                self.get_potential_peers_for_sync_request(threshold_pow)
+                    .into_iter()
+                    .max_by_key(rank),
+            );
+        }
+
+        stalled.into_iter().unique().collect()
+    }
+
+    /// Record the round-trip times of the connected peers, as measured by
+    /// pings.
+    fn record_round_trip_times(&mut self, peer_map: &HashMap<SocketAddr, PeerInfo>) {
+        for (peer, peer_info) in peer_map {
+            if let Some(latency) = peer_info.latency() {
+                self.peer_quality.entry(*peer).or_default().round_trip_time =
+                    Some(latency.round_trip_time);
+            }
+        }
     }
 
     /// Return a list of peers that have reported to be in possession of blocks
@@ -205,37 +340,69 @@ impl SyncState {
             .collect()
     }
 
+    /// Pick the peer to request the next blocks from, among the peers holding
+    /// blocks with a PoW above a threshold. Peers that stalled on these blocks
+    /// are avoided, and reliable, fast peers are preferred.
+    fn choose_peer_for_sync_request(&self, threshold_pow: ProofOfWork) -> Option<SocketAddr> {
+        let candidates = self.get_potential_peers_for_sync_request(threshold_pow);
+        sync_requests::choose_sync_peer(
+            &candidates,
+            &self.peer_quality,
+            &self.stalled_peers,
+            &mut rand::rng(),
+        )
+    }
+
     /// Determine if a peer should be sanctioned for failing to respond to a
-    /// synchronization request fast enough. Also determine if a new request
-    /// should be made or the previous one should be allowed to run for longer.
+    /// synchronization request within [INDIVIDUAL_PEER_SYNCHRONIZATION_TIMEOUT].
+    /// Also determine if a new request should be made, because the previous
+    /// one missed its deadline, or the previous one should be allowed to run
+    /// for longer.
+    ///
+    /// The last request is served once the node synced up to the requested
+    /// height, whether as its tip or as the champion of the sync anchor. The
+    /// outcome is recorded in the quality of the peer the request was sent
+    /// to.
     ///
     /// Returns (peer to be sanctioned, attempt new request).
-    fn get_status_of_last_request(
-        &self,
-        current_block_height: BlockHeight,
+    fn update_status_of_last_request(
+        &mut self,
+        synced_block_height: BlockHeight,
         now: SystemTime,
     ) -> (Option<SocketAddr>, bool) {
-        // A peer is sanctioned if no answer has been received after N times the sync request
-        // interval.
-        match self.last_sync_request {
-            None => {
-                // No sync request has been made since startup of program
-                (None, true)
-            }
-            Some((req_time, requested_height, peer_sa)) => {
-                if requested_height < current_block_height {
-                    // The last sync request updated the state
-                    (None, true)
-                } else if req_time + INDIVIDUAL_PEER_SYNCHRONIZATION_TIMEOUT < now {
-                    // The last sync request was not answered, sanction peer
-                    // and make a new sync request.
-                    (Some(peer_sa), true)
-                } else {
-                    // The last sync request has not yet been answered. But it has
-                    // not timed out yet.
-                    (None, false)
-                }
-            }
+        let Some(request) = self.last_sync_request else {
+            // No sync request has been made since startup of program
+            return (None, true);
+        };
+
+        if request.requested_height <= synced_block_height {
+            // The last sync request updated the state
+            let response_time = now.duration_since(request.sent).unwrap_or_default();
+            self.peer_quality
+                .entry(request.peer)
+                .or_default()
+                .record_served(response_time);
+            self.stalled_peers.clear();
+            self.last_sync_request = None;
+            (None, true)
+        } else if request.deadline < now {
+            // The last sync request was not answered in time, fail over to
+            // another peer. The deadline adapts to the peer's earlier response
+            // times, so the peer is only sanctioned if it also missed the
+            // deadline that applies to every peer.
+            self.peer_quality
+                .entry(request.peer)
+                .or_default()
+                .record_timeout();
+            self.stalled_peers.insert(request.peer);
+            self.last_sync_request = None;
+            let missed_individual_timeout =
+                request.sent + INDIVIDUAL_PEER_SYNCHRONIZATION_TIMEOUT <= now;
+            (missed_individual_timeout.then_some(request.peer), true)
+        } else {
+            // The last sync request has not yet been answered. But it has
+            // not timed out yet.
+            (None, false)
         }
     }
 }
@@ -339,7 +506,7 @@ impl PotentialPeersState {
             // Prevent connecting to self. Note that we *only* use instance ID to prevent this,
             // meaning this will allow multiple nodes e.g. running on the same computer to form
             // a complete graph.
-            .filter(|pp| pp.1.instance_id != own_instance_id)
+            .filter(|pp| !is_own_instance_id(own_instance_id, pp.1.instance_id))
             // Prevent connecting to peer we already are connected to
             .filter(|potential_peer| !peers_instance_ids.contains(&potential_peer.1.instance_id))
             .filter(|potential_peer| !peers_listen_addresses.contains(potential_peer.0))
@@ -404,7 +571,7 @@ impl MainLoopHandler {
         incoming_peer_listener: TcpListener,
         global_state_lock: GlobalStateLock,
         main_to_peer_broadcast_tx: broadcast::Sender<MainToPeerTask>,
-        peer_task_to_main_tx: mpsc::Sender<PeerTaskToMain>,
+        peer_task_to_main_tx: BackpressureSender<PeerTaskToMain>,
         main_to_miner_tx: mpsc::Sender<MainToMiner>,
 
         peer_task_to_main_rx: mpsc::Receiver<PeerTaskToMain>,
@@ -684,6 +851,23 @@ impl MainLoopHandler {
     ) -> Result<()> {
         let new_block_hash = new_block.hash();
 
+        // a read-only node of a shared block store waits for its writer before
+        // acquiring the lock.
+        if !self
+            .global_state_lock
+            .blocks_are_in_shared_block_store(&[new_block_hash])
+            .await
+        {
+            let rpc_server_to_main_tx = self.global_state_lock.rpc_server_to_main_tx();
+            let retry = async move {
+                let _ = rpc_server_to_main_tx
+                    .send(RPCServerToMain::ProofOfWorkSolution(new_block))
+                    .await;
+            };
+            self.spawn_shared_block_store_wait(main_loop_state, vec![new_block_hash], retry);
+            return Ok(());
+        }
+
         // clone block in advance, so lock is held less time.
         let new_block_clone = (*new_block).clone();
 
@@ -728,6 +912,101 @@ impl MainLoopHandler {
         Ok(())
     }
 
+    /// Wait in a spawned task, so as not to block the main loop, for the
+    /// writer of the shared block store to store the blocks with the given
+    /// digests, and then run `retry`, which hands the blocks back to the main
+    /// loop. Blocks that the writer does not store in time are dropped.
+    fn spawn_shared_block_store_wait(
+        &self,
+        main_loop_state: &mut MutableMainLoopState,
+        block_digests: Vec<Digest>,
+        retry: impl Future<Output = ()> + Send + 'static,
+    ) {
+        debug!(
+            "Waiting for writer of shared block store to store {} block(s)",
+            block_digests.len()
+        );
+        let global_state_lock = self.global_state_lock.clone();
+        let shared_block_store_wait = tokio::task::spawn(async move {
+            match global_state_lock
+                .wait_for_shared_block_store(&block_digests)
+                .await
+            {
+                Ok(()) => retry.await,
+                Err(e) => warn!("Dropping blocks: {e}"),
+            }
+        });
+        main_loop_state.task_handles.push(shared_block_store_wait);
+        main_loop_state.task_handles.retain(|th| !th.is_finished());
+    }
+
+    /// Set blocks received from the primary as new tips. The blocks were
+    /// validated by the replication task.
+    ///
+    /// Locking:
+    ///   * acquires `global_state_lock` for write
+    async fn handle_replicated_blocks(
+        &mut self,
+        main_loop_state: &mut MutableMainLoopState,
+        blocks: Vec<Block>,
+    ) -> Result<()> {
+        let Some(last_block) = blocks.last().cloned() else {
+            return Ok(());
+        };
+
+        let block_digests = blocks.iter().map(|block| block.hash()).collect_vec();
+        if !self
+            .global_state_lock
+            .blocks_are_in_shared_block_store(&block_digests)
+            .await
+        {
+            let rpc_server_to_main_tx = self.global_state_lock.rpc_server_to_main_tx();
+            let retry = async move {
+                let _ = rpc_server_to_main_tx
+                    .send(RPCServerToMain::ReplicatedBlocks(blocks))
+                    .await;
+            };
+            self.spawn_shared_block_store_wait(main_loop_state, block_digests, retry);
+            return Ok(());
+        }
+
+        let update_jobs = {
+            let mut gsm = self.global_state_lock.lock_guard_mut().await;
+
+            // The blocks may have arrived from peers already.
+            if !gsm.incoming_block_is_more_canonical(&last_block) {
+                debug!("Replicated blocks are not new. Discarding.");
+                return Ok(());
+            }
+
+            self.main_to_miner_tx.send(MainToMiner::WaitForContinue);
+
+            let mut update_jobs = vec![];
+            for block in &blocks {
+                update_jobs.extend(gsm.set_new_tip(block.clone()).await?);
+            }
+            gsm.flush_databases().await?;
+            update_jobs
+        };
+
+        info!(
+            "Replicated block is new tip: {:x}; height: {}",
+            last_block.hash(),
+            last_block.header().height
+        );
+        for block in &blocks {
+            Self::spawn_block_notify_command(
+                &self.global_state_lock.cli().block_notify,
+                block.hash(),
+            );
+        }
+        self.main_to_peer_broadcast(MainToPeerTask::Block(Box::new(last_block)));
+        self.spawn_mempool_txs_update_job(main_loop_state, update_jobs);
+        self.main_to_miner_tx.send(MainToMiner::NewBlock);
+
+        Ok(())
+    }
+
     /// Locking:
     ///   * acquires `global_state_lock` for write
     async fn handle_miner_task_message(
@@ -800,6 +1079,39 @@ impl MainLoopHandler {
                 // received by main-loop.
                 self.main_to_miner_tx.send(MainToMiner::Continue);
             }
+            MinerToMain::ProposalExpired(proposal_digest) => {
+                log_slow_scope!(fn_name!() + "::MinerToMain::ProposalExpired");
+
+                let proposal = {
+                    let mut state = self.global_state_lock.lock_guard_mut().await;
+                    let proposal_is_current = state.mining_state.block_proposal.has_own()
+                        && state.mining_state.block_proposal.map(|block| block.hash())
+                            == Some(proposal_digest);
+                    if proposal_is_current {
+                        state.mining_state.num_expired_block_proposals += 1;
+                        state.mining_state.block_proposal.map(|block| block.clone())
+                    } else {
+                        None
+                    }
+                };
+
+                let Some(proposal) = proposal else {
+                    debug!("Expired block proposal is no longer current. Ignoring.");
+                    return Ok(None);
+                };
+
+                warn!(
+                    "Own block proposal for height {} has not resulted in a block within the \
+                     expected horizon. Guessers may be withholding the solution.",
+                    proposal.header().height
+                );
+
+                if !self.global_state_lock.cli().secret_compositions {
+                    info!("Re-broadcasting own block proposal");
+                    let pmsg = MainToPeerTask::BlockProposalNotification((&proposal).into());
+                    self.main_to_peer_broadcast(pmsg);
+                }
+            }
             MinerToMain::Shutdown(exit_code) => {
                 return Ok(Some(exit_code));
             }
@@ -821,6 +1133,21 @@ impl MainLoopHandler {
                 log_slow_scope!(fn_name!() + "::PeerTaskToMain::NewBlocks");
 
                 let block_hashes = blocks.iter().map(|x| x.hash()).collect_vec();
+                if !self
+                    .global_state_lock
+                    .blocks_are_in_shared_block_store(&block_hashes)
+                    .await
+                {
+                    let peer_task_to_main_tx = self.peer_task_to_main_tx.clone();
+                    let retry = async move {
+                        let _ = peer_task_to_main_tx
+                            .send(PeerTaskToMain::NewBlocks(blocks))
+                            .await;
+                    };
+                    self.spawn_shared_block_store_wait(main_loop_state, block_hashes, retry);
+                    return Ok(());
+                }
+
                 let last_block = blocks.last().unwrap().to_owned();
                 let update_jobs = {
                     // The peer tasks also check this condition, if block is more canonical than current
@@ -828,6 +1155,16 @@ impl MainLoopHandler {
                     // through a message from another peer (or from own miner).
                     let sync_mode_threshold = self.global_state_lock.cli().sync_mode_threshold;
                     let mut global_state_mut = self.global_state_lock.lock_guard_mut().await;
+                    if blocks
+                        .iter()
+                        .any(|block| global_state_mut.block_invalidations.rejects(block))
+                    {
+                        warn!(
+                            "Blocks from peer extend a block marked invalid. Not storing blocks."
+                        );
+                        return Ok(());
+                    }
+
                     let new_canonical =
                         global_state_mut.incoming_block_is_more_canonical(&last_block);
 
@@ -879,6 +1216,7 @@ impl MainLoopHandler {
                         if !stay_in_sync_mode {
                             info!("Exiting sync mode");
                             global_state_mut.net.sync_anchor = None;
+                            global_state_mut.block_application_progress.finish();
                             self.main_to_miner_tx.send(MainToMiner::StopSyncing);
                         }
                     }
@@ -961,8 +1299,13 @@ impl MainLoopHandler {
                         "Entering synchronization mode due to peer {} indicating tip height {}; cumulative pow: {:?}",
                         peer_address, claimed_height, claimed_cumulative_pow
                     );
-                    global_state_mut.net.sync_anchor =
-                        Some(SyncAnchor::new(claimed_cumulative_pow, claimed_block_mmra));
+                    let mut sync_anchor =
+                        SyncAnchor::new(claimed_cumulative_pow, claimed_block_mmra);
+                    sync_anchor.announced_by = Some(peer_address);
+                    global_state_mut.net.sync_anchor = Some(sync_anchor);
+                    global_state_mut
+                        .block_application_progress
+                        .start(BlockApplicationSource::PeerSync, Some(claimed_height));
                     self.main_to_miner_tx.send(MainToMiner::StartSyncing);
                 }
             }
@@ -973,10 +1316,7 @@ impl MainLoopHandler {
                     "Removing max block height from sync data structure for peer {}",
                     socket_addr
                 );
-                main_loop_state
-                    .sync_state
-                    .peer_sync_states
-                    .remove(&socket_addr);
+                main_loop_state.sync_state.remove_peer(socket_addr);
 
                 // Get out of sync mode if needed.
                 let sync_mode_threshold = self.global_state_lock.cli().sync_mode_threshold;
@@ -991,6 +1331,7 @@ impl MainLoopHandler {
                     if !stay_in_sync_mode {
                         info!("Exiting sync mode");
                         global_state_mut.net.sync_anchor = None;
+                        global_state_mut.block_application_progress.finish();
                     }
                 }
             }
@@ -1121,11 +1462,53 @@ impl MainLoopHandler {
                     self.main_to_peer_broadcast(pmsg);
                 }
             }
+            PeerTaskToMain::DisconnectDuplicate(peer_address) => {
+                let pmsg = MainToPeerTask::Disconnect(peer_address);
+                self.main_to_peer_broadcast(pmsg);
+            }
+            PeerTaskToMain::Introduce {
+                recipient,
+                introduction,
+            } => {
+                let pmsg = MainToPeerTask::Introduce(recipient, introduction);
+                self.main_to_peer_broadcast(pmsg);
+            }
+            PeerTaskToMain::Introduced(introduction) => {
+                self.rendezvous(introduction, main_loop_state).await;
+            }
+            PeerTaskToMain::WalletBackup(peer_address, message) => {
+                self.handle_wallet_backup_message(peer_address, *message)
+                    .await;
+            }
         }
 
         Ok(())
     }
 
+    /// Warn if the own clock deviates from those of the peers, judged by the
+    /// median of the clock offsets measured by pings.
+    ///
+    /// Locking:
+    ///   * acquires `global_state_lock` for read
+    async fn check_clock_offset(&self) {
+        let Some(offset_millis) = self
+            .global_state_lock
+            .lock_guard()
+            .await
+            .net
+            .median_peer_clock_offset_millis()
+        else {
+            return;
+        };
+
+        if u128::from(offset_millis.unsigned_abs()) > CLOCK_OFFSET_WARNING_THRESHOLD.as_millis() {
+            warn!(
+                "The clocks of peers are {offset_millis} ms off from the own clock (median). \
+                Consider synchronizing the system clock."
+            );
+        }
+    }
+
     /// If necessary, disconnect from peers.
     ///
     /// While a reasonable effort is made to never have more connections than
@@ -1133,6 +1516,11 @@ impl MainLoopHandler {
     /// this is not guaranteed. For example, bootstrap nodes temporarily allow a
     /// surplus of incoming connections to provide their service more reliably.
     ///
+    /// In load-shedding mode, the lower limit
+    /// [`load_shedding_max_num_peers`](crate::application::config::cli_args::Args::load_shedding_max_num_peers)
+    /// applies, and outbound connections are disconnected too if disconnecting
+    /// inbound connections does not suffice.
+    ///
     /// Never disconnects peers listed as CLI arguments.
     ///
     /// Locking:
@@ -1140,35 +1528,51 @@ impl MainLoopHandler {
     async fn prune_peers(&self) -> Result<()> {
         // fetch all relevant info from global state; don't hold the lock
         let cli_args = self.global_state_lock.cli();
-        let connected_peers = self
-            .global_state_lock
-            .lock_guard()
-            .await
-            .net
-            .peer_map
-            .values()
-            .cloned()
-            .collect_vec();
+        let global_state = self.global_state_lock.lock_guard().await;
+        let connected_peers = global_state.net.peer_map.values().cloned().collect_vec();
+        let is_shedding_load = global_state.load_shedding.is_active();
+        let max_num_peers = global_state.load_shedding.max_num_peers(cli_args);
+        drop(global_state);
 
         let num_peers = connected_peers.len();
-        let max_num_peers = cli_args.max_num_peers;
         if num_peers <= max_num_peers {
             debug!("No need to prune any peer connections.");
             return Ok(());
         }
         warn!("Connected to {num_peers} peers, which exceeds the maximum ({max_num_peers}).");
 
-        // If all connections are outbound, it's OK to exceed the max.
-        if connected_peers.iter().all(|p| p.connection_is_outbound()) {
+        // Unless shedding load, it's OK to exceed the max if all connections
+        // are outbound.
+        if !is_shedding_load && connected_peers.iter().all(|p| p.connection_is_outbound()) {
             warn!("Not disconnecting from any peer because all connections are outbound.");
             return Ok(());
         }
 
         let num_peers_to_disconnect = num_peers - max_num_peers;
-        let peers_to_disconnect = connected_peers
+        let (outbound_peers, inbound_peers): (Vec<_>, Vec<_>) = connected_peers
             .into_iter()
             .filter(|peer| !cli_args.peers.contains(&peer.connected_address()))
-            .choose_multiple(&mut rand::rng(), num_peers_to_disconnect);
+            .partition(|peer| peer.connection_is_outbound());
+        let peers_to_disconnect = if is_shedding_load {
+            // Free the slots of inbound connections first, and those of outbound
+            // connections only if that does not suffice.
+            let mut rng = rand::rng();
+            let mut peers = inbound_peers
+                .into_iter()
+                .choose_multiple(&mut rng, num_peers_to_disconnect);
+            let num_outbound_to_disconnect = num_peers_to_disconnect - peers.len();
+            peers.extend(
+                outbound_peers
+                    .into_iter()
+                    .choose_multiple(&mut rng, num_outbound_to_disconnect),
+            );
+            peers
+        } else {
+            inbound_peers
+                .into_iter()
+                .chain(outbound_peers)
+                .choose_multiple(&mut rand::rng(), num_peers_to_disconnect)
+        };
         match peers_to_disconnect.len() {
             0 => warn!("Not disconnecting from any peer because of manual override."),
             i => info!("Disconnecting from {i} peers."),
@@ -1208,11 +1612,6 @@ impl MainLoopHandler {
         }
 
         // Else, try to reconnect.
-        let own_handshake_data = self
-            .global_state_lock
-            .lock_guard()
-            .await
-            .get_own_handshakedata();
         for &peer_with_lost_connection in peers_with_lost_connection {
             // Disallow reconnection if peer is in bad standing
             let peer_standing = self
@@ -1227,6 +1626,12 @@ impl MainLoopHandler {
                 continue;
             }
 
+            let own_handshake_data = self
+                .global_state_lock
+                .lock_guard()
+                .await
+                .get_own_handshakedata();
+
             debug!("Attempting to reconnect to peer: {peer_with_lost_connection}");
             let global_state_lock = self.global_state_lock.clone();
             let main_to_peer_broadcast_rx = self.main_to_peer_broadcast_tx.subscribe();
@@ -1263,10 +1668,10 @@ impl MainLoopHandler {
         let connected_peers = global_state.net.peer_map.values().cloned().collect_vec();
         let own_instance_id = global_state.net.instance_id;
         let own_handshake_data = global_state.get_own_handshakedata();
+        let max_num_peers = global_state.load_shedding.max_num_peers(cli_args);
         drop(global_state);
 
         let num_peers = connected_peers.len();
-        let max_num_peers = cli_args.max_num_peers;
 
         // Don't make an outgoing connection if
         // - the peer limit is reached (or exceeded), or
@@ -1285,6 +1690,27 @@ impl MainLoopHandler {
         let pmsg = MainToPeerTask::MakePeerDiscoveryRequest;
         self.main_to_peer_broadcast(pmsg);
 
+        // Peers behind NAT are not found through peer lists, so ask a relay to
+        // introduce this node to one of them.
+        if cli_args.rendezvous {
+            let relay = connected_peers
+                .iter()
+                .filter(|peer| peer.introduces_peers())
+                .choose(&mut rand::rng());
+            if let Some(relay) = relay {
+                let connected = connected_peers
+                    .iter()
+                    .map(|peer| peer.instance_id())
+                    .take(MAX_NUM_CONNECTED_PEERS_IN_RENDEZVOUS_REQUEST)
+                    .collect();
+                let pmsg = MainToPeerTask::RequestIntroduction(
+                    relay.connected_address(),
+                    RendezvousRequest { connected },
+                );
+                self.main_to_peer_broadcast(pmsg);
+            }
+        }
+
         // Get a peer candidate from the list of potential peers. Generally,
         // the peer lists requested in the previous step will not have come in
         // yet. Therefore, the new candidate is selected based on somewhat
@@ -1325,6 +1751,61 @@ impl MainLoopHandler {
         Ok(())
     }
 
+    /// Connect to a peer that a relay introduced this node to, unless already
+    /// connected to it or at the peer limit.
+    ///
+    /// Locking:
+    ///   * acquires `global_state_lock` for read
+    async fn rendezvous(
+        &self,
+        introduction: RendezvousIntroduction,
+        main_loop_state: &mut MutableMainLoopState,
+    ) {
+        let cli_args = self.global_state_lock.cli();
+        let global_state = self.global_state_lock.lock_guard().await;
+        let own_instance_id = global_state.net.instance_id;
+        let already_connected = global_state
+            .net
+            .peer_map
+            .values()
+            .any(|peer| peer.instance_id() == introduction.instance_id);
+        let num_peers = global_state.net.peer_map.len();
+        let max_num_peers = global_state.load_shedding.max_num_peers(cli_args);
+        let own_handshake_data = global_state.get_own_handshakedata();
+        drop(global_state);
+
+        if already_connected
+            || introduction.instance_id == own_instance_id
+            || num_peers >= max_num_peers
+        {
+            debug!(
+                "Not connecting to introduced peer {}",
+                introduction.peer_address
+            );
+            return;
+        }
+
+        info!(
+            "Connecting to introduced peer {}",
+            introduction.peer_address
+        );
+        let global_state_lock = self.global_state_lock.clone();
+        let main_to_peer_broadcast_rx = self.main_to_peer_broadcast_tx.subscribe();
+        let peer_task_to_main_tx = self.peer_task_to_main_tx.to_owned();
+        let rendezvous_task = tokio::task::spawn(async move {
+            rendezvous_with_peer(
+                introduction,
+                global_state_lock,
+                main_to_peer_broadcast_rx,
+                peer_task_to_main_tx,
+                own_handshake_data,
+            )
+            .await;
+        });
+        main_loop_state.task_handles.push(rendezvous_task);
+        main_loop_state.task_handles.retain(|th| !th.is_finished());
+    }
+
     /// Return a list of block heights for a block-batch request.
     ///
     /// Returns an ordered list of the heights of *most preferred block*
@@ -1359,7 +1840,7 @@ impl MainLoopHandler {
     /// Logic for requesting the batch-download of blocks from peers
     ///
     /// Locking:
-    ///   * acquires `global_state_lock` for read
+    ///   * acquires `global_state_lock` for read and write
     async fn block_sync(&mut self, main_loop_state: &mut MutableMainLoopState) -> Result<()> {
         let global_state = self.global_state_lock.lock_guard().await;
 
@@ -1390,11 +1871,10 @@ impl MainLoopHandler {
             // Abandon attempt, and punish all peers claiming to serve these
             // blocks.
             drop(global_state);
-            self.global_state_lock
-                .lock_guard_mut()
-                .await
-                .net
-                .sync_anchor = None;
+            let mut global_state_mut = self.global_state_lock.lock_guard_mut().await;
+            global_state_mut.net.sync_anchor = None;
+            global_state_mut.block_application_progress.finish();
+            drop(global_state_mut);
 
             let peers_to_punish = main_loop_state
                 .sync_state
@@ -1408,12 +1888,20 @@ impl MainLoopHandler {
             return Ok(());
         }
 
+        // Blocks that do not (yet) beat the own tip are stored as the anchor's
+        // champion, which counts as progress too.
+        let synced_height = anchor
+            .champion
+            .map_or(own_tip_height, |(champion_height, _)| {
+                champion_height.max(own_tip_height)
+            });
         let (peer_to_sanction, try_new_request): (Option<SocketAddr>, bool) = main_loop_state
             .sync_state
-            .get_status_of_last_request(own_tip_height, self.now());
+            .update_status_of_last_request(synced_height, self.now());
 
         // Sanction peer if they failed to respond
         if let Some(peer) = peer_to_sanction {
+            warn!("Peer {peer} did not serve sync request in time. Failing over to another peer.");
             let pmsg = MainToPeerTask::PeerSynchronizationTimeout(peer);
             self.main_to_peer_broadcast(pmsg);
         }
@@ -1426,11 +1914,13 @@ impl MainLoopHandler {
         // Create the next request from the reported
         info!("Creating new sync request");
 
-        // Pick a random peer that has reported to have relevant blocks
-        let candidate_peers = main_loop_state
+        // Pick the best peer that has reported to have relevant blocks
+        main_loop_state
+            .sync_state
+            .record_round_trip_times(&global_state.net.peer_map);
+        let chosen_peer = main_loop_state
             .sync_state
-            .get_potential_peers_for_sync_request(own_cumulative_pow);
-        let chosen_peer = candidate_peers.choose(&mut rand::rng());
+            .choose_peer_for_sync_request(own_cumulative_pow);
         assert!(
             chosen_peer.is_some(),
             "A synchronization candidate must be available for a request. \
@@ -1465,52 +1955,672 @@ impl MainLoopHandler {
             chosen_peer, own_tip_hash, own_tip_height
         );
         let pmsg = MainToPeerTask::RequestBlockBatch(MainToPeerTaskBatchBlockRequest {
-            peer_addr_target: *chosen_peer,
+            peer_addr_target: chosen_peer,
             known_blocks: ordered_preferred_block_digests,
             anchor_mmr: anchor.block_mmr.clone(),
         });
         self.main_to_peer_broadcast(pmsg);
 
         // Record that this request was sent to the peer
-        let requested_block_height = own_tip_height.next();
+        let requested_block_height = synced_height.next();
         main_loop_state
             .sync_state
-            .record_request(requested_block_height, *chosen_peer, self.now());
+            .record_request(requested_block_height, chosen_peer, self.now());
+        global_state
+            .block_application_progress
+            .record_serving_peer(chosen_peer);
 
         Ok(())
     }
 
-    /// Scheduled task for upgrading the proofs of transactions in the mempool.
+    /// Sample resource usage and enter or exit load-shedding mode accordingly.
     ///
-    /// Will either perform a merge of two transactions supported with single
-    /// proofs, or will upgrade a transaction proof of the type
-    /// `ProofCollection` to `SingleProof`.
+    /// Upon entering load-shedding mode, surplus incoming connections are
+    /// closed. Other measures take effect where load is generated, by
+    /// consulting [`GlobalState::load_shedding`].
     ///
-    /// All proving takes place in a spawned task such that it doesn't block
-    /// the main loop. The MutableMainLoopState gets the JoinHandle of the
-    /// spawned upgrade task such that its status can be expected.
-    async fn proof_upgrader(&mut self, main_loop_state: &mut MutableMainLoopState) -> Result<()> {
-        fn attempt_upgrade(
-            global_state: &GlobalState,
-            main_loop_state: &MutableMainLoopState,
-        ) -> bool {
-            let previous_upgrade_task_is_still_running = main_loop_state
-                .proof_upgrader_task
-                .as_ref()
-                .is_some_and(|x| !x.is_finished());
-            global_state.cli().tx_proof_upgrading
-                && global_state.net.sync_anchor.is_none()
-                && global_state.proving_capability() == TxProvingCapability::SingleProof
-                && !previous_upgrade_task_is_still_running
+    /// If load shedding is disabled, the sample is only recorded, for the
+    /// [proof-upgrade schedule](GlobalState::proof_upgrade_schedule).
+    ///
+    /// Locking:
+    ///   * acquires `global_state_lock` for write
+    async fn load_shedding(&self, main_loop_state: &mut MutableMainLoopState) -> Result<()> {
+        let Some(resource_monitor) = main_loop_state.resource_monitor.as_mut() else {
+            return Ok(());
+        };
+
+        let pressure = resource_monitor.sample();
+        let mut global_state = self.global_state_lock.lock_guard_mut().await;
+        if !global_state.cli().load_shedding {
+            global_state.load_shedding.record_pressure(pressure);
+            return Ok(());
         }
 
-        trace!("Running proof upgrader scheduled task");
+        let thresholds = LoadSheddingThresholds::from(global_state.cli());
+        let transition = global_state
+            .load_shedding
+            .update(pressure, &thresholds, Timestamp::now());
+        drop(global_state);
 
-        // Check if it's time to run the proof-upgrader, and if we're capable
-        // of upgrading a transaction proof.
-        let upgrade_candidate = {
-            let mut global_state = self.global_state_lock.lock_guard_mut().await;
-            if !attempt_upgrade(&global_state, main_loop_state) {
+        if transition == Some(LoadSheddingTransition::Entered) {
+            self.prune_peers().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Evaluate the alert rules, if any are configured, and publish the alerts
+    /// that fired or whose silence lapsed.
+    ///
+    /// Locking:
+    ///   * acquires `global_state_lock` for write
+    async fn evaluate_alerts(&self) {
+        self.global_state_lock
+            .lock_guard_mut()
+            .await
+            .evaluate_alerts(Timestamp::now())
+            .await;
+    }
+
+    /// Detect a tip that has not advanced for [`TIP_STALL_TARGET_INTERVALS`]
+    /// target block intervals while peers claim blocks with more PoW, see
+    /// [`tip_stall`].
+    ///
+    /// Upon a stall, the stalled sync peers are disconnected, except for those
+    /// given as CLI arguments, the remaining peers are asked for their tips,
+    /// and a [tip-stall alert](AlertKind::TipStall) is raised. The alert is
+    /// resolved once the tip advances, or no peer claims better blocks
+    /// anymore.
+    ///
+    /// Locking:
+    ///   * acquires `global_state_lock` for read, and for write upon a
+    ///     transition
+    async fn tip_stall_watchdog(&self, main_loop_state: &mut MutableMainLoopState) {
+        let global_state = self.global_state_lock.lock_guard().await;
+        let tip_header = *global_state.chain.light_state().header();
+        let tip_digest = global_state.chain.light_state().hash();
+        drop(global_state);
+
+        let stall_period =
+            Duration::from(self.global_state_lock.cli().network.target_block_interval())
+                * TIP_STALL_TARGET_INTERVALS;
+        let max_claimed_height = main_loop_state
+            .sync_state
+            .peer_sync_states
+            .values()
+            .filter(|claim| claim.claimed_max_pow > tip_header.cumulative_proof_of_work)
+            .map(|claim| claim.claimed_max_height)
+            .max();
+        let transition = main_loop_state.tip_stall_watchdog.check(
+            tip_digest,
+            max_claimed_height.is_some(),
+            stall_period,
+            self.now(),
+        );
+
+        match transition {
+            None => {}
+            Some(TipStallTransition::Recovered) => {
+                info!("Tip is advancing again.");
+                self.global_state_lock
+                    .lock_guard_mut()
+                    .await
+                    .alerts
+                    .resolve(AlertKind::TipStall);
+            }
+            Some(TipStallTransition::Stalled(stalled_for)) => {
+                let stalled_for = Timestamp::millis(stalled_for.as_millis() as u64);
+                let message = format!(
+                    "Tip {} did not advance for {}, while peers claim height {}.",
+                    tip_header.height,
+                    stalled_for.format_human_duration(),
+                    max_claimed_height.unwrap_or(tip_header.height),
+                );
+
+                let manual_peers = &self.global_state_lock.cli().peers;
+                let stalled_peers = main_loop_state
+                    .sync_state
+                    .take_stalled_peers(tip_header.cumulative_proof_of_work)
+                    .into_iter()
+                    .filter(|peer| !manual_peers.contains(peer))
+                    .collect_vec();
+                warn!(
+                    "{message} Rotating out {} stalled sync peers.",
+                    stalled_peers.len()
+                );
+                for peer in stalled_peers {
+                    self.main_to_peer_broadcast(MainToPeerTask::Disconnect(peer));
+                }
+                self.main_to_peer_broadcast(MainToPeerTask::RequestBlockNotification);
+
+                self.global_state_lock
+                    .lock_guard_mut()
+                    .await
+                    .raise_alert(AlertKind::TipStall, message, Timestamp::from(self.now()))
+                    .await;
+            }
+        }
+    }
+
+    /// Ask the connected trusted peers which version of the wallet's backup
+    /// they hold, if wallet backups are enabled. Their answers are handled by
+    /// [`Self::handle_wallet_backup_message`].
+    ///
+    /// Locking:
+    ///   * acquires `global_state_lock` for read
+    async fn request_wallet_backup_status(&self) {
+        let global_state = self.global_state_lock.lock_guard().await;
+        if global_state.cli().wallet_backup_peers.is_empty() {
+            return;
+        }
+
+        let backup_id = WalletBackupKey::new(&global_state.wallet_state.wallet_entropy).id();
+        let peers = global_state
+            .net
+            .peer_map
+            .iter()
+            .filter(|(address, peer_info)| {
+                peer_info.exchanges_wallet_backups()
+                    && global_state.cli().is_wallet_backup_peer(address.ip())
+            })
+            .map(|(address, _)| *address)
+            .collect_vec();
+        drop(global_state);
+
+        for peer in peers {
+            let message = WalletBackupMessage::StatusRequest { backup_id };
+            let pmsg = MainToPeerTask::WalletBackup(peer, Box::new(message));
+            self.main_to_peer_broadcast(pmsg);
+        }
+    }
+
+    /// Reconcile the wallet's backup with the one a trusted peer holds: send
+    /// the peer the increments it is missing, or fetch those this node is
+    /// missing, eg after losing its data directory.
+    ///
+    /// New recovery data is only sealed into the backup once a peer reported
+    /// that it is not ahead, so that a node that lost its data directory
+    /// first catches up with its peers instead of starting a diverging
+    /// backup. If a backup diverges nonetheless, eg because the peer holding
+    /// the old one was unreachable for a while, it is fetched in full and
+    /// merged, see [`WalletBackup::receive`]. If the wallet's backup is kept,
+    /// the peer is told to replace its copy.
+    ///
+    /// [`WalletBackup::receive`]: crate::state::wallet::wallet_backup::WalletBackup::receive
+    ///
+    /// Locking:
+    ///   * acquires `global_state_lock` for read
+    ///   * acquires `global_state_lock` for write, if recovery data is
+    ///     imported
+    async fn handle_wallet_backup_message(
+        &self,
+        peer_address: SocketAddr,
+        message: WalletBackupMessage,
+    ) {
+        let (key, incoming_secrets_path) = {
+            let global_state = self.global_state_lock.lock_guard().await;
+            (
+                WalletBackupKey::new(&global_state.wallet_state.wallet_entropy),
+                global_state
+                    .wallet_state
+                    .configuration
+                    .incoming_secrets_path(),
+            )
+        };
+        let backup_id = key.id();
+        if message.backup_id() != backup_id {
+            debug!("Ignoring message from {peer_address} about another wallet backup");
+            return;
+        }
+
+        let mut wallet_backup = self.global_state_lock.wallet_backup().lock().await;
+        let reply = match message {
+            WalletBackupMessage::Status { head, .. } => {
+                wallet_backup.record_peer_head(peer_address, head);
+                if wallet_backup.is_fetching_diverging_backup() {
+                    None
+                } else if head.version > wallet_backup.head().version {
+                    Some(WalletBackupMessage::Fetch {
+                        backup_id,
+                        after_version: wallet_backup.head().version,
+                    })
+                } else if wallet_backup.diverges_from(head) {
+                    if wallet_backup.has_merged(head) {
+                        Some(WalletBackupMessage::Replace {
+                            backup_id,
+                            increments: wallet_backup.increments_after(0),
+                        })
+                    } else if wallet_backup.fetch_diverging(peer_address) {
+                        Some(WalletBackupMessage::Fetch {
+                            backup_id,
+                            after_version: 0,
+                        })
+                    } else {
+                        let error = format!(
+                            "Wallet backup held by {peer_address} diverges from own backup \
+                            at version {}",
+                            head.version
+                        );
+                        warn!("{error}");
+                        wallet_backup.record_error(error);
+                        None
+                    }
+                } else {
+                    match wallet_backup.seal(&key, &incoming_secrets_path).await {
+                        Ok(0) => (),
+                        Ok(num_increments) => {
+                            debug!("Sealed {num_increments} new wallet backup increments");
+                        }
+                        Err(e) => {
+                            let error = format!("Could not seal wallet backup: {e:#}");
+                            error!("{error}");
+                            wallet_backup.record_error(error);
+                        }
+                    }
+
+                    (head != wallet_backup.head()).then(|| WalletBackupMessage::Store {
+                        backup_id,
+                        increments: wallet_backup.increments_after(head.version),
+                    })
+                }
+            }
+            WalletBackupMessage::Increments { increments, .. } => {
+                let received = match wallet_backup.receive(&key, peer_address, increments).await {
+                    Ok(received) => received,
+                    Err(e) => {
+                        let error =
+                            format!("Invalid wallet backup increments from {peer_address}: {e:#}");
+                        warn!("{error}");
+                        wallet_backup.record_error(error);
+                        Default::default()
+                    }
+                };
+
+                let entries = match wallet_backup
+                    .unknown_to_wallet(&incoming_secrets_path, received.entries)
+                    .await
+                {
+                    Ok(entries) => entries,
+                    Err(e) => {
+                        let error = format!("Could not import wallet backup: {e:#}");
+                        error!("{error}");
+                        wallet_backup.record_error(error);
+                        vec![]
+                    }
+                };
+                if !entries.is_empty() {
+                    // Never wait for the global state lock while holding the
+                    // backup's.
+                    drop(wallet_backup);
+                    let imported = self
+                        .global_state_lock
+                        .lock_guard_mut()
+                        .await
+                        .import_wallet_backup_entries(entries)
+                        .await;
+                    wallet_backup = self.global_state_lock.wallet_backup().lock().await;
+                    match imported {
+                        Ok(()) => wallet_backup.record_restored(Timestamp::now()),
+                        Err(e) => {
+                            let error = format!("Could not import wallet backup: {e:#}");
+                            error!("{error}");
+                            wallet_backup.record_error(error);
+                        }
+                    }
+                }
+
+                if received.replace {
+                    Some(WalletBackupMessage::Replace {
+                        backup_id,
+                        increments: wallet_backup.increments_after(0),
+                    })
+                } else {
+                    received
+                        .fetch_after
+                        .map(|after_version| WalletBackupMessage::Fetch {
+                            backup_id,
+                            after_version,
+                        })
+                }
+            }
+            WalletBackupMessage::Refused { reason, .. } => {
+                let error = format!("{peer_address} refused wallet backup message: {reason}");
+                warn!("{error}");
+                wallet_backup.record_error(error);
+                None
+            }
+            WalletBackupMessage::StatusRequest { .. }
+            | WalletBackupMessage::Store { .. }
+            | WalletBackupMessage::Fetch { .. }
+            | WalletBackupMessage::Replace { .. } => {
+                error!("Peer loop must answer wallet backup requests itself");
+                None
+            }
+        };
+        drop(wallet_backup);
+
+        if let Some(reply) = reply {
+            let pmsg = MainToPeerTask::WalletBackup(peer_address, Box::new(reply));
+            self.main_to_peer_broadcast(pmsg);
+        }
+    }
+
+    /// Scheduled task for publishing signed checkpoints of the canonical
+    /// chain, if enabled with
+    /// [`checkpoint_interval`](crate::application::config::cli_args::Args::checkpoint_interval).
+    ///
+    /// Signs the canonical block at the most recent height that is a multiple
+    /// of the interval, unless it has already been signed. Signing produces a
+    /// proof, so it takes place in a spawned task.
+    ///
+    /// Locking:
+    ///   * acquires `global_state_lock` for read
+    async fn publish_checkpoint(&self, main_loop_state: &mut MutableMainLoopState) -> Result<()> {
+        let Some(interval) = self.global_state_lock.cli().checkpoint_interval else {
+            return Ok(());
+        };
+
+        let previous_checkpoint_task_is_still_running = main_loop_state
+            .checkpoint_task
+            .as_ref()
+            .is_some_and(|x| !x.is_finished());
+        if previous_checkpoint_task_is_still_running {
+            return Ok(());
+        }
+
+        let network = self.global_state_lock.cli().network;
+        let (checkpoint, secret) = {
+            let global_state = self.global_state_lock.lock_guard().await;
+            if !global_state.chain.is_archival_node() || global_state.net.sync_anchor.is_some() {
+                return Ok(());
+            }
+
+            let tip_height = global_state.chain.light_state().header().height;
+            let Some(height) = checkpoint_height(tip_height, interval.get()) else {
+                return Ok(());
+            };
+
+            let Some(digest) = BlockSelector::Height(height).as_digest(&global_state).await else {
+                return Ok(());
+            };
+
+            if global_state
+                .checkpoints
+                .get(height)
+                .is_some_and(|signed| signed.checkpoint.block_digest == digest)
+            {
+                return Ok(());
+            }
+
+            let Some(block) = global_state
+                .chain
+                .archival_state()
+                .get_block(digest)
+                .await?
+            else {
+                warn!("Canonical block at height {height} not found; cannot checkpoint it");
+                return Ok(());
+            };
+            let Some(checkpoint) = Checkpoint::from_block(&block, network) else {
+                warn!("Failed to compute checkpoint of block at height {height}");
+                return Ok(());
+            };
+
+            let secret = global_state
+                .wallet_state
+                .wallet_entropy
+                .checkpoint_signing_secret();
+
+            (checkpoint, secret)
+        };
+
+        info!(
+            "Signing checkpoint of block {} at height {}",
+            checkpoint.block_digest.to_hex(),
+            checkpoint.height
+        );
+
+        let proof_job_options = self
+            .global_state_lock
+            .cli()
+            .proof_job_options(TritonVmJobPriority::Low);
+        let mut global_state_lock = self.global_state_lock.clone();
+        let checkpoint_task = tokio::task::spawn(async move {
+            match SignedCheckpoint::sign(checkpoint, secret, vm_job_queue(), proof_job_options)
+                .await
+            {
+                Ok(signed_checkpoint) => {
+                    global_state_lock
+                        .lock_guard_mut()
+                        .await
+                        .checkpoints
+                        .insert(signed_checkpoint);
+                    info!("Published checkpoint at height {}", checkpoint.height);
+                }
+                Err(e) => warn!("Failed to sign checkpoint: {e}"),
+            }
+        });
+
+        main_loop_state.checkpoint_task = Some(checkpoint_task);
+
+        Ok(())
+    }
+
+    /// Scheduled task for checking for new releases, if enabled with
+    /// [`release_manifest_url`](crate::application::config::cli_args::Args::release_manifest_url).
+    ///
+    /// A newer release is reported as an
+    /// [update alert](AlertKind::UpdateAvailable), which is resolved once the
+    /// node is up to date.
+    ///
+    /// Fetching the manifest involves network requests to a third party, so
+    /// it takes place in a spawned task.
+    fn check_for_release(&self, main_loop_state: &mut MutableMainLoopState) {
+        let cli = self.global_state_lock.cli();
+        let (Some(url), Some(signer)) = (
+            cli.release_manifest_url.clone(),
+            cli.release_manifest_signer,
+        ) else {
+            return;
+        };
+
+        let previous_release_check_task_is_still_running = main_loop_state
+            .release_check_task
+            .as_ref()
+            .is_some_and(|x| !x.is_finished());
+        if previous_release_check_task_is_still_running {
+            return;
+        }
+
+        let network = cli.network;
+        let mut global_state_lock = self.global_state_lock.clone();
+        let release_check_task = tokio::task::spawn(async move {
+            let manifest = fetch_release_manifest(&url, network, signer).await;
+            let mut global_state = global_state_lock.lock_guard_mut().await;
+            let update_status = &mut global_state.update_status;
+            let previous_notice = update_status.notice.clone();
+            update_status.record_check(manifest, Timestamp::now());
+
+            if let Some(e) = &update_status.last_error {
+                warn!("Failed to check for new releases: {e}");
+            }
+            let notice = update_status.notice.clone();
+            if notice == previous_notice {
+                return;
+            }
+
+            global_state.alerts.resolve(AlertKind::UpdateAvailable);
+            let message = match notice {
+                UpdateNotice::Available { version } => {
+                    format!("Neptune Core {version} is available.")
+                }
+                UpdateNotice::Mandatory { version, height } => {
+                    format!("Neptune Core {version} is available and must be installed before block height {height}.")
+                }
+                UpdateNotice::Unknown | UpdateNotice::UpToDate => return,
+            };
+            global_state
+                .raise_alert(AlertKind::UpdateAvailable, message, Timestamp::now())
+                .await;
+        });
+
+        main_loop_state.release_check_task = Some(release_check_task);
+    }
+
+    /// Scheduled task for deleting the bodies of abandoned fork blocks, if
+    /// enabled with
+    /// [`fork_pruning_depth`](crate::application::config::cli_args::Args::fork_pruning_depth).
+    ///
+    /// See [`ArchivalState::prune_fork_blocks`](crate::state::archival_state::ArchivalState::prune_fork_blocks).
+    ///
+    /// Locking:
+    ///   * acquires `global_state_lock` for write
+    async fn prune_fork_blocks(&self) -> Result<()> {
+        let Some(depth) = self.global_state_lock.cli().fork_pruning_depth else {
+            return Ok(());
+        };
+
+        let mut global_state = self.global_state_lock.lock_guard_mut().await;
+        if !global_state.chain.is_archival_node() || global_state.net.sync_anchor.is_some() {
+            return Ok(());
+        }
+
+        let tip_height = global_state.chain.light_state().header().height;
+        if let Err(e) = global_state
+            .chain
+            .archival_state_mut()
+            .prune_fork_blocks(tip_height, depth.get())
+            .await
+        {
+            warn!("Failed to prune fork blocks: {e}");
+        }
+
+        Ok(())
+    }
+
+    /// Scheduled task for deleting the bodies of canonical blocks, if running
+    /// with
+    /// [`headers_only`](crate::application::config::cli_args::Args::headers_only).
+    ///
+    /// Runs during syncing too, such that the bodies of synced blocks do not
+    /// pile up. Block files are compacted without holding the lock on the
+    /// global state, which is only held to find the blocks to prune and to
+    /// index the compacted files.
+    ///
+    /// Locking:
+    ///   * acquires `global_state_lock` for read and for write, but not
+    ///     while compacting block files
+    async fn prune_canonical_blocks(&self) -> Result<()> {
+        if !self.global_state_lock.cli().headers_only {
+            return Ok(());
+        }
+
+        let pruning = {
+            let global_state = self.global_state_lock.lock_guard().await;
+            if !global_state.chain.is_archival_node() {
+                return Ok(());
+            }
+
+            let tip_height = global_state.chain.light_state().header().height;
+            global_state
+                .chain
+                .archival_state()
+                .canonical_blocks_to_prune(tip_height, HEADERS_ONLY_RETENTION_DEPTH)
+                .await
+        };
+        let Some(pruning) = pruning else {
+            return Ok(());
+        };
+
+        for (file_index, pruned) in pruning.blocks {
+            let compacted = async {
+                let compaction = self
+                    .global_state_lock
+                    .lock_guard()
+                    .await
+                    .chain
+                    .archival_state()
+                    .prepare_block_file_compaction(file_index, pruned)
+                    .await?;
+
+                // The lock is released before writing the compacted file.
+                let compaction = compaction.write().await?;
+                self.global_state_lock
+                    .lock_guard_mut()
+                    .await
+                    .chain
+                    .archival_state_mut()
+                    .finish_block_file_compaction(compaction)
+                    .await
+            };
+            match compacted.await {
+                Ok(Some(_)) => (),
+
+                // Blocks were stored in the file meanwhile. Try again later.
+                Ok(None) => return Ok(()),
+                Err(e) => {
+                    warn!("Failed to prune canonical blocks: {e}");
+                    return Ok(());
+                }
+            }
+        }
+
+        self.global_state_lock
+            .lock_guard_mut()
+            .await
+            .chain
+            .archival_state_mut()
+            .set_canonical_pruning_height(pruning.pruned_up_to)
+            .await;
+
+        Ok(())
+    }
+
+    /// Scheduled task for upgrading the proofs of transactions in the mempool.
+    ///
+    /// Will either perform a merge of two transactions supported with single
+    /// proofs, or will upgrade a transaction proof of the type
+    /// `ProofCollection` to `SingleProof`.
+    ///
+    /// All proving takes place in a spawned task such that it doesn't block
+    /// the main loop. The MutableMainLoopState gets the JoinHandle of the
+    /// spawned upgrade task such that its status can be expected.
+    async fn proof_upgrader(&mut self, main_loop_state: &mut MutableMainLoopState) -> Result<()> {
+        fn attempt_upgrade(
+            global_state: &GlobalState,
+            main_loop_state: &MutableMainLoopState,
+        ) -> bool {
+            let previous_upgrade_task_is_still_running = main_loop_state
+                .proof_upgrader_task
+                .as_ref()
+                .is_some_and(|x| !x.is_finished());
+            let cpu_percent = global_state
+                .load_shedding
+                .status()
+                .pressure
+                .map(|pressure| pressure.cpu_percent);
+            let deferral = global_state
+                .proof_upgrade_schedule
+                .deferral(Local::now().time(), cpu_percent);
+            if let Some(deferral) = deferral {
+                trace!("Proof upgrading deferred: {deferral}");
+            }
+
+            global_state.cli().tx_proof_upgrading
+                && deferral.is_none()
+                && !global_state.load_shedding.is_active()
+                && global_state.net.sync_anchor.is_none()
+                && global_state.proving_capability() == TxProvingCapability::SingleProof
+                && !previous_upgrade_task_is_still_running
+        }
+
+        trace!("Running proof upgrader scheduled task");
+
+        // Check if it's time to run the proof-upgrader, and if we're capable
+        // of upgrading a transaction proof.
+        let upgrade_candidate = {
+            let mut global_state = self.global_state_lock.lock_guard_mut().await;
+            if !attempt_upgrade(&global_state, main_loop_state) {
                 trace!("Not attempting upgrade.");
                 return Ok(());
             }
@@ -1555,6 +2665,71 @@ impl MainLoopHandler {
         Ok(())
     }
 
+    /// Spawn a task upgrading a transaction initiated by this node from a
+    /// proof collection to a single proof, and relaying the upgraded
+    /// transaction to peers.
+    ///
+    /// Does nothing unless own transactions take the priority lane, this node
+    /// can produce single proofs, and the transaction is backed by a proof
+    /// collection.
+    async fn upgrade_own_proof_collection(
+        &self,
+        transaction: &Transaction,
+        main_loop_state: &mut MutableMainLoopState,
+    ) {
+        let TransactionProof::ProofCollection(proof_collection) = &transaction.proof else {
+            return;
+        };
+        let cli = self.global_state_lock.cli();
+        if cli.no_own_tx_priority || cli.proving_capability() != TxProvingCapability::SingleProof {
+            return;
+        }
+
+        let upgrade_job = self
+            .global_state_lock
+            .lock_guard_mut()
+            .await
+            .upgrade_proof_collection_job(
+                transaction.kernel.clone(),
+                proof_collection.clone(),
+                UpgradeIncentive::Critical,
+            )
+            .await;
+        let upgrade_job = match upgrade_job {
+            Ok(job) => UpgradeJob::ProofCollectionToSingleProof(job),
+            Err(e) => {
+                warn!(
+                    "Could not upgrade own transaction {}: {e}",
+                    transaction.txid()
+                );
+                return;
+            }
+        };
+
+        info!(
+            "Upgrading own transaction {} to single proof",
+            transaction.txid()
+        );
+        let vm_job_queue = vm_job_queue();
+        let global_state_lock = self.global_state_lock.clone();
+        let main_to_peer_broadcast_tx = self.main_to_peer_broadcast_tx.clone();
+        let proof_upgrader_task = tokio::task::spawn(async move {
+            upgrade_job
+                .handle_upgrade(vm_job_queue, global_state_lock, main_to_peer_broadcast_tx)
+                .await
+        });
+
+        // Keep the scheduled proof upgrader from upgrading the same
+        // transaction, unless it is busy with another one already.
+        let scheduled_upgrade_is_running = main_loop_state
+            .proof_upgrader_task
+            .as_ref()
+            .is_some_and(|task| !task.is_finished());
+        if !scheduled_upgrade_is_running {
+            main_loop_state.proof_upgrader_task = Some(proof_upgrader_task);
+        }
+    }
+
     /// Post-processing when new block has arrived. Spawn a task to update
     /// transactions in the mempool. Only when the spawned task has completed,
     /// should the miner continue.
@@ -1633,6 +2808,50 @@ impl MainLoopHandler {
         let mut tx_proof_upgrade_interval = time::interval(PROOF_UPGRADE_INTERVAL);
         tx_proof_upgrade_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
 
+        let mut checkpoint_interval = time::interval(CHECKPOINT_INTERVAL);
+        checkpoint_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let mut release_check_interval = time::interval(RELEASE_CHECK_INTERVAL);
+        release_check_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let mut fork_pruning_interval = time::interval(FORK_PRUNING_INTERVAL);
+        fork_pruning_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let mut canonical_pruning_interval = time::interval(CANONICAL_PRUNING_INTERVAL);
+        canonical_pruning_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let mut spill_drain_interval = time::interval(SPILL_DRAIN_INTERVAL);
+        spill_drain_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let mut load_shedding_interval = time::interval(LOAD_SHEDDING_SAMPLE_INTERVAL);
+        load_shedding_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let mut alert_evaluation_interval = time::interval(ALERT_EVALUATION_INTERVAL);
+        alert_evaluation_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let mut tip_stall_check_interval = time::interval(TIP_STALL_CHECK_INTERVAL);
+        tip_stall_check_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let mut wallet_backup_interval = time::interval(WALLET_BACKUP_INTERVAL);
+        wallet_backup_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let mut data_directory_cleanup_interval = time::interval(DATA_DIRECTORY_CLEANUP_INTERVAL);
+        data_directory_cleanup_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let data_dir = self
+            .global_state_lock
+            .lock_guard()
+            .await
+            .wallet_state
+            .configuration
+            .data_directory()
+            .root_dir_path();
+        let memory_limit = self
+            .global_state_lock
+            .cli()
+            .load_shedding_memory_limit
+            .map(|limit| limit.as_u64());
+        main_loop_state.resource_monitor = Some(ResourceMonitor::new(data_dir, memory_limit));
+
         // Spawn tasks to monitor for SIGTERM, SIGINT, and SIGQUIT. These
         // signals are only used on Unix systems.
         let (tx_term, mut rx_term) = mpsc::channel::<()>(2);
@@ -1674,15 +2893,21 @@ impl MainLoopHandler {
         #[cfg(not(unix))]
         drop((tx_term, tx_int, tx_quit));
 
-        // Use a semaphore to limit number of incoming connections. Should only be
-        // relevant as a countermeasure against a DOS. Each incoming connection
-        // must acquire a permit. If none is free, the below call to `acquire_owned`
-        // will only be resolved when an incoming connection is closed. This
-        // value is set much higher than the configured max number of peers since it's
-        // only intended to be used in case of heavy DOS.
-        let incoming_connections_limit = Arc::new(Semaphore::new(
-            self.global_state_lock.cli().max_num_peers * 2 + 4,
-        ));
+        // Limit the number of incoming connections that are handshaking. Should
+        // only be relevant as a countermeasure against a DOS. Each incoming
+        // connection must acquire a permit, which it releases once the
+        // handshake completes or fails.
+        let handshake_limiter = self
+            .global_state_lock
+            .lock(|s| s.connection_lifecycles.handshake_limiter())
+            .await;
+
+        // Time spent handling each message, recorded outside of the global
+        // state lock.
+        let message_latencies = self
+            .global_state_lock
+            .lock(|s| s.message_latencies.clone())
+            .await;
 
         let exit_code: i32 = loop {
             select! {
@@ -1719,27 +2944,23 @@ impl MainLoopHandler {
                         continue;
                     }
 
-                    // Bump semaphore counter for incoming connections. Should
-                    // be done after the precheck to prevent unnecessary
+                    // Acquire a handshake permit for the incoming connection.
+                    // Should be done after the precheck to prevent unnecessary
                     // acquisitions.
                     let timeout = Duration::from_secs(self.global_state_lock.cli().handshake_timeout.into());
-                    let permit = time::timeout(timeout, incoming_connections_limit.clone().acquire_owned()).await;
-                    let Ok(permit) = permit else {
-                        warn!("Too many incoming connections to handle. Dropping incoming connection from {ip}.");
+                    let Some(permit) = handshake_limiter.acquire(ip, timeout).await else {
                         continue;
                     };
 
-                    let permit = permit?;
-
                     let state = self.global_state_lock.lock_guard().await;
                     let main_to_peer_broadcast_rx_clone: broadcast::Receiver<MainToPeerTask> = self.main_to_peer_broadcast_tx.subscribe();
-                    let peer_task_to_main_tx_clone: mpsc::Sender<PeerTaskToMain> = self.peer_task_to_main_tx.clone();
+                    let peer_task_to_main_tx_clone: BackpressureSender<PeerTaskToMain> = self.peer_task_to_main_tx.clone();
                     let own_handshake_data: HandshakeData = state.get_own_handshakedata();
                     let global_state_lock = self.global_state_lock.clone(); // bump arc refcount.
                     let incoming_peer_task_handle = tokio::task::spawn(async move {
                         // Permit is passed to answer_peer and released after handshake,
-                        // not when the connection closes. This prevents semaphore
-                        // starvation attacks.
+                        // not when the connection closes. This prevents starvation
+                        // attacks.
                         match answer_peer(
                             stream,
                             global_state_lock,
@@ -1760,16 +2981,20 @@ impl MainLoopHandler {
                 // Handle messages from peer tasks
                 Some(msg) = self.peer_task_to_main_rx.recv() => {
                     debug!("Received message sent to main task.");
-                    self.handle_peer_task_message(
-                        msg,
-                        &mut main_loop_state,
-                    )
-                    .await?
+                    let message_type = msg.to_string();
+                    let started = Instant::now();
+                    let result = self.handle_peer_task_message(msg, &mut main_loop_state).await;
+                    message_latencies.record(MessageSource::PeerTask, message_type, started.elapsed());
+                    result?
                 }
 
                 // Handle messages from miner task
                 Some(main_message) = self.miner_to_main_rx.recv() => {
-                    let exit_code = self.handle_miner_task_message(main_message, &mut main_loop_state).await?;
+                    let message_type = main_message.to_string();
+                    let started = Instant::now();
+                    let result = self.handle_miner_task_message(main_message, &mut main_loop_state).await;
+                    message_latencies.record(MessageSource::Miner, message_type, started.elapsed());
+                    let exit_code = result?;
 
                     if let Some(exit_code) = exit_code {
                         break exit_code;
@@ -1784,7 +3009,11 @@ impl MainLoopHandler {
 
                 // Handle messages from rpc server task
                 Some(rpc_server_message) = self.rpc_server_to_main_rx.recv() => {
-                    let shutdown_after_execution = self.handle_rpc_server_message(rpc_server_message.clone(), &mut main_loop_state).await?;
+                    let message_type = rpc_server_message.to_string();
+                    let started = Instant::now();
+                    let result = self.handle_rpc_server_message(rpc_server_message.clone(), &mut main_loop_state).await;
+                    message_latencies.record(MessageSource::RpcServer, message_type, started.elapsed());
+                    let shutdown_after_execution = result?;
                     if shutdown_after_execution {
                         break SUCCESS_EXIT_CODE
                     }
@@ -1798,6 +3027,8 @@ impl MainLoopHandler {
                     // more peers if needed.
                     debug!("Timer: peer discovery job");
 
+                    self.check_clock_offset().await;
+
                     let perform_discovery = if !self.global_state_lock.cli().network.performs_peer_discovery() {
                         // this makes regtest mode behave in a local, controlled way
                         // because no regtest nodes attempt to discover eachother, so the only
@@ -1840,38 +3071,128 @@ impl MainLoopHandler {
                         .await;
                 }
 
-                // Clean up incoming UTXO notifications: remove stale / too old
-                // UTXO notifications from pool
-                _ = utxo_notification_cleanup_interval.tick() => {
-                    log_slow_scope!(fn_name!() + "::select::utxo_notification_cleanup_interval");
+                // Clean up incoming UTXO notifications: remove stale / too old
+                // UTXO notifications from pool
+                _ = utxo_notification_cleanup_interval.tick() => {
+                    log_slow_scope!(fn_name!() + "::select::utxo_notification_cleanup_interval");
+
+                    debug!("Timer: UTXO notification pool cleanup job");
+
+                    // Danger: possible loss of funds.
+                    //
+                    // See description of prune_stale_expected_utxos().
+                    //
+                    // This call is disabled until such time as a thorough
+                    // evaluation and perhaps reimplementation determines that
+                    // it can be called safely without possible loss of funds.
+                    // self.global_state_lock.lock_mut(|s| s.wallet_state.prune_stale_expected_utxos()).await;
+                }
+
+                // Handle membership proof resynchronization
+                _ = mp_resync_interval.tick() => {
+                    log_slow_scope!(fn_name!() + "::select::mp_resync_interval");
+
+                    debug!("Timer: Membership proof resync job");
+                    self.global_state_lock.resync_membership_proofs().await?;
+                }
+
+                // run the proof upgrader. The callee checks if proof upgrading
+                // should be done.
+                _ = tx_proof_upgrade_interval.tick() => {
+                    log_slow_scope!(fn_name!() + "::select::tx_proof_upgrade_interval");
+
+                    trace!("Timer: tx-proof-upgrader");
+                    self.proof_upgrader(&mut main_loop_state).await?;
+                }
+
+                // sample resource usage, and enter or exit load-shedding mode.
+                // the sample also gates proof upgrading.
+                _ = load_shedding_interval.tick() => {
+                    log_slow_scope!(fn_name!() + "::select::load_shedding_interval");
+
+                    trace!("Timer: load shedding");
+                    self.load_shedding(&mut main_loop_state).await?;
+                }
+
+                // evaluate the alert rules, and lift lapsed silences.
+                _ = alert_evaluation_interval.tick() => {
+                    log_slow_scope!(fn_name!() + "::select::alert_evaluation_interval");
+
+                    trace!("Timer: alert evaluation");
+                    self.evaluate_alerts().await;
+                }
+
+                // detect a stalled tip, and rotate out the stalled sync peers.
+                _ = tip_stall_check_interval.tick() => {
+                    log_slow_scope!(fn_name!() + "::select::tip_stall_check_interval");
+
+                    trace!("Timer: tip stall check");
+                    self.tip_stall_watchdog(&mut main_loop_state).await;
+                }
+
+                // reconcile the wallet's backup with the trusted peers.
+                _ = wallet_backup_interval.tick() => {
+                    log_slow_scope!(fn_name!() + "::select::wallet_backup_interval");
+
+                    trace!("Timer: wallet backup");
+                    self.request_wallet_backup_status().await;
+                }
+
+                // sign a checkpoint of the canonical chain, if one is due.
+                _ = checkpoint_interval.tick() => {
+                    log_slow_scope!(fn_name!() + "::select::checkpoint_interval");
+
+                    trace!("Timer: checkpoint");
+                    self.publish_checkpoint(&mut main_loop_state).await?;
+                }
+
+                // check for new releases, if enabled.
+                _ = release_check_interval.tick() => {
+                    log_slow_scope!(fn_name!() + "::select::release_check_interval");
 
-                    debug!("Timer: UTXO notification pool cleanup job");
+                    trace!("Timer: release check");
+                    self.check_for_release(&mut main_loop_state);
+                }
 
-                    // Danger: possible loss of funds.
-                    //
-                    // See description of prune_stale_expected_utxos().
-                    //
-                    // This call is disabled until such time as a thorough
-                    // evaluation and perhaps reimplementation determines that
-                    // it can be called safely without possible loss of funds.
-                    // self.global_state_lock.lock_mut(|s| s.wallet_state.prune_stale_expected_utxos()).await;
+                // delete the bodies of fork blocks deep below the tip.
+                _ = fork_pruning_interval.tick() => {
+                    log_slow_scope!(fn_name!() + "::select::fork_pruning_interval");
+
+                    trace!("Timer: fork pruning");
+                    self.prune_fork_blocks().await?;
                 }
 
-                // Handle membership proof resynchronization
-                _ = mp_resync_interval.tick() => {
-                    log_slow_scope!(fn_name!() + "::select::mp_resync_interval");
+                // delete the bodies of canonical blocks deep below the tip.
+                _ = canonical_pruning_interval.tick() => {
+                    log_slow_scope!(fn_name!() + "::select::canonical_pruning_interval");
 
-                    debug!("Timer: Membership proof resync job");
-                    self.global_state_lock.resync_membership_proofs().await?;
+                    trace!("Timer: canonical pruning");
+                    self.prune_canonical_blocks().await?;
                 }
 
-                // run the proof upgrader. The callee checks if proof upgrading
-                // should be done.
-                _ = tx_proof_upgrade_interval.tick() => {
-                    log_slow_scope!(fn_name!() + "::select::tx_proof_upgrade_interval");
+                // rotate logs and delete expired files in the data directory.
+                _ = data_directory_cleanup_interval.tick() => {
+                    log_slow_scope!(fn_name!() + "::select::data_directory_cleanup_interval");
 
-                    trace!("Timer: tx-proof-upgrader");
-                    self.proof_upgrader(&mut main_loop_state).await?;
+                    trace!("Timer: data directory cleanup");
+                    match self.global_state_lock.clean_data_directory().await {
+                        Ok(report) => debug!("Cleaned up data directory: {report:?}"),
+                        Err(e) => warn!("Failed to clean up data directory: {e}"),
+                    }
+                }
+
+                // deliver messages from peer tasks that were spilled to disk
+                // while the channel was full, once it has drained.
+                _ = spill_drain_interval.tick() => {
+                    log_slow_scope!(fn_name!() + "::select::spill_drain_interval");
+
+                    trace!("Timer: spill drain");
+                    while self.peer_task_to_main_rx.is_empty() {
+                        let Some(msg) = self.peer_task_to_main_tx.take_spilled().await else {
+                            break;
+                        };
+                        self.handle_peer_task_message(msg, &mut main_loop_state).await?;
+                    }
                 }
 
             }
@@ -1907,6 +3228,12 @@ impl MainLoopHandler {
                 if let Ok(notification) = transaction.as_ref().try_into() {
                     let pmsg = MainToPeerTask::TransactionNotification(notification);
                     self.main_to_peer_broadcast(pmsg);
+
+                    // Own transactions take the priority lane: upgrade them to
+                    // single proofs right away, rather than leaving it to the
+                    // scheduled proof upgrader.
+                    self.upgrade_own_proof_collection(&transaction, main_loop_state)
+                        .await;
                 } else {
                     // Otherwise, upgrade its proof quality, and share it by
                     // spinning up the proof upgrader.
@@ -2002,6 +3329,9 @@ impl MainLoopHandler {
             RPCServerToMain::SetTipToStoredBlock(digest) => {
                 info!("setting tip to {digest:x}");
 
+                // Ask miner to stop work until state update is completed
+                self.main_to_miner_tx.send(MainToMiner::WaitForContinue);
+
                 let block_notify = self.global_state_lock.cli().block_notify.clone();
                 let res = self
                     .global_state_lock()
@@ -2010,8 +3340,15 @@ impl MainLoopHandler {
                     .set_tip_to_stored_block(digest)
                     .await;
                 match res {
-                    Ok(_) => Self::spawn_block_notify_command(&block_notify, digest),
-                    Err(e) => error!("Failed to set tip to {digest:x}: {e}"),
+                    Ok(update_jobs) => {
+                        Self::spawn_block_notify_command(&block_notify, digest);
+                        self.spawn_mempool_txs_update_job(main_loop_state, update_jobs);
+                        self.main_to_miner_tx.send(MainToMiner::NewBlock);
+                    }
+                    Err(e) => {
+                        error!("Failed to set tip to {digest:x}: {e}");
+                        self.main_to_miner_tx.send(MainToMiner::Continue);
+                    }
                 };
 
                 Ok(false)
@@ -2043,6 +3380,11 @@ impl MainLoopHandler {
 
                 Ok(false)
             }
+            RPCServerToMain::ReplicatedBlocks(blocks) => {
+                self.handle_replicated_blocks(main_loop_state, blocks)
+                    .await?;
+                Ok(false)
+            }
             RPCServerToMain::ProofOfWorkSolution(new_block) => {
                 info!("Handling PoW solution from RPC call");
 
@@ -2108,6 +3450,20 @@ impl MainLoopHandler {
         // Flush all databases
         self.global_state_lock.flush_databases().await?;
 
+        // A graceful shutdown ends a clean run, so the next start is not
+        // counted towards a crash loop.
+        let crash_counter = CrashCounter::new(
+            self.global_state_lock
+                .lock_guard()
+                .await
+                .wallet_state
+                .configuration
+                .data_directory(),
+        );
+        if let Err(e) = crash_counter.record_clean_run().await {
+            warn!("Could not record clean run: {e}");
+        }
+
         tokio::time::sleep(Duration::from_millis(50)).await;
 
         // Child tasks should have finished by now. If not, abort them.
@@ -2151,6 +3507,7 @@ mod tests {
     use super::*;
     use crate::application::config::cli_args;
     use crate::application::config::network::Network;
+    use crate::application::loops::channel::BlockProposalNotification;
     use crate::tests::shared::blocks::invalid_empty_block;
     use crate::tests::shared::blocks::invalid_empty_block1_with_guesser_fraction;
     use crate::tests::shared::globalstate::get_dummy_peer_incoming;
@@ -2238,6 +3595,58 @@ mod tests {
         }
     }
 
+    #[apply(shared_tokio_runtime)]
+    async fn expired_own_proposal_is_rebroadcast() {
+        let TestSetup {
+            mut main_loop_handler,
+            mut main_to_peer_rx,
+            ..
+        } = setup(1, 0, cli_args::Args::default()).await;
+        let network = main_loop_handler.global_state_lock.cli().network;
+        let mut mutable_main_loop_state = main_loop_handler.mutable();
+
+        let proposal = invalid_empty_block(&Block::genesis(network), network);
+        main_loop_handler
+            .global_state_lock
+            .lock_guard_mut()
+            .await
+            .mining_state
+            .block_proposal = BlockProposal::own_proposal(proposal.clone(), vec![]);
+
+        // expiry of a proposal that is no longer current is ignored
+        main_loop_handler
+            .handle_miner_task_message(
+                MinerToMain::ProposalExpired(Digest::default()),
+                &mut mutable_main_loop_state,
+            )
+            .await
+            .unwrap();
+        assert!(main_to_peer_rx.try_recv().is_err());
+
+        main_loop_handler
+            .handle_miner_task_message(
+                MinerToMain::ProposalExpired(proposal.hash()),
+                &mut mutable_main_loop_state,
+            )
+            .await
+            .unwrap();
+        let Ok(MainToPeerTask::BlockProposalNotification(notification)) =
+            main_to_peer_rx.try_recv()
+        else {
+            panic!("Expired proposal must be re-broadcast");
+        };
+        assert_eq!(BlockProposalNotification::from(&proposal), notification);
+        assert_eq!(
+            1,
+            main_loop_handler
+                .global_state_lock
+                .lock_guard()
+                .await
+                .mining_state
+                .num_expired_block_proposals
+        );
+    }
+
     #[apply(shared_tokio_runtime)]
     async fn handle_self_guessed_block_new_tip() {
         // A new tip is registered by main_loop. Verify correct state update.
@@ -2539,6 +3948,170 @@ mod tests {
                 "Sync mode must be unset on timeout"
             );
         }
+
+        #[apply(shared_tokio_runtime)]
+        #[traced_test]
+        async fn stalled_sync_request_fails_over_to_other_peer() {
+            let TestSetup {
+                mut main_loop_handler,
+                mut main_to_peer_rx,
+                ..
+            } = setup(0, 0, cli_args::Args::default()).await;
+            let mut mutable_main_loop_state = main_loop_handler.mutable();
+
+            let claimed_max_height = 1_000u64.into();
+            let claimed_max_pow = ProofOfWork::new([100; 6]);
+            main_loop_handler
+                .global_state_lock
+                .lock_guard_mut()
+                .await
+                .net
+                .sync_anchor = Some(SyncAnchor::new(
+                claimed_max_pow,
+                MmrAccumulator::new_from_leafs(vec![]),
+            ));
+            for i in 0..2 {
+                mutable_main_loop_state.sync_state.peer_sync_states.insert(
+                    get_dummy_socket_address(i),
+                    PeerSynchronizationState::new(claimed_max_height, claimed_max_pow),
+                );
+            }
+
+            let requested_peer = |msg: MainToPeerTask| match msg {
+                MainToPeerTask::RequestBlockBatch(request) => request.peer_addr_target,
+                other => panic!("expected block batch request, got {other:?}"),
+            };
+
+            main_loop_handler
+                .block_sync(&mut mutable_main_loop_state)
+                .await
+                .unwrap();
+            let stalled_peer = requested_peer(main_to_peer_rx.try_recv().unwrap());
+
+            // Before the deadline, the request is left running.
+            main_loop_handler
+                .block_sync(&mut mutable_main_loop_state)
+                .await
+                .unwrap();
+            assert!(main_to_peer_rx.try_recv().is_err());
+
+            // After the deadline, the stalled peer is sanctioned and the
+            // request goes to the other peer.
+            main_loop_handler = main_loop_handler.with_mocked_time(
+                SystemTime::now()
+                    + INDIVIDUAL_PEER_SYNCHRONIZATION_TIMEOUT
+                    + Duration::from_secs(1),
+            );
+            main_loop_handler
+                .block_sync(&mut mutable_main_loop_state)
+                .await
+                .unwrap();
+            assert!(matches!(
+                main_to_peer_rx.try_recv().unwrap(),
+                MainToPeerTask::PeerSynchronizationTimeout(peer) if peer == stalled_peer
+            ));
+            let failover_peer = requested_peer(main_to_peer_rx.try_recv().unwrap());
+            assert_ne!(stalled_peer, failover_peer);
+            assert_eq!(
+                1,
+                mutable_main_loop_state.sync_state.peer_quality[&stalled_peer].num_timeouts
+            );
+        }
+
+        #[test]
+        fn peer_missing_adaptive_deadline_is_failed_over_without_sanction() {
+            let mut sync_state = SyncState::default();
+            let peer = get_dummy_socket_address(0);
+            sync_state
+                .peer_quality
+                .entry(peer)
+                .or_default()
+                .record_served(Duration::from_secs(1));
+
+            let now = SystemTime::now();
+            sync_state.record_request(10u64.into(), peer, now);
+            let past_deadline =
+                now + sync_requests::MIN_SYNC_REQUEST_TIMEOUT + Duration::from_secs(1);
+            assert_eq!(
+                (None, true),
+                sync_state.update_status_of_last_request(5u64.into(), past_deadline)
+            );
+            assert!(sync_state.stalled_peers.contains(&peer));
+            assert_eq!(1, sync_state.peer_quality[&peer].num_timeouts);
+
+            // A peer that misses the deadline applying to all peers is
+            // sanctioned.
+            sync_state.record_request(10u64.into(), peer, now);
+            let past_individual_timeout =
+                now + INDIVIDUAL_PEER_SYNCHRONIZATION_TIMEOUT + Duration::from_secs(1);
+            assert_eq!(
+                (Some(peer), true),
+                sync_state.update_status_of_last_request(5u64.into(), past_individual_timeout)
+            );
+        }
+
+        #[apply(shared_tokio_runtime)]
+        #[traced_test]
+        async fn stalled_tip_rotates_out_sync_peers_and_raises_alert() {
+            let TestSetup {
+                mut main_loop_handler,
+                mut main_to_peer_rx,
+                ..
+            } = setup(0, 0, cli_args::Args::default()).await;
+            let mut mutable_main_loop_state = main_loop_handler.mutable();
+
+            let claimed_max_height = 1_000u64.into();
+            let claimed_max_pow = ProofOfWork::new([100; 6]);
+            let claiming_peers = [0, 1].map(get_dummy_socket_address);
+            for peer in claiming_peers {
+                mutable_main_loop_state.sync_state.peer_sync_states.insert(
+                    peer,
+                    PeerSynchronizationState::new(claimed_max_height, claimed_max_pow),
+                );
+            }
+
+            main_loop_handler
+                .tip_stall_watchdog(&mut mutable_main_loop_state)
+                .await;
+            assert!(main_to_peer_rx.try_recv().is_err());
+
+            let stall_period = Duration::from(
+                main_loop_handler
+                    .global_state_lock
+                    .cli()
+                    .network
+                    .target_block_interval(),
+            ) * TIP_STALL_TARGET_INTERVALS;
+            main_loop_handler = main_loop_handler
+                .with_mocked_time(SystemTime::now() + stall_period + Duration::from_secs(1));
+            main_loop_handler
+                .tip_stall_watchdog(&mut mutable_main_loop_state)
+                .await;
+
+            let mut disconnected = vec![];
+            let mut requested_tips = false;
+            while let Ok(message) = main_to_peer_rx.try_recv() {
+                match message {
+                    MainToPeerTask::Disconnect(peer) => disconnected.push(peer),
+                    MainToPeerTask::RequestBlockNotification => requested_tips = true,
+                    other => panic!("unexpected message {other:?}"),
+                }
+            }
+            // Only one of the peers that claim the missing blocks is rotated
+            // out, since none of them failed a request.
+            assert_eq!(1, disconnected.len());
+            assert!(claiming_peers.contains(&disconnected[0]));
+            assert!(requested_tips);
+
+            let alerts = main_loop_handler
+                .global_state_lock
+                .lock_guard()
+                .await
+                .alerts
+                .active(Timestamp::now());
+            assert_eq!(1, alerts.len());
+            assert_eq!(AlertKind::TipStall, alerts[0].kind);
+        }
     }
 
     mod proof_upgrader {
@@ -2700,10 +4273,81 @@ mod tests {
                 other => panic!("Must have sent transaction notification to peer loop after successful proof upgrade. Got:\n{other:?}"),
             }
         }
+
+        #[apply(shared_tokio_runtime)]
+        #[traced_test]
+        async fn own_proof_collection_tx_is_upgraded_and_relayed_immediately() {
+            let TestSetup {
+                mut main_loop_handler,
+                mut main_to_peer_rx,
+                ..
+            } = setup(0, 0, cli_args::Args::default()).await;
+
+            // Proof upgrading of foreign transactions is not enabled.
+            let mocked_cli = cli_args::Args {
+                tx_proving_capability: Some(TxProvingCapability::SingleProof),
+                ..Default::default()
+            };
+            main_loop_handler
+                .global_state_lock
+                .set_cli(mocked_cli.clone())
+                .await;
+            let mut mutable_main_loop_state = main_loop_handler.mutable();
+
+            let consensus_rule_set =
+                ConsensusRuleSet::infer_from(mocked_cli.network, BlockHeight::genesis());
+            let own_tx = tx_no_outputs(
+                &mut main_loop_handler.global_state_lock,
+                TxProvingCapability::ProofCollection,
+                NativeCurrencyAmount::coins(1),
+                consensus_rule_set,
+            )
+            .await;
+            main_loop_handler
+                .global_state_lock
+                .lock_guard_mut()
+                .await
+                .mempool_insert((*own_tx).clone(), UpgradePriority::Critical)
+                .await;
+
+            main_loop_handler
+                .handle_rpc_server_message(
+                    RPCServerToMain::BroadcastTx(own_tx.clone()),
+                    &mut mutable_main_loop_state,
+                )
+                .await
+                .unwrap();
+
+            match main_to_peer_rx.recv().await {
+                Ok(MainToPeerTask::TransactionNotification(tx_noti)) => {
+                    assert_eq!(own_tx.txid(), tx_noti.txid);
+                    assert_eq!(
+                        TransactionProofQuality::ProofCollection,
+                        tx_noti.proof_quality
+                    );
+                }
+                other => panic!("Must relay own transaction immediately. Got:\n{other:?}"),
+            }
+
+            // The upgrade runs without waiting for the scheduled upgrader.
+            mutable_main_loop_state
+                .proof_upgrader_task
+                .expect("own transaction must be upgraded")
+                .await
+                .unwrap();
+            match main_to_peer_rx.recv().await {
+                Ok(MainToPeerTask::TransactionNotification(tx_noti)) => {
+                    assert_eq!(TransactionProofQuality::SingleProof, tx_noti.proof_quality);
+                }
+                other => panic!("Must relay upgraded own transaction. Got:\n{other:?}"),
+            }
+        }
     }
 
     mod peer_discovery {
         use super::*;
+        use crate::state::load_shedding::ResourcePressure;
+        use crate::state::load_shedding::LOAD_SHEDDING_CONSECUTIVE_SAMPLES;
 
         #[apply(shared_tokio_runtime)]
         #[traced_test]
@@ -2769,6 +4413,60 @@ mod tests {
             assert!(main_to_peer_rx.is_empty());
         }
 
+        #[apply(shared_tokio_runtime)]
+        #[traced_test]
+        async fn prune_outbound_connections_when_shedding_load() {
+            let num_init_peers_outgoing = 6;
+            let num_init_peers_incoming = 0;
+            let TestSetup {
+                mut main_loop_handler,
+                mut main_to_peer_rx,
+                ..
+            } = setup(
+                num_init_peers_outgoing,
+                num_init_peers_incoming,
+                cli_args::Args::default(),
+            )
+            .await;
+
+            let mocked_cli = cli_args::Args {
+                max_num_peers: 10,
+                load_shedding_max_num_peers: 2,
+                ..Default::default()
+            };
+            main_loop_handler
+                .global_state_lock
+                .set_cli(mocked_cli.clone())
+                .await;
+
+            // Not shedding load, so the regular maximum applies.
+            main_loop_handler.prune_peers().await.unwrap();
+            assert!(main_to_peer_rx.is_empty());
+
+            let full_pressure = ResourcePressure {
+                cpu_percent: 100.0,
+                memory_percent: 100.0,
+                disk_percent: 100.0,
+            };
+            let thresholds = LoadSheddingThresholds::from(&mocked_cli);
+            {
+                let mut global_state = main_loop_handler.global_state_lock.lock_guard_mut().await;
+                for _ in 0..LOAD_SHEDDING_CONSECUTIVE_SAMPLES {
+                    global_state
+                        .load_shedding
+                        .update(full_pressure, &thresholds, Timestamp::now());
+                }
+                assert!(global_state.load_shedding.is_active());
+            }
+
+            main_loop_handler.prune_peers().await.unwrap();
+            assert_eq!(4, main_to_peer_rx.len());
+            for _ in 0..4 {
+                let peer_msg = main_to_peer_rx.recv().await.unwrap();
+                assert!(matches!(peer_msg, MainToPeerTask::Disconnect(_)))
+            }
+        }
+
         #[apply(shared_tokio_runtime)]
         #[traced_test]
         async fn skip_peer_discovery_if_peer_limit_is_exceeded() {
@@ -3114,7 +4812,7 @@ mod tests {
                     main_to_peer_rx_mock,
                     peer_to_main_tx_clone,
                     own_handshake,
-                    None, // No semaphore permit in test
+                    None, // No handshake permit in test
                 )
                 .await
                 {
@@ -0,0 +1,174 @@
+//! Processing latency of the messages handled by the main loop.
+//!
+//! The main loop handles messages from the peer tasks, the miner, and the RPC
+//! server one at a time, so a single slow message stalls all others. The
+//! time spent handling each message is recorded in a histogram per message
+//! type, and reported by the `main_loop_stats` RPC endpoint, such that stalls
+//! can be attributed to specific message types.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Upper bounds of the histogram buckets, in increasing order. Every
+/// histogram has one more bucket, for latencies above the last bound.
+pub const LATENCY_BUCKET_BOUNDS: [Duration; 7] = [
+    Duration::from_millis(1),
+    Duration::from_millis(10),
+    Duration::from_millis(100),
+    Duration::from_secs(1),
+    Duration::from_secs(10),
+    Duration::from_secs(60),
+    Duration::from_secs(600),
+];
+
+/// The task that sent a message to the main loop.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, strum::Display,
+)]
+pub enum MessageSource {
+    PeerTask,
+    Miner,
+    RpcServer,
+}
+
+/// The processing latency of one message type since startup.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageLatency {
+    pub source: MessageSource,
+
+    /// The name of the message's variant, e.g. `NewBlocks`.
+    pub message_type: String,
+
+    /// Number of messages handled.
+    pub count: u64,
+
+    /// Total time spent handling messages.
+    pub total: Duration,
+
+    /// Longest time spent handling a single message.
+    pub max: Duration,
+
+    /// Number of messages per bucket: `buckets[i]` counts the messages
+    /// handled within `LATENCY_BUCKET_BOUNDS[i]`, but not within the
+    /// previous bound. The last bucket counts the messages above all bounds.
+    pub buckets: Vec<u64>,
+}
+
+impl MessageLatency {
+    fn new(source: MessageSource, message_type: String) -> Self {
+        Self {
+            source,
+            message_type,
+            count: 0,
+            total: Duration::ZERO,
+            max: Duration::ZERO,
+            buckets: vec![0; LATENCY_BUCKET_BOUNDS.len() + 1],
+        }
+    }
+
+    fn record(&mut self, latency: Duration) {
+        let bucket = LATENCY_BUCKET_BOUNDS
+            .iter()
+            .position(|bound| latency <= *bound)
+            .unwrap_or(LATENCY_BUCKET_BOUNDS.len());
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.total += latency;
+        self.max = self.max.max(latency);
+    }
+
+    /// The average time spent handling a message, or zero if none was
+    /// handled.
+    pub fn mean(&self) -> Duration {
+        u32::try_from(self.count)
+            .ok()
+            .and_then(|count| self.total.checked_div(count))
+            .unwrap_or_default()
+    }
+}
+
+/// Processing latencies of the main loop, as reported by the
+/// `main_loop_stats` RPC endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MainLoopStats {
+    /// See [`LATENCY_BUCKET_BOUNDS`].
+    pub bucket_bounds: Vec<Duration>,
+
+    /// One entry per message type handled since startup, ordered by total
+    /// time spent, most first.
+    pub message_types: Vec<MessageLatency>,
+}
+
+/// Records the latencies of the main loop. Clones share their records, such
+/// that the main loop can record without acquiring the global state lock.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MessageLatencyRecorder(
+    Arc<Mutex<HashMap<(MessageSource, String), MessageLatency>>>,
+);
+
+impl MessageLatencyRecorder {
+    pub(crate) fn record(&self, source: MessageSource, message_type: String, latency: Duration) {
+        self.0
+            .lock()
+            .unwrap()
+            .entry((source, message_type.clone()))
+            .or_insert_with(|| MessageLatency::new(source, message_type))
+            .record(latency);
+    }
+
+    pub(crate) fn stats(&self) -> MainLoopStats {
+        let mut message_types = self.0.lock().unwrap().values().cloned().collect::<Vec<_>>();
+        message_types.sort_by(|a, b| b.total.cmp(&a.total));
+
+        MainLoopStats {
+            bucket_bounds: LATENCY_BUCKET_BOUNDS.to_vec(),
+            message_types,
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latencies_are_bucketed_per_message_type() {
+        let recorder = MessageLatencyRecorder::default();
+        let new_blocks = || "NewBlocks".to_string();
+        recorder.record(MessageSource::PeerTask, new_blocks(), Duration::ZERO);
+        recorder.record(
+            MessageSource::PeerTask,
+            new_blocks(),
+            Duration::from_secs(2),
+        );
+        recorder.record(
+            MessageSource::PeerTask,
+            new_blocks(),
+            Duration::from_secs(3600),
+        );
+        recorder.record(
+            MessageSource::Miner,
+            "NewBlockFound".to_string(),
+            Duration::from_millis(5),
+        );
+
+        let stats = recorder.stats();
+        assert_eq!(2, stats.message_types.len());
+
+        let peer = &stats.message_types[0];
+        assert_eq!(MessageSource::PeerTask, peer.source);
+        assert_eq!(3, peer.count);
+        assert_eq!(Duration::from_secs(3600), peer.max);
+        assert_eq!(vec![1, 0, 0, 0, 1, 0, 0, 1], peer.buckets);
+        assert_eq!(Duration::from_secs(3602) / 3, peer.mean());
+
+        let miner = &stats.message_types[1];
+        assert_eq!(vec![0, 1, 0, 0, 0, 0, 0, 0], miner.buckets);
+    }
+}
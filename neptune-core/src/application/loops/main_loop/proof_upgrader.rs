@@ -194,6 +194,11 @@ impl UpdateMutatorSetDataJob {
         }
     }
 
+    /// The kernel of the transaction to be updated.
+    pub(crate) fn old_kernel(&self) -> &TransactionKernel {
+        &self.old_kernel
+    }
+
     pub(crate) async fn upgrade(
         self,
         triton_vm_job_queue: Arc<TritonVmJobQueue>,
@@ -418,9 +423,12 @@ impl UpgradeJob {
     ) {
         let mut upgrade_job = self;
 
+        // Critical upgrades are those of transactions initiated by this node.
         let upgrade_incentive = upgrade_job.upgrade_incentive();
         let priority = match upgrade_incentive {
-            UpgradeIncentive::Critical => TritonVmJobPriority::High,
+            UpgradeIncentive::Critical => global_state_lock
+                .cli()
+                .own_tx_job_priority(TritonVmJobPriority::High),
             _ => TritonVmJobPriority::Low,
         };
 
@@ -434,7 +442,20 @@ impl UpgradeJob {
             // note: if this task is cancelled, the job will continue
             // because TritonVmJobOptions::cancel_job_rx is None.
             // see how compose_task handles cancellation in mine_loop.
-            let job_options = global_state_lock.cli().proof_job_options(priority);
+            //
+            // The proving of own transactions can be cancelled by the user
+            // though, until they are broadcast.
+            let mut job_options = global_state_lock.cli().proof_job_options(priority);
+            let is_cancellable = upgrade_incentive == UpgradeIncentive::Critical;
+            if is_cancellable {
+                job_options.cancel_job_rx = Some(
+                    global_state_lock
+                        .lock_guard_mut()
+                        .await
+                        .proving_cancellations
+                        .register(&affected_txids),
+                );
+            }
 
             // It's a important to *not* hold any locks when proving happens.
             // Otherwise, entire application freezes!!
@@ -468,6 +489,17 @@ impl UpgradeJob {
                     (upgraded_tx, expected_utxos)
                 }
                 Err(e) => {
+                    if is_cancellable
+                        && global_state_lock
+                            .lock_guard_mut()
+                            .await
+                            .proving_cancellations
+                            .finish(&affected_txids)
+                    {
+                        info!("Proving of own transaction was cancelled.");
+                        return;
+                    }
+
                     error!("UpgradeProof job failed. error: {e}");
                     error!(
                         "Consider lowering your proving capability to {}, in case it is set higher.\nCurrent proving \
@@ -482,6 +514,14 @@ impl UpgradeJob {
             /* Check if upgrade resulted in valid transaction */
             upgrade_job = {
                 let mut global_state = global_state_lock.lock_guard_mut().await;
+
+                // The transaction was removed from the mempool when it was
+                // cancelled, and must not be reinserted.
+                if is_cancellable && global_state.proving_cancellations.finish(&affected_txids) {
+                    info!("Discarding proof of cancelled own transaction.");
+                    return;
+                }
+
                 let tip_mutator_set = global_state
                     .chain
                     .light_state()
@@ -0,0 +1,133 @@
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+
+use sysinfo::Disks;
+use sysinfo::Pid;
+use sysinfo::ProcessRefreshKind;
+use sysinfo::ProcessesToUpdate;
+use sysinfo::System;
+
+use crate::state::load_shedding::ResourcePressure;
+
+/// Samples the CPU and memory usage of this node's process, and the usage of
+/// the disk holding its data directory. Other processes on the same machine
+/// are not taken into account. Memory usage is relative to the configured
+/// memory limit, if any, and to total memory otherwise.
+///
+/// CPU usage is measured between two consecutive calls to
+/// [`Self::sample`], so the monitor must be kept alive across samples.
+#[derive(Debug)]
+pub(crate) struct ResourceMonitor {
+    system: System,
+    disks: Disks,
+    data_dir: PathBuf,
+
+    /// Memory budget in bytes. Total memory is used if unset.
+    memory_limit: Option<u64>,
+
+    /// This node's process, if it can be determined.
+    pid: Option<Pid>,
+}
+
+impl ResourceMonitor {
+    pub(crate) fn new(data_dir: PathBuf, memory_limit: Option<u64>) -> Self {
+        let mut monitor = Self {
+            system: System::new(),
+            disks: Disks::new_with_refreshed_list(),
+            data_dir,
+            memory_limit,
+            pid: sysinfo::get_current_pid().ok(),
+        };
+        monitor.refresh_process();
+
+        monitor
+    }
+
+    fn refresh_process(&mut self) {
+        if let Some(pid) = self.pid {
+            self.system.refresh_processes_specifics(
+                ProcessesToUpdate::Some(&[pid]),
+                ProcessRefreshKind::new().with_cpu().with_memory(),
+            );
+        }
+    }
+
+    pub(crate) fn sample(&mut self) -> ResourcePressure {
+        self.refresh_process();
+        self.system.refresh_memory();
+        self.disks.refresh();
+
+        let process = self.pid.and_then(|pid| self.system.process(pid));
+
+        // Usage across all cores, so that it does not exceed 100%.
+        let num_cores = std::thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        let cpu_percent = process.map_or(0.0, |process| process.cpu_usage()) / num_cores as f32;
+
+        let memory_budget = self
+            .memory_limit
+            .unwrap_or_else(|| self.system.total_memory());
+        let memory_percent = match memory_budget {
+            0 => 0.0,
+            budget => 100.0 * process.map_or(0, |process| process.memory()) as f32 / budget as f32,
+        };
+
+        ResourcePressure {
+            cpu_percent: cpu_percent.min(100.0),
+            memory_percent: memory_percent.min(100.0),
+            disk_percent: self.disk_percent(),
+        }
+    }
+
+    /// Usage of the disk holding the data directory. The disk is identified as
+    /// the one with the longest mount point that is a prefix of the data
+    /// directory. Returns 0 if no such disk is found.
+    fn disk_percent(&self) -> f32 {
+        let data_dir = self
+            .data_dir
+            .canonicalize()
+            .unwrap_or_else(|_| self.data_dir.clone());
+
+        self.disks
+            .list()
+            .iter()
+            .filter(|disk| data_dir.starts_with(disk.mount_point()))
+            .max_by_key(|disk| disk.mount_point().components().count())
+            .filter(|disk| disk.total_space() > 0)
+            .map(|disk| {
+                let used = disk.total_space().saturating_sub(disk.available_space());
+                100.0 * used as f32 / disk.total_space() as f32
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_is_within_bounds() {
+        let mut monitor = ResourceMonitor::new(std::env::temp_dir(), None);
+        let pressure = monitor.sample();
+        for percent in [
+            pressure.cpu_percent,
+            pressure.memory_percent,
+            pressure.disk_percent,
+        ] {
+            assert!((0.0..=100.0).contains(&percent), "got {percent}");
+        }
+    }
+
+    #[test]
+    fn memory_usage_is_relative_to_limit() {
+        let mut monitor = ResourceMonitor::new(std::env::temp_dir(), Some(1));
+        if monitor
+            .pid
+            .and_then(|pid| monitor.system.process(pid))
+            .is_some()
+        {
+            assert_eq!(100.0, monitor.sample().memory_percent);
+        }
+    }
+}
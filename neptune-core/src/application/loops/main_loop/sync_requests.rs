@@ -0,0 +1,209 @@
+//! Deadlines and peer selection for the block batch requests of sync mode.
+//!
+//! In sync mode, the main loop requests one batch of blocks at a time from a
+//! peer that claims to hold them. If the peer does not deliver before the
+//! request's deadline, the request fails over to another peer holding the
+//! blocks. Peers are picked by how well they served earlier requests, then by
+//! the round-trip time measured by pings, and the deadline adapts to how fast the picked peer responded before. A peer is
+//! only sanctioned if it also missed the longest deadline, which applies to
+//! peers that never served a request.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use rand::seq::IndexedRandom;
+use rand::Rng;
+
+use crate::protocol::consensus::block::block_height::BlockHeight;
+
+/// Shortest time a peer is given to respond to a block batch request.
+pub(super) const MIN_SYNC_REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// A peer that served earlier requests is given this multiple of its average
+/// response time to respond to the next one.
+const RESPONSE_TIME_DEADLINE_FACTOR: u32 = 4;
+
+/// A block batch request sent to a peer in sync mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct SyncRequest {
+    pub(super) peer: SocketAddr,
+
+    /// Height of the first requested block. The request is served once the
+    /// node has synced up to this height.
+    pub(super) requested_height: BlockHeight,
+    pub(super) sent: SystemTime,
+
+    /// Time after which the peer is considered to have stalled.
+    pub(super) deadline: SystemTime,
+}
+
+/// How well a peer served the block batch requests sent to it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(super) struct SyncPeerQuality {
+    pub(super) num_served: u32,
+    pub(super) num_timeouts: u32,
+
+    /// Moving average of the times the peer took to serve requests.
+    pub(super) response_time: Option<Duration>,
+
+    /// Round-trip time measured by pings, if the peer answered any.
+    pub(super) round_trip_time: Option<Duration>,
+}
+
+impl SyncPeerQuality {
+    pub(super) fn record_served(&mut self, response_time: Duration) {
+        self.num_served = self.num_served.saturating_add(1);
+        self.response_time = Some(match self.response_time {
+            Some(average) => (average * 3 + response_time) / 4,
+            None => response_time,
+        });
+    }
+
+    pub(super) fn record_timeout(&mut self) {
+        self.num_timeouts = self.num_timeouts.saturating_add(1);
+    }
+
+    /// The time the peer is given to serve a request. Peers that never served
+    /// a request get `max_timeout`.
+    pub(super) fn timeout(&self, max_timeout: Duration) -> Duration {
+        self.response_time
+            .map(|response_time| {
+                (response_time * RESPONSE_TIME_DEADLINE_FACTOR)
+                    .clamp(MIN_SYNC_REQUEST_TIMEOUT, max_timeout)
+            })
+            .unwrap_or(max_timeout)
+    }
+
+    /// Lower is better: fewer timeouts first, then faster responses, then
+    /// shorter round-trip times.
+    pub(super) fn rank(&self) -> (u32, Duration, Duration) {
+        (
+            self.num_timeouts,
+            self.response_time.unwrap_or(Duration::MAX),
+            self.round_trip_time.unwrap_or(Duration::MAX),
+        )
+    }
+}
+
+/// Pick the peer to send the next request to, among the `candidates` that
+/// hold the requested blocks.
+///
+/// Peers in `stalled` failed to serve the requested blocks before and are
+/// only picked if no other candidate is left. Otherwise, the peers of the
+/// best [quality](SyncPeerQuality) are preferred, and ties are broken at
+/// random.
+pub(super) fn choose_sync_peer<R: Rng + ?Sized>(
+    candidates: &[SocketAddr],
+    quality: &HashMap<SocketAddr, SyncPeerQuality>,
+    stalled: &HashSet<SocketAddr>,
+    rng: &mut R,
+) -> Option<SocketAddr> {
+    let fresh = candidates
+        .iter()
+        .copied()
+        .filter(|peer| !stalled.contains(peer))
+        .collect::<Vec<_>>();
+    let eligible: &[SocketAddr] = if fresh.is_empty() { candidates } else { &fresh };
+
+    let rank = |peer: &SocketAddr| quality.get(peer).copied().unwrap_or_default().rank();
+    let best_rank = eligible.iter().map(rank).min()?;
+    let best = eligible
+        .iter()
+        .copied()
+        .filter(|peer| rank(peer) == best_rank)
+        .collect::<Vec<_>>();
+
+    best.choose(rng).copied()
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+    use crate::tests::shared::globalstate::get_dummy_socket_address;
+
+    #[test]
+    fn deadline_adapts_to_response_time() {
+        let max_timeout = Duration::from_secs(120);
+        let mut quality = SyncPeerQuality::default();
+        assert_eq!(max_timeout, quality.timeout(max_timeout));
+
+        quality.record_served(Duration::from_secs(10));
+        assert_eq!(Duration::from_secs(40), quality.timeout(max_timeout));
+
+        quality.record_served(Duration::from_secs(2));
+        assert_eq!(Some(Duration::from_secs(8)), quality.response_time);
+        assert_eq!(Duration::from_secs(32), quality.timeout(max_timeout));
+
+        quality.record_served(Duration::from_secs(600));
+        assert_eq!(max_timeout, quality.timeout(max_timeout));
+
+        let mut fast = SyncPeerQuality::default();
+        fast.record_served(Duration::from_secs(1));
+        assert_eq!(MIN_SYNC_REQUEST_TIMEOUT, fast.timeout(max_timeout));
+    }
+
+    #[test]
+    fn stalled_and_unreliable_peers_are_avoided() {
+        let mut rng = rand::rng();
+        let [a, b, c] = [0, 1, 2].map(get_dummy_socket_address);
+        let candidates = [a, b, c];
+
+        let mut quality = HashMap::<_, SyncPeerQuality>::new();
+        quality.entry(a).or_default().record_timeout();
+        quality
+            .entry(b)
+            .or_default()
+            .record_served(Duration::from_secs(1));
+
+        let no_stalled = HashSet::new();
+        assert_eq!(
+            Some(b),
+            choose_sync_peer(&candidates, &quality, &no_stalled, &mut rng)
+        );
+
+        let stalled = HashSet::from([b]);
+        assert_eq!(
+            Some(c),
+            choose_sync_peer(&candidates, &quality, &stalled, &mut rng)
+        );
+
+        // Stalled peers are retried once no other peer is left.
+        let all_stalled = HashSet::from(candidates);
+        assert_eq!(
+            Some(b),
+            choose_sync_peer(&candidates, &quality, &all_stalled, &mut rng)
+        );
+        assert_eq!(None, choose_sync_peer(&[], &quality, &no_stalled, &mut rng));
+    }
+
+    #[test]
+    fn round_trip_time_breaks_ties() {
+        let mut rng = rand::rng();
+        let [a, b, c] = [0, 1, 2].map(get_dummy_socket_address);
+        let candidates = [a, b, c];
+
+        let mut quality = HashMap::<_, SyncPeerQuality>::new();
+        quality.entry(a).or_default().round_trip_time = Some(Duration::from_millis(300));
+        quality.entry(b).or_default().round_trip_time = Some(Duration::from_millis(20));
+
+        let no_stalled = HashSet::new();
+        assert_eq!(
+            Some(b),
+            choose_sync_peer(&candidates, &quality, &no_stalled, &mut rng)
+        );
+
+        // A served request outweighs a short round-trip time.
+        quality
+            .entry(c)
+            .or_default()
+            .record_served(Duration::from_secs(5));
+        assert_eq!(
+            Some(c),
+            choose_sync_peer(&candidates, &quality, &no_stalled, &mut rng)
+        );
+    }
+}
@@ -0,0 +1,135 @@
+//! Detection of a tip that stopped advancing while peers claim to hold better
+//! blocks.
+//!
+//! Sync mode fails over between peers one request at a time, and gives up
+//! once it makes no progress for a while. Neither helps if all peers that were
+//! asked keep failing, or if sync mode was abandoned while peers still claim
+//! more proof-of-work than the tip. The [`TipStallWatchdog`] catches both:
+//! once the tip has not advanced for [`TIP_STALL_TARGET_INTERVALS`] target
+//! block intervals while peers claim better blocks, the main loop rotates out
+//! the stalled sync peers, asks the remaining peers for their tips, and raises
+//! a [tip-stall alert](crate::state::alerts::AlertKind::TipStall).
+
+use std::time::Duration;
+use std::time::SystemTime;
+
+use tasm_lib::prelude::Digest;
+
+/// Number of target block intervals the tip may stand still while peers claim
+/// better blocks, before it is considered stalled.
+pub(super) const TIP_STALL_TARGET_INTERVALS: u32 = 5;
+
+/// A change in whether the tip is stalled, as reported by
+/// [`TipStallWatchdog::check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum TipStallTransition {
+    /// The tip has not advanced for the given time while peers claim better
+    /// blocks. Reported again after every further stall period, so that peers
+    /// are rotated until the tip advances.
+    Stalled(Duration),
+
+    /// The tip advanced, or no peer claims better blocks anymore.
+    Recovered,
+}
+
+/// Tracks when the tip last advanced.
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct TipStallWatchdog {
+    /// The tip, and when it was first observed.
+    tip: Option<(Digest, SystemTime)>,
+
+    /// When the stall was last reported, if the tip is stalled.
+    last_reported: Option<SystemTime>,
+}
+
+impl TipStallWatchdog {
+    /// Observe the tip, and whether peers claim blocks with more proof-of-work
+    /// than it. Returns the transition, if any.
+    pub(super) fn check(
+        &mut self,
+        tip: Digest,
+        peers_claim_better_blocks: bool,
+        stall_period: Duration,
+        now: SystemTime,
+    ) -> Option<TipStallTransition> {
+        let was_stalled = self.last_reported.is_some();
+        let since = match self.tip {
+            Some((observed_tip, since)) if observed_tip == tip => since,
+            _ => {
+                self.tip = Some((tip, now));
+                self.last_reported = None;
+                return was_stalled.then_some(TipStallTransition::Recovered);
+            }
+        };
+
+        if !peers_claim_better_blocks {
+            self.last_reported = None;
+            return was_stalled.then_some(TipStallTransition::Recovered);
+        }
+
+        let period_start = self.last_reported.unwrap_or(since);
+        if now.duration_since(period_start).unwrap_or_default() < stall_period {
+            return None;
+        }
+
+        self.last_reported = Some(now);
+        let stalled_for = now.duration_since(since).unwrap_or_default();
+        Some(TipStallTransition::Stalled(stalled_for))
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use rand::random;
+
+    use super::*;
+
+    #[test]
+    fn stall_is_reported_once_per_period_until_tip_advances() {
+        let period = Duration::from_secs(100);
+        let start = SystemTime::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let tip: Digest = random();
+        let mut watchdog = TipStallWatchdog::default();
+
+        assert_eq!(None, watchdog.check(tip, true, period, at(0)));
+        assert_eq!(None, watchdog.check(tip, true, period, at(99)));
+        assert_eq!(
+            Some(TipStallTransition::Stalled(Duration::from_secs(100))),
+            watchdog.check(tip, true, period, at(100))
+        );
+        assert_eq!(None, watchdog.check(tip, true, period, at(150)));
+        assert_eq!(
+            Some(TipStallTransition::Stalled(Duration::from_secs(200))),
+            watchdog.check(tip, true, period, at(200))
+        );
+
+        let next_tip: Digest = random();
+        assert_eq!(
+            Some(TipStallTransition::Recovered),
+            watchdog.check(next_tip, true, period, at(210))
+        );
+        assert_eq!(None, watchdog.check(next_tip, true, period, at(300)));
+    }
+
+    #[test]
+    fn tip_is_not_stalled_unless_peers_claim_better_blocks() {
+        let period = Duration::from_secs(100);
+        let start = SystemTime::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let tip: Digest = random();
+        let mut watchdog = TipStallWatchdog::default();
+
+        assert_eq!(None, watchdog.check(tip, false, period, at(0)));
+        assert_eq!(None, watchdog.check(tip, false, period, at(1000)));
+        assert_eq!(
+            Some(TipStallTransition::Stalled(Duration::from_secs(1001))),
+            watchdog.check(tip, true, period, at(1001))
+        );
+        assert_eq!(
+            Some(TipStallTransition::Recovered),
+            watchdog.check(tip, false, period, at(1002))
+        );
+    }
+}
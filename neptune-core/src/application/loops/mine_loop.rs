@@ -1,22 +1,35 @@
+pub(crate) mod block_template_check;
 pub mod coinbase_distribution;
 pub(crate) mod composer_parameters;
+pub(crate) mod nonce_partition;
+pub(crate) mod stale_block_check;
+
 use std::cmp::max;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
 
 use anyhow::bail;
+use anyhow::Context;
 use anyhow::Result;
 use block_header::BlockHeader;
+use block_template_check::check_block_template;
+use block_template_check::BlockTemplateError;
 use composer_parameters::ComposerParameters;
 use futures::channel::oneshot;
+use itertools::Itertools;
+use nonce_partition::NoncePartition;
 use num_traits::CheckedSub;
 use num_traits::Zero;
 use primitive_witness::PrimitiveWitness;
 use rand::rngs::StdRng;
 use rand::Rng;
 use rand::SeedableRng;
-use rayon::iter::ParallelIterator;
 use rayon::ThreadPoolBuilder;
+use stale_block_check::FoundBlock;
 use tasm_lib::twenty_first::tip5::digest::Digest;
 use tokio::select;
 use tokio::sync::mpsc;
@@ -31,9 +44,12 @@ use crate::api::tx_initiation::builder::transaction_builder::TransactionBuilder;
 use crate::api::tx_initiation::builder::transaction_proof_builder::TransactionProofBuilder;
 use crate::api::tx_initiation::builder::triton_vm_proof_job_options_builder::TritonVmProofJobOptionsBuilder;
 use crate::api::tx_initiation::error::CreateProofError;
+use crate::application::config::inclusion_policy::ExcludedCandidate;
+use crate::application::config::inclusion_policy::ExclusionReason;
 use crate::application::config::network::Network;
 use crate::application::config::tx_upgrade_filter::TxUpgradeFilter;
 use crate::application::job_queue::errors::JobHandleError;
+use crate::application::loops::channel::backpressure::BackpressureSender;
 use crate::application::loops::channel::*;
 use crate::application::loops::main_loop::proof_upgrader::UpgradeJob;
 use crate::application::triton_vm_job_queue::vm_job_queue;
@@ -41,7 +57,6 @@ use crate::application::triton_vm_job_queue::TritonVmJobPriority;
 use crate::application::triton_vm_job_queue::TritonVmJobQueue;
 use crate::protocol::consensus::block::block_header::BlockPow;
 use crate::protocol::consensus::block::block_height::BlockHeight;
-use crate::protocol::consensus::block::block_transaction::BlockOrRegularTransaction;
 use crate::protocol::consensus::block::block_transaction::BlockTransaction;
 use crate::protocol::consensus::block::difficulty_control::difficulty_control;
 use crate::protocol::consensus::block::pow::GuesserBuffer;
@@ -55,12 +70,21 @@ use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurr
 use crate::protocol::proof_abstractions::tasm::program::TritonVmProofJobOptions;
 use crate::protocol::proof_abstractions::timestamp::Timestamp;
 use crate::protocol::shared::SIZE_20MB_IN_BYTES;
+use crate::state::mining::composer_lease::ComposerCoordinator;
+use crate::state::mining::composer_lease::FileLeaseStore;
+use crate::state::mining::guesser_statistics::GuesserStatistics;
 use crate::state::transaction::transaction_details::TransactionDetails;
+use crate::state::transaction::transaction_kernel_id::TransactionKernelId;
+use crate::state::wallet::composer_payout::ProposedComposerPayout;
 use crate::state::wallet::expected_utxo::ExpectedUtxo;
 use crate::state::wallet::transaction_output::TxOutputList;
 use crate::state::GlobalStateLock;
 use crate::COMPOSITION_FAILED_EXIT_CODE;
 
+/// Number of nonces a guesser thread tries between checking whether it
+/// should stop, and updating its statistics.
+const NUM_GUESSES_PER_CHECK: u64 = 1 << 10;
+
 /// Information related to guessing.
 #[derive(Debug, Clone)]
 pub(crate) struct GuessingConfiguration {
@@ -68,6 +92,10 @@ pub(crate) struct GuessingConfiguration {
     pub(crate) address: ReceivingAddress,
     pub(crate) override_rng: Option<StdRng>,
     pub(crate) override_timestamp: Option<Timestamp>,
+
+    /// Where the guesser threads report their progress. Must be created for
+    /// the number of guesser threads used.
+    pub(crate) statistics: Option<Arc<GuesserStatistics>>,
 }
 
 /// Creates a block transaction and composes a block from it. Returns the block
@@ -181,6 +209,7 @@ fn guess_worker(
         address: guesser_address,
         override_rng: rng,
         override_timestamp: now,
+        statistics,
     } = guessing_configuration;
 
     let now = now.unwrap_or(Timestamp::now());
@@ -219,7 +248,10 @@ fn guess_worker(
 
     let prev_difficulty = previous_block_header.difficulty;
     let threshold = prev_difficulty.target();
-    let threads_to_use = num_guesser_threads.unwrap_or_else(rayon::current_num_threads);
+    let threads_to_use = statistics.as_ref().map_or_else(
+        || num_guesser_threads.unwrap_or_else(rayon::current_num_threads),
+        |statistics| statistics.num_threads(),
+    );
     let new_block_height = block.header().height;
     info!(
         "Guessing with {} threads on block {:x} of height {} with {} outputs and difficulty {}. Target: {threshold:x}",
@@ -256,32 +288,34 @@ fn guess_worker(
         .unwrap();
 
     let index_picker_preimage = guesser_buffer.index_picker_preimage(&mast_auth_paths);
-    let guess_result = pool.install(|| {
-        rayon::iter::repeat(0)
-            .map_init(
-                || rng.clone().unwrap_or(std_rng_from_thread_rng()),
-                |rng, _i| {
-                    guess_nonce_iteration(
-                        &guesser_buffer,
-                        &mast_auth_paths,
-                        index_picker_preimage,
-                        threshold,
-                        rng,
-                        &sender,
-                    )
-                },
-            )
-            .find_any(|r| !r.block_not_found())
-            .unwrap()
+    let base_nonce: Digest = rng.unwrap_or_else(std_rng_from_thread_rng).random();
+    let statistics = statistics.unwrap_or_else(|| Arc::new(GuesserStatistics::new(threads_to_use)));
+    let found = AtomicBool::new(false);
+    let guess_results = pool.broadcast(|context| {
+        if !statistics.claim_partition(context.index()) {
+            error!(
+                "Nonce partition {} is already being guessed on. Not guessing on it again.",
+                context.index()
+            );
+            return None;
+        }
+
+        let mut partition = NoncePartition::new(base_nonce, context.index(), context.num_threads());
+        guess_partition(
+            &guesser_buffer,
+            &mast_auth_paths,
+            index_picker_preimage,
+            threshold,
+            &mut partition,
+            &sender,
+            &found,
+            &statistics,
+        )
     });
 
-    let pow = match guess_result {
-        GuessNonceResult::Cancelled => {
-            info!("Stopping guessing task",);
-            return;
-        }
-        GuessNonceResult::NonceFound { pow } => pow,
-        GuessNonceResult::BlockNotFound => unreachable!(),
+    let Some(pow) = guess_results.into_iter().flatten().next() else {
+        info!("Stopping guessing task",);
+        return;
     };
 
     info!("Found valid block with nonce ({:x}).", pow.nonce);
@@ -317,14 +351,44 @@ Difficulty threshold: {threshold}
         .unwrap_or_else(|_| warn!("Receiver in mining loop closed prematurely"))
 }
 
-enum GuessNonceResult {
-    NonceFound { pow: Box<BlockPow> },
-    BlockNotFound,
-    Cancelled,
-}
-impl GuessNonceResult {
-    fn block_not_found(&self) -> bool {
-        matches!(self, Self::BlockNotFound)
+/// Guess the nonces of `partition` until a valid one is found, by this or
+/// another thread, or until guessing is cancelled.
+#[allow(clippy::too_many_arguments)]
+fn guess_partition(
+    guesser_buffer: &GuesserBuffer<{ BlockPow::MERKLE_TREE_HEIGHT }>,
+    mast_auth_paths: &PowMastPaths,
+    index_picker_preimage: Digest,
+    threshold: Digest,
+    partition: &mut NoncePartition,
+    sender: &oneshot::Sender<NewBlockFound>,
+    found: &AtomicBool,
+    statistics: &GuesserStatistics,
+) -> Option<Box<BlockPow>> {
+    let thread_index = partition.thread_index();
+    loop {
+        for i in 1..=NUM_GUESSES_PER_CHECK {
+            let result = guess_nonce_iteration(
+                guesser_buffer,
+                mast_auth_paths,
+                index_picker_preimage,
+                threshold,
+                partition.next_nonce(),
+            );
+            if let Some(pow) = result {
+                statistics.record_guesses(thread_index, i);
+                found.store(true, Ordering::Relaxed);
+                return Some(Box::new(pow));
+            }
+        }
+        statistics.record_guesses(thread_index, NUM_GUESSES_PER_CHECK);
+
+        if found.load(Ordering::Relaxed) {
+            return None;
+        }
+        if sender.is_canceled() {
+            debug!("Guesser was cancelled.");
+            return None;
+        }
     }
 }
 
@@ -335,29 +399,15 @@ fn guess_nonce_iteration(
     mast_auth_paths: &PowMastPaths,
     index_picker_preimage: Digest,
     threshold: Digest,
-    rng: &mut rand::rngs::StdRng,
-    sender: &oneshot::Sender<NewBlockFound>,
-) -> GuessNonceResult {
-    let nonce: Digest = rng.random();
-
-    // Check every N guesses if task has been cancelled.
-    if nonce.values()[0].raw_u64().trailing_zeros() >= 16 && sender.is_canceled() {
-        debug!("Guesser was cancelled.");
-        return GuessNonceResult::Cancelled;
-    }
-
-    let result = Pow::guess(
+    nonce: Digest,
+) -> Option<BlockPow> {
+    Pow::guess(
         guesser_buffer,
         mast_auth_paths,
         index_picker_preimage,
         nonce,
         threshold,
-    );
-
-    match result {
-        Some(pow) => GuessNonceResult::NonceFound { pow: Box::new(pow) },
-        None => GuessNonceResult::BlockNotFound,
-    }
+    )
 }
 
 /// Make a coinbase transaction rewarding the composer identified by receiving
@@ -512,128 +562,274 @@ pub(crate) async fn create_block_transaction_from(
     // A coinbase transaction implies mining. So you *must*
     // be able to create a SingleProof.
     let vm_job_queue = vm_job_queue();
-    let (coinbase_transaction, composer_txos) = make_coinbase_transaction_stateless(
+    let coinbase_transaction = make_coinbase_transaction_stateless(
         predecessor_block,
         composer_parameters.clone(),
         timestamp,
         vm_job_queue.clone(),
         job_options.clone(),
-    )
-    .await?;
-
-    // Get most valuable transactions from mempool.
-    let max_num_mergers = global_state_lock.cli().max_num_compose_mergers.get();
-    let mut transactions_to_merge = match &tx_merge_origin {
-        TxMergeOrigin::Mempool => global_state_lock
-            .lock_guard()
-            .await
-            .mempool
-            .get_transactions_for_block_composition(
-                block_capacity_for_transactions,
-                Some(max_num_mergers),
-            ),
-        #[cfg(test)]
-        TxMergeOrigin::ExplicitList(transactions) => transactions.to_owned(),
-    };
+    );
 
-    // If no updated single-proof transaction were found in the mempool, try
-    // to find one that's not updated, since updating this is faster than
-    // producing a new single proof-backed transaction.
     let proof_job_options = TritonVmProofJobOptionsBuilder::new()
         .template(&job_options)
         .proof_type(TransactionProofType::SingleProof)
         .build();
-    if transactions_to_merge.is_empty() && tx_merge_origin == TxMergeOrigin::Mempool {
-        info!("No synced single-proof tx found for merge looking for one to update");
-        let min_gobbling_fee = NativeCurrencyAmount::zero();
-        let update_job = global_state_lock
-            .lock_guard_mut()
-            .await
-            .preferred_update_job_from_mempool(min_gobbling_fee, TxUpgradeFilter::match_all())
-            .await;
-        let update_job = update_job.map(UpgradeJob::UpdateMutatorSetData);
-        if let Some(update_job) = update_job {
-            let wallet_entropy = global_state_lock
+    let transaction_to_include = async {
+        // Get most valuable transactions from mempool.
+        let max_num_mergers = global_state_lock.cli().max_num_compose_mergers.get();
+        let mut transactions_to_merge = match &tx_merge_origin {
+            TxMergeOrigin::Mempool => {
+                let selection = {
+                    let state = global_state_lock.lock_guard().await;
+                    state.mempool.select_for_block_composition(
+                        block_capacity_for_transactions,
+                        Some(max_num_mergers),
+                        &state.inclusion_policy,
+                    )
+                };
+                let (transactions, excluded) = selection.read().await;
+                record_excluded_candidates(&global_state_lock, excluded, timestamp).await;
+                transactions
+            }
+            #[cfg(test)]
+            TxMergeOrigin::ExplicitList(transactions) => transactions.to_owned(),
+        };
+
+        // If no updated single-proof transaction were found in the mempool, try
+        // to find one that's not updated, since updating this is faster than
+        // producing a new single proof-backed transaction.
+        if transactions_to_merge.is_empty() && tx_merge_origin == TxMergeOrigin::Mempool {
+            info!("No synced single-proof tx found for merge looking for one to update");
+            let min_gobbling_fee = NativeCurrencyAmount::zero();
+            let update_job = global_state_lock
                 .lock_guard_mut()
                 .await
-                .wallet_state
-                .wallet_entropy
-                .clone();
-            let notification_policy = global_state_lock.cli().fee_notification;
-            if let Ok((updated_tx, _)) = update_job
-                .upgrade(
-                    vm_job_queue.clone(),
-                    proof_job_options.clone(),
-                    &wallet_entropy,
-                    block_height,
-                    notification_policy,
-                )
-                .await
-            {
-                info!("Successfully updated transaction for merge");
-                transactions_to_merge = vec![updated_tx];
+                .preferred_update_job_from_mempool(min_gobbling_fee, TxUpgradeFilter::match_all())
+                .await;
+            let exclusion = match &update_job {
+                Some(job) => global_state_lock
+                    .lock_guard()
+                    .await
+                    .inclusion_policy
+                    .check(job.old_kernel())
+                    .err()
+                    .map(|reason| (job.old_kernel().txid(), reason)),
+                None => None,
+            };
+            let update_job = match exclusion {
+                Some(excluded) => {
+                    record_excluded_candidates(&global_state_lock, vec![excluded], timestamp).await;
+                    None
+                }
+                None => update_job,
+            };
+            let update_job = update_job.map(UpgradeJob::UpdateMutatorSetData);
+            if let Some(update_job) = update_job {
+                let wallet_entropy = global_state_lock
+                    .lock_guard_mut()
+                    .await
+                    .wallet_state
+                    .wallet_entropy
+                    .clone();
+                let notification_policy = global_state_lock.cli().fee_notification;
+                if let Ok((updated_tx, _)) = update_job
+                    .upgrade(
+                        vm_job_queue.clone(),
+                        proof_job_options.clone(),
+                        &wallet_entropy,
+                        block_height,
+                        notification_policy,
+                    )
+                    .await
+                {
+                    info!("Successfully updated transaction for merge");
+                    transactions_to_merge = vec![updated_tx];
+                }
+            } else {
+                info!("No suitable transaction found for updating.");
             }
-        } else {
-            info!("No suitable transaction found for updating.");
         }
-    }
 
-    // If necessary, populate list with nop-tx.
-    // Guarantees that some merge happens in below loop, which sets merge-bit.
-    if transactions_to_merge.is_empty() {
-        info!("Creating nop transaction to set merge bit through a merge");
-        let nop = TransactionDetails::nop(
-            predecessor_block_ms,
-            timestamp,
-            global_state_lock.cli().network,
-        );
-        let nop = PrimitiveWitness::from_transaction_details(&nop);
-
-        let proof = TransactionProofBuilder::new()
-            .consensus_rule_set(consensus_rule_set)
-            .primitive_witness_ref(&nop)
-            .job_queue(vm_job_queue.clone())
-            .proof_job_options(proof_job_options)
-            .build()
-            .await?;
-        let nop = Transaction {
-            kernel: nop.kernel,
-            proof,
-        };
+        // If necessary, populate list with nop-tx.
+        // Guarantees that the merge with the coinbase transaction happens,
+        // which sets merge-bit.
+        if transactions_to_merge.is_empty() {
+            info!("Creating nop transaction to set merge bit through a merge");
+            let nop = TransactionDetails::nop(
+                predecessor_block_ms,
+                timestamp,
+                global_state_lock.cli().network,
+            );
+            let nop = PrimitiveWitness::from_transaction_details(&nop);
+
+            let proof = TransactionProofBuilder::new()
+                .consensus_rule_set(consensus_rule_set)
+                .primitive_witness_ref(&nop)
+                .job_queue(vm_job_queue.clone())
+                .proof_job_options(proof_job_options)
+                .build()
+                .await?;
+            let nop = Transaction {
+                kernel: nop.kernel,
+                proof,
+            };
 
-        transactions_to_merge = vec![nop];
-    }
+            transactions_to_merge = vec![nop];
+        }
 
-    let num_merges = transactions_to_merge.len();
-    let mut block_transaction = BlockOrRegularTransaction::from(coinbase_transaction);
-    for (i, tx_to_include) in transactions_to_merge.into_iter().enumerate() {
-        info!("Merging transaction {} / {}", i + 1, num_merges);
-        info!(
-            "Merging tx with {} inputs, {} outputs. With fee {}.",
-            tx_to_include.kernel.inputs.len(),
-            tx_to_include.kernel.outputs.len(),
-            tx_to_include.kernel.fee
-        );
-        block_transaction = BlockTransaction::merge(
-            block_transaction,
-            tx_to_include,
-            rng.random(),
+        merge_transactions_pairwise(
+            transactions_to_merge,
+            &mut rng,
             vm_job_queue.clone(),
-            job_options.clone(),
+            &job_options,
             consensus_rule_set,
         )
-        .await?
-        .into(); // fix #579.  propagate error up.
+        .await
+    };
+
+    // The coinbase transaction and the transaction it is merged with do not
+    // depend on each other, so their proofs are produced simultaneously if
+    // the job queue has the workers for it.
+    let ((coinbase_transaction, composer_txos), tx_to_include) =
+        tokio::try_join!(coinbase_transaction, transaction_to_include)?;
+
+    info!(
+        "Merging tx with {} inputs, {} outputs. With fee {}.",
+        tx_to_include.kernel.inputs.len(),
+        tx_to_include.kernel.outputs.len(),
+        tx_to_include.kernel.fee
+    );
+    let included_fee = tx_to_include.kernel.fee;
+    let block_transaction = BlockTransaction::merge(
+        coinbase_transaction.into(),
+        tx_to_include,
+        rng.random(),
+        vm_job_queue,
+        job_options,
+        consensus_rule_set,
+    )
+    .await?; // fix #579.  propagate error up.
+
+    check_block_template(
+        block_height,
+        &block_transaction.kernel,
+        composer_txos.total_native_coins(),
+        included_fee,
+        composer_parameters.guesser_fee_fraction(),
+    )?;
+
+    // Off-chain payouts are recorded before the proposal can become a block,
+    // since the recipients cannot claim them otherwise.
+    let foreign_payouts = composer_parameters
+        .foreign_offchain_payouts(&composer_txos)
+        .into_iter()
+        .map(|utxo_triple| ProposedComposerPayout {
+            block_height,
+            utxo_triple,
+        })
+        .collect_vec();
+    if !foreign_payouts.is_empty() {
+        let payouts_file = global_state_lock
+            .lock_guard()
+            .await
+            .wallet_state
+            .configuration
+            .data_directory()
+            .composer_payouts_file_path();
+        ProposedComposerPayout::record_all(&foreign_payouts, &payouts_file)
+            .await
+            .with_context(|| {
+                format!(
+                    "Could not record composer payouts to {}",
+                    payouts_file.display()
+                )
+            })?;
     }
 
     let own_expected_utxos = composer_parameters.extract_expected_utxos(composer_txos);
 
-    Ok((
-        block_transaction
-            .try_into()
-            .expect("Must have merged at least once"),
-        own_expected_utxos,
-    ))
+    Ok((block_transaction, own_expected_utxos))
+}
+
+/// Merge transactions pairwise, level by level, into a single transaction.
+///
+/// The merges of a level do not depend on each other, so their proofs are
+/// produced simultaneously if the job queue has the workers for it.
+async fn merge_transactions_pairwise(
+    mut transactions: Vec<Transaction>,
+    rng: &mut StdRng,
+    vm_job_queue: Arc<TritonVmJobQueue>,
+    job_options: &TritonVmProofJobOptions,
+    consensus_rule_set: ConsensusRuleSet,
+) -> Result<Transaction> {
+    let mut level = 1;
+    while transactions.len() > 1 {
+        let num_merges = transactions.len() / 2;
+        info!("Merging {num_merges} pair(s) of transactions at level {level}");
+
+        let mut merges = vec![];
+        let mut unpaired = None;
+        let mut transactions_iter = transactions.into_iter();
+        while let Some(left) = transactions_iter.next() {
+            let Some(right) = transactions_iter.next() else {
+                unpaired = Some(left);
+                break;
+            };
+            info!(
+                "Merging tx with {} inputs, {} outputs. With fee {}.",
+                right.kernel.inputs.len(),
+                right.kernel.outputs.len(),
+                right.kernel.fee
+            );
+            merges.push(left.merge_with(
+                right,
+                rng.random(),
+                vm_job_queue.clone(),
+                job_options.clone(),
+                consensus_rule_set,
+            ));
+        }
+
+        transactions = futures::future::try_join_all(merges).await?;
+        transactions.extend(unpaired);
+        level += 1;
+    }
+
+    transactions
+        .pop()
+        .ok_or_else(|| anyhow::anyhow!("no transactions to merge"))
+}
+
+/// Record the transactions that the inclusion policy excluded from a block
+/// proposal composed at `timestamp`, in the composer inclusion audit file.
+///
+/// Locking:
+///   * acquires `global_state_lock` for read
+async fn record_excluded_candidates(
+    global_state_lock: &GlobalStateLock,
+    excluded: Vec<(TransactionKernelId, ExclusionReason)>,
+    timestamp: Timestamp,
+) {
+    if excluded.is_empty() {
+        return;
+    }
+
+    let audit_file = global_state_lock
+        .lock_guard()
+        .await
+        .wallet_state
+        .configuration
+        .data_directory()
+        .composer_inclusion_audit_file_path();
+    for (txid, reason) in excluded {
+        ExcludedCandidate {
+            txid,
+            reason,
+            timestamp,
+        }
+        .record(&audit_file)
+        .await;
+    }
 }
 
 ///
@@ -641,8 +837,8 @@ pub(crate) async fn create_block_transaction_from(
 /// Locking:
 ///   * acquires `global_state_lock` for write
 pub(crate) async fn mine(
-    mut from_main: mpsc::Receiver<MainToMiner>,
-    to_main: mpsc::Sender<MinerToMain>,
+    from_main: &mut mpsc::Receiver<MainToMiner>,
+    to_main: BackpressureSender<MinerToMain>,
     mut global_state_lock: GlobalStateLock,
 ) -> Result<()> {
     // Set PoW guessing to restart every N seconds, if it has been started. Only
@@ -678,6 +874,47 @@ pub(crate) async fn mine(
     let guess_restart_timer = time::sleep(infinite);
     tokio::pin!(guess_restart_timer);
 
+    // Own block proposals that have not resulted in a block within this
+    // horizon, counted from the first own proposal for the current tip, are
+    // considered expired. This can happen if guessers withhold the solution.
+    let proposal_expiry_horizon = Duration::from_millis(
+        network.target_block_interval().to_millis()
+            * u64::from(cli_args.proposal_expiry_horizon.get()),
+    );
+    let proposal_expiry_timer = time::sleep(infinite);
+    tokio::pin!(proposal_expiry_timer);
+    let mut expiring_proposal: Option<Digest> = None;
+
+    // Composition is suspended after too many reorganizations orphaned the
+    // block that was composed on.
+    let reorg_cooldown_timer = time::sleep(infinite);
+    tokio::pin!(reorg_cooldown_timer);
+    let mut composed_on: Option<Digest> = None;
+
+    // Composers of a cluster agree on which of them composes on the tip. The
+    // others stand by until the lease of the composing node expires.
+    let mut composer_coordinator = match &cli_args.composer_lease_dir {
+        Some(directory) => {
+            tokio::fs::create_dir_all(directory)
+                .await
+                .with_context(|| {
+                    format!(
+                        "Could not create composer lease directory {}",
+                        directory.display()
+                    )
+                })?;
+            let instance_id = global_state_lock.lock(|s| s.net.instance_id).await;
+            Some(ComposerCoordinator::new(
+                Arc::new(FileLeaseStore::new(directory.clone())),
+                instance_id,
+                cli_args.composer_lease_duration,
+            ))
+        }
+        None => None,
+    };
+    let composer_standby_timer = time::sleep(infinite);
+    tokio::pin!(composer_standby_timer);
+
     let mut pause_mine = false;
     let mut wait_for_confirmation = false;
     loop {
@@ -729,6 +966,16 @@ pub(crate) async fn mine(
             global_state_lock
                 .set_mining_status_to_guessing(&proposal)
                 .await;
+            let guesser_statistics = Arc::new(GuesserStatistics::new(
+                cli_args
+                    .guesser_threads
+                    .unwrap_or_else(rayon::current_num_threads),
+            ));
+            global_state_lock
+                .lock_guard_mut()
+                .await
+                .mining_state
+                .guesser_statistics = Some(guesser_statistics.clone());
 
             let guesser_key = global_state_lock
                 .lock_guard()
@@ -750,6 +997,7 @@ pub(crate) async fn mine(
                     address: guesser_key.to_address().into(),
                     override_rng: None,
                     override_timestamp: None,
+                    statistics: Some(guesser_statistics),
                 },
             );
 
@@ -767,18 +1015,70 @@ pub(crate) async fn mine(
         let (cancel_compose_tx, cancel_compose_rx) = tokio::sync::watch::channel(());
 
         let compose = cli_args.compose;
-        let mut composer_task = if !wait_for_confirmation
+        let may_compose = !wait_for_confirmation
             && compose
             && guesser_task.is_none()
             && !is_syncing
             && !pause_mine
-            && is_connected
-        {
+            && is_connected;
+        let compose_suspended_until = if may_compose {
+            global_state_lock
+                .lock_guard()
+                .await
+                .mining_state
+                .reorg_cooldown
+                .suspended_until(Instant::now())
+        } else {
+            None
+        };
+        if let Some(resume) = compose_suspended_until {
+            info!("Too many reorganizations orphaned composed blocks. Suspending composition.");
+            reorg_cooldown_timer
+                .as_mut()
+                .reset(time::Instant::from_std(resume));
+        }
+
+        let mut composer_standby = None;
+        if let Some(coordinator) = composer_coordinator.as_mut() {
+            if may_compose && compose_suspended_until.is_none() {
+                let tip_digest = global_state_lock
+                    .lock(|s| s.chain.light_state().hash())
+                    .await;
+                composer_standby = coordinator.claim(tip_digest).await;
+            } else {
+                // A composer that does not compose must not keep the
+                // standbys from composing.
+                coordinator.release().await;
+            }
+            global_state_lock
+                .lock_guard_mut()
+                .await
+                .mining_state
+                .composer_standby = composer_standby;
+        }
+        if let Some(lease) = composer_standby {
+            info!(
+                "Composer {:x} holds the lease on the tip. Standing by.",
+                lease.holder
+            );
+            let remaining = lease
+                .expires
+                .duration_since(SystemTime::now())
+                .unwrap_or_default();
+            composer_standby_timer
+                .as_mut()
+                .reset(tokio::time::Instant::now() + remaining);
+        }
+
+        let is_composing =
+            may_compose && compose_suspended_until.is_none() && composer_standby.is_none();
+        let mut composer_task = if is_composing {
             global_state_lock.set_mining_status_to_composing().await;
 
             let latest_block = global_state_lock
                 .lock(|s| s.chain.light_state().to_owned())
                 .await;
+            composed_on = Some(latest_block.hash());
             let compose_task = compose_block(
                 latest_block,
                 global_state_lock.clone(),
@@ -803,38 +1103,82 @@ pub(crate) async fn mine(
             _ = &mut guess_restart_timer => {
                 restart_guessing = true;
             }
+
+            _ = &mut reorg_cooldown_timer, if compose_suspended_until.is_some() => {
+                info!("Reorganization cooldown is over. Resuming composition.");
+            }
+
+            _ = &mut composer_standby_timer, if composer_standby.is_some() => {
+                debug!("Composer lease on the tip may have expired. Trying to take over.");
+            }
+
+            // Two composers must not prove on the same parent, so stop once
+            // another composer took over the lease, and stand by for it.
+            lost_lease = async {
+                match composer_coordinator.as_mut() {
+                    Some(coordinator) => coordinator.lease_lost().await,
+                    None => std::future::pending().await,
+                }
+            }, if is_composing => {
+                warn!("Composer {:x} took over the lease on the tip. Cancelling composition.", lost_lease.holder);
+                stop_composing = true;
+            }
+
+            // Don't interrupt composition, as that would waste the proving
+            // effort. The timer is checked again once composition completes.
+            _ = &mut proposal_expiry_timer, if expiring_proposal.is_some() && !is_composing => {
+                if let Some(proposal_digest) = expiring_proposal {
+                    debug!("Own block proposal expired. Notifying main loop.");
+                    to_main.send(MinerToMain::ProposalExpired(proposal_digest)).await?;
+                }
+
+                // Alert again if the next horizon also passes without a block.
+                proposal_expiry_timer
+                    .as_mut()
+                    .reset(tokio::time::Instant::now() + proposal_expiry_horizon);
+
+                // Guessing restarts with updated parameters.
+                restart_guessing = true;
+            }
             Ok(Err(e)) = &mut composer_task => {
 
-                match e.root_cause().downcast_ref::<CreateProofError>() {
-                    // address issue 579.
-                    //
-                    // check if error indicates job was cancelled. If so,
-                    // simply log and continue, but ignore the error.
-                    //
-                    // this is a fail-safe and appears unreachable for present
-                    // codebase during normal mining-loop operation.
-                    //
-                    // job cancellation can occur any time that the cancellation
-                    // channel Sender gets dropped, which occurs if
-                    // composer_task gets aborted which occurs if any other
-                    // branch of this select!{} resolves first.  Common causes
-                    // are NewBlock and NewBlockProposal messages from main.
-                    //
-                    // HOWEVER: if the composer_task is aborted because another
-                    // branch of the select resolves first then this branch
-                    // should not execute making this check unnecessary.
-                    //
-                    // The remaining sources of cancellation are:
-                    // 1. mining loop exits, eg during graceful shutdown.
-                    // 2. some future change to codebase
-                    Some(CreateProofError::JobHandleError(JobHandleError::JobCancelled)) => {
-                        debug!("composer job was cancelled. continuing normal operation");
-                    }
-                    _ => {
-                        // Ensure graceful shutdown in case of error during composition.
-                        stop_composing = true;
-                        error!("Composition failed: {}", e);
-                        to_main.send(MinerToMain::Shutdown(COMPOSITION_FAILED_EXIT_CODE)).await?;
+                // A defective block template is not worth proving, and is
+                // likely to recur, so stop composing without shutting down.
+                if let Some(template_error) = e.downcast_ref::<BlockTemplateError>() {
+                    stop_composing = true;
+                    error!("Aborted composition of defective block template: {template_error}");
+                } else {
+                    match e.root_cause().downcast_ref::<CreateProofError>() {
+                        // address issue 579.
+                        //
+                        // check if error indicates job was cancelled. If so,
+                        // simply log and continue, but ignore the error.
+                        //
+                        // this is a fail-safe and appears unreachable for present
+                        // codebase during normal mining-loop operation.
+                        //
+                        // job cancellation can occur any time that the cancellation
+                        // channel Sender gets dropped, which occurs if
+                        // composer_task gets aborted which occurs if any other
+                        // branch of this select!{} resolves first.  Common causes
+                        // are NewBlock and NewBlockProposal messages from main.
+                        //
+                        // HOWEVER: if the composer_task is aborted because another
+                        // branch of the select resolves first then this branch
+                        // should not execute making this check unnecessary.
+                        //
+                        // The remaining sources of cancellation are:
+                        // 1. mining loop exits, eg during graceful shutdown.
+                        // 2. some future change to codebase
+                        Some(CreateProofError::JobHandleError(JobHandleError::JobCancelled)) => {
+                            debug!("composer job was cancelled. continuing normal operation");
+                        }
+                        _ => {
+                            // Ensure graceful shutdown in case of error during composition.
+                            stop_composing = true;
+                            error!("Composition failed: {}", e);
+                            to_main.send(MinerToMain::Shutdown(COMPOSITION_FAILED_EXIT_CODE)).await?;
+                        }
                     }
                 }
             },
@@ -854,7 +1198,31 @@ pub(crate) async fn mine(
                         stop_guessing = true;
                         stop_composing = true;
 
+                        expiring_proposal = None;
+                        proposal_expiry_timer
+                            .as_mut()
+                            .reset(tokio::time::Instant::now() + infinite);
+
                         info!("Miner task received notification about new block");
+
+                        if let Some(predecessor) = composed_on.take() {
+                            let orphaned = !global_state_lock
+                                .lock_guard()
+                                .await
+                                .chain
+                                .archival_state()
+                                .block_belongs_to_canonical_chain(predecessor)
+                                .await;
+                            if orphaned {
+                                info!("Reorganization orphaned the block composed on.");
+                                global_state_lock
+                                    .lock_guard_mut()
+                                    .await
+                                    .mining_state
+                                    .reorg_cooldown
+                                    .record_reorg(Instant::now());
+                            }
+                        }
                     }
                     MainToMiner::NewBlockProposal => {
                         stop_guessing = true;
@@ -901,6 +1269,16 @@ pub(crate) async fn mine(
 
                 match new_composition {
                     Ok((new_block_proposal, composer_utxos)) => {
+                        // The horizon starts with the first own proposal
+                        // for the current tip, such that repeated
+                        // compositions do not postpone expiry.
+                        if expiring_proposal.is_none() {
+                            proposal_expiry_timer
+                                .as_mut()
+                                .reset(tokio::time::Instant::now() + proposal_expiry_horizon);
+                        }
+                        expiring_proposal = Some(new_block_proposal.hash());
+
                         to_main.send(MinerToMain::BlockProposal(Box::new((new_block_proposal, composer_utxos)))).await?;
                         wait_for_confirmation = true;
                     },
@@ -924,19 +1302,74 @@ pub(crate) async fn mine(
                             .lock(|s| s.chain.light_state().to_owned())
                             .await;
 
-                        if !new_block_found.block.has_proof_of_work(cli_args.network, latest_block.header()) {
-                            error!("Own mined block did not have valid PoW Discarding.");
-                        } else if !new_block_found.block.is_valid(&latest_block, Timestamp::now(), global_state_lock.cli().network).await {
+                        // The tip can move on while guessing, in which case
+                        // the solution is worthless unless it outweighs the
+                        // tip. Guessing restarts on the proposal for the
+                        // current tip.
+                        let found_header = *new_block_found.block.header();
+                        let parent_is_canonical = global_state_lock
+                            .lock_guard()
+                            .await
+                            .chain
+                            .archival_state()
+                            .block_belongs_to_canonical_chain(found_header.prev_block_digest)
+                            .await;
+                        let found_block = stale_block_check::check_found_block_against_tip(
+                            &found_header,
+                            &latest_block,
+                            parent_is_canonical,
+                        );
+
+                        // A block that outweighs the tip is validated against
+                        // its own parent.
+                        let parent = match found_block {
+                            Ok(FoundBlock::ExtendsTip) => Some(latest_block),
+                            Ok(FoundBlock::OutweighsTip) => {
+                                info!("Own found block of height {} outweighs the tip.", found_header.height);
+                                let parent = global_state_lock
+                                    .lock_guard()
+                                    .await
+                                    .chain
+                                    .archival_state()
+                                    .get_block(found_header.prev_block_digest)
+                                    .await;
+                                match parent {
+                                    Ok(Some(parent)) => Some(parent),
+                                    Ok(None) => {
+                                        warn!("Discarding own found block of height {}: parent is unknown", found_header.height);
+                                        None
+                                    }
+                                    Err(e) => {
+                                        warn!("Discarding own found block of height {}: could not load parent: {e}", found_header.height);
+                                        None
+                                    }
+                                }
+                            }
+                            Err(stale) => {
+                                warn!("Discarding own found block of height {}: {stale}", found_header.height);
+                                None
+                            }
+                        };
+
+                        if let Some(parent) = parent {
+                            if !new_block_found.block.has_proof_of_work(cli_args.network, parent.header()) {
+                                error!("Own mined block did not have valid PoW Discarding.");
+                            } else if !new_block_found.block.is_valid(&parent, Timestamp::now(), global_state_lock.cli().network).await {
                                 // Block could be invalid if for instance the proof and proof-of-work
                                 // took less time than the minimum block time.
                                 error!("Found block with valid proof-of-work but block is invalid.");
-                        } else {
+                            } else {
+                                info!("Found new {} block with block height {}. Hash: {:x}", global_state_lock.cli().network, new_block_found.block.kernel.header.height, new_block_found.block.hash());
 
-                            info!("Found new {} block with block height {}. Hash: {:x}", global_state_lock.cli().network, new_block_found.block.kernel.header.height, new_block_found.block.hash());
+                                to_main.send(MinerToMain::NewBlockFound(new_block_found)).await?;
 
-                            to_main.send(MinerToMain::NewBlockFound(new_block_found)).await?;
+                                expiring_proposal = None;
+                                proposal_expiry_timer
+                                    .as_mut()
+                                    .reset(tokio::time::Instant::now() + infinite);
 
-                            wait_for_confirmation = true;
+                                wait_for_confirmation = true;
+                            }
                         }
                     },
                 };
@@ -993,6 +1426,7 @@ pub(crate) mod tests {
     use num_traits::Pow;
     use num_traits::Zero;
     use rand::RngCore;
+    use rayon::iter::ParallelIterator;
     use tracing_test::traced_test;
 
     use super::*;
@@ -1124,8 +1558,7 @@ pub(crate) mod tests {
                         &mast_auth_paths,
                         index_picker_preimage,
                         threshold,
-                        prng,
-                        &worker_task_tx,
+                        prng.random(),
                     );
                 })
                 .count();
@@ -1617,6 +2050,7 @@ pub(crate) mod tests {
                 address: guesser_key.to_address().into(),
                 override_rng: None,
                 override_timestamp: None,
+                statistics: None,
             },
             None,
         );
@@ -1701,6 +2135,7 @@ pub(crate) mod tests {
                 address: guesser_key.to_address().into(),
                 override_rng: None,
                 override_timestamp: None,
+                statistics: None,
             },
             None,
         );
@@ -1853,6 +2288,7 @@ pub(crate) mod tests {
                     address: guesser_key.to_address().into(),
                     override_rng: None,
                     override_timestamp: None,
+                    statistics: None,
                 },
                 Some(target_block_interval),
             );
@@ -2309,15 +2745,14 @@ pub(crate) mod tests {
 
         let (miner_to_main_tx, _miner_to_main_rx) =
             mpsc::channel::<MinerToMain>(MINER_CHANNEL_CAPACITY);
-        let (main_to_miner_tx, main_to_miner_rx) =
+        let miner_to_main_tx = BackpressureSender::blocking("miner to main", miner_to_main_tx);
+        let (main_to_miner_tx, mut main_to_miner_rx) =
             mpsc::channel::<MainToMiner>(MINER_CHANNEL_CAPACITY);
 
         // create a task that for the mining-loop
-        let mine_task = mine(
-            main_to_miner_rx,
-            miner_to_main_tx,
-            global_state_lock.clone(),
-        );
+        let miner_state_lock = global_state_lock.clone();
+        let mine_task =
+            async move { mine(&mut main_to_miner_rx, miner_to_main_tx, miner_state_lock).await };
 
         // spawn the mining-loop task.
         let jh = tokio::task::spawn(mine_task);
@@ -2492,6 +2927,7 @@ pub(crate) mod tests {
                     address: guesser_key.to_address().into(),
                     override_rng: None,
                     override_timestamp: Some(block_time),
+                    statistics: None,
                 },
                 None,
             );
@@ -0,0 +1,193 @@
+use num_traits::CheckedSub;
+
+use crate::api::export::NativeCurrencyAmount;
+use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::protocol::consensus::block::Block;
+use crate::protocol::consensus::transaction::transaction_kernel::TransactionKernel;
+
+/// Ways in which a block template can deviate from what the consensus rules
+/// allow and the operator configured.
+///
+/// A template with any of these defects would either produce an invalid block
+/// or pay less than it should, so composition is aborted before the block
+/// proof is produced.
+#[derive(Debug, Clone, Copy, PartialEq, thiserror::Error)]
+pub(crate) enum BlockTemplateError {
+    #[error("block template for height {block_height} has no coinbase")]
+    MissingCoinbase { block_height: BlockHeight },
+
+    #[error(
+        "block template for height {block_height} has coinbase {actual}, \
+         but the block subsidy is {expected}"
+    )]
+    CoinbaseMismatch {
+        block_height: BlockHeight,
+        expected: NativeCurrencyAmount,
+        actual: NativeCurrencyAmount,
+    },
+
+    #[error("block template has negative fee {fee}")]
+    NegativeFee { fee: NativeCurrencyAmount },
+
+    #[error(
+        "composer outputs of block template pay {actual}, but guesser fraction \
+         {guesser_fee_fraction} leaves {expected} to the composer"
+    )]
+    ComposerRewardMismatch {
+        guesser_fee_fraction: f64,
+        expected: NativeCurrencyAmount,
+        actual: NativeCurrencyAmount,
+    },
+
+    #[error(
+        "block template pays {actual} to the guesser, but the coinbase \
+         remainder plus the fees of included transactions is {expected}"
+    )]
+    GuesserRewardMismatch {
+        expected: NativeCurrencyAmount,
+        actual: NativeCurrencyAmount,
+    },
+}
+
+/// Check the block transaction of a block template against the consensus rules
+/// and the operator's configuration, before spending a proving run on it.
+///
+/// Verifies that
+///  - the coinbase equals the block subsidy for `block_height`,
+///  - the composer outputs pay exactly the composer's share of the subsidy, as
+///    determined by `guesser_fee_fraction`, and
+///  - the fee of the block transaction, which is the guesser's reward, is the
+///    remainder of the subsidy plus `included_fee`, the fees of the
+///    transactions merged into the template.
+pub(crate) fn check_block_template(
+    block_height: BlockHeight,
+    block_transaction_kernel: &TransactionKernel,
+    composer_reward: NativeCurrencyAmount,
+    included_fee: NativeCurrencyAmount,
+    guesser_fee_fraction: f64,
+) -> Result<(), BlockTemplateError> {
+    let block_subsidy = Block::block_subsidy(block_height);
+    let Some(coinbase) = block_transaction_kernel.coinbase else {
+        return Err(BlockTemplateError::MissingCoinbase { block_height });
+    };
+    if coinbase != block_subsidy {
+        return Err(BlockTemplateError::CoinbaseMismatch {
+            block_height,
+            expected: block_subsidy,
+            actual: coinbase,
+        });
+    }
+
+    for fee in [included_fee, block_transaction_kernel.fee] {
+        if fee.is_negative() {
+            return Err(BlockTemplateError::NegativeFee { fee });
+        }
+    }
+
+    // Mirrors the computation in `ComposerParameters::tx_outputs`.
+    let guesser_share = block_subsidy.lossy_f64_fraction_mul(guesser_fee_fraction);
+    let expected_composer_reward = block_subsidy
+        .checked_sub(&guesser_share)
+        .unwrap_or_default();
+    if composer_reward != expected_composer_reward {
+        return Err(BlockTemplateError::ComposerRewardMismatch {
+            guesser_fee_fraction,
+            expected: expected_composer_reward,
+            actual: composer_reward,
+        });
+    }
+
+    let expected_guesser_reward = guesser_share + included_fee;
+    if block_transaction_kernel.fee != expected_guesser_reward {
+        return Err(BlockTemplateError::GuesserRewardMismatch {
+            expected: expected_guesser_reward,
+            actual: block_transaction_kernel.fee,
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use tasm_lib::prelude::Digest;
+
+    use super::*;
+    use crate::protocol::consensus::transaction::transaction_kernel::TransactionKernelProxy;
+    use crate::protocol::proof_abstractions::timestamp::Timestamp;
+
+    fn kernel(
+        coinbase: Option<NativeCurrencyAmount>,
+        fee: NativeCurrencyAmount,
+    ) -> TransactionKernel {
+        TransactionKernelProxy {
+            inputs: vec![],
+            outputs: vec![],
+            announcements: vec![],
+            fee,
+            coinbase,
+            timestamp: Timestamp::now(),
+            mutator_set_hash: Digest::default(),
+            merge_bit: true,
+        }
+        .into_kernel()
+    }
+
+    #[test]
+    fn consistent_template_passes_and_deviations_are_caught() {
+        let height = BlockHeight::from(10u64);
+        let subsidy = Block::block_subsidy(height);
+        let guesser_share = subsidy.lossy_f64_fraction_mul(0.25);
+        let composer_reward = subsidy.checked_sub(&guesser_share).unwrap();
+        let included_fee = NativeCurrencyAmount::coins(2);
+        let guesser_reward = guesser_share + included_fee;
+
+        let good = kernel(Some(subsidy), guesser_reward);
+        assert_eq!(
+            Ok(()),
+            check_block_template(height, &good, composer_reward, included_fee, 0.25)
+        );
+
+        assert!(matches!(
+            check_block_template(
+                height,
+                &kernel(None, guesser_reward),
+                composer_reward,
+                included_fee,
+                0.25
+            ),
+            Err(BlockTemplateError::MissingCoinbase { .. })
+        ));
+
+        let short_coinbase = subsidy
+            .checked_sub(&NativeCurrencyAmount::coins(1))
+            .unwrap();
+        assert!(matches!(
+            check_block_template(
+                height,
+                &kernel(Some(short_coinbase), guesser_reward),
+                composer_reward,
+                included_fee,
+                0.25
+            ),
+            Err(BlockTemplateError::CoinbaseMismatch { .. })
+        ));
+
+        assert!(matches!(
+            check_block_template(height, &good, composer_reward, included_fee, 0.5),
+            Err(BlockTemplateError::ComposerRewardMismatch { .. })
+        ));
+
+        assert!(matches!(
+            check_block_template(
+                height,
+                &kernel(Some(subsidy), guesser_share),
+                composer_reward,
+                included_fee,
+                0.25
+            ),
+            Err(BlockTemplateError::GuesserRewardMismatch { .. })
+        ));
+    }
+}
@@ -7,6 +7,7 @@ use crate::api::export::Timestamp;
 use crate::application::config::fee_notification_policy::FeeNotificationPolicy;
 use crate::application::loops::mine_loop::coinbase_distribution::CoinbaseDistribution;
 use crate::protocol::consensus::block::MINING_REWARD_TIME_LOCK_PERIOD;
+use crate::protocol::consensus::transaction::utxo_triple::UtxoTriple;
 use crate::state::wallet::expected_utxo::ExpectedUtxo;
 use crate::state::wallet::expected_utxo::UtxoNotifier;
 use crate::state::wallet::transaction_output::TxOutput;
@@ -146,10 +147,27 @@ impl ComposerParameters {
         self.maybe_receiver_preimage
     }
 
+    pub(crate) fn guesser_fee_fraction(&self) -> f64 {
+        self.guesser_fee_fraction
+    }
+
     pub(crate) fn notification_policy(&self) -> FeeNotificationPolicy {
         self.notification_policy
     }
 
+    /// The composer outputs that only this node can tell their recipients
+    /// about: those notified off-chain, to addresses whose receiver preimage
+    /// is unknown.
+    pub(crate) fn foreign_offchain_payouts(&self, composer_txos: &TxOutputList) -> Vec<UtxoTriple> {
+        if self.notification_policy() != FeeNotificationPolicy::OffChain
+            || self.maybe_receiver_preimage().is_some()
+        {
+            return vec![];
+        }
+
+        composer_txos.iter().map(|txo| txo.utxo_triple()).collect()
+    }
+
     /// Convert the [`TxOutputList`] to a list of [`ExpectedUtxo`]s consistent
     /// with the composer parameters.
     ///
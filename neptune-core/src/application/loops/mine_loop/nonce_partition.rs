@@ -0,0 +1,72 @@
+//! Partitioning of the nonce space among local guesser threads.
+//!
+//! All threads guessing on a block share a random base nonce. Thread `t` of
+//! `n` tries the nonces whose first element exceeds that of the base nonce by
+//! `t`, `t + n`, `t + 2n`, and so on, so no two threads ever try the same
+//! nonce.
+
+use tasm_lib::prelude::Digest;
+use tasm_lib::triton_vm::prelude::BFieldElement;
+
+/// The part of the nonce space that one guesser thread guesses on.
+#[derive(Debug, Clone)]
+pub(crate) struct NoncePartition {
+    base: Digest,
+    thread_index: u64,
+    num_threads: u64,
+    num_guesses: u64,
+}
+
+impl NoncePartition {
+    pub(crate) fn new(base: Digest, thread_index: usize, num_threads: usize) -> Self {
+        assert!(
+            thread_index < num_threads,
+            "thread index {thread_index} must be less than number of threads {num_threads}"
+        );
+
+        Self {
+            base,
+            thread_index: thread_index as u64,
+            num_threads: num_threads as u64,
+            num_guesses: 0,
+        }
+    }
+
+    pub(crate) fn thread_index(&self) -> usize {
+        self.thread_index as usize
+    }
+
+    pub(crate) fn next_nonce(&mut self) -> Digest {
+        let offset = self.num_guesses * self.num_threads + self.thread_index;
+        self.num_guesses += 1;
+
+        let mut values = self.base.values();
+        values[0] += BFieldElement::new(offset);
+        Digest::new(values)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::collections::HashSet;
+
+    use rand::random;
+
+    use super::*;
+
+    #[test]
+    fn threads_never_guess_the_same_nonce() {
+        let base: Digest = random();
+        let num_threads = 7;
+        let num_guesses_per_thread = 100;
+
+        let mut nonces = HashSet::new();
+        for thread_index in 0..num_threads {
+            let mut partition = NoncePartition::new(base, thread_index, num_threads);
+            for _ in 0..num_guesses_per_thread {
+                assert!(nonces.insert(partition.next_nonce()));
+            }
+        }
+    }
+}
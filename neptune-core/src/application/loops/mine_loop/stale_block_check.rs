@@ -0,0 +1,149 @@
+use tasm_lib::prelude::Digest;
+
+use crate::protocol::consensus::block::block_header::BlockHeader;
+use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::protocol::consensus::block::difficulty_control::ProofOfWork;
+use crate::protocol::consensus::block::Block;
+
+/// How a block found by own guessing relates to the tip, if it is worth
+/// submitting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FoundBlock {
+    /// The block's parent is the tip.
+    ExtendsTip,
+
+    /// The block's parent is not the tip, but the block has more cumulative
+    /// proof-of-work than the tip, so it becomes the tip once submitted. It
+    /// must be validated against its parent rather than the tip.
+    OutweighsTip,
+}
+
+/// Ways in which a block found by own guessing no longer extends the tip.
+///
+/// Guessing continues on a proposal until the main loop announces a new tip,
+/// so a solution can be found for a proposal whose parent was superseded in
+/// the meantime. Unless such a block has more cumulative proof-of-work than
+/// the tip, it is not submitted. Instead, guessing restarts on the proposal for
+/// the current tip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub(crate) enum StaleBlockError {
+    #[error(
+        "parent {parent:x} was superseded by tip {tip:x} of height {tip_height}, \
+         with cumulative proof-of-work {tip_cumulative_pow} against {found_cumulative_pow}"
+    )]
+    Superseded {
+        parent: Digest,
+        tip: Digest,
+        tip_height: BlockHeight,
+        tip_cumulative_pow: ProofOfWork,
+        found_cumulative_pow: ProofOfWork,
+    },
+
+    #[error(
+        "parent {parent:x} was orphaned by a reorganization to tip {tip:x} of \
+         height {tip_height}, with cumulative proof-of-work {tip_cumulative_pow} \
+         against {found_cumulative_pow}"
+    )]
+    Orphaned {
+        parent: Digest,
+        tip: Digest,
+        tip_height: BlockHeight,
+        tip_cumulative_pow: ProofOfWork,
+        found_cumulative_pow: ProofOfWork,
+    },
+}
+
+/// Check whether a block found by own guessing is worth submitting, before
+/// its proof-of-work and validity are checked and it is submitted.
+///
+/// A block that does not extend `tip` is only worth submitting if it has more
+/// cumulative proof-of-work than `tip`. `parent_is_canonical` tells whether the
+/// found block's parent belongs to the canonical chain, which distinguishes a
+/// tip that moved on from a reorganization.
+pub(crate) fn check_found_block_against_tip(
+    found: &BlockHeader,
+    tip: &Block,
+    parent_is_canonical: bool,
+) -> Result<FoundBlock, StaleBlockError> {
+    let parent = found.prev_block_digest;
+    if parent == tip.hash() {
+        return Ok(FoundBlock::ExtendsTip);
+    }
+
+    let tip_cumulative_pow = tip.header().cumulative_proof_of_work;
+    let found_cumulative_pow = found.cumulative_proof_of_work;
+    if found_cumulative_pow > tip_cumulative_pow {
+        return Ok(FoundBlock::OutweighsTip);
+    }
+
+    let tip_height = tip.header().height;
+    if !parent_is_canonical {
+        return Err(StaleBlockError::Orphaned {
+            parent,
+            tip: tip.hash(),
+            tip_height,
+            tip_cumulative_pow,
+            found_cumulative_pow,
+        });
+    }
+
+    Err(StaleBlockError::Superseded {
+        parent,
+        tip: tip.hash(),
+        tip_height,
+        tip_cumulative_pow,
+        found_cumulative_pow,
+    })
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+    use crate::application::config::network::Network;
+    use crate::tests::shared::blocks::invalid_empty_block;
+
+    #[test]
+    fn found_block_on_tip_is_not_stale() {
+        let network = Network::Main;
+        let genesis = Block::genesis(network);
+        let found = invalid_empty_block(&genesis, network);
+
+        assert_eq!(
+            Ok(FoundBlock::ExtendsTip),
+            check_found_block_against_tip(found.header(), &genesis, true)
+        );
+    }
+
+    #[test]
+    fn found_block_on_superseded_parent_is_stale() {
+        let network = Network::Main;
+        let genesis = Block::genesis(network);
+        let found = invalid_empty_block(&genesis, network);
+        let tip = invalid_empty_block(&genesis, network);
+
+        assert!(matches!(
+            check_found_block_against_tip(found.header(), &tip, true),
+            Err(StaleBlockError::Superseded { parent, tip_height, .. })
+                if parent == genesis.hash() && tip_height == tip.header().height
+        ));
+        assert!(matches!(
+            check_found_block_against_tip(found.header(), &tip, false),
+            Err(StaleBlockError::Orphaned { parent, .. }) if parent == genesis.hash()
+        ));
+    }
+
+    #[test]
+    fn found_block_outweighing_tip_is_submitted() {
+        let network = Network::Main;
+        let genesis = Block::genesis(network);
+        let tip = invalid_empty_block(&genesis, network);
+        let mut found = *invalid_empty_block(&genesis, network).header();
+        found.cumulative_proof_of_work = tip.header().cumulative_proof_of_work + found.difficulty;
+
+        assert_eq!(
+            Ok(FoundBlock::OutweighsTip),
+            check_found_block_against_tip(&found, &tip, false)
+        );
+    }
+}
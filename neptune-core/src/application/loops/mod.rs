@@ -3,3 +3,5 @@ pub mod connect_to_peers;
 pub mod main_loop;
 pub mod mine_loop;
 pub mod peer_loop;
+pub mod replication_loop;
+pub mod task_supervisor;
@@ -1,6 +1,7 @@
 use std::cmp;
 use std::marker::Unpin;
 use std::net::SocketAddr;
+use std::time::Duration;
 use std::time::SystemTime;
 
 use anyhow::bail;
@@ -19,12 +20,15 @@ use tasm_lib::twenty_first::prelude::MmrMembershipProof;
 use tasm_lib::twenty_first::util_types::mmr::mmr_accumulator::MmrAccumulator;
 use tokio::select;
 use tokio::sync::broadcast;
-use tokio::sync::mpsc;
+use tokio::time;
+use tokio::time::Instant;
+use tokio::time::MissedTickBehavior;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
 use tracing::warn;
 
+use crate::application::loops::channel::backpressure::BackpressureSender;
 use crate::application::loops::channel::MainToPeerTask;
 use crate::application::loops::channel::PeerTaskToMain;
 use crate::application::loops::channel::PeerTaskToMainTransaction;
@@ -39,12 +43,38 @@ use crate::protocol::consensus::block::FUTUREDATING_LIMIT;
 use crate::protocol::consensus::consensus_rule_set::ConsensusRuleSet;
 use crate::protocol::consensus::transaction::transaction_kernel::TransactionConfirmabilityError;
 use crate::protocol::consensus::transaction::Transaction;
+use crate::protocol::peer::block_chunk::BlockChunk;
+use crate::protocol::peer::block_chunk::BlockChunkAssembler;
+use crate::protocol::peer::block_chunk::BLOCK_CHUNK_SIZE;
+use crate::protocol::peer::block_relay;
+use crate::protocol::peer::connection_lifecycle::ConnectionLifecycle;
+use crate::protocol::peer::connection_lifecycle::ConnectionState;
 use crate::protocol::peer::handshake_data::HandshakeData;
+use crate::protocol::peer::handshake_data::BLOCK_CHUNKS_CAPABILITY;
+use crate::protocol::peer::handshake_data::HEADER_FIRST_RELAY_CAPABILITY;
+use crate::protocol::peer::handshake_data::PING_CAPABILITY;
+use crate::protocol::peer::handshake_data::RENDEZVOUS_CAPABILITY;
+use crate::protocol::peer::handshake_data::RENDEZVOUS_RELAY_CAPABILITY;
+use crate::protocol::peer::message_sequence::Replay;
+use crate::protocol::peer::message_sequence::SequenceViolations;
+use crate::protocol::peer::peer_block_notifications::PeerBlockHeaderNotification;
+use crate::protocol::peer::peer_block_notifications::PeerBlockNotification;
 use crate::protocol::peer::peer_info::PeerConnectionInfo;
 use crate::protocol::peer::peer_info::PeerInfo;
+use crate::protocol::peer::peer_latency::PeerLatency;
+use crate::protocol::peer::peer_latency::Ping;
+use crate::protocol::peer::peer_latency::Pong;
+use crate::protocol::peer::plausible_deniability::RelayRole;
+use crate::protocol::peer::rendezvous::RendezvousIntroduction;
+use crate::protocol::peer::rendezvous::RendezvousRequest;
+use crate::protocol::peer::rendezvous::MAX_NUM_CONNECTED_PEERS_IN_RENDEZVOUS_REQUEST;
+use crate::protocol::peer::rendezvous::MIN_RENDEZVOUS_INTERVAL;
 use crate::protocol::peer::transfer_block::TransferBlock;
+use crate::protocol::peer::transfer_transaction::TransferTransaction;
+use crate::protocol::peer::wallet_backup::WalletBackupMessage;
 use crate::protocol::peer::BlockProposalRequest;
 use crate::protocol::peer::BlockRequestBatch;
+use crate::protocol::peer::InstanceId;
 use crate::protocol::peer::IssuedSyncChallenge;
 use crate::protocol::peer::MutablePeerState;
 use crate::protocol::peer::NegativePeerSanction;
@@ -57,6 +87,8 @@ use crate::protocol::proof_abstractions::mast_hash::MastHash;
 use crate::protocol::proof_abstractions::timestamp::Timestamp;
 use crate::state::mempool::MEMPOOL_TX_THRESHOLD_AGE_IN_SECS;
 use crate::state::mining::block_proposal::BlockProposalRejectError;
+use crate::state::transaction::transaction_kernel_id::TransactionKernelId;
+use crate::state::wallet::wallet_backup::MAX_NUM_INCREMENTS_PER_MESSAGE;
 use crate::state::GlobalState;
 use crate::state::GlobalStateLock;
 use crate::util_types::mutator_set::removal_record::RemovalRecordValidityError;
@@ -89,26 +121,60 @@ const DISCONNECT_CONNECTION: bool = true;
 
 pub type PeerStandingNumber = i32;
 
+/// Whether to keep the existing of two connections to the same peer instance,
+/// rather than the new one.
+///
+/// Both ends must keep the same connection. If the two connect to each other
+/// at the same time, each sees the other's connection as the newer one, so
+/// then the connection initiated by the node with the smaller instance ID is
+/// kept. If both connections were initiated by the same node, the newer one
+/// is closed.
+fn keep_existing_duplicate(
+    own_instance_id: InstanceId,
+    peer_instance_id: InstanceId,
+    existing_is_inbound: bool,
+    new_is_inbound: bool,
+) -> bool {
+    if existing_is_inbound == new_is_inbound {
+        return true;
+    }
+
+    // Inbound connections are initiated by the peer.
+    let keep_inbound = peer_instance_id < own_instance_id;
+    existing_is_inbound == keep_inbound
+}
+
 /// Handles messages from peers via TCP
 ///
 /// also handles messages from main task over the main-to-peer-tasks broadcast
 /// channel.
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct PeerLoopHandler {
-    to_main_tx: mpsc::Sender<PeerTaskToMain>,
+    to_main_tx: BackpressureSender<PeerTaskToMain>,
     global_state_lock: GlobalStateLock,
     peer_address: SocketAddr,
     peer_handshake_data: HandshakeData,
     inbound_connection: bool,
     distance: u8,
     rng: StdRng,
+
+    /// Which messages from the main task are pushed to the peer.
+    relay_role: RelayRole,
+
+    /// The state of the connection. Set once the handshake completes, or
+    /// when the peer loop is entered.
+    lifecycle: Option<ConnectionLifecycle>,
+
+    /// Violations of the sequence numbering of frames, as detected by the
+    /// codec of the connection.
+    sequence_violations: SequenceViolations,
     #[cfg(test)]
     mock_now: Option<Timestamp>,
 }
 
 impl PeerLoopHandler {
     pub(crate) fn new(
-        to_main_tx: mpsc::Sender<PeerTaskToMain>,
+        to_main_tx: BackpressureSender<PeerTaskToMain>,
         global_state_lock: GlobalStateLock,
         peer_address: SocketAddr,
         peer_handshake_data: HandshakeData,
@@ -123,15 +189,31 @@ impl PeerLoopHandler {
             inbound_connection,
             distance,
             rng: StdRng::from_rng(&mut rand::rng()),
+            relay_role: RelayRole::default(),
+            lifecycle: None,
+            sequence_violations: SequenceViolations::default(),
             #[cfg(test)]
             mock_now: None,
         }
     }
 
+    /// Continue the lifecycle of a connection whose handshake has completed.
+    pub(crate) fn with_lifecycle(mut self, lifecycle: ConnectionLifecycle) -> Self {
+        self.lifecycle = Some(lifecycle);
+        self
+    }
+
+    /// Punish the violations of the sequence numbering of frames on the
+    /// connection.
+    pub(crate) fn with_sequence_violations(mut self, violations: SequenceViolations) -> Self {
+        self.sequence_violations = violations;
+        self
+    }
+
     /// Allows for mocked timestamps such that time dependencies may be tested.
     #[cfg(test)]
     pub(crate) fn with_mocked_time(
-        to_main_tx: mpsc::Sender<PeerTaskToMain>,
+        to_main_tx: BackpressureSender<PeerTaskToMain>,
         global_state_lock: GlobalStateLock,
         peer_address: SocketAddr,
         peer_handshake_data: HandshakeData,
@@ -148,6 +230,9 @@ impl PeerLoopHandler {
             distance,
             mock_now: Some(mocked_time),
             rng: StdRng::from_rng(&mut rand::rng()),
+            relay_role: RelayRole::default(),
+            lifecycle: None,
+            sequence_violations: SequenceViolations::default(),
         }
     }
 
@@ -170,6 +255,87 @@ impl PeerLoopHandler {
         }
     }
 
+    /// The current time, adjusted by the median clock offset of the peers.
+    /// Blocks received from peers are judged against it, such that a slightly
+    /// skewed own clock does not make this node reject valid blocks.
+    ///
+    /// # Locking
+    ///   * acquires `global_state_lock` for read
+    async fn network_adjusted_now(&self) -> Timestamp {
+        let now = self.now();
+        self.global_state_lock
+            .lock_guard()
+            .await
+            .net
+            .network_adjusted_time(now)
+    }
+
+    /// Record the announcement of the given new block, such that the peer is
+    /// credited once the block is validated, if it announced it first.
+    ///
+    /// # Locking:
+    ///   * acquires `global_state_lock` for write
+    async fn record_block_announcement(&mut self, block_digest: Digest) {
+        self.global_state_lock
+            .lock_guard_mut()
+            .await
+            .net
+            .recent_block_announcements
+            .record(block_digest, self.peer_address);
+    }
+
+    /// Credit the peers that first announced the given validated blocks.
+    ///
+    /// # Locking:
+    ///   * acquires `global_state_lock` for write
+    async fn credit_block_announcements(&mut self, blocks: &[Block]) {
+        let mut global_state = self.global_state_lock.lock_guard_mut().await;
+        for block in blocks {
+            let Some(announcer) = global_state
+                .net
+                .recent_block_announcements
+                .credit(block.hash())
+            else {
+                continue;
+            };
+            if let Some(peer_info) = global_state.net.peer_map.get_mut(&announcer) {
+                peer_info.record_block_announced_first();
+            }
+        }
+    }
+
+    /// Keep a transaction synced to an unknown mutator set until the block
+    /// producing that mutator set arrives.
+    ///
+    /// # Locking:
+    ///   * acquires `global_state_lock` for write
+    async fn keep_orphan_transaction(&mut self, transaction: Transaction) {
+        let txid = transaction.kernel.txid();
+        let now = self.now();
+        let kept = self
+            .global_state_lock
+            .lock_guard_mut()
+            .await
+            .mempool
+            .insert_orphan(transaction, self.peer_address.ip(), now);
+        if kept {
+            debug!("Keeping transaction {txid} until the block it is synced to arrives");
+        } else {
+            debug!("Not keeping transaction {txid} synced to unknown mutator set");
+        }
+    }
+
+    /// Record that the peer announced the given transaction.
+    ///
+    /// # Locking:
+    ///   * acquires the transaction announcements' lock, but not
+    ///     `global_state_lock`
+    fn record_transaction_announcement(&self, txid: TransactionKernelId) {
+        self.global_state_lock
+            .transaction_announcements()
+            .record(txid, self.peer_address);
+    }
+
     /// Punish a peer for bad behavior.
     ///
     /// Return `Err` if the peer in question is (now) banned.
@@ -289,8 +455,9 @@ impl PeerLoopHandler {
     /// main loop.
     ///
     /// # Locking
-    ///   * Acquires `global_state_lock` for write via `self.punish(..)` and
-    ///     `self.reward(..)`.
+    ///   * Acquires `global_state_lock` for write via `self.punish(..)`,
+    ///     `self.reward(..)`, `self.credit_block_announcements(..)`, and the
+    ///     block validation cache.
     ///
     /// # Panics
     ///
@@ -309,7 +476,7 @@ impl PeerLoopHandler {
                 "blocks"
             }
         );
-        let now = self.now();
+        let now = self.network_adjusted_now().await;
         debug!("validating with respect to current timestamp {now}");
         let mut previous_block = &parent_of_first_block;
         for new_block in &received_blocks {
@@ -318,8 +485,9 @@ impl PeerLoopHandler {
                 previous_block.header(),
             );
             debug!("new block has proof of work? {new_block_has_proof_of_work}");
-            let new_block_is_valid = new_block
-                .is_valid(previous_block, now, self.global_state_lock.cli().network)
+            let new_block_is_valid = self
+                .global_state_lock
+                .is_valid_block(new_block, previous_block, now)
                 .await;
             debug!("new block is valid? {new_block_is_valid}");
             if !new_block_has_proof_of_work {
@@ -391,6 +559,9 @@ impl PeerLoopHandler {
             return Ok(None);
         }
 
+        // The blocks are valid, so whoever announced them first was right.
+        self.credit_block_announcements(&received_blocks).await;
+
         // Send the new blocks to the main task which handles the state update
         // and storage to the database.
         let number_of_received_blocks = received_blocks.len();
@@ -425,8 +596,8 @@ impl PeerLoopHandler {
     ///    reconciliation list is cleared.
     ///
     /// Locking:
-    ///   * Acquires `global_state_lock` for write via `self.punish(..)` and
-    ///     `self.reward(..)`.
+    ///   * Acquires `global_state_lock` for write via `self.punish(..)`,
+    ///     `self.reward(..)`, and the block validation cache.
     async fn try_ensure_path<S>(
         &mut self,
         received_block: Box<Block>,
@@ -459,12 +630,10 @@ impl PeerLoopHandler {
         let received_block_matches_fork_reconciliation_list = if let Some(successor) =
             peer_state.fork_reconciliation_blocks.last()
         {
-            let valid = successor
-                .is_valid(
-                    received_block.as_ref(),
-                    self.now(),
-                    self.global_state_lock.cli().network,
-                )
+            let now = self.network_adjusted_now().await;
+            let valid = self
+                .global_state_lock
+                .is_valid_block(successor, received_block.as_ref(), now)
                 .await;
             if !valid {
                 warn!(
@@ -576,6 +745,184 @@ impl PeerLoopHandler {
         Ok(())
     }
 
+    /// Handle a notification of a new block, requesting the block or issuing
+    /// a sync challenge if the block has more proof-of-work than the tip.
+    /// Returns Ok(true) if the connection should be closed.
+    ///
+    /// Locking:
+    ///   * Acquires `global_state_lock` for read.
+    ///   * Acquires `global_state_lock` for write if the block is new.
+    async fn handle_block_notification<S>(
+        &mut self,
+        block_notification: PeerBlockNotification,
+        peer: &mut S,
+        peer_state_info: &mut MutablePeerState,
+    ) -> Result<bool>
+    where
+        S: Sink<PeerMessage> + TryStream<Ok = PeerMessage> + Unpin,
+        <S as Sink<PeerMessage>>::Error: std::error::Error + Sync + Send + 'static,
+        <S as TryStream>::Error: std::error::Error,
+    {
+        const SYNC_CHALLENGE_COOLDOWN: Timestamp = Timestamp::minutes(10);
+
+        let (tip_header, sync_anchor_is_set) = {
+            let state = self.global_state_lock.lock_guard().await;
+            (
+                *state.chain.light_state().header(),
+                state.net.sync_anchor.is_some(),
+            )
+        };
+        debug!(
+            "Got BlockNotification of height {}. Own height is {}",
+            block_notification.height, tip_header.height
+        );
+
+        let sync_mode_threshold = self.global_state_lock.cli().sync_mode_threshold;
+        let now = self.now();
+        let time_since_latest_successful_challenge = peer_state_info
+            .successful_sync_challenge_response_time
+            .map(|then| now - then);
+        let cooldown_expired = time_since_latest_successful_challenge
+            .is_none_or(|time_passed| time_passed > SYNC_CHALLENGE_COOLDOWN);
+        let exceeds_sync_mode_threshold = GlobalState::sync_mode_threshold_stateless(
+            &tip_header,
+            block_notification.height,
+            block_notification.cumulative_proof_of_work,
+            sync_mode_threshold,
+        );
+        if cooldown_expired && exceeds_sync_mode_threshold {
+            debug!("sync mode criterion satisfied.");
+
+            if peer_state_info.sync_challenge.is_some() {
+                warn!("Cannot launch new sync challenge because one is already on-going.");
+                return Ok(KEEP_CONNECTION_ALIVE);
+            }
+
+            info!("Peer indicates block which satisfies sync mode criterion, issuing challenge.");
+            let challenge =
+                SyncChallenge::generate(&block_notification, tip_header.height, self.rng.random());
+            peer_state_info.sync_challenge = Some(IssuedSyncChallenge::new(
+                challenge,
+                block_notification.cumulative_proof_of_work,
+                self.now(),
+            ));
+
+            debug!("sending challenge ...");
+            peer.send(PeerMessage::SyncChallenge(challenge)).await?;
+
+            return Ok(KEEP_CONNECTION_ALIVE);
+        }
+
+        peer_state_info.highest_shared_block_height = block_notification.height;
+        let block_is_new =
+            tip_header.cumulative_proof_of_work < block_notification.cumulative_proof_of_work;
+
+        debug!("block_is_new: {}", block_is_new);
+
+        if block_is_new {
+            self.record_block_announcement(block_notification.hash)
+                .await;
+        }
+
+        if block_is_new
+            && peer_state_info.fork_reconciliation_blocks.is_empty()
+            && !sync_anchor_is_set
+            && !exceeds_sync_mode_threshold
+        {
+            debug!(
+                "sending BlockRequestByHeight to peer for block with height {}",
+                block_notification.height
+            );
+            peer.send(PeerMessage::BlockRequestByHeight(block_notification.height))
+                .await?;
+        } else {
+            debug!(
+                "ignoring peer block. height {}. new: {}, reconciling_fork: {}",
+                block_notification.height,
+                block_is_new,
+                !peer_state_info.fork_reconciliation_blocks.is_empty()
+            );
+        }
+
+        Ok(KEEP_CONNECTION_ALIVE)
+    }
+
+    /// Handle a notification of a new block that carries its header.
+    ///
+    /// If the parent is known, the header's consistency with it and its
+    /// proof-of-work are verified before the block is requested, and the peer
+    /// is punished if either check fails. Otherwise, or if the checks pass,
+    /// the notification is handled like a [`PeerBlockNotification`].
+    ///
+    /// Locking:
+    ///   * Acquires `global_state_lock` for read.
+    ///   * Acquires `global_state_lock` for write via `self.punish(..)`.
+    async fn handle_block_header_notification<S>(
+        &mut self,
+        header_notification: PeerBlockHeaderNotification,
+        peer: &mut S,
+        peer_state_info: &mut MutablePeerState,
+    ) -> Result<bool>
+    where
+        S: Sink<PeerMessage> + TryStream<Ok = PeerMessage> + Unpin,
+        <S as Sink<PeerMessage>>::Error: std::error::Error + Sync + Send + 'static,
+        <S as TryStream>::Error: std::error::Error,
+    {
+        let header = header_notification.header;
+        let block_digest = header_notification.hash();
+        debug!(
+            "Got BlockHeaderNotification for block {block_digest:x} of height {}",
+            header.height
+        );
+
+        let parent_header = {
+            let state = self.global_state_lock.lock_guard().await;
+            let tip = state.chain.light_state();
+            if tip.hash() == header.prev_block_digest {
+                Some(*tip.header())
+            } else if state.chain.is_archival_node() {
+                state
+                    .chain
+                    .archival_state()
+                    .get_block_header(header.prev_block_digest)
+                    .await
+            } else {
+                None
+            }
+        };
+
+        if let Some(parent_header) = parent_header {
+            let network = self.global_state_lock.cli().network;
+            let has_proof_of_work = Block::header_has_proof_of_work(
+                &header,
+                header_notification.pow_mast_paths,
+                network,
+                &parent_header,
+            );
+            let validation_result = Block::validate_header(
+                &header,
+                &parent_header,
+                header.prev_block_digest,
+                self.now(),
+                network,
+            );
+            if !has_proof_of_work || validation_result.is_err() {
+                warn!(
+                    "Received invalid header of block {block_digest:x} from peer {}. \
+                     Has proof-of-work: {has_proof_of_work}; validation result: \
+                     {validation_result:?}",
+                    self.peer_address
+                );
+                self.punish(NegativePeerSanction::InvalidBlockHeader(block_digest))
+                    .await?;
+                return Ok(KEEP_CONNECTION_ALIVE);
+            }
+        }
+
+        self.handle_block_notification((&header_notification).into(), peer, peer_state_info)
+            .await
+    }
+
     /// Handle peer messages and returns Ok(true) if connection should be closed.
     /// Connection should also be closed if an error is returned.
     /// Otherwise, returns OK(false).
@@ -682,89 +1029,34 @@ impl PeerLoopHandler {
                 Ok(KEEP_CONNECTION_ALIVE)
             }
             PeerMessage::BlockNotification(block_notification) => {
-                const SYNC_CHALLENGE_COOLDOWN: Timestamp = Timestamp::minutes(10);
-
-                let (tip_header, sync_anchor_is_set) = {
-                    let state = self.global_state_lock.lock_guard().await;
-                    (
-                        *state.chain.light_state().header(),
-                        state.net.sync_anchor.is_some(),
-                    )
-                };
-                debug!(
-                    "Got BlockNotification of height {}. Own height is {}",
-                    block_notification.height, tip_header.height
-                );
-
-                let sync_mode_threshold = self.global_state_lock.cli().sync_mode_threshold;
-                let now = self.now();
-                let time_since_latest_successful_challenge = peer_state_info
-                    .successful_sync_challenge_response_time
-                    .map(|then| now - then);
-                let cooldown_expired = time_since_latest_successful_challenge
-                    .is_none_or(|time_passed| time_passed > SYNC_CHALLENGE_COOLDOWN);
-                let exceeds_sync_mode_threshold = GlobalState::sync_mode_threshold_stateless(
-                    &tip_header,
-                    block_notification.height,
-                    block_notification.cumulative_proof_of_work,
-                    sync_mode_threshold,
-                );
-                if cooldown_expired && exceeds_sync_mode_threshold {
-                    debug!("sync mode criterion satisfied.");
-
-                    if peer_state_info.sync_challenge.is_some() {
-                        warn!("Cannot launch new sync challenge because one is already on-going.");
-                        return Ok(KEEP_CONNECTION_ALIVE);
-                    }
-
-                    info!(
-                        "Peer indicates block which satisfies sync mode criterion, issuing challenge."
-                    );
-                    let challenge = SyncChallenge::generate(
-                        &block_notification,
-                        tip_header.height,
-                        self.rng.random(),
-                    );
-                    peer_state_info.sync_challenge = Some(IssuedSyncChallenge::new(
-                        challenge,
-                        block_notification.cumulative_proof_of_work,
-                        self.now(),
-                    ));
-
-                    debug!("sending challenge ...");
-                    peer.send(PeerMessage::SyncChallenge(challenge)).await?;
-
+                self.handle_block_notification(block_notification, peer, peer_state_info)
+                    .await
+            }
+            PeerMessage::BlockHeaderNotification(header_notification) => {
+                self.handle_block_header_notification(*header_notification, peer, peer_state_info)
+                    .await
+            }
+            PeerMessage::Ping(ping) => {
+                // Pongs reveal the skew of the local clock.
+                if self.global_state_lock.cli().plausible_deniability {
+                    debug!("Ignoring ping in plausible deniability mode");
                     return Ok(KEEP_CONNECTION_ALIVE);
                 }
 
-                peer_state_info.highest_shared_block_height = block_notification.height;
-                let block_is_new = tip_header.cumulative_proof_of_work
-                    < block_notification.cumulative_proof_of_work;
-
-                debug!("block_is_new: {}", block_is_new);
-
-                if block_is_new
-                    && peer_state_info.fork_reconciliation_blocks.is_empty()
-                    && !sync_anchor_is_set
-                    && !exceeds_sync_mode_threshold
-                {
-                    debug!(
-                        "sending BlockRequestByHeight to peer for block with height {}",
-                        block_notification.height
-                    );
-                    peer.send(PeerMessage::BlockRequestByHeight(block_notification.height))
-                        .await?;
-                } else {
-                    debug!(
-                        "ignoring peer block. height {}. new: {}, reconciling_fork: {}",
-                        block_notification.height,
-                        block_is_new,
-                        !peer_state_info.fork_reconciliation_blocks.is_empty()
-                    );
-                }
+                let ping_received_at = self.now();
+                peer.send(PeerMessage::Pong(Pong {
+                    nonce: ping.nonce,
+                    ping_received_at,
+                    sent_at: self.now(),
+                }))
+                .await?;
 
                 Ok(KEEP_CONNECTION_ALIVE)
             }
+            PeerMessage::Pong(pong) => {
+                self.handle_pong(pong, peer_state_info).await;
+                Ok(KEEP_CONNECTION_ALIVE)
+            }
             PeerMessage::SyncChallenge(sync_challenge) => {
                 let response = {
                     log_slow_scope!(fn_name!() + "::PeerMessage::SyncChallenge");
@@ -777,12 +1069,30 @@ impl PeerLoopHandler {
                     self.punish(NegativePeerSanction::ReceivedSyncChallenge)
                         .await?;
 
-                    let response = self
-                        .global_state_lock
-                        .lock_guard()
-                        .await
-                        .response_to_sync_challenge(sync_challenge)
+                    let state = self.global_state_lock.lock_guard().await;
+
+                    // Headers-only nodes cannot answer challenges for blocks
+                    // whose bodies were pruned, which is not the peer's fault.
+                    let pruned_up_to = state
+                        .chain
+                        .archival_state()
+                        .canonical_pruning_height()
                         .await;
+                    let challenges_pruned_bodies = !pruned_up_to.is_genesis()
+                        && sync_challenge.challenges.iter().any(|child_height| {
+                            child_height
+                                .previous()
+                                .is_some_and(|parent_height| parent_height <= pruned_up_to)
+                        });
+                    if challenges_pruned_bodies {
+                        drop(state);
+                        debug!("Bodies of challenged blocks were pruned");
+                        peer.send(PeerMessage::UnableToSatisfyBatchRequest).await?;
+                        return Ok(KEEP_CONNECTION_ALIVE);
+                    }
+
+                    let response = state.response_to_sync_challenge(sync_challenge).await;
+                    drop(state);
 
                     match response {
                         Ok(resp) => resp,
@@ -909,6 +1219,10 @@ impl PeerLoopHandler {
                 Ok(KEEP_CONNECTION_ALIVE)
             }
             PeerMessage::BlockRequestByHash(block_digest) => {
+                if self.try_send_block_in_chunks(block_digest, peer).await? {
+                    return Ok(KEEP_CONNECTION_ALIVE);
+                }
+
                 let block = self
                     .global_state_lock
                     .lock_guard()
@@ -964,15 +1278,29 @@ impl PeerLoopHandler {
                         return Ok(KEEP_CONNECTION_ALIVE);
                     };
 
-                    let canonical_chain_block: Block = self
+                    if self
+                        .try_send_block_in_chunks(canonical_block_digest, peer)
+                        .await?
+                    {
+                        debug!("Sent block in chunks");
+                        return Ok(KEEP_CONNECTION_ALIVE);
+                    }
+
+                    let canonical_chain_block = self
                         .global_state_lock
                         .lock_guard()
                         .await
                         .chain
                         .archival_state()
                         .get_block(canonical_block_digest)
-                        .await?
-                        .unwrap();
+                        .await?;
+
+                    // Headers-only nodes do not retain the bodies of old blocks.
+                    let Some(canonical_chain_block) = canonical_chain_block else {
+                        debug!("Body of requested block at height {block_height} was pruned");
+                        peer.send(PeerMessage::UnableToSatisfyBatchRequest).await?;
+                        return Ok(KEEP_CONNECTION_ALIVE);
+                    };
 
                     PeerMessage::Block(Box::new(canonical_chain_block.try_into().unwrap()))
                 };
@@ -985,34 +1313,42 @@ impl PeerLoopHandler {
             PeerMessage::Block(t_block) => {
                 log_slow_scope!(fn_name!() + "::PeerMessage::Block");
 
-                debug!(
-                    "Got new block from peer {}, height {}, mined {}",
-                    self.peer_address,
-                    t_block.header.height,
-                    t_block.header.timestamp.standard_format()
-                );
-                let new_block_height = t_block.header.height;
+                self.handle_transfer_block(t_block, peer, peer_state_info)
+                    .await?;
+
+                // Reward happens as part of `try_ensure_path`
 
-                let block = match Block::try_from(*t_block) {
-                    Ok(block) => Box::new(block),
+                Ok(KEEP_CONNECTION_ALIVE)
+            }
+            PeerMessage::BlockChunk(chunk) => {
+                log_slow_scope!(fn_name!() + "::PeerMessage::BlockChunk");
+
+                let block = match BlockChunkAssembler::receive(
+                    &mut peer_state_info.incoming_block_chunks,
+                    chunk,
+                ) {
+                    Ok(Some(block)) => block,
+                    Ok(None) => return Ok(KEEP_CONNECTION_ALIVE),
                     Err(e) => {
-                        warn!("Peer sent invalid block: {e:?}");
-                        self.punish(NegativePeerSanction::InvalidTransferBlock)
-                            .await?;
+                        warn!("Peer sent invalid block chunk: {e}");
+                        self.punish(NegativePeerSanction::InvalidMessage).await?;
 
                         return Ok(KEEP_CONNECTION_ALIVE);
                     }
                 };
 
-                // Update the value for the highest known height that peer possesses iff
-                // we are not in a fork reconciliation state.
-                if peer_state_info.fork_reconciliation_blocks.is_empty() {
-                    peer_state_info.highest_shared_block_height = new_block_height;
-                }
-
-                self.try_ensure_path(block, peer, peer_state_info).await?;
+                let t_block = match TransferBlock::try_from(block) {
+                    Ok(t_block) => Box::new(t_block),
+                    Err(e) => {
+                        warn!("Peer sent invalid block in chunks: {e:?}");
+                        self.punish(NegativePeerSanction::InvalidTransferBlock)
+                            .await?;
 
-                // Reward happens as part of `try_ensure_path`
+                        return Ok(KEEP_CONNECTION_ALIVE);
+                    }
+                };
+                self.handle_transfer_block(t_block, peer, peer_state_info)
+                    .await?;
 
                 Ok(KEEP_CONNECTION_ALIVE)
             }
@@ -1133,12 +1469,19 @@ impl PeerLoopHandler {
                 let mut returned_blocks: Vec<Block> =
                     Vec::with_capacity(digests_of_returned_blocks.len());
                 for block_digest in digests_of_returned_blocks {
-                    let block = state
-                        .chain
-                        .archival_state()
-                        .get_block(block_digest)
-                        .await?
-                        .unwrap();
+                    let block = state.chain.archival_state().get_block(block_digest).await?;
+
+                    // Headers-only nodes do not retain the bodies of old
+                    // blocks, which is not the peer's fault.
+                    let Some(block) = block else {
+                        if returned_blocks.is_empty() {
+                            drop(state);
+                            debug!("Bodies of requested blocks were pruned");
+                            peer.send(PeerMessage::UnableToSatisfyBatchRequest).await?;
+                            return Ok(KEEP_CONNECTION_ALIVE);
+                        }
+                        break;
+                    };
                     returned_blocks.push(block);
                 }
 
@@ -1319,6 +1662,21 @@ impl PeerLoopHandler {
                     return Ok(KEEP_CONNECTION_ALIVE);
                 }
 
+                // Not the peer's fault, so don't punish.
+                if self
+                    .global_state_lock
+                    .lock_guard()
+                    .await
+                    .load_shedding
+                    .rejects_fee(
+                        transaction.kernel.fee,
+                        self.global_state_lock.cli().load_shedding_min_fee,
+                    )
+                {
+                    debug!("Ignoring low-fee transaction while shedding load");
+                    return Ok(KEEP_CONNECTION_ALIVE);
+                }
+
                 let transaction: Transaction = (*transaction).into();
 
                 let (tip, mutator_set_accumulator_after, current_block_height) = {
@@ -1383,15 +1741,33 @@ impl PeerLoopHandler {
                     return Ok(KEEP_CONNECTION_ALIVE);
                 }
 
-                // 5. if transaction is not confirmable, punish.
-                if !transaction.is_confirmable_relative_to(&mutator_set_accumulator_after) {
-                    warn!(
-                        "Received unconfirmable transaction with TXID {}. Unconfirmable because:",
-                        transaction.kernel.txid()
-                    );
-                    // get fine-grained error code for informative logging
-                    let confirmability_error_code = transaction
-                        .kernel
+                let tx_timestamp = transaction.kernel.timestamp;
+
+                // 5. Ignore if transaction is too old
+                let now = self.now();
+                if tx_timestamp < now - Timestamp::seconds(MEMPOOL_TX_THRESHOLD_AGE_IN_SECS) {
+                    // TODO: Consider punishing here
+                    warn!("Received too old tx");
+                    return Ok(KEEP_CONNECTION_ALIVE);
+                }
+
+                // 6. Ignore if transaction is too far into the future
+                if tx_timestamp >= now + FUTUREDATING_LIMIT {
+                    // TODO: Consider punishing here
+                    warn!("Received tx too far into the future. Got timestamp: {tx_timestamp:?}");
+                    return Ok(KEEP_CONNECTION_ALIVE);
+                }
+
+                // 7. If transaction is not confirmable, punish -- unless it is
+                // synced to a mutator set that this node does not know yet.
+                if !transaction.is_confirmable_relative_to(&mutator_set_accumulator_after) {
+                    warn!(
+                        "Received unconfirmable transaction with TXID {}. Unconfirmable because:",
+                        transaction.kernel.txid()
+                    );
+                    // get fine-grained error code for informative logging
+                    let confirmability_error_code = transaction
+                        .kernel
                         .is_confirmable_relative_to(&mutator_set_accumulator_after);
                     match confirmability_error_code {
                         Ok(_) => unreachable!(),
@@ -1415,6 +1791,16 @@ impl PeerLoopHandler {
                                     debug!("invalid because membership proof for chunk index {chunk_index} is invalid");
                                 }
                             };
+
+                            // The peer may have applied a block that this node
+                            // has not seen yet.
+                            if transaction.kernel.mutator_set_hash
+                                != mutator_set_accumulator_after.hash()
+                            {
+                                self.keep_orphan_transaction(transaction).await;
+                                return Ok(KEEP_CONNECTION_ALIVE);
+                            }
+
                             self.punish(NegativePeerSanction::UnconfirmableTransaction)
                                 .await?;
                             return Ok(KEEP_CONNECTION_ALIVE);
@@ -1458,23 +1844,6 @@ impl PeerLoopHandler {
                     return Ok(KEEP_CONNECTION_ALIVE);
                 }
 
-                let tx_timestamp = transaction.kernel.timestamp;
-
-                // 6. Ignore if transaction is too old
-                let now = self.now();
-                if tx_timestamp < now - Timestamp::seconds(MEMPOOL_TX_THRESHOLD_AGE_IN_SECS) {
-                    // TODO: Consider punishing here
-                    warn!("Received too old tx");
-                    return Ok(KEEP_CONNECTION_ALIVE);
-                }
-
-                // 7. Ignore if transaction is too far into the future
-                if tx_timestamp >= now + FUTUREDATING_LIMIT {
-                    // TODO: Consider punishing here
-                    warn!("Received tx too far into the future. Got timestamp: {tx_timestamp:?}");
-                    return Ok(KEEP_CONNECTION_ALIVE);
-                }
-
                 // Otherwise, relay to main
                 let pt2m_transaction = PeerTaskToMainTransaction {
                     transaction,
@@ -1487,6 +1856,8 @@ impl PeerLoopHandler {
                 Ok(KEEP_CONNECTION_ALIVE)
             }
             PeerMessage::TransactionNotification(tx_notification) => {
+                self.record_transaction_announcement(tx_notification.txid);
+
                 // addresses #457
                 // new scope for state read-lock to avoid holding across peer.send()
                 {
@@ -1502,9 +1873,19 @@ impl PeerLoopHandler {
                         return Ok(KEEP_CONNECTION_ALIVE);
                     }
 
+                    let state = self.global_state_lock.lock_guard().await;
+
+                    // Ignore low-fee transactions while shedding load.
+                    if state.load_shedding.rejects_fee(
+                        tx_notification.fee,
+                        self.global_state_lock.cli().load_shedding_min_fee,
+                    ) {
+                        debug!("transaction fee too low while shedding load");
+                        return Ok(KEEP_CONNECTION_ALIVE);
+                    }
+
                     // 1. Ignore if we already know this transaction, and
                     // the proof quality is not higher than what we already know.
-                    let state = self.global_state_lock.lock_guard().await;
                     let accept_tx = state.mempool.accept_transaction(
                         tx_notification.txid,
                         tx_notification.proof_quality,
@@ -1538,19 +1919,31 @@ impl PeerLoopHandler {
                 Ok(KEEP_CONNECTION_ALIVE)
             }
             PeerMessage::TransactionRequest(transaction_identifier) => {
-                let state = self.global_state_lock.lock_guard().await;
-                let Some(transaction) = state.mempool.get(transaction_identifier) else {
-                    return Ok(KEEP_CONNECTION_ALIVE);
+                // Drop state immediately to prevent holding over a response.
+                let resident = {
+                    let state = self.global_state_lock.lock_guard().await;
+                    match state.mempool.get(transaction_identifier) {
+                        Some(transaction) => Ok(TransferTransaction::try_from(transaction)),
+                        None => Err(state.mempool.read_spilled(transaction_identifier)),
+                    }
                 };
 
-                let Ok(transfer_transaction) = transaction.try_into() else {
+                // Spilled transactions are read back without holding the lock.
+                let transfer_transaction = match resident {
+                    Ok(transfer_transaction) => transfer_transaction,
+                    Err(Some(spilled_read)) => {
+                        let Some(transaction) = spilled_read.transaction().await else {
+                            return Ok(KEEP_CONNECTION_ALIVE);
+                        };
+                        TransferTransaction::try_from(&transaction)
+                    }
+                    Err(None) => return Ok(KEEP_CONNECTION_ALIVE),
+                };
+                let Ok(transfer_transaction) = transfer_transaction else {
                     warn!("Peer requested transaction that cannot be converted to transfer object");
                     return Ok(KEEP_CONNECTION_ALIVE);
                 };
 
-                // Drop state immediately to prevent holding over a response.
-                drop(state);
-
                 peer.send(PeerMessage::Transaction(Box::new(transfer_transaction)))
                     .await?;
 
@@ -1635,8 +2028,9 @@ impl PeerLoopHandler {
                 // the one whose favorability is being computed.
                 let state = self.global_state_lock.lock_guard().await;
                 let tip = state.chain.light_state();
+                let now = state.net.network_adjusted_time(self.now());
                 let proposal_is_valid = new_proposal
-                    .is_valid(tip, self.now(), self.global_state_lock.cli().network)
+                    .is_valid(tip, now, self.global_state_lock.cli().network)
                     .await;
                 if !proposal_is_valid {
                     drop(state);
@@ -1681,14 +2075,397 @@ impl PeerLoopHandler {
 
                 Ok(KEEP_CONNECTION_ALIVE)
             }
+            PeerMessage::RendezvousRequest(request) => {
+                self.handle_rendezvous_request(request, peer, peer_state_info)
+                    .await?;
+                Ok(KEEP_CONNECTION_ALIVE)
+            }
+            PeerMessage::RendezvousIntroduction(introduction) => {
+                self.handle_rendezvous_introduction(introduction, peer_state_info)
+                    .await?;
+                Ok(KEEP_CONNECTION_ALIVE)
+            }
+            PeerMessage::WalletBackup(message) => {
+                log_slow_scope!(fn_name!() + "::PeerMessage::WalletBackup");
+
+                self.handle_wallet_backup_message(*message, peer).await?;
+                Ok(KEEP_CONNECTION_ALIVE)
+            }
+        }
+    }
+
+    /// Handle a block the peer sent, whole or in chunks: try to connect it
+    /// to the chain.
+    ///
+    /// Locking:
+    ///   * acquires `global_state_lock` for write via `self.punish(..)` and
+    ///     `Self::try_ensure_path()`.
+    async fn handle_transfer_block<S>(
+        &mut self,
+        t_block: Box<TransferBlock>,
+        peer: &mut S,
+        peer_state_info: &mut MutablePeerState,
+    ) -> Result<()>
+    where
+        S: Sink<PeerMessage> + TryStream<Ok = PeerMessage> + Unpin,
+        <S as Sink<PeerMessage>>::Error: std::error::Error + Sync + Send + 'static,
+        <S as TryStream>::Error: std::error::Error,
+    {
+        debug!(
+            "Got new block from peer {}, height {}, mined {}",
+            self.peer_address,
+            t_block.header.height,
+            t_block.header.timestamp.standard_format()
+        );
+        let new_block_height = t_block.header.height;
+
+        let block = match Block::try_from(*t_block) {
+            Ok(block) => Box::new(block),
+            Err(e) => {
+                warn!("Peer sent invalid block: {e:?}");
+                self.punish(NegativePeerSanction::InvalidTransferBlock)
+                    .await?;
+
+                return Ok(());
+            }
+        };
+
+        // Update the value for the highest known height that peer possesses iff
+        // we are not in a fork reconciliation state.
+        if peer_state_info.fork_reconciliation_blocks.is_empty() {
+            peer_state_info.highest_shared_block_height = new_block_height;
+        }
+
+        self.try_ensure_path(block, peer, peer_state_info).await
+    }
+
+    /// Send the requested block to the peer in chunks read from disk, if the
+    /// peer can reassemble them. Returns `false` if the block must be sent
+    /// whole instead, because the peer does not support chunks, or because
+    /// the block is not stored on disk, like the genesis block.
+    ///
+    /// Locking:
+    ///   * acquires `global_state_lock` for read, and releases it before
+    ///     sending.
+    async fn try_send_block_in_chunks<S>(&self, block_digest: Digest, peer: &mut S) -> Result<bool>
+    where
+        S: Sink<PeerMessage> + Unpin,
+        <S as Sink<PeerMessage>>::Error: std::error::Error + Sync + Send + 'static,
+    {
+        if !self
+            .peer_handshake_data
+            .has_capability(BLOCK_CHUNKS_CAPABILITY)
+        {
+            return Ok(false);
+        }
+
+        let stored = self
+            .global_state_lock
+            .lock_guard()
+            .await
+            .chain
+            .archival_state()
+            .stored_block_bytes(block_digest)
+            .await;
+        let Some(stored) = stored else {
+            return Ok(false);
+        };
+
+        let total_length = stored.length();
+        let mut offset = 0;
+        while offset < total_length {
+            let bytes = stored.read(offset, BLOCK_CHUNK_SIZE).await?;
+            let num_bytes = bytes.len() as u64;
+            peer.send(PeerMessage::BlockChunk(BlockChunk {
+                block_digest,
+                total_length,
+                offset,
+                bytes,
+            }))
+            .await?;
+            offset += num_bytes;
+        }
+
+        Ok(true)
+    }
+
+    /// Introduce the peer to another peer that accepts introductions, as a
+    /// relay.
+    ///
+    /// Locking:
+    ///   * acquires `global_state_lock` for read
+    ///   * acquires `global_state_lock` for write via Self::punish()
+    async fn handle_rendezvous_request<S>(
+        &mut self,
+        request: RendezvousRequest,
+        peer: &mut S,
+        peer_state_info: &mut MutablePeerState,
+    ) -> Result<()>
+    where
+        S: Sink<PeerMessage> + TryStream<Ok = PeerMessage> + Unpin,
+        <S as Sink<PeerMessage>>::Error: std::error::Error + Sync + Send + 'static,
+        <S as TryStream>::Error: std::error::Error,
+    {
+        if !self.global_state_lock.cli().rendezvous_relay
+            || !self
+                .peer_handshake_data
+                .has_capability(RENDEZVOUS_CAPABILITY)
+        {
+            self.punish(NegativePeerSanction::UnwantedMessage).await?;
+            return Ok(());
         }
+
+        if request.connected.len() > MAX_NUM_CONNECTED_PEERS_IN_RENDEZVOUS_REQUEST {
+            self.punish(NegativePeerSanction::InvalidMessage).await?;
+            return Ok(());
+        }
+
+        // The requester asks whenever it discovers peers, which may be more
+        // often than introductions are made.
+        let now = self.now();
+        if peer_state_info
+            .last_rendezvous_request
+            .is_some_and(|then| now - then < MIN_RENDEZVOUS_INTERVAL)
+        {
+            debug!("Ignoring rendezvous request from {}", self.peer_address);
+            return Ok(());
+        }
+        peer_state_info.last_rendezvous_request = Some(now);
+
+        let introduction = RendezvousIntroduction::for_requester(
+            self.global_state_lock
+                .lock_guard()
+                .await
+                .net
+                .peer_map
+                .values(),
+            self.peer_address,
+            &request,
+            &mut self.rng,
+        );
+        let Some(introduction) = introduction else {
+            debug!("Found no peer to introduce {} to", self.peer_address);
+            return Ok(());
+        };
+
+        info!(
+            "Introducing {} and {} to each other",
+            self.peer_address, introduction.peer_address
+        );
+        peer.send(PeerMessage::RendezvousIntroduction(introduction))
+            .await?;
+        self.send_to_main(
+            PeerTaskToMain::Introduce {
+                recipient: introduction.peer_address,
+                introduction: RendezvousIntroduction::of_requester(
+                    self.peer_address,
+                    self.peer_handshake_data.instance_id,
+                ),
+            },
+            line!(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Pass an introduction by a relay on to the main loop, which connects to
+    /// the introduced peer.
+    ///
+    /// Locking:
+    ///   * acquires `global_state_lock` for write via Self::punish()
+    async fn handle_rendezvous_introduction(
+        &mut self,
+        introduction: RendezvousIntroduction,
+        peer_state_info: &mut MutablePeerState,
+    ) -> Result<()> {
+        if !self.global_state_lock.cli().rendezvous
+            || !self
+                .peer_handshake_data
+                .has_capability(RENDEZVOUS_RELAY_CAPABILITY)
+        {
+            self.punish(NegativePeerSanction::UnwantedMessage).await?;
+            return Ok(());
+        }
+
+        // Prevent relays from making this node connect to arbitrary addresses
+        // at a high rate, and to addresses on the local network. Relays only
+        // rate-limit introductions per requester, so honest relays can exceed
+        // the rate, and excess introductions are not punished.
+        let now = self.now();
+        if peer_state_info
+            .last_rendezvous_introduction
+            .is_some_and(|then| now - then < MIN_RENDEZVOUS_INTERVAL)
+        {
+            debug!(
+                "Ignoring rendezvous introduction from {}",
+                self.peer_address
+            );
+            return Ok(());
+        }
+        peer_state_info.last_rendezvous_introduction = Some(now);
+
+        if PeerInfo::ip_is_local(introduction.peer_address.ip())
+            && !PeerInfo::ip_is_local(self.peer_address.ip())
+        {
+            self.punish(NegativePeerSanction::InvalidMessage).await?;
+            return Ok(());
+        }
+
+        self.send_to_main(PeerTaskToMain::Introduced(introduction), line!())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Serve the backups this node holds for the peer, or pass the peer's
+    /// answers about this node's own backup on to the main loop. Requests
+    /// from peers that are not trusted are refused.
+    ///
+    /// Locking:
+    ///   * acquires the held wallet backups' lock, but not `global_state_lock`
+    ///   * acquires `global_state_lock` for write via Self::punish()
+    async fn handle_wallet_backup_message<S>(
+        &mut self,
+        message: WalletBackupMessage,
+        peer: &mut S,
+    ) -> Result<()>
+    where
+        S: Sink<PeerMessage> + TryStream<Ok = PeerMessage> + Unpin,
+        <S as Sink<PeerMessage>>::Error: std::error::Error + Sync + Send + 'static,
+        <S as TryStream>::Error: std::error::Error,
+    {
+        let backup_id = message.backup_id();
+        let is_trusted = self
+            .global_state_lock
+            .cli()
+            .is_wallet_backup_peer(self.peer_address.ip());
+        let reply = match message {
+            _ if !is_trusted => {
+                debug!(
+                    "Refusing wallet backup message from untrusted peer {}",
+                    self.peer_address
+                );
+                match message {
+                    WalletBackupMessage::StatusRequest { .. }
+                    | WalletBackupMessage::Store { .. }
+                    | WalletBackupMessage::Fetch { .. }
+                    | WalletBackupMessage::Replace { .. } => WalletBackupMessage::Refused {
+                        backup_id,
+                        reason: "not a trusted peer".to_string(),
+                    },
+                    _ => return Ok(()),
+                }
+            }
+            WalletBackupMessage::StatusRequest { .. } => WalletBackupMessage::Status {
+                backup_id,
+                head: self
+                    .global_state_lock
+                    .held_wallet_backups()
+                    .lock()
+                    .await
+                    .head(backup_id),
+            },
+            WalletBackupMessage::Store { ref increments, .. }
+            | WalletBackupMessage::Replace { ref increments, .. }
+                if increments.len() > MAX_NUM_INCREMENTS_PER_MESSAGE =>
+            {
+                self.punish(NegativePeerSanction::InvalidMessage).await?;
+                return Ok(());
+            }
+            WalletBackupMessage::Store { increments, .. } => {
+                let stored = self
+                    .global_state_lock
+                    .held_wallet_backups()
+                    .lock()
+                    .await
+                    .store(backup_id, increments)
+                    .await;
+                match stored {
+                    Ok(head) => WalletBackupMessage::Status { backup_id, head },
+                    Err(e) => {
+                        warn!("Not storing wallet backup of {}: {e}", self.peer_address);
+                        WalletBackupMessage::Refused {
+                            backup_id,
+                            reason: e.to_string(),
+                        }
+                    }
+                }
+            }
+            WalletBackupMessage::Replace { increments, .. } => {
+                let stored = self
+                    .global_state_lock
+                    .held_wallet_backups()
+                    .lock()
+                    .await
+                    .replace(backup_id, increments)
+                    .await;
+                match stored {
+                    Ok(head) => WalletBackupMessage::Status { backup_id, head },
+                    Err(e) => {
+                        warn!("Not storing wallet backup of {}: {e}", self.peer_address);
+                        WalletBackupMessage::Refused {
+                            backup_id,
+                            reason: e.to_string(),
+                        }
+                    }
+                }
+            }
+            WalletBackupMessage::Fetch { after_version, .. } => {
+                let increments = self
+                    .global_state_lock
+                    .held_wallet_backups()
+                    .lock()
+                    .await
+                    .increments_after(backup_id, after_version)
+                    .await;
+                match increments {
+                    Ok(increments) => WalletBackupMessage::Increments {
+                        backup_id,
+                        increments,
+                    },
+                    Err(e) => {
+                        warn!(
+                            "Could not read wallet backup of {}: {e:#}",
+                            self.peer_address
+                        );
+                        WalletBackupMessage::Refused {
+                            backup_id,
+                            reason: "could not read backup".to_string(),
+                        }
+                    }
+                }
+            }
+            WalletBackupMessage::Increments { ref increments, .. }
+                if increments.len() > MAX_NUM_INCREMENTS_PER_MESSAGE =>
+            {
+                self.punish(NegativePeerSanction::InvalidMessage).await?;
+                return Ok(());
+            }
+            WalletBackupMessage::Status { .. }
+            | WalletBackupMessage::Increments { .. }
+            | WalletBackupMessage::Refused { .. } => {
+                self.send_to_main(
+                    PeerTaskToMain::WalletBackup(self.peer_address, Box::new(message)),
+                    line!(),
+                )
+                .await?;
+                return Ok(());
+            }
+        };
+
+        peer.send(PeerMessage::WalletBackup(Box::new(reply)))
+            .await?;
+
+        Ok(())
     }
 
     /// send msg to main via mpsc channel `to_main_tx` and logs if slow.
     ///
-    /// the channel could potentially fill up in which case the send() will
-    /// block until there is capacity.  we wrap the send() so we can log if
-    /// that ever happens to the extent it passes slow-scope threshold.
+    /// the channel could potentially fill up in which case the send() applies
+    /// the configured overflow policy, which may block until there is
+    /// capacity.  we wrap the send() so we can log if that ever happens to the
+    /// extent it passes slow-scope threshold.
     async fn send_to_main(
         &self,
         msg: PeerTaskToMain,
@@ -1700,11 +2477,48 @@ impl PeerLoopHandler {
         self.to_main_tx.send(msg).await
     }
 
+    /// In plausible deniability mode, switch this transaction-relay peer to
+    /// block relay if the message is not relayed to it, and no connected peer
+    /// relays blocks. Otherwise, the blocks this node finds or receives would
+    /// not be relayed at all once the block-relay peers disconnected. Returns
+    /// true iff the role was switched.
+    ///
+    /// Locking:
+    ///   * acquires `global_state_lock` for write if this peer relays
+    ///     transactions only
+    async fn take_over_block_relay(&mut self, msg: &MainToPeerTask) -> bool {
+        if self.relay_role != RelayRole::Transactions || !RelayRole::Blocks.relays(msg) {
+            return false;
+        }
+
+        let mut global_state = self.global_state_lock.lock_guard_mut().await;
+        let peer_map = &mut global_state.net.peer_map;
+        if peer_map
+            .values()
+            .any(|peer| peer.relay_role() == RelayRole::Blocks)
+        {
+            return false;
+        }
+        let Some(peer_info) = peer_map.get_mut(&self.peer_address) else {
+            return false;
+        };
+
+        info!(
+            "No peer relays blocks. Switching {} to block relay.",
+            self.peer_address
+        );
+        peer_info.set_relay_role(RelayRole::Blocks);
+        self.relay_role = RelayRole::Blocks;
+
+        true
+    }
+
     /// Handle message from main task. The boolean return value indicates if
     /// the connection should be closed.
     ///
     /// Locking:
-    ///   * acquires `global_state_lock` for write via Self::punish()
+    ///   * acquires `global_state_lock` for write via Self::punish() and
+    ///     Self::take_over_block_relay()
     async fn handle_main_task_message<S>(
         &mut self,
         msg: MainToPeerTask,
@@ -1717,17 +2531,24 @@ impl PeerLoopHandler {
         <S as TryStream>::Error: std::error::Error,
     {
         debug!("Handling {} message from main in peer loop", msg.get_type());
+        if !self.relay_role.relays(&msg) && !self.take_over_block_relay(&msg).await {
+            return Ok(KEEP_CONNECTION_ALIVE);
+        }
+
         match msg {
             MainToPeerTask::Block(block) => {
                 // We don't currently differentiate whether a new block came from a peer, or from our
                 // own miner. It's always shared through this logic.
-                let new_block_height = block.kernel.header.height;
-                if new_block_height > peer_state_info.highest_shared_block_height {
-                    debug!("Sending PeerMessage::BlockNotification");
-                    peer_state_info.highest_shared_block_height = new_block_height;
-                    peer.send(PeerMessage::BlockNotification(block.as_ref().into()))
-                        .await?;
-                    debug!("Sent PeerMessage::BlockNotification");
+                let delay = self.block_relay_delay().await;
+                if delay.is_zero() {
+                    self.relay_block(&block, peer, peer_state_info).await?;
+                } else {
+                    // Supersedes any block still waiting to be relayed.
+                    debug!(
+                        "Delaying relay of block to {} by {delay:?}",
+                        self.peer_address
+                    );
+                    peer_state_info.delayed_block_relay = Some((block, Instant::now() + delay));
                 }
                 Ok(KEEP_CONNECTION_ALIVE)
             }
@@ -1769,6 +2590,13 @@ impl PeerLoopHandler {
                 peer.send(PeerMessage::PeerListRequest).await?;
                 Ok(KEEP_CONNECTION_ALIVE)
             }
+            MainToPeerTask::RequestBlockNotification => {
+                peer_state_info
+                    .recent_announcements
+                    .expect_block_announcement();
+                peer.send(PeerMessage::BlockNotificationRequest).await?;
+                Ok(KEEP_CONNECTION_ALIVE)
+            }
             MainToPeerTask::Disconnect(peer_address) => {
                 log_slow_scope!(fn_name!() + "::MainToPeerTask::Disconnect");
 
@@ -1809,7 +2637,172 @@ impl PeerLoopHandler {
                 debug!("Sent PeerMessage::BlockProposalNotification");
                 Ok(KEEP_CONNECTION_ALIVE)
             }
+            MainToPeerTask::RequestIntroduction(relay, request) => {
+                if relay == self.peer_address {
+                    peer.send(PeerMessage::RendezvousRequest(request)).await?;
+                }
+                Ok(KEEP_CONNECTION_ALIVE)
+            }
+            MainToPeerTask::Introduce(recipient, introduction) => {
+                if recipient == self.peer_address {
+                    peer.send(PeerMessage::RendezvousIntroduction(introduction))
+                        .await?;
+                }
+                Ok(KEEP_CONNECTION_ALIVE)
+            }
+            MainToPeerTask::WalletBackup(recipient, message) => {
+                if recipient == self.peer_address {
+                    peer.send(PeerMessage::WalletBackup(message)).await?;
+                }
+                Ok(KEEP_CONNECTION_ALIVE)
+            }
+        }
+    }
+
+    /// Whether to periodically ping the peer.
+    fn pings_peer(&self) -> bool {
+        let cli_args = self.global_state_lock.cli();
+        !cli_args.peer_ping_interval.is_zero()
+            && !cli_args.plausible_deniability
+            && self.peer_handshake_data.has_capability(PING_CAPABILITY)
+    }
+
+    /// Send a new ping to the peer. A previous ping that was not answered by
+    /// now is considered lost.
+    async fn send_ping<S>(
+        &mut self,
+        peer: &mut S,
+        peer_state_info: &mut MutablePeerState,
+    ) -> Result<()>
+    where
+        S: Sink<PeerMessage> + TryStream<Ok = PeerMessage> + Unpin,
+        <S as Sink<PeerMessage>>::Error: std::error::Error + Sync + Send + 'static,
+        <S as TryStream>::Error: std::error::Error,
+    {
+        if let Some(lost) = peer_state_info.outstanding_ping {
+            debug!(
+                "Peer {} did not answer ping {}",
+                self.peer_address, lost.nonce
+            );
+        }
+
+        let ping = Ping {
+            nonce: self.rng.random(),
+            sent_at: self.now(),
+        };
+        peer_state_info.outstanding_ping = Some(ping);
+        peer.send(PeerMessage::Ping(ping)).await?;
+
+        Ok(())
+    }
+
+    /// Measure the peer's latency from its answer to the outstanding ping.
+    ///
+    /// Locking:
+    ///   * acquires `global_state_lock` for write
+    async fn handle_pong(&mut self, pong: Pong, peer_state_info: &mut MutablePeerState) {
+        let pong_received_at = self.now();
+        let Some(ping) = peer_state_info
+            .outstanding_ping
+            .take_if(|ping| ping.nonce == pong.nonce)
+        else {
+            debug!("Ignoring unsolicited pong from {}", self.peer_address);
+            return;
+        };
+
+        let Some(sample) = PeerLatency::sample(ping, pong, pong_received_at) else {
+            debug!(
+                "Ignoring pong with inconsistent timestamps from {}",
+                self.peer_address
+            );
+            return;
+        };
+        debug!(
+            "Peer {} has round-trip time {} ms and clock offset {} ms",
+            self.peer_address,
+            sample.round_trip_time.as_millis(),
+            sample.clock_offset_millis
+        );
+
+        if let Some(peer_info) = self
+            .global_state_lock
+            .lock_guard_mut()
+            .await
+            .net
+            .peer_map
+            .get_mut(&self.peer_address)
+        {
+            peer_info.record_latency_sample(sample);
+        }
+    }
+
+    /// How long to wait before relaying a new block to this peer, given its
+    /// rank among the connected peers.
+    ///
+    /// Locking:
+    ///   * Acquires `global_state_lock` for read.
+    async fn block_relay_delay(&self) -> Duration {
+        let cli_args = self.global_state_lock.cli();
+        if cli_args.block_relay_stagger.is_zero() {
+            return Duration::ZERO;
+        }
+
+        let global_state = self.global_state_lock.lock_guard().await;
+        let peers = global_state
+            .net
+            .peer_map
+            .iter()
+            .map(|(address, peer_info)| {
+                (
+                    *address,
+                    peer_info.num_blocks_announced_first(),
+                    peer_info.latency().map(|latency| latency.round_trip_time),
+                )
+            });
+
+        block_relay::relay_delay(
+            self.peer_address,
+            peers,
+            cli_args.block_relay_fan_out,
+            cli_args.block_relay_stagger,
+        )
+    }
+
+    /// Notify the peer of a new block, unless it already knows of a block at
+    /// the same height or higher.
+    async fn relay_block<S>(
+        &self,
+        block: &Block,
+        peer: &mut S,
+        peer_state_info: &mut MutablePeerState,
+    ) -> Result<()>
+    where
+        S: Sink<PeerMessage> + TryStream<Ok = PeerMessage> + Unpin,
+        <S as Sink<PeerMessage>>::Error: std::error::Error + Sync + Send + 'static,
+        <S as TryStream>::Error: std::error::Error,
+    {
+        let new_block_height = block.kernel.header.height;
+        if new_block_height <= peer_state_info.highest_shared_block_height {
+            return Ok(());
+        }
+
+        peer_state_info.highest_shared_block_height = new_block_height;
+        if self
+            .peer_handshake_data
+            .has_capability(HEADER_FIRST_RELAY_CAPABILITY)
+        {
+            debug!("Sending PeerMessage::BlockHeaderNotification");
+            peer.send(PeerMessage::BlockHeaderNotification(Box::new(block.into())))
+                .await?;
+            debug!("Sent PeerMessage::BlockHeaderNotification");
+        } else {
+            debug!("Sending PeerMessage::BlockNotification");
+            peer.send(PeerMessage::BlockNotification(block.into()))
+                .await?;
+            debug!("Sent PeerMessage::BlockNotification");
         }
+
+        Ok(())
     }
 
     /// Loop for the peer tasks. Awaits either a message from the peer over TCP,
@@ -1825,8 +2818,51 @@ impl PeerLoopHandler {
         <S as Sink<PeerMessage>>::Error: std::error::Error + Sync + Send + 'static,
         <S as TryStream>::Error: std::error::Error,
     {
+        let pings_peer = self.pings_peer();
+        let ping_interval_duration = self.global_state_lock.cli().peer_ping_interval;
+        let mut ping_interval = time::interval(ping_interval_duration.max(Duration::from_secs(1)));
+        ping_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
         loop {
+            let block_relay_deadline = peer_state_info
+                .delayed_block_relay
+                .as_ref()
+                .map(|(_, deadline)| *deadline);
+
+            // Only pinged peers are expected to send messages regularly.
+            let idle_deadline = self
+                .lifecycle
+                .as_ref()
+                .and_then(|lifecycle| lifecycle.deadline())
+                .filter(|_| pings_peer);
             select! {
+                // Disconnect a peer that has been silent for too long
+                _ = time::sleep_until(idle_deadline.unwrap_or_else(Instant::now)), if idle_deadline.is_some() => {
+                    let Some(lifecycle) = &self.lifecycle else {
+                        continue;
+                    };
+                    bail!(lifecycle.time_out());
+                }
+
+                // Relay a new block once it is this peer's turn
+                _ = time::sleep_until(block_relay_deadline.unwrap_or_else(Instant::now)), if block_relay_deadline.is_some() => {
+                    let Some((block, _)) = peer_state_info.delayed_block_relay.take() else {
+                        continue;
+                    };
+                    if let Err(err) = self.relay_block(&block, &mut peer, peer_state_info).await {
+                        warn!("Closing connection to {} because of error {err}.", self.peer_address);
+                        bail!("{err}");
+                    }
+                }
+
+                // Measure the peer's latency
+                _ = ping_interval.tick(), if pings_peer => {
+                    if let Err(err) = self.send_ping(&mut peer, peer_state_info).await {
+                        warn!("Closing connection to {} because of error {err}.", self.peer_address);
+                        bail!("{err}");
+                    }
+                }
+
                 // Handle peer messages
                 peer_message = peer.try_next() => {
                     let peer_address = self.peer_address;
@@ -1848,6 +2884,15 @@ impl PeerLoopHandler {
                         info!("Peer {peer_address} closed connection.");
                         break;
                     };
+                    if let Some(lifecycle) = &mut self.lifecycle {
+                        lifecycle.record_activity();
+                    }
+
+                    // The codec drops replayed frames, so their violations
+                    // surface with the next message that is delivered.
+                    if self.sequence_violations.take() > 0 {
+                        self.punish(NegativePeerSanction::OutOfSequenceMessage).await?;
+                    }
 
                     let (syncing, frozen) =
                         self.global_state_lock.lock(|s| (s.net.sync_anchor.is_some(), s.net.freeze)).await;
@@ -1868,6 +2913,21 @@ impl PeerLoopHandler {
                         debug!("Ignoring message because state updates have been paused.");
                         continue;
                     }
+                    match peer_state_info
+                        .recent_announcements
+                        .check(&peer_message, self.now())
+                    {
+                        Replay::Fresh => {}
+                        Replay::Tolerated => {
+                            debug!("Ignoring replayed {message_type} message from {peer_address}");
+                            continue;
+                        }
+                        Replay::Excessive => {
+                            debug!("Ignoring replayed {message_type} message from {peer_address}");
+                            self.punish(NegativePeerSanction::ReplayedAnnouncement).await?;
+                            continue;
+                        }
+                    }
 
                     match self
                         .handle_peer_message(peer_message, &mut peer, peer_state_info)
@@ -1921,6 +2981,43 @@ impl PeerLoopHandler {
         Ok(())
     }
 
+    /// Flush and shut down the connection after the peer loop has exited.
+    /// Errors are logged but otherwise ignored, since the connection is
+    /// closing regardless.
+    async fn drain<S>(&mut self, peer: &mut S)
+    where
+        S: Sink<PeerMessage> + Unpin,
+        <S as Sink<PeerMessage>>::Error: std::error::Error,
+    {
+        let Some(mut lifecycle) = self.lifecycle.take() else {
+            return;
+        };
+        if let Err(err) = lifecycle.transition(ConnectionState::Draining) {
+            warn!("{err}");
+            return;
+        }
+
+        match lifecycle.within(peer.close()).await {
+            Ok(Ok(())) => (),
+            Ok(Err(err)) => debug!("Failed to close connection to {}: {err}", self.peer_address),
+            Err(_) => (), // logged by lifecycle
+        }
+    }
+
+    /// Start tracking a connection that was handed to the peer loop without a
+    /// lifecycle, as happens in tests. Its handshake has already completed.
+    async fn track_handshaken_connection(&self) -> Result<ConnectionLifecycle> {
+        let mut lifecycle = self
+            .global_state_lock
+            .lock_guard()
+            .await
+            .connection_lifecycles
+            .track(self.peer_address);
+        lifecycle.transition(ConnectionState::Handshaking)?;
+
+        Ok(lifecycle)
+    }
+
     /// Function called before entering the peer loop. Reads the potentially stored
     /// peer standing from the database and does other book-keeping before entering
     /// its final resting place: the `peer_loop`. Note that the peer has already been
@@ -1939,6 +3036,13 @@ impl PeerLoopHandler {
         <S as Sink<PeerMessage>>::Error: std::error::Error + Sync + Send + 'static,
         <S as TryStream>::Error: std::error::Error,
     {
+        let mut lifecycle = match self.lifecycle.take() {
+            Some(lifecycle) => lifecycle,
+            None => self.track_handshaken_connection().await?,
+        };
+        lifecycle.transition(ConnectionState::Active)?;
+        self.lifecycle = Some(lifecycle);
+
         let cli_args = self.global_state_lock.cli().clone();
 
         let standing = self
@@ -1973,17 +3077,46 @@ impl PeerLoopHandler {
         // need to make the a check again while holding a write-lock, since
         // we're modifying `peer_map` here. Holding a read-lock doesn't work
         // since it would have to be dropped before acquiring the write-lock.
-        {
+        //
+        // A peer instance that is connected already, possibly via another
+        // address, is not connected to twice. Which of the two connections is
+        // closed is decided such that both ends agree, see
+        // [keep_existing_duplicate].
+        let replaced_duplicate = {
             let mut global_state = self.global_state_lock.lock_guard_mut().await;
+            let own_instance_id = global_state.net.instance_id;
             let peer_map = &mut global_state.net.peer_map;
-            if peer_map
+            let duplicate = peer_map
                 .values()
-                .any(|pi| pi.instance_id() == self.peer_handshake_data.instance_id)
-            {
-                bail!("Attempted to connect to already connected peer. Aborting connection.");
-            }
+                .find(|pi| pi.instance_id() == self.peer_handshake_data.instance_id)
+                .map(|pi| (pi.connected_address(), pi.connection_is_inbound()));
+            let replaced_duplicate = match duplicate {
+                Some((existing_address, existing_is_inbound))
+                    if keep_existing_duplicate(
+                        own_instance_id,
+                        self.peer_handshake_data.instance_id,
+                        existing_is_inbound,
+                        self.inbound_connection,
+                    ) =>
+                {
+                    drop(global_state);
+                    info!(
+                        "Already connected to {} via {existing_address}. \
+                         Closing duplicate connection.",
+                        self.peer_address
+                    );
+                    // The peer may have closed this connection already.
+                    if let Err(e) = peer.send(PeerMessage::Bye).await {
+                        debug!("Could not say bye on duplicate connection: {e}");
+                    }
+                    self.drain(&mut peer).await;
+                    return Ok(());
+                }
+                Some((existing_address, _)) => Some(existing_address),
+                None => None,
+            };
 
-            if peer_map.len() >= cli_args.max_num_peers {
+            if replaced_duplicate.is_none() && peer_map.len() >= cli_args.max_num_peers {
                 bail!("Attempted to connect to more peers than allowed. Aborting connection.");
             }
 
@@ -1993,7 +3126,34 @@ impl PeerLoopHandler {
                 bail!("Already connected to peer. Aborting connection");
             }
 
-            peer_map.insert(self.peer_address, new_peer);
+            // In plausible deniability mode, no peer learns of both the
+            // transactions and the blocks this node relays.
+            if cli_args.plausible_deniability {
+                self.relay_role =
+                    RelayRole::assign(peer_map.values().map(|peer| peer.relay_role()));
+            }
+
+            peer_map.insert(self.peer_address, new_peer.with_relay_role(self.relay_role));
+            global_state.net.fork_signals.record(
+                self.peer_address.ip(),
+                &self.peer_handshake_data,
+                SystemTime::now(),
+            );
+
+            replaced_duplicate
+        };
+
+        if let Some(existing_address) = replaced_duplicate {
+            info!(
+                "Connected to the same peer as {existing_address} via {}. \
+                 Closing the connection via {existing_address}.",
+                self.peer_address
+            );
+            self.send_to_main(
+                PeerTaskToMain::DisconnectDuplicate(existing_address),
+                line!(),
+            )
+            .await?;
         }
 
         // `MutablePeerState` contains the part of the peer-loop's state that is mutable
@@ -2013,12 +3173,15 @@ impl PeerLoopHandler {
         {
             // Send block notification request to catch up ASAP, in case we're
             // behind the newly-connected peer.
+            peer_state.recent_announcements.expect_block_announcement();
             peer.send(PeerMessage::BlockNotificationRequest).await?;
         }
 
-        let res = self.run(peer, from_main_rx, &mut peer_state).await;
+        let res = self.run(&mut peer, from_main_rx, &mut peer_state).await;
         debug!("Exited peer loop for {}", self.peer_address);
 
+        self.drain(&mut peer).await;
+
         close_peer_connected_callback(
             self.global_state_lock.clone(),
             self.peer_address,
@@ -2058,6 +3221,7 @@ mod tests {
     use rand::rngs::StdRng;
     use rand::Rng;
     use rand::SeedableRng;
+    use tokio::sync::mpsc;
     use tokio::sync::mpsc::error::TryRecvError;
     use tracing_test::traced_test;
 
@@ -2065,7 +3229,6 @@ mod tests {
     use crate::application::config::cli_args;
     use crate::application::config::network::Network;
     use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
-    use crate::protocol::peer::peer_block_notifications::PeerBlockNotification;
     use crate::protocol::peer::transaction_notification::TransactionNotification;
     use crate::protocol::peer::Sanction;
     use crate::state::mempool::upgrade_priority::UpgradePriority;
@@ -2083,6 +3246,26 @@ mod tests {
     use crate::tests::shared::Mock;
     use crate::tests::shared_tokio_runtime;
 
+    #[test]
+    fn both_ends_keep_the_same_duplicate_connection() {
+        let (a, b): (InstanceId, InstanceId) = (1, 2);
+
+        // a and b connected to each other at the same time. For each, the
+        // other's connection is the new one.
+        let a_keeps_existing = keep_existing_duplicate(a, b, false, true);
+        let b_keeps_existing = keep_existing_duplicate(b, a, false, true);
+        assert_ne!(a_keeps_existing, b_keeps_existing);
+
+        // The kept connection is the one initiated by a, the node with the
+        // smaller instance ID: a's outgoing connection.
+        assert!(a_keeps_existing);
+
+        // If both connections are initiated by the same node, the newer one is
+        // closed.
+        assert!(keep_existing_duplicate(a, b, true, true));
+        assert!(keep_existing_duplicate(a, b, false, false));
+    }
+
     #[traced_test]
     #[apply(shared_tokio_runtime)]
     async fn no_disconnect_on_invalid_message() {
@@ -2398,6 +3581,7 @@ mod tests {
         use crate::protocol::consensus::block::validity::block_primitive_witness::BlockPrimitiveWitness;
         use crate::state::wallet::address::generation_address::GenerationReceivingAddress;
         use crate::tests::shared::blocks::fake_valid_block_proposal_successor_for_test;
+        use crate::tests::shared::blocks::invalid_empty_block;
         use crate::tests::shared::blocks::next_block;
         use crate::tests::shared::globalstate::test_setup_custom_genesis_block;
         use crate::tests::tokio_runtime;
@@ -2564,53 +3748,154 @@ mod tests {
                 .unwrap();
             let genesis: Block = Block::genesis(network);
 
-            let mut peer_loop_handler = PeerLoopHandler::new(
-                to_main_tx.clone(),
+            let mut peer_loop_handler = PeerLoopHandler::new(
+                to_main_tx.clone(),
+                state_lock.clone(),
+                peer_address,
+                hsd,
+                true,
+                1,
+            );
+
+            let (
+                block_without_any_pow,
+                block_with_valid_mock_pow,
+                valid_pow_reboot,
+                valid_pow_alpha,
+            ) = pow_related_blocks(network, &genesis).await;
+            assert!(
+                peer_loop_handler
+                    .handle_blocks(vec![block_without_any_pow], genesis.clone())
+                    .await
+                    .unwrap()
+                    .is_none(),
+                "Must return None on invalid Pow"
+            );
+            assert!(
+                peer_loop_handler
+                    .handle_blocks(vec![block_with_valid_mock_pow], genesis.clone())
+                    .await
+                    .unwrap()
+                    .is_none(),
+                "Must return None on valid mock Pow and invalid Pow"
+            );
+            assert_eq!(
+                BlockHeight::genesis().next(),
+                peer_loop_handler
+                    .handle_blocks(vec![valid_pow_reboot], genesis.clone())
+                    .await
+                    .unwrap()
+                    .unwrap(),
+                "Must return Some(1) on valid Pow"
+            );
+            assert!(
+                peer_loop_handler
+                    .handle_blocks(vec![valid_pow_alpha], genesis.clone())
+                    .await
+                    .unwrap()
+                    .is_none(),
+                "Must return None on hardfork-alpha solution when rule set is reboot"
+            );
+        }
+
+        #[traced_test]
+        #[apply(shared_tokio_runtime)]
+        async fn block_header_notification_is_verified_before_block_is_requested() {
+            let network = Network::Main;
+            let (_peer_broadcast_tx, from_main_rx_clone, to_main_tx, _to_main_rx1, state_lock, hsd) =
+                get_test_genesis_setup(network, 0, cli_args::Args::default_with_network(network))
+                    .await
+                    .unwrap();
+            let peer_address = get_dummy_socket_address(0);
+            let genesis = Block::genesis(network);
+            let (without_any_pow, _, with_valid_pow, _) =
+                pow_related_blocks(network, &genesis).await;
+
+            let mock = Mock::new(vec![
+                Action::Read(PeerMessage::BlockHeaderNotification(Box::new(
+                    (&without_any_pow).into(),
+                ))),
+                Action::Read(PeerMessage::BlockHeaderNotification(Box::new(
+                    (&with_valid_pow).into(),
+                ))),
+                Action::Write(PeerMessage::BlockRequestByHeight(
+                    with_valid_pow.header().height,
+                )),
+                Action::Read(PeerMessage::Bye),
+            ]);
+
+            let mut peer_loop_handler = PeerLoopHandler::with_mocked_time(
+                to_main_tx,
+                state_lock.clone(),
+                peer_address,
+                hsd,
+                false,
+                1,
+                with_valid_pow.header().timestamp,
+            );
+            peer_loop_handler
+                .run_wrapper(mock, from_main_rx_clone)
+                .await
+                .unwrap();
+
+            let standing = state_lock
+                .lock_guard()
+                .await
+                .net
+                .get_peer_standing_from_database(peer_address.ip())
+                .await
+                .unwrap();
+            assert_eq!(
+                NegativePeerSanction::InvalidBlockHeader(without_any_pow.hash()),
+                standing
+                    .latest_punishment
+                    .expect("peer must be sanctioned for invalid header")
+                    .0
+            );
+        }
+
+        #[traced_test]
+        #[apply(shared_tokio_runtime)]
+        async fn replayed_block_notification_is_ignored() {
+            let network = Network::Main;
+            let (_peer_broadcast_tx, from_main_rx_clone, to_main_tx, _to_main_rx1, state_lock, hsd) =
+                get_test_genesis_setup(network, 0, cli_args::Args::default_with_network(network))
+                    .await
+                    .unwrap();
+            let peer_address = get_dummy_socket_address(0);
+            let block1 = invalid_empty_block(&Block::genesis(network), network);
+
+            // The block is requested once, not once per notification.
+            let mock = Mock::new(vec![
+                Action::Read(PeerMessage::BlockNotification((&block1).into())),
+                Action::Write(PeerMessage::BlockRequestByHeight(block1.header().height)),
+                Action::Read(PeerMessage::BlockNotification((&block1).into())),
+                Action::Read(PeerMessage::Bye),
+            ]);
+
+            let mut peer_loop_handler = PeerLoopHandler::with_mocked_time(
+                to_main_tx,
                 state_lock.clone(),
                 peer_address,
                 hsd,
-                true,
+                false,
                 1,
+                block1.header().timestamp,
             );
+            peer_loop_handler
+                .run_wrapper(mock, from_main_rx_clone)
+                .await
+                .unwrap();
 
-            let (
-                block_without_any_pow,
-                block_with_valid_mock_pow,
-                valid_pow_reboot,
-                valid_pow_alpha,
-            ) = pow_related_blocks(network, &genesis).await;
-            assert!(
-                peer_loop_handler
-                    .handle_blocks(vec![block_without_any_pow], genesis.clone())
-                    .await
-                    .unwrap()
-                    .is_none(),
-                "Must return None on invalid Pow"
-            );
-            assert!(
-                peer_loop_handler
-                    .handle_blocks(vec![block_with_valid_mock_pow], genesis.clone())
-                    .await
-                    .unwrap()
-                    .is_none(),
-                "Must return None on valid mock Pow and invalid Pow"
-            );
-            assert_eq!(
-                BlockHeight::genesis().next(),
-                peer_loop_handler
-                    .handle_blocks(vec![valid_pow_reboot], genesis.clone())
-                    .await
-                    .unwrap()
-                    .unwrap(),
-                "Must return Some(1) on valid Pow"
-            );
+            let standing = state_lock
+                .lock_guard()
+                .await
+                .net
+                .get_peer_standing_from_database(peer_address.ip())
+                .await;
             assert!(
-                peer_loop_handler
-                    .handle_blocks(vec![valid_pow_alpha], genesis.clone())
-                    .await
-                    .unwrap()
-                    .is_none(),
-                "Must return None on hardfork-alpha solution when rule set is reboot"
+                standing.is_none_or(|standing| standing.latest_punishment.is_none()),
+                "a single replayed notification is not sanctioned"
             );
         }
 
@@ -4480,7 +5765,7 @@ mod tests {
             to_main_rx: mpsc::Receiver<PeerTaskToMain>,
             from_main_rx: broadcast::Receiver<MainToPeerTask>,
             peer_state: MutablePeerState,
-            to_main_tx: mpsc::Sender<PeerTaskToMain>,
+            to_main_tx: BackpressureSender<PeerTaskToMain>,
             genesis_block: Block,
             peer_broadcast_tx: broadcast::Sender<MainToPeerTask>,
         }
@@ -5094,4 +6379,348 @@ mod tests {
             Ok(())
         }
     }
+
+    mod pings {
+        use super::*;
+        use crate::tests::shared::globalstate::get_dummy_peer_outgoing;
+
+        #[traced_test]
+        #[apply(shared_tokio_runtime)]
+        async fn pings_are_answered() {
+            let network = Network::Main;
+            let (_peer_broadcast_tx, from_main_rx_clone, to_main_tx, _to_main_rx1, state_lock, hsd) =
+                get_test_genesis_setup(network, 0, cli_args::Args::default_with_network(network))
+                    .await
+                    .unwrap();
+            let now = Timestamp::now();
+
+            let mock = Mock::new(vec![
+                Action::Read(PeerMessage::Ping(Ping {
+                    nonce: 5,
+                    sent_at: now - Timestamp::seconds(1),
+                })),
+                Action::Write(PeerMessage::Pong(Pong {
+                    nonce: 5,
+                    ping_received_at: now,
+                    sent_at: now,
+                })),
+                Action::Read(PeerMessage::Bye),
+            ]);
+
+            let mut peer_loop_handler = PeerLoopHandler::with_mocked_time(
+                to_main_tx,
+                state_lock,
+                get_dummy_socket_address(0),
+                hsd,
+                false,
+                1,
+                now,
+            );
+            peer_loop_handler
+                .run_wrapper(mock, from_main_rx_clone)
+                .await
+                .unwrap();
+        }
+
+        #[traced_test]
+        #[apply(shared_tokio_runtime)]
+        async fn answered_pings_are_measured() {
+            let network = Network::Main;
+            let (
+                _peer_broadcast_tx,
+                _from_main_rx_clone,
+                to_main_tx,
+                _to_main_rx1,
+                mut state_lock,
+                hsd,
+            ) = get_test_genesis_setup(network, 0, cli_args::Args::default_with_network(network))
+                .await
+                .unwrap();
+            let peer_address = get_dummy_socket_address(0);
+            state_lock
+                .lock_guard_mut()
+                .await
+                .net
+                .peer_map
+                .insert(peer_address, get_dummy_peer_outgoing(peer_address));
+            let now = Timestamp::now();
+            let mut peer_loop_handler = PeerLoopHandler::with_mocked_time(
+                to_main_tx,
+                state_lock.clone(),
+                peer_address,
+                hsd,
+                false,
+                1,
+                now,
+            );
+
+            // The peer's clock is one second ahead, and it answers instantly
+            // after 50 ms.
+            let mut peer_state = MutablePeerState::new(hsd.tip_header.height);
+            peer_state.outstanding_ping = Some(Ping {
+                nonce: 7,
+                sent_at: now - Timestamp::millis(100),
+            });
+            let pong = Pong {
+                nonce: 7,
+                ping_received_at: now + Timestamp::millis(950),
+                sent_at: now + Timestamp::millis(950),
+            };
+            peer_loop_handler.handle_pong(pong, &mut peer_state).await;
+
+            // Pongs that do not answer the outstanding ping are ignored.
+            peer_loop_handler.handle_pong(pong, &mut peer_state).await;
+
+            let latency = state_lock
+                .lock_guard()
+                .await
+                .net
+                .peer_map
+                .get(&peer_address)
+                .unwrap()
+                .latency()
+                .unwrap();
+            assert_eq!(Duration::from_millis(100), latency.round_trip_time);
+            assert_eq!(1000, latency.clock_offset_millis);
+            assert_eq!(1, latency.num_samples);
+            assert_eq!(
+                Some(1000),
+                state_lock
+                    .lock_guard()
+                    .await
+                    .net
+                    .median_peer_clock_offset_millis()
+            );
+        }
+    }
+
+    mod rendezvous {
+        use super::*;
+        use crate::protocol::peer::peer_info::PeerConnectionInfo;
+
+        /// A peer behind NAT, which only connects to others.
+        fn peer_behind_nat(address: SocketAddr) -> (PeerInfo, HandshakeData) {
+            let mut handshake = get_dummy_handshake_data_for_genesis(Network::Main);
+            handshake.listen_port = None;
+            handshake.add_capability(RENDEZVOUS_CAPABILITY);
+            let peer_info = PeerInfo::new(
+                PeerConnectionInfo::new(None, address, true),
+                &handshake,
+                SystemTime::now(),
+                cli_args::Args::default().peer_tolerance,
+            );
+            (peer_info, handshake)
+        }
+
+        #[traced_test]
+        #[apply(shared_tokio_runtime)]
+        async fn relay_introduces_peers_behind_nat_to_each_other() {
+            let network = Network::Main;
+            let cli = cli_args::Args {
+                rendezvous_relay: true,
+                ..cli_args::Args::default_with_network(network)
+            };
+            let (_peer_broadcast_tx, from_main_rx, to_main_tx, mut to_main_rx, mut state_lock, _) =
+                get_test_genesis_setup(network, 0, cli).await.unwrap();
+
+            let requester_address = get_dummy_socket_address(0);
+            let (requester, requester_handshake) = peer_behind_nat(requester_address);
+            let introducee_address = get_dummy_socket_address(1);
+            let (introducee, _) = peer_behind_nat(introducee_address);
+            {
+                let mut state = state_lock.lock_guard_mut().await;
+                state.net.peer_map.insert(requester_address, requester);
+                state
+                    .net
+                    .peer_map
+                    .insert(introducee_address, introducee.clone());
+            }
+
+            let mock = Mock::new(vec![
+                Action::Read(PeerMessage::RendezvousRequest(RendezvousRequest {
+                    connected: vec![],
+                })),
+                Action::Write(PeerMessage::RendezvousIntroduction(
+                    RendezvousIntroduction {
+                        peer_address: introducee_address,
+                        instance_id: introducee.instance_id(),
+                        initiator: true,
+                    },
+                )),
+                // Too soon after the previous request to be answered.
+                Action::Read(PeerMessage::RendezvousRequest(RendezvousRequest {
+                    connected: vec![],
+                })),
+                Action::Read(PeerMessage::Bye),
+            ]);
+
+            let mut peer_loop_handler = PeerLoopHandler::new(
+                to_main_tx,
+                state_lock.clone(),
+                requester_address,
+                requester_handshake.clone(),
+                true,
+                1,
+            );
+            peer_loop_handler
+                .run_wrapper(mock, from_main_rx)
+                .await
+                .unwrap();
+
+            let expected_to_main = PeerTaskToMain::Introduce {
+                recipient: introducee_address,
+                introduction: RendezvousIntroduction {
+                    peer_address: requester_address,
+                    instance_id: requester_handshake.instance_id,
+                    initiator: false,
+                },
+            };
+            assert_eq!(expected_to_main, to_main_rx.recv().await.unwrap());
+        }
+
+        #[traced_test]
+        #[apply(shared_tokio_runtime)]
+        async fn rendezvous_requests_are_unwanted_by_non_relays() {
+            let network = Network::Main;
+            let (_peer_broadcast_tx, from_main_rx, to_main_tx, _to_main_rx, state_lock, _) =
+                get_test_genesis_setup(network, 0, cli_args::Args::default_with_network(network))
+                    .await
+                    .unwrap();
+
+            let requester_address = get_dummy_socket_address(0);
+            let (_, requester_handshake) = peer_behind_nat(requester_address);
+            let mock = Mock::new(vec![
+                Action::Read(PeerMessage::RendezvousRequest(RendezvousRequest {
+                    connected: vec![],
+                })),
+                Action::Read(PeerMessage::Bye),
+            ]);
+
+            let mut peer_loop_handler = PeerLoopHandler::new(
+                to_main_tx,
+                state_lock.clone(),
+                requester_address,
+                requester_handshake,
+                true,
+                1,
+            );
+            peer_loop_handler
+                .run_wrapper(mock, from_main_rx)
+                .await
+                .unwrap();
+
+            let peer_standing = state_lock
+                .lock_guard()
+                .await
+                .net
+                .get_peer_standing_from_database(requester_address.ip())
+                .await
+                .unwrap();
+            assert_eq!(
+                NegativePeerSanction::UnwantedMessage,
+                peer_standing.latest_punishment.unwrap().0
+            );
+        }
+
+        #[traced_test]
+        #[apply(shared_tokio_runtime)]
+        async fn excess_introductions_are_ignored_without_punishment() {
+            let network = Network::Main;
+            let cli = cli_args::Args {
+                rendezvous: true,
+                ..cli_args::Args::default_with_network(network)
+            };
+            let (_peer_broadcast_tx, from_main_rx, to_main_tx, mut to_main_rx, state_lock, mut hsd) =
+                get_test_genesis_setup(network, 0, cli).await.unwrap();
+            hsd.add_capability(RENDEZVOUS_RELAY_CAPABILITY);
+
+            let relay_address = get_dummy_socket_address(0);
+            let introduction = |peer_address: &str| {
+                Action::Read(PeerMessage::RendezvousIntroduction(
+                    RendezvousIntroduction {
+                        peer_address: peer_address.parse().unwrap(),
+                        instance_id: rand::random(),
+                        initiator: true,
+                    },
+                ))
+            };
+            let mock = Mock::new(vec![
+                introduction("1.2.3.4:9798"),
+                introduction("1.2.3.5:9798"),
+                Action::Read(PeerMessage::Bye),
+            ]);
+
+            let mut peer_loop_handler =
+                PeerLoopHandler::new(to_main_tx, state_lock.clone(), relay_address, hsd, false, 1);
+            peer_loop_handler
+                .run_wrapper(mock, from_main_rx)
+                .await
+                .unwrap();
+
+            assert!(state_lock
+                .lock_guard()
+                .await
+                .net
+                .get_peer_standing_from_database(relay_address.ip())
+                .await
+                .and_then(|standing| standing.latest_punishment)
+                .is_none());
+
+            let mut introduced = vec![];
+            while let Ok(message) = to_main_rx.try_recv() {
+                if let PeerTaskToMain::Introduced(introduction) = message {
+                    introduced.push(introduction.peer_address);
+                }
+            }
+            assert_eq!(
+                vec!["1.2.3.4:9798".parse::<SocketAddr>().unwrap()],
+                introduced
+            );
+        }
+
+        #[traced_test]
+        #[apply(shared_tokio_runtime)]
+        async fn introductions_to_local_addresses_are_rejected() {
+            let network = Network::Main;
+            let cli = cli_args::Args {
+                rendezvous: true,
+                ..cli_args::Args::default_with_network(network)
+            };
+            let (_peer_broadcast_tx, from_main_rx, to_main_tx, mut to_main_rx, state_lock, mut hsd) =
+                get_test_genesis_setup(network, 0, cli).await.unwrap();
+            hsd.add_capability(RENDEZVOUS_RELAY_CAPABILITY);
+
+            let relay_address = get_dummy_socket_address(0);
+            let mock = Mock::new(vec![
+                Action::Read(PeerMessage::RendezvousIntroduction(
+                    RendezvousIntroduction {
+                        peer_address: "192.168.0.1:9798".parse().unwrap(),
+                        instance_id: rand::random(),
+                        initiator: true,
+                    },
+                )),
+                Action::Read(PeerMessage::Bye),
+            ]);
+
+            let mut peer_loop_handler =
+                PeerLoopHandler::new(to_main_tx, state_lock.clone(), relay_address, hsd, false, 1);
+            peer_loop_handler
+                .run_wrapper(mock, from_main_rx)
+                .await
+                .unwrap();
+
+            let peer_standing = state_lock
+                .lock_guard()
+                .await
+                .net
+                .get_peer_standing_from_database(relay_address.ip())
+                .await
+                .unwrap();
+            assert_eq!(
+                NegativePeerSanction::InvalidMessage,
+                peer_standing.latest_punishment.unwrap().0
+            );
+            assert!(to_main_rx.try_recv().is_err());
+        }
+    }
 }
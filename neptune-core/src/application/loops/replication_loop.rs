@@ -0,0 +1,464 @@
+//! Tasks that replicate a primary node to its standbys, see
+//! [`crate::state::replication`].
+//!
+//! The primary runs [`serve_standbys`], which answers each authenticated
+//! standby's requests for blocks and expected UTXOs. A standby runs
+//! [`follow_primary`], which periodically pulls everything the primary has
+//! that the standby lacks, validates the blocks, and hands them to the main
+//! loop.
+//!
+//! Only the handshake travels in plaintext. Once both sides authenticated,
+//! every frame is encrypted and authenticated with the session's keys.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use anyhow::bail;
+use anyhow::ensure;
+use anyhow::Context;
+use bytes::Bytes;
+use futures::SinkExt;
+use futures::StreamExt;
+use futures::TryStreamExt;
+use rand::random;
+use tasm_lib::prelude::Digest;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::time;
+use tokio_util::codec::Framed;
+use tokio_util::codec::LengthDelimitedCodec;
+use tracing::debug;
+use tracing::info;
+use tracing::warn;
+
+use crate::application::loops::channel::RPCServerToMain;
+use crate::application::loops::connect_to_peers::MAX_PEER_FRAME_LENGTH_IN_BYTES;
+use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::protocol::consensus::block::Block;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+use crate::state::replication::ReplicationMessage;
+use crate::state::replication::ReplicationRole;
+use crate::state::replication::ReplicationSecret;
+use crate::state::replication::ReplicationSession;
+use crate::state::replication::MAX_REPLICATED_BLOCKS_PER_BATCH;
+use crate::GlobalStateLock;
+
+/// Time between a standby's requests to its primary.
+const REPLICATION_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Maximum time to wait for the other side of a replication connection to
+/// respond.
+const REPLICATION_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Maximum number of the standby's most recent blocks that it reports to the
+/// primary, to find the first block the standby lacks. If the two nodes have
+/// diverged further, the standby must resynchronize from its peers.
+const MAX_KNOWN_BLOCKS: usize = 100;
+
+/// A connection between a standby and its primary. Frames are encrypted once
+/// the handshake completed.
+struct ReplicationConnection {
+    framed: Framed<TcpStream, LengthDelimitedCodec>,
+    session: Option<ReplicationSession>,
+}
+
+impl ReplicationConnection {
+    fn new(stream: TcpStream) -> Self {
+        let codec = LengthDelimitedCodec::builder()
+            .max_frame_length(MAX_PEER_FRAME_LENGTH_IN_BYTES)
+            .new_codec();
+        Self {
+            framed: Framed::new(stream, codec),
+            session: None,
+        }
+    }
+
+    /// Encrypt all further frames with the keys of the session.
+    fn secure(&mut self, session: ReplicationSession) {
+        self.session = Some(session);
+    }
+
+    async fn send(&mut self, message: ReplicationMessage) -> anyhow::Result<()> {
+        let mut frame = bincode::serialize(&message)?;
+        if let Some(session) = self.session.as_mut() {
+            frame = session.seal(&frame)?;
+        }
+        self.framed.send(Bytes::from(frame)).await?;
+
+        Ok(())
+    }
+
+    /// The next message, or `None` if the connection was closed.
+    async fn try_next(&mut self) -> anyhow::Result<Option<ReplicationMessage>> {
+        let Some(frame) = self.framed.try_next().await? else {
+            return Ok(None);
+        };
+        let message = match self.session.as_mut() {
+            Some(session) => bincode::deserialize(&session.open(&frame)?)?,
+            None => bincode::deserialize(&frame)?,
+        };
+
+        Ok(Some(message))
+    }
+}
+
+async fn receive(connection: &mut ReplicationConnection) -> anyhow::Result<ReplicationMessage> {
+    match time::timeout(REPLICATION_TIMEOUT, connection.try_next()).await {
+        Ok(Ok(Some(message))) => Ok(message),
+        Ok(Ok(None)) => bail!("connection closed"),
+        Ok(Err(e)) => Err(e),
+        Err(_) => bail!("timed out after {} seconds", REPLICATION_TIMEOUT.as_secs()),
+    }
+}
+
+/// Accept connections from standbys, and serve each of them until it
+/// disconnects.
+pub(crate) async fn serve_standbys(
+    listener: TcpListener,
+    global_state_lock: GlobalStateLock,
+    secret: ReplicationSecret,
+) -> anyhow::Result<()> {
+    loop {
+        let (stream, address) = listener.accept().await?;
+        let global_state_lock = global_state_lock.clone();
+        tokio::spawn(async move {
+            info!("Standby connected from {address}");
+            match serve_standby(stream, global_state_lock, secret).await {
+                Ok(()) => info!("Standby at {address} disconnected"),
+                Err(e) => warn!("Replication to standby at {address} failed: {e:#}"),
+            }
+        });
+    }
+}
+
+async fn serve_standby(
+    stream: TcpStream,
+    mut global_state_lock: GlobalStateLock,
+    secret: ReplicationSecret,
+) -> anyhow::Result<()> {
+    let mut connection = ReplicationConnection::new(stream);
+
+    let ReplicationMessage::Hello { network, challenge } = receive(&mut connection).await? else {
+        bail!("expected hello");
+    };
+    let own_network = global_state_lock.cli().network;
+    if network != own_network {
+        let reason = format!("primary is on {own_network}");
+        connection
+            .send(ReplicationMessage::Refused(reason.clone()))
+            .await?;
+        bail!("standby is on {network}, but {reason}");
+    }
+
+    let own_challenge: Digest = random();
+    connection
+        .send(ReplicationMessage::Challenge {
+            challenge: own_challenge,
+            response: secret.response(ReplicationRole::Primary, challenge),
+        })
+        .await?;
+    let ReplicationMessage::Authenticate { response } = receive(&mut connection).await? else {
+        bail!("expected authentication");
+    };
+    if response != secret.response(ReplicationRole::Standby, own_challenge) {
+        let reason = "authentication failed".to_string();
+        connection
+            .send(ReplicationMessage::Refused(reason.clone()))
+            .await?;
+        bail!(reason);
+    }
+    connection.secure(secret.session(ReplicationRole::Primary, challenge, own_challenge));
+
+    while let Some(request) = connection.try_next().await? {
+        let response = match request {
+            ReplicationMessage::SyncRequest {
+                known_blocks,
+                num_expected_utxos,
+            } => sync_response(&global_state_lock, &known_blocks, num_expected_utxos).await?,
+            ReplicationMessage::DemoteRequest => {
+                global_state_lock
+                    .lock_guard_mut()
+                    .await
+                    .set_replication_role(ReplicationRole::Standby)
+                    .await?;
+                warn!("Demoted to standby by request of a standby. Spending is disabled.");
+                ReplicationMessage::Demoted
+            }
+            _ => bail!("unexpected message"),
+        };
+        connection.send(response).await?;
+    }
+
+    Ok(())
+}
+
+/// The canonical blocks following the most recent of `known_blocks` that is
+/// canonical, and all expected UTXOs unless the standby already has as many as
+/// this node.
+async fn sync_response(
+    global_state_lock: &GlobalStateLock,
+    known_blocks: &[Digest],
+    num_expected_utxos: u64,
+) -> anyhow::Result<ReplicationMessage> {
+    let state = global_state_lock.lock_guard().await;
+    let archival_state = state.chain.archival_state();
+
+    let mut common_height = None;
+    for &digest in known_blocks {
+        if archival_state
+            .block_belongs_to_canonical_chain(digest)
+            .await
+        {
+            common_height = archival_state
+                .get_block_header(digest)
+                .await
+                .map(|header| header.height);
+            break;
+        }
+    }
+    let Some(common_height) = common_height else {
+        return Ok(ReplicationMessage::Refused(
+            "no recent block in common; synchronize from peers".to_string(),
+        ));
+    };
+
+    let tip_height = state.chain.light_state().header().height;
+    let mut blocks = vec![];
+    let mut height = common_height.next();
+    while height <= tip_height && blocks.len() < MAX_REPLICATED_BLOCKS_PER_BATCH {
+        let Some(digest) = archival_state
+            .archival_block_mmr
+            .ammr()
+            .try_get_leaf(height.into())
+            .await
+        else {
+            break;
+        };
+        let block = archival_state
+            .get_block(digest)
+            .await?
+            .with_context(|| format!("canonical block at height {height} not found"))?;
+        blocks.push(block);
+        height = height.next();
+    }
+
+    let expected_utxos = if state.wallet_state.num_expected_utxos().await == num_expected_utxos {
+        None
+    } else {
+        let expected_utxos: Vec<_> = state
+            .wallet_state
+            .wallet_db
+            .stream_expected_utxos()
+            .await
+            .map(|(_, expected_utxo)| expected_utxo)
+            .collect()
+            .await;
+        Some(expected_utxos)
+    };
+
+    Ok(ReplicationMessage::SyncResponse {
+        blocks,
+        expected_utxos,
+    })
+}
+
+/// Connect to the primary at `primary`, and authenticate both sides.
+async fn connect(
+    primary: SocketAddr,
+    global_state_lock: &GlobalStateLock,
+    secret: ReplicationSecret,
+) -> anyhow::Result<ReplicationConnection> {
+    let stream = time::timeout(REPLICATION_TIMEOUT, TcpStream::connect(primary))
+        .await
+        .context("timed out connecting to primary")??;
+    let mut connection = ReplicationConnection::new(stream);
+
+    let own_challenge: Digest = random();
+    connection
+        .send(ReplicationMessage::Hello {
+            network: global_state_lock.cli().network,
+            challenge: own_challenge,
+        })
+        .await?;
+    let (challenge, response) = match receive(&mut connection).await? {
+        ReplicationMessage::Challenge {
+            challenge,
+            response,
+        } => (challenge, response),
+        ReplicationMessage::Refused(reason) => bail!("primary refused connection: {reason}"),
+        _ => bail!("expected challenge"),
+    };
+    ensure!(
+        response == secret.response(ReplicationRole::Primary, own_challenge),
+        "primary failed to authenticate"
+    );
+    connection
+        .send(ReplicationMessage::Authenticate {
+            response: secret.response(ReplicationRole::Standby, challenge),
+        })
+        .await?;
+    connection.secure(secret.session(ReplicationRole::Standby, own_challenge, challenge));
+
+    Ok(connection)
+}
+
+/// Pull blocks and expected UTXOs from the primary until the standby has
+/// caught up.
+///
+/// Returns the height of the last block received, if any.
+async fn replicate(
+    connection: &mut ReplicationConnection,
+    global_state_lock: &mut GlobalStateLock,
+) -> anyhow::Result<Option<BlockHeight>> {
+    let network = global_state_lock.cli().network;
+    let mut known_blocks = {
+        let state = global_state_lock.lock_guard().await;
+        let tip_digest = state.chain.light_state().hash();
+        let mut known_blocks = vec![tip_digest];
+        known_blocks.extend(
+            state
+                .chain
+                .archival_state()
+                .get_ancestor_block_digests(tip_digest, MAX_KNOWN_BLOCKS - 1)
+                .await,
+        );
+        known_blocks
+    };
+
+    // The last block of the previous batch, if any.
+    let mut parent: Option<Block> = None;
+    let mut replicated_height = None;
+    loop {
+        let num_expected_utxos = global_state_lock
+            .lock_guard()
+            .await
+            .wallet_state
+            .num_expected_utxos()
+            .await;
+        connection
+            .send(ReplicationMessage::SyncRequest {
+                known_blocks: known_blocks.clone(),
+                num_expected_utxos,
+            })
+            .await?;
+        let (blocks, expected_utxos) = match receive(connection).await? {
+            ReplicationMessage::SyncResponse {
+                blocks,
+                expected_utxos,
+            } => (blocks, expected_utxos),
+            ReplicationMessage::Refused(reason) => bail!("primary refused: {reason}"),
+            _ => bail!("expected sync response"),
+        };
+
+        if let Some(expected_utxos) = expected_utxos {
+            debug!("Received {} expected UTXOs", expected_utxos.len());
+            global_state_lock
+                .lock_guard_mut()
+                .await
+                .wallet_state
+                .add_expected_utxos(expected_utxos)
+                .await;
+        }
+
+        let Some(last_block) = blocks.last().cloned() else {
+            return Ok(replicated_height);
+        };
+
+        // The first block's parent is one of the known blocks, which the
+        // standby has stored.
+        let mut previous = match parent.take() {
+            Some(parent) => parent,
+            None => global_state_lock
+                .lock_guard()
+                .await
+                .chain
+                .archival_state()
+                .get_block(blocks[0].header().prev_block_digest)
+                .await?
+                .context("primary sent block with unknown parent")?,
+        };
+        for block in &blocks {
+            if let Err(e) = block.validate(&previous, Timestamp::now(), network).await {
+                bail!(
+                    "primary sent invalid block at height {}: {e}",
+                    block.header().height
+                );
+            }
+            previous = block.clone();
+        }
+
+        let is_last_batch = blocks.len() < MAX_REPLICATED_BLOCKS_PER_BATCH;
+        global_state_lock
+            .rpc_server_to_main_tx()
+            .send(RPCServerToMain::ReplicatedBlocks(blocks))
+            .await
+            .context("main loop is gone")?;
+
+        replicated_height = Some(last_block.header().height);
+        known_blocks = vec![last_block.hash()];
+        parent = Some(last_block);
+        if is_last_batch {
+            return Ok(replicated_height);
+        }
+    }
+}
+
+/// Replicate from `primary` for as long as this node is a standby.
+pub(crate) async fn follow_primary(
+    primary: SocketAddr,
+    mut global_state_lock: GlobalStateLock,
+    secret: ReplicationSecret,
+) -> anyhow::Result<()> {
+    let mut interval = time::interval(REPLICATION_INTERVAL);
+    interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+    let mut connection = None;
+
+    loop {
+        interval.tick().await;
+        if global_state_lock
+            .lock_guard()
+            .await
+            .replication
+            .is_spend_enabled()
+        {
+            connection = None;
+            continue;
+        }
+
+        let result = match connection.as_mut() {
+            Some(connection) => replicate(connection, &mut global_state_lock).await,
+            None => match connect(primary, &global_state_lock, secret).await {
+                Ok(new_connection) => {
+                    info!("Connected to primary at {primary}");
+                    replicate(connection.insert(new_connection), &mut global_state_lock).await
+                }
+                Err(e) => Err(e),
+            },
+        };
+        if let Err(e) = &result {
+            warn!("Replication from primary at {primary} failed: {e:#}");
+            connection = None;
+        }
+        global_state_lock
+            .lock_guard_mut()
+            .await
+            .replication
+            .record_sync(result);
+    }
+}
+
+/// Ask the primary at `primary` to stop spending, so that this node can be
+/// promoted.
+pub(crate) async fn demote_primary(
+    primary: SocketAddr,
+    global_state_lock: &GlobalStateLock,
+    secret: ReplicationSecret,
+) -> anyhow::Result<()> {
+    let mut connection = connect(primary, global_state_lock, secret).await?;
+    connection.send(ReplicationMessage::DemoteRequest).await?;
+    match receive(&mut connection).await? {
+        ReplicationMessage::Demoted => Ok(()),
+        ReplicationMessage::Refused(reason) => bail!("primary refused: {reason}"),
+        _ => bail!("expected demotion"),
+    }
+}
@@ -0,0 +1,377 @@
+//! Supervision of the long-running tasks spawned at startup.
+//!
+//! A supervised task fails when it returns an error or panics. Failed tasks
+//! are restarted according to their [RestartPolicy], and their states are
+//! reported over RPC as part of the node's health. A panic additionally
+//! leaves a [crash report](crate::application::crash_report) bundle, whose
+//! path is reported with the task's state.
+
+use std::any::Any;
+use std::collections::VecDeque;
+use std::panic::AssertUnwindSafe;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+use crate::application::crash_report;
+use crate::application::loops::channel::RPCServerToMain;
+use crate::GlobalStateLock;
+
+/// Time between the failure of a task and its restart.
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+
+/// The tasks that are supervised.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display)]
+pub enum SupervisedTask {
+    Miner,
+    RpcServer,
+    JsonRpcServer,
+    ReplicationServer,
+    StandbyFollower,
+}
+
+impl SupervisedTask {
+    pub(crate) fn restart_policy(self) -> RestartPolicy {
+        match self {
+            // Mining and replication are not essential to the node, so give
+            // up on them if they keep failing.
+            Self::Miner | Self::ReplicationServer | Self::StandbyFollower => {
+                RestartPolicy::Restart {
+                    max_failures: 5,
+                    window: Duration::from_secs(10 * 60),
+                }
+            }
+
+            // Without RPC servers the node cannot be controlled, so shut it
+            // down rather than let it run unattended.
+            Self::RpcServer | Self::JsonRpcServer => RestartPolicy::Escalate {
+                max_failures: 3,
+                window: Duration::from_secs(60),
+            },
+        }
+    }
+}
+
+/// What to do when a supervised task fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RestartPolicy {
+    /// Restart the task, unless it failed `max_failures` times within
+    /// `window`, in which case it is abandoned.
+    Restart {
+        max_failures: usize,
+        window: Duration,
+    },
+
+    /// Restart the task, unless it failed `max_failures` times within
+    /// `window`, in which case the node is shut down.
+    Escalate {
+        max_failures: usize,
+        window: Duration,
+    },
+}
+
+impl RestartPolicy {
+    fn failure_window(self) -> FailureWindow {
+        let (Self::Restart {
+            max_failures,
+            window,
+        }
+        | Self::Escalate {
+            max_failures,
+            window,
+        }) = self;
+
+        FailureWindow {
+            max_failures,
+            window,
+            failures: VecDeque::new(),
+        }
+    }
+}
+
+/// The recent failures of a task.
+#[derive(Debug, Clone)]
+struct FailureWindow {
+    max_failures: usize,
+    window: Duration,
+    failures: VecDeque<Instant>,
+}
+
+impl FailureWindow {
+    /// Record a failure. Returns true iff the task failed too often to be
+    /// restarted.
+    fn record(&mut self, now: Instant) -> bool {
+        while self
+            .failures
+            .front()
+            .is_some_and(|failure| now.duration_since(*failure) >= self.window)
+        {
+            self.failures.pop_front();
+        }
+        self.failures.push_back(now);
+
+        self.failures.len() >= self.max_failures
+    }
+}
+
+/// The state of a supervised task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display)]
+pub enum TaskState {
+    Running,
+
+    /// Waiting to be restarted after a failure.
+    Restarting,
+
+    /// Returned without failure, e.g. because the node is shutting down.
+    Finished,
+
+    /// Failed too often to be restarted.
+    Abandoned,
+
+    /// Failed too often to be restarted, causing the node to shut down.
+    Escalated,
+}
+
+/// Reports the state of a supervised task.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskStatus {
+    pub task: SupervisedTask,
+    pub state: TaskState,
+    pub num_restarts: u64,
+
+    /// The error or panic message of the most recent failure.
+    pub last_failure: Option<String>,
+
+    /// The crash report bundle written for the most recent failure, if that
+    /// failure was a panic.
+    pub last_crash_report: Option<PathBuf>,
+}
+
+/// The supervised tasks whose states are reported over RPC.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TaskStatusRegistry(Vec<Arc<Mutex<TaskStatus>>>);
+
+impl TaskStatusRegistry {
+    fn register(&mut self, task: SupervisedTask) -> Arc<Mutex<TaskStatus>> {
+        let status = Arc::new(Mutex::new(TaskStatus {
+            task,
+            state: TaskState::Running,
+            num_restarts: 0,
+            last_failure: None,
+            last_crash_report: None,
+        }));
+        self.0.push(status.clone());
+
+        status
+    }
+
+    pub(crate) fn statuses(&self) -> Vec<TaskStatus> {
+        self.0
+            .iter()
+            .map(|status| status.lock().unwrap().clone())
+            .collect()
+    }
+}
+
+pub(crate) fn panic_message(panic: &(dyn Any + Send)) -> String {
+    if let Some(s) = panic.downcast_ref::<&'static str>() {
+        (*s).to_string()
+    } else if let Some(s) = panic.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Spawn a task that runs the futures returned by `run`, starting a new one
+/// whenever the previous one fails, as permitted by `policy`.
+///
+/// Aborting the returned task also aborts the supervised future.
+pub(crate) async fn supervise<F>(
+    global_state_lock: &GlobalStateLock,
+    task: SupervisedTask,
+    policy: RestartPolicy,
+    mut run: F,
+) -> JoinHandle<()>
+where
+    F: FnMut() -> BoxFuture<'static, anyhow::Result<()>> + Send + 'static,
+{
+    let status = global_state_lock
+        .lock_guard_mut()
+        .await
+        .task_statuses
+        .register(task);
+    let rpc_server_to_main_tx = global_state_lock.rpc_server_to_main_tx();
+
+    tokio::spawn(async move {
+        let mut failures = policy.failure_window();
+        loop {
+            let outcome =
+                crash_report::in_subsystem(task, AssertUnwindSafe(run()).catch_unwind()).await;
+            let (failure, crash_report) = match outcome {
+                Ok(Ok(())) => {
+                    info!("{task} task finished");
+                    status.lock().unwrap().state = TaskState::Finished;
+                    return;
+                }
+                Ok(Err(e)) => (format!("{e:#}"), None),
+                Err(panic) => (panic_message(&*panic), crash_report::take_last_report()),
+            };
+            error!("{task} task failed: {failure}");
+
+            let give_up = failures.record(Instant::now());
+            let state = match (give_up, policy) {
+                (false, _) => TaskState::Restarting,
+                (true, RestartPolicy::Restart { .. }) => TaskState::Abandoned,
+                (true, RestartPolicy::Escalate { .. }) => TaskState::Escalated,
+            };
+            {
+                let mut status = status.lock().unwrap();
+                status.state = state;
+                status.last_failure = Some(failure);
+                status.last_crash_report = crash_report;
+            }
+
+            match state {
+                TaskState::Abandoned => {
+                    error!("{task} task failed too often; not restarting it");
+                    return;
+                }
+                TaskState::Escalated => {
+                    error!("{task} task failed too often; shutting down");
+                    if rpc_server_to_main_tx
+                        .send(RPCServerToMain::Shutdown)
+                        .await
+                        .is_err()
+                    {
+                        error!("could not request shutdown from main task");
+                    }
+                    return;
+                }
+                _ => {}
+            }
+
+            tokio::time::sleep(RESTART_DELAY).await;
+            warn!("restarting {task} task");
+            {
+                let mut status = status.lock().unwrap();
+                status.state = TaskState::Running;
+                status.num_restarts += 1;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+
+    use macro_rules_attr::apply;
+
+    use super::*;
+    use crate::application::config::cli_args;
+    use crate::state::wallet::wallet_entropy::WalletEntropy;
+    use crate::tests::shared::globalstate::mock_genesis_global_state;
+    use crate::tests::shared_tokio_runtime;
+
+    #[test]
+    fn only_failures_within_window_count() {
+        let window = Duration::from_secs(60);
+        let mut failures = RestartPolicy::Restart {
+            max_failures: 3,
+            window,
+        }
+        .failure_window();
+
+        let start = Instant::now();
+        assert!(!failures.record(start));
+        assert!(!failures.record(start + Duration::from_secs(10)));
+        assert!(!failures.record(start + window + Duration::from_secs(5)));
+        assert!(failures.record(start + window + Duration::from_secs(6)));
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn failing_task_is_restarted_until_abandoned() {
+        let global_state_lock =
+            mock_genesis_global_state(2, WalletEntropy::devnet_wallet(), cli_args::Args::default())
+                .await;
+        let num_runs = Arc::new(AtomicUsize::new(0));
+        let policy = RestartPolicy::Restart {
+            max_failures: 2,
+            window: Duration::from_secs(60),
+        };
+
+        let runs = num_runs.clone();
+        let supervisor = supervise(
+            &global_state_lock,
+            SupervisedTask::Miner,
+            policy,
+            move || {
+                let run = runs.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if run == 0 {
+                        panic!("boom");
+                    }
+                    anyhow::bail!("failed again")
+                }
+                .boxed()
+            },
+        )
+        .await;
+        supervisor.await.unwrap();
+
+        assert_eq!(2, num_runs.load(Ordering::SeqCst));
+        let statuses = global_state_lock
+            .lock_guard()
+            .await
+            .task_statuses
+            .statuses();
+        assert_eq!(
+            vec![TaskStatus {
+                task: SupervisedTask::Miner,
+                state: TaskState::Abandoned,
+                num_restarts: 1,
+                last_failure: Some("failed again".to_string()),
+                last_crash_report: None,
+            }],
+            statuses
+        );
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn task_that_returns_is_not_restarted() {
+        let global_state_lock =
+            mock_genesis_global_state(2, WalletEntropy::devnet_wallet(), cli_args::Args::default())
+                .await;
+        let policy = SupervisedTask::RpcServer.restart_policy();
+        let supervisor = supervise(
+            &global_state_lock,
+            SupervisedTask::RpcServer,
+            policy,
+            || async { Ok(()) }.boxed(),
+        )
+        .await;
+        supervisor.await.unwrap();
+
+        let statuses = global_state_lock
+            .lock_guard()
+            .await
+            .task_statuses
+            .statuses();
+        assert_eq!(TaskState::Finished, statuses[0].state);
+        assert_eq!(0, statuses[0].num_restarts);
+    }
+}
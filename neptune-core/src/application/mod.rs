@@ -1,8 +1,11 @@
 pub mod config;
+pub mod crash_report;
 pub mod database;
 pub mod job_queue;
 pub mod json_rpc;
 pub mod locks;
 pub mod loops;
+pub mod plugin_hooks;
 pub mod rpc;
+pub mod safe_mode;
 pub mod triton_vm_job_queue;
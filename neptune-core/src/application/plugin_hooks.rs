@@ -0,0 +1,336 @@
+//! Invocation of operator-supplied executables on node events.
+//!
+//! Each configured [`PluginHook`] names an event and an executable. When the
+//! event occurs, the executable is spawned with a JSON description of the
+//! event on its stdin. Hooks run in the background and cannot affect the
+//! node: their exit codes are only logged.
+//!
+//! Hooks are confined as far as is possible without platform-specific
+//! sandboxing: they get an environment containing only `PATH`, run in the
+//! system's temporary directory, are killed if they exceed their timeout, and
+//! only a bounded number of them runs at a time. Events that arrive while all
+//! slots are occupied are dropped, so that a slow hook cannot make the node
+//! fall behind.
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::bail;
+use anyhow::Context;
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Digest;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+use tracing::debug;
+use tracing::warn;
+
+use crate::application::config::cli_args;
+use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+use crate::state::alerts::AlertKind;
+use crate::state::transaction::transaction_kernel_id::TransactionKernelId;
+
+/// Maximum number of bytes of a hook's stderr that are logged if it fails.
+const MAX_LOGGED_HOOK_OUTPUT: u64 = 4 * 1024;
+
+/// The kinds of events that hooks can subscribe to.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    strum::Display,
+    strum::EnumString,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum HookEventKind {
+    /// A block became the new tip.
+    NewBlock,
+
+    /// The wallet received a UTXO in a block that became the new tip.
+    WalletReceive,
+
+    /// A mempool transaction was included in a block that became the new
+    /// tip.
+    TxConfirmed,
+
+    /// An own transaction was cancelled before it was broadcast.
+    TxCancelled,
+
+    /// An alert rule fired.
+    Alert,
+}
+
+/// An event passed to hooks, serialized as JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum HookEvent {
+    NewBlock {
+        block_digest: Digest,
+        height: BlockHeight,
+        timestamp: Timestamp,
+    },
+    WalletReceive {
+        block_digest: Digest,
+        height: BlockHeight,
+        aocl_leaf_index: u64,
+        amount: NativeCurrencyAmount,
+    },
+    TxConfirmed {
+        block_digest: Digest,
+        height: BlockHeight,
+        transaction_id: TransactionKernelId,
+        fee: NativeCurrencyAmount,
+    },
+    TxCancelled {
+        transaction_id: TransactionKernelId,
+        timestamp: Timestamp,
+    },
+    Alert {
+        kind: AlertKind,
+        message: String,
+        timestamp: Timestamp,
+    },
+}
+
+impl HookEvent {
+    pub fn kind(&self) -> HookEventKind {
+        match self {
+            HookEvent::NewBlock { .. } => HookEventKind::NewBlock,
+            HookEvent::WalletReceive { .. } => HookEventKind::WalletReceive,
+            HookEvent::TxConfirmed { .. } => HookEventKind::TxConfirmed,
+            HookEvent::TxCancelled { .. } => HookEventKind::TxCancelled,
+            HookEvent::Alert { .. } => HookEventKind::Alert,
+        }
+    }
+}
+
+/// An executable to run on an event, as given on the command line.
+///
+/// Parsed from `EVENT=PATH`, e.g. `new-block=/usr/local/bin/on-block`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginHook {
+    pub event: HookEventKind,
+    pub executable: PathBuf,
+}
+
+impl FromStr for PluginHook {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((event, executable)) = s.split_once('=') else {
+            return Err(format!("Expected `EVENT=PATH`, got `{s}`"));
+        };
+        let event = event
+            .trim()
+            .parse()
+            .map_err(|_| format!("Unknown event in `{s}`"))?;
+        let executable = executable.trim();
+        if executable.is_empty() {
+            return Err(format!("Missing executable in `{s}`"));
+        }
+
+        Ok(Self {
+            event,
+            executable: PathBuf::from(executable),
+        })
+    }
+}
+
+/// The configured hooks, and the limits on running them.
+#[derive(Debug, Clone)]
+pub(crate) struct PluginHooks {
+    hooks: Vec<PluginHook>,
+    timeout: Duration,
+
+    /// One permit per hook that may run at a time.
+    slots: Arc<Semaphore>,
+}
+
+impl PluginHooks {
+    pub(crate) fn new(cli: &cli_args::Args) -> Self {
+        Self {
+            hooks: cli.plugin_hooks.clone(),
+            timeout: cli.plugin_hook_timeout,
+            slots: Arc::new(Semaphore::new(cli.plugin_hook_max_concurrency.get())),
+        }
+    }
+
+    /// Whether any hook runs on events of the given kind.
+    pub(crate) fn subscribes_to(&self, kind: HookEventKind) -> bool {
+        self.hooks.iter().any(|hook| hook.event == kind)
+    }
+
+    /// Spawn the hooks that subscribe to `event`, without waiting for them to
+    /// finish.
+    pub(crate) fn dispatch(&self, event: &HookEvent) {
+        let payload = match serde_json::to_vec(event) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("Failed to serialize {} event for hooks: {e}", event.kind());
+                return;
+            }
+        };
+
+        for hook in self.hooks.iter().filter(|hook| hook.event == event.kind()) {
+            let Ok(slot) = self.slots.clone().try_acquire_owned() else {
+                warn!(
+                    "Too many hooks running; not running {} for {} event",
+                    hook.executable.display(),
+                    hook.event
+                );
+                continue;
+            };
+
+            let hook = hook.clone();
+            let payload = payload.clone();
+            let timeout = self.timeout;
+            tokio::spawn(async move {
+                match run_hook(&hook, &payload, timeout).await {
+                    Ok(()) => debug!("Hook {} succeeded", hook.executable.display()),
+                    Err(e) => warn!("Hook {} failed: {e:#}", hook.executable.display()),
+                }
+                drop(slot);
+            });
+        }
+    }
+}
+
+/// Run `hook` with `payload` on stdin, and wait for it to exit successfully.
+///
+/// The hook is killed if it does not exit within `timeout`.
+async fn run_hook(hook: &PluginHook, payload: &[u8], timeout: Duration) -> anyhow::Result<()> {
+    let mut command = Command::new(&hook.executable);
+    command.env_clear();
+    if let Some(path) = std::env::var_os("PATH") {
+        command.env("PATH", path);
+    }
+    let mut child = command
+        .current_dir(std::env::temp_dir())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .context("failed to start")?;
+    let mut stdin = child.stdin.take().context("stdin not captured")?;
+    let stderr = child.stderr.take().context("stderr not captured")?;
+
+    let run = async {
+        // A hook that does not read its stdin closes it, which is no error.
+        if let Err(e) = stdin.write_all(payload).await {
+            debug!("Could not write event to hook stdin: {e}");
+        }
+        drop(stdin);
+
+        let mut output = vec![];
+        stderr
+            .take(MAX_LOGGED_HOOK_OUTPUT)
+            .read_to_end(&mut output)
+            .await?;
+        let status = child.wait().await?;
+        anyhow::Ok((status, output))
+    };
+
+    let Ok(result) = tokio::time::timeout(timeout, run).await else {
+        bail!("timed out after {} seconds", timeout.as_secs());
+    };
+    let (status, output) = result?;
+    if !status.success() {
+        bail!("{status}: {}", String::from_utf8_lossy(&output).trim());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use macro_rules_attr::apply;
+
+    use super::*;
+    use crate::tests::shared_tokio_runtime;
+
+    #[test]
+    fn plugin_hooks_are_parsed_from_event_and_path() {
+        assert_eq!(
+            PluginHook {
+                event: HookEventKind::WalletReceive,
+                executable: PathBuf::from("/usr/local/bin/on-receive"),
+            },
+            "wallet-receive=/usr/local/bin/on-receive".parse().unwrap()
+        );
+        assert!("new-block".parse::<PluginHook>().is_err());
+        assert!("new-block=".parse::<PluginHook>().is_err());
+        assert!("new-epoch=/bin/true".parse::<PluginHook>().is_err());
+    }
+
+    #[test]
+    fn events_are_tagged_with_their_kind() {
+        let event = HookEvent::NewBlock {
+            block_digest: Digest::default(),
+            height: 1u64.into(),
+            timestamp: Timestamp::now(),
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!("new-block", json["event"]);
+        assert_eq!(HookEventKind::NewBlock, event.kind());
+    }
+
+    #[test]
+    fn only_subscribed_event_kinds_are_dispatched() {
+        let cli = cli_args::Args {
+            plugin_hooks: vec!["tx-confirmed=/bin/true".parse().unwrap()],
+            ..Default::default()
+        };
+        let hooks = PluginHooks::new(&cli);
+        assert!(hooks.subscribes_to(HookEventKind::TxConfirmed));
+        assert!(!hooks.subscribes_to(HookEventKind::NewBlock));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[apply(shared_tokio_runtime)]
+    async fn failing_and_slow_hooks_are_reported() {
+        let hook = |executable: &str| PluginHook {
+            event: HookEventKind::NewBlock,
+            executable: PathBuf::from(executable),
+        };
+        let timeout = Duration::from_secs(5);
+
+        assert!(run_hook(&hook("/bin/cat"), b"{}", timeout).await.is_ok());
+        assert!(run_hook(&hook("/bin/false"), b"{}", timeout).await.is_err());
+        assert!(run_hook(&hook("/nonexistent"), b"{}", timeout)
+            .await
+            .is_err());
+
+        let data_dir = crate::tests::shared::files::unit_test_data_directory(
+            crate::application::config::network::Network::Main,
+        )
+        .unwrap();
+        let script = data_dir.root_dir_path().join("slow_hook.sh");
+        std::fs::create_dir_all(data_dir.root_dir_path()).unwrap();
+        std::fs::write(&script, "#!/bin/sh\nsleep 10\n").unwrap();
+        let mut permissions = std::fs::metadata(&script).unwrap().permissions();
+        std::os::unix::fs::PermissionsExt::set_mode(&mut permissions, 0o755);
+        std::fs::set_permissions(&script, permissions).unwrap();
+
+        let slow = run_hook(
+            &hook(script.to_str().unwrap()),
+            b"{}",
+            Duration::from_millis(100),
+        )
+        .await;
+        assert!(slow.unwrap_err().to_string().contains("timed out"));
+    }
+}
@@ -1,2 +1,4 @@
 pub mod auth;
+pub mod request_limiter;
 pub mod server;
+pub mod spending_pin;
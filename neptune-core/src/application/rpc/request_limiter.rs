@@ -0,0 +1,353 @@
+//! Bounds on the resources consumed by RPC requests.
+//!
+//! Both the tarpc server and the JSON-RPC server route every request through a
+//! shared [RpcRequestLimiter], which enforces:
+//!
+//!  - a global limit on the number of concurrently executing requests. Requests
+//!    that arrive when the limit is reached are rejected immediately rather
+//!    than queued, such that a handful of slow calls cannot pile up unboundedly.
+//!  - a timeout per request, configurable per method.
+//!
+//! Cancellation is cooperative: when a client disconnects, the transport drops
+//! the future handling the request, which releases any state locks held by it.
+//! Such requests are counted as cancelled in the [RpcRequestMetrics].
+use std::collections::HashMap;
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tarpc::server::Serve;
+use tarpc::ServerError;
+use tokio::sync::Semaphore;
+use tracing::warn;
+
+use crate::application::config::cli_args;
+
+/// A timeout override for a single RPC method, as given on the command line.
+///
+/// Parsed from `METHOD=SECONDS`, e.g. `history=300`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RpcMethodTimeout {
+    pub method: String,
+    pub timeout: Duration,
+}
+
+impl FromStr for RpcMethodTimeout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((method, seconds)) = s.split_once('=') else {
+            return Err(format!("Expected `METHOD=SECONDS`, got `{s}`"));
+        };
+        let method = method.trim();
+        if method.is_empty() {
+            return Err(format!("Missing method name in `{s}`"));
+        }
+        let seconds = seconds
+            .trim()
+            .parse::<u64>()
+            .map_err(|e| format!("Invalid number of seconds in `{s}`: {e}"))?;
+
+        Ok(Self {
+            method: method.to_owned(),
+            timeout: Duration::from_secs(seconds),
+        })
+    }
+}
+
+/// Reason why an RPC request was not executed to completion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum RpcRequestRejection {
+    #[error("server is busy: too many concurrent requests")]
+    Busy,
+
+    #[error("request timed out after {} seconds", .0.as_secs())]
+    TimedOut(Duration),
+}
+
+impl From<RpcRequestRejection> for ServerError {
+    fn from(rejection: RpcRequestRejection) -> Self {
+        let kind = match rejection {
+            RpcRequestRejection::Busy => std::io::ErrorKind::WouldBlock,
+            RpcRequestRejection::TimedOut(_) => std::io::ErrorKind::TimedOut,
+        };
+        ServerError::new(kind, rejection.to_string())
+    }
+}
+
+/// Counters describing how RPC requests have been handled since startup.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcRequestMetrics {
+    /// Number of requests currently executing.
+    pub in_flight: usize,
+
+    /// Maximum number of requests that may execute concurrently.
+    pub max_concurrent: usize,
+
+    /// Number of requests that ran to completion.
+    pub completed: u64,
+
+    /// Number of requests rejected because the concurrency limit was reached.
+    pub rejected_busy: u64,
+
+    /// Number of requests aborted because they exceeded their timeout.
+    pub timed_out: u64,
+
+    /// Number of requests abandoned before completion, typically because the
+    /// client disconnected.
+    pub cancelled: u64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    completed: AtomicU64,
+    rejected_busy: AtomicU64,
+    timed_out: AtomicU64,
+    cancelled: AtomicU64,
+}
+
+/// Counts a request as cancelled unless disarmed before being dropped.
+struct CancellationGuard<'a> {
+    counters: &'a Counters,
+    armed: bool,
+}
+
+impl Drop for CancellationGuard<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            self.counters.cancelled.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Enforces concurrency limits and timeouts on RPC requests.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct RpcRequestLimiter {
+    permits: Arc<Semaphore>,
+    max_concurrent: usize,
+    default_timeout: Duration,
+    method_timeouts: HashMap<String, Duration>,
+    counters: Counters,
+}
+
+impl RpcRequestLimiter {
+    pub fn new(
+        max_concurrent: usize,
+        default_timeout: Duration,
+        method_timeouts: impl IntoIterator<Item = RpcMethodTimeout>,
+    ) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(max_concurrent)),
+            max_concurrent,
+            default_timeout,
+            method_timeouts: method_timeouts
+                .into_iter()
+                .map(|t| (t.method, t.timeout))
+                .collect(),
+            counters: Counters::default(),
+        }
+    }
+
+    /// Instantiate a limiter configured from the command-line arguments.
+    pub fn from_cli(cli: &cli_args::Args) -> Self {
+        Self::new(
+            cli.rpc_max_concurrent_requests,
+            cli.rpc_request_timeout,
+            cli.rpc_method_timeouts.clone(),
+        )
+    }
+
+    /// The timeout that applies to the given method.
+    ///
+    /// tarpc method names are prefixed with the service name, e.g.
+    /// `RPC.history`. Overrides may be given either with or without that
+    /// prefix.
+    pub fn timeout_for(&self, method: &str) -> Duration {
+        let unqualified = method
+            .split_once('.')
+            .map(|(_service, name)| name)
+            .unwrap_or(method);
+
+        self.method_timeouts
+            .get(method)
+            .or_else(|| self.method_timeouts.get(unqualified))
+            .copied()
+            .unwrap_or(self.default_timeout)
+    }
+
+    /// Execute the request handler `fut` for `method` within the limits.
+    ///
+    /// If `fut` is dropped before it completes, the request is counted as
+    /// cancelled.
+    pub async fn run<F: Future>(
+        &self,
+        method: &str,
+        fut: F,
+    ) -> Result<F::Output, RpcRequestRejection> {
+        let Ok(_permit) = self.permits.clone().try_acquire_owned() else {
+            self.counters.rejected_busy.fetch_add(1, Ordering::Relaxed);
+            warn!("Rejecting RPC request {method}: concurrency limit reached");
+            return Err(RpcRequestRejection::Busy);
+        };
+
+        let mut guard = CancellationGuard {
+            counters: &self.counters,
+            armed: true,
+        };
+        let timeout = self.timeout_for(method);
+        let result = tokio::time::timeout(timeout, fut).await;
+        guard.armed = false;
+
+        match result {
+            Ok(output) => {
+                self.counters.completed.fetch_add(1, Ordering::Relaxed);
+                Ok(output)
+            }
+            Err(_) => {
+                self.counters.timed_out.fetch_add(1, Ordering::Relaxed);
+                warn!(
+                    "RPC request {method} timed out after {} seconds",
+                    timeout.as_secs()
+                );
+                Err(RpcRequestRejection::TimedOut(timeout))
+            }
+        }
+    }
+
+    /// A snapshot of the request counters.
+    pub fn metrics(&self) -> RpcRequestMetrics {
+        RpcRequestMetrics {
+            in_flight: self.max_concurrent - self.permits.available_permits(),
+            max_concurrent: self.max_concurrent,
+            completed: self.counters.completed.load(Ordering::Relaxed),
+            rejected_busy: self.counters.rejected_busy.load(Ordering::Relaxed),
+            timed_out: self.counters.timed_out.load(Ordering::Relaxed),
+            cancelled: self.counters.cancelled.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A tarpc [Serve] wrapper that executes every request through an
+/// [RpcRequestLimiter].
+#[derive(Debug, Clone)]
+pub struct LimitedServe<S> {
+    inner: S,
+    limiter: Arc<RpcRequestLimiter>,
+}
+
+impl<S> LimitedServe<S> {
+    pub fn new(inner: S, limiter: Arc<RpcRequestLimiter>) -> Self {
+        Self { inner, limiter }
+    }
+}
+
+impl<S: Serve> Serve for LimitedServe<S> {
+    type Req = S::Req;
+    type Resp = S::Resp;
+
+    async fn serve(
+        self,
+        ctx: tarpc::context::Context,
+        req: Self::Req,
+    ) -> Result<Self::Resp, ServerError> {
+        let method = self.inner.method(&req).unwrap_or("unknown");
+        self.limiter.run(method, self.inner.serve(ctx, req)).await?
+    }
+
+    fn method(&self, request: &Self::Req) -> Option<&'static str> {
+        self.inner.method(request)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use futures::future::pending;
+    use macro_rules_attr::apply;
+
+    use super::*;
+    use crate::tests::shared_tokio_runtime;
+
+    #[test]
+    fn method_timeout_parses() {
+        assert_eq!(
+            RpcMethodTimeout {
+                method: "history".to_owned(),
+                timeout: Duration::from_secs(300),
+            },
+            "history=300".parse().unwrap()
+        );
+        assert!("history".parse::<RpcMethodTimeout>().is_err());
+        assert!("=5".parse::<RpcMethodTimeout>().is_err());
+        assert!("history=soon".parse::<RpcMethodTimeout>().is_err());
+    }
+
+    #[test]
+    fn method_timeouts_match_with_and_without_service_prefix() {
+        let limiter =
+            RpcRequestLimiter::new(1, Duration::from_secs(10), ["history=300".parse().unwrap()]);
+        assert_eq!(Duration::from_secs(300), limiter.timeout_for("history"));
+        assert_eq!(Duration::from_secs(300), limiter.timeout_for("RPC.history"));
+        assert_eq!(
+            Duration::from_secs(10),
+            limiter.timeout_for("RPC.block_height")
+        );
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn rejects_requests_beyond_concurrency_limit() {
+        let limiter = Arc::new(RpcRequestLimiter::new(1, Duration::from_secs(10), []));
+        let (release_tx, release_rx) = tokio::sync::oneshot::channel::<()>();
+
+        let limiter_clone = limiter.clone();
+        let slow = tokio::spawn(async move { limiter_clone.run("slow", release_rx).await });
+        while limiter.metrics().in_flight == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        assert_eq!(
+            Err(RpcRequestRejection::Busy),
+            limiter.run("fast", async {}).await
+        );
+
+        release_tx.send(()).unwrap();
+        assert!(slow.await.unwrap().is_ok());
+        assert!(limiter.run("fast", async {}).await.is_ok());
+
+        let metrics = limiter.metrics();
+        assert_eq!(0, metrics.in_flight);
+        assert_eq!(2, metrics.completed);
+        assert_eq!(1, metrics.rejected_busy);
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn slow_requests_time_out() {
+        let limiter = RpcRequestLimiter::new(1, Duration::from_millis(10), []);
+        assert_eq!(
+            Err(RpcRequestRejection::TimedOut(Duration::from_millis(10))),
+            limiter.run("never", pending::<()>()).await
+        );
+        assert_eq!(1, limiter.metrics().timed_out);
+        assert_eq!(0, limiter.metrics().in_flight);
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn dropped_requests_count_as_cancelled() {
+        let limiter = RpcRequestLimiter::new(1, Duration::from_secs(10), []);
+        let request = limiter.run("abandoned", pending::<()>());
+        let _ = tokio::time::timeout(Duration::from_millis(10), request).await;
+
+        let metrics = limiter.metrics();
+        assert_eq!(1, metrics.cancelled);
+        assert_eq!(0, metrics.in_flight);
+        assert_eq!(0, metrics.completed);
+    }
+}
@@ -43,10 +43,16 @@
 //!
 //! Every RPC method returns an [RpcResult] which is wrapped inside a
 //! [tarpc::Response] by the rpc server.
+pub mod address_validation;
 pub mod coinbase_output_readable;
+pub mod diagnostics;
+pub mod fork_readiness;
+pub mod mempool_acceptance;
 pub mod mempool_transaction_info;
+pub mod node_health;
 pub mod overview_data;
 pub mod proof_of_work_puzzle;
+pub mod transaction_verification;
 pub mod ui_utxo;
 
 use std::collections::HashMap;
@@ -54,9 +60,11 @@ use std::collections::HashSet;
 use std::net::IpAddr;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::SystemTime;
 
 use anyhow::anyhow;
 use anyhow::Result;
+use futures::future::BoxFuture;
 use get_size2::GetSize;
 use itertools::Itertools;
 use num_traits::Zero;
@@ -65,6 +73,8 @@ use serde::Serialize;
 use systemstat::Platform;
 use systemstat::System;
 use tarpc::context;
+use tarpc::server::Serve;
+use tasm_lib::triton_vm::prelude::BFieldElement;
 use tasm_lib::twenty_first::prelude::Mmr;
 use tasm_lib::twenty_first::tip5::digest::Digest;
 use tracing::debug;
@@ -74,22 +84,52 @@ use tracing::warn;
 
 use super::auth;
 use crate::api;
+use crate::api::amount::error::AmountError;
+use crate::api::amount::DenominatedAmount;
+use crate::api::amount::Denomination;
+use crate::api::amount::ValidatedAmount;
+use crate::api::chain::error::ChainError;
+use crate::api::chain::ChainExportReport;
+use crate::api::chain::ChainWork;
+use crate::api::chain::ForkChoiceAudit;
 use crate::api::tx_initiation;
 use crate::api::tx_initiation::builder::tx_input_list_builder::InputSelectionPolicy;
 use crate::api::tx_initiation::builder::tx_output_list_builder::OutputFormat;
+use crate::api::tx_initiation::planner::TransferPlan;
+use crate::api::tx_initiation::raw_transaction::RawTransactionSpec;
+use crate::api::wallet::HistoricalBalance;
+use crate::api::wallet::UtxoMemos;
 use crate::application::config::network::Network;
+use crate::application::config::retention_policy::CleanupReport;
+use crate::application::database::read_cache_budget::TableReadCacheMetrics;
 use crate::application::database::storage::storage_vec::traits::StorageVecBase;
+use crate::application::loops::channel::backpressure::ChannelMetrics;
 use crate::application::loops::channel::ClaimUtxoData;
 use crate::application::loops::channel::RPCServerToMain;
+use crate::application::loops::main_loop::message_latency::MainLoopStats;
 use crate::application::loops::main_loop::proof_upgrader::UpgradeJob;
 use crate::application::loops::mine_loop::coinbase_distribution::CoinbaseDistribution;
+use crate::application::loops::replication_loop;
+use crate::application::rpc::request_limiter::RpcRequestLimiter;
+use crate::application::rpc::request_limiter::RpcRequestMetrics;
+use crate::application::rpc::server::address_validation::AddressVerdict;
+use crate::application::rpc::server::address_validation::MAX_ADDRESSES_PER_VALIDATION;
 use crate::application::rpc::server::coinbase_output_readable::CoinbaseOutputReadable;
+use crate::application::rpc::server::diagnostics::DiagnosticsBundle;
 use crate::application::rpc::server::error::RpcError;
+use crate::application::rpc::server::fork_readiness::ForkReadinessReport;
+use crate::application::rpc::server::mempool_acceptance::MempoolAcceptanceReport;
 use crate::application::rpc::server::mempool_transaction_info::MempoolTransactionInfo;
+use crate::application::rpc::server::node_health::NodeHealth;
 use crate::application::rpc::server::overview_data::OverviewData;
 use crate::application::rpc::server::proof_of_work_puzzle::ProofOfWorkPuzzle;
+use crate::application::rpc::server::transaction_verification::TransactionVerificationReport;
 use crate::application::rpc::server::ui_utxo::UiUtxo;
 use crate::application::rpc::server::ui_utxo::UtxoStatusEvent;
+use crate::application::rpc::spending_pin::SpendingPinChallenge;
+use crate::application::rpc::spending_pin::SpendingPinResponse;
+use crate::application::triton_vm_job_queue::vm_job_queue;
+use crate::application::triton_vm_job_queue::TritonVmJobPriority;
 use crate::macros::fn_name;
 use crate::macros::log_slow_scope;
 use crate::protocol::consensus::block::block_header::BlockHeader;
@@ -100,43 +140,95 @@ use crate::protocol::consensus::block::block_kernel::BlockKernel;
 use crate::protocol::consensus::block::block_selector::BlockSelector;
 use crate::protocol::consensus::block::difficulty_control::Difficulty;
 use crate::protocol::consensus::block::Block;
+use crate::protocol::consensus::chain_parameters::ChainParameters;
+use crate::protocol::consensus::consensus_rule_set::ConsensusRuleSet;
 use crate::protocol::consensus::transaction::announcement::Announcement;
 use crate::protocol::consensus::transaction::transaction_kernel::TransactionKernel;
 use crate::protocol::consensus::transaction::transaction_proof::TransactionProofType;
+use crate::protocol::consensus::transaction::utxo_triple::UtxoTriple;
 use crate::protocol::consensus::transaction::Transaction;
 use crate::protocol::consensus::transaction::TransactionProof;
 use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
+use crate::protocol::peer::bandwidth::BandwidthReport;
+use crate::protocol::peer::connection_lifecycle::ConnectionLifecycleMetrics;
 use crate::protocol::peer::peer_info::PeerInfo;
 use crate::protocol::peer::InstanceId;
 use crate::protocol::peer::PeerStanding;
 use crate::protocol::proof_abstractions::timestamp::Timestamp;
+use crate::state::alerts::Alert;
+use crate::state::alerts::AlertKind;
+use crate::state::archival_state::fork_pruning::ForkPruningStatus;
+use crate::state::block_application_progress::BlockApplicationProgress;
+use crate::state::block_application_progress::MAX_PROGRESS_WAIT;
+use crate::state::block_validation_cache::BlockValidationCacheMetrics;
+use crate::state::checkpoint::SignedCheckpoint;
+use crate::state::database::ForkPruningRecord;
+use crate::state::event_log::SequencedEvent;
+use crate::state::mempool::fee_histogram::MempoolFeeSummary;
+use crate::state::mempool::mempool_journal::MempoolDiff;
+use crate::state::mempool::mempool_journal::MempoolDiffToken;
+use crate::state::mempool::zero_conf_risk::ZeroConfRisk;
+use crate::state::mempool::zero_conf_risk::ZeroConfRiskFactors;
 use crate::state::mining::mining_state::MAX_NUM_EXPORTED_BLOCK_PROPOSAL_STORED;
+use crate::state::mining::mining_status::MiningStatus;
+use crate::state::mining::mining_status::MiningStatusReport;
+use crate::state::mining::proposal_verdict::ProposalVerdict;
+use crate::state::mutator_set_statistics::MutatorSetStatistics;
+use crate::state::proof_upgrade_schedule::ProofUpgradeSchedule;
+use crate::state::proof_upgrade_schedule::ProofUpgradeScheduleStatus;
+use crate::state::release_manifest::UpdateStatus;
+use crate::state::replication::ReplicationRole;
+use crate::state::replication::ReplicationSecret;
+use crate::state::replication::ReplicationStatus;
+use crate::state::sync_status::SyncStatus;
 use crate::state::transaction::transaction_details::TransactionDetails;
 use crate::state::transaction::transaction_kernel_id::TransactionKernelId;
 use crate::state::transaction::tx_creation_artifacts::TxCreationArtifacts;
 use crate::state::wallet::address::encrypted_utxo_notification::EncryptedUtxoNotification;
 use crate::state::wallet::address::KeyType;
+use crate::state::wallet::address::PaymentRequest;
+use crate::state::wallet::address::PaymentRequestData;
 use crate::state::wallet::address::ReceivingAddress;
 use crate::state::wallet::address::SpendingKey;
+use crate::state::wallet::address::SymmetricAddressData;
 use crate::state::wallet::change_policy::ChangePolicy;
 use crate::state::wallet::coin_with_possible_timelock::CoinWithPossibleTimeLock;
+use crate::state::wallet::composer_payout::ComposerPayout;
+use crate::state::wallet::composer_payout::ProposedComposerPayout;
+use crate::state::wallet::derivation_vectors::DerivationVectors;
 use crate::state::wallet::expected_utxo::UtxoNotifier;
 use crate::state::wallet::incoming_utxo::IncomingUtxo;
 use crate::state::wallet::monitored_utxo::MonitoredUtxo;
+use crate::state::wallet::reserve_report::ReserveReport;
+use crate::state::wallet::reserve_report::SignedReserveReport;
+use crate::state::wallet::symmetric_key_rotation::SymmetricKeyRotationStatus;
 use crate::state::wallet::transaction_input::TxInputList;
 use crate::state::wallet::transaction_output::TxOutputList;
+use crate::state::wallet::utxo_ownership_proof::UtxoOwnershipProof;
+use crate::state::wallet::wallet_backup::WalletBackupStatus;
+use crate::state::wallet::wallet_entropy::WalletEntropy;
+use crate::state::wallet::wallet_stats::WalletStats;
 use crate::state::wallet::wallet_status::WalletStatus;
+use crate::state::watch_list::WatchEntry;
+use crate::state::watch_list::WatchItem;
+use crate::state::watch_list::WatchMatch;
 use crate::state::GlobalState;
 use crate::state::GlobalStateLock;
 use crate::twenty_first::prelude::Tip5;
 use crate::util_types::mutator_set::addition_record::AdditionRecord;
 use crate::util_types::mutator_set::archival_mutator_set::ResponseMsMembershipProofPrivacyPreserving;
+use crate::util_types::mutator_set::membership_bundle::MembershipBundle;
+use crate::util_types::mutator_set::membership_bundle::MAX_MEMBERSHIP_BUNDLE_SEARCH_DEPTH;
+use crate::util_types::mutator_set::membership_bundle::MAX_MEMBERSHIP_BUNDLE_SIZE;
 use crate::util_types::mutator_set::removal_record::absolute_index_set::AbsoluteIndexSet;
 use crate::DataDirectory;
 
 /// result returned by RPC methods
 pub type RpcResult<T> = Result<T, error::RpcError>;
 
+/// Maximum number of calls in a single [RPC::batch].
+pub const MAX_RPC_BATCH_SIZE: usize = 100;
+
 #[tarpc::service]
 pub trait RPC {
     /******** READ DATA ********/
@@ -201,6 +293,43 @@ pub trait RPC {
     /// ```
     async fn network() -> RpcResult<Network>;
 
+    /// Return all consensus constants in effect on the network this
+    /// neptune-core instance is running, such as the maximum block size under
+    /// each rule set, the target block interval, and the subsidy schedule.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // query the consensus constants, and the limits in effect at the tip
+    /// let parameters = client.chain_parameters(context::current(), token).await??;
+    /// let tip_height = client.block_height(context::current(), token).await??;
+    /// let limits = parameters.rule_set_at(tip_height);
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn chain_parameters(token: auth::Token) -> RpcResult<ChainParameters>;
+
     /// Returns local socket used for incoming peer-connections. Does not show
     /// the public IP address, as the client does not know this.
     ///
@@ -303,6 +432,99 @@ pub trait RPC {
     /// ```
     async fn block_height(token: auth::Token) -> RpcResult<BlockHeight>;
 
+    /// Return the progress of applying blocks, eg while syncing from peers.
+    ///
+    /// If `after_sequence` is given, waits until there is progress newer than
+    /// the one with that [`sequence`] number, for up to 30 seconds. Passing
+    /// the sequence number of each response to the next call yields a stream
+    /// of updates, suitable for dashboards.
+    ///
+    /// [`sequence`]: BlockApplicationProgress::sequence
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // follow the progress of applying blocks.
+    /// let mut sequence = None;
+    /// loop {
+    ///     let progress = client
+    ///         .block_application_progress(context::current(), token, sequence)
+    ///         .await??;
+    ///     println!(
+    ///         "height {}, {:.1} blocks/s",
+    ///         progress.current_height, progress.blocks_per_second
+    ///     );
+    ///     sequence = Some(progress.sequence);
+    /// }
+    /// # }
+    /// ```
+    async fn block_application_progress(
+        token: auth::Token,
+        after_sequence: Option<u64>,
+    ) -> RpcResult<BlockApplicationProgress>;
+
+    /// Return the progress of catching up with the network, stage by stage:
+    /// learning the tip to sync towards, downloading blocks, updating the
+    /// wallet, and updating the mempool's transactions. Each stage reports
+    /// its progress towards a target, an estimated completion time if known,
+    /// and the peers currently serving it.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // query the progress of catching up
+    /// let status = client.sync_status(context::current(), token).await??;
+    /// match status.current_stage() {
+    ///     Some(stage) => println!("syncing: {stage}"),
+    ///     None => println!("synced"),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn sync_status(token: auth::Token) -> RpcResult<SyncStatus>;
+
     /// Return the guesser reward of the most favorable block proposal
     ///
     /// Returns None if no proposal is known building on the current tip.
@@ -521,6 +743,27 @@ pub trait RPC {
         index_sets: Vec<AbsoluteIndexSet>,
     ) -> RpcResult<ResponseMsMembershipProofPrivacyPreserving>;
 
+    /// Produce a [`MembershipBundle`] for up to
+    /// [`MAX_MEMBERSHIP_BUNDLE_SIZE`] addition records: the tip's mutator set
+    /// accumulator along with the AOCL authentication paths of the UTXOs and
+    /// the inactive Bloom filter chunks their removal records may touch.
+    ///
+    /// Allows a wallet-only node that learned of its UTXOs out-of-band to
+    /// become spend-capable without syncing block bodies: it assembles the
+    /// membership proofs with [`MembershipBundle::membership_proof`] and
+    /// imports them. Caller reveals which addition records it is interested
+    /// in, so this endpoint is intended for an archival node caller trusts.
+    ///
+    /// Only the [`MAX_MEMBERSHIP_BUNDLE_SEARCH_DEPTH`] most recent AOCL leafs
+    /// are searched. Addition records that are not among them are listed as
+    /// missing. Requires an archival mutator set.
+    ///
+    /// The bundle is imported with [`Self::import_membership_bundle`].
+    async fn utxo_membership_bundle(
+        token: auth::Token,
+        addition_records: Vec<AdditionRecord>,
+    ) -> RpcResult<MembershipBundle>;
+
     /// Return the announements contained in a specified block.
     ///
     /// Returns `None` if the selected block could not be found, otherwise
@@ -890,6 +1133,11 @@ pub trait RPC {
     /// Note that by default `KeyType::Symmetric` is used for change outputs
     /// and block rewards.
     ///
+    /// If the node was started with a symmetric-key rotation policy, ie with
+    /// `--symmetric-key-max-uses` or `--symmetric-key-max-age`, requesting a
+    /// `KeyType::Symmetric` address returns the active symmetric key until it
+    /// is rotated. See `symmetric_key_rotation_status`.
+    ///
     /// If in any doubt, just use [KeyType::Generation].
     ///
     /// ```no_run
@@ -930,7 +1178,14 @@ pub trait RPC {
         key_type: KeyType,
     ) -> RpcResult<ReceivingAddress>;
 
-    /// Return all known keys, for every [KeyType]
+    /// Get the status of the rotation of the symmetric key used for receiving
+    /// funds, eg for change outputs.
+    ///
+    /// Reports the configured rotation policy, the derivation index, number
+    /// of uses, and age of the active key, and whether it is due for rotation.
+    /// Without a policy, a new symmetric key is derived every time one is
+    /// needed, and there is no active key. Retired keys are still scanned for
+    /// incoming UTXOs.
     ///
     /// ```no_run
     /// # use anyhow::Result;
@@ -956,18 +1211,35 @@ pub trait RPC {
     /// # // load the cookie file from disk and assign it to a token
     /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
     /// #
-    /// // query neptune-core server to get all known keys for every [KeyType]
-    /// let known_keys = client.known_keys(context::current(), token ).await??;
+    /// // query neptune-core server for the state of symmetric-key rotation
+    /// let status = client.symmetric_key_rotation_status(context::current(), token).await??;
     /// # Ok(())
     /// # }
     /// ```
-    async fn known_keys(token: auth::Token) -> RpcResult<Vec<SpendingKey>>;
+    async fn symmetric_key_rotation_status(
+        token: auth::Token,
+    ) -> RpcResult<SymmetricKeyRotationStatus>;
 
-    /// Return known keys for the provided [KeyType]
+    /// Hand out a symmetric-key address for receiving funds sent by this
+    /// wallet to itself, and return the data needed to display it.
+    ///
+    /// Like [next_receiving_address](Self::next_receiving_address) with
+    /// [KeyType::Symmetric], this respects the symmetric-key rotation policy.
+    /// Outputs to symmetric-key addresses take less blockchain space than
+    /// outputs to generation addresses, so they are the cheaper choice for
+    /// self-transfers. The returned
+    /// [`address`](crate::state::wallet::address::SymmetricAddressData::address)
+    /// can be passed to [send](Self::send) like any other address, and
+    /// off-chain notifications for it can be claimed with
+    /// [claim_utxo](Self::claim_utxo).
+    ///
+    /// Security: anyone knowing the address can spend the funds sent to it.
+    /// Never share it; show the
+    /// [`display_address`](crate::state::wallet::address::SymmetricAddressData::display_address)
+    /// instead.
     ///
     /// ```no_run
     /// # use anyhow::Result;
-    /// use neptune_cash::state::wallet::address::KeyType;
     /// # use neptune_cash::application::rpc::server::RPCClient;
     /// # use neptune_cash::application::rpc::auth;
     /// # use tarpc::tokio_serde::formats::Json;
@@ -990,20 +1262,20 @@ pub trait RPC {
     /// # // load the cookie file from disk and assign it to a token
     /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
     /// #
-    /// // set a key type
-    /// let key_type = KeyType::Symmetric;
-    ///
-    /// // query neptune-core server to get all known keys by [KeyType]
-    /// let known_keys_by_keytype = client.known_keys_by_keytype(context::current(), token, key_type ).await??;
+    /// // query neptune-core server for a symmetric-key address
+    /// let address_data = client.next_symmetric_address(context::current(), token).await??;
+    /// println!("{}", address_data.display_address);
     /// # Ok(())
     /// # }
     /// ```
-    async fn known_keys_by_keytype(
-        token: auth::Token,
-        key_type: KeyType,
-    ) -> RpcResult<Vec<SpendingKey>>;
+    async fn next_symmetric_address(token: auth::Token) -> RpcResult<SymmetricAddressData>;
 
-    /// Return the number of transactions in the mempool
+    /// Return the data needed to display every symmetric-key address known to
+    /// the wallet, in the order of derivation. All of them are scanned for
+    /// incoming UTXOs.
+    ///
+    /// See [next_symmetric_address](Self::next_symmetric_address) for the
+    /// security considerations.
     ///
     /// ```no_run
     /// # use anyhow::Result;
@@ -1029,22 +1301,26 @@ pub trait RPC {
     /// # // load the cookie file from disk and assign it to a token
     /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
     /// #
-    /// // query neptune-core server to get the number of transactions in the mempool
-    /// let mempool_tx_count = client.mempool_tx_count(context::current(), token ).await??;
+    /// // query neptune-core server for all symmetric-key addresses
+    /// let addresses = client.symmetric_addresses(context::current(), token).await??;
     /// # Ok(())
     /// # }
     /// ```
-    async fn mempool_tx_count(token: auth::Token) -> RpcResult<usize>;
-
-    // TODO: Change to return current size and max size
-    async fn mempool_size(token: auth::Token) -> RpcResult<usize>;
+    async fn symmetric_addresses(token: auth::Token) -> RpcResult<Vec<SymmetricAddressData>>;
 
-    async fn mempool_tx_ids(token: auth::Token) -> RpcResult<Vec<TransactionKernelId>>;
-
-    /// Return info about the transactions in the mempool
+    /// Generate a new generation address and return the data needed to
+    /// display a request for payment to it: the bech32m address, a payment URI,
+    /// and a payload to render as a QR code.
+    ///
+    /// If `amount` is set, the URI and QR payload request that amount. Fails,
+    /// without generating an address, if it is not positive.
+    ///
+    /// See [PaymentRequest](crate::state::wallet::address::PaymentRequest) for
+    /// the URI format.
     ///
     /// ```no_run
     /// # use anyhow::Result;
+    /// use neptune_cash::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
     /// # use neptune_cash::application::rpc::server::RPCClient;
     /// # use neptune_cash::application::rpc::auth;
     /// # use tarpc::tokio_serde::formats::Json;
@@ -1067,30 +1343,21 @@ pub trait RPC {
     /// # // load the cookie file from disk and assign it to a token
     /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
     /// #
-    /// // index to start from in the mempool
-    /// let start_index : usize = 37;
+    /// // request payment of 10 coins
+    /// let amount = Some(NativeCurrencyAmount::coins(10));
     ///
-    /// // number of transactions
-    /// let number : usize = 8;
-    ///
-    /// // query neptune-core server to get the info of transactions in the mempool
-    /// let mempool_overview = client.mempool_overview(context::current(), token, start_index, number ).await??;
+    /// // query neptune-core server to get a payment request for a new address
+    /// let payment_request = client.next_payment_request(context::current(), token, amount).await??;
+    /// println!("{}", payment_request.uri);
     /// # Ok(())
     /// # }
     /// ```
-    async fn mempool_overview(
-        token: auth::Token,
-        start_index: usize,
-        number: usize,
-    ) -> RpcResult<Vec<MempoolTransactionInfo>>;
-
-    /// Return transaction kernel by id if found in mempool.
-    async fn mempool_tx_kernel(
+    async fn next_payment_request(
         token: auth::Token,
-        tx_kernel_id: TransactionKernelId,
-    ) -> RpcResult<Option<TransactionKernel>>;
+        amount: Option<NativeCurrencyAmount>,
+    ) -> RpcResult<PaymentRequestData>;
 
-    /// Return the information used on the dashboard's overview tab
+    /// Return all known keys, for every [KeyType]
     ///
     /// ```no_run
     /// # use anyhow::Result;
@@ -1116,18 +1383,18 @@ pub trait RPC {
     /// # // load the cookie file from disk and assign it to a token
     /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
     /// #
-    /// // query neptune-core server to get the info used on dashboard overview tab
-    /// let dashboard_data = client.dashboard_overview_data(context::current(), token).await??;
+    /// // query neptune-core server to get all known keys for every [KeyType]
+    /// let known_keys = client.known_keys(context::current(), token ).await??;
     /// # Ok(())
     /// # }
     /// ```
-    async fn dashboard_overview_data(token: auth::Token) -> RpcResult<OverviewData>;
+    async fn known_keys(token: auth::Token) -> RpcResult<Vec<SpendingKey>>;
 
-    /// Determine whether the user-supplied string is a valid address
+    /// Return known keys for the provided [KeyType]
     ///
     /// ```no_run
     /// # use anyhow::Result;
-    /// use neptune_cash::application::config::network::Network;
+    /// use neptune_cash::state::wallet::address::KeyType;
     /// # use neptune_cash::application::rpc::server::RPCClient;
     /// # use neptune_cash::application::rpc::auth;
     /// # use tarpc::tokio_serde::formats::Json;
@@ -1150,24 +1417,20 @@ pub trait RPC {
     /// # // load the cookie file from disk and assign it to a token
     /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
     /// #
-    /// // address to validate
-    /// let address : String = "0x484389349834834DF23".to_string();
-    ///
-    /// // network type
-    /// let network : Network = Network::Main;
+    /// // set a key type
+    /// let key_type = KeyType::Symmetric;
     ///
-    /// // query neptune-core server to check if the supplied address is valid
-    /// let is_valid_address = client.validate_address(context::current(), token, address, network).await??;
+    /// // query neptune-core server to get all known keys by [KeyType]
+    /// let known_keys_by_keytype = client.known_keys_by_keytype(context::current(), token, key_type ).await??;
     /// # Ok(())
     /// # }
     /// ```
-    async fn validate_address(
+    async fn known_keys_by_keytype(
         token: auth::Token,
-        address: String,
-        network: Network,
-    ) -> RpcResult<Option<ReceivingAddress>>;
+        key_type: KeyType,
+    ) -> RpcResult<Vec<SpendingKey>>;
 
-    /// Determine whether the user-supplied string is a valid amount
+    /// Return the number of transactions in the mempool
     ///
     /// ```no_run
     /// # use anyhow::Result;
@@ -1193,24 +1456,22 @@ pub trait RPC {
     /// # // load the cookie file from disk and assign it to a token
     /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
     /// #
-    /// // address to validate
-    /// let amount : String = "132".to_string();
-    ///
-    /// // query neptune-core server to determine if the amount is valid
-    /// let is_valid_address = client.validate_amount(context::current(), token, amount ).await??;
+    /// // query neptune-core server to get the number of transactions in the mempool
+    /// let mempool_tx_count = client.mempool_tx_count(context::current(), token ).await??;
     /// # Ok(())
     /// # }
     /// ```
-    async fn validate_amount(
-        token: auth::Token,
-        amount: String,
-    ) -> RpcResult<Option<NativeCurrencyAmount>>;
+    async fn mempool_tx_count(token: auth::Token) -> RpcResult<usize>;
 
-    /// Determine whether the given amount is less than (or equal to) the balance
+    // TODO: Change to return current size and max size
+    async fn mempool_size(token: auth::Token) -> RpcResult<usize>;
+
+    async fn mempool_tx_ids(token: auth::Token) -> RpcResult<Vec<TransactionKernelId>>;
+
+    /// Return info about the transactions in the mempool
     ///
     /// ```no_run
     /// # use anyhow::Result;
-    /// use neptune_cash::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
     /// # use neptune_cash::application::rpc::server::RPCClient;
     /// # use neptune_cash::application::rpc::auth;
     /// # use tarpc::tokio_serde::formats::Json;
@@ -1233,20 +1494,33 @@ pub trait RPC {
     /// # // load the cookie file from disk and assign it to a token
     /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
     /// #
-    /// // setting the amount to 47
-    /// let amount : NativeCurrencyAmount = NativeCurrencyAmount::coins(47);
+    /// // index to start from in the mempool
+    /// let start_index : usize = 37;
     ///
-    /// // query neptune-core server to determine if the amount is less than or equal to the balance
-    /// let amount_less_or_equals_balance = client.amount_leq_confirmed_available_balance(context::current(), token, amount ).await??;
+    /// // number of transactions
+    /// let number : usize = 8;
+    ///
+    /// // query neptune-core server to get the info of transactions in the mempool
+    /// let mempool_overview = client.mempool_overview(context::current(), token, start_index, number ).await??;
     /// # Ok(())
     /// # }
     /// ```
-    async fn amount_leq_confirmed_available_balance(
+    async fn mempool_overview(
         token: auth::Token,
-        amount: NativeCurrencyAmount,
-    ) -> RpcResult<bool>;
+        start_index: usize,
+        number: usize,
+    ) -> RpcResult<Vec<MempoolTransactionInfo>>;
 
-    /// Generate a report of all owned and unspent coins, whether time-locked or not.
+    /// Summarize the mempool as a histogram over fee density, and forecast
+    /// the revenue of the next block if it were composed now from the most
+    /// fee-dense transactions, for each of the given block size cutoffs in
+    /// bytes.
+    ///
+    /// Helps deciding whether to compose now or to wait for more fees. The
+    /// summary is maintained as transactions enter and leave the mempool, so
+    /// it is cheap to request. The forecasts are upper bounds, as they ignore
+    /// whether transactions are synced to the tip and backed by single
+    /// proofs.
     ///
     /// ```no_run
     /// # use anyhow::Result;
@@ -1272,15 +1546,27 @@ pub trait RPC {
     /// # // load the cookie file from disk and assign it to a token
     /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
     /// #
-    /// // query neptune-core server to get the list of owned and unspent coins
-    /// let own_coins = client.list_own_coins(context::current(), token ).await??;
+    /// // forecast revenue for blocks of 100 kB and 1 MB
+    /// let size_cutoffs = vec![100_000, 1_000_000];
+    ///
+    /// // query neptune-core server for the fee summary
+    /// let fee_summary = client.mempool_fee_summary(context::current(), token, size_cutoffs).await??;
     /// # Ok(())
     /// # }
     /// ```
-    async fn list_own_coins(token: auth::Token) -> RpcResult<Vec<CoinWithPossibleTimeLock>>;
+    async fn mempool_fee_summary(
+        token: auth::Token,
+        size_cutoffs: Vec<usize>,
+    ) -> RpcResult<MempoolFeeSummary>;
 
-    /// Generate a list of all UTXOs, currently owned, historical, time-locked,
-    /// not, abandoned.
+    /// List the transactions that entered and left the mempool since the
+    /// previous poll, along with the mempool's size and total fees.
+    ///
+    /// Pass `None` on the first poll, and afterwards the token of the
+    /// previous diff. Cheaper than polling the full mempool, as only changed
+    /// transaction ids are sent. If the token is too old, or stems from
+    /// before a restart of the node, the diff lists the full mempool and is
+    /// marked as a reset.
     ///
     /// ```no_run
     /// # use anyhow::Result;
@@ -1306,19 +1592,52 @@ pub trait RPC {
     /// # // load the cookie file from disk and assign it to a token
     /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
     /// #
-    /// // query neptune-core server to get the list of UTXOs
-    /// let own_coins = client.list_utxos(context::current(), token ).await??;
+    /// // the first poll returns the full mempool
+    /// let diff = client.mempool_diff(context::current(), token, None).await??;
+    ///
+    /// // later polls return only what changed since
+    /// let diff = client.mempool_diff(context::current(), token, Some(diff.token)).await??;
     /// # Ok(())
     /// # }
     /// ```
-    async fn list_utxos(token: auth::Token) -> RpcResult<Vec<UiUtxo>>;
+    async fn mempool_diff(
+        token: auth::Token,
+        since_token: Option<MempoolDiffToken>,
+    ) -> RpcResult<MempoolDiff>;
 
-    /// Get CPU temperature.
+    /// Return transaction kernel by id if found in mempool.
+    async fn mempool_tx_kernel(
+        token: auth::Token,
+        tx_kernel_id: TransactionKernelId,
+    ) -> RpcResult<Option<TransactionKernel>>;
+
+    /// Return true iff the wallet's own transaction with the given id is in
+    /// the mempool and can be replaced by a transaction spending the same
+    /// inputs, e.g., to raise its fee.
+    ///
+    /// A transaction that other pending transactions of the wallet depend on
+    /// is not replaceable, since the replacement would invalidate those.
+    async fn mempool_tx_is_replaceable(
+        token: auth::Token,
+        tx_kernel_id: TransactionKernelId,
+    ) -> RpcResult<bool>;
+
+    /// Assess the risk of accepting an unconfirmed transaction as payment.
+    ///
+    /// Intended for point-of-sale integrations that must decide whether to
+    /// hand over goods before the payment is mined. The risk is scored from
+    /// observed conflicting spends of the transaction's inputs, its fee
+    /// density relative to the rest of the mempool, the number of connected
+    /// peers that announced it, and its proof type.
+    ///
+    /// Returns `None` if the transaction is not in the mempool.
     ///
     /// ```no_run
     /// # use anyhow::Result;
     /// # use neptune_cash::application::rpc::server::RPCClient;
     /// # use neptune_cash::application::rpc::auth;
+    /// # use neptune_cash::state::mempool::zero_conf_risk::ZeroConfRiskLevel;
+    /// # use neptune_cash::state::transaction::transaction_kernel_id::TransactionKernelId;
     /// # use tarpc::tokio_serde::formats::Json;
     /// # use tarpc::serde_transport::tcp;
     /// # use tarpc::client;
@@ -1339,151 +1658,33 @@ pub trait RPC {
     /// # // load the cookie file from disk and assign it to a token
     /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
     /// #
-    /// // query neptune-core server instance to get its CPU temperature
-    /// let cpu_temperature = client.cpu_temp(context::current(), token ).await??;
+    /// // the id of the transaction that pays the merchant, as hex
+    /// let txid: TransactionKernelId = "<transaction-id-hex>".parse()?;
+    ///
+    /// // query neptune-core server for the risk of accepting the transaction
+    /// let risk = client.zero_conf_risk(context::current(), token, txid).await??;
+    ///
+    /// let accept = risk.is_some_and(|risk| risk.level == ZeroConfRiskLevel::Low);
     /// # Ok(())
     /// # }
     /// ```
-    async fn cpu_temp(token: auth::Token) -> RpcResult<Option<f32>>;
+    async fn zero_conf_risk(
+        token: auth::Token,
+        tx_kernel_id: TransactionKernelId,
+    ) -> RpcResult<Option<ZeroConfRisk>>;
 
-    /// Get the proof-of-work puzzle for the current block proposal. Uses the
-    /// node's secret key to populate the guesser digest.
+    /// Verify a transaction without adding it to the mempool or broadcasting
+    /// it.
     ///
-    /// Returns `None` if no block proposal for the next block is known yet.
-    async fn pow_puzzle_internal_key(token: auth::Token) -> RpcResult<Option<ProofOfWorkPuzzle>>;
-
-    /// Get the proof-of-work puzzle for the current block proposal. Like
-    /// [Self::pow_puzzle_internal_key] but returned puzzle uses an externally
-    /// provided digest to populate the guesser digest field in the block
-    /// header, meaning that this client cannot claim the reward in case a
-    /// valid PoW-solution is found. This endpoint allows for "cold" guessing
-    /// where the node does not hold the key to spend the guesser reward.
-    ///
-    /// Returns `None` if no block proposal for the next block is known yet.
-    async fn pow_puzzle_external_key(
-        token: auth::Token,
-        guesser_fee_address: ReceivingAddress,
-    ) -> RpcResult<Option<ProofOfWorkPuzzle>>;
-
-    /// Get the proof-of-work puzzle for the current block proposal, along with
-    /// the block proposal itself. Works like [`Self::pow_puzzle_external_key`]
-    /// but does not task the node with remembering the proposal, meaning that
-    /// another node can receive the solution if one is found. If a solution is
-    /// found, the endpoint [`Self::provide_new_tip()`] can be used to pass the
-    /// solution onto a node.
-    async fn full_pow_puzzle_external_key(
-        token: auth::Token,
-        guesser_fee_address: ReceivingAddress,
-    ) -> RpcResult<Option<(Block, ProofOfWorkPuzzle)>>;
-
-    /// todo: docs.
-    ///
-    /// meanwhile see [tx_initiation::initiator::TransactionInitiator::spendable_inputs()]
-    async fn spendable_inputs(token: auth::Token) -> RpcResult<TxInputList>;
-
-    /// retrieve spendable inputs sufficient to cover spend_amount by applying selection policy.
-    ///
-    /// see [InputSelectionPolicy]
-    ///
-    /// pub enum InputSelectionPolicy {
-    ///     Random,
-    ///     ByNativeCoinAmount(SortOrder),
-    ///     ByUtxoSize(SortOrder),
-    /// }
-    ///
-    /// todo: docs.
-    ///
-    /// meanwhile see [tx_initiation::initiator::TransactionInitiator::select_spendable_inputs()]
-    async fn select_spendable_inputs(
-        token: auth::Token,
-        policy: InputSelectionPolicy,
-        spend_amount: NativeCurrencyAmount,
-    ) -> RpcResult<TxInputList>;
-
-    /// generate tx outputs from list of OutputFormat.
-    ///
-    /// OutputFormat can be address:amount, address:amount:medium, address:utxo,
-    /// address:utxo:medium, tx_output, etc.
-    ///
-    /// todo: docs.
-    ///
-    /// meanwhile see [tx_initiation::initiator::TransactionInitiator::generate_tx_outputs()]
-    async fn generate_tx_outputs(
-        token: auth::Token,
-        outputs: Vec<OutputFormat>,
-    ) -> RpcResult<TxOutputList>;
-
-    /// Helper endpoint for constructing a transaction. Can be used in
-    /// connection with other endpoints, e.g. endpoints that select inputs and
-    /// outputs to a transaction.
-    ///
-    /// meanwhile see [tx_initiation::initiator::TransactionInitiator::generate_tx_details()]
-    async fn generate_tx_details(
-        token: auth::Token,
-        tx_inputs: TxInputList,
-        tx_outputs: TxOutputList,
-        change_policy: ChangePolicy,
-        fee: NativeCurrencyAmount,
-    ) -> RpcResult<TransactionDetails>;
-
-    /// todo: docs.
-    ///
-    /// meanwhile see [tx_initiation::initiator::TransactionInitiator::generate_witness_proof()]
-    async fn generate_witness_proof(
-        token: auth::Token,
-        tx_details: TransactionDetails,
-    ) -> RpcResult<TransactionProof>;
-
-    /// assemble a transaction from TransactionDetails and a TransactionProof.
-    ///
-    /// todo: docs.
-    ///
-    /// meanwhile see [tx_initiation::initiator::TransactionInitiator::assemble_transaction()]
-    async fn assemble_transaction(
-        token: auth::Token,
-        transaction_details: TransactionDetails,
-        transaction_proof: TransactionProof,
-    ) -> RpcResult<Transaction>;
-
-    /// assemble transaction artifacts from TransactionDetails and a TransactionProof.
-    ///
-    /// todo: docs.
-    ///
-    /// meanwhile see [tx_initiation::initiator::TransactionInitiator::assemble_transaction_artifacts()]
-    async fn assemble_transaction_artifacts(
-        token: auth::Token,
-        transaction_details: TransactionDetails,
-        transaction_proof: TransactionProof,
-    ) -> RpcResult<TxCreationArtifacts>;
-
-    /// todo: docs.
-    ///
-    /// meanwhile see [tx_initiation::initiator::TransactionInitiator::proof_type()]
-    async fn proof_type(
-        token: auth::Token,
-        txid: TransactionKernelId,
-    ) -> RpcResult<TransactionProofType>;
-
-    /******** BLOCKCHAIN STATISTICS ********/
-    // Place all endpoints that relate to statistics of the blockchain here
-
-    /// Return the block intervals of a range of blocks. Return value is the
-    /// number of milliseconds it took to mine the (canonical) block with the
-    /// specified height. Does not include the interval between genesis block
-    /// and block 1 since genesis block was not actually mined and its timestamp
-    /// doesn't carry the same meaning as those of later blocks.
-    async fn block_intervals(
-        token: auth::Token,
-        last_block: BlockSelector,
-        max_num_blocks: Option<usize>,
-    ) -> RpcResult<Option<Vec<(u64, u64)>>>;
-
-    /// Return the difficulties of a range of blocks.
+    /// Checks the validity of the proof, the consistency of the inputs with
+    /// the mutator set of the tip, and the consensus limits on the number of
+    /// inputs, outputs, and announcements and on size. This allows e.g.
+    /// payment processors to pre-validate transactions submitted by
+    /// customers. Every failed check is listed in the returned report.
     ///
     /// ```no_run
     /// # use anyhow::Result;
-    /// use neptune_cash::protocol::consensus::block::block_selector::BlockSelector;
-    /// use neptune_cash::protocol::consensus::block::block_selector::BlockSelectorLiteral;
+    /// # use neptune_cash::api::export::Transaction;
     /// # use neptune_cash::application::rpc::server::RPCClient;
     /// # use neptune_cash::application::rpc::auth;
     /// # use tarpc::tokio_serde::formats::Json;
@@ -1506,39 +1707,37 @@ pub trait RPC {
     /// # // load the cookie file from disk and assign it to a token
     /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
     /// #
-    /// // sets the last block
-    /// let last_block : BlockSelector = BlockSelector::Special(BlockSelectorLiteral::Genesis);
-    ///
-    /// // set maximum number of blocks to 5 blocks
-    /// let max_num_blocks : Option<usize> = Some(5);
+    /// // transaction submitted by a customer
+    /// let transaction: Transaction =
+    ///     serde_json::from_str(&std::fs::read_to_string("transaction.json")?)?;
     ///
-    /// // query neptune-core server to get difficulties of a range of blocks
-    /// let block_difficulties = client.block_difficulties(context::current(), token, last_block, max_num_blocks).await??;
+    /// let report = client.verify_transaction(context::current(), token, transaction).await??;
+    /// if !report.is_valid() {
+    ///     println!("rejected: {:?}", report.failures);
+    /// }
     /// # Ok(())
     /// # }
     /// ```
-    async fn block_difficulties(
+    async fn verify_transaction(
         token: auth::Token,
-        last_block: BlockSelector,
-        max_num_blocks: Option<usize>,
-    ) -> RpcResult<Vec<(u64, Difficulty)>>;
-
-    /******** PEER INTERACTIONS ********/
-
-    /// Broadcast transaction notifications for all transactions in this node's
-    /// mempool.
-    async fn broadcast_all_mempool_txs(token: auth::Token) -> RpcResult<()>;
-
-    /// Broadcast running node's current favorable block proposal.
-    async fn broadcast_block_proposal(token: auth::Token) -> RpcResult<()>;
+        transaction: Transaction,
+    ) -> RpcResult<TransactionVerificationReport>;
 
-    /******** CHANGE THINGS ********/
-    // Place all things that change state here
-
-    /// Clears standing for all peers, connected or not
+    /// Run a transaction through the mempool admission checks without adding
+    /// it to the mempool or broadcasting it.
+    ///
+    /// Applies the checks of [Self::verify_transaction()] and, in addition,
+    /// the policy this node applies to transactions received from peers: the
+    /// relay fee, load shedding, age, whether the transaction is already
+    /// known, conflicts with mempool transactions, and the size of the
+    /// mempool. Every reason for rejection is listed in the returned report,
+    /// along with the mempool transactions that the transaction would
+    /// replace. This allows integrators to debug transactions that nodes
+    /// refuse to relay.
     ///
     /// ```no_run
     /// # use anyhow::Result;
+    /// # use neptune_cash::api::export::Transaction;
     /// # use neptune_cash::application::rpc::server::RPCClient;
     /// # use neptune_cash::application::rpc::auth;
     /// # use tarpc::tokio_serde::formats::Json;
@@ -1561,24 +1760,43 @@ pub trait RPC {
     /// # // load the cookie file from disk and assign it to a token
     /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
     /// #
-    /// // neptune-core server clears standing for all peers that are connected or not
-    /// let _ = client.clear_all_standings(context::current(), token).await??;
+    /// // transaction that peers refuse to relay, eg as saved by its creator
+    /// let transaction: Transaction =
+    ///     serde_json::from_str(&std::fs::read_to_string("transaction.json")?)?;
+    ///
+    /// let report = client.test_mempool_accept(context::current(), token, transaction).await??;
+    /// if !report.accepted() {
+    ///     println!("verification failures: {:?}", report.verification.failures);
+    ///     println!("mempool rejections: {:?}", report.rejections);
+    /// }
     /// # Ok(())
     /// # }
     /// ```
-    async fn clear_all_standings(token: auth::Token) -> RpcResult<()>;
+    async fn test_mempool_accept(
+        token: auth::Token,
+        transaction: Transaction,
+    ) -> RpcResult<MempoolAcceptanceReport>;
 
-    /// Clears standing for ip, whether connected or not
+    /// Prove to an auditor that the wallet controlled UTXOs at a block,
+    /// without disclosing spending keys.
+    ///
+    /// Discloses the UTXOs with the given AOCL leaf indices, or all unspent
+    /// UTXOs of the wallet if none are given. Returns `None` if the block is
+    /// unknown. The wallet only retains membership proofs for the most recent
+    /// blocks, so proofs for older blocks cannot be produced.
+    ///
+    /// The proof can be verified offline with [`UtxoOwnershipProof::verify`].
     ///
     /// ```no_run
     /// # use anyhow::Result;
     /// # use neptune_cash::application::rpc::server::RPCClient;
     /// # use neptune_cash::application::rpc::auth;
+    /// # use neptune_cash::protocol::consensus::block::block_selector::BlockSelector;
+    /// # use neptune_cash::protocol::consensus::block::block_selector::BlockSelectorLiteral;
     /// # use tarpc::tokio_serde::formats::Json;
     /// # use tarpc::serde_transport::tcp;
     /// # use tarpc::client;
     /// # use tarpc::context;
-    /// # use std::net::{IpAddr, Ipv4Addr};
     /// #
     /// # #[tokio::main]
     /// # async fn main() -> Result<()>{
@@ -1586,69 +1804,57 @@ pub trait RPC {
     /// # // create a serde/json transport over tcp.
     /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
     /// #
-    /// // create an rpc client using the transport.
-    /// let client = RPCClient::new(client::Config::default(), transport).spawn();
-    ///
-    /// // Defines cookie hint
-    /// let cookie_hint = client.cookie_hint(context::current()).await??;
-    ///
-    /// // load the cookie file from disk and assign it to a token
-    /// let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
-    ///
-    /// // IP address 87.23.90.12 to clear standing
-    /// let ip = IpAddr::V4(Ipv4Addr::new(87, 23, 90, 12));
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // prove ownership of all unspent UTXOs at the tip
+    /// let tip = BlockSelector::Special(BlockSelectorLiteral::Tip);
+    /// let proof = client
+    ///     .prove_utxo_ownership(context::current(), token, tip, vec![])
+    ///     .await??
+    ///     .expect("tip is known");
     ///
-    /// // neptune-core server clears standing for all peers that are connected or not
-    /// let _ = client.clear_standing_by_ip(context::current(), token, ip).await??;
+    /// // the auditor verifies the proof
+    /// let report = proof.verify()?;
+    /// println!("{} in {} UTXOs", report.total, report.utxos.len());
     /// # Ok(())
     /// # }
     /// ```
-    async fn clear_standing_by_ip(token: auth::Token, ip: IpAddr) -> RpcResult<()>;
-
-    /// record transaction and initiate broadcast to peers
-    ///
-    /// todo: docs.
-    ///
-    /// meanwhile see [tx_initiation::initiator::TransactionInitiator::record_and_broadcast_transaction()]
-    async fn record_and_broadcast_transaction(
+    async fn prove_utxo_ownership(
         token: auth::Token,
-        tx_artifacts: TxCreationArtifacts,
-    ) -> RpcResult<()>;
+        block_selector: BlockSelector,
+        aocl_leaf_indices: Vec<u64>,
+    ) -> RpcResult<Option<UtxoOwnershipProof>>;
 
-    /// Send coins to one or more recipients
-    ///
-    /// note: sending is rate-limited to 2 sends per block until block
-    /// 25000 is reached.
-    ///
-    /// `outputs` is a list of transaction outputs in any format supported by [OutputFormat].
-    ///
-    /// `change_policy` specifies how to handle change in the typical case that
-    /// the transaction input amount exceeds the output amount.
-    ///
-    /// `fee` represents the fee in native coins to pay the miner who mines
-    /// the block that initially confirms the resulting transaction.
+    /// Produce a signed proof-of-reserve report for the wallet's unspent
+    /// UTXOs at a block.
     ///
-    /// a [Digest] of the resulting [Transaction](crate::protocol::consensus::transaction::Transaction) is returned on success, else [None].
+    /// The report lists the commitments of the UTXOs, their membership proofs
+    /// and their total amount, and is signed with the spending keys of the
+    /// UTXOs. Returns `None` if the block is unknown. As with
+    /// [`RPC::prove_utxo_ownership`], only recent blocks are supported.
     ///
-    /// A list of the encoded transaction notifications is also returned. The
-    /// relevant notifications should be sent to the transaction receiver(s) in
-    /// case `Offchain` notification is used for any output(s).
+    /// Signing produces a proof per lock script of the UTXOs, ie roughly per
+    /// receiving address, which takes a while. Auditors verify the
+    /// report offline with [`SignedReserveReport::verify`], against a
+    /// checkpoint of the chain they trust.
     ///
     /// ```no_run
     /// # use anyhow::Result;
-    /// # use neptune_cash::application::config::network::Network;
-    /// # use neptune_cash::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
-    /// # use neptune_cash::state::wallet::address::ReceivingAddress;
-    /// # use neptune_cash::state::wallet::utxo_notification::UtxoNotificationMedium;
     /// # use neptune_cash::application::rpc::server::RPCClient;
     /// # use neptune_cash::application::rpc::auth;
-    /// # use neptune_cash::api::export::ChangePolicy;
-    /// # use neptune_cash::api::export::OutputFormat;
+    /// # use neptune_cash::protocol::consensus::block::block_selector::BlockSelector;
+    /// # use neptune_cash::protocol::consensus::block::block_selector::BlockSelectorLiteral;
     /// # use tarpc::tokio_serde::formats::Json;
     /// # use tarpc::serde_transport::tcp;
     /// # use tarpc::client;
     /// # use tarpc::context;
-    /// # use std::net::IpAddr;
     /// #
     /// # #[tokio::main]
     /// # async fn main() -> Result<()>{
@@ -1665,72 +1871,60 @@ pub trait RPC {
     /// # // load the cookie file from disk and assign it to a token
     /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
     /// #
-    /// // List of receiving addresses and the amounts to send
-    /// let outputs: Vec<OutputFormat> = vec![
-    ///     (ReceivingAddress::from_bech32m("nolgam1lf8vc5xpa4jf9vjakts632fct5q80d4m6tax39nrl8c55dta2h7n7lnkh9pmwckl0ndwc7897xwfgx5vv02xdt3099z62222wazz7tjl6umzewla9xzxyqefh2w47v4eh0xzvfsxjk6kq5u84rwwlflq7cs726ljttl6ls860te04cwpy5kk8n40qqjnps0gdp46namhsa3cqt0uc0s5e34h6s5rw2kl77uvvs4rlnn5t8wtuefsduuccwsxmk27r8d48g49swgafhj6wmvu5cx3lweqhnxgdgm7mmdq7ck6wkurw2jzl64k9u34kzgu9stgd47ljzte0hz0n2lcng83vtpf0u9f4hggw4llqsz2fqpe4096d9v5fzg7xvxg6zvr7gksq4yqgn8shepg5xsczmzz256m9c6r8zqdkzy4tk9he59ndtdkrrr8u5v6ztnvkvmy4sed7p7plm2y09sgksw6zcjayls4wl9fnqu97kyx9cdknksar7h8jetygur979rt5arcwmvp2dy3ynt6arna2yjpevt9209v9g2p5cvp6gjp9850w3w6afeg8yuhp6u447hrudcssyjauqa2p7jk4tz37wg70yrdhsgn35sc0hdkclvpapu75dgtmswk0vtgadx44mqdps6ry6005xqups9dpc93u66qj9j7lfaqgdqrrfg9pkxhjl99ge387rh257x2phfvjvc8y66p22wax8myyhm7mgmlxu9gug0km3lmn4lzcyj32mduy6msy4kfn5z2tr67zfxadnj6wc0av27mk0j90pf67uzp9ps8aekr24kpv5n3qeczfznen9vj67ft95s93t26l8uh87qr6kp8lsyuzm4h36de830h6rr3lhg5ac995nrsu6h0p56t5tnglvx0s02mr0ts95fgcevveky5kkw6zgj6jd5m3n5ljhw862km8sedr30xvg8t9vh409ufuxdnfuypvqdq49z6mp46p936pjzwwqjda6yy5wuxx9lffrxwcmfqzch6nz2l4mwd2vlsdr58vhygppy6nm6tduyemw4clwj9uac4v990xt6jt7e2al7m6sjlq4qgxfjf4ytx8f5j460vvr7yac9hsvlsat2vh5gl55mt4wr7v5p3m6k5ya5442xdarastxlmpf2vqz5lusp8tlglxkj0jksgwqgtj6j0kxwmw40egpzs5rr996xpv8wwqyja4tmw599n9fh77f5ruxk69vtpwl9z5ezmdn92cpyyhwff59ypp0z5rv98vdvm67umqzt0ljjan30u3a8nga35fdy450ht9gef24mveucxqwv5aflge5r3amxsvd7l30j9kcqm7alq0ks2wqpde7pdct2gmvafxvjg3ad0a3h58assjaszvmykl3k5tn238gstm2shlvad4a53mm5ztvp5q2zt4pdzj0ssevlkumwhc0g5cxnxc9u7rh9gffkq7h9ufcxkgtghe32sv3vwzkessr52mcmajt83lvz45wqru9hht8cytfedtjlv7z7en6pp0guja85ft3rv6hzf2e02e7wfu38s0nyfzkc2qy2k298qtmxgrpduntejtvenr80csnckajnhu44399tkm0a7wdldalf678n9prd54twwlw24xhppxqlquatfztllkeejlkfxuayddwagh6uzx040tqlcs7hcflnu0ywynmz0chz48qcx7dsc4gpseu0dqvmmezpuv0tawm78nleju2vp4lkehua56hrnuj2wuc5lqvxlnskvp53vu7e2399pgp7xcwe3ww23qcd9pywladq34nk6cwcvtj3vdfgwf6r7s6vq46y2x05e043nj6tu8am2und8z3ftf3he5ccjxamtnmxfd79m04ph36kzx6e789dhqrwmwcfrn9ulsedeplk3dvrmad6f20y9qfl6n6kzaxkmmmaq4d6s5rl4kmhc7fcdkrkandw2jxdjckuscu56syly8rtjatj4j2ug23cwvep3dgcdvmtr32296nf9vdl3rcu0r7hge23ydt83k5nhtnexuqrnamveacz6c43eay9nz4pjjwjatkgp80lg9tnf5kdr2eel8s2fk6v338x4hu00htemm5pq6qlucqqq5tchhtekjzdu50erqd2fkdu9th3wl0mqxz5u7wnpgwgpammv2yqpa5znljegyhke0dz9vg27uh5t5x6qdgf7vu54lqssejekwzfxchjyq2s8frm9fmt688w76aug56v6n3w5xdre78xplfsdw3e4j6dc5w7tf83r25re0duq6h8z54wnkqr9yh2k0skjqea4elgcr4aw7hks9m8w3tx8w9xlxpqqll2zeql55ew7e90dyuynkqxfuqzv45t22ljamdll3udvqrllprdltthzm866jdaxkkrnryj4cmc2m7sk99clgql3ynrhe9kynqn4mh3tepk8dtq7cndtc2hma29s4cuylsvg04s70uyr53w5656su5rjem5egss08zrfaef0mww6t8pr26uph2n8a2cs55ydx4xhasjqk7xs0akh6f26j2ec4d8pd0kdf4jya6p9jl48wmy5autdpw2q8mehrq6kypt573genj66l5zkq6xvrdqugmfczxa2gj9ylx3pgpjqnhuem9udfkj9qr2y8lh728sr7uaedu5wwmfa72ykh395jqh7f7f9p2gskn6u7k844kpnwe3eqv84pl53r6x9af88a8ey7298njdg03h8mxqz2x6z8ys3qpuxq768tjq0zhrnjgns8d78euzwsvx6vn4f9tftrp68zcch3h75mc9drpt7tpvnyyqfjuqclxhdwhdwtsakecv04p9r3jx90htql9a3ht5mxrj4ercv4cd52wk4qhu7dn4tqe7yclqx2l36gcsrzmdlv440qls7qjpq6k95mst485vpennnur8h62a7d7syvyer89qtyfzlfhz8a5a0x5tuwhc9mah0e944xzhsc6uvpv8vat44w7r3xyw8q85y77jux8zhndrhdn36swryffqmpkxgcw4g29q40sul4fl5vrfru08a5j3rd3jl8799srpf2xqpxq38wwvhr4mxqf5wwdqfqq7harshggvufzlgn0l9fq0j76dyuge75jmzy8celvw6wesfs82n4jw2k8jnus2zds5a67my339uuzka4w72tau6j7wyu0lla0mcjpaflphsuy7f2phev6tr8vc9nj2mczkeg4vy3n5jkgecwgrvwu3vw9x5knpkxzv8kw3dpzzxy3rvrs56vxw8ugmyz2vdj6dakjyq3feym4290l7hgdt0ac5u49sekezzf0ghwmlek4h75fkzpvuly9zupw32dd3l9my282nekgk78fe6ayjyhczetxf8r82yd2askl52kmupr9xaxw0jd08dsd3523ea6ge48384rlmt4mu4w4x0q9s", Network::Main)?, NativeCurrencyAmount::coins(20)).into(),
-    ///     (ReceivingAddress::from_bech32m("nolgam1ld9vc5xpa4jf9vjakts632fct5q80d4m6tax39nrl8c55dta2h7n7lnkh9pmwckl0ndwc7897xwfgx5vv02xdt3099z62222wazz7tjl6umzewla9xzxyqefh2w47v4eh0xzvfsxjk6kq5u84rwwlflq7cs726ljttl6ls860te04cwpy5kk8n40qqjnps0gdp46namhsa3cqt0uc0s5e34h6s5rw2kl77uvvs4rlnn5t8wtuefsduuccwsxmk27r8d48g49swgafhj6wmvu5cx3lweqhnxgdgm7mmdq7ck6wkurw2jzl64k9u34kzgu9stgd47ljzte0hz0n2lcng83vtpf0u9f4hggw4llqsz2fqpe4096d9v5fzg7xvxg6zvr7gksq4yqgn8shepg5xsczmzz256m9c6r8zqdkzy4tk9he59ndtdkrrr8u5v6ztnvkvmy4sed7p7plm2y09sgksw6zcjayls4wl9fnqu97kyx9cdknksar7h8jetygur979rt5arcwmvp2dy3ynt6arna2yjpevt9209v9g2p5cvp6gjp9850w3w6afeg8yuhp6u447hrudcssyjauqa2p7jk4tz37wg70yrdhsgn35sc0hdkclvpapu75dgtmswk0vtgadx44mqdps6ry6005xqups9dpc93u66qj9j7lfaqgdqrrfg9pkxhjl99ge387rh257x2phfvjvc8y66p22wax8myyhm7mgmlxu9gug0km3lmn4lzcyj32mduy6msy4kfn5z2tr67zfxadnj6wc0av27mk0j90pf67uzp9ps8aekr24kpv5n3qeczfznen9vj67ft95s93t26l8uh87qr6kp8lsyuzm4h36de830h6rr3lhg5ac995nrsu6h0p56t5tnglvx0s02mr0ts95fgcevveky5kkw6zgj6jd5m3n5ljhw862km8sedr30xvg8t9vh409ufuxdnfuypvqdq49z6mp46p936pjzwwqjda6yy5wuxx9lffrxwcmfqzch6nz2l4mwd2vlsdr58vhygppy6nm6tduyemw4clwj9uac4v990xt6jt7e2al7m6sjlq4qgxfjf4ytx8f5j460vvr7yac9hsvlsat2vh5gl55mt4wr7v5p3m6k5ya5442xdarastxlmpf2vqz5lusp8tlglxkj0jksgwqgtj6j0kxwmw40egpzs5rr996xpv8wwqyja4tmw599n9fh77f5ruxk69vtpwl9z5ezmdn92cpyyhwff59ypp0z5rv98vdvm67umqzt0ljjan30u3a8nga35fdy450ht9gef24mveucxqwv5aflge5r3amxsvd7l30j9kcqm7alq0ks2wqpde7pdct2gmvafxvjg3ad0a3h58assjaszvmykl3k5tn238gstm2shlvad4a53mm5ztvp5q2zt4pdzj0ssevlkumwhc0g5cxnxc9u7rh9gffkq7h9ufcxkgtghe32sv3vwzkessr52mcmajt83lvz45wqru9hht8cytfedtjlv7z7en6pp0guja85ft3rv6hzf2e02e7wfu38s0nyfzkc2qy2k298qtmxgrpduntejtvenr80csnckajnhu44399tkm0a7wdldalf678n9prd54twwlw24xhppxqlquatfztllkeejlkfxuayddwagh6uzx040tqlcs7hcflnu0ywynmz0chz48qcx7dsc4gpseu0dqvmmezpuv0tawm78nleju2vp4lkehua56hrnuj2wuc5lqvxlnskvp53vu7e2399pgp7xcwe3ww23qcd9pywladq34nk6cwcvtj3vdfgwf6r7s6vq46y2x05e043nj6tu8am2und8z3ftf3he5ccjxamtnmxfd79m04ph36kzx6e789dhqrwmwcfrn9ulsedeplk3dvrmad6f20y9qfl6n6kzaxkmmmaq4d6s5rl4kmhc7fcdkrkandw2jxdjckuscu56syly8rtjatj4j2ug23cwvep3dgcdvmtr32296nf9vdl3rcu0r7hge23ydt83k5nhtnexuqrnamveacz6c43eay9nz4pjjwjatkgp80lg9tnf5kdr2eel8s2fk6v338x4hu00htemm5pq6qlucqqq5tchhtekjzdu50erqd2fkdu9th3wl0mqxz5u7wnpgwgpammv2yqpa5znljegyhke0dz9vg27uh5t5x6qdgf7vu54lqssejekwzfxchjyq2s8frm9fmt688w76aug56v6n3w5xdre78xplfsdw3e4j6dc5w7tf83r25re0duq6h8z54wnkqr9yh2k0skjqea4elgcr4aw7hks9m8w3tx8w9xlxpqqll2zeql55ew7e90dyuynkqxfuqzv45t22ljamdll3udvqrllprdltthzm866jdaxkkrnryj4cmc2m7sk99clgql3ynrhe9kynqn4mh3tepk8dtq7cndtc2hma29s4cuylsvg04s70uyr53w5656su5rjem5egss08zrfaef0mww6t8pr26uph2n8a2cs55ydx4xhasjqk7xs0akh6f26j2ec4d8pd0kdf4jya6p9jl48wmy5autdpw2q8mehrq6kypt573genj66l5zkq6xvrdqugmfczxa2gj9ylx3pgpjqnhuem9udfkj9qr2y8lh728sr7uaedu5wwmfa72ykh395jqh7f7f9p2gskn6u7k844kpnwe3eqv84pl53r6x9af88a8ey7298njdg03h8mxqz2x6z8ys3qpuxq768tjq0zhrnjgns8d78euzwsvx6vn4f9tftrp68zcch3h75mc9drpt7tpvnyyqfjuqclxhdwhdwtsakecv04p9r3jx90htql9a3ht5mxrj4ercv4cd52wk4qhu7dn4tqe7yclqx2l36gcsrzmdlv440qls7qjpq6k95mst485vpennnur8h62a7d7syvyer89qtyfzlfhz8a5a0x5tuwhc9mah0e944xzhsc6uvpv8vat44w7r3xyw8q85y77jux8zhndrhdn36swryffqmpkxgcw4g29q40sul4fl5vrfru08a5j3rd3jl8799srpf2xqpxq38wwvhr4mxqf5wwdqfqq7harshggvufzlgn0l9fq0j76dyuge75jmzy8celvw6wesfs82n4jw2k8jnus2zds5a67my339uuzka4w72tau6j7wyu0lla0mcjpaflphsuy7f2phev6tr8vc9nj2mczkeg4vy3n5jkgecwgrvwu3vw9x5knpkxzv8kw3dpzzxy3rvrs56vxw8ugmyz2vdj6dakjyq3feym4290l7hgdt0ac5u49sekezzf0ghwmlek4h75fkzpvuly9zupw32dd3l9my282nekgk78fe6ayjyhczetxf8r82yd2askl52kmupr9xaxw0jd08dsd3523ea6ge48384rlmt4mu4w4x0q9s", Network::Main)?, NativeCurrencyAmount::coins(57)).into(),
-    /// ];
+    /// // produce a proof-of-reserve report at the tip
+    /// let tip = BlockSelector::Special(BlockSelectorLiteral::Tip);
+    /// let signed_report = client
+    ///     .proof_of_reserve(context::current(), token, tip)
+    ///     .await??
+    ///     .expect("tip is known");
     ///
-    /// // change policy.
-    /// // default is recover to next unused key, via onchain notification
-    /// let change_policy = ChangePolicy::default();
-    /// #
-    /// // Max fee
-    /// let fee : NativeCurrencyAmount = NativeCurrencyAmount::coins(10);
-    /// #
-    /// // neptune-core server sends token to a single recipient
-    /// let send_result = client.send(context::current(), token, outputs, change_policy, fee).await??;
+    /// println!("reserves: {}", signed_report.report.total);
     /// # Ok(())
     /// # }
     /// ```
-    async fn send(
+    async fn proof_of_reserve(
         token: auth::Token,
-        outputs: Vec<OutputFormat>,
-        change_policy: ChangePolicy,
-        fee: NativeCurrencyAmount,
-    ) -> RpcResult<TxCreationArtifacts>;
+        block_selector: BlockSelector,
+    ) -> RpcResult<Option<SignedReserveReport>>;
 
-    /// Like `send` but the resulting transaction is *transparent*. No privacy.
+    /// Return the information used on the dashboard's overview tab
     ///
-    /// Specifically, the resulting transaction contains announcements that
-    /// themselves contain the raw UTXOs and commitment randomnesses. This
-    /// info suffices to derive the addition records and removal records,
-    /// thereby exposing not just the amounts but also the origins and
-    /// destinations of the transfer.
-    async fn send_transparent(
-        token: auth::Token,
-        outputs: Vec<OutputFormat>,
-        change_policy: ChangePolicy,
-        fee: NativeCurrencyAmount,
-    ) -> RpcResult<TxCreationArtifacts>;
-
-    /// Upgrade a proof for a transaction found in the mempool. If the
-    /// transaction cannot be in the mempool, or the transaction is not in need
-    /// of upgrading because it is already single proof-backed and synced, then
-    /// false is returned. Otherwise true is returned.
-    ///
-    /// No fees will be collected from the proof upgrading.
-    ///
-    /// Returns Ok(true) if a transaction for upgrading was found
-    /// Returns Ok(false) if no transaction for upgrading was found
-    /// Returns an error if something else failed.
-    async fn upgrade(token: auth::Token, tx_kernel_id: TransactionKernelId) -> RpcResult<bool>;
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // query neptune-core server to get the info used on dashboard overview tab
+    /// let dashboard_data = client.dashboard_overview_data(context::current(), token).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn dashboard_overview_data(token: auth::Token) -> RpcResult<OverviewData>;
 
-    /// claim a utxo
-    ///
-    /// The input string must be a valid bech32m encoded `UtxoTransferEncrypted`
-    /// for the current network and the wallet must have the corresponding
-    /// `SpendingKey` for decryption.
-    ///
-    /// upon success, a new `ExpectedUtxo` will be added to the local wallet
-    /// state.
-    ///
-    /// if the utxo has already been claimed, this call has no effect.
-    ///
-    /// Return true if a new expected UTXO was added, otherwise false.
+    /// Determine whether the user-supplied string is a valid address
     ///
     /// ```no_run
     /// # use anyhow::Result;
+    /// use neptune_cash::application::config::network::Network;
     /// # use neptune_cash::application::rpc::server::RPCClient;
     /// # use neptune_cash::application::rpc::auth;
     /// # use tarpc::tokio_serde::formats::Json;
@@ -1753,43 +1947,28 @@ pub trait RPC {
     /// # // load the cookie file from disk and assign it to a token
     /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
     /// #
-    /// // Encryted value of utxo transfer
-    /// let utxo_transfer_encrypted = "XXXXXXX".to_string();
+    /// // address to validate
+    /// let address : String = "0x484389349834834DF23".to_string();
     ///
-    /// // max search depth is set to 3
-    /// let max_search_depth : Option<u64> = Some(3);
+    /// // network type
+    /// let network : Network = Network::Main;
     ///
-    /// // claim utxo
-    /// let utxo_claimed = client.claim_utxo(context::current(), token, utxo_transfer_encrypted, max_search_depth).await??;
+    /// // query neptune-core server to check if the supplied address is valid
+    /// let is_valid_address = client.validate_address(context::current(), token, address, network).await??;
     /// # Ok(())
     /// # }
     /// ```
-    async fn claim_utxo(
+    async fn validate_address(
         token: auth::Token,
-        utxo_transfer_encrypted: String,
-        max_search_depth: Option<u64>,
-    ) -> RpcResult<bool>;
-
-    /// Delete all transactions from the mempool.
-    async fn clear_mempool(token: auth::Token) -> RpcResult<()>;
+        address: String,
+        network: Network,
+    ) -> RpcResult<Option<ReceivingAddress>>;
 
-    /// Pause receiving of blocks, block proposals, and transactions. If
-    /// activated, no new blocks will be received. Transactions, blocks, and
-    /// block proposals originating locally will not be shared with peers.
-    /// Mining should be paused when this is activated. Cannot be called if the
-    /// client is currently syncing.
+    /// Determine whether the user-supplied string is a valid amount
     ///
-    /// Can be used to build a big transaction through the merge of multiple
-    /// smaller transactions without risking that the smaller, unmerged
-    /// transactions are mined.
-    async fn freeze(token: auth::Token) -> RpcResult<()>;
-
-    /// Resume state updates. If state updates were paused, start receiving and
-    /// transmitting blocks, block proposals, and transactions again. Otherwise,
-    /// does nothing.
-    async fn unfreeze(token: auth::Token) -> RpcResult<()>;
-
-    /// Stop miner if running
+    /// The amount is a decimal number of coins. Returns `None` if it is
+    /// malformed, out of range, or not a whole number of nau, the smallest
+    /// unit, rather than rounding it.
     ///
     /// ```no_run
     /// # use anyhow::Result;
@@ -1815,14 +1994,30 @@ pub trait RPC {
     /// # // load the cookie file from disk and assign it to a token
     /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
     /// #
-    ///  // stops miner if running
-    /// let _ = client.pause_miner(context::current(), token).await??;
+    /// // address to validate
+    /// let amount : String = "132".to_string();
+    ///
+    /// // query neptune-core server to determine if the amount is valid
+    /// let is_valid_address = client.validate_amount(context::current(), token, amount ).await??;
     /// # Ok(())
     /// # }
     /// ```
-    async fn pause_miner(token: auth::Token) -> RpcResult<()>;
+    async fn validate_amount(
+        token: auth::Token,
+        amount: String,
+    ) -> RpcResult<Option<NativeCurrencyAmount>>;
 
-    /// Start miner if not running
+    /// Parse a user-supplied amount.
+    ///
+    /// The amount is a decimal number of coins, optionally followed by the
+    /// unit of another [`Denomination`], eg `"1.5"` or `"1500 mNPT"`. Returns
+    /// [`RpcError::InvalidAmount`] if the string is malformed, out of range,
+    /// or not a whole number of nau, rather than rounding it. Unlike
+    /// [`validate_amount`](Self::validate_amount), it reports why an amount
+    /// is invalid.
+    ///
+    /// [`Denomination`]: crate::api::amount::Denomination
+    /// [`RpcError::InvalidAmount`]: error::RpcError::InvalidAmount
     ///
     /// ```no_run
     /// # use anyhow::Result;
@@ -1848,80 +2043,115 @@ pub trait RPC {
     /// # // load the cookie file from disk and assign it to a token
     /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
     /// #
-    ///  // start miner if not running
-    /// let _ = client.restart_miner(context::current(), token).await??;
+    /// // amount to parse
+    /// let amount : String = "1500 mNPT".to_string();
+    ///
+    /// // query neptune-core server to parse the amount
+    /// let amount = client.parse_amount(context::current(), token, amount ).await??;
     /// # Ok(())
     /// # }
     /// ```
-    async fn restart_miner(token: auth::Token) -> RpcResult<()>;
-
-    /// Set coinbase distribution for this node's block proposals. This
-    /// distribution will be in effect until it is overwritted or manually
-    /// unset. The value set through this command stays in effect regardless of
-    /// whether the block's proposals are mined or not. Only by overwriting this
-    /// value by calling this function again, or by unsetting this value through
-    /// [`RPC::unset_coinbase_distribution()`] can this effect be overturned.
-    ///
-    /// To guarantee that the set coinbase distribution is applied to the *next*
-    /// locally produced block proposal, this function call can be followed up
-    /// by a call to first [`RPC::pause_miner()`] and then
-    /// [`RPC::restart_miner()`]. If this is not done, the node may continue
-    /// working on an already started block proposal with another distribution.
-    async fn set_coinbase_distribution(
-        token: auth::Token,
-        coinbase_distribution: Vec<CoinbaseOutputReadable>,
-    ) -> RpcResult<()>;
-
-    /// Remove a coinbase distribution from state, thus defaulting back to
-    /// rewarding the node's own wallet with the composer's coinbase outputs.
-    ///
-    /// Can be used to delete coinbase distributions set through
-    /// [`RPC::set_coinbase_distribution()`].
-    async fn unset_coinbase_distribution(token: auth::Token) -> RpcResult<()>;
+    async fn parse_amount(token: auth::Token, amount: String) -> RpcResult<NativeCurrencyAmount>;
 
-    /// mine a series of blocks to the node's wallet.
+    /// Validate many user-supplied addresses at once, eg to pre-screen the
+    /// deposit and withdrawal addresses of customers.
     ///
-    /// Can be used only if the network uses mock blocks.
-    /// (presently only the regtest network)
+    /// Returns an [`AddressVerdict`] per address, in the order of the
+    /// addresses. An address is checked for being well-formed, for belonging
+    /// to the network, for being of one of the `accepted_key_types`, and for
+    /// matching a known pattern of addresses that no one can spend from. An
+    /// empty list of accepted key types accepts all of them.
     ///
-    /// these blocks can be generated quickly because they do not have
-    /// a real ZK proof.  they have a witness "proof" and will validate correctly.
-    /// witness proofs contain secrets that must not be shared, so this is
-    /// allowed only on the regtest network, for development purposes.
+    /// At most [`MAX_ADDRESSES_PER_VALIDATION`] addresses can be validated in
+    /// a single call.
     ///
-    /// The timestamp of each block will be the current system time, meaning
-    /// that they will be temporally very close to eachother.
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// use neptune_cash::application::config::network::Network;
+    /// use neptune_cash::state::wallet::address::KeyType;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // addresses to validate
+    /// let addresses : Vec<String> = vec!["nolgam1...".to_string(), "nolgam1...".to_string()];
     ///
-    /// see [api::regtest::RegTest::mine_blocks_to_wallet()]
-    async fn mine_blocks_to_wallet(token: auth::Token, n_blocks: u32) -> RpcResult<()>;
-
-    /// Provide a PoW-solution to the current block proposal.
+    /// // only accept generation addresses
+    /// let accepted_key_types = vec![KeyType::Generation];
     ///
-    /// If the solution is considered valid by the running node, the new block
-    /// is broadcast to all peers on the network, and `true` is returned.
-    /// Otherwise the provided solution is ignored, and `false` is returned.
-    async fn provide_pow_solution(
+    /// // query neptune-core server for a verdict on each address
+    /// let verdicts = client
+    ///     .validate_addresses(context::current(), token, addresses, Network::Main, accepted_key_types)
+    ///     .await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn validate_addresses(
         token: auth::Token,
-        pow: BlockPow,
-        proposal_id: Digest,
-    ) -> RpcResult<bool>;
+        addresses: Vec<String>,
+        network: Network,
+        accepted_key_types: Vec<KeyType>,
+    ) -> RpcResult<Vec<AddressVerdict>>;
 
-    /// Provide a PoW solution along with a valid block proposal. Caller must
-    /// provide both the block proposal and the pow solution.
+    /// Determine whether the given amount is less than (or equal to) the balance
     ///
-    /// Works like [`Self::provide_pow_solution()`] except that it takes a full
-    /// block proposal rather than a proposal ID. This allows for the provision
-    /// of a block without the node having to know the associated block proposal
-    /// since proposal is provided by the caller. Can be used in conjuction with
-    /// [`Self::full_pow_puzzle_external_key`].
-    async fn provide_new_tip(
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// use neptune_cash::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // setting the amount to 47
+    /// let amount : NativeCurrencyAmount = NativeCurrencyAmount::coins(47);
+    ///
+    /// // query neptune-core server to determine if the amount is less than or equal to the balance
+    /// let amount_less_or_equals_balance = client.amount_leq_confirmed_available_balance(context::current(), token, amount ).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn amount_leq_confirmed_available_balance(
         token: auth::Token,
-        pow: BlockPow,
-        block_proposal: Block,
+        amount: NativeCurrencyAmount,
     ) -> RpcResult<bool>;
 
-    /// mark MUTXOs as abandoned. Does not actually delete any elements in the
-    /// list.
+    /// Generate a report of all owned and unspent coins, whether time-locked or not.
     ///
     /// ```no_run
     /// # use anyhow::Result;
@@ -1947,22 +2177,24 @@ pub trait RPC {
     /// # // load the cookie file from disk and assign it to a token
     /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
     /// #
-    ///  // marks mutxos as abandoned
-    /// let abandoned_monitored_utxos = client.prune_abandoned_monitored_utxos(context::current(), token).await??;
+    /// // query neptune-core server to get the list of owned and unspent coins
+    /// let own_coins = client.list_own_coins(context::current(), token ).await??;
     /// # Ok(())
     /// # }
     /// ```
-    async fn prune_abandoned_monitored_utxos(token: auth::Token) -> RpcResult<usize>;
-
-    /// Set the tip of the blockchain state to a given block, identified by its
-    /// hash. The block must be stored, but it does not need to live on the
-    /// canonical chain.
-    async fn set_tip(token: auth::Token, indicated_tip: Digest) -> RpcResult<()>;
+    async fn list_own_coins(token: auth::Token) -> RpcResult<Vec<CoinWithPossibleTimeLock>>;
 
-    /// Gracious shutdown.
+    /// Summarize the fragmentation of the wallet's unspent UTXOs.
+    ///
+    /// Reports the number of UTXOs by value and by age, the UTXOs worth less
+    /// than `dust_threshold`, and the average size of the membership proofs
+    /// the wallet updates with every block. If the wallet holds many small
+    /// UTXOs, the report suggests which ones to consolidate by sending them
+    /// to oneself.
     ///
     /// ```no_run
     /// # use anyhow::Result;
+    /// # use neptune_cash::api::export::NativeCurrencyAmount;
     /// # use neptune_cash::application::rpc::server::RPCClient;
     /// # use neptune_cash::application::rpc::auth;
     /// # use tarpc::tokio_serde::formats::Json;
@@ -1985,2635 +2217,8122 @@ pub trait RPC {
     /// # // load the cookie file from disk and assign it to a token
     /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
     /// #
-    ///  // shutdowns the node
-    /// let is_shutdown = client.shutdown(context::current(), token).await??;
+    /// // UTXOs worth less than this are considered dust
+    /// let dust_threshold = NativeCurrencyAmount::coins_from_str("0.01")?;
+    ///
+    /// // query neptune-core server for the wallet's fragmentation statistics
+    /// let wallet_stats = client.wallet_stats(context::current(), token, dust_threshold).await??;
     /// # Ok(())
     /// # }
-    async fn shutdown(token: auth::Token) -> RpcResult<bool>;
-}
-
-#[derive(Clone)]
-pub(crate) struct NeptuneRPCServer {
-    pub(crate) state: GlobalStateLock,
-    pub(crate) rpc_server_to_main_tx: tokio::sync::mpsc::Sender<RPCServerToMain>,
-
-    // copy of DataDirectory for this neptune-core instance.
-    data_directory: DataDirectory,
-
-    // list of tokens that are valid.  RPC clients must present a token that
-    // matches one of these.  there should only be one of each `Token` variant
-    // in the list (dups ignored).
-    valid_tokens: Vec<auth::Token>,
-}
-
-impl NeptuneRPCServer {
-    /// instantiate a new [NeptuneRPCServer]
-    pub fn new(
-        state: GlobalStateLock,
-        rpc_server_to_main_tx: tokio::sync::mpsc::Sender<RPCServerToMain>,
-        data_directory: DataDirectory,
-        valid_tokens: Vec<auth::Token>,
-    ) -> Self {
-        Self {
-            state,
-            valid_tokens,
-            rpc_server_to_main_tx,
-            data_directory,
-        }
-    }
-
-    async fn confirmations_internal(&self, state: &GlobalState) -> Option<BlockHeight> {
-        match state.get_latest_balance_height().await {
-            Some(latest_balance_height) => {
-                let tip_block_header = state.chain.light_state().header();
+    /// ```
+    async fn wallet_stats(
+        token: auth::Token,
+        dust_threshold: NativeCurrencyAmount,
+    ) -> RpcResult<WalletStats>;
 
-                assert!(tip_block_header.height >= latest_balance_height);
+    /// Generate a list of all UTXOs, currently owned, historical, time-locked,
+    /// not, abandoned.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // query neptune-core server to get the list of UTXOs
+    /// let own_coins = client.list_utxos(context::current(), token ).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn list_utxos(token: auth::Token) -> RpcResult<Vec<UiUtxo>>;
 
-                // subtract latest balance height from chain tip.
-                //
-                // we add 1 to the result because the block that a tx is confirmed
-                // in is considered the 1st confirmation.
-                //
-                // note: BlockHeight is u64 internally and BlockHeight::sub() returns i128.
-                //       The subtraction and cast is safe given we passed the above assert.
-                let confirmations: BlockHeight =
-                    ((tip_block_header.height - latest_balance_height) as u64 + 1).into();
-                Some(confirmations)
-            }
-            None => None,
-        }
-    }
+    /// Get CPU temperature.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // query neptune-core server instance to get its CPU temperature
+    /// let cpu_temperature = client.cpu_temp(context::current(), token ).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn cpu_temp(token: auth::Token) -> RpcResult<Option<f32>>;
 
-    /// Return temperature of CPU, if available.
-    fn cpu_temp_inner() -> Option<f32> {
-        let current_system = System::new();
-        current_system.cpu_temp().ok()
-    }
+    /// Get a summary of the node's health, including whether it is shedding
+    /// load due to CPU, memory, or disk pressure.
+    ///
+    /// In load-shedding mode, the node pauses transaction-proof upgrading,
+    /// maintains fewer peer connections, and rejects low-fee transactions. The
+    /// mode is entered and exited automatically if enabled; see
+    /// `--load-shedding` and the `--load-shedding-*` options.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // query neptune-core server instance for its health
+    /// let health = client.node_health(context::current(), token).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn node_health(token: auth::Token) -> RpcResult<NodeHealth>;
 
-    /// Assemble a data for the wallet to register the UTXO. Returns `Ok(None)`
-    /// if the UTXO has already been claimed by the wallet.
+    /// Get the readiness of the network for upcoming consensus changes.
     ///
-    /// `max_search_depth` denotes how many blocks back from tip we attempt
-    /// to find the transaction in a block. `None` means unlimited.
+    /// Nodes signal readiness for the consensus rule sets they know of that
+    /// activate after their tip, in the handshake. For each rule set signaled
+    /// for, by this node or its peers, the report lists the number and
+    /// percentage of connected and recently connected peers signaling
+    /// readiness. Rule sets this node does not know of are included, as they
+    /// indicate that this node is outdated.
     ///
-    /// `encrypted_utxo_notification` is expected to hold encrypted data about
-    /// a future or past UTXO, which can be claimed by this client.
-    async fn claim_utxo_inner(
-        &self,
-        encrypted_utxo_notification: String,
-        max_search_depth: Option<u64>,
-    ) -> Result<Option<ClaimUtxoData>, error::ClaimError> {
-        let span = tracing::debug_span!("Claim UTXO inner");
-        let _enter = span.enter();
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // query neptune-core server instance for the readiness of the network
+    /// let readiness = client.fork_readiness(context::current(), token).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn fork_readiness(token: auth::Token) -> RpcResult<ForkReadinessReport>;
 
-        // deserialize UtxoTransferEncrypted from bech32m string.
-        let network = self.state.cli().network;
-        let utxo_transfer_encrypted =
-            EncryptedUtxoNotification::from_bech32m(&encrypted_utxo_notification, network)?;
+    /// Get the settings the node was started with, as a TOML configuration
+    /// file that can be passed back with `--config`.
+    ///
+    /// Includes the defaults, the contents of the configuration file, and the
+    /// command-line arguments, which take precedence in that order. Returns
+    /// `None` if the node was not started from the command line.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // query neptune-core server instance for its effective configuration
+    /// let config = client.effective_config(context::current(), token).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn effective_config(token: auth::Token) -> RpcResult<Option<String>>;
 
-        // // acquire global state read lock
-        let state = self.state.lock_guard().await;
+    /// Get a bundle of diagnostics to attach to bug reports: versions, a
+    /// summary of the configuration, the peer table, the tip, recent warnings
+    /// and errors, and resource usage.
+    ///
+    /// Wallet data is never included. Configuration values that are secret or
+    /// reveal the local file system are always redacted, and network addresses
+    /// are redacted unless `include_addresses` is set. The bundle lists what
+    /// was redacted from it.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // query neptune-core server instance for diagnostics, without addresses
+    /// let include_addresses = false;
+    /// let bundle = client
+    ///     .diagnostics(context::current(), token, include_addresses)
+    ///     .await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn diagnostics(
+        token: auth::Token,
+        include_addresses: bool,
+    ) -> RpcResult<DiagnosticsBundle>;
+
+    /// Get counters describing how RPC requests have been handled since
+    /// startup, including the number of requests rejected because the server
+    /// was busy, requests that timed out, and requests that were cancelled
+    /// because the client disconnected.
+    ///
+    /// The limits are configured with `--rpc-max-concurrent-requests`,
+    /// `--rpc-request-timeout`, and `--rpc-method-timeouts`.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // query neptune-core server instance for its RPC request metrics
+    /// let metrics = client.rpc_request_metrics(context::current(), token).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn rpc_request_metrics(token: auth::Token) -> RpcResult<RpcRequestMetrics>;
+
+    /// Get counters describing the block validation cache, which remembers
+    /// the outcome of validating blocks so that blocks received repeatedly,
+    /// eg during reorgs, are only verified once.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // query neptune-core server instance for its block validation cache metrics
+    /// let metrics = client.block_validation_cache_metrics(context::current(), token).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn block_validation_cache_metrics(
+        token: auth::Token,
+    ) -> RpcResult<BlockValidationCacheMetrics>;
+
+    /// Get statistics on the usage of the chain: the size of the append-only
+    /// commitment list (AOCL) of the mutator set, the estimated anonymity set
+    /// of inputs, the density of the active window of the sliding window
+    /// Bloom filter, and the number of additions and removals per block in
+    /// recent blocks.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // query neptune-core server instance for its mutator set statistics
+    /// let statistics = client.mutator_set_statistics(context::current(), token).await??;
+    /// println!("anonymity set: {}", statistics.estimated_anonymity_set_size);
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn mutator_set_statistics(token: auth::Token) -> RpcResult<MutatorSetStatistics>;
+
+    /// Get the signed checkpoints of the canonical chain published by this
+    /// node, most recent first.
+    ///
+    /// Checkpoints are only published if the node was started with
+    /// `--checkpoint-interval`. Each checkpoint commits to the digest, height,
+    /// cumulative proof-of-work, and mutator set hash of a canonical block,
+    /// and is signed with a key derived from the node's wallet. Light clients
+    /// can verify the signatures and compare checkpoints across nodes.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // query neptune-core server instance for its published checkpoints
+    /// let checkpoints = client.checkpoints(context::current(), token).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn checkpoints(token: auth::Token) -> RpcResult<Vec<SignedCheckpoint>>;
+
+    /// Get the outcome of the most recent checks for new releases.
+    ///
+    /// Releases are only checked for if the node was started with
+    /// `--release-manifest-url`. The notice reports whether a newer version is
+    /// available, and whether it must be installed before some block height.
+    /// Only manifests signed by the release key of the node's network are
+    /// trusted. The node never updates itself.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // query neptune-core server instance for available updates
+    /// let update_status = client.update_status(context::current(), token).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn update_status(token: auth::Token) -> RpcResult<UpdateStatus>;
+
+    /// Get whether this node is a primary or a standby, and how far it has
+    /// replicated from its primary.
+    ///
+    /// Only a primary can initiate transactions.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // query neptune-core server instance for its replication status
+    /// let replication_status = client.replication_status(context::current(), token).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn replication_status(token: auth::Token) -> RpcResult<ReplicationStatus>;
+
+    /// Get the status of the pruning of abandoned fork blocks.
+    ///
+    /// Fork blocks are only pruned if the node is archival and was started
+    /// with `--fork-pruning-depth`, in which case the bodies and proofs of
+    /// fork blocks deeper than this depth are deleted, while their headers
+    /// are retained. The counters cover all pruning since the block index
+    /// database was created.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // query neptune-core server instance for its fork-pruning status
+    /// let status = client.fork_pruning_status(context::current(), token).await??;
+    /// println!("pruned {} fork blocks", status.num_pruned_blocks);
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn fork_pruning_status(token: auth::Token) -> RpcResult<ForkPruningStatus>;
+
+    /// Watch new blocks for announcements carrying the receiver identifier of
+    /// `item`, independently of the wallet.
+    ///
+    /// Matches are logged and can be queried with `watch_matches`. Only the
+    /// receiver identifier is retained, so no secret material is stored even
+    /// if `item` is a symmetric address. Watching an already-watched item
+    /// replaces its label. Watched items are not persisted across restarts.
+    ///
+    /// Returns the receiver identifier that is matched.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::state::watch_list::WatchItem;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // watch a customer's deposit address
+    /// let item = WatchItem::Address("nolgam1...".to_string());
+    /// let receiver_identifier = client
+    ///     .watch(context::current(), token, item, "customer 42".to_string())
+    ///     .await??;
+    ///
+    /// // stop watching it
+    /// client.unwatch(context::current(), token, receiver_identifier).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn watch(token: auth::Token, item: WatchItem, label: String) -> RpcResult<BFieldElement>;
+
+    /// Stop watching a receiver identifier. Returns true iff it was watched.
+    /// Matches found already are retained.
+    async fn unwatch(token: auth::Token, receiver_identifier: BFieldElement) -> RpcResult<bool>;
+
+    /// List the receiver identifiers watched by this node.
+    async fn watched_items(token: auth::Token) -> RpcResult<Vec<WatchEntry>>;
+
+    /// Get the matches of watched items in canonical blocks at or above
+    /// `from_height`, oldest first.
+    ///
+    /// Matches in blocks that were reorganized away are dropped. Only the most
+    /// recent matches are retained.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::api::export::BlockHeight;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // matches since block 1000
+    /// let matches = client
+    ///     .watch_matches(context::current(), token, BlockHeight::from(1000u64))
+    ///     .await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn watch_matches(
+        token: auth::Token,
+        from_height: BlockHeight,
+    ) -> RpcResult<Vec<WatchMatch>>;
+
+    /// Replay the events caused by new tips, ie new blocks, UTXOs received by
+    /// the wallet and confirmed transactions, that come after sequence number
+    /// `since` and were not acknowledged by `subscriber`. Oldest first, and
+    /// at most [MAX_NUM_REPLAYED_EVENTS](crate::state::event_log::MAX_NUM_REPLAYED_EVENTS)
+    /// per call.
+    ///
+    /// Every event carries a sequence number that increases by one with every
+    /// event. The node remembers, also across restarts, the last sequence
+    /// number each subscriber acknowledged with
+    /// [acknowledge_events](RPC::acknowledge_events). Events are replayed
+    /// until they are acknowledged, so a subscriber that fails while handling
+    /// them receives them again. An event is not recorded again if a
+    /// reorganization repeats it. Only the most recent events are retained.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // events not yet acknowledged by the "accounting" subscriber
+    /// let events = client
+    ///     .replay_events(context::current(), token, "accounting".to_string(), 0)
+    ///     .await??;
+    /// for event in &events {
+    ///     println!("{}: {:?}", event.sequence_number, event.event);
+    /// }
+    ///
+    /// // once handled, the events are not replayed to the subscriber again
+    /// if let Some(last) = events.last() {
+    ///     let subscriber = "accounting".to_string();
+    ///     client
+    ///         .acknowledge_events(context::current(), token, subscriber, last.sequence_number)
+    ///         .await??;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn replay_events(
+        token: auth::Token,
+        subscriber: String,
+        since: u64,
+    ) -> RpcResult<Vec<SequencedEvent>>;
+
+    /// Acknowledge the events up to and including `sequence_number` on behalf
+    /// of `subscriber`, such that [replay_events](RPC::replay_events) does not
+    /// return them to the subscriber again. Returns once the acknowledgement
+    /// is persisted.
+    async fn acknowledge_events(
+        token: auth::Token,
+        subscriber: String,
+        sequence_number: u64,
+    ) -> RpcResult<()>;
+
+    /// Report the congestion of the channels to the main task since startup:
+    /// how often they were full, and what happened to the messages that did
+    /// not fit, as governed by `--peer-channel-overflow-policy`.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// for channel in client.channel_metrics(context::current(), token).await?? {
+    ///     println!("{}: {} dropped", channel.name, channel.num_shed + channel.num_timed_out);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn channel_metrics(token: auth::Token) -> RpcResult<Vec<ChannelMetrics>>;
+
+    /// Report the effectiveness of the in-memory read caches of the mutator
+    /// set and the archival block MMR since startup, as sized by
+    /// `--db-read-cache-size`. Tables whose cache is disabled are omitted, as
+    /// are all tables on nodes without archival state.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// for cache in client.db_read_cache_metrics(context::current(), token).await?? {
+    ///     println!("{}: hit ratio {:?}", cache.table, cache.metrics.hit_ratio());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn db_read_cache_metrics(token: auth::Token) -> RpcResult<Vec<TableReadCacheMetrics>>;
+
+    /// Report the bytes exchanged with peers, split by message category, in
+    /// total since startup and for each connected peer.
+    ///
+    /// Sizes are those of the encoded messages, before compression. Helps
+    /// operators identify the peers and kinds of traffic using the most
+    /// bandwidth.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// let report = client.bandwidth(context::current(), token).await??;
+    /// for peer in report.peers {
+    ///     println!("{}: {} bytes", peer.address, peer.usage.bytes_total());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn bandwidth(token: auth::Token) -> RpcResult<BandwidthReport>;
+
+    /// Report how many peer connections are in each lifecycle state
+    /// (connecting, handshaking, active, draining, closed), how many entered
+    /// each state since startup, and how many timed out in each state.
+    ///
+    /// Connections that keep timing out in the same state point to network
+    /// problems or misbehaving peers.
+    ///
+    /// Also reports how many incoming connections are handshaking, how many
+    /// were dropped because too many were handshaking, and how many handshakes
+    /// timed out after the peer had sent only part of its handshake. The
+    /// latter two point to slow-loris attacks.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// let metrics = client.connection_lifecycle(context::current(), token).await??;
+    /// for state in metrics.states {
+    ///     println!("{}: {} current, {} timed out", state.state, state.current, state.timed_out);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn connection_lifecycle(token: auth::Token) -> RpcResult<ConnectionLifecycleMetrics>;
+
+    /// Report the time the main loop spent handling messages from the peer
+    /// tasks, the miner, and the RPC server since startup, as a latency
+    /// histogram per message type.
+    ///
+    /// The main loop handles one message at a time, so message types with
+    /// long latencies stall the node and point to the cause of a stall.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// let stats = client.main_loop_stats(context::current(), token).await??;
+    /// for latency in stats.message_types {
+    ///     println!("{} {}: {} handled, max {:?}", latency.source, latency.message_type, latency.count, latency.max);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn main_loop_stats(token: auth::Token) -> RpcResult<MainLoopStats>;
+
+    /// Clean up the data directory now rather than at the next hourly
+    /// cleanup: rotate large logs, and delete expired rotated logs, database
+    /// migration backups, and orphaned temporary files.
+    ///
+    /// Retention is governed by `--max-log-file-size`, `--log-retention`,
+    /// `--migration-backup-retention`, and `--temporary-file-retention`.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// let report = client.clean_data_directory(context::current(), token).await??;
+    /// println!("reclaimed {} bytes", report.num_bytes_reclaimed);
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn clean_data_directory(token: auth::Token) -> RpcResult<CleanupReport>;
+
+    /// Export block headers, transaction kernel metadata, and fees of the
+    /// canonical blocks with heights in the given range to CSV files in the
+    /// data directory, for offline analytics.
+    ///
+    /// Blocks are written as they are read, so exporting a long range does
+    /// not use more memory than a short one. The export stops at the tip.
+    /// Requires an archival node. See
+    /// [Chain::export_csv()](crate::api::chain::Chain::export_csv()) for the
+    /// files and their columns.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use neptune_cash::protocol::consensus::block::block_height::BlockHeight;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// let report = client
+    ///     .export_chain_data(
+    ///         context::current(),
+    ///         token,
+    ///         BlockHeight::genesis(),
+    ///         BlockHeight::from(1000u64),
+    ///     )
+    ///     .await??;
+    /// println!("exported {} blocks to {}", report.num_blocks, report.directory.display());
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn export_chain_data(
+        token: auth::Token,
+        first_height: BlockHeight,
+        last_height: BlockHeight,
+    ) -> RpcResult<ChainExportReport>;
+
+    /// Return the wallet's confirmed balances and unspent UTXOs as of the
+    /// canonical block at the given height, for accounting and audits.
+    ///
+    /// Requires an archival node. The height may lie at most
+    /// `--max-historical-balance-depth` blocks below the tip. See
+    /// [Wallet::balance_at_height()](crate::api::wallet::Wallet::balance_at_height())
+    /// for how the historical view is computed.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use neptune_cash::protocol::consensus::block::block_height::BlockHeight;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// let balance = client
+    ///     .historical_balance(context::current(), token, BlockHeight::from(1000u64))
+    ///     .await??;
+    /// println!("balance at block {}: {}", balance.block_height, balance.confirmed_total);
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn historical_balance(
+        token: auth::Token,
+        height: BlockHeight,
+    ) -> RpcResult<HistoricalBalance>;
+
+    /// Return the memos that senders announced along with UTXOs received by
+    /// the wallet, for every monitored UTXO that has any.
+    ///
+    /// Memos of recognized formats, such as amount hints and invoice ids, are
+    /// decoded. Others are returned raw, as their format identifier and
+    /// payload. See [memo](crate::state::wallet::memo) for how memos are
+    /// announced.
+    ///
+    /// Memos are unauthenticated, so anyone who knows the receiving address
+    /// can attach memos to a payment to it.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// let utxo_memos = client.utxo_memos(context::current(), token).await??;
+    /// for utxo in utxo_memos {
+    ///     println!("{} received at block {}: {:?}", utxo.amount, utxo.confirmed_at, utxo.memos);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn utxo_memos(token: auth::Token) -> RpcResult<Vec<UtxoMemos>>;
+
+    /// Return deterministic key and address derivation vectors, with which
+    /// third-party wallet implementations can verify that they derive the
+    /// same keys and addresses as neptune-core.
+    ///
+    /// Vectors are derived from the seed with the given BIP-39 mnemonic
+    /// `phrase`, or from the publicly known devnet seed if `phrase` is `None`,
+    /// for indices `0..num_indices` of every key type. Addresses are encoded
+    /// for the node's network. At most 256 indices are supported.
+    ///
+    /// Vectors include secret key material and do not depend on the node's
+    /// wallet. Never pass the phrase of a wallet holding funds, least of all
+    /// to a remote node.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // vectors for the devnet seed
+    /// let derived = client.derivation_vectors(context::current(), token, None, 10).await??;
+    /// for vector in derived.vectors {
+    ///     println!("{} {}: {}", vector.key_type, vector.index, vector.address);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn derivation_vectors(
+        token: auth::Token,
+        phrase: Option<Vec<String>>,
+        num_indices: u64,
+    ) -> RpcResult<DerivationVectors>;
+
+    /// Return the proof-of-work accumulated by the chain up to the specified
+    /// block, or `None` if the block is not known.
+    ///
+    /// `cumulative_proof_of_work` is the work of the block's ancestors, as
+    /// declared in its header and compared by the fork-choice rule.
+    /// `total_proof_of_work` adds the block's own difficulty.
+    ///
+    /// Blocks other than the tip require an archival node.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// use neptune_cash::protocol::consensus::block::block_selector::BlockSelector;
+    /// use neptune_cash::protocol::consensus::block::block_selector::BlockSelectorLiteral;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// let block_selector = BlockSelector::Special(BlockSelectorLiteral::Tip);
+    /// if let Some(work) = client.chain_work(context::current(), token, block_selector).await?? {
+    ///     println!("tip {} has cumulative work {}", work.height, work.cumulative_proof_of_work);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn chain_work(
+        token: auth::Token,
+        block_selector: BlockSelector,
+    ) -> RpcResult<Option<ChainWork>>;
+
+    /// Explain the current fork-choice decision, to help debug sync
+    /// disagreements between nodes.
+    ///
+    /// Lists the competing tips found in the `depth` heights below the tip
+    /// and above it, ie the stored blocks that are neither canonical nor have
+    /// a stored successor, along with their cumulative proof-of-work, the
+    /// height at which they fork off the canonical chain, and why the current
+    /// tip is preferred. A competing tip with verdict `MoreWork` means the
+    /// node did not switch to a heavier chain, which points at invalid or
+    /// missing blocks.
+    ///
+    /// Also reports the cumulative proof-of-work of the chain the node is
+    /// syncing towards, if it is syncing.
+    ///
+    /// `depth` is capped at 1000. Requires an archival node.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// let audit = client.fork_choice_audit(context::current(), token, 100).await??;
+    /// for competing_tip in audit.competing_tips {
+    ///     println!(
+    ///         "{} forks off at {:?}: {:?}",
+    ///         competing_tip.work.digest, competing_tip.fork_height, competing_tip.verdict
+    ///     );
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn fork_choice_audit(token: auth::Token, depth: u64) -> RpcResult<ForkChoiceAudit>;
+
+    /// Return the mining status and, while guessing, the number of guesses
+    /// and guess rate of each local guesser thread.
+    ///
+    /// `num_overlaps` counts the times two guesser threads were assigned the
+    /// same part of the nonce space, and should always be zero.
+    ///
+    /// The report also shows how many reorganizations orphaned the block the
+    /// composer was composing on, and for how long composition is suspended
+    /// because they exhausted the budget set by `--reorg-compose-budget`.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// let report = client.mining_status(context::current(), token).await??;
+    /// if let Some(guessing) = report.guessing {
+    ///     println!("guessing at {:.0} guesses/s", guessing.guess_rate());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn mining_status(token: auth::Token) -> RpcResult<MiningStatusReport>;
+
+    /// Get the proof-of-work puzzle for the current block proposal. Uses the
+    /// node's secret key to populate the guesser digest.
+    ///
+    /// Returns `None` if no block proposal for the next block is known yet.
+    async fn pow_puzzle_internal_key(token: auth::Token) -> RpcResult<Option<ProofOfWorkPuzzle>>;
+
+    /// Get the proof-of-work puzzle for the current block proposal. Like
+    /// [Self::pow_puzzle_internal_key] but returned puzzle uses an externally
+    /// provided digest to populate the guesser digest field in the block
+    /// header, meaning that this client cannot claim the reward in case a
+    /// valid PoW-solution is found. This endpoint allows for "cold" guessing
+    /// where the node does not hold the key to spend the guesser reward.
+    ///
+    /// Returns `None` if no block proposal for the next block is known yet.
+    async fn pow_puzzle_external_key(
+        token: auth::Token,
+        guesser_fee_address: ReceivingAddress,
+    ) -> RpcResult<Option<ProofOfWorkPuzzle>>;
+
+    /// Get the proof-of-work puzzle for the current block proposal, along with
+    /// the block proposal itself. Works like [`Self::pow_puzzle_external_key`]
+    /// but does not task the node with remembering the proposal, meaning that
+    /// another node can receive the solution if one is found. If a solution is
+    /// found, the endpoint [`Self::provide_new_tip()`] can be used to pass the
+    /// solution onto a node.
+    async fn full_pow_puzzle_external_key(
+        token: auth::Token,
+        guesser_fee_address: ReceivingAddress,
+    ) -> RpcResult<Option<(Block, ProofOfWorkPuzzle)>>;
+
+    /// todo: docs.
+    ///
+    /// meanwhile see [tx_initiation::initiator::TransactionInitiator::spendable_inputs()]
+    async fn spendable_inputs(token: auth::Token) -> RpcResult<TxInputList>;
+
+    /// retrieve spendable inputs sufficient to cover spend_amount by applying selection policy.
+    ///
+    /// see [InputSelectionPolicy]
+    ///
+    /// pub enum InputSelectionPolicy {
+    ///     Random,
+    ///     ByNativeCoinAmount(SortOrder),
+    ///     ByUtxoSize(SortOrder),
+    /// }
+    ///
+    /// todo: docs.
+    ///
+    /// meanwhile see [tx_initiation::initiator::TransactionInitiator::select_spendable_inputs()]
+    async fn select_spendable_inputs(
+        token: auth::Token,
+        policy: InputSelectionPolicy,
+        spend_amount: NativeCurrencyAmount,
+    ) -> RpcResult<TxInputList>;
+
+    /// generate tx outputs from list of OutputFormat.
+    ///
+    /// OutputFormat can be address:amount, address:amount:medium, address:utxo,
+    /// address:utxo:medium, tx_output, etc.
+    ///
+    /// todo: docs.
+    ///
+    /// meanwhile see [tx_initiation::initiator::TransactionInitiator::generate_tx_outputs()]
+    async fn generate_tx_outputs(
+        token: auth::Token,
+        outputs: Vec<OutputFormat>,
+    ) -> RpcResult<TxOutputList>;
+
+    /// Helper endpoint for constructing a transaction. Can be used in
+    /// connection with other endpoints, e.g. endpoints that select inputs and
+    /// outputs to a transaction.
+    ///
+    /// meanwhile see [tx_initiation::initiator::TransactionInitiator::generate_tx_details()]
+    async fn generate_tx_details(
+        token: auth::Token,
+        tx_inputs: TxInputList,
+        tx_outputs: TxOutputList,
+        change_policy: ChangePolicy,
+        fee: NativeCurrencyAmount,
+    ) -> RpcResult<TransactionDetails>;
+
+    /// todo: docs.
+    ///
+    /// meanwhile see [tx_initiation::initiator::TransactionInitiator::generate_witness_proof()]
+    async fn generate_witness_proof(
+        token: auth::Token,
+        tx_details: TransactionDetails,
+    ) -> RpcResult<TransactionProof>;
+
+    /// assemble a transaction from TransactionDetails and a TransactionProof.
+    ///
+    /// todo: docs.
+    ///
+    /// meanwhile see [tx_initiation::initiator::TransactionInitiator::assemble_transaction()]
+    async fn assemble_transaction(
+        token: auth::Token,
+        transaction_details: TransactionDetails,
+        transaction_proof: TransactionProof,
+    ) -> RpcResult<Transaction>;
+
+    /// assemble transaction artifacts from TransactionDetails and a TransactionProof.
+    ///
+    /// todo: docs.
+    ///
+    /// meanwhile see [tx_initiation::initiator::TransactionInitiator::assemble_transaction_artifacts()]
+    async fn assemble_transaction_artifacts(
+        token: auth::Token,
+        transaction_details: TransactionDetails,
+        transaction_proof: TransactionProof,
+    ) -> RpcResult<TxCreationArtifacts>;
+
+    /// todo: docs.
+    ///
+    /// meanwhile see [tx_initiation::initiator::TransactionInitiator::proof_type()]
+    async fn proof_type(
+        token: auth::Token,
+        txid: TransactionKernelId,
+    ) -> RpcResult<TransactionProofType>;
+
+    /******** BLOCKCHAIN STATISTICS ********/
+    // Place all endpoints that relate to statistics of the blockchain here
+
+    /// Return the block intervals of a range of blocks. Return value is the
+    /// number of milliseconds it took to mine the (canonical) block with the
+    /// specified height. Does not include the interval between genesis block
+    /// and block 1 since genesis block was not actually mined and its timestamp
+    /// doesn't carry the same meaning as those of later blocks.
+    async fn block_intervals(
+        token: auth::Token,
+        last_block: BlockSelector,
+        max_num_blocks: Option<usize>,
+    ) -> RpcResult<Option<Vec<(u64, u64)>>>;
+
+    /// Return the difficulties of a range of blocks.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// use neptune_cash::protocol::consensus::block::block_selector::BlockSelector;
+    /// use neptune_cash::protocol::consensus::block::block_selector::BlockSelectorLiteral;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // sets the last block
+    /// let last_block : BlockSelector = BlockSelector::Special(BlockSelectorLiteral::Genesis);
+    ///
+    /// // set maximum number of blocks to 5 blocks
+    /// let max_num_blocks : Option<usize> = Some(5);
+    ///
+    /// // query neptune-core server to get difficulties of a range of blocks
+    /// let block_difficulties = client.block_difficulties(context::current(), token, last_block, max_num_blocks).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn block_difficulties(
+        token: auth::Token,
+        last_block: BlockSelector,
+        max_num_blocks: Option<usize>,
+    ) -> RpcResult<Vec<(u64, Difficulty)>>;
+
+    /******** PEER INTERACTIONS ********/
+
+    /// Broadcast transaction notifications for all transactions in this node's
+    /// mempool.
+    async fn broadcast_all_mempool_txs(token: auth::Token) -> RpcResult<()>;
+
+    /// Broadcast running node's current favorable block proposal.
+    async fn broadcast_block_proposal(token: auth::Token) -> RpcResult<()>;
+
+    /******** CHANGE THINGS ********/
+    // Place all things that change state here
+
+    /// Clears standing for all peers, connected or not
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // neptune-core server clears standing for all peers that are connected or not
+    /// let _ = client.clear_all_standings(context::current(), token).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn clear_all_standings(token: auth::Token) -> RpcResult<()>;
+
+    /// Clears standing for ip, whether connected or not
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// # use std::net::{IpAddr, Ipv4Addr};
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// // create an rpc client using the transport.
+    /// let client = RPCClient::new(client::Config::default(), transport).spawn();
+    ///
+    /// // Defines cookie hint
+    /// let cookie_hint = client.cookie_hint(context::current()).await??;
+    ///
+    /// // load the cookie file from disk and assign it to a token
+    /// let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    ///
+    /// // IP address 87.23.90.12 to clear standing
+    /// let ip = IpAddr::V4(Ipv4Addr::new(87, 23, 90, 12));
+    ///
+    /// // neptune-core server clears standing for all peers that are connected or not
+    /// let _ = client.clear_standing_by_ip(context::current(), token, ip).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn clear_standing_by_ip(token: auth::Token, ip: IpAddr) -> RpcResult<()>;
+
+    /// record transaction and initiate broadcast to peers
+    ///
+    /// todo: docs.
+    ///
+    /// meanwhile see [tx_initiation::initiator::TransactionInitiator::record_and_broadcast_transaction()]
+    async fn record_and_broadcast_transaction(
+        token: auth::Token,
+        tx_artifacts: TxCreationArtifacts,
+    ) -> RpcResult<()>;
+
+    /// Send coins to one or more recipients
+    ///
+    /// note: sending is rate-limited to 2 sends per block until block
+    /// 25000 is reached.
+    ///
+    /// `outputs` is a list of transaction outputs in any format supported by [OutputFormat].
+    ///
+    /// `change_policy` specifies how to handle change in the typical case that
+    /// the transaction input amount exceeds the output amount.
+    ///
+    /// `fee` represents the fee in native coins to pay the miner who mines
+    /// the block that initially confirms the resulting transaction.
+    ///
+    /// a [Digest] of the resulting [Transaction](crate::protocol::consensus::transaction::Transaction) is returned on success, else [None].
+    ///
+    /// A list of the encoded transaction notifications is also returned. The
+    /// relevant notifications should be sent to the transaction receiver(s) in
+    /// case `Offchain` notification is used for any output(s).
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::config::network::Network;
+    /// # use neptune_cash::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
+    /// # use neptune_cash::state::wallet::address::ReceivingAddress;
+    /// # use neptune_cash::state::wallet::utxo_notification::UtxoNotificationMedium;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use neptune_cash::api::export::ChangePolicy;
+    /// # use neptune_cash::api::export::OutputFormat;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// # use std::net::IpAddr;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // List of receiving addresses and the amounts to send
+    /// let outputs: Vec<OutputFormat> = vec![
+    ///     (ReceivingAddress::from_bech32m("nolgam1lf8vc5xpa4jf9vjakts632fct5q80d4m6tax39nrl8c55dta2h7n7lnkh9pmwckl0ndwc7897xwfgx5vv02xdt3099z62222wazz7tjl6umzewla9xzxyqefh2w47v4eh0xzvfsxjk6kq5u84rwwlflq7cs726ljttl6ls860te04cwpy5kk8n40qqjnps0gdp46namhsa3cqt0uc0s5e34h6s5rw2kl77uvvs4rlnn5t8wtuefsduuccwsxmk27r8d48g49swgafhj6wmvu5cx3lweqhnxgdgm7mmdq7ck6wkurw2jzl64k9u34kzgu9stgd47ljzte0hz0n2lcng83vtpf0u9f4hggw4llqsz2fqpe4096d9v5fzg7xvxg6zvr7gksq4yqgn8shepg5xsczmzz256m9c6r8zqdkzy4tk9he59ndtdkrrr8u5v6ztnvkvmy4sed7p7plm2y09sgksw6zcjayls4wl9fnqu97kyx9cdknksar7h8jetygur979rt5arcwmvp2dy3ynt6arna2yjpevt9209v9g2p5cvp6gjp9850w3w6afeg8yuhp6u447hrudcssyjauqa2p7jk4tz37wg70yrdhsgn35sc0hdkclvpapu75dgtmswk0vtgadx44mqdps6ry6005xqups9dpc93u66qj9j7lfaqgdqrrfg9pkxhjl99ge387rh257x2phfvjvc8y66p22wax8myyhm7mgmlxu9gug0km3lmn4lzcyj32mduy6msy4kfn5z2tr67zfxadnj6wc0av27mk0j90pf67uzp9ps8aekr24kpv5n3qeczfznen9vj67ft95s93t26l8uh87qr6kp8lsyuzm4h36de830h6rr3lhg5ac995nrsu6h0p56t5tnglvx0s02mr0ts95fgcevveky5kkw6zgj6jd5m3n5ljhw862km8sedr30xvg8t9vh409ufuxdnfuypvqdq49z6mp46p936pjzwwqjda6yy5wuxx9lffrxwcmfqzch6nz2l4mwd2vlsdr58vhygppy6nm6tduyemw4clwj9uac4v990xt6jt7e2al7m6sjlq4qgxfjf4ytx8f5j460vvr7yac9hsvlsat2vh5gl55mt4wr7v5p3m6k5ya5442xdarastxlmpf2vqz5lusp8tlglxkj0jksgwqgtj6j0kxwmw40egpzs5rr996xpv8wwqyja4tmw599n9fh77f5ruxk69vtpwl9z5ezmdn92cpyyhwff59ypp0z5rv98vdvm67umqzt0ljjan30u3a8nga35fdy450ht9gef24mveucxqwv5aflge5r3amxsvd7l30j9kcqm7alq0ks2wqpde7pdct2gmvafxvjg3ad0a3h58assjaszvmykl3k5tn238gstm2shlvad4a53mm5ztvp5q2zt4pdzj0ssevlkumwhc0g5cxnxc9u7rh9gffkq7h9ufcxkgtghe32sv3vwzkessr52mcmajt83lvz45wqru9hht8cytfedtjlv7z7en6pp0guja85ft3rv6hzf2e02e7wfu38s0nyfzkc2qy2k298qtmxgrpduntejtvenr80csnckajnhu44399tkm0a7wdldalf678n9prd54twwlw24xhppxqlquatfztllkeejlkfxuayddwagh6uzx040tqlcs7hcflnu0ywynmz0chz48qcx7dsc4gpseu0dqvmmezpuv0tawm78nleju2vp4lkehua56hrnuj2wuc5lqvxlnskvp53vu7e2399pgp7xcwe3ww23qcd9pywladq34nk6cwcvtj3vdfgwf6r7s6vq46y2x05e043nj6tu8am2und8z3ftf3he5ccjxamtnmxfd79m04ph36kzx6e789dhqrwmwcfrn9ulsedeplk3dvrmad6f20y9qfl6n6kzaxkmmmaq4d6s5rl4kmhc7fcdkrkandw2jxdjckuscu56syly8rtjatj4j2ug23cwvep3dgcdvmtr32296nf9vdl3rcu0r7hge23ydt83k5nhtnexuqrnamveacz6c43eay9nz4pjjwjatkgp80lg9tnf5kdr2eel8s2fk6v338x4hu00htemm5pq6qlucqqq5tchhtekjzdu50erqd2fkdu9th3wl0mqxz5u7wnpgwgpammv2yqpa5znljegyhke0dz9vg27uh5t5x6qdgf7vu54lqssejekwzfxchjyq2s8frm9fmt688w76aug56v6n3w5xdre78xplfsdw3e4j6dc5w7tf83r25re0duq6h8z54wnkqr9yh2k0skjqea4elgcr4aw7hks9m8w3tx8w9xlxpqqll2zeql55ew7e90dyuynkqxfuqzv45t22ljamdll3udvqrllprdltthzm866jdaxkkrnryj4cmc2m7sk99clgql3ynrhe9kynqn4mh3tepk8dtq7cndtc2hma29s4cuylsvg04s70uyr53w5656su5rjem5egss08zrfaef0mww6t8pr26uph2n8a2cs55ydx4xhasjqk7xs0akh6f26j2ec4d8pd0kdf4jya6p9jl48wmy5autdpw2q8mehrq6kypt573genj66l5zkq6xvrdqugmfczxa2gj9ylx3pgpjqnhuem9udfkj9qr2y8lh728sr7uaedu5wwmfa72ykh395jqh7f7f9p2gskn6u7k844kpnwe3eqv84pl53r6x9af88a8ey7298njdg03h8mxqz2x6z8ys3qpuxq768tjq0zhrnjgns8d78euzwsvx6vn4f9tftrp68zcch3h75mc9drpt7tpvnyyqfjuqclxhdwhdwtsakecv04p9r3jx90htql9a3ht5mxrj4ercv4cd52wk4qhu7dn4tqe7yclqx2l36gcsrzmdlv440qls7qjpq6k95mst485vpennnur8h62a7d7syvyer89qtyfzlfhz8a5a0x5tuwhc9mah0e944xzhsc6uvpv8vat44w7r3xyw8q85y77jux8zhndrhdn36swryffqmpkxgcw4g29q40sul4fl5vrfru08a5j3rd3jl8799srpf2xqpxq38wwvhr4mxqf5wwdqfqq7harshggvufzlgn0l9fq0j76dyuge75jmzy8celvw6wesfs82n4jw2k8jnus2zds5a67my339uuzka4w72tau6j7wyu0lla0mcjpaflphsuy7f2phev6tr8vc9nj2mczkeg4vy3n5jkgecwgrvwu3vw9x5knpkxzv8kw3dpzzxy3rvrs56vxw8ugmyz2vdj6dakjyq3feym4290l7hgdt0ac5u49sekezzf0ghwmlek4h75fkzpvuly9zupw32dd3l9my282nekgk78fe6ayjyhczetxf8r82yd2askl52kmupr9xaxw0jd08dsd3523ea6ge48384rlmt4mu4w4x0q9s", Network::Main)?, NativeCurrencyAmount::coins(20)).into(),
+    ///     (ReceivingAddress::from_bech32m("nolgam1ld9vc5xpa4jf9vjakts632fct5q80d4m6tax39nrl8c55dta2h7n7lnkh9pmwckl0ndwc7897xwfgx5vv02xdt3099z62222wazz7tjl6umzewla9xzxyqefh2w47v4eh0xzvfsxjk6kq5u84rwwlflq7cs726ljttl6ls860te04cwpy5kk8n40qqjnps0gdp46namhsa3cqt0uc0s5e34h6s5rw2kl77uvvs4rlnn5t8wtuefsduuccwsxmk27r8d48g49swgafhj6wmvu5cx3lweqhnxgdgm7mmdq7ck6wkurw2jzl64k9u34kzgu9stgd47ljzte0hz0n2lcng83vtpf0u9f4hggw4llqsz2fqpe4096d9v5fzg7xvxg6zvr7gksq4yqgn8shepg5xsczmzz256m9c6r8zqdkzy4tk9he59ndtdkrrr8u5v6ztnvkvmy4sed7p7plm2y09sgksw6zcjayls4wl9fnqu97kyx9cdknksar7h8jetygur979rt5arcwmvp2dy3ynt6arna2yjpevt9209v9g2p5cvp6gjp9850w3w6afeg8yuhp6u447hrudcssyjauqa2p7jk4tz37wg70yrdhsgn35sc0hdkclvpapu75dgtmswk0vtgadx44mqdps6ry6005xqups9dpc93u66qj9j7lfaqgdqrrfg9pkxhjl99ge387rh257x2phfvjvc8y66p22wax8myyhm7mgmlxu9gug0km3lmn4lzcyj32mduy6msy4kfn5z2tr67zfxadnj6wc0av27mk0j90pf67uzp9ps8aekr24kpv5n3qeczfznen9vj67ft95s93t26l8uh87qr6kp8lsyuzm4h36de830h6rr3lhg5ac995nrsu6h0p56t5tnglvx0s02mr0ts95fgcevveky5kkw6zgj6jd5m3n5ljhw862km8sedr30xvg8t9vh409ufuxdnfuypvqdq49z6mp46p936pjzwwqjda6yy5wuxx9lffrxwcmfqzch6nz2l4mwd2vlsdr58vhygppy6nm6tduyemw4clwj9uac4v990xt6jt7e2al7m6sjlq4qgxfjf4ytx8f5j460vvr7yac9hsvlsat2vh5gl55mt4wr7v5p3m6k5ya5442xdarastxlmpf2vqz5lusp8tlglxkj0jksgwqgtj6j0kxwmw40egpzs5rr996xpv8wwqyja4tmw599n9fh77f5ruxk69vtpwl9z5ezmdn92cpyyhwff59ypp0z5rv98vdvm67umqzt0ljjan30u3a8nga35fdy450ht9gef24mveucxqwv5aflge5r3amxsvd7l30j9kcqm7alq0ks2wqpde7pdct2gmvafxvjg3ad0a3h58assjaszvmykl3k5tn238gstm2shlvad4a53mm5ztvp5q2zt4pdzj0ssevlkumwhc0g5cxnxc9u7rh9gffkq7h9ufcxkgtghe32sv3vwzkessr52mcmajt83lvz45wqru9hht8cytfedtjlv7z7en6pp0guja85ft3rv6hzf2e02e7wfu38s0nyfzkc2qy2k298qtmxgrpduntejtvenr80csnckajnhu44399tkm0a7wdldalf678n9prd54twwlw24xhppxqlquatfztllkeejlkfxuayddwagh6uzx040tqlcs7hcflnu0ywynmz0chz48qcx7dsc4gpseu0dqvmmezpuv0tawm78nleju2vp4lkehua56hrnuj2wuc5lqvxlnskvp53vu7e2399pgp7xcwe3ww23qcd9pywladq34nk6cwcvtj3vdfgwf6r7s6vq46y2x05e043nj6tu8am2und8z3ftf3he5ccjxamtnmxfd79m04ph36kzx6e789dhqrwmwcfrn9ulsedeplk3dvrmad6f20y9qfl6n6kzaxkmmmaq4d6s5rl4kmhc7fcdkrkandw2jxdjckuscu56syly8rtjatj4j2ug23cwvep3dgcdvmtr32296nf9vdl3rcu0r7hge23ydt83k5nhtnexuqrnamveacz6c43eay9nz4pjjwjatkgp80lg9tnf5kdr2eel8s2fk6v338x4hu00htemm5pq6qlucqqq5tchhtekjzdu50erqd2fkdu9th3wl0mqxz5u7wnpgwgpammv2yqpa5znljegyhke0dz9vg27uh5t5x6qdgf7vu54lqssejekwzfxchjyq2s8frm9fmt688w76aug56v6n3w5xdre78xplfsdw3e4j6dc5w7tf83r25re0duq6h8z54wnkqr9yh2k0skjqea4elgcr4aw7hks9m8w3tx8w9xlxpqqll2zeql55ew7e90dyuynkqxfuqzv45t22ljamdll3udvqrllprdltthzm866jdaxkkrnryj4cmc2m7sk99clgql3ynrhe9kynqn4mh3tepk8dtq7cndtc2hma29s4cuylsvg04s70uyr53w5656su5rjem5egss08zrfaef0mww6t8pr26uph2n8a2cs55ydx4xhasjqk7xs0akh6f26j2ec4d8pd0kdf4jya6p9jl48wmy5autdpw2q8mehrq6kypt573genj66l5zkq6xvrdqugmfczxa2gj9ylx3pgpjqnhuem9udfkj9qr2y8lh728sr7uaedu5wwmfa72ykh395jqh7f7f9p2gskn6u7k844kpnwe3eqv84pl53r6x9af88a8ey7298njdg03h8mxqz2x6z8ys3qpuxq768tjq0zhrnjgns8d78euzwsvx6vn4f9tftrp68zcch3h75mc9drpt7tpvnyyqfjuqclxhdwhdwtsakecv04p9r3jx90htql9a3ht5mxrj4ercv4cd52wk4qhu7dn4tqe7yclqx2l36gcsrzmdlv440qls7qjpq6k95mst485vpennnur8h62a7d7syvyer89qtyfzlfhz8a5a0x5tuwhc9mah0e944xzhsc6uvpv8vat44w7r3xyw8q85y77jux8zhndrhdn36swryffqmpkxgcw4g29q40sul4fl5vrfru08a5j3rd3jl8799srpf2xqpxq38wwvhr4mxqf5wwdqfqq7harshggvufzlgn0l9fq0j76dyuge75jmzy8celvw6wesfs82n4jw2k8jnus2zds5a67my339uuzka4w72tau6j7wyu0lla0mcjpaflphsuy7f2phev6tr8vc9nj2mczkeg4vy3n5jkgecwgrvwu3vw9x5knpkxzv8kw3dpzzxy3rvrs56vxw8ugmyz2vdj6dakjyq3feym4290l7hgdt0ac5u49sekezzf0ghwmlek4h75fkzpvuly9zupw32dd3l9my282nekgk78fe6ayjyhczetxf8r82yd2askl52kmupr9xaxw0jd08dsd3523ea6ge48384rlmt4mu4w4x0q9s", Network::Main)?, NativeCurrencyAmount::coins(57)).into(),
+    /// ];
+    ///
+    /// // change policy.
+    /// // default is recover to next unused key, via onchain notification
+    /// let change_policy = ChangePolicy::default();
+    /// #
+    /// // Max fee
+    /// let fee : NativeCurrencyAmount = NativeCurrencyAmount::coins(10);
+    /// #
+    /// // neptune-core server sends token to a single recipient
+    /// let send_result = client.send(context::current(), token, outputs, change_policy, fee).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn send(
+        token: auth::Token,
+        outputs: Vec<OutputFormat>,
+        change_policy: ChangePolicy,
+        fee: NativeCurrencyAmount,
+    ) -> RpcResult<TxCreationArtifacts>;
+
+    /// Plan the payment of `outputs` from this node's wallet, without
+    /// building anything.
+    ///
+    /// Reports how many transactions are needed under consensus limits and
+    /// the send rate-limit, the fees paid when paying `fee` per transaction,
+    /// how many confirmation rounds the transactions must be chained over,
+    /// and an estimate of the total proving time. Useful for planning large
+    /// transfers.
+    ///
+    /// See [TransferPlanner](crate::api::tx_initiation::planner::TransferPlanner)
+    /// for details.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use neptune_cash::api::export::OutputFormat;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// # // payments, as for `send`
+    /// # let outputs: Vec<OutputFormat> = vec![];
+    /// #
+    /// // fee paid by each transaction
+    /// let fee = NativeCurrencyAmount::coins_from_str("0.01")?;
+    ///
+    /// // neptune-core server plans the transfer
+    /// let plan = client.plan_transfer(context::current(), token, outputs, fee).await??;
+    /// println!("transactions needed: {}", plan.num_transactions());
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn plan_transfer(
+        token: auth::Token,
+        outputs: Vec<OutputFormat>,
+        fee: NativeCurrencyAmount,
+    ) -> RpcResult<TransferPlan>;
+
+    /// Like `send` but the resulting transaction is *transparent*. No privacy.
+    ///
+    /// Specifically, the resulting transaction contains announcements that
+    /// themselves contain the raw UTXOs and commitment randomnesses. This
+    /// info suffices to derive the addition records and removal records,
+    /// thereby exposing not just the amounts but also the origins and
+    /// destinations of the transfer.
+    async fn send_transparent(
+        token: auth::Token,
+        outputs: Vec<OutputFormat>,
+        change_policy: ChangePolicy,
+        fee: NativeCurrencyAmount,
+    ) -> RpcResult<TxCreationArtifacts>;
+
+    /// Like `send` but spends exactly the wallet's UTXOs with the given AOCL
+    /// leaf indices, instead of automatically selected ones.
+    ///
+    /// Fails if any of the UTXOs is not a synced, unspent UTXO of the wallet,
+    /// is time-locked, is already spent by a mempool transaction, or if the
+    /// selected UTXOs do not cover the outputs and fee. Any excess is handled
+    /// according to `change_policy`.
+    async fn send_with_inputs(
+        token: auth::Token,
+        outputs: Vec<OutputFormat>,
+        aocl_leaf_indices: Vec<u64>,
+        change_policy: ChangePolicy,
+        fee: NativeCurrencyAmount,
+    ) -> RpcResult<TxCreationArtifacts>;
+
+    /// Expert mode: build, prove, and broadcast a transaction from a JSON
+    /// description of its inputs, outputs, fee, timestamp, and announcements.
+    ///
+    /// See [RawTransactionSpec] for the format. The spec is validated before
+    /// anything is built, and the inputs are validated as by
+    /// `send_with_inputs`.
+    async fn send_raw_transaction(
+        token: auth::Token,
+        spec: String,
+    ) -> RpcResult<TxCreationArtifacts>;
+
+    /// Cancel a transaction initiated by this node, eg with `send`, that is
+    /// still being proven.
+    ///
+    /// The send methods return before the transaction is proven, and proving
+    /// happens in the background. Until the proven transaction is broadcast,
+    /// it can be cancelled by its id: the proving job is aborted, the
+    /// transaction is removed from the mempool, its inputs can be spent
+    /// again, and a `tx-cancelled` event is published.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use neptune_cash::api::export::ChangePolicy;
+    /// # use neptune_cash::api::export::NativeCurrencyAmount;
+    /// # use neptune_cash::api::export::OutputFormat;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// # // payments, as for `send`
+    /// # let outputs: Vec<OutputFormat> = vec![];
+    /// # let fee = NativeCurrencyAmount::coins(1);
+    /// #
+    /// let artifacts = client
+    ///     .send(context::current(), token, outputs, ChangePolicy::default(), fee)
+    ///     .await??;
+    ///
+    /// // changed our mind before the transaction was proven
+    /// let txid = artifacts.transaction().txid();
+    /// client.cancel_transaction(context::current(), token, txid).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn cancel_transaction(
+        token: auth::Token,
+        transaction_id: TransactionKernelId,
+    ) -> RpcResult<()>;
+
+    /// Issue a challenge that must be answered with the spending PIN before
+    /// funds can be spent.
+    ///
+    /// Only available if neptune-core was started with `--spending-pin-file`.
+    /// In that case `send`, `send_transparent`, `send_with_inputs`, and
+    /// `send_raw_transaction` are refused unless preceded by a correct
+    /// response to `unlock_spending`. Challenges expire after a minute and
+    /// can be answered once.
+    ///
+    /// See [spending_pin](crate::application::rpc::spending_pin) for details.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use neptune_cash::application::rpc::spending_pin::SpendingPinKey;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// let challenge = client.spending_pin_challenge(context::current(), token).await??;
+    ///
+    /// // derive the key from the PIN entered by the user
+    /// let response = SpendingPinKey::derive("1234", challenge.salt).respond(&challenge);
+    /// client.unlock_spending(context::current(), token, response).await??;
+    ///
+    /// // the next spend, eg with `send`, is authorized
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn spending_pin_challenge(token: auth::Token) -> RpcResult<SpendingPinChallenge>;
+
+    /// Answer a challenge issued by `spending_pin_challenge`.
+    ///
+    /// A correct response authorizes a single spend within the next minute.
+    /// The response, correct or not, is recorded in the spending-PIN audit
+    /// file.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use neptune_cash::application::rpc::spending_pin::SpendingPinKey;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// let challenge = client.spending_pin_challenge(context::current(), token).await??;
+    ///
+    /// // derive the key from the PIN entered by the user
+    /// let response = SpendingPinKey::derive("1234", challenge.salt).respond(&challenge);
+    /// client.unlock_spending(context::current(), token, response).await??;
+    ///
+    /// // the next spend, eg with `send`, is authorized
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn unlock_spending(token: auth::Token, response: SpendingPinResponse) -> RpcResult<()>;
+
+    /// Upgrade a proof for a transaction found in the mempool. If the
+    /// transaction cannot be in the mempool, or the transaction is not in need
+    /// of upgrading because it is already single proof-backed and synced, then
+    /// false is returned. Otherwise true is returned.
+    ///
+    /// No fees will be collected from the proof upgrading.
+    ///
+    /// Returns Ok(true) if a transaction for upgrading was found
+    /// Returns Ok(false) if no transaction for upgrading was found
+    /// Returns an error if something else failed.
+    async fn upgrade(token: auth::Token, tx_kernel_id: TransactionKernelId) -> RpcResult<bool>;
+
+    /// Return the schedule that determines when the node may upgrade the
+    /// proofs of 3rd party transactions, and whether it currently permits
+    /// upgrading.
+    ///
+    /// The schedule is set at startup with `--proof-upgrade-window` and
+    /// `--proof-upgrade-max-cpu`, and can be changed with
+    /// [`set_proof_upgrade_schedule`](RPC::set_proof_upgrade_schedule).
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// let status = client.proof_upgrade_schedule(context::current(), token).await??;
+    /// match status.deferral {
+    ///     Some(deferral) => println!("proof upgrading deferred: {deferral}"),
+    ///     None => println!("proof upgrading permitted"),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn proof_upgrade_schedule(token: auth::Token) -> RpcResult<ProofUpgradeScheduleStatus>;
+
+    /// Replace the schedule that determines when the node may upgrade the
+    /// proofs of 3rd party transactions, e.g. to pause upgrading during
+    /// business hours or while other workloads run on the machine.
+    ///
+    /// The new schedule applies to upgrades started after this call. An
+    /// upgrade in progress is not interrupted. The change is not persisted,
+    /// so the schedule given on the command line applies after a restart.
+    ///
+    /// Returns [`RpcError::Failed`] if the maximum CPU usage is not between 1
+    /// and 100 percent.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// use neptune_cash::state::proof_upgrade_schedule::ProofUpgradeSchedule;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // upgrade only at night, and while the machine is mostly idle
+    /// let schedule = ProofUpgradeSchedule {
+    ///     windows: vec!["01:00-06:00".parse().map_err(anyhow::Error::msg)?],
+    ///     max_cpu_percent: Some(50),
+    ///     paused: false,
+    /// };
+    /// client.set_proof_upgrade_schedule(context::current(), token, schedule).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn set_proof_upgrade_schedule(
+        token: auth::Token,
+        schedule: ProofUpgradeSchedule,
+    ) -> RpcResult<()>;
+
+    /// The alerts whose condition currently holds, including silenced ones.
+    ///
+    /// Alerts are raised by the rules configured with the `--alert-*`
+    /// command-line arguments, which are evaluated periodically. The list is
+    /// empty if no rules are configured.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// for alert in client.alerts(context::current(), token).await?? {
+    ///     println!("{alert} (since {})", alert.since);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn alerts(token: auth::Token) -> RpcResult<Vec<Alert>>;
+
+    /// Stop alerts of the given kind from being passed to plugin hooks and
+    /// recorded in the event log for the given duration, e.g. during planned
+    /// maintenance. Silenced alerts are still listed by [`RPC::alerts`].
+    ///
+    /// A kind can be silenced before its alert fires. A duration of zero lifts
+    /// the silence. An alert that is still active when its silence ends is
+    /// published then. Silences are not persisted across restarts.
+    ///
+    /// Returns the time until which the kind is silenced.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// use neptune_cash::protocol::proof_abstractions::timestamp::Timestamp;
+    /// use neptune_cash::state::alerts::AlertKind;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// let until = client
+    ///     .silence_alert(context::current(), token, AlertKind::LowPeerCount, Timestamp::hours(2))
+    ///     .await??;
+    /// println!("low peer count alerts silenced until {until}");
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn silence_alert(
+        token: auth::Token,
+        kind: AlertKind,
+        duration: Timestamp,
+    ) -> RpcResult<Timestamp>;
+
+    /// The state of the wallet's encrypted backup to the trusted peers set
+    /// with `--wallet-backup-peer`: its version, which version each trusted
+    /// peer holds, and when recovery data was last fetched back from a peer.
+    ///
+    /// Fails if no trusted peers are configured.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// let status = client
+    ///     .wallet_backup_status(context::current(), token)
+    ///     .await??;
+    /// for peer in status.peers {
+    ///     println!("{} holds version {} of {}", peer.address, peer.version, status.version);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn wallet_backup_status(token: auth::Token) -> RpcResult<WalletBackupStatus>;
+
+    /// claim a utxo
+    ///
+    /// The input string must be a valid bech32m encoded `UtxoTransferEncrypted`
+    /// for the current network and the wallet must have the corresponding
+    /// `SpendingKey` for decryption.
+    ///
+    /// upon success, a new `ExpectedUtxo` will be added to the local wallet
+    /// state.
+    ///
+    /// if the utxo has already been claimed, this call has no effect.
+    ///
+    /// Return true if a new expected UTXO was added, otherwise false.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // Encryted value of utxo transfer
+    /// let utxo_transfer_encrypted = "XXXXXXX".to_string();
+    ///
+    /// // max search depth is set to 3
+    /// let max_search_depth : Option<u64> = Some(3);
+    ///
+    /// // claim utxo
+    /// let utxo_claimed = client.claim_utxo(context::current(), token, utxo_transfer_encrypted, max_search_depth).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn claim_utxo(
+        token: auth::Token,
+        utxo_transfer_encrypted: String,
+        max_search_depth: Option<u64>,
+    ) -> RpcResult<bool>;
+
+    /// Export the composer's off-chain payouts to foreign addresses, for
+    /// blocks at or above `from_height` in the canonical chain.
+    ///
+    /// A composer that distributes the coinbase to foreign addresses, e.g. a
+    /// mining pool paying its members, with `--fee-notification off-chain`,
+    /// records these payouts when composing a block proposal. Without them,
+    /// the recipients cannot claim their payouts. Each recipient imports the
+    /// `utxo_triple` of its payouts with
+    /// [`import_utxo_claim`](RPC::import_utxo_claim).
+    ///
+    /// Payouts of proposals that did not become canonical blocks are omitted.
+    /// Requires an archival node.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // export the payouts of all blocks from height 1000 onwards
+    /// let payouts = client
+    ///     .composer_payouts(context::current(), token, 1000u64.into())
+    ///     .await??;
+    /// println!("{}", serde_json::to_string_pretty(&payouts)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn composer_payouts(
+        token: auth::Token,
+        from_height: BlockHeight,
+    ) -> RpcResult<Vec<ComposerPayout>>;
+
+    /// Import a raw UTXO claim, e.g. a composer payout exported by a mining
+    /// pool, and register the UTXO as monitored by the wallet.
+    ///
+    /// The receiver digest must belong to a known key of the wallet, and the
+    /// UTXO's addition record must be found in a canonical block within
+    /// `max_search_depth` blocks from the tip. `None` means unlimited.
+    ///
+    /// Returns true if the UTXO was newly registered, and false if the wallet
+    /// already monitors it.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// # let payout: neptune_cash::api::export::ComposerPayout =
+    /// #     serde_json::from_str(&std::fs::read_to_string("payout.json")?)?;
+    /// // import a payout received from the pool
+    /// let was_new = client
+    ///     .import_utxo_claim(context::current(), token, payout.utxo_triple, None)
+    ///     .await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn import_utxo_claim(
+        token: auth::Token,
+        utxo_triple: UtxoTriple,
+        max_search_depth: Option<u64>,
+    ) -> RpcResult<bool>;
+
+    /// Import the membership proofs of UTXOs of this wallet from a
+    /// [`MembershipBundle`] produced by an archival node with
+    /// [`Self::utxo_membership_bundle`], and monitor the UTXOs.
+    ///
+    /// Allows a wallet-only node that learned of its UTXOs out-of-band to
+    /// become spend-capable without syncing block bodies. The bundle must have
+    /// been produced at this node's tip. Each receiver digest must belong to a
+    /// known key of the wallet, and each UTXO must be contained in the bundle
+    /// and unspent.
+    ///
+    /// Returns the number of imported UTXOs.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// # let payout: neptune_cash::api::export::ComposerPayout =
+    /// #     serde_json::from_str(&std::fs::read_to_string("payout.json")?)?;
+    /// # let bundle = serde_json::from_str(&std::fs::read_to_string("bundle.json")?)?;
+    /// // import a UTXO using a bundle obtained from an archival node
+    /// let num_imported = client
+    ///     .import_membership_bundle(context::current(), token, bundle, vec![payout.utxo_triple])
+    ///     .await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn import_membership_bundle(
+        token: auth::Token,
+        bundle: MembershipBundle,
+        utxo_triples: Vec<UtxoTriple>,
+    ) -> RpcResult<usize>;
+
+    /// Delete all transactions from the mempool.
+    async fn clear_mempool(token: auth::Token) -> RpcResult<()>;
+
+    /// Promote this standby node to primary, enabling it to spend.
+    ///
+    /// The primary shares this node's wallet, so it is demoted first, to
+    /// ensure that the two nodes never both spend. If the primary cannot be
+    /// reached, promotion fails unless `force` is set. Only set `force` if the
+    /// primary is known to be down for good, or to have been demoted.
+    ///
+    /// The new role is persisted, and survives restarts.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // take over from a primary that is being decommissioned
+    /// client.promote_standby(context::current(), token, false).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn promote_standby(token: auth::Token, force: bool) -> RpcResult<()>;
+
+    /// Pause receiving of blocks, block proposals, and transactions. If
+    /// activated, no new blocks will be received. Transactions, blocks, and
+    /// block proposals originating locally will not be shared with peers.
+    /// Mining should be paused when this is activated. Cannot be called if the
+    /// client is currently syncing.
+    ///
+    /// Can be used to build a big transaction through the merge of multiple
+    /// smaller transactions without risking that the smaller, unmerged
+    /// transactions are mined.
+    async fn freeze(token: auth::Token) -> RpcResult<()>;
+
+    /// Resume state updates. If state updates were paused, start receiving and
+    /// transmitting blocks, block proposals, and transactions again. Otherwise,
+    /// does nothing.
+    async fn unfreeze(token: auth::Token) -> RpcResult<()>;
+
+    /// Stop miner if running
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    ///  // stops miner if running
+    /// let _ = client.pause_miner(context::current(), token).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn pause_miner(token: auth::Token) -> RpcResult<()>;
+
+    /// Start miner if not running
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    ///  // start miner if not running
+    /// let _ = client.restart_miner(context::current(), token).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn restart_miner(token: auth::Token) -> RpcResult<()>;
+
+    /// Set coinbase distribution for this node's block proposals. This
+    /// distribution will be in effect until it is overwritted or manually
+    /// unset. The value set through this command stays in effect regardless of
+    /// whether the block's proposals are mined or not. Only by overwriting this
+    /// value by calling this function again, or by unsetting this value through
+    /// [`RPC::unset_coinbase_distribution()`] can this effect be overturned.
+    ///
+    /// To guarantee that the set coinbase distribution is applied to the *next*
+    /// locally produced block proposal, this function call can be followed up
+    /// by a call to first [`RPC::pause_miner()`] and then
+    /// [`RPC::restart_miner()`]. If this is not done, the node may continue
+    /// working on an already started block proposal with another distribution.
+    async fn set_coinbase_distribution(
+        token: auth::Token,
+        coinbase_distribution: Vec<CoinbaseOutputReadable>,
+    ) -> RpcResult<()>;
+
+    /// Remove a coinbase distribution from state, thus defaulting back to
+    /// rewarding the node's own wallet with the composer's coinbase outputs.
+    ///
+    /// Can be used to delete coinbase distributions set through
+    /// [`RPC::set_coinbase_distribution()`].
+    async fn unset_coinbase_distribution(token: auth::Token) -> RpcResult<()>;
+
+    /// mine a series of blocks to the node's wallet.
+    ///
+    /// Can be used only if the network uses mock blocks.
+    /// (presently only the regtest network)
+    ///
+    /// these blocks can be generated quickly because they do not have
+    /// a real ZK proof.  they have a witness "proof" and will validate correctly.
+    /// witness proofs contain secrets that must not be shared, so this is
+    /// allowed only on the regtest network, for development purposes.
+    ///
+    /// The timestamp of each block will be the current system time, meaning
+    /// that they will be temporally very close to eachother.
+    ///
+    /// see [api::regtest::RegTest::mine_blocks_to_wallet()]
+    async fn mine_blocks_to_wallet(token: auth::Token, n_blocks: u32) -> RpcResult<()>;
+
+    /// mark a stored block invalid, forcing the node onto the most canonical
+    /// stored chain that does not contain it. Returns the digest of the
+    /// resulting tip.
+    ///
+    /// Can be used only on the regtest network, to test the handling of
+    /// reorganizations in downstream software. The block and its descendants
+    /// are rejected until [`RPC::reconsider_block()`] is called.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use neptune_cash::protocol::consensus::block::block_selector::BlockSelector;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// // orphan the tip, then restore it
+    /// let tip = client.block_digest(context::current(), token, BlockSelector::Tip).await??.unwrap();
+    /// let new_tip = client.invalidate_block(context::current(), token, tip).await??;
+    /// assert_ne!(tip, new_tip);
+    /// let restored_tip = client.reconsider_block(context::current(), token, tip).await??;
+    /// assert_eq!(tip, restored_tip);
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn invalidate_block(token: auth::Token, block_digest: Digest) -> RpcResult<Digest>;
+
+    /// remove the mark set by [`RPC::invalidate_block()`] from a block, and
+    /// switch to the most canonical stored chain. Returns the digest of the
+    /// resulting tip.
+    ///
+    /// Can be used only on the regtest network.
+    async fn reconsider_block(token: auth::Token, block_digest: Digest) -> RpcResult<Digest>;
+
+    /// Provide a PoW-solution to the current block proposal.
+    ///
+    /// If the solution is considered valid by the running node, the new block
+    /// is broadcast to all peers on the network, and `true` is returned.
+    /// Otherwise the provided solution is ignored, and `false` is returned.
+    async fn provide_pow_solution(
+        token: auth::Token,
+        pow: BlockPow,
+        proposal_id: Digest,
+    ) -> RpcResult<bool>;
+
+    /// Provide a PoW solution along with a valid block proposal. Caller must
+    /// provide both the block proposal and the pow solution.
+    ///
+    /// Works like [`Self::provide_pow_solution()`] except that it takes a full
+    /// block proposal rather than a proposal ID. This allows for the provision
+    /// of a block without the node having to know the associated block proposal
+    /// since proposal is provided by the caller. Can be used in conjuction with
+    /// [`Self::full_pow_puzzle_external_key`].
+    async fn provide_new_tip(
+        token: auth::Token,
+        pow: BlockPow,
+        block_proposal: Block,
+    ) -> RpcResult<bool>;
+
+    /// Validate a block proposal, typically one received from a third-party
+    /// composer, without guessing on it.
+    ///
+    /// Checks that the proposal extends the current tip, that its guesser
+    /// reward meets the node's `--minimum-guesser-fraction`, and that it is
+    /// valid, including its block proof. The proposal is not stored, and the
+    /// verdict is not affected by the node's own block proposal. Verifying the
+    /// proof takes a while.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// # let guesser_fee_address = client.next_receiving_address(context::current(), token, neptune_cash::state::wallet::address::KeyType::Generation).await??;
+    /// # let Some((proposal, _puzzle)) = client.full_pow_puzzle_external_key(context::current(), token, guesser_fee_address).await?? else { return Ok(()) };
+    /// // validate a block proposal received from a third party
+    /// let verdict = client.validate_proposal(context::current(), token, proposal).await??;
+    /// if let (true, Some(guesser_reward)) = (verdict.is_acceptable(), verdict.guesser_reward) {
+    ///     println!("proposal pays {guesser_reward} to the guesser");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn validate_proposal(token: auth::Token, proposal: Block) -> RpcResult<ProposalVerdict>;
+
+    /// mark MUTXOs as abandoned. Does not actually delete any elements in the
+    /// list.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    ///  // marks mutxos as abandoned
+    /// let abandoned_monitored_utxos = client.prune_abandoned_monitored_utxos(context::current(), token).await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn prune_abandoned_monitored_utxos(token: auth::Token) -> RpcResult<usize>;
+
+    /// Set the tip of the blockchain state to a given block, identified by its
+    /// hash. The block must be stored, but it does not need to live on the
+    /// canonical chain.
+    async fn set_tip(token: auth::Token, indicated_tip: Digest) -> RpcResult<()>;
+
+    /// Gracious shutdown.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    ///  // shutdowns the node
+    /// let is_shutdown = client.shutdown(context::current(), token).await??;
+    /// # Ok(())
+    /// # }
+    async fn shutdown(token: auth::Token) -> RpcResult<bool>;
+
+    /******** BATCHING ********/
+    /// Make several calls in one round trip.
+    ///
+    /// The calls are executed one after the other, in the given order, and
+    /// their responses are returned in the same order. Every call is
+    /// authenticated with its own token and fails or succeeds on its own; a
+    /// failing call does not stop the calls after it. Batches cannot be
+    /// nested, and hold at most [MAX_RPC_BATCH_SIZE] calls.
+    ///
+    /// The batch counts as one request towards the limits on concurrency and
+    /// duration of requests.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use neptune_cash::application::rpc::server::RPCClient;
+    /// # use neptune_cash::application::rpc::server::RPCRequest;
+    /// # use neptune_cash::application::rpc::server::RPCResponse;
+    /// # use neptune_cash::application::rpc::auth;
+    /// # use tarpc::tokio_serde::formats::Json;
+    /// # use tarpc::serde_transport::tcp;
+    /// # use tarpc::client;
+    /// # use tarpc::context;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()>{
+    /// #
+    /// # // create a serde/json transport over tcp.
+    /// # let transport = tcp::connect("127.0.0.1:9799", Json::default).await?;
+    /// #
+    /// # // create an rpc client using the transport.
+    /// # let client = RPCClient::new(client::Config::default(), transport).spawn();
+    /// #
+    /// # // Defines cookie hint
+    /// # let cookie_hint = client.cookie_hint(context::current()).await??;
+    /// #
+    /// # // load the cookie file from disk and assign it to a token
+    /// # let token : auth::Token = auth::Cookie::try_load(&cookie_hint.data_directory).await?.into();
+    /// #
+    /// let calls = vec![
+    ///     RPCRequest::BlockHeight { token },
+    ///     RPCRequest::Confirmations { token },
+    /// ];
+    /// let responses = client.batch(context::current(), token, calls).await??;
+    /// if let RPCResponse::BlockHeight(Ok(height)) = &responses[0] {
+    ///     println!("block height: {height}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn batch(token: auth::Token, calls: Vec<RPCRequest>) -> RpcResult<Vec<RPCResponse>>;
+}
+
+#[derive(Clone)]
+pub(crate) struct NeptuneRPCServer {
+    pub(crate) state: GlobalStateLock,
+    pub(crate) rpc_server_to_main_tx: tokio::sync::mpsc::Sender<RPCServerToMain>,
+
+    // copy of DataDirectory for this neptune-core instance.
+    data_directory: DataDirectory,
+
+    // list of tokens that are valid.  RPC clients must present a token that
+    // matches one of these.  there should only be one of each `Token` variant
+    // in the list (dups ignored).
+    valid_tokens: Vec<auth::Token>,
+
+    // limits on concurrency and duration of requests, shared by all RPC
+    // servers of this neptune-core instance.
+    request_limiter: Arc<RpcRequestLimiter>,
+}
+
+impl NeptuneRPCServer {
+    /// instantiate a new [NeptuneRPCServer]
+    pub fn new(
+        state: GlobalStateLock,
+        rpc_server_to_main_tx: tokio::sync::mpsc::Sender<RPCServerToMain>,
+        data_directory: DataDirectory,
+        valid_tokens: Vec<auth::Token>,
+        request_limiter: Arc<RpcRequestLimiter>,
+    ) -> Self {
+        Self {
+            state,
+            valid_tokens,
+            rpc_server_to_main_tx,
+            data_directory,
+            request_limiter,
+        }
+    }
+
+    /// refuse to spend unless the spending PIN, if any, was entered.
+    async fn authorize_spend(&self, method: &str) -> Result<(), RpcError> {
+        let spending_pin = self.state.lock_guard().await.spending_pin.clone();
+        Ok(spending_pin.authorize_spend(method).await?)
+    }
+
+    /// refuse to build a transaction step by step unless the spending PIN, if
+    /// any, was entered. the authorization is consumed once the transaction
+    /// is broadcast.
+    async fn require_spend_authorization(&self, method: &str) -> Result<(), RpcError> {
+        let spending_pin = self.state.lock_guard().await.spending_pin.clone();
+        Ok(spending_pin.require_authorization(method).await?)
+    }
+
+    /// refuse outputs and fees that are negative, or whose total exceeds the
+    /// supply.  summing them unchecked could overflow.
+    fn validate_spend_amounts(
+        outputs: &[OutputFormat],
+        fee: NativeCurrencyAmount,
+    ) -> Result<ValidatedAmount, RpcError> {
+        let fee = ValidatedAmount::new(fee)?;
+        Ok(outputs.iter().try_fold(fee, |total, output| {
+            total.checked_add(output.validated_amount()?)
+        })?)
+    }
+
+    async fn confirmations_internal(&self, state: &GlobalState) -> Option<BlockHeight> {
+        match state.get_latest_balance_height().await {
+            Some(latest_balance_height) => {
+                let tip_block_header = state.chain.light_state().header();
+
+                assert!(tip_block_header.height >= latest_balance_height);
+
+                // subtract latest balance height from chain tip.
+                //
+                // we add 1 to the result because the block that a tx is confirmed
+                // in is considered the 1st confirmation.
+                //
+                // note: BlockHeight is u64 internally and BlockHeight::sub() returns i128.
+                //       The subtraction and cast is safe given we passed the above assert.
+                let confirmations: BlockHeight =
+                    ((tip_block_header.height - latest_balance_height) as u64 + 1).into();
+                Some(confirmations)
+            }
+            None => None,
+        }
+    }
+
+    /// Return temperature of CPU, if available.
+    fn cpu_temp_inner() -> Option<f32> {
+        let current_system = System::new();
+        current_system.cpu_temp().ok()
+    }
+
+    /// Assemble a data for the wallet to register the UTXO. Returns `Ok(None)`
+    /// if the UTXO has already been claimed by the wallet.
+    ///
+    /// `max_search_depth` denotes how many blocks back from tip we attempt
+    /// to find the transaction in a block. `None` means unlimited.
+    ///
+    /// `encrypted_utxo_notification` is expected to hold encrypted data about
+    /// a future or past UTXO, which can be claimed by this client.
+    async fn claim_utxo_inner(
+        &self,
+        encrypted_utxo_notification: String,
+        max_search_depth: Option<u64>,
+    ) -> Result<Option<ClaimUtxoData>, error::ClaimError> {
+        let span = tracing::debug_span!("Claim UTXO inner");
+        let _enter = span.enter();
+
+        // deserialize UtxoTransferEncrypted from bech32m string.
+        let network = self.state.cli().network;
+        let utxo_transfer_encrypted =
+            EncryptedUtxoNotification::from_bech32m(&encrypted_utxo_notification, network)?;
+
+        // // acquire global state read lock
+        let state = self.state.lock_guard().await;
 
         // find known spending key by receiver_identifier
         let spending_key = state
             .wallet_state
-            .find_known_spending_key_for_receiver_identifier(
-                utxo_transfer_encrypted.receiver_identifier,
-            )
-            .ok_or(error::ClaimError::UtxoUnknown)?;
+            .find_known_spending_key_for_receiver_identifier(
+                utxo_transfer_encrypted.receiver_identifier,
+            )
+            .ok_or(error::ClaimError::UtxoUnknown)?;
+
+        // decrypt utxo_transfer_encrypted into UtxoTransfer
+        let utxo_notification = utxo_transfer_encrypted.decrypt_with_spending_key(&spending_key)?;
+
+        tracing::debug!("claim-utxo: decrypted {:#?}", utxo_notification);
+
+        // search for matching monitored utxo and return early if found.
+        if state
+            .wallet_state
+            .find_monitored_utxo(&utxo_notification.utxo, utxo_notification.sender_randomness)
+            .await
+            .is_some()
+        {
+            info!("found monitored utxo. Returning early.");
+            return Ok(None);
+        }
+
+        // construct an IncomingUtxo
+        let incoming_utxo = IncomingUtxo::from_utxo_notification_payload(
+            utxo_notification,
+            spending_key.privacy_preimage(),
+        );
+
+        self.prepare_claim_utxo_data(&state, incoming_utxo, max_search_depth)
+            .await
+            .map(Some)
+    }
+
+    /// Assemble a data for the wallet to register an imported UTXO claim.
+    /// Returns `Ok(None)` if the UTXO has already been claimed by the wallet.
+    ///
+    /// Unlike [Self::claim_utxo_inner()], the UTXO must have been mined in a
+    /// canonical block within `max_search_depth` blocks from the tip.
+    async fn import_utxo_claim_inner(
+        &self,
+        utxo_triple: UtxoTriple,
+        max_search_depth: Option<u64>,
+    ) -> Result<Option<ClaimUtxoData>, error::ClaimError> {
+        let state = self.state.lock_guard().await;
+
+        // find known spending key by receiver_digest
+        let spending_key = state
+            .wallet_state
+            .find_known_spending_key_for_receiver_digest(utxo_triple.receiver_digest)
+            .ok_or(error::ClaimError::UtxoUnknown)?;
+
+        // search for matching monitored utxo and return early if found.
+        if state
+            .wallet_state
+            .find_monitored_utxo(&utxo_triple.utxo, utxo_triple.sender_randomness)
+            .await
+            .is_some()
+        {
+            info!("found monitored utxo. Returning early.");
+            return Ok(None);
+        }
+
+        let incoming_utxo = IncomingUtxo {
+            utxo: utxo_triple.utxo,
+            sender_randomness: utxo_triple.sender_randomness,
+            receiver_preimage: spending_key.privacy_preimage(),
+            is_guesser_fee: false,
+        };
+        let claim_data = self
+            .prepare_claim_utxo_data(&state, incoming_utxo, max_search_depth)
+            .await?;
+        if claim_data.prepared_monitored_utxo.is_none() {
+            return Err(error::ClaimError::NotMined);
+        }
+
+        Ok(Some(claim_data))
+    }
+
+    /// Assemble the data for the wallet to register an incoming UTXO, with a
+    /// monitored UTXO if the UTXO was mined within `max_search_depth` blocks
+    /// from the tip.
+    async fn prepare_claim_utxo_data(
+        &self,
+        state: &GlobalState,
+        incoming_utxo: IncomingUtxo,
+        max_search_depth: Option<u64>,
+    ) -> Result<ClaimUtxoData, error::ClaimError> {
+        // Check if we can satisfy typescripts
+        if !incoming_utxo.utxo.all_type_script_states_are_valid() {
+            let err = error::ClaimError::InvalidTypeScript;
+            warn!("{}", err.to_string());
+            return Err(err);
+        }
+
+        // check if wallet is already expecting this utxo.
+        let addition_record = incoming_utxo.addition_record();
+        let has_expected_utxo = state.wallet_state.has_expected_utxo(addition_record).await;
+
+        // Check if UTXO has already been mined in a transaction.
+        let mined_in_block = state
+            .chain
+            .archival_state()
+            .find_canonical_block_with_output(addition_record, max_search_depth)
+            .await;
+        let maybe_prepared_mutxo = match mined_in_block {
+            Some(block) => {
+                let aocl_leaf_index = {
+                    // Find matching AOCL leaf index that must be in this block
+                    let last_aocl_index_in_block = block
+                        .mutator_set_accumulator_after()
+                        .expect("Block from state must have mutator set after")
+                        .aocl
+                        .num_leafs()
+                        - 1;
+                    let num_outputs_in_block: u64 = block
+                        .mutator_set_update()
+                        .expect("Block from state must have mutator set update")
+                        .additions
+                        .len()
+                        .try_into()
+                        .unwrap();
+                    let min_aocl_leaf_index = last_aocl_index_in_block - num_outputs_in_block + 1;
+                    let mut haystack = last_aocl_index_in_block;
+                    let ams = state.chain.archival_state().archival_mutator_set.ams();
+                    while ams.aocl.get_leaf_async(haystack).await
+                        != addition_record.canonical_commitment
+                    {
+                        assert!(haystack > min_aocl_leaf_index);
+                        haystack -= 1;
+                    }
+
+                    haystack
+                };
+                let item = Tip5::hash(&incoming_utxo.utxo);
+                let ams = state.chain.archival_state().archival_mutator_set.ams();
+                let msmp = ams
+                    .restore_membership_proof(
+                        item,
+                        incoming_utxo.sender_randomness,
+                        incoming_utxo.receiver_preimage,
+                        aocl_leaf_index,
+                    )
+                    .await
+                    .map_err(|x| anyhow!("Could not restore mutator set membership proof. Is archival mutator set corrupted? Got error: {x}"))?;
+
+                let tip_digest = state.chain.light_state().hash();
+
+                let mut monitored_utxo = MonitoredUtxo::new(
+                    incoming_utxo.utxo.clone(),
+                    self.state.cli().number_of_mps_per_utxo,
+                    msmp.aocl_leaf_index,
+                    msmp.sender_randomness,
+                    msmp.receiver_preimage,
+                    &block,
+                );
+                monitored_utxo.add_membership_proof_for_tip(tip_digest, msmp.clone());
+
+                // Was UTXO already spent? If so, register it as such.
+                let msa = ams.accumulator().await;
+                if !msa.verify(item, &msmp) {
+                    warn!("Claimed UTXO was already spent. Marking it as such.");
+
+                    if let Some(spending_block) = state
+                        .chain
+                        .archival_state()
+                        .find_canonical_block_with_input(
+                            msmp.compute_indices(item),
+                            max_search_depth,
+                        )
+                        .await
+                    {
+                        warn!(
+                            "Claimed UTXO was spent in block {:x}; which has height {}",
+                            spending_block.hash(),
+                            spending_block.header().height
+                        );
+                        monitored_utxo.mark_as_spent(&spending_block);
+                    } else {
+                        error!("Claimed UTXO's mutator set membership proof was invalid but we could not find the block in which it was spent. This is most likely a bug in the software.");
+                    }
+                }
+
+                Some(monitored_utxo)
+            }
+            None => None,
+        };
+
+        let expected_utxo = incoming_utxo.into_expected_utxo(UtxoNotifier::Cli);
+        Ok(ClaimUtxoData {
+            prepared_monitored_utxo: maybe_prepared_mutxo,
+            has_expected_utxo,
+            expected_utxo,
+        })
+    }
+
+    /// Prove ownership of the wallet's UTXOs at the selected block. Returns
+    /// `None` if the block is unknown.
+    async fn utxo_ownership_proof_inner(
+        &self,
+        block_selector: BlockSelector,
+        aocl_leaf_indices: &[u64],
+    ) -> RpcResult<Option<UtxoOwnershipProof>> {
+        let state = self.state.lock_guard().await;
+        let Some(digest) = block_selector.as_digest(&state).await else {
+            return Ok(None);
+        };
+        let tip = state.chain.light_state();
+        let (block_height, mutator_set_accumulator) = if digest == tip.hash() {
+            (tip.header().height, tip.mutator_set_accumulator_after())
+        } else {
+            match state.chain.archival_state().get_block(digest).await {
+                Ok(Some(block)) => (block.header().height, block.mutator_set_accumulator_after()),
+                Ok(None) => return Ok(None),
+                Err(error) => return Err(RpcError::Failed(error.to_string())),
+            }
+        };
+        let mutator_set_accumulator =
+            mutator_set_accumulator.map_err(|error| RpcError::Failed(error.to_string()))?;
+
+        state
+            .wallet_state
+            .prove_utxo_ownership(
+                digest,
+                block_height,
+                mutator_set_accumulator,
+                aocl_leaf_indices,
+            )
+            .await
+            .map(Some)
+            .map_err(|error| RpcError::WalletError(error.to_string()))
+    }
+
+    /// Return a PoW puzzle with the provided guesser address.
+    async fn pow_puzzle_inner(
+        mut self,
+        guesser_address: ReceivingAddress,
+        mut proposal: Block,
+    ) -> RpcResult<Option<ProofOfWorkPuzzle>> {
+        let latest_block_header = *self.state.lock_guard().await.chain.light_state().header();
+
+        proposal.set_header_guesser_address(guesser_address);
+        let puzzle = ProofOfWorkPuzzle::new(proposal.clone(), latest_block_header.difficulty);
+
+        // Record block proposal in case of guesser-success, for later
+        // retrieval. But limit number of blocks stored this way.
+        let mut state = self.state.lock_guard_mut().await;
+        if state.mining_state.exported_block_proposals.len()
+            >= MAX_NUM_EXPORTED_BLOCK_PROPOSAL_STORED
+        {
+            return Err(error::RpcError::ExportedBlockProposalStorageCapacityExceeded);
+        }
+
+        state
+            .mining_state
+            .exported_block_proposals
+            .insert(puzzle.id, proposal);
+
+        Ok(Some(puzzle))
+    }
+
+    /// Verify a pow solution and send it to main loop if it is valid.
+    async fn pow_solution_inner(&self, mut proposal: Block, pow: BlockPow) -> RpcResult<bool> {
+        // Check if solution works.
+        let latest_block_header = *self.state.lock_guard().await.chain.light_state().header();
+
+        proposal.set_header_pow(pow);
+
+        if !proposal.has_proof_of_work(self.state.cli().network, &latest_block_header) {
+            warn!("Got claimed PoW solution but PoW solution is not valid.");
+            return Ok(false);
+        }
+
+        // No time to waste! Inform main_loop!
+        let solution = Box::new(proposal);
+        let _ = self
+            .rpc_server_to_main_tx
+            .send(RPCServerToMain::ProofOfWorkSolution(solution))
+            .await;
+
+        Ok(true)
+    }
+
+    /// get the data_directory for this neptune-core instance
+    pub fn data_directory(&self) -> &DataDirectory {
+        &self.data_directory
+    }
+}
+
+impl RPC for NeptuneRPCServer {
+    // documented in trait. do not add doc-comment.
+    async fn cookie_hint(self, _: context::Context) -> RpcResult<auth::CookieHint> {
+        log_slow_scope!(fn_name!());
+
+        if self.state.cli().disable_cookie_hint {
+            Err(error::RpcError::CookieHintDisabled)
+        } else {
+            Ok(auth::CookieHint {
+                data_directory: self.data_directory().to_owned(),
+                network: self.state.cli().network,
+            })
+        }
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn network(self, _: context::Context) -> RpcResult<Network> {
+        log_slow_scope!(fn_name!());
+
+        Ok(self.state.cli().network)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn chain_parameters(
+        self,
+        _: context::Context,
+        token: auth::Token,
+    ) -> RpcResult<ChainParameters> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        Ok(ChainParameters::for_network(self.state.cli().network))
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn own_listen_address_for_peers(
+        self,
+        _context: context::Context,
+        token: auth::Token,
+    ) -> RpcResult<Option<SocketAddr>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let listen_port = self.state.cli().own_listen_port();
+        let listen_for_peers_ip = self.state.cli().peer_listen_addr;
+        Ok(listen_port.map(|port| SocketAddr::new(listen_for_peers_ip, port)))
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn own_instance_id(
+        self,
+        _context: context::Context,
+        token: auth::Token,
+    ) -> RpcResult<InstanceId> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        Ok(self.state.lock_guard().await.net.instance_id)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn block_height(self, _: context::Context, token: auth::Token) -> RpcResult<BlockHeight> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        Ok(self
+            .state
+            .lock_guard()
+            .await
+            .chain
+            .light_state()
+            .kernel
+            .header
+            .height)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn block_application_progress(
+        self,
+        _: context::Context,
+        token: auth::Token,
+        after_sequence: Option<u64>,
+    ) -> RpcResult<BlockApplicationProgress> {
+        // Not timed, since waiting for progress is expected to be slow.
+        token.auth(&self.valid_tokens)?;
+
+        // Read without the global state lock, which is held while applying
+        // blocks.
+        let progress = self.state.block_application_progress();
+        Ok(match after_sequence {
+            Some(sequence) => progress.next_after(sequence, MAX_PROGRESS_WAIT).await,
+            None => progress.current(),
+        })
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn sync_status(self, _: context::Context, token: auth::Token) -> RpcResult<SyncStatus> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        Ok(self.state.lock_guard().await.sync_status().await)
+    }
+
+    async fn best_proposal(
+        self,
+        _: context::Context,
+        token: auth::Token,
+    ) -> RpcResult<Option<BlockInfo>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let state = self.state.lock_guard().await;
+        let tip_digest = state.chain.light_state().hash();
+        let proposal = &state.mining_state.block_proposal;
+
+        // Returning BlockInfo here is not completely kosher since a few fields
+        // don't make sense in this context. But it's a close fit.
+        Ok(proposal.map(|block| {
+            BlockInfo::new(
+                block,
+                state.chain.archival_state().genesis_block().hash(),
+                tip_digest,
+                vec![],
+                false,
+            )
+        }))
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn confirmations(
+        self,
+        _: context::Context,
+        token: auth::Token,
+    ) -> RpcResult<Option<BlockHeight>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let guard = self.state.lock_guard().await;
+        Ok(self.confirmations_internal(&guard).await)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn latest_address(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+        key_type: KeyType,
+    ) -> RpcResult<ReceivingAddress> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let state = self.state.lock_guard().await;
+
+        let current_counter = state.wallet_state.spending_key_counter(key_type);
+        let index = current_counter.checked_sub(1);
+        let Some(index) = index else {
+            return Err(RpcError::WalletKeyCounterIsZero);
+        };
+
+        Ok(state
+            .wallet_state
+            .nth_spending_key(key_type, index)
+            .to_address())
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn utxo_digest(
+        self,
+        _: context::Context,
+        token: auth::Token,
+        leaf_index: u64,
+    ) -> RpcResult<Option<Digest>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let state = self.state.lock_guard().await;
+        let aocl = &state.chain.archival_state().archival_mutator_set.ams().aocl;
+
+        Ok(
+            match leaf_index > 0 && leaf_index < aocl.num_leafs().await {
+                true => Some(aocl.get_leaf_async(leaf_index).await),
+                false => None,
+            },
+        )
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn utxo_origin_block(
+        self,
+        _: context::Context,
+        token: auth::Token,
+        addition_record: AdditionRecord,
+        max_search_depth: Option<u64>,
+    ) -> RpcResult<Option<Digest>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let state = self.state.lock_guard().await;
+        let block = state
+            .chain
+            .archival_state()
+            .find_canonical_block_with_output(addition_record, max_search_depth)
+            .await;
+
+        Ok(block.map(|block| block.hash()))
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn block_digest(
+        self,
+        _: context::Context,
+        token: auth::Token,
+        block_selector: BlockSelector,
+    ) -> RpcResult<Option<Digest>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let state = self.state.lock_guard().await;
+        let archival_state = state.chain.archival_state();
+        let Some(digest) = block_selector.as_digest(&state).await else {
+            return Ok(None);
+        };
+        // verify the block actually exists
+        Ok(archival_state
+            .get_block_header(digest)
+            .await
+            .map(|_| digest))
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn block_info(
+        self,
+        _: context::Context,
+        token: auth::Token,
+        block_selector: BlockSelector,
+    ) -> RpcResult<Option<BlockInfo>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let state = self.state.lock_guard().await;
+        let Some(digest) = block_selector.as_digest(&state).await else {
+            return Ok(None);
+        };
+        let tip_digest = state.chain.light_state().hash();
+        let archival_state = state.chain.archival_state();
+
+        let Some(block) = archival_state.get_block(digest).await.unwrap() else {
+            return Ok(None);
+        };
+        let is_canonical = archival_state
+            .block_belongs_to_canonical_chain(digest)
+            .await;
+
+        // sibling blocks are those at the same height, with different digest
+        let sibling_blocks = archival_state
+            .block_height_to_block_digests(block.header().height)
+            .await
+            .into_iter()
+            .filter(|d| *d != digest)
+            .collect();
+
+        Ok(Some(BlockInfo::new(
+            &block,
+            archival_state.genesis_block().hash(),
+            tip_digest,
+            sibling_blocks,
+            is_canonical,
+        )))
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn block_kernel(
+        self,
+        _: context::Context,
+        token: auth::Token,
+        block_selector: BlockSelector,
+    ) -> RpcResult<Option<BlockKernel>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let state = self.state.lock_guard().await;
+        let Some(digest) = block_selector.as_digest(&state).await else {
+            return Ok(None);
+        };
+
+        let block = state
+            .chain
+            .archival_state()
+            .get_block(digest)
+            .await
+            .expect("Program must be able to read archival state data.");
+        let block_kernel = block.map(|block| block.kernel.clone());
+
+        Ok(block_kernel)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn addition_record_indices_for_block(
+        self,
+        _: context::Context,
+        token: auth::Token,
+        block_selector: BlockSelector,
+    ) -> RpcResult<Vec<(AdditionRecord, Option<u64>)>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let state = self.state.lock_guard().await;
+        let Some(digest) = block_selector.as_digest(&state).await else {
+            return Ok(vec![]);
+        };
+
+        let addition_records_dictionary = state
+            .chain
+            .archival_state()
+            .get_addition_record_indices_for_block(digest)
+            .await
+            .into_iter()
+            .flatten()
+            .collect_vec();
+
+        Ok(addition_records_dictionary)
+    }
+
+    async fn restore_membership_proof_privacy_preserving(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+        requests: Vec<AbsoluteIndexSet>,
+    ) -> RpcResult<ResponseMsMembershipProofPrivacyPreserving> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let state = self.state.lock_guard().await;
+        let ams = state.chain.archival_state().archival_mutator_set.ams();
+
+        let mut membership_proofs = Vec::with_capacity(requests.len());
+        for request in requests {
+            match ams
+                .restore_membership_proof_privacy_preserving(request)
+                .await
+            {
+                Ok(msmp) => membership_proofs.push(msmp),
+                Err(err) => {
+                    debug!("Failed to restore MSMP: {err}");
+                    return Err(RpcError::CannotRestoreMembershipProofs(err.to_string()));
+                }
+            }
+        }
+
+        debug!("Restored {} msmps", membership_proofs.len());
+        debug!(
+            "AOCL MMR lengths: [{}]",
+            membership_proofs
+                .iter()
+                .map(|x| x.aocl_auth_paths.len().to_string())
+                .join(", ")
+        );
+
+        let cur_block = state.chain.light_state();
+        let tip_height = cur_block.header().height;
+        let tip_hash = cur_block.hash();
+        let tip_mutator_set = cur_block
+            .mutator_set_accumulator_after()
+            .expect("Tip must have valid MSA after");
+
+        Ok(ResponseMsMembershipProofPrivacyPreserving {
+            tip_height,
+            tip_hash,
+            membership_proofs,
+            tip_mutator_set,
+        })
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn utxo_membership_bundle(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+        addition_records: Vec<AdditionRecord>,
+    ) -> RpcResult<MembershipBundle> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        if addition_records.len() > MAX_MEMBERSHIP_BUNDLE_SIZE {
+            return Err(RpcError::CannotRestoreMembershipProofs(format!(
+                "requested {} addition records, but at most {MAX_MEMBERSHIP_BUNDLE_SIZE} are allowed",
+                addition_records.len()
+            )));
+        }
+
+        // The search reads up to MAX_MEMBERSHIP_BUNDLE_SEARCH_DEPTH leafs, so
+        // the lock is released between batches.
+        const SEARCH_BATCH_SIZE: u64 = 1 << 12;
+
+        let num_leafs = {
+            let state = self.state.lock_guard().await;
+            let ams = state.chain.archival_state().archival_mutator_set.ams();
+            ams.aocl.num_leafs().await
+        };
+        let search_start = num_leafs.saturating_sub(MAX_MEMBERSHIP_BUNDLE_SEARCH_DEPTH);
+
+        let mut remaining = addition_records.into_iter().unique().collect_vec();
+        let mut located = vec![];
+        let mut search_end = num_leafs;
+        while !remaining.is_empty() && search_end > search_start {
+            let batch_start = search_end
+                .saturating_sub(SEARCH_BATCH_SIZE)
+                .max(search_start);
+            let state = self.state.lock_guard().await;
+            let ams = state.chain.archival_state().archival_mutator_set.ams();
+            located.extend(
+                ams.locate_addition_records(&mut remaining, batch_start..search_end)
+                    .await,
+            );
+            search_end = batch_start;
+        }
+
+        let state = self.state.lock_guard().await;
+        let tip = state.chain.light_state();
+        let ams = state.chain.archival_state().archival_mutator_set.ams();
+
+        ams.membership_bundle(located, remaining, tip.header().height, tip.hash())
+            .await
+            .map_err(|err| RpcError::CannotRestoreMembershipProofs(err.to_string()))
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn announcements_in_block(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+        block_selector: BlockSelector,
+    ) -> RpcResult<Option<Vec<Announcement>>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let state = self.state.lock_guard().await;
+        let Some(digest) = block_selector.as_digest(&state).await else {
+            return Ok(None);
+        };
+        let archival_state = state.chain.archival_state();
+        let Some(block) = archival_state.get_block(digest).await.unwrap() else {
+            return Ok(None);
+        };
+
+        Ok(Some(block.body().transaction_kernel.announcements.clone()))
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn block_digests_by_height(
+        self,
+        _: context::Context,
+        token: auth::Token,
+        height: BlockHeight,
+    ) -> RpcResult<Vec<Digest>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        Ok(self
+            .state
+            .lock_guard()
+            .await
+            .chain
+            .archival_state()
+            .block_height_to_block_digests(height)
+            .await)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn latest_tip_digests(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+        n: usize,
+    ) -> RpcResult<Vec<Digest>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let state = self.state.lock_guard().await;
+
+        let latest_block_digest = state.chain.light_state().hash();
+
+        Ok(state
+            .chain
+            .archival_state()
+            .get_ancestor_block_digests(latest_block_digest, n)
+            .await)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn peer_info(self, _: context::Context, token: auth::Token) -> RpcResult<Vec<PeerInfo>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        Ok(self
+            .state
+            .lock_guard()
+            .await
+            .net
+            .peer_map
+            .values()
+            .cloned()
+            .collect())
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn all_punished_peers(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<HashMap<IpAddr, PeerStanding>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let mut sanctions_in_memory = HashMap::default();
+
+        let global_state = self.state.lock_guard().await;
+
+        // Get all connected peers
+        for (socket_address, peer_info) in &global_state.net.peer_map {
+            if peer_info.standing().is_negative() {
+                sanctions_in_memory.insert(socket_address.ip(), peer_info.standing());
+            }
+        }
+
+        let sanctions_in_db = global_state.net.all_peer_sanctions_in_database();
+
+        // Combine result for currently connected peers and previously connected peers but
+        // use result for currently connected peer if there is an overlap
+        let mut all_sanctions = sanctions_in_memory;
+        for (ip_addr, sanction) in sanctions_in_db {
+            if sanction.is_negative() {
+                all_sanctions.entry(ip_addr).or_insert(sanction);
+            }
+        }
+
+        Ok(all_sanctions)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn validate_address(
+        self,
+        _ctx: context::Context,
+        token: auth::Token,
+        address_string: String,
+        network: Network,
+    ) -> RpcResult<Option<ReceivingAddress>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let ret = ReceivingAddress::from_bech32m(&address_string, network).ok();
+        tracing::debug!(
+            "Responding to address validation request of {address_string}: {}",
+            ret.is_some()
+        );
+        Ok(ret)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn validate_amount(
+        self,
+        _ctx: context::Context,
+        token: auth::Token,
+        amount_string: String,
+    ) -> RpcResult<Option<NativeCurrencyAmount>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        Ok(Denomination::Coin.parse(&amount_string).ok())
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn parse_amount(
+        self,
+        _ctx: context::Context,
+        token: auth::Token,
+        amount_string: String,
+    ) -> RpcResult<NativeCurrencyAmount> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        Ok(amount_string.parse::<DenominatedAmount>()?.amount())
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn validate_addresses(
+        self,
+        _ctx: context::Context,
+        token: auth::Token,
+        addresses: Vec<String>,
+        network: Network,
+        accepted_key_types: Vec<KeyType>,
+    ) -> RpcResult<Vec<AddressVerdict>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        if addresses.len() > MAX_ADDRESSES_PER_VALIDATION {
+            return Err(RpcError::TooManyAddresses(addresses.len()));
+        }
+
+        // parsing lattice keys takes a while; do not block the runtime.
+        tokio::task::spawn_blocking(move || {
+            address_validation::validate_addresses(&addresses, network, &accepted_key_types)
+        })
+        .await
+        .map_err(|e| RpcError::Failed(e.to_string()))
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn amount_leq_confirmed_available_balance(
+        self,
+        _ctx: context::Context,
+        token: auth::Token,
+        amount: NativeCurrencyAmount,
+    ) -> RpcResult<bool> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+        let amount = ValidatedAmount::new(amount)?.amount();
+
+        let gs = self.state.lock_guard().await;
+        let wallet_status = gs.get_wallet_status_for_tip().await;
+
+        let confirmed_available = wallet_status.available_confirmed(Timestamp::now());
+
+        // test inequality
+        Ok(amount <= confirmed_available)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn confirmed_available_balance(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<NativeCurrencyAmount> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let gs = self.state.lock_guard().await;
+        let wallet_status = gs.get_wallet_status_for_tip().await;
+
+        let confirmed_available = wallet_status.available_confirmed(Timestamp::now());
+
+        Ok(confirmed_available)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn unconfirmed_available_balance(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<NativeCurrencyAmount> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let gs = self.state.lock_guard().await;
+        let wallet_status = gs.get_wallet_status_for_tip().await;
+
+        Ok(gs
+            .wallet_state
+            .unconfirmed_available_balance(&wallet_status, Timestamp::now()))
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn wallet_status(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<WalletStatus> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        Ok(self
+            .state
+            .lock_guard()
+            .await
+            .get_wallet_status_for_tip()
+            .await)
+    }
+
+    async fn num_expected_utxos(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<u64> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        Ok(self
+            .state
+            .lock_guard()
+            .await
+            .wallet_state
+            .num_expected_utxos()
+            .await)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn header(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+        block_selector: BlockSelector,
+    ) -> RpcResult<Option<BlockHeader>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let state = self.state.lock_guard().await;
+        let Some(block_digest) = block_selector.as_digest(&state).await else {
+            return Ok(None);
+        };
+        Ok(state
+            .chain
+            .archival_state()
+            .get_block_header(block_digest)
+            .await)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn next_receiving_address(
+        mut self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+        key_type: KeyType,
+    ) -> RpcResult<ReceivingAddress> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        Ok(self
+            .state
+            .api_mut()
+            .wallet_mut()
+            .next_receiving_address(key_type)
+            .await?)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn symmetric_key_rotation_status(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<SymmetricKeyRotationStatus> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        Ok(self
+            .state
+            .lock_guard()
+            .await
+            .wallet_state
+            .symmetric_key_rotation_status(Timestamp::now()))
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn next_symmetric_address(
+        mut self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<SymmetricAddressData> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let network = self.state.cli().network;
+        let address = self
+            .state
+            .api_mut()
+            .wallet_mut()
+            .next_receiving_address(KeyType::Symmetric)
+            .await?
+            .to_bech32m(network)?;
+
+        self.state
+            .lock_guard()
+            .await
+            .wallet_state
+            .symmetric_address_data(network)?
+            .into_iter()
+            .find(|address_data| address_data.address == address)
+            .ok_or_else(|| error::RpcError::Failed("symmetric key is not known".to_owned()))
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn symmetric_addresses(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<Vec<SymmetricAddressData>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let network = self.state.cli().network;
+        Ok(self
+            .state
+            .lock_guard()
+            .await
+            .wallet_state
+            .symmetric_address_data(network)?)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn next_payment_request(
+        mut self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+        amount: Option<NativeCurrencyAmount>,
+    ) -> RpcResult<PaymentRequestData> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+        let amount = amount
+            .map(ValidatedAmount::new)
+            .transpose()?
+            .map(|amount| amount.amount());
+        if let Some(amount) = amount.filter(|amount| !amount.is_positive()) {
+            return Err(AmountError::OutOfRange(format!("{} nau", amount.to_nau())).into());
+        }
+
+        let address = self
+            .state
+            .api_mut()
+            .wallet_mut()
+            .next_receiving_address(KeyType::Generation)
+            .await?;
+        let request = PaymentRequest::new(address, amount);
+
+        Ok(PaymentRequestData::new(&request, self.state.cli().network)?)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn known_keys(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<Vec<SpendingKey>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        Ok(self
+            .state
+            .lock_guard()
+            .await
+            .wallet_state
+            .get_all_known_spending_keys()
+            .collect())
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn known_keys_by_keytype(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+        key_type: KeyType,
+    ) -> RpcResult<Vec<SpendingKey>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        Ok(self
+            .state
+            .lock_guard()
+            .await
+            .wallet_state
+            .get_known_spending_keys(key_type)
+            .collect())
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn mempool_tx_count(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<usize> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        Ok(self.state.lock_guard().await.mempool.len())
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn mempool_size(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<usize> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        Ok(self.state.lock_guard().await.mempool.get_size())
+    }
+
+    async fn mempool_tx_ids(
+        self,
+        _context: ::tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<Vec<TransactionKernelId>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+        let txids: Vec<_> = self
+            .state
+            .lock_guard()
+            .await
+            .mempool
+            .fee_density_iter()
+            .map(|(kernel_id, _)| kernel_id)
+            .collect();
+
+        Ok(txids)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn history(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<Vec<(Digest, BlockHeight, Timestamp, NativeCurrencyAmount)>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let history = self.state.lock_guard().await.get_balance_history().await;
+
+        // sort
+        let mut display_history: Vec<(Digest, BlockHeight, Timestamp, NativeCurrencyAmount)> =
+            history
+                .iter()
+                .map(|(h, t, bh, a)| (*h, *bh, *t, *a))
+                .collect::<Vec<_>>();
+        display_history.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+        // return
+        Ok(display_history)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn dashboard_overview_data(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<OverviewData> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let now = Timestamp::now();
+        let state = self.state.lock_guard().await;
+        let tip_digest = {
+            log_slow_scope!(fn_name!() + "::hash() tip digest");
+            state.chain.light_state().hash()
+        };
+        let tip_header = *state.chain.light_state().header();
+        let syncing = state.net.sync_anchor.is_some();
+        let mempool_size = {
+            log_slow_scope!(fn_name!() + "::mempool.get_size()");
+            state.mempool.get_size()
+        };
+        let mempool_total_tx_count = {
+            log_slow_scope!(fn_name!() + "::mempool.len()");
+            state.mempool.len()
+        };
+        let mempool_own_tx_count = {
+            log_slow_scope!(fn_name!() + "::mempool.num_own_txs()");
+            state.mempool.num_own_txs()
+        };
+        let cpu_temp = None; // disable for now.  call is too slow.
+        let proving_capability = self.state.cli().proving_capability();
+
+        let peer_count = Some(state.net.peer_map.len());
+        let max_num_peers = self.state.cli().max_num_peers;
+
+        let mining_status = Some(state.mining_state.mining_status);
+
+        let confirmations = {
+            log_slow_scope!(fn_name!() + "::confirmations_internal()");
+            self.confirmations_internal(&state).await
+        };
+
+        let wallet_status = {
+            log_slow_scope!(fn_name!() + "::get_wallet_status_for_tip()");
+            state.get_wallet_status_for_tip().await
+        };
+        let wallet_state = &state.wallet_state;
+
+        let confirmed_available_balance = {
+            log_slow_scope!(fn_name!() + "::confirmed_available_balance()");
+            wallet_status.available_confirmed(now)
+        };
+        let confirmed_total_balance = {
+            log_slow_scope!(fn_name!() + "::confirmed_total_balance()");
+            wallet_status.total_confirmed()
+        };
+
+        let unconfirmed_available_balance = {
+            log_slow_scope!(fn_name!() + "::unconfirmed_available_balance()");
+            wallet_state.unconfirmed_available_balance(&wallet_status, now)
+        };
+        let unconfirmed_total_balance = {
+            log_slow_scope!(fn_name!() + "::unconfirmed_total_balance()");
+            wallet_state.unconfirmed_total_balance(&wallet_status)
+        };
+
+        Ok(OverviewData {
+            tip_digest,
+            tip_header,
+            syncing,
+            confirmed_available_balance,
+            confirmed_total_balance,
+            unconfirmed_available_balance,
+            unconfirmed_total_balance,
+            mempool_size,
+            mempool_total_tx_count,
+            mempool_own_tx_count,
+            peer_count,
+            max_num_peers,
+            mining_status,
+            proving_capability,
+            confirmations,
+            cpu_temp,
+        })
+    }
+
+    /******** CHANGE THINGS ********/
+    // Locking:
+    //   * acquires `global_state_lock` for write
+    //
+    // documented in trait. do not add doc-comment.
+    async fn clear_all_standings(
+        mut self,
+        _: context::Context,
+        token: auth::Token,
+    ) -> RpcResult<()> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let mut global_state_mut = self.state.lock_guard_mut().await;
+        global_state_mut
+            .net
+            .peer_map
+            .iter_mut()
+            .for_each(|(_, peerinfo)| {
+                peerinfo.standing.clear_standing();
+            });
+
+        // iterates and modifies standing field for all connected peers
+        global_state_mut.net.clear_all_standings_in_database().await;
+
+        Ok(global_state_mut.flush_databases().await?)
+    }
+
+    // Locking:
+    //   * acquires `global_state_lock` for write
+    //
+    // documented in trait. do not add doc-comment.
+    async fn clear_standing_by_ip(
+        mut self,
+        _: context::Context,
+        token: auth::Token,
+        ip: IpAddr,
+    ) -> RpcResult<()> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let mut global_state_mut = self.state.lock_guard_mut().await;
+        global_state_mut
+            .net
+            .peer_map
+            .iter_mut()
+            .for_each(|(socketaddr, peerinfo)| {
+                if socketaddr.ip() == ip {
+                    peerinfo.standing.clear_standing();
+                }
+            });
+
+        //Also clears this IP's standing in database, whether it is connected or not.
+        global_state_mut.net.clear_ip_standing_in_database(ip).await;
+
+        Ok(global_state_mut.flush_databases().await?)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn record_and_broadcast_transaction(
+        mut self,
+        _: context::Context,
+        token: auth::Token,
+        tx_artifacts: TxCreationArtifacts,
+    ) -> RpcResult<()> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+        self.authorize_spend(&fn_name!()).await?;
+
+        Ok(self
+            .state
+            .api_mut()
+            .tx_initiator_mut()
+            .record_and_broadcast_transaction(&tx_artifacts)
+            .await?)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn send(
+        mut self,
+        _ctx: context::Context,
+        token: auth::Token,
+        outputs: Vec<OutputFormat>,
+        change_policy: ChangePolicy,
+        fee: NativeCurrencyAmount,
+    ) -> RpcResult<TxCreationArtifacts> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+        Self::validate_spend_amounts(&outputs, fee)?;
+        self.authorize_spend(&fn_name!()).await?;
+
+        Ok(self
+            .state
+            .api_mut()
+            .tx_sender_mut()
+            .send(outputs, change_policy, fee, Timestamp::now())
+            .await?)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn plan_transfer(
+        self,
+        _ctx: context::Context,
+        token: auth::Token,
+        outputs: Vec<OutputFormat>,
+        fee: NativeCurrencyAmount,
+    ) -> RpcResult<TransferPlan> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+        Self::validate_spend_amounts(&outputs, fee)?;
+
+        Ok(self
+            .state
+            .api()
+            .tx_initiator()
+            .plan_transfer(outputs, fee, Timestamp::now())
+            .await?)
+    }
+
+    // documented in trait. do not add doc-commtn.
+    async fn send_transparent(
+        mut self,
+        _ctx: context::Context,
+        token: auth::Token,
+        outputs: Vec<OutputFormat>,
+        change_policy: ChangePolicy,
+        fee: NativeCurrencyAmount,
+    ) -> RpcResult<TxCreationArtifacts> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+        Self::validate_spend_amounts(&outputs, fee)?;
+        self.authorize_spend(&fn_name!()).await?;
+
+        Ok(self
+            .state
+            .api_mut()
+            .tx_initiator_mut()
+            .send_transparent(outputs, change_policy, fee, Timestamp::now())
+            .await?)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn send_with_inputs(
+        mut self,
+        _ctx: context::Context,
+        token: auth::Token,
+        outputs: Vec<OutputFormat>,
+        aocl_leaf_indices: Vec<u64>,
+        change_policy: ChangePolicy,
+        fee: NativeCurrencyAmount,
+    ) -> RpcResult<TxCreationArtifacts> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+        Self::validate_spend_amounts(&outputs, fee)?;
+        self.authorize_spend(&fn_name!()).await?;
+
+        Ok(self
+            .state
+            .api_mut()
+            .tx_sender_mut()
+            .send_with_inputs(
+                outputs,
+                &aocl_leaf_indices,
+                change_policy,
+                fee,
+                Timestamp::now(),
+            )
+            .await?)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn send_raw_transaction(
+        mut self,
+        _ctx: context::Context,
+        token: auth::Token,
+        spec: String,
+    ) -> RpcResult<TxCreationArtifacts> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let spec =
+            RawTransactionSpec::from_json(&spec).map_err(tx_initiation::error::SendError::from)?;
+        self.authorize_spend(&fn_name!()).await?;
+        Ok(self.state.api_mut().tx_sender_mut().send_raw(&spec).await?)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn cancel_transaction(
+        mut self,
+        _ctx: context::Context,
+        token: auth::Token,
+        transaction_id: TransactionKernelId,
+    ) -> RpcResult<()> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        Ok(self
+            .state
+            .api_mut()
+            .tx_initiator_mut()
+            .cancel(transaction_id)
+            .await?)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn spending_pin_challenge(
+        self,
+        _ctx: context::Context,
+        token: auth::Token,
+    ) -> RpcResult<SpendingPinChallenge> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        Ok(self.state.lock_guard().await.spending_pin.challenge()?)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn unlock_spending(
+        self,
+        _ctx: context::Context,
+        token: auth::Token,
+        response: SpendingPinResponse,
+    ) -> RpcResult<()> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let spending_pin = self.state.lock_guard().await.spending_pin.clone();
+        Ok(spending_pin.unlock(response).await?)
+    }
+
+    async fn upgrade(
+        mut self,
+        _ctx: context::Context,
+        token: auth::Token,
+        tx_kernel_id: TransactionKernelId,
+    ) -> RpcResult<bool> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        // Does transaction exist and is it in need of upgrading?
+        let Some((tx, upgrade_priority)) = self
+            .state
+            .lock_guard()
+            .await
+            .mempool
+            .get_with_priority(tx_kernel_id)
+            .map(|(x, y)| (x.to_owned(), y))
+        else {
+            return Ok(false);
+        };
+
+        let current_msa = self
+            .state
+            .lock_guard()
+            .await
+            .chain
+            .light_state()
+            .mutator_set_accumulator_after()
+            .expect("mutator set of tip must exist");
+        let is_synced = tx.kernel.mutator_set_hash == current_msa.hash();
+
+        let upgrade_job = match (&tx.proof, is_synced) {
+            (TransactionProof::SingleProof(_), true) => return Ok(false),
+            (TransactionProof::SingleProof(neptune_proof), false) => {
+                let gobbling_potential = NativeCurrencyAmount::zero();
+                let update_job = self
+                    .state
+                    .lock_guard_mut()
+                    .await
+                    .update_single_proof_job(
+                        tx.kernel,
+                        neptune_proof.to_owned(),
+                        upgrade_priority.incentive_given_gobble_potential(gobbling_potential),
+                    )
+                    .await?;
+                UpgradeJob::UpdateMutatorSetData(update_job)
+            }
+            (TransactionProof::ProofCollection(proof_collection), _) => {
+                // It doesn't matter if the proof collection is updated or not,
+                // since the later call to upgrade the transaction handles the
+                // case of unsynced single proof backed transactions.
+                let gobbling_potential = NativeCurrencyAmount::zero();
+                let raise_job = self
+                    .state
+                    .lock_guard_mut()
+                    .await
+                    .upgrade_proof_collection_job(
+                        tx.kernel,
+                        proof_collection.to_owned(),
+                        upgrade_priority.incentive_given_gobble_potential(gobbling_potential),
+                    )
+                    .await?;
+                UpgradeJob::ProofCollectionToSingleProof(raise_job)
+            }
+
+            // This implementation is not done because local transaction initiation
+            // should always produce proof collections or single proofs, and
+            // primitive witnesses may never be shared on the network. So it seems
+            // there is no use case for implementing this.
+            (TransactionProof::Witness(_), _) => {
+                error!("Can't upgrade primitive witnesses through this command.");
+                return Ok(false);
+            }
+        };
+
+        let _ = self
+            .rpc_server_to_main_tx
+            .send(RPCServerToMain::PerformTxProofUpgrade(Box::new(
+                upgrade_job,
+            )))
+            .await;
+
+        Ok(true)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn proof_upgrade_schedule(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<ProofUpgradeScheduleStatus> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let state = self.state.lock_guard().await;
+        let schedule = state.proof_upgrade_schedule.clone();
+        let cpu_percent = state
+            .load_shedding
+            .status()
+            .pressure
+            .map(|pressure| pressure.cpu_percent);
+        let deferral = schedule.deferral(chrono::Local::now().time(), cpu_percent);
+
+        Ok(ProofUpgradeScheduleStatus {
+            schedule,
+            deferral,
+            cpu_percent,
+        })
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn set_proof_upgrade_schedule(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+        schedule: ProofUpgradeSchedule,
+    ) -> RpcResult<()> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        schedule.validate().map_err(RpcError::Failed)?;
+        info!(
+            "Proof-upgrade schedule set via RPC: windows: [{}], max cpu: {:?}, paused: {}",
+            schedule.windows.iter().join(", "),
+            schedule.max_cpu_percent,
+            schedule.paused
+        );
+        self.state.lock_guard_mut().await.proof_upgrade_schedule = schedule;
+
+        Ok(())
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn alerts(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<Vec<Alert>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        Ok(self
+            .state
+            .lock_guard()
+            .await
+            .alerts
+            .active(Timestamp::now()))
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn silence_alert(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+        kind: AlertKind,
+        duration: Timestamp,
+    ) -> RpcResult<Timestamp> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let until = Timestamp::now() + duration;
+        info!("Alert {kind} silenced via RPC until {until}");
+        self.state
+            .lock_guard_mut()
+            .await
+            .alerts
+            .silence(kind, until);
+
+        Ok(until)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn wallet_backup_status(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<WalletBackupStatus> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        if self.state.cli().wallet_backup_peers.is_empty() {
+            return Err(RpcError::Failed(
+                "no trusted peers for wallet backups are configured".to_string(),
+            ));
+        }
+
+        let num_held_backups = self.state.held_wallet_backups().lock().await.len();
+        Ok(self
+            .state
+            .wallet_backup()
+            .lock()
+            .await
+            .status(num_held_backups))
+    }
+
+    // // documented in trait. do not add doc-comment.
+    async fn claim_utxo(
+        mut self,
+        _ctx: context::Context,
+        token: auth::Token,
+        encrypted_utxo_notification: String,
+        max_search_depth: Option<u64>,
+    ) -> RpcResult<bool> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let claim_data = self
+            .claim_utxo_inner(encrypted_utxo_notification, max_search_depth)
+            .await?;
+
+        let Some(claim_data) = claim_data else {
+            // UTXO has already been claimed by wallet
+            warn!("UTXO notification of amount was already received. Not adding again.");
+            return Ok(false);
+        };
+
+        let expected_utxo_was_new = !claim_data.has_expected_utxo;
+        self.state
+            .lock_guard_mut()
+            .await
+            .wallet_state
+            .claim_utxo(claim_data)
+            .await
+            .map_err(error::ClaimError::from)?;
 
-        // decrypt utxo_transfer_encrypted into UtxoTransfer
-        let utxo_notification = utxo_transfer_encrypted.decrypt_with_spending_key(&spending_key)?;
+        Ok(expected_utxo_was_new)
+    }
 
-        tracing::debug!("claim-utxo: decrypted {:#?}", utxo_notification);
+    // documented in trait. do not add doc-comment.
+    async fn composer_payouts(
+        self,
+        _ctx: context::Context,
+        token: auth::Token,
+        from_height: BlockHeight,
+    ) -> RpcResult<Vec<ComposerPayout>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
 
-        // search for matching monitored utxo and return early if found.
-        if state
+        let payouts_file = self
+            .state
+            .lock_guard()
+            .await
             .wallet_state
-            .find_monitored_utxo(&utxo_notification.utxo, utxo_notification.sender_randomness)
+            .configuration
+            .data_directory()
+            .composer_payouts_file_path();
+        let payouts = ProposedComposerPayout::read_all(&payouts_file)
             .await
-            .is_some()
-        {
-            info!("found monitored utxo. Returning early.");
-            return Ok(None);
-        }
+            .map_err(|e| RpcError::Failed(e.to_string()))?;
 
-        // construct an IncomingUtxo
-        let incoming_utxo = IncomingUtxo::from_utxo_notification_payload(
-            utxo_notification,
-            spending_key.privacy_preimage(),
-        );
+        ProposedComposerPayout::mined(&*self.state.lock_guard().await, payouts, from_height)
+            .await
+            .map_err(|e| RpcError::Failed(e.to_string()))
+    }
 
-        // Check if we can satisfy typescripts
-        if !incoming_utxo.utxo.all_type_script_states_are_valid() {
-            let err = error::ClaimError::InvalidTypeScript;
-            warn!("{}", err.to_string());
-            return Err(err);
-        }
+    // documented in trait. do not add doc-comment.
+    async fn import_utxo_claim(
+        mut self,
+        _ctx: context::Context,
+        token: auth::Token,
+        utxo_triple: UtxoTriple,
+        max_search_depth: Option<u64>,
+    ) -> RpcResult<bool> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
 
-        // check if wallet is already expecting this utxo.
-        let addition_record = incoming_utxo.addition_record();
-        let has_expected_utxo = state.wallet_state.has_expected_utxo(addition_record).await;
+        let Some(claim_data) = self
+            .import_utxo_claim_inner(utxo_triple, max_search_depth)
+            .await?
+        else {
+            warn!("Imported UTXO is already monitored. Not adding again.");
+            return Ok(false);
+        };
 
-        // Check if UTXO has already been mined in a transaction.
-        let mined_in_block = state
-            .chain
-            .archival_state()
-            .find_canonical_block_with_output(addition_record, max_search_depth)
-            .await;
-        let maybe_prepared_mutxo = match mined_in_block {
-            Some(block) => {
-                let aocl_leaf_index = {
-                    // Find matching AOCL leaf index that must be in this block
-                    let last_aocl_index_in_block = block
-                        .mutator_set_accumulator_after()
-                        .expect("Block from state must have mutator set after")
-                        .aocl
-                        .num_leafs()
-                        - 1;
-                    let num_outputs_in_block: u64 = block
-                        .mutator_set_update()
-                        .expect("Block from state must have mutator set update")
-                        .additions
-                        .len()
-                        .try_into()
-                        .unwrap();
-                    let min_aocl_leaf_index = last_aocl_index_in_block - num_outputs_in_block + 1;
-                    let mut haystack = last_aocl_index_in_block;
-                    let ams = state.chain.archival_state().archival_mutator_set.ams();
-                    while ams.aocl.get_leaf_async(haystack).await
-                        != addition_record.canonical_commitment
-                    {
-                        assert!(haystack > min_aocl_leaf_index);
-                        haystack -= 1;
-                    }
+        self.state
+            .lock_guard_mut()
+            .await
+            .wallet_state
+            .claim_utxo(claim_data)
+            .await
+            .map_err(error::ClaimError::from)?;
+
+        Ok(true)
+    }
 
-                    haystack
-                };
-                let item = Tip5::hash(&incoming_utxo.utxo);
-                let ams = state.chain.archival_state().archival_mutator_set.ams();
-                let msmp = ams
-                    .restore_membership_proof(
-                        item,
-                        incoming_utxo.sender_randomness,
-                        incoming_utxo.receiver_preimage,
-                        aocl_leaf_index,
-                    )
-                    .await
-                    .map_err(|x| anyhow!("Could not restore mutator set membership proof. Is archival mutator set corrupted? Got error: {x}"))?;
+    // documented in trait. do not add doc-comment.
+    async fn import_membership_bundle(
+        mut self,
+        _ctx: context::Context,
+        token: auth::Token,
+        bundle: MembershipBundle,
+        utxo_triples: Vec<UtxoTriple>,
+    ) -> RpcResult<usize> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
 
-                let tip_digest = state.chain.light_state().hash();
+        Ok(self
+            .state
+            .api_mut()
+            .wallet_mut()
+            .import_membership_bundle(bundle, utxo_triples)
+            .await?)
+    }
 
-                let mut monitored_utxo = MonitoredUtxo::new(
-                    incoming_utxo.utxo.clone(),
-                    self.state.cli().number_of_mps_per_utxo,
-                    msmp.aocl_leaf_index,
-                    msmp.sender_randomness,
-                    msmp.receiver_preimage,
-                    &block,
-                );
-                monitored_utxo.add_membership_proof_for_tip(tip_digest, msmp.clone());
+    // documented in trait. do not add doc-comment.
+    async fn shutdown(self, _: context::Context, token: auth::Token) -> RpcResult<bool> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
 
-                // Was UTXO already spent? If so, register it as such.
-                let msa = ams.accumulator().await;
-                if !msa.verify(item, &msmp) {
-                    warn!("Claimed UTXO was already spent. Marking it as such.");
+        // 1. Send shutdown message to main
+        let response = self
+            .rpc_server_to_main_tx
+            .send(RPCServerToMain::Shutdown)
+            .await;
 
-                    if let Some(spending_block) = state
-                        .chain
-                        .archival_state()
-                        .find_canonical_block_with_input(
-                            msmp.compute_indices(item),
-                            max_search_depth,
-                        )
-                        .await
-                    {
-                        warn!(
-                            "Claimed UTXO was spent in block {:x}; which has height {}",
-                            spending_block.hash(),
-                            spending_block.header().height
-                        );
-                        monitored_utxo.mark_as_spent(&spending_block);
-                    } else {
-                        error!("Claimed UTXO's mutator set membership proof was invalid but we could not find the block in which it was spent. This is most likely a bug in the software.");
-                    }
-                }
+        // 2. Send acknowledgement to client.
+        Ok(response.is_ok())
+    }
 
-                Some(monitored_utxo)
+    // documented in trait. do not add doc-comment.
+    async fn batch(
+        self,
+        context: context::Context,
+        token: auth::Token,
+        calls: Vec<RPCRequest>,
+    ) -> RpcResult<Vec<RPCResponse>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        if calls.len() > MAX_RPC_BATCH_SIZE {
+            return Err(error::RpcError::BatchTooLarge(calls.len()));
+        }
+
+        let mut responses = Vec::with_capacity(calls.len());
+        for call in calls {
+            if matches!(call, RPCRequest::Batch { .. }) {
+                responses.push(RPCResponse::Batch(Err(error::RpcError::NestedBatch)));
+                continue;
             }
-            None => None,
-        };
 
-        let expected_utxo = incoming_utxo.into_expected_utxo(UtxoNotifier::Cli);
-        Ok(Some(ClaimUtxoData {
-            prepared_monitored_utxo: maybe_prepared_mutxo,
-            has_expected_utxo,
-            expected_utxo,
-        }))
+            // Boxed, because serving a call may itself serve a batch.
+            let response: BoxFuture<'static, Result<RPCResponse, tarpc::ServerError>> =
+                Box::pin(Serve::serve(self.clone().serve(), context, call));
+            let response = response
+                .await
+                .map_err(|e| error::RpcError::Failed(e.to_string()))?;
+            responses.push(response);
+        }
+
+        Ok(responses)
     }
 
-    /// Return a PoW puzzle with the provided guesser address.
-    async fn pow_puzzle_inner(
+    // documented in trait. do not add doc-comment.
+    async fn clear_mempool(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<()> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let _ = self
+            .rpc_server_to_main_tx
+            .send(RPCServerToMain::ClearMempool)
+            .await;
+        Ok(())
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn promote_standby(
         mut self,
-        guesser_address: ReceivingAddress,
-        mut proposal: Block,
-    ) -> RpcResult<Option<ProofOfWorkPuzzle>> {
-        let latest_block_header = *self.state.lock_guard().await.chain.light_state().header();
+        _context: tarpc::context::Context,
+        token: auth::Token,
+        force: bool,
+    ) -> RpcResult<()> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
 
-        proposal.set_header_guesser_address(guesser_address);
-        let puzzle = ProofOfWorkPuzzle::new(proposal.clone(), latest_block_header.difficulty);
+        let replication = self.state.lock_guard().await.replication.clone();
+        if replication.is_spend_enabled() {
+            return Err(error::RpcError::NotStandby);
+        }
 
-        // Record block proposal in case of guesser-success, for later
-        // retrieval. But limit number of blocks stored this way.
-        let mut state = self.state.lock_guard_mut().await;
-        if state.mining_state.exported_block_proposals.len()
-            >= MAX_NUM_EXPORTED_BLOCK_PROPOSAL_STORED
-        {
-            return Err(error::RpcError::ExportedBlockProposalStorageCapacityExceeded);
+        let demotion = match (
+            replication.primary,
+            &self.state.cli().replication_secret_file,
+        ) {
+            (Some(primary), Some(path)) => match ReplicationSecret::read_from_file(path).await {
+                Ok(secret) => replication_loop::demote_primary(primary, &self.state, secret).await,
+                Err(e) => Err(e),
+            },
+            _ => Err(anyhow::anyhow!("primary is unknown")),
+        };
+        match demotion {
+            Ok(()) => info!("Demoted primary"),
+            Err(e) if force => warn!("Promoting to primary without demoting primary: {e:#}"),
+            Err(e) => return Err(error::RpcError::CannotDemotePrimary(format!("{e:#}"))),
         }
 
-        state
-            .mining_state
-            .exported_block_proposals
-            .insert(puzzle.id, proposal);
+        self.state
+            .lock_guard_mut()
+            .await
+            .set_replication_role(ReplicationRole::Primary)
+            .await
+            .map_err(|e| error::RpcError::Failed(e.to_string()))?;
+        info!("Promoted to primary. Spending is enabled.");
 
-        Ok(Some(puzzle))
+        Ok(())
     }
 
-    /// Verify a pow solution and send it to main loop if it is valid.
-    async fn pow_solution_inner(&self, mut proposal: Block, pow: BlockPow) -> RpcResult<bool> {
-        // Check if solution works.
-        let latest_block_header = *self.state.lock_guard().await.chain.light_state().header();
+    // documented in trait. do not add doc-comment.
+    async fn freeze(
+        mut self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<()> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
 
-        proposal.set_header_pow(pow);
+        let mut state = self.state.lock_guard_mut().await;
 
-        if !proposal.has_proof_of_work(self.state.cli().network, &latest_block_header) {
-            warn!("Got claimed PoW solution but PoW solution is not valid.");
-            return Ok(false);
+        if state.net.sync_anchor.is_some() {
+            error!("Cannot pause state updates when syncing.");
+            return Err(error::RpcError::CannotPauseWhileSyncing);
         }
 
-        // No time to waste! Inform main_loop!
-        let solution = Box::new(proposal);
-        let _ = self
-            .rpc_server_to_main_tx
-            .send(RPCServerToMain::ProofOfWorkSolution(solution))
-            .await;
+        state.net.freeze = true;
 
-        Ok(true)
+        Ok(())
     }
 
-    /// get the data_directory for this neptune-core instance
-    pub fn data_directory(&self) -> &DataDirectory {
-        &self.data_directory
+    // documented in trait. do not add doc-comment.
+    async fn unfreeze(
+        mut self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<()> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        self.state.lock_mut(|state| state.net.freeze = false).await;
+
+        Ok(())
+    }
+
+    async fn pause_miner(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<()> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        if self.state.cli().mine() {
+            let _ = self
+                .rpc_server_to_main_tx
+                .send(RPCServerToMain::PauseMiner)
+                .await;
+        } else {
+            info!("Cannot pause miner since it was never started");
+        }
+        Ok(())
     }
-}
 
-impl RPC for NeptuneRPCServer {
     // documented in trait. do not add doc-comment.
-    async fn cookie_hint(self, _: context::Context) -> RpcResult<auth::CookieHint> {
+    async fn restart_miner(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<()> {
         log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
 
-        if self.state.cli().disable_cookie_hint {
-            Err(error::RpcError::CookieHintDisabled)
+        if self.state.cli().mine() {
+            let _ = self
+                .rpc_server_to_main_tx
+                .send(RPCServerToMain::RestartMiner)
+                .await;
         } else {
-            Ok(auth::CookieHint {
-                data_directory: self.data_directory().to_owned(),
-                network: self.state.cli().network,
-            })
+            info!("Cannot restart miner since it was never started");
         }
+        Ok(())
     }
 
-    // documented in trait. do not add doc-comment.
-    async fn network(self, _: context::Context) -> RpcResult<Network> {
-        log_slow_scope!(fn_name!());
+    // documented in trait. do not add doc-comment.
+    async fn set_coinbase_distribution(
+        mut self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+        coinbase_distribution_readable: Vec<CoinbaseOutputReadable>,
+    ) -> RpcResult<()> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let network = self.state.cli().network;
+        let mut coinbase_distribution = vec![];
+        for output in coinbase_distribution_readable {
+            let output = match output.into_coinbase_output(network) {
+                Ok(cbo) => cbo,
+                Err(err) => return Err(RpcError::InvalidCoinbaseDistribution(err.to_string())),
+            };
+            coinbase_distribution.push(output);
+        }
+
+        let coinbase_distribution = match CoinbaseDistribution::try_new(coinbase_distribution) {
+            Ok(cd) => cd,
+            Err(err) => return Err(RpcError::InvalidCoinbaseDistribution(err.to_string())),
+        };
+
+        if !self.state.cli().compose {
+            warn!("Cannot set coinbase distribution as node is not composing");
+            return Err(RpcError::NotComposing);
+        }
+
+        let mut state = self.state.lock_guard_mut().await;
+        state
+            .mining_state
+            .set_coinbase_distribution(coinbase_distribution);
 
-        Ok(self.state.cli().network)
+        Ok(())
     }
 
     // documented in trait. do not add doc-comment.
-    async fn own_listen_address_for_peers(
-        self,
-        _context: context::Context,
+    async fn unset_coinbase_distribution(
+        mut self,
+        _context: tarpc::context::Context,
         token: auth::Token,
-    ) -> RpcResult<Option<SocketAddr>> {
+    ) -> RpcResult<()> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let listen_port = self.state.cli().own_listen_port();
-        let listen_for_peers_ip = self.state.cli().peer_listen_addr;
-        Ok(listen_port.map(|port| SocketAddr::new(listen_for_peers_ip, port)))
-    }
-
-    // documented in trait. do not add doc-comment.
-    async fn own_instance_id(
-        self,
-        _context: context::Context,
-        token: auth::Token,
-    ) -> RpcResult<InstanceId> {
-        log_slow_scope!(fn_name!());
-        token.auth(&self.valid_tokens)?;
+        if self.state.cli().mine() {
+            let mut state = self.state.lock_guard_mut().await;
+            state.mining_state.unset_coinbase_distribution();
+        } else {
+            warn!("Cannot unset coinbase distribution as node is not mining");
+        }
 
-        Ok(self.state.lock_guard().await.net.instance_id)
+        Ok(())
     }
 
     // documented in trait. do not add doc-comment.
-    async fn block_height(self, _: context::Context, token: auth::Token) -> RpcResult<BlockHeight> {
+    async fn mine_blocks_to_wallet(
+        mut self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+        n_blocks: u32,
+    ) -> RpcResult<()> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
+        let include_mempool_txs = true;
         Ok(self
             .state
-            .lock_guard()
-            .await
-            .chain
-            .light_state()
-            .kernel
-            .header
-            .height)
+            .api_mut()
+            .regtest_mut()
+            .mine_blocks_to_wallet(n_blocks, include_mempool_txs)
+            .await?)
     }
 
-    async fn best_proposal(
-        self,
-        _: context::Context,
+    // documented in trait. do not add doc-comment.
+    async fn invalidate_block(
+        mut self,
+        _context: tarpc::context::Context,
         token: auth::Token,
-    ) -> RpcResult<Option<BlockInfo>> {
+        block_digest: Digest,
+    ) -> RpcResult<Digest> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let state = self.state.lock_guard().await;
-        let tip_digest = state.chain.light_state().hash();
-        let proposal = &state.mining_state.block_proposal;
-
-        // Returning BlockInfo here is not completely kosher since a few fields
-        // don't make sense in this context. But it's a close fit.
-        Ok(proposal.map(|block| {
-            BlockInfo::new(
-                block,
-                state.chain.archival_state().genesis_block().hash(),
-                tip_digest,
-                vec![],
-                false,
-            )
-        }))
+        Ok(self
+            .state
+            .api_mut()
+            .regtest_mut()
+            .invalidate_block(block_digest)
+            .await?)
     }
 
     // documented in trait. do not add doc-comment.
-    async fn confirmations(
-        self,
-        _: context::Context,
+    async fn reconsider_block(
+        mut self,
+        _context: tarpc::context::Context,
         token: auth::Token,
-    ) -> RpcResult<Option<BlockHeight>> {
+        block_digest: Digest,
+    ) -> RpcResult<Digest> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let guard = self.state.lock_guard().await;
-        Ok(self.confirmations_internal(&guard).await)
+        Ok(self
+            .state
+            .api_mut()
+            .regtest_mut()
+            .reconsider_block(block_digest)
+            .await?)
     }
 
     // documented in trait. do not add doc-comment.
-    async fn latest_address(
+    async fn provide_pow_solution(
         self,
         _context: tarpc::context::Context,
         token: auth::Token,
-        key_type: KeyType,
-    ) -> RpcResult<ReceivingAddress> {
+        pow: BlockPow,
+        proposal_id: Digest,
+    ) -> RpcResult<bool> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let state = self.state.lock_guard().await;
-
-        let current_counter = state.wallet_state.spending_key_counter(key_type);
-        let index = current_counter.checked_sub(1);
-        let Some(index) = index else {
-            return Err(RpcError::WalletKeyCounterIsZero);
+        // Find proposal from list of exported proposals.
+        let Some(proposal) = self
+            .state
+            .lock_guard()
+            .await
+            .mining_state
+            .exported_block_proposals
+            .get(&proposal_id)
+            .map(|x| x.to_owned())
+        else {
+            warn!(
+                "Got claimed PoW solution but no challenge was known. \
+                Did solution come in too late?"
+            );
+            return Ok(false);
         };
 
-        Ok(state
-            .wallet_state
-            .nth_spending_key(key_type, index)
-            .to_address())
+        self.pow_solution_inner(proposal, pow).await
     }
 
     // documented in trait. do not add doc-comment.
-    async fn utxo_digest(
+    async fn provide_new_tip(
         self,
-        _: context::Context,
+        _context: tarpc::context::Context,
         token: auth::Token,
-        leaf_index: u64,
-    ) -> RpcResult<Option<Digest>> {
+        pow: BlockPow,
+        proposal: Block,
+    ) -> RpcResult<bool> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let state = self.state.lock_guard().await;
-        let aocl = &state.chain.archival_state().archival_mutator_set.ams().aocl;
+        // Since block comes from external source, we need to check validity.
+        let current_tip = self.state.lock_guard().await.chain.light_state().clone();
+        if !proposal
+            .is_valid(&current_tip, Timestamp::now(), self.state.cli().network)
+            .await
+        {
+            warn!("Got claimed new block that was not valid");
+            return Ok(false);
+        }
 
-        Ok(
-            match leaf_index > 0 && leaf_index < aocl.num_leafs().await {
-                true => Some(aocl.get_leaf_async(leaf_index).await),
-                false => None,
-            },
-        )
+        self.pow_solution_inner(proposal, pow).await
     }
 
     // documented in trait. do not add doc-comment.
-    async fn utxo_origin_block(
+    async fn validate_proposal(
         self,
-        _: context::Context,
+        _context: tarpc::context::Context,
         token: auth::Token,
-        addition_record: AdditionRecord,
-        max_search_depth: Option<u64>,
-    ) -> RpcResult<Option<Digest>> {
+        proposal: Block,
+    ) -> RpcResult<ProposalVerdict> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let state = self.state.lock_guard().await;
-        let block = state
-            .chain
-            .archival_state()
-            .find_canonical_block_with_output(addition_record, max_search_depth)
-            .await;
-
-        Ok(block.map(|block| block.hash()))
+        // Verifying the proof takes a while, so do not hold the lock.
+        let current_tip = self.state.lock_guard().await.chain.light_state().clone();
+        let cli = self.state.cli();
+
+        Ok(ProposalVerdict::new(
+            &proposal,
+            &current_tip,
+            Timestamp::now(),
+            cli.network,
+            cli.minimum_guesser_fraction,
+        )
+        .await)
     }
 
     // documented in trait. do not add doc-comment.
-    async fn block_digest(
-        self,
-        _: context::Context,
+    async fn prune_abandoned_monitored_utxos(
+        mut self,
+        _context: tarpc::context::Context,
         token: auth::Token,
-        block_selector: BlockSelector,
-    ) -> RpcResult<Option<Digest>> {
+    ) -> RpcResult<usize> {
+        const DEFAULT_MUTXO_PRUNE_DEPTH: usize = 200;
+
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let state = self.state.lock_guard().await;
-        let archival_state = state.chain.archival_state();
-        let Some(digest) = block_selector.as_digest(&state).await else {
-            return Ok(None);
-        };
-        // verify the block actually exists
-        Ok(archival_state
-            .get_block_header(digest)
+        let mut global_state_mut = self.state.lock_guard_mut().await;
+
+        let prune_count_res = global_state_mut
+            .prune_abandoned_monitored_utxos(DEFAULT_MUTXO_PRUNE_DEPTH)
+            .await;
+
+        global_state_mut
+            .flush_databases()
             .await
-            .map(|_| digest))
+            .expect("flushed DBs");
+
+        match prune_count_res {
+            Ok(prune_count) => {
+                info!("Marked {prune_count} monitored UTXOs as abandoned");
+                Ok(prune_count)
+            }
+            Err(err) => {
+                error!("Pruning monitored UTXOs failed with error: {err}");
+                Ok(0)
+            }
+        }
     }
 
-    // documented in trait. do not add doc-comment.
-    async fn block_info(
+    // Documented in trait. Do not add doc-comment.
+    async fn set_tip(
         self,
-        _: context::Context,
+        _context: tarpc::context::Context,
         token: auth::Token,
-        block_selector: BlockSelector,
-    ) -> RpcResult<Option<BlockInfo>> {
+        indicated_tip: Digest,
+    ) -> RpcResult<()> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let state = self.state.lock_guard().await;
-        let Some(digest) = block_selector.as_digest(&state).await else {
-            return Ok(None);
-        };
-        let tip_digest = state.chain.light_state().hash();
-        let archival_state = state.chain.archival_state();
-
-        let Some(block) = archival_state.get_block(digest).await.unwrap() else {
-            return Ok(None);
-        };
-        let is_canonical = archival_state
-            .block_belongs_to_canonical_chain(digest)
-            .await;
-
-        // sibling blocks are those at the same height, with different digest
-        let sibling_blocks = archival_state
-            .block_height_to_block_digests(block.header().height)
+        // Set tip asynchronously -- avoid RPC timeout.
+        self.rpc_server_to_main_tx
+            .send(RPCServerToMain::SetTipToStoredBlock(indicated_tip))
             .await
-            .into_iter()
-            .filter(|d| *d != digest)
-            .collect();
+            .map_err(|e| RpcError::Failed(format!("could not send message to main loop: {e}")))?;
 
-        Ok(Some(BlockInfo::new(
-            &block,
-            archival_state.genesis_block().hash(),
-            tip_digest,
-            sibling_blocks,
-            is_canonical,
-        )))
+        Ok(())
     }
 
     // documented in trait. do not add doc-comment.
-    async fn block_kernel(
+    async fn list_own_coins(
         self,
-        _: context::Context,
+        _context: ::tarpc::context::Context,
         token: auth::Token,
-        block_selector: BlockSelector,
-    ) -> RpcResult<Option<BlockKernel>> {
+    ) -> RpcResult<Vec<CoinWithPossibleTimeLock>> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
         let state = self.state.lock_guard().await;
-        let Some(digest) = block_selector.as_digest(&state).await else {
-            return Ok(None);
-        };
-
-        let block = state
-            .chain
-            .archival_state()
-            .get_block(digest)
-            .await
-            .expect("Program must be able to read archival state data.");
-        let block_kernel = block.map(|block| block.kernel.clone());
+        let tip = state.chain.light_state();
+        let tip_hash = tip.hash();
+        let tip_msa = tip
+            .mutator_set_accumulator_after()
+            .expect("Block from state must have mutator set after");
 
-        Ok(block_kernel)
+        Ok(state
+            .wallet_state
+            .get_all_own_coins_with_possible_timelocks(&tip_msa, tip_hash)
+            .await)
     }
 
     // documented in trait. do not add doc-comment.
-    async fn addition_record_indices_for_block(
+    async fn wallet_stats(
         self,
-        _: context::Context,
+        _context: ::tarpc::context::Context,
         token: auth::Token,
-        block_selector: BlockSelector,
-    ) -> RpcResult<Vec<(AdditionRecord, Option<u64>)>> {
+        dust_threshold: NativeCurrencyAmount,
+    ) -> RpcResult<WalletStats> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
+        let dust_threshold = ValidatedAmount::new(dust_threshold)?.amount();
 
         let state = self.state.lock_guard().await;
-        let Some(digest) = block_selector.as_digest(&state).await else {
-            return Ok(vec![]);
-        };
-
-        let addition_records_dictionary = state
-            .chain
-            .archival_state()
-            .get_addition_record_indices_for_block(digest)
-            .await
-            .into_iter()
-            .flatten()
-            .collect_vec();
+        let tip = state.chain.light_state();
+        let tip_hash = tip.hash();
+        let tip_msa = tip
+            .mutator_set_accumulator_after()
+            .expect("Block from state must have mutator set after");
 
-        Ok(addition_records_dictionary)
+        Ok(state
+            .wallet_state
+            .wallet_stats(&tip_msa, tip_hash, Timestamp::now(), dust_threshold)
+            .await)
     }
 
-    async fn restore_membership_proof_privacy_preserving(
+    /// Get [`UiUtxo`] from three sources:
+    /// 1) the wallet database for monitored UTXOs (these are confirmed);
+    /// 2) the mempool (these are pending); and
+    /// 3) the wallet database for expected UTXOs (expected).
+    ///
+    /// The assembled list of [`UiUtxo`] is deduplicated based on addition record, which is
+    /// collected separately in a hash set. Note that this order is important because it
+    /// implicitly resolves duplicate conflicts. A duplicate [`UiUtxo`] cannot be inserted again,
+    /// so the first stage that applies determines the "received" label --
+    /// confirmed / pending / expected.
+    async fn list_utxos(
         self,
-        _context: tarpc::context::Context,
+        _context: ::tarpc::context::Context,
         token: auth::Token,
-        requests: Vec<AbsoluteIndexSet>,
-    ) -> RpcResult<ResponseMsMembershipProofPrivacyPreserving> {
+    ) -> RpcResult<Vec<UiUtxo>> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
+        // get owned UTXOs
+        let mut ui_utxos = vec![];
+        let mut present_addition_records = HashSet::new();
         let state = self.state.lock_guard().await;
-        let ams = state.chain.archival_state().archival_mutator_set.ams();
-
-        let mut membership_proofs = Vec::with_capacity(requests.len());
-        for request in requests {
-            match ams
-                .restore_membership_proof_privacy_preserving(request)
-                .await
-            {
-                Ok(msmp) => membership_proofs.push(msmp),
-                Err(err) => {
-                    debug!("Failed to restore MSMP: {err}");
-                    return Err(RpcError::CannotRestoreMembershipProofs(err.to_string()));
+        for monitored_utxo in state
+            .wallet_state
+            .wallet_db
+            .monitored_utxos()
+            .get_all()
+            .await
+        {
+            let received = UtxoStatusEvent::Confirmed {
+                block_height: monitored_utxo.confirmed_in_block.2,
+                timestamp: monitored_utxo.confirmed_in_block.1,
+            };
+            let spent = if let Some((_, timestamp, block_height)) = monitored_utxo.spent_in_block {
+                UtxoStatusEvent::Confirmed {
+                    block_height,
+                    timestamp,
                 }
+            } else {
+                UtxoStatusEvent::None
+            };
+
+            if present_addition_records.insert(monitored_utxo.addition_record()) {
+                ui_utxos.push(UiUtxo {
+                    received,
+                    spent,
+                    aocl_leaf_index: Some(monitored_utxo.aocl_leaf_index),
+                    amount: monitored_utxo.utxo.get_native_currency_amount(),
+                    release_date: monitored_utxo.utxo.release_date(),
+                });
             }
         }
 
-        debug!("Restored {} msmps", membership_proofs.len());
-        debug!(
-            "AOCL MMR lengths: [{}]",
-            membership_proofs
-                .iter()
-                .map(|x| x.aocl_auth_paths.len().to_string())
-                .join(", ")
-        );
+        // get unconfirmed incoming UTXOs
+        for (incoming_utxo, addition_record) in state.wallet_state.mempool_unspent_utxos_iter() {
+            if present_addition_records.insert(addition_record) {
+                ui_utxos.push(UiUtxo {
+                    received: UtxoStatusEvent::Pending,
+                    aocl_leaf_index: None,
+                    spent: UtxoStatusEvent::None,
+                    amount: incoming_utxo.get_native_currency_amount(),
+                    release_date: incoming_utxo.release_date(),
+                });
+            }
+        }
 
-        let cur_block = state.chain.light_state();
-        let tip_height = cur_block.header().height;
-        let tip_hash = cur_block.hash();
-        let tip_mutator_set = cur_block
-            .mutator_set_accumulator_after()
-            .expect("Tip must have valid MSA after");
+        // get expected UTXOs
+        for expected_utxo in state.wallet_state.wallet_db.all_expected_utxos().await {
+            if present_addition_records.insert(expected_utxo.addition_record) {
+                ui_utxos.push(UiUtxo {
+                    received: UtxoStatusEvent::Expected,
+                    aocl_leaf_index: None,
+                    spent: UtxoStatusEvent::None,
+                    amount: expected_utxo.utxo.get_native_currency_amount(),
+                    release_date: expected_utxo.utxo.release_date(),
+                });
+            }
+        }
 
-        Ok(ResponseMsMembershipProofPrivacyPreserving {
-            tip_height,
-            tip_hash,
-            membership_proofs,
-            tip_mutator_set,
-        })
+        // mark "spent" label on unconfirmed outgoing UTXOs as "pending"
+        let mut markable_indices = HashSet::new();
+        for (_outgoing_utxo, aocl_leaf_index) in state.wallet_state.mempool_spent_utxos_iter() {
+            markable_indices.insert(aocl_leaf_index);
+        }
+        for ui_utxo in &mut ui_utxos {
+            if let Some(aocl_leaf_index) = ui_utxo.aocl_leaf_index {
+                if markable_indices.contains(&aocl_leaf_index) {
+                    ui_utxo.spent = UtxoStatusEvent::Pending;
+                }
+            }
+        }
+
+        Ok(ui_utxos)
     }
 
     // documented in trait. do not add doc-comment.
-    async fn announcements_in_block(
+    async fn cpu_temp(
         self,
         _context: tarpc::context::Context,
         token: auth::Token,
-        block_selector: BlockSelector,
-    ) -> RpcResult<Option<Vec<Announcement>>> {
+    ) -> RpcResult<Option<f32>> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let state = self.state.lock_guard().await;
-        let Some(digest) = block_selector.as_digest(&state).await else {
-            return Ok(None);
-        };
-        let archival_state = state.chain.archival_state();
-        let Some(block) = archival_state.get_block(digest).await.unwrap() else {
-            return Ok(None);
-        };
-
-        Ok(Some(block.body().transaction_kernel.announcements.clone()))
+        Ok(Self::cpu_temp_inner())
     }
 
     // documented in trait. do not add doc-comment.
-    async fn block_digests_by_height(
+    async fn node_health(
         self,
-        _: context::Context,
+        _context: tarpc::context::Context,
         token: auth::Token,
-        height: BlockHeight,
-    ) -> RpcResult<Vec<Digest>> {
+    ) -> RpcResult<NodeHealth> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        Ok(self
-            .state
-            .lock_guard()
-            .await
-            .chain
-            .archival_state()
-            .block_height_to_block_digests(height)
-            .await)
+        Ok(NodeHealth::from(&*self.state.lock_guard().await))
     }
 
     // documented in trait. do not add doc-comment.
-    async fn latest_tip_digests(
+    async fn fork_readiness(
         self,
         _context: tarpc::context::Context,
         token: auth::Token,
-        n: usize,
-    ) -> RpcResult<Vec<Digest>> {
-        log_slow_scope!(fn_name!());
-        token.auth(&self.valid_tokens)?;
-
-        let state = self.state.lock_guard().await;
-
-        let latest_block_digest = state.chain.light_state().hash();
-
-        Ok(state
-            .chain
-            .archival_state()
-            .get_ancestor_block_digests(latest_block_digest, n)
-            .await)
-    }
-
-    // documented in trait. do not add doc-comment.
-    async fn peer_info(self, _: context::Context, token: auth::Token) -> RpcResult<Vec<PeerInfo>> {
+    ) -> RpcResult<ForkReadinessReport> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        Ok(self
-            .state
-            .lock_guard()
-            .await
-            .net
-            .peer_map
-            .values()
-            .cloned()
-            .collect())
+        Ok(ForkReadinessReport::collect(
+            &*self.state.lock_guard().await,
+            SystemTime::now(),
+        ))
     }
 
     // documented in trait. do not add doc-comment.
-    async fn all_punished_peers(
+    async fn effective_config(
         self,
         _context: tarpc::context::Context,
         token: auth::Token,
-    ) -> RpcResult<HashMap<IpAddr, PeerStanding>> {
+    ) -> RpcResult<Option<String>> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let mut sanctions_in_memory = HashMap::default();
-
-        let global_state = self.state.lock_guard().await;
-
-        // Get all connected peers
-        for (socket_address, peer_info) in &global_state.net.peer_map {
-            if peer_info.standing().is_negative() {
-                sanctions_in_memory.insert(socket_address.ip(), peer_info.standing());
-            }
-        }
-
-        let sanctions_in_db = global_state.net.all_peer_sanctions_in_database();
-
-        // Combine result for currently connected peers and previously connected peers but
-        // use result for currently connected peer if there is an overlap
-        let mut all_sanctions = sanctions_in_memory;
-        for (ip_addr, sanction) in sanctions_in_db {
-            if sanction.is_negative() {
-                all_sanctions.entry(ip_addr).or_insert(sanction);
-            }
-        }
-
-        Ok(all_sanctions)
+        Ok(self.state.cli().effective_config.clone())
     }
 
     // documented in trait. do not add doc-comment.
-    async fn validate_address(
+    async fn diagnostics(
         self,
-        _ctx: context::Context,
+        _context: tarpc::context::Context,
         token: auth::Token,
-        address_string: String,
-        network: Network,
-    ) -> RpcResult<Option<ReceivingAddress>> {
+        include_addresses: bool,
+    ) -> RpcResult<DiagnosticsBundle> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let ret = ReceivingAddress::from_bech32m(&address_string, network).ok();
-        tracing::debug!(
-            "Responding to address validation request of {address_string}: {}",
-            ret.is_some()
-        );
-        Ok(ret)
+        Ok(DiagnosticsBundle::collect(
+            &*self.state.lock_guard().await,
+            include_addresses,
+        ))
     }
 
     // documented in trait. do not add doc-comment.
-    async fn validate_amount(
+    async fn rpc_request_metrics(
         self,
-        _ctx: context::Context,
+        _context: tarpc::context::Context,
         token: auth::Token,
-        amount_string: String,
-    ) -> RpcResult<Option<NativeCurrencyAmount>> {
+    ) -> RpcResult<RpcRequestMetrics> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        // parse string
-        if let Ok(amt) = NativeCurrencyAmount::coins_from_str(&amount_string) {
-            Ok(Some(amt))
-        } else {
-            Ok(None)
-        }
+        Ok(self.request_limiter.metrics())
     }
 
     // documented in trait. do not add doc-comment.
-    async fn amount_leq_confirmed_available_balance(
+    async fn block_validation_cache_metrics(
         self,
-        _ctx: context::Context,
+        _context: tarpc::context::Context,
         token: auth::Token,
-        amount: NativeCurrencyAmount,
-    ) -> RpcResult<bool> {
+    ) -> RpcResult<BlockValidationCacheMetrics> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let gs = self.state.lock_guard().await;
-        let wallet_status = gs.get_wallet_status_for_tip().await;
-
-        let confirmed_available = wallet_status.available_confirmed(Timestamp::now());
-
-        // test inequality
-        Ok(amount <= confirmed_available)
+        Ok(self
+            .state
+            .lock_guard()
+            .await
+            .block_validation_cache
+            .metrics())
     }
 
     // documented in trait. do not add doc-comment.
-    async fn confirmed_available_balance(
+    async fn mutator_set_statistics(
         self,
         _context: tarpc::context::Context,
         token: auth::Token,
-    ) -> RpcResult<NativeCurrencyAmount> {
+    ) -> RpcResult<MutatorSetStatistics> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let gs = self.state.lock_guard().await;
-        let wallet_status = gs.get_wallet_status_for_tip().await;
-
-        let confirmed_available = wallet_status.available_confirmed(Timestamp::now());
-
-        Ok(confirmed_available)
+        self.state
+            .lock_guard()
+            .await
+            .mutator_set_statistics()
+            .map_err(|e| RpcError::Failed(e.to_string()))
     }
 
     // documented in trait. do not add doc-comment.
-    async fn unconfirmed_available_balance(
+    async fn checkpoints(
         self,
         _context: tarpc::context::Context,
         token: auth::Token,
-    ) -> RpcResult<NativeCurrencyAmount> {
+    ) -> RpcResult<Vec<SignedCheckpoint>> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let gs = self.state.lock_guard().await;
-        let wallet_status = gs.get_wallet_status_for_tip().await;
-
-        Ok(gs
-            .wallet_state
-            .unconfirmed_available_balance(&wallet_status, Timestamp::now()))
+        Ok(self.state.lock_guard().await.checkpoints.all())
     }
 
     // documented in trait. do not add doc-comment.
-    async fn wallet_status(
+    async fn update_status(
         self,
         _context: tarpc::context::Context,
         token: auth::Token,
-    ) -> RpcResult<WalletStatus> {
+    ) -> RpcResult<UpdateStatus> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        Ok(self
-            .state
-            .lock_guard()
-            .await
-            .get_wallet_status_for_tip()
-            .await)
+        Ok(self.state.lock_guard().await.update_status.clone())
     }
 
-    async fn num_expected_utxos(
+    // documented in trait. do not add doc-comment.
+    async fn replication_status(
         self,
         _context: tarpc::context::Context,
         token: auth::Token,
-    ) -> RpcResult<u64> {
+    ) -> RpcResult<ReplicationStatus> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        Ok(self
-            .state
-            .lock_guard()
-            .await
-            .wallet_state
-            .num_expected_utxos()
-            .await)
+        Ok(self.state.lock_guard().await.replication.clone())
     }
 
     // documented in trait. do not add doc-comment.
-    async fn header(
+    async fn fork_pruning_status(
         self,
         _context: tarpc::context::Context,
         token: auth::Token,
-        block_selector: BlockSelector,
-    ) -> RpcResult<Option<BlockHeader>> {
+    ) -> RpcResult<ForkPruningStatus> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
         let state = self.state.lock_guard().await;
-        let Some(block_digest) = block_selector.as_digest(&state).await else {
-            return Ok(None);
+        let depth = state
+            .cli()
+            .fork_pruning_depth
+            .filter(|_| state.chain.is_archival_node())
+            .map(|depth| depth.get());
+        let record = if state.chain.is_archival_node() {
+            state.chain.archival_state().fork_pruning_record().await
+        } else {
+            ForkPruningRecord::default()
         };
-        Ok(state
-            .chain
-            .archival_state()
-            .get_block_header(block_digest)
-            .await)
+
+        Ok(ForkPruningStatus::new(depth, record))
     }
 
     // documented in trait. do not add doc-comment.
-    async fn next_receiving_address(
-        mut self,
+    async fn watch(
+        self,
         _context: tarpc::context::Context,
         token: auth::Token,
-        key_type: KeyType,
-    ) -> RpcResult<ReceivingAddress> {
+        item: WatchItem,
+        label: String,
+    ) -> RpcResult<BFieldElement> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
+        let network = self.state.cli().network;
         Ok(self
             .state
-            .api_mut()
-            .wallet_mut()
-            .next_receiving_address(key_type)
-            .await?)
+            .lock_guard_mut()
+            .await
+            .watch_list
+            .watch(&item, label, network)?)
     }
 
     // documented in trait. do not add doc-comment.
-    async fn known_keys(
+    async fn unwatch(
         self,
         _context: tarpc::context::Context,
         token: auth::Token,
-    ) -> RpcResult<Vec<SpendingKey>> {
+        receiver_identifier: BFieldElement,
+    ) -> RpcResult<bool> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
         Ok(self
             .state
-            .lock_guard()
+            .lock_guard_mut()
             .await
-            .wallet_state
-            .get_all_known_spending_keys()
-            .collect())
+            .watch_list
+            .unwatch(receiver_identifier))
     }
 
     // documented in trait. do not add doc-comment.
-    async fn known_keys_by_keytype(
+    async fn watched_items(
         self,
         _context: tarpc::context::Context,
         token: auth::Token,
-        key_type: KeyType,
-    ) -> RpcResult<Vec<SpendingKey>> {
+    ) -> RpcResult<Vec<WatchEntry>> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        Ok(self
-            .state
-            .lock_guard()
-            .await
-            .wallet_state
-            .get_known_spending_keys(key_type)
-            .collect())
+        Ok(self.state.lock_guard().await.watch_list.entries())
     }
 
     // documented in trait. do not add doc-comment.
-    async fn mempool_tx_count(
+    async fn watch_matches(
         self,
         _context: tarpc::context::Context,
         token: auth::Token,
-    ) -> RpcResult<usize> {
+        from_height: BlockHeight,
+    ) -> RpcResult<Vec<WatchMatch>> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        Ok(self.state.lock_guard().await.mempool.len())
+        Ok(self
+            .state
+            .lock_guard()
+            .await
+            .watch_list
+            .matches(from_height))
     }
 
     // documented in trait. do not add doc-comment.
-    async fn mempool_size(
+    async fn replay_events(
         self,
         _context: tarpc::context::Context,
         token: auth::Token,
-    ) -> RpcResult<usize> {
+        subscriber: String,
+        since: u64,
+    ) -> RpcResult<Vec<SequencedEvent>> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        Ok(self.state.lock_guard().await.mempool.get_size())
+        Ok(self.state.replay_events(&subscriber, since).await)
     }
 
-    async fn mempool_tx_ids(
+    // documented in trait. do not add doc-comment.
+    async fn acknowledge_events(
         self,
-        _context: ::tarpc::context::Context,
+        _context: tarpc::context::Context,
         token: auth::Token,
-    ) -> RpcResult<Vec<TransactionKernelId>> {
+        subscriber: String,
+        sequence_number: u64,
+    ) -> RpcResult<()> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
-        let txids: Vec<_> = self
-            .state
-            .lock_guard()
-            .await
-            .mempool
-            .fee_density_iter()
-            .map(|(kernel_id, _)| kernel_id)
-            .collect();
 
-        Ok(txids)
+        Ok(self
+            .state
+            .acknowledge_events(&subscriber, sequence_number)
+            .await?)
     }
 
     // documented in trait. do not add doc-comment.
-    async fn history(
+    async fn channel_metrics(
         self,
         _context: tarpc::context::Context,
         token: auth::Token,
-    ) -> RpcResult<Vec<(Digest, BlockHeight, Timestamp, NativeCurrencyAmount)>> {
+    ) -> RpcResult<Vec<ChannelMetrics>> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let history = self.state.lock_guard().await.get_balance_history().await;
-
-        // sort
-        let mut display_history: Vec<(Digest, BlockHeight, Timestamp, NativeCurrencyAmount)> =
-            history
-                .iter()
-                .map(|(h, t, bh, a)| (*h, *bh, *t, *a))
-                .collect::<Vec<_>>();
-        display_history.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
-
-        // return
-        Ok(display_history)
+        Ok(self.state.lock_guard().await.channel_metrics.metrics())
     }
 
     // documented in trait. do not add doc-comment.
-    async fn dashboard_overview_data(
+    async fn db_read_cache_metrics(
         self,
         _context: tarpc::context::Context,
         token: auth::Token,
-    ) -> RpcResult<OverviewData> {
+    ) -> RpcResult<Vec<TableReadCacheMetrics>> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let now = Timestamp::now();
         let state = self.state.lock_guard().await;
-        let tip_digest = {
-            log_slow_scope!(fn_name!() + "::hash() tip digest");
-            state.chain.light_state().hash()
-        };
-        let tip_header = *state.chain.light_state().header();
-        let syncing = state.net.sync_anchor.is_some();
-        let mempool_size = {
-            log_slow_scope!(fn_name!() + "::mempool.get_size()");
-            state.mempool.get_size()
-        };
-        let mempool_total_tx_count = {
-            log_slow_scope!(fn_name!() + "::mempool.len()");
-            state.mempool.len()
-        };
-        let mempool_own_tx_count = {
-            log_slow_scope!(fn_name!() + "::mempool.num_own_txs()");
-            state.mempool.num_own_txs()
-        };
-        let cpu_temp = None; // disable for now.  call is too slow.
-        let proving_capability = self.state.cli().proving_capability();
-
-        let peer_count = Some(state.net.peer_map.len());
-        let max_num_peers = self.state.cli().max_num_peers;
-
-        let mining_status = Some(state.mining_state.mining_status);
-
-        let confirmations = {
-            log_slow_scope!(fn_name!() + "::confirmations_internal()");
-            self.confirmations_internal(&state).await
-        };
-
-        let wallet_status = {
-            log_slow_scope!(fn_name!() + "::get_wallet_status_for_tip()");
-            state.get_wallet_status_for_tip().await
-        };
-        let wallet_state = &state.wallet_state;
-
-        let confirmed_available_balance = {
-            log_slow_scope!(fn_name!() + "::confirmed_available_balance()");
-            wallet_status.available_confirmed(now)
-        };
-        let confirmed_total_balance = {
-            log_slow_scope!(fn_name!() + "::confirmed_total_balance()");
-            wallet_status.total_confirmed()
-        };
-
-        let unconfirmed_available_balance = {
-            log_slow_scope!(fn_name!() + "::unconfirmed_available_balance()");
-            wallet_state.unconfirmed_available_balance(&wallet_status, now)
-        };
-        let unconfirmed_total_balance = {
-            log_slow_scope!(fn_name!() + "::unconfirmed_total_balance()");
-            wallet_state.unconfirmed_total_balance(&wallet_status)
-        };
+        if !state.chain.is_archival_node() {
+            return Ok(vec![]);
+        }
 
-        Ok(OverviewData {
-            tip_digest,
-            tip_header,
-            syncing,
-            confirmed_available_balance,
-            confirmed_total_balance,
-            unconfirmed_available_balance,
-            unconfirmed_total_balance,
-            mempool_size,
-            mempool_total_tx_count,
-            mempool_own_tx_count,
-            peer_count,
-            max_num_peers,
-            mining_status,
-            proving_capability,
-            confirmations,
-            cpu_temp,
-        })
+        Ok(state.chain.archival_state().read_cache_metrics())
     }
 
-    /******** CHANGE THINGS ********/
-    // Locking:
-    //   * acquires `global_state_lock` for write
-    //
     // documented in trait. do not add doc-comment.
-    async fn clear_all_standings(
-        mut self,
-        _: context::Context,
+    async fn bandwidth(
+        self,
+        _context: tarpc::context::Context,
         token: auth::Token,
-    ) -> RpcResult<()> {
+    ) -> RpcResult<BandwidthReport> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let mut global_state_mut = self.state.lock_guard_mut().await;
-        global_state_mut
-            .net
-            .peer_map
-            .iter_mut()
-            .for_each(|(_, peerinfo)| {
-                peerinfo.standing.clear_standing();
-            });
+        Ok(self.state.lock_guard().await.bandwidth.report())
+    }
 
-        // iterates and modifies standing field for all connected peers
-        global_state_mut.net.clear_all_standings_in_database().await;
+    // documented in trait. do not add doc-comment.
+    async fn connection_lifecycle(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<ConnectionLifecycleMetrics> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
 
-        Ok(global_state_mut.flush_databases().await?)
+        Ok(self
+            .state
+            .lock_guard()
+            .await
+            .connection_lifecycles
+            .metrics())
     }
 
-    // Locking:
-    //   * acquires `global_state_lock` for write
-    //
     // documented in trait. do not add doc-comment.
-    async fn clear_standing_by_ip(
-        mut self,
-        _: context::Context,
+    async fn main_loop_stats(
+        self,
+        _context: tarpc::context::Context,
         token: auth::Token,
-        ip: IpAddr,
-    ) -> RpcResult<()> {
+    ) -> RpcResult<MainLoopStats> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let mut global_state_mut = self.state.lock_guard_mut().await;
-        global_state_mut
-            .net
-            .peer_map
-            .iter_mut()
-            .for_each(|(socketaddr, peerinfo)| {
-                if socketaddr.ip() == ip {
-                    peerinfo.standing.clear_standing();
-                }
-            });
+        Ok(self.state.lock_guard().await.message_latencies.stats())
+    }
 
-        //Also clears this IP's standing in database, whether it is connected or not.
-        global_state_mut.net.clear_ip_standing_in_database(ip).await;
+    // documented in trait. do not add doc-comment.
+    async fn clean_data_directory(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<CleanupReport> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
 
-        Ok(global_state_mut.flush_databases().await?)
+        self.state
+            .clean_data_directory()
+            .await
+            .map_err(|e| RpcError::Failed(e.to_string()))
     }
 
     // documented in trait. do not add doc-comment.
-    async fn record_and_broadcast_transaction(
-        mut self,
-        _: context::Context,
+    async fn export_chain_data(
+        self,
+        _context: tarpc::context::Context,
         token: auth::Token,
-        tx_artifacts: TxCreationArtifacts,
-    ) -> RpcResult<()> {
+        first_height: BlockHeight,
+        last_height: BlockHeight,
+    ) -> RpcResult<ChainExportReport> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
         Ok(self
             .state
-            .api_mut()
-            .tx_initiator_mut()
-            .record_and_broadcast_transaction(&tx_artifacts)
+            .api()
+            .chain()
+            .export_csv(first_height..=last_height)
             .await?)
     }
 
     // documented in trait. do not add doc-comment.
-    async fn send(
-        mut self,
-        _ctx: context::Context,
+    async fn historical_balance(
+        self,
+        _context: tarpc::context::Context,
         token: auth::Token,
-        outputs: Vec<OutputFormat>,
-        change_policy: ChangePolicy,
-        fee: NativeCurrencyAmount,
-    ) -> RpcResult<TxCreationArtifacts> {
+        height: BlockHeight,
+    ) -> RpcResult<HistoricalBalance> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        Ok(self
-            .state
-            .api_mut()
-            .tx_sender_mut()
-            .send(outputs, change_policy, fee, Timestamp::now())
-            .await?)
+        Ok(self.state.api().wallet().balance_at_height(height).await?)
     }
 
-    // documented in trait. do not add doc-commtn.
-    async fn send_transparent(
-        mut self,
-        _ctx: context::Context,
+    // documented in trait. do not add doc-comment.
+    async fn utxo_memos(
+        self,
+        _context: tarpc::context::Context,
         token: auth::Token,
-        outputs: Vec<OutputFormat>,
-        change_policy: ChangePolicy,
-        fee: NativeCurrencyAmount,
-    ) -> RpcResult<TxCreationArtifacts> {
+    ) -> RpcResult<Vec<UtxoMemos>> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        Ok(self
-            .state
-            .api_mut()
-            .tx_initiator_mut()
-            .send_transparent(outputs, change_policy, fee, Timestamp::now())
-            .await?)
+        Ok(self.state.api().wallet().utxo_memos().await)
     }
 
-    async fn upgrade(
-        mut self,
-        _ctx: context::Context,
+    // documented in trait. do not add doc-comment.
+    async fn derivation_vectors(
+        self,
+        _context: tarpc::context::Context,
         token: auth::Token,
-        tx_kernel_id: TransactionKernelId,
-    ) -> RpcResult<bool> {
+        phrase: Option<Vec<String>>,
+        num_indices: u64,
+    ) -> RpcResult<DerivationVectors> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        // Does transaction exist and is it in need of upgrading?
-        let Some((tx, upgrade_priority)) = self
-            .state
-            .lock_guard()
+        let entropy = match phrase {
+            Some(phrase) => WalletEntropy::from_phrase(&phrase)
+                .map_err(|e| RpcError::WalletError(e.to_string()))?,
+            None => WalletEntropy::devnet_wallet(),
+        };
+        let network = self.state.cli().network;
+
+        // deriving lattice keys takes a while; do not block the runtime.
+        tokio::task::spawn_blocking(move || {
+            DerivationVectors::derive(&entropy, network, num_indices)
+        })
+        .await
+        .map_err(|e| RpcError::Failed(e.to_string()))?
+        .map_err(|e| RpcError::WalletError(e.to_string()))
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn chain_work(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+        block_selector: BlockSelector,
+    ) -> RpcResult<Option<ChainWork>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        // release the lock before calling the api, which acquires it.
+        let Some(digest) = block_selector
+            .as_digest(&*self.state.lock_guard().await)
             .await
-            .mempool
-            .get_with_priority(tx_kernel_id)
-            .map(|(x, y)| (x.to_owned(), y))
         else {
-            return Ok(false);
+            return Ok(None);
         };
 
-        let current_msa = self
-            .state
-            .lock_guard()
-            .await
-            .chain
-            .light_state()
-            .mutator_set_accumulator_after()
-            .expect("mutator set of tip must exist");
-        let is_synced = tx.kernel.mutator_set_hash == current_msa.hash();
+        match self.state.api().chain().chain_work(digest).await {
+            Ok(work) => Ok(Some(work)),
+            Err(ChainError::UnknownBlock(_)) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
 
-        let upgrade_job = match (&tx.proof, is_synced) {
-            (TransactionProof::SingleProof(_), true) => return Ok(false),
-            (TransactionProof::SingleProof(neptune_proof), false) => {
-                let gobbling_potential = NativeCurrencyAmount::zero();
-                let update_job = self
-                    .state
-                    .lock_guard_mut()
-                    .await
-                    .update_single_proof_job(
-                        tx.kernel,
-                        neptune_proof.to_owned(),
-                        upgrade_priority.incentive_given_gobble_potential(gobbling_potential),
-                    )
-                    .await?;
-                UpgradeJob::UpdateMutatorSetData(update_job)
-            }
-            (TransactionProof::ProofCollection(proof_collection), _) => {
-                // It doesn't matter if the proof collection is updated or not,
-                // since the later call to upgrade the transaction handles the
-                // case of unsynced single proof backed transactions.
-                let gobbling_potential = NativeCurrencyAmount::zero();
-                let raise_job = self
-                    .state
-                    .lock_guard_mut()
-                    .await
-                    .upgrade_proof_collection_job(
-                        tx.kernel,
-                        proof_collection.to_owned(),
-                        upgrade_priority.incentive_given_gobble_potential(gobbling_potential),
-                    )
-                    .await?;
-                UpgradeJob::ProofCollectionToSingleProof(raise_job)
-            }
+    // documented in trait. do not add doc-comment.
+    async fn fork_choice_audit(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+        depth: u64,
+    ) -> RpcResult<ForkChoiceAudit> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
 
-            // This implementation is not done because local transaction initiation
-            // should always produce proof collections or single proofs, and
-            // primitive witnesses may never be shared on the network. So it seems
-            // there is no use case for implementing this.
-            (TransactionProof::Witness(_), _) => {
-                error!("Can't upgrade primitive witnesses through this command.");
-                return Ok(false);
-            }
+        Ok(self.state.api().chain().fork_choice_audit(depth).await?)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn mining_status(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+    ) -> RpcResult<MiningStatusReport> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let state = self.state.lock_guard().await;
+        let status = state.mining_state.mining_status;
+        let guessing = match status {
+            MiningStatus::Guessing(_) => state
+                .mining_state
+                .guesser_statistics
+                .as_ref()
+                .map(|statistics| statistics.report()),
+            _ => None,
         };
+        let reorg_cooldown = state
+            .mining_state
+            .reorg_cooldown
+            .status(std::time::Instant::now());
 
-        let _ = self
-            .rpc_server_to_main_tx
-            .send(RPCServerToMain::PerformTxProofUpgrade(Box::new(
-                upgrade_job,
-            )))
-            .await;
+        let composer_standby = state.mining_state.composer_standby;
 
-        Ok(true)
+        Ok(MiningStatusReport {
+            status,
+            guessing,
+            reorg_cooldown,
+            composer_standby,
+        })
     }
 
-    // // documented in trait. do not add doc-comment.
-    async fn claim_utxo(
-        mut self,
-        _ctx: context::Context,
+    // documented in trait. do not add doc-comment.
+    async fn pow_puzzle_internal_key(
+        self,
+        _context: tarpc::context::Context,
         token: auth::Token,
-        encrypted_utxo_notification: String,
-        max_search_depth: Option<u64>,
-    ) -> RpcResult<bool> {
+    ) -> RpcResult<Option<ProofOfWorkPuzzle>> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let claim_data = self
-            .claim_utxo_inner(encrypted_utxo_notification, max_search_depth)
-            .await?;
-
-        let Some(claim_data) = claim_data else {
-            // UTXO has already been claimed by wallet
-            warn!("UTXO notification of amount was already received. Not adding again.");
-            return Ok(false);
+        let Some(proposal) = self
+            .state
+            .lock_guard()
+            .await
+            .mining_state
+            .block_proposal
+            .map(|x| x.to_owned())
+        else {
+            return Ok(None);
         };
 
-        let expected_utxo_was_new = !claim_data.has_expected_utxo;
-        self.state
-            .lock_guard_mut()
+        let guesser_key = self
+            .state
+            .lock_guard()
             .await
             .wallet_state
-            .claim_utxo(claim_data)
-            .await
-            .map_err(error::ClaimError::from)?;
+            .wallet_entropy
+            .guesser_fee_key();
 
-        Ok(expected_utxo_was_new)
+        self.pow_puzzle_inner(guesser_key.to_address().into(), proposal)
+            .await
     }
 
     // documented in trait. do not add doc-comment.
-    async fn shutdown(self, _: context::Context, token: auth::Token) -> RpcResult<bool> {
+    async fn pow_puzzle_external_key(
+        self,
+        _context: tarpc::context::Context,
+        token: auth::Token,
+        guesser_fee_address: ReceivingAddress,
+    ) -> RpcResult<Option<ProofOfWorkPuzzle>> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        // 1. Send shutdown message to main
-        let response = self
-            .rpc_server_to_main_tx
-            .send(RPCServerToMain::Shutdown)
-            .await;
+        let Some(proposal) = self
+            .state
+            .lock_guard()
+            .await
+            .mining_state
+            .block_proposal
+            .map(|x| x.to_owned())
+        else {
+            return Ok(None);
+        };
 
-        // 2. Send acknowledgement to client.
-        Ok(response.is_ok())
+        self.pow_puzzle_inner(guesser_fee_address, proposal).await
     }
 
     // documented in trait. do not add doc-comment.
-    async fn clear_mempool(
+    async fn full_pow_puzzle_external_key(
         self,
         _context: tarpc::context::Context,
         token: auth::Token,
-    ) -> RpcResult<()> {
+        guesser_fee_address: ReceivingAddress,
+    ) -> RpcResult<Option<(Block, ProofOfWorkPuzzle)>> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let _ = self
-            .rpc_server_to_main_tx
-            .send(RPCServerToMain::ClearMempool)
-            .await;
-        Ok(())
+        let (mut proposal, latest_block_header) = {
+            let global_state = self.state.lock_guard().await;
+            let Some(proposal) = global_state
+                .mining_state
+                .block_proposal
+                .map(|x| x.to_owned())
+            else {
+                return Ok(None);
+            };
+
+            let latest_block_header = *global_state.chain.light_state().header();
+            (proposal, latest_block_header)
+        };
+
+        proposal.set_header_guesser_address(guesser_fee_address);
+        let puzzle = ProofOfWorkPuzzle::new(proposal.clone(), latest_block_header.difficulty);
+
+        Ok(Some((proposal, puzzle)))
     }
 
     // documented in trait. do not add doc-comment.
-    async fn freeze(
-        mut self,
-        _context: tarpc::context::Context,
+    async fn spendable_inputs(
+        self,
+        _: context::Context,
         token: auth::Token,
-    ) -> RpcResult<()> {
+    ) -> RpcResult<TxInputList> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let mut state = self.state.lock_guard_mut().await;
-
-        if state.net.sync_anchor.is_some() {
-            error!("Cannot pause state updates when syncing.");
-            return Err(error::RpcError::CannotPauseWhileSyncing);
-        }
-
-        state.net.freeze = true;
-
-        Ok(())
+        Ok(self
+            .state
+            .api()
+            .tx_initiator()
+            .spendable_inputs(Timestamp::now())
+            .await)
     }
 
     // documented in trait. do not add doc-comment.
-    async fn unfreeze(
-        mut self,
-        _context: tarpc::context::Context,
+    async fn select_spendable_inputs(
+        self,
+        _: context::Context,
         token: auth::Token,
-    ) -> RpcResult<()> {
+        policy: InputSelectionPolicy,
+        spend_amount: NativeCurrencyAmount,
+    ) -> RpcResult<TxInputList> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
+        let spend_amount = ValidatedAmount::new(spend_amount)?.amount();
 
-        self.state.lock_mut(|state| state.net.freeze = false).await;
-
-        Ok(())
+        Ok(self
+            .state
+            .api()
+            .tx_initiator()
+            .select_spendable_inputs(policy, spend_amount, Timestamp::now())
+            .await
+            .into())
     }
 
-    async fn pause_miner(
+    // documented in trait. do not add doc-comment.
+    async fn generate_tx_outputs(
         self,
-        _context: tarpc::context::Context,
+        _: context::Context,
         token: auth::Token,
-    ) -> RpcResult<()> {
+        outputs: Vec<OutputFormat>,
+    ) -> RpcResult<TxOutputList> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        if self.state.cli().mine() {
-            let _ = self
-                .rpc_server_to_main_tx
-                .send(RPCServerToMain::PauseMiner)
-                .await;
-        } else {
-            info!("Cannot pause miner since it was never started");
-        }
-        Ok(())
+        Ok(self
+            .state
+            .api()
+            .tx_initiator()
+            .generate_tx_outputs(outputs)
+            .await)
     }
 
     // documented in trait. do not add doc-comment.
-    async fn restart_miner(
+    async fn generate_tx_details(
         self,
-        _context: tarpc::context::Context,
+        _: context::Context,
         token: auth::Token,
-    ) -> RpcResult<()> {
+        tx_inputs: TxInputList,
+        tx_outputs: TxOutputList,
+        change_policy: ChangePolicy,
+        fee: NativeCurrencyAmount,
+    ) -> RpcResult<TransactionDetails> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
+        ValidatedAmount::new(fee)?.checked_add(ValidatedAmount::checked_sum(
+            tx_outputs
+                .utxos_iter()
+                .into_iter()
+                .flat_map(|utxo| utxo.native_currency_amounts().collect_vec()),
+        )?)?;
+        self.require_spend_authorization(&fn_name!()).await?;
 
-        if self.state.cli().mine() {
-            let _ = self
-                .rpc_server_to_main_tx
-                .send(RPCServerToMain::RestartMiner)
-                .await;
-        } else {
-            info!("Cannot restart miner since it was never started");
-        }
-        Ok(())
+        Ok(self
+            .state
+            .api()
+            .tx_initiator()
+            .generate_tx_details(tx_inputs, tx_outputs, change_policy, fee)
+            .await?)
     }
 
     // documented in trait. do not add doc-comment.
-    async fn set_coinbase_distribution(
-        mut self,
-        _context: tarpc::context::Context,
+    async fn generate_witness_proof(
+        self,
+        _: context::Context,
         token: auth::Token,
-        coinbase_distribution_readable: Vec<CoinbaseOutputReadable>,
-    ) -> RpcResult<()> {
+        tx_details: TransactionDetails,
+    ) -> RpcResult<TransactionProof> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
+        self.require_spend_authorization(&fn_name!()).await?;
 
-        let network = self.state.cli().network;
-        let mut coinbase_distribution = vec![];
-        for output in coinbase_distribution_readable {
-            let output = match output.into_coinbase_output(network) {
-                Ok(cbo) => cbo,
-                Err(err) => return Err(RpcError::InvalidCoinbaseDistribution(err.to_string())),
-            };
-            coinbase_distribution.push(output);
-        }
-
-        let coinbase_distribution = match CoinbaseDistribution::try_new(coinbase_distribution) {
-            Ok(cd) => cd,
-            Err(err) => return Err(RpcError::InvalidCoinbaseDistribution(err.to_string())),
-        };
-
-        if !self.state.cli().compose {
-            warn!("Cannot set coinbase distribution as node is not composing");
-            return Err(RpcError::NotComposing);
-        }
-
-        let mut state = self.state.lock_guard_mut().await;
-        state
-            .mining_state
-            .set_coinbase_distribution(coinbase_distribution);
-
-        Ok(())
+        Ok(self
+            .state
+            .api()
+            .tx_initiator()
+            .generate_witness_proof(Arc::new(tx_details)))
     }
 
     // documented in trait. do not add doc-comment.
-    async fn unset_coinbase_distribution(
-        mut self,
-        _context: tarpc::context::Context,
+    async fn assemble_transaction(
+        self,
+        _: context::Context,
         token: auth::Token,
-    ) -> RpcResult<()> {
+        transaction_details: TransactionDetails,
+        transaction_proof: TransactionProof,
+    ) -> RpcResult<Transaction> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
+        self.require_spend_authorization(&fn_name!()).await?;
 
-        if self.state.cli().mine() {
-            let mut state = self.state.lock_guard_mut().await;
-            state.mining_state.unset_coinbase_distribution();
-        } else {
-            warn!("Cannot unset coinbase distribution as node is not mining");
-        }
-
-        Ok(())
+        Ok(self
+            .state
+            .api()
+            .tx_initiator()
+            .assemble_transaction(&transaction_details, transaction_proof)?)
     }
 
     // documented in trait. do not add doc-comment.
-    async fn mine_blocks_to_wallet(
-        mut self,
-        _context: tarpc::context::Context,
+    async fn assemble_transaction_artifacts(
+        self,
+        _: context::Context,
         token: auth::Token,
-        n_blocks: u32,
-    ) -> RpcResult<()> {
+        transaction_details: TransactionDetails,
+        transaction_proof: TransactionProof,
+    ) -> RpcResult<TxCreationArtifacts> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
+        self.require_spend_authorization(&fn_name!()).await?;
 
-        let include_mempool_txs = true;
         Ok(self
             .state
-            .api_mut()
-            .regtest_mut()
-            .mine_blocks_to_wallet(n_blocks, include_mempool_txs)
-            .await?)
+            .api()
+            .tx_initiator()
+            .assemble_transaction_artifacts(transaction_details, transaction_proof)?)
     }
 
     // documented in trait. do not add doc-comment.
-    async fn provide_pow_solution(
+    async fn proof_type(
+        self,
+        _ctx: context::Context,
+        token: auth::Token,
+        txid: TransactionKernelId,
+    ) -> RpcResult<TransactionProofType> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        Ok(self.state.api().tx_initiator().proof_type(txid).await?)
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn block_intervals(
         self,
         _context: tarpc::context::Context,
         token: auth::Token,
-        pow: BlockPow,
-        proposal_id: Digest,
-    ) -> RpcResult<bool> {
+        last_block: BlockSelector,
+        max_num_blocks: Option<usize>,
+    ) -> RpcResult<Option<Vec<(u64, u64)>>> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        // Find proposal from list of exported proposals.
-        let Some(proposal) = self
-            .state
-            .lock_guard()
-            .await
-            .mining_state
-            .exported_block_proposals
-            .get(&proposal_id)
-            .map(|x| x.to_owned())
-        else {
-            warn!(
-                "Got claimed PoW solution but no challenge was known. \
-                Did solution come in too late?"
-            );
-            return Ok(false);
+        let state = self.state.lock_guard().await;
+        let Some(last_block) = last_block.as_digest(&state).await else {
+            return Ok(None);
         };
+        let mut intervals = vec![];
+        let mut current = state
+            .chain
+            .archival_state()
+            .get_block_header(last_block)
+            .await
+            .expect("If digest can be found, block header should also be known");
+        let mut parent = state
+            .chain
+            .archival_state()
+            .get_block_header(current.prev_block_digest)
+            .await;
 
-        self.pow_solution_inner(proposal, pow).await
+        // Exclude genesis since it was not mined. So block interval 0-->1
+        // is not included.
+        while parent.is_some()
+            && !parent.unwrap().height.is_genesis()
+            && max_num_blocks.is_none_or(|max_num| max_num > intervals.len())
+        {
+            let parent_ = parent.unwrap();
+            let interval = current.timestamp.to_millis() - parent_.timestamp.to_millis();
+            let block_height: u64 = current.height.into();
+            intervals.push((block_height, interval));
+            current = parent_;
+            parent = state
+                .chain
+                .archival_state()
+                .get_block_header(current.prev_block_digest)
+                .await;
+        }
+
+        Ok(Some(intervals))
     }
 
-    // documented in trait. do not add doc-comment.
-    async fn provide_new_tip(
+    async fn block_difficulties(
         self,
         _context: tarpc::context::Context,
         token: auth::Token,
-        pow: BlockPow,
-        proposal: Block,
-    ) -> RpcResult<bool> {
+        last_block: BlockSelector,
+        max_num_blocks: Option<usize>,
+    ) -> RpcResult<Vec<(u64, Difficulty)>> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        // Since block comes from external source, we need to check validity.
-        let current_tip = self.state.lock_guard().await.chain.light_state().clone();
-        if !proposal
-            .is_valid(&current_tip, Timestamp::now(), self.state.cli().network)
-            .await
+        let state = self.state.lock_guard().await;
+        let last_block = last_block.as_digest(&state).await;
+        let Some(last_block) = last_block else {
+            return Ok(vec![]);
+        };
+
+        let mut difficulties = vec![];
+
+        let mut current = state
+            .chain
+            .archival_state()
+            .get_block_header(last_block)
+            .await;
+        while current.is_some()
+            && max_num_blocks.is_none_or(|max_num| max_num >= difficulties.len())
         {
-            warn!("Got claimed new block that was not valid");
-            return Ok(false);
+            let current_ = current.unwrap();
+            let height: u64 = current_.height.into();
+            difficulties.push((height, current_.difficulty));
+            current = state
+                .chain
+                .archival_state()
+                .get_block_header(current_.prev_block_digest)
+                .await;
         }
 
-        self.pow_solution_inner(proposal, pow).await
+        Ok(difficulties)
     }
 
     // documented in trait. do not add doc-comment.
-    async fn prune_abandoned_monitored_utxos(
-        mut self,
+    async fn broadcast_all_mempool_txs(
+        self,
         _context: tarpc::context::Context,
         token: auth::Token,
-    ) -> RpcResult<usize> {
-        const DEFAULT_MUTXO_PRUNE_DEPTH: usize = 200;
-
+    ) -> RpcResult<()> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let mut global_state_mut = self.state.lock_guard_mut().await;
-
-        let prune_count_res = global_state_mut
-            .prune_abandoned_monitored_utxos(DEFAULT_MUTXO_PRUNE_DEPTH)
+        // If this sending fails, it means `main_loop` is no longer running,
+        // and node is crashed. No reason to log anything additional.
+        let _ = self
+            .rpc_server_to_main_tx
+            .send(RPCServerToMain::BroadcastMempoolTransactions)
             .await;
 
-        global_state_mut
-            .flush_databases()
-            .await
-            .expect("flushed DBs");
-
-        match prune_count_res {
-            Ok(prune_count) => {
-                info!("Marked {prune_count} monitored UTXOs as abandoned");
-                Ok(prune_count)
-            }
-            Err(err) => {
-                error!("Pruning monitored UTXOs failed with error: {err}");
-                Ok(0)
-            }
-        }
+        Ok(())
     }
 
-    // Documented in trait. Do not add doc-comment.
-    async fn set_tip(
+    async fn broadcast_block_proposal(
         self,
         _context: tarpc::context::Context,
         token: auth::Token,
-        indicated_tip: Digest,
     ) -> RpcResult<()> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        // Set tip asynchronously -- avoid RPC timeout.
-        self.rpc_server_to_main_tx
-            .send(RPCServerToMain::SetTipToStoredBlock(indicated_tip))
-            .await
-            .map_err(|e| RpcError::Failed(format!("could not send message to main loop: {e}")))?;
+        let _ = self
+            .rpc_server_to_main_tx
+            .send(RPCServerToMain::BroadcastBlockProposal)
+            .await;
 
         Ok(())
     }
 
     // documented in trait. do not add doc-comment.
-    async fn list_own_coins(
-        self,
-        _context: ::tarpc::context::Context,
-        token: auth::Token,
-    ) -> RpcResult<Vec<CoinWithPossibleTimeLock>> {
-        log_slow_scope!(fn_name!());
-        token.auth(&self.valid_tokens)?;
-
-        let state = self.state.lock_guard().await;
-        let tip = state.chain.light_state();
-        let tip_hash = tip.hash();
-        let tip_msa = tip
-            .mutator_set_accumulator_after()
-            .expect("Block from state must have mutator set after");
-
-        Ok(state
-            .wallet_state
-            .get_all_own_coins_with_possible_timelocks(&tip_msa, tip_hash)
-            .await)
-    }
-
-    /// Get [`UiUtxo`] from three sources:
-    /// 1) the wallet database for monitored UTXOs (these are confirmed);
-    /// 2) the mempool (these are pending); and
-    /// 3) the wallet database for expected UTXOs (expected).
-    ///
-    /// The assembled list of [`UiUtxo`] is deduplicated based on addition record, which is
-    /// collected separately in a hash set. Note that this order is important because it
-    /// implicitly resolves duplicate conflicts. A duplicate [`UiUtxo`] cannot be inserted again,
-    /// so the first stage that applies determines the "received" label --
-    /// confirmed / pending / expected.
-    async fn list_utxos(
+    async fn mempool_overview(
         self,
         _context: ::tarpc::context::Context,
         token: auth::Token,
-    ) -> RpcResult<Vec<UiUtxo>> {
+        start_index: usize,
+        number: usize,
+    ) -> RpcResult<Vec<MempoolTransactionInfo>> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        // get owned UTXOs
-        let mut ui_utxos = vec![];
-        let mut present_addition_records = HashSet::new();
-        let state = self.state.lock_guard().await;
-        for monitored_utxo in state
-            .wallet_state
-            .wallet_db
-            .monitored_utxos()
-            .get_all()
-            .await
-        {
-            let received = UtxoStatusEvent::Confirmed {
-                block_height: monitored_utxo.confirmed_in_block.2,
-                timestamp: monitored_utxo.confirmed_in_block.1,
-            };
-            let spent = if let Some((_, timestamp, block_height)) = monitored_utxo.spent_in_block {
-                UtxoStatusEvent::Confirmed {
-                    block_height,
-                    timestamp,
-                }
-            } else {
-                UtxoStatusEvent::None
-            };
-
-            if present_addition_records.insert(monitored_utxo.addition_record()) {
-                ui_utxos.push(UiUtxo {
-                    received,
-                    spent,
-                    aocl_leaf_index: Some(monitored_utxo.aocl_leaf_index),
-                    amount: monitored_utxo.utxo.get_native_currency_amount(),
-                    release_date: monitored_utxo.utxo.release_date(),
-                });
-            }
-        }
+        let global_state = self.state.lock_guard().await;
+        let mempool_txkids = global_state
+            .mempool
+            .fee_density_iter()
+            .skip(start_index)
+            .take(number)
+            .map(|(txkid, _)| txkid)
+            .collect_vec();
 
-        // get unconfirmed incoming UTXOs
-        for (incoming_utxo, addition_record) in state.wallet_state.mempool_unspent_utxos_iter() {
-            if present_addition_records.insert(addition_record) {
-                ui_utxos.push(UiUtxo {
-                    received: UtxoStatusEvent::Pending,
-                    aocl_leaf_index: None,
-                    spent: UtxoStatusEvent::None,
-                    amount: incoming_utxo.get_native_currency_amount(),
-                    release_date: incoming_utxo.release_date(),
-                });
-            }
-        }
+        let (incoming, outgoing): (HashMap<_, _>, HashMap<_, _>) = {
+            let (incoming_iter, outgoing_iter) =
+                global_state.wallet_state.mempool_balance_updates();
+            (incoming_iter.collect(), outgoing_iter.collect())
+        };
 
-        // get expected UTXOs
-        for expected_utxo in state.wallet_state.wallet_db.all_expected_utxos().await {
-            if present_addition_records.insert(expected_utxo.addition_record) {
-                ui_utxos.push(UiUtxo {
-                    received: UtxoStatusEvent::Expected,
-                    aocl_leaf_index: None,
-                    spent: UtxoStatusEvent::None,
-                    amount: expected_utxo.utxo.get_native_currency_amount(),
-                    release_date: expected_utxo.utxo.release_date(),
-                });
-            }
-        }
+        let tip_msah = global_state
+            .chain
+            .light_state()
+            .mutator_set_accumulator_after()
+            .expect("Block from state must have mutator set after")
+            .hash();
 
-        // mark "spent" label on unconfirmed outgoing UTXOs as "pending"
-        let mut markable_indices = HashSet::new();
-        for (_outgoing_utxo, aocl_leaf_index) in state.wallet_state.mempool_spent_utxos_iter() {
-            markable_indices.insert(aocl_leaf_index);
-        }
-        for ui_utxo in &mut ui_utxos {
-            if let Some(aocl_leaf_index) = ui_utxo.aocl_leaf_index {
-                if markable_indices.contains(&aocl_leaf_index) {
-                    ui_utxo.spent = UtxoStatusEvent::Pending;
-                }
-            }
-        }
+        let mempool_transactions = mempool_txkids
+            .iter()
+            .filter_map(|id| {
+                let mut mptxi = global_state
+                    .mempool
+                    .get(*id)
+                    .map(|tx| (MempoolTransactionInfo::from(tx), tx.kernel.mutator_set_hash))
+                    .map(|(mptxi, tx_msah)| {
+                        if tx_msah == tip_msah {
+                            mptxi.synced()
+                        } else {
+                            mptxi
+                        }
+                    });
+                if mptxi.is_some() {
+                    if let Some(pos_effect) = incoming.get(id) {
+                        mptxi = Some(mptxi.unwrap().with_positive_effect_on_balance(*pos_effect));
+                    }
+                    if let Some(neg_effect) = outgoing.get(id) {
+                        mptxi = Some(mptxi.unwrap().with_negative_effect_on_balance(*neg_effect));
+                    }
+                }
 
-        Ok(ui_utxos)
+                mptxi
+            })
+            .collect_vec();
+
+        Ok(mempool_transactions)
     }
 
     // documented in trait. do not add doc-comment.
-    async fn cpu_temp(
+    async fn mempool_fee_summary(
         self,
-        _context: tarpc::context::Context,
+        _context: ::tarpc::context::Context,
         token: auth::Token,
-    ) -> RpcResult<Option<f32>> {
+        size_cutoffs: Vec<usize>,
+    ) -> RpcResult<MempoolFeeSummary> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        Ok(Self::cpu_temp_inner())
+        let global_state = self.state.lock_guard().await;
+        let next_height = global_state.chain.light_state().header().height.next();
+        let block_subsidy = Block::block_subsidy(next_height);
+        let guesser_fee = block_subsidy.lossy_f64_fraction_mul(global_state.cli().guesser_fraction);
+        let composer_reward = block_subsidy - guesser_fee;
+
+        Ok(global_state
+            .mempool
+            .fee_summary(&size_cutoffs, block_subsidy, composer_reward))
     }
 
     // documented in trait. do not add doc-comment.
-    async fn pow_puzzle_internal_key(
+    async fn mempool_diff(
         self,
-        _context: tarpc::context::Context,
+        _context: ::tarpc::context::Context,
         token: auth::Token,
-    ) -> RpcResult<Option<ProofOfWorkPuzzle>> {
+        since_token: Option<MempoolDiffToken>,
+    ) -> RpcResult<MempoolDiff> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let Some(proposal) = self
+        Ok(self
             .state
             .lock_guard()
             .await
-            .mining_state
-            .block_proposal
-            .map(|x| x.to_owned())
-        else {
-            return Ok(None);
-        };
+            .mempool
+            .diff_since(since_token))
+    }
 
-        let guesser_key = self
+    // documented in trait. do not add doc-comment.
+    async fn mempool_tx_kernel(
+        self,
+        _context: ::tarpc::context::Context,
+        token: auth::Token,
+        tx_kernel_id: TransactionKernelId,
+    ) -> RpcResult<Option<TransactionKernel>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        Ok(self
             .state
             .lock_guard()
             .await
-            .wallet_state
-            .wallet_entropy
-            .guesser_fee_key();
-
-        self.pow_puzzle_inner(guesser_key.to_address().into(), proposal)
-            .await
+            .mempool
+            .get(tx_kernel_id)
+            .map(|tx| &tx.kernel)
+            .cloned())
     }
 
     // documented in trait. do not add doc-comment.
-    async fn pow_puzzle_external_key(
+    async fn mempool_tx_is_replaceable(
         self,
-        _context: tarpc::context::Context,
+        _context: ::tarpc::context::Context,
         token: auth::Token,
-        guesser_fee_address: ReceivingAddress,
-    ) -> RpcResult<Option<ProofOfWorkPuzzle>> {
+        tx_kernel_id: TransactionKernelId,
+    ) -> RpcResult<bool> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let Some(proposal) = self
+        Ok(self
             .state
             .lock_guard()
             .await
-            .mining_state
-            .block_proposal
-            .map(|x| x.to_owned())
-        else {
-            return Ok(None);
+            .wallet_state
+            .mempool_transaction_is_replaceable(tx_kernel_id))
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn zero_conf_risk(
+        self,
+        _context: ::tarpc::context::Context,
+        token: auth::Token,
+        tx_kernel_id: TransactionKernelId,
+    ) -> RpcResult<Option<ZeroConfRisk>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let num_announcing_peers = self
+            .state
+            .transaction_announcements()
+            .num_announcers(tx_kernel_id);
+        let global_state = self.state.lock_guard().await;
+        let num_connected_peers = global_state.net.peer_map.len();
+
+        Ok(ZeroConfRiskFactors::new(
+            &global_state.mempool,
+            tx_kernel_id,
+            num_announcing_peers,
+            num_connected_peers,
+        )
+        .map(|factors| ZeroConfRisk::new(tx_kernel_id, factors)))
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn verify_transaction(
+        self,
+        _context: ::tarpc::context::Context,
+        token: auth::Token,
+        transaction: Transaction,
+    ) -> RpcResult<TransactionVerificationReport> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let network = self.state.cli().network;
+        let (tip, tip_mutator_set_accumulator, consensus_rule_set) = {
+            let state = self.state.lock_guard().await;
+            let tip = state.chain.light_state();
+            (
+                tip.hash(),
+                tip.mutator_set_accumulator_after()
+                    .expect("Block from state must have mutator set after"),
+                ConsensusRuleSet::infer_from(network, tip.header().height),
+            )
         };
 
-        self.pow_puzzle_inner(guesser_fee_address, proposal).await
+        Ok(TransactionVerificationReport::verify(
+            &transaction,
+            tip,
+            &tip_mutator_set_accumulator,
+            network,
+            consensus_rule_set,
+            Timestamp::now(),
+        )
+        .await)
     }
 
     // documented in trait. do not add doc-comment.
-    async fn full_pow_puzzle_external_key(
+    async fn test_mempool_accept(
         self,
-        _context: tarpc::context::Context,
+        _context: ::tarpc::context::Context,
         token: auth::Token,
-        guesser_fee_address: ReceivingAddress,
-    ) -> RpcResult<Option<(Block, ProofOfWorkPuzzle)>> {
+        transaction: Transaction,
+    ) -> RpcResult<MempoolAcceptanceReport> {
         log_slow_scope!(fn_name!());
         token.auth(&self.valid_tokens)?;
 
-        let (mut proposal, latest_block_header) = {
-            let global_state = self.state.lock_guard().await;
-            let Some(proposal) = global_state
-                .mining_state
-                .block_proposal
-                .map(|x| x.to_owned())
-            else {
-                return Ok(None);
-            };
+        let network = self.state.cli().network;
+        let (tip, tip_mutator_set_accumulator, consensus_rule_set) = {
+            let state = self.state.lock_guard().await;
+            let tip = state.chain.light_state();
+            (
+                tip.hash(),
+                tip.mutator_set_accumulator_after()
+                    .expect("Block from state must have mutator set after"),
+                ConsensusRuleSet::infer_from(network, tip.header().height),
+            )
+        };
 
-            let latest_block_header = *global_state.chain.light_state().header();
-            (proposal, latest_block_header)
+        let now = Timestamp::now();
+        let verification = TransactionVerificationReport::verify(
+            &transaction,
+            tip,
+            &tip_mutator_set_accumulator,
+            network,
+            consensus_rule_set,
+            now,
+        )
+        .await;
+
+        let state = self.state.lock_guard().await;
+        Ok(MempoolAcceptanceReport::assess(
+            verification,
+            &transaction,
+            &state.mempool,
+            &state.load_shedding,
+            self.state.cli(),
+            now,
+        ))
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn prove_utxo_ownership(
+        self,
+        _context: ::tarpc::context::Context,
+        token: auth::Token,
+        block_selector: BlockSelector,
+        aocl_leaf_indices: Vec<u64>,
+    ) -> RpcResult<Option<UtxoOwnershipProof>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        self.utxo_ownership_proof_inner(block_selector, &aocl_leaf_indices)
+            .await
+    }
+
+    // documented in trait. do not add doc-comment.
+    async fn proof_of_reserve(
+        self,
+        _context: ::tarpc::context::Context,
+        token: auth::Token,
+        block_selector: BlockSelector,
+    ) -> RpcResult<Option<SignedReserveReport>> {
+        log_slow_scope!(fn_name!());
+        token.auth(&self.valid_tokens)?;
+
+        let Some(ownership) = self.utxo_ownership_proof_inner(block_selector, &[]).await? else {
+            return Ok(None);
         };
+        let report = ReserveReport::new(self.state.cli().network, ownership, Timestamp::now())
+            .map_err(|error| RpcError::WalletError(error.to_string()))?;
 
-        proposal.set_header_guesser_address(guesser_fee_address);
-        let puzzle = ProofOfWorkPuzzle::new(proposal.clone(), latest_block_header.difficulty);
+        let mut lock_scripts_and_witnesses = HashMap::new();
+        {
+            let state = self.state.lock_guard().await;
+            for disclosed in &report.ownership.utxos {
+                let lock_script_hash = disclosed.utxo.lock_script_hash();
+                if lock_scripts_and_witnesses.contains_key(&lock_script_hash) {
+                    continue;
+                }
+                let Some(spending_key) = state
+                    .wallet_state
+                    .find_spending_key_for_utxo(&disclosed.utxo)
+                else {
+                    return Err(RpcError::WalletError(format!(
+                        "no spending key for lock script {lock_script_hash}"
+                    )));
+                };
+                lock_scripts_and_witnesses
+                    .insert(lock_script_hash, spending_key.lock_script_and_witness());
+            }
+        }
+
+        let proof_job_options = self
+            .state
+            .cli()
+            .proof_job_options(TritonVmJobPriority::Normal);
+        let lock_scripts_and_witnesses = lock_scripts_and_witnesses.into_values().collect();
+        SignedReserveReport::sign(
+            report,
+            lock_scripts_and_witnesses,
+            vm_job_queue(),
+            proof_job_options,
+        )
+        .await
+        .map(Some)
+        .map_err(|error| RpcError::Failed(error.to_string()))
+    }
+}
+
+pub mod error {
+    use super::*;
+
+    /// enumerates possible rpc api errors
+    #[derive(Debug, thiserror::Error, Serialize, Deserialize)]
+    #[non_exhaustive]
+    pub enum RpcError {
+        // auth error
+        #[error("auth error: {0}")]
+        Auth(#[from] auth::error::AuthError),
+
+        #[error("spending PIN error: {0}")]
+        SpendingPin(#[from] crate::application::rpc::spending_pin::error::SpendingPinError),
+
+        // catch-all error, eg for anyhow errors
+        #[error("rpc call failed: {0}")]
+        Failed(String),
+
+        // API specific error variants.
+        #[error("cookie hints are disabled on this node")]
+        CookieHintDisabled,
+
+        #[error("capacity to store exported block proposals exceeded")]
+        ExportedBlockProposalStorageCapacityExceeded,
+
+        /// transaction initiation failed. Clients can branch on the kind of
+        /// failure.
+        #[error("transaction initiation error: {0}")]
+        TxInitiation(tx_initiation::error::TxInitiationError),
+
+        #[error("upgrade proof error: {0}")]
+        UpgradeProofError(String),
+
+        #[error("cancel transaction error: {0}")]
+        CancelTxError(String),
+
+        #[error("regtest error: {0}")]
+        RegTestError(String),
+
+        #[error("wallet error: {0}")]
+        WalletError(String),
+
+        #[error("claim error: {0}")]
+        ClaimError(String),
+
+        #[error("Cannot pause state updates while client is syncing")]
+        CannotPauseWhileSyncing,
+
+        #[error("Invalid coinbase distribution: {0}")]
+        InvalidCoinbaseDistribution(String),
+
+        #[error("Node is not setup to compose")]
+        NotComposing,
+
+        #[error("Cannot restore membership proofs: {0}")]
+        CannotRestoreMembershipProofs(String),
+
+        #[error("Wallet key counter is zero. Must be positive after init")]
+        WalletKeyCounterIsZero,
+
+        #[error("batch of {0} calls exceeds the maximum of {MAX_RPC_BATCH_SIZE}")]
+        BatchTooLarge(usize),
+
+        #[error("batches cannot be nested")]
+        NestedBatch,
+
+        #[error("Node is not a standby")]
+        NotStandby,
+
+        #[error("Cannot demote primary: {0}")]
+        CannotDemotePrimary(String),
+
+        #[error("invalid amount: {0}")]
+        InvalidAmount(String),
+
+        #[error("{0} addresses exceed the maximum of {MAX_ADDRESSES_PER_VALIDATION}")]
+        TooManyAddresses(usize),
+
+        #[error("chain error: {0}")]
+        ChainError(String),
+    }
+
+    impl From<api::chain::error::ChainError> for RpcError {
+        fn from(err: api::chain::error::ChainError) -> Self {
+            RpcError::ChainError(err.to_string())
+        }
+    }
+
+    impl From<api::amount::error::AmountError> for RpcError {
+        fn from(err: api::amount::error::AmountError) -> Self {
+            RpcError::InvalidAmount(err.to_string())
+        }
+    }
+
+    impl From<tx_initiation::error::CreateTxError> for RpcError {
+        fn from(err: tx_initiation::error::CreateTxError) -> Self {
+            RpcError::TxInitiation((&err).into())
+        }
+    }
+
+    impl From<tx_initiation::error::UpgradeProofError> for RpcError {
+        fn from(err: tx_initiation::error::UpgradeProofError) -> Self {
+            RpcError::UpgradeProofError(err.to_string())
+        }
+    }
+
+    impl From<tx_initiation::error::SendError> for RpcError {
+        fn from(err: tx_initiation::error::SendError) -> Self {
+            RpcError::TxInitiation((&err).into())
+        }
+    }
+
+    impl From<tx_initiation::error::CancelTxError> for RpcError {
+        fn from(err: tx_initiation::error::CancelTxError) -> Self {
+            RpcError::CancelTxError(err.to_string())
+        }
+    }
+
+    impl From<api::regtest::error::RegTestError> for RpcError {
+        fn from(err: api::regtest::error::RegTestError) -> Self {
+            RpcError::RegTestError(err.to_string())
+        }
+    }
+
+    impl From<api::wallet::error::WalletError> for RpcError {
+        fn from(err: api::wallet::error::WalletError) -> Self {
+            RpcError::WalletError(err.to_string())
+        }
+    }
+
+    impl From<ClaimError> for RpcError {
+        fn from(err: ClaimError) -> Self {
+            RpcError::ClaimError(err.to_string())
+        }
+    }
 
-        Ok(Some((proposal, puzzle)))
+    // convert anyhow::Error to an RpcError::Failed.
+    // note that anyhow Error is not serializable.
+    impl From<anyhow::Error> for RpcError {
+        fn from(e: anyhow::Error) -> Self {
+            Self::Failed(e.to_string())
+        }
     }
 
-    // documented in trait. do not add doc-comment.
-    async fn spendable_inputs(
-        self,
-        _: context::Context,
-        token: auth::Token,
-    ) -> RpcResult<TxInputList> {
-        log_slow_scope!(fn_name!());
-        token.auth(&self.valid_tokens)?;
+    /// enumerates possible transaction send errors
+    #[derive(Debug, Clone, thiserror::Error, Serialize, Deserialize)]
+    #[non_exhaustive]
+    pub enum ClaimError {
+        #[error("utxo does not match any known wallet key")]
+        UtxoUnknown,
 
-        Ok(self
-            .state
-            .api()
-            .tx_initiator()
-            .spendable_inputs(Timestamp::now())
-            .await)
-    }
+        #[error("invalid type script in claim utxo")]
+        InvalidTypeScript,
 
-    // documented in trait. do not add doc-comment.
-    async fn select_spendable_inputs(
-        self,
-        _: context::Context,
-        token: auth::Token,
-        policy: InputSelectionPolicy,
-        spend_amount: NativeCurrencyAmount,
-    ) -> RpcResult<TxInputList> {
-        log_slow_scope!(fn_name!());
-        token.auth(&self.valid_tokens)?;
+        #[error("utxo was not mined in a canonical block within the search depth")]
+        NotMined,
 
-        Ok(self
-            .state
-            .api()
-            .tx_initiator()
-            .select_spendable_inputs(policy, spend_amount, Timestamp::now())
-            .await
-            .into())
+        // catch-all error, eg for anyhow errors
+        #[error("claim unsuccessful")]
+        Failed(String),
     }
 
-    // documented in trait. do not add doc-comment.
-    async fn generate_tx_outputs(
-        self,
-        _: context::Context,
-        token: auth::Token,
-        outputs: Vec<OutputFormat>,
-    ) -> RpcResult<TxOutputList> {
-        log_slow_scope!(fn_name!());
-        token.auth(&self.valid_tokens)?;
-
-        Ok(self
-            .state
-            .api()
-            .tx_initiator()
-            .generate_tx_outputs(outputs)
-            .await)
+    // convert anyhow::Error to a ClaimError::Failed.
+    // note that anyhow Error is not serializable.
+    impl From<anyhow::Error> for ClaimError {
+        fn from(e: anyhow::Error) -> Self {
+            Self::Failed(e.to_string())
+        }
     }
+}
 
-    // documented in trait. do not add doc-comment.
-    async fn generate_tx_details(
-        self,
-        _: context::Context,
-        token: auth::Token,
-        tx_inputs: TxInputList,
-        tx_outputs: TxOutputList,
-        change_policy: ChangePolicy,
-        fee: NativeCurrencyAmount,
-    ) -> RpcResult<TransactionDetails> {
-        log_slow_scope!(fn_name!());
-        token.auth(&self.valid_tokens)?;
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::num::NonZero;
 
-        Ok(self
-            .state
-            .api()
-            .tx_initiator()
-            .generate_tx_details(tx_inputs, tx_outputs, change_policy, fee)
-            .await?)
-    }
+    use anyhow::Result;
+    use macro_rules_attr::apply;
+    use num_traits::One;
+    use num_traits::Zero;
+    use proptest::prop_assume;
+    use rand::rngs::StdRng;
+    use rand::Rng;
+    use rand::SeedableRng;
+    use strum::IntoEnumIterator;
+    use tracing_test::traced_test;
 
-    // documented in trait. do not add doc-comment.
-    async fn generate_witness_proof(
-        self,
-        _: context::Context,
-        token: auth::Token,
-        tx_details: TransactionDetails,
-    ) -> RpcResult<TransactionProof> {
-        log_slow_scope!(fn_name!());
-        token.auth(&self.valid_tokens)?;
+    use super::*;
+    use crate::api::tx_initiation::error::TxInitiationError;
+    use crate::application::config::cli_args;
+    use crate::application::config::network::Network;
+    use crate::application::database::storage::storage_vec::traits::*;
+    use crate::application::rpc::server::NeptuneRPCServer;
+    use crate::protocol::consensus::block::block_selector::BlockSelectorLiteral;
+    use crate::protocol::peer::NegativePeerSanction;
+    use crate::protocol::peer::PeerSanction;
+    use crate::protocol::proof_abstractions::mast_hash::MastHash;
+    use crate::state::checkpoint::Checkpoint;
+    use crate::state::mining::guesser_statistics::GuesserStatistics;
+    use crate::state::proof_upgrade_schedule::ProofUpgradeDeferral;
+    use crate::state::release_manifest::UpdateNotice;
+    use crate::state::wallet::address::generation_address::GenerationReceivingAddress;
+    use crate::state::wallet::address::generation_address::GenerationSpendingKey;
+    use crate::state::wallet::utxo_notification::UtxoNotificationMedium;
+    use crate::state::wallet::wallet_backup::WalletBackupHead;
+    use crate::state::wallet::wallet_backup::WalletBackupPeer;
+    use crate::state::wallet::wallet_entropy::WalletEntropy;
+    use crate::tests::shared::blocks::invalid_block_with_transaction;
+    use crate::tests::shared::blocks::invalid_empty_block;
+    use crate::tests::shared::blocks::invalid_empty_block1_with_guesser_fraction;
+    use crate::tests::shared::blocks::make_mock_block;
+    use crate::tests::shared::files::unit_test_data_directory;
+    use crate::tests::shared::globalstate::get_dummy_socket_address;
+    use crate::tests::shared::globalstate::mock_genesis_global_state;
+    use crate::tests::shared::mock_tx::invalid_empty_single_proof_transaction;
+    use crate::tests::shared::strategies::txkernel;
+    use crate::tests::shared_tokio_runtime;
+    use crate::util_types::mutator_set::removal_record::absolute_index_set::AbsoluteIndexSet;
+    use crate::Block;
+    use crate::VERSION;
 
-        Ok(self
-            .state
-            .api()
-            .tx_initiator()
-            .generate_witness_proof(Arc::new(tx_details)))
-    }
+    const NUM_ANNOUNCEMENTS_BLOCK1: usize = 7;
 
-    // documented in trait. do not add doc-comment.
-    async fn assemble_transaction(
-        self,
-        _: context::Context,
-        token: auth::Token,
-        transaction_details: TransactionDetails,
-        transaction_proof: TransactionProof,
-    ) -> RpcResult<Transaction> {
-        log_slow_scope!(fn_name!());
-        token.auth(&self.valid_tokens)?;
+    async fn test_rpc_server(
+        wallet_entropy: WalletEntropy,
+        peer_count: u8,
+        cli: cli_args::Args,
+    ) -> NeptuneRPCServer {
+        let global_state_lock =
+            mock_genesis_global_state(peer_count, wallet_entropy, cli.clone()).await;
 
-        Ok(self
-            .state
-            .api()
-            .tx_initiator()
-            .assemble_transaction(&transaction_details, transaction_proof)?)
-    }
+        let data_directory = unit_test_data_directory(cli.network).unwrap();
 
-    // documented in trait. do not add doc-comment.
-    async fn assemble_transaction_artifacts(
-        self,
-        _: context::Context,
-        token: auth::Token,
-        transaction_details: TransactionDetails,
-        transaction_proof: TransactionProof,
-    ) -> RpcResult<TxCreationArtifacts> {
-        log_slow_scope!(fn_name!());
-        token.auth(&self.valid_tokens)?;
+        let valid_tokens: Vec<auth::Token> =
+            vec![auth::Cookie::try_new(&data_directory).await.unwrap().into()];
 
-        Ok(self
-            .state
-            .api()
-            .tx_initiator()
-            .assemble_transaction_artifacts(transaction_details, transaction_proof)?)
-    }
+        let rpc_to_main_tx = global_state_lock.rpc_server_to_main_tx();
 
-    // documented in trait. do not add doc-comment.
-    async fn proof_type(
-        self,
-        _ctx: context::Context,
-        token: auth::Token,
-        txid: TransactionKernelId,
-    ) -> RpcResult<TransactionProofType> {
-        log_slow_scope!(fn_name!());
-        token.auth(&self.valid_tokens)?;
+        let request_limiter = Arc::new(RpcRequestLimiter::from_cli(&cli));
 
-        Ok(self.state.api().tx_initiator().proof_type(txid).await?)
+        NeptuneRPCServer::new(
+            global_state_lock,
+            rpc_to_main_tx,
+            data_directory,
+            valid_tokens,
+            request_limiter,
+        )
     }
 
-    // documented in trait. do not add doc-comment.
-    async fn block_intervals(
-        self,
-        _context: tarpc::context::Context,
-        token: auth::Token,
-        last_block: BlockSelector,
-        max_num_blocks: Option<usize>,
-    ) -> RpcResult<Option<Vec<(u64, u64)>>> {
-        log_slow_scope!(fn_name!());
-        token.auth(&self.valid_tokens)?;
-
-        let state = self.state.lock_guard().await;
-        let Some(last_block) = last_block.as_digest(&state).await else {
-            return Ok(None);
-        };
-        let mut intervals = vec![];
-        let mut current = state
-            .chain
-            .archival_state()
-            .get_block_header(last_block)
+    async fn cookie_token(server: &NeptuneRPCServer) -> auth::Token {
+        auth::Cookie::try_load(server.data_directory())
             .await
-            .expect("If digest can be found, block header should also be known");
-        let mut parent = state
-            .chain
-            .archival_state()
-            .get_block_header(current.prev_block_digest)
-            .await;
+            .unwrap()
+            .into()
+    }
 
-        // Exclude genesis since it was not mined. So block interval 0-->1
-        // is not included.
-        while parent.is_some()
-            && !parent.unwrap().height.is_genesis()
-            && max_num_blocks.is_none_or(|max_num| max_num > intervals.len())
-        {
-            let parent_ = parent.unwrap();
-            let interval = current.timestamp.to_millis() - parent_.timestamp.to_millis();
-            let block_height: u64 = current.height.into();
-            intervals.push((block_height, interval));
-            current = parent_;
-            parent = state
-                .chain
-                .archival_state()
-                .get_block_header(current.prev_block_digest)
-                .await;
+    #[apply(shared_tokio_runtime)]
+    async fn network_response_is_consistent() -> Result<()> {
+        for network in [Network::Main, Network::Testnet(0)] {
+            let rpc_server = test_rpc_server(
+                WalletEntropy::new_random(),
+                2,
+                cli_args::Args::default_with_network(network),
+            )
+            .await;
+            assert_eq!(network, rpc_server.network(context::current()).await?);
         }
 
-        Ok(Some(intervals))
+        Ok(())
     }
 
-    async fn block_difficulties(
-        self,
-        _context: tarpc::context::Context,
-        token: auth::Token,
-        last_block: BlockSelector,
-        max_num_blocks: Option<usize>,
-    ) -> RpcResult<Vec<(u64, Difficulty)>> {
-        log_slow_scope!(fn_name!());
-        token.auth(&self.valid_tokens)?;
+    #[apply(shared_tokio_runtime)]
+    async fn batch_returns_responses_in_order_with_per_call_errors() {
+        let network = Network::Main;
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(network),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
+        let ctx = context::current();
 
-        let state = self.state.lock_guard().await;
-        let last_block = last_block.as_digest(&state).await;
-        let Some(last_block) = last_block else {
-            return Ok(vec![]);
-        };
+        let calls = vec![
+            RPCRequest::Network {},
+            RPCRequest::Batch {
+                token,
+                calls: vec![],
+            },
+            RPCRequest::BlockHeight { token },
+        ];
+        let responses = rpc_server.clone().batch(ctx, token, calls).await.unwrap();
+        assert_eq!(3, responses.len());
+        assert!(matches!(&responses[0], RPCResponse::Network(Ok(n)) if *n == network));
+        assert!(matches!(
+            &responses[1],
+            RPCResponse::Batch(Err(error::RpcError::NestedBatch))
+        ));
+        assert!(
+            matches!(&responses[2], RPCResponse::BlockHeight(Ok(height)) if *height == BlockHeight::genesis())
+        );
 
-        let mut difficulties = vec![];
+        let too_many_calls = (0..=MAX_RPC_BATCH_SIZE)
+            .map(|_| RPCRequest::Network {})
+            .collect();
+        assert!(matches!(
+            rpc_server.batch(ctx, token, too_many_calls).await,
+            Err(error::RpcError::BatchTooLarge(_))
+        ));
+    }
 
-        let mut current = state
-            .chain
-            .archival_state()
-            .get_block_header(last_block)
+    #[apply(shared_tokio_runtime)]
+    async fn verify_that_all_requests_leave_server_running() -> Result<()> {
+        // Got through *all* request types and verify that server does not crash.
+        // We don't care about the actual response data in this test, just that the
+        // requests do not crash the server.
+
+        let network = Network::Main;
+        let mut rng = StdRng::seed_from_u64(123456789088u64);
+
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_pseudorandom(rng.random()),
+            2,
+            cli_args::Args::default_with_network(network),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
+        let ctx = context::current();
+        let _ = rpc_server.clone().network(ctx).await;
+        let _ = rpc_server
+            .clone()
+            .own_listen_address_for_peers(ctx, token)
+            .await;
+        let _ = rpc_server.clone().own_instance_id(ctx, token).await;
+        let _ = rpc_server.clone().block_height(ctx, token).await;
+        let _ = rpc_server
+            .clone()
+            .block_application_progress(ctx, token, None)
+            .await;
+        let _ = rpc_server.clone().best_proposal(ctx, token).await;
+        let _ = rpc_server
+            .clone()
+            .latest_address(ctx, token, KeyType::Generation)
+            .await
+            .unwrap();
+        let _ = rpc_server
+            .clone()
+            .latest_address(ctx, token, KeyType::Symmetric)
+            .await
+            .unwrap();
+        let _ = rpc_server.clone().peer_info(ctx, token).await;
+        let _ = rpc_server
+            .clone()
+            .block_digests_by_height(ctx, token, 42u64.into())
+            .await;
+        let _ = rpc_server
+            .clone()
+            .block_digests_by_height(ctx, token, 0u64.into())
+            .await;
+        let _ = rpc_server.clone().all_punished_peers(ctx, token).await;
+        let _ = rpc_server.clone().latest_tip_digests(ctx, token, 2).await;
+        let _ = rpc_server
+            .clone()
+            .header(ctx, token, BlockSelector::Digest(Digest::default()))
+            .await;
+        let _ = rpc_server
+            .clone()
+            .block_info(ctx, token, BlockSelector::Digest(Digest::default()))
+            .await;
+        let _ = rpc_server
+            .clone()
+            .block_kernel(ctx, token, BlockSelector::Digest(Digest::default()))
+            .await;
+        let _ = rpc_server
+            .clone()
+            .addition_record_indices_for_block(ctx, token, BlockSelector::Digest(Digest::default()))
+            .await;
+        let _ = rpc_server
+            .clone()
+            .restore_membership_proof_privacy_preserving(
+                ctx,
+                token,
+                vec![AbsoluteIndexSet::compute(
+                    Digest::default(),
+                    Digest::default(),
+                    Digest::default(),
+                    444,
+                )],
+            )
+            .await;
+        let _ = rpc_server
+            .clone()
+            .announcements_in_block(ctx, token, BlockSelector::Digest(Digest::default()))
+            .await;
+        let _ = rpc_server
+            .clone()
+            .block_digest(ctx, token, BlockSelector::Digest(Digest::default()))
+            .await;
+        let _ = rpc_server.clone().utxo_digest(ctx, token, 0).await;
+        let _ = rpc_server
+            .clone()
+            .confirmed_available_balance(ctx, token)
+            .await;
+        let _ = rpc_server.clone().history(ctx, token).await;
+        let _ = rpc_server.clone().wallet_status(ctx, token).await;
+        let own_receiving_address = rpc_server
+            .clone()
+            .next_receiving_address(ctx, token, KeyType::Generation)
+            .await?;
+        let _ = rpc_server.clone().mempool_tx_count(ctx, token).await;
+        let _ = rpc_server.clone().mempool_size(ctx, token).await;
+        let _ = rpc_server.clone().dashboard_overview_data(ctx, token).await;
+        let _ = rpc_server
+            .clone()
+            .validate_address(
+                ctx,
+                token,
+                "Not a valid address".to_owned(),
+                Network::Testnet(0),
+            )
+            .await;
+        let _ = rpc_server
+            .clone()
+            .validate_amount(ctx, token, "132".to_owned())
+            .await;
+        let _ = rpc_server
+            .clone()
+            .parse_amount(ctx, token, "1.5 mNPT".to_owned())
+            .await;
+        let _ = rpc_server
+            .clone()
+            .validate_addresses(
+                ctx,
+                token,
+                vec!["Not a valid address".to_owned()],
+                Network::Testnet(0),
+                vec![],
+            )
+            .await;
+        let _ = rpc_server.clone().pow_puzzle_internal_key(ctx, token).await;
+        let _ = rpc_server
+            .clone()
+            .pow_puzzle_external_key(ctx, token, own_receiving_address.clone())
+            .await;
+        let _ = rpc_server
+            .clone()
+            .provide_pow_solution(ctx, token, rng.random(), rng.random())
+            .await;
+        let _ = rpc_server
+            .clone()
+            .full_pow_puzzle_external_key(ctx, token, own_receiving_address.clone())
+            .await
+            .unwrap();
+        let _ = rpc_server
+            .clone()
+            .spendable_inputs(ctx, token)
+            .await
+            .unwrap();
+        let _ = rpc_server
+            .clone()
+            .select_spendable_inputs(
+                ctx,
+                token,
+                InputSelectionPolicy::Random,
+                NativeCurrencyAmount::coins(5),
+            )
+            .await;
+        let _ = rpc_server
+            .clone()
+            .generate_tx_outputs(ctx, token, vec![])
+            .await
+            .unwrap();
+        let tx_details = rpc_server
+            .clone()
+            .generate_tx_details(
+                ctx,
+                token,
+                TxInputList::default(),
+                TxOutputList::default(),
+                ChangePolicy::default(),
+                NativeCurrencyAmount::zero(),
+            )
+            .await
+            .unwrap();
+        let tx_proof = rpc_server
+            .clone()
+            .generate_witness_proof(ctx, token, tx_details.clone())
+            .await
+            .unwrap();
+        let _ = rpc_server
+            .clone()
+            .assemble_transaction(ctx, token, tx_details, tx_proof)
+            .await
+            .unwrap();
+        let _ = rpc_server
+            .clone()
+            .provide_new_tip(ctx, token, rng.random(), Block::genesis(network))
+            .await
+            .unwrap();
+        let _ = rpc_server
+            .clone()
+            .validate_proposal(ctx, token, Block::genesis(network))
+            .await
+            .unwrap();
+        let _ = rpc_server
+            .clone()
+            .block_intervals(
+                ctx,
+                token,
+                BlockSelector::Special(BlockSelectorLiteral::Tip),
+                None,
+            )
+            .await;
+        let _ = rpc_server
+            .clone()
+            .block_difficulties(
+                ctx,
+                token,
+                BlockSelector::Special(BlockSelectorLiteral::Tip),
+                None,
+            )
+            .await;
+        let _ = rpc_server
+            .clone()
+            .broadcast_all_mempool_txs(ctx, token)
+            .await;
+        let _ = rpc_server.clone().mempool_overview(ctx, token, 0, 20).await;
+        let _ = rpc_server
+            .clone()
+            .wallet_stats(ctx, token, NativeCurrencyAmount::zero())
+            .await;
+        let _ = rpc_server.clone().update_status(ctx, token).await;
+        let _ = rpc_server.clone().replication_status(ctx, token).await;
+        let _ = rpc_server.clone().promote_standby(ctx, token, false).await;
+        let _ = rpc_server
+            .clone()
+            .mempool_tx_kernel(ctx, token, Default::default())
             .await;
-        while current.is_some()
-            && max_num_blocks.is_none_or(|max_num| max_num >= difficulties.len())
-        {
-            let current_ = current.unwrap();
-            let height: u64 = current_.height.into();
-            difficulties.push((height, current_.difficulty));
-            current = state
-                .chain
-                .archival_state()
-                .get_block_header(current_.prev_block_digest)
-                .await;
-        }
-
-        Ok(difficulties)
-    }
-
-    // documented in trait. do not add doc-comment.
-    async fn broadcast_all_mempool_txs(
-        self,
-        _context: tarpc::context::Context,
-        token: auth::Token,
-    ) -> RpcResult<()> {
-        log_slow_scope!(fn_name!());
-        token.auth(&self.valid_tokens)?;
-
-        // If this sending fails, it means `main_loop` is no longer running,
-        // and node is crashed. No reason to log anything additional.
-        let _ = self
-            .rpc_server_to_main_tx
-            .send(RPCServerToMain::BroadcastMempoolTransactions)
+        let _ = rpc_server
+            .clone()
+            .mempool_tx_is_replaceable(ctx, token, Default::default())
             .await;
+        let _ = rpc_server
+            .clone()
+            .zero_conf_risk(ctx, token, Default::default())
+            .await;
+        let _ = rpc_server.clone().clear_all_standings(ctx, token).await;
+        let _ = rpc_server
+            .clone()
+            .clear_standing_by_ip(ctx, token, "127.0.0.1".parse().unwrap())
+            .await;
+        let output: OutputFormat = (
+            own_receiving_address.clone(),
+            NativeCurrencyAmount::one_nau(),
+        )
+            .into();
+        let _ = rpc_server
+            .clone()
+            .send(
+                ctx,
+                token,
+                vec![output],
+                ChangePolicy::ExactChange,
+                NativeCurrencyAmount::one_nau(),
+            )
+            .await;
+        let _ = rpc_server
+            .clone()
+            .upgrade(ctx, token, TransactionKernelId::default())
+            .await;
+        let _ = rpc_server
+            .clone()
+            .cancel_transaction(ctx, token, TransactionKernelId::default())
+            .await;
+        let _ = rpc_server.clone().mempool_tx_ids(ctx, token).await;
 
-        Ok(())
-    }
-
-    async fn broadcast_block_proposal(
-        self,
-        _context: tarpc::context::Context,
-        token: auth::Token,
-    ) -> RpcResult<()> {
-        log_slow_scope!(fn_name!());
-        token.auth(&self.valid_tokens)?;
+        let my_output: OutputFormat =
+            (own_receiving_address, NativeCurrencyAmount::one_nau()).into();
+        let _ = rpc_server
+            .clone()
+            .send(
+                ctx,
+                token,
+                vec![my_output],
+                ChangePolicy::ExactChange,
+                NativeCurrencyAmount::one_nau(),
+            )
+            .await;
 
-        let _ = self
-            .rpc_server_to_main_tx
-            .send(RPCServerToMain::BroadcastBlockProposal)
+        let _ = rpc_server.clone().pause_miner(ctx, token).await;
+        let _ = rpc_server.clone().restart_miner(ctx, token).await;
+        let _ = rpc_server
+            .clone()
+            .set_coinbase_distribution(ctx, token, vec![])
+            .await;
+        let _ = rpc_server
+            .clone()
+            .unset_coinbase_distribution(ctx, token)
+            .await;
+        let _ = rpc_server
+            .clone()
+            .prune_abandoned_monitored_utxos(ctx, token)
+            .await;
+        let _ = rpc_server
+            .clone()
+            .batch(ctx, token, vec![RPCRequest::BlockHeight { token }])
             .await;
+        let _ = rpc_server.shutdown(ctx, token).await;
 
         Ok(())
     }
 
-    // documented in trait. do not add doc-comment.
-    async fn mempool_overview(
-        self,
-        _context: ::tarpc::context::Context,
-        token: auth::Token,
-        start_index: usize,
-        number: usize,
-    ) -> RpcResult<Vec<MempoolTransactionInfo>> {
-        log_slow_scope!(fn_name!());
-        token.auth(&self.valid_tokens)?;
-
-        let global_state = self.state.lock_guard().await;
-        let mempool_txkids = global_state
-            .mempool
-            .fee_density_iter()
-            .skip(start_index)
-            .take(number)
-            .map(|(txkid, _)| txkid)
-            .collect_vec();
-
-        let (incoming, outgoing): (HashMap<_, _>, HashMap<_, _>) = {
-            let (incoming_iter, outgoing_iter) =
-                global_state.wallet_state.mempool_balance_updates();
-            (incoming_iter.collect(), outgoing_iter.collect())
-        };
-
-        let tip_msah = global_state
-            .chain
-            .light_state()
-            .mutator_set_accumulator_after()
-            .expect("Block from state must have mutator set after")
-            .hash();
-
-        let mempool_transactions = mempool_txkids
-            .iter()
-            .filter_map(|id| {
-                let mut mptxi = global_state
-                    .mempool
-                    .get(*id)
-                    .map(|tx| (MempoolTransactionInfo::from(tx), tx.kernel.mutator_set_hash))
-                    .map(|(mptxi, tx_msah)| {
-                        if tx_msah == tip_msah {
-                            mptxi.synced()
-                        } else {
-                            mptxi
-                        }
-                    });
-                if mptxi.is_some() {
-                    if let Some(pos_effect) = incoming.get(id) {
-                        mptxi = Some(mptxi.unwrap().with_positive_effect_on_balance(*pos_effect));
-                    }
-                    if let Some(neg_effect) = outgoing.get(id) {
-                        mptxi = Some(mptxi.unwrap().with_negative_effect_on_balance(*neg_effect));
-                    }
-                }
+    #[apply(shared_tokio_runtime)]
+    async fn latest_address_and_get_new_address_are_consistent() {
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(Network::Main),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
 
-                mptxi
-            })
-            .collect_vec();
+        for key_type in KeyType::iter() {
+            let addr0 = rpc_server
+                .clone()
+                .latest_address(context::current(), token, key_type)
+                .await
+                .unwrap();
+            let addr1 = rpc_server
+                .clone()
+                .next_receiving_address(context::current(), token, key_type)
+                .await
+                .unwrap();
+            assert_ne!(addr0, addr1);
 
-        Ok(mempool_transactions)
-    }
+            let addr1_again = rpc_server
+                .clone()
+                .latest_address(context::current(), token, key_type)
+                .await
+                .unwrap();
+            assert_eq!(addr1, addr1_again);
 
-    // documented in trait. do not add doc-comment.
-    async fn mempool_tx_kernel(
-        self,
-        _context: ::tarpc::context::Context,
-        token: auth::Token,
-        tx_kernel_id: TransactionKernelId,
-    ) -> RpcResult<Option<TransactionKernel>> {
-        log_slow_scope!(fn_name!());
-        token.auth(&self.valid_tokens)?;
+            let addr2 = rpc_server
+                .clone()
+                .next_receiving_address(context::current(), token, key_type)
+                .await
+                .unwrap();
+            let addr2_again = rpc_server
+                .clone()
+                .latest_address(context::current(), token, key_type)
+                .await
+                .unwrap();
+            assert_eq!(addr2, addr2_again);
 
-        Ok(self
-            .state
-            .lock_guard()
-            .await
-            .mempool
-            .get(tx_kernel_id)
-            .map(|tx| &tx.kernel)
-            .cloned())
+            // Ensure endpoint is idempotent
+            let addr2_again_again = rpc_server
+                .clone()
+                .latest_address(context::current(), token, key_type)
+                .await
+                .unwrap();
+            assert_eq!(addr2, addr2_again_again);
+        }
     }
-}
-
-pub mod error {
-    use super::*;
-
-    /// enumerates possible rpc api errors
-    #[derive(Debug, thiserror::Error, Serialize, Deserialize)]
-    #[non_exhaustive]
-    pub enum RpcError {
-        // auth error
-        #[error("auth error: {0}")]
-        Auth(#[from] auth::error::AuthError),
-
-        // catch-all error, eg for anyhow errors
-        #[error("rpc call failed: {0}")]
-        Failed(String),
-
-        // API specific error variants.
-        #[error("cookie hints are disabled on this node")]
-        CookieHintDisabled,
 
-        #[error("capacity to store exported block proposals exceeded")]
-        ExportedBlockProposalStorageCapacityExceeded,
+    #[apply(shared_tokio_runtime)]
+    async fn watch_and_unwatch_address() {
+        let network = Network::Main;
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(network),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
 
-        #[error("create transaction error: {0}")]
-        CreateTxError(String),
+        let address = GenerationSpendingKey::derive_from_seed(rand::random()).to_address();
+        let item = WatchItem::Address(address.to_bech32m(network).unwrap());
+        let receiver_identifier = rpc_server
+            .clone()
+            .watch(context::current(), token, item, "customer".to_string())
+            .await
+            .unwrap();
+        assert_eq!(address.receiver_identifier(), receiver_identifier);
 
-        #[error("upgrade proof error: {0}")]
-        UpgradeProofError(String),
+        let watched_items = rpc_server
+            .clone()
+            .watched_items(context::current(), token)
+            .await
+            .unwrap();
+        assert_eq!(1, watched_items.len());
+        assert_eq!("customer", watched_items[0].label);
 
-        #[error("send error: {0}")]
-        SendError(String),
+        assert!(rpc_server
+            .clone()
+            .unwatch(context::current(), token, receiver_identifier)
+            .await
+            .unwrap());
+        assert!(rpc_server
+            .clone()
+            .watched_items(context::current(), token)
+            .await
+            .unwrap()
+            .is_empty());
 
-        #[error("regtest error: {0}")]
-        RegTestError(String),
+        let invalid_item = WatchItem::Address("not an address".to_string());
+        assert!(rpc_server
+            .clone()
+            .watch(context::current(), token, invalid_item, String::new())
+            .await
+            .is_err());
+    }
 
-        #[error("wallet error: {0}")]
-        WalletError(String),
+    #[apply(shared_tokio_runtime)]
+    async fn replay_events_delivers_events_until_acknowledged() {
+        use crate::application::plugin_hooks::HookEvent;
 
-        #[error("claim error: {0}")]
-        ClaimError(String),
+        let network = Network::Main;
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(network),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
 
-        #[error("Cannot pause state updates while client is syncing")]
-        CannotPauseWhileSyncing,
+        let genesis = Block::genesis(network);
+        let new_block = HookEvent::NewBlock {
+            block_digest: genesis.hash(),
+            height: genesis.header().height,
+            timestamp: genesis.header().timestamp,
+        };
+        rpc_server
+            .state
+            .lock_guard()
+            .await
+            .event_log
+            .lock()
+            .await
+            .record(vec![new_block.clone(), new_block.clone()]);
 
-        #[error("Invalid coinbase distribution: {0}")]
-        InvalidCoinbaseDistribution(String),
+        let replay = |subscriber: &str, since| {
+            rpc_server.clone().replay_events(
+                context::current(),
+                token,
+                subscriber.to_string(),
+                since,
+            )
+        };
+        let events = replay("alice", 0).await.unwrap();
+        assert_eq!(1, events.len());
+        assert_eq!(new_block, events[0].event);
+        assert_eq!(events, replay("alice", 0).await.unwrap());
 
-        #[error("Node is not setup to compose")]
-        NotComposing,
+        rpc_server
+            .clone()
+            .acknowledge_events(
+                context::current(),
+                token,
+                "alice".to_string(),
+                events[0].sequence_number,
+            )
+            .await
+            .unwrap();
+        assert!(replay("alice", 0).await.unwrap().is_empty());
+        assert!(replay("bob", events[0].sequence_number)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(events, replay("carol", 0).await.unwrap());
+    }
 
-        #[error("Cannot restore membership proofs: {0}")]
-        CannotRestoreMembershipProofs(String),
+    #[apply(shared_tokio_runtime)]
+    async fn parse_amount_rejects_amounts_that_would_be_rounded() {
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(Network::Main),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
+        let parse = |amount: &str| {
+            rpc_server
+                .clone()
+                .parse_amount(context::current(), token, amount.to_owned())
+        };
 
-        #[error("Wallet key counter is zero. Must be positive after init")]
-        WalletKeyCounterIsZero,
+        assert_eq!(
+            NativeCurrencyAmount::coins(2).half(),
+            parse("1000 mNPT").await.unwrap()
+        );
+        assert_eq!(NativeCurrencyAmount::coins(3), parse("3").await.unwrap());
+        assert!(matches!(
+            parse("0.5 nau").await,
+            Err(RpcError::InvalidAmount(_))
+        ));
+        assert!(matches!(
+            parse("1.5 dogecoin").await,
+            Err(RpcError::InvalidAmount(_))
+        ));
+        assert!(matches!(
+            parse("42000001").await,
+            Err(RpcError::InvalidAmount(_))
+        ));
     }
 
-    impl From<tx_initiation::error::CreateTxError> for RpcError {
-        fn from(err: tx_initiation::error::CreateTxError) -> Self {
-            RpcError::CreateTxError(err.to_string())
-        }
-    }
+    #[apply(shared_tokio_runtime)]
+    async fn validate_amount_rejects_amounts_that_would_be_rounded() {
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(Network::Main),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
+        let validate = |amount: &str| {
+            rpc_server
+                .clone()
+                .validate_amount(context::current(), token, amount.to_owned())
+        };
 
-    impl From<tx_initiation::error::UpgradeProofError> for RpcError {
-        fn from(err: tx_initiation::error::UpgradeProofError) -> Self {
-            RpcError::UpgradeProofError(err.to_string())
-        }
+        assert_eq!(
+            Some(NativeCurrencyAmount::coins(1)),
+            validate("1").await.unwrap()
+        );
+        assert_eq!(None, validate("1.5 mNPT").await.unwrap());
+        assert_eq!(
+            None,
+            validate("0.0000000000000000000000000000001").await.unwrap()
+        );
     }
 
-    impl From<tx_initiation::error::SendError> for RpcError {
-        fn from(err: tx_initiation::error::SendError) -> Self {
-            RpcError::SendError(err.to_string())
-        }
-    }
+    #[apply(shared_tokio_runtime)]
+    async fn validate_addresses_returns_verdict_per_address_up_to_limit() {
+        let network = Network::Main;
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(network),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
+        let own_address = rpc_server
+            .clone()
+            .next_receiving_address(context::current(), token, KeyType::Generation)
+            .await
+            .unwrap()
+            .to_bech32m(network)
+            .unwrap();
 
-    impl From<api::regtest::error::RegTestError> for RpcError {
-        fn from(err: api::regtest::error::RegTestError) -> Self {
-            RpcError::RegTestError(err.to_string())
-        }
-    }
+        let verdicts = rpc_server
+            .clone()
+            .validate_addresses(
+                context::current(),
+                token,
+                vec![own_address.clone(), "not an address".to_owned()],
+                network,
+                vec![],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            vec![
+                AddressVerdict::Valid(KeyType::Generation),
+                AddressVerdict::Malformed
+            ],
+            verdicts
+        );
 
-    impl From<api::wallet::error::WalletError> for RpcError {
-        fn from(err: api::wallet::error::WalletError) -> Self {
-            RpcError::WalletError(err.to_string())
-        }
+        let too_many_addresses = vec![own_address; MAX_ADDRESSES_PER_VALIDATION + 1];
+        assert!(matches!(
+            rpc_server
+                .validate_addresses(
+                    context::current(),
+                    token,
+                    too_many_addresses,
+                    network,
+                    vec![]
+                )
+                .await,
+            Err(RpcError::TooManyAddresses(_))
+        ));
     }
 
-    impl From<ClaimError> for RpcError {
-        fn from(err: ClaimError) -> Self {
-            RpcError::ClaimError(err.to_string())
-        }
-    }
+    #[apply(shared_tokio_runtime)]
+    async fn block_application_progress_follows_new_tips() {
+        let network = Network::Main;
+        let mut rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(network),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
 
-    // convert anyhow::Error to an RpcError::Failed.
-    // note that anyhow Error is not serializable.
-    impl From<anyhow::Error> for RpcError {
-        fn from(e: anyhow::Error) -> Self {
-            Self::Failed(e.to_string())
-        }
+        let before = rpc_server
+            .clone()
+            .block_application_progress(context::current(), token, None)
+            .await
+            .unwrap();
+        assert_eq!(BlockHeight::genesis(), before.current_height);
+        assert!(before.source.is_none());
+
+        let waiting = tokio::spawn(rpc_server.clone().block_application_progress(
+            context::current(),
+            token,
+            Some(before.sequence),
+        ));
+        let block1 = invalid_empty_block(&Block::genesis(network), network);
+        rpc_server.state.set_new_tip(block1).await.unwrap();
+
+        let after = waiting.await.unwrap().unwrap();
+        assert!(after.sequence > before.sequence);
+        assert_eq!(BlockHeight::from(1u64), after.current_height);
     }
 
-    /// enumerates possible transaction send errors
-    #[derive(Debug, Clone, thiserror::Error, Serialize, Deserialize)]
-    #[non_exhaustive]
-    pub enum ClaimError {
-        #[error("utxo does not match any known wallet key")]
-        UtxoUnknown,
+    #[apply(shared_tokio_runtime)]
+    async fn mutator_set_statistics_track_new_tips() {
+        let network = Network::Main;
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(network),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
+        let statistics = || {
+            rpc_server
+                .clone()
+                .mutator_set_statistics(context::current(), token)
+        };
 
-        #[error("invalid type script in claim utxo")]
-        InvalidTypeScript,
+        let before = statistics().await.unwrap();
+        assert!(before.aocl_leaf_count > 0, "premine must be in AOCL");
+        assert_eq!(before.aocl_leaf_count, before.estimated_anonymity_set_size);
+        assert_eq!(0, before.num_sampled_blocks);
+        assert_eq!(None, before.additions_per_block);
 
-        // catch-all error, eg for anyhow errors
-        #[error("claim unsuccessful")]
-        Failed(String),
-    }
+        let block1 = invalid_empty_block(&Block::genesis(network), network);
+        rpc_server.state.set_new_tip(block1).await.unwrap();
 
-    // convert anyhow::Error to a ClaimError::Failed.
-    // note that anyhow Error is not serializable.
-    impl From<anyhow::Error> for ClaimError {
-        fn from(e: anyhow::Error) -> Self {
-            Self::Failed(e.to_string())
-        }
+        let after = statistics().await.unwrap();
+        assert_eq!(BlockHeight::from(1u64), after.tip_height);
+        assert_eq!(1, after.num_sampled_blocks);
+        assert_eq!(
+            Some((after.aocl_leaf_count - before.aocl_leaf_count) as f64),
+            after.additions_per_block
+        );
+        assert_eq!(Some(0.0), after.removals_per_block);
     }
-}
-
-#[cfg(test)]
-#[cfg_attr(coverage_nightly, coverage(off))]
-mod tests {
-    use anyhow::Result;
-    use macro_rules_attr::apply;
-    use num_traits::One;
-    use num_traits::Zero;
-    use proptest::prop_assume;
-    use rand::rngs::StdRng;
-    use rand::Rng;
-    use rand::SeedableRng;
-    use strum::IntoEnumIterator;
-    use tracing_test::traced_test;
 
-    use super::*;
-    use crate::application::config::cli_args;
-    use crate::application::config::network::Network;
-    use crate::application::database::storage::storage_vec::traits::*;
-    use crate::application::rpc::server::NeptuneRPCServer;
-    use crate::protocol::consensus::block::block_selector::BlockSelectorLiteral;
-    use crate::protocol::peer::NegativePeerSanction;
-    use crate::protocol::peer::PeerSanction;
-    use crate::protocol::proof_abstractions::mast_hash::MastHash;
-    use crate::state::wallet::address::generation_address::GenerationReceivingAddress;
-    use crate::state::wallet::address::generation_address::GenerationSpendingKey;
-    use crate::state::wallet::utxo_notification::UtxoNotificationMedium;
-    use crate::state::wallet::wallet_entropy::WalletEntropy;
-    use crate::tests::shared::blocks::invalid_block_with_transaction;
-    use crate::tests::shared::blocks::make_mock_block;
-    use crate::tests::shared::files::unit_test_data_directory;
-    use crate::tests::shared::globalstate::mock_genesis_global_state;
-    use crate::tests::shared::strategies::txkernel;
-    use crate::tests::shared_tokio_runtime;
-    use crate::util_types::mutator_set::removal_record::absolute_index_set::AbsoluteIndexSet;
-    use crate::Block;
+    #[apply(shared_tokio_runtime)]
+    async fn fork_readiness_counts_own_and_peer_signals() {
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(Network::Main),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
 
-    const NUM_ANNOUNCEMENTS_BLOCK1: usize = 7;
+        let report = rpc_server
+            .fork_readiness(context::current(), token)
+            .await
+            .unwrap();
+        assert_eq!(2, report.connected_peers);
 
-    async fn test_rpc_server(
-        wallet_entropy: WalletEntropy,
-        peer_count: u8,
-        cli: cli_args::Args,
-    ) -> NeptuneRPCServer {
-        let global_state_lock =
-            mock_genesis_global_state(peer_count, wallet_entropy, cli.clone()).await;
+        let alpha = report
+            .rule_sets
+            .iter()
+            .find(|readiness| readiness.rule_set == "HardforkAlpha")
+            .unwrap();
+        assert!(alpha.signaled_by_self);
+        assert_eq!(
+            ConsensusRuleSet::HardforkAlpha.activation_height(Network::Main),
+            alpha.activation_height
+        );
+        assert_eq!(0, alpha.connected_peers_signaling);
+        assert_eq!(0.0, alpha.connected_peers_ready_percent);
+    }
 
-        let data_directory = unit_test_data_directory(cli.network).unwrap();
+    #[apply(shared_tokio_runtime)]
+    async fn diagnostics_redact_peer_addresses_unless_included() {
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(Network::Main),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
 
-        let valid_tokens: Vec<auth::Token> =
-            vec![auth::Cookie::try_new(&data_directory).await.unwrap().into()];
+        let redacted = rpc_server
+            .clone()
+            .diagnostics(context::current(), token, false)
+            .await
+            .unwrap();
+        assert_eq!(2, redacted.peers.len());
+        assert!(redacted.peers.iter().all(|peer| peer.address.is_none()));
+        assert!(redacted.redactions.addresses);
 
-        let rpc_to_main_tx = global_state_lock.rpc_server_to_main_tx();
+        let included = rpc_server
+            .diagnostics(context::current(), token, true)
+            .await
+            .unwrap();
+        assert!(included.peers.iter().all(|peer| peer.address.is_some()));
+        assert!(!included.redactions.addresses);
+        assert_eq!(redacted.tip, included.tip);
+    }
 
-        NeptuneRPCServer::new(
-            global_state_lock,
-            rpc_to_main_tx,
-            data_directory,
-            valid_tokens,
+    #[apply(shared_tokio_runtime)]
+    async fn invalidate_block_is_regtest_only() {
+        let network = Network::Main;
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(network),
         )
-    }
+        .await;
+        let token = cookie_token(&rpc_server).await;
 
-    async fn cookie_token(server: &NeptuneRPCServer) -> auth::Token {
-        auth::Cookie::try_load(server.data_directory())
-            .await
-            .unwrap()
-            .into()
+        let block1 = invalid_empty_block(&Block::genesis(network), network);
+        rpc_server.state.set_new_tip(block1.clone()).await.unwrap();
+
+        assert!(matches!(
+            rpc_server
+                .clone()
+                .invalidate_block(context::current(), token, block1.hash())
+                .await,
+            Err(RpcError::RegTestError(_))
+        ));
+        assert!(matches!(
+            rpc_server
+                .clone()
+                .reconsider_block(context::current(), token, block1.hash())
+                .await,
+            Err(RpcError::RegTestError(_))
+        ));
+        assert_eq!(
+            block1.hash(),
+            rpc_server
+                .state
+                .lock_guard()
+                .await
+                .chain
+                .light_state()
+                .hash()
+        );
     }
 
     #[apply(shared_tokio_runtime)]
-    async fn network_response_is_consistent() -> Result<()> {
-        for network in [Network::Main, Network::Testnet(0)] {
+    async fn chain_parameters_match_running_network() {
+        for network in [Network::Main, Network::RegTest] {
             let rpc_server = test_rpc_server(
                 WalletEntropy::new_random(),
                 2,
                 cli_args::Args::default_with_network(network),
             )
             .await;
-            assert_eq!(network, rpc_server.network(context::current()).await?);
-        }
+            let token = cookie_token(&rpc_server).await;
+            let parameters = rpc_server
+                .clone()
+                .chain_parameters(context::current(), token)
+                .await
+                .unwrap();
 
-        Ok(())
+            assert_eq!(network, parameters.network);
+            assert_eq!(
+                network.target_block_interval(),
+                parameters.target_block_interval
+            );
+
+            let tip_height = rpc_server
+                .clone()
+                .block_height(context::current(), token)
+                .await
+                .unwrap();
+            let consensus_rule_set = rpc_server.state.lock_guard().await.consensus_rule_set();
+            let limits = parameters.rule_set_at(tip_height).unwrap();
+            assert_eq!(consensus_rule_set, limits.rule_set);
+            assert_eq!(consensus_rule_set.max_block_size(), limits.max_block_size);
+        }
     }
 
     #[apply(shared_tokio_runtime)]
-    async fn verify_that_all_requests_leave_server_running() -> Result<()> {
-        // Got through *all* request types and verify that server does not crash.
-        // We don't care about the actual response data in this test, just that the
-        // requests do not crash the server.
+    async fn sync_status_reports_stage_and_serving_peers() {
+        use tasm_lib::twenty_first::util_types::mmr::mmr_accumulator::MmrAccumulator;
 
-        let network = Network::Main;
-        let mut rng = StdRng::seed_from_u64(123456789088u64);
+        use crate::protocol::consensus::block::difficulty_control::ProofOfWork;
+        use crate::state::block_application_progress::BlockApplicationSource;
+        use crate::state::networking_state::SyncAnchor;
+        use crate::state::sync_status::SyncStage;
+        use crate::tests::shared::globalstate::get_dummy_socket_address;
 
         let rpc_server = test_rpc_server(
-            WalletEntropy::new_pseudorandom(rng.random()),
+            WalletEntropy::new_random(),
             2,
-            cli_args::Args::default_with_network(network),
+            cli_args::Args::default_with_network(Network::Main),
         )
         .await;
         let token = cookie_token(&rpc_server).await;
-        let ctx = context::current();
-        let _ = rpc_server.clone().network(ctx).await;
-        let _ = rpc_server
-            .clone()
-            .own_listen_address_for_peers(ctx, token)
-            .await;
-        let _ = rpc_server.clone().own_instance_id(ctx, token).await;
-        let _ = rpc_server.clone().block_height(ctx, token).await;
-        let _ = rpc_server.clone().best_proposal(ctx, token).await;
-        let _ = rpc_server
+        let sync_status = || rpc_server.clone().sync_status(context::current(), token);
+
+        let status = sync_status().await.unwrap();
+        assert_eq!(Some(0), status.blocks.target);
+        assert!(status.headers.is_complete());
+        assert!(status.blocks.is_complete());
+        assert!(status.blocks.peers.is_empty());
+
+        let [announcer, server] = [0, 1].map(get_dummy_socket_address);
+        {
+            let mut state = rpc_server.state.lock_guard_mut().await;
+            let mut anchor =
+                SyncAnchor::new(ProofOfWork::MAXIMUM, MmrAccumulator::new_from_leafs(vec![]));
+            anchor.announced_by = Some(announcer);
+            state.net.sync_anchor = Some(anchor);
+            state
+                .block_application_progress
+                .start(BlockApplicationSource::PeerSync, Some(100u64.into()));
+            state.block_application_progress.record_serving_peer(server);
+        }
+
+        let status = sync_status().await.unwrap();
+        assert_eq!(Some(SyncStage::Blocks), status.current_stage());
+        assert_eq!(Some(100), status.headers.target);
+        assert_eq!(vec![announcer], status.headers.peers);
+        assert_eq!(Some(100), status.blocks.target);
+        assert_eq!(0, status.blocks.current);
+        assert_eq!(vec![server], status.blocks.peers);
+        assert!(!status.is_synced());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn zero_conf_risk_is_unknown_for_transactions_not_in_mempool() {
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(Network::Main),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
+
+        let risk = rpc_server
             .clone()
-            .latest_address(ctx, token, KeyType::Generation)
+            .zero_conf_risk(context::current(), token, TransactionKernelId::default())
             .await
             .unwrap();
-        let _ = rpc_server
+        assert!(risk.is_none());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn mempool_fee_summary_forecasts_each_size_cutoff() {
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(Network::Main),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
+
+        let size_cutoffs = vec![1_000, 1_000_000];
+        let summary = rpc_server
             .clone()
-            .latest_address(ctx, token, KeyType::Symmetric)
+            .mempool_fee_summary(context::current(), token, size_cutoffs.clone())
             .await
             .unwrap();
-        let _ = rpc_server.clone().peer_info(ctx, token).await;
-        let _ = rpc_server
-            .clone()
-            .block_digests_by_height(ctx, token, 42u64.into())
-            .await;
-        let _ = rpc_server
-            .clone()
-            .block_digests_by_height(ctx, token, 0u64.into())
-            .await;
-        let _ = rpc_server.clone().all_punished_peers(ctx, token).await;
-        let _ = rpc_server.clone().latest_tip_digests(ctx, token, 2).await;
-        let _ = rpc_server
-            .clone()
-            .header(ctx, token, BlockSelector::Digest(Digest::default()))
-            .await;
-        let _ = rpc_server
-            .clone()
-            .block_info(ctx, token, BlockSelector::Digest(Digest::default()))
-            .await;
-        let _ = rpc_server
-            .clone()
-            .block_kernel(ctx, token, BlockSelector::Digest(Digest::default()))
-            .await;
-        let _ = rpc_server
-            .clone()
-            .addition_record_indices_for_block(ctx, token, BlockSelector::Digest(Digest::default()))
-            .await;
-        let _ = rpc_server
-            .clone()
-            .restore_membership_proof_privacy_preserving(
-                ctx,
-                token,
-                vec![AbsoluteIndexSet::compute(
-                    Digest::default(),
-                    Digest::default(),
-                    Digest::default(),
-                    444,
-                )],
-            )
-            .await;
-        let _ = rpc_server
-            .clone()
-            .announcements_in_block(ctx, token, BlockSelector::Digest(Digest::default()))
-            .await;
-        let _ = rpc_server
+        assert!(summary.buckets.is_empty());
+        assert_eq!(
+            size_cutoffs,
+            summary
+                .forecasts
+                .iter()
+                .map(|forecast| forecast.size_cutoff)
+                .collect_vec()
+        );
+
+        let block_subsidy = Block::block_subsidy(BlockHeight::genesis().next());
+        for forecast in summary.forecasts {
+            assert_eq!(0, forecast.num_txs);
+            assert!(forecast.total_fees.is_zero());
+            assert_eq!(block_subsidy, forecast.total_reward);
+            assert!(forecast.composer_reward <= block_subsidy);
+        }
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn mempool_diff_lists_changes_since_token() {
+        let network = Network::Main;
+        let mut rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(network),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
+
+        let first = rpc_server
             .clone()
-            .block_digest(ctx, token, BlockSelector::Digest(Digest::default()))
+            .mempool_diff(context::current(), token, None)
+            .await
+            .unwrap();
+        assert!(first.reset);
+        assert!(first.added.is_empty());
+        assert_eq!(0, first.num_txs);
+
+        let transaction = invalid_empty_single_proof_transaction();
+        let txid = transaction.kernel.txid();
+        rpc_server
+            .state
+            .lock_guard_mut()
+            .await
+            .mempool_insert(transaction, UpgradePriority::Irrelevant)
             .await;
-        let _ = rpc_server.clone().utxo_digest(ctx, token, 0).await;
-        let _ = rpc_server
+
+        let second = rpc_server
             .clone()
-            .confirmed_available_balance(ctx, token)
+            .mempool_diff(context::current(), token, Some(first.token))
+            .await
+            .unwrap();
+        assert!(!second.reset);
+        assert_eq!(vec![txid], second.added);
+        assert!(second.removed.is_empty());
+        assert_eq!(1, second.num_txs);
+        assert!(second.total_size > 0);
+
+        rpc_server
+            .state
+            .lock_guard_mut()
+            .await
+            .mempool_clear()
             .await;
-        let _ = rpc_server.clone().history(ctx, token).await;
-        let _ = rpc_server.clone().wallet_status(ctx, token).await;
-        let own_receiving_address = rpc_server
-            .clone()
-            .next_receiving_address(ctx, token, KeyType::Generation)
-            .await?;
-        let _ = rpc_server.clone().mempool_tx_count(ctx, token).await;
-        let _ = rpc_server.clone().mempool_size(ctx, token).await;
-        let _ = rpc_server.clone().dashboard_overview_data(ctx, token).await;
-        let _ = rpc_server
+        let third = rpc_server
             .clone()
-            .validate_address(
-                ctx,
-                token,
-                "Not a valid address".to_owned(),
-                Network::Testnet(0),
-            )
-            .await;
-        let _ = rpc_server.clone().pow_puzzle_internal_key(ctx, token).await;
-        let _ = rpc_server
+            .mempool_diff(context::current(), token, Some(second.token))
+            .await
+            .unwrap();
+        assert!(third.added.is_empty());
+        assert_eq!(vec![txid], third.removed);
+        assert_eq!(0, third.num_txs);
+
+        // added and removed since the first poll cancel out
+        let since_first = rpc_server
+            .mempool_diff(context::current(), token, Some(first.token))
+            .await
+            .unwrap();
+        assert!(since_first.added.is_empty());
+        assert!(since_first.removed.is_empty());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn verify_transaction_does_not_touch_mempool() {
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(Network::Main),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
+
+        let transaction = invalid_empty_single_proof_transaction();
+        let report = rpc_server
             .clone()
-            .pow_puzzle_external_key(ctx, token, own_receiving_address.clone())
-            .await;
-        let _ = rpc_server
+            .verify_transaction(context::current(), token, transaction.clone())
+            .await
+            .unwrap();
+        assert_eq!(transaction.kernel.txid(), report.txid);
+        assert!(!report.is_valid());
+        assert!(report
+            .failures
+            .contains(&transaction_verification::TransactionVerificationFailure::InvalidProof));
+
+        let mempool_tx_count = rpc_server
             .clone()
-            .provide_pow_solution(ctx, token, rng.random(), rng.random())
-            .await;
-        let _ = rpc_server
+            .mempool_tx_count(context::current(), token)
+            .await
+            .unwrap();
+        assert_eq!(0, mempool_tx_count);
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn test_mempool_accept_reports_rejections_without_inserting() {
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(Network::Main),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
+
+        let transaction = invalid_empty_single_proof_transaction();
+        let report = rpc_server
             .clone()
-            .full_pow_puzzle_external_key(ctx, token, own_receiving_address.clone())
+            .test_mempool_accept(context::current(), token, transaction.clone())
             .await
             .unwrap();
-        let _ = rpc_server
+        assert!(!report.accepted());
+        assert!(report
+            .verification
+            .failures
+            .contains(&transaction_verification::TransactionVerificationFailure::InvalidProof));
+        assert!(report
+            .rejections
+            .contains(&mempool_acceptance::MempoolRejectionReason::NotSynced));
+        assert!(report.replaces.is_empty());
+
+        let mempool_tx_count = rpc_server
             .clone()
-            .spendable_inputs(ctx, token)
+            .mempool_tx_count(context::current(), token)
             .await
             .unwrap();
-        let _ = rpc_server
+        assert_eq!(0, mempool_tx_count);
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn wallet_stats_of_devnet_premine_counts_all_unspent_utxos() {
+        let rpc_server = test_rpc_server(
+            WalletEntropy::devnet_wallet(),
+            2,
+            cli_args::Args::default_with_network(Network::Main),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
+
+        let own_coins = rpc_server
             .clone()
-            .select_spendable_inputs(
-                ctx,
-                token,
-                InputSelectionPolicy::Random,
-                NativeCurrencyAmount::coins(5),
-            )
-            .await;
-        let _ = rpc_server
+            .list_own_coins(context::current(), token)
+            .await
+            .unwrap();
+        assert!(!own_coins.is_empty());
+
+        let stats = rpc_server
             .clone()
-            .generate_tx_outputs(ctx, token, vec![])
+            .wallet_stats(context::current(), token, NativeCurrencyAmount::zero())
             .await
             .unwrap();
-        let tx_details = rpc_server
+        assert_eq!(own_coins.len(), stats.num_utxos);
+        assert_eq!(
+            own_coins
+                .iter()
+                .map(|coin| coin.amount)
+                .sum::<NativeCurrencyAmount>(),
+            stats.total_amount
+        );
+        assert_eq!(
+            stats.num_utxos,
+            stats
+                .value_buckets
+                .iter()
+                .map(|bucket| bucket.num_utxos)
+                .sum::<usize>()
+        );
+        assert_eq!(0, stats.num_dust_utxos);
+        assert!(stats.average_membership_proof_size > 0.0);
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn utxo_ownership_proof_of_devnet_premine_verifies() {
+        let network = Network::Main;
+        let rpc_server = test_rpc_server(
+            WalletEntropy::devnet_wallet(),
+            2,
+            cli_args::Args::default_with_network(network),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
+
+        let proof = rpc_server
             .clone()
-            .generate_tx_details(
-                ctx,
+            .prove_utxo_ownership(
+                context::current(),
                 token,
-                TxInputList::default(),
-                TxOutputList::default(),
-                ChangePolicy::default(),
-                NativeCurrencyAmount::zero(),
+                BlockSelector::Special(BlockSelectorLiteral::Tip),
+                vec![],
             )
             .await
+            .unwrap()
             .unwrap();
-        let tx_proof = rpc_server
+        let report = proof.verify().unwrap();
+        let genesis_block = Block::genesis(network);
+        assert_eq!(genesis_block.hash(), report.block_digest);
+        assert_eq!(
+            genesis_block
+                .mutator_set_accumulator_after()
+                .unwrap()
+                .hash(),
+            report.mutator_set_hash
+        );
+        assert!(!report.utxos.is_empty());
+        assert!(report.total.is_positive());
+
+        let aocl_leaf_index = report.utxos[0].aocl_leaf_index;
+        let selected = rpc_server
             .clone()
-            .generate_witness_proof(ctx, token, tx_details.clone())
+            .prove_utxo_ownership(
+                context::current(),
+                token,
+                BlockSelector::Special(BlockSelectorLiteral::Tip),
+                vec![aocl_leaf_index],
+            )
             .await
+            .unwrap()
             .unwrap();
-        let _ = rpc_server
+        assert_eq!(1, selected.utxos.len());
+
+        assert!(rpc_server
             .clone()
-            .assemble_transaction(ctx, token, tx_details, tx_proof)
+            .prove_utxo_ownership(
+                context::current(),
+                token,
+                BlockSelector::Special(BlockSelectorLiteral::Tip),
+                vec![u64::MAX]
+            )
             .await
-            .unwrap();
-        let _ = rpc_server
-            .clone()
-            .provide_new_tip(ctx, token, rng.random(), Block::genesis(network))
+            .is_err());
+        assert!(rpc_server
+            .prove_utxo_ownership(
+                context::current(),
+                token,
+                BlockSelector::Digest(Digest::default()),
+                vec![],
+            )
             .await
-            .unwrap();
-        let _ = rpc_server
+            .unwrap()
+            .is_none());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn proof_of_reserve_of_devnet_premine_verifies_against_genesis() {
+        // a network with mock proofs, since signing proves the hash lock.
+        let network = Network::RegTest;
+        let rpc_server = test_rpc_server(
+            WalletEntropy::devnet_wallet(),
+            2,
+            cli_args::Args::default_with_network(network),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
+
+        let signed_report = rpc_server
             .clone()
-            .block_intervals(
-                ctx,
+            .proof_of_reserve(
+                context::current(),
                 token,
                 BlockSelector::Special(BlockSelectorLiteral::Tip),
-                None,
             )
-            .await;
-        let _ = rpc_server
-            .clone()
-            .block_difficulties(
-                ctx,
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(1, signed_report.signatures.len());
+
+        let genesis_block = Block::genesis(network);
+        let snapshot = Checkpoint::from_block(&genesis_block, network).unwrap();
+        let report = signed_report.verify(network, &snapshot).await.unwrap();
+        assert!(report.total.is_positive());
+        assert_eq!(signed_report.report.total, report.total);
+
+        // The signature must come from the UTXOs' spending keys.
+        let mut resigned = signed_report.clone();
+        resigned.signatures[0].lock_script_hash = Digest::default();
+        assert!(resigned.verify(network, &snapshot).await.is_err());
+
+        let other_snapshot = Checkpoint {
+            block_digest: Digest::default(),
+            ..snapshot
+        };
+        assert!(signed_report
+            .verify(network, &other_snapshot)
+            .await
+            .is_err());
+
+        assert!(rpc_server
+            .proof_of_reserve(
+                context::current(),
                 token,
-                BlockSelector::Special(BlockSelectorLiteral::Tip),
-                None,
+                BlockSelector::Digest(Digest::default()),
             )
-            .await;
-        let _ = rpc_server
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn symmetric_receiving_address_is_reused_until_rotation() {
+        let cli_args = cli_args::Args {
+            symmetric_key_max_uses: Some(NonZero::new(2).unwrap()),
+            ..cli_args::Args::default_with_network(Network::Main)
+        };
+        let rpc_server = test_rpc_server(WalletEntropy::new_random(), 2, cli_args).await;
+        let token = cookie_token(&rpc_server).await;
+
+        let mut addresses = vec![];
+        for _ in 0..3 {
+            let address = rpc_server
+                .clone()
+                .next_receiving_address(context::current(), token, KeyType::Symmetric)
+                .await
+                .unwrap();
+            addresses.push(address);
+        }
+        assert_eq!(addresses[0], addresses[1]);
+        assert_ne!(addresses[1], addresses[2]);
+
+        let status = rpc_server
             .clone()
-            .broadcast_all_mempool_txs(ctx, token)
-            .await;
-        let _ = rpc_server.clone().mempool_overview(ctx, token, 0, 20).await;
-        let _ = rpc_server
+            .symmetric_key_rotation_status(context::current(), token)
+            .await
+            .unwrap();
+        assert_eq!(Some(2), status.policy.max_uses);
+        assert_eq!(1, status.num_uses);
+        assert!(!status.rotation_is_due);
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn next_symmetric_address_can_be_sent_to_and_is_listed() {
+        let network = Network::Main;
+        let cli_args = cli_args::Args {
+            symmetric_key_max_uses: Some(NonZero::new(2).unwrap()),
+            ..cli_args::Args::default_with_network(network)
+        };
+        let rpc_server = test_rpc_server(WalletEntropy::new_random(), 2, cli_args).await;
+        let token = cookie_token(&rpc_server).await;
+
+        let address_data = rpc_server
             .clone()
-            .mempool_tx_kernel(ctx, token, Default::default())
-            .await;
-        let _ = rpc_server.clone().clear_all_standings(ctx, token).await;
-        let _ = rpc_server
+            .next_symmetric_address(context::current(), token)
+            .await
+            .unwrap();
+        assert!(address_data.is_active);
+
+        let address = ReceivingAddress::from_bech32m(&address_data.address, network).unwrap();
+        assert_eq!(KeyType::Symmetric, KeyType::from(&address));
+        assert_eq!(
+            address_data.display_address,
+            address.to_display_bech32m(network).unwrap()
+        );
+        assert_ne!(address_data.address, address_data.display_address);
+
+        let latest_address = rpc_server
             .clone()
-            .clear_standing_by_ip(ctx, token, "127.0.0.1".parse().unwrap())
-            .await;
-        let output: OutputFormat = (
-            own_receiving_address.clone(),
-            NativeCurrencyAmount::one_nau(),
+            .latest_address(context::current(), token, KeyType::Symmetric)
+            .await
+            .unwrap();
+        assert_eq!(latest_address, address);
+
+        let known_addresses = rpc_server
+            .clone()
+            .symmetric_addresses(context::current(), token)
+            .await
+            .unwrap();
+        assert_eq!(
+            (0..known_addresses.len() as u64).collect_vec(),
+            known_addresses
+                .iter()
+                .map(|known| known.derivation_index)
+                .collect_vec()
+        );
+        assert_eq!(
+            vec![&address_data],
+            known_addresses
+                .iter()
+                .filter(|known| known.is_active)
+                .collect_vec()
+        );
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn next_payment_request_uses_new_generation_address() {
+        let network = Network::Main;
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(network),
         )
-            .into();
-        let _ = rpc_server
+        .await;
+        let token = cookie_token(&rpc_server).await;
+
+        let amount = Some(NativeCurrencyAmount::coins(10));
+        let payment_request = rpc_server
             .clone()
-            .send(
-                ctx,
-                token,
-                vec![output],
-                ChangePolicy::ExactChange,
-                NativeCurrencyAmount::one_nau(),
-            )
-            .await;
-        let _ = rpc_server
+            .next_payment_request(context::current(), token, amount)
+            .await
+            .unwrap();
+        let latest_address = rpc_server
             .clone()
-            .upgrade(ctx, token, TransactionKernelId::default())
-            .await;
-        let _ = rpc_server.clone().mempool_tx_ids(ctx, token).await;
+            .latest_address(context::current(), token, KeyType::Generation)
+            .await
+            .unwrap();
 
-        let my_output: OutputFormat =
-            (own_receiving_address, NativeCurrencyAmount::one_nau()).into();
-        let _ = rpc_server
+        assert_eq!(
+            latest_address.to_bech32m(network).unwrap(),
+            payment_request.address
+        );
+        assert_eq!(amount, payment_request.amount);
+
+        for uri in [&payment_request.uri, &payment_request.qr_payload] {
+            let parsed = PaymentRequest::from_uri(uri, network).unwrap();
+            assert_eq!(latest_address, parsed.address);
+            assert_eq!(amount, parsed.amount);
+        }
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn next_payment_request_rejects_non_positive_amount() {
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(Network::Main),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
+
+        for amount in [
+            NativeCurrencyAmount::zero(),
+            -NativeCurrencyAmount::coins(1),
+        ] {
+            assert!(rpc_server
+                .clone()
+                .next_payment_request(context::current(), token, Some(amount))
+                .await
+                .is_err());
+        }
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn plan_transfer_respects_wallet_balance() {
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(Network::Main),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
+
+        let empty_plan = rpc_server
             .clone()
-            .send(
-                ctx,
+            .plan_transfer(
+                context::current(),
                 token,
-                vec![my_output],
-                ChangePolicy::ExactChange,
-                NativeCurrencyAmount::one_nau(),
+                vec![],
+                NativeCurrencyAmount::coins(1),
             )
-            .await;
+            .await
+            .unwrap();
+        assert_eq!(0, empty_plan.num_transactions());
 
-        let _ = rpc_server.clone().pause_miner(ctx, token).await;
-        let _ = rpc_server.clone().restart_miner(ctx, token).await;
-        let _ = rpc_server
+        let address = rpc_server
             .clone()
-            .set_coinbase_distribution(ctx, token, vec![])
-            .await;
-        let _ = rpc_server
+            .latest_address(context::current(), token, KeyType::Generation)
+            .await
+            .unwrap();
+        let outputs = vec![(address, NativeCurrencyAmount::coins(1)).into()];
+        let result = rpc_server
             .clone()
-            .unset_coinbase_distribution(ctx, token)
+            .plan_transfer(
+                context::current(),
+                token,
+                outputs,
+                NativeCurrencyAmount::zero(),
+            )
             .await;
-        let _ = rpc_server
+        assert!(
+            matches!(
+                result,
+                Err(RpcError::TxInitiation(
+                    TxInitiationError::InsufficientFunds { .. }
+                ))
+            ),
+            "empty wallet cannot fund a payment"
+        );
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn plan_transfer_rejects_invalid_amounts() {
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(Network::Main),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
+        let address = rpc_server
             .clone()
-            .prune_abandoned_monitored_utxos(ctx, token)
-            .await;
-        let _ = rpc_server.shutdown(ctx, token).await;
+            .latest_address(context::current(), token, KeyType::Generation)
+            .await
+            .unwrap();
+        let plan = |amounts: Vec<NativeCurrencyAmount>, fee: NativeCurrencyAmount| {
+            let outputs = amounts
+                .into_iter()
+                .map(|amount| (address.clone(), amount).into())
+                .collect_vec();
+            rpc_server
+                .clone()
+                .plan_transfer(context::current(), token, outputs, fee)
+        };
 
-        Ok(())
+        let half_supply = NativeCurrencyAmount::coins(21_000_000);
+        let max_nau = NativeCurrencyAmount::from_nau(i128::MAX);
+        for (amounts, fee) in [
+            (vec![], NativeCurrencyAmount::from_nau(-1)),
+            (
+                vec![-NativeCurrencyAmount::coins(1)],
+                NativeCurrencyAmount::zero(),
+            ),
+            (vec![max_nau, max_nau], NativeCurrencyAmount::zero()),
+            (
+                vec![half_supply, half_supply],
+                NativeCurrencyAmount::coins(1),
+            ),
+        ] {
+            assert!(matches!(
+                plan(amounts, fee).await,
+                Err(RpcError::InvalidAmount(_))
+            ));
+        }
     }
 
     #[apply(shared_tokio_runtime)]
-    async fn latest_address_and_get_new_address_are_consistent() {
+    async fn validate_proposal_reports_invalid_proof_of_proposal_extending_tip() {
+        let network = Network::Main;
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(network),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
+        let proposal = invalid_empty_block1_with_guesser_fraction(network, 0.6).await;
+
+        let verdict = rpc_server
+            .clone()
+            .validate_proposal(context::current(), token, proposal)
+            .await
+            .unwrap();
+        assert!(verdict.extends_tip);
+        assert!(verdict.pays_sufficient_guesser_reward());
+        assert!(verdict.validation_error.is_some());
+        assert!(!verdict.is_acceptable());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn update_status_is_unknown_until_checked() {
         let rpc_server = test_rpc_server(
             WalletEntropy::new_random(),
             2,
@@ -4622,46 +10341,77 @@ mod tests {
         .await;
         let token = cookie_token(&rpc_server).await;
 
-        for key_type in KeyType::iter() {
-            let addr0 = rpc_server
-                .clone()
-                .latest_address(context::current(), token, key_type)
-                .await
-                .unwrap();
-            let addr1 = rpc_server
-                .clone()
-                .next_receiving_address(context::current(), token, key_type)
-                .await
-                .unwrap();
-            assert_ne!(addr0, addr1);
+        let status = rpc_server
+            .clone()
+            .update_status(context::current(), token)
+            .await
+            .unwrap();
+        assert_eq!(VERSION, status.current_version);
+        assert_eq!(UpdateNotice::Unknown, status.notice);
+        assert!(status.last_checked.is_none());
+    }
 
-            let addr1_again = rpc_server
-                .clone()
-                .latest_address(context::current(), token, key_type)
-                .await
-                .unwrap();
-            assert_eq!(addr1, addr1_again);
+    #[apply(shared_tokio_runtime)]
+    async fn standby_is_promoted_only_if_primary_is_demoted_or_forced() {
+        let cli = cli_args::Args {
+            standby_of: Some("127.0.0.1:1".parse().unwrap()),
+            ..cli_args::Args::default_with_network(Network::Main)
+        };
+        let rpc_server = test_rpc_server(WalletEntropy::new_random(), 2, cli).await;
+        let token = cookie_token(&rpc_server).await;
 
-            let addr2 = rpc_server
-                .clone()
-                .next_receiving_address(context::current(), token, key_type)
-                .await
-                .unwrap();
-            let addr2_again = rpc_server
-                .clone()
-                .latest_address(context::current(), token, key_type)
-                .await
-                .unwrap();
-            assert_eq!(addr2, addr2_again);
+        let status = rpc_server
+            .clone()
+            .replication_status(context::current(), token)
+            .await
+            .unwrap();
+        assert_eq!(ReplicationRole::Standby, status.role);
 
-            // Ensure endpoint is idempotent
-            let addr2_again_again = rpc_server
-                .clone()
-                .latest_address(context::current(), token, key_type)
-                .await
-                .unwrap();
-            assert_eq!(addr2, addr2_again_again);
-        }
+        let unforced = rpc_server
+            .clone()
+            .promote_standby(context::current(), token, false)
+            .await;
+        assert!(matches!(
+            unforced,
+            Err(error::RpcError::CannotDemotePrimary(_))
+        ));
+
+        rpc_server
+            .clone()
+            .promote_standby(context::current(), token, true)
+            .await
+            .unwrap();
+        let status = rpc_server
+            .clone()
+            .replication_status(context::current(), token)
+            .await
+            .unwrap();
+        assert!(status.is_spend_enabled());
+
+        let again = rpc_server
+            .clone()
+            .promote_standby(context::current(), token, true)
+            .await;
+        assert!(matches!(again, Err(error::RpcError::NotStandby)));
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn fork_pruning_status_reports_configured_depth() {
+        let cli = cli_args::Args {
+            fork_pruning_depth: Some(NonZero::new(100).unwrap()),
+            ..cli_args::Args::default_with_network(Network::Main)
+        };
+        let rpc_server = test_rpc_server(WalletEntropy::new_random(), 2, cli).await;
+        let token = cookie_token(&rpc_server).await;
+
+        let status = rpc_server
+            .clone()
+            .fork_pruning_status(context::current(), token)
+            .await
+            .unwrap();
+        assert_eq!(Some(100), status.depth);
+        assert_eq!(0, status.num_pruned_blocks);
+        assert_eq!(0, status.num_bytes_reclaimed);
     }
 
     #[traced_test]
@@ -5498,10 +11248,279 @@ mod tests {
         )
         .await;
         let token = cookie_token(&rpc_server).await;
-        let _current_server_temperature = rpc_server
-            .cpu_temp(context::current(), token)
+        let _current_server_temperature = rpc_server
+            .cpu_temp(context::current(), token)
+            .await
+            .unwrap();
+    }
+
+    #[traced_test]
+    #[apply(shared_tokio_runtime)]
+    async fn derivation_vectors_are_independent_of_node_wallet() {
+        let network = Network::Main;
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(network),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
+
+        let devnet = rpc_server
+            .clone()
+            .derivation_vectors(context::current(), token, None, 2)
+            .await
+            .unwrap();
+        assert_eq!(
+            DerivationVectors::derive(&WalletEntropy::devnet_wallet(), network, 2).unwrap(),
+            devnet
+        );
+
+        let from_phrase = rpc_server
+            .clone()
+            .derivation_vectors(context::current(), token, Some(devnet.phrase.clone()), 2)
+            .await
+            .unwrap();
+        assert_eq!(devnet, from_phrase);
+
+        assert!(rpc_server
+            .derivation_vectors(context::current(), token, Some(vec!["abandon".into()]), 2)
+            .await
+            .is_err());
+    }
+
+    #[traced_test]
+    #[apply(shared_tokio_runtime)]
+    async fn proof_upgrade_schedule_can_be_replaced() {
+        let cli_args = cli_args::Args {
+            proof_upgrade_max_cpu: Some(80),
+            ..cli_args::Args::default_with_network(Network::Main)
+        };
+        let rpc_server = test_rpc_server(WalletEntropy::new_random(), 2, cli_args).await;
+        let token = cookie_token(&rpc_server).await;
+
+        let status = rpc_server
+            .clone()
+            .proof_upgrade_schedule(context::current(), token)
+            .await
+            .unwrap();
+        assert_eq!(Some(80), status.schedule.max_cpu_percent);
+        assert!(status.schedule.windows.is_empty());
+
+        let paused = ProofUpgradeSchedule {
+            paused: true,
+            ..Default::default()
+        };
+        rpc_server
+            .clone()
+            .set_proof_upgrade_schedule(context::current(), token, paused.clone())
+            .await
+            .unwrap();
+        let status = rpc_server
+            .clone()
+            .proof_upgrade_schedule(context::current(), token)
+            .await
+            .unwrap();
+        assert_eq!(paused, status.schedule);
+        assert_eq!(Some(ProofUpgradeDeferral::Paused), status.deferral);
+
+        let invalid = ProofUpgradeSchedule {
+            max_cpu_percent: Some(0),
+            ..Default::default()
+        };
+        assert!(rpc_server
+            .set_proof_upgrade_schedule(context::current(), token, invalid)
+            .await
+            .is_err());
+    }
+
+    #[traced_test]
+    #[apply(shared_tokio_runtime)]
+    async fn alerts_can_be_listed_and_silenced() {
+        let cli_args = cli_args::Args {
+            alert_min_peers: Some(100),
+            ..cli_args::Args::default_with_network(Network::Main)
+        };
+        let rpc_server = test_rpc_server(WalletEntropy::new_random(), 2, cli_args).await;
+        let token = cookie_token(&rpc_server).await;
+        assert!(rpc_server
+            .clone()
+            .alerts(context::current(), token)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let until = rpc_server
+            .clone()
+            .silence_alert(
+                context::current(),
+                token,
+                AlertKind::LowPeerCount,
+                Timestamp::hours(1),
+            )
+            .await
+            .unwrap();
+        let mut state = rpc_server.state.lock_guard_mut().await;
+        let last_sequence_number = state.event_log.lock().await.last_sequence_number();
+        state.evaluate_alerts(Timestamp::now()).await;
+
+        // A silenced alert is not recorded in the event log.
+        assert_eq!(
+            last_sequence_number,
+            state.event_log.lock().await.last_sequence_number()
+        );
+        drop(state);
+
+        let alerts = rpc_server
+            .clone()
+            .alerts(context::current(), token)
+            .await
+            .unwrap();
+        assert_eq!(1, alerts.len());
+        assert_eq!(AlertKind::LowPeerCount, alerts[0].kind);
+        assert_eq!(Some(until), alerts[0].silenced_until);
+
+        rpc_server
+            .clone()
+            .silence_alert(
+                context::current(),
+                token,
+                AlertKind::LowPeerCount,
+                Timestamp::zero(),
+            )
+            .await
+            .unwrap();
+        let alerts = rpc_server.alerts(context::current(), token).await.unwrap();
+        assert_eq!(None, alerts[0].silenced_until);
+    }
+
+    #[traced_test]
+    #[apply(shared_tokio_runtime)]
+    async fn wallet_backup_status_lists_trusted_peers() {
+        let network = Network::Main;
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(network),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
+        assert!(rpc_server
+            .wallet_backup_status(context::current(), token)
+            .await
+            .is_err());
+
+        let peer_address = get_dummy_socket_address(0);
+        let cli_args = cli_args::Args {
+            wallet_backup_peers: vec![peer_address.ip()],
+            ..cli_args::Args::default_with_network(network)
+        };
+        let rpc_server = test_rpc_server(WalletEntropy::new_random(), 2, cli_args).await;
+        let token = cookie_token(&rpc_server).await;
+        let status = rpc_server
+            .clone()
+            .wallet_backup_status(context::current(), token)
+            .await
+            .unwrap();
+        assert_eq!(0, status.version);
+        assert!(status.peers.is_empty());
+
+        let peer_head = WalletBackupHead {
+            version: 3,
+            digest: rand::random(),
+        };
+        rpc_server
+            .state
+            .wallet_backup()
+            .lock()
+            .await
+            .record_peer_head(peer_address, peer_head);
+        let status = rpc_server
+            .wallet_backup_status(context::current(), token)
+            .await
+            .unwrap();
+        assert_eq!(
+            vec![WalletBackupPeer {
+                address: peer_address,
+                version: 3,
+                in_sync: false,
+            }],
+            status.peers
+        );
+    }
+
+    #[traced_test]
+    #[apply(shared_tokio_runtime)]
+    async fn clean_data_directory_rotates_large_logs() {
+        let cli_args = cli_args::Args {
+            max_log_file_size: 1,
+            ..cli_args::Args::default_with_network(Network::Main)
+        };
+        let rpc_server = test_rpc_server(WalletEntropy::new_random(), 2, cli_args).await;
+        let token = cookie_token(&rpc_server).await;
+        let audit_file = rpc_server
+            .state
+            .lock_guard()
+            .await
+            .wallet_state
+            .configuration
+            .data_directory()
+            .send_policy_audit_file_path();
+        tokio::fs::write(&audit_file, "{}\n").await.unwrap();
+
+        let report = rpc_server
+            .clean_data_directory(context::current(), token)
+            .await
+            .unwrap();
+        assert_eq!(1, report.num_logs_rotated);
+        assert_eq!(0, report.num_files_removed);
+        assert!(!audit_file.exists());
+    }
+
+    #[traced_test]
+    #[apply(shared_tokio_runtime)]
+    async fn mining_status_reports_guesser_threads_only_while_guessing() {
+        let mut rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(Network::Main),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
+        let report = rpc_server
+            .clone()
+            .mining_status(context::current(), token)
+            .await
+            .unwrap();
+        assert!(matches!(report.status, MiningStatus::Inactive));
+        assert!(report.guessing.is_none());
+        assert_eq!(0, report.reorg_cooldown.num_reorgs);
+        assert!(report.reorg_cooldown.remaining.is_none());
+        assert!(report.composer_standby.is_none());
+
+        let statistics = Arc::new(GuesserStatistics::new(3));
+        statistics.record_guesses(1, 10);
+        let genesis = Block::genesis(Network::Main);
+        rpc_server
+            .state
+            .set_mining_status_to_guessing(&genesis)
+            .await;
+        rpc_server
+            .state
+            .lock_guard_mut()
+            .await
+            .mining_state
+            .guesser_statistics = Some(statistics);
+
+        let guessing = rpc_server
+            .mining_status(context::current(), token)
             .await
+            .unwrap()
+            .guessing
             .unwrap();
+        assert_eq!(3, guessing.threads.len());
+        assert_eq!(10, guessing.threads[1].num_guesses);
+        assert_eq!(0, guessing.num_overlaps);
     }
 
     #[traced_test]
@@ -5537,6 +11556,92 @@ mod tests {
             .is_err());
     }
 
+    #[apply(shared_tokio_runtime)]
+    async fn spends_are_refused_until_spending_pin_is_entered() {
+        use crate::application::rpc::spending_pin::error::SpendingPinError;
+        use crate::application::rpc::spending_pin::SpendingPinGuard;
+        use crate::application::rpc::spending_pin::SpendingPinKey;
+
+        let network = Network::Main;
+        let ctx = context::current();
+        let rpc_server = test_rpc_server(
+            WalletEntropy::new_random(),
+            2,
+            cli_args::Args::default_with_network(network),
+        )
+        .await;
+        let token = cookie_token(&rpc_server).await;
+        let audit_file = rpc_server.data_directory.spending_pin_audit_file_path();
+        rpc_server.state.lock_guard_mut().await.spending_pin = Arc::new(SpendingPinGuard::new(
+            Some(SpendingPinKey::generate("1234")),
+            audit_file.clone(),
+        ));
+
+        let address = GenerationSpendingKey::derive_from_seed(rand::rng().random()).to_address();
+        let send = || {
+            rpc_server.clone().send(
+                ctx,
+                token,
+                vec![(address.into(), NativeCurrencyAmount::coins(1)).into()],
+                ChangePolicy::ExactChange,
+                NativeCurrencyAmount::zero(),
+            )
+        };
+        assert!(matches!(
+            send().await,
+            Err(error::RpcError::SpendingPin(SpendingPinError::Locked))
+        ));
+        assert!(matches!(
+            rpc_server
+                .clone()
+                .generate_tx_details(
+                    ctx,
+                    token,
+                    TxInputList::default(),
+                    TxOutputList::default(),
+                    ChangePolicy::ExactChange,
+                    NativeCurrencyAmount::zero(),
+                )
+                .await,
+            Err(error::RpcError::SpendingPin(SpendingPinError::Locked))
+        ));
+
+        let challenge = rpc_server
+            .clone()
+            .spending_pin_challenge(ctx, token)
+            .await
+            .unwrap();
+        let response = SpendingPinKey::derive("0000", challenge.salt).respond(&challenge);
+        assert!(matches!(
+            rpc_server
+                .clone()
+                .unlock_spending(ctx, token, response)
+                .await,
+            Err(error::RpcError::SpendingPin(SpendingPinError::WrongPin))
+        ));
+
+        let challenge = rpc_server
+            .clone()
+            .spending_pin_challenge(ctx, token)
+            .await
+            .unwrap();
+        let response = SpendingPinKey::derive("1234", challenge.salt).respond(&challenge);
+        rpc_server
+            .clone()
+            .unlock_spending(ctx, token, response)
+            .await
+            .unwrap();
+
+        // The wallet has no funds, but the spend gets past the PIN.
+        assert!(!matches!(
+            send().await,
+            Err(error::RpcError::SpendingPin(_))
+        ));
+
+        let audit = tokio::fs::read_to_string(&audit_file).await.unwrap();
+        assert_eq!(5, audit.lines().count());
+    }
+
     #[apply(shared_tokio_runtime)]
     async fn coinbase_distribution_happy_path() {
         let network = Network::Main;
@@ -5657,6 +11762,68 @@ mod tests {
             .is_err());
     }
 
+    #[apply(shared_tokio_runtime)]
+    async fn utxo_membership_bundle_devnet_wallet() {
+        let ctx = context::current();
+        let rpc_server =
+            test_rpc_server(WalletEntropy::devnet_wallet(), 2, cli_args::Args::default()).await;
+        let token = cookie_token(&rpc_server).await;
+
+        let utxo = rpc_server
+            .state
+            .lock_guard()
+            .await
+            .wallet_spendable_inputs(Timestamp::now())
+            .await
+            .into_iter()
+            .collect_vec()[0]
+            .clone();
+        let msmp = utxo.mutator_set_mp().clone();
+        let item = Tip5::hash(&utxo.utxo);
+        let unknown = AdditionRecord::new(Digest::default());
+
+        let bundle = rpc_server
+            .clone()
+            .utxo_membership_bundle(ctx, token, vec![msmp.addition_record(item), unknown])
+            .await
+            .unwrap();
+        assert_eq!(BlockHeight::genesis(), bundle.tip_height);
+        assert_eq!(vec![unknown], bundle.missing);
+        assert_eq!(
+            msmp,
+            bundle
+                .membership_proof(item, msmp.sender_randomness, msmp.receiver_preimage)
+                .unwrap()
+        );
+
+        let too_many = vec![unknown; MAX_MEMBERSHIP_BUNDLE_SIZE + 1];
+        assert!(rpc_server
+            .clone()
+            .utxo_membership_bundle(ctx, token, too_many)
+            .await
+            .is_err());
+
+        let utxo_triple = UtxoTriple {
+            utxo: utxo.utxo.clone(),
+            sender_randomness: msmp.sender_randomness,
+            receiver_digest: msmp.receiver_preimage.hash(),
+        };
+        let mut stale_bundle = bundle.clone();
+        stale_bundle.tip_hash = Digest::default();
+        assert!(rpc_server
+            .clone()
+            .import_membership_bundle(ctx, token, stale_bundle, vec![utxo_triple.clone()])
+            .await
+            .is_err());
+        assert_eq!(
+            1,
+            rpc_server
+                .import_membership_bundle(ctx, token, bundle, vec![utxo_triple])
+                .await
+                .unwrap()
+        );
+    }
+
     mod pow_puzzle_tests {
         use rand::random;
 
@@ -6027,6 +12194,81 @@ mod tests {
             worker::claim_utxo_unowned(true).await
         }
 
+        #[traced_test]
+        #[apply(shared_tokio_runtime)]
+        async fn import_utxo_claim_of_mined_composer_payout() -> Result<()> {
+            let network = Network::Main;
+            let bob_wallet = WalletEntropy::new_random();
+            let mut bob = test_rpc_server(
+                bob_wallet.clone(),
+                2,
+                cli_args::Args::default_with_network(network),
+            )
+            .await;
+            let bob_token = cookie_token(&bob).await;
+            bob.clone()
+                .next_receiving_address(context::current(), bob_token, KeyType::Generation)
+                .await?;
+
+            // the pool composes a block paying bob, and notifies bob off-chain.
+            let bob_key = bob_wallet.nth_generation_spending_key(0);
+            let genesis_block = Block::genesis(network);
+            let (block1, payouts) =
+                make_mock_block(&genesis_block, None, bob_key, rand::random(), network).await;
+            let utxo_triples = payouts
+                .iter()
+                .map(|payout| UtxoTriple {
+                    utxo: payout.utxo.clone(),
+                    sender_randomness: payout.sender_randomness,
+                    receiver_digest: payout.receiver_preimage.hash(),
+                })
+                .collect_vec();
+
+            // claims of unmined UTXOs are rejected.
+            let unmined = bob
+                .clone()
+                .import_utxo_claim(context::current(), bob_token, utxo_triples[0].clone(), None)
+                .await;
+            assert!(unmined.is_err());
+
+            bob.state.set_new_tip(block1).await?;
+            for utxo_triple in &utxo_triples {
+                assert!(
+                    bob.clone()
+                        .import_utxo_claim(context::current(), bob_token, utxo_triple.clone(), None)
+                        .await?
+                );
+                assert!(bob
+                    .state
+                    .lock_guard()
+                    .await
+                    .wallet_state
+                    .find_monitored_utxo(&utxo_triple.utxo, utxo_triple.sender_randomness)
+                    .await
+                    .is_some());
+            }
+
+            // importing again has no effect.
+            assert!(
+                !bob.clone()
+                    .import_utxo_claim(context::current(), bob_token, utxo_triples[0].clone(), None)
+                    .await?
+            );
+
+            // claims for foreign keys are rejected.
+            let foreign = UtxoTriple {
+                receiver_digest: rand::random(),
+                ..utxo_triples[0].clone()
+            };
+            assert!(bob
+                .clone()
+                .import_utxo_claim(context::current(), bob_token, foreign, None)
+                .await
+                .is_err());
+
+            Ok(())
+        }
+
         mod worker {
             use cli_args::Args;
 
@@ -6411,8 +12653,11 @@ mod tests {
     mod send_tests {
         use super::*;
         use crate::api::export::TxProvingCapability;
+        use crate::api::tx_initiation::error::CreateTxError;
+        use crate::api::tx_initiation::error::SendError;
         use crate::application::rpc::server::error::RpcError;
         use crate::tests::shared::blocks::mine_block_to_wallet_invalid_block_proof;
+        use crate::twenty_first::bfe_vec;
 
         #[traced_test]
         #[apply(shared_tokio_runtime)]
@@ -6513,7 +12758,8 @@ mod tests {
                     0..2 => assert!(result.is_ok()),
                     _ => assert!(matches!(
                         result,
-                        Err(RpcError::SendError(s)) if s.contains("Send rate limit reached")
+                        Err(RpcError::TxInitiation(TxInitiationError::Other(s)))
+                            if s.contains("Send rate limit reached")
                     )),
                 }
             }
@@ -6521,6 +12767,256 @@ mod tests {
             Ok(())
         }
 
+        #[traced_test]
+        #[apply(shared_tokio_runtime)]
+        async fn send_with_inputs_spends_exactly_the_selected_utxos() -> Result<()> {
+            let network = Network::Main;
+            let cli_args = cli_args::Args {
+                tx_proving_capability: Some(TxProvingCapability::ProofCollection),
+                network,
+                ..Default::default()
+            };
+            let mut rpc_server = test_rpc_server(WalletEntropy::devnet_wallet(), 2, cli_args).await;
+            let ctx = context::current();
+            let token = cookie_token(&rpc_server).await;
+            let timestamp = network.launch_date() + Timestamp::months(7);
+            mine_block_to_wallet_invalid_block_proof(&mut rpc_server.state, Some(timestamp))
+                .await?;
+
+            let now = Timestamp::now();
+            let unspent = rpc_server
+                .state
+                .lock_guard()
+                .await
+                .get_wallet_status_for_tip()
+                .await
+                .synced_unspent;
+            let (liquid, _) = unspent
+                .iter()
+                .find(|(element, _)| element.utxo.can_spend_at(now))
+                .unwrap();
+            let (time_locked, _) = unspent
+                .iter()
+                .find(|(element, _)| !element.utxo.can_spend_at(now))
+                .unwrap();
+            let available = liquid.utxo.get_native_currency_amount();
+
+            let address: ReceivingAddress =
+                GenerationSpendingKey::derive_from_seed(StdRng::seed_from_u64(1815).random())
+                    .to_address()
+                    .into();
+            let fee = NativeCurrencyAmount::coins(1);
+            let send = |aocl_leaf_indices: Vec<u64>, amount: NativeCurrencyAmount| {
+                let output: OutputFormat =
+                    (address.clone(), amount, UtxoNotificationMedium::OnChain).into();
+                let mut state = rpc_server.state.clone();
+                async move {
+                    state
+                        .api_mut()
+                        .tx_sender_mut()
+                        .send_with_inputs(
+                            vec![output],
+                            &aocl_leaf_indices,
+                            ChangePolicy::Burn,
+                            fee,
+                            now,
+                        )
+                        .await
+                }
+            };
+            let amount = NativeCurrencyAmount::coins(1);
+
+            assert!(matches!(
+                send(vec![u64::MAX], amount).await,
+                Err(SendError::Tx(CreateTxError::UnknownInput {
+                    aocl_leaf_index: u64::MAX
+                }))
+            ));
+            assert!(matches!(
+                send(vec![liquid.aocl_leaf_index; 2], amount).await,
+                Err(SendError::Tx(CreateTxError::DuplicateInput { .. }))
+            ));
+            assert!(matches!(
+                send(vec![time_locked.aocl_leaf_index], amount).await,
+                Err(SendError::Tx(CreateTxError::TimeLockedInput { .. }))
+            ));
+            assert!(matches!(
+                send(vec![liquid.aocl_leaf_index], available).await,
+                Err(SendError::Tx(CreateTxError::InsufficientFunds { .. }))
+            ));
+
+            let output: OutputFormat = (address, amount, UtxoNotificationMedium::OnChain).into();
+            let artifacts = rpc_server
+                .clone()
+                .send_with_inputs(
+                    ctx,
+                    token,
+                    vec![output.clone()],
+                    vec![liquid.aocl_leaf_index],
+                    ChangePolicy::Burn,
+                    fee,
+                )
+                .await?;
+            let spent = artifacts
+                .details()
+                .tx_inputs
+                .iter()
+                .map(|input| input.mutator_set_mp().aocl_leaf_index)
+                .collect::<Vec<_>>();
+            assert_eq!(vec![liquid.aocl_leaf_index], spent);
+
+            // The input is now spent by a transaction in the mempool.
+            assert!(matches!(
+                send(vec![liquid.aocl_leaf_index], amount).await,
+                Err(SendError::Tx(CreateTxError::InputSpentInMempool { .. }))
+            ));
+
+            Ok(())
+        }
+
+        #[traced_test]
+        #[apply(shared_tokio_runtime)]
+        async fn send_raw_transaction_builds_the_specified_transaction() -> Result<()> {
+            let network = Network::Main;
+            let cli_args = cli_args::Args {
+                tx_proving_capability: Some(TxProvingCapability::ProofCollection),
+                network,
+                ..Default::default()
+            };
+            let mut rpc_server = test_rpc_server(WalletEntropy::devnet_wallet(), 2, cli_args).await;
+            let ctx = context::current();
+            let token = cookie_token(&rpc_server).await;
+            let timestamp = network.launch_date() + Timestamp::months(7);
+            mine_block_to_wallet_invalid_block_proof(&mut rpc_server.state, Some(timestamp))
+                .await?;
+
+            let now = Timestamp::now();
+            let aocl_leaf_index = rpc_server
+                .state
+                .lock_guard()
+                .await
+                .get_wallet_status_for_tip()
+                .await
+                .synced_unspent
+                .iter()
+                .find(|(element, _)| element.utxo.can_spend_at(now))
+                .unwrap()
+                .0
+                .aocl_leaf_index;
+            let address: ReceivingAddress =
+                GenerationSpendingKey::derive_from_seed(StdRng::seed_from_u64(1815).random())
+                    .to_address()
+                    .into();
+            let spec = |timestamp: Timestamp| {
+                serde_json::json!({
+                    "inputs": [aocl_leaf_index],
+                    "outputs": [{
+                        "address": address.to_bech32m(network).unwrap(),
+                        "amount": "1",
+                    }],
+                    "fee": "0.5",
+                    "timestamp": timestamp.to_millis(),
+                    "announcements": [[1, 2, 3]],
+                })
+                .to_string()
+            };
+
+            let send = |spec: String| rpc_server.clone().send_raw_transaction(ctx, token, spec);
+            assert!(send("{".to_string()).await.is_err());
+            assert!(send(spec(now + Timestamp::hours(1))).await.is_err());
+
+            let artifacts = send(spec(now)).await?;
+            let kernel = &artifacts.transaction().kernel;
+            assert_eq!(now, kernel.timestamp);
+            assert_eq!(NativeCurrencyAmount::coins_from_str("0.5")?, kernel.fee);
+            assert!(kernel
+                .announcements
+                .contains(&Announcement::new(bfe_vec![1, 2, 3])));
+            let spent = artifacts
+                .details()
+                .tx_inputs
+                .iter()
+                .map(|input| input.mutator_set_mp().aocl_leaf_index)
+                .collect::<Vec<_>>();
+            assert_eq!(vec![aocl_leaf_index], spent);
+
+            Ok(())
+        }
+
+        #[traced_test]
+        #[apply(shared_tokio_runtime)]
+        async fn cancel_transaction_removes_unproven_own_transaction() -> Result<()> {
+            let network = Network::Main;
+            let cli_args = cli_args::Args {
+                tx_proving_capability: Some(TxProvingCapability::ProofCollection),
+                network,
+                ..Default::default()
+            };
+            let mut rpc_server = test_rpc_server(WalletEntropy::devnet_wallet(), 2, cli_args).await;
+            let ctx = context::current();
+            let token = cookie_token(&rpc_server).await;
+            let timestamp = network.launch_date() + Timestamp::months(7);
+            mine_block_to_wallet_invalid_block_proof(&mut rpc_server.state, Some(timestamp))
+                .await?;
+
+            let now = Timestamp::now();
+            let aocl_leaf_index = rpc_server
+                .state
+                .lock_guard()
+                .await
+                .get_wallet_status_for_tip()
+                .await
+                .synced_unspent
+                .iter()
+                .find(|(element, _)| element.utxo.can_spend_at(now))
+                .unwrap()
+                .0
+                .aocl_leaf_index;
+            let address: ReceivingAddress =
+                GenerationSpendingKey::derive_from_seed(StdRng::seed_from_u64(1816).random())
+                    .to_address()
+                    .into();
+            let spec = serde_json::json!({
+                "inputs": [aocl_leaf_index],
+                "outputs": [{
+                    "address": address.to_bech32m(network).unwrap(),
+                    "amount": "1",
+                }],
+                "fee": "0.5",
+                "timestamp": now.to_millis(),
+            })
+            .to_string();
+
+            assert!(rpc_server
+                .clone()
+                .cancel_transaction(ctx, token, TransactionKernelId::default())
+                .await
+                .is_err());
+
+            let artifacts = rpc_server
+                .clone()
+                .send_raw_transaction(ctx, token, spec)
+                .await?;
+            let txid = artifacts.transaction().txid();
+            assert!(rpc_server.state.lock_guard().await.mempool.contains(txid));
+
+            rpc_server
+                .clone()
+                .cancel_transaction(ctx, token, txid)
+                .await?;
+            assert!(!rpc_server.state.lock_guard().await.mempool.contains(txid));
+            assert!(
+                rpc_server
+                    .clone()
+                    .cancel_transaction(ctx, token, txid)
+                    .await
+                    .is_err(),
+                "can only cancel once"
+            );
+
+            Ok(())
+        }
+
         mod worker {
             use super::*;
             use crate::state::wallet::address::generation_address::GenerationReceivingAddress;
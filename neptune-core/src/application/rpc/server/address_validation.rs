@@ -0,0 +1,137 @@
+use rayon::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Digest;
+
+use crate::application::config::network::Network;
+use crate::state::wallet::address::KeyType;
+use crate::state::wallet::address::ReceivingAddress;
+
+/// Maximum number of addresses validated in a single call.
+pub const MAX_ADDRESSES_PER_VALIDATION: usize = 10_000;
+
+/// The verdict on a user-supplied address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AddressVerdict {
+    /// A well-formed address of an accepted type.
+    Valid(KeyType),
+
+    /// Not a well-formed address for any network.
+    Malformed,
+
+    /// A well-formed address for another network.
+    WrongNetwork,
+
+    /// A well-formed address of a type that was not accepted.
+    UnacceptedKeyType(KeyType),
+
+    /// A well-formed address that no one can spend from. Funds sent to it are
+    /// lost.
+    KnownBurn(KeyType),
+}
+
+impl AddressVerdict {
+    /// Whether funds can safely be sent to the address.
+    pub fn is_valid(&self) -> bool {
+        matches!(self, Self::Valid(_))
+    }
+
+    /// Validate an address for the given network. An empty list of accepted
+    /// key types accepts all of them.
+    pub(crate) fn of(address: &str, network: Network, accepted_key_types: &[KeyType]) -> Self {
+        let Ok(address) = ReceivingAddress::from_bech32m(address, network) else {
+            return if ReceivingAddress::is_bech32m_for_other_network(address, network) {
+                Self::WrongNetwork
+            } else {
+                Self::Malformed
+            };
+        };
+
+        let key_type = KeyType::from(&address);
+        if is_burn_spending_lock(address.spending_lock()) {
+            Self::KnownBurn(key_type)
+        } else if !accepted_key_types.is_empty() && !accepted_key_types.contains(&key_type) {
+            Self::UnacceptedKeyType(key_type)
+        } else {
+            Self::Valid(key_type)
+        }
+    }
+}
+
+/// Validate addresses in parallel. The verdicts are in the order of the
+/// addresses.
+pub(crate) fn validate_addresses(
+    addresses: &[String],
+    network: Network,
+    accepted_key_types: &[KeyType],
+) -> Vec<AddressVerdict> {
+    addresses
+        .par_iter()
+        .map(|address| AddressVerdict::of(address, network, accepted_key_types))
+        .collect()
+}
+
+/// Whether the spending lock matches a pattern that has no known preimage.
+///
+/// A spending lock is a hash digest. Digests whose elements are all equal, eg
+/// all zero, were picked by hand rather than derived from a secret.
+fn is_burn_spending_lock(spending_lock: Digest) -> bool {
+    let [first, rest @ ..] = spending_lock.values();
+    rest.iter().all(|element| *element == first)
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use rand::random;
+    use tasm_lib::prelude::Tip5;
+    use tasm_lib::triton_vm::prelude::BFieldElement;
+
+    use super::*;
+    use crate::state::wallet::address::generation_address::GenerationReceivingAddress;
+    use crate::state::wallet::address::symmetric_key::SymmetricKey;
+
+    #[test]
+    fn verdicts_are_in_order_of_addresses() {
+        let network = Network::Main;
+        let generation =
+            ReceivingAddress::from(GenerationReceivingAddress::derive_from_seed(random()));
+        let symmetric = ReceivingAddress::from(SymmetricKey::from_seed(random()));
+        let addresses = vec![
+            generation.to_bech32m(network).unwrap(),
+            symmetric.to_bech32m(network).unwrap(),
+            generation.to_bech32m(Network::Testnet(0)).unwrap(),
+            "nolgam1notanaddress".to_string(),
+        ];
+
+        assert_eq!(
+            vec![
+                AddressVerdict::Valid(KeyType::Generation),
+                AddressVerdict::Valid(KeyType::Symmetric),
+                AddressVerdict::WrongNetwork,
+                AddressVerdict::Malformed,
+            ],
+            validate_addresses(&addresses, network, &[])
+        );
+        assert_eq!(
+            vec![
+                AddressVerdict::Valid(KeyType::Generation),
+                AddressVerdict::UnacceptedKeyType(KeyType::Symmetric),
+                AddressVerdict::WrongNetwork,
+                AddressVerdict::Malformed,
+            ],
+            validate_addresses(&addresses, network, &[KeyType::Generation])
+        );
+    }
+
+    #[test]
+    fn hand_picked_spending_locks_are_burns() {
+        assert!(is_burn_spending_lock(Digest::default()));
+        assert!(is_burn_spending_lock(Digest::new(
+            [BFieldElement::new(7); Digest::LEN]
+        )));
+        assert!(!is_burn_spending_lock(Tip5::hash(&BFieldElement::new(
+            random()
+        ))));
+    }
+}
@@ -0,0 +1,404 @@
+//! A bundle of diagnostics to attach to bug reports.
+//!
+//! The [`DiagnosticsBundle`] collects what is usually asked for when a node
+//! misbehaves: versions, a summary of the configuration, the peer table, the
+//! tip, recent warnings and errors, and resource usage.
+//!
+//! Bundles are meant to be shared. They never contain wallet data, and values
+//! that are secret or reveal the local file system are redacted from the
+//! configuration, see [`REDACTED_CONFIG_KEYS`], and the paths of the data
+//! directory and the home directory from all other configuration values and
+//! from log lines. Network addresses, of peers
+//! and of this node, are redacted too unless the caller opts in, see
+//! [`ADDRESS_CONFIG_KEYS`]. Every bundle lists what was redacted from it.
+
+use std::net::IpAddr;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use directories::BaseDirs;
+use regex::Captures;
+use regex::Regex;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::application::config::network::Network;
+use crate::application::crash_report;
+use crate::application::crash_report::TipSummary;
+use crate::application::loops::task_supervisor::TaskStatus;
+use crate::application::safe_mode::SafeModeStatus;
+use crate::protocol::peer::peer_info::PeerInfo;
+use crate::protocol::peer::peer_latency::PeerLatency;
+use crate::protocol::peer::InstanceId;
+use crate::protocol::peer::PeerStanding;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+use crate::state::load_shedding::LoadSheddingStatus;
+use crate::state::GlobalState;
+
+/// Replaces redacted values.
+pub const REDACTED: &str = "<redacted>";
+
+/// Configuration keys whose values are always redacted, as they are secret or
+/// reveal the local file system.
+pub const REDACTED_CONFIG_KEYS: &[&str] = &[
+    "data_dir",
+    "import_blocks_from_directory",
+    "block_notify",
+    "plugin_hook",
+    "denied_addresses_file",
+    "spending_pin_file",
+    "release_manifest_url",
+    "replication_secret_file",
+    "shared_block_dir",
+    "composer_lease_dir",
+    "excluded_announcements_file",
+];
+
+/// Configuration keys holding network addresses, whose values are redacted
+/// unless addresses are included.
+pub const ADDRESS_CONFIG_KEYS: &[&str] = &[
+    "peer",
+    "peer_listen_addr",
+    "listen_rpc",
+    "ban",
+    "wallet_backup_peer",
+    "whitelisted_composer",
+    "replication_listen_addr",
+    "standby_of",
+];
+
+/// Number of most recent warnings and errors included in a bundle.
+const MAX_RECENT_ERRORS: usize = 50;
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// What was withheld from a [`DiagnosticsBundle`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Redactions {
+    /// The configuration keys whose values were replaced by [`REDACTED`].
+    pub config_keys: Vec<String>,
+
+    /// Whether network addresses were redacted from the configuration, the
+    /// peer table, and the log lines.
+    pub addresses: bool,
+}
+
+/// A connected peer, as described in a [`DiagnosticsBundle`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PeerDiagnostics {
+    /// The address of the connection, or `None` if redacted.
+    pub address: Option<SocketAddr>,
+    pub inbound: bool,
+
+    /// The version the peer claims to run.
+    pub version: String,
+    pub is_archival_node: bool,
+    pub connection_established: SystemTime,
+    pub standing: PeerStanding,
+    pub latency: Option<PeerLatency>,
+}
+
+impl PeerDiagnostics {
+    fn new(peer: &PeerInfo, include_addresses: bool) -> Self {
+        Self {
+            address: include_addresses.then(|| peer.connected_address()),
+            inbound: peer.connection_is_inbound(),
+            version: peer.version().to_string(),
+            is_archival_node: peer.is_archival_node(),
+            connection_established: peer.connection_established(),
+            standing: peer.standing(),
+            latency: peer.latency(),
+        }
+    }
+}
+
+/// Information about a node for support requests, with sensitive values
+/// redacted.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DiagnosticsBundle {
+    pub created: Timestamp,
+    pub version: String,
+
+    /// The operating system and CPU architecture, as `os/arch`.
+    pub platform: String,
+    pub network: Network,
+    pub instance_id: InstanceId,
+
+    /// The settings the node was started with, as a TOML configuration file.
+    /// `None` if the node was not started from the command line.
+    pub config: Option<String>,
+    pub tip: TipSummary,
+    pub tip_timestamp: Timestamp,
+
+    /// Whether the node is synchronizing blocks from peers.
+    pub syncing: bool,
+    pub peers: Vec<PeerDiagnostics>,
+
+    /// Whether the node is shedding load, and the most recently sampled
+    /// resource usage.
+    pub load_shedding: LoadSheddingStatus,
+    pub safe_mode: Option<SafeModeStatus>,
+
+    /// The states of the long-running tasks. Crash report bundles are named
+    /// relative to the crash reports directory.
+    pub tasks: Vec<TaskStatus>,
+
+    /// The most recent warnings and errors logged, oldest first. Only
+    /// available if the logger keeps recent log lines.
+    pub recent_errors: Vec<String>,
+    pub redactions: Redactions,
+}
+
+impl DiagnosticsBundle {
+    /// Collect diagnostics from the node's state. Network addresses are
+    /// redacted unless `include_addresses` is set.
+    pub(crate) fn collect(state: &GlobalState, include_addresses: bool) -> Self {
+        let address_candidates = address_candidates();
+        let data_directory = state
+            .wallet_state
+            .configuration
+            .data_directory()
+            .root_dir_path();
+        let paths = local_paths(Some(data_directory));
+        let redact = |text: String| {
+            let text = redact_paths(&text, &paths);
+            if include_addresses {
+                text
+            } else {
+                redact_addresses(&text, &address_candidates)
+            }
+        };
+
+        let mut redacted_config_keys = vec![];
+        let config = state.cli().effective_config.as_deref().map(|config| {
+            redact_config(config, include_addresses, &paths, &mut redacted_config_keys)
+        });
+
+        let tip = state.chain.light_state();
+        let mut peers = state
+            .net
+            .peer_map
+            .values()
+            .map(|peer| PeerDiagnostics::new(peer, include_addresses))
+            .collect::<Vec<_>>();
+        peers.sort_by_key(|peer| peer.connection_established);
+
+        let tasks = state
+            .task_statuses
+            .statuses()
+            .into_iter()
+            .map(|mut status| {
+                status.last_failure = status.last_failure.map(redact);
+                status.last_crash_report = status
+                    .last_crash_report
+                    .and_then(|path| path.file_name().map(PathBuf::from));
+                status
+            })
+            .collect();
+
+        let recent_errors = crash_report::recent_logs()
+            .into_iter()
+            .filter(|line| line.contains("ERROR") || line.contains("WARN"))
+            .collect::<Vec<_>>();
+        let num_older_errors = recent_errors.len().saturating_sub(MAX_RECENT_ERRORS);
+        let recent_errors = recent_errors
+            .into_iter()
+            .skip(num_older_errors)
+            .map(redact)
+            .collect();
+
+        Self {
+            created: Timestamp::now(),
+            version: VERSION.to_string(),
+            platform: format!("{}/{}", std::env::consts::OS, std::env::consts::ARCH),
+            network: state.cli().network,
+            instance_id: state.net.instance_id,
+            config,
+            tip: TipSummary {
+                height: tip.header().height,
+                digest: tip.hash(),
+            },
+            tip_timestamp: tip.header().timestamp,
+            syncing: state.net.sync_anchor.is_some(),
+            peers,
+            load_shedding: state.load_shedding.status(),
+            safe_mode: state.safe_mode,
+            tasks,
+            recent_errors,
+            redactions: Redactions {
+                config_keys: redacted_config_keys,
+                addresses: !include_addresses,
+            },
+        }
+    }
+}
+
+/// Replace the values of the redacted keys in a TOML configuration, and the
+/// given `paths` in all other values, and record which keys were redacted. A
+/// configuration that cannot be parsed is withheld entirely.
+pub(crate) fn redact_config(
+    config: &str,
+    include_addresses: bool,
+    paths: &[String],
+    redacted_keys: &mut Vec<String>,
+) -> String {
+    fn redact_value_paths(value: &mut toml::Value, paths: &[String]) {
+        match value {
+            toml::Value::String(text) => *text = redact_paths(text, paths),
+            toml::Value::Array(values) => {
+                for value in values {
+                    redact_value_paths(value, paths);
+                }
+            }
+            toml::Value::Table(table) => {
+                for value in table.values_mut() {
+                    redact_value_paths(value, paths);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let Ok(mut table) = config.parse::<toml::Table>() else {
+        return REDACTED.to_string();
+    };
+
+    for (key, value) in table.iter_mut() {
+        let is_redacted = REDACTED_CONFIG_KEYS.contains(&key.as_str())
+            || (!include_addresses && ADDRESS_CONFIG_KEYS.contains(&key.as_str()));
+        if is_redacted {
+            *value = toml::Value::String(REDACTED.to_string());
+            redacted_keys.push(key.clone());
+        } else {
+            redact_value_paths(value, paths);
+        }
+    }
+
+    toml::to_string(&table).unwrap_or_else(|_| REDACTED.to_string())
+}
+
+/// The paths of the given data directory and of the home directory, as
+/// redacted by [`redact_paths`].
+pub(crate) fn local_paths(data_directory: Option<PathBuf>) -> Vec<String> {
+    let home_directory = BaseDirs::new().map(|dirs| dirs.home_dir().to_path_buf());
+    redacted_paths([data_directory, home_directory].into_iter().flatten())
+}
+
+/// The given paths as they appear in log lines, longest first, so that a
+/// path is redacted before any directory containing it. The root directory
+/// is skipped, as redacting it would garble every path.
+fn redacted_paths(paths: impl IntoIterator<Item = PathBuf>) -> Vec<String> {
+    let mut paths = paths
+        .into_iter()
+        .filter(|path| path.parent().is_some())
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>();
+    paths.sort_by_key(|path| std::cmp::Reverse(path.len()));
+    paths
+}
+
+/// Replace the occurrences of the given paths in `text` by [`REDACTED`].
+fn redact_paths(text: &str, paths: &[String]) -> String {
+    paths.iter().fold(text.to_string(), |text, path| {
+        text.replace(path.as_str(), REDACTED)
+    })
+}
+
+/// Matches the stretches of text that may be IP or socket addresses.
+fn address_candidates() -> Regex {
+    Regex::new(r"[0-9A-Fa-f:.\[\]]+").unwrap()
+}
+
+/// Replace the IP and socket addresses in `text` by [`REDACTED`].
+///
+/// Only whole words that contain a digit are considered, so that eg the
+/// `::` of module paths is kept.
+fn redact_addresses(text: &str, candidates: &Regex) -> String {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    candidates
+        .replace_all(text, |captures: &Captures| {
+            let found = captures.get(0).unwrap();
+            let candidate = found.as_str();
+            let trimmed = candidate.trim_end_matches(['.', ':']);
+            let is_whole_word = !text[..found.start()].ends_with(is_word_char)
+                && !text[found.end()..].starts_with(is_word_char);
+            let is_address =
+                trimmed.parse::<IpAddr>().is_ok() || trimmed.parse::<SocketAddr>().is_ok();
+            if is_whole_word && trimmed.contains(|c: char| c.is_ascii_digit()) && is_address {
+                format!("{REDACTED}{}", &candidate[trimmed.len()..])
+            } else {
+                candidate.to_string()
+            }
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn addresses_are_redacted_from_text() {
+        let candidates = address_candidates();
+        let line = "2026-10-15T12:34:56.789Z  WARN neptune_cash::application: Failed to \
+                    connect to 8.8.8.8:9798, [::1]:9799 and 2001:db8::1. Block abcd00 at \
+                    height 1.5";
+        assert_eq!(
+            "2026-10-15T12:34:56.789Z  WARN neptune_cash::application: Failed to \
+             connect to <redacted>, <redacted> and <redacted>. Block abcd00 at \
+             height 1.5",
+            redact_addresses(line, &candidates)
+        );
+    }
+
+    #[test]
+    fn paths_are_redacted_from_text() {
+        let paths = redacted_paths([
+            PathBuf::from("/home/alice"),
+            PathBuf::from("/home/alice/.local/share/neptune/main"),
+            PathBuf::from("/"),
+        ]);
+        let line = "2026-10-15T12:34:56.789Z  WARN neptune_cash::state: Could not spill \
+                    to /home/alice/.local/share/neptune/main/mempool: disk full, and not \
+                    back up to /home/alice/backups either";
+        assert_eq!(
+            "2026-10-15T12:34:56.789Z  WARN neptune_cash::state: Could not spill \
+             to <redacted>/mempool: disk full, and not back up to \
+             <redacted>/backups either",
+            redact_paths(line, &paths)
+        );
+    }
+
+    #[test]
+    fn secrets_are_always_redacted_from_config() {
+        let config = r#"
+            data_dir = "/home/alice/.neptune"
+            spending_pin_file = "/home/alice/pin"
+            plugin_hook = ["alert=/home/alice/bin/on-alert"]
+            peer = ["8.8.8.8:9798"]
+            max_num_peers = "10"
+            other_file = ["/home/alice/notify.log"]
+        "#;
+        let paths = redacted_paths([PathBuf::from("/home/alice")]);
+
+        let mut redacted_keys = vec![];
+        let redacted = redact_config(config, false, &paths, &mut redacted_keys);
+        assert!(!redacted.contains("alice"));
+        assert!(!redacted.contains("8.8.8.8"));
+        assert!(redacted.contains("max_num_peers = \"10\""));
+        assert!(redacted.contains("<redacted>/notify.log"));
+        redacted_keys.sort();
+        assert_eq!(
+            vec!["data_dir", "peer", "plugin_hook", "spending_pin_file"],
+            redacted_keys
+        );
+
+        let mut redacted_keys = vec![];
+        let redacted = redact_config(config, true, &paths, &mut redacted_keys);
+        assert!(!redacted.contains("alice"));
+        assert!(redacted.contains("8.8.8.8"));
+        assert_eq!(3, redacted_keys.len());
+    }
+}
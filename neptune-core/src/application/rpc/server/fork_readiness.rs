@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+
+use serde::Deserialize;
+use serde::Serialize;
+use strum::IntoEnumIterator;
+
+use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::protocol::consensus::consensus_rule_set::ConsensusRuleSet;
+use crate::protocol::peer::fork_signaling;
+use crate::state::GlobalState;
+
+/// The readiness of the network for a consensus rule set, as signaled by
+/// peers.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RuleSetReadiness {
+    /// The name of the rule set.
+    pub rule_set: String,
+
+    /// The height at which the rule set activates on this network, or `None`
+    /// if this node does not know of the rule set.
+    pub activation_height: Option<BlockHeight>,
+
+    /// Whether this node signals readiness for the rule set.
+    pub signaled_by_self: bool,
+
+    /// Number of connected peers signaling readiness.
+    pub connected_peers_signaling: usize,
+
+    /// Percentage of connected peers signaling readiness.
+    pub connected_peers_ready_percent: f64,
+
+    /// Number of recently connected peers signaling readiness.
+    pub recent_peers_signaling: usize,
+
+    /// Percentage of recently connected peers signaling readiness.
+    pub recent_peers_ready_percent: f64,
+}
+
+/// The readiness of the network for upcoming consensus changes.
+///
+/// Covers the rule sets this node signals readiness for and those signaled
+/// for by peers. Recent peers are those whose handshake was received within
+/// the [signal window](fork_signaling::SIGNAL_WINDOW), connected or not.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ForkReadinessReport {
+    /// Height of the tip.
+    pub tip_height: BlockHeight,
+
+    /// Number of connected peers.
+    pub connected_peers: usize,
+
+    /// Number of recently connected peers.
+    pub recent_peers: usize,
+
+    pub rule_sets: Vec<RuleSetReadiness>,
+}
+
+impl ForkReadinessReport {
+    pub(crate) fn collect(state: &GlobalState, now: SystemTime) -> Self {
+        let network = state.cli().network;
+        let tip_height = state.chain.light_state().header().height;
+
+        let mut connected_peers = 0;
+        let mut connected_signals = BTreeMap::<&str, usize>::new();
+        for peer in state.net.peer_map.values() {
+            connected_peers += 1;
+            for rule_set in peer.signaled_rule_sets() {
+                *connected_signals.entry(rule_set.as_str()).or_default() += 1;
+            }
+        }
+
+        let mut recent_peers = 0;
+        let mut recent_signals = BTreeMap::<&str, usize>::new();
+        for rule_sets in state.net.fork_signals.recent(now) {
+            recent_peers += 1;
+            for rule_set in rule_sets {
+                *recent_signals.entry(rule_set.as_str()).or_default() += 1;
+            }
+        }
+
+        let own_signals = fork_signaling::pending_rule_sets(network, tip_height)
+            .map(|rule_set| rule_set.to_string())
+            .collect::<Vec<_>>();
+        let mut names = own_signals.clone();
+        names.extend(connected_signals.keys().map(|name| name.to_string()));
+        names.extend(recent_signals.keys().map(|name| name.to_string()));
+        names.sort();
+        names.dedup();
+
+        let rule_sets = names
+            .into_iter()
+            .map(|name| {
+                let activation_height = ConsensusRuleSet::iter()
+                    .find(|rule_set| rule_set.to_string() == name)
+                    .and_then(|rule_set| rule_set.activation_height(network));
+                let connected_peers_signaling =
+                    connected_signals.get(name.as_str()).copied().unwrap_or(0);
+                let recent_peers_signaling =
+                    recent_signals.get(name.as_str()).copied().unwrap_or(0);
+
+                RuleSetReadiness {
+                    signaled_by_self: own_signals.contains(&name),
+                    rule_set: name,
+                    activation_height,
+                    connected_peers_signaling,
+                    connected_peers_ready_percent: percentage(
+                        connected_peers_signaling,
+                        connected_peers,
+                    ),
+                    recent_peers_signaling,
+                    recent_peers_ready_percent: percentage(recent_peers_signaling, recent_peers),
+                }
+            })
+            .collect();
+
+        Self {
+            tip_height,
+            connected_peers,
+            recent_peers,
+            rule_sets,
+        }
+    }
+}
+
+fn percentage(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        return 0.0;
+    }
+
+    100.0 * part as f64 / whole as f64
+}
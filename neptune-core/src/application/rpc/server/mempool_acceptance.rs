@@ -0,0 +1,133 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::transaction_verification::TransactionVerificationReport;
+use crate::api::export::Timestamp;
+use crate::api::export::Transaction;
+use crate::api::export::TransactionKernelId;
+use crate::application::config::cli_args;
+use crate::state::load_shedding::LoadShedding;
+use crate::state::mempool::InsertionPreview;
+use crate::state::mempool::Mempool;
+use crate::state::mempool::MEMPOOL_TX_THRESHOLD_AGE_IN_SECS;
+
+/// A reason why the mempool would not accept a transaction from a peer, in
+/// addition to the failures listed in the [TransactionVerificationReport].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, strum_macros::Display)]
+pub enum MempoolRejectionReason {
+    /// The transaction is backed by a primitive witness, which is never
+    /// shared with peers.
+    NoProof,
+
+    /// The transaction does not meet this node's relay policy, typically
+    /// because a proof-collection backed transaction pays too low a fee.
+    NotRelayable,
+
+    /// The node is shedding load and only accepts transactions paying at
+    /// least the configured minimum fee.
+    LoadShedding,
+
+    /// The same transaction, or one of at least the same proof quality, is
+    /// already in the mempool or was recently merged away.
+    AlreadyKnown,
+
+    /// The timestamp of the transaction lies too far in the past.
+    TooOld,
+
+    /// The transaction is not synced to the mutator set of the tip.
+    NotSynced,
+
+    /// The transaction double-spends inputs of mempool transactions that pay
+    /// a higher fee density.
+    Eclipsed { conflicts: Vec<TransactionKernelId> },
+
+    /// The mempool is full and the transaction pays the lowest fee density.
+    MempoolFull,
+}
+
+/// The verdict of running a transaction through the mempool admission checks,
+/// without adding it to the mempool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MempoolAcceptanceReport {
+    /// The outcome of verifying the transaction against the tip.
+    pub verification: TransactionVerificationReport,
+
+    /// Every mempool-specific reason the transaction would be rejected.
+    pub rejections: Vec<MempoolRejectionReason>,
+
+    /// The mempool transactions the transaction would replace, if accepted.
+    pub replaces: Vec<TransactionKernelId>,
+}
+
+impl MempoolAcceptanceReport {
+    /// Assess a verified transaction against the admission policy of the
+    /// mempool, as applied to transactions received from peers.
+    pub(crate) fn assess(
+        verification: TransactionVerificationReport,
+        transaction: &Transaction,
+        mempool: &Mempool,
+        load_shedding: &LoadShedding,
+        cli: &cli_args::Args,
+        now: Timestamp,
+    ) -> Self {
+        let kernel = &transaction.kernel;
+        let mut rejections = vec![];
+
+        match transaction.proof.proof_quality() {
+            Ok(proof_quality) => {
+                let num_inputs = kernel.inputs.len().try_into().unwrap_or(u64::MAX);
+                if !cli.relay_transaction(num_inputs, kernel.fee, proof_quality) {
+                    rejections.push(MempoolRejectionReason::NotRelayable);
+                }
+
+                if !mempool.accept_transaction(
+                    kernel.txid(),
+                    proof_quality,
+                    kernel.mutator_set_hash,
+                ) {
+                    rejections.push(MempoolRejectionReason::AlreadyKnown);
+                }
+            }
+            Err(_) => rejections.push(MempoolRejectionReason::NoProof),
+        }
+
+        if load_shedding.rejects_fee(kernel.fee, cli.load_shedding_min_fee) {
+            rejections.push(MempoolRejectionReason::LoadShedding);
+        }
+
+        if kernel.timestamp < now - Timestamp::seconds(MEMPOOL_TX_THRESHOLD_AGE_IN_SECS) {
+            rejections.push(MempoolRejectionReason::TooOld);
+        }
+
+        if !verification.synced_to_tip {
+            rejections.push(MempoolRejectionReason::NotSynced);
+        }
+
+        let mut replaces = vec![];
+        match mempool.insertion_preview(transaction) {
+            InsertionPreview::Duplicate => {
+                if !rejections.contains(&MempoolRejectionReason::AlreadyKnown) {
+                    rejections.push(MempoolRejectionReason::AlreadyKnown);
+                }
+            }
+            InsertionPreview::Eclipsed { conflicts } => {
+                rejections.push(MempoolRejectionReason::Eclipsed { conflicts });
+            }
+            InsertionPreview::Full => rejections.push(MempoolRejectionReason::MempoolFull),
+            InsertionPreview::Inserted {
+                replaces: conflicts,
+            } => replaces = conflicts,
+        }
+
+        Self {
+            verification,
+            rejections,
+            replaces,
+        }
+    }
+
+    /// Returns true iff the mempool would accept the transaction from a peer.
+    pub fn accepted(&self) -> bool {
+        self.verification.is_valid() && self.rejections.is_empty()
+    }
+}
@@ -0,0 +1,52 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::application::config::network::Network;
+use crate::application::loops::task_supervisor::TaskStatus;
+use crate::application::safe_mode::SafeModeStatus;
+use crate::state::load_shedding::LoadSheddingStatus;
+use crate::state::GlobalState;
+
+/// Summarizes whether the node is operating normally.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NodeHealth {
+    /// The network this node is running on.
+    pub network: Network,
+
+    /// Whether the node is shedding load due to resource pressure, and the
+    /// most recently sampled resource usage.
+    pub load_shedding: LoadSheddingStatus,
+
+    /// Whether the node runs in safe mode, without its non-essential
+    /// subsystems, and why.
+    pub safe_mode: Option<SafeModeStatus>,
+
+    /// Number of connected peers.
+    pub num_peers: usize,
+
+    /// Whether the node is synchronizing blocks from peers.
+    pub syncing: bool,
+
+    /// Number of times an own block proposal expired without resulting in a
+    /// block, which may indicate that guessers withhold solutions.
+    pub num_expired_block_proposals: u64,
+
+    /// The states of the long-running tasks, such as the miner and the RPC
+    /// servers, which are restarted when they fail. A task that crashed
+    /// reports the crash report bundle of its most recent panic.
+    pub tasks: Vec<TaskStatus>,
+}
+
+impl From<&GlobalState> for NodeHealth {
+    fn from(state: &GlobalState) -> Self {
+        Self {
+            network: state.cli().network,
+            load_shedding: state.load_shedding.status(),
+            safe_mode: state.safe_mode,
+            num_peers: state.net.peer_map.len(),
+            syncing: state.net.sync_anchor.is_some(),
+            num_expired_block_proposals: state.mining_state.num_expired_block_proposals,
+            tasks: state.task_statuses.statuses(),
+        }
+    }
+}
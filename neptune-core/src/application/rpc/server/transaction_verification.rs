@@ -0,0 +1,272 @@
+use get_size2::GetSize;
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Digest;
+use tasm_lib::triton_vm::prelude::BFieldElement;
+
+use crate::api::export::NativeCurrencyAmount;
+use crate::api::export::Network;
+use crate::api::export::Timestamp;
+use crate::api::export::Transaction;
+use crate::api::export::TransactionKernelId;
+use crate::api::export::TransactionProofType;
+use crate::application::loops::peer_loop::MAX_ANNOUNCEMENT_MESSAGE_SIZE;
+use crate::protocol::consensus::block::mutator_set_update::MutatorSetUpdate;
+use crate::protocol::consensus::block::FUTUREDATING_LIMIT;
+use crate::protocol::consensus::consensus_rule_set::ConsensusRuleSet;
+use crate::protocol::consensus::transaction::transaction_kernel::TransactionConfirmabilityError;
+use crate::util_types::mutator_set::mutator_set_accumulator::MutatorSetAccumulator;
+
+/// A reason why a transaction cannot be included in the next block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum_macros::Display)]
+pub enum TransactionVerificationFailure {
+    /// The proof does not attest to the validity of the transaction kernel.
+    InvalidProof,
+
+    /// Only the composer of a block may produce a transaction with a coinbase.
+    HasCoinbase,
+
+    NegativeFee,
+
+    TooManyInputs {
+        num_inputs: usize,
+        max: usize,
+    },
+
+    TooManyOutputs {
+        num_outputs: usize,
+        max: usize,
+    },
+
+    TooManyAnnouncements {
+        num_announcements: usize,
+        max: usize,
+    },
+
+    /// The announcement at the given index is larger than nodes relay.
+    OversizedAnnouncement {
+        index: usize,
+        size: usize,
+        max: usize,
+    },
+
+    /// The transaction is larger than a block may be, in bytes.
+    TooLarge {
+        size: usize,
+        max: usize,
+    },
+
+    /// The removal record at the given index is not valid relative to the
+    /// mutator set of the tip, typically because its membership proofs are
+    /// outdated.
+    InvalidRemovalRecord {
+        index: usize,
+    },
+
+    /// Two inputs spend the same UTXO.
+    DuplicateInputs,
+
+    /// The input at the given index was already spent on the canonical chain.
+    AlreadySpentInput {
+        index: usize,
+    },
+
+    /// The inputs could not be unpacked.
+    RemovalRecordUnpackFailure,
+
+    /// The inputs and outputs cannot be applied to the mutator set of the tip.
+    CannotApplyToMutatorSet,
+
+    /// The timestamp of the transaction lies too far into the future.
+    FutureDated,
+}
+
+impl From<TransactionConfirmabilityError> for TransactionVerificationFailure {
+    fn from(error: TransactionConfirmabilityError) -> Self {
+        match error {
+            TransactionConfirmabilityError::InvalidRemovalRecord(index) => {
+                Self::InvalidRemovalRecord { index }
+            }
+            TransactionConfirmabilityError::DuplicateInputs => Self::DuplicateInputs,
+            TransactionConfirmabilityError::AlreadySpentInput(index) => {
+                Self::AlreadySpentInput { index }
+            }
+            TransactionConfirmabilityError::RemovalRecordUnpackFailure => {
+                Self::RemovalRecordUnpackFailure
+            }
+        }
+    }
+}
+
+/// The outcome of verifying a transaction against the current tip, without
+/// adding it to the mempool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionVerificationReport {
+    pub txid: TransactionKernelId,
+    pub proof_type: TransactionProofType,
+    pub fee: NativeCurrencyAmount,
+
+    /// The block the transaction was verified against.
+    pub tip: Digest,
+
+    /// Whether the transaction is synced to the mutator set of the tip. An
+    /// unsynced transaction may still be confirmable, but nodes only accept
+    /// synced transactions into their mempool.
+    pub synced_to_tip: bool,
+
+    /// Every reason the transaction cannot be included in the next block.
+    /// Empty iff the transaction is valid.
+    pub failures: Vec<TransactionVerificationFailure>,
+}
+
+impl TransactionVerificationReport {
+    /// Verify the transaction's proof, its consistency with the mutator set
+    /// of the tip, and the consensus limits.
+    ///
+    /// Verifying the proof is expensive, so callers should not hold a lock on
+    /// the global state while awaiting this function.
+    pub(crate) async fn verify(
+        transaction: &Transaction,
+        tip: Digest,
+        tip_mutator_set_accumulator: &MutatorSetAccumulator,
+        network: Network,
+        consensus_rule_set: ConsensusRuleSet,
+        now: Timestamp,
+    ) -> Self {
+        let kernel = &transaction.kernel;
+        let mut failures = vec![];
+
+        if !transaction.is_valid(network, consensus_rule_set).await {
+            failures.push(TransactionVerificationFailure::InvalidProof);
+        }
+
+        if kernel.coinbase.is_some() {
+            failures.push(TransactionVerificationFailure::HasCoinbase);
+        }
+
+        if kernel.fee.is_negative() {
+            failures.push(TransactionVerificationFailure::NegativeFee);
+        }
+
+        let max = consensus_rule_set.max_num_inputs();
+        if kernel.inputs.len() > max {
+            failures.push(TransactionVerificationFailure::TooManyInputs {
+                num_inputs: kernel.inputs.len(),
+                max,
+            });
+        }
+
+        let max = consensus_rule_set.max_num_outputs();
+        if kernel.outputs.len() > max {
+            failures.push(TransactionVerificationFailure::TooManyOutputs {
+                num_outputs: kernel.outputs.len(),
+                max,
+            });
+        }
+
+        let max = consensus_rule_set.max_num_announcements();
+        if kernel.announcements.len() > max {
+            failures.push(TransactionVerificationFailure::TooManyAnnouncements {
+                num_announcements: kernel.announcements.len(),
+                max,
+            });
+        }
+
+        for (index, announcement) in kernel.announcements.iter().enumerate() {
+            if announcement.message.len() > MAX_ANNOUNCEMENT_MESSAGE_SIZE {
+                failures.push(TransactionVerificationFailure::OversizedAnnouncement {
+                    index,
+                    size: announcement.message.len(),
+                    max: MAX_ANNOUNCEMENT_MESSAGE_SIZE,
+                });
+            }
+        }
+
+        let max = consensus_rule_set.max_block_size() * BFieldElement::BYTES;
+        let size = transaction.get_size();
+        if size > max {
+            failures.push(TransactionVerificationFailure::TooLarge { size, max });
+        }
+
+        match kernel.is_confirmable_relative_to(tip_mutator_set_accumulator) {
+            Ok(()) => {
+                let ms_update =
+                    MutatorSetUpdate::new(kernel.inputs.clone(), kernel.outputs.clone());
+                if ms_update
+                    .apply_to_accumulator(&mut tip_mutator_set_accumulator.clone())
+                    .is_err()
+                {
+                    failures.push(TransactionVerificationFailure::CannotApplyToMutatorSet);
+                }
+            }
+            Err(error) => failures.push(error.into()),
+        }
+
+        if kernel.timestamp >= now + FUTUREDATING_LIMIT {
+            failures.push(TransactionVerificationFailure::FutureDated);
+        }
+
+        Self {
+            txid: kernel.txid(),
+            proof_type: (&transaction.proof).into(),
+            fee: kernel.fee,
+            tip,
+            synced_to_tip: kernel.mutator_set_hash == tip_mutator_set_accumulator.hash(),
+            failures,
+        }
+    }
+
+    /// Returns true iff the transaction can be included in the next block.
+    pub fn is_valid(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use macro_rules_attr::apply;
+
+    use super::*;
+    use crate::protocol::consensus::transaction::transaction_kernel::TransactionKernelModifier;
+    use crate::tests::shared::mock_tx::make_mock_transaction_with_mutator_set_hash_and_timestamp;
+    use crate::tests::shared_tokio_runtime;
+
+    #[apply(shared_tokio_runtime)]
+    async fn reports_every_failure_of_invalid_transaction() {
+        let network = Network::Main;
+        let msa = MutatorSetAccumulator::default();
+        let now = Timestamp::now();
+        let mut transaction = make_mock_transaction_with_mutator_set_hash_and_timestamp(
+            vec![],
+            vec![],
+            msa.hash(),
+            now + Timestamp::hours(1),
+        );
+        transaction.kernel = TransactionKernelModifier::default()
+            .coinbase(Some(NativeCurrencyAmount::coins(1)))
+            .fee(-NativeCurrencyAmount::coins(1))
+            .modify(transaction.kernel);
+
+        let report = TransactionVerificationReport::verify(
+            &transaction,
+            Digest::default(),
+            &msa,
+            network,
+            ConsensusRuleSet::default(),
+            now,
+        )
+        .await;
+
+        assert!(!report.is_valid());
+        assert!(report.synced_to_tip);
+        for failure in [
+            TransactionVerificationFailure::InvalidProof,
+            TransactionVerificationFailure::HasCoinbase,
+            TransactionVerificationFailure::NegativeFee,
+            TransactionVerificationFailure::FutureDated,
+        ] {
+            assert!(report.failures.contains(&failure), "missing {failure}");
+        }
+    }
+}
@@ -0,0 +1,566 @@
+//! An optional PIN that RPC clients must know in order to spend funds.
+//!
+//! On a shared host, every user who can read the RPC cookie can spend from
+//! the wallet. Starting neptune-core with `--spending-pin-file <PATH>` adds a
+//! second, low-security factor: the RPC methods that spend funds are refused
+//! unless the client first proves knowledge of a PIN.
+//!
+//! The proof is a challenge-response:
+//!
+//!  1. the client requests a [SpendingPinChallenge] with the
+//!     `spending_pin_challenge` RPC method.
+//!  2. the client derives the [SpendingPinKey] from the PIN and the salt in
+//!     the challenge, and answers with [SpendingPinKey::respond()] via the
+//!     `unlock_spending` RPC method.
+//!  3. a correct response authorizes a single spend within
+//!     [AUTHORIZATION_LIFETIME].
+//!
+//! A transaction built step by step, from `generate_tx_details` to
+//! `record_and_broadcast_transaction`, counts as a single spend: the building
+//! steps require the authorization, and broadcasting consumes it.
+//!
+//! Challenges can be answered once, and expire after [CHALLENGE_LIFETIME].
+//! After [MAX_NUM_FREE_ATTEMPTS] consecutive wrong PINs, responses are refused
+//! for a lockout period that doubles with every further wrong PIN, up to
+//! [MAX_LOCKOUT].
+//!
+//! The PIN file holds only the salt and the derived key, never the PIN, and
+//! should be stored apart from the wallet, readable by neptune-core but not by
+//! the users of the host. It is created with
+//! [SpendingPinKey::write_to_file()], or `neptune-cli create-spending-pin-file`.
+//!
+//! Every response and every spend attempt is recorded in
+//! `spending_pin_audit.jsonl` in the data directory.
+//!
+//! note: PINs have little entropy, so an attacker who can read the PIN file
+//! can recover the PIN by brute force. The PIN protects against users who can
+//! read the cookie, not against users who can read the PIN file.
+use std::collections::VecDeque;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use serde::Deserialize;
+use serde::Serialize;
+use sha3::Digest as _;
+use sha3::Sha3_256;
+use tokio::io::AsyncWriteExt;
+
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+
+/// number of hash iterations when deriving a [SpendingPinKey] from a PIN.
+const KEY_DERIVATION_ITERATIONS: usize = 100_000;
+
+/// time within which a [SpendingPinChallenge] must be answered.
+pub const CHALLENGE_LIFETIME: Duration = Duration::from_secs(60);
+
+/// time within which a correct response must be followed by a spend.
+pub const AUTHORIZATION_LIFETIME: Duration = Duration::from_secs(60);
+
+/// number of consecutive wrong PINs before responses are refused for a while.
+pub const MAX_NUM_FREE_ATTEMPTS: u32 = 3;
+
+/// time responses are refused after the first wrong PIN beyond
+/// [MAX_NUM_FREE_ATTEMPTS]. doubles with every further wrong PIN.
+pub const MIN_LOCKOUT: Duration = Duration::from_secs(30);
+
+/// longest time responses are refused after a wrong PIN.
+pub const MAX_LOCKOUT: Duration = Duration::from_secs(60 * 60);
+
+/// maximum number of unanswered challenges. The oldest is forgotten when a
+/// new one is issued.
+const MAX_NUM_OPEN_CHALLENGES: usize = 16;
+
+type Bytes = [u8; 32];
+
+/// a key derived from a spending PIN.
+///
+/// This is the content of the file given by `--spending-pin-file`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpendingPinKey {
+    salt: Bytes,
+    key: Bytes,
+}
+
+impl SpendingPinKey {
+    /// derive the key for `pin`, with a random salt.
+    pub fn generate(pin: &str) -> Self {
+        Self::derive(pin, rand::random())
+    }
+
+    /// derive the key for `pin`, with the salt of a [SpendingPinChallenge].
+    pub fn derive(pin: &str, salt: Bytes) -> Self {
+        let mut key: Bytes = Sha3_256::new()
+            .chain_update(salt)
+            .chain_update(pin.as_bytes())
+            .finalize()
+            .into();
+        for _ in 1..KEY_DERIVATION_ITERATIONS {
+            key = Sha3_256::new()
+                .chain_update(salt)
+                .chain_update(key)
+                .finalize()
+                .into();
+        }
+
+        Self { salt, key }
+    }
+
+    /// the response to `challenge`.
+    pub fn respond(&self, challenge: &SpendingPinChallenge) -> SpendingPinResponse {
+        SpendingPinResponse {
+            nonce: challenge.nonce,
+            mac: self.mac(challenge.nonce),
+        }
+    }
+
+    fn mac(&self, nonce: Bytes) -> Bytes {
+        Sha3_256::new()
+            .chain_update(self.key)
+            .chain_update(nonce)
+            .finalize()
+            .into()
+    }
+
+    /// read a key from a file written by [Self::write_to_file()].
+    pub fn read_from_file(path: &Path) -> Result<Self, error::SpendingPinFileError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| error::SpendingPinFileError::Read(path.into(), e.to_string()))?;
+
+        serde_json::from_str(&contents)
+            .map_err(|e| error::SpendingPinFileError::Parse(path.into(), e.to_string()))
+    }
+
+    /// write the key to a new file. Fails if the file exists.
+    ///
+    /// On unix, the file is readable and writable by its owner only.
+    pub fn write_to_file(&self, path: &Path) -> Result<(), error::SpendingPinFileError> {
+        let write = || -> anyhow::Result<()> {
+            let mut options = std::fs::OpenOptions::new();
+            options.write(true).create_new(true);
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+            let file = options.open(path)?;
+            serde_json::to_writer(file, self)?;
+            Ok(())
+        };
+
+        write().map_err(|e| error::SpendingPinFileError::Write(path.into(), e.to_string()))
+    }
+}
+
+/// a challenge issued by the `spending_pin_challenge` RPC method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpendingPinChallenge {
+    /// salt for deriving the [SpendingPinKey] from the PIN.
+    pub salt: Bytes,
+
+    /// random value identifying the challenge.
+    pub nonce: Bytes,
+}
+
+/// the answer to a [SpendingPinChallenge], produced by
+/// [SpendingPinKey::respond()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpendingPinResponse {
+    nonce: Bytes,
+    mac: Bytes,
+}
+
+/// a record in the spending-PIN audit file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SpendingPinAuditRecord {
+    timestamp: Timestamp,
+    event: String,
+    outcome: Result<(), error::SpendingPinError>,
+}
+
+#[derive(Debug, Default)]
+struct GuardState {
+    /// unanswered challenges with the time they were issued, oldest first.
+    challenges: VecDeque<(Bytes, Instant)>,
+
+    /// the time at which the pending spend authorization expires, if any.
+    authorized_until: Option<Instant>,
+
+    /// number of wrong PINs since the last correct one.
+    num_failed_attempts: u32,
+
+    /// the time until which responses are refused, if any.
+    locked_out_until: Option<Instant>,
+}
+
+impl GuardState {
+    /// record a wrong PIN, and start a lockout if there were too many.
+    fn record_failed_attempt(&mut self, now: Instant) {
+        self.num_failed_attempts = self.num_failed_attempts.saturating_add(1);
+        let Some(num_excess_attempts) = self
+            .num_failed_attempts
+            .checked_sub(MAX_NUM_FREE_ATTEMPTS + 1)
+        else {
+            return;
+        };
+
+        let lockout = MIN_LOCKOUT
+            .checked_mul(1 << num_excess_attempts.min(16))
+            .unwrap_or(MAX_LOCKOUT)
+            .min(MAX_LOCKOUT);
+        self.locked_out_until = Some(now + lockout);
+    }
+}
+
+/// enforces the spending PIN, if one is configured.
+#[derive(Debug, Default)]
+pub(crate) struct SpendingPinGuard {
+    key: Option<SpendingPinKey>,
+    audit_file: PathBuf,
+    state: Mutex<GuardState>,
+}
+
+impl SpendingPinGuard {
+    pub(crate) fn new(key: Option<SpendingPinKey>, audit_file: PathBuf) -> Self {
+        Self {
+            key,
+            audit_file,
+            state: Mutex::default(),
+        }
+    }
+
+    /// issue a new challenge.
+    pub(crate) fn challenge(&self) -> Result<SpendingPinChallenge, error::SpendingPinError> {
+        let key = self
+            .key
+            .as_ref()
+            .ok_or(error::SpendingPinError::NotEnabled)?;
+        let nonce: Bytes = rand::random();
+
+        let mut state = self.state.lock().unwrap();
+        if state.challenges.len() >= MAX_NUM_OPEN_CHALLENGES {
+            state.challenges.pop_front();
+        }
+        state.challenges.push_back((nonce, Instant::now()));
+
+        Ok(SpendingPinChallenge {
+            salt: key.salt,
+            nonce,
+        })
+    }
+
+    /// verify the response to a challenge and, if correct, authorize a single
+    /// spend.
+    pub(crate) async fn unlock(
+        &self,
+        response: SpendingPinResponse,
+    ) -> Result<(), error::SpendingPinError> {
+        let outcome = self.verify(response, Instant::now());
+        self.audit("unlock", &outcome).await;
+
+        outcome
+    }
+
+    fn verify(
+        &self,
+        response: SpendingPinResponse,
+        now: Instant,
+    ) -> Result<(), error::SpendingPinError> {
+        let key = self
+            .key
+            .as_ref()
+            .ok_or(error::SpendingPinError::NotEnabled)?;
+
+        let mut state = self.state.lock().unwrap();
+        if let Some(until) = state.locked_out_until.filter(|until| now < *until) {
+            let remaining = until.saturating_duration_since(now).as_secs() + 1;
+            return Err(error::SpendingPinError::TooManyAttempts(remaining));
+        }
+        state
+            .challenges
+            .retain(|(_, issued)| now.saturating_duration_since(*issued) < CHALLENGE_LIFETIME);
+        let index = state
+            .challenges
+            .iter()
+            .position(|(nonce, _)| *nonce == response.nonce)
+            .ok_or(error::SpendingPinError::UnknownChallenge)?;
+        state.challenges.remove(index);
+
+        // compare without branching on the position of the first difference.
+        let difference = key
+            .mac(response.nonce)
+            .iter()
+            .zip(response.mac)
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+        if difference != 0 {
+            state.record_failed_attempt(now);
+            return Err(error::SpendingPinError::WrongPin);
+        }
+
+        state.num_failed_attempts = 0;
+        state.locked_out_until = None;
+        state.authorized_until = Some(now + AUTHORIZATION_LIFETIME);
+        Ok(())
+    }
+
+    /// consume the pending spend authorization, if a PIN is configured.
+    ///
+    /// `method` names the RPC method attempting to spend, for the audit file.
+    pub(crate) async fn authorize_spend(
+        &self,
+        method: &str,
+    ) -> Result<(), error::SpendingPinError> {
+        if self.key.is_none() {
+            return Ok(());
+        }
+
+        let outcome = self.consume_authorization(Instant::now());
+        self.audit(method, &outcome).await;
+
+        outcome
+    }
+
+    /// refuse unless a spend is authorized, if a PIN is configured, without
+    /// consuming the authorization. For the steps of building a transaction
+    /// that is spent by a later call.
+    ///
+    /// `method` names the RPC method, for the audit file.
+    pub(crate) async fn require_authorization(
+        &self,
+        method: &str,
+    ) -> Result<(), error::SpendingPinError> {
+        if self.key.is_none() {
+            return Ok(());
+        }
+
+        let outcome = self.check_authorization(Instant::now());
+        self.audit(method, &outcome).await;
+
+        outcome
+    }
+
+    fn check_authorization(&self, now: Instant) -> Result<(), error::SpendingPinError> {
+        match self.state.lock().unwrap().authorized_until {
+            Some(expiry) if now < expiry => Ok(()),
+            _ => Err(error::SpendingPinError::Locked),
+        }
+    }
+
+    fn consume_authorization(&self, now: Instant) -> Result<(), error::SpendingPinError> {
+        match self.state.lock().unwrap().authorized_until.take() {
+            Some(expiry) if now < expiry => Ok(()),
+            _ => Err(error::SpendingPinError::Locked),
+        }
+    }
+
+    // failure to write the audit record is logged but does not change the
+    // outcome.
+    async fn audit(&self, event: &str, outcome: &Result<(), error::SpendingPinError>) {
+        let record = SpendingPinAuditRecord {
+            timestamp: Timestamp::now(),
+            event: event.to_owned(),
+            outcome: outcome.clone(),
+        };
+
+        let result = async {
+            let mut line = serde_json::to_string(&record)?;
+            line.push('\n');
+            tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.audit_file)
+                .await?
+                .write_all(line.as_bytes())
+                .await?;
+            anyhow::Ok(())
+        }
+        .await;
+
+        if let Err(e) = result {
+            tracing::error!(
+                "Could not write spending-PIN audit record to {}: {e}",
+                self.audit_file.display()
+            );
+        }
+    }
+}
+
+pub mod error {
+    use super::*;
+
+    /// enumerates reasons a spend is refused because of the spending PIN
+    #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, Serialize, Deserialize)]
+    #[non_exhaustive]
+    pub enum SpendingPinError {
+        #[error("no spending PIN is configured on this node")]
+        NotEnabled,
+
+        #[error("spending is locked; answer a spending-PIN challenge first")]
+        Locked,
+
+        #[error("the challenge is unknown, expired, or was already answered")]
+        UnknownChallenge,
+
+        #[error("wrong spending PIN")]
+        WrongPin,
+
+        #[error("too many wrong spending PINs; try again in {0} seconds")]
+        TooManyAttempts(u64),
+    }
+
+    /// enumerates possible errors when reading or writing a spending-PIN file
+    #[derive(Debug, Clone, thiserror::Error)]
+    #[non_exhaustive]
+    pub enum SpendingPinFileError {
+        #[error("could not read spending-PIN file {}: {1}", .0.display())]
+        Read(PathBuf, String),
+
+        #[error("invalid spending-PIN file {}: {1}", .0.display())]
+        Parse(PathBuf, String),
+
+        #[error("could not write spending-PIN file {}: {1}", .0.display())]
+        Write(PathBuf, String),
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    fn guard(pin: &str) -> SpendingPinGuard {
+        SpendingPinGuard::new(Some(SpendingPinKey::generate(pin)), PathBuf::default())
+    }
+
+    #[test]
+    fn correct_response_authorizes_single_spend() {
+        let guard = guard("1234");
+        let challenge = guard.challenge().unwrap();
+        let response = SpendingPinKey::derive("1234", challenge.salt).respond(&challenge);
+
+        let now = Instant::now();
+        assert!(guard.consume_authorization(now).is_err());
+        guard.verify(response, now).unwrap();
+        guard.consume_authorization(now).unwrap();
+        assert_eq!(
+            Err(error::SpendingPinError::Locked),
+            guard.consume_authorization(now)
+        );
+    }
+
+    #[test]
+    fn challenges_can_be_answered_once() {
+        let guard = guard("1234");
+        let challenge = guard.challenge().unwrap();
+        let response = SpendingPinKey::derive("1234", challenge.salt).respond(&challenge);
+
+        let now = Instant::now();
+        guard.verify(response, now).unwrap();
+        assert_eq!(
+            Err(error::SpendingPinError::UnknownChallenge),
+            guard.verify(response, now)
+        );
+    }
+
+    #[test]
+    fn wrong_pin_and_expired_challenges_are_refused() {
+        let guard = guard("1234");
+        let challenge = guard.challenge().unwrap();
+        let response = SpendingPinKey::derive("4321", challenge.salt).respond(&challenge);
+        assert_eq!(
+            Err(error::SpendingPinError::WrongPin),
+            guard.verify(response, Instant::now())
+        );
+
+        let challenge = guard.challenge().unwrap();
+        let response = SpendingPinKey::derive("1234", challenge.salt).respond(&challenge);
+        assert_eq!(
+            Err(error::SpendingPinError::UnknownChallenge),
+            guard.verify(response, Instant::now() + CHALLENGE_LIFETIME)
+        );
+    }
+
+    #[test]
+    fn repeated_wrong_pins_lock_out_with_backoff() {
+        let guard = guard("1234");
+        let mut now = Instant::now();
+        let attempt = |pin: &str, now: Instant| {
+            let challenge = guard.challenge().unwrap();
+            let response = SpendingPinKey::derive(pin, challenge.salt).respond(&challenge);
+            guard.verify(response, now)
+        };
+
+        for _ in 0..=MAX_NUM_FREE_ATTEMPTS {
+            assert_eq!(Err(error::SpendingPinError::WrongPin), attempt("0000", now));
+        }
+        assert!(matches!(
+            attempt("1234", now),
+            Err(error::SpendingPinError::TooManyAttempts(_))
+        ));
+
+        // the lockout doubles with every further wrong PIN.
+        now += MIN_LOCKOUT;
+        assert_eq!(Err(error::SpendingPinError::WrongPin), attempt("0000", now));
+        assert!(attempt("1234", now + MIN_LOCKOUT).is_err());
+        now += 2 * MIN_LOCKOUT;
+
+        // a correct PIN resets the count.
+        attempt("1234", now).unwrap();
+        assert_eq!(Err(error::SpendingPinError::WrongPin), attempt("0000", now));
+        attempt("1234", now).unwrap();
+    }
+
+    #[test]
+    fn building_steps_require_but_do_not_consume_authorization() {
+        let guard = guard("1234");
+        let now = Instant::now();
+        assert!(guard.check_authorization(now).is_err());
+
+        let challenge = guard.challenge().unwrap();
+        let response = SpendingPinKey::derive("1234", challenge.salt).respond(&challenge);
+        guard.verify(response, now).unwrap();
+        guard.check_authorization(now).unwrap();
+        guard.check_authorization(now).unwrap();
+        guard.consume_authorization(now).unwrap();
+        assert!(guard.check_authorization(now).is_err());
+    }
+
+    #[test]
+    fn authorization_expires() {
+        let guard = guard("1234");
+        let challenge = guard.challenge().unwrap();
+        let response = SpendingPinKey::derive("1234", challenge.salt).respond(&challenge);
+
+        let now = Instant::now();
+        guard.verify(response, now).unwrap();
+        assert_eq!(
+            Err(error::SpendingPinError::Locked),
+            guard.consume_authorization(now + AUTHORIZATION_LIFETIME)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pin_file_is_private_to_its_owner() {
+        use std::os::unix::fs::PermissionsExt;
+
+        use crate::application::config::network::Network;
+        use crate::tests::shared::files::unit_test_data_directory;
+
+        let data_dir = unit_test_data_directory(Network::Main).unwrap();
+        std::fs::create_dir_all(data_dir.root_dir_path()).unwrap();
+        let path = data_dir.root_dir_path().join("spending_pin.json");
+
+        let key = SpendingPinKey::generate("1234");
+        key.write_to_file(&path).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(0o600, mode & 0o777);
+        assert_eq!(key, SpendingPinKey::read_from_file(&path).unwrap());
+        assert!(key.write_to_file(&path).is_err());
+    }
+
+    #[test]
+    fn no_challenges_without_pin() {
+        let guard = SpendingPinGuard::default();
+        assert_eq!(Err(error::SpendingPinError::NotEnabled), guard.challenge());
+    }
+}
@@ -0,0 +1,182 @@
+//! Safe mode, in which the node starts without its non-essential subsystems
+//! after it crashed repeatedly.
+//!
+//! Every run of the node increments a counter in
+//! [DataDirectory::crash_counter_file_path()] when it starts, and resets it
+//! once it has run cleanly, i.e. for [CLEAN_RUN_DURATION] or until a graceful
+//! shutdown. A counter that reaches `--safe-mode-crash-threshold` at startup
+//! thus indicates a crash loop, and the node starts in safe mode. `--safe-mode`
+//! forces it.
+//!
+//! In safe mode, the node neither composes, guesses, nor upgrades transaction
+//! proofs, and it maintains at most [SAFE_MODE_MAX_NUM_PEERS] peers. The RPC
+//! server runs as usual, so the operator can diagnose the node, e.g. through
+//! its crash reports and health. Since a clean run resets the counter, the
+//! node leaves safe mode at the next restart after a clean run, unless it is
+//! forced.
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::warn;
+
+use crate::application::config::cli_args;
+use crate::application::config::data_directory::DataDirectory;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+
+/// How long the node must run before the run counts as clean.
+pub const CLEAN_RUN_DURATION: Duration = Duration::from_secs(10 * 60);
+
+/// The maximum number of peers in safe mode.
+pub const SAFE_MODE_MAX_NUM_PEERS: usize = 2;
+
+/// Why the node runs in safe mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SafeModeReason {
+    /// Safe mode was requested with `--safe-mode`.
+    Forced,
+
+    /// The given number of consecutive runs ended without a clean run.
+    CrashLoop { consecutive_crashes: u32 },
+}
+
+/// Whether the node runs in safe mode, and why.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SafeModeStatus {
+    pub reason: SafeModeReason,
+    pub since: Timestamp,
+}
+
+impl SafeModeStatus {
+    /// Decide whether to start in safe mode, given the number of consecutive
+    /// runs that ended without a clean run.
+    pub(crate) fn decide(cli: &cli_args::Args, consecutive_crashes: u32) -> Option<Self> {
+        let reason = if cli.safe_mode {
+            SafeModeReason::Forced
+        } else if consecutive_crashes >= cli.safe_mode_crash_threshold {
+            SafeModeReason::CrashLoop {
+                consecutive_crashes,
+            }
+        } else {
+            return None;
+        };
+
+        Some(Self {
+            reason,
+            since: Timestamp::now(),
+        })
+    }
+
+    /// Disable the non-essential subsystems in the configuration the node
+    /// starts with.
+    pub(crate) fn restrict(&self, cli: &mut cli_args::Args) {
+        warn!(
+            "Starting in safe mode ({:?}): no composing, guessing, or proof \
+             upgrading, and at most {SAFE_MODE_MAX_NUM_PEERS} peers.",
+            self.reason
+        );
+        cli.compose = false;
+        cli.guess = false;
+        cli.tx_proof_upgrading = false;
+        cli.max_num_peers = cli.max_num_peers.min(SAFE_MODE_MAX_NUM_PEERS);
+        cli.peers.truncate(SAFE_MODE_MAX_NUM_PEERS);
+    }
+}
+
+/// The number of consecutive runs that did not end cleanly, persisted in the
+/// data directory.
+#[derive(Debug, Clone)]
+pub(crate) struct CrashCounter {
+    path: PathBuf,
+}
+
+impl CrashCounter {
+    pub(crate) fn new(data_directory: &DataDirectory) -> Self {
+        Self {
+            path: data_directory.crash_counter_file_path(),
+        }
+    }
+
+    /// Record the start of a run. Returns the number of consecutive earlier
+    /// runs that ended without a clean run.
+    ///
+    /// A missing or unreadable counter counts as zero.
+    pub(crate) async fn record_start(&self) -> io::Result<u32> {
+        let consecutive_crashes = match tokio::fs::read_to_string(&self.path).await {
+            Ok(contents) => contents.trim().parse().unwrap_or_default(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e),
+        };
+        self.write(consecutive_crashes.saturating_add(1)).await?;
+
+        Ok(consecutive_crashes)
+    }
+
+    /// Record that the current run is clean.
+    pub(crate) async fn record_clean_run(&self) -> io::Result<()> {
+        self.write(0).await
+    }
+
+    async fn write(&self, count: u32) -> io::Result<()> {
+        tokio::fs::write(&self.path, count.to_string()).await
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use macro_rules_attr::apply;
+
+    use super::*;
+    use crate::application::config::network::Network;
+    use crate::tests::shared::files::unit_test_data_directory;
+    use crate::tests::shared_tokio_runtime;
+
+    #[apply(shared_tokio_runtime)]
+    async fn counter_counts_runs_until_clean_run() {
+        let data_dir = unit_test_data_directory(Network::Main).unwrap();
+        DataDirectory::create_dir_if_not_exists(&data_dir.root_dir_path())
+            .await
+            .unwrap();
+        let counter = CrashCounter::new(&data_dir);
+
+        assert_eq!(0, counter.record_start().await.unwrap());
+        assert_eq!(1, counter.record_start().await.unwrap());
+        assert_eq!(2, counter.record_start().await.unwrap());
+
+        counter.record_clean_run().await.unwrap();
+        assert_eq!(0, counter.record_start().await.unwrap());
+    }
+
+    #[test]
+    fn safe_mode_after_crash_loop_or_when_forced() {
+        let mut cli = cli_args::Args {
+            compose: true,
+            guess: true,
+            tx_proof_upgrading: true,
+            ..Default::default()
+        };
+        let threshold = cli.safe_mode_crash_threshold;
+        assert!(SafeModeStatus::decide(&cli, threshold - 1).is_none());
+
+        let status = SafeModeStatus::decide(&cli, threshold).unwrap();
+        assert_eq!(
+            SafeModeReason::CrashLoop {
+                consecutive_crashes: threshold
+            },
+            status.reason
+        );
+        status.restrict(&mut cli);
+        assert!(!cli.mine());
+        assert!(!cli.tx_proof_upgrading);
+        assert!(cli.max_num_peers <= SAFE_MODE_MAX_NUM_PEERS);
+
+        cli.safe_mode = true;
+        assert_eq!(
+            SafeModeReason::Forced,
+            SafeModeStatus::decide(&cli, 0).unwrap().reason
+        );
+    }
+}
@@ -39,6 +39,11 @@ impl TritonVmJobQueue {
     /// callers should execute resource intensive triton-vm tasks in this
     /// queue to avoid running simultaneous tasks that could exceed hardware
     /// capabilities.
+    ///
+    /// The queue runs one task at a time, unless configured otherwise with
+    /// [JobQueue::set_num_workers()], eg by the `--proof-job-workers` CLI
+    /// argument. Tasks that do not depend on each other should be added
+    /// without awaiting each other, so that they can run simultaneously.
     pub fn get_instance() -> Arc<Self> {
         use std::sync::OnceLock;
         static INSTANCE: OnceLock<Arc<TritonVmJobQueue>> = OnceLock::new();
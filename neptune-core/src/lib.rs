@@ -40,6 +40,7 @@ pub mod bench_helpers;
 
 use std::env;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use anyhow::Context;
 use anyhow::Result;
@@ -49,6 +50,7 @@ use chrono::Local;
 use chrono::Utc;
 use futures::future;
 use futures::Future;
+use futures::FutureExt;
 use futures::StreamExt;
 use itertools::Itertools;
 use prelude::tasm_lib;
@@ -71,8 +73,14 @@ use tracing::info;
 use triton_vm::prelude::BFieldElement;
 
 use crate::application::config::data_directory::DataDirectory;
+use crate::application::crash_report;
+use crate::application::database::open_files::OpenFileBudget;
+use crate::application::database::read_cache_budget::ReadCacheBudget;
 use crate::application::json_rpc::server::rpc::RpcServer;
 use crate::application::locks::tokio as sync_tokio;
+use crate::application::loops::channel::backpressure::BackpressureSender;
+use crate::application::loops::channel::backpressure::OverflowPolicy;
+use crate::application::loops::channel::backpressure::SpillQueue;
 use crate::application::loops::channel::MainToMiner;
 use crate::application::loops::channel::MainToPeerTask;
 use crate::application::loops::channel::MinerToMain;
@@ -80,8 +88,18 @@ use crate::application::loops::channel::PeerTaskToMain;
 use crate::application::loops::channel::RPCServerToMain;
 use crate::application::loops::connect_to_peers::call_peer;
 use crate::application::loops::main_loop::MainLoopHandler;
+use crate::application::loops::replication_loop;
+use crate::application::loops::task_supervisor;
+use crate::application::loops::task_supervisor::SupervisedTask;
+use crate::application::rpc::request_limiter::LimitedServe;
+use crate::application::rpc::request_limiter::RpcRequestLimiter;
 use crate::application::rpc::server::RPC;
+use crate::application::safe_mode::CrashCounter;
+use crate::application::safe_mode::SafeModeStatus;
+use crate::application::safe_mode::CLEAN_RUN_DURATION;
+use crate::application::triton_vm_job_queue::vm_job_queue;
 use crate::state::archival_state::ArchivalState;
+use crate::state::replication::ReplicationSecret;
 use crate::state::wallet::wallet_state::WalletState;
 use crate::state::GlobalStateLock;
 
@@ -109,7 +127,12 @@ pub fn display_banner() {
     println!("{}", NEPTUNE_BANNER);
 }
 
-pub async fn initialize(cli_args: cli_args::Args) -> Result<MainLoopHandler> {
+/// Start the node's subsystems and return the handler of its main loop, which
+/// the caller is responsible for running.
+///
+/// Applications embedding a node should use
+/// [NodeBuilder](api::node::NodeBuilder) instead.
+pub async fn initialize(mut cli_args: cli_args::Args) -> Result<MainLoopHandler> {
     async fn spawn(fut: impl Future<Output = ()> + Send + 'static) {
         tokio::spawn(fut);
     }
@@ -124,13 +147,30 @@ pub async fn initialize(cli_args: cli_args::Args) -> Result<MainLoopHandler> {
     // Get data directory (wallet, block database), create one if none exists
     let data_directory = DataDirectory::get(cli_args.data_dir.clone(), cli_args.network)?;
     DataDirectory::create_dir_if_not_exists(&data_directory.root_dir_path()).await?;
+    data_directory.ensure_network(cli_args.network).await?;
     info!("Data directory is {}", data_directory);
 
+    // enter safe mode after a crash loop, before any subsystem is configured.
+    let crash_counter = CrashCounter::new(&data_directory);
+    let consecutive_crashes = crash_counter.record_start().await?;
+    let safe_mode = SafeModeStatus::decide(&cli_args, consecutive_crashes);
+    if let Some(safe_mode) = &safe_mode {
+        safe_mode.restrict(&mut cli_args);
+    }
+    crash_report::install(&data_directory, &cli_args);
+    OpenFileBudget::from_cli(&cli_args).apply();
+    ReadCacheBudget::from_cli(&cli_args).apply();
+
+    vm_job_queue().set_num_workers(cli_args.proof_job_workers);
+
     let (rpc_server_to_main_tx, rpc_server_to_main_rx) =
         mpsc::channel::<RPCServerToMain>(RPC_CHANNEL_CAPACITY);
     let genesis = Block::genesis(cli_args.network);
-    let global_state =
+    let mut global_state =
         GlobalState::try_new(data_directory.clone(), genesis, cli_args.clone()).await?;
+    let tip = global_state.chain.light_state();
+    crash_report::record_tip(tip.header().height, tip.hash());
+    global_state.safe_mode = safe_mode;
     let mut global_state_lock =
         GlobalStateLock::from_global_state(global_state, rpc_server_to_main_tx.clone());
 
@@ -141,6 +181,24 @@ pub async fn initialize(cli_args: cli_args::Args) -> Result<MainLoopHandler> {
     // Add the MPSC (multi-producer, single consumer) channel for peer-task-to-main communication
     let (peer_task_to_main_tx, peer_task_to_main_rx) =
         mpsc::channel::<PeerTaskToMain>(PEER_CHANNEL_CAPACITY);
+    let peer_overflow_policy = cli_args.peer_channel_overflow_policy;
+    let spill_queue = if peer_overflow_policy == OverflowPolicy::SpillToDisk {
+        Some(SpillQueue::new(data_directory.channel_spill_dir_path()).await?)
+    } else {
+        None
+    };
+    let peer_task_to_main_tx = BackpressureSender::new(
+        "peer task to main",
+        peer_task_to_main_tx,
+        peer_overflow_policy,
+        cli_args.channel_send_timeout,
+        spill_queue,
+    );
+    global_state_lock
+        .lock_guard_mut()
+        .await
+        .channel_metrics
+        .register(&peer_task_to_main_tx);
 
     if let Some(block_import_directory) =
         global_state_lock.cli().import_blocks_from_directory.clone()
@@ -183,6 +241,15 @@ pub async fn initialize(cli_args: cli_args::Args) -> Result<MainLoopHandler> {
         .await?;
     info!("UTXO restoration check complete");
 
+    // After an unclean shutdown, the wallet may be synced to another block
+    // than the tip.
+    let wallet_reconciliation = global_state_lock
+        .lock_guard_mut()
+        .await
+        .reconcile_wallet_with_tip()
+        .await?;
+    info!("Wallet reconciliation with tip: {wallet_reconciliation}");
+
     // Bind socket to port on this machine, to handle incoming connections from peers
     let incoming_peer_listener = if let Some(incoming_peer_listener) = cli_args.own_listen_port() {
         let ret = TcpListener::bind((cli_args.peer_listen_addr, incoming_peer_listener))
@@ -196,18 +263,36 @@ pub async fn initialize(cli_args: cli_args::Args) -> Result<MainLoopHandler> {
     };
 
     // Connect to peers, and provide each peer task with a thread-safe copy of the state
-    let own_handshake_data: HandshakeData =
-        global_state_lock.lock_guard().await.get_own_handshakedata();
     info!(
         "Most known canonical block has height {}",
-        own_handshake_data.tip_header.height
+        global_state_lock
+            .lock_guard()
+            .await
+            .chain
+            .light_state()
+            .header()
+            .height
     );
     let mut task_join_handles = vec![];
+
+    // a run that lasts long enough is clean, however it ends.
+    let clean_run_join_handle = tokio::task::spawn(async move {
+        tokio::time::sleep(CLEAN_RUN_DURATION).await;
+        match crash_counter.record_clean_run().await {
+            Ok(()) => debug!("Recorded clean run"),
+            Err(e) => tracing::warn!("Could not record clean run: {e}"),
+        }
+    });
+    task_join_handles.push(clean_run_join_handle);
+
     for peer_address in global_state_lock.cli().peers.clone() {
+        let own_handshake_data: HandshakeData =
+            global_state_lock.lock_guard().await.get_own_handshakedata();
         let peer_state_var = global_state_lock.clone(); // bump arc refcount
         let main_to_peer_broadcast_rx_clone: broadcast::Receiver<MainToPeerTask> =
             main_to_peer_broadcast_tx.subscribe();
-        let peer_task_to_main_tx_clone: mpsc::Sender<PeerTaskToMain> = peer_task_to_main_tx.clone();
+        let peer_task_to_main_tx_clone: BackpressureSender<PeerTaskToMain> =
+            peer_task_to_main_tx.clone();
         let peer_join_handle = tokio::task::spawn(async move {
             call_peer(
                 peer_address,
@@ -225,29 +310,45 @@ pub async fn initialize(cli_args: cli_args::Args) -> Result<MainLoopHandler> {
 
     // Start mining tasks if requested
     let (miner_to_main_tx, miner_to_main_rx) = mpsc::channel::<MinerToMain>(MINER_CHANNEL_CAPACITY);
+    let miner_to_main_tx = BackpressureSender::blocking("miner to main", miner_to_main_tx);
+    global_state_lock
+        .lock_guard_mut()
+        .await
+        .channel_metrics
+        .register(&miner_to_main_tx);
     let (main_to_miner_tx, main_to_miner_rx) = mpsc::channel::<MainToMiner>(MINER_CHANNEL_CAPACITY);
     let miner_state_lock = global_state_lock.clone(); // bump arc refcount.
     if global_state_lock.cli().mine() {
-        let miner_join_handle = tokio::task::spawn(async move {
-            application::loops::mine_loop::mine(
-                main_to_miner_rx,
-                miner_to_main_tx,
-                miner_state_lock,
-            )
-            .await
-            .expect("Error in mining task");
-        });
+        // The receiver outlives failed mining tasks, for restarts to use.
+        let main_to_miner_rx = Arc::new(tokio::sync::Mutex::new(main_to_miner_rx));
+        let miner_join_handle = task_supervisor::supervise(
+            &global_state_lock,
+            SupervisedTask::Miner,
+            SupervisedTask::Miner.restart_policy(),
+            move || {
+                let main_to_miner_rx = main_to_miner_rx.clone();
+                let miner_to_main_tx = miner_to_main_tx.clone();
+                let miner_state_lock = miner_state_lock.clone();
+                async move {
+                    application::loops::mine_loop::mine(
+                        &mut *main_to_miner_rx.lock().await,
+                        miner_to_main_tx,
+                        miner_state_lock,
+                    )
+                    .await
+                }
+                .boxed()
+            },
+        )
+        .await;
         task_join_handles.push(miner_join_handle);
         info!("Started mining task");
     }
 
     // Start RPC server for CLI request and more. It's important that this is done as late
     // as possible, so requests do not hang while initialization code runs.
-    let mut rpc_listener = tarpc::serde_transport::tcp::listen(
-        format!("127.0.0.1:{}", global_state_lock.cli().rpc_port),
-        Json::default,
-    )
-    .await?;
+    let rpc_address = format!("127.0.0.1:{}", global_state_lock.cli().rpc_port());
+    let mut rpc_listener = tarpc::serde_transport::tcp::listen(&rpc_address, Json::default).await?;
     rpc_listener.config_mut().max_frame_length(usize::MAX);
 
     let rpc_state_lock = global_state_lock.clone();
@@ -259,46 +360,161 @@ pub async fn initialize(cli_args: cli_args::Args) -> Result<MainLoopHandler> {
             .into(),
     ];
 
-    let rpc_join_handle = tokio::spawn(async move {
-        rpc_listener
-            // Ignore accept errors.
-            .filter_map(|r| future::ready(r.ok()))
-            .map(server::BaseChannel::with_defaults)
-            // Limit channels to 5 per IP. 1 for dashboard and a few more for CLI interactions
-            .max_channels_per_key(5, |t| t.transport().peer_addr().unwrap().ip())
-            // serve is generated by the service attribute. It takes as input any type implementing
-            // the generated RPC trait.
-            .map(move |channel| {
-                let server = application::rpc::server::NeptuneRPCServer::new(
-                    rpc_state_lock.clone(),
-                    rpc_server_to_main_tx.clone(),
-                    data_directory.clone(),
-                    valid_tokens.clone(),
-                );
-
-                channel.execute(server.serve()).for_each(spawn)
-            })
-            // Max 10 channels.
-            .buffer_unordered(10)
-            .for_each(|_| async {})
-            .await;
-    });
+    // RPC requests over all transports share the same limits.
+    let rpc_request_limiter = Arc::new(RpcRequestLimiter::from_cli(global_state_lock.cli()));
+    let tarpc_request_limiter = rpc_request_limiter.clone();
+
+    // The listener bound above is used by the first RPC server task. Restarted
+    // tasks bind a new one.
+    let mut rpc_listener = Some(rpc_listener);
+    let rpc_join_handle = task_supervisor::supervise(
+        &global_state_lock,
+        SupervisedTask::RpcServer,
+        SupervisedTask::RpcServer.restart_policy(),
+        move || {
+            let rpc_listener = rpc_listener.take();
+            let rpc_address = rpc_address.clone();
+            let rpc_state_lock = rpc_state_lock.clone();
+            let rpc_server_to_main_tx = rpc_server_to_main_tx.clone();
+            let data_directory = data_directory.clone();
+            let valid_tokens = valid_tokens.clone();
+            let tarpc_request_limiter = tarpc_request_limiter.clone();
+            async move {
+                let rpc_listener = match rpc_listener {
+                    Some(rpc_listener) => rpc_listener,
+                    None => {
+                        let mut rpc_listener =
+                            tarpc::serde_transport::tcp::listen(&rpc_address, Json::default)
+                                .await?;
+                        rpc_listener.config_mut().max_frame_length(usize::MAX);
+                        rpc_listener
+                    }
+                };
+
+                rpc_listener
+                    // Ignore accept errors.
+                    .filter_map(|r| future::ready(r.ok()))
+                    .map(server::BaseChannel::with_defaults)
+                    // Limit channels to 5 per IP. 1 for dashboard and a few more for CLI interactions
+                    .max_channels_per_key(5, |t| t.transport().peer_addr().unwrap().ip())
+                    // serve is generated by the service attribute. It takes as input any type implementing
+                    // the generated RPC trait.
+                    .map(move |channel| {
+                        let server = application::rpc::server::NeptuneRPCServer::new(
+                            rpc_state_lock.clone(),
+                            rpc_server_to_main_tx.clone(),
+                            data_directory.clone(),
+                            valid_tokens.clone(),
+                            tarpc_request_limiter.clone(),
+                        );
+                        let serve =
+                            LimitedServe::new(server.serve(), tarpc_request_limiter.clone());
+
+                        channel.execute(serve).for_each(spawn)
+                    })
+                    // Max 10 channels.
+                    .buffer_unordered(10)
+                    .for_each(|_| async {})
+                    .await;
+
+                Ok(())
+            }
+            .boxed()
+        },
+    )
+    .await;
     task_join_handles.push(rpc_join_handle);
     info!("Started RPC server");
 
     if let Some(addr) = global_state_lock.cli().listen_rpc {
-        let listener = TcpListener::bind(addr).await.unwrap();
+        let mut listener = Some(TcpListener::bind(addr).await.unwrap());
         let json_rpc_state_lock = global_state_lock.clone();
 
-        let json_rpc_join_handle = tokio::spawn(async move {
-            let rpc_server = RpcServer::new(json_rpc_state_lock, None);
-            rpc_server.serve_http(listener).await;
-        });
+        let json_rpc_join_handle = task_supervisor::supervise(
+            &global_state_lock,
+            SupervisedTask::JsonRpcServer,
+            SupervisedTask::JsonRpcServer.restart_policy(),
+            move || {
+                let listener = listener.take();
+                let json_rpc_state_lock = json_rpc_state_lock.clone();
+                let rpc_request_limiter = rpc_request_limiter.clone();
+                async move {
+                    let listener = match listener {
+                        Some(listener) => listener,
+                        None => TcpListener::bind(addr).await?,
+                    };
+                    let rpc_server = RpcServer::new(json_rpc_state_lock, None)
+                        .with_request_limiter(rpc_request_limiter);
+                    rpc_server.serve_http(listener).await;
+
+                    Ok(())
+                }
+                .boxed()
+            },
+        )
+        .await;
         task_join_handles.push(json_rpc_join_handle);
 
         info!("Started HTTP-JSON RPC server on {}.", addr);
     }
 
+    // Replicate to standbys, or from the primary if this node is a standby.
+    let replication_secret = match &global_state_lock.cli().replication_secret_file {
+        Some(path) => Some(ReplicationSecret::read_from_file(path).await?),
+        None => None,
+    };
+    if let (Some(addr), Some(secret)) = (
+        global_state_lock.cli().replication_listen_addr,
+        replication_secret,
+    ) {
+        let mut listener = Some(
+            TcpListener::bind(addr)
+                .await
+                .with_context(|| format!("Failed to bind replication listener to {addr}"))?,
+        );
+        let replication_state_lock = global_state_lock.clone();
+
+        let replication_join_handle = task_supervisor::supervise(
+            &global_state_lock,
+            SupervisedTask::ReplicationServer,
+            SupervisedTask::ReplicationServer.restart_policy(),
+            move || {
+                let listener = listener.take();
+                let replication_state_lock = replication_state_lock.clone();
+                async move {
+                    let listener = match listener {
+                        Some(listener) => listener,
+                        None => TcpListener::bind(addr).await?,
+                    };
+                    replication_loop::serve_standbys(listener, replication_state_lock, secret).await
+                }
+                .boxed()
+            },
+        )
+        .await;
+        task_join_handles.push(replication_join_handle);
+
+        info!("Accepting standby connections on {addr}.");
+    }
+    if let (Some(primary), Some(secret)) = (global_state_lock.cli().standby_of, replication_secret)
+    {
+        let standby_state_lock = global_state_lock.clone();
+
+        let standby_join_handle = task_supervisor::supervise(
+            &global_state_lock,
+            SupervisedTask::StandbyFollower,
+            SupervisedTask::StandbyFollower.restart_policy(),
+            move || {
+                replication_loop::follow_primary(primary, standby_state_lock.clone(), secret)
+                    .boxed()
+            },
+        )
+        .await;
+        task_join_handles.push(standby_join_handle);
+
+        info!("Replicating from primary at {primary}.");
+    }
+
     // Handle incoming connections, messages from peer tasks, and messages from the mining task
     Ok(MainLoopHandler::new(
         incoming_peer_listener,
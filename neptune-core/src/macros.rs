@@ -21,7 +21,7 @@ macro_rules! state_lock_call_mut_async {
 /// for calling an immutable async callback fn with correct StateLock variant
 /// this appears impossible to do without a macro.
 macro_rules! state_lock_call_async {
-    ($state_lock:expr, $func:expr, $($arg:expr),*) => {
+    ($state_lock:expr, $func:expr $(, $arg:expr)* $(,)?) => {
         async {
             match $state_lock {
                 StateLock::Lock(ref global_state_lock) => {
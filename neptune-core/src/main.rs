@@ -1,9 +1,10 @@
 use std::process;
 
 use anyhow::Result;
-use clap::Parser;
 use neptune_cash::application::config::cli_args;
+use neptune_cash::application::crash_report::RecentLogWriter;
 use neptune_cash::display_banner;
+use tracing_subscriber::fmt::writer::MakeWriterExt;
 use tracing_subscriber::EnvFilter;
 use tracing_subscriber::FmtSubscriber;
 
@@ -17,7 +18,7 @@ pub fn main() -> Result<()> {
 
     let run_result = tokio_runtime.block_on(async {
         // Fetch the CLI arguments
-        let args = cli_args::Args::parse();
+        let args = cli_args::Args::parse_with_config_file();
 
         #[cfg(not(feature = "tokio-console"))]
         {
@@ -56,7 +57,8 @@ pub fn main() -> Result<()> {
 
 /// Configure logger to use ISO-8601, of which rfc3339 is a subset. Install
 /// global collector configured based on RUST_LOG env var. Accepted `RUST_LOG`
-/// values are `trace`, `debug`, `info`, `warn`, and `error`.
+/// values are `trace`, `debug`, `info`, `warn`, and `error`. Recent log lines
+/// are also kept in memory, for inclusion in crash reports.
 fn set_up_logger() {
     let info_env_filter =
         EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info,tarpc=warn"));
@@ -64,6 +66,7 @@ fn set_up_logger() {
         .with_timer(tracing_subscriber::fmt::time::UtcTime::rfc_3339())
         .with_env_filter(info_env_filter)
         .with_thread_ids(true)
+        .with_writer(std::io::stdout.and(RecentLogWriter::default))
         .finish();
     tracing::subscriber::set_global_default(subscriber)
         .map_err(|_err| eprintln!("Unable to set global default subscriber"))
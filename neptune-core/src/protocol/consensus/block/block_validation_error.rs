@@ -1,10 +1,13 @@
+use serde::Deserialize;
+use serde::Serialize;
+
 use crate::util_types::mutator_set::removal_record::removal_record_list::RemovalRecordListUnpackError;
 
 /// The reasons why a [`Block`](crate::protocol::consensus::block::Block) can be
 /// invalid.
 ///
 /// Conversely, defines what it means for a block to be "valid".
-#[derive(Debug, Clone, Copy, thiserror::Error, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, thiserror::Error, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BlockValidationError {
     // 0. `previous_block` is consistent with current block
     ///   0.a) Block height is previous plus one
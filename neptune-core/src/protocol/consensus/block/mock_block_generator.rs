@@ -10,6 +10,7 @@ use crate::application::config::network::Network;
 use crate::application::loops::mine_loop::composer_parameters::ComposerParameters;
 use crate::application::loops::mine_loop::prepare_coinbase_transaction_stateless;
 use crate::protocol::consensus::block::block_transaction::BlockTransaction;
+use crate::protocol::consensus::block::block_validation_error::BlockValidationError;
 use crate::protocol::consensus::block::validity::block_primitive_witness::BlockPrimitiveWitness;
 use crate::protocol::consensus::block::validity::block_program::BlockProgram;
 use crate::protocol::consensus::block::validity::block_proof_witness::BlockProofWitness;
@@ -77,6 +78,27 @@ impl MockBlockGenerator {
         }
     }
 
+    /// Create `num_transactions` nop-transactions with mock proofs, each of
+    /// which can be merged into the block transaction of a successor of
+    /// `predecessor_block`.
+    pub fn mock_nop_transactions(
+        network: Network,
+        predecessor_block: &Block,
+        timestamp: Timestamp,
+        num_transactions: usize,
+    ) -> Result<Vec<Transaction>, BlockValidationError> {
+        let consensus_rule_set =
+            ConsensusRuleSet::infer_from(network, predecessor_block.header().height.next());
+        let nop_details = TransactionDetails::nop(
+            predecessor_block.mutator_set_accumulator_after()?,
+            timestamp,
+            network,
+        );
+        let nop_transaction = Self::mock_transaction_from_details(&nop_details, consensus_rule_set);
+
+        Ok(vec![nop_transaction; num_transactions])
+    }
+
     /// Merge two transactions for tests, without proving but such that the
     /// result seems valid.
     fn fake_merge_block_transactions_for_tests(
@@ -6,7 +6,7 @@ pub mod block_info;
 pub mod block_kernel;
 pub mod block_selector;
 pub(crate) mod block_transaction;
-mod block_validation_error;
+pub mod block_validation_error;
 pub mod difficulty_control;
 pub(crate) mod guesser_receiver_data;
 pub mod mock_block_generator;
@@ -723,15 +723,12 @@ impl Block {
 
         let consensus_rule_set = ConsensusRuleSet::infer_from(network, self.header().height);
 
-        // 0.a)
-        if previous_block.kernel.header.height.next() != self.kernel.header.height {
-            return Err(BlockValidationError::BlockHeight);
-        }
-
-        // 0.b)
-        if previous_block.hash() != self.kernel.header.prev_block_digest {
-            return Err(BlockValidationError::PrevBlockDigest);
-        }
+        // 0.a) and 0.b)
+        Self::validate_header_ancestry(
+            self.header(),
+            previous_block.header(),
+            previous_block.hash(),
+        )?;
 
         // 0.c)
         let mut mmra = previous_block.kernel.body.block_mmr_accumulator.clone();
@@ -740,46 +737,8 @@ impl Block {
             return Err(BlockValidationError::BlockMmrUpdate);
         }
 
-        // 0.d)
-        if previous_block.kernel.header.timestamp + network.minimum_block_time()
-            > self.kernel.header.timestamp
-        {
-            return Err(BlockValidationError::MinimumBlockTime);
-        }
-
-        // 0.e)
-        let expected_difficulty = if Self::should_reset_difficulty(
-            network,
-            self.header().timestamp,
-            previous_block.header().timestamp,
-        ) {
-            network.genesis_difficulty()
-        } else {
-            difficulty_control(
-                self.header().timestamp,
-                previous_block.header().timestamp,
-                previous_block.header().difficulty,
-                network.target_block_interval(),
-                previous_block.header().height,
-            )
-        };
-
-        if self.kernel.header.difficulty != expected_difficulty {
-            return Err(BlockValidationError::Difficulty);
-        }
-
-        // 0.f)
-        let expected_cumulative_proof_of_work =
-            previous_block.header().cumulative_proof_of_work + previous_block.header().difficulty;
-        if self.header().cumulative_proof_of_work != expected_cumulative_proof_of_work {
-            return Err(BlockValidationError::CumulativeProofOfWork);
-        }
-
-        // 0.g)
-        let future_limit = now + FUTUREDATING_LIMIT;
-        if self.kernel.header.timestamp >= future_limit {
-            return Err(BlockValidationError::FutureDating);
-        }
+        // 0.d) through 0.g)
+        Self::validate_header_progression(self.header(), previous_block.header(), now, network)?;
 
         // 1.a)
         for required_claim in BlockAppendix::consensus_claims(self.body(), consensus_rule_set) {
@@ -912,6 +871,92 @@ impl Block {
         elapsed_interval >= reset_interval
     }
 
+    /// Verify a block header against the header of the previous block, without
+    /// access to the rest of either block.
+    ///
+    /// Performs those checks of [`Self::validate`] that only involve headers,
+    /// which lets a node decide whether a block announced by its header is
+    /// worth downloading. Like [`Self::validate`], this function does **not**
+    /// check the proof-of-work; see [`Self::header_has_proof_of_work`].
+    pub(crate) fn validate_header(
+        header: &BlockHeader,
+        previous_header: &BlockHeader,
+        previous_digest: Digest,
+        now: Timestamp,
+        network: Network,
+    ) -> Result<(), BlockValidationError> {
+        Self::validate_header_ancestry(header, previous_header, previous_digest)?;
+        Self::validate_header_progression(header, previous_header, now, network)
+    }
+
+    /// Checks 0.a) and 0.b) of [`Self::validate`].
+    fn validate_header_ancestry(
+        header: &BlockHeader,
+        previous_header: &BlockHeader,
+        previous_digest: Digest,
+    ) -> Result<(), BlockValidationError> {
+        // 0.a)
+        if previous_header.height.next() != header.height {
+            return Err(BlockValidationError::BlockHeight);
+        }
+
+        // 0.b)
+        if previous_digest != header.prev_block_digest {
+            return Err(BlockValidationError::PrevBlockDigest);
+        }
+
+        Ok(())
+    }
+
+    /// Checks 0.d) through 0.g) of [`Self::validate`].
+    fn validate_header_progression(
+        header: &BlockHeader,
+        previous_header: &BlockHeader,
+        now: Timestamp,
+        network: Network,
+    ) -> Result<(), BlockValidationError> {
+        // 0.d)
+        if previous_header.timestamp + network.minimum_block_time() > header.timestamp {
+            return Err(BlockValidationError::MinimumBlockTime);
+        }
+
+        // 0.e)
+        let expected_difficulty = if Self::should_reset_difficulty(
+            network,
+            header.timestamp,
+            previous_header.timestamp,
+        ) {
+            network.genesis_difficulty()
+        } else {
+            difficulty_control(
+                header.timestamp,
+                previous_header.timestamp,
+                previous_header.difficulty,
+                network.target_block_interval(),
+                previous_header.height,
+            )
+        };
+
+        if header.difficulty != expected_difficulty {
+            return Err(BlockValidationError::Difficulty);
+        }
+
+        // 0.f)
+        let expected_cumulative_proof_of_work =
+            previous_header.cumulative_proof_of_work + previous_header.difficulty;
+        if header.cumulative_proof_of_work != expected_cumulative_proof_of_work {
+            return Err(BlockValidationError::CumulativeProofOfWork);
+        }
+
+        // 0.g)
+        let future_limit = now + FUTUREDATING_LIMIT;
+        if header.timestamp >= future_limit {
+            return Err(BlockValidationError::FutureDating);
+        }
+
+        Ok(())
+    }
+
     /// Determine whether the proof-of-work puzzle was solved correctly.
     ///
     /// Specifically, compare the hash of the current block against the
@@ -941,6 +986,45 @@ impl Block {
         self.pow_verify(threshold, consensus_rule_set)
     }
 
+    /// Like [`Self::has_proof_of_work`], but for a block of which only the
+    /// header and the MAST authentication paths of its `pow` field are known.
+    ///
+    /// Also returns false if the authentication paths do not belong to the
+    /// header.
+    pub(crate) fn header_has_proof_of_work(
+        header: &BlockHeader,
+        pow_mast_paths: PowMastPaths,
+        network: Network,
+        previous_block_header: &BlockHeader,
+    ) -> bool {
+        if !pow_mast_paths.agrees_with_header(header) {
+            return false;
+        }
+
+        if Self::should_reset_difficulty(network, header.timestamp, previous_block_header.timestamp)
+            && header.difficulty == network.genesis_difficulty()
+        {
+            return true;
+        }
+
+        let threshold = previous_block_header.difficulty.target();
+        if network.allows_mock_pow() && pow_mast_paths.fast_mast_hash(header.pow) <= threshold {
+            return true;
+        }
+
+        let consensus_rule_set =
+            ConsensusRuleSet::infer_from(network, previous_block_header.height.next());
+        header
+            .pow
+            .validate(
+                pow_mast_paths,
+                threshold,
+                consensus_rule_set,
+                header.prev_block_digest,
+            )
+            .is_ok()
+    }
+
     /// Produce the MAST authentication paths for the `pow` field on
     /// [`BlockHeader`], against the block MAST hash.
     pub(crate) fn pow_mast_paths(&self) -> PowMastPaths {
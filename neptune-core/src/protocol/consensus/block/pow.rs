@@ -22,6 +22,7 @@ use tasm_lib::twenty_first::bfe_array;
 
 use crate::application::loops::channel::Cancelable;
 use crate::protocol::consensus::block::block_header::BlockHeader;
+use crate::protocol::consensus::block::block_header::BlockHeaderField;
 use crate::protocol::consensus::block::block_kernel::BlockKernel;
 use crate::protocol::consensus::block::Block;
 use crate::protocol::consensus::consensus_rule_set::ConsensusRuleSet;
@@ -221,6 +222,13 @@ impl PowMastPaths {
         )
     }
 
+    /// Returns true iff the authentication path of the `pow` field is that of
+    /// the given header, *i.e.*, iff these paths authenticate the header and
+    /// not only its `pow` field.
+    pub(crate) fn agrees_with_header(&self, header: &BlockHeader) -> bool {
+        BlockHeader::mast_path(header, BlockHeaderField::Pow) == self.pow
+    }
+
     pub fn fast_mast_hash<const MERKLE_TREE_HEIGHT: usize>(
         self,
         pow: Pow<MERKLE_TREE_HEIGHT>,
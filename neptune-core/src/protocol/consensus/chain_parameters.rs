@@ -0,0 +1,244 @@
+//! The consensus constants in effect on a network.
+//!
+//! Tooling that needs eg the maximum block size or the subsidy schedule should
+//! ask the node for its [`ChainParameters`] rather than hard-code them, as they
+//! differ between networks and change with hard forks.
+
+use std::fmt::Display;
+
+use serde::Deserialize;
+use serde::Serialize;
+use strum::IntoEnumIterator;
+
+use super::block::block_height::BLOCKS_PER_GENERATION;
+use super::block::block_height::NUM_BLOCKS_SKIPPED_BECAUSE_REBOOT;
+use super::block::FUTUREDATING_LIMIT;
+use super::block::INITIAL_BLOCK_SUBSIDY;
+use super::block::MINING_REWARD_TIME_LOCK_PERIOD;
+use super::block::PREMINE_MAX_SIZE;
+use super::consensus_rule_set::ConsensusRuleSet;
+use crate::api::export::BlockHeight;
+use crate::api::export::NativeCurrencyAmount;
+use crate::api::export::Network;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+
+/// The limits imposed by one [`ConsensusRuleSet`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleSetParameters {
+    pub rule_set: ConsensusRuleSet,
+
+    /// Height of the first block following this rule set, or `None` if the
+    /// network never follows it.
+    pub activation_height: Option<BlockHeight>,
+
+    /// Maximum block size in number of `BFieldElement`s.
+    pub max_block_size: usize,
+    pub max_num_inputs: usize,
+    pub max_num_outputs: usize,
+    pub max_num_announcements: usize,
+}
+
+impl RuleSetParameters {
+    fn new(rule_set: ConsensusRuleSet, network: Network) -> Self {
+        Self {
+            rule_set,
+            activation_height: rule_set.activation_height(network),
+            max_block_size: rule_set.max_block_size(),
+            max_num_inputs: rule_set.max_num_inputs(),
+            max_num_outputs: rule_set.max_num_outputs(),
+            max_num_announcements: rule_set.max_num_announcements(),
+        }
+    }
+}
+
+/// All consensus constants in effect on a [`Network`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainParameters {
+    pub network: Network,
+
+    /// The limits of every rule set, in order of activation. Rule sets the
+    /// network never follows come last.
+    pub rule_sets: Vec<RuleSetParameters>,
+
+    /// Desired average time between blocks.
+    pub target_block_interval: Timestamp,
+
+    /// Blocks spaced apart by less than this are invalid.
+    pub minimum_block_time: Timestamp,
+
+    /// Blocks whose timestamp exceeds the current time by this much or more
+    /// are invalid.
+    pub futuredating_limit: Timestamp,
+
+    /// Half of every block subsidy and guesser fee is time locked for this
+    /// period.
+    pub mining_reward_time_lock_period: Timestamp,
+
+    /// The subsidy of blocks of the first generation. It halves with every
+    /// generation.
+    pub initial_block_subsidy: NativeCurrencyAmount,
+
+    /// The number of blocks in a generation.
+    pub blocks_per_generation: u64,
+
+    /// The number of blocks of the chain preceding the reboot, which count
+    /// towards the first generation.
+    pub num_blocks_skipped_because_reboot: u64,
+
+    pub premine_max_size: NativeCurrencyAmount,
+}
+
+impl ChainParameters {
+    /// The consensus constants of `network`.
+    pub fn for_network(network: Network) -> Self {
+        let mut rule_sets = ConsensusRuleSet::iter()
+            .map(|rule_set| RuleSetParameters::new(rule_set, network))
+            .collect::<Vec<_>>();
+        rule_sets.sort_by_key(|parameters| {
+            (
+                parameters.activation_height.is_none(),
+                parameters.activation_height,
+            )
+        });
+
+        Self {
+            network,
+            rule_sets,
+            target_block_interval: network.target_block_interval(),
+            minimum_block_time: network.minimum_block_time(),
+            futuredating_limit: FUTUREDATING_LIMIT,
+            mining_reward_time_lock_period: MINING_REWARD_TIME_LOCK_PERIOD,
+            initial_block_subsidy: INITIAL_BLOCK_SUBSIDY,
+            blocks_per_generation: BLOCKS_PER_GENERATION,
+            num_blocks_skipped_because_reboot: NUM_BLOCKS_SKIPPED_BECAUSE_REBOOT,
+            premine_max_size: PREMINE_MAX_SIZE,
+        }
+    }
+
+    /// The limits in effect for the block at `height`.
+    pub fn rule_set_at(&self, height: BlockHeight) -> Option<&RuleSetParameters> {
+        self.rule_sets
+            .iter()
+            .rev()
+            .find(|parameters| parameters.activation_height.is_some_and(|h| h <= height))
+    }
+
+    /// The block subsidy at `height`, according to the subsidy schedule.
+    pub fn block_subsidy(&self, height: BlockHeight) -> NativeCurrencyAmount {
+        let generation = height
+            .value()
+            .saturating_add(self.num_blocks_skipped_because_reboot)
+            / self.blocks_per_generation;
+
+        let mut subsidy = self.initial_block_subsidy;
+        for _ in 0..generation {
+            subsidy.div_two();
+            if subsidy.is_zero() {
+                break;
+            }
+        }
+
+        subsidy
+    }
+}
+
+impl Display for RuleSetParameters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.activation_height {
+            Some(height) => write!(f, "{} (from height {height})", self.rule_set)?,
+            None => write!(f, "{} (never active)", self.rule_set)?,
+        }
+        write!(
+            f,
+            ": max block size {}, max inputs {}, max outputs {}, max announcements {}",
+            self.max_block_size,
+            self.max_num_inputs,
+            self.max_num_outputs,
+            self.max_num_announcements
+        )
+    }
+}
+
+impl Display for ChainParameters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "network: {}", self.network)?;
+        writeln!(f, "rule sets:")?;
+        for rule_set in &self.rule_sets {
+            writeln!(f, "  {rule_set}")?;
+        }
+        writeln!(
+            f,
+            "target block interval: {} ms",
+            self.target_block_interval.to_millis()
+        )?;
+        writeln!(
+            f,
+            "minimum block time: {} ms",
+            self.minimum_block_time.to_millis()
+        )?;
+        writeln!(
+            f,
+            "futuredating limit: {}",
+            self.futuredating_limit.format_human_duration()
+        )?;
+        writeln!(
+            f,
+            "mining reward time lock: {}",
+            self.mining_reward_time_lock_period.format_human_duration()
+        )?;
+        writeln!(f, "initial block subsidy: {}", self.initial_block_subsidy)?;
+        writeln!(f, "blocks per generation: {}", self.blocks_per_generation)?;
+        writeln!(
+            f,
+            "blocks skipped because of reboot: {}",
+            self.num_blocks_skipped_because_reboot
+        )?;
+        writeln!(f, "premine max size: {}", self.premine_max_size)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+    use crate::protocol::consensus::block::Block;
+
+    #[test]
+    fn rule_sets_agree_with_consensus_rules() {
+        for network in [
+            Network::Main,
+            Network::TestnetMock,
+            Network::RegTest,
+            Network::Testnet(0),
+        ] {
+            let parameters = ChainParameters::for_network(network);
+            for height in [0u64, 119, 120, 14_999, 15_000, 1_000_000] {
+                let height = BlockHeight::from(height);
+                let expected = ConsensusRuleSet::infer_from(network, height);
+                let actual = parameters.rule_set_at(height).unwrap();
+                assert_eq!(expected, actual.rule_set, "{network} at height {height}");
+                assert_eq!(expected.max_block_size(), actual.max_block_size);
+            }
+        }
+    }
+
+    #[test]
+    fn subsidy_schedule_agrees_with_block_subsidy() {
+        let parameters = ChainParameters::for_network(Network::Main);
+        let first_halving = BLOCKS_PER_GENERATION - NUM_BLOCKS_SKIPPED_BECAUSE_REBOOT;
+        for height in [
+            0,
+            1,
+            first_halving - 1,
+            first_halving,
+            first_halving + 10 * BLOCKS_PER_GENERATION,
+            BlockHeight::MAX,
+        ] {
+            let height = BlockHeight::from(height);
+            assert_eq!(
+                Block::block_subsidy(height),
+                parameters.block_subsidy(height)
+            );
+        }
+    }
+}
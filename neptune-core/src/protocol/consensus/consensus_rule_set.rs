@@ -1,3 +1,5 @@
+use serde::Deserialize;
+use serde::Serialize;
 use strum_macros::EnumIter;
 
 use crate::api::export::BlockHeight;
@@ -23,7 +25,18 @@ pub const BLOCK_HEIGHT_HARDFORK_ALPHA_TESTNET: BlockHeight =
 /// Consensus logic not captured by this encapsulation lives on
 /// [`Transaction::is_valid`][super::transaction::Transaction::is_valid] and
 /// ultimately [`Block::is_valid`][super::block::Block::is_valid].
-#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Default, strum_macros::Display)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    EnumIter,
+    Default,
+    strum_macros::Display,
+    Serialize,
+    Deserialize,
+)]
 pub enum ConsensusRuleSet {
     #[default]
     Reboot,
@@ -68,6 +81,29 @@ impl ConsensusRuleSet {
         }
     }
 
+    /// Height of the first block that follows this [`ConsensusRuleSet`] on the
+    /// given [`Network`], or `None` if the network never follows it.
+    ///
+    /// Agrees with [`Self::infer_from`]: a rule set applies from its activation
+    /// height until the activation height of the next one.
+    pub(crate) fn activation_height(&self, network: Network) -> Option<BlockHeight> {
+        match (self, network) {
+            (ConsensusRuleSet::Reboot, Network::Main | Network::Testnet(_)) => {
+                Some(BlockHeight::genesis())
+            }
+            (ConsensusRuleSet::Reboot, Network::TestnetMock | Network::RegTest) => None,
+            (ConsensusRuleSet::HardforkAlpha, Network::Main) => {
+                Some(BLOCK_HEIGHT_HARDFORK_ALPHA_MAIN_NET)
+            }
+            (ConsensusRuleSet::HardforkAlpha, Network::Testnet(_)) => {
+                Some(BLOCK_HEIGHT_HARDFORK_ALPHA_TESTNET)
+            }
+            (ConsensusRuleSet::HardforkAlpha, Network::TestnetMock | Network::RegTest) => {
+                Some(BlockHeight::genesis())
+            }
+        }
+    }
+
     pub(crate) fn max_num_inputs(&self) -> usize {
         match self {
             ConsensusRuleSet::Reboot | ConsensusRuleSet::HardforkAlpha => {
@@ -101,6 +137,7 @@ pub(crate) mod tests {
     use rand::rngs::StdRng;
     use rand::Rng;
     use rand::SeedableRng;
+    use strum::IntoEnumIterator;
     use tracing_test::traced_test;
 
     use super::*;
@@ -261,6 +298,40 @@ pub(crate) mod tests {
         .unwrap()
     }
 
+    #[test]
+    fn activation_heights_agree_with_inferred_rule_set() {
+        for network in [
+            Network::Main,
+            Network::TestnetMock,
+            Network::RegTest,
+            Network::Testnet(0),
+        ] {
+            for rule_set in ConsensusRuleSet::iter() {
+                let Some(activation_height) = rule_set.activation_height(network) else {
+                    continue;
+                };
+                assert_eq!(
+                    rule_set,
+                    ConsensusRuleSet::infer_from(network, activation_height),
+                    "{rule_set} must apply from its activation height on {network}"
+                );
+                if let Some(previous_height) = activation_height.previous() {
+                    assert_ne!(
+                        rule_set,
+                        ConsensusRuleSet::infer_from(network, previous_height),
+                        "{rule_set} must not apply before its activation height on {network}"
+                    );
+                }
+            }
+
+            let genesis_rule_set = ConsensusRuleSet::infer_from(network, BlockHeight::genesis());
+            assert_eq!(
+                Some(BlockHeight::genesis()),
+                genesis_rule_set.activation_height(network)
+            );
+        }
+    }
+
     #[traced_test]
     #[test]
     fn new_blocks_at_block_height_10_000() {
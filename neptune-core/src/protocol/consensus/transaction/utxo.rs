@@ -171,6 +171,14 @@ impl Utxo {
     /// native currency unspendable.)
     pub fn get_native_currency_amount(&self) -> NativeCurrencyAmount {
         crate::macros::log_slow_scope!();
+        self.native_currency_amounts().sum()
+    }
+
+    /// Get the amounts of the individual native currency coins in this UTXO.
+    ///
+    /// Unlike [`Self::get_native_currency_amount`], this lets the caller
+    /// validate each amount and add them up without risking overflow.
+    pub fn native_currency_amounts(&self) -> impl Iterator<Item = NativeCurrencyAmount> + '_ {
         self.coins
             .iter()
             .filter(|coin| coin.type_script_hash == NativeCurrency.hash())
@@ -178,7 +186,6 @@ impl Utxo {
                 Ok(boxed_amount) => *boxed_amount,
                 Err(_) => NativeCurrencyAmount::zero(),
             })
-            .sum()
     }
 
     /// If the UTXO has a timelock, find out what the release date is.
@@ -46,7 +46,7 @@ use crate::triton_vm::prelude::triton_instr;
 /// program related to block validity, it is important to use `safe_add` rather than `+` as
 /// the latter operation does not care about overflow. Not testing for overflow can cause
 /// inflation bugs.
-#[derive(Clone, Debug, Copy, Serialize, Deserialize, Eq, Default, BFieldCodec)]
+#[derive(Clone, Debug, Copy, Serialize, Deserialize, Eq, Hash, Default, BFieldCodec)]
 pub struct NativeCurrencyAmount(i128);
 
 impl TasmObject for NativeCurrencyAmount {
@@ -85,7 +85,7 @@ impl NativeCurrencyAmount {
     /// The conversion factor is 10^30 * 2^2.
     /// It is such that 42 000 000 * 10^30 * 2^2 is just one bit shy of being 128 bits
     /// wide. The one shy bit is used for the sign.
-    const fn conversion_factor() -> i128 {
+    pub(crate) const fn conversion_factor() -> i128 {
         let mut product = 1i128;
         let ten = 10i128;
         let mut i = 0;
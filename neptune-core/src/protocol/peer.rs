@@ -1,24 +1,46 @@
+pub mod bandwidth;
+pub(crate) mod block_chunk;
+pub(crate) mod block_relay;
+pub mod connection_lifecycle;
+pub(crate) mod fork_signaling;
 pub(crate) mod handshake_data;
+pub(crate) mod message_sequence;
 pub mod peer_block_notifications;
+pub(crate) mod peer_codec;
 pub mod peer_info;
+pub mod peer_latency;
+pub(crate) mod peer_message_format;
+pub mod plausible_deniability;
+pub(crate) mod rendezvous;
+pub(crate) mod transaction_announcements;
 pub mod transaction_notification;
 pub mod transfer_block;
 pub mod transfer_transaction;
+pub(crate) mod wallet_backup;
 
 use std::fmt::Display;
 use std::net::SocketAddr;
 use std::time::SystemTime;
 
+use bandwidth::BandwidthCategory;
+use block_chunk::BlockChunk;
+use block_chunk::BlockChunkAssembler;
 use handshake_data::HandshakeData;
 use itertools::Itertools;
+use message_sequence::ReplayWindow;
 use num_bigint::BigUint;
 use num_traits::ToPrimitive;
 use num_traits::Zero;
+use peer_block_notifications::PeerBlockHeaderNotification;
 use peer_block_notifications::PeerBlockNotification;
+use peer_latency::Ping;
+use peer_latency::Pong;
 use rand::rngs::StdRng;
 use rand::Rng;
 use rand::RngCore;
 use rand::SeedableRng;
+use rendezvous::RendezvousIntroduction;
+use rendezvous::RendezvousRequest;
 use serde::Deserialize;
 use serde::Serialize;
 use tasm_lib::twenty_first::prelude::Mmr;
@@ -30,6 +52,7 @@ use tracing::trace;
 use tracing::warn;
 use transaction_notification::TransactionNotification;
 use transfer_transaction::TransferTransaction;
+use wallet_backup::WalletBackupMessage;
 
 use super::consensus::block::block_header::BlockHeader;
 use super::consensus::block::block_header::BlockHeaderWithBlockHashWitness;
@@ -109,6 +132,17 @@ pub enum NegativePeerSanction {
     /// bandwidth) to respond to.
     ReceivedSyncChallenge,
     UnrelayableTransaction,
+
+    /// A block header notification whose header is inconsistent with its
+    /// parent or lacks proof-of-work.
+    InvalidBlockHeader(Digest),
+
+    /// A frame whose sequence number was already received, or that skips
+    /// sequence numbers.
+    OutOfSequenceMessage,
+
+    /// An announcement that repeats one the peer made recently.
+    ReplayedAnnouncement,
 }
 
 /// The reason for improving a peer's standing
@@ -184,6 +218,9 @@ impl Display for NegativePeerSanction {
             NegativePeerSanction::FishyDifficultiesChallengeResponse => "fishy difficulties",
             NegativePeerSanction::ReceivedSyncChallenge => "received sync challenge",
             NegativePeerSanction::UnrelayableTransaction => "unrelayable transaction",
+            NegativePeerSanction::InvalidBlockHeader(_) => "invalid block header",
+            NegativePeerSanction::OutOfSequenceMessage => "out-of-sequence message",
+            NegativePeerSanction::ReplayedAnnouncement => "replayed announcement",
         };
         write!(f, "{string}")
     }
@@ -263,6 +300,9 @@ impl Sanction for NegativePeerSanction {
             NegativePeerSanction::FishyDifficultiesChallengeResponse => -51,
             NegativePeerSanction::ReceivedSyncChallenge => -50,
             NegativePeerSanction::UnrelayableTransaction => -10,
+            NegativePeerSanction::InvalidBlockHeader(_) => -10,
+            NegativePeerSanction::OutOfSequenceMessage => -10,
+            NegativePeerSanction::ReplayedAnnouncement => -1,
         }
     }
 }
@@ -479,6 +519,8 @@ pub(crate) enum PeerMessage {
 
     BlockRequestBatch(BlockRequestBatch), // TODO: Consider restricting this in size
     BlockResponseBatch(Vec<(TransferBlock, MmrMembershipProof)>), // TODO: Consider restricting this in size
+    /// Sent instead of requested blocks that this node cannot serve, eg
+    /// because their bodies were pruned.
     UnableToSatisfyBatchRequest,
 
     SyncChallenge(SyncChallenge),
@@ -505,6 +547,25 @@ pub(crate) enum PeerMessage {
     /// Inform peer that we are disconnecting them.
     Bye,
     ConnectionStatus(TransferConnectionStatus),
+    /// Like `BlockNotification`, but carrying the block's header. Only sent to
+    /// peers that advertise support for it.
+    BlockHeaderNotification(Box<PeerBlockHeaderNotification>),
+    /// Request a [`Pong`] for measuring latency and clock offset. Only sent to
+    /// peers that advertise support for it.
+    Ping(Ping),
+    Pong(Pong),
+    /// Ask for an introduction to another peer. Only sent to peers that
+    /// advertise support for it.
+    RendezvousRequest(RendezvousRequest),
+    /// Introduce a peer to connect to by simultaneous open. Only sent to peers
+    /// that advertise support for it.
+    RendezvousIntroduction(RendezvousIntroduction),
+    /// Part of a requested block's encoding. Only sent to peers that
+    /// advertise support for it.
+    BlockChunk(BlockChunk),
+    /// Replicate encrypted wallet backups. Only sent to trusted peers that
+    /// advertise support for it.
+    WalletBackup(Box<WalletBackupMessage>),
     // New variants must be added here at the bottom to be backwards compatible.
 }
 
@@ -532,10 +593,51 @@ impl PeerMessage {
             PeerMessage::UnableToSatisfyBatchRequest => "unable to satisfy batch request",
             PeerMessage::SyncChallenge(_) => "sync challenge",
             PeerMessage::SyncChallengeResponse(_) => "sync challenge response",
+            PeerMessage::BlockHeaderNotification(_) => "block header notification",
+            PeerMessage::Ping(_) => "ping",
+            PeerMessage::Pong(_) => "pong",
+            PeerMessage::RendezvousRequest(_) => "rendezvous request",
+            PeerMessage::RendezvousIntroduction(_) => "rendezvous introduction",
+            PeerMessage::BlockChunk(_) => "block chunk",
+            PeerMessage::WalletBackup(_) => "wallet backup",
         }
         .to_string()
     }
 
+    /// The category whose bandwidth this message counts toward.
+    pub(crate) fn bandwidth_category(&self) -> BandwidthCategory {
+        match self {
+            PeerMessage::Handshake { .. } => BandwidthCategory::Other,
+            PeerMessage::Block(_) => BandwidthCategory::Blocks,
+            PeerMessage::BlockNotificationRequest => BandwidthCategory::Blocks,
+            PeerMessage::BlockNotification(_) => BandwidthCategory::Blocks,
+            PeerMessage::BlockRequestByHeight(_) => BandwidthCategory::Blocks,
+            PeerMessage::BlockRequestByHash(_) => BandwidthCategory::Blocks,
+            PeerMessage::BlockRequestBatch(_) => BandwidthCategory::Sync,
+            PeerMessage::BlockResponseBatch(_) => BandwidthCategory::Sync,
+            PeerMessage::Transaction(_) => BandwidthCategory::Transactions,
+            PeerMessage::TransactionNotification(_) => BandwidthCategory::Transactions,
+            PeerMessage::TransactionRequest(_) => BandwidthCategory::Transactions,
+            PeerMessage::PeerListRequest => BandwidthCategory::Other,
+            PeerMessage::PeerListResponse(_) => BandwidthCategory::Other,
+            PeerMessage::Bye => BandwidthCategory::Other,
+            PeerMessage::ConnectionStatus(_) => BandwidthCategory::Other,
+            PeerMessage::BlockProposalNotification(_) => BandwidthCategory::Blocks,
+            PeerMessage::BlockProposalRequest(_) => BandwidthCategory::Blocks,
+            PeerMessage::BlockProposal(_) => BandwidthCategory::Blocks,
+            PeerMessage::UnableToSatisfyBatchRequest => BandwidthCategory::Sync,
+            PeerMessage::SyncChallenge(_) => BandwidthCategory::Sync,
+            PeerMessage::SyncChallengeResponse(_) => BandwidthCategory::Sync,
+            PeerMessage::BlockHeaderNotification(_) => BandwidthCategory::Blocks,
+            PeerMessage::Ping(_) => BandwidthCategory::Pings,
+            PeerMessage::Pong(_) => BandwidthCategory::Pings,
+            PeerMessage::RendezvousRequest(_) => BandwidthCategory::Other,
+            PeerMessage::RendezvousIntroduction(_) => BandwidthCategory::Other,
+            PeerMessage::BlockChunk(_) => BandwidthCategory::Blocks,
+            PeerMessage::WalletBackup(_) => BandwidthCategory::Other,
+        }
+    }
+
     pub fn ignore_when_not_sync(&self) -> bool {
         match self {
             PeerMessage::Handshake { .. } => false,
@@ -559,6 +661,13 @@ impl PeerMessage {
             PeerMessage::UnableToSatisfyBatchRequest => true,
             PeerMessage::SyncChallenge(_) => false,
             PeerMessage::SyncChallengeResponse(_) => false,
+            PeerMessage::BlockHeaderNotification(_) => false,
+            PeerMessage::Ping(_) => false,
+            PeerMessage::Pong(_) => false,
+            PeerMessage::RendezvousRequest(_) => false,
+            PeerMessage::RendezvousIntroduction(_) => false,
+            PeerMessage::BlockChunk(_) => false,
+            PeerMessage::WalletBackup(_) => false,
         }
     }
 
@@ -586,6 +695,13 @@ impl PeerMessage {
             PeerMessage::UnableToSatisfyBatchRequest => false,
             PeerMessage::SyncChallenge(_) => false,
             PeerMessage::SyncChallengeResponse(_) => false,
+            PeerMessage::BlockHeaderNotification(_) => false,
+            PeerMessage::Ping(_) => false,
+            PeerMessage::Pong(_) => false,
+            PeerMessage::RendezvousRequest(_) => false,
+            PeerMessage::RendezvousIntroduction(_) => false,
+            PeerMessage::BlockChunk(_) => true,
+            PeerMessage::WalletBackup(_) => false,
         }
     }
 
@@ -614,6 +730,13 @@ impl PeerMessage {
             PeerMessage::PeerListResponse(_) => false,
             PeerMessage::Bye => false,
             PeerMessage::ConnectionStatus(_) => false,
+            PeerMessage::BlockHeaderNotification(_) => true,
+            PeerMessage::Ping(_) => false,
+            PeerMessage::Pong(_) => false,
+            PeerMessage::RendezvousRequest(_) => false,
+            PeerMessage::RendezvousIntroduction(_) => false,
+            PeerMessage::BlockChunk(_) => true,
+            PeerMessage::WalletBackup(_) => true,
         }
     }
 }
@@ -630,6 +753,25 @@ pub struct MutablePeerState {
     ///
     /// Used to prevent issuing multiple sync challenges in short succession.
     pub(crate) successful_sync_challenge_response_time: Option<Timestamp>,
+
+    /// A new block waiting to be relayed to the peer, and when to relay it.
+    /// Blocks are relayed to better-propagating peers first.
+    pub(crate) delayed_block_relay: Option<(Box<Block>, tokio::time::Instant)>,
+
+    /// The most recent ping sent to the peer, until it is answered.
+    pub(crate) outstanding_ping: Option<Ping>,
+
+    /// When the peer last asked this node for an introduction.
+    pub(crate) last_rendezvous_request: Option<Timestamp>,
+
+    /// When the peer last introduced this node to another peer.
+    pub(crate) last_rendezvous_introduction: Option<Timestamp>,
+
+    /// The block the peer is sending in chunks, until all have arrived.
+    pub(crate) incoming_block_chunks: Option<BlockChunkAssembler>,
+
+    /// The announcements the peer made most recently.
+    pub(crate) recent_announcements: ReplayWindow,
 }
 
 impl MutablePeerState {
@@ -639,6 +781,12 @@ impl MutablePeerState {
             fork_reconciliation_blocks: vec![],
             sync_challenge: None,
             successful_sync_challenge_response_time: None,
+            delayed_block_relay: None,
+            outstanding_ping: None,
+            last_rendezvous_request: None,
+            last_rendezvous_introduction: None,
+            incoming_block_chunks: None,
+            recent_announcements: ReplayWindow::default(),
         }
     }
 }
@@ -0,0 +1,246 @@
+//! Accounting of the bytes exchanged with peers, by message category and by
+//! peer.
+//!
+//! Every connection records the size of each message it encodes or decodes,
+//! before compression, into counters of its own and into the node's totals.
+//! The totals cover the node's entire run time, whereas per-peer counters are
+//! only reported while the peer is connected.
+
+use std::net::SocketAddr;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use serde::Deserialize;
+use serde::Serialize;
+use strum::EnumCount;
+use strum::IntoEnumIterator;
+
+/// The kind of traffic a peer message belongs to.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    strum::Display,
+    strum::EnumCount,
+    strum::EnumIter,
+)]
+pub enum BandwidthCategory {
+    /// Blocks, block proposals, and notifications and requests for them.
+    Blocks,
+
+    /// Transactions, and notifications and requests for them.
+    Transactions,
+
+    /// Batches of blocks exchanged while syncing, and sync challenges.
+    Sync,
+
+    /// Pings and pongs measuring latency.
+    Pings,
+
+    /// Handshakes, peer lists, and connection management.
+    Other,
+}
+
+/// Bytes exchanged in one [`BandwidthCategory`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CategoryUsage {
+    pub category: BandwidthCategory,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+/// Bytes exchanged, split by [`BandwidthCategory`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BandwidthUsage {
+    pub categories: Vec<CategoryUsage>,
+}
+
+impl BandwidthUsage {
+    pub fn bytes_sent(&self) -> u64 {
+        self.categories.iter().map(|usage| usage.bytes_sent).sum()
+    }
+
+    pub fn bytes_received(&self) -> u64 {
+        self.categories
+            .iter()
+            .map(|usage| usage.bytes_received)
+            .sum()
+    }
+
+    /// Bytes sent and received.
+    pub fn bytes_total(&self) -> u64 {
+        self.bytes_sent() + self.bytes_received()
+    }
+}
+
+/// Bytes exchanged with a connected peer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerBandwidthUsage {
+    pub address: SocketAddr,
+    pub usage: BandwidthUsage,
+}
+
+/// The bandwidth used by the node, as reported by the `bandwidth` RPC
+/// endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BandwidthReport {
+    /// Bytes exchanged with all peers since the node started, including
+    /// peers that have since disconnected.
+    pub total: BandwidthUsage,
+
+    /// Bytes exchanged with each connected peer, most bytes first.
+    pub peers: Vec<PeerBandwidthUsage>,
+}
+
+#[derive(Debug, Default)]
+struct BandwidthCounters {
+    sent: [AtomicU64; BandwidthCategory::COUNT],
+    received: [AtomicU64; BandwidthCategory::COUNT],
+}
+
+impl BandwidthCounters {
+    fn usage(&self) -> BandwidthUsage {
+        let categories = BandwidthCategory::iter()
+            .map(|category| CategoryUsage {
+                category,
+                bytes_sent: self.sent[category as usize].load(Ordering::Relaxed),
+                bytes_received: self.received[category as usize].load(Ordering::Relaxed),
+            })
+            .collect();
+
+        BandwidthUsage { categories }
+    }
+}
+
+/// Records the traffic of a single connection.
+#[derive(Debug, Clone)]
+pub(crate) struct ConnectionBandwidth {
+    peer: Arc<BandwidthCounters>,
+    total: Arc<BandwidthCounters>,
+}
+
+impl ConnectionBandwidth {
+    pub(crate) fn record_sent(&self, category: BandwidthCategory, num_bytes: usize) {
+        for counters in [&self.peer, &self.total] {
+            counters.sent[category as usize].fetch_add(num_bytes as u64, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn record_received(&self, category: BandwidthCategory, num_bytes: usize) {
+        for counters in [&self.peer, &self.total] {
+            counters.received[category as usize].fetch_add(num_bytes as u64, Ordering::Relaxed);
+        }
+    }
+}
+
+/// The bandwidth counters of the node and of its connections.
+///
+/// A connection's counters are dropped from the registry once the connection
+/// no longer holds them, so disconnected peers do not accumulate.
+#[derive(Debug, Default)]
+pub(crate) struct BandwidthRegistry {
+    total: Arc<BandwidthCounters>,
+    peers: Mutex<Vec<(SocketAddr, Arc<BandwidthCounters>)>>,
+}
+
+impl BandwidthRegistry {
+    /// Start accounting for a new connection to `peer_address`.
+    pub(crate) fn register(&self, peer_address: SocketAddr) -> ConnectionBandwidth {
+        let peer = Arc::new(BandwidthCounters::default());
+        let mut peers = self.peers.lock().unwrap();
+        peers.retain(|(_, counters)| Arc::strong_count(counters) > 1);
+        peers.push((peer_address, peer.clone()));
+
+        ConnectionBandwidth {
+            peer,
+            total: self.total.clone(),
+        }
+    }
+
+    pub(crate) fn report(&self) -> BandwidthReport {
+        let peers = self.peers.lock().unwrap();
+        let mut peers = peers
+            .iter()
+            .filter(|(_, counters)| Arc::strong_count(counters) > 1)
+            .map(|(address, counters)| PeerBandwidthUsage {
+                address: *address,
+                usage: counters.usage(),
+            })
+            .collect::<Vec<_>>();
+        peers.sort_by_key(|peer| std::cmp::Reverse(peer.usage.bytes_total()));
+
+        BandwidthReport {
+            total: self.total.usage(),
+            peers,
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    fn category_usage(usage: &BandwidthUsage, category: BandwidthCategory) -> CategoryUsage {
+        usage.categories[category as usize]
+    }
+
+    #[test]
+    fn traffic_is_attributed_to_peer_category_and_total() {
+        let registry = BandwidthRegistry::default();
+        let alice: SocketAddr = "127.0.0.1:9798".parse().unwrap();
+        let bob: SocketAddr = "127.0.0.2:9798".parse().unwrap();
+        let to_alice = registry.register(alice);
+        let to_bob = registry.register(bob);
+
+        to_alice.record_sent(BandwidthCategory::Blocks, 1000);
+        to_alice.record_received(BandwidthCategory::Pings, 10);
+        to_bob.record_received(BandwidthCategory::Transactions, 100);
+
+        let report = registry.report();
+        assert_eq!(1110, report.total.bytes_total());
+        assert_eq!(
+            vec![alice, bob],
+            report
+                .peers
+                .iter()
+                .map(|peer| peer.address)
+                .collect::<Vec<_>>()
+        );
+
+        let alice_usage = &report.peers[0].usage;
+        assert_eq!(1000, alice_usage.bytes_sent());
+        assert_eq!(10, alice_usage.bytes_received());
+        assert_eq!(
+            CategoryUsage {
+                category: BandwidthCategory::Pings,
+                bytes_sent: 0,
+                bytes_received: 10,
+            },
+            category_usage(alice_usage, BandwidthCategory::Pings)
+        );
+        assert_eq!(
+            100,
+            category_usage(&report.total, BandwidthCategory::Transactions).bytes_received
+        );
+    }
+
+    #[test]
+    fn disconnected_peers_count_toward_total_only() {
+        let registry = BandwidthRegistry::default();
+        let to_alice = registry.register("127.0.0.1:9798".parse().unwrap());
+        to_alice.record_sent(BandwidthCategory::Sync, 500);
+        drop(to_alice);
+
+        let report = registry.report();
+        assert!(report.peers.is_empty());
+        assert_eq!(500, report.total.bytes_sent());
+    }
+}
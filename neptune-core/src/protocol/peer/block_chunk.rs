@@ -0,0 +1,229 @@
+//! Streaming of stored blocks to peers, in chunks.
+//!
+//! Answering a block request with a single [`PeerMessage::Block`] loads the
+//! whole block from disk, converts it, and encodes it, per request. With
+//! blocks of several megabytes and many peers requesting them at once, that
+//! spikes memory. Peers that advertise [`BLOCK_CHUNKS_CAPABILITY`] are instead
+//! sent the block's encoding as stored in the archival state, read from disk
+//! one [`BlockChunk`] at a time, so serving a block holds at most one chunk in
+//! memory per request. The recipient reassembles the chunks with a
+//! [`BlockChunkAssembler`] and verifies that they encode the announced block.
+//!
+//! [`PeerMessage::Block`]: super::PeerMessage::Block
+//! [`BLOCK_CHUNKS_CAPABILITY`]: super::handshake_data::BLOCK_CHUNKS_CAPABILITY
+
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::twenty_first::tip5::digest::Digest;
+
+use super::peer_message_format::MAX_BLOCK_MESSAGE_SIZE;
+use crate::protocol::consensus::block::Block;
+
+/// Maximum number of block bytes per chunk. Small enough for a
+/// [`BlockChunk`] message to stay within the size limit of messages that do
+/// not contain whole blocks.
+pub(crate) const BLOCK_CHUNK_SIZE: usize = 512 * 1024;
+
+/// A contiguous part of a block's encoding.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub(crate) struct BlockChunk {
+    /// Digest of the block the chunk is part of.
+    pub(crate) block_digest: Digest,
+
+    /// Length of the block's encoding, in bytes.
+    pub(crate) total_length: u64,
+
+    /// Position of the chunk's first byte in the block's encoding.
+    pub(crate) offset: u64,
+
+    pub(crate) bytes: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub(crate) enum BlockChunkError {
+    #[error("block of {0} bytes exceeds the maximum of {MAX_BLOCK_MESSAGE_SIZE} bytes")]
+    TooLarge(u64),
+
+    #[error("chunk at offset {offset} does not continue the block, expected offset {expected}")]
+    OutOfOrder { offset: u64, expected: u64 },
+
+    #[error("chunk is empty or exceeds the block's length")]
+    InvalidLength,
+
+    #[error("chunks do not encode a block: {0}")]
+    Undecodable(String),
+
+    #[error("chunks encode block {actual:x} instead of {announced:x}")]
+    WrongDigest { announced: Digest, actual: Digest },
+}
+
+/// Reassembles a block from the consecutive chunks of its encoding.
+#[derive(Debug, Clone)]
+pub(crate) struct BlockChunkAssembler {
+    block_digest: Digest,
+    total_length: u64,
+    bytes: Vec<u8>,
+}
+
+impl BlockChunkAssembler {
+    /// Add a chunk to the block being assembled in `pending`. A chunk at
+    /// offset zero starts a new block, discarding any incomplete one.
+    ///
+    /// Returns the block once all its chunks were received. On error, the
+    /// incomplete block is discarded.
+    pub(crate) fn receive(
+        pending: &mut Option<Self>,
+        chunk: BlockChunk,
+    ) -> Result<Option<Block>, BlockChunkError> {
+        let result = Self::receive_inner(pending, chunk);
+        if !matches!(result, Ok(None)) {
+            *pending = None;
+        }
+
+        result
+    }
+
+    fn receive_inner(
+        pending: &mut Option<Self>,
+        chunk: BlockChunk,
+    ) -> Result<Option<Block>, BlockChunkError> {
+        if chunk.offset == 0 {
+            if chunk.total_length > MAX_BLOCK_MESSAGE_SIZE as u64 {
+                return Err(BlockChunkError::TooLarge(chunk.total_length));
+            }
+
+            *pending = Some(Self {
+                block_digest: chunk.block_digest,
+                total_length: chunk.total_length,
+                bytes: vec![],
+            });
+        }
+
+        let Some(assembler) = pending.as_mut() else {
+            return Err(BlockChunkError::OutOfOrder {
+                offset: chunk.offset,
+                expected: 0,
+            });
+        };
+
+        let expected = assembler.bytes.len() as u64;
+        if chunk.block_digest != assembler.block_digest
+            || chunk.total_length != assembler.total_length
+            || chunk.offset != expected
+        {
+            return Err(BlockChunkError::OutOfOrder {
+                offset: chunk.offset,
+                expected,
+            });
+        }
+
+        let new_length = expected.saturating_add(chunk.bytes.len() as u64);
+        if chunk.bytes.is_empty() || new_length > assembler.total_length {
+            return Err(BlockChunkError::InvalidLength);
+        }
+
+        assembler.bytes.extend_from_slice(&chunk.bytes);
+        if new_length < assembler.total_length {
+            return Ok(None);
+        }
+
+        let block: Block = bincode::deserialize(&assembler.bytes)
+            .map_err(|e| BlockChunkError::Undecodable(e.to_string()))?;
+        if block.hash() != assembler.block_digest {
+            return Err(BlockChunkError::WrongDigest {
+                announced: assembler.block_digest,
+                actual: block.hash(),
+            });
+        }
+
+        Ok(Some(block))
+    }
+}
+
+/// Split a block's encoding into chunks of at most `chunk_size` bytes.
+#[cfg(test)]
+pub(crate) fn chunks_of(block: &Block, chunk_size: usize) -> Vec<BlockChunk> {
+    let bytes = bincode::serialize(block).unwrap();
+    let total_length = bytes.len() as u64;
+    bytes
+        .chunks(chunk_size)
+        .scan(0u64, |offset, chunk| {
+            let block_chunk = BlockChunk {
+                block_digest: block.hash(),
+                total_length,
+                offset: *offset,
+                bytes: chunk.to_vec(),
+            };
+            *offset += chunk.len() as u64;
+            Some(block_chunk)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+    use crate::application::config::network::Network;
+    use crate::tests::shared::blocks::invalid_empty_block;
+
+    #[test]
+    fn chunks_reassemble_to_block() {
+        let network = Network::Main;
+        let genesis = Block::genesis(network);
+        let block = invalid_empty_block(&genesis, network);
+        let chunks = chunks_of(&block, 1000);
+        assert!(chunks.len() > 1);
+
+        let mut pending = None;
+        let (last, init) = chunks.split_last().unwrap();
+        for chunk in init {
+            assert_eq!(
+                None,
+                BlockChunkAssembler::receive(&mut pending, chunk.clone()).unwrap()
+            );
+        }
+        let reassembled = BlockChunkAssembler::receive(&mut pending, last.clone())
+            .unwrap()
+            .unwrap();
+        assert_eq!(block.hash(), reassembled.hash());
+        assert!(pending.is_none());
+    }
+
+    #[test]
+    fn inconsistent_chunks_are_rejected() {
+        let network = Network::Main;
+        let block = Block::genesis(network);
+        let chunk = chunks_of(&block, usize::MAX).remove(0);
+        let mut pending = None;
+
+        let mut unstarted = chunk.clone();
+        unstarted.offset = 1;
+        assert!(BlockChunkAssembler::receive(&mut pending, unstarted).is_err());
+
+        let mut truncated = chunk.clone();
+        truncated.total_length += 1;
+        assert_eq!(
+            None,
+            BlockChunkAssembler::receive(&mut pending, truncated).unwrap()
+        );
+        let mut mismatched = chunk.clone();
+        mismatched.offset = chunk.bytes.len() as u64;
+        assert!(BlockChunkAssembler::receive(&mut pending, mismatched).is_err());
+        assert!(pending.is_none());
+
+        let mut wrong_digest = chunk;
+        wrong_digest.block_digest = Digest::default();
+        assert!(matches!(
+            BlockChunkAssembler::receive(&mut pending, wrong_digest),
+            Err(BlockChunkError::WrongDigest { .. })
+        ));
+
+        let mut oversized = chunks_of(&block, usize::MAX).remove(0);
+        oversized.total_length = MAX_BLOCK_MESSAGE_SIZE as u64 + 1;
+        assert_eq!(
+            Err(BlockChunkError::TooLarge(oversized.total_length)),
+            BlockChunkAssembler::receive(&mut pending, oversized)
+        );
+    }
+}
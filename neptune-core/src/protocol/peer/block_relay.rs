@@ -0,0 +1,170 @@
+//! Prioritization of peers when relaying new blocks.
+//!
+//! Peers that have historically been the first to announce new blocks, which
+//! then turned out valid, are likely well connected, so relaying to them first
+//! speeds up propagation through the network. Peers are ranked by the number
+//! of new blocks they announced first, then by round-trip time, and relayed to
+//! in batches of `--block-relay-fan-out` peers, each batch
+//! `--block-relay-stagger` after the previous one.
+
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::num::NonZero;
+use std::time::Duration;
+
+use tasm_lib::prelude::Digest;
+
+/// Number of block announcements remembered for determining which peer
+/// announced a block first.
+const MAX_NUM_REMEMBERED_ANNOUNCEMENTS: usize = 64;
+
+/// The most recent new blocks announced by peers, along with the peer that
+/// announced each first, until it is credited.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RecentBlockAnnouncements(VecDeque<(Digest, Option<SocketAddr>)>);
+
+impl RecentBlockAnnouncements {
+    /// Record an announcement of a new block by the given peer. Returns true
+    /// iff no peer announced it before.
+    pub(crate) fn record(&mut self, block_digest: Digest, announcer: SocketAddr) -> bool {
+        if self.0.iter().any(|(digest, _)| *digest == block_digest) {
+            return false;
+        }
+
+        if self.0.len() == MAX_NUM_REMEMBERED_ANNOUNCEMENTS {
+            self.0.pop_front();
+        }
+        self.0.push_back((block_digest, Some(announcer)));
+
+        true
+    }
+
+    /// The peer that announced the block first, if it was not credited
+    /// before. To be called once the block has been received and validated,
+    /// such that announcements of invalid blocks earn nothing.
+    pub(crate) fn credit(&mut self, block_digest: Digest) -> Option<SocketAddr> {
+        self.0
+            .iter_mut()
+            .find(|(digest, _)| *digest == block_digest)
+            .and_then(|(_, announcer)| announcer.take())
+    }
+}
+
+/// How long to wait before relaying a new block to the peer at `own_address`.
+///
+/// `peers` lists every connected peer with the number of blocks it announced
+/// first and its round-trip time if measured, including the peer at
+/// `own_address`. Peers with equal numbers of first announcements are ranked
+/// by round-trip time, unmeasured ones last. Remaining ties are broken by
+/// address so that every peer task arrives at the same ranking.
+pub(crate) fn relay_delay(
+    own_address: SocketAddr,
+    peers: impl IntoIterator<Item = (SocketAddr, u64, Option<Duration>)>,
+    fan_out: NonZero<usize>,
+    stagger: Duration,
+) -> Duration {
+    let mut peers = peers.into_iter().collect::<Vec<_>>();
+    peers.sort_unstable_by(
+        |(address, num_first, round_trip_time),
+         (other_address, other_num_first, other_round_trip_time)| {
+            other_num_first
+                .cmp(num_first)
+                .then_with(|| {
+                    round_trip_time
+                        .unwrap_or(Duration::MAX)
+                        .cmp(&other_round_trip_time.unwrap_or(Duration::MAX))
+                })
+                .then_with(|| address.cmp(other_address))
+        },
+    );
+    let Some(rank) = peers
+        .iter()
+        .position(|(address, _, _)| *address == own_address)
+    else {
+        return Duration::ZERO;
+    };
+
+    let batch = u32::try_from(rank / fan_out.get()).unwrap_or(u32::MAX);
+    stagger.saturating_mul(batch)
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use tasm_lib::twenty_first::bfe;
+
+    use super::*;
+
+    fn address(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    #[test]
+    fn best_propagating_peers_are_relayed_to_first() {
+        let peers = [
+            (address(1), 0, None),
+            (address(2), 7, None),
+            (address(3), 3, None),
+            (address(4), 3, None),
+            (address(5), 1, None),
+        ];
+        let fan_out = NonZero::new(2).unwrap();
+        let stagger = Duration::from_millis(100);
+        let delay = |port| relay_delay(address(port), peers, fan_out, stagger);
+
+        assert_eq!(Duration::ZERO, delay(2));
+        assert_eq!(Duration::ZERO, delay(3));
+        assert_eq!(Duration::from_millis(100), delay(4));
+        assert_eq!(Duration::from_millis(100), delay(5));
+        assert_eq!(Duration::from_millis(200), delay(1));
+
+        assert_eq!(Duration::ZERO, delay(6), "unknown peers are not delayed");
+    }
+
+    #[test]
+    fn faster_peers_are_relayed_to_first_among_equals() {
+        let millis = |millis| Some(Duration::from_millis(millis));
+        let peers = [
+            (address(1), 3, None),
+            (address(2), 3, millis(250)),
+            (address(3), 3, millis(40)),
+            (address(4), 5, millis(900)),
+        ];
+        let fan_out = NonZero::new(1).unwrap();
+        let stagger = Duration::from_millis(100);
+        let delay = |port| relay_delay(address(port), peers, fan_out, stagger);
+
+        assert_eq!(Duration::ZERO, delay(4));
+        assert_eq!(Duration::from_millis(100), delay(3));
+        assert_eq!(Duration::from_millis(200), delay(2));
+        assert_eq!(Duration::from_millis(300), delay(1));
+    }
+
+    #[test]
+    fn only_first_announcement_is_recorded() {
+        let mut announcements = RecentBlockAnnouncements::default();
+        let digest = Digest::default();
+        assert!(announcements.record(digest, address(1)));
+        assert!(!announcements.record(digest, address(2)));
+
+        for i in 1..=MAX_NUM_REMEMBERED_ANNOUNCEMENTS {
+            assert!(announcements.record(Digest::new([bfe!(i as u64); 5]), address(1)));
+        }
+        assert!(
+            announcements.record(digest, address(2)),
+            "old announcements are forgotten"
+        );
+    }
+
+    #[test]
+    fn first_announcer_is_credited_once() {
+        let mut announcements = RecentBlockAnnouncements::default();
+        let digest = Digest::default();
+        assert_eq!(None, announcements.credit(digest), "unannounced block");
+
+        announcements.record(digest, address(1));
+        announcements.record(digest, address(2));
+        assert_eq!(Some(address(1)), announcements.credit(digest));
+        assert_eq!(None, announcements.credit(digest));
+    }
+}
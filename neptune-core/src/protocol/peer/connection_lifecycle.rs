@@ -0,0 +1,607 @@
+//! The lifecycle of a peer connection, as an explicit state machine.
+//!
+//! Every connection, incoming or outgoing, moves through the states of
+//! [`ConnectionState`] in order, possibly skipping ahead to
+//! [`ConnectionState::Draining`] or [`ConnectionState::Closed`]. Each state
+//! except `Closed` has a timeout, so a connection whose peer stops
+//! responding, for instance because the connection is half-open, does not
+//! hang indefinitely in any state.
+//!
+//! Incoming connections additionally need a [`HandshakePermit`] to
+//! handshake, which caps the number of connections that are handshaking at
+//! the same time, in total and per IP address. Together with the timeout of
+//! [`ConnectionState::Handshaking`], this protects the node against slow-loris
+//! attacks, i.e. connections that never complete the handshake.
+//!
+//! Transitions are logged and counted, and the counters are reported by the
+//! `connection_lifecycle` RPC endpoint.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::IpAddr;
+use std::net::SocketAddr;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::Serialize;
+use strum::EnumCount;
+use strum::IntoEnumIterator;
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::sync::Semaphore;
+use tokio::time::Instant;
+use tracing::debug;
+use tracing::warn;
+
+use crate::application::config::cli_args;
+
+/// Time allowed for flushing and shutting down a connection that is being
+/// closed.
+const DRAINING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The state of a peer connection.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    strum::Display,
+    strum::EnumCount,
+    strum::EnumIter,
+)]
+pub enum ConnectionState {
+    /// The TCP connection is being established.
+    Connecting,
+
+    /// Handshakes and connection statuses are being exchanged.
+    Handshaking,
+
+    /// The peer loop runs.
+    Active,
+
+    /// The connection is being flushed and shut down.
+    Draining,
+
+    /// The connection is closed.
+    Closed,
+}
+
+impl ConnectionState {
+    /// Whether a connection in this state may move to state `to`.
+    pub fn can_transition_to(self, to: Self) -> bool {
+        use ConnectionState::*;
+        matches!(
+            (self, to),
+            (Connecting, Handshaking | Closed)
+                | (Handshaking, Active | Draining | Closed)
+                | (Active, Draining | Closed)
+                | (Draining, Closed)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub(crate) enum ConnectionLifecycleError {
+    #[error("illegal connection state transition from {from} to {to}")]
+    IllegalTransition {
+        from: ConnectionState,
+        to: ConnectionState,
+    },
+
+    #[error("connection timed out after {timeout:?} in state {state}")]
+    TimedOut {
+        state: ConnectionState,
+        timeout: Duration,
+    },
+}
+
+/// The timeout of each state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct LifecycleTimeouts {
+    /// Time allowed for establishing the TCP connection.
+    pub(crate) connecting: Duration,
+
+    /// Time allowed for the entire handshake.
+    pub(crate) handshaking: Duration,
+
+    /// Time allowed without receiving any message from an active peer, or
+    /// `None` for no limit.
+    pub(crate) active_idle: Option<Duration>,
+
+    /// Time allowed for flushing and shutting down the connection.
+    pub(crate) draining: Duration,
+}
+
+impl LifecycleTimeouts {
+    pub(crate) fn new(cli: &cli_args::Args) -> Self {
+        let handshake_timeout = Duration::from_secs(cli.handshake_timeout.into());
+        Self {
+            connecting: handshake_timeout,
+            handshaking: handshake_timeout,
+            active_idle: Some(cli.peer_idle_timeout).filter(|timeout| !timeout.is_zero()),
+            draining: DRAINING_TIMEOUT,
+        }
+    }
+
+    fn of(&self, state: ConnectionState) -> Option<Duration> {
+        match state {
+            ConnectionState::Connecting => Some(self.connecting),
+            ConnectionState::Handshaking => Some(self.handshaking),
+            ConnectionState::Active => self.active_idle,
+            ConnectionState::Draining => Some(self.draining),
+            ConnectionState::Closed => None,
+        }
+    }
+}
+
+/// Counters of a single [`ConnectionState`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConnectionStateMetrics {
+    pub state: ConnectionState,
+
+    /// Connections currently in this state.
+    pub current: u64,
+
+    /// Connections that entered this state since the node started.
+    pub entered: u64,
+
+    /// Connections that timed out in this state since the node started.
+    pub timed_out: u64,
+}
+
+/// The lifecycle counters of all peer connections, as reported by the
+/// `connection_lifecycle` RPC endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConnectionLifecycleMetrics {
+    pub states: Vec<ConnectionStateMetrics>,
+
+    /// Incoming connections that are currently handshaking.
+    pub pending_handshakes: u64,
+
+    /// Incoming connections dropped since the node started because too many
+    /// connections were handshaking, in total or from the same IP address.
+    pub refused_handshakes: u64,
+
+    /// Handshakes that timed out since the node started after the peer had
+    /// sent part of its handshake, which indicates a slow-loris attack.
+    pub slow_handshakes: u64,
+}
+
+#[derive(Debug, Default)]
+struct LifecycleCounters {
+    current: [AtomicU64; ConnectionState::COUNT],
+    entered: [AtomicU64; ConnectionState::COUNT],
+    timed_out: [AtomicU64; ConnectionState::COUNT],
+    pending_handshakes: AtomicU64,
+    refused_handshakes: AtomicU64,
+    slow_handshakes: AtomicU64,
+}
+
+impl LifecycleCounters {
+    fn enter(&self, state: ConnectionState) {
+        self.current[state as usize].fetch_add(1, Ordering::Relaxed);
+        self.entered[state as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn leave(&self, state: ConnectionState) {
+        self.current[state as usize].fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Hands out a [`ConnectionLifecycle`] to every connection, and aggregates
+/// their transitions.
+#[derive(Debug)]
+pub(crate) struct ConnectionLifecycleRegistry {
+    timeouts: LifecycleTimeouts,
+    counters: Arc<LifecycleCounters>,
+    handshake_limiter: HandshakeLimiter,
+}
+
+impl ConnectionLifecycleRegistry {
+    pub(crate) fn new(cli: &cli_args::Args) -> Self {
+        let counters = Arc::<LifecycleCounters>::default();
+        Self {
+            timeouts: LifecycleTimeouts::new(cli),
+            handshake_limiter: HandshakeLimiter::new(cli, counters.clone()),
+            counters,
+        }
+    }
+
+    /// The limiter that incoming connections acquire their
+    /// [`HandshakePermit`] from.
+    pub(crate) fn handshake_limiter(&self) -> HandshakeLimiter {
+        self.handshake_limiter.clone()
+    }
+
+    /// Start tracking a new connection to `peer_address`, in state
+    /// [`ConnectionState::Connecting`].
+    pub(crate) fn track(&self, peer_address: SocketAddr) -> ConnectionLifecycle {
+        ConnectionLifecycle::new(peer_address, self.timeouts, self.counters.clone())
+    }
+
+    pub(crate) fn metrics(&self) -> ConnectionLifecycleMetrics {
+        let states = ConnectionState::iter()
+            .map(|state| ConnectionStateMetrics {
+                state,
+                current: self.counters.current[state as usize].load(Ordering::Relaxed),
+                entered: self.counters.entered[state as usize].load(Ordering::Relaxed),
+                timed_out: self.counters.timed_out[state as usize].load(Ordering::Relaxed),
+            })
+            .collect();
+
+        ConnectionLifecycleMetrics {
+            states,
+            pending_handshakes: self.counters.pending_handshakes.load(Ordering::Relaxed),
+            refused_handshakes: self.counters.refused_handshakes.load(Ordering::Relaxed),
+            slow_handshakes: self.counters.slow_handshakes.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Caps the number of incoming connections that are handshaking at the same
+/// time, in total and per IP address.
+#[derive(Debug, Clone)]
+pub(crate) struct HandshakeLimiter {
+    total: Arc<Semaphore>,
+    max_per_ip: usize,
+    per_ip: Arc<Mutex<HashMap<IpAddr, usize>>>,
+    counters: Arc<LifecycleCounters>,
+}
+
+impl HandshakeLimiter {
+    fn new(cli: &cli_args::Args, counters: Arc<LifecycleCounters>) -> Self {
+        let max_total = cli
+            .max_pending_handshakes
+            .map(|max| max.get())
+            .unwrap_or(cli.max_num_peers * 2 + 4);
+        Self {
+            total: Arc::new(Semaphore::new(max_total)),
+            max_per_ip: cli.max_pending_handshakes_per_ip.get(),
+            per_ip: Arc::default(),
+            counters,
+        }
+    }
+
+    /// Acquire a permit to handshake with an incoming connection from `ip`.
+    ///
+    /// Returns `None` right away if too many connections from `ip` are
+    /// handshaking, and after `timeout` if too many connections are
+    /// handshaking in total.
+    pub(crate) async fn acquire(&self, ip: IpAddr, timeout: Duration) -> Option<HandshakePermit> {
+        let per_ip_slot = {
+            let mut per_ip = self.per_ip.lock().unwrap();
+            let pending = per_ip.entry(ip).or_default();
+            if *pending >= self.max_per_ip {
+                drop(per_ip);
+                self.refuse(ip, "from this IP");
+                return None;
+            }
+            *pending += 1;
+
+            PerIpSlot {
+                ip,
+                per_ip: self.per_ip.clone(),
+            }
+        };
+
+        let Ok(Ok(total_slot)) =
+            tokio::time::timeout(timeout, self.total.clone().acquire_owned()).await
+        else {
+            self.refuse(ip, "in total");
+            return None;
+        };
+
+        self.counters
+            .pending_handshakes
+            .fetch_add(1, Ordering::Relaxed);
+        Some(HandshakePermit {
+            _total_slot: total_slot,
+            _per_ip_slot: per_ip_slot,
+            counters: self.counters.clone(),
+        })
+    }
+
+    fn refuse(&self, ip: IpAddr, scope: &str) {
+        self.counters
+            .refused_handshakes
+            .fetch_add(1, Ordering::Relaxed);
+        warn!("Too many pending handshakes {scope}. Dropping incoming connection from {ip}.");
+    }
+}
+
+/// Permission for an incoming connection to handshake. Dropped once the
+/// handshake completes or fails, so that idle connections cannot hold on to
+/// it.
+#[derive(Debug)]
+pub(crate) struct HandshakePermit {
+    _total_slot: OwnedSemaphorePermit,
+    _per_ip_slot: PerIpSlot,
+    counters: Arc<LifecycleCounters>,
+}
+
+impl Drop for HandshakePermit {
+    fn drop(&mut self) {
+        self.counters
+            .pending_handshakes
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug)]
+struct PerIpSlot {
+    ip: IpAddr,
+    per_ip: Arc<Mutex<HashMap<IpAddr, usize>>>,
+}
+
+impl Drop for PerIpSlot {
+    fn drop(&mut self) {
+        let mut per_ip = self.per_ip.lock().unwrap();
+        if let Some(pending) = per_ip.get_mut(&self.ip) {
+            *pending -= 1;
+            if *pending == 0 {
+                per_ip.remove(&self.ip);
+            }
+        }
+    }
+}
+
+/// The state of a single connection.
+///
+/// A connection that is dropped before reaching [`ConnectionState::Closed`],
+/// for instance because of an error, is closed on drop.
+#[derive(Debug)]
+pub(crate) struct ConnectionLifecycle {
+    peer_address: SocketAddr,
+    state: ConnectionState,
+    entered_at: Instant,
+    deadline: Option<Instant>,
+    timeouts: LifecycleTimeouts,
+    counters: Arc<LifecycleCounters>,
+}
+
+impl ConnectionLifecycle {
+    fn new(
+        peer_address: SocketAddr,
+        timeouts: LifecycleTimeouts,
+        counters: Arc<LifecycleCounters>,
+    ) -> Self {
+        let state = ConnectionState::Connecting;
+        counters.enter(state);
+        let entered_at = Instant::now();
+
+        Self {
+            peer_address,
+            state,
+            entered_at,
+            deadline: timeouts.of(state).map(|timeout| entered_at + timeout),
+            timeouts,
+            counters,
+        }
+    }
+
+    pub(crate) fn state(&self) -> ConnectionState {
+        self.state
+    }
+
+    /// The instant at which the connection times out in its current state,
+    /// if any.
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Move the connection to state `to`, which starts its timeout.
+    pub(crate) fn transition(
+        &mut self,
+        to: ConnectionState,
+    ) -> Result<(), ConnectionLifecycleError> {
+        let from = self.state;
+        if !from.can_transition_to(to) {
+            return Err(ConnectionLifecycleError::IllegalTransition { from, to });
+        }
+
+        debug!(
+            "Connection to {}: {from} -> {to} after {:?}",
+            self.peer_address,
+            self.entered_at.elapsed()
+        );
+        self.counters.leave(from);
+        self.counters.enter(to);
+        self.state = to;
+        self.entered_at = Instant::now();
+        self.deadline = self
+            .timeouts
+            .of(to)
+            .map(|timeout| self.entered_at + timeout);
+
+        Ok(())
+    }
+
+    /// Postpone the deadline of an active connection, because the peer has
+    /// shown signs of life. No-op in any other state.
+    pub(crate) fn record_activity(&mut self) {
+        if self.state == ConnectionState::Active {
+            self.deadline = self
+                .timeouts
+                .active_idle
+                .map(|timeout| Instant::now() + timeout);
+        }
+    }
+
+    /// Record that the connection timed out in its current state.
+    pub(crate) fn time_out(&self) -> ConnectionLifecycleError {
+        let state = self.state;
+        self.counters.timed_out[state as usize].fetch_add(1, Ordering::Relaxed);
+        let timeout = self.timeouts.of(state).unwrap_or_default();
+        warn!(
+            "Connection to {} timed out after {timeout:?} in state {state}",
+            self.peer_address
+        );
+
+        ConnectionLifecycleError::TimedOut { state, timeout }
+    }
+
+    /// Record that the peer had sent only part of its handshake when the
+    /// handshake timed out.
+    pub(crate) fn record_slow_handshake(&self) {
+        self.counters
+            .slow_handshakes
+            .fetch_add(1, Ordering::Relaxed);
+        warn!(
+            "Peer {} sent its handshake too slowly, possibly a slow-loris attack",
+            self.peer_address
+        );
+    }
+
+    /// Run `future` to completion, unless the connection times out in its
+    /// current state first.
+    pub(crate) async fn within<F: Future>(
+        &self,
+        future: F,
+    ) -> Result<F::Output, ConnectionLifecycleError> {
+        let Some(deadline) = self.deadline else {
+            return Ok(future.await);
+        };
+
+        tokio::time::timeout_at(deadline, future)
+            .await
+            .map_err(|_| self.time_out())
+    }
+}
+
+impl Drop for ConnectionLifecycle {
+    fn drop(&mut self) {
+        if self.state != ConnectionState::Closed {
+            // Every state may transition to `Closed`.
+            let _ = self.transition(ConnectionState::Closed);
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::num::NonZero;
+
+    use super::*;
+
+    fn registry() -> ConnectionLifecycleRegistry {
+        ConnectionLifecycleRegistry::new(&cli_args::Args::default())
+    }
+
+    fn metrics_of(
+        registry: &ConnectionLifecycleRegistry,
+        state: ConnectionState,
+    ) -> ConnectionStateMetrics {
+        registry.metrics().states[state as usize]
+    }
+
+    #[test]
+    fn only_forward_transitions_are_legal() {
+        use ConnectionState::*;
+        for from in ConnectionState::iter() {
+            assert!(!from.can_transition_to(from));
+            assert!(!from.can_transition_to(Connecting));
+            assert_eq!(from != Closed, from.can_transition_to(Closed));
+        }
+        assert!(!Connecting.can_transition_to(Active));
+        assert!(!Draining.can_transition_to(Active));
+    }
+
+    #[tokio::test]
+    async fn transitions_are_metered() {
+        let registry = registry();
+        let mut lifecycle = registry.track("127.0.0.1:9798".parse().unwrap());
+        lifecycle.transition(ConnectionState::Handshaking).unwrap();
+        lifecycle.transition(ConnectionState::Active).unwrap();
+
+        assert_eq!(
+            ConnectionStateMetrics {
+                state: ConnectionState::Active,
+                current: 1,
+                entered: 1,
+                timed_out: 0,
+            },
+            metrics_of(&registry, ConnectionState::Active)
+        );
+        assert_eq!(
+            0,
+            metrics_of(&registry, ConnectionState::Connecting).current
+        );
+
+        assert_eq!(
+            Err(ConnectionLifecycleError::IllegalTransition {
+                from: ConnectionState::Active,
+                to: ConnectionState::Handshaking,
+            }),
+            lifecycle.transition(ConnectionState::Handshaking)
+        );
+
+        drop(lifecycle);
+        assert_eq!(0, metrics_of(&registry, ConnectionState::Active).current);
+        assert_eq!(1, metrics_of(&registry, ConnectionState::Closed).entered);
+    }
+
+    #[tokio::test]
+    async fn stalled_handshake_times_out() {
+        let handshaking = Duration::from_millis(10);
+        let registry = ConnectionLifecycleRegistry {
+            timeouts: LifecycleTimeouts {
+                handshaking,
+                ..LifecycleTimeouts::new(&cli_args::Args::default())
+            },
+            ..registry()
+        };
+        let mut lifecycle = registry.track("127.0.0.1:9798".parse().unwrap());
+        lifecycle.transition(ConnectionState::Handshaking).unwrap();
+
+        let result = lifecycle.within(std::future::pending::<()>()).await;
+
+        assert_eq!(
+            Err(ConnectionLifecycleError::TimedOut {
+                state: ConnectionState::Handshaking,
+                timeout: handshaking,
+            }),
+            result
+        );
+        assert_eq!(
+            1,
+            metrics_of(&registry, ConnectionState::Handshaking).timed_out
+        );
+    }
+
+    #[tokio::test]
+    async fn pending_handshakes_are_capped_per_ip_and_in_total() {
+        let cli = cli_args::Args {
+            max_pending_handshakes: NonZero::new(3),
+            max_pending_handshakes_per_ip: NonZero::new(2).unwrap(),
+            ..Default::default()
+        };
+        let registry = ConnectionLifecycleRegistry::new(&cli);
+        let limiter = registry.handshake_limiter();
+        let timeout = Duration::from_millis(10);
+        let alice: IpAddr = "127.0.0.1".parse().unwrap();
+        let bob: IpAddr = "127.0.0.2".parse().unwrap();
+
+        let alice_1 = limiter.acquire(alice, timeout).await.unwrap();
+        let _alice_2 = limiter.acquire(alice, timeout).await.unwrap();
+        assert!(limiter.acquire(alice, timeout).await.is_none());
+
+        let bob_1 = limiter.acquire(bob, timeout).await.unwrap();
+        assert!(limiter.acquire(bob, timeout).await.is_none());
+        assert_eq!(3, registry.metrics().pending_handshakes);
+        assert_eq!(2, registry.metrics().refused_handshakes);
+
+        // completed handshakes free their slots.
+        drop(alice_1);
+        drop(bob_1);
+        let _alice_3 = limiter.acquire(alice, timeout).await.unwrap();
+        assert_eq!(2, registry.metrics().pending_handshakes);
+    }
+}
@@ -0,0 +1,203 @@
+//! Signaling of readiness for upcoming consensus changes.
+//!
+//! Before a hard fork activates, operators want to know how much of the
+//! network runs software that follows the new rules. Every node therefore
+//! advertises, in its handshake, a capability flag starting with
+//! [`FORK_SIGNAL_PREFIX`] for each [`ConsensusRuleSet`] it knows of that
+//! activates later than its tip. Flags naming rule sets this node does not
+//! know of are counted too, such that operators of outdated nodes can see the
+//! network moving on.
+//!
+//! Block headers carry no signaling field, as adding one would itself be a
+//! consensus change. Readiness is measured over peers instead: those
+//! connected now, and those whose handshakes were received within the
+//! [`SIGNAL_WINDOW`]. Peers are told apart by IP address, as instance ids are
+//! chosen by the peer and, with plausible deniability, change on every
+//! connection.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use strum::IntoEnumIterator;
+
+use super::handshake_data::HandshakeData;
+use crate::application::config::network::Network;
+use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::protocol::consensus::consensus_rule_set::ConsensusRuleSet;
+
+/// Prefix of the capability flags that signal readiness for a consensus rule
+/// set, eg `ready:HardforkAlpha`.
+pub(crate) const FORK_SIGNAL_PREFIX: &str = "ready:";
+
+/// How long the signals of a peer count after its handshake was received.
+pub(crate) const SIGNAL_WINDOW: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Number of distinct peers whose signals are remembered.
+const MAX_NUM_OBSERVED_PEERS: usize = 1000;
+
+/// Number of rule sets a peer's signals are counted for. Further signals are
+/// ignored.
+const MAX_NUM_SIGNALED_RULE_SETS: usize = 8;
+
+/// Maximum length of a signaled rule-set name. Longer names are ignored.
+const MAX_RULE_SET_NAME_LEN: usize = 64;
+
+/// The rule sets this node signals readiness for: those it knows of that
+/// activate on the network after the given tip.
+pub(crate) fn pending_rule_sets(
+    network: Network,
+    tip_height: BlockHeight,
+) -> impl Iterator<Item = ConsensusRuleSet> {
+    ConsensusRuleSet::iter().filter(move |rule_set| {
+        rule_set
+            .activation_height(network)
+            .is_some_and(|activation_height| activation_height > tip_height)
+    })
+}
+
+/// The capability flag that signals readiness for the rule set.
+pub(crate) fn signal(rule_set: ConsensusRuleSet) -> String {
+    format!("{FORK_SIGNAL_PREFIX}{rule_set}")
+}
+
+/// The names of the rule sets signaled for in a handshake, at most
+/// [`MAX_NUM_SIGNALED_RULE_SETS`] of them.
+pub(crate) fn signaled_rule_sets(handshake: &HandshakeData) -> Vec<String> {
+    handshake
+        .capabilities()
+        .filter_map(|flag| flag.strip_prefix(FORK_SIGNAL_PREFIX))
+        .filter(|name| !name.is_empty() && name.len() <= MAX_RULE_SET_NAME_LEN)
+        .take(MAX_NUM_SIGNALED_RULE_SETS)
+        .map(str::to_string)
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+struct ObservedSignals {
+    rule_sets: Vec<String>,
+    received: SystemTime,
+}
+
+/// The signals of the peers that connected most recently, connected or not.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ForkSignals {
+    observed: HashMap<IpAddr, ObservedSignals>,
+}
+
+impl ForkSignals {
+    /// Record the signals in the handshake of a peer at the given IP address,
+    /// replacing those sent from that address before. Forgets signals received
+    /// before the [`SIGNAL_WINDOW`], and those of the least recently connected
+    /// peer if too many are remembered.
+    pub(crate) fn record(&mut self, ip: IpAddr, handshake: &HandshakeData, now: SystemTime) {
+        self.observed.insert(
+            ip,
+            ObservedSignals {
+                rule_sets: signaled_rule_sets(handshake),
+                received: now,
+            },
+        );
+
+        self.observed
+            .retain(|_, signals| !is_expired(signals.received, now));
+        if self.observed.len() > MAX_NUM_OBSERVED_PEERS {
+            let oldest = self
+                .observed
+                .iter()
+                .min_by_key(|(_, signals)| signals.received)
+                .map(|(ip, _)| *ip);
+            if let Some(oldest) = oldest {
+                self.observed.remove(&oldest);
+            }
+        }
+    }
+
+    /// The rule sets signaled for by each peer whose handshake was received
+    /// within the [`SIGNAL_WINDOW`].
+    pub(crate) fn recent(&self, now: SystemTime) -> impl Iterator<Item = &[String]> {
+        self.observed
+            .values()
+            .filter(move |signals| !is_expired(signals.received, now))
+            .map(|signals| signals.rule_sets.as_slice())
+    }
+}
+
+fn is_expired(received: SystemTime, now: SystemTime) -> bool {
+    now.duration_since(received)
+        .is_ok_and(|age| age > SIGNAL_WINDOW)
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+    use crate::protocol::consensus::consensus_rule_set::BLOCK_HEIGHT_HARDFORK_ALPHA_MAIN_NET;
+    use crate::tests::shared::globalstate::get_dummy_handshake_data_for_genesis;
+
+    #[test]
+    fn only_rule_sets_activating_after_tip_are_pending() {
+        let network = Network::Main;
+        assert_eq!(
+            vec![ConsensusRuleSet::HardforkAlpha],
+            pending_rule_sets(network, BlockHeight::genesis()).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            0,
+            pending_rule_sets(network, BLOCK_HEIGHT_HARDFORK_ALPHA_MAIN_NET).count()
+        );
+        assert_eq!(
+            0,
+            pending_rule_sets(Network::RegTest, BlockHeight::genesis()).count()
+        );
+    }
+
+    #[test]
+    fn signals_round_trip_including_unknown_rule_sets() {
+        let mut handshake = get_dummy_handshake_data_for_genesis(Network::Main);
+        handshake.add_capability("zstd");
+        handshake.add_capability(&signal(ConsensusRuleSet::HardforkAlpha));
+        handshake.add_capability("ready:HardforkOmega");
+        handshake.add_capability(FORK_SIGNAL_PREFIX);
+        handshake.add_capability(&format!(
+            "{FORK_SIGNAL_PREFIX}{}",
+            "a".repeat(MAX_RULE_SET_NAME_LEN + 1)
+        ));
+
+        assert_eq!(
+            vec!["HardforkAlpha".to_string(), "HardforkOmega".to_string()],
+            signaled_rule_sets(&handshake)
+        );
+
+        for i in 0..2 * MAX_NUM_SIGNALED_RULE_SETS {
+            handshake.add_capability(&format!("{FORK_SIGNAL_PREFIX}Hardfork{i}"));
+        }
+        assert_eq!(
+            MAX_NUM_SIGNALED_RULE_SETS,
+            signaled_rule_sets(&handshake).len()
+        );
+    }
+
+    #[test]
+    fn signals_expire_and_are_replaced() {
+        let mut handshake = get_dummy_handshake_data_for_genesis(Network::Main);
+        let mut fork_signals = ForkSignals::default();
+        let start = SystemTime::now();
+        let ip = IpAddr::from([127, 0, 0, 1]);
+
+        fork_signals.record(ip, &handshake, start);
+
+        // a reconnecting peer replaces its signals, whatever its instance id
+        handshake.instance_id = rand::random();
+        handshake.add_capability(&signal(ConsensusRuleSet::HardforkAlpha));
+        fork_signals.record(ip, &handshake, start);
+        assert_eq!(
+            vec![vec!["HardforkAlpha".to_string()]],
+            fork_signals.recent(start).collect::<Vec<_>>()
+        );
+
+        let later = start + SIGNAL_WINDOW + Duration::from_secs(1);
+        assert_eq!(0, fork_signals.recent(later).count());
+    }
+}
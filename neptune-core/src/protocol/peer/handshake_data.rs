@@ -31,7 +31,92 @@ pub(crate) struct HandshakeData {
     /// compare own timestamp to peer's or to a list of peers.
     pub timestamp: SystemTime,
 
-    /// Use this field to add extra data in a backwards compatible manner. An
-    /// encoding should be selected for this. Currently unused.
+    /// Use this field to add extra data in a backwards compatible manner.
+    ///
+    /// Encoded as a comma-separated list of capability flags, see
+    /// [`HandshakeData::has_capability`]. Peers ignore flags they do not know.
     pub extra_data: ExtraDataString,
 }
+
+/// Capability flag advertising support for zstd-compressed peer messages.
+pub(crate) const ZSTD_COMPRESSION_CAPABILITY: &str = "zstd";
+
+/// Capability flag advertising support for receiving
+/// [`PeerMessage::BlockHeaderNotification`](crate::protocol::peer::PeerMessage::BlockHeaderNotification).
+pub(crate) const HEADER_FIRST_RELAY_CAPABILITY: &str = "header-first";
+
+/// Capability flag advertising support for answering
+/// [`PeerMessage::Ping`](crate::protocol::peer::PeerMessage::Ping).
+pub(crate) const PING_CAPABILITY: &str = "ping";
+
+/// Capability flag advertising that the node asks for, and accepts,
+/// [`PeerMessage::RendezvousIntroduction`](crate::protocol::peer::PeerMessage::RendezvousIntroduction)s.
+pub(crate) const RENDEZVOUS_CAPABILITY: &str = "rendezvous";
+
+/// Capability flag advertising that the node answers
+/// [`PeerMessage::RendezvousRequest`](crate::protocol::peer::PeerMessage::RendezvousRequest)s.
+pub(crate) const RENDEZVOUS_RELAY_CAPABILITY: &str = "rendezvous-relay";
+
+/// Capability flag advertising support for receiving requested blocks as
+/// [`PeerMessage::BlockChunk`](crate::protocol::peer::PeerMessage::BlockChunk)s.
+pub(crate) const BLOCK_CHUNKS_CAPABILITY: &str = "block-chunks";
+
+/// Capability flag advertising that the node exchanges
+/// [`PeerMessage::WalletBackup`](crate::protocol::peer::PeerMessage::WalletBackup)s
+/// with the peers it trusts.
+pub(crate) const WALLET_BACKUP_CAPABILITY: &str = "wallet-backup";
+
+/// Capability flag advertising support for sequence-numbered frames, see
+/// [message_sequence](crate::protocol::peer::message_sequence).
+pub(crate) const MESSAGE_SEQUENCE_CAPABILITY: &str = "sequence";
+
+impl HandshakeData {
+    /// The capability flags advertised in the `extra_data` field.
+    pub(crate) fn capabilities(&self) -> impl Iterator<Item = &str> {
+        self.extra_data
+            .as_str()
+            .split(',')
+            .map(str::trim)
+            .filter(|flag| !flag.is_empty())
+    }
+
+    /// Returns true iff the given capability flag is advertised.
+    pub(crate) fn has_capability(&self, capability: &str) -> bool {
+        self.capabilities().any(|flag| flag == capability)
+    }
+
+    /// Advertise a capability flag. Flags that do not fit in the `extra_data`
+    /// field are silently dropped, and adding a flag twice has no effect.
+    pub(crate) fn add_capability(&mut self, capability: &str) {
+        if self.has_capability(capability) {
+            return;
+        }
+
+        let separator = if self.extra_data.is_empty() { "" } else { "," };
+        let extended = format!("{}{separator}{capability}", self.extra_data);
+        if let Ok(extra_data) = ExtraDataString::try_from_str(&extended) {
+            self.extra_data = extra_data;
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+    use crate::tests::shared::globalstate::get_dummy_handshake_data_for_genesis;
+
+    #[test]
+    fn capabilities_round_trip() {
+        let mut handshake = get_dummy_handshake_data_for_genesis(Network::Main);
+        assert!(!handshake.has_capability(ZSTD_COMPRESSION_CAPABILITY));
+
+        handshake.add_capability(ZSTD_COMPRESSION_CAPABILITY);
+        handshake.add_capability(ZSTD_COMPRESSION_CAPABILITY);
+        handshake.add_capability("foo");
+        assert!(handshake.has_capability(ZSTD_COMPRESSION_CAPABILITY));
+        assert!(handshake.has_capability("foo"));
+        assert!(!handshake.has_capability("zst"));
+        assert_eq!("zstd,foo", handshake.extra_data.as_str());
+    }
+}
@@ -0,0 +1,375 @@
+//! Protection against peers replaying old messages.
+//!
+//! Once both ends of a connection advertise the
+//! [`MESSAGE_SEQUENCE_CAPABILITY`](super::handshake_data::MESSAGE_SEQUENCE_CAPABILITY),
+//! every frame is prefixed with its sequence number on the connection,
+//! counting from zero when the handshake completes. TCP delivers frames in
+//! order and exactly once, so a frame that does not carry the expected number
+//! was numbered wrongly by the peer itself. Frames whose number was already
+//! received are replays and are dropped; skipped numbers are tolerated. Both
+//! are recorded as [`SequenceViolations`], which the peer loop punishes.
+//!
+//! Sequence numbers do not stop a peer from sending the same content twice
+//! under fresh numbers. The [`ReplayWindow`] therefore remembers the most
+//! recent announcements received on a connection, such that an announcement
+//! that is repeated, eg a stale block notification, is dropped before it can
+//! reset what the node believes about the peer's tip. Honest peers repeat
+//! announcements too, eg when re-broadcasting an expired block proposal, so
+//! announcements are forgotten after a while, and only peers that repeat
+//! many announcements are punished. A block announcement that this node asked
+//! for, with a [`PeerMessage::BlockNotificationRequest`], is never a replay.
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
+
+use tasm_lib::prelude::Digest;
+use tracing::warn;
+
+use super::transfer_transaction::TransactionProofQuality;
+use super::PeerMessage;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+use crate::state::transaction::transaction_kernel_id::TransactionKernelId;
+
+/// Number of bytes of the sequence number prefixed to every frame.
+pub(crate) const SEQUENCE_NUMBER_SIZE: usize = std::mem::size_of::<u64>();
+
+/// Number of most recent announcements remembered per connection.
+pub(crate) const REPLAY_WINDOW_SIZE: usize = 256;
+
+/// How long an announcement is remembered, and how long a repeated
+/// announcement counts towards [`REPLAY_TOLERANCE`].
+pub(crate) const REPLAY_WINDOW_DURATION: Timestamp = Timestamp::minutes(1);
+
+/// Number of repeated announcements a peer may make within
+/// [`REPLAY_WINDOW_DURATION`] before it is punished.
+pub(crate) const REPLAY_TOLERANCE: usize = 16;
+
+/// The number of sequence violations on a connection, shared between the
+/// codec that detects them and the peer loop that punishes them.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SequenceViolations(Arc<AtomicU64>);
+
+impl SequenceViolations {
+    fn record(&self) {
+        self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// The number of violations recorded since the last call.
+    pub(crate) fn take(&self) -> u64 {
+        self.0.swap(0, std::sync::atomic::Ordering::Relaxed)
+    }
+}
+
+/// Checks the sequence numbers of the frames received on a connection.
+#[derive(Debug, Clone)]
+pub(crate) struct IncomingSequence {
+    next: u64,
+    violations: SequenceViolations,
+}
+
+impl IncomingSequence {
+    pub(crate) fn new(violations: SequenceViolations) -> Self {
+        Self {
+            next: 0,
+            violations,
+        }
+    }
+
+    /// Returns false iff the frame with the given sequence number is a replay
+    /// and must be dropped.
+    pub(crate) fn accept(&mut self, sequence_number: u64) -> bool {
+        match sequence_number.cmp(&self.next) {
+            Ordering::Equal => {
+                self.next = sequence_number.saturating_add(1);
+                true
+            }
+            Ordering::Less => {
+                warn!(
+                    "Dropping replayed peer frame {sequence_number}; expected {}",
+                    self.next
+                );
+                self.violations.record();
+                false
+            }
+            Ordering::Greater => {
+                warn!(
+                    "Peer skipped frames {} to {}",
+                    self.next,
+                    sequence_number - 1
+                );
+                self.violations.record();
+                self.next = sequence_number.saturating_add(1);
+                true
+            }
+        }
+    }
+}
+
+/// What a peer announced. Two announcements with the same key carry the same
+/// news.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Announcement {
+    Block(Digest),
+    Transaction(TransactionKernelId, TransactionProofQuality),
+    BlockProposal(Digest),
+}
+
+impl Announcement {
+    fn of(message: &PeerMessage) -> Option<Self> {
+        match message {
+            PeerMessage::BlockNotification(notification) => Some(Self::Block(notification.hash)),
+            PeerMessage::BlockHeaderNotification(notification) => {
+                Some(Self::Block(notification.hash()))
+            }
+            PeerMessage::TransactionNotification(notification) => Some(Self::Transaction(
+                notification.txid,
+                notification.proof_quality,
+            )),
+            PeerMessage::BlockProposalNotification(notification) => {
+                Some(Self::BlockProposal(notification.body_mast_hash))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// What the [`ReplayWindow`] makes of a received message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Replay {
+    /// The message does not repeat a recent announcement.
+    Fresh,
+
+    /// The message repeats a recent announcement and must be dropped.
+    Tolerated,
+
+    /// The message repeats a recent announcement and must be dropped, and
+    /// the peer repeated more than [`REPLAY_TOLERANCE`] announcements
+    /// recently.
+    Excessive,
+}
+
+/// The most recent announcements received from a peer.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ReplayWindow {
+    order: VecDeque<(Announcement, Timestamp)>,
+    seen: HashSet<Announcement>,
+
+    /// When the peer recently repeated an announcement.
+    replays: VecDeque<Timestamp>,
+
+    /// Whether this node asked the peer to announce its tip.
+    block_announcement_requested: bool,
+}
+
+impl ReplayWindow {
+    /// Determine whether the message, received at `now`, repeats an
+    /// announcement in the window. Otherwise, remembers the announcement the
+    /// message makes, if any, forgetting the oldest one if the window is full.
+    pub(crate) fn check(&mut self, message: &PeerMessage, now: Timestamp) -> Replay {
+        self.forget_expired(now);
+
+        let Some(announcement) = Announcement::of(message) else {
+            return Replay::Fresh;
+        };
+        let requested = matches!(announcement, Announcement::Block(_))
+            && std::mem::take(&mut self.block_announcement_requested);
+        if self.seen.contains(&announcement) {
+            if requested {
+                return Replay::Fresh;
+            }
+
+            self.replays.push_back(now);
+            return if self.replays.len() > REPLAY_TOLERANCE {
+                Replay::Excessive
+            } else {
+                Replay::Tolerated
+            };
+        }
+
+        self.seen.insert(announcement);
+        self.order.push_back((announcement, now));
+        if self.order.len() > REPLAY_WINDOW_SIZE {
+            if let Some((oldest, _)) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+
+        Replay::Fresh
+    }
+
+    /// Exempt the next block announcement from the window, because this node
+    /// asked the peer for it with a [`PeerMessage::BlockNotificationRequest`].
+    pub(crate) fn expect_block_announcement(&mut self) {
+        self.block_announcement_requested = true;
+    }
+
+    /// Forget announcements and replays older than [`REPLAY_WINDOW_DURATION`].
+    fn forget_expired(&mut self, now: Timestamp) {
+        while let Some(&(oldest, seen_at)) = self.order.front() {
+            if seen_at + REPLAY_WINDOW_DURATION > now {
+                break;
+            }
+            self.seen.remove(&oldest);
+            self.order.pop_front();
+        }
+        while self
+            .replays
+            .front()
+            .is_some_and(|&replayed_at| replayed_at + REPLAY_WINDOW_DURATION <= now)
+        {
+            self.replays.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+    use crate::application::config::network::Network;
+    use crate::application::loops::channel::BlockProposalNotification;
+    use crate::protocol::consensus::block::Block;
+    use crate::protocol::peer::peer_block_notifications::PeerBlockHeaderNotification;
+    use crate::protocol::peer::peer_block_notifications::PeerBlockNotification;
+
+    #[test]
+    fn replayed_and_skipped_frames_are_violations() {
+        let violations = SequenceViolations::default();
+        let mut incoming = IncomingSequence::new(violations.clone());
+        assert!(incoming.accept(0));
+        assert!(incoming.accept(1));
+        assert_eq!(0, violations.take());
+
+        assert!(!incoming.accept(0), "replayed frame must be dropped");
+        assert!(!incoming.accept(1), "replayed frame must be dropped");
+        assert!(incoming.accept(2));
+        assert_eq!(2, violations.take());
+        assert_eq!(0, violations.take());
+
+        assert!(incoming.accept(5), "skipped frames are tolerated");
+        assert!(incoming.accept(6));
+        assert_eq!(1, violations.take());
+    }
+
+    #[test]
+    fn repeated_announcements_are_replays() {
+        let genesis = Block::genesis(Network::Main);
+        let notification = PeerMessage::BlockNotification(PeerBlockNotification::from(&genesis));
+        let header_notification = PeerMessage::BlockHeaderNotification(Box::new(
+            PeerBlockHeaderNotification::from(&genesis),
+        ));
+        let now = Timestamp::now();
+
+        let mut window = ReplayWindow::default();
+        assert_eq!(Replay::Fresh, window.check(&notification, now));
+        assert_eq!(Replay::Tolerated, window.check(&notification, now));
+        assert_eq!(
+            Replay::Tolerated,
+            window.check(&header_notification, now),
+            "header notification announces the same block"
+        );
+
+        assert_eq!(
+            Replay::Fresh,
+            window.check(&PeerMessage::PeerListRequest, now)
+        );
+        assert_eq!(
+            Replay::Fresh,
+            window.check(&PeerMessage::PeerListRequest, now),
+            "only announcements can be replays"
+        );
+    }
+
+    #[test]
+    fn requested_block_announcement_is_not_a_replay() {
+        let genesis = Block::genesis(Network::Main);
+        let notification = PeerMessage::BlockNotification(PeerBlockNotification::from(&genesis));
+        let now = Timestamp::now();
+
+        let mut window = ReplayWindow::default();
+        assert_eq!(Replay::Fresh, window.check(&notification, now));
+
+        window.expect_block_announcement();
+        assert_eq!(
+            Replay::Fresh,
+            window.check(&proposal_notification(0), now),
+            "only block announcements are requested"
+        );
+        assert_eq!(Replay::Fresh, window.check(&notification, now));
+        assert_eq!(
+            Replay::Tolerated,
+            window.check(&notification, now),
+            "only one announcement was requested"
+        );
+    }
+
+    fn proposal_notification(i: u64) -> PeerMessage {
+        PeerMessage::BlockProposalNotification(BlockProposalNotification {
+            body_mast_hash: Digest::new([i.into(); Digest::LEN]),
+            guesser_fee: Default::default(),
+            height: i.into(),
+        })
+    }
+
+    #[test]
+    fn oldest_announcements_are_forgotten() {
+        let now = Timestamp::now();
+        let mut window = ReplayWindow::default();
+        for i in 0..=REPLAY_WINDOW_SIZE as u64 {
+            assert_eq!(Replay::Fresh, window.check(&proposal_notification(i), now));
+        }
+
+        assert_eq!(Replay::Fresh, window.check(&proposal_notification(0), now));
+        assert_eq!(
+            Replay::Tolerated,
+            window.check(&proposal_notification(REPLAY_WINDOW_SIZE as u64), now)
+        );
+    }
+
+    #[test]
+    fn announcements_expire() {
+        let now = Timestamp::now();
+        let mut window = ReplayWindow::default();
+        assert_eq!(Replay::Fresh, window.check(&proposal_notification(0), now));
+        assert_eq!(
+            Replay::Tolerated,
+            window.check(&proposal_notification(0), now + Timestamp::seconds(1))
+        );
+        assert_eq!(
+            Replay::Fresh,
+            window.check(&proposal_notification(0), now + REPLAY_WINDOW_DURATION),
+            "announcement can be repeated once it expired"
+        );
+    }
+
+    #[test]
+    fn only_many_replays_are_excessive() {
+        let now = Timestamp::now();
+        let mut window = ReplayWindow::default();
+        assert_eq!(Replay::Fresh, window.check(&proposal_notification(0), now));
+        for _ in 0..REPLAY_TOLERANCE {
+            assert_eq!(
+                Replay::Tolerated,
+                window.check(&proposal_notification(0), now)
+            );
+        }
+        assert_eq!(
+            Replay::Excessive,
+            window.check(&proposal_notification(0), now)
+        );
+
+        let later = now + REPLAY_WINDOW_DURATION;
+        assert_eq!(
+            Replay::Fresh,
+            window.check(&proposal_notification(0), later)
+        );
+        assert_eq!(
+            Replay::Tolerated,
+            window.check(&proposal_notification(0), later),
+            "replays expire"
+        );
+    }
+}
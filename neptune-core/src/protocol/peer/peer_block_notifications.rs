@@ -2,8 +2,10 @@ use serde::Deserialize;
 use serde::Serialize;
 use tasm_lib::prelude::Digest;
 
+use crate::protocol::consensus::block::block_header::BlockHeader;
 use crate::protocol::consensus::block::block_height::BlockHeight;
 use crate::protocol::consensus::block::difficulty_control::ProofOfWork;
+use crate::protocol::consensus::block::pow::PowMastPaths;
 use crate::protocol::consensus::block::Block;
 
 /// Used to tell peers that a new block has been found without having to
@@ -25,11 +27,59 @@ impl From<&Block> for PeerBlockNotification {
     }
 }
 
+/// Used to tell peers that a new block has been found by pushing its header,
+/// which is small regardless of the size of the block.
+///
+/// Unlike a [`PeerBlockNotification`], the header lets the receiver verify
+/// the block's difficulty and proof-of-work before deciding whether to request
+/// the full block. Only sent to peers that advertise the
+/// [`HEADER_FIRST_RELAY_CAPABILITY`](super::handshake_data::HEADER_FIRST_RELAY_CAPABILITY).
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub(crate) struct PeerBlockHeaderNotification {
+    pub(crate) header: BlockHeader,
+
+    /// Authenticates the header, and its `pow` field in particular, against
+    /// the block hash.
+    pub(crate) pow_mast_paths: PowMastPaths,
+}
+
+impl PeerBlockHeaderNotification {
+    /// The hash of the announced block. Only meaningful if the authentication
+    /// paths belong to the header, which
+    /// [`Block::header_has_proof_of_work`] verifies.
+    pub(crate) fn hash(&self) -> Digest {
+        self.pow_mast_paths.fast_mast_hash(self.header.pow)
+    }
+}
+
+impl From<&Block> for PeerBlockHeaderNotification {
+    fn from(block: &Block) -> Self {
+        PeerBlockHeaderNotification {
+            header: *block.header(),
+            pow_mast_paths: block.pow_mast_paths(),
+        }
+    }
+}
+
+impl From<&PeerBlockHeaderNotification> for PeerBlockNotification {
+    fn from(notification: &PeerBlockHeaderNotification) -> Self {
+        PeerBlockNotification {
+            hash: notification.hash(),
+            height: notification.header.height,
+            cumulative_proof_of_work: notification.header.cumulative_proof_of_work,
+        }
+    }
+}
+
 #[cfg(test)]
 #[cfg_attr(coverage_nightly, coverage(off))]
 mod tests {
+    use super::PeerBlockHeaderNotification;
     use super::PeerBlockNotification;
+    use crate::application::config::network::Network;
     use crate::protocol::consensus::block::validity::block_primitive_witness::tests::deterministic_block_primitive_witness;
+    use crate::protocol::consensus::block::Block;
+    use crate::tests::shared::blocks::invalid_empty_block;
 
     #[test]
     fn block_notification_hash_matches_block_hash() {
@@ -42,4 +92,18 @@ mod tests {
             "Block notification hash must match block hash"
         );
     }
+
+    #[test]
+    fn block_header_notification_hash_matches_block_hash() {
+        let network = Network::Main;
+        let block = invalid_empty_block(&Block::genesis(network), network);
+        let as_notification: PeerBlockHeaderNotification = (&block).into();
+        assert_eq!(block.hash(), as_notification.hash());
+        assert!(as_notification
+            .pow_mast_paths
+            .agrees_with_header(&as_notification.header));
+
+        let as_block_notification = PeerBlockNotification::from(&as_notification);
+        assert_eq!(PeerBlockNotification::from(&block), as_block_notification);
+    }
 }
@@ -0,0 +1,541 @@
+use std::io::Read;
+
+use bytes::Buf;
+use bytes::BufMut;
+use bytes::Bytes;
+use bytes::BytesMut;
+use tokio_util::codec::Decoder;
+use tokio_util::codec::Encoder;
+use tokio_util::codec::LengthDelimitedCodec;
+
+use super::message_sequence::IncomingSequence;
+use super::message_sequence::SequenceViolations;
+use super::message_sequence::SEQUENCE_NUMBER_SIZE;
+
+/// Marker byte for a frame whose payload is sent as-is.
+const FRAME_FLAG_RAW: u8 = 0;
+
+/// Marker byte for a frame whose payload is a zstd frame.
+const FRAME_FLAG_ZSTD: u8 = 1;
+
+/// The zstd compression level used for outgoing messages. Level 3 is zstd's
+/// own default and a good trade-off between speed and ratio for the mostly
+/// high-entropy proof data that dominates large peer messages.
+const ZSTD_COMPRESSION_LEVEL: i32 = 3;
+
+/// Size in bytes of the length prefix of every frame, as used by
+/// [`LengthDelimitedCodec`] by default.
+const FRAME_HEADER_SIZE: usize = 4;
+
+/// Number of leading message bytes passed to a [`MessageSizeLimit`].
+const MESSAGE_PREFIX_LENGTH: usize = 16;
+
+/// Number of compressed bytes after which the start of a zstd frame can be
+/// decompressed: the maximum size of a zstd frame header plus that of a block.
+const ZSTD_FIRST_BLOCK_MAX_SIZE: usize = 18 + 128 * 1024;
+
+/// Returns the maximum size of a message, given its first bytes, or `None` if
+/// more bytes are needed to tell.
+pub(crate) type MessageSizeLimit = fn(&[u8]) -> Option<usize>;
+
+/// Length-delimited codec for peer connections with optional zstd
+/// compression.
+///
+/// Until [`PeerCodec::enable_compression`] is called, the wire format is
+/// identical to that of a plain [`LengthDelimitedCodec`], so the handshake
+/// is always exchanged uncompressed. Once compression has been negotiated in
+/// the handshake, every frame payload is prefixed with a one-byte flag that
+/// indicates whether the remainder of the payload is zstd-compressed.
+/// Payloads smaller than the threshold are never compressed, and neither are
+/// payloads that would not shrink.
+///
+/// Likewise, once sequencing has been negotiated, every frame payload is
+/// prefixed with the frame's sequence number, see
+/// [message_sequence](super::message_sequence). The sequence number precedes
+/// the compression flag, such that replayed frames are dropped without being
+/// decompressed.
+///
+/// If a [`MessageSizeLimit`] is set, a frame is rejected as soon as its length
+/// prefix and enough of its payload have arrived to tell that it exceeds the
+/// limit of its message type, so oversized frames are never buffered.
+#[derive(Debug, Clone)]
+pub(crate) struct PeerCodec {
+    inner: LengthDelimitedCodec,
+    max_frame_length: usize,
+    message_size_limit: Option<MessageSizeLimit>,
+
+    /// Minimum payload size for compression to be attempted. `None` if
+    /// compression is not enabled on this connection.
+    compression_threshold: Option<usize>,
+
+    /// The sequence number of the next outgoing frame. `None` if sequencing is
+    /// not enabled on this connection.
+    outgoing_sequence_number: Option<u64>,
+
+    /// `None` if sequencing is not enabled on this connection.
+    incoming_sequence: Option<IncomingSequence>,
+}
+
+impl PeerCodec {
+    pub(crate) fn new(max_frame_length: usize) -> Self {
+        let mut inner = LengthDelimitedCodec::new();
+        inner.set_max_frame_length(max_frame_length);
+        Self {
+            inner,
+            max_frame_length,
+            message_size_limit: None,
+            compression_threshold: None,
+            outgoing_sequence_number: None,
+            incoming_sequence: None,
+        }
+    }
+
+    /// Reject frames exceeding the limit of the message they contain.
+    pub(crate) fn with_message_size_limit(mut self, limit: MessageSizeLimit) -> Self {
+        self.message_size_limit = Some(limit);
+        self
+    }
+
+    /// Switch to the compressed wire format. Must be called by both ends of
+    /// the connection at the same point in the message stream.
+    pub(crate) fn enable_compression(&mut self, threshold: usize) {
+        self.compression_threshold = Some(threshold);
+    }
+
+    pub(crate) fn compression_is_enabled(&self) -> bool {
+        self.compression_threshold.is_some()
+    }
+
+    /// Switch to the sequenced wire format. Must be called by both ends of the
+    /// connection at the same point in the message stream. Returns the
+    /// violations of the peer's numbering, as they are detected.
+    pub(crate) fn enable_sequencing(&mut self) -> SequenceViolations {
+        let violations = SequenceViolations::default();
+        self.outgoing_sequence_number = Some(0);
+        self.incoming_sequence = Some(IncomingSequence::new(violations.clone()));
+        violations
+    }
+
+    pub(crate) fn sequencing_is_enabled(&self) -> bool {
+        self.incoming_sequence.is_some()
+    }
+
+    /// Returns true if the next frame has been buffered completely, such that
+    /// it can be decoded. Returns an error as soon as the buffered part of the
+    /// frame shows that it exceeds the limit of its message type.
+    fn next_frame_is_buffered(&self, src: &mut BytesMut) -> std::io::Result<bool> {
+        let Some(header) = src.get(..FRAME_HEADER_SIZE) else {
+            return Ok(false);
+        };
+        let frame_length = u32::from_be_bytes(header.try_into().unwrap()) as usize;
+
+        // Let the inner codec reject frames exceeding the max frame length.
+        let buffered = &src[FRAME_HEADER_SIZE..];
+        if frame_length > self.max_frame_length || buffered.len() >= frame_length {
+            return Ok(true);
+        }
+
+        if let Some(limit) = self.message_size_limit {
+            let Some(message_prefix) = self.message_prefix(buffered)? else {
+                return Ok(false);
+            };
+            let Some(max_message_size) = limit(&message_prefix) else {
+                return Ok(false);
+            };
+
+            // Compressed messages are never bigger than uncompressed ones.
+            let message_size = frame_length.saturating_sub(self.payload_overhead());
+            if message_size > max_message_size {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!(
+                        "peer frame is {message_size} bytes; \
+                         max for its message type is {max_message_size} bytes"
+                    ),
+                ));
+            }
+        }
+
+        src.reserve(FRAME_HEADER_SIZE + frame_length - src.len());
+        Ok(false)
+    }
+
+    /// Number of bytes preceding the message in the payload of a frame.
+    fn payload_overhead(&self) -> usize {
+        let sequence_number_size = if self.sequencing_is_enabled() {
+            SEQUENCE_NUMBER_SIZE
+        } else {
+            0
+        };
+        let compression_flag_size = usize::from(self.compression_is_enabled());
+
+        sequence_number_size + compression_flag_size
+    }
+
+    /// The first bytes of the message in the partially buffered payload of a
+    /// frame, decompressed if necessary. Returns `None` if the payload does not
+    /// contain any bytes of the message yet.
+    fn message_prefix(&self, payload: &[u8]) -> std::io::Result<Option<Vec<u8>>> {
+        let Some(message) = payload.get(self.payload_overhead()..) else {
+            return Ok(None);
+        };
+        let raw_prefix = &message[..message.len().min(MESSAGE_PREFIX_LENGTH)];
+        if !self.compression_is_enabled() {
+            return Ok(Some(raw_prefix.to_vec()));
+        }
+
+        match payload[self.payload_overhead() - 1] {
+            FRAME_FLAG_ZSTD => {
+                // The stream fails once the buffered data is exhausted, after
+                // yielding whatever could be decompressed.
+                let mut prefix = vec![];
+                let _ = zstd::stream::read::Decoder::new(message)?
+                    .take(MESSAGE_PREFIX_LENGTH as u64)
+                    .read_to_end(&mut prefix);
+                if prefix.is_empty() && message.len() >= ZSTD_FIRST_BLOCK_MAX_SIZE {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "peer frame contains invalid zstd data",
+                    ));
+                }
+                Ok(Some(prefix))
+            }
+            _ => Ok(Some(raw_prefix.to_vec())),
+        }
+    }
+
+    /// Strip the sequence number from the frame. Returns `None` if the frame
+    /// is a replay.
+    fn check_sequence_number(&mut self, mut frame: BytesMut) -> std::io::Result<Option<BytesMut>> {
+        let Some(incoming_sequence) = &mut self.incoming_sequence else {
+            return Ok(Some(frame));
+        };
+
+        if frame.len() < SEQUENCE_NUMBER_SIZE {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "peer frame is missing sequence number",
+            ));
+        }
+
+        let sequence_number = frame.get_u64();
+        Ok(incoming_sequence.accept(sequence_number).then_some(frame))
+    }
+
+    /// Encode the payload, prefixed with the next sequence number if
+    /// sequencing is enabled.
+    fn encode_payload(&mut self, payload: Bytes, dst: &mut BytesMut) -> std::io::Result<()> {
+        let Some(sequence_number) = self.outgoing_sequence_number else {
+            return self.inner.encode(payload, dst);
+        };
+
+        let mut sequenced = BytesMut::with_capacity(SEQUENCE_NUMBER_SIZE + payload.len());
+        sequenced.put_u64(sequence_number);
+        sequenced.extend_from_slice(&payload);
+        self.inner.encode(sequenced.freeze(), dst)?;
+
+        // Only frames that were sent are numbered, such that the peer sees no
+        // gap.
+        self.outgoing_sequence_number = Some(sequence_number.saturating_add(1));
+        Ok(())
+    }
+
+    fn decompress(&self, compressed: &[u8]) -> std::io::Result<BytesMut> {
+        let mut decoder = zstd::stream::read::Decoder::new(compressed)?;
+        let mut decompressed = vec![];
+        (&mut decoder)
+            .take(MESSAGE_PREFIX_LENGTH as u64)
+            .read_to_end(&mut decompressed)?;
+        let max_length = self
+            .message_size_limit
+            .and_then(|limit| limit(&decompressed))
+            .map_or(self.max_frame_length, |max_message_size| {
+                max_message_size.min(self.max_frame_length)
+            });
+
+        // Read at most one byte more than allowed, such that decompression
+        // bombs are rejected without allocating more than the limit.
+        let remaining_limit = (max_length + 1).saturating_sub(decompressed.len()) as u64;
+        decoder
+            .take(remaining_limit)
+            .read_to_end(&mut decompressed)?;
+
+        if decompressed.len() > max_length {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "decompressed peer message exceeds max length",
+            ));
+        }
+
+        Ok(BytesMut::from(&decompressed[..]))
+    }
+}
+
+impl Decoder for PeerCodec {
+    type Item = BytesMut;
+    type Error = std::io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let mut frame = loop {
+            if !self.next_frame_is_buffered(src)? {
+                return Ok(None);
+            }
+            let Some(frame) = self.inner.decode(src)? else {
+                return Ok(None);
+            };
+            if let Some(frame) = self.check_sequence_number(frame)? {
+                break frame;
+            }
+        };
+
+        if !self.compression_is_enabled() {
+            return Ok(Some(frame));
+        }
+
+        if frame.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "peer frame is missing compression flag",
+            ));
+        }
+
+        match frame.get_u8() {
+            FRAME_FLAG_RAW => Ok(Some(frame)),
+            FRAME_FLAG_ZSTD => self.decompress(&frame).map(Some),
+            flag => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("unknown peer frame compression flag {flag}"),
+            )),
+        }
+    }
+}
+
+impl Encoder<Bytes> for PeerCodec {
+    type Error = std::io::Error;
+
+    fn encode(&mut self, item: Bytes, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let Some(threshold) = self.compression_threshold else {
+            return self.encode_payload(item, dst);
+        };
+
+        if item.len() >= threshold {
+            let compressed = zstd::bulk::compress(&item, ZSTD_COMPRESSION_LEVEL)?;
+            if compressed.len() < item.len() {
+                let mut payload = BytesMut::with_capacity(compressed.len() + 1);
+                payload.put_u8(FRAME_FLAG_ZSTD);
+                payload.extend_from_slice(&compressed);
+                return self.encode_payload(payload.freeze(), dst);
+            }
+        }
+
+        let mut payload = BytesMut::with_capacity(item.len() + 1);
+        payload.put_u8(FRAME_FLAG_RAW);
+        payload.extend_from_slice(&item);
+        self.encode_payload(payload.freeze(), dst)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    const MAX_FRAME_LENGTH: usize = 1024 * 1024;
+
+    fn round_trip(codec: &mut PeerCodec, payload: &[u8]) -> (BytesMut, usize) {
+        let mut buf = BytesMut::new();
+        codec
+            .encode(Bytes::copy_from_slice(payload), &mut buf)
+            .unwrap();
+        let wire_length = buf.len();
+        let decoded = codec.decode(&mut buf).unwrap().unwrap();
+        assert!(buf.is_empty());
+
+        (decoded, wire_length)
+    }
+
+    #[test]
+    fn uncompressed_codec_matches_length_delimited_codec() {
+        let payload = Bytes::from_static(b"hello neptune");
+        let mut codec = PeerCodec::new(MAX_FRAME_LENGTH);
+        let mut reference = LengthDelimitedCodec::new();
+
+        let mut own = BytesMut::new();
+        let mut expected = BytesMut::new();
+        codec.encode(payload.clone(), &mut own).unwrap();
+        reference.encode(payload, &mut expected).unwrap();
+
+        assert_eq!(expected, own);
+    }
+
+    #[test]
+    fn small_messages_are_not_compressed() {
+        let mut codec = PeerCodec::new(MAX_FRAME_LENGTH);
+        codec.enable_compression(1024);
+
+        let payload = vec![0u8; 100];
+        let (decoded, wire_length) = round_trip(&mut codec, &payload);
+        assert_eq!(payload, decoded.to_vec());
+
+        // 4 bytes length prefix, 1 byte flag
+        assert_eq!(payload.len() + 5, wire_length);
+    }
+
+    #[test]
+    fn large_compressible_messages_are_compressed() {
+        let mut codec = PeerCodec::new(MAX_FRAME_LENGTH);
+        codec.enable_compression(1024);
+
+        let payload = vec![42u8; 100_000];
+        let (decoded, wire_length) = round_trip(&mut codec, &payload);
+        assert_eq!(payload, decoded.to_vec());
+        assert!(wire_length < payload.len() / 10);
+    }
+
+    #[test]
+    fn incompressible_messages_are_sent_raw() {
+        let mut codec = PeerCodec::new(MAX_FRAME_LENGTH);
+        codec.enable_compression(16);
+
+        let payload: Vec<u8> = (0..10_000).map(|_| rand::random()).collect();
+        let (decoded, wire_length) = round_trip(&mut codec, &payload);
+        assert_eq!(payload, decoded.to_vec());
+        assert_eq!(payload.len() + 5, wire_length);
+    }
+
+    #[test]
+    fn decompression_bomb_is_rejected() {
+        let max_frame_length = 1000;
+        let bomb = zstd::bulk::compress(&vec![0u8; 10 * max_frame_length], 3).unwrap();
+        let mut payload = vec![FRAME_FLAG_ZSTD];
+        payload.extend_from_slice(&bomb);
+
+        let mut buf = BytesMut::new();
+        LengthDelimitedCodec::new()
+            .encode(Bytes::from(payload), &mut buf)
+            .unwrap();
+
+        let mut codec = PeerCodec::new(max_frame_length);
+        codec.enable_compression(0);
+        assert!(codec.decode(&mut buf).is_err());
+    }
+
+    fn limit_of_100_bytes(prefix: &[u8]) -> Option<usize> {
+        prefix.first().map(|_| 100)
+    }
+
+    #[test]
+    fn oversized_frame_is_rejected_before_it_is_buffered() {
+        let mut buf = BytesMut::new();
+        buf.put_u32(1000);
+        buf.put_u8(42);
+
+        let mut codec =
+            PeerCodec::new(MAX_FRAME_LENGTH).with_message_size_limit(limit_of_100_bytes);
+        assert!(codec.decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn frame_within_limit_is_decoded_once_buffered() {
+        let mut sender = PeerCodec::new(MAX_FRAME_LENGTH);
+        let payload = vec![42u8; 100];
+        let mut frame = BytesMut::new();
+        sender
+            .encode(Bytes::copy_from_slice(&payload), &mut frame)
+            .unwrap();
+
+        let mut codec =
+            PeerCodec::new(MAX_FRAME_LENGTH).with_message_size_limit(limit_of_100_bytes);
+        let mut buf = BytesMut::from(&frame[..10]);
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+        buf.extend_from_slice(&frame[10..]);
+        assert_eq!(payload, codec.decode(&mut buf).unwrap().unwrap().to_vec());
+    }
+
+    #[test]
+    fn compressed_frame_exceeding_limit_of_message_type_is_rejected() {
+        let mut sender = PeerCodec::new(MAX_FRAME_LENGTH);
+        sender.enable_compression(0);
+        let mut buf = BytesMut::new();
+        sender
+            .encode(Bytes::from(vec![42u8; 10_000]), &mut buf)
+            .unwrap();
+        assert!(buf.len() < 100);
+
+        let mut codec =
+            PeerCodec::new(MAX_FRAME_LENGTH).with_message_size_limit(limit_of_100_bytes);
+        codec.enable_compression(0);
+        assert!(codec.decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn unknown_flag_is_rejected() {
+        let mut buf = BytesMut::new();
+        LengthDelimitedCodec::new()
+            .encode(Bytes::from_static(&[7, 1, 2, 3]), &mut buf)
+            .unwrap();
+
+        let mut codec = PeerCodec::new(MAX_FRAME_LENGTH);
+        codec.enable_compression(0);
+        assert!(codec.decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn sequenced_frames_round_trip_with_compression() {
+        let mut codec = PeerCodec::new(MAX_FRAME_LENGTH);
+        codec.enable_compression(1024);
+        let violations = codec.enable_sequencing();
+
+        for payload in [vec![1u8; 100], vec![42u8; 100_000], vec![2u8; 10]] {
+            let (decoded, _) = round_trip(&mut codec, &payload);
+            assert_eq!(payload, decoded.to_vec());
+        }
+        assert_eq!(0, violations.take());
+    }
+
+    #[test]
+    fn replayed_frames_are_dropped() {
+        let mut sender = PeerCodec::new(MAX_FRAME_LENGTH);
+        sender.enable_sequencing();
+        let mut receiver = PeerCodec::new(MAX_FRAME_LENGTH);
+        let violations = receiver.enable_sequencing();
+
+        let mut first = BytesMut::new();
+        sender
+            .encode(Bytes::from_static(b"first"), &mut first)
+            .unwrap();
+        let mut second = BytesMut::new();
+        sender
+            .encode(Bytes::from_static(b"second"), &mut second)
+            .unwrap();
+
+        // the peer sends the first frame, then replays it before the second
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(&first);
+        buf.extend_from_slice(&first);
+        buf.extend_from_slice(&second);
+
+        assert_eq!(
+            &b"first"[..],
+            &receiver.decode(&mut buf).unwrap().unwrap()[..]
+        );
+        assert_eq!(
+            &b"second"[..],
+            &receiver.decode(&mut buf).unwrap().unwrap()[..]
+        );
+        assert!(buf.is_empty());
+        assert_eq!(1, violations.take());
+    }
+
+    #[test]
+    fn unsequenced_frame_is_rejected() {
+        let mut buf = BytesMut::new();
+        LengthDelimitedCodec::new()
+            .encode(Bytes::from_static(&[1, 2, 3]), &mut buf)
+            .unwrap();
+
+        let mut codec = PeerCodec::new(MAX_FRAME_LENGTH);
+        codec.enable_sequencing();
+        assert!(codec.decode(&mut buf).is_err());
+    }
+}
@@ -5,6 +5,12 @@ use std::time::SystemTime;
 use serde::Deserialize;
 use serde::Serialize;
 
+use super::fork_signaling;
+use super::handshake_data::RENDEZVOUS_CAPABILITY;
+use super::handshake_data::RENDEZVOUS_RELAY_CAPABILITY;
+use super::handshake_data::WALLET_BACKUP_CAPABILITY;
+use super::peer_latency::PeerLatency;
+use super::plausible_deniability::RelayRole;
 use super::InstanceId;
 use super::PeerStanding;
 use crate::HandshakeData;
@@ -39,6 +45,40 @@ pub struct PeerInfo {
     pub(crate) standing: PeerStanding,
     version: String,
     is_archival_node: bool,
+
+    /// Which messages from the main task are pushed to this peer. Only
+    /// restricted in plausible deniability mode.
+    #[serde(default)]
+    relay_role: RelayRole,
+
+    /// Number of valid new blocks that this peer announced before any other
+    /// peer.
+    #[serde(default)]
+    num_blocks_announced_first: u64,
+
+    /// Round-trip time and clock offset, if the peer answered any pings.
+    #[serde(default)]
+    latency: Option<PeerLatency>,
+
+    /// Whether the peer accepts introductions to other peers, see
+    /// [`RENDEZVOUS_CAPABILITY`].
+    #[serde(default)]
+    accepts_introductions: bool,
+
+    /// Whether the peer introduces peers to each other, see
+    /// [`RENDEZVOUS_RELAY_CAPABILITY`].
+    #[serde(default)]
+    introduces_peers: bool,
+
+    /// Whether the peer exchanges wallet backups with the peers it trusts,
+    /// see [`WALLET_BACKUP_CAPABILITY`].
+    #[serde(default)]
+    exchanges_wallet_backups: bool,
+
+    /// The consensus rule sets the peer signals readiness for, see
+    /// [`fork_signaling`].
+    #[serde(default)]
+    signaled_rule_sets: Vec<String>,
 }
 
 impl PeerInfo {
@@ -58,6 +98,13 @@ impl PeerInfo {
             standing,
             version: peer_handshake.version.to_string(),
             is_archival_node: peer_handshake.is_archival_node,
+            relay_role: RelayRole::default(),
+            num_blocks_announced_first: 0,
+            latency: None,
+            accepts_introductions: peer_handshake.has_capability(RENDEZVOUS_CAPABILITY),
+            introduces_peers: peer_handshake.has_capability(RENDEZVOUS_RELAY_CAPABILITY),
+            exchanges_wallet_backups: peer_handshake.has_capability(WALLET_BACKUP_CAPABILITY),
+            signaled_rule_sets: fork_signaling::signaled_rule_sets(peer_handshake),
         }
     }
 
@@ -66,6 +113,59 @@ impl PeerInfo {
         self
     }
 
+    pub(crate) fn with_relay_role(mut self, relay_role: RelayRole) -> Self {
+        self.relay_role = relay_role;
+        self
+    }
+
+    pub(crate) fn set_relay_role(&mut self, relay_role: RelayRole) {
+        self.relay_role = relay_role;
+    }
+
+    pub fn relay_role(&self) -> RelayRole {
+        self.relay_role
+    }
+
+    /// Number of valid new blocks that this peer announced before any other
+    /// peer.
+    /// Peers that announce many blocks first are relayed new blocks first.
+    pub fn num_blocks_announced_first(&self) -> u64 {
+        self.num_blocks_announced_first
+    }
+
+    pub(crate) fn record_block_announced_first(&mut self) {
+        self.num_blocks_announced_first += 1;
+    }
+
+    /// Round-trip time and clock offset, if the peer answered any pings.
+    pub fn latency(&self) -> Option<PeerLatency> {
+        self.latency
+    }
+
+    pub(crate) fn record_latency_sample(&mut self, sample: PeerLatency) {
+        self.latency = Some(match self.latency {
+            Some(latency) => latency.update(sample),
+            None => sample,
+        });
+    }
+
+    pub(crate) fn accepts_introductions(&self) -> bool {
+        self.accepts_introductions
+    }
+
+    pub(crate) fn introduces_peers(&self) -> bool {
+        self.introduces_peers
+    }
+
+    pub(crate) fn exchanges_wallet_backups(&self) -> bool {
+        self.exchanges_wallet_backups
+    }
+
+    /// The names of the consensus rule sets the peer signals readiness for.
+    pub fn signaled_rule_sets(&self) -> &[String] {
+        &self.signaled_rule_sets
+    }
+
     pub(crate) fn instance_id(&self) -> u128 {
         self.instance_id
     }
@@ -179,6 +279,12 @@ impl rand::distr::Distribution<PeerInfo> for rand::distr::StandardUniform {
             .map(char::from)
             .collect(),
             is_archival_node: rng.random(),
+            relay_role: RelayRole::default(),
+            num_blocks_announced_first: rng.random(),
+            latency: None,
+            accepts_introductions: rng.random(),
+            introduces_peers: rng.random(),
+            exchanges_wallet_backups: rng.random(),
         }
     }
 }
@@ -0,0 +1,227 @@
+//! Measurement of round-trip times and clock offsets of peers.
+//!
+//! Peers that advertise
+//! [`PING_CAPABILITY`](super::handshake_data::PING_CAPABILITY) are
+//! periodically sent a [`Ping`], which they answer with a [`Pong`] stating
+//! when they received the ping and when they sent the pong. As in NTP, the four
+//! timestamps yield the round-trip time without the peer's processing time,
+//! and the offset of the peer's clock assuming symmetric network delays.
+//!
+//! Round-trip times break ties when choosing peers to sync from and to relay
+//! blocks to. The median clock offset adjusts the time against which the
+//! timestamps of blocks received from peers are judged.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+
+/// Weight of the newest sample in the smoothed measurements, as a fraction
+/// `1/SMOOTHING_DENOMINATOR`.
+const SMOOTHING_DENOMINATOR: i64 = 8;
+
+/// Minimum number of peers with measured clock offsets for the own clock to
+/// be adjusted, such that a few peers cannot shift it.
+const MIN_PEERS_FOR_CLOCK_ADJUSTMENT: usize = 5;
+
+/// Maximum adjustment of the own clock. Well below the tolerance for
+/// futuredated blocks, such that a majority of peers with skewed clocks
+/// cannot make this node accept blocks that others reject.
+const MAX_CLOCK_ADJUSTMENT: Timestamp = Timestamp::minutes(1);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub(crate) struct Ping {
+    pub(crate) nonce: u64,
+    pub(crate) sent_at: Timestamp,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub(crate) struct Pong {
+    /// The nonce of the answered [`Ping`].
+    pub(crate) nonce: u64,
+
+    /// When the ping was received, according to the answering peer's clock.
+    pub(crate) ping_received_at: Timestamp,
+
+    /// When the pong was sent, according to the answering peer's clock.
+    pub(crate) sent_at: Timestamp,
+}
+
+/// Round-trip time and clock offset of a peer, smoothed over all pings.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct PeerLatency {
+    /// Round-trip time, excluding the time the peer took to answer.
+    pub round_trip_time: Duration,
+
+    /// Difference between the peer's clock and this node's clock, in
+    /// milliseconds. Positive if the peer's clock is ahead.
+    pub clock_offset_millis: i64,
+
+    /// Number of answered pings the measurements are based on.
+    pub num_samples: u64,
+}
+
+impl PeerLatency {
+    /// Measure the latency from a single answered ping. Returns `None` if the
+    /// timestamps are inconsistent, for instance because the pong claims to
+    /// have been sent before the ping was received.
+    pub(crate) fn sample(ping: Ping, pong: Pong, pong_received_at: Timestamp) -> Option<Self> {
+        let millis = |timestamp: Timestamp| i64::try_from(timestamp.to_millis()).ok();
+        let t0 = millis(ping.sent_at)?;
+        let t1 = millis(pong.ping_received_at)?;
+        let t2 = millis(pong.sent_at)?;
+        let t3 = millis(pong_received_at)?;
+
+        let round_trip_millis = (t3 - t0) - (t2 - t1);
+        if t3 < t0 || t2 < t1 || round_trip_millis < 0 {
+            return None;
+        }
+
+        Some(Self {
+            round_trip_time: Duration::from_millis(round_trip_millis.unsigned_abs()),
+            clock_offset_millis: ((t1 - t0) + (t2 - t3)) / 2,
+            num_samples: 1,
+        })
+    }
+
+    /// Fold a new sample into the smoothed measurements.
+    pub(crate) fn update(self, sample: Self) -> Self {
+        let smooth = |old: i64, new: i64| old + (new - old) / SMOOTHING_DENOMINATOR;
+        let round_trip_millis = smooth(
+            i64::try_from(self.round_trip_time.as_millis()).unwrap_or(i64::MAX),
+            i64::try_from(sample.round_trip_time.as_millis()).unwrap_or(i64::MAX),
+        );
+
+        Self {
+            round_trip_time: Duration::from_millis(round_trip_millis.unsigned_abs()),
+            clock_offset_millis: smooth(self.clock_offset_millis, sample.clock_offset_millis),
+            num_samples: self.num_samples.saturating_add(sample.num_samples),
+        }
+    }
+}
+
+/// The median of the given clock offsets, in milliseconds, or `None` if there
+/// are none.
+pub(crate) fn median_clock_offset_millis(offsets: impl IntoIterator<Item = i64>) -> Option<i64> {
+    let mut offsets = offsets.into_iter().collect::<Vec<_>>();
+    if offsets.is_empty() {
+        return None;
+    }
+
+    offsets.sort_unstable();
+    let middle = offsets.len() / 2;
+    if offsets.len() % 2 == 0 {
+        Some((offsets[middle - 1] + offsets[middle]) / 2)
+    } else {
+        Some(offsets[middle])
+    }
+}
+
+/// The own time `now`, adjusted by the median of the peers' clock offsets, in
+/// milliseconds. The adjustment is bounded, and only made if enough peers were
+/// measured.
+pub(crate) fn network_adjusted_time(
+    now: Timestamp,
+    offsets: impl IntoIterator<Item = i64>,
+) -> Timestamp {
+    let offsets = offsets.into_iter().collect::<Vec<_>>();
+    if offsets.len() < MIN_PEERS_FOR_CLOCK_ADJUSTMENT {
+        return now;
+    }
+    let Some(median) = median_clock_offset_millis(offsets) else {
+        return now;
+    };
+
+    let max_adjustment = MAX_CLOCK_ADJUSTMENT.to_millis() as i64;
+    let adjustment = median.clamp(-max_adjustment, max_adjustment);
+    let magnitude = Timestamp::millis(adjustment.unsigned_abs());
+    if adjustment < 0 {
+        now - magnitude
+    } else {
+        now + magnitude
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    fn at(millis: u64) -> Timestamp {
+        Timestamp::millis(millis)
+    }
+
+    #[test]
+    fn sample_excludes_processing_time_and_measures_offset() {
+        // Peer's clock is 1000 ms ahead, each direction takes 50 ms, and the
+        // peer takes 30 ms to answer.
+        let ping = Ping {
+            nonce: 7,
+            sent_at: at(10_000),
+        };
+        let pong = Pong {
+            nonce: 7,
+            ping_received_at: at(11_050),
+            sent_at: at(11_080),
+        };
+        let latency = PeerLatency::sample(ping, pong, at(10_130)).unwrap();
+        assert_eq!(Duration::from_millis(100), latency.round_trip_time);
+        assert_eq!(1000, latency.clock_offset_millis);
+    }
+
+    #[test]
+    fn inconsistent_timestamps_are_rejected() {
+        let ping = Ping {
+            nonce: 0,
+            sent_at: at(10_000),
+        };
+        let pong = Pong {
+            nonce: 0,
+            ping_received_at: at(10_000),
+            sent_at: at(10_500),
+        };
+        assert!(PeerLatency::sample(ping, pong, at(10_100)).is_none());
+    }
+
+    #[test]
+    fn update_smooths_towards_new_samples() {
+        let latency = PeerLatency {
+            round_trip_time: Duration::from_millis(100),
+            clock_offset_millis: 0,
+            num_samples: 1,
+        };
+        let sample = PeerLatency {
+            round_trip_time: Duration::from_millis(180),
+            clock_offset_millis: -800,
+            num_samples: 1,
+        };
+        let updated = latency.update(sample);
+        assert_eq!(Duration::from_millis(110), updated.round_trip_time);
+        assert_eq!(-100, updated.clock_offset_millis);
+        assert_eq!(2, updated.num_samples);
+    }
+
+    #[test]
+    fn median_of_clock_offsets() {
+        assert_eq!(None, median_clock_offset_millis([]));
+        assert_eq!(Some(5), median_clock_offset_millis([100, -3, 5]));
+        assert_eq!(Some(4), median_clock_offset_millis([100, -3, 5, 3]));
+    }
+
+    #[test]
+    fn network_adjusted_time_is_bounded_and_needs_enough_peers() {
+        let now = at(1_000_000);
+        assert_eq!(now, network_adjusted_time(now, [500; 4]));
+        assert_eq!(at(1_000_500), network_adjusted_time(now, [500; 5]));
+        assert_eq!(
+            at(999_000),
+            network_adjusted_time(now, [-1000, -1000, -1000, 0, 10_000_000])
+        );
+        assert_eq!(
+            now + MAX_CLOCK_ADJUSTMENT,
+            network_adjusted_time(now, [10_000_000; 5])
+        );
+    }
+}
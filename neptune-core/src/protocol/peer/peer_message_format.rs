@@ -0,0 +1,314 @@
+//! Bincode encoding of [`PeerMessage`]s with per-type size limits.
+//!
+//! Every peer message arrives in a length-delimited frame. Before decoding a
+//! frame, its message type is read from the leading variant index, and the
+//! frame is rejected if it exceeds the limit for that type. The limits for
+//! blocks, transactions, and the proofs they carry are derived from the
+//! consensus rules' maximum block size. Decoding is bounded by the frame's
+//! length, so a small frame cannot make the decoder allocate memory for a
+//! claimed billion-element vector.
+//!
+//! The [`PeerCodec`](super::peer_codec::PeerCodec) enforces the same limits as
+//! soon as the start of a frame has arrived, with
+//! [`max_message_size_from_prefix`], such that an oversized frame is rejected
+//! before it is buffered.
+
+use std::io;
+use std::pin::Pin;
+
+use bincode::Options;
+use bytes::Bytes;
+use bytes::BytesMut;
+use tasm_lib::triton_vm::prelude::BFieldElement;
+use tokio_serde::Deserializer;
+use tokio_serde::Serializer;
+
+use super::bandwidth::ConnectionBandwidth;
+use super::PeerMessage;
+use super::SYNC_CHALLENGE_NUM_BLOCK_PAIRS;
+use crate::application::loops::connect_to_peers::MAX_PEER_FRAME_LENGTH_IN_BYTES;
+use crate::protocol::consensus::consensus_rule_set::ConsensusRuleSet;
+
+/// Maximum size in bytes of an encoded block, or of an encoded transaction,
+/// which cannot be bigger than the block that includes it.
+///
+/// Twice the maximum block size, as bincode encodes a [`BFieldElement`] in up
+/// to 9 bytes, and to leave headroom for future rule sets.
+pub(crate) const MAX_BLOCK_MESSAGE_SIZE: usize =
+    2 * BFieldElement::BYTES * ConsensusRuleSet::HardforkAlpha.max_block_size();
+
+/// Maximum size in bytes of a sync challenge response, which contains the
+/// challenged block pairs, the tip, and the tip's parent.
+const MAX_SYNC_CHALLENGE_RESPONSE_SIZE: usize =
+    (2 * SYNC_CHALLENGE_NUM_BLOCK_PAIRS + 3) * MAX_BLOCK_MESSAGE_SIZE;
+
+/// Maximum size in bytes of messages that do not contain blocks or
+/// transactions.
+const MAX_SMALL_MESSAGE_SIZE: usize = 1024 * 1024;
+
+// Indices of the [`PeerMessage`] variants whose limits differ from
+// [`MAX_SMALL_MESSAGE_SIZE`]. Variants are never reordered, as that would
+// break compatibility with other nodes, so these indices are stable.
+const BLOCK_VARIANT_INDEX: u32 = 1;
+const BLOCK_RESPONSE_BATCH_VARIANT_INDEX: u32 = 7;
+const SYNC_CHALLENGE_RESPONSE_VARIANT_INDEX: u32 = 10;
+const BLOCK_PROPOSAL_VARIANT_INDEX: u32 = 13;
+const TRANSACTION_VARIANT_INDEX: u32 = 14;
+
+/// Maximum size in bytes of an encoded [`PeerMessage`] with the given variant
+/// index.
+fn max_message_size(variant_index: u32) -> usize {
+    let max_size = match variant_index {
+        BLOCK_VARIANT_INDEX | BLOCK_PROPOSAL_VARIANT_INDEX | TRANSACTION_VARIANT_INDEX => {
+            MAX_BLOCK_MESSAGE_SIZE
+        }
+        SYNC_CHALLENGE_RESPONSE_VARIANT_INDEX => MAX_SYNC_CHALLENGE_RESPONSE_SIZE,
+        BLOCK_RESPONSE_BATCH_VARIANT_INDEX => MAX_PEER_FRAME_LENGTH_IN_BYTES,
+        _ => MAX_SMALL_MESSAGE_SIZE,
+    };
+
+    max_size.min(MAX_PEER_FRAME_LENGTH_IN_BYTES)
+}
+
+/// Maximum length in bytes of a bincode-encoded variant index.
+const MAX_VARIANT_INDEX_LENGTH: usize = 5;
+
+/// Maximum size in bytes of the encoded [`PeerMessage`] that starts with
+/// `prefix`. Returns `None` if the prefix is too short to tell the message
+/// type.
+pub(crate) fn max_message_size_from_prefix(prefix: &[u8]) -> Option<usize> {
+    match bincode_options()
+        .allow_trailing_bytes()
+        .deserialize::<u32>(prefix)
+    {
+        Ok(variant_index) => Some(max_message_size(variant_index)),
+        Err(_) if prefix.len() < MAX_VARIANT_INDEX_LENGTH => None,
+
+        // Not a valid message, which is rejected when decoded.
+        Err(_) => Some(MAX_SMALL_MESSAGE_SIZE),
+    }
+}
+
+fn bincode_options() -> impl Options + Copy {
+    bincode::DefaultOptions::new()
+}
+
+/// Encodes and decodes [`PeerMessage`]s, enforcing the size limit of each
+/// message type when decoding, and accounting for the bandwidth used.
+///
+/// The wire format is that of [`tokio_serde::formats::SymmetricalBincode`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PeerMessageFormat {
+    bandwidth: Option<ConnectionBandwidth>,
+}
+
+impl PeerMessageFormat {
+    /// Record the size of every message encoded or decoded in `bandwidth`.
+    pub(crate) fn with_bandwidth(bandwidth: ConnectionBandwidth) -> Self {
+        Self {
+            bandwidth: Some(bandwidth),
+        }
+    }
+}
+
+impl Deserializer<PeerMessage> for PeerMessageFormat {
+    type Error = io::Error;
+
+    fn deserialize(self: Pin<&mut Self>, src: &BytesMut) -> Result<PeerMessage, Self::Error> {
+        let invalid_data = |e| io::Error::new(io::ErrorKind::InvalidData, e);
+
+        let variant_index: u32 = bincode_options()
+            .allow_trailing_bytes()
+            .deserialize(src)
+            .map_err(invalid_data)?;
+        let max_size = max_message_size(variant_index);
+        if src.len() > max_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "peer message with variant index {variant_index} is {} bytes; \
+                     max is {max_size} bytes",
+                    src.len()
+                ),
+            ));
+        }
+
+        let message: PeerMessage = bincode_options()
+            .with_limit(src.len() as u64)
+            .deserialize(src)
+            .map_err(invalid_data)?;
+        if let Some(bandwidth) = &self.bandwidth {
+            bandwidth.record_received(message.bandwidth_category(), src.len());
+        }
+
+        Ok(message)
+    }
+}
+
+impl Serializer<PeerMessage> for PeerMessageFormat {
+    type Error = io::Error;
+
+    fn serialize(self: Pin<&mut Self>, item: &PeerMessage) -> Result<Bytes, Self::Error> {
+        let bytes = bincode_options()
+            .serialize(item)
+            .map(Bytes::from)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if let Some(bandwidth) = &self.bandwidth {
+            bandwidth.record_sent(item.bandwidth_category(), bytes.len());
+        }
+
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use tasm_lib::twenty_first::prelude::MmrMembershipProof;
+
+    use super::*;
+    use crate::application::config::network::Network;
+    use crate::protocol::consensus::block::block_header::BlockHeaderWithBlockHashWitness;
+    use crate::protocol::consensus::block::block_header::HeaderToBlockHashWitness;
+    use crate::protocol::consensus::block::Block;
+    use crate::protocol::consensus::transaction::validity::neptune_proof::Proof;
+    use crate::protocol::peer::bandwidth::BandwidthCategory;
+    use crate::protocol::peer::bandwidth::BandwidthRegistry;
+    use crate::protocol::peer::transfer_block::TransferBlock;
+    use crate::protocol::peer::transfer_transaction::TransferTransaction;
+    use crate::protocol::peer::SyncChallengeResponse;
+    use crate::tests::shared::mock_tx::invalid_empty_single_proof_transaction;
+
+    fn encode(message: &PeerMessage) -> BytesMut {
+        let bytes = Pin::new(&mut PeerMessageFormat::default())
+            .serialize(message)
+            .unwrap();
+        BytesMut::from(&bytes[..])
+    }
+
+    fn decode(frame: &BytesMut) -> io::Result<PeerMessage> {
+        Pin::new(&mut PeerMessageFormat::default()).deserialize(frame)
+    }
+
+    fn variant_index(message: &PeerMessage) -> u32 {
+        bincode_options()
+            .allow_trailing_bytes()
+            .deserialize(&encode(message))
+            .unwrap()
+    }
+
+    #[test]
+    fn variant_indices_match_peer_message() {
+        let genesis = Block::genesis(Network::Main);
+        let transfer_block = TransferBlock {
+            header: *genesis.header(),
+            body: genesis.body().clone(),
+            appendix: genesis.appendix().clone(),
+            proof: Proof::invalid_with_size(0),
+        };
+        let transaction =
+            TransferTransaction::try_from(&invalid_empty_single_proof_transaction()).unwrap();
+        let pow_witness = BlockHeaderWithBlockHashWitness::new(
+            *genesis.header(),
+            HeaderToBlockHashWitness::from(&genesis),
+        );
+        let sync_challenge_response = SyncChallengeResponse {
+            blocks: std::array::from_fn(|_| (transfer_block.clone(), transfer_block.clone())),
+            membership_proofs: std::array::from_fn(|_| MmrMembershipProof::new(vec![])),
+            tip_parent: transfer_block.clone(),
+            tip: transfer_block.clone(),
+            pow_witnesses: std::array::from_fn(|_| pow_witness.clone()),
+        };
+
+        assert_eq!(
+            BLOCK_VARIANT_INDEX,
+            variant_index(&PeerMessage::Block(Box::new(transfer_block)))
+        );
+        assert_eq!(
+            BLOCK_RESPONSE_BATCH_VARIANT_INDEX,
+            variant_index(&PeerMessage::BlockResponseBatch(vec![]))
+        );
+        assert_eq!(
+            SYNC_CHALLENGE_RESPONSE_VARIANT_INDEX,
+            variant_index(&PeerMessage::SyncChallengeResponse(Box::new(
+                sync_challenge_response
+            )))
+        );
+        assert_eq!(
+            BLOCK_PROPOSAL_VARIANT_INDEX,
+            variant_index(&PeerMessage::BlockProposal(Box::new(genesis)))
+        );
+        assert_eq!(
+            TRANSACTION_VARIANT_INDEX,
+            variant_index(&PeerMessage::Transaction(Box::new(transaction)))
+        );
+    }
+
+    #[test]
+    fn messages_within_limits_round_trip() {
+        for message in [
+            PeerMessage::Bye,
+            PeerMessage::PeerListRequest,
+            PeerMessage::BlockProposal(Box::new(Block::genesis(Network::Main))),
+        ] {
+            assert_eq!(message, decode(&encode(&message)).unwrap());
+        }
+    }
+
+    #[test]
+    fn encoded_and_decoded_messages_are_accounted() {
+        let registry = BandwidthRegistry::default();
+        let mut format =
+            PeerMessageFormat::with_bandwidth(registry.register("127.0.0.1:9798".parse().unwrap()));
+
+        let frame = Pin::new(&mut format).serialize(&PeerMessage::Bye).unwrap();
+        Pin::new(&mut format)
+            .deserialize(&BytesMut::from(&frame[..]))
+            .unwrap();
+
+        let report = registry.report();
+        let other = report.peers[0].usage.categories[BandwidthCategory::Other as usize];
+        assert_eq!(frame.len() as u64, other.bytes_sent);
+        assert_eq!(frame.len() as u64, other.bytes_received);
+        assert_eq!(2 * frame.len() as u64, report.total.bytes_total());
+    }
+
+    #[test]
+    fn oversized_messages_are_rejected_before_decoding() {
+        let mut frame = encode(&PeerMessage::PeerListRequest);
+        frame.extend_from_slice(&vec![0u8; MAX_SMALL_MESSAGE_SIZE]);
+        let error = decode(&frame).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+        assert!(error.to_string().contains("max is"));
+    }
+
+    #[test]
+    fn limit_is_known_from_variant_index() {
+        let frame = encode(&PeerMessage::PeerListRequest);
+        assert_eq!(None, max_message_size_from_prefix(&[]));
+        assert_eq!(
+            Some(MAX_SMALL_MESSAGE_SIZE),
+            max_message_size_from_prefix(&frame)
+        );
+        assert_eq!(
+            Some(MAX_BLOCK_MESSAGE_SIZE),
+            max_message_size_from_prefix(&[BLOCK_VARIANT_INDEX as u8])
+        );
+
+        // A variant index encoded in more than one byte.
+        assert_eq!(None, max_message_size_from_prefix(&[251, 0]));
+        assert_eq!(
+            Some(MAX_SMALL_MESSAGE_SIZE),
+            max_message_size_from_prefix(&[251, 0, 1])
+        );
+    }
+
+    #[test]
+    fn claimed_lengths_beyond_frame_are_rejected() {
+        // A peer list response claiming to contain 2^40 peers.
+        let mut frame = BytesMut::new();
+        frame.extend_from_slice(&[18, 253]);
+        frame.extend_from_slice(&(1u64 << 40).to_le_bytes());
+        assert!(decode(&frame).is_err());
+    }
+}
@@ -0,0 +1,195 @@
+//! Plausible deniability mode, enabled with `--plausible-deniability`.
+//!
+//! In this mode the node tries to reveal nothing to peers that would let them
+//! attribute a transaction, or the wallet behind it, to this node:
+//!
+//!  - Handshakes carry no identifier that originates locally and persists
+//!    across connections. The instance ID is fresh for every connection (see
+//!    [`connection_instance_id`]), the timestamp is coarsened, and nodes that
+//!    do not mine announce the genesis header instead of their tip.
+//!  - Every peer is assigned a [`RelayRole`] when it connects. Transaction
+//!    notifications are only pushed to transaction-relay peers and block
+//!    notifications only to block-relay peers, so peers that watch this node
+//!    relay blocks never see it announce a transaction. Relayed transactions
+//!    take the same route as own ones, which makes the two indistinguishable.
+//!    At least one peer relays blocks, so a node needs two peers to broadcast
+//!    transactions.
+//!
+//! The isolation is enforced in the peer loop, which passes every message
+//! from the main task through [`RelayRole::relays`] before it reaches the
+//! peer, and in [`GlobalState::get_own_handshakedata`], which must be called
+//! anew for every connection. Responses to requests from peers are not
+//! restricted.
+//!
+//! [`GlobalState::get_own_handshakedata`]: crate::state::GlobalState::get_own_handshakedata
+
+use std::time::Duration;
+use std::time::SystemTime;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Tip5;
+use tasm_lib::triton_vm::prelude::BFieldElement;
+
+use super::InstanceId;
+use crate::application::loops::channel::MainToPeerTask;
+
+/// Handshake timestamps are rounded down to a multiple of this duration, so
+/// that they do not reveal the skew of the local clock. Must stay well below
+/// the time difference that peers tolerate.
+pub(crate) const HANDSHAKE_TIMESTAMP_GRANULARITY: Duration = Duration::from_secs(30);
+
+/// Which messages from the main task are pushed to a peer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RelayRole {
+    /// Relay everything. The role of every peer outside plausible deniability
+    /// mode.
+    #[default]
+    All,
+
+    /// Relay transactions but not blocks.
+    Transactions,
+
+    /// Relay blocks but not transactions.
+    Blocks,
+}
+
+impl RelayRole {
+    /// The role of a newly connected peer in plausible deniability mode, given
+    /// the roles of the connected peers.
+    ///
+    /// Balances the number of peers in each role. Favors block relay, so that
+    /// a miner with a single peer still relays the blocks it finds. If all
+    /// block-relay peers disconnect, a transaction-relay peer takes over
+    /// block relay, see the peer loop.
+    pub(crate) fn assign(connected_peers: impl IntoIterator<Item = RelayRole>) -> Self {
+        let (num_transaction_relays, num_block_relays) =
+            connected_peers
+                .into_iter()
+                .fold(
+                    (0usize, 0usize),
+                    |(transactions, blocks), role| match role {
+                        RelayRole::All => (transactions, blocks),
+                        RelayRole::Transactions => (transactions + 1, blocks),
+                        RelayRole::Blocks => (transactions, blocks + 1),
+                    },
+                );
+
+        if num_block_relays <= num_transaction_relays {
+            RelayRole::Blocks
+        } else {
+            RelayRole::Transactions
+        }
+    }
+
+    /// Returns true iff the message may be pushed to a peer with this role.
+    pub(crate) fn relays(self, message: &MainToPeerTask) -> bool {
+        match (self, message) {
+            (RelayRole::All, _) => true,
+            (
+                RelayRole::Transactions,
+                MainToPeerTask::Block(_) | MainToPeerTask::BlockProposalNotification(_),
+            ) => false,
+            (RelayRole::Blocks, MainToPeerTask::TransactionNotification(_)) => false,
+            _ => true,
+        }
+    }
+}
+
+/// An instance ID for a single connection. Peers cannot link it to the IDs
+/// of other connections, but [`is_own_instance_id`] recognizes it given the
+/// node's actual instance ID, which is never revealed in this mode.
+pub(crate) fn connection_instance_id(own_instance_id: InstanceId) -> InstanceId {
+    let nonce: u64 = rand::random();
+    (InstanceId::from(nonce) << 64) | InstanceId::from(instance_id_tag(own_instance_id, nonce))
+}
+
+/// Returns true iff `instance_id` is the node's own instance ID, or one
+/// generated from it by [`connection_instance_id`].
+pub(crate) fn is_own_instance_id(own_instance_id: InstanceId, instance_id: InstanceId) -> bool {
+    let nonce = (instance_id >> 64) as u64;
+    instance_id == own_instance_id || instance_id as u64 == instance_id_tag(own_instance_id, nonce)
+}
+
+/// Authenticates `nonce` under the secret instance ID.
+fn instance_id_tag(own_instance_id: InstanceId, nonce: u64) -> u64 {
+    let preimage = [
+        own_instance_id as u64,
+        (own_instance_id >> 64) as u64,
+        nonce,
+    ]
+    .map(BFieldElement::new);
+    Tip5::hash_varlen(&preimage).values()[0].value()
+}
+
+/// The given time, rounded down to [`HANDSHAKE_TIMESTAMP_GRANULARITY`].
+pub(crate) fn coarse_timestamp(time: SystemTime) -> SystemTime {
+    let granularity = HANDSHAKE_TIMESTAMP_GRANULARITY.as_secs();
+    let since_epoch = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    SystemTime::UNIX_EPOCH + Duration::from_secs(since_epoch - since_epoch % granularity)
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+    use crate::application::config::network::Network;
+    use crate::application::loops::channel::BlockProposalNotification;
+    use crate::protocol::consensus::block::Block;
+
+    #[test]
+    fn connection_instance_ids_are_fresh_and_recognized() {
+        let own_instance_id: InstanceId = rand::random();
+        let first = connection_instance_id(own_instance_id);
+        let second = connection_instance_id(own_instance_id);
+        assert_ne!(first, second);
+        assert_ne!(own_instance_id, first);
+
+        assert!(is_own_instance_id(own_instance_id, own_instance_id));
+        assert!(is_own_instance_id(own_instance_id, first));
+        assert!(is_own_instance_id(own_instance_id, second));
+        assert!(!is_own_instance_id(own_instance_id, rand::random()));
+        assert!(!is_own_instance_id(rand::random(), first));
+    }
+
+    #[test]
+    fn roles_are_balanced_and_isolated() {
+        let mut roles = vec![];
+        for _ in 0..5 {
+            roles.push(RelayRole::assign(roles.clone()));
+        }
+        assert_eq!(
+            vec![
+                RelayRole::Blocks,
+                RelayRole::Transactions,
+                RelayRole::Blocks,
+                RelayRole::Transactions,
+                RelayRole::Blocks,
+            ],
+            roles
+        );
+
+        let block_message = MainToPeerTask::BlockProposalNotification(
+            BlockProposalNotification::from(&Block::genesis(Network::Main)),
+        );
+        assert!(!RelayRole::Transactions.relays(&block_message));
+        assert!(RelayRole::Blocks.relays(&block_message));
+        assert!(RelayRole::All.relays(&block_message));
+
+        let other_message = MainToPeerTask::MakePeerDiscoveryRequest;
+        assert!(RelayRole::Transactions.relays(&other_message));
+        assert!(RelayRole::Blocks.relays(&other_message));
+    }
+
+    #[test]
+    fn coarse_timestamp_is_within_granularity() {
+        let now = SystemTime::now();
+        let coarse = coarse_timestamp(now);
+        assert!(coarse <= now);
+        assert!(now.duration_since(coarse).unwrap() < HANDSHAKE_TIMESTAMP_GRANULARITY);
+        assert_eq!(coarse, coarse_timestamp(coarse));
+    }
+}
@@ -0,0 +1,223 @@
+//! Introductions of peers that cannot accept incoming connections.
+//!
+//! Nodes behind NAT can only make outgoing connections, so two such nodes
+//! never connect to each other directly. A relay that both are connected to
+//! can introduce them: on request of one of them, the relay tells each of the
+//! two the address it observes for the other. Both then connect to each other
+//! at the same time (TCP simultaneous open), which passes NATs that map
+//! outgoing connections from the same local port to the same external port.
+//! For the address observed by the relay to be the one the other peer must
+//! connect to, nodes that ask for introductions make all outgoing peer
+//! connections from the same local port.
+//!
+//! The requester and the introduced peer, as well as the relay, must opt in,
+//! see [`RENDEZVOUS_CAPABILITY`] and [`RENDEZVOUS_RELAY_CAPABILITY`].
+//!
+//! [`RENDEZVOUS_CAPABILITY`]: super::handshake_data::RENDEZVOUS_CAPABILITY
+//! [`RENDEZVOUS_RELAY_CAPABILITY`]: super::handshake_data::RENDEZVOUS_RELAY_CAPABILITY
+
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use rand::seq::IteratorRandom;
+use rand::Rng;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::net::TcpSocket;
+use tokio::net::TcpStream;
+
+use super::peer_info::PeerInfo;
+use super::InstanceId;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+
+/// Maximum number of connected peers a [`RendezvousRequest`] may list.
+pub(crate) const MAX_NUM_CONNECTED_PEERS_IN_RENDEZVOUS_REQUEST: usize = 100;
+
+/// Minimum time between two introductions a relay makes on request of the
+/// same peer, and between two introductions a node accepts from the same
+/// relay.
+pub(crate) const MIN_RENDEZVOUS_INTERVAL: Timestamp = Timestamp::minutes(1);
+
+/// Number of times an introduced peer is connected to before giving up. Both
+/// peers must have sent their connection attempt before either of them
+/// arrives, so the first attempts typically fail.
+pub(crate) const RENDEZVOUS_NUM_ATTEMPTS: usize = 5;
+
+/// Time between two attempts to connect to an introduced peer.
+pub(crate) const RENDEZVOUS_ATTEMPT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Asks a relay to introduce the sender to another of the relay's peers.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub(crate) struct RendezvousRequest {
+    /// Instance IDs of the sender's peers, which need no introduction.
+    pub(crate) connected: Vec<InstanceId>,
+}
+
+/// Introduces a peer to connect to by simultaneous open.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub(crate) struct RendezvousIntroduction {
+    /// The introduced peer's address, as observed by the relay.
+    pub(crate) peer_address: SocketAddr,
+
+    pub(crate) instance_id: InstanceId,
+
+    /// Whether the recipient starts the handshake once connected. Exactly one
+    /// of the two introduced peers does.
+    pub(crate) initiator: bool,
+}
+
+impl RendezvousIntroduction {
+    /// Choose a peer to introduce the requester to: one that accepts
+    /// introductions and is not connected to the requester already.
+    ///
+    /// Returns the introduction for the requester, who starts the handshake.
+    pub(crate) fn for_requester<'a, R: Rng + ?Sized>(
+        peers: impl IntoIterator<Item = &'a PeerInfo>,
+        requester: SocketAddr,
+        request: &RendezvousRequest,
+        rng: &mut R,
+    ) -> Option<Self> {
+        peers
+            .into_iter()
+            .filter(|peer| peer.accepts_introductions())
+            .filter(|peer| peer.connected_address() != requester)
+            .filter(|peer| !request.connected.contains(&peer.instance_id()))
+            .choose(rng)
+            .map(|peer| Self {
+                peer_address: peer.connected_address(),
+                instance_id: peer.instance_id(),
+                initiator: true,
+            })
+    }
+
+    /// The introduction of the requester to the peer it was introduced to.
+    pub(crate) fn of_requester(requester: SocketAddr, instance_id: InstanceId) -> Self {
+        Self {
+            peer_address: requester,
+            instance_id,
+            initiator: false,
+        }
+    }
+}
+
+/// Open a TCP connection to `peer_address` from the given local port.
+///
+/// The port may be shared with other connections, including ones to the same
+/// peer that are being opened concurrently, which is what makes simultaneous
+/// open work.
+pub(crate) async fn connect_from_port(
+    peer_address: SocketAddr,
+    local_port: u16,
+) -> std::io::Result<TcpStream> {
+    let (socket, local_address) = match peer_address {
+        SocketAddr::V4(_) => (
+            TcpSocket::new_v4()?,
+            SocketAddr::from((Ipv4Addr::UNSPECIFIED, local_port)),
+        ),
+        SocketAddr::V6(_) => (
+            TcpSocket::new_v6()?,
+            SocketAddr::from((Ipv6Addr::UNSPECIFIED, local_port)),
+        ),
+    };
+    socket.set_reuseaddr(true)?;
+    #[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+    socket.set_reuseport(true)?;
+    socket.bind(local_address)?;
+
+    socket.connect(peer_address).await
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::time::SystemTime;
+
+    use macro_rules_attr::apply;
+
+    use super::*;
+    use crate::application::config::cli_args;
+    use crate::application::config::network::Network;
+    use crate::protocol::peer::handshake_data::RENDEZVOUS_CAPABILITY;
+    use crate::protocol::peer::peer_info::PeerConnectionInfo;
+    use crate::tests::shared::globalstate::get_dummy_handshake_data_for_genesis;
+    use crate::tests::shared::globalstate::get_dummy_socket_address;
+    use crate::tests::shared_tokio_runtime;
+
+    fn peer(count: u8, accepts_introductions: bool) -> PeerInfo {
+        let address = get_dummy_socket_address(count);
+        let mut handshake = get_dummy_handshake_data_for_genesis(Network::Main);
+        if accepts_introductions {
+            handshake.add_capability(RENDEZVOUS_CAPABILITY);
+        }
+        PeerInfo::new(
+            PeerConnectionInfo::new(None, address, true),
+            &handshake,
+            SystemTime::now(),
+            cli_args::Args::default().peer_tolerance,
+        )
+    }
+
+    #[test]
+    fn only_peers_that_opted_in_and_are_not_connected_are_introduced() {
+        let requester = peer(0, true);
+        let opted_out = peer(1, false);
+        let connected = peer(2, true);
+        let introducee = peer(3, true);
+        let peers = [&requester, &opted_out, &connected, &introducee];
+        let request = RendezvousRequest {
+            connected: vec![connected.instance_id()],
+        };
+
+        let mut rng = rand::rng();
+        for _ in 0..10 {
+            let introduction = RendezvousIntroduction::for_requester(
+                peers,
+                requester.connected_address(),
+                &request,
+                &mut rng,
+            )
+            .unwrap();
+            assert_eq!(introducee.connected_address(), introduction.peer_address);
+            assert_eq!(introducee.instance_id(), introduction.instance_id);
+            assert!(introduction.initiator);
+        }
+
+        let no_candidates = [&requester, &opted_out, &connected];
+        assert!(RendezvousIntroduction::for_requester(
+            no_candidates,
+            requester.connected_address(),
+            &request,
+            &mut rng,
+        )
+        .is_none());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn connections_can_share_local_port() {
+        let listener = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .await
+            .unwrap();
+        let peer_address = listener.local_addr().unwrap();
+        let local_port = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        let first = connect_from_port(peer_address, local_port).await.unwrap();
+        let (_, first_seen_from) = listener.accept().await.unwrap();
+        assert_eq!(local_port, first.local_addr().unwrap().port());
+        assert_eq!(local_port, first_seen_from.port());
+
+        let other_listener = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .await
+            .unwrap();
+        let second = connect_from_port(other_listener.local_addr().unwrap(), local_port)
+            .await
+            .unwrap();
+        assert_eq!(local_port, second.local_addr().unwrap().port());
+    }
+}
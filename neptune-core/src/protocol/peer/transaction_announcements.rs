@@ -0,0 +1,89 @@
+//! Tracking of which peers announced which transactions.
+//!
+//! The number of peers that announced a transaction indicates how far it has
+//! propagated through the network, and thus how likely it is to reach the
+//! composers.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+
+use crate::state::transaction::transaction_kernel_id::TransactionKernelId;
+
+/// Number of transactions for which announcing peers are remembered.
+const MAX_NUM_REMEMBERED_TRANSACTIONS: usize = 1000;
+
+/// The peers that announced the most recently announced transactions.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TransactionAnnouncements {
+    announcers: HashMap<TransactionKernelId, HashSet<SocketAddr>>,
+
+    /// In order of first announcement, for forgetting the oldest.
+    order: VecDeque<TransactionKernelId>,
+}
+
+impl TransactionAnnouncements {
+    /// Record that the peer at `peer_address` announced the transaction.
+    pub(crate) fn record(&mut self, txid: TransactionKernelId, peer_address: SocketAddr) {
+        if !self.announcers.contains_key(&txid) {
+            if self.order.len() == MAX_NUM_REMEMBERED_TRANSACTIONS {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.announcers.remove(&oldest);
+                }
+            }
+            self.order.push_back(txid);
+        }
+
+        self.announcers
+            .entry(txid)
+            .or_default()
+            .insert(peer_address);
+    }
+
+    /// The number of distinct peers that announced the transaction.
+    pub(crate) fn num_announcers(&self, txid: TransactionKernelId) -> usize {
+        self.announcers.get(&txid).map_or(0, HashSet::len)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use rand::random;
+
+    use super::*;
+
+    fn address(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    #[test]
+    fn distinct_announcers_are_counted() {
+        let mut announcements = TransactionAnnouncements::default();
+        let txid = random();
+        assert_eq!(0, announcements.num_announcers(txid));
+
+        announcements.record(txid, address(1));
+        announcements.record(txid, address(1));
+        announcements.record(txid, address(2));
+        assert_eq!(2, announcements.num_announcers(txid));
+    }
+
+    #[test]
+    fn old_announcements_are_forgotten() {
+        let mut announcements = TransactionAnnouncements::default();
+        let first = random();
+        announcements.record(first, address(1));
+        for _ in 0..MAX_NUM_REMEMBERED_TRANSACTIONS {
+            announcements.record(random(), address(1));
+        }
+
+        assert_eq!(0, announcements.num_announcers(first));
+        assert_eq!(MAX_NUM_REMEMBERED_TRANSACTIONS, announcements.order.len());
+        assert_eq!(
+            MAX_NUM_REMEMBERED_TRANSACTIONS,
+            announcements.announcers.len()
+        );
+    }
+}
@@ -0,0 +1,83 @@
+//! Replication of encrypted wallet backups between trusted peers.
+//!
+//! A node periodically asks each of its trusted peers which version of its
+//! backup they hold. A peer that is behind is sent the missing increments, a
+//! peer that is ahead, e.g. because this node lost its data directory, is
+//! asked for them. See [`crate::state::wallet::wallet_backup`] for the format
+//! of backups.
+//!
+//! Both peers must opt in, see [`WALLET_BACKUP_CAPABILITY`], and must have
+//! configured each other as trusted. Messages from any other peer are
+//! [refused](WalletBackupMessage::Refused).
+//!
+//! [`WALLET_BACKUP_CAPABILITY`]: super::handshake_data::WALLET_BACKUP_CAPABILITY
+
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Digest;
+
+use crate::state::wallet::wallet_backup::WalletBackupHead;
+use crate::state::wallet::wallet_backup::WalletBackupIncrement;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub(crate) enum WalletBackupMessage {
+    /// Ask which version of a backup the peer holds.
+    StatusRequest { backup_id: Digest },
+
+    /// The version of a backup the sender holds. Answers a `StatusRequest`,
+    /// and acknowledges a `Store` or `Replace`.
+    Status {
+        backup_id: Digest,
+        head: WalletBackupHead,
+    },
+
+    /// Increments extending the backup the peer holds, in order. Holds at
+    /// most [`MAX_NUM_INCREMENTS_PER_MESSAGE`] increments.
+    ///
+    /// [`MAX_NUM_INCREMENTS_PER_MESSAGE`]: crate::state::wallet::wallet_backup::MAX_NUM_INCREMENTS_PER_MESSAGE
+    Store {
+        backup_id: Digest,
+        increments: Vec<WalletBackupIncrement>,
+    },
+
+    /// Ask for the increments of a backup following the given version.
+    Fetch {
+        backup_id: Digest,
+        after_version: u64,
+    },
+
+    /// Answers a `Fetch`.
+    Increments {
+        backup_id: Digest,
+        increments: Vec<WalletBackupIncrement>,
+    },
+
+    /// The sender did not act on a message.
+    Refused { backup_id: Digest, reason: String },
+
+    /// Increments of a backup that diverges from the one the peer holds, and
+    /// that replaces it, starting with the first increment. Sent once the
+    /// recovery data of the peer's backup was merged into the sender's. Holds
+    /// at most [`MAX_NUM_INCREMENTS_PER_MESSAGE`] increments, the peer is
+    /// sent the remaining ones with `Store`.
+    ///
+    /// [`MAX_NUM_INCREMENTS_PER_MESSAGE`]: crate::state::wallet::wallet_backup::MAX_NUM_INCREMENTS_PER_MESSAGE
+    Replace {
+        backup_id: Digest,
+        increments: Vec<WalletBackupIncrement>,
+    },
+}
+
+impl WalletBackupMessage {
+    pub(crate) fn backup_id(&self) -> Digest {
+        match self {
+            WalletBackupMessage::StatusRequest { backup_id }
+            | WalletBackupMessage::Status { backup_id, .. }
+            | WalletBackupMessage::Store { backup_id, .. }
+            | WalletBackupMessage::Fetch { backup_id, .. }
+            | WalletBackupMessage::Increments { backup_id, .. }
+            | WalletBackupMessage::Refused { backup_id, .. }
+            | WalletBackupMessage::Replace { backup_id, .. } => *backup_id,
+        }
+    }
+}
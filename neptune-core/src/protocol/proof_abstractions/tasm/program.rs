@@ -152,8 +152,9 @@ pub struct TritonVmProofJobOptions {
     ///
     /// used when selecting the next job to run.
     ///
-    /// note that if a lower priority job is already running then a higher
-    /// priority job still must wait for it to complete.
+    /// note that if lower priority jobs are already occupying all workers of
+    /// the queue then a higher priority job still must wait for one of them to
+    /// complete.
     pub job_priority: TritonVmJobPriority,
 
     /// job-specific settings
@@ -52,6 +52,14 @@ impl From<Timestamp> for Duration {
     }
 }
 
+impl From<SystemTime> for Timestamp {
+    /// Times before the UNIX epoch are mapped to the epoch.
+    fn from(time: SystemTime) -> Self {
+        let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        Self::millis(since_epoch.as_millis() as u64)
+    }
+}
+
 impl PartialOrd for Timestamp {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
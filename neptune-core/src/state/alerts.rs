@@ -0,0 +1,531 @@
+//! Alert rules that turn the node into a basic monitor of itself and the
+//! network.
+//!
+//! The main loop periodically evaluates the configured [`AlertRules`] against
+//! [`AlertMetrics`] sampled from the global state. An alert fires when its
+//! rule's condition starts to hold, and resolves when it stops holding. Fired
+//! alerts are logged, passed to plugin hooks, and recorded in the event log,
+//! unless the operator silenced them. An alert that is still active when its
+//! silence lapses is published then.
+//!
+//! Some alerts are raised by the main loop itself rather than by a rule, see
+//! [`Alerts::raise`].
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::info;
+use tracing::warn;
+
+use crate::application::config::cli_args;
+use crate::application::plugin_hooks::HookEvent;
+use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::protocol::consensus::block::difficulty_control::Difficulty;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+
+/// Number of most recent tips whose difficulty is compared against that of
+/// the current tip, for the [`AlertKind::DifficultyDrop`] rule.
+pub(crate) const DIFFICULTY_DROP_WINDOW: usize = 100;
+
+/// The kinds of alerts, one per rule.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    strum::Display,
+    strum::EnumString,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum AlertKind {
+    /// No new block was received for longer than the configured time.
+    NoNewBlock,
+
+    /// The difficulty of the tip dropped by more than the configured
+    /// percentage below the highest difficulty of recent tips.
+    DifficultyDrop,
+
+    /// The node is connected to fewer peers than configured.
+    LowPeerCount,
+
+    /// The mempool holds more transactions than configured.
+    MempoolFull,
+
+    /// The tip did not advance for several target block intervals, while
+    /// peers claim to hold blocks with more proof-of-work. Raised by the main
+    /// loop regardless of the configured rules.
+    TipStall,
+
+    /// A newer release is available, according to the release manifest. Raised
+    /// by the main loop if release checks are enabled.
+    UpdateAvailable,
+}
+
+/// An alert whose condition currently holds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Alert {
+    pub kind: AlertKind,
+
+    /// Human-readable description of the condition, as of when it started to
+    /// hold.
+    pub message: String,
+
+    /// The time at which the alert fired.
+    pub since: Timestamp,
+
+    /// The time until which the alert is silenced, if it is.
+    pub silenced_until: Option<Timestamp>,
+}
+
+impl Display for Alert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.kind, self.message)
+    }
+}
+
+/// The thresholds of the alert rules. A rule is disabled if its threshold is
+/// unset.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub(crate) struct AlertRules {
+    pub(crate) max_time_without_block: Option<Timestamp>,
+    pub(crate) max_difficulty_drop_percent: Option<u8>,
+    pub(crate) min_num_peers: Option<usize>,
+    pub(crate) max_mempool_num_txs: Option<usize>,
+}
+
+impl From<&cli_args::Args> for AlertRules {
+    fn from(cli: &cli_args::Args) -> Self {
+        Self {
+            max_time_without_block: cli
+                .alert_no_block_minutes
+                .map(|minutes| Timestamp::seconds(minutes.saturating_mul(60))),
+            max_difficulty_drop_percent: cli.alert_difficulty_drop_percent,
+            min_num_peers: cli.alert_min_peers,
+            max_mempool_num_txs: cli.alert_max_mempool_txs,
+        }
+    }
+}
+
+impl AlertRules {
+    pub(crate) fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// The quantities that alert rules are evaluated against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct AlertMetrics {
+    pub(crate) tip_height: BlockHeight,
+    pub(crate) tip_timestamp: Timestamp,
+    pub(crate) tip_difficulty: Difficulty,
+    pub(crate) num_peers: usize,
+    pub(crate) mempool_num_txs: usize,
+}
+
+/// The active alerts, the operator's silences, and the recent difficulties
+/// that the [`AlertKind::DifficultyDrop`] rule compares against.
+#[derive(Debug, Clone, Default)]
+pub struct Alerts {
+    active: HashMap<AlertKind, Alert>,
+    silences: HashMap<AlertKind, Timestamp>,
+    recent_difficulties: BTreeMap<BlockHeight, Difficulty>,
+}
+
+impl Alerts {
+    /// The alerts whose condition currently holds, including silenced ones,
+    /// ordered by kind.
+    pub fn active(&self, now: Timestamp) -> Vec<Alert> {
+        let mut alerts = self
+            .active
+            .values()
+            .map(|alert| Alert {
+                silenced_until: self.silenced_until(alert.kind, now),
+                ..alert.clone()
+            })
+            .collect::<Vec<_>>();
+        alerts.sort_by_key(|alert| alert.kind);
+        alerts
+    }
+
+    /// Do not fire alerts of the given kind before `until`. A silence can be
+    /// set before the alert fires, and is lifted by silencing until a time
+    /// that has passed.
+    pub(crate) fn silence(&mut self, kind: AlertKind, until: Timestamp) {
+        self.silences.insert(kind, until);
+    }
+
+    fn silenced_until(&self, kind: AlertKind, now: Timestamp) -> Option<Timestamp> {
+        self.silences
+            .get(&kind)
+            .copied()
+            .filter(|until| *until > now)
+    }
+
+    /// Evaluate the rules against the metrics. Returns the events of the
+    /// alerts that fired and are not silenced, and of the alerts whose silence
+    /// lapsed, see [`Self::lift_lapsed_silences`].
+    pub(crate) fn evaluate(
+        &mut self,
+        rules: &AlertRules,
+        metrics: &AlertMetrics,
+        now: Timestamp,
+    ) -> Vec<HookEvent> {
+        self.record_difficulty(metrics.tip_height, metrics.tip_difficulty);
+
+        let conditions = [
+            (
+                AlertKind::NoNewBlock,
+                Self::no_new_block(rules, metrics, now),
+            ),
+            (
+                AlertKind::DifficultyDrop,
+                self.difficulty_drop(rules, metrics),
+            ),
+            (
+                AlertKind::LowPeerCount,
+                Self::low_peer_count(rules, metrics),
+            ),
+            (AlertKind::MempoolFull, Self::mempool_full(rules, metrics)),
+        ];
+
+        let mut events = vec![];
+        for (kind, condition) in conditions {
+            let Some(message) = condition else {
+                if self.active.remove(&kind).is_some() {
+                    info!("Alert resolved: {kind}");
+                }
+                continue;
+            };
+
+            events.extend(self.raise(kind, message, now));
+        }
+        events.extend(self.lift_lapsed_silences(now));
+
+        events
+    }
+
+    /// Forget the silences that lapsed by `now`. Returns the events of the
+    /// alerts that were silenced and are still active, since the operator
+    /// has not been notified of their current state.
+    pub(crate) fn lift_lapsed_silences(&mut self, now: Timestamp) -> Vec<HookEvent> {
+        let mut lapsed = self
+            .silences
+            .iter()
+            .filter(|(_, until)| **until <= now)
+            .map(|(kind, until)| (*kind, *until))
+            .collect::<Vec<_>>();
+        lapsed.sort();
+
+        let mut events = vec![];
+        for (kind, until) in lapsed {
+            self.silences.remove(&kind);
+
+            // alerts that fired after the silence lapsed were published then.
+            let Some(alert) = self.active.get(&kind).filter(|alert| alert.since < until) else {
+                continue;
+            };
+            warn!("Silence lapsed on active alert: {alert}");
+            events.push(HookEvent::Alert {
+                kind,
+                message: alert.message.clone(),
+                timestamp: now,
+            });
+        }
+
+        events
+    }
+
+    /// Fire an alert, unless it is active already. Returns its event, unless
+    /// it is silenced.
+    ///
+    /// Alerts raised this way, rather than by a rule, stay active until they
+    /// are [resolved](Self::resolve).
+    pub(crate) fn raise(
+        &mut self,
+        kind: AlertKind,
+        message: String,
+        now: Timestamp,
+    ) -> Option<HookEvent> {
+        if self.active.contains_key(&kind) {
+            return None;
+        }
+
+        let alert = Alert {
+            kind,
+            message,
+            since: now,
+            silenced_until: None,
+        };
+        let event = if self.silenced_until(kind, now).is_some() {
+            info!("Silenced alert fired: {alert}");
+            None
+        } else {
+            warn!("Alert fired: {alert}");
+            Some(HookEvent::Alert {
+                kind,
+                message: alert.message.clone(),
+                timestamp: now,
+            })
+        };
+        self.active.insert(kind, alert);
+
+        event
+    }
+
+    /// Resolve an alert raised by [`Self::raise`].
+    pub(crate) fn resolve(&mut self, kind: AlertKind) {
+        if self.active.remove(&kind).is_some() {
+            info!("Alert resolved: {kind}");
+        }
+    }
+
+    /// Remember the difficulty of the tip. Difficulties of heights above the
+    /// tip are forgotten, as they belong to abandoned blocks.
+    fn record_difficulty(&mut self, height: BlockHeight, difficulty: Difficulty) {
+        self.recent_difficulties
+            .retain(|recorded, _| *recorded < height);
+        self.recent_difficulties.insert(height, difficulty);
+        while self.recent_difficulties.len() > DIFFICULTY_DROP_WINDOW {
+            self.recent_difficulties.pop_first();
+        }
+    }
+
+    fn no_new_block(rules: &AlertRules, metrics: &AlertMetrics, now: Timestamp) -> Option<String> {
+        let max_time = rules.max_time_without_block?;
+        (metrics.tip_timestamp + max_time < now).then(|| {
+            format!(
+                "No new block for {}. Tip is block {} from {}.",
+                (now - metrics.tip_timestamp).format_human_duration(),
+                metrics.tip_height,
+                metrics.tip_timestamp.standard_format(),
+            )
+        })
+    }
+
+    fn difficulty_drop(&self, rules: &AlertRules, metrics: &AlertMetrics) -> Option<String> {
+        let max_drop_percent = rules.max_difficulty_drop_percent?;
+        let (&peak_height, &peak_difficulty) = self
+            .recent_difficulties
+            .iter()
+            .max_by_key(|(_, difficulty)| **difficulty)?;
+
+        let as_f64 = |difficulty: Difficulty| BigUint::from(difficulty).to_f64();
+        let peak = as_f64(peak_difficulty)?;
+        let tip = as_f64(metrics.tip_difficulty)?;
+        let drop_percent = (peak - tip) / peak * 100.0;
+
+        (drop_percent > f64::from(max_drop_percent)).then(|| {
+            format!(
+                "Difficulty of tip {} dropped by {drop_percent:.1}% since block {peak_height}.",
+                metrics.tip_height,
+            )
+        })
+    }
+
+    fn low_peer_count(rules: &AlertRules, metrics: &AlertMetrics) -> Option<String> {
+        let min_num_peers = rules.min_num_peers?;
+        (metrics.num_peers < min_num_peers).then(|| {
+            format!(
+                "Connected to {} peers, fewer than {min_num_peers}.",
+                metrics.num_peers
+            )
+        })
+    }
+
+    fn mempool_full(rules: &AlertRules, metrics: &AlertMetrics) -> Option<String> {
+        let max_num_txs = rules.max_mempool_num_txs?;
+        (metrics.mempool_num_txs > max_num_txs).then(|| {
+            format!(
+                "Mempool holds {} transactions, more than {max_num_txs}.",
+                metrics.mempool_num_txs
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    fn metrics(now: Timestamp) -> AlertMetrics {
+        AlertMetrics {
+            tip_height: BlockHeight::from(10u64),
+            tip_timestamp: now,
+            tip_difficulty: Difficulty::from(1_000_000u32),
+            num_peers: 8,
+            mempool_num_txs: 0,
+        }
+    }
+
+    fn fired_kinds(events: &[HookEvent]) -> Vec<AlertKind> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                HookEvent::Alert { kind, .. } => Some(*kind),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn disabled_rules_never_fire() {
+        let now = Timestamp::now();
+        let mut alerts = Alerts::default();
+        let mut metrics = metrics(now - Timestamp::days(1));
+        metrics.num_peers = 0;
+        metrics.mempool_num_txs = 1_000_000;
+
+        let rules = AlertRules::default();
+        assert!(rules.is_empty());
+        assert!(alerts.evaluate(&rules, &metrics, now).is_empty());
+        assert!(alerts.active(now).is_empty());
+    }
+
+    #[test]
+    fn alerts_fire_once_and_resolve() {
+        let now = Timestamp::now();
+        let rules = AlertRules {
+            max_time_without_block: Some(Timestamp::minutes(30)),
+            min_num_peers: Some(3),
+            max_mempool_num_txs: Some(100),
+            ..Default::default()
+        };
+        let mut alerts = Alerts::default();
+        let mut metrics = metrics(now);
+        assert!(alerts.evaluate(&rules, &metrics, now).is_empty());
+
+        metrics.num_peers = 2;
+        metrics.mempool_num_txs = 101;
+        let later = now + Timestamp::minutes(31);
+        assert_eq!(
+            vec![
+                AlertKind::NoNewBlock,
+                AlertKind::LowPeerCount,
+                AlertKind::MempoolFull
+            ],
+            fired_kinds(&alerts.evaluate(&rules, &metrics, later))
+        );
+        assert_eq!(3, alerts.active(later).len());
+
+        // Conditions that keep holding do not fire again.
+        let even_later = later + Timestamp::minutes(1);
+        assert!(alerts.evaluate(&rules, &metrics, even_later).is_empty());
+
+        metrics.num_peers = 3;
+        metrics.tip_timestamp = even_later;
+        assert!(alerts.evaluate(&rules, &metrics, even_later).is_empty());
+        assert_eq!(
+            vec![AlertKind::MempoolFull],
+            alerts
+                .active(even_later)
+                .into_iter()
+                .map(|alert| alert.kind)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn difficulty_drop_is_measured_against_recent_peak() {
+        let now = Timestamp::now();
+        let rules = AlertRules {
+            max_difficulty_drop_percent: Some(20),
+            ..Default::default()
+        };
+        let mut alerts = Alerts::default();
+        let mut metrics = metrics(now);
+        assert!(alerts.evaluate(&rules, &metrics, now).is_empty());
+
+        metrics.tip_height = metrics.tip_height.next();
+        metrics.tip_difficulty = Difficulty::from(850_000u32);
+        assert!(alerts.evaluate(&rules, &metrics, now).is_empty());
+
+        metrics.tip_height = metrics.tip_height.next();
+        metrics.tip_difficulty = Difficulty::from(790_000u32);
+        assert_eq!(
+            vec![AlertKind::DifficultyDrop],
+            fired_kinds(&alerts.evaluate(&rules, &metrics, now))
+        );
+
+        // A reorganization back to the peak forgets the abandoned blocks.
+        metrics.tip_height = BlockHeight::from(10u64);
+        metrics.tip_difficulty = Difficulty::from(1_000_000u32);
+        assert!(alerts.evaluate(&rules, &metrics, now).is_empty());
+        assert!(alerts.active(now).is_empty());
+    }
+
+    #[test]
+    fn silenced_alerts_are_listed_but_do_not_fire() {
+        let now = Timestamp::now();
+        let rules = AlertRules {
+            min_num_peers: Some(3),
+            ..Default::default()
+        };
+        let mut alerts = Alerts::default();
+        let mut metrics = metrics(now);
+        metrics.num_peers = 0;
+
+        let until = now + Timestamp::hours(1);
+        alerts.silence(AlertKind::LowPeerCount, until);
+        assert!(alerts.evaluate(&rules, &metrics, now).is_empty());
+
+        let active = alerts.active(now);
+        assert_eq!(1, active.len());
+        assert_eq!(Some(until), active[0].silenced_until);
+
+        // Once the silence expires, the alert is listed as unsilenced and
+        // fires, but only once while its condition keeps holding.
+        let later = until + Timestamp::seconds(1);
+        assert_eq!(
+            vec![AlertKind::LowPeerCount],
+            fired_kinds(&alerts.evaluate(&rules, &metrics, later))
+        );
+        assert_eq!(None, alerts.active(later)[0].silenced_until);
+        assert!(alerts.evaluate(&rules, &metrics, later).is_empty());
+    }
+
+    #[test]
+    fn lapsed_silences_of_resolved_alerts_do_not_fire() {
+        let now = Timestamp::now();
+        let mut alerts = Alerts::default();
+        let until = now + Timestamp::hours(1);
+        alerts.silence(AlertKind::TipStall, until);
+        assert!(alerts
+            .raise(AlertKind::TipStall, "Tip did not advance.".to_string(), now)
+            .is_none());
+        alerts.resolve(AlertKind::TipStall);
+
+        assert!(alerts.lift_lapsed_silences(until).is_empty());
+    }
+
+    #[test]
+    fn raised_alerts_outlive_rule_evaluation_until_resolved() {
+        let now = Timestamp::now();
+        let mut alerts = Alerts::default();
+        let message = "Tip did not advance.".to_string();
+
+        assert!(alerts
+            .raise(AlertKind::TipStall, message.clone(), now)
+            .is_some());
+        assert!(alerts.raise(AlertKind::TipStall, message, now).is_none());
+        assert!(alerts
+            .evaluate(&AlertRules::default(), &metrics(now), now)
+            .is_empty());
+        assert_eq!(1, alerts.active(now).len());
+
+        alerts.resolve(AlertKind::TipStall);
+        assert!(alerts.active(now).is_empty());
+    }
+}
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::ops::DerefMut;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use anyhow::bail;
 use anyhow::Result;
@@ -8,19 +9,25 @@ use memmap2::MmapOptions;
 use num_traits::Zero;
 use tasm_lib::twenty_first::prelude::Mmr;
 use tasm_lib::twenty_first::tip5::digest::Digest;
+use tokio::io::AsyncReadExt;
 use tokio::io::AsyncSeekExt;
 use tokio::io::AsyncWriteExt;
 use tokio::io::SeekFrom;
 use tracing::debug;
 use tracing::warn;
 
+pub mod fork_pruning;
+pub(crate) mod headers_only;
 pub(crate) mod import_blocks_from_files;
+pub(crate) mod shared_block_store;
 
 use super::shared::new_block_file_is_needed;
 use super::StorageVecBase;
 use crate::api::export::Network;
 use crate::application::config::data_directory::DataDirectory;
-use crate::application::database::create_db_if_missing;
+use crate::application::database::open_files::db_options;
+use crate::application::database::open_files::DbStore;
+use crate::application::database::read_cache_budget::TableReadCacheMetrics;
 use crate::application::database::storage::storage_schema::traits::*;
 use crate::application::database::NeptuneLevelDb;
 use crate::application::database::WriteBatchAsync;
@@ -31,6 +38,8 @@ use crate::protocol::consensus::block::block_height::BlockHeight;
 use crate::protocol::consensus::block::mutator_set_update::MutatorSetUpdate;
 use crate::protocol::consensus::block::Block;
 use crate::protocol::consensus::transaction::transaction_kernel::TransactionKernelProxy;
+use crate::state::archival_state::shared_block_store::SharedBlockStore;
+use crate::state::archival_state::shared_block_store::READ_ONLY_READ_ATTEMPTS;
 use crate::state::database::BlockFileLocation;
 use crate::state::database::BlockIndexKey;
 use crate::state::database::BlockIndexValue;
@@ -83,6 +92,10 @@ pub struct ArchivalState {
 
     /// The network that this node is on. Used to simplify method interfaces.
     network: Network,
+
+    /// The block files shared with other nodes on the same host, if any, in
+    /// which case blocks are stored there instead of in the data directory.
+    shared_block_store: Option<Arc<SharedBlockStore>>,
 }
 
 // The only reason we have this `Debug` implementation is that it's required
@@ -95,10 +108,52 @@ impl core::fmt::Debug for ArchivalState {
             .field("genesis_block", &self.genesis_block)
             .field("network", &self.network)
             .field("archival_block_mmr", &self.archival_block_mmr)
+            .field("shared_block_store", &self.shared_block_store)
             .finish()
     }
 }
 
+/// Where the encoding of a stored block lives on disk. Lets the block be read
+/// in parts, without loading all of it into memory.
+#[derive(Debug, Clone)]
+pub(crate) struct StoredBlockBytes {
+    path: PathBuf,
+    offset: u64,
+    length: u64,
+}
+
+impl StoredBlockBytes {
+    /// The length of the block's encoding, in bytes.
+    pub(crate) fn length(&self) -> u64 {
+        self.length
+    }
+
+    /// Read up to `max_len` bytes of the block's encoding, starting at
+    /// position `start` in it.
+    pub(crate) async fn read(&self, start: u64, max_len: usize) -> Result<Vec<u8>> {
+        let remaining = self.length.saturating_sub(start);
+        let len = usize::try_from(remaining).map_or(max_len, |remaining| remaining.min(max_len));
+
+        let mut block_file = tokio::fs::File::open(&self.path).await?;
+        let requested_end = self.offset.saturating_add(self.length);
+        let file_size = block_file.metadata().await?.len();
+        if requested_end > file_size {
+            bail!(
+                "Data corruption: Attempted to read beyond end of file '{}'. (Size: {}, Requested End: {})",
+                self.path.display(), file_size, requested_end
+            );
+        }
+
+        block_file
+            .seek(SeekFrom::Start(self.offset + start))
+            .await?;
+        let mut bytes = vec![0; len];
+        block_file.read_exact(&mut bytes).await?;
+
+        Ok(bytes)
+    }
+}
+
 impl ArchivalState {
     /// Create databases for block persistence
     async fn initialize_block_index_database(
@@ -109,7 +164,7 @@ impl ArchivalState {
 
         let block_index = NeptuneLevelDb::<BlockIndexKey, BlockIndexValue>::new(
             &block_index_db_dir_path,
-            &create_db_if_missing(),
+            &db_options(DbStore::BlockIndex),
         )
         .await?;
 
@@ -122,7 +177,7 @@ impl ArchivalState {
         DataDirectory::create_dir_if_not_exists(&ms_db_dir_path).await?;
 
         let path = ms_db_dir_path.clone();
-        let result = NeptuneLevelDb::new(&path, &create_db_if_missing()).await;
+        let result = NeptuneLevelDb::new(&path, &db_options(DbStore::MutatorSet)).await;
 
         let db = match result {
             Ok(db) => db,
@@ -153,7 +208,7 @@ impl ArchivalState {
         DataDirectory::create_dir_if_not_exists(&abmmr_dir_path).await?;
 
         let path = abmmr_dir_path.clone();
-        let result = NeptuneLevelDb::new(&path, &create_db_if_missing()).await;
+        let result = NeptuneLevelDb::new(&path, &db_options(DbStore::ArchivalBlockMmr)).await;
 
         let db = match result {
             Ok(db) => db,
@@ -262,6 +317,17 @@ impl ArchivalState {
         data_dir: DataDirectory,
         genesis_block: Block,
         network: Network,
+    ) -> Self {
+        Self::new_with_shared_block_store(data_dir, genesis_block, network, None).await
+    }
+
+    /// Like [`Self::new`], but with the block files in a [`SharedBlockStore`]
+    /// instead of in the data directory, if one is given.
+    pub(crate) async fn new_with_shared_block_store(
+        data_dir: DataDirectory,
+        genesis_block: Block,
+        network: Network,
+        shared_block_store: Option<SharedBlockStore>,
     ) -> Self {
         let mut archival_mutator_set = ArchivalState::initialize_mutator_set(&data_dir)
             .await
@@ -299,20 +365,37 @@ impl ArchivalState {
             .expect("Must be able to initialize block index database");
         debug!("Got block index database");
         let genesis_block = Box::new(genesis_block);
-        Self {
+        let mut archival_state = Self {
             data_dir,
             block_index_db,
             genesis_block,
             archival_mutator_set,
             archival_block_mmr,
             network,
-        }
+            shared_block_store: shared_block_store.map(Arc::new),
+        };
+
+        archival_state
+            .finish_interrupted_block_file_compaction()
+            .await
+            .expect("Must be able to finish interrupted block file compaction");
+
+        archival_state
     }
 
     pub(crate) fn genesis_block(&self) -> &Block {
         &self.genesis_block
     }
 
+    /// The effectiveness of the read caches of the mutator set and the
+    /// archival block MMR, for every table whose read cache is enabled.
+    pub(crate) fn read_cache_metrics(&self) -> Vec<TableReadCacheMetrics> {
+        let mut metrics = self.archival_mutator_set.read_cache_metrics();
+        metrics.extend(self.archival_block_mmr.read_cache_metrics());
+
+        metrics
+    }
+
     /// Return the number of files used to store the raw blocks.
     #[cfg(test)]
     pub(crate) async fn num_block_files(&self) -> u32 {
@@ -326,9 +409,27 @@ impl ArchivalState {
     }
 
     /// Return the directory in which the raw blocks are stored.
-    #[cfg(test)]
     pub(crate) fn block_dir_path(&self) -> PathBuf {
-        self.data_dir.block_dir_path()
+        match &self.shared_block_store {
+            Some(shared_block_store) => shared_block_store.dir().to_owned(),
+            None => self.data_dir.block_dir_path(),
+        }
+    }
+
+    /// Return the path of the file with the given index in which raw blocks
+    /// are stored.
+    pub(crate) fn block_file_path(&self, file_index: u32) -> PathBuf {
+        match &self.shared_block_store {
+            Some(shared_block_store) => shared_block_store.block_file_path(file_index),
+            None => self.data_dir.block_file_path(file_index),
+        }
+    }
+
+    /// Return the shared block store, if this node only reads from it.
+    pub(crate) fn read_only_shared_block_store(&self) -> Option<&Arc<SharedBlockStore>> {
+        self.shared_block_store
+            .as_ref()
+            .filter(|shared_block_store| shared_block_store.is_read_only())
     }
 
     /// Write a block disk, without setting it as tip. The returned (key, value)
@@ -336,7 +437,11 @@ impl ArchivalState {
     /// retrievable.
     ///
     /// This function only stores the block to a file. It does not modify any
-    /// database. It does, however, read from the block index database.
+    /// database. It does, however, read from the block index database. If
+    /// this node only reads from a shared block store, nothing is written;
+    /// instead, the block's location is taken from the store. The caller must
+    /// have waited for the node writing to the store to store the block, see
+    /// [`wait_for_shared_block_store`](crate::state::GlobalStateLock::wait_for_shared_block_store).
     ///
     /// The caller should verify that the block is not already stored, otherwise
     /// the block will be stored twice which will lead to inconsistencies.
@@ -349,6 +454,24 @@ impl ArchivalState {
             bail!("invalid block: could not get mutator set update");
         }
 
+        if let Some(shared_block_store) = self.read_only_shared_block_store() {
+            let Some(file_location) = shared_block_store
+                .locate(new_block.hash())
+                .await?
+                .filter(|location| !location.is_pruned())
+            else {
+                bail!(
+                    "block {:x} is not yet stored by the writer of shared block store {}",
+                    new_block.hash(),
+                    shared_block_store.dir().display()
+                );
+            };
+
+            return Ok(self
+                .block_record_index_entries(new_block, file_location)
+                .await);
+        }
+
         // Fetch last file record to find disk location to store block.
         // This record must exist in the DB already, unless this is the first block
         // stored on disk.
@@ -360,7 +483,7 @@ impl ArchivalState {
             .unwrap_or_default();
 
         // Open the file that was last used for storing a block
-        let mut block_file_path = self.data_dir.block_file_path(last_rec.last_file);
+        let mut block_file_path = self.block_file_path(last_rec.last_file);
         let serialized_block: Vec<u8> = bincode::serialize(new_block)?;
         let serialized_block_size: u64 = serialized_block.len() as u64;
 
@@ -371,7 +494,7 @@ impl ArchivalState {
             last_rec = LastFileRecord {
                 last_file: last_rec.last_file + 1,
             };
-            block_file_path = self.data_dir.block_file_path(last_rec.last_file);
+            block_file_path = self.block_file_path(last_rec.last_file);
             block_file = DataDirectory::open_ensure_parent_dir_exists(&block_file_path).await?;
         }
 
@@ -411,13 +534,6 @@ impl ArchivalState {
             block_file.metadata().await.unwrap().len()
         );
 
-        let height_record_key = BlockIndexKey::Height(new_block.header().height);
-        let mut blocks_at_same_height: Vec<Digest> =
-            match self.block_index_db.get(height_record_key).await {
-                Some(rec) => rec.as_height_record(),
-                None => vec![],
-            };
-
         // Write to file with mmap, only map relevant part of file into memory
         // we use spawn_blocking to make the blocking mmap async-friendly.
         tokio::task::spawn_blocking(move || {
@@ -440,8 +556,44 @@ impl ArchivalState {
         })
         .await?;
 
+        let file_location = BlockFileLocation {
+            file_index: last_rec.last_file,
+            offset: file_offset,
+            block_length: serialized_block_size as usize,
+        };
+        if let Some(shared_block_store) = &self.shared_block_store {
+            shared_block_store
+                .announce(&[(new_block.hash(), file_location)])
+                .await?;
+        }
+
         // Update block index database with newly stored block
-        let mut block_index_entries: Vec<(BlockIndexKey, BlockIndexValue)> = vec![];
+        let mut block_index_entries: Vec<(BlockIndexKey, BlockIndexValue)> = vec![
+            (file_record_key, BlockIndexValue::File(file_record_value)),
+            (BlockIndexKey::LastFile, BlockIndexValue::LastFile(last_rec)),
+        ];
+        block_index_entries.extend(
+            self.block_record_index_entries(new_block, file_location)
+                .await,
+        );
+
+        Ok(block_index_entries)
+    }
+
+    /// The (key, value) pairs that index the block record of a block stored
+    /// at the given location, and its height.
+    async fn block_record_index_entries(
+        &self,
+        new_block: &Block,
+        file_location: BlockFileLocation,
+    ) -> Vec<(BlockIndexKey, BlockIndexValue)> {
+        let height_record_key = BlockIndexKey::Height(new_block.header().height);
+        let mut blocks_at_same_height: Vec<Digest> =
+            match self.block_index_db.get(height_record_key).await {
+                Some(rec) => rec.as_height_record(),
+                None => vec![],
+            };
+
         let block_record_key: BlockIndexKey = BlockIndexKey::Block(new_block.hash());
         let num_additions: u64 = new_block
             .mutator_set_update()
@@ -452,11 +604,7 @@ impl ArchivalState {
             .expect("Num addition records cannot exceed u64::MAX");
         let block_record_value: BlockIndexValue = BlockIndexValue::Block(Box::new(BlockRecord {
             block_header: *new_block.header(),
-            file_location: BlockFileLocation {
-                file_index: last_rec.last_file,
-                offset: file_offset,
-                block_length: serialized_block_size as usize,
-            },
+            file_location,
             min_aocl_index: new_block
                 .mutator_set_accumulator_after()
                 .expect("MS update for new block must exist")
@@ -467,22 +615,26 @@ impl ArchivalState {
             block_hash_witness: HeaderToBlockHashWitness::from(new_block),
         }));
 
-        block_index_entries.push((file_record_key, BlockIndexValue::File(file_record_value)));
-        block_index_entries.push((block_record_key, block_record_value));
-
-        block_index_entries.push((BlockIndexKey::LastFile, BlockIndexValue::LastFile(last_rec)));
-        blocks_at_same_height.push(new_block.hash());
-        block_index_entries.push((
-            height_record_key,
-            BlockIndexValue::Height(blocks_at_same_height),
-        ));
+        if !blocks_at_same_height.contains(&new_block.hash()) {
+            blocks_at_same_height.push(new_block.hash());
+        }
 
-        Ok(block_index_entries)
+        vec![
+            (block_record_key, block_record_value),
+            (
+                height_record_key,
+                BlockIndexValue::Height(blocks_at_same_height),
+            ),
+        ]
     }
 
     async fn write_block_internal(&mut self, block: &Block, is_canonical_tip: bool) -> Result<()> {
         let block_is_new = self.get_block_header(block.hash()).await.is_none();
-        let mut block_index_entries = if block_is_new {
+        let body_was_pruned = self
+            .get_block_record(block.hash())
+            .await
+            .is_some_and(|record| record.body_is_pruned());
+        let mut block_index_entries = if block_is_new || body_was_pruned {
             self.store_block(block).await?
         } else {
             warn!(
@@ -605,9 +757,50 @@ impl ArchivalState {
     }
 
     async fn get_block_from_block_record(&self, block_record: BlockRecord) -> Result<Block> {
-        let block_file_path: PathBuf = self
-            .data_dir
-            .block_file_path(block_record.file_location.file_index);
+        let Some(shared_block_store) = self.read_only_shared_block_store() else {
+            return self.read_block(block_record.file_location).await;
+        };
+
+        // The writer of the shared block store can be relocating the block,
+        // by compacting its file, in which case the announced location and
+        // the file contents briefly disagree. Checking the hash of the block
+        // catches this, and a later attempt picks up the new location. The
+        // global state lock is held here, so attempts are not spaced out.
+        let block_digest = BlockHeaderWithBlockHashWitness::new(
+            block_record.block_header,
+            block_record.block_hash_witness.clone(),
+        )
+        .hash();
+        let mut attempt = 1;
+        loop {
+            let location = shared_block_store
+                .locate(block_digest)
+                .await?
+                .unwrap_or(block_record.file_location);
+            if location.is_pruned() {
+                bail!(
+                    "body of block {block_digest:x} was pruned by the writer of shared block store {}",
+                    shared_block_store.dir().display()
+                );
+            }
+
+            let result = self.read_block(location).await.and_then(|block| {
+                if block.hash() != block_digest {
+                    bail!("shared block store holds a different block where {block_digest:x} was announced");
+                }
+                Ok(block)
+            });
+            if result.is_ok() || attempt == READ_ONLY_READ_ATTEMPTS {
+                return result;
+            }
+
+            attempt += 1;
+            tokio::task::yield_now().await;
+        }
+    }
+
+    async fn read_block(&self, file_location: BlockFileLocation) -> Result<Block> {
+        let block_file_path: PathBuf = self.block_file_path(file_location.file_index);
 
         tokio::task::spawn_blocking(move || {
             let block_file = std::fs::File::open(&block_file_path)?;
@@ -618,8 +811,8 @@ impl ArchivalState {
 
             // 2. validate that the requested slice is within the file's bounds.
             // See: https://github.com/Neptune-Crypto/neptune-core/issues/471
-            let requested_end = block_record.file_location.offset
-                .saturating_add(block_record.file_location.block_length as u64);
+            let requested_end = file_location.offset
+                .saturating_add(file_location.block_length as u64);
 
             if requested_end > file_size {
                 bail!(
@@ -631,8 +824,8 @@ impl ArchivalState {
             // 3. The slice is valid, so we can safely memory-map it.
             let mmap = unsafe {
                 MmapOptions::new()
-                    .offset(block_record.file_location.offset)
-                    .len(block_record.file_location.block_length)
+                    .offset(file_location.offset)
+                    .len(file_location.block_length)
                     .map(&block_file)?
             };
 
@@ -906,7 +1099,8 @@ impl ArchivalState {
     ///
     /// Return:
     ///  - `Ok(Some(block))` in case of success.
-    ///  - `Ok(None)` if the block does not live in archival state.
+    ///  - `Ok(None)` if the block does not live in archival state, or if its
+    ///    body was pruned.
     ///  - `Err(_)` if there was a problem reading from archival state.
     pub(crate) async fn get_block(&self, block_digest: Digest) -> Result<Option<Block>> {
         let maybe_record = self.get_block_record(block_digest).await;
@@ -916,17 +1110,56 @@ impl ArchivalState {
             return Ok(maybe_genesis_block);
         };
 
+        if record.body_is_pruned() {
+            return Ok(None);
+        }
+
         // Fetch block from disk
         let block = self.get_block_from_block_record(record).await?;
 
         Ok(Some(block))
     }
 
+    /// Return where the encoding of the block with the given digest is stored,
+    /// for reading it in parts.
+    ///
+    /// Returns `None` if the block does not live in archival state, if its
+    /// body was pruned, or for the genesis block, which is not stored on disk.
+    pub(crate) async fn stored_block_bytes(
+        &self,
+        block_digest: Digest,
+    ) -> Option<StoredBlockBytes> {
+        let record = self.get_block_record(block_digest).await?;
+        if record.body_is_pruned() {
+            return None;
+        }
+
+        let mut file_location = record.file_location;
+        if let Some(shared_block_store) = self.read_only_shared_block_store() {
+            file_location = shared_block_store
+                .locate(block_digest)
+                .await
+                .ok()
+                .flatten()
+                .unwrap_or(file_location);
+            if file_location.is_pruned() {
+                return None;
+            }
+        }
+
+        Some(StoredBlockBytes {
+            path: self.block_file_path(file_location.file_index),
+            offset: file_location.offset,
+            length: file_location.block_length as u64,
+        })
+    }
+
     /// Returns a [`HashMap`] of [`AdditionRecord`] to [`Option`] of AOCL leaf
     /// index (`u64`) for all outputs in a given block. If the block is not
     /// canonical, the indices are all `None`, and conversely, if the block is
     /// canonical then the indices point into the current mutator set AOCL.
-    /// If the block does not live in the archival state, return `None`.
+    /// If the block does not live in the archival state, or if it is not
+    /// canonical and its body was pruned, return `None`.
     ///
     /// # Panics
     ///
@@ -984,6 +1217,10 @@ impl ArchivalState {
         // map) will be set to `None` because AOCL leaf indices are only defined
         // for confirmed outputs.
         else {
+            if block_record.body_is_pruned() {
+                return None;
+            }
+
             let block = self
                 .get_block_from_block_record(block_record)
                 .await
@@ -1235,9 +1472,11 @@ impl ArchivalState {
     /// `new_block` is also assumed to be valid. This function will return an
     /// error if the new block does not have a mutator set update.
     ///
+    /// Returns an error if the bodies of the blocks to roll back were pruned,
+    /// see [`Self::ensure_bodies_retained_from`].
+    ///
     /// # Panics
     ///
-    ///  - If the database does not contain rolled back blocks.
     ///  - If there is no path to the new block.
     pub async fn update_mutator_set(&mut self, new_block: &Block) -> Result<()> {
         #[cfg(test)]
@@ -1276,7 +1515,7 @@ impl ArchivalState {
 
             // Find path from mutator set sync digest to new block. Optimize for the common case,
             // where the new block is the child block of block that the mutator set is synced to.
-            let (backwards, _luca, forwards) =
+            let (backwards, luca, forwards) =
                 if ms_block_sync_digest == new_block.header().prev_block_digest {
                     // Trivial path
                     (vec![], ms_block_sync_digest, vec![])
@@ -1287,16 +1526,24 @@ impl ArchivalState {
                 };
             let forwards = [forwards, vec![new_block.hash()]].concat();
 
+            // Check before modifying the mutator set, so that it is not left
+            // rolled back halfway.
+            if !backwards.is_empty() {
+                let Some(luca_header) = self.get_block_header(luca).await else {
+                    bail!("Unknown common ancestor {luca} of mutator set and new block");
+                };
+                self.ensure_bodies_retained_from(luca_header.height.next())
+                    .await?;
+            }
+
             (forwards, backwards)
         };
 
         for digest in backwards {
             // Roll back mutator set
-            let rollback_block = self
-                .get_block(digest)
-                .await
-                .expect("Fetching block must succeed")
-                .unwrap();
+            let Some(rollback_block) = self.get_block(digest).await? else {
+                bail!("Cannot roll back mutator set: body of block {digest} is not available");
+            };
 
             debug!(
                 "Updating mutator set: rolling back block with height {}",
@@ -1341,10 +1588,10 @@ impl ArchivalState {
                 // with which this function is invoked.
                 new_block.to_owned()
             } else {
-                self.get_block(digest)
-                    .await
-                    .expect("Fetching block must succeed")
-                    .unwrap()
+                let Some(block) = self.get_block(digest).await? else {
+                    bail!("Cannot update mutator set: body of block {digest} is not available");
+                };
+                block
             };
             debug!(
                 "Updating mutator set: adding block with height {}.  Mined: {}",
@@ -1447,6 +1694,7 @@ pub(super) mod tests {
     use crate::tests::shared::archival::add_block_to_archival_state;
     use crate::tests::shared::archival::mock_genesis_archival_state;
     use crate::tests::shared::blocks::invalid_block_with_transaction;
+    use crate::tests::shared::blocks::invalid_empty_blocks;
     use crate::tests::shared::blocks::make_mock_block;
     use crate::tests::shared::files::unit_test_data_directory;
     use crate::tests::shared::globalstate::mock_genesis_global_state;
@@ -1724,6 +1972,85 @@ pub(super) mod tests {
         Ok(())
     }
 
+    #[traced_test]
+    #[apply(shared_tokio_runtime)]
+    async fn stored_block_bytes_can_be_read_in_parts() -> Result<()> {
+        let network = Network::Main;
+        let (mut archival_state, _peer_db_lock, _data_dir) =
+            mock_genesis_archival_state(network).await;
+        let genesis_digest = archival_state.genesis_block.hash();
+        assert!(archival_state
+            .stored_block_bytes(genesis_digest)
+            .await
+            .is_none());
+
+        let own_key = WalletEntropy::new_random().nth_generation_spending_key_for_tests(0);
+        let (block_1, _) = make_mock_block(
+            &archival_state.genesis_block,
+            None,
+            own_key,
+            rand::rng().random(),
+            network,
+        )
+        .await;
+        archival_state.write_block_as_tip(&block_1).await?;
+
+        let stored = archival_state
+            .stored_block_bytes(block_1.hash())
+            .await
+            .unwrap();
+        let expected = bincode::serialize(&block_1)?;
+        assert_eq!(expected.len() as u64, stored.length());
+
+        let mut read = vec![];
+        while (read.len() as u64) < stored.length() {
+            read.extend(stored.read(read.len() as u64, 1000).await?);
+        }
+        assert_eq!(expected, read);
+        assert!(stored.read(stored.length(), 1000).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[traced_test]
+    #[apply(shared_tokio_runtime)]
+    async fn read_only_node_reads_blocks_stored_by_writer_of_shared_block_store() -> Result<()> {
+        let network = Network::Main;
+        let shared_dir = unit_test_data_directory(network)?
+            .root_dir_path()
+            .join("shared_blocks");
+        let open = |shared_block_store: SharedBlockStore| async move {
+            ArchivalState::new_with_shared_block_store(
+                unit_test_data_directory(network).unwrap(),
+                Block::genesis(network),
+                network,
+                Some(shared_block_store),
+            )
+            .await
+        };
+        let mut writer = open(SharedBlockStore::open_for_writing(shared_dir.clone()).await?).await;
+        let mut read_only = open(SharedBlockStore::open_read_only(shared_dir.clone()).await?).await;
+
+        let blocks = invalid_empty_blocks(&writer.genesis_block, 2, network);
+        for block in &blocks {
+            writer.write_block_as_tip(block).await?;
+            read_only.write_block_as_tip(block).await?;
+        }
+
+        for block in &blocks {
+            assert_eq!(
+                Some(block),
+                read_only.get_block(block.hash()).await?.as_ref()
+            );
+        }
+        assert_eq!(blocks.last(), read_only.get_tip_from_disk().await?.as_ref());
+        assert_eq!(shared_dir, writer.block_dir_path());
+        assert!(!read_only.data_dir.block_file_path(0).exists());
+        assert!(!writer.data_dir.block_file_path(0).exists());
+
+        Ok(())
+    }
+
     #[traced_test]
     #[apply(shared_tokio_runtime)]
     async fn update_mutator_set_rollback_ms_block_sync_multiple_inputs_outputs_in_block_test() {
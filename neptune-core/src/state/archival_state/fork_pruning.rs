@@ -0,0 +1,599 @@
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use itertools::Itertools;
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::twenty_first::tip5::digest::Digest;
+use tracing::debug;
+use tracing::info;
+
+use super::ArchivalState;
+use crate::application::database::NeptuneLevelDb;
+use crate::application::database::WriteBatchAsync;
+use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::state::database::BlockFileLocation;
+use crate::state::database::BlockIndexKey;
+use crate::state::database::BlockIndexValue;
+use crate::state::database::BlockRecord;
+use crate::state::database::FileRecord;
+use crate::state::database::ForkPruningRecord;
+use crate::state::shared::BLOCK_FILENAME_PREFIX;
+
+/// Maximum number of heights inspected for fork blocks in one invocation of
+/// [`ArchivalState::prune_fork_blocks`]. Bounds the time spent holding the
+/// lock on the archival state when catching up on a long chain.
+pub(crate) const MAX_NUM_HEIGHTS_PER_FORK_PRUNING: u64 = 10_000;
+
+/// File extension of a block file being compacted. Must not match the naming
+/// scheme of block files, as these are picked up by block import.
+const COMPACTED_BLOCK_FILE_EXTENSION: &str = "compacting";
+
+/// The state of fork-block pruning, as reported over RPC.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ForkPruningStatus {
+    /// The depth below the tip beyond which fork blocks are pruned, or `None`
+    /// if pruning is disabled.
+    pub depth: Option<u64>,
+
+    /// All fork blocks at or below this height have been pruned.
+    pub pruned_up_to: BlockHeight,
+
+    /// The number of fork blocks whose bodies were pruned.
+    pub num_pruned_blocks: u64,
+
+    /// The number of bytes freed from the block files.
+    pub num_bytes_reclaimed: u64,
+}
+
+impl ForkPruningStatus {
+    pub(crate) fn new(depth: Option<u64>, record: ForkPruningRecord) -> Self {
+        Self {
+            depth,
+            pruned_up_to: record.pruned_up_to,
+            num_pruned_blocks: record.num_pruned_blocks,
+            num_bytes_reclaimed: record.num_bytes_reclaimed,
+        }
+    }
+}
+
+/// A prepared compaction of a block file. See
+/// [`ArchivalState::prepare_block_file_compaction`].
+#[derive(Debug)]
+pub(crate) struct BlockFileCompaction {
+    file_index: u32,
+    pruned: HashSet<Digest>,
+    file_record: Option<FileRecord>,
+    block_index_db: NeptuneLevelDb<BlockIndexKey, BlockIndexValue>,
+    block_file_path: PathBuf,
+    compacted_block_file_path: PathBuf,
+}
+
+/// A written, but not yet indexed, compaction of a block file. See
+/// [`ArchivalState::finish_block_file_compaction`].
+#[derive(Debug)]
+pub(crate) struct WrittenBlockFileCompaction {
+    file_index: u32,
+    file_record: Option<FileRecord>,
+    pruned_blocks: Vec<(Digest, BlockRecord)>,
+    retained_blocks: Vec<(Digest, BlockRecord)>,
+    old_file_size: u64,
+    new_locations: Vec<BlockFileLocation>,
+    block_file_path: PathBuf,
+    compacted_block_file_path: PathBuf,
+}
+
+impl BlockFileCompaction {
+    /// Write the compacted block file next to the original. Does not need
+    /// the lock on the archival state.
+    pub(crate) async fn write(self) -> Result<WrittenBlockFileCompaction> {
+        let Self {
+            file_index,
+            pruned,
+            file_record,
+            block_index_db,
+            block_file_path,
+            compacted_block_file_path,
+        } = self;
+
+        // The block index has no mapping from files to blocks, so iterate over
+        // all records. This is expensive, but only happens when blocks are
+        // pruned.
+        let stored_blocks = tokio::task::spawn_blocking(move || {
+            block_index_db
+                .iter()
+                .filter_map(|(key, value)| match (key, value) {
+                    (BlockIndexKey::Block(digest), BlockIndexValue::Block(block_record))
+                        if block_record.file_location.file_index == file_index
+                            && !block_record.body_is_pruned() =>
+                    {
+                        Some((digest, *block_record))
+                    }
+                    _ => None,
+                })
+                .sorted_by_key(|(_, block_record)| block_record.file_location.offset)
+                .collect_vec()
+        })
+        .await?;
+
+        let (pruned_blocks, retained_blocks): (Vec<(Digest, BlockRecord)>, Vec<_>) = stored_blocks
+            .into_iter()
+            .partition(|(digest, _)| pruned.contains(digest));
+
+        debug!(
+            "Compacting block file {}, pruning {} blocks",
+            block_file_path.display(),
+            pruned_blocks.len()
+        );
+
+        let retained_locations = retained_blocks
+            .iter()
+            .map(|(_, block_record)| block_record.file_location)
+            .collect_vec();
+        let (old_file_size, new_locations) = {
+            let block_file_path = block_file_path.clone();
+            let compacted_block_file_path = compacted_block_file_path.clone();
+            tokio::task::spawn_blocking(move || -> Result<(u64, Vec<BlockFileLocation>)> {
+                let mut block_file = std::fs::File::open(&block_file_path)?;
+                let old_file_size = block_file.metadata()?.len();
+                let mut compacted_block_file =
+                    std::io::BufWriter::new(std::fs::File::create(&compacted_block_file_path)?);
+
+                let mut offset = 0;
+                let mut new_locations = vec![];
+                for location in retained_locations {
+                    let mut serialized_block = vec![0u8; location.block_length];
+                    block_file.seek(SeekFrom::Start(location.offset))?;
+                    block_file.read_exact(&mut serialized_block)?;
+                    compacted_block_file.write_all(&serialized_block)?;
+
+                    new_locations.push(BlockFileLocation {
+                        file_index,
+                        offset,
+                        block_length: location.block_length,
+                    });
+                    offset += location.block_length as u64;
+                }
+
+                // The compacted file must be persisted before the index
+                // points into it.
+                compacted_block_file.into_inner()?.sync_all()?;
+
+                Ok((old_file_size, new_locations))
+            })
+            .await??
+        };
+
+        Ok(WrittenBlockFileCompaction {
+            file_index,
+            file_record,
+            pruned_blocks,
+            retained_blocks,
+            old_file_size,
+            new_locations,
+            block_file_path,
+            compacted_block_file_path,
+        })
+    }
+}
+
+impl ArchivalState {
+    /// Return the progress of fork-block pruning.
+    pub(crate) async fn fork_pruning_record(&self) -> ForkPruningRecord {
+        self.block_index_db
+            .get(BlockIndexKey::ForkPruning)
+            .await
+            .map(|x| x.as_fork_pruning_record())
+            .unwrap_or_default()
+    }
+
+    fn compacted_block_file_path(&self, file_index: u32) -> PathBuf {
+        let prefix = BLOCK_FILENAME_PREFIX;
+        let extension = COMPACTED_BLOCK_FILE_EXTENSION;
+        self.block_dir_path()
+            .join(format!("{prefix}{file_index}.{extension}"))
+    }
+
+    /// Delete the bodies and proofs of fork blocks that are more than `depth`
+    /// blocks below the tip, retaining their headers.
+    ///
+    /// A fork block is a stored block that does not belong to the canonical
+    /// chain. The block files holding pruned blocks are compacted, which frees
+    /// disk space. Pruned blocks can no longer be served to peers, but are
+    /// stored again if received again, eg in case of a reorganization onto
+    /// the abandoned fork.
+    ///
+    /// Resumes from where the previous invocation left off, and inspects at
+    /// most [`MAX_NUM_HEIGHTS_PER_FORK_PRUNING`] heights. Returns the updated
+    /// progress.
+    pub(crate) async fn prune_fork_blocks(
+        &mut self,
+        tip_height: BlockHeight,
+        depth: u64,
+    ) -> Result<ForkPruningRecord> {
+        let mut record = self.fork_pruning_record().await;
+        let Some(max_height) = u64::from(tip_height).checked_sub(depth) else {
+            return Ok(record);
+        };
+        let min_height = u64::from(record.pruned_up_to) + 1;
+        let max_height =
+            max_height.min(min_height.saturating_add(MAX_NUM_HEIGHTS_PER_FORK_PRUNING - 1));
+        if min_height > max_height {
+            return Ok(record);
+        }
+
+        // group fork blocks by the file they are stored in
+        let mut fork_blocks: BTreeMap<u32, HashSet<Digest>> = BTreeMap::new();
+        for height in min_height..=max_height {
+            let Some(canonical_digest) = self.archival_block_mmr.ammr().try_get_leaf(height).await
+            else {
+                break;
+            };
+            for digest in self.block_height_to_block_digests(height.into()).await {
+                if digest == canonical_digest {
+                    continue;
+                }
+                let Some(block_record) = self.get_block_record(digest).await else {
+                    continue;
+                };
+                if block_record.body_is_pruned() {
+                    continue;
+                }
+
+                fork_blocks
+                    .entry(block_record.file_location.file_index)
+                    .or_default()
+                    .insert(digest);
+            }
+        }
+
+        for (file_index, pruned) in fork_blocks {
+            record = self.compact_block_file(file_index, &pruned).await?;
+        }
+
+        record.pruned_up_to = max_height.into();
+        self.block_index_db
+            .put(
+                BlockIndexKey::ForkPruning,
+                BlockIndexValue::ForkPruning(record),
+            )
+            .await;
+
+        Ok(record)
+    }
+
+    /// Rewrite a block file without the blocks in `pruned`, and mark those
+    /// blocks as pruned.
+    ///
+    /// The compacted file is written next to the original, and indexed before
+    /// it replaces the original. If interrupted after indexing, the
+    /// replacement is completed by
+    /// [`Self::finish_interrupted_block_file_compaction`].
+    ///
+    /// Holds the lock on the archival state throughout. To write the
+    /// compacted file without holding it, use
+    /// [`Self::prepare_block_file_compaction`] and
+    /// [`Self::finish_block_file_compaction`] instead.
+    pub(super) async fn compact_block_file(
+        &mut self,
+        file_index: u32,
+        pruned: &HashSet<Digest>,
+    ) -> Result<ForkPruningRecord> {
+        let compaction = self
+            .prepare_block_file_compaction(file_index, pruned.clone())
+            .await?
+            .write()
+            .await?;
+        self.finish_block_file_compaction(compaction)
+            .await?
+            .context("block file changed during compaction")
+    }
+
+    /// Prepare the compaction of a block file, which is then written by
+    /// [`BlockFileCompaction::write`] without holding the lock on the
+    /// archival state, and indexed by [`Self::finish_block_file_compaction`].
+    pub(crate) async fn prepare_block_file_compaction(
+        &self,
+        file_index: u32,
+        pruned: HashSet<Digest>,
+    ) -> Result<BlockFileCompaction> {
+        if self.read_only_shared_block_store().is_some() {
+            bail!("cannot compact block files of a read-only shared block store");
+        }
+
+        let file_record = self
+            .block_index_db
+            .get(BlockIndexKey::File(file_index))
+            .await
+            .map(|x| x.as_file_record());
+
+        Ok(BlockFileCompaction {
+            file_index,
+            pruned,
+            file_record,
+            block_index_db: self.block_index_db.clone(),
+            block_file_path: self.block_file_path(file_index),
+            compacted_block_file_path: self.compacted_block_file_path(file_index),
+        })
+    }
+
+    /// Index a compacted block file and let it replace the original, marking
+    /// the blocks left out of it as pruned.
+    ///
+    /// Returns `None`, and discards the compacted file, if blocks were stored
+    /// in or removed from the original file since the compaction was
+    /// prepared.
+    pub(crate) async fn finish_block_file_compaction(
+        &mut self,
+        compaction: WrittenBlockFileCompaction,
+    ) -> Result<Option<ForkPruningRecord>> {
+        let WrittenBlockFileCompaction {
+            file_index,
+            file_record,
+            pruned_blocks,
+            retained_blocks,
+            old_file_size,
+            new_locations,
+            block_file_path,
+            compacted_block_file_path,
+        } = compaction;
+
+        let file_record_key = BlockIndexKey::File(file_index);
+        let current_file_record = self
+            .block_index_db
+            .get(file_record_key)
+            .await
+            .map(|x| x.as_file_record());
+        let file_is_unchanged = match (file_record, current_file_record) {
+            (Some(prepared), Some(current)) => {
+                prepared.blocks_in_file_count == current.blocks_in_file_count
+                    && prepared.file_size == current.file_size
+            }
+            (None, None) => true,
+            _ => false,
+        };
+        if !file_is_unchanged {
+            debug!(
+                "Block file {} changed during compaction; retrying later",
+                block_file_path.display()
+            );
+            let _ = tokio::fs::remove_file(&compacted_block_file_path).await;
+            return Ok(None);
+        }
+
+        let new_file_size = new_locations
+            .iter()
+            .map(|location| location.block_length as u64)
+            .sum::<u64>();
+
+        let mut batch = WriteBatchAsync::new();
+        if let Some(mut file_record) = current_file_record {
+            file_record.blocks_in_file_count = retained_blocks.len() as u32;
+            file_record.file_size = new_file_size;
+            let headers = retained_blocks
+                .iter()
+                .map(|(_, block_record)| block_record.block_header)
+                .collect_vec();
+            if let Some((min, max)) = headers.iter().map(|h| h.height).minmax().into_option() {
+                file_record.min_block_height = min;
+                file_record.max_block_height = max;
+            }
+            if let Some((min, max)) = headers.iter().map(|h| h.timestamp).minmax().into_option() {
+                file_record.min_block_timestamp = min;
+                file_record.max_block_timestamp = max;
+            }
+            batch.op_write(file_record_key, BlockIndexValue::File(file_record));
+        }
+
+        let mut announced_locations = vec![];
+        for ((digest, mut block_record), location) in retained_blocks.into_iter().zip(new_locations)
+        {
+            announced_locations.push((digest, location));
+            block_record.file_location = location;
+            batch.op_write(
+                BlockIndexKey::Block(digest),
+                BlockIndexValue::Block(Box::new(block_record)),
+            );
+        }
+
+        let num_pruned_blocks = pruned_blocks.len() as u64;
+        for (digest, mut block_record) in pruned_blocks {
+            announced_locations.push((digest, BlockFileLocation::pruned()));
+            block_record.file_location = BlockFileLocation::pruned();
+            batch.op_write(
+                BlockIndexKey::Block(digest),
+                BlockIndexValue::Block(Box::new(block_record)),
+            );
+        }
+
+        let mut record = self.fork_pruning_record().await;
+        record.num_pruned_blocks += num_pruned_blocks;
+        record.num_bytes_reclaimed += old_file_size.saturating_sub(new_file_size);
+        record.pending_compaction = Some(file_index);
+        batch.op_write(
+            BlockIndexKey::ForkPruning,
+            BlockIndexValue::ForkPruning(record),
+        );
+        self.block_index_db.batch_write(batch).await;
+
+        tokio::fs::rename(&compacted_block_file_path, &block_file_path).await?;
+        if let Some(shared_block_store) = &self.shared_block_store {
+            shared_block_store.announce(&announced_locations).await?;
+        }
+
+        record.pending_compaction = None;
+        self.block_index_db
+            .put(
+                BlockIndexKey::ForkPruning,
+                BlockIndexValue::ForkPruning(record),
+            )
+            .await;
+
+        info!(
+            "Pruned {num_pruned_blocks} block bodies from {}, freeing {} bytes",
+            block_file_path.display(),
+            old_file_size.saturating_sub(new_file_size)
+        );
+
+        Ok(Some(record))
+    }
+
+    /// Complete a block file compaction that was interrupted after the block
+    /// index was updated, but before the compacted file replaced the original.
+    pub(super) async fn finish_interrupted_block_file_compaction(&mut self) -> Result<()> {
+        let mut record = self.fork_pruning_record().await;
+        let Some(file_index) = record.pending_compaction else {
+            return Ok(());
+        };
+
+        let compacted_block_file_path = self.compacted_block_file_path(file_index);
+        if tokio::fs::try_exists(&compacted_block_file_path).await? {
+            info!("Finishing interrupted compaction of block file {file_index}");
+            tokio::fs::rename(&compacted_block_file_path, self.block_file_path(file_index)).await?;
+        }
+
+        record.pending_compaction = None;
+        self.block_index_db
+            .put(
+                BlockIndexKey::ForkPruning,
+                BlockIndexValue::ForkPruning(record),
+            )
+            .await;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use macro_rules_attr::apply;
+
+    use super::*;
+    use crate::api::export::Network;
+    use crate::api::export::Timestamp;
+    use crate::protocol::consensus::block::Block;
+    use crate::state::archival_state::tests::make_test_archival_state;
+    use crate::tests::shared::archival::add_block_to_archival_state;
+    use crate::tests::shared::blocks::invalid_empty_block_with_timestamp;
+    use crate::tests::shared::blocks::invalid_empty_blocks;
+    use crate::tests::shared_tokio_runtime;
+
+    /// Return an archival state with a canonical chain of `chain_length`
+    /// blocks, and a fork block at height 1 which is stored between blocks
+    /// 1 and 2 of the canonical chain.
+    async fn archival_state_with_fork(
+        network: Network,
+        chain_length: usize,
+    ) -> (ArchivalState, Vec<Block>, Block) {
+        let mut archival_state = make_test_archival_state(network).await;
+        let genesis = archival_state.genesis_block().clone();
+        let canonical = invalid_empty_blocks(&genesis, chain_length, network);
+        let fork_block = invalid_empty_block_with_timestamp(
+            &genesis,
+            canonical[0].header().timestamp + Timestamp::minutes(1),
+            network,
+        );
+
+        add_block_to_archival_state(&mut archival_state, canonical[0].clone())
+            .await
+            .unwrap();
+        archival_state
+            .write_block_not_tip(&fork_block)
+            .await
+            .unwrap();
+        for block in &canonical[1..] {
+            add_block_to_archival_state(&mut archival_state, block.clone())
+                .await
+                .unwrap();
+        }
+
+        (archival_state, canonical, fork_block)
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn fork_blocks_are_pruned_only_below_depth() {
+        let network = Network::Main;
+        let (mut archival_state, canonical, fork_block) =
+            archival_state_with_fork(network, 4).await;
+        let tip_height = canonical.last().unwrap().header().height;
+
+        let record = archival_state
+            .prune_fork_blocks(tip_height, 4)
+            .await
+            .unwrap();
+        assert_eq!(0, record.num_pruned_blocks);
+        assert!(archival_state
+            .get_block(fork_block.hash())
+            .await
+            .unwrap()
+            .is_some());
+
+        let record = archival_state
+            .prune_fork_blocks(tip_height, 3)
+            .await
+            .unwrap();
+        assert_eq!(1, record.num_pruned_blocks);
+        assert_eq!(BlockHeight::from(1u64), record.pruned_up_to);
+        assert_eq!(
+            bincode::serialized_size(&fork_block).unwrap(),
+            record.num_bytes_reclaimed
+        );
+        assert!(record.pending_compaction.is_none());
+
+        // header is retained, body is not
+        assert!(archival_state
+            .get_block(fork_block.hash())
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            Some(*fork_block.header()),
+            archival_state.get_block_header(fork_block.hash()).await
+        );
+
+        // canonical blocks stored after the pruned block are still readable
+        for block in &canonical {
+            assert_eq!(
+                Some(block.clone()),
+                archival_state.get_block(block.hash()).await.unwrap()
+            );
+        }
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn pruned_fork_block_can_be_stored_again() {
+        let network = Network::Main;
+        let (mut archival_state, canonical, fork_block) =
+            archival_state_with_fork(network, 3).await;
+        let tip_height = canonical.last().unwrap().header().height;
+        archival_state
+            .prune_fork_blocks(tip_height, 1)
+            .await
+            .unwrap();
+
+        archival_state
+            .write_block_not_tip(&fork_block)
+            .await
+            .unwrap();
+        assert_eq!(
+            Some(fork_block.clone()),
+            archival_state.get_block(fork_block.hash()).await.unwrap()
+        );
+        assert_eq!(
+            2,
+            archival_state
+                .block_height_to_block_digests(1u64.into())
+                .await
+                .len()
+        );
+    }
+}
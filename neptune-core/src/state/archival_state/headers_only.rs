@@ -0,0 +1,271 @@
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+
+use anyhow::ensure;
+use anyhow::Result;
+use tasm_lib::twenty_first::tip5::digest::Digest;
+
+use super::ArchivalState;
+use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::state::database::BlockIndexKey;
+use crate::state::database::BlockIndexValue;
+
+/// Number of blocks below the tip whose bodies a node running with
+/// [`headers_only`](crate::application::config::cli_args::Args::headers_only)
+/// retains. Reorganizations deeper than this cannot be followed, since rolling
+/// back the archival mutator set requires the bodies of the rolled-back
+/// blocks.
+pub(crate) const HEADERS_ONLY_RETENTION_DEPTH: u64 = 100;
+
+/// Maximum number of heights pruned in one invocation of
+/// [`ArchivalState::prune_canonical_blocks`]. Bounds the time spent holding
+/// the lock on the archival state when catching up on a long chain.
+pub(crate) const MAX_NUM_HEIGHTS_PER_CANONICAL_PRUNING: u64 = 10_000;
+
+/// The canonical blocks whose bodies are to be pruned next. See
+/// [`ArchivalState::canonical_blocks_to_prune`].
+#[derive(Debug, Clone)]
+pub(crate) struct CanonicalPruning {
+    /// The height up to which bodies are pruned once all `blocks` are.
+    pub(crate) pruned_up_to: BlockHeight,
+
+    /// The blocks to prune, grouped by the index of the file they are stored
+    /// in.
+    pub(crate) blocks: BTreeMap<u32, HashSet<Digest>>,
+}
+
+impl ArchivalState {
+    /// All canonical blocks at or below this height have had their bodies
+    /// pruned.
+    pub(crate) async fn canonical_pruning_height(&self) -> BlockHeight {
+        self.block_index_db
+            .get(BlockIndexKey::CanonicalPruning)
+            .await
+            .map(|x| x.as_canonical_pruning_height())
+            .unwrap_or_default()
+    }
+
+    /// Fail unless the bodies of all canonical blocks from the given height
+    /// on are retained, ie were not pruned. Walking a path that starts below
+    /// the pruning height is impossible, as it requires the pruned bodies.
+    pub(crate) async fn ensure_bodies_retained_from(&self, height: BlockHeight) -> Result<()> {
+        let pruned_up_to = self.canonical_pruning_height().await;
+        ensure!(
+            pruned_up_to.is_genesis() || height > pruned_up_to,
+            "The bodies of blocks at height {height} and below were pruned, up to height \
+            {pruned_up_to}"
+        );
+
+        Ok(())
+    }
+
+    /// Delete the bodies and proofs of canonical blocks that are more than
+    /// `depth` blocks below the tip, retaining their headers.
+    ///
+    /// Shares the compaction of block files with
+    /// [`Self::prune_fork_blocks`]. Pruned blocks can no longer be served to
+    /// peers, but are stored again if received again.
+    ///
+    /// Resumes from where the previous invocation left off, and inspects at
+    /// most [`MAX_NUM_HEIGHTS_PER_CANONICAL_PRUNING`] heights. Returns the
+    /// height up to which bodies have been pruned.
+    ///
+    /// Holds the lock on the archival state throughout. To compact block
+    /// files without holding it, use [`Self::canonical_blocks_to_prune`]
+    /// and [`Self::prepare_block_file_compaction`] instead.
+    pub(crate) async fn prune_canonical_blocks(
+        &mut self,
+        tip_height: BlockHeight,
+        depth: u64,
+    ) -> Result<BlockHeight> {
+        let Some(pruning) = self.canonical_blocks_to_prune(tip_height, depth).await else {
+            return Ok(self.canonical_pruning_height().await);
+        };
+
+        for (file_index, pruned) in &pruning.blocks {
+            self.compact_block_file(*file_index, pruned).await?;
+        }
+        self.set_canonical_pruning_height(pruning.pruned_up_to)
+            .await;
+
+        Ok(pruning.pruned_up_to)
+    }
+
+    /// The canonical blocks whose bodies are to be pruned next, grouped by
+    /// the file they are stored in, or `None` if there is nothing to prune.
+    /// See [`Self::prune_canonical_blocks`].
+    pub(crate) async fn canonical_blocks_to_prune(
+        &self,
+        tip_height: BlockHeight,
+        depth: u64,
+    ) -> Option<CanonicalPruning> {
+        let pruned_up_to = self.canonical_pruning_height().await;
+        let max_height = u64::from(tip_height).checked_sub(depth)?;
+        let min_height = u64::from(pruned_up_to) + 1;
+        let max_height =
+            max_height.min(min_height.saturating_add(MAX_NUM_HEIGHTS_PER_CANONICAL_PRUNING - 1));
+        if min_height > max_height {
+            return None;
+        }
+
+        // group canonical blocks by the file they are stored in
+        let mut blocks: BTreeMap<u32, HashSet<Digest>> = BTreeMap::new();
+        for height in min_height..=max_height {
+            let Some(digest) = self.archival_block_mmr.ammr().try_get_leaf(height).await else {
+                break;
+            };
+            let Some(block_record) = self.get_block_record(digest).await else {
+                continue;
+            };
+            if block_record.body_is_pruned() {
+                continue;
+            }
+
+            blocks
+                .entry(block_record.file_location.file_index)
+                .or_default()
+                .insert(digest);
+        }
+
+        Some(CanonicalPruning {
+            pruned_up_to: BlockHeight::from(max_height),
+            blocks,
+        })
+    }
+
+    /// Record that the bodies of all canonical blocks up to the given height
+    /// have been pruned.
+    pub(crate) async fn set_canonical_pruning_height(&mut self, pruned_up_to: BlockHeight) {
+        self.block_index_db
+            .put(
+                BlockIndexKey::CanonicalPruning,
+                BlockIndexValue::CanonicalPruning(pruned_up_to),
+            )
+            .await;
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use macro_rules_attr::apply;
+
+    use super::*;
+    use crate::api::export::Network;
+    use crate::state::archival_state::tests::make_test_archival_state;
+    use crate::tests::shared::archival::add_block_to_archival_state;
+    use crate::tests::shared::blocks::invalid_empty_blocks;
+    use crate::tests::shared_tokio_runtime;
+
+    #[apply(shared_tokio_runtime)]
+    async fn canonical_blocks_are_pruned_only_below_depth() {
+        let network = Network::Main;
+        let mut archival_state = make_test_archival_state(network).await;
+        let genesis = archival_state.genesis_block().clone();
+        let blocks = invalid_empty_blocks(&genesis, 4, network);
+        for block in &blocks {
+            add_block_to_archival_state(&mut archival_state, block.clone())
+                .await
+                .unwrap();
+        }
+        let tip_height = blocks.last().unwrap().header().height;
+
+        let pruned_up_to = archival_state
+            .prune_canonical_blocks(tip_height, 2)
+            .await
+            .unwrap();
+        assert_eq!(BlockHeight::from(2u64), pruned_up_to);
+        assert_eq!(
+            pruned_up_to,
+            archival_state.canonical_pruning_height().await
+        );
+
+        // headers are retained, bodies only above the depth
+        for block in &blocks[..2] {
+            assert!(archival_state
+                .get_block(block.hash())
+                .await
+                .unwrap()
+                .is_none());
+            assert_eq!(
+                Some(*block.header()),
+                archival_state.get_block_header(block.hash()).await
+            );
+        }
+        for block in &blocks[2..] {
+            assert_eq!(
+                Some(block.clone()),
+                archival_state.get_block(block.hash()).await.unwrap()
+            );
+        }
+        assert!(
+            archival_state
+                .block_belongs_to_canonical_chain(blocks[0].hash())
+                .await
+        );
+
+        // nothing more to prune until the tip advances
+        assert_eq!(
+            pruned_up_to,
+            archival_state
+                .prune_canonical_blocks(tip_height, 2)
+                .await
+                .unwrap()
+        );
+
+        // paths can only be walked above the pruning height
+        assert!(archival_state
+            .ensure_bodies_retained_from(pruned_up_to)
+            .await
+            .is_err());
+        assert!(archival_state
+            .ensure_bodies_retained_from(pruned_up_to.next())
+            .await
+            .is_ok());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn compaction_is_discarded_if_blocks_are_stored_meanwhile() {
+        let network = Network::Main;
+        let mut archival_state = make_test_archival_state(network).await;
+        let genesis = archival_state.genesis_block().clone();
+        let blocks = invalid_empty_blocks(&genesis, 4, network);
+        for block in &blocks[..3] {
+            add_block_to_archival_state(&mut archival_state, block.clone())
+                .await
+                .unwrap();
+        }
+        let tip_height = blocks[2].header().height;
+
+        let pruning = archival_state
+            .canonical_blocks_to_prune(tip_height, 1)
+            .await
+            .unwrap();
+        let (file_index, pruned) = pruning.blocks.into_iter().next().unwrap();
+        let compaction = archival_state
+            .prepare_block_file_compaction(file_index, pruned)
+            .await
+            .unwrap()
+            .write()
+            .await
+            .unwrap();
+
+        // a block is stored in the same file while the compacted file is
+        // written without the lock
+        add_block_to_archival_state(&mut archival_state, blocks[3].clone())
+            .await
+            .unwrap();
+
+        assert!(archival_state
+            .finish_block_file_compaction(compaction)
+            .await
+            .unwrap()
+            .is_none());
+        for block in &blocks {
+            assert_eq!(
+                Some(block.clone()),
+                archival_state.get_block(block.hash()).await.unwrap()
+            );
+        }
+    }
+}
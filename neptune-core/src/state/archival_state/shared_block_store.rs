@@ -0,0 +1,653 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::bail;
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Digest;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncSeekExt;
+use tokio::io::AsyncWriteExt;
+use tokio::io::SeekFrom;
+use tokio::sync::Mutex;
+use tracing::debug;
+use tracing::info;
+use tracing::warn;
+
+use crate::state::database::BlockFileLocation;
+use crate::state::shared::BLOCK_FILENAME_EXTENSION;
+use crate::state::shared::BLOCK_FILENAME_PREFIX;
+
+/// The file whose lock designates the single node writing to a shared block
+/// store.
+pub(crate) const WRITER_LOCK_FILE_NAME: &str = "writer.lock";
+
+/// The journal of block locations, through which the writer announces stored
+/// and relocated blocks to read-only nodes.
+pub(crate) const JOURNAL_FILE_NAME: &str = "block_locations.journal";
+
+/// The number of entries the journal may hold before it is compacted, if
+/// fewer than half of them are the latest location of a block.
+const MIN_NUM_ENTRIES_BEFORE_COMPACTION: usize = 1_000;
+
+/// How often a read-only node checks the journal while waiting for the writer
+/// to store a block.
+pub(crate) const JOURNAL_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How long a read-only node waits for the writer to store a block that the
+/// node itself received, before giving up on storing it.
+pub(crate) const READ_ONLY_STORE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How often a read-only node tries to read a block before giving up, in case
+/// the writer is relocating it. Attempts are not spaced out, as blocks are
+/// read while holding the global state lock.
+pub(crate) const READ_ONLY_READ_ATTEMPTS: usize = 3;
+
+/// Size of the length prefix of every journal entry.
+const ENTRY_LENGTH_PREFIX_SIZE: usize = size_of::<u32>();
+
+/// Size of the header of the journal, which holds its generation. Every
+/// compaction of the journal starts a new generation.
+const JOURNAL_HEADER_SIZE: usize = size_of::<u64>();
+
+/// Ways in which a shared block store cannot be opened.
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum SharedBlockStoreError {
+    #[error("could not access shared block store {}: {source}", dir.display())]
+    Io {
+        dir: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error(
+        "shared block store {} is already being written to by another node. \
+         Other nodes must open it read-only.",
+        .0.display()
+    )]
+    WriterLocked(PathBuf),
+
+    #[error(
+        "shared block store {} has no journal. Start the node writing to it first.",
+        .0.display()
+    )]
+    Uninitialized(PathBuf),
+}
+
+/// The location of a block, as announced through the journal. A pruned
+/// location announces that the block's body was deleted.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct JournalEntry {
+    digest: Digest,
+    location: BlockFileLocation,
+}
+
+/// What a read-only node learned from the journal so far.
+#[derive(Debug, Default)]
+struct JournalCache {
+    /// The generation of the journal that was read, if any.
+    generation: Option<u64>,
+
+    /// The number of bytes of the journal that were read.
+    num_bytes_read: u64,
+
+    /// The latest location of every announced block.
+    locations: HashMap<Digest, BlockFileLocation>,
+}
+
+impl JournalCache {
+    /// Read the entries appended to the journal since the last refresh.
+    /// Trailing bytes that do not form a complete entry, because the writer is
+    /// appending them right now, are left for the next refresh.
+    ///
+    /// A compacted journal holds the latest location of every announced
+    /// block, so the cache is rebuilt from it.
+    async fn refresh(&mut self, journal_path: &Path) -> Result<()> {
+        let mut journal = tokio::fs::File::open(journal_path).await?;
+        let journal_length = journal.metadata().await?.len();
+        if journal_length < JOURNAL_HEADER_SIZE as u64 {
+            // The writer is creating the journal right now.
+            return Ok(());
+        }
+
+        let generation = journal.read_u64_le().await?;
+        if self.generation != Some(generation) {
+            *self = Self {
+                generation: Some(generation),
+                num_bytes_read: JOURNAL_HEADER_SIZE as u64,
+                locations: HashMap::new(),
+            };
+        }
+        if journal_length < self.num_bytes_read {
+            bail!(
+                "journal {} shrank from {} to {journal_length} bytes",
+                journal_path.display(),
+                self.num_bytes_read
+            );
+        }
+        if journal_length == self.num_bytes_read {
+            return Ok(());
+        }
+
+        journal.seek(SeekFrom::Start(self.num_bytes_read)).await?;
+        let mut appended = vec![];
+        journal.read_to_end(&mut appended).await?;
+
+        let (entries, num_bytes_consumed) = decode_entries(&appended)?;
+        for entry in entries {
+            self.locations.insert(entry.digest, entry.location);
+        }
+        self.num_bytes_read += num_bytes_consumed as u64;
+
+        Ok(())
+    }
+}
+
+/// Decode the complete entries at the start of `bytes`, and return them along
+/// with the number of bytes they occupy.
+fn decode_entries(bytes: &[u8]) -> Result<(Vec<JournalEntry>, usize)> {
+    let mut entries = vec![];
+    let mut position = 0;
+    while let Some(prefix) = bytes.get(position..position + ENTRY_LENGTH_PREFIX_SIZE) {
+        let entry_length = u32::from_le_bytes(prefix.try_into()?) as usize;
+        let start = position + ENTRY_LENGTH_PREFIX_SIZE;
+        let Some(encoding) = bytes.get(start..start + entry_length) else {
+            break;
+        };
+        entries.push(bincode::deserialize(encoding)?);
+        position = start + entry_length;
+    }
+
+    Ok((entries, position))
+}
+
+fn encode_entry(entry: &JournalEntry) -> Result<Vec<u8>> {
+    let encoding = bincode::serialize(entry)?;
+    let entry_length = u32::try_from(encoding.len())?;
+
+    Ok([entry_length.to_le_bytes().to_vec(), encoding].concat())
+}
+
+/// The journal, as written by the writer of the store.
+#[derive(Debug)]
+struct JournalWriter {
+    file: tokio::fs::File,
+    generation: u64,
+    num_entries: usize,
+
+    /// The latest location of every announced block, which is all a
+    /// compacted journal holds.
+    locations: HashMap<Digest, BlockFileLocation>,
+}
+
+impl JournalWriter {
+    /// Append the given entries, and compact the journal if most of its
+    /// entries are superseded.
+    async fn append(&mut self, journal_path: &Path, entries: Vec<JournalEntry>) -> Result<()> {
+        let mut encoding = vec![];
+        for entry in &entries {
+            encoding.extend(encode_entry(entry)?);
+        }
+        self.file.write_all(&encoding).await?;
+        self.file.sync_data().await?;
+
+        self.num_entries += entries.len();
+        for entry in entries {
+            self.locations.insert(entry.digest, entry.location);
+        }
+
+        let is_mostly_superseded = self.num_entries > 2 * self.locations.len();
+        if self.num_entries > MIN_NUM_ENTRIES_BEFORE_COMPACTION && is_mostly_superseded {
+            // The entries are persisted already, and a journal that was not
+            // compacted is still valid.
+            if let Err(e) = self.compact(journal_path).await {
+                warn!("Could not compact journal {}: {e}", journal_path.display());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replace the journal by one of a new generation that holds only the
+    /// latest location of every announced block.
+    ///
+    /// The new journal is written next to the old one and then renamed over
+    /// it, so that read-only nodes see either journal in full.
+    async fn compact(&mut self, journal_path: &Path) -> Result<()> {
+        let generation = self.generation + 1;
+        let mut contents = generation.to_le_bytes().to_vec();
+        for (digest, location) in &self.locations {
+            contents.extend(encode_entry(&JournalEntry {
+                digest: *digest,
+                location: *location,
+            })?);
+        }
+
+        let partial_path = journal_path.with_extension("partial");
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .read(true)
+            .write(true)
+            .open(&partial_path)
+            .await?;
+        file.write_all(&contents).await?;
+        file.sync_all().await?;
+        tokio::fs::rename(&partial_path, journal_path).await?;
+
+        debug!(
+            "Compacted journal {} from {} to {} entries",
+            journal_path.display(),
+            self.num_entries,
+            self.locations.len()
+        );
+        self.file = file;
+        self.generation = generation;
+        self.num_entries = self.locations.len();
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+enum Access {
+    /// Holds the lock that keeps other nodes from writing, for as long as the
+    /// store is open.
+    Writer {
+        _lock: std::fs::File,
+        journal: Mutex<JournalWriter>,
+    },
+    ReadOnly {
+        cache: Mutex<JournalCache>,
+    },
+}
+
+/// A directory of block files shared by several nodes on the same host, such
+/// that the blocks are stored only once.
+///
+/// A single node writes blocks to the store, and holds an exclusive lock on
+/// it for as long as it runs. Every other node opens the store read-only. All
+/// nodes keep their own block index, mutator set, and block MMR databases,
+/// and validate blocks themselves; only the block files are shared.
+///
+/// The writer announces the location of every block it stores to the
+/// read-only nodes through a journal it appends to. Before a read-only node
+/// stores a block, it waits for the writer to store the same block, and then
+/// indexes the announced location. When the writer relocates blocks by
+/// compacting a block file, it announces the new locations, which read-only
+/// nodes pick up before reading a block, so that their cached locations never
+/// go stale. Once most entries of the journal are superseded, the writer
+/// compacts the journal too, so that it does not grow without bound.
+#[derive(Debug)]
+pub(crate) struct SharedBlockStore {
+    dir: PathBuf,
+    access: Access,
+}
+
+impl SharedBlockStore {
+    /// Open the store in `dir` for writing, creating it if it does not exist.
+    ///
+    /// Fails if another node has the store open for writing.
+    pub(crate) async fn open_for_writing(dir: PathBuf) -> Result<Self, SharedBlockStoreError> {
+        let io_error = |source| SharedBlockStoreError::Io {
+            dir: dir.clone(),
+            source,
+        };
+
+        tokio::fs::create_dir_all(&dir).await.map_err(io_error)?;
+        let lock = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(dir.join(WRITER_LOCK_FILE_NAME))
+            .map_err(io_error)?;
+        match lock.try_lock() {
+            Ok(()) => {}
+            Err(std::fs::TryLockError::WouldBlock) => {
+                return Err(SharedBlockStoreError::WriterLocked(dir.clone()))
+            }
+            Err(std::fs::TryLockError::Error(source)) => return Err(io_error(source)),
+        }
+
+        // A crash of the writer can leave a partially written entry at the
+        // end of the journal, which must go before entries are appended.
+        let journal_path = dir.join(JOURNAL_FILE_NAME);
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(&journal_path)
+            .await
+            .map_err(io_error)?;
+        let mut contents = vec![];
+        file.read_to_end(&mut contents).await.map_err(io_error)?;
+        let Some((header, entries)) = contents.split_at_checked(JOURNAL_HEADER_SIZE) else {
+            let generation = 0u64;
+            file.set_len(0).await.map_err(io_error)?;
+            file.seek(SeekFrom::Start(0)).await.map_err(io_error)?;
+            file.write_all(&generation.to_le_bytes())
+                .await
+                .map_err(io_error)?;
+            file.sync_data().await.map_err(io_error)?;
+
+            return Ok(Self::writer(dir, lock, file, generation, vec![]));
+        };
+
+        let generation = u64::from_le_bytes(header.try_into().unwrap());
+        let (entries, num_complete_bytes) = decode_entries(entries).unwrap_or_default();
+        if num_complete_bytes < contents.len() - JOURNAL_HEADER_SIZE {
+            info!(
+                "Discarding {} bytes of incomplete entry from {}",
+                contents.len() - JOURNAL_HEADER_SIZE - num_complete_bytes,
+                journal_path.display()
+            );
+            file.set_len((JOURNAL_HEADER_SIZE + num_complete_bytes) as u64)
+                .await
+                .map_err(io_error)?;
+        }
+        file.seek(SeekFrom::End(0)).await.map_err(io_error)?;
+
+        Ok(Self::writer(dir, lock, file, generation, entries))
+    }
+
+    fn writer(
+        dir: PathBuf,
+        lock: std::fs::File,
+        file: tokio::fs::File,
+        generation: u64,
+        entries: Vec<JournalEntry>,
+    ) -> Self {
+        let num_entries = entries.len();
+        let locations = entries
+            .into_iter()
+            .map(|entry| (entry.digest, entry.location))
+            .collect();
+
+        Self {
+            dir,
+            access: Access::Writer {
+                _lock: lock,
+                journal: Mutex::new(JournalWriter {
+                    file,
+                    generation,
+                    num_entries,
+                    locations,
+                }),
+            },
+        }
+    }
+
+    /// Open the store in `dir` read-only. The store must have been created by
+    /// the node writing to it.
+    ///
+    /// Nothing is ever written to the directory, so it can be mounted
+    /// read-only.
+    pub(crate) async fn open_read_only(dir: PathBuf) -> Result<Self, SharedBlockStoreError> {
+        let journal_path = dir.join(JOURNAL_FILE_NAME);
+        match tokio::fs::try_exists(&journal_path).await {
+            Ok(true) => {}
+            Ok(false) => return Err(SharedBlockStoreError::Uninitialized(dir)),
+            Err(source) => return Err(SharedBlockStoreError::Io { dir, source }),
+        }
+
+        Ok(Self {
+            dir,
+            access: Access::ReadOnly {
+                cache: Mutex::new(JournalCache::default()),
+            },
+        })
+    }
+
+    /// The directory holding the shared block files.
+    pub(crate) fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Return true iff this node only reads from the store.
+    pub(crate) fn is_read_only(&self) -> bool {
+        matches!(self.access, Access::ReadOnly { .. })
+    }
+
+    /// The path of the shared block file with the given index.
+    pub(crate) fn block_file_path(&self, file_index: u32) -> PathBuf {
+        let prefix = BLOCK_FILENAME_PREFIX;
+        let extension = BLOCK_FILENAME_EXTENSION;
+        self.dir.join(format!("{prefix}{file_index}.{extension}"))
+    }
+
+    fn journal_path(&self) -> PathBuf {
+        self.dir.join(JOURNAL_FILE_NAME)
+    }
+
+    /// Announce the locations of stored, relocated, or pruned blocks to the
+    /// read-only nodes. The blocks must have been persisted at these
+    /// locations.
+    pub(crate) async fn announce(&self, locations: &[(Digest, BlockFileLocation)]) -> Result<()> {
+        let Access::Writer { journal, .. } = &self.access else {
+            bail!("cannot announce blocks to read-only shared block store");
+        };
+        if locations.is_empty() {
+            return Ok(());
+        }
+
+        let entries = locations
+            .iter()
+            .map(|(digest, location)| JournalEntry {
+                digest: *digest,
+                location: *location,
+            })
+            .collect();
+
+        journal
+            .lock()
+            .await
+            .append(&self.journal_path(), entries)
+            .await
+    }
+
+    /// The latest announced location of the block with the given digest, if
+    /// any.
+    ///
+    /// Only read-only nodes track the journal. Picks up the entries appended
+    /// since the previous lookup, so that locations are never stale.
+    pub(crate) async fn locate(&self, digest: Digest) -> Result<Option<BlockFileLocation>> {
+        let Access::ReadOnly { cache } = &self.access else {
+            bail!("the writer of a shared block store does not track its journal");
+        };
+
+        let mut cache = cache.lock().await;
+        cache.refresh(&self.journal_path()).await?;
+
+        Ok(cache.locations.get(&digest).copied())
+    }
+
+    /// Wait up to `timeout` for the writer to store the block with the given
+    /// digest, and return its location.
+    ///
+    /// Must not be called while holding the global state lock, as the writer
+    /// may take a while to store the block.
+    pub(crate) async fn wait_for(
+        &self,
+        digest: Digest,
+        timeout: Duration,
+    ) -> Result<Option<BlockFileLocation>> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let location = self
+                .locate(digest)
+                .await?
+                .filter(|location| !location.is_pruned());
+            if location.is_some() || tokio::time::Instant::now() >= deadline {
+                return Ok(location);
+            }
+
+            debug!("Waiting for writer of shared block store to store block {digest:x}");
+            tokio::time::sleep(JOURNAL_POLL_INTERVAL).await;
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use itertools::Itertools;
+    use macro_rules_attr::apply;
+
+    use super::*;
+    use crate::application::config::network::Network;
+    use crate::tests::shared::files::unit_test_data_directory;
+    use crate::tests::shared_tokio_runtime;
+
+    fn location(file_index: u32, offset: u64) -> BlockFileLocation {
+        BlockFileLocation {
+            file_index,
+            offset,
+            block_length: 100,
+        }
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn store_has_single_writer() {
+        let dir = unit_test_data_directory(Network::Main)
+            .unwrap()
+            .root_dir_path()
+            .join("shared_blocks");
+
+        assert!(matches!(
+            SharedBlockStore::open_read_only(dir.clone()).await,
+            Err(SharedBlockStoreError::Uninitialized(_))
+        ));
+
+        let writer = SharedBlockStore::open_for_writing(dir.clone())
+            .await
+            .unwrap();
+        assert!(!writer.is_read_only());
+        assert!(matches!(
+            SharedBlockStore::open_for_writing(dir.clone()).await,
+            Err(SharedBlockStoreError::WriterLocked(_))
+        ));
+
+        let read_only = SharedBlockStore::open_read_only(dir.clone()).await.unwrap();
+        assert!(read_only.is_read_only());
+        assert!(read_only.announce(&[]).await.is_err());
+
+        drop(writer);
+        assert!(SharedBlockStore::open_for_writing(dir).await.is_ok());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn read_only_node_picks_up_announced_locations() {
+        let dir = unit_test_data_directory(Network::Main)
+            .unwrap()
+            .root_dir_path()
+            .join("shared_blocks");
+        let writer = SharedBlockStore::open_for_writing(dir.clone())
+            .await
+            .unwrap();
+        let read_only = SharedBlockStore::open_read_only(dir).await.unwrap();
+
+        let [a, b] = [Digest::new([1u64.into(); 5]), Digest::new([2u64.into(); 5])];
+        assert!(read_only.locate(a).await.unwrap().is_none());
+
+        writer
+            .announce(&[(a, location(0, 0)), (b, location(0, 100))])
+            .await
+            .unwrap();
+        assert_eq!(0, read_only.locate(b).await.unwrap().unwrap().file_index);
+
+        // relocation, eg by compaction, supersedes the cached location
+        writer.announce(&[(b, location(1, 0))]).await.unwrap();
+        assert_eq!(1, read_only.locate(b).await.unwrap().unwrap().file_index);
+
+        // pruned blocks are not waited for
+        writer
+            .announce(&[(a, BlockFileLocation::pruned())])
+            .await
+            .unwrap();
+        assert!(read_only
+            .wait_for(a, Duration::ZERO)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(read_only
+            .wait_for(b, Duration::ZERO)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn superseded_entries_are_compacted_away() {
+        let dir = unit_test_data_directory(Network::Main)
+            .unwrap()
+            .root_dir_path()
+            .join("shared_blocks");
+        let writer = SharedBlockStore::open_for_writing(dir.clone())
+            .await
+            .unwrap();
+        let read_only = SharedBlockStore::open_read_only(dir.clone()).await.unwrap();
+
+        let [a, b] = [Digest::new([1u64.into(); 5]), Digest::new([2u64.into(); 5])];
+        writer.announce(&[(a, location(0, 0))]).await.unwrap();
+        assert!(read_only.locate(a).await.unwrap().is_some());
+
+        let relocations = (0..MIN_NUM_ENTRIES_BEFORE_COMPACTION as u64)
+            .map(|offset| (b, location(1, offset)))
+            .collect_vec();
+        writer.announce(&relocations).await.unwrap();
+
+        let journal_length = tokio::fs::metadata(dir.join(JOURNAL_FILE_NAME))
+            .await
+            .unwrap()
+            .len();
+        let entry_length = encode_entry(&JournalEntry {
+            digest: b,
+            location: location(1, 0),
+        })
+        .unwrap()
+        .len();
+        assert_eq!(
+            (JOURNAL_HEADER_SIZE + 2 * entry_length) as u64,
+            journal_length
+        );
+
+        // a read-only node that read the journal before its compaction picks
+        // up the compacted journal, as does a writer that opens it later
+        let latest_offset = MIN_NUM_ENTRIES_BEFORE_COMPACTION as u64 - 1;
+        assert_eq!(0, read_only.locate(a).await.unwrap().unwrap().offset);
+        assert_eq!(
+            latest_offset,
+            read_only.locate(b).await.unwrap().unwrap().offset
+        );
+
+        drop(writer);
+        let writer = SharedBlockStore::open_for_writing(dir).await.unwrap();
+        writer.announce(&[(a, location(2, 0))]).await.unwrap();
+        assert_eq!(2, read_only.locate(a).await.unwrap().unwrap().file_index);
+        assert_eq!(
+            latest_offset,
+            read_only.locate(b).await.unwrap().unwrap().offset
+        );
+    }
+
+    #[test]
+    fn incomplete_trailing_entry_is_not_decoded() {
+        let entry = JournalEntry {
+            digest: Digest::default(),
+            location: location(3, 7),
+        };
+        let encoding = encode_entry(&entry).unwrap();
+        let journal = [encoding.clone(), encoding.clone()].concat();
+
+        let (entries, num_bytes) = decode_entries(&journal[..journal.len() - 1]).unwrap();
+        assert_eq!(1, entries.len());
+        assert_eq!(encoding.len(), num_bytes);
+
+        let (entries, num_bytes) = decode_entries(&journal).unwrap();
+        assert_eq!(2, entries.len());
+        assert_eq!(journal.len(), num_bytes);
+    }
+}
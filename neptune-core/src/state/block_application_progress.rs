@@ -0,0 +1,279 @@
+//! Progress of long-running block application, for monitoring catch-ups.
+//!
+//! Importing blocks from a directory, or syncing thousands of blocks from
+//! peers, holds the global state lock for long stretches. The progress is
+//! therefore published through a [`watch`] channel that can be read without
+//! that lock. Clients follow it by repeatedly asking for the update after the
+//! last [`sequence`](BlockApplicationProgress::sequence) they saw.
+
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::sync::watch;
+
+use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+
+/// The longest time a client is kept waiting for the next progress update.
+pub(crate) const MAX_PROGRESS_WAIT: Duration = Duration::from_secs(30);
+
+/// Number of most recently applied blocks the rate is measured over.
+const RATE_WINDOW: usize = 100;
+
+/// What is applying blocks in bulk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display)]
+pub enum BlockApplicationSource {
+    /// Blocks are imported from a directory, see
+    /// [`import_blocks_from_directory`](crate::application::config::cli_args::Args::import_blocks_from_directory).
+    DirectoryImport,
+
+    /// Blocks are downloaded from peers in sync mode.
+    PeerSync,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockApplicationProgress {
+    /// Incremented on every update.
+    pub sequence: u64,
+
+    /// What is applying blocks in bulk, or `None` if the node is not catching
+    /// up.
+    pub source: Option<BlockApplicationSource>,
+
+    /// Height of the tip.
+    pub current_height: BlockHeight,
+
+    /// Height at which catching up is complete, if known.
+    pub target_height: Option<BlockHeight>,
+
+    /// Number of blocks applied since catching up started.
+    pub num_applied: u64,
+
+    /// Rate at which the most recent blocks were applied.
+    pub blocks_per_second: f64,
+
+    /// When the target height is expected to be reached, at the current rate.
+    pub estimated_completion: Option<Timestamp>,
+
+    /// The peer that the latest batch of blocks was requested from, when
+    /// syncing from peers.
+    pub serving_peer: Option<SocketAddr>,
+}
+
+impl BlockApplicationProgress {
+    fn new(current_height: BlockHeight) -> Self {
+        Self {
+            sequence: 0,
+            source: None,
+            current_height,
+            target_height: None,
+            num_applied: 0,
+            blocks_per_second: 0.0,
+            estimated_completion: None,
+            serving_peer: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ProgressState {
+    progress: BlockApplicationProgress,
+
+    /// When the most recent blocks were applied, oldest first.
+    recently_applied: VecDeque<Instant>,
+}
+
+impl ProgressState {
+    fn update_estimates(&mut self) {
+        let (Some(first), Some(last)) =
+            (self.recently_applied.front(), self.recently_applied.back())
+        else {
+            return;
+        };
+        let elapsed = last.duration_since(*first).as_secs_f64();
+        if elapsed > 0.0 {
+            self.progress.blocks_per_second = (self.recently_applied.len() - 1) as f64 / elapsed;
+        }
+
+        let current_height = u64::from(self.progress.current_height);
+        self.progress.estimated_completion = self
+            .progress
+            .target_height
+            .map(u64::from)
+            .filter(|&target_height| {
+                target_height > current_height && self.progress.blocks_per_second > 0.0
+            })
+            .map(|target_height| {
+                let remaining = (target_height - current_height) as f64;
+                let millis = remaining / self.progress.blocks_per_second * 1000.0;
+                Timestamp::now() + Timestamp::millis(millis as u64)
+            });
+    }
+}
+
+/// Shared handle through which the block application pipeline publishes its
+/// progress.
+#[derive(Debug, Clone)]
+pub(crate) struct BlockApplicationProgressTracker(Arc<watch::Sender<ProgressState>>);
+
+impl BlockApplicationProgressTracker {
+    pub(crate) fn new(current_height: BlockHeight) -> Self {
+        let (sender, _) = watch::channel(ProgressState {
+            progress: BlockApplicationProgress::new(current_height),
+            recently_applied: VecDeque::with_capacity(RATE_WINDOW),
+        });
+
+        Self(Arc::new(sender))
+    }
+
+    fn update(&self, modify: impl FnOnce(&mut ProgressState)) {
+        self.0.send_modify(|state| {
+            modify(state);
+            state.progress.sequence += 1;
+        });
+    }
+
+    /// Start catching up, towards `target_height` if known.
+    pub(crate) fn start(&self, source: BlockApplicationSource, target_height: Option<BlockHeight>) {
+        self.update(|state| {
+            state.progress.source = Some(source);
+            state.progress.target_height = target_height;
+            state.progress.num_applied = 0;
+            state.progress.blocks_per_second = 0.0;
+            state.progress.estimated_completion = None;
+            state.progress.serving_peer = None;
+            state.recently_applied.clear();
+        });
+    }
+
+    /// Stop catching up.
+    pub(crate) fn finish(&self) {
+        self.update(|state| {
+            state.progress.source = None;
+            state.progress.target_height = None;
+            state.progress.estimated_completion = None;
+            state.progress.serving_peer = None;
+            state.recently_applied.clear();
+        });
+    }
+
+    /// Record that the next batch of blocks was requested from `peer`.
+    pub(crate) fn record_serving_peer(&self, peer: SocketAddr) {
+        self.update(|state| state.progress.serving_peer = Some(peer));
+    }
+
+    /// Record that a block of the given height became the tip.
+    pub(crate) fn record_applied(&self, height: BlockHeight) {
+        self.record_applied_at(height, Instant::now());
+    }
+
+    fn record_applied_at(&self, height: BlockHeight, now: Instant) {
+        self.update(|state| {
+            state.progress.current_height = height;
+            if state.progress.source.is_none() {
+                return;
+            }
+
+            state.progress.num_applied += 1;
+            if state.recently_applied.len() == RATE_WINDOW {
+                state.recently_applied.pop_front();
+            }
+            state.recently_applied.push_back(now);
+            state.update_estimates();
+        });
+    }
+
+    pub(crate) fn current(&self) -> BlockApplicationProgress {
+        self.0.borrow().progress.clone()
+    }
+
+    /// Wait for the first update after the one with sequence number
+    /// `after_sequence`, and return it. Returns the current progress if there
+    /// is no such update within `timeout`.
+    pub(crate) async fn next_after(
+        &self,
+        after_sequence: u64,
+        timeout: Duration,
+    ) -> BlockApplicationProgress {
+        let mut receiver = self.0.subscribe();
+        let _ = tokio::time::timeout(
+            timeout,
+            receiver.wait_for(|state| state.progress.sequence > after_sequence),
+        )
+        .await;
+
+        self.current()
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use macro_rules_attr::apply;
+
+    use super::*;
+    use crate::tests::shared_tokio_runtime;
+
+    #[test]
+    fn rate_and_completion_are_estimated_while_catching_up() {
+        let tracker = BlockApplicationProgressTracker::new(BlockHeight::genesis());
+
+        // Blocks applied outside of a catch-up only move the tip.
+        tracker.record_applied(1u64.into());
+        let progress = tracker.current();
+        assert_eq!(BlockHeight::from(1u64), progress.current_height);
+        assert_eq!(0, progress.num_applied);
+
+        tracker.start(BlockApplicationSource::PeerSync, Some(101u64.into()));
+        let start = Instant::now();
+        for i in 0..=10u64 {
+            tracker.record_applied_at((1 + i).into(), start + Duration::from_millis(100 * i));
+        }
+
+        let progress = tracker.current();
+        assert_eq!(Some(BlockApplicationSource::PeerSync), progress.source);
+        assert_eq!(11, progress.num_applied);
+        assert!((progress.blocks_per_second - 10.0).abs() < 1e-6);
+
+        // 90 blocks remain at 10 blocks per second.
+        let estimated_completion = progress.estimated_completion.unwrap();
+        assert!(estimated_completion > Timestamp::now() + Timestamp::seconds(8));
+        assert!(estimated_completion <= Timestamp::now() + Timestamp::seconds(9));
+
+        let peer = "127.0.0.1:9798".parse().unwrap();
+        tracker.record_serving_peer(peer);
+        assert_eq!(Some(peer), tracker.current().serving_peer);
+
+        tracker.finish();
+        let progress = tracker.current();
+        assert!(progress.source.is_none());
+        assert!(progress.estimated_completion.is_none());
+        assert!(progress.serving_peer.is_none());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn clients_wait_for_next_update() {
+        let tracker = BlockApplicationProgressTracker::new(BlockHeight::genesis());
+        let sequence = tracker.current().sequence;
+
+        // Without updates, waiting ends at the timeout.
+        let progress = tracker
+            .next_after(sequence, Duration::from_millis(10))
+            .await;
+        assert_eq!(sequence, progress.sequence);
+
+        let waiting = {
+            let tracker = tracker.clone();
+            tokio::spawn(async move { tracker.next_after(sequence, MAX_PROGRESS_WAIT).await })
+        };
+        tracker.record_applied(1u64.into());
+        let progress = waiting.await.unwrap();
+        assert!(progress.sequence > sequence);
+        assert_eq!(BlockHeight::from(1u64), progress.current_height);
+    }
+}
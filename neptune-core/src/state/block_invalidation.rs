@@ -0,0 +1,143 @@
+//! Blocks marked invalid by the operator, to simulate forks on RegTest.
+//!
+//! Marking a block invalid makes the node reject it and its descendants, and
+//! leave the chain containing it for the most canonical stored chain that does
+//! not. Removing the mark lets the node return to that chain. Downstream
+//! software can use this to test its handling of reorganizations.
+
+use std::collections::HashSet;
+
+use anyhow::bail;
+use anyhow::ensure;
+use anyhow::Result;
+use tasm_lib::prelude::Digest;
+
+use crate::protocol::consensus::block::block_header::BlockHeader;
+use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::protocol::consensus::block::difficulty_control::ProofOfWork;
+use crate::protocol::consensus::block::Block;
+use crate::state::archival_state::ArchivalState;
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BlockInvalidations {
+    /// The blocks marked invalid.
+    marked: HashSet<Digest>,
+
+    /// The marked blocks and their stored descendants.
+    rejected: HashSet<Digest>,
+}
+
+impl BlockInvalidations {
+    /// Whether `block` is, or extends, a block marked invalid.
+    ///
+    /// Descendants are known once stored, and rejected blocks are not stored,
+    /// so it suffices to check the block and its parent.
+    pub(crate) fn rejects(&self, block: &Block) -> bool {
+        !self.rejected.is_empty()
+            && (self.rejected.contains(&block.hash())
+                || self.rejected.contains(&block.header().prev_block_digest))
+    }
+
+    /// Mark the block invalid. Returns the most canonical stored block that
+    /// is not rejected.
+    pub(crate) async fn invalidate(
+        &mut self,
+        block_digest: Digest,
+        tip: &Block,
+        archival_state: &ArchivalState,
+    ) -> Result<Digest> {
+        let Some(header) = archival_state.get_block_header(block_digest).await else {
+            bail!("unknown block {block_digest:x}");
+        };
+        ensure!(
+            !header.height.is_genesis(),
+            "the genesis block cannot be invalidated"
+        );
+
+        self.marked.insert(block_digest);
+        self.refresh(header.height, tip, archival_state).await
+    }
+
+    /// Remove the mark from the block. Returns the most canonical stored
+    /// block that is not rejected.
+    pub(crate) async fn reconsider(
+        &mut self,
+        block_digest: Digest,
+        tip: &Block,
+        archival_state: &ArchivalState,
+    ) -> Result<Digest> {
+        let Some(header) = archival_state.get_block_header(block_digest).await else {
+            bail!("unknown block {block_digest:x}");
+        };
+
+        self.marked.remove(&block_digest);
+        self.refresh(header.height, tip, archival_state).await
+    }
+
+    /// Recompute the rejected blocks, walking the stored blocks upwards from
+    /// below the lowest of the marked blocks and the block at `changed_height`,
+    /// and find the most canonical block that is not rejected. Ties are
+    /// resolved in favor of the tip, to avoid needless reorganizations.
+    async fn refresh(
+        &mut self,
+        changed_height: BlockHeight,
+        tip: &Block,
+        archival_state: &ArchivalState,
+    ) -> Result<Digest> {
+        let mut lowest_height = changed_height;
+        for digest in &self.marked {
+            if let Some(header) = archival_state.get_block_header(*digest).await {
+                lowest_height = lowest_height.min(header.height);
+            }
+        }
+
+        // No block below the lowest marked block can descend from one.
+        let Some(floor) = lowest_height.previous() else {
+            bail!("the genesis block cannot be invalidated");
+        };
+
+        self.rejected.clear();
+        let mut best: Option<(Digest, ProofOfWork)> = None;
+        let mut is_better = |digest: Digest, header: &BlockHeader| {
+            if best.is_none_or(|(_, pow)| header.cumulative_proof_of_work > pow) {
+                best = Some((digest, header.cumulative_proof_of_work));
+            }
+        };
+
+        let mut valid = HashSet::new();
+        for digest in archival_state.block_height_to_block_digests(floor).await {
+            if let Some(header) = archival_state.get_block_header(digest).await {
+                is_better(digest, &header);
+                valid.insert(digest);
+            }
+        }
+
+        let mut height = floor;
+        while !valid.is_empty() {
+            height = height.next();
+            let mut valid_at_height = HashSet::new();
+            for digest in archival_state.block_height_to_block_digests(height).await {
+                let Some(header) = archival_state.get_block_header(digest).await else {
+                    continue;
+                };
+                if self.marked.contains(&digest) || !valid.contains(&header.prev_block_digest) {
+                    self.rejected.insert(digest);
+                    continue;
+                }
+
+                is_better(digest, &header);
+                valid_at_height.insert(digest);
+            }
+            valid = valid_at_height;
+        }
+
+        let Some((best_digest, best_pow)) = best else {
+            bail!("no stored block at height {floor}");
+        };
+        if !self.rejects(tip) && tip.header().cumulative_proof_of_work >= best_pow {
+            return Ok(tip.hash());
+        }
+
+        Ok(best_digest)
+    }
+}
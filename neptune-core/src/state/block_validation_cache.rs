@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Digest;
+
+use crate::protocol::consensus::block::block_validation_error::BlockValidationError;
+
+/// Maximum number of validation results held by the [`BlockValidationCache`].
+/// Entries are small, so this comfortably covers repeated reorgs.
+pub(crate) const BLOCK_VALIDATION_CACHE_CAPACITY: usize = 10_000;
+
+/// Counters describing the effectiveness of the [`BlockValidationCache`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct BlockValidationCacheMetrics {
+    /// Number of lookups that found a cached result.
+    pub hits: u64,
+
+    /// Number of lookups that did not find a cached result.
+    pub misses: u64,
+
+    /// Number of results removed to make room for new ones.
+    pub evictions: u64,
+
+    /// Number of results currently cached.
+    pub len: usize,
+
+    /// Maximum number of results cached.
+    pub capacity: usize,
+}
+
+/// Remembers the outcome of block validation, keyed by block digest, so that
+/// blocks received repeatedly during reorg churn are only verified once.
+///
+/// Because the block digest commits to the entire block, including its proof
+/// and its predecessor's digest, the outcome of validation is determined by
+/// the digest, with the following exceptions that callers must respect:
+///
+///  - results are only valid when the block was validated against the
+///    predecessor it commits to;
+///  - [`BlockValidationError::FutureDating`] depends on the current time, so
+///    such results are never cached.
+///
+/// When full, the oldest result is evicted.
+#[derive(Debug, Clone)]
+pub(crate) struct BlockValidationCache {
+    results: HashMap<Digest, Result<(), BlockValidationError>>,
+    insertion_order: VecDeque<Digest>,
+    capacity: usize,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl Default for BlockValidationCache {
+    fn default() -> Self {
+        Self::new(BLOCK_VALIDATION_CACHE_CAPACITY)
+    }
+}
+
+impl BlockValidationCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            results: HashMap::new(),
+            insertion_order: VecDeque::new(),
+            capacity,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
+    }
+
+    /// Return the cached validation result for the block with this digest,
+    /// if any.
+    pub(crate) fn get(&mut self, digest: Digest) -> Option<Result<(), BlockValidationError>> {
+        let result = self.results.get(&digest).copied();
+        if result.is_some() {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+
+        result
+    }
+
+    /// Record the validation result for the block with this digest. Results
+    /// that depend on the current time are ignored.
+    pub(crate) fn insert(&mut self, digest: Digest, result: Result<(), BlockValidationError>) {
+        if result == Err(BlockValidationError::FutureDating) || self.capacity == 0 {
+            return;
+        }
+
+        if self.results.insert(digest, result).is_some() {
+            return;
+        }
+
+        self.insertion_order.push_back(digest);
+        while self.insertion_order.len() > self.capacity {
+            if let Some(oldest) = self.insertion_order.pop_front() {
+                self.results.remove(&oldest);
+                self.evictions += 1;
+            }
+        }
+    }
+
+    pub(crate) fn metrics(&self) -> BlockValidationCacheMetrics {
+        BlockValidationCacheMetrics {
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+            len: self.results.len(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use rand::random;
+
+    use super::*;
+
+    #[test]
+    fn cached_results_are_returned_and_counted() {
+        let mut cache = BlockValidationCache::new(10);
+        let valid: Digest = random();
+        let invalid: Digest = random();
+
+        assert_eq!(None, cache.get(valid));
+        cache.insert(valid, Ok(()));
+        cache.insert(invalid, Err(BlockValidationError::ProofValidity));
+
+        assert_eq!(Some(Ok(())), cache.get(valid));
+        assert_eq!(
+            Some(Err(BlockValidationError::ProofValidity)),
+            cache.get(invalid)
+        );
+
+        let metrics = cache.metrics();
+        assert_eq!(2, metrics.hits);
+        assert_eq!(1, metrics.misses);
+        assert_eq!(2, metrics.len);
+    }
+
+    #[test]
+    fn future_dated_blocks_are_not_cached() {
+        let mut cache = BlockValidationCache::new(10);
+        let digest: Digest = random();
+        cache.insert(digest, Err(BlockValidationError::FutureDating));
+        assert_eq!(None, cache.get(digest));
+        assert_eq!(0, cache.metrics().len);
+    }
+
+    #[test]
+    fn oldest_results_are_evicted_when_full() {
+        let mut cache = BlockValidationCache::new(2);
+        let digests: [Digest; 3] = random();
+        for digest in digests {
+            cache.insert(digest, Ok(()));
+        }
+
+        assert_eq!(None, cache.get(digests[0]));
+        assert_eq!(Some(Ok(())), cache.get(digests[1]));
+        assert_eq!(Some(Ok(())), cache.get(digests[2]));
+        assert_eq!(1, cache.metrics().evictions);
+        assert_eq!(2, cache.metrics().len);
+    }
+
+    #[test]
+    fn reinserting_does_not_duplicate_entries() {
+        let mut cache = BlockValidationCache::new(2);
+        let digest: Digest = random();
+        cache.insert(digest, Ok(()));
+        cache.insert(digest, Ok(()));
+        cache.insert(random(), Ok(()));
+        assert_eq!(Some(Ok(())), cache.get(digest));
+        assert_eq!(0, cache.metrics().evictions);
+    }
+}
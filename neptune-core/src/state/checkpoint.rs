@@ -0,0 +1,267 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Digest;
+use tasm_lib::prelude::Tip5;
+use tasm_lib::triton_vm::prelude::BFieldCodec;
+use tasm_lib::triton_vm::prelude::BFieldElement;
+use tasm_lib::triton_vm::prelude::PublicInput;
+use tasm_lib::triton_vm::proof::Claim;
+use tasm_lib::twenty_first::bfe_vec;
+
+use crate::api::tx_initiation::error::CreateProofError;
+use crate::application::config::network::Network;
+use crate::application::triton_vm_job_queue::TritonVmJobQueue;
+use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::protocol::consensus::block::difficulty_control::ProofOfWork;
+use crate::protocol::consensus::block::Block;
+use crate::protocol::consensus::transaction::lock_script::LockScript;
+use crate::protocol::consensus::transaction::lock_script::LockScriptAndWitness;
+use crate::protocol::consensus::transaction::Proof;
+use crate::protocol::proof_abstractions::tasm::program::TritonVmProofJobOptions;
+use crate::protocol::proof_abstractions::verifier;
+
+/// Number of signed checkpoints retained by the node, most recent first.
+pub(crate) const MAX_NUM_RETAINED_CHECKPOINTS: usize = 16;
+
+/// Domain separator for checkpoint digests, so that a checkpoint signature
+/// cannot be mistaken for any other use of a hash lock.
+const CHECKPOINT_DOMAIN_SEPARATOR: u64 = 0x6368_6b70_7430_3031;
+
+/// A summary of the canonical chain at a given height.
+///
+/// Light clients bootstrapping from several independent nodes can compare
+/// their [`SignedCheckpoint`]s for the same height. Because checkpoints are
+/// only produced at heights that are multiples of the configured interval,
+/// independent nodes checkpoint the same blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub network: Network,
+    pub block_digest: Digest,
+    pub height: BlockHeight,
+    pub cumulative_proof_of_work: ProofOfWork,
+
+    /// Hash of the mutator set accumulator after applying the block.
+    pub mutator_set_hash: Digest,
+}
+
+impl Checkpoint {
+    /// Summarize the chain at `block`. Returns `None` if the mutator set
+    /// accumulator after the block cannot be computed, which is never the
+    /// case for valid blocks.
+    pub fn from_block(block: &Block, network: Network) -> Option<Self> {
+        let mutator_set_hash = block.mutator_set_accumulator_after().ok()?.hash();
+
+        Some(Self {
+            network,
+            block_digest: block.hash(),
+            height: block.header().height,
+            cumulative_proof_of_work: block.header().cumulative_proof_of_work,
+            mutator_set_hash,
+        })
+    }
+
+    /// The digest that is signed.
+    pub fn digest(&self) -> Digest {
+        Tip5::hash_varlen(
+            &[
+                bfe_vec![CHECKPOINT_DOMAIN_SEPARATOR, self.network.id()],
+                self.block_digest.encode(),
+                self.height.encode(),
+                self.cumulative_proof_of_work.encode(),
+                self.mutator_set_hash.encode(),
+            ]
+            .concat(),
+        )
+    }
+
+    fn public_input(&self) -> Vec<BFieldElement> {
+        self.digest().reversed().values().to_vec()
+    }
+}
+
+/// A [`Checkpoint`] signed by a node.
+///
+/// The signature is a STARK proof that the signer knows a preimage of its
+/// public key, with the checkpoint digest as public input. Specifically, it is
+/// a proof of the standard hash lock, see
+/// [`LockScript::standard_hash_lock_from_after_image`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedCheckpoint {
+    pub checkpoint: Checkpoint,
+
+    /// The public key of the signer, which identifies the node.
+    pub signer: Digest,
+
+    pub signature: Proof,
+}
+
+impl SignedCheckpoint {
+    /// Sign `checkpoint` with the secret whose hash is the signer's public key.
+    ///
+    /// Produces a proof, which takes a while.
+    pub(crate) async fn sign(
+        checkpoint: Checkpoint,
+        secret: Digest,
+        triton_vm_job_queue: Arc<TritonVmJobQueue>,
+        proof_job_options: TritonVmProofJobOptions,
+    ) -> Result<Self, CreateProofError> {
+        let signature = LockScriptAndWitness::standard_hash_lock_from_preimage(secret)
+            .prove(
+                PublicInput::new(checkpoint.public_input()),
+                triton_vm_job_queue,
+                proof_job_options,
+            )
+            .await?;
+
+        Ok(Self {
+            checkpoint,
+            signer: secret.hash(),
+            signature,
+        })
+    }
+
+    /// The claim proven by the signature.
+    pub fn claim(&self) -> Claim {
+        let program_digest = LockScript::standard_hash_lock_from_after_image(self.signer).hash();
+        Claim::new(program_digest).with_input(self.checkpoint.public_input())
+    }
+
+    /// Verify that the checkpoint is for `network` and was signed by
+    /// [`Self::signer`].
+    ///
+    /// On networks that use mock proofs, any valid mock proof is accepted.
+    ///
+    /// This says nothing about whether the checkpoint describes the
+    /// canonical chain. Light clients establish that by comparing checkpoints
+    /// from several independent signers.
+    pub async fn verify(&self, network: Network) -> bool {
+        self.checkpoint.network == network
+            && verifier::verify(self.claim(), self.signature.clone(), network).await
+    }
+}
+
+/// The checkpoints produced by this node, indexed by height.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Checkpoints(BTreeMap<BlockHeight, SignedCheckpoint>);
+
+impl Checkpoints {
+    /// Store a signed checkpoint, replacing any previous checkpoint at the
+    /// same height, eg because of a reorganization. Only the
+    /// [`MAX_NUM_RETAINED_CHECKPOINTS`] highest checkpoints are retained.
+    pub(crate) fn insert(&mut self, signed_checkpoint: SignedCheckpoint) {
+        self.0
+            .insert(signed_checkpoint.checkpoint.height, signed_checkpoint);
+        while self.0.len() > MAX_NUM_RETAINED_CHECKPOINTS {
+            self.0.pop_first();
+        }
+    }
+
+    /// The checkpoint at `height`, if any.
+    pub(crate) fn get(&self, height: BlockHeight) -> Option<&SignedCheckpoint> {
+        self.0.get(&height)
+    }
+
+    /// All retained checkpoints, most recent first.
+    pub(crate) fn all(&self) -> Vec<SignedCheckpoint> {
+        self.0.values().rev().cloned().collect()
+    }
+}
+
+/// The height of the most recent checkpoint, given the current tip height and
+/// checkpoint interval. Returns `None` if no checkpoint height has been
+/// reached, ie, before the first interval has passed.
+pub(crate) fn checkpoint_height(tip_height: BlockHeight, interval: u64) -> Option<BlockHeight> {
+    let tip_height: u64 = tip_height.into();
+    let height = tip_height - tip_height % interval;
+    (height > 0).then(|| height.into())
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use macro_rules_attr::apply;
+    use num_traits::Zero;
+    use rand::random;
+
+    use super::*;
+    use crate::tests::shared_tokio_runtime;
+
+    fn checkpoint(network: Network, height: u64) -> Checkpoint {
+        Checkpoint {
+            network,
+            block_digest: random(),
+            height: height.into(),
+            cumulative_proof_of_work: ProofOfWork::zero(),
+            mutator_set_hash: random(),
+        }
+    }
+
+    #[test]
+    fn checkpoint_heights_are_multiples_of_interval() {
+        assert_eq!(None, checkpoint_height(0u64.into(), 100));
+        assert_eq!(None, checkpoint_height(99u64.into(), 100));
+        assert_eq!(Some(100u64.into()), checkpoint_height(100u64.into(), 100));
+        assert_eq!(Some(200u64.into()), checkpoint_height(299u64.into(), 100));
+    }
+
+    #[test]
+    fn digest_commits_to_network() {
+        let checkpoint_main = checkpoint(Network::Main, 100);
+        let checkpoint_testnet = Checkpoint {
+            network: Network::Testnet(0),
+            ..checkpoint_main
+        };
+        assert_ne!(checkpoint_main.digest(), checkpoint_testnet.digest());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn signature_verifies_only_for_signed_checkpoint_and_signer() {
+        // a network with real proofs, since mock proofs prove nothing.
+        let network = Network::Main;
+        let secret: Digest = random();
+        let options = TritonVmProofJobOptions::default_with_network(network);
+
+        let signed = SignedCheckpoint::sign(
+            checkpoint(network, 100),
+            secret,
+            TritonVmJobQueue::get_instance(),
+            options,
+        )
+        .await
+        .unwrap();
+        assert_eq!(secret.hash(), signed.signer);
+        assert!(signed.verify(network).await);
+        assert!(!signed.verify(Network::Testnet(0)).await);
+
+        let mut other_signer = signed.clone();
+        other_signer.signer = random();
+        assert!(!other_signer.verify(network).await);
+
+        let mut other_checkpoint = signed.clone();
+        other_checkpoint.checkpoint.height = 200u64.into();
+        assert!(!other_checkpoint.verify(network).await);
+    }
+
+    #[test]
+    fn only_highest_checkpoints_are_retained() {
+        let mut checkpoints = Checkpoints::default();
+        for height in 1..=(MAX_NUM_RETAINED_CHECKPOINTS as u64 + 2) {
+            checkpoints.insert(SignedCheckpoint {
+                checkpoint: checkpoint(Network::Main, height),
+                signer: random(),
+                signature: Proof::mock(false),
+            });
+        }
+
+        let all = checkpoints.all();
+        assert_eq!(MAX_NUM_RETAINED_CHECKPOINTS, all.len());
+        assert_eq!(
+            BlockHeight::from(MAX_NUM_RETAINED_CHECKPOINTS as u64 + 2),
+            all[0].checkpoint.height
+        );
+        assert!(checkpoints.get(1u64.into()).is_none());
+    }
+}
@@ -21,6 +21,23 @@ pub struct BlockFileLocation {
     pub block_length: usize,
 }
 
+impl BlockFileLocation {
+    /// The location of a block whose body was pruned and is no longer stored
+    /// on disk. No block serializes to zero bytes, so this location cannot
+    /// refer to a stored block.
+    pub(crate) fn pruned() -> Self {
+        Self {
+            file_index: 0,
+            offset: 0,
+            block_length: 0,
+        }
+    }
+
+    pub(crate) fn is_pruned(&self) -> bool {
+        self.block_length == 0
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BlockRecord {
     pub block_header: BlockHeader,
@@ -44,6 +61,12 @@ impl BlockRecord {
         // guaranteed to not overflow.
         self.min_aocl_index + self.num_additions - 1
     }
+
+    /// Returns true iff the block body and proof were deleted from disk, such
+    /// that only the header is retained.
+    pub fn body_is_pruned(&self) -> bool {
+        self.file_location.is_pruned()
+    }
 }
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
@@ -97,6 +120,26 @@ pub struct LastFileRecord {
     pub last_file: u32,
 }
 
+/// Progress of the pruning of abandoned fork blocks, see
+/// [`ArchivalState::prune_fork_blocks`](crate::state::archival_state::ArchivalState::prune_fork_blocks).
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct ForkPruningRecord {
+    /// All fork blocks at or below this height have been pruned.
+    pub pruned_up_to: BlockHeight,
+
+    /// The number of fork blocks whose bodies were pruned, since the database
+    /// was created. Includes canonical blocks pruned in headers-only mode.
+    pub num_pruned_blocks: u64,
+
+    /// The number of bytes freed from the block files, since the database was
+    /// created.
+    pub num_bytes_reclaimed: u64,
+
+    /// The block file whose compacted replacement has been written and
+    /// indexed, but may not yet have been moved into place.
+    pub(crate) pending_compaction: Option<u32>,
+}
+
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub enum BlockIndexKey {
     Block(Digest),       // points to block headers and file locations
@@ -107,6 +150,10 @@ pub enum BlockIndexKey {
     // Tip-hash could also be fetched from archival block MMR instead. Maybe
     // this key is superfluous?
     BlockTipDigest, // points to block digest of most canonical block known
+
+    ForkPruning, // points to progress of fork-block pruning
+
+    CanonicalPruning, // points to progress of headers-only pruning
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -116,6 +163,8 @@ pub enum BlockIndexValue {
     Height(Vec<Digest>),
     LastFile(LastFileRecord),
     BlockTipDigest(Digest),
+    ForkPruning(ForkPruningRecord),
+    CanonicalPruning(BlockHeight),
 }
 
 impl BlockIndexValue {
@@ -153,6 +202,20 @@ impl BlockIndexValue {
             _ => panic!("Requested BlockTipDigest, found {:?}", self),
         }
     }
+
+    pub fn as_fork_pruning_record(&self) -> ForkPruningRecord {
+        match self {
+            BlockIndexValue::ForkPruning(rec) => rec.to_owned(),
+            _ => panic!("Requested ForkPruning, found {:?}", self),
+        }
+    }
+
+    pub fn as_canonical_pruning_height(&self) -> BlockHeight {
+        match self {
+            BlockIndexValue::CanonicalPruning(height) => height.to_owned(),
+            _ => panic!("Requested CanonicalPruning, found {:?}", self),
+        }
+    }
 }
 
 #[derive(Clone)]
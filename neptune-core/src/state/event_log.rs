@@ -0,0 +1,489 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use anyhow::Context;
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+use tokio::sync::Notify;
+use tracing::error;
+use tracing::warn;
+
+use crate::application::plugin_hooks::HookEvent;
+
+/// Number of events retained by the node for replay, most recent last.
+pub(crate) const MAX_NUM_RETAINED_EVENTS: usize = 10_000;
+
+/// Number of events the events file may hold before it is rewritten with only
+/// the retained events.
+const MAX_NUM_PERSISTED_EVENTS: usize = 2 * MAX_NUM_RETAINED_EVENTS;
+
+/// Maximum number of events returned by a single replay.
+pub const MAX_NUM_REPLAYED_EVENTS: usize = 1_000;
+
+/// An event with its position in the node's event log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SequencedEvent {
+    /// Increases by one with every recorded event, also across restarts. The
+    /// first event has sequence number 1.
+    pub sequence_number: u64,
+
+    pub event: HookEvent,
+}
+
+/// The events caused by new tips, alerts, and cancellations, numbered in the
+/// order in which they were recorded, and the sequence number up to which each
+/// subscriber has acknowledged them.
+///
+/// Each event is delivered to a subscriber at least once: a replay returns the
+/// events after the subscriber's cursor, which advances only once the
+/// subscriber acknowledges them. An event that equals a
+/// retained one, eg because a reorganization made its block canonical again,
+/// is not recorded twice. Events and cursors are persisted in the data
+/// directory, so that neither is lost across restarts.
+///
+/// Files are written by a background task, so that neither recording nor
+/// replaying events waits for the disk while the log is locked.
+#[derive(Debug, Default)]
+pub(crate) struct EventLog {
+    events: VecDeque<SequencedEvent>,
+    retained: HashSet<HookEvent>,
+    last_sequence_number: u64,
+    cursors: HashMap<String, u64>,
+
+    /// Number of events in the events file, including those that are no
+    /// longer retained.
+    num_persisted_events: usize,
+
+    /// Passes writes to the task that persists events and cursors. Nothing is
+    /// persisted if unset.
+    file_writer: Option<mpsc::UnboundedSender<FileOperation>>,
+
+    /// Wakes the tasks waiting for new events.
+    recorded: Arc<Notify>,
+}
+
+#[derive(Debug)]
+enum FileOperation {
+    AppendEvents(String),
+    RewriteEvents(String),
+    WriteCursors {
+        contents: String,
+        written: oneshot::Sender<Result<()>>,
+    },
+}
+
+/// A subscriber's cursor, as advanced by [`EventLog::acknowledge`].
+#[derive(Debug)]
+#[must_use]
+pub(crate) struct Acknowledgement {
+    cursor_written: Option<oneshot::Receiver<Result<()>>>,
+}
+
+impl Acknowledgement {
+    /// Wait until the subscriber's cursor is persisted. If it could not be
+    /// persisted, the acknowledged events are replayed to the subscriber
+    /// again after a restart.
+    ///
+    /// Must not be awaited while holding the event log's lock.
+    pub(crate) async fn persisted(self) -> Result<()> {
+        if let Some(cursor_written) = self.cursor_written {
+            cursor_written
+                .await
+                .map_err(|_| anyhow!("Event log writer stopped"))??;
+        }
+
+        Ok(())
+    }
+}
+
+impl EventLog {
+    /// Read the event log and the subscriber cursors persisted in the given
+    /// files, if they exist, and start the task that persists them from now
+    /// on.
+    ///
+    /// Only the most recent events are retained. The events file is rewritten
+    /// if it holds more than these, so that it does not grow without bound.
+    pub(crate) async fn load(events_file: PathBuf, cursors_file: PathBuf) -> Result<Self> {
+        let mut event_log = Self::default();
+        let mut num_persisted_events = 0;
+        if let Some(contents) = read_if_exists(&events_file).await? {
+            for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+                // A line that was only partially written, eg because the node
+                // crashed, is skipped.
+                let Ok(event) = serde_json::from_str::<SequencedEvent>(line) else {
+                    warn!("Skipping invalid event in {}", events_file.display());
+                    continue;
+                };
+                num_persisted_events += 1;
+                event_log.retain(event);
+            }
+        }
+
+        event_log.cursors = match read_if_exists(&cursors_file).await? {
+            Some(contents) => serde_json::from_str(&contents)
+                .with_context(|| format!("Invalid event cursors in {}", cursors_file.display()))?,
+            None => HashMap::new(),
+        };
+
+        // Numbering continues after the highest known sequence number, even
+        // if the events file was removed.
+        event_log.last_sequence_number = event_log
+            .events
+            .back()
+            .map(|event| event.sequence_number)
+            .into_iter()
+            .chain(event_log.cursors.values().copied())
+            .max()
+            .unwrap_or_default();
+
+        if num_persisted_events > event_log.events.len() {
+            rewrite(&events_file, &event_log.serialized_events()?).await?;
+        }
+        event_log.num_persisted_events = event_log.events.len();
+
+        let (file_writer, file_operations) = mpsc::unbounded_channel();
+        tokio::spawn(perform_file_operations(
+            events_file,
+            cursors_file,
+            file_operations,
+        ));
+        event_log.file_writer = Some(file_writer);
+
+        Ok(event_log)
+    }
+
+    /// The sequence number of the most recent event, or zero if no event was
+    /// ever recorded.
+    pub(crate) fn last_sequence_number(&self) -> u64 {
+        self.last_sequence_number
+    }
+
+    /// Notified whenever new events are recorded.
+    ///
+    /// Only tasks that are already waiting are woken, so a task should enable
+    /// its [`Notify::notified`] future before it replays events.
+    pub(crate) fn recorded(&self) -> Arc<Notify> {
+        self.recorded.clone()
+    }
+
+    /// Number the events that are not retained already, and record them.
+    ///
+    /// The events are persisted in the background, and waiting tasks are
+    /// notified, see [`Self::recorded`].
+    pub(crate) fn record(&mut self, events: Vec<HookEvent>) {
+        let mut lines = String::new();
+        let mut num_new_events = 0;
+        for event in events {
+            if self.retained.contains(&event) {
+                continue;
+            }
+
+            self.last_sequence_number += 1;
+            let event = SequencedEvent {
+                sequence_number: self.last_sequence_number,
+                event,
+            };
+            match serde_json::to_string(&event) {
+                Ok(line) => {
+                    lines.push_str(&line);
+                    lines.push('\n');
+                    num_new_events += 1;
+                }
+                Err(e) => error!("Could not serialize event {}: {e}", event.sequence_number),
+            }
+
+            self.retain(event);
+        }
+
+        if num_new_events > 0 {
+            self.recorded.notify_waiters();
+        }
+
+        let Some(file_writer) = &self.file_writer else {
+            return;
+        };
+        if num_new_events == 0 {
+            return;
+        }
+
+        // Rewriting the file with only the retained events keeps it from
+        // growing without bound.
+        let operation = if self.num_persisted_events + num_new_events > MAX_NUM_PERSISTED_EVENTS {
+            match self.serialized_events() {
+                Ok(contents) => {
+                    self.num_persisted_events = self.events.len();
+                    FileOperation::RewriteEvents(contents)
+                }
+                Err(e) => {
+                    error!("Could not serialize events: {e}");
+                    return;
+                }
+            }
+        } else {
+            self.num_persisted_events += num_new_events;
+            FileOperation::AppendEvents(lines)
+        };
+        if file_writer.send(operation).is_err() {
+            error!("Could not persist events: event log writer stopped");
+        }
+    }
+
+    /// The retained events that `subscriber` has not acknowledged yet and
+    /// that come after `since`, oldest first and at most
+    /// [MAX_NUM_REPLAYED_EVENTS] of them.
+    ///
+    /// The events are replayed to the subscriber again until it acknowledges
+    /// them, see [`Self::acknowledge`].
+    pub(crate) fn replay(&self, subscriber: &str, since: u64) -> Vec<SequencedEvent> {
+        let cursor = self.cursors.get(subscriber).copied().unwrap_or_default();
+        let since = since.max(cursor);
+        self.events
+            .iter()
+            .filter(|event| event.sequence_number > since)
+            .take(MAX_NUM_REPLAYED_EVENTS)
+            .cloned()
+            .collect()
+    }
+
+    /// Advance the cursor of `subscriber` to `sequence_number`, such that the
+    /// events up to and including it are not replayed to the subscriber
+    /// again. The cursor never moves back, nor past the most recent event.
+    ///
+    /// The cursor is persisted in the background, see
+    /// [`Acknowledgement::persisted`].
+    pub(crate) fn acknowledge(
+        &mut self,
+        subscriber: &str,
+        sequence_number: u64,
+    ) -> Acknowledgement {
+        let cursor = self.cursors.get(subscriber).copied().unwrap_or_default();
+        let sequence_number = sequence_number.min(self.last_sequence_number);
+        if sequence_number <= cursor {
+            return Acknowledgement {
+                cursor_written: None,
+            };
+        }
+        self.cursors.insert(subscriber.to_string(), sequence_number);
+
+        let cursor_written = self.file_writer.as_ref().map(|file_writer| {
+            let (written, cursor_written) = oneshot::channel();
+            let contents = serde_json::to_string(&self.cursors).map_err(anyhow::Error::from);
+            match contents {
+                Ok(contents) => {
+                    // If the writer stopped, dropping `written` reports it.
+                    let _ = file_writer.send(FileOperation::WriteCursors { contents, written });
+                }
+                Err(e) => {
+                    let _ = written.send(Err(e));
+                }
+            }
+            cursor_written
+        });
+
+        Acknowledgement { cursor_written }
+    }
+
+    fn retain(&mut self, event: SequencedEvent) {
+        self.retained.insert(event.event.clone());
+        self.events.push_back(event);
+        if self.events.len() > MAX_NUM_RETAINED_EVENTS {
+            if let Some(evicted) = self.events.pop_front() {
+                self.retained.remove(&evicted.event);
+            }
+        }
+    }
+
+    fn serialized_events(&self) -> Result<String> {
+        Ok(self
+            .events
+            .iter()
+            .map(|event| serde_json::to_string(event).map(|line| line + "\n"))
+            .collect::<Result<String, _>>()?)
+    }
+}
+
+/// Persist events and cursors, in the order in which they were recorded.
+/// Stops when the event log is dropped.
+async fn perform_file_operations(
+    events_file: PathBuf,
+    cursors_file: PathBuf,
+    mut file_operations: mpsc::UnboundedReceiver<FileOperation>,
+) {
+    while let Some(operation) = file_operations.recv().await {
+        match operation {
+            FileOperation::AppendEvents(lines) => {
+                if let Err(e) = append(&events_file, &lines).await {
+                    error!("Could not persist events: {e:#}");
+                }
+            }
+            FileOperation::RewriteEvents(contents) => {
+                if let Err(e) = rewrite(&events_file, &contents).await {
+                    error!("Could not persist events: {e:#}");
+                }
+            }
+            FileOperation::WriteCursors { contents, written } => {
+                let _ = written.send(rewrite(&cursors_file, &contents).await);
+            }
+        }
+    }
+}
+
+async fn read_if_exists(path: &Path) -> Result<Option<String>> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Could not read {}", path.display())),
+    }
+}
+
+async fn append(path: &Path, lines: &str) -> Result<()> {
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await
+        .with_context(|| format!("Could not open {}", path.display()))?;
+    file.write_all(lines.as_bytes()).await?;
+    file.flush().await?;
+
+    Ok(())
+}
+
+/// Replace the file's contents, such that a crash leaves either the old or the
+/// new contents.
+async fn rewrite(path: &Path, contents: &str) -> Result<()> {
+    let temporary = path.with_extension("tmp");
+    tokio::fs::write(&temporary, contents)
+        .await
+        .with_context(|| format!("Could not write {}", temporary.display()))?;
+    tokio::fs::rename(&temporary, path)
+        .await
+        .with_context(|| format!("Could not write {}", path.display()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use macro_rules_attr::apply;
+    use tasm_lib::prelude::Digest;
+
+    use super::*;
+    use crate::application::config::network::Network;
+    use crate::protocol::proof_abstractions::timestamp::Timestamp;
+    use crate::tests::shared::files::unit_test_data_directory;
+    use crate::tests::shared_tokio_runtime;
+
+    fn new_block(height: u64) -> HookEvent {
+        HookEvent::NewBlock {
+            block_digest: Digest::default(),
+            height: height.into(),
+            timestamp: Timestamp::now(),
+        }
+    }
+
+    async fn event_log(data_dir: &Path) -> EventLog {
+        EventLog::load(
+            data_dir.join("wallet_events.jsonl"),
+            data_dir.join("event_cursors.json"),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn events_are_numbered_and_deduplicated() {
+        let mut event_log = EventLog::default();
+        let first = new_block(1);
+        event_log.record(vec![first.clone(), new_block(2)]);
+        event_log.record(vec![first, new_block(3)]);
+
+        assert_eq!(3, event_log.last_sequence_number());
+        let events = event_log.replay("alice", 0);
+        assert_eq!(
+            vec![1, 2, 3],
+            events.iter().map(|e| e.sequence_number).collect::<Vec<_>>()
+        );
+        assert_eq!(new_block(3).kind(), events[2].event.kind());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn events_are_replayed_until_acknowledged() {
+        let mut event_log = EventLog::default();
+        event_log.record(vec![new_block(1), new_block(2)]);
+
+        assert_eq!(2, event_log.replay("alice", 0).len());
+        assert_eq!(2, event_log.replay("alice", 0).len());
+        assert_eq!(1, event_log.replay("bob", 1).len());
+
+        event_log.acknowledge("alice", 1).persisted().await.unwrap();
+        assert_eq!(1, event_log.replay("alice", 0).len());
+
+        // the cursor never moves back, nor past the most recent event
+        event_log.acknowledge("alice", 0).persisted().await.unwrap();
+        event_log.acknowledge("bob", 100).persisted().await.unwrap();
+        assert_eq!(1, event_log.replay("alice", 0).len());
+
+        event_log.record(vec![new_block(3)]);
+        let events = event_log.replay("bob", 0);
+        assert_eq!(
+            vec![3],
+            events.iter().map(|e| e.sequence_number).collect::<Vec<_>>()
+        );
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn events_and_cursors_survive_restarts() {
+        let data_dir = unit_test_data_directory(Network::Main).unwrap();
+        let root = data_dir.root_dir_path();
+        tokio::fs::create_dir_all(&root).await.unwrap();
+
+        let mut before = event_log(&root).await;
+        before.record(vec![new_block(1), new_block(2)]);
+        assert_eq!(1, before.replay("alice", 1).len());
+        before.acknowledge("alice", 2).persisted().await.unwrap();
+
+        let mut after = event_log(&root).await;
+        assert_eq!(2, after.last_sequence_number());
+        assert!(after.replay("alice", 0).is_empty());
+        assert_eq!(2, after.replay("bob", 0).len());
+
+        after.record(vec![new_block(3)]);
+        assert_eq!(3, after.last_sequence_number());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn events_file_is_compacted() {
+        let data_dir = unit_test_data_directory(Network::Main).unwrap();
+        let root = data_dir.root_dir_path();
+        tokio::fs::create_dir_all(&root).await.unwrap();
+
+        let mut event_log = event_log(&root).await;
+        for height in 0..MAX_NUM_PERSISTED_EVENTS as u64 + 1 {
+            event_log.record(vec![new_block(height)]);
+        }
+
+        // Persisting the cursor waits for all earlier writes.
+        let last_sequence_number = event_log.last_sequence_number();
+        event_log
+            .acknowledge("alice", last_sequence_number)
+            .persisted()
+            .await
+            .unwrap();
+
+        let contents = tokio::fs::read_to_string(root.join("wallet_events.jsonl"))
+            .await
+            .unwrap();
+        assert_eq!(MAX_NUM_RETAINED_EVENTS, contents.lines().count());
+    }
+}
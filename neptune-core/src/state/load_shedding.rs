@@ -0,0 +1,299 @@
+use std::fmt::Display;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::info;
+use tracing::warn;
+
+use crate::application::config::cli_args;
+use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+
+/// Number of percentage points that all resources must be below their
+/// threshold for the pressure to be considered subsided. Prevents flapping in
+/// and out of load-shedding mode.
+pub(crate) const LOAD_SHEDDING_HYSTERESIS_PERCENT: f32 = 10.0;
+
+/// Number of consecutive samples that must agree before load-shedding mode is
+/// entered or exited.
+pub(crate) const LOAD_SHEDDING_CONSECUTIVE_SAMPLES: u8 = 3;
+
+/// A sample of the resource usage of this node.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct ResourcePressure {
+    /// CPU usage of this node's process, in percent across all cores.
+    pub cpu_percent: f32,
+
+    /// Memory usage of this node's process, in percent of the configured memory
+    /// limit or, if none, of total memory.
+    pub memory_percent: f32,
+
+    /// Usage of the disk holding the data directory, in percent of its
+    /// capacity.
+    pub disk_percent: f32,
+}
+
+impl Display for ResourcePressure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "cpu: {:.1}%, memory: {:.1}%, disk: {:.1}%",
+            self.cpu_percent, self.memory_percent, self.disk_percent
+        )
+    }
+}
+
+impl ResourcePressure {
+    /// Returns true if any resource exceeds its threshold.
+    fn exceeds(&self, thresholds: &LoadSheddingThresholds) -> bool {
+        self.cpu_percent >= thresholds.cpu_percent
+            || self.memory_percent >= thresholds.memory_percent
+            || self.disk_percent >= thresholds.disk_percent
+    }
+
+    /// Returns true if all resources are comfortably below their threshold.
+    fn subsided(&self, thresholds: &LoadSheddingThresholds) -> bool {
+        self.cpu_percent < thresholds.cpu_percent - LOAD_SHEDDING_HYSTERESIS_PERCENT
+            && self.memory_percent < thresholds.memory_percent - LOAD_SHEDDING_HYSTERESIS_PERCENT
+            && self.disk_percent < thresholds.disk_percent - LOAD_SHEDDING_HYSTERESIS_PERCENT
+    }
+}
+
+/// The resource usage, in percent, above which load-shedding mode is entered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct LoadSheddingThresholds {
+    pub(crate) cpu_percent: f32,
+    pub(crate) memory_percent: f32,
+    pub(crate) disk_percent: f32,
+}
+
+impl From<&cli_args::Args> for LoadSheddingThresholds {
+    fn from(cli: &cli_args::Args) -> Self {
+        Self {
+            cpu_percent: cli.load_shedding_cpu_threshold.into(),
+            memory_percent: cli.load_shedding_memory_threshold.into(),
+            disk_percent: cli.load_shedding_disk_threshold.into(),
+        }
+    }
+}
+
+/// A change of load-shedding mode, as reported by [`LoadShedding::update`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LoadSheddingTransition {
+    Entered,
+    Exited,
+}
+
+/// Publicly visible load-shedding status, as reported by the health RPC.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct LoadSheddingStatus {
+    /// Whether the node is in load-shedding mode. In this mode, the node does
+    /// not upgrade transaction proofs, maintains fewer peer connections, and
+    /// rejects transactions paying low fees.
+    pub active: bool,
+
+    /// The time at which load-shedding mode was last entered, if active.
+    pub since: Option<Timestamp>,
+
+    /// The most recent sample of resource usage, if any.
+    pub pressure: Option<ResourcePressure>,
+}
+
+/// Tracks whether the node is under resource pressure and should shed load.
+///
+/// Only updated by the main loop, which samples resource usage periodically.
+/// Read by the tasks that shed load.
+#[derive(Debug, Clone, Default)]
+pub struct LoadShedding {
+    status: LoadSheddingStatus,
+    consecutive_high_samples: u8,
+    consecutive_low_samples: u8,
+}
+
+impl LoadShedding {
+    /// Whether the node is in load-shedding mode.
+    pub fn is_active(&self) -> bool {
+        self.status.active
+    }
+
+    pub fn status(&self) -> LoadSheddingStatus {
+        self.status
+    }
+
+    /// Returns true if a transaction paying `fee` should be rejected because
+    /// the node is shedding load.
+    pub(crate) fn rejects_fee(
+        &self,
+        fee: NativeCurrencyAmount,
+        min_fee: NativeCurrencyAmount,
+    ) -> bool {
+        self.is_active() && fee < min_fee
+    }
+
+    /// The maximum number of peers to maintain, given the configured maximum
+    /// and the current mode.
+    pub(crate) fn max_num_peers(&self, cli: &cli_args::Args) -> usize {
+        if self.is_active() {
+            cli.max_num_peers.min(cli.load_shedding_max_num_peers)
+        } else {
+            cli.max_num_peers
+        }
+    }
+
+    /// Record a new sample of resource usage without acting on it, because
+    /// load shedding is disabled.
+    pub(crate) fn record_pressure(&mut self, pressure: ResourcePressure) {
+        self.status.pressure = Some(pressure);
+    }
+
+    /// Record a new sample of resource usage. Returns the transition, if the
+    /// sample caused load-shedding mode to be entered or exited.
+    pub(crate) fn update(
+        &mut self,
+        pressure: ResourcePressure,
+        thresholds: &LoadSheddingThresholds,
+        now: Timestamp,
+    ) -> Option<LoadSheddingTransition> {
+        self.status.pressure = Some(pressure);
+
+        if pressure.exceeds(thresholds) {
+            self.consecutive_high_samples = self.consecutive_high_samples.saturating_add(1);
+        } else {
+            self.consecutive_high_samples = 0;
+        }
+
+        if pressure.subsided(thresholds) {
+            self.consecutive_low_samples = self.consecutive_low_samples.saturating_add(1);
+        } else {
+            self.consecutive_low_samples = 0;
+        }
+
+        if !self.status.active && self.consecutive_high_samples >= LOAD_SHEDDING_CONSECUTIVE_SAMPLES
+        {
+            warn!(
+                "Resource pressure exceeds thresholds ({pressure}). Entering load-shedding mode."
+            );
+            self.status.active = true;
+            self.status.since = Some(now);
+            return Some(LoadSheddingTransition::Entered);
+        }
+
+        if self.status.active && self.consecutive_low_samples >= LOAD_SHEDDING_CONSECUTIVE_SAMPLES {
+            info!("Resource pressure has subsided ({pressure}). Exiting load-shedding mode.");
+            self.status.active = false;
+            self.status.since = None;
+            return Some(LoadSheddingTransition::Exited);
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use num_traits::Zero;
+
+    use super::*;
+
+    fn thresholds() -> LoadSheddingThresholds {
+        LoadSheddingThresholds {
+            cpu_percent: 90.0,
+            memory_percent: 90.0,
+            disk_percent: 90.0,
+        }
+    }
+
+    fn pressure(percent: f32) -> ResourcePressure {
+        ResourcePressure {
+            cpu_percent: percent,
+            memory_percent: percent,
+            disk_percent: percent,
+        }
+    }
+
+    #[test]
+    fn enters_after_consecutive_high_samples_and_exits_after_subsiding() {
+        let mut load_shedding = LoadShedding::default();
+        let now = Timestamp::now();
+
+        for _ in 1..LOAD_SHEDDING_CONSECUTIVE_SAMPLES {
+            assert_eq!(
+                None,
+                load_shedding.update(pressure(95.0), &thresholds(), now)
+            );
+        }
+        assert_eq!(
+            Some(LoadSheddingTransition::Entered),
+            load_shedding.update(pressure(95.0), &thresholds(), now)
+        );
+        assert!(load_shedding.is_active());
+        assert_eq!(Some(now), load_shedding.status().since);
+
+        // below threshold but within hysteresis margin: stay in mode.
+        for _ in 0..10 {
+            assert_eq!(
+                None,
+                load_shedding.update(pressure(85.0), &thresholds(), now)
+            );
+        }
+        assert!(load_shedding.is_active());
+
+        for _ in 1..LOAD_SHEDDING_CONSECUTIVE_SAMPLES {
+            assert_eq!(
+                None,
+                load_shedding.update(pressure(50.0), &thresholds(), now)
+            );
+        }
+        assert_eq!(
+            Some(LoadSheddingTransition::Exited),
+            load_shedding.update(pressure(50.0), &thresholds(), now)
+        );
+        assert!(!load_shedding.is_active());
+        assert_eq!(None, load_shedding.status().since);
+    }
+
+    #[test]
+    fn single_resource_above_threshold_triggers_mode() {
+        let mut load_shedding = LoadShedding::default();
+        let disk_full = ResourcePressure {
+            disk_percent: 99.0,
+            ..Default::default()
+        };
+
+        for _ in 0..LOAD_SHEDDING_CONSECUTIVE_SAMPLES {
+            load_shedding.update(disk_full, &thresholds(), Timestamp::now());
+        }
+        assert!(load_shedding.is_active());
+    }
+
+    #[test]
+    fn interrupted_spike_does_not_trigger_mode() {
+        let mut load_shedding = LoadShedding::default();
+        for _ in 0..10 {
+            load_shedding.update(pressure(95.0), &thresholds(), Timestamp::now());
+            load_shedding.update(pressure(50.0), &thresholds(), Timestamp::now());
+        }
+        assert!(!load_shedding.is_active());
+    }
+
+    #[test]
+    fn low_fees_and_peers_are_shed_only_when_active() {
+        let mut cli = cli_args::Args::default();
+        cli.max_num_peers = 10;
+        cli.load_shedding_max_num_peers = 3;
+        let min_fee = NativeCurrencyAmount::coins(1);
+        let low_fee = NativeCurrencyAmount::zero();
+
+        let mut load_shedding = LoadShedding::default();
+        assert!(!load_shedding.rejects_fee(low_fee, min_fee));
+        assert_eq!(10, load_shedding.max_num_peers(&cli));
+
+        for _ in 0..LOAD_SHEDDING_CONSECUTIVE_SAMPLES {
+            load_shedding.update(pressure(100.0), &thresholds(), Timestamp::now());
+        }
+        assert!(load_shedding.rejects_fee(low_fee, min_fee));
+        assert!(!load_shedding.rejects_fee(min_fee, min_fee));
+        assert_eq!(3, load_shedding.max_num_peers(&cli));
+    }
+}
@@ -10,16 +10,23 @@
 //! are interested in the transaction with either the highest or the lowest 'fee
 //! density'.
 
+pub(crate) mod conflicting_spends;
+pub mod fee_histogram;
 pub mod mempool_event;
+pub mod mempool_journal;
+pub(crate) mod mempool_spillover;
 pub(crate) mod mempool_update_job;
 pub(crate) mod mempool_update_job_result;
 pub(crate) mod merge_input_cache;
+pub(crate) mod orphan_pool;
 pub(crate) mod primitive_witness_update;
 pub mod upgrade_priority;
+pub mod zero_conf_risk;
 
 use std::collections::hash_map::RandomState;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::net::IpAddr;
 
 use bytesize::ByteSize;
 use get_size2::GetSize;
@@ -52,6 +59,8 @@ use tracing::error;
 use tracing::warn;
 
 use crate::api::export::NeptuneProof;
+use crate::application::config::inclusion_policy::ExclusionReason;
+use crate::application::config::inclusion_policy::InclusionPolicy;
 use crate::application::config::tx_upgrade_filter::TxUpgradeFilter;
 use crate::protocol::consensus::block::Block;
 use crate::protocol::consensus::transaction::primitive_witness::PrimitiveWitness;
@@ -60,12 +69,25 @@ use crate::protocol::consensus::transaction::validity::neptune_proof::Proof;
 use crate::protocol::consensus::transaction::validity::proof_collection::ProofCollection;
 use crate::protocol::consensus::transaction::Transaction;
 use crate::protocol::consensus::transaction::TransactionProof;
+use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
 use crate::protocol::peer::transfer_transaction::TransactionProofQuality;
 use crate::protocol::proof_abstractions::timestamp::Timestamp;
+use crate::state::mempool::conflicting_spends::AbsoluteIndices;
+use crate::state::mempool::conflicting_spends::ConflictingSpends;
+use crate::state::mempool::fee_histogram::FeeHistogram;
+use crate::state::mempool::fee_histogram::MempoolFeeSummary;
 use crate::state::mempool::mempool_event::MempoolEvent;
+use crate::state::mempool::mempool_journal::MempoolDiff;
+use crate::state::mempool::mempool_journal::MempoolDiffToken;
+use crate::state::mempool::mempool_journal::MempoolJournal;
+use crate::state::mempool::mempool_journal::MempoolMutation;
+use crate::state::mempool::mempool_spillover::MempoolSpillover;
+use crate::state::mempool::mempool_spillover::SpilledRead;
 use crate::state::mempool::mempool_update_job::MempoolUpdateJob;
 use crate::state::mempool::merge_input_cache::MergeInputCache;
 use crate::state::mempool::merge_input_cache::MergeInputCacheElement;
+use crate::state::mempool::orphan_pool::OrphanPool;
+use crate::state::mempool::orphan_pool::OrphanPoolLimits;
 use crate::state::mempool::primitive_witness_update::PrimitiveWitnessUpdate;
 use crate::state::mempool::upgrade_priority::UpgradePriority;
 use crate::state::transaction::transaction_kernel_id::TransactionKernelId;
@@ -167,6 +189,16 @@ pub struct Mempool {
     #[get_size(ignore)]
     upgrade_priorities: PriorityQueue<TransactionKernelId, UpgradePriority>,
 
+    /// Fee totals of all transactions in the mempool, bucketed by fee
+    /// density. Kept in sync with `tx_dictionary`.
+    #[get_size(ignore)]
+    fee_histogram: FeeHistogram,
+
+    /// The transactions that entered and left the mempool, such that pollers
+    /// can be sent only what changed. Kept in sync with `tx_dictionary`.
+    #[get_size(ignore)]
+    journal: MempoolJournal,
+
     /// The digest of the chain's tip. Used to discover reorganizations.
     tip_digest: Digest,
 
@@ -186,6 +218,168 @@ pub struct Mempool {
     /// "unconflicted" again. This list can only grow when [`Self::insert`] is
     /// called and can shrink when [`Self::update_with_block`] is called.
     merge_input_cache: MergeInputCache,
+
+    /// Inputs that were observed to be spent by conflicting transactions.
+    /// Used to assess the risk of accepting unconfirmed transactions.
+    #[get_size(ignore)]
+    conflicting_spends: ConflictingSpends,
+
+    /// Transactions synced to a mutator set that is not known yet, kept until
+    /// the block producing it arrives. Not considered part of the mempool.
+    // Bounded by number of transactions rather than by size.
+    #[get_size(ignore)]
+    orphans: OrphanPool,
+
+    /// Transactions that did not fit within `max_total_size`, kept on disk
+    /// instead of being forgotten. Not considered part of the mempool, but
+    /// returned for block composition and to peers requesting them.
+    #[get_size(ignore)]
+    spillover: Option<MempoolSpillover>,
+}
+
+/// A transaction selected for block composition.
+#[derive(Debug)]
+enum SelectedTransaction {
+    Resident(Transaction),
+    Spilled(SpilledRead),
+}
+
+/// Transactions selected for block composition, in descending order by fee
+/// density, see [`Mempool::select_for_block_composition`]. Transactions that
+/// were spilled to disk still have to be read back.
+#[derive(Debug)]
+pub(crate) struct BlockCompositionSelection {
+    selected: Vec<SelectedTransaction>,
+    excluded: Vec<(TransactionKernelId, ExclusionReason)>,
+}
+
+impl BlockCompositionSelection {
+    /// The selected transactions, and the transactions that the inclusion
+    /// policy excluded along with the reason for their exclusion. Spilled
+    /// transactions that cannot be read back are left out.
+    ///
+    /// Must not be called while holding the global state lock.
+    pub(crate) async fn read(
+        self,
+    ) -> (
+        Vec<Transaction>,
+        Vec<(TransactionKernelId, ExclusionReason)>,
+    ) {
+        let mut transactions = Vec::with_capacity(self.selected.len());
+        for selected in self.selected {
+            match selected {
+                SelectedTransaction::Resident(transaction) => transactions.push(transaction),
+                SelectedTransaction::Spilled(read) => {
+                    transactions.extend(read.transaction().await);
+                }
+            }
+        }
+
+        (transactions, self.excluded)
+    }
+}
+
+/// The inputs that `new_tx` spends in common with the transactions it
+/// conflicts with, unless the conflict is explained by a merge or by an update
+/// of the same transaction.
+fn conflicting_spends(
+    new_tx: &Transaction,
+    conflicts: &HashMap<TransactionKernelId, &Transaction>,
+) -> Vec<AbsoluteIndices> {
+    let new_txid = new_tx.txid();
+    let new_tx_indices: HashSet<_> = new_tx
+        .kernel
+        .inputs
+        .iter()
+        .map(|x| x.absolute_indices.to_array())
+        .collect();
+
+    conflicts
+        .iter()
+        .filter(|(conflicting_txid, conflicting_tx)| {
+            **conflicting_txid != new_txid
+                && !TransactionKernel::have_merge_relationship(
+                    &new_tx.kernel,
+                    &conflicting_tx.kernel,
+                )
+                && !TransactionKernel::have_merge_relationship(
+                    &conflicting_tx.kernel,
+                    &new_tx.kernel,
+                )
+        })
+        .flat_map(|(_, conflicting_tx)| &conflicting_tx.kernel.inputs)
+        .map(|input| input.absolute_indices.to_array())
+        .filter(|absolute_indices| new_tx_indices.contains(absolute_indices))
+        .collect()
+}
+
+/// Whether `new_tx` has a higher proof quality than the transactions it
+/// conflicts with, such that it replaces them regardless of fee density.
+fn new_tx_has_higher_proof_quality_than_conflicts(
+    new_tx: &Transaction,
+    conflicts: &HashMap<TransactionKernelId, &Transaction>,
+    current_msa_hash: Digest,
+) -> bool {
+    match &new_tx.proof {
+        TransactionProof::Witness(witness) => {
+            // A primitive witness backed transaction *can* replace
+            // another transaction, if the other transaction is also
+            // primitive witness backed, *and* it is synced against a
+            // the current mutator set, and the previous one is not.
+            conflicts.iter().all(|(_, existing_tx)| {
+                matches!(&existing_tx.proof, TransactionProof::Witness(_))
+                    && existing_tx.kernel.mutator_set_hash != current_msa_hash
+                    && witness.kernel.mutator_set_hash == current_msa_hash
+            })
+        }
+        TransactionProof::ProofCollection(_) => {
+            // A ProofCollection backed transaction will always replace
+            // a primitive witness backed transaction, and will replace
+            // other proof collection backed transaction if the mutator
+            // set is updated, and the old transaction does not have an
+            // updated mutator set.
+            conflicts
+                .iter()
+                .any(|x| matches!(&x.1.proof, TransactionProof::Witness(_)))
+                || conflicts.iter().all(|(_, existing_tx)| {
+                    matches!(&existing_tx.proof, TransactionProof::ProofCollection(_))
+                        && existing_tx.kernel.mutator_set_hash != current_msa_hash
+                        && new_tx.kernel.mutator_set_hash == current_msa_hash
+                })
+        }
+        TransactionProof::SingleProof(_) => {
+            // A SingleProof-backed transaction kicks out conflicts if
+            // a) any conflicts are not SingleProof, or
+            // b) the conflict (as there can be only one) has the same
+            //    txk-id, which indicates mutator set update, and the
+            //    new transaction has an updated mutator set hash.
+            conflicts.iter().any(|(conflicting_txkid, conflicting_tx)| {
+                !matches!(&conflicting_tx.proof, TransactionProof::SingleProof(_))
+                    || *conflicting_txkid == new_tx.kernel.txid()
+                        && new_tx.kernel.mutator_set_hash == current_msa_hash
+            })
+        }
+    }
+}
+
+/// How the mempool would treat a new transaction, as predicted by
+/// [`Mempool::insertion_preview`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum InsertionPreview {
+    /// An identical transaction is already in the mempool.
+    Duplicate,
+
+    /// The transaction conflicts with transactions that have a higher fee
+    /// density and at least the same proof quality, so it is ignored.
+    Eclipsed { conflicts: Vec<TransactionKernelId> },
+
+    /// The mempool is full, and the transaction pays the lowest fee density,
+    /// so it would be evicted right away.
+    Full,
+
+    /// The transaction is inserted, replacing the listed conflicting
+    /// transactions.
+    Inserted { replaces: Vec<TransactionKernelId> },
 }
 
 /// note that all methods that modify state and result in a MempoolEvent
@@ -217,13 +411,32 @@ impl Mempool {
             tx_dictionary: table,
             fee_densities,
             upgrade_priorities,
+            fee_histogram: FeeHistogram::default(),
+            journal: MempoolJournal::default(),
             tip_digest,
             tip_mutator_set_hash,
             tx_proving_capability,
             merge_input_cache,
+            conflicting_spends: ConflictingSpends::default(),
+            orphans: OrphanPool::new(OrphanPoolLimits::default()),
+            spillover: None,
         }
     }
 
+    /// Set the bounds on the transactions kept while waiting for the block
+    /// they are synced to.
+    pub(crate) fn with_orphan_pool_limits(mut self, limits: OrphanPoolLimits) -> Self {
+        self.orphans = OrphanPool::new(limits);
+        self
+    }
+
+    /// Keep transactions that do not fit within the RAM budget on disk, rather
+    /// than forgetting them.
+    pub(crate) fn with_spillover(mut self, spillover: MempoolSpillover) -> Self {
+        self.spillover = Some(spillover);
+        self
+    }
+
     /// Update mempool with chain information.
     ///
     /// Returns an error if the provided block does not have a mutator set
@@ -250,6 +463,18 @@ impl Mempool {
         new_tx_proof_quality: TransactionProofQuality,
         new_tx_mutator_set_hash: Digest,
     ) -> bool {
+        if let Some(spilled) = self
+            .spillover
+            .as_ref()
+            .and_then(|spillover| spillover.get(new_tx_txid))
+        {
+            // Spilled transactions are backed by single proofs, so only a
+            // version synced to a newer mutator set is better.
+            return new_tx_proof_quality == TransactionProofQuality::SingleProof
+                && spilled.kernel.mutator_set_hash != self.tip_mutator_set_hash
+                && new_tx_mutator_set_hash == self.tip_mutator_set_hash;
+        }
+
         let Some(existing_tx) = self.tx_dictionary.get(&new_tx_txid) else {
             // Transaction is not in mempool. Is it in the cache of conflicting
             // transactions?
@@ -496,6 +721,14 @@ impl Mempool {
             .map(|x| &x.transaction)
     }
 
+    /// Start reading a transaction back from disk, if it was spilled there.
+    /// The read must be awaited without holding the global state lock.
+    pub(crate) fn read_spilled(&self, transaction_id: TransactionKernelId) -> Option<SpilledRead> {
+        self.spillover
+            .as_ref()
+            .and_then(|spillover| spillover.read(transaction_id))
+    }
+
     /// get transaction from mempool, with its associated upgrade priority.
     ///
     /// Computes in O(1) from HashMap
@@ -537,6 +770,178 @@ impl Mempool {
         conflict_txs_in_mempool
     }
 
+    /// Predict how [`Self::insert`] would treat `new_tx`, without modifying
+    /// the mempool. It is the caller's responsibility to validate the
+    /// transaction.
+    ///
+    /// Eviction because of size is estimated from the current size of the
+    /// mempool, ignoring the space freed by replaced transactions.
+    pub(crate) fn insertion_preview(&self, new_tx: &Transaction) -> InsertionPreview {
+        let conflicts = self.transaction_conflicts_with(new_tx);
+        self.insertion_preview_with_conflicts(new_tx, &conflicts)
+    }
+
+    /// Like [`Self::insertion_preview`], for the given conflicts of `new_tx`
+    /// as returned by [`Self::transaction_conflicts_with`].
+    fn insertion_preview_with_conflicts(
+        &self,
+        new_tx: &Transaction,
+        conflicts: &HashMap<TransactionKernelId, &Transaction>,
+    ) -> InsertionPreview {
+        let txid = new_tx.txid();
+        if conflicts
+            .get(&txid)
+            .is_some_and(|existing_tx| **existing_tx == *new_tx)
+        {
+            return InsertionPreview::Duplicate;
+        }
+
+        let fee_density = new_tx.fee_density();
+        if let Some(min_fee_of_conflicts) = conflicts.values().map(|tx| tx.fee_density()).min() {
+            let should_replace_conflict = new_tx_has_higher_proof_quality_than_conflicts(
+                new_tx,
+                conflicts,
+                self.tip_mutator_set_hash,
+            ) || min_fee_of_conflicts < fee_density;
+            if !should_replace_conflict {
+                return InsertionPreview::Eclipsed {
+                    conflicts: conflicts.keys().copied().collect(),
+                };
+            }
+        }
+
+        let exceeds_max_size = (*self).get_size() + new_tx.get_size() > self.max_total_size;
+        let lowest_remaining_fee_density = self
+            .fee_densities
+            .iter()
+            .filter(|(txid, _)| !conflicts.contains_key(txid))
+            .map(|(_, fee_density)| fee_density)
+            .min();
+        if exceeds_max_size
+            && lowest_remaining_fee_density.is_some_and(|lowest| fee_density <= *lowest)
+        {
+            return InsertionPreview::Full;
+        }
+
+        InsertionPreview::Inserted {
+            replaces: conflicts
+                .keys()
+                .copied()
+                .filter(|conflicting_txid| *conflicting_txid != txid)
+                .collect(),
+        }
+    }
+
+    /// Return the number of inputs of the transaction that were observed to be
+    /// spent by a conflicting transaction, or `None` if the transaction is not
+    /// in the mempool.
+    pub(crate) fn num_inputs_with_conflicting_spends(
+        &self,
+        transaction_id: TransactionKernelId,
+    ) -> Option<usize> {
+        let transaction = self.get(transaction_id)?;
+        let num_conflicting = transaction
+            .kernel
+            .inputs
+            .iter()
+            .filter(|input| {
+                self.conflicting_spends
+                    .contains(&input.absolute_indices.to_array())
+            })
+            .count();
+
+        Some(num_conflicting)
+    }
+
+    /// Return the fraction of the other transactions in the mempool that have
+    /// a lower fee density than the given transaction, or `None` if the
+    /// transaction is not in the mempool. A transaction that is alone in the
+    /// mempool has the highest fee density.
+    ///
+    /// Computes in O(n)
+    pub(crate) fn fee_density_percentile(
+        &self,
+        transaction_id: TransactionKernelId,
+    ) -> Option<f64> {
+        let (_, fee_density) = self.fee_densities.get(&transaction_id)?;
+        let num_others = self.fee_densities.len() - 1;
+        if num_others == 0 {
+            return Some(1.0);
+        }
+
+        let num_lower = self
+            .fee_densities
+            .iter()
+            .filter(|(_, other_fee_density)| *other_fee_density < fee_density)
+            .count();
+
+        Some(num_lower as f64 / num_others as f64)
+    }
+
+    /// Keep a transaction received from a peer, which is synced to a mutator
+    /// set this node does not know yet, until the block producing that mutator
+    /// set arrives. It is the caller's responsibility to validate the
+    /// transaction.
+    ///
+    /// Returns false if the transaction was not kept, because it is synced to
+    /// the tip, is already in the mempool, or because of the limits of the
+    /// orphan pool.
+    pub(crate) fn insert_orphan(
+        &mut self,
+        transaction: Transaction,
+        received_from: IpAddr,
+        now: Timestamp,
+    ) -> bool {
+        if transaction.kernel.mutator_set_hash == self.tip_mutator_set_hash
+            || self.contains(transaction.txid())
+        {
+            return false;
+        }
+
+        self.orphans.insert(transaction, received_from, now)
+    }
+
+    /// Number of transactions kept while waiting for the block they are synced
+    /// to.
+    pub(crate) fn num_orphans(&self) -> usize {
+        self.orphans.len()
+    }
+
+    /// Move the orphans synced to the mutator set after the new tip into the
+    /// mempool, and drop expired orphans.
+    fn resolve_orphans(&mut self, new_tip: &Block) -> anyhow::Result<Vec<MempoolEvent>> {
+        self.orphans.prune_expired(Timestamp::now());
+
+        let mutator_set_accumulator = new_tip.mutator_set_accumulator_after()?;
+        let mut events = vec![];
+        for transaction in self.orphans.resolve(mutator_set_accumulator.hash()) {
+            if !transaction.is_confirmable_relative_to(&mutator_set_accumulator) {
+                debug!(
+                    "Dropping orphan transaction {} that is unconfirmable under the mutator set it is synced to",
+                    transaction.txid()
+                );
+                continue;
+            }
+
+            let Ok(proof_quality) = transaction.proof.proof_quality() else {
+                continue;
+            };
+            if self.accept_transaction(
+                transaction.txid(),
+                proof_quality,
+                transaction.kernel.mutator_set_hash,
+            ) {
+                debug!(
+                    "Moving orphan transaction {} into mempool",
+                    transaction.txid()
+                );
+                events.extend(self.insert(transaction, UpgradePriority::Irrelevant));
+            }
+        }
+
+        Ok(events)
+    }
+
     /// Insert a transaction into the mempool. It is the caller's responsibility to validate
     /// the transaction.
     ///
@@ -554,53 +959,6 @@ impl Mempool {
         new_tx: Transaction,
         priority: UpgradePriority,
     ) -> Vec<MempoolEvent> {
-        fn new_tx_has_higher_proof_quality_than_conflicts(
-            new_tx: &Transaction,
-            conflicts: &HashMap<TransactionKernelId, &Transaction>,
-            current_msa_hash: Digest,
-        ) -> bool {
-            match &new_tx.proof {
-                TransactionProof::Witness(witness) => {
-                    // A primitive witness backed transaction *can* replace
-                    // another transaction, if the other transaction is also
-                    // primitive witness backed, *and* it is synced against a
-                    // the current mutator set, and the previous one is not.
-                    conflicts.iter().all(|(_, existing_tx)| {
-                        matches!(&existing_tx.proof, TransactionProof::Witness(_))
-                            && existing_tx.kernel.mutator_set_hash != current_msa_hash
-                            && witness.kernel.mutator_set_hash == current_msa_hash
-                    })
-                }
-                TransactionProof::ProofCollection(_) => {
-                    // A ProofCollection backed transaction will always replace
-                    // a primitive witness backed transaction, and will replace
-                    // other proof collection backed transaction if the mutator
-                    // set is updated, and the old transaction does not have an
-                    // updated mutator set.
-                    conflicts
-                        .iter()
-                        .any(|x| matches!(&x.1.proof, TransactionProof::Witness(_)))
-                        || conflicts.iter().all(|(_, existing_tx)| {
-                            matches!(&existing_tx.proof, TransactionProof::ProofCollection(_))
-                                && existing_tx.kernel.mutator_set_hash != current_msa_hash
-                                && new_tx.kernel.mutator_set_hash == current_msa_hash
-                        })
-                }
-                TransactionProof::SingleProof(_) => {
-                    // A SingleProof-backed transaction kicks out conflicts if
-                    // a) any conflicts are not SingleProof, or
-                    // b) the conflict (as there can be only one) has the same
-                    //    txk-id, which indicates mutator set update, and the
-                    //    new transaction has an updated mutator set hash.
-                    conflicts.iter().any(|(conflicting_txkid, conflicting_tx)| {
-                        !matches!(&conflicting_tx.proof, TransactionProof::SingleProof(_))
-                            || *conflicting_txkid == new_tx.kernel.txid()
-                                && new_tx.kernel.mutator_set_hash == current_msa_hash
-                    })
-                }
-            }
-        }
-
         // If transaction to be inserted conflicts with transactions already in
         // the mempool, we replace them -- but only if the new transaction has a
         // higher fee-density than the ones already in mempool, or if it has
@@ -610,14 +968,41 @@ impl Mempool {
         // that were merged since the merged transaction is *very* likely to
         // have a higher fee density that the lowest one of the ones that were
         // merged.
+        let txid = new_tx.txid();
+
+        // An orphan is superseded by the same transaction under a known
+        // mutator set.
+        self.orphans.remove(txid);
+
         let conflicts = self.transaction_conflicts_with(&new_tx);
+        let conflicting_spends = conflicting_spends(&new_tx, &conflicts);
+        let preview = self.insertion_preview_with_conflicts(&new_tx, &conflicts);
+        let conflicts = conflicts
+            .into_iter()
+            .map(|x| (x.0, x.1.proof.as_single_proof()))
+            .collect_vec();
+        for absolute_indices in conflicting_spends {
+            self.conflicting_spends.record(absolute_indices);
+        }
 
-        // Do not insert an existing transaction again, if its an exact copy.
-        let txid = new_tx.txid();
-        if let Some(existing_tx) = conflicts.get(&txid) {
-            if **existing_tx == new_tx {
+        match preview {
+            // Do not insert an existing transaction again, if its an exact
+            // copy.
+            InsertionPreview::Duplicate => return vec![],
+
+            // If new transaction has a lower fee density than the one previous
+            // seen, ignore it. Stop execution here.
+            InsertionPreview::Eclipsed { .. } => {
+                debug!(
+                    "Attempted to insert transaction into mempool but it's \
+                     fee density was eclipsed by another transaction."
+                );
                 return vec![];
             }
+
+            // A transaction that would be evicted right away is still
+            // inserted, and then evicted when the mempool shrinks.
+            InsertionPreview::Full | InsertionPreview::Inserted { .. } => (),
         }
 
         // Ensure we never throw away a primitive witness if we have one. This
@@ -636,61 +1021,41 @@ impl Mempool {
         };
 
         let mut events = vec![];
-        let new_tx_has_higher_proof_quality = new_tx_has_higher_proof_quality_than_conflicts(
-            &new_tx.transaction,
-            &conflicts,
-            self.tip_mutator_set_hash,
-        );
-        let min_fee_of_conflicts = conflicts.values().map(|tx| tx.fee_density()).min();
-        let conflicts = conflicts
-            .into_iter()
-            .map(|x| (x.0, x.1.proof.as_single_proof()))
-            .collect_vec();
-        if let Some(min_fee_of_conflicting_tx) = min_fee_of_conflicts {
-            let better_fee_density = min_fee_of_conflicting_tx < new_tx.transaction.fee_density();
-            let should_replace_conflict = new_tx_has_higher_proof_quality || better_fee_density;
-            if should_replace_conflict {
-                for (conflicting_txid, single_proof) in conflicts {
-                    let e = self.remove(conflicting_txid).unwrap_or_else(|| {
-                        panic!("Reported conflict {conflicting_txid} must exist")
-                    });
-                    let MempoolEvent::RemoveTx(removed) = &e else {
-                        panic!("remove must return remove event");
-                    };
-
-                    // Conditionally store existing transaction in conflict
-                    // cache.
-                    if let Some(old_proof) = single_proof {
-                        if new_tx.transaction.proof.is_single_proof()
-                            && TransactionKernel::have_merge_relationship(
-                                &new_tx.transaction.kernel,
-                                removed,
-                            )
-                        {
-                            let upgrade_priority = self
-                                .upgrade_priorities
-                                .get(&conflicting_txid)
-                                .map(|x| *x.1)
-                                .unwrap_or_default();
-                            self.merge_input_cache.insert(
-                                removed.to_owned(),
-                                old_proof,
-                                upgrade_priority,
-                            );
-                        }
-                    }
+        for (conflicting_txid, single_proof) in conflicts {
+            let e = self
+                .remove(conflicting_txid)
+                .unwrap_or_else(|| panic!("Reported conflict {conflicting_txid} must exist"));
+            let MempoolEvent::RemoveTx(removed) = &e else {
+                panic!("remove must return remove event");
+            };
 
-                    events.push(e);
+            // Conditionally store existing transaction in conflict
+            // cache.
+            if let Some(old_proof) = single_proof {
+                if new_tx.transaction.proof.is_single_proof()
+                    && TransactionKernel::have_merge_relationship(
+                        &new_tx.transaction.kernel,
+                        removed,
+                    )
+                {
+                    let upgrade_priority = self
+                        .upgrade_priorities
+                        .get(&conflicting_txid)
+                        .map(|x| *x.1)
+                        .unwrap_or_default();
+                    self.merge_input_cache
+                        .insert(removed.to_owned(), old_proof, upgrade_priority);
                 }
-            } else {
-                // If new transaction has a lower fee density than the one previous seen,
-                // ignore it. Stop execution here.
-                debug!(
-                    "Attempted to insert transaction into mempool but it's \
-                     fee density was eclipsed by another transaction."
-                );
-                return events;
             }
+
+            events.push(e);
+        }
+
+        // Spilled transactions must not be selected alongside one they
+        // conflict with.
+        if let Some(spillover) = &mut self.spillover {
+            spillover.remove(txid);
+            spillover.remove_conflicts(&new_tx.transaction);
         }
 
         // Insert the new transaction, if transaction with this txid already
@@ -698,9 +1063,13 @@ impl Mempool {
         self.fee_densities
             .push(txid, new_tx.transaction.fee_density());
         events.push(MempoolEvent::AddTx(new_tx.transaction.kernel.clone()));
+        self.fee_histogram.add(&new_tx.transaction);
         if let Some(removed) = self.tx_dictionary.insert(txid, new_tx) {
+            self.fee_histogram.remove(&removed.transaction);
+            self.journal.record(txid, MempoolMutation::Removed);
             events.push(MempoolEvent::RemoveTx(removed.transaction.kernel));
         }
+        self.journal.record(txid, MempoolMutation::Added);
 
         if !priority.is_irrelevant() {
             self.upgrade_priorities.push(txid, priority);
@@ -732,13 +1101,21 @@ impl Mempool {
         MempoolEvent::normalize(events)
     }
 
-    /// remove a transaction from the `Mempool`
+    /// remove a transaction from the `Mempool`, or from the transactions
+    /// spilled to disk.
     ///
     /// Does nothing if the transaction cannot be found in the mempool.
     pub(super) fn remove(&mut self, transaction_id: TransactionKernelId) -> Option<MempoolEvent> {
+        if let Some(spillover) = &mut self.spillover {
+            spillover.remove(transaction_id);
+        }
+
         self.tx_dictionary.remove(&transaction_id).map(|tx| {
             self.fee_densities.remove(&transaction_id);
             self.upgrade_priorities.remove(&transaction_id);
+            self.fee_histogram.remove(&tx.transaction);
+            self.journal
+                .record(transaction_id, MempoolMutation::Removed);
             debug_assert_eq!(self.tx_dictionary.len(), self.fee_densities.len());
             MempoolEvent::RemoveTx(tx.transaction.kernel)
         })
@@ -780,9 +1157,85 @@ impl Mempool {
     pub(super) fn clear(&mut self) -> Vec<MempoolEvent> {
         // note: this causes event listeners to be notified of each removed tx.
         self.merge_input_cache.clear();
+        self.conflicting_spends.clear();
+        self.orphans.clear();
+        if let Some(spillover) = &mut self.spillover {
+            spillover.clear();
+        }
         self.retain(|_| false)
     }
 
+    /// Summarize the mempool's transactions by fee density, and forecast the
+    /// revenue of the next block for each of the given size cutoffs, in
+    /// bytes.
+    ///
+    /// The forecasts are upper bounds, as they assume that every transaction
+    /// in the mempool can be included in the next block, regardless of its
+    /// proof type and whether it is synced to the tip.
+    ///
+    /// Computes in O(1) in the number of transactions.
+    pub fn fee_summary(
+        &self,
+        size_cutoffs: &[usize],
+        block_subsidy: NativeCurrencyAmount,
+        composer_reward: NativeCurrencyAmount,
+    ) -> MempoolFeeSummary {
+        MempoolFeeSummary {
+            buckets: self.fee_histogram.buckets(),
+            forecasts: size_cutoffs
+                .iter()
+                .map(|&cutoff| {
+                    self.fee_histogram
+                        .forecast(cutoff, block_subsidy, composer_reward)
+                })
+                .collect(),
+        }
+    }
+
+    /// List the transactions that entered and left the mempool since the state
+    /// identified by `since`, which a previous diff returned.
+    ///
+    /// If `since` is `None`, or the mutations since are no longer retained,
+    /// the diff lists all transactions as added, and is marked as a reset.
+    ///
+    /// Computes in O(k) in the number of mutations since, or O(n) on reset.
+    pub fn diff_since(&self, since: Option<MempoolDiffToken>) -> MempoolDiff {
+        let mutations = since.and_then(|since| self.journal.mutations_since(since));
+        let reset = mutations.is_none();
+        let (added, removed) = match mutations {
+            Some(mutations) => {
+                // a transaction's first mutation tells whether it was in the
+                // mempool then, and the dictionary whether it is now.
+                let mut seen = HashSet::new();
+                let mut added = vec![];
+                let mut removed = vec![];
+                for &(txid, mutation) in mutations {
+                    if !seen.insert(txid) {
+                        continue;
+                    }
+                    match (mutation, self.contains(txid)) {
+                        (MempoolMutation::Added, true) => added.push(txid),
+                        (MempoolMutation::Removed, false) => removed.push(txid),
+                        _ => (),
+                    }
+                }
+                (added, removed)
+            }
+            None => (self.tx_dictionary.keys().copied().collect(), vec![]),
+        };
+
+        let buckets = self.fee_histogram.buckets();
+        MempoolDiff {
+            token: self.journal.token(),
+            reset,
+            added,
+            removed,
+            num_txs: self.len(),
+            total_size: buckets.iter().map(|bucket| bucket.total_size).sum(),
+            total_fees: buckets.iter().map(|bucket| bucket.total_fee).sum(),
+        }
+    }
+
     /// Return the number of transactions currently stored in the Mempool.
     /// Computes in O(1)
     pub fn len(&self) -> usize {
@@ -797,6 +1250,44 @@ impl Mempool {
         self.upgrade_priorities.len()
     }
 
+    /// Return the number of transactions stored in the mempool that are synced
+    /// to the tip, ie that do not need to be updated before they can be mined,
+    /// and the number of transactions that are synced or that this node
+    /// updates to the tip itself.
+    ///
+    /// Transactions that only other nodes can update, such as proof-collection
+    /// backed transactions without a primitive witness, are counted in
+    /// neither.
+    ///
+    /// Computes in O(n)
+    pub(crate) fn num_synced_and_syncable_txs(&self) -> (usize, usize) {
+        let mut num_synced = 0;
+        let mut num_syncable = 0;
+        for tx in self.tx_dictionary.values() {
+            if self.tx_is_synced(&tx.transaction.kernel) {
+                num_synced += 1;
+                num_syncable += 1;
+            } else if self.is_updated_by_this_node(tx) {
+                num_syncable += 1;
+            }
+        }
+
+        (num_synced, num_syncable)
+    }
+
+    /// Whether this node updates the transaction to a new tip, see
+    /// [`Self::update_with_block`].
+    fn is_updated_by_this_node(&self, tx: &MempoolTransaction) -> bool {
+        match &tx.transaction.proof {
+            TransactionProof::Witness(_) => true,
+            TransactionProof::ProofCollection(_) => tx.primitive_witness.is_some(),
+            TransactionProof::SingleProof(_) => {
+                self.tx_proving_capability == TxProvingCapability::SingleProof
+                    && tx.upgrade_priority == UpgradePriority::Critical
+            }
+        }
+    }
+
     /// check if `Mempool` is empty
     ///
     /// Computes in O(1)
@@ -804,68 +1295,155 @@ impl Mempool {
         self.tx_dictionary.is_empty()
     }
 
-    /// Return a vector with copies of the transactions, in descending order by
-    /// fee density. Only returns transactions that are
-    /// - backed by single proofs, and
-    /// - synced to the tip.
+    /// Select transactions for block composition, in descending order by fee
+    /// density. Only selects transactions that are
+    /// - backed by single proofs,
+    /// - synced to the tip, and
+    /// - not excluded by the inclusion policy.
+    ///
+    /// Transactions spilled to disk are considered too, and read back when
+    /// the selection is read.
     ///
-    /// Number of transactions returned can be capped by either size (measured
+    /// Number of transactions selected can be capped by either size (measured
     /// in bytes), or by transaction count. The function guarantees that neither
-    /// of the specified limits will be exceeded.
-    pub(crate) fn get_transactions_for_block_composition(
+    /// of the specified limits will be exceeded. Excluded transactions do not
+    /// count towards the limits, and are returned along with the reason for
+    /// their exclusion.
+    pub(crate) fn select_for_block_composition(
         &self,
         mut remaining_storage: usize,
         max_num_txs: Option<usize>,
-    ) -> Vec<Transaction> {
-        let mut transactions = vec![];
+        inclusion_policy: &InclusionPolicy,
+    ) -> BlockCompositionSelection {
+        let mut selected = vec![];
+        let mut excluded = vec![];
 
-        for (transaction_digest, _fee_density) in self.fee_density_iter() {
+        let spilled = self
+            .spillover
+            .iter()
+            .flat_map(|spillover| spillover.fee_density_iter());
+        let candidates = self
+            .fee_density_iter()
+            .merge_by(spilled, |(_, left), (_, right)| left >= right);
+        for (transaction_digest, _fee_density) in candidates {
             // No more transactions can possibly be packed
-            if remaining_storage == 0 || max_num_txs.is_some_and(|max| transactions.len() == max) {
+            if remaining_storage == 0 || max_num_txs.is_some_and(|max| selected.len() == max) {
                 break;
             }
 
-            if let Some(transaction_ptr) = self.get(transaction_digest) {
-                // Only return transaction synced to tip
-                if !self.tx_is_synced(&transaction_ptr.kernel) {
-                    continue;
+            let resident = self.get(transaction_digest);
+            let spilled = self
+                .spillover
+                .as_ref()
+                .and_then(|spillover| spillover.get(transaction_digest));
+            let (kernel, transaction_size) = match (resident, spilled) {
+                (Some(transaction), _) => {
+                    if !matches!(transaction.proof, TransactionProof::SingleProof(_)) {
+                        continue;
+                    }
+                    (&transaction.kernel, transaction.get_size())
                 }
+                // Only transactions backed by single proofs are spilled.
+                (None, Some(spilled)) => (&spilled.kernel, spilled.size),
+                (None, None) => continue,
+            };
 
-                if !matches!(transaction_ptr.proof, TransactionProof::SingleProof(_)) {
-                    continue;
-                }
+            // Only return transaction synced to tip
+            if !self.tx_is_synced(kernel) {
+                continue;
+            }
+
+            if let Err(reason) = inclusion_policy.check(kernel) {
+                excluded.push((transaction_digest, reason));
+                continue;
+            }
 
-                let transaction_copy = transaction_ptr.to_owned();
-                let transaction_size = transaction_copy.get_size();
+            // Current transaction is too big
+            if transaction_size > remaining_storage {
+                continue;
+            }
 
-                // Current transaction is too big
-                if transaction_size > remaining_storage {
-                    continue;
+            // Include transaction
+            remaining_storage -= transaction_size;
+            match resident {
+                Some(transaction) => {
+                    selected.push(SelectedTransaction::Resident(transaction.clone()));
                 }
-
-                // Include transaction
-                remaining_storage -= transaction_size;
-                transactions.push(transaction_copy)
+                None => selected.extend(
+                    self.read_spilled(transaction_digest)
+                        .map(SelectedTransaction::Spilled),
+                ),
             }
         }
 
-        transactions
+        BlockCompositionSelection { selected, excluded }
+    }
+
+    /// Return a vector with copies of the transactions selected for block
+    /// composition, see [`Self::select_for_block_composition`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a spilled transaction is selected, as those must be read
+    /// back asynchronously.
+    #[cfg(test)]
+    pub(crate) fn get_transactions_for_block_composition(
+        &self,
+        remaining_storage: usize,
+        max_num_txs: Option<usize>,
+    ) -> Vec<Transaction> {
+        self.get_transactions_for_block_composition_with_policy(
+            remaining_storage,
+            max_num_txs,
+            &InclusionPolicy::default(),
+        )
+        .0
+    }
+
+    /// Like [`Self::get_transactions_for_block_composition`], but skips
+    /// transactions that the inclusion policy excludes.
+    #[cfg(test)]
+    pub(crate) fn get_transactions_for_block_composition_with_policy(
+        &self,
+        remaining_storage: usize,
+        max_num_txs: Option<usize>,
+        inclusion_policy: &InclusionPolicy,
+    ) -> (
+        Vec<Transaction>,
+        Vec<(TransactionKernelId, ExclusionReason)>,
+    ) {
+        let selection =
+            self.select_for_block_composition(remaining_storage, max_num_txs, inclusion_policy);
+        let transactions = selection
+            .selected
+            .into_iter()
+            .map(|selected| match selected {
+                SelectedTransaction::Resident(transaction) => transaction,
+                SelectedTransaction::Spilled(_) => {
+                    panic!("spilled transactions must be read back asynchronously")
+                }
+            })
+            .collect();
+
+        (transactions, selection.excluded)
     }
 
     /// Removes the transaction with the lowest [`FeeDensity`] from the mempool.
     /// Returns the removed value.
     ///
     /// Computes in θ(lg N)
-    fn pop_min(&mut self) -> Option<(MempoolEvent, FeeDensity)> {
-        if let Some((txkid, fee_density)) = self.fee_densities.pop_min() {
+    fn pop_min(&mut self) -> Option<(MempoolEvent, MempoolTransaction)> {
+        if let Some((txkid, _fee_density)) = self.fee_densities.pop_min() {
             if let Some(tx) = self.tx_dictionary.remove(&txkid) {
                 self.upgrade_priorities.remove(&txkid);
+                self.fee_histogram.remove(&tx.transaction);
+                self.journal.record(txkid, MempoolMutation::Removed);
 
                 debug_assert_eq!(self.tx_dictionary.len(), self.fee_densities.len());
 
-                let event = MempoolEvent::RemoveTx(tx.transaction.kernel);
+                let event = MempoolEvent::RemoveTx(tx.transaction.kernel.clone());
 
-                return Some((event, fee_density));
+                return Some((event, tx));
             }
         }
         None
@@ -913,6 +1491,10 @@ impl Mempool {
             cutoff < transaction.kernel.timestamp
         };
 
+        if let Some(spillover) = &mut self.spillover {
+            spillover.retain(|_, spilled| cutoff < spilled.kernel.timestamp);
+        }
+
         self.retain(keep)
     }
 
@@ -927,9 +1509,13 @@ impl Mempool {
         new_block: &Block,
     ) -> anyhow::Result<(Vec<MempoolEvent>, Vec<MempoolUpdateJob>)> {
         // If the mempool is empty, there is nothing to do.
-        if self.is_empty() && self.merge_input_cache.is_empty() {
+        if self.is_empty()
+            && self.merge_input_cache.is_empty()
+            && self.spillover.as_ref().is_none_or(|s| s.is_empty())
+        {
             self.set_sync_labels(new_block)?;
-            return Ok((vec![], vec![]));
+            let events = self.resolve_orphans(new_block)?;
+            return Ok((events, vec![]));
         }
 
         // If we discover a reorganization, we currently just clear the mempool,
@@ -976,6 +1562,18 @@ impl Mempool {
             let removed = self.retain(still_valid);
             events.extend(removed);
         }
+        if let Some(spillover) = &mut self.spillover {
+            spillover.retain(|_, spilled| {
+                !spilled.kernel.inputs.is_empty()
+                    && spilled.kernel.inputs.iter().all(|input| {
+                        input
+                            .absolute_indices
+                            .to_array()
+                            .iter()
+                            .any(|index| !block_bf_set_union.contains(index))
+                    })
+            });
+        }
 
         // Restore transactions from blocks. Do this prior to the collection of
         // update jobs since we migth restore a transaction that we need to
@@ -1090,6 +1688,12 @@ impl Mempool {
         // Update the sync-label to keep track of reorganizations
         self.set_sync_labels(new_block)?;
 
+        // Orphans synced to the new tip can now be confirmed.
+        {
+            let resolved = self.resolve_orphans(new_block)?;
+            events.extend(resolved);
+        }
+
         let events = MempoolEvent::normalize(events);
 
         Ok((events, update_jobs))
@@ -1115,11 +1719,12 @@ impl Mempool {
                     "Dominated by cache but cannot remove element"
                 );
             } else {
-                let Some((removed, _)) = self.pop_min() else {
+                let Some((removed, evicted)) = self.pop_min() else {
                     error!("Mempool is empty but exceeds max allowed size");
                     return removal_events;
                 };
 
+                self.spill(evicted);
                 removal_events.push(removed);
             }
         }
@@ -1129,6 +1734,22 @@ impl Mempool {
         removal_events
     }
 
+    /// Move a transaction evicted for lack of space to disk, if a spillover
+    /// is configured and the transaction can be included in a block without
+    /// this node updating it.
+    fn spill(&mut self, evicted: MempoolTransaction) {
+        let Some(spillover) = &mut self.spillover else {
+            return;
+        };
+
+        if evicted.transaction.proof.is_single_proof()
+            && evicted.upgrade_priority.is_irrelevant()
+            && evicted.primitive_witness.is_none()
+        {
+            spillover.spill(evicted.transaction);
+        }
+    }
+
     /// Shrinks internal data structures as much as possible.
     /// Computes in O(n) (Likely)
     fn shrink_to_fit(&mut self) {
@@ -1138,7 +1759,7 @@ impl Mempool {
     }
 
     /// Return whether the transaction is synced to the tip block.
-    fn tx_is_synced(&self, transaction_kernel: &TransactionKernel) -> bool {
+    pub(crate) fn tx_is_synced(&self, transaction_kernel: &TransactionKernel) -> bool {
         self.tip_mutator_set_hash == transaction_kernel.mutator_set_hash
     }
 
@@ -1203,6 +1824,7 @@ mod tests {
     use rand::rngs::StdRng;
     use rand::Rng;
     use rand::SeedableRng;
+    use tasm_lib::triton_vm::prelude::BFieldElement;
     use tracing_test::traced_test;
 
     use super::*;
@@ -1217,6 +1839,7 @@ mod tests {
     use crate::protocol::consensus::block::block_height::BlockHeight;
     use crate::protocol::consensus::block::block_transaction::BlockTransaction;
     use crate::protocol::consensus::consensus_rule_set::ConsensusRuleSet;
+    use crate::protocol::consensus::transaction::announcement::Announcement;
     use crate::protocol::consensus::transaction::primitive_witness::PrimitiveWitness;
     use crate::protocol::consensus::transaction::transaction_kernel::TransactionKernelModifier;
     use crate::protocol::consensus::transaction::validity::single_proof::produce_single_proof;
@@ -1233,6 +1856,7 @@ mod tests {
     use crate::state::GlobalStateLock;
     use crate::tests::shared::blocks::invalid_empty_block_with_timestamp;
     use crate::tests::shared::blocks::make_mock_block;
+    use crate::tests::shared::files::unit_test_data_directory;
     use crate::tests::shared::globalstate::mock_genesis_global_state;
     use crate::tests::shared::mock_tx::make_plenty_mock_transaction_supported_by_invalid_single_proofs;
     use crate::tests::shared::mock_tx::mock_transactions_with_sized_single_proof;
@@ -1295,6 +1919,54 @@ mod tests {
         assert!(mempool.len().is_zero());
     }
 
+    #[test]
+    fn fee_summary_tracks_inserted_and_removed_transactions() {
+        let network = Network::Main;
+        let genesis_block = Block::genesis(network);
+        let mut mempool = Mempool::new(
+            ByteSize::gb(1),
+            TxProvingCapability::ProofCollection,
+            &genesis_block,
+        );
+
+        let subsidy = NativeCurrencyAmount::coins(128);
+        let composer_reward = NativeCurrencyAmount::coins(64);
+        let num_txs_in_summary = |mempool: &Mempool| {
+            let summary = mempool.fee_summary(&[usize::MAX], subsidy, composer_reward);
+            let num_txs_in_buckets = summary
+                .buckets
+                .iter()
+                .map(|bucket| bucket.num_txs)
+                .sum::<usize>();
+            assert_eq!(num_txs_in_buckets, summary.forecasts[0].num_txs);
+            num_txs_in_buckets
+        };
+
+        let txs = make_plenty_mock_transaction_supported_by_primitive_witness(3);
+        for tx in &txs {
+            mempool.insert(tx.clone(), UpgradePriority::Irrelevant);
+        }
+        assert_eq!(3, num_txs_in_summary(&mempool));
+
+        let summary = mempool.fee_summary(&[usize::MAX], subsidy, composer_reward);
+        let total_fee = txs
+            .iter()
+            .map(|tx| tx.kernel.fee)
+            .sum::<NativeCurrencyAmount>();
+        assert_eq!(total_fee, summary.forecasts[0].total_fees);
+        assert_eq!(subsidy + total_fee, summary.forecasts[0].total_reward);
+
+        mempool.remove(txs[0].kernel.txid());
+        assert_eq!(2, num_txs_in_summary(&mempool));
+
+        mempool.clear();
+        assert_eq!(0, num_txs_in_summary(&mempool));
+        assert!(mempool
+            .fee_summary(&[], subsidy, composer_reward)
+            .buckets
+            .is_empty());
+    }
+
     /// Create a mempool with n transactions, all "synced" to the provided
     /// block.
     ///
@@ -1631,6 +2303,73 @@ mod tests {
         )
     }
 
+    #[traced_test]
+    #[apply(shared_tokio_runtime)]
+    async fn transactions_excluded_by_inclusion_policy_are_skipped() {
+        let network = Network::Main;
+        let sync_block = Block::genesis(network);
+        let mut mempool = Mempool::new(
+            ByteSize::gb(1),
+            TxProvingCapability::ProofCollection,
+            &sync_block,
+        );
+        let mutator_set_hash = sync_block.mutator_set_accumulator_after().unwrap().hash();
+        let large_announcement = Announcement::new(vec![BFieldElement::new(1); 100]);
+        let mut excluded_txids = HashSet::new();
+        for (i, mut tx) in make_plenty_mock_transaction_supported_by_invalid_single_proofs(6)
+            .into_iter()
+            .enumerate()
+        {
+            let announcements = match i % 2 {
+                0 => vec![large_announcement.clone()],
+                _ => vec![],
+            };
+            tx.kernel = TransactionKernelModifier::default()
+                .mutator_set_hash(mutator_set_hash)
+                .announcements(announcements)
+                .modify(tx.kernel);
+            if i % 2 == 0 {
+                excluded_txids.insert(tx.kernel.txid());
+            }
+            mempool.insert(tx, UpgradePriority::Irrelevant);
+        }
+
+        let policy = InclusionPolicy::new(Some(99), []);
+        let (included, excluded) = mempool.get_transactions_for_block_composition_with_policy(
+            SIZE_20MB_IN_BYTES,
+            Some(2),
+            &policy,
+        );
+
+        // excluded transactions do not count towards the limit
+        assert_eq!(2, included.len());
+        assert!(included
+            .iter()
+            .all(|tx| !excluded_txids.contains(&tx.kernel.txid())));
+        assert!(!excluded.is_empty());
+        for (txid, reason) in excluded {
+            assert!(excluded_txids.contains(&txid));
+            assert_eq!(
+                ExclusionReason::AnnouncementTooLarge { size: 100, max: 99 },
+                reason
+            );
+        }
+
+        let (all, excluded) = mempool.get_transactions_for_block_composition_with_policy(
+            SIZE_20MB_IN_BYTES,
+            None,
+            &policy,
+        );
+        assert_eq!(3, all.len());
+        assert_eq!(3, excluded.len());
+        assert_eq!(
+            6,
+            mempool
+                .get_transactions_for_block_composition(SIZE_20MB_IN_BYTES, None)
+                .len()
+        );
+    }
+
     #[traced_test]
     #[apply(shared_tokio_runtime)]
     async fn most_dense_proof_collection_test() {
@@ -2435,6 +3174,89 @@ mod tests {
         );
     }
 
+    #[test]
+    fn orphans_enter_mempool_when_their_block_arrives() {
+        use std::net::Ipv4Addr;
+
+        use crate::tests::shared::blocks::invalid_empty_block;
+        use crate::tests::shared::mock_tx::make_mock_transaction_with_mutator_set_hash;
+        use crate::util_types::mutator_set::addition_record::AdditionRecord;
+
+        let network = Network::Main;
+        let genesis_block = Block::genesis(network);
+        let block_1 = invalid_empty_block(&genesis_block, network);
+        let mut mempool = Mempool::new(
+            ByteSize::gb(1),
+            TxProvingCapability::SingleProof,
+            &genesis_block,
+        );
+        let peer = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let now = Timestamp::now();
+
+        // Transactions synced to the tip are not orphans.
+        let synced = make_mock_transaction_with_mutator_set_hash(
+            vec![],
+            vec![AdditionRecord::new(Digest::default())],
+            genesis_block
+                .mutator_set_accumulator_after()
+                .unwrap()
+                .hash(),
+        );
+        assert!(!mempool.insert_orphan(synced, peer, now));
+
+        let orphan = make_mock_transaction_with_mutator_set_hash(
+            vec![],
+            vec![AdditionRecord::new(Digest::default())],
+            block_1.mutator_set_accumulator_after().unwrap().hash(),
+        );
+        assert!(mempool.insert_orphan(orphan.clone(), peer, now));
+        assert!(!mempool.contains(orphan.txid()));
+        assert_eq!(1, mempool.num_orphans());
+
+        let (events, _) = mempool.update_with_block(&block_1).unwrap();
+        assert_eq!(vec![MempoolEvent::AddTx(orphan.kernel.clone())], events);
+        assert!(mempool.contains(orphan.txid()));
+        assert_eq!(0, mempool.num_orphans());
+    }
+
+    #[test]
+    fn only_txs_this_node_updates_count_as_syncable() {
+        use crate::tests::shared::blocks::invalid_empty_block;
+        use crate::tests::shared::mock_tx::make_mock_transaction_with_mutator_set_hash;
+        use crate::util_types::mutator_set::addition_record::AdditionRecord;
+
+        let network = Network::Main;
+        let genesis_block = Block::genesis(network);
+        let block_1 = invalid_empty_block(&genesis_block, network);
+        let mut mempool = Mempool::new(
+            ByteSize::gb(1),
+            TxProvingCapability::SingleProof,
+            &genesis_block,
+        );
+        let mock_tx = |mutator_set_hash: Digest| {
+            make_mock_transaction_with_mutator_set_hash(
+                vec![],
+                vec![AdditionRecord::new(rand::random())],
+                mutator_set_hash,
+            )
+        };
+        let synced_hash = genesis_block
+            .mutator_set_accumulator_after()
+            .unwrap()
+            .hash();
+        let unsynced_hash = block_1.mutator_set_accumulator_after().unwrap().hash();
+
+        mempool.insert(mock_tx(synced_hash), UpgradePriority::Irrelevant);
+        assert_eq!((1, 1), mempool.num_synced_and_syncable_txs());
+
+        // Single-proof backed transactions of others are updated by others.
+        mempool.insert(mock_tx(unsynced_hash), UpgradePriority::Irrelevant);
+        assert_eq!((1, 1), mempool.num_synced_and_syncable_txs());
+
+        mempool.insert(mock_tx(unsynced_hash), UpgradePriority::Critical);
+        assert_eq!((1, 2), mempool.num_synced_and_syncable_txs());
+    }
+
     #[traced_test]
     #[apply(shared_tokio_runtime)]
     async fn conflicting_txs_preserve_highest_fee() {
@@ -2505,6 +3327,14 @@ mod tests {
             assert_eq!(1, MempoolEvent::num_adds(&events));
             assert_eq!(1, mempool.len());
             assert_eq!(*tx_low_fee, *mempool.get(tx_low_fee.kernel.txid()).unwrap());
+            assert_eq!(
+                Some(0),
+                mempool.num_inputs_with_conflicting_spends(tx_low_fee.kernel.txid())
+            );
+            assert_eq!(
+                Some(1.0),
+                mempool.fee_density_percentile(tx_low_fee.kernel.txid())
+            );
         }
 
         // Insert a transaction that spends the same UTXO and has a higher fee.
@@ -2527,6 +3357,16 @@ mod tests {
                 *tx_high_fee,
                 *mempool.get(tx_high_fee.kernel.txid()).unwrap()
             );
+
+            // The replaced transaction is evidence of a double-spend attempt.
+            assert_eq!(
+                Some(tx_high_fee.kernel.inputs.len()),
+                mempool.num_inputs_with_conflicting_spends(tx_high_fee.kernel.txid())
+            );
+            assert_eq!(
+                None,
+                mempool.num_inputs_with_conflicting_spends(tx_low_fee.kernel.txid())
+            );
         }
 
         // Insert a conflicting transaction with a lower fee and verify that it
@@ -2552,6 +3392,110 @@ mod tests {
         }
     }
 
+    #[traced_test]
+    #[apply(shared_tokio_runtime)]
+    async fn insertion_preview_predicts_outcome_of_insert() {
+        // Create a global state object, controlled by a preminer who receives a premine-UTXO.
+        let network = Network::Main;
+        let mut preminer = mock_genesis_global_state(
+            2,
+            WalletEntropy::devnet_wallet(),
+            cli_args::Args::default_with_network(network),
+        )
+        .await;
+        let premine_spending_key = preminer
+            .lock_guard()
+            .await
+            .wallet_state
+            .wallet_entropy
+            .nth_generation_spending_key_for_tests(0);
+        let premine_address = premine_spending_key.to_address();
+        let mut rng = StdRng::seed_from_u64(589111u64);
+
+        let make_transaction_with_fee =
+            |fee: NativeCurrencyAmount,
+             preminer_clone: GlobalStateLock,
+             sender_randomness: Digest| async move {
+                let consensus_rule_set =
+                    ConsensusRuleSet::infer_from(network, BlockHeight::genesis());
+                let in_seven_months =
+                    Block::genesis(network).kernel.header.timestamp + Timestamp::months(7);
+
+                let receiver_data = TxOutput::offchain_native_currency(
+                    NativeCurrencyAmount::coins(1),
+                    sender_randomness,
+                    premine_address.into(),
+                    false,
+                );
+                let tx_outputs: TxOutputList = vec![receiver_data.clone()].into();
+                let config = TxCreationConfig::default()
+                    .recover_change_on_chain(premine_spending_key.into())
+                    .with_prover_capability(TxProvingCapability::ProofCollection);
+                preminer_clone
+                    .api()
+                    .tx_initiator_internal()
+                    .create_transaction(
+                        tx_outputs.clone(),
+                        fee,
+                        in_seven_months,
+                        config,
+                        consensus_rule_set,
+                    )
+                    .await
+                    .expect("producing proof collection should succeed")
+            };
+
+        let tx_low_fee = make_transaction_with_fee(
+            NativeCurrencyAmount::coins(1),
+            preminer.clone(),
+            rng.random(),
+        )
+        .await
+        .transaction;
+        let tx_high_fee = make_transaction_with_fee(
+            NativeCurrencyAmount::coins(10),
+            preminer.clone(),
+            rng.random(),
+        )
+        .await
+        .transaction;
+        let tx_medium_fee = make_transaction_with_fee(
+            NativeCurrencyAmount::coins(4),
+            preminer.clone(),
+            rng.random(),
+        )
+        .await
+        .transaction;
+
+        let mempool = &mut preminer.lock_guard_mut().await.mempool;
+        assert_eq!(
+            InsertionPreview::Inserted { replaces: vec![] },
+            mempool.insertion_preview(&tx_low_fee)
+        );
+        mempool.insert(tx_low_fee.clone().into(), UpgradePriority::Irrelevant);
+
+        assert_eq!(
+            InsertionPreview::Inserted {
+                replaces: vec![tx_low_fee.kernel.txid()]
+            },
+            mempool.insertion_preview(&tx_high_fee)
+        );
+        mempool.insert(tx_high_fee.clone().into(), UpgradePriority::Irrelevant);
+        assert_eq!(
+            InsertionPreview::Duplicate,
+            mempool.insertion_preview(&tx_high_fee)
+        );
+
+        assert_eq!(
+            InsertionPreview::Eclipsed {
+                conflicts: vec![tx_high_fee.kernel.txid()]
+            },
+            mempool.insertion_preview(&tx_medium_fee)
+        );
+        let events = mempool.insert(tx_medium_fee.clone().into(), UpgradePriority::Irrelevant);
+        assert!(events.is_empty());
+    }
+
     #[apply(shared_tokio_runtime)]
     async fn single_proof_status_is_respected_for_block_composition() {
         let network = Network::Main;
@@ -2686,6 +3630,80 @@ mod tests {
         );
     }
 
+    #[apply(shared_tokio_runtime)]
+    async fn evicted_txs_are_spilled_and_still_selected_for_composition_and_relay() {
+        let network = Network::Main;
+        let genesis_block = Block::genesis(network);
+        let data_dir = unit_test_data_directory(network).unwrap();
+        let spillover =
+            MempoolSpillover::new(data_dir.mempool_spillover_dir_path(), ByteSize::gb(1)).unwrap();
+        let mut mempool = Mempool::new(
+            ByteSize::mb(3),
+            TxProvingCapability::ProofCollection,
+            &genesis_block,
+        )
+        .with_spillover(spillover);
+
+        let mutator_set_hash = genesis_block
+            .mutator_set_accumulator_after()
+            .unwrap()
+            .hash();
+        let mut txs = mock_transactions_with_sized_single_proof(7, ByteSize::mb(1))
+            .into_iter()
+            .map(|mut tx| {
+                tx.kernel = TransactionKernelModifier::default()
+                    .mutator_set_hash(mutator_set_hash)
+                    .modify(tx.kernel);
+                tx
+            })
+            .collect_vec();
+        for tx in txs.clone() {
+            mempool.insert(tx, UpgradePriority::Irrelevant);
+        }
+
+        let spillover = mempool.spillover.as_ref().unwrap();
+        assert!(
+            !spillover.is_empty(),
+            "Test assumption: Not all txs can fit into mempool"
+        );
+        assert_eq!(txs.len(), mempool.len() + spillover.len());
+        for tx in &txs {
+            let transaction = match mempool.get(tx.txid()) {
+                Some(transaction) => Some(transaction.clone()),
+                None => mempool.read_spilled(tx.txid()).unwrap().transaction().await,
+            };
+            assert_eq!(Some(tx), transaction.as_ref());
+            assert!(
+                !mempool.accept_transaction(
+                    tx.txid(),
+                    TransactionProofQuality::SingleProof,
+                    mutator_set_hash
+                ),
+                "known transaction must not be requested again"
+            );
+        }
+
+        txs.sort_by_key(|tx| tx.fee_density());
+        txs.reverse();
+        let (selected, _) = mempool
+            .select_for_block_composition(SIZE_20MB_IN_BYTES, None, &InclusionPolicy::default())
+            .read()
+            .await;
+        assert_eq!(txs, selected);
+
+        // A spilled transaction is dropped when a transaction it conflicts
+        // with enters the mempool.
+        let spilled = txs.pop().unwrap();
+        mempool.remove(txs[0].txid());
+        let mut conflicting = txs[0].clone();
+        conflicting.kernel = TransactionKernelModifier::default()
+            .inputs(spilled.kernel.inputs.clone())
+            .modify(conflicting.kernel);
+        mempool.insert(conflicting, UpgradePriority::Irrelevant);
+        assert!(mempool.get(spilled.txid()).is_none());
+        assert!(mempool.read_spilled(spilled.txid()).is_none());
+    }
+
     #[traced_test]
     #[apply(shared_tokio_runtime)]
     async fn get_mempool_size() {
@@ -0,0 +1,47 @@
+use std::collections::HashSet;
+use std::collections::VecDeque;
+
+use crate::util_types::mutator_set::shared::NUM_TRIALS;
+
+/// Number of conflicting spends that are remembered.
+const MAX_NUM_REMEMBERED_SPENDS: usize = 1000;
+
+pub(super) type AbsoluteIndices = [u128; NUM_TRIALS as usize];
+
+/// The inputs, identified by their absolute indices, that were observed to be
+/// spent by more than one transaction that is not the merge of the others.
+///
+/// Such conflicts are evidence of double-spend attempts. They are remembered
+/// even after the losing transaction has left the mempool, since the winning
+/// transaction remains at risk of being replaced until it is mined.
+#[derive(Debug, Clone, Default)]
+pub(super) struct ConflictingSpends {
+    spends: HashSet<AbsoluteIndices>,
+
+    /// In order of observation, for forgetting the oldest.
+    order: VecDeque<AbsoluteIndices>,
+}
+
+impl ConflictingSpends {
+    pub(super) fn record(&mut self, absolute_indices: AbsoluteIndices) {
+        if !self.spends.insert(absolute_indices) {
+            return;
+        }
+
+        self.order.push_back(absolute_indices);
+        if self.order.len() > MAX_NUM_REMEMBERED_SPENDS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.spends.remove(&oldest);
+            }
+        }
+    }
+
+    pub(super) fn contains(&self, absolute_indices: &AbsoluteIndices) -> bool {
+        self.spends.contains(absolute_indices)
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.spends.clear();
+        self.order.clear();
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+
+use get_size2::GetSize;
+use num_traits::Zero;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::api::export::NativeCurrencyAmount;
+use crate::protocol::consensus::transaction::Transaction;
+
+/// Fee totals of the mempool's transactions, bucketed by fee density.
+///
+/// Updated incrementally as transactions enter and leave the mempool, so
+/// summarizing the mempool does not require visiting its transactions.
+/// Transaction `i` falls in bucket `b` iff `2^(b-1) <= fee_i / size_i < 2^b`,
+/// with fee in nau and size in bytes, and bucket 0 holding transactions that
+/// pay less than one nau per byte.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FeeHistogram {
+    buckets: BTreeMap<u32, BucketTotals>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct BucketTotals {
+    num_txs: usize,
+    total_size: usize,
+    total_fee: i128,
+}
+
+/// The transactions of a fee density range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeBucket {
+    /// Lower bound (inclusive) of the fee per byte of the transactions in
+    /// this bucket.
+    pub min_fee_per_byte: NativeCurrencyAmount,
+    pub num_txs: usize,
+
+    /// In bytes.
+    pub total_size: usize,
+    pub total_fee: NativeCurrencyAmount,
+}
+
+/// Projected revenue of the next block, if it includes the mempool's most
+/// fee-dense transactions up to a size cutoff.
+///
+/// Transaction fees go to the guesser, and the block subsidy is divided
+/// between composer and guesser. A node that both composes and guesses earns
+/// [Self::total_reward].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevenueForecast {
+    /// In bytes.
+    pub size_cutoff: usize,
+
+    /// Number of included transactions. Approximate when the cutoff falls
+    /// within a bucket.
+    pub num_txs: usize,
+
+    /// Total size of the included transactions, in bytes.
+    pub total_size: usize,
+    pub total_fees: NativeCurrencyAmount,
+
+    /// The composer's share of the block subsidy.
+    pub composer_reward: NativeCurrencyAmount,
+
+    /// Block subsidy plus fees.
+    pub total_reward: NativeCurrencyAmount,
+}
+
+/// A fee histogram of the mempool and revenue forecasts for the next block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MempoolFeeSummary {
+    /// Non-empty buckets, in descending order of fee density.
+    pub buckets: Vec<FeeBucket>,
+
+    /// One forecast per requested size cutoff, in the requested order.
+    pub forecasts: Vec<RevenueForecast>,
+}
+
+impl GetSize for FeeHistogram {
+    fn get_heap_size(&self) -> usize {
+        self.buckets.len() * size_of::<(u32, BucketTotals)>()
+    }
+}
+
+impl FeeHistogram {
+    fn bucket(fee: i128, size: usize) -> u32 {
+        let fee_per_byte = fee.max(0) / i128::try_from(size.max(1)).unwrap_or(i128::MAX);
+        i128::BITS - fee_per_byte.leading_zeros()
+    }
+
+    fn min_fee_per_byte(bucket: u32) -> NativeCurrencyAmount {
+        match bucket {
+            0 => NativeCurrencyAmount::zero(),
+            b => NativeCurrencyAmount::from_nau(1 << (b - 1)),
+        }
+    }
+
+    pub(crate) fn add(&mut self, transaction: &Transaction) {
+        let fee = transaction.kernel.fee.to_nau();
+        let size = transaction.get_size();
+        let totals = self.buckets.entry(Self::bucket(fee, size)).or_default();
+        totals.num_txs += 1;
+        totals.total_size += size;
+        totals.total_fee += fee;
+    }
+
+    pub(crate) fn remove(&mut self, transaction: &Transaction) {
+        let fee = transaction.kernel.fee.to_nau();
+        let size = transaction.get_size();
+        let bucket = Self::bucket(fee, size);
+        let Some(totals) = self.buckets.get_mut(&bucket) else {
+            return;
+        };
+        totals.num_txs = totals.num_txs.saturating_sub(1);
+        totals.total_size = totals.total_size.saturating_sub(size);
+        totals.total_fee -= fee;
+        if totals.num_txs == 0 {
+            self.buckets.remove(&bucket);
+        }
+    }
+
+    /// The non-empty buckets, in descending order of fee density.
+    pub(crate) fn buckets(&self) -> Vec<FeeBucket> {
+        self.buckets
+            .iter()
+            .rev()
+            .map(|(&bucket, totals)| FeeBucket {
+                min_fee_per_byte: Self::min_fee_per_byte(bucket),
+                num_txs: totals.num_txs,
+                total_size: totals.total_size,
+                total_fee: NativeCurrencyAmount::from_nau(totals.total_fee),
+            })
+            .collect()
+    }
+
+    /// Forecast the revenue of a block that includes the most fee-dense
+    /// transactions fitting in `size_cutoff` bytes.
+    ///
+    /// Within the bucket where the cutoff falls, the fee is assumed to be
+    /// evenly spread over the bucket's bytes.
+    pub(crate) fn forecast(
+        &self,
+        size_cutoff: usize,
+        block_subsidy: NativeCurrencyAmount,
+        composer_reward: NativeCurrencyAmount,
+    ) -> RevenueForecast {
+        let mut num_txs = 0;
+        let mut total_size = 0;
+        let mut total_fees = 0i128;
+        for totals in self.buckets.values().rev() {
+            let remaining = size_cutoff - total_size;
+            if remaining == 0 {
+                break;
+            }
+            if totals.total_size <= remaining {
+                num_txs += totals.num_txs;
+                total_size += totals.total_size;
+                total_fees += totals.total_fee;
+                continue;
+            }
+
+            let fraction = remaining as f64 / totals.total_size as f64;
+            num_txs += (totals.num_txs as f64 * fraction) as usize;
+            total_size += remaining;
+            total_fees += (totals.total_fee as f64 * fraction) as i128;
+        }
+
+        let total_fees = NativeCurrencyAmount::from_nau(total_fees);
+        RevenueForecast {
+            size_cutoff,
+            num_txs,
+            total_size,
+            total_fees,
+            composer_reward,
+            total_reward: block_subsidy + total_fees,
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    fn totals(num_txs: usize, total_size: usize, total_fee: i128) -> BucketTotals {
+        BucketTotals {
+            num_txs,
+            total_size,
+            total_fee,
+        }
+    }
+
+    #[test]
+    fn transactions_are_bucketed_by_fee_density() {
+        assert_eq!(0, FeeHistogram::bucket(0, 100));
+        assert_eq!(0, FeeHistogram::bucket(99, 100));
+        assert_eq!(1, FeeHistogram::bucket(100, 100));
+        assert_eq!(2, FeeHistogram::bucket(200, 100));
+        assert_eq!(2, FeeHistogram::bucket(399, 100));
+        assert_eq!(3, FeeHistogram::bucket(400, 100));
+        assert_eq!(
+            NativeCurrencyAmount::from_nau(4),
+            FeeHistogram::min_fee_per_byte(3)
+        );
+    }
+
+    #[test]
+    fn forecast_includes_most_fee_dense_transactions_first() {
+        let histogram = FeeHistogram {
+            buckets: [(1, totals(4, 400, 400)), (5, totals(2, 100, 2000))].into(),
+        };
+        let subsidy = NativeCurrencyAmount::from_nau(10_000);
+        let composer_reward = NativeCurrencyAmount::from_nau(5_000);
+
+        let small = histogram.forecast(100, subsidy, composer_reward);
+        assert_eq!(2, small.num_txs);
+        assert_eq!(NativeCurrencyAmount::from_nau(2000), small.total_fees);
+        assert_eq!(NativeCurrencyAmount::from_nau(12_000), small.total_reward);
+
+        let partial = histogram.forecast(300, subsidy, composer_reward);
+        assert_eq!(4, partial.num_txs);
+        assert_eq!(300, partial.total_size);
+        assert_eq!(NativeCurrencyAmount::from_nau(2200), partial.total_fees);
+
+        let all = histogram.forecast(10_000, subsidy, composer_reward);
+        assert_eq!(6, all.num_txs);
+        assert_eq!(500, all.total_size);
+        assert_eq!(NativeCurrencyAmount::from_nau(2400), all.total_fees);
+        assert_eq!(composer_reward, all.composer_reward);
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::VecDeque;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
+use crate::state::transaction::transaction_kernel_id::TransactionKernelId;
+
+/// Number of mempool mutations retained for computing diffs. Clients that
+/// poll less often than it takes the mempool to mutate this many times get
+/// the full mempool instead of a diff.
+pub(crate) const MEMPOOL_JOURNAL_CAPACITY: usize = 10_000;
+
+/// Identifies a state of the mempool, as seen by a client polling for
+/// [`MempoolDiff`]s. Opaque to clients, which hand it back with their next
+/// poll.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MempoolDiffToken {
+    /// Distinguishes journals of different runs of the node, whose sequence
+    /// numbers are unrelated.
+    instance: u64,
+
+    /// The number of mutations recorded before the state.
+    sequence: u64,
+}
+
+/// The transactions that entered and left the mempool since a client's
+/// previous poll, as returned by the `mempool_diff` RPC endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MempoolDiff {
+    /// The token to pass with the next poll.
+    pub token: MempoolDiffToken,
+
+    /// True iff the diff is relative to an empty mempool, rather than to the
+    /// state of the previous poll. Happens on the first poll, and when the
+    /// previous poll's token is too old or from before a restart of the
+    /// node. Clients must then discard their view of the mempool.
+    pub reset: bool,
+
+    /// Transactions that are in the mempool now, but were not then.
+    pub added: Vec<TransactionKernelId>,
+
+    /// Transactions that were in the mempool then, but are not now.
+    pub removed: Vec<TransactionKernelId>,
+
+    /// The number of transactions in the mempool now.
+    pub num_txs: usize,
+
+    /// The total size of the transactions in the mempool now, in bytes.
+    pub total_size: usize,
+
+    /// The total fee of the transactions in the mempool now.
+    pub total_fees: NativeCurrencyAmount,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MempoolMutation {
+    Added,
+    Removed,
+}
+
+/// Records which transactions entered and left the mempool, numbered by
+/// sequence, such that changes since a [`MempoolDiffToken`] can be listed.
+/// Only the last [`MEMPOOL_JOURNAL_CAPACITY`] mutations are kept.
+#[derive(Debug, Clone)]
+pub(crate) struct MempoolJournal {
+    instance: u64,
+    next_sequence: u64,
+    entries: VecDeque<(TransactionKernelId, MempoolMutation)>,
+}
+
+impl Default for MempoolJournal {
+    fn default() -> Self {
+        Self {
+            instance: rand::random(),
+            next_sequence: 0,
+            entries: VecDeque::new(),
+        }
+    }
+}
+
+impl MempoolJournal {
+    pub(crate) fn record(&mut self, txid: TransactionKernelId, mutation: MempoolMutation) {
+        if self.entries.len() == MEMPOOL_JOURNAL_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((txid, mutation));
+        self.next_sequence += 1;
+    }
+
+    /// The token identifying the current state.
+    pub(crate) fn token(&self) -> MempoolDiffToken {
+        MempoolDiffToken {
+            instance: self.instance,
+            sequence: self.next_sequence,
+        }
+    }
+
+    /// The mutations since the state identified by `since`, oldest first, or
+    /// `None` if they are not all retained.
+    pub(crate) fn mutations_since(
+        &self,
+        since: MempoolDiffToken,
+    ) -> Option<impl Iterator<Item = &(TransactionKernelId, MempoolMutation)>> {
+        let first_retained = self.next_sequence - self.entries.len() as u64;
+        if since.instance != self.instance
+            || since.sequence < first_retained
+            || since.sequence > self.next_sequence
+        {
+            return None;
+        }
+
+        let skip = usize::try_from(since.sequence - first_retained).ok()?;
+        Some(self.entries.iter().skip(skip))
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    #[test]
+    fn mutations_since_token_are_listed_until_evicted() {
+        let mut journal = MempoolJournal::default();
+        let start = journal.token();
+        let txid = TransactionKernelId::default();
+        journal.record(txid, MempoolMutation::Added);
+        let middle = journal.token();
+        journal.record(txid, MempoolMutation::Removed);
+
+        let since_start = journal.mutations_since(start).unwrap().collect_vec();
+        assert_eq!(2, since_start.len());
+        assert_eq!(
+            vec![&(txid, MempoolMutation::Removed)],
+            journal.mutations_since(middle).unwrap().collect_vec()
+        );
+        assert_eq!(0, journal.mutations_since(journal.token()).unwrap().count());
+
+        let other_run = MempoolJournal::default();
+        assert!(journal.mutations_since(other_run.token()).is_none());
+
+        for _ in 0..MEMPOOL_JOURNAL_CAPACITY {
+            journal.record(txid, MempoolMutation::Added);
+        }
+        assert!(journal.mutations_since(start).is_none());
+        assert!(journal.mutations_since(middle).is_none());
+    }
+}
@@ -0,0 +1,516 @@
+//! Disk tier for mempool transactions that do not fit in RAM.
+//!
+//! When the mempool exceeds its RAM budget, the transactions paying the lowest
+//! fee density are moved to the [`MempoolSpillover`] instead of being
+//! forgotten, such that a spike of high-fee transactions does not evict others
+//! that would still make it into the following blocks. Only single-proof
+//! backed transactions that are irrelevant to this node are spilled: those
+//! can be composed into blocks as they are, and the node never has to update
+//! them.
+//!
+//! An index of the spilled transactions, holding their kernels, is kept in
+//! RAM, such that they can be selected and invalidated without being read
+//! back. Every transaction is stored in a file of its own, which starts with a
+//! header holding the format version, the length of the payload, and a
+//! checksum of it. Files that were truncated or corrupted are detected when
+//! read, and the transaction is dropped.
+//!
+//! The files are written, read and removed by a blocking task that owns the
+//! directory, in the order the operations were requested. Updating the index
+//! therefore never waits for the disk, and spilled transactions are read back
+//! through a [`SpilledRead`] that is awaited without holding the global state
+//! lock.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::ensure;
+use bytesize::ByteSize;
+use get_size2::GetSize;
+use num_rational::BigRational as FeeDensity;
+use priority_queue::DoublePriorityQueue;
+use sha3::Digest as _;
+use sha3::Sha3_256;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+use tracing::debug;
+use tracing::warn;
+
+use crate::application::config::retention_policy::TEMPORARY_FILE_EXTENSION;
+use crate::protocol::consensus::transaction::transaction_kernel::TransactionKernel;
+use crate::protocol::consensus::transaction::Transaction;
+use crate::state::transaction::transaction_kernel_id::TransactionKernelId;
+use crate::util_types::mutator_set::shared::NUM_TRIALS;
+
+const SPILLOVER_FILE_EXTENSION: &str = "tx";
+
+/// Identifies a file as a spilled mempool transaction.
+const MAGIC: [u8; 4] = *b"NMSP";
+
+const FORMAT_VERSION: u8 = 1;
+
+const CHECKSUM_SIZE: usize = 32;
+
+/// Magic, format version, payload length, and checksum.
+const HEADER_SIZE: usize = MAGIC.len() + 1 + std::mem::size_of::<u64>() + CHECKSUM_SIZE;
+
+type AbsoluteIndices = [u128; NUM_TRIALS as usize];
+
+/// What the mempool needs to know about a spilled transaction without reading
+/// it back.
+#[derive(Debug, Clone)]
+pub(crate) struct SpilledTransaction {
+    pub(crate) kernel: TransactionKernel,
+
+    /// Size of the transaction in RAM, as counted by the mempool.
+    pub(crate) size: usize,
+
+    /// Size of the file holding the transaction.
+    file_size: u64,
+}
+
+impl SpilledTransaction {
+    /// Whether the transaction spends an input that `indices` also spend.
+    fn conflicts_with(&self, indices: &HashSet<AbsoluteIndices>) -> bool {
+        self.kernel
+            .inputs
+            .iter()
+            .any(|input| indices.contains(&input.absolute_indices.to_array()))
+    }
+}
+
+/// A file system operation on the spillover directory.
+#[derive(Debug)]
+enum FileOperation {
+    Write(TransactionKernelId, Box<Transaction>),
+    Remove(TransactionKernelId),
+    Read(TransactionKernelId, oneshot::Sender<Option<Transaction>>),
+    #[cfg(test)]
+    Flush(oneshot::Sender<()>),
+}
+
+/// A spilled transaction being read back from disk. Must be awaited without
+/// holding the global state lock.
+#[derive(Debug)]
+pub(crate) struct SpilledRead(oneshot::Receiver<Option<Transaction>>);
+
+impl SpilledRead {
+    /// The spilled transaction, or `None` if its file cannot be read or is
+    /// corrupt.
+    pub(crate) async fn transaction(self) -> Option<Transaction> {
+        self.0.await.ok().flatten()
+    }
+}
+
+/// Transactions moved out of the mempool's RAM budget onto disk. Not
+/// considered part of the mempool.
+///
+/// Only creating the spillover performs blocking file system operations; all
+/// others are performed by a blocking task.
+#[derive(Debug, Clone)]
+pub(crate) struct MempoolSpillover {
+    directory: PathBuf,
+
+    /// Maximum combined size of the spilled files. In bytes.
+    max_disk_size: u64,
+
+    disk_size: u64,
+
+    transactions: HashMap<TransactionKernelId, SpilledTransaction>,
+
+    fee_densities: DoublePriorityQueue<TransactionKernelId, FeeDensity>,
+
+    file_operations: mpsc::UnboundedSender<FileOperation>,
+}
+
+impl MempoolSpillover {
+    /// Creates a spillover in `directory`, discarding transactions spilled by
+    /// a previous run. Peers relay those again if they are still unconfirmed.
+    ///
+    /// Must be called from within a tokio runtime, on which the blocking task
+    /// owning the directory is spawned.
+    pub(crate) fn new(directory: PathBuf, max_disk_size: ByteSize) -> std::io::Result<Self> {
+        if directory.try_exists()? {
+            fs::remove_dir_all(&directory)?;
+        }
+        fs::create_dir_all(&directory)?;
+
+        let (file_operations, file_operations_rx) = mpsc::unbounded_channel();
+        let task_directory = directory.clone();
+        tokio::task::spawn_blocking(move || {
+            perform_file_operations(&task_directory, file_operations_rx)
+        });
+
+        Ok(Self {
+            directory,
+            max_disk_size: max_disk_size.as_u64(),
+            disk_size: 0,
+            transactions: HashMap::new(),
+            fee_densities: DoublePriorityQueue::new(),
+            file_operations,
+        })
+    }
+
+    #[cfg(test)]
+    fn path(&self, txid: TransactionKernelId) -> PathBuf {
+        path(&self.directory, txid)
+    }
+
+    /// Wait for all requested file system operations to complete.
+    #[cfg(test)]
+    pub(crate) async fn flush(&self) {
+        let (done, done_rx) = oneshot::channel();
+        self.request(FileOperation::Flush(done));
+        let _ = done_rx.await;
+    }
+
+    fn request(&self, operation: FileOperation) {
+        if self.file_operations.send(operation).is_err() {
+            warn!("Task owning mempool spillover directory has stopped");
+        }
+    }
+
+    /// Number of spilled transactions.
+    pub(crate) fn len(&self) -> usize {
+        self.transactions.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+
+    pub(crate) fn contains(&self, txid: TransactionKernelId) -> bool {
+        self.transactions.contains_key(&txid)
+    }
+
+    pub(crate) fn get(&self, txid: TransactionKernelId) -> Option<&SpilledTransaction> {
+        self.transactions.get(&txid)
+    }
+
+    /// The spilled transactions, in order of descending fee density.
+    pub(crate) fn fee_density_iter(
+        &self,
+    ) -> impl Iterator<Item = (TransactionKernelId, FeeDensity)> {
+        self.fee_densities.clone().into_sorted_iter().rev()
+    }
+
+    /// Spill the transaction to disk. Then, if the spilled transactions
+    /// exceed the disk budget, drop those with the lowest fee density.
+    pub(crate) fn spill(&mut self, transaction: Transaction) {
+        let txid = transaction.txid();
+        self.remove(txid);
+
+        let file_size = match bincode::serialized_size(&transaction) {
+            Ok(payload_size) => HEADER_SIZE as u64 + payload_size,
+            Err(error) => {
+                warn!("Failed to serialize transaction {txid} for spilling: {error}");
+                return;
+            }
+        };
+
+        self.disk_size += file_size;
+        self.transactions.insert(
+            txid,
+            SpilledTransaction {
+                kernel: transaction.kernel.clone(),
+                size: transaction.get_size(),
+                file_size,
+            },
+        );
+        self.fee_densities.push(txid, transaction.fee_density());
+        self.request(FileOperation::Write(txid, Box::new(transaction)));
+        debug!("Spilled transaction {txid} to disk");
+
+        while self.disk_size > self.max_disk_size {
+            let Some((evicted, _)) = self.fee_densities.peek_min() else {
+                break;
+            };
+            let evicted = *evicted;
+            debug!("Mempool spillover is full; dropping transaction {evicted}");
+            self.remove(evicted);
+        }
+    }
+
+    /// Start reading a spilled transaction back. Returns `None` if the
+    /// transaction is not spilled.
+    pub(crate) fn read(&self, txid: TransactionKernelId) -> Option<SpilledRead> {
+        if !self.contains(txid) {
+            return None;
+        }
+
+        let (transaction, transaction_rx) = oneshot::channel();
+        self.request(FileOperation::Read(txid, transaction));
+
+        Some(SpilledRead(transaction_rx))
+    }
+
+    /// Remove a spilled transaction. Returns whether it was spilled.
+    pub(crate) fn remove(&mut self, txid: TransactionKernelId) -> bool {
+        let Some(spilled) = self.transactions.remove(&txid) else {
+            return false;
+        };
+        self.fee_densities.remove(&txid);
+        self.disk_size -= spilled.file_size;
+        self.request(FileOperation::Remove(txid));
+
+        true
+    }
+
+    /// Remove the spilled transactions that spend any input `transaction`
+    /// spends.
+    pub(crate) fn remove_conflicts(&mut self, transaction: &Transaction) {
+        let indices: HashSet<_> = transaction
+            .kernel
+            .inputs
+            .iter()
+            .map(|input| input.absolute_indices.to_array())
+            .collect();
+        self.retain(|_, spilled| !spilled.conflicts_with(&indices));
+    }
+
+    /// Remove the spilled transactions that do not satisfy the predicate.
+    pub(crate) fn retain<F>(&mut self, mut predicate: F)
+    where
+        F: FnMut(TransactionKernelId, &SpilledTransaction) -> bool,
+    {
+        let victims = self
+            .transactions
+            .iter()
+            .filter(|(txid, spilled)| !predicate(**txid, spilled))
+            .map(|(txid, _)| *txid)
+            .collect::<Vec<_>>();
+        for txid in victims {
+            self.remove(txid);
+        }
+    }
+
+    /// Remove all spilled transactions.
+    pub(crate) fn clear(&mut self) {
+        self.retain(|_, _| false);
+    }
+}
+
+/// Perform the requested file system operations, in order, until the
+/// spillover is dropped.
+fn perform_file_operations(
+    directory: &Path,
+    mut operations: mpsc::UnboundedReceiver<FileOperation>,
+) {
+    while let Some(operation) = operations.blocking_recv() {
+        match operation {
+            FileOperation::Write(txid, transaction) => write(directory, txid, &transaction),
+            FileOperation::Remove(txid) => remove_file(&path(directory, txid)),
+            FileOperation::Read(txid, transaction) => {
+                let _ = transaction.send(read(directory, txid));
+            }
+            #[cfg(test)]
+            FileOperation::Flush(done) => {
+                let _ = done.send(());
+            }
+        }
+    }
+}
+
+fn path(directory: &Path, txid: TransactionKernelId) -> PathBuf {
+    directory.join(format!("{txid}.{SPILLOVER_FILE_EXTENSION}"))
+}
+
+fn write(directory: &Path, txid: TransactionKernelId, transaction: &Transaction) {
+    let bytes = match encode(transaction) {
+        Ok(bytes) => bytes,
+        Err(error) => {
+            warn!("Failed to serialize transaction {txid} for spilling: {error}");
+            return;
+        }
+    };
+
+    // Write under a temporary name so that a crash never leaves a partially
+    // written file under the final name.
+    let path = path(directory, txid);
+    let temporary_path = path.with_extension(TEMPORARY_FILE_EXTENSION);
+    let result =
+        fs::write(&temporary_path, &bytes).and_then(|()| fs::rename(&temporary_path, &path));
+    if let Err(error) = result {
+        warn!(
+            "Failed to spill transaction {txid} to {}: {error}",
+            path.display()
+        );
+        let _ = fs::remove_file(&temporary_path);
+    }
+}
+
+fn read(directory: &Path, txid: TransactionKernelId) -> Option<Transaction> {
+    let path = path(directory, txid);
+    let transaction = fs::read(&path)
+        .map_err(anyhow::Error::from)
+        .and_then(|bytes| decode(&bytes))
+        .and_then(|transaction| {
+            ensure!(
+                transaction.txid() == txid,
+                "file holds transaction {}",
+                transaction.txid()
+            );
+            Ok(transaction)
+        });
+
+    match transaction {
+        Ok(transaction) => Some(transaction),
+        Err(error) => {
+            warn!(
+                "Failed to read spilled transaction {txid} from {}: {error}",
+                path.display()
+            );
+            None
+        }
+    }
+}
+
+fn remove_file(path: &Path) {
+    if let Err(error) = fs::remove_file(path) {
+        warn!("Failed to remove {}: {error}", path.display());
+    }
+}
+
+fn encode(transaction: &Transaction) -> bincode::Result<Vec<u8>> {
+    let payload = bincode::serialize(transaction)?;
+
+    let mut bytes = Vec::with_capacity(HEADER_SIZE + payload.len());
+    bytes.extend_from_slice(&MAGIC);
+    bytes.push(FORMAT_VERSION);
+    bytes.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+    bytes.extend_from_slice(&Sha3_256::digest(&payload));
+    bytes.extend_from_slice(&payload);
+
+    Ok(bytes)
+}
+
+fn decode(bytes: &[u8]) -> anyhow::Result<Transaction> {
+    ensure!(bytes.len() >= HEADER_SIZE, "file is truncated");
+    let (header, payload) = bytes.split_at(HEADER_SIZE);
+    let (magic, header) = header.split_at(MAGIC.len());
+    let (version, header) = header.split_at(1);
+    let (length, checksum) = header.split_at(std::mem::size_of::<u64>());
+
+    ensure!(magic == MAGIC, "not a spilled transaction");
+    ensure!(
+        version[0] == FORMAT_VERSION,
+        "unsupported format version {}",
+        version[0]
+    );
+    let length = u64::from_be_bytes(length.try_into()?);
+    ensure!(
+        length == payload.len() as u64,
+        "expected {length} bytes of payload, found {}",
+        payload.len()
+    );
+    ensure!(
+        Sha3_256::digest(payload).as_slice() == checksum,
+        "checksum mismatch"
+    );
+
+    Ok(bincode::deserialize(payload)?)
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use macro_rules_attr::apply;
+
+    use super::*;
+    use crate::application::config::network::Network;
+    use crate::tests::shared::files::unit_test_data_directory;
+    use crate::tests::shared::mock_tx::mock_transactions_with_sized_single_proof;
+    use crate::tests::shared_tokio_runtime;
+
+    fn spillover(max_disk_size: ByteSize) -> MempoolSpillover {
+        let data_dir = unit_test_data_directory(Network::Main).unwrap();
+        MempoolSpillover::new(data_dir.mempool_spillover_dir_path(), max_disk_size).unwrap()
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn spilled_transactions_are_read_back() {
+        let mut spillover = spillover(ByteSize::mb(10));
+        let txs = mock_transactions_with_sized_single_proof(3, ByteSize::kb(10));
+        for tx in &txs {
+            spillover.spill(tx.clone());
+        }
+        assert_eq!(3, spillover.len());
+
+        for tx in &txs {
+            let read = spillover.read(tx.txid()).unwrap();
+            assert_eq!(Some(tx), read.transaction().await.as_ref());
+        }
+
+        let txid = txs[0].txid();
+        let read = spillover.read(txid).unwrap();
+        assert!(spillover.remove(txid));
+        assert_eq!(
+            Some(&txs[0]),
+            read.transaction().await.as_ref(),
+            "read requested before removal must succeed"
+        );
+        assert!(!spillover.contains(txid));
+        assert!(spillover.read(txid).is_none());
+
+        spillover.flush().await;
+        assert!(!spillover.path(txid).exists());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn corrupt_files_are_rejected() {
+        let mut spillover = spillover(ByteSize::mb(10));
+        let tx = mock_transactions_with_sized_single_proof(1, ByteSize::kb(10)).remove(0);
+        let txid = tx.txid();
+        spillover.spill(tx);
+        spillover.flush().await;
+
+        let path = spillover.path(txid);
+        let mut bytes = fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        fs::write(&path, &bytes).unwrap();
+        assert_eq!(None, spillover.read(txid).unwrap().transaction().await);
+
+        bytes.truncate(last);
+        fs::write(&path, &bytes).unwrap();
+        assert_eq!(None, spillover.read(txid).unwrap().transaction().await);
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn lowest_fee_densities_are_dropped_when_disk_budget_is_exceeded() {
+        let mut txs = mock_transactions_with_sized_single_proof(5, ByteSize::kb(10));
+        txs.sort_unstable_by_key(|tx| tx.fee_density());
+        let max_file_size = txs
+            .iter()
+            .map(|tx| encode(tx).unwrap().len() as u64)
+            .max()
+            .unwrap();
+
+        let mut spillover = spillover(ByteSize::b(3 * max_file_size));
+        for tx in &txs {
+            spillover.spill(tx.clone());
+        }
+
+        assert!((3..txs.len()).contains(&spillover.len()));
+        assert!(spillover.disk_size <= spillover.max_disk_size);
+        let num_dropped = txs.len() - spillover.len();
+        for (i, tx) in txs.iter().enumerate() {
+            assert_eq!(i >= num_dropped, spillover.contains(tx.txid()));
+        }
+        assert_eq!(
+            txs.last().unwrap().txid(),
+            spillover.fee_density_iter().next().unwrap().0
+        );
+
+        spillover.flush().await;
+        let disk_size = txs
+            .iter()
+            .filter(|tx| spillover.contains(tx.txid()))
+            .map(|tx| fs::metadata(spillover.path(tx.txid())).unwrap().len())
+            .sum::<u64>();
+        assert_eq!(spillover.disk_size, disk_size);
+    }
+}
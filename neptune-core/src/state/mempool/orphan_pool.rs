@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::time::Duration;
+
+use tasm_lib::prelude::Digest;
+
+use crate::protocol::consensus::transaction::transaction_kernel::TransactionKernelId;
+use crate::protocol::consensus::transaction::Transaction;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+
+/// Bounds on the [`OrphanPool`], protecting against peers that fill it with
+/// transactions whose block never arrives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct OrphanPoolLimits {
+    /// Maximum number of orphans kept. Zero disables the pool.
+    pub(crate) max_num_transactions: usize,
+
+    /// Maximum number of orphans kept from any one peer.
+    pub(crate) max_num_transactions_per_peer: usize,
+
+    /// Time after which an orphan is dropped if its block has not arrived.
+    pub(crate) expiry: Duration,
+}
+
+impl Default for OrphanPoolLimits {
+    fn default() -> Self {
+        Self {
+            max_num_transactions: 100,
+            max_num_transactions_per_peer: 10,
+            expiry: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Orphan {
+    transaction: Transaction,
+    received_from: IpAddr,
+    received_at: Timestamp,
+}
+
+/// Transactions received from peers that are synced to a mutator set this node
+/// does not know yet.
+///
+/// This happens when a peer has already applied a block that this node has
+/// not, and updated the transaction to the mutator set after that block. Such
+/// transactions are kept here, keyed by the mutator set they depend on, until
+/// the block producing that mutator set arrives. They are not considered part
+/// of the mempool.
+#[derive(Debug, Clone)]
+pub(crate) struct OrphanPool {
+    limits: OrphanPoolLimits,
+    orphans: HashMap<TransactionKernelId, Orphan>,
+    by_mutator_set_hash: HashMap<Digest, HashSet<TransactionKernelId>>,
+    num_per_peer: HashMap<IpAddr, usize>,
+}
+
+impl OrphanPool {
+    pub(crate) fn new(limits: OrphanPoolLimits) -> Self {
+        Self {
+            limits,
+            orphans: HashMap::default(),
+            by_mutator_set_hash: HashMap::default(),
+            num_per_peer: HashMap::default(),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.orphans.len()
+    }
+
+    pub(crate) fn contains(&self, txid: TransactionKernelId) -> bool {
+        self.orphans.contains_key(&txid)
+    }
+
+    /// Keep a transaction until the block producing its mutator set arrives.
+    /// The oldest orphan is dropped to make room if the pool is full.
+    ///
+    /// Returns false if the transaction is already kept, or if the pool is
+    /// disabled or holds the maximum number of orphans from this peer.
+    pub(super) fn insert(
+        &mut self,
+        transaction: Transaction,
+        received_from: IpAddr,
+        now: Timestamp,
+    ) -> bool {
+        self.prune_expired(now);
+
+        let txid = transaction.kernel.txid();
+        let num_from_peer = self.num_per_peer.get(&received_from).copied();
+        if self.limits.max_num_transactions == 0
+            || self.contains(txid)
+            || num_from_peer.unwrap_or_default() >= self.limits.max_num_transactions_per_peer
+        {
+            return false;
+        }
+
+        if self.len() >= self.limits.max_num_transactions {
+            let oldest = self
+                .orphans
+                .iter()
+                .min_by_key(|(_, orphan)| orphan.received_at)
+                .map(|(txid, _)| *txid);
+            if let Some(oldest) = oldest {
+                self.remove(oldest);
+            }
+        }
+
+        self.by_mutator_set_hash
+            .entry(transaction.kernel.mutator_set_hash)
+            .or_default()
+            .insert(txid);
+        *self.num_per_peer.entry(received_from).or_default() += 1;
+        self.orphans.insert(
+            txid,
+            Orphan {
+                transaction,
+                received_from,
+                received_at: now,
+            },
+        );
+
+        true
+    }
+
+    pub(super) fn remove(&mut self, txid: TransactionKernelId) -> Option<Transaction> {
+        let orphan = self.orphans.remove(&txid)?;
+
+        let mutator_set_hash = orphan.transaction.kernel.mutator_set_hash;
+        if let Some(txids) = self.by_mutator_set_hash.get_mut(&mutator_set_hash) {
+            txids.remove(&txid);
+            if txids.is_empty() {
+                self.by_mutator_set_hash.remove(&mutator_set_hash);
+            }
+        }
+
+        if let Some(num_from_peer) = self.num_per_peer.get_mut(&orphan.received_from) {
+            *num_from_peer -= 1;
+            if *num_from_peer == 0 {
+                self.num_per_peer.remove(&orphan.received_from);
+            }
+        }
+
+        Some(orphan.transaction)
+    }
+
+    /// Remove and return all orphans synced to the given mutator set.
+    pub(super) fn resolve(&mut self, mutator_set_hash: Digest) -> Vec<Transaction> {
+        let txids = self
+            .by_mutator_set_hash
+            .get(&mutator_set_hash)
+            .cloned()
+            .unwrap_or_default();
+
+        txids
+            .into_iter()
+            .filter_map(|txid| self.remove(txid))
+            .collect()
+    }
+
+    /// Drop the orphans whose block did not arrive in time.
+    pub(super) fn prune_expired(&mut self, now: Timestamp) {
+        let expiry = Timestamp::millis(
+            self.limits
+                .expiry
+                .as_millis()
+                .try_into()
+                .unwrap_or(u64::MAX),
+        );
+        let expired = self
+            .orphans
+            .iter()
+            .filter(|(_, orphan)| orphan.received_at + expiry < now)
+            .map(|(txid, _)| *txid)
+            .collect::<Vec<_>>();
+        for txid in expired {
+            self.remove(txid);
+        }
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.orphans.clear();
+        self.by_mutator_set_hash.clear();
+        self.num_per_peer.clear();
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use rand::random;
+
+    use super::*;
+    use crate::tests::shared::mock_tx::make_mock_transaction_with_mutator_set_hash;
+    use crate::util_types::mutator_set::addition_record::AdditionRecord;
+
+    fn orphan(mutator_set_hash: Digest) -> Transaction {
+        make_mock_transaction_with_mutator_set_hash(
+            vec![],
+            vec![AdditionRecord::new(random())],
+            mutator_set_hash,
+        )
+    }
+
+    fn peer(i: u8) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::new(10, 0, 0, i))
+    }
+
+    #[test]
+    fn orphans_are_resolved_by_their_mutator_set() {
+        let mut pool = OrphanPool::new(OrphanPoolLimits::default());
+        let now = Timestamp::now();
+        let (hash_a, hash_b) = (random(), random());
+
+        let a = orphan(hash_a);
+        let b = orphan(hash_b);
+        assert!(pool.insert(a.clone(), peer(1), now));
+        assert!(pool.insert(b.clone(), peer(1), now));
+        assert!(!pool.insert(a.clone(), peer(2), now));
+        assert_eq!(2, pool.len());
+
+        assert_eq!(vec![a], pool.resolve(hash_a));
+        assert!(pool.resolve(hash_a).is_empty());
+        assert!(pool.contains(b.kernel.txid()));
+        assert_eq!(1, pool.len());
+    }
+
+    #[test]
+    fn peers_cannot_exceed_their_share() {
+        let limits = OrphanPoolLimits {
+            max_num_transactions: 4,
+            max_num_transactions_per_peer: 2,
+            ..Default::default()
+        };
+        let mut pool = OrphanPool::new(limits);
+        let now = Timestamp::now();
+
+        assert!(pool.insert(orphan(random()), peer(1), now));
+        assert!(pool.insert(orphan(random()), peer(1), now));
+        assert!(!pool.insert(orphan(random()), peer(1), now));
+
+        // A full pool drops its oldest orphan for a new one.
+        let oldest = orphan(random());
+        assert!(pool.insert(oldest.clone(), peer(2), now - Timestamp::seconds(1)));
+        assert!(pool.insert(orphan(random()), peer(3), now));
+        assert!(pool.insert(orphan(random()), peer(3), now));
+        assert_eq!(4, pool.len());
+        assert!(!pool.contains(oldest.kernel.txid()));
+    }
+
+    #[test]
+    fn orphans_expire() {
+        let mut pool = OrphanPool::new(OrphanPoolLimits::default());
+        let now = Timestamp::now();
+        let tx = orphan(random());
+        assert!(pool.insert(tx.clone(), peer(1), now));
+
+        pool.prune_expired(now + Timestamp::minutes(5));
+        assert!(pool.contains(tx.kernel.txid()));
+
+        pool.prune_expired(now + Timestamp::minutes(11));
+        assert!(!pool.contains(tx.kernel.txid()));
+        assert!(pool.resolve(tx.kernel.mutator_set_hash).is_empty());
+    }
+
+    #[test]
+    fn zero_limit_disables_pool() {
+        let limits = OrphanPoolLimits {
+            max_num_transactions: 0,
+            ..Default::default()
+        };
+        let mut pool = OrphanPool::new(limits);
+        assert!(!pool.insert(orphan(random()), peer(1), Timestamp::now()));
+    }
+}
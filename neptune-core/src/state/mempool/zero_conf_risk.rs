@@ -0,0 +1,220 @@
+//! Assessment of the risk of accepting an unconfirmed transaction as payment.
+//!
+//! A merchant that hands over goods before the payment is mined risks that a
+//! conflicting transaction, spending the same inputs, is mined instead. The
+//! risk grows with the evidence of such conflicts, and with how unlikely the
+//! transaction is to be picked up by a composer soon: a low fee density, poor
+//! propagation through the network, and a proof that composers cannot include
+//! yet all delay confirmation and leave room for a double-spend.
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::consensus::transaction::transaction_proof::TransactionProofType;
+use crate::state::mempool::Mempool;
+use crate::state::transaction::transaction_kernel_id::TransactionKernelId;
+
+/// The number of announcing peers above which propagation is considered
+/// complete.
+const WELL_PROPAGATED_NUM_PEERS: usize = 8;
+
+/// Scores below this are [`ZeroConfRiskLevel::Low`].
+const LOW_RISK_THRESHOLD: u8 = 25;
+
+/// Scores below this, and not below [`LOW_RISK_THRESHOLD`], are
+/// [`ZeroConfRiskLevel::Medium`].
+const MEDIUM_RISK_THRESHOLD: u8 = 60;
+
+/// The observations that the risk score is computed from.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ZeroConfRiskFactors {
+    /// The fraction of the other mempool transactions that pay a lower fee
+    /// density.
+    pub fee_density_percentile: f64,
+
+    /// The number of inputs that were observed to be spent by a conflicting
+    /// transaction.
+    pub num_conflicting_inputs: usize,
+
+    /// The number of connected peers that announced the transaction.
+    pub num_announcing_peers: usize,
+    pub num_connected_peers: usize,
+
+    pub proof_type: TransactionProofType,
+
+    /// Whether the transaction is synced to the current tip's mutator set.
+    pub is_synced: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display)]
+pub enum ZeroConfRiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+/// The risk of accepting an unconfirmed transaction, scored from 0 (lowest)
+/// to 100 (highest).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ZeroConfRisk {
+    pub transaction_id: TransactionKernelId,
+    pub factors: ZeroConfRiskFactors,
+    pub score: u8,
+    pub level: ZeroConfRiskLevel,
+}
+
+impl ZeroConfRiskFactors {
+    /// Collect the risk factors of a mempool transaction, or return `None` if
+    /// the transaction is not in the mempool.
+    pub(crate) fn new(
+        mempool: &Mempool,
+        transaction_id: TransactionKernelId,
+        num_announcing_peers: usize,
+        num_connected_peers: usize,
+    ) -> Option<Self> {
+        let transaction = mempool.get(transaction_id)?;
+
+        Some(Self {
+            fee_density_percentile: mempool.fee_density_percentile(transaction_id)?,
+            num_conflicting_inputs: mempool.num_inputs_with_conflicting_spends(transaction_id)?,
+            num_announcing_peers,
+            num_connected_peers,
+            proof_type: (&transaction.proof).into(),
+            is_synced: mempool.tx_is_synced(&transaction.kernel),
+        })
+    }
+
+    /// The risk score, from 0 to 100.
+    ///
+    /// Any observed conflicting spend is decisive. Otherwise, the score adds
+    /// up to 30 points for a low fee density, up to 30 points for poor
+    /// propagation, up to 40 points for a proof that composers cannot include
+    /// without upgrading it, and 10 points if the transaction must first be
+    /// updated to the current tip.
+    pub fn score(&self) -> u8 {
+        if self.num_conflicting_inputs > 0 {
+            return 100;
+        }
+
+        let fee_risk = (1.0 - self.fee_density_percentile.clamp(0.0, 1.0)) * 30.0;
+
+        let num_peers_for_full_propagation =
+            self.num_connected_peers.min(WELL_PROPAGATED_NUM_PEERS);
+        let propagation = if num_peers_for_full_propagation == 0 {
+            0.0
+        } else {
+            (self.num_announcing_peers as f64 / num_peers_for_full_propagation as f64).min(1.0)
+        };
+        let propagation_risk = (1.0 - propagation) * 30.0;
+
+        let proof_risk = match self.proof_type {
+            TransactionProofType::PrimitiveWitness => 40.0,
+            TransactionProofType::ProofCollection => 20.0,
+            TransactionProofType::SingleProof => 0.0,
+        };
+        let sync_risk = if self.is_synced { 0.0 } else { 10.0 };
+
+        (fee_risk + propagation_risk + proof_risk + sync_risk)
+            .round()
+            .min(100.0) as u8
+    }
+}
+
+impl ZeroConfRisk {
+    pub(crate) fn new(transaction_id: TransactionKernelId, factors: ZeroConfRiskFactors) -> Self {
+        let score = factors.score();
+        let level = if score < LOW_RISK_THRESHOLD {
+            ZeroConfRiskLevel::Low
+        } else if score < MEDIUM_RISK_THRESHOLD {
+            ZeroConfRiskLevel::Medium
+        } else {
+            ZeroConfRiskLevel::High
+        };
+
+        Self {
+            transaction_id,
+            factors,
+            score,
+            level,
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use rand::random;
+
+    use super::*;
+
+    fn well_propagated_single_proof() -> ZeroConfRiskFactors {
+        ZeroConfRiskFactors {
+            fee_density_percentile: 1.0,
+            num_conflicting_inputs: 0,
+            num_announcing_peers: 8,
+            num_connected_peers: 10,
+            proof_type: TransactionProofType::SingleProof,
+            is_synced: true,
+        }
+    }
+
+    #[test]
+    fn well_propagated_single_proof_with_high_fee_is_low_risk() {
+        let risk = ZeroConfRisk::new(random(), well_propagated_single_proof());
+        assert_eq!(0, risk.score);
+        assert_eq!(ZeroConfRiskLevel::Low, risk.level);
+    }
+
+    #[test]
+    fn conflicting_spend_is_high_risk() {
+        let factors = ZeroConfRiskFactors {
+            num_conflicting_inputs: 1,
+            ..well_propagated_single_proof()
+        };
+        let risk = ZeroConfRisk::new(random(), factors);
+        assert_eq!(100, risk.score);
+        assert_eq!(ZeroConfRiskLevel::High, risk.level);
+    }
+
+    #[test]
+    fn each_factor_raises_the_score() {
+        let baseline = well_propagated_single_proof().score();
+        let riskier = [
+            ZeroConfRiskFactors {
+                fee_density_percentile: 0.5,
+                ..well_propagated_single_proof()
+            },
+            ZeroConfRiskFactors {
+                num_announcing_peers: 1,
+                ..well_propagated_single_proof()
+            },
+            ZeroConfRiskFactors {
+                proof_type: TransactionProofType::ProofCollection,
+                ..well_propagated_single_proof()
+            },
+            ZeroConfRiskFactors {
+                is_synced: false,
+                ..well_propagated_single_proof()
+            },
+        ];
+
+        for factors in riskier {
+            assert!(baseline < factors.score(), "{factors:?}");
+        }
+    }
+
+    #[test]
+    fn unannounced_primitive_witness_without_peers_is_high_risk() {
+        let factors = ZeroConfRiskFactors {
+            fee_density_percentile: 0.0,
+            num_conflicting_inputs: 0,
+            num_announcing_peers: 0,
+            num_connected_peers: 0,
+            proof_type: TransactionProofType::PrimitiveWitness,
+            is_synced: false,
+        };
+        let risk = ZeroConfRisk::new(random(), factors);
+        assert_eq!(100, risk.score);
+        assert_eq!(ZeroConfRiskLevel::High, risk.level);
+    }
+}
@@ -0,0 +1,585 @@
+//! Coordination of the composers of a cluster, so that only one of them proves
+//! a block on any given parent.
+//!
+//! Composers sharing a [`ComposerLeaseStore`] compete for a lease on the tip
+//! before composing on it. The winner renews its lease for as long as it
+//! builds on that tip, and the others stand by. A holder that stops composing
+//! releases its lease. If the holder stops renewing, eg because it crashed,
+//! its lease expires. Either way, one of the standbys takes over.
+//!
+//! Leases expire according to the wall clocks of the composers, so their
+//! clocks must be synchronized.
+
+use std::fmt::Debug;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use anyhow::Context;
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Digest;
+use tokio::task::JoinHandle;
+use tracing::debug;
+use tracing::warn;
+
+/// Number of attempts to claim a lease that other composers compete for,
+/// before giving up until the next try.
+const MAX_CLAIM_ATTEMPTS: usize = 3;
+
+/// Lease files of parents that were not built on for this long are removed.
+const LEASE_FILE_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The right of one composer to compose on a parent block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComposerLease {
+    /// The block to be composed on.
+    pub parent: Digest,
+
+    /// The [instance id](crate::state::networking_state::NetworkingState::instance_id)
+    /// of the composer holding the lease.
+    pub holder: u128,
+
+    /// Time after which the lease can be taken over, unless renewed.
+    pub expires: SystemTime,
+}
+
+impl ComposerLease {
+    pub(crate) fn is_expired(&self, now: SystemTime) -> bool {
+        self.expires <= now
+    }
+}
+
+/// The result of an attempt to acquire a lease.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LeaseClaim {
+    /// The lease was acquired or renewed, and this composer may compose.
+    Acquired(ComposerLease),
+
+    /// Another composer holds the lease. This composer stands by until it
+    /// expires.
+    HeldByOther(ComposerLease),
+}
+
+/// Shared storage through which the composers of a cluster agree on who
+/// composes.
+///
+/// Implementations must guarantee that at most one composer acquires any
+/// given lease, and that a lease is only taken over once it has expired.
+#[async_trait::async_trait]
+pub(crate) trait ComposerLeaseStore: Debug + Send + Sync {
+    /// Acquire the lease on `parent` for `holder`, lasting `duration` from
+    /// `now`. If `holder` already holds it, the lease is renewed.
+    async fn acquire(
+        &self,
+        parent: Digest,
+        holder: u128,
+        now: SystemTime,
+        duration: Duration,
+    ) -> Result<LeaseClaim>;
+
+    /// Let the lease on `parent` expire at `now`, if `holder` holds it, so
+    /// that another composer can take it over.
+    async fn release(&self, parent: Digest, holder: u128, now: SystemTime) -> Result<()>;
+}
+
+/// Stores leases as files in a directory shared by all composers, eg on a
+/// network file system.
+///
+/// Taking over a lease creates a new file, named after the parent and the
+/// generation of the lease, which fails if another composer created it first.
+/// Only the holder writes to its file, to renew the lease.
+#[derive(Debug, Clone)]
+pub(crate) struct FileLeaseStore {
+    directory: PathBuf,
+}
+
+impl FileLeaseStore {
+    pub(crate) fn new(directory: PathBuf) -> Self {
+        Self { directory }
+    }
+
+    fn path(&self, parent: Digest, generation: u64) -> PathBuf {
+        self.directory.join(format!("{parent:x}.{generation}"))
+    }
+
+    /// The latest generation of the lease on `parent`, if any, and remove
+    /// the files of old leases.
+    async fn latest_generation(&self, parent: Digest, now: SystemTime) -> Result<Option<u64>> {
+        let prefix = format!("{parent:x}.");
+        let mut latest = None;
+        let mut entries = tokio::fs::read_dir(&self.directory)
+            .await
+            .with_context(|| format!("Could not read {}", self.directory.display()))?;
+        while let Some(entry) = entries.next_entry().await? {
+            let file_name = entry.file_name();
+            let Some(file_name) = file_name.to_str() else {
+                continue;
+            };
+            if let Some(generation) = file_name
+                .strip_prefix(&prefix)
+                .and_then(|generation| generation.parse::<u64>().ok())
+            {
+                latest = latest.max(Some(generation));
+                continue;
+            }
+
+            let is_stale = entry
+                .metadata()
+                .await
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| {
+                    now.duration_since(modified).unwrap_or_default() > LEASE_FILE_RETENTION
+                });
+            if is_stale {
+                let _ = tokio::fs::remove_file(entry.path()).await;
+            }
+        }
+
+        Ok(latest)
+    }
+
+    /// Read the lease of the given generation. A file that is still being
+    /// written by its creator is read as a fresh lease of an unknown holder.
+    async fn read(
+        &self,
+        parent: Digest,
+        generation: u64,
+        duration: Duration,
+    ) -> Result<ComposerLease> {
+        let path = self.path(parent, generation);
+        let contents = tokio::fs::read(&path)
+            .await
+            .with_context(|| format!("Could not read {}", path.display()))?;
+        if let Ok(lease) = serde_json::from_slice(&contents) {
+            return Ok(lease);
+        }
+
+        let created = tokio::fs::metadata(&path).await?.modified()?;
+        Ok(ComposerLease {
+            parent,
+            holder: 0,
+            expires: created + duration,
+        })
+    }
+
+    /// Create the file of a new generation. Returns `false` if another
+    /// composer created it first.
+    async fn create(&self, generation: u64, lease: &ComposerLease) -> Result<bool> {
+        let path = self.path(lease.parent, generation);
+        let file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .await;
+        match file {
+            Ok(_) => {
+                self.write(generation, lease).await?;
+                Ok(true)
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => Ok(false),
+            Err(e) => Err(e).with_context(|| format!("Could not create {}", path.display())),
+        }
+    }
+
+    /// Replace the contents of a lease file atomically.
+    async fn write(&self, generation: u64, lease: &ComposerLease) -> Result<()> {
+        let path = self.path(lease.parent, generation);
+        let temp_path = path.with_extension(format!("{generation}.{:x}.tmp", lease.holder));
+        tokio::fs::write(&temp_path, serde_json::to_vec(lease)?)
+            .await
+            .with_context(|| format!("Could not write {}", temp_path.display()))?;
+        tokio::fs::rename(&temp_path, &path)
+            .await
+            .with_context(|| format!("Could not write {}", path.display()))
+    }
+}
+
+#[async_trait::async_trait]
+impl ComposerLeaseStore for FileLeaseStore {
+    async fn acquire(
+        &self,
+        parent: Digest,
+        holder: u128,
+        now: SystemTime,
+        duration: Duration,
+    ) -> Result<LeaseClaim> {
+        let own_lease = ComposerLease {
+            parent,
+            holder,
+            expires: now + duration,
+        };
+
+        let mut current = None;
+        for _ in 0..MAX_CLAIM_ATTEMPTS {
+            let next_generation = match self.latest_generation(parent, now).await? {
+                None => 0,
+                Some(generation) => {
+                    let lease = self.read(parent, generation, duration).await?;
+                    if lease.holder == holder {
+                        self.write(generation, &own_lease).await?;
+                        return Ok(LeaseClaim::Acquired(own_lease));
+                    }
+                    if !lease.is_expired(now) {
+                        return Ok(LeaseClaim::HeldByOther(lease));
+                    }
+
+                    current = Some(lease);
+                    generation + 1
+                }
+            };
+
+            if self.create(next_generation, &own_lease).await? {
+                return Ok(LeaseClaim::Acquired(own_lease));
+            }
+        }
+
+        // Other composers keep claiming the lease. One of them holds it now.
+        Ok(LeaseClaim::HeldByOther(current.unwrap_or(ComposerLease {
+            holder: 0,
+            ..own_lease
+        })))
+    }
+
+    async fn release(&self, parent: Digest, holder: u128, now: SystemTime) -> Result<()> {
+        let Some(generation) = self.latest_generation(parent, now).await? else {
+            return Ok(());
+        };
+        let lease = self.read(parent, generation, Duration::ZERO).await?;
+        if lease.holder != holder || lease.is_expired(now) {
+            return Ok(());
+        }
+
+        self.write(
+            generation,
+            &ComposerLease {
+                expires: now,
+                ..lease
+            },
+        )
+        .await
+    }
+}
+
+/// A lease this composer holds, renewed in the background until dropped.
+#[derive(Debug)]
+struct HeldLease {
+    parent: Digest,
+
+    /// Renews the lease until it is lost, and then resolves to the lease of
+    /// the composer that took over.
+    renewal: JoinHandle<ComposerLease>,
+}
+
+impl Drop for HeldLease {
+    fn drop(&mut self) {
+        self.renewal.abort();
+    }
+}
+
+/// Acquires the lease on the tip before composing, and keeps renewing it for
+/// as long as the composer builds on that tip.
+#[derive(Debug)]
+pub(crate) struct ComposerCoordinator {
+    store: Arc<dyn ComposerLeaseStore>,
+    holder: u128,
+    duration: Duration,
+    held: Option<HeldLease>,
+}
+
+impl ComposerCoordinator {
+    pub(crate) fn new(
+        store: Arc<dyn ComposerLeaseStore>,
+        holder: u128,
+        duration: Duration,
+    ) -> Self {
+        Self {
+            store,
+            holder,
+            duration,
+            held: None,
+        }
+    }
+
+    /// Claim the right to compose on `parent`. Returns the lease of the
+    /// composer to stand by for, if another composer holds it.
+    ///
+    /// A lease on any other parent is given up. If the lease store cannot be
+    /// reached, this composer composes anyway, as a duplicate block proposal
+    /// is less harmful than none.
+    pub(crate) async fn claim(&mut self, parent: Digest) -> Option<ComposerLease> {
+        if self
+            .held
+            .as_ref()
+            .is_some_and(|held| held.parent == parent && !held.renewal.is_finished())
+        {
+            return None;
+        }
+        self.held = None;
+
+        let claim = self
+            .store
+            .acquire(parent, self.holder, SystemTime::now(), self.duration)
+            .await;
+        match claim {
+            Ok(LeaseClaim::Acquired(_)) => {
+                debug!("Acquired composer lease on {parent:x}.");
+                let renewal = tokio::spawn(Self::keep_renewed(
+                    self.store.clone(),
+                    parent,
+                    self.holder,
+                    self.duration,
+                ));
+                self.held = Some(HeldLease { parent, renewal });
+                None
+            }
+            Ok(LeaseClaim::HeldByOther(lease)) => Some(lease),
+            Err(e) => {
+                warn!("Could not acquire composer lease, composing anyway: {e:#}");
+                None
+            }
+        }
+    }
+
+    /// Give up the lease held by this composer, if any, because it stopped
+    /// composing, so that a standby can take over right away.
+    pub(crate) async fn release(&mut self) {
+        let Some(held) = self.held.take() else {
+            return;
+        };
+        let parent = held.parent;
+        drop(held);
+
+        match self
+            .store
+            .release(parent, self.holder, SystemTime::now())
+            .await
+        {
+            Ok(()) => debug!("Released composer lease on {parent:x}."),
+            Err(e) => warn!("Could not release composer lease, letting it expire: {e:#}"),
+        }
+    }
+
+    /// Wait until another composer takes over the lease held by this
+    /// composer, and return its lease. Pending forever if no lease is held.
+    ///
+    /// Cancel safe.
+    pub(crate) async fn lease_lost(&mut self) -> ComposerLease {
+        let Some(held) = self.held.as_mut() else {
+            return std::future::pending().await;
+        };
+        let renewal = (&mut held.renewal).await;
+
+        // A finished renewal task must not be polled again.
+        self.held = None;
+        match renewal {
+            Ok(lease) => lease,
+            Err(e) => {
+                warn!("Composer lease renewal failed: {e}");
+                std::future::pending().await
+            }
+        }
+    }
+
+    async fn keep_renewed(
+        store: Arc<dyn ComposerLeaseStore>,
+        parent: Digest,
+        holder: u128,
+        duration: Duration,
+    ) -> ComposerLease {
+        loop {
+            tokio::time::sleep(duration / 3).await;
+            match store
+                .acquire(parent, holder, SystemTime::now(), duration)
+                .await
+            {
+                Ok(LeaseClaim::Acquired(_)) => {}
+                Ok(LeaseClaim::HeldByOther(lease)) => {
+                    warn!(
+                        "Lost composer lease on {parent:x} to composer {:x}.",
+                        lease.holder
+                    );
+                    return lease;
+                }
+                Err(e) => warn!("Could not renew composer lease: {e:#}"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use macro_rules_attr::apply;
+    use rand::random;
+
+    use super::*;
+    use crate::tests::shared_tokio_runtime;
+
+    fn lease_store() -> FileLeaseStore {
+        let directory = std::env::temp_dir().join(format!("composer-leases-{}", random::<u64>()));
+        std::fs::create_dir_all(&directory).unwrap();
+        FileLeaseStore::new(directory)
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn only_one_composer_holds_lease() {
+        let store = lease_store();
+        let parent = random();
+        let duration = Duration::from_secs(60);
+        let now = SystemTime::now();
+        let [alice, bob] = [1, 2];
+
+        let LeaseClaim::Acquired(lease) =
+            store.acquire(parent, alice, now, duration).await.unwrap()
+        else {
+            panic!("first composer must acquire lease");
+        };
+        assert_eq!(alice, lease.holder);
+
+        let claim = store.acquire(parent, bob, now, duration).await.unwrap();
+        assert_eq!(LeaseClaim::HeldByOther(lease), claim);
+
+        // Renewal extends the lease.
+        let later = now + Duration::from_secs(30);
+        let LeaseClaim::Acquired(renewed) =
+            store.acquire(parent, alice, later, duration).await.unwrap()
+        else {
+            panic!("holder must be able to renew lease");
+        };
+        assert_eq!(later + duration, renewed.expires);
+        let claim = store
+            .acquire(parent, bob, now + duration, duration)
+            .await
+            .unwrap();
+        assert_eq!(LeaseClaim::HeldByOther(renewed), claim);
+
+        // Leases on other parents are independent.
+        let other_parent = random();
+        let claim = store
+            .acquire(other_parent, bob, now, duration)
+            .await
+            .unwrap();
+        assert!(matches!(claim, LeaseClaim::Acquired(_)));
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn standby_takes_over_expired_lease() {
+        let store = lease_store();
+        let parent = random();
+        let duration = Duration::from_secs(60);
+        let now = SystemTime::now();
+        let [alice, bob, carol] = [1, 2, 3];
+
+        store.acquire(parent, alice, now, duration).await.unwrap();
+
+        let after_expiry = now + duration;
+        let LeaseClaim::Acquired(lease) = store
+            .acquire(parent, bob, after_expiry, duration)
+            .await
+            .unwrap()
+        else {
+            panic!("standby must take over expired lease");
+        };
+        assert_eq!(bob, lease.holder);
+
+        // The former holder lost the lease, and so does every other composer.
+        for composer in [alice, carol] {
+            let claim = store
+                .acquire(parent, composer, after_expiry, duration)
+                .await
+                .unwrap();
+            assert_eq!(LeaseClaim::HeldByOther(lease), claim);
+        }
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn coordinators_fail_over_once_holder_stops_renewing() {
+        let store: Arc<dyn ComposerLeaseStore> = Arc::new(lease_store());
+        let parent = random();
+        let duration = Duration::from_millis(300);
+        let mut alice = ComposerCoordinator::new(store.clone(), 1, duration);
+        let mut bob = ComposerCoordinator::new(store.clone(), 2, duration);
+
+        assert!(alice.claim(parent).await.is_none());
+        assert!(bob.claim(parent).await.is_some());
+
+        // The holder keeps renewing its lease beyond its original expiry.
+        tokio::time::sleep(2 * duration).await;
+        assert!(alice.claim(parent).await.is_none());
+        let standby_for = bob.claim(parent).await.unwrap();
+        assert_eq!(1, standby_for.holder);
+
+        // Moving on to another parent gives up the lease.
+        let next_parent = random();
+        assert!(alice.claim(next_parent).await.is_none());
+        tokio::time::sleep(2 * duration).await;
+        assert!(bob.claim(parent).await.is_none());
+        assert_eq!(Some(2), alice.claim(parent).await.map(|lease| lease.holder));
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn released_lease_is_taken_over_right_away() {
+        let store: Arc<dyn ComposerLeaseStore> = Arc::new(lease_store());
+        let parent = random();
+        let duration = Duration::from_secs(60);
+        let mut alice = ComposerCoordinator::new(store.clone(), 1, duration);
+        let mut bob = ComposerCoordinator::new(store.clone(), 2, duration);
+
+        assert!(alice.claim(parent).await.is_none());
+        assert!(bob.claim(parent).await.is_some());
+
+        // Releasing a lease that is not held changes nothing.
+        bob.release().await;
+        assert!(bob.claim(parent).await.is_some());
+
+        alice.release().await;
+        assert!(bob.claim(parent).await.is_none());
+        assert_eq!(Some(2), alice.claim(parent).await.map(|lease| lease.holder));
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn holder_learns_of_lost_lease() {
+        let store: Arc<dyn ComposerLeaseStore> = Arc::new(lease_store());
+        let parent = random();
+        let duration = Duration::from_millis(300);
+        let mut alice = ComposerCoordinator::new(store.clone(), 1, duration);
+        assert!(alice.claim(parent).await.is_none());
+
+        // Bob takes over, as if the lease had expired.
+        let after_expiry = SystemTime::now() + duration;
+        let LeaseClaim::Acquired(lease) = store
+            .acquire(parent, 2, after_expiry, Duration::from_secs(60))
+            .await
+            .unwrap()
+        else {
+            panic!("lease must be taken over after expiry");
+        };
+
+        let lost_lease = tokio::time::timeout(2 * duration, alice.lease_lost())
+            .await
+            .unwrap();
+        assert_eq!(lease, lost_lease);
+        assert_eq!(Some(lease), alice.claim(parent).await);
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn lease_being_written_is_held() {
+        let store = lease_store();
+        let parent = random();
+        let duration = Duration::from_secs(60);
+
+        // A file that was just created by another composer, but not written
+        // yet.
+        std::fs::File::create(store.path(parent, 0)).unwrap();
+
+        let claim = store
+            .acquire(parent, 1, SystemTime::now(), duration)
+            .await
+            .unwrap();
+        assert!(matches!(claim, LeaseClaim::HeldByOther(lease) if lease.holder == 0));
+    }
+}
@@ -0,0 +1,139 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Counters shared by the local guesser threads, and read by RPC.
+///
+/// Every thread guesses on its own partition of the nonce space. Claiming a
+/// partition twice would make two threads duplicate each other's work, so it
+/// is counted as an overlap.
+#[derive(Debug)]
+pub(crate) struct GuesserStatistics {
+    start: SystemTime,
+    num_guesses: Vec<AtomicU64>,
+    claimed_partitions: Vec<AtomicBool>,
+    num_overlaps: AtomicU64,
+}
+
+/// Guessing statistics of a single local guesser thread.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GuesserThreadStatistics {
+    pub num_guesses: u64,
+
+    /// Guesses per second since guessing started.
+    pub guess_rate: f64,
+}
+
+/// Guessing statistics of all local guesser threads, since the guesser last
+/// started on a block proposal.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GuessingStatistics {
+    pub elapsed: Duration,
+    pub threads: Vec<GuesserThreadStatistics>,
+
+    /// Number of times a thread was assigned a part of the nonce space that
+    /// another thread was already guessing on. Always zero unless there is a
+    /// bug.
+    pub num_overlaps: u64,
+}
+
+impl GuessingStatistics {
+    /// Guesses per second of all threads combined.
+    pub fn guess_rate(&self) -> f64 {
+        self.threads.iter().map(|thread| thread.guess_rate).sum()
+    }
+}
+
+impl GuesserStatistics {
+    pub(crate) fn new(num_threads: usize) -> Self {
+        Self {
+            start: SystemTime::now(),
+            num_guesses: (0..num_threads).map(|_| AtomicU64::new(0)).collect(),
+            claimed_partitions: (0..num_threads).map(|_| AtomicBool::new(false)).collect(),
+            num_overlaps: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn num_threads(&self) -> usize {
+        self.num_guesses.len()
+    }
+
+    /// Record that a thread started guessing on the given partition of the
+    /// nonce space. Returns false if another thread already claimed it.
+    pub(crate) fn claim_partition(&self, partition: usize) -> bool {
+        let already_claimed = self
+            .claimed_partitions
+            .get(partition)
+            .is_none_or(|claimed| claimed.swap(true, Ordering::Relaxed));
+        if already_claimed {
+            self.num_overlaps.fetch_add(1, Ordering::Relaxed);
+        }
+
+        !already_claimed
+    }
+
+    pub(crate) fn record_guesses(&self, thread_index: usize, num_guesses: u64) {
+        if let Some(counter) = self.num_guesses.get(thread_index) {
+            counter.fetch_add(num_guesses, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn report(&self) -> GuessingStatistics {
+        let elapsed = self.start.elapsed().unwrap_or_default();
+        let threads = self
+            .num_guesses
+            .iter()
+            .map(|num_guesses| {
+                let num_guesses = num_guesses.load(Ordering::Relaxed);
+                let guess_rate = if elapsed.is_zero() {
+                    0.0
+                } else {
+                    num_guesses as f64 / elapsed.as_secs_f64()
+                };
+                GuesserThreadStatistics {
+                    num_guesses,
+                    guess_rate,
+                }
+            })
+            .collect();
+
+        GuessingStatistics {
+            elapsed,
+            threads,
+            num_overlaps: self.num_overlaps.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn claiming_a_partition_twice_is_an_overlap() {
+        let statistics = GuesserStatistics::new(2);
+        assert!(statistics.claim_partition(0));
+        assert!(statistics.claim_partition(1));
+        assert!(!statistics.claim_partition(1));
+        assert!(!statistics.claim_partition(2));
+
+        statistics.record_guesses(0, 5);
+        statistics.record_guesses(0, 2);
+        let report = statistics.report();
+        assert_eq!(2, report.num_overlaps);
+        assert_eq!(
+            vec![7, 0],
+            report
+                .threads
+                .iter()
+                .map(|thread| thread.num_guesses)
+                .collect::<Vec<_>>()
+        );
+    }
+}
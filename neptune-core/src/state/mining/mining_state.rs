@@ -1,10 +1,15 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use itertools::Itertools;
 use tasm_lib::prelude::Digest;
 use tracing::info;
 
+use super::composer_lease::ComposerLease;
+use super::guesser_statistics::GuesserStatistics;
 use super::mining_status::MiningStatus;
+use super::reorg_cooldown::ReorgCooldown;
+use crate::application::config::cli_args;
 use crate::application::loops::mine_loop::coinbase_distribution::CoinbaseDistribution;
 use crate::state::BlockProposal;
 use crate::Block;
@@ -38,6 +43,23 @@ pub struct MiningState {
     // Only the mining task should write to this, anyone can read.
     pub(crate) mining_status: MiningStatus,
 
+    /// Progress of the local guesser threads on the most recent block
+    /// proposal they guessed on. Only the mining task should write to this.
+    pub(crate) guesser_statistics: Option<Arc<GuesserStatistics>>,
+
+    /// Number of times an own block proposal expired without resulting in a
+    /// block, since startup. Only updateable by main loop.
+    pub(crate) num_expired_block_proposals: u64,
+
+    /// Limits how quickly the composer restarts after reorganizations that
+    /// orphaned its work. Only the mining task should write to this.
+    pub(crate) reorg_cooldown: ReorgCooldown,
+
+    /// The lease of the composer this node stands by for, if another composer
+    /// of the cluster holds the lease on the tip. Only the mining task should
+    /// write to this.
+    pub(crate) composer_standby: Option<ComposerLease>,
+
     /// Parameters used to override default coinbase behavior. Can e.g. be used
     /// to set a new coinbase distribution for the next block proposal produced
     /// on this node.
@@ -45,6 +67,13 @@ pub struct MiningState {
 }
 
 impl MiningState {
+    pub(crate) fn new(cli: &cli_args::Args) -> Self {
+        Self {
+            reorg_cooldown: ReorgCooldown::new(cli),
+            ..Default::default()
+        }
+    }
+
     pub(crate) fn overridden_coinbase_distribution(&self) -> Option<CoinbaseDistribution> {
         self.override_coinbase_settings
             .coinbase_distribution
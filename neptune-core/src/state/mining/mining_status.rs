@@ -5,6 +5,9 @@ use std::time::SystemTime;
 use serde::Deserialize;
 use serde::Serialize;
 
+use super::composer_lease::ComposerLease;
+use super::guesser_statistics::GuessingStatistics;
+use super::reorg_cooldown::ReorgCooldownStatus;
 use crate::protocol::consensus::block::Block;
 use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
 
@@ -51,6 +54,17 @@ pub enum MiningStatus {
     Inactive,
 }
 
+/// The mining status, along with the progress of the local guesser threads
+/// while guessing, the composer's budget for reorganizations, and the lease
+/// of the composer this node stands by for, if any.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MiningStatusReport {
+    pub status: MiningStatus,
+    pub guessing: Option<GuessingStatistics>,
+    pub reorg_cooldown: ReorgCooldownStatus,
+    pub composer_standby: Option<ComposerLease>,
+}
+
 impl Display for MiningStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let elapsed_time_exact = match self {
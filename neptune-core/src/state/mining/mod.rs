@@ -1,3 +1,7 @@
 pub mod block_proposal;
+pub mod composer_lease;
+pub mod guesser_statistics;
 pub mod mining_state;
 pub mod mining_status;
+pub mod proposal_verdict;
+pub mod reorg_cooldown;
@@ -0,0 +1,121 @@
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Digest;
+
+use crate::application::config::network::Network;
+use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::protocol::consensus::block::block_validation_error::BlockValidationError;
+use crate::protocol::consensus::block::Block;
+use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+
+/// The outcome of validating a block proposal, typically one received from a
+/// third-party composer by an operator who only guesses.
+///
+/// A proposal is worth guessing on if it extends the current tip, pays the
+/// guesser enough, and is valid in every other respect, see
+/// [`Self::is_acceptable`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProposalVerdict {
+    pub height: BlockHeight,
+    pub prev_block_digest: Digest,
+
+    /// Whether the proposal's predecessor is the current tip.
+    pub extends_tip: bool,
+
+    /// The reward to the guesser who finds a valid nonce, which is the fee of
+    /// the proposal's transaction. `None` if the fee is negative.
+    pub guesser_reward: Option<NativeCurrencyAmount>,
+
+    /// The guesser reward relative to the block subsidy. Can exceed 1 because
+    /// of transaction fees. `None` if the fee is negative.
+    pub guesser_fraction: Option<f64>,
+
+    /// The guesser fraction below which this node does not guess on foreign
+    /// proposals.
+    pub minimum_guesser_fraction: f64,
+
+    /// The first consensus rule violated by the proposal, if any. Includes
+    /// checks of the header linkage and of the block proof, but not of the
+    /// proof-of-work, which proposals do not have yet.
+    pub validation_error: Option<BlockValidationError>,
+}
+
+impl ProposalVerdict {
+    /// Validate `proposal` as the successor of `tip`.
+    ///
+    /// Verifies the block proof, which takes a while.
+    pub(crate) async fn new(
+        proposal: &Block,
+        tip: &Block,
+        now: Timestamp,
+        network: Network,
+        minimum_guesser_fraction: f64,
+    ) -> Self {
+        Self {
+            height: proposal.header().height,
+            prev_block_digest: proposal.header().prev_block_digest,
+            extends_tip: proposal.header().prev_block_digest == tip.hash(),
+            guesser_reward: proposal.body().total_guesser_reward().ok(),
+            guesser_fraction: proposal.relative_guesser_reward().ok(),
+            minimum_guesser_fraction,
+            validation_error: proposal.validate(tip, now, network).await.err(),
+        }
+    }
+
+    pub fn pays_sufficient_guesser_reward(&self) -> bool {
+        self.guesser_fraction
+            .is_some_and(|fraction| fraction >= self.minimum_guesser_fraction)
+    }
+
+    /// Whether the proposal is worth guessing on.
+    pub fn is_acceptable(&self) -> bool {
+        self.extends_tip && self.pays_sufficient_guesser_reward() && self.validation_error.is_none()
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use macro_rules_attr::apply;
+
+    use super::*;
+    use crate::tests::shared::blocks::invalid_empty_block1_with_guesser_fraction;
+    use crate::tests::shared_tokio_runtime;
+
+    #[apply(shared_tokio_runtime)]
+    async fn verdict_reports_guesser_fraction_and_invalid_proof() {
+        let network = Network::Main;
+        let genesis = Block::genesis(network);
+        let proposal = invalid_empty_block1_with_guesser_fraction(network, 0.6).await;
+        let now = proposal.header().timestamp;
+
+        let verdict = ProposalVerdict::new(&proposal, &genesis, now, network, 0.5).await;
+        assert!(verdict.extends_tip);
+        assert_eq!(BlockHeight::from(1u64), verdict.height);
+        assert!((verdict.guesser_fraction.unwrap() - 0.6).abs() < 1e-6);
+        assert!(verdict.pays_sufficient_guesser_reward());
+        assert!(verdict.validation_error.is_some());
+        assert!(!verdict.is_acceptable());
+
+        let demanding = ProposalVerdict::new(&proposal, &genesis, now, network, 0.7).await;
+        assert!(!demanding.pays_sufficient_guesser_reward());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn proposal_not_extending_tip_is_rejected() {
+        let network = Network::Main;
+        let genesis = Block::genesis(network);
+        let proposal = invalid_empty_block1_with_guesser_fraction(network, 0.6).await;
+        let now = proposal.header().timestamp;
+
+        let verdict = ProposalVerdict::new(&proposal, &proposal, now, network, 0.5).await;
+        assert!(!verdict.extends_tip);
+        assert_eq!(
+            Some(BlockValidationError::BlockHeight),
+            verdict.validation_error
+        );
+        assert!(!verdict.is_acceptable());
+        assert_eq!(genesis.hash(), verdict.prev_block_digest);
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+use std::time::Instant;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::application::config::cli_args;
+
+/// Limits how quickly the composer restarts after reorganizations.
+///
+/// A reorganization that orphans the block the composer built on wastes the
+/// composition. To prevent a composer from thrashing while competing chains
+/// keep replacing each other, only `budget` such reorganizations are absorbed
+/// per `cooldown` period. Once the budget is exhausted, composition is
+/// suspended until the oldest reorganization within the period falls out of
+/// it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ReorgCooldown {
+    budget: usize,
+    cooldown: Duration,
+
+    /// Reorganizations within the last cooldown period, oldest first.
+    reorgs: VecDeque<Instant>,
+
+    num_reorgs: u64,
+}
+
+/// The state of the composer's reorganization budget, as reported by the
+/// `mining_status` RPC endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReorgCooldownStatus {
+    /// Number of reorganizations absorbed per cooldown period before
+    /// composition is suspended.
+    pub budget: usize,
+
+    /// Length of the cooldown period. Zero if the policy is disabled.
+    pub cooldown: Duration,
+
+    /// Number of reorganizations that orphaned the composer's work within the
+    /// last cooldown period.
+    pub num_recent_reorgs: usize,
+
+    /// Number of reorganizations that orphaned the composer's work since
+    /// startup.
+    pub num_reorgs: u64,
+
+    /// Time until composition resumes, if it is suspended.
+    pub remaining: Option<Duration>,
+}
+
+impl ReorgCooldown {
+    pub(crate) fn new(cli: &cli_args::Args) -> Self {
+        Self {
+            budget: cli.reorg_compose_budget,
+            cooldown: cli.reorg_compose_cooldown,
+            ..Default::default()
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        !self.cooldown.is_zero()
+    }
+
+    fn forget_expired(&mut self, now: Instant) {
+        while self
+            .reorgs
+            .front()
+            .is_some_and(|reorg| now.saturating_duration_since(*reorg) >= self.cooldown)
+        {
+            self.reorgs.pop_front();
+        }
+    }
+
+    /// Record a reorganization that orphaned the composer's work.
+    pub(crate) fn record_reorg(&mut self, now: Instant) {
+        self.num_reorgs += 1;
+        if !self.is_enabled() {
+            return;
+        }
+
+        self.forget_expired(now);
+        self.reorgs.push_back(now);
+    }
+
+    fn recent_reorgs(&self, now: Instant) -> impl Iterator<Item = &Instant> {
+        self.reorgs
+            .iter()
+            .filter(move |reorg| now.saturating_duration_since(**reorg) < self.cooldown)
+    }
+
+    /// The time at which composition may resume, or `None` if the composer
+    /// may start right away.
+    pub(crate) fn suspended_until(&self, now: Instant) -> Option<Instant> {
+        let recent_reorgs = self.recent_reorgs(now).collect::<Vec<_>>();
+        let index = recent_reorgs
+            .len()
+            .checked_sub(self.budget)?
+            .checked_sub(1)?;
+
+        Some(*recent_reorgs[index] + self.cooldown)
+    }
+
+    pub(crate) fn status(&self, now: Instant) -> ReorgCooldownStatus {
+        ReorgCooldownStatus {
+            budget: self.budget,
+            cooldown: self.cooldown,
+            num_recent_reorgs: self.recent_reorgs(now).count(),
+            num_reorgs: self.num_reorgs,
+            remaining: self
+                .suspended_until(now)
+                .map(|resume| resume.saturating_duration_since(now)),
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    fn reorg_cooldown(budget: usize, cooldown: Duration) -> ReorgCooldown {
+        ReorgCooldown {
+            budget,
+            cooldown,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn composition_is_suspended_once_budget_is_exhausted() {
+        let cooldown = Duration::from_secs(60);
+        let mut reorg_cooldown = reorg_cooldown(2, cooldown);
+        let start = Instant::now();
+
+        reorg_cooldown.record_reorg(start);
+        reorg_cooldown.record_reorg(start + Duration::from_secs(10));
+        assert!(reorg_cooldown
+            .suspended_until(start + Duration::from_secs(10))
+            .is_none());
+
+        let now = start + Duration::from_secs(20);
+        reorg_cooldown.record_reorg(now);
+        assert_eq!(Some(start + cooldown), reorg_cooldown.suspended_until(now));
+
+        let status = reorg_cooldown.status(now);
+        assert_eq!(3, status.num_recent_reorgs);
+        assert_eq!(Some(Duration::from_secs(40)), status.remaining);
+
+        // The oldest reorganization falls out of the period.
+        assert!(reorg_cooldown.suspended_until(start + cooldown).is_none());
+        assert_eq!(2, reorg_cooldown.status(start + cooldown).num_recent_reorgs);
+        assert_eq!(3, reorg_cooldown.status(start + cooldown).num_reorgs);
+    }
+
+    #[test]
+    fn zero_budget_suspends_after_every_reorg() {
+        let cooldown = Duration::from_secs(60);
+        let mut reorg_cooldown = reorg_cooldown(0, cooldown);
+        let now = Instant::now();
+
+        assert!(reorg_cooldown.suspended_until(now).is_none());
+        reorg_cooldown.record_reorg(now);
+        assert_eq!(Some(now + cooldown), reorg_cooldown.suspended_until(now));
+    }
+
+    #[test]
+    fn zero_cooldown_disables_policy() {
+        let mut reorg_cooldown = reorg_cooldown(0, Duration::ZERO);
+        let now = Instant::now();
+
+        reorg_cooldown.record_reorg(now);
+        reorg_cooldown.record_reorg(now);
+        assert!(reorg_cooldown.suspended_until(now).is_none());
+
+        let status = reorg_cooldown.status(now);
+        assert_eq!(0, status.num_recent_reorgs);
+        assert_eq!(2, status.num_reorgs);
+    }
+}
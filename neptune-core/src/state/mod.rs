@@ -1,13 +1,26 @@
+pub mod alerts;
 pub mod archival_state;
+pub mod block_application_progress;
+pub mod block_invalidation;
+pub mod block_validation_cache;
 pub mod blockchain_state;
+pub mod checkpoint;
 pub mod database;
+pub mod event_log;
 pub mod light_state;
+pub mod load_shedding;
 pub mod mempool;
 pub mod mining;
+pub mod mutator_set_statistics;
 pub mod networking_state;
+pub mod proof_upgrade_schedule;
+pub mod release_manifest;
+pub mod replication;
 pub mod shared;
+pub mod sync_status;
 pub mod transaction;
 pub mod wallet;
+pub mod watch_list;
 
 use std::cmp::max;
 use std::collections::HashMap;
@@ -16,56 +29,102 @@ use std::net::SocketAddr;
 use std::ops::Deref;
 use std::ops::DerefMut;
 use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use std::time::SystemTime;
 
+use alerts::AlertKind;
+use alerts::AlertMetrics;
+use alerts::AlertRules;
+use alerts::Alerts;
 use anyhow::bail;
 use anyhow::ensure;
 use anyhow::Result;
+use block_application_progress::BlockApplicationProgressTracker;
+use block_application_progress::BlockApplicationSource;
+use block_invalidation::BlockInvalidations;
+use block_validation_cache::BlockValidationCache;
 use blockchain_state::BlockchainArchivalState;
 use blockchain_state::BlockchainState;
+use checkpoint::Checkpoints;
 use itertools::Itertools;
 use light_state::LightState;
+use load_shedding::LoadShedding;
 use mempool::Mempool;
 use mining::block_proposal::BlockProposal;
 use mining::mining_state::MiningState;
 use mining::mining_status::ComposingWorkInfo;
 use mining::mining_status::GuessingWorkInfo;
 use mining::mining_status::MiningStatus;
+use mutator_set_statistics::MutatorSetGrowthTracker;
+use mutator_set_statistics::MutatorSetStatistics;
 use networking_state::NetworkingState;
 use num_traits::CheckedSub;
 use num_traits::Zero;
+use proof_upgrade_schedule::ProofUpgradeSchedule;
+use release_manifest::UpdateStatus;
+use replication::ReplicationRole;
+use replication::ReplicationStatus;
+use sync_status::SyncStage;
+use sync_status::SyncStageProgress;
+use sync_status::SyncStatus;
 use tasm_lib::triton_vm::prelude::*;
 use tasm_lib::twenty_first::tip5::digest::Digest;
 use tracing::debug;
 use tracing::info;
 use tracing::trace;
 use tracing::warn;
+use transaction::proving_cancellation::ProvingCancellations;
 use transaction::transaction_kernel_id::TransactionKernelId;
 use transaction::tx_creation_artifacts::TxCreationArtifacts;
 use transaction::tx_creation_artifacts::TxCreationArtifactsError;
 use transaction::tx_proving_capability::TxProvingCapability;
+use wallet::sync_reconciliation::WalletReconciliation;
+use wallet::wallet_backup::HeldWalletBackups;
+use wallet::wallet_backup::WalletBackup;
+use wallet::wallet_backup::WalletBackupKey;
+use wallet::wallet_state::IncomingUtxoRecoveryData;
 use wallet::wallet_state::WalletState;
 use wallet::wallet_status::WalletStatus;
+use watch_list::WatchList;
+
+use self::event_log::EventLog;
+use self::event_log::SequencedEvent;
 
 use crate::api;
 use crate::api::export::NeptuneProof;
+use crate::api::tx_initiation::recipient_policy::AddressDenylist;
+use crate::api::tx_initiation::recipient_policy::RecipientPolicy;
 use crate::application::config::cli_args;
 use crate::application::config::data_directory::DataDirectory;
+use crate::application::config::inclusion_policy::InclusionPolicy;
+use crate::application::config::retention_policy::CleanupReport;
+use crate::application::config::retention_policy::RetentionPolicy;
 use crate::application::config::tx_upgrade_filter::TxUpgradeFilter;
+use crate::application::crash_report;
 use crate::application::database::storage::storage_schema::traits::StorageWriter as SW;
 use crate::application::database::storage::storage_vec::traits::*;
 use crate::application::locks::tokio as sync_tokio;
 use crate::application::locks::tokio::AtomicRwReadGuard;
 use crate::application::locks::tokio::AtomicRwWriteGuard;
+use crate::application::loops::channel::backpressure::ChannelMetricsRegistry;
+use crate::application::loops::main_loop::message_latency::MessageLatencyRecorder;
 use crate::application::loops::main_loop::proof_upgrader::ProofCollectionToSingleProof;
 use crate::application::loops::main_loop::proof_upgrader::UpdateMutatorSetDataJob;
 use crate::application::loops::main_loop::proof_upgrader::SEARCH_DEPTH_FOR_BLOCKS_FOR_MS_UPDATE;
 use crate::application::loops::main_loop::upgrade_incentive::UpgradeIncentive;
 use crate::application::loops::mine_loop::composer_parameters::ComposerParameters;
+use crate::application::loops::task_supervisor::TaskStatusRegistry;
+use crate::application::plugin_hooks::HookEvent;
+use crate::application::plugin_hooks::PluginHooks;
+use crate::application::rpc::spending_pin::SpendingPinGuard;
+use crate::application::rpc::spending_pin::SpendingPinKey;
+use crate::application::safe_mode::SafeModeStatus;
 use crate::protocol::consensus::block::block_header::BlockHeader;
 use crate::protocol::consensus::block::block_header::BlockHeaderWithBlockHashWitness;
 use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::protocol::consensus::block::block_validation_error::BlockValidationError;
 use crate::protocol::consensus::block::difficulty_control::ProofOfWork;
 use crate::protocol::consensus::block::mutator_set_update::MutatorSetUpdate;
 use crate::protocol::consensus::block::Block;
@@ -75,14 +134,31 @@ use crate::protocol::consensus::transaction::transaction_kernel::TransactionKern
 use crate::protocol::consensus::transaction::validity::proof_collection::ProofCollection;
 use crate::protocol::consensus::transaction::Transaction;
 use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
+use crate::protocol::peer::bandwidth::BandwidthRegistry;
+use crate::protocol::peer::connection_lifecycle::ConnectionLifecycleRegistry;
+use crate::protocol::peer::fork_signaling;
 use crate::protocol::peer::handshake_data::HandshakeData;
 use crate::protocol::peer::handshake_data::VersionString;
+use crate::protocol::peer::handshake_data::BLOCK_CHUNKS_CAPABILITY;
+use crate::protocol::peer::handshake_data::HEADER_FIRST_RELAY_CAPABILITY;
+use crate::protocol::peer::handshake_data::MESSAGE_SEQUENCE_CAPABILITY;
+use crate::protocol::peer::handshake_data::PING_CAPABILITY;
+use crate::protocol::peer::handshake_data::RENDEZVOUS_CAPABILITY;
+use crate::protocol::peer::handshake_data::RENDEZVOUS_RELAY_CAPABILITY;
+use crate::protocol::peer::handshake_data::WALLET_BACKUP_CAPABILITY;
+use crate::protocol::peer::handshake_data::ZSTD_COMPRESSION_CAPABILITY;
 use crate::protocol::peer::peer_info::PeerInfo;
+use crate::protocol::peer::plausible_deniability;
+use crate::protocol::peer::transaction_announcements::TransactionAnnouncements;
 use crate::protocol::peer::transfer_block::TransferBlock;
 use crate::protocol::peer::SyncChallenge;
 use crate::protocol::peer::SyncChallengeResponse;
 use crate::protocol::peer::SYNC_CHALLENGE_POW_WITNESS_LENGTH;
 use crate::protocol::proof_abstractions::timestamp::Timestamp;
+use crate::state::archival_state::shared_block_store::SharedBlockStore;
+use crate::state::archival_state::shared_block_store::READ_ONLY_STORE_TIMEOUT;
+use crate::state::mempool::mempool_event::MempoolEvent;
+use crate::state::mempool::mempool_spillover::MempoolSpillover;
 use crate::state::mempool::mempool_update_job::MempoolUpdateJob;
 use crate::state::mempool::upgrade_priority::UpgradePriority;
 use crate::state::mining::block_proposal::BlockProposalRejectError;
@@ -166,6 +242,27 @@ pub struct GlobalStateLock {
     // for broadcasting Tx as well as the RPC API.
     // (we might consider renaming the channel.)
     rpc_server_to_main_tx: tokio::sync::mpsc::Sender<RPCServerToMain>,
+
+    /// Progress of applying blocks, readable without acquiring the lock.
+    block_application_progress: BlockApplicationProgressTracker,
+
+    /// The wallet's backup and the backups held for trusted peers, usable
+    /// without acquiring the lock.
+    wallet_backup: Arc<tokio::sync::Mutex<WalletBackup>>,
+    held_wallet_backups: Arc<tokio::sync::Mutex<HeldWalletBackups>>,
+
+    /// Numbered events for replay to subscribers, usable without acquiring
+    /// the lock. May be locked while holding the global state lock, but not
+    /// the other way around.
+    event_log: Arc<tokio::sync::Mutex<EventLog>>,
+
+    /// The peers that announced recent transactions, usable without acquiring
+    /// the lock.
+    transaction_announcements: Arc<std::sync::Mutex<TransactionAnnouncements>>,
+
+    /// The shared block store, if this node only reads from it. Waiting for
+    /// its writer happens without acquiring the lock.
+    read_only_shared_block_store: Option<Arc<SharedBlockStore>>,
 }
 
 impl GlobalStateLock {
@@ -174,6 +271,17 @@ impl GlobalStateLock {
         rpc_server_to_main_tx: tokio::sync::mpsc::Sender<RPCServerToMain>,
     ) -> Self {
         let cli = global_state.cli.clone();
+        let block_application_progress = global_state.block_application_progress.clone();
+        let wallet_backup = global_state.wallet_backup.clone();
+        let held_wallet_backups = global_state.held_wallet_backups.clone();
+        let event_log = global_state.event_log.clone();
+        let transaction_announcements = global_state.net.transaction_announcements.clone();
+        let read_only_shared_block_store = match &global_state.chain {
+            BlockchainState::Archival(chain) => {
+                chain.archival_state.read_only_shared_block_store().cloned()
+            }
+            BlockchainState::Light(_) => None,
+        };
         let global_state_lock = sync_tokio::AtomicRw::from((
             global_state,
             Some("GlobalState"),
@@ -184,6 +292,12 @@ impl GlobalStateLock {
             global_state_lock,
             cli,
             rpc_server_to_main_tx,
+            block_application_progress,
+            wallet_backup,
+            held_wallet_backups,
+            event_log,
+            transaction_announcements,
+            read_only_shared_block_store,
         }
     }
 
@@ -228,6 +342,24 @@ impl GlobalStateLock {
         self.lock_guard_mut().await.flush_databases().await
     }
 
+    /// rotate and delete files in the data directory, according to the
+    /// configured [RetentionPolicy].
+    pub(crate) async fn clean_data_directory(&self) -> Result<CleanupReport> {
+        let data_directory = self
+            .lock_guard()
+            .await
+            .wallet_state
+            .configuration
+            .data_directory()
+            .clone();
+        let policy = RetentionPolicy::from_cli(self.cli());
+
+        Ok(
+            tokio::task::spawn_blocking(move || policy.enforce(&data_directory, SystemTime::now()))
+                .await?,
+        )
+    }
+
     /// access the public Api in mutable context
     pub fn api_mut(&mut self) -> api::Api {
         self.clone().into()
@@ -255,9 +387,60 @@ impl GlobalStateLock {
 
     /// store a block (non coinbase)
     pub async fn set_new_tip(&mut self, new_block: Block) -> Result<Vec<MempoolUpdateJob>> {
+        self.wait_for_shared_block_store(&[new_block.hash()])
+            .await?;
         self.lock_guard_mut().await.set_new_tip(new_block).await
     }
 
+    /// Return true iff the blocks with the given digests can be stored right
+    /// away, ie unless this node only reads from a shared block store whose
+    /// writer has not stored all of them yet.
+    pub(crate) async fn blocks_are_in_shared_block_store(&self, block_digests: &[Digest]) -> bool {
+        let Some(shared_block_store) = &self.read_only_shared_block_store else {
+            return true;
+        };
+
+        for block_digest in block_digests {
+            match shared_block_store
+                .wait_for(*block_digest, Duration::ZERO)
+                .await
+            {
+                Ok(Some(_)) => {}
+                Ok(None) => return false,
+                Err(e) => {
+                    warn!("Could not look up block {block_digest:x} in shared block store: {e}");
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Wait for the node writing to the shared block store to store the blocks
+    /// with the given digests, if this node only reads from such a store. Must
+    /// be called before storing the blocks, and without holding the lock, as
+    /// the writer can take a while to store them.
+    pub(crate) async fn wait_for_shared_block_store(&self, block_digests: &[Digest]) -> Result<()> {
+        let Some(shared_block_store) = &self.read_only_shared_block_store else {
+            return Ok(());
+        };
+
+        for block_digest in block_digests {
+            let location = shared_block_store
+                .wait_for(*block_digest, READ_ONLY_STORE_TIMEOUT)
+                .await?;
+            if location.is_none() {
+                bail!(
+                    "block {block_digest:x} was not stored by the writer of shared block store {}",
+                    shared_block_store.dir().display()
+                );
+            }
+        }
+
+        Ok(())
+    }
+
     /// resync membership proofs
     pub async fn resync_membership_proofs(&mut self) -> Result<()> {
         self.lock_guard_mut().await.resync_membership_proofs().await
@@ -286,6 +469,68 @@ impl GlobalStateLock {
         self.rpc_server_to_main_tx.clone()
     }
 
+    /// Progress of applying blocks. Can be read while the lock is held, eg by
+    /// a long block import.
+    pub(crate) fn block_application_progress(&self) -> &BlockApplicationProgressTracker {
+        &self.block_application_progress
+    }
+
+    /// The wallet's backup to trusted peers. Must not be locked while holding
+    /// the global state lock.
+    pub(crate) fn wallet_backup(&self) -> &tokio::sync::Mutex<WalletBackup> {
+        &self.wallet_backup
+    }
+
+    /// The backups held for trusted peers. Must not be locked while holding
+    /// the global state lock.
+    pub(crate) fn held_wallet_backups(&self) -> &tokio::sync::Mutex<HeldWalletBackups> {
+        &self.held_wallet_backups
+    }
+
+    /// The peers that announced recent transactions. Never held across an
+    /// `.await`, so it may be locked while holding the global state lock.
+    pub(crate) fn transaction_announcements(
+        &self,
+    ) -> std::sync::MutexGuard<'_, TransactionAnnouncements> {
+        self.transaction_announcements.lock().unwrap()
+    }
+
+    /// Replay the events that `subscriber` has not acknowledged yet and that
+    /// come after `since`. See [`EventLog::replay`].
+    ///
+    /// Locking:
+    ///   * acquires the event log's lock, but not the global state lock
+    pub(crate) async fn replay_events(&self, subscriber: &str, since: u64) -> Vec<SequencedEvent> {
+        self.event_log.lock().await.replay(subscriber, since)
+    }
+
+    /// Acknowledge the events up to and including `sequence_number` on behalf
+    /// of `subscriber`, and wait until this is persisted. See
+    /// [`EventLog::acknowledge`].
+    ///
+    /// Locking:
+    ///   * acquires the event log's lock, but not the global state lock
+    pub(crate) async fn acknowledge_events(
+        &self,
+        subscriber: &str,
+        sequence_number: u64,
+    ) -> Result<()> {
+        let acknowledgement = self
+            .event_log
+            .lock()
+            .await
+            .acknowledge(subscriber, sequence_number);
+        acknowledgement.persisted().await
+    }
+
+    /// Notified whenever new events are recorded. See [`EventLog::recorded`].
+    ///
+    /// Locking:
+    ///   * acquires the event log's lock, but not the global state lock
+    pub(crate) async fn events_recorded(&self) -> Arc<tokio::sync::Notify> {
+        self.event_log.lock().await.recorded()
+    }
+
     /// Test helper function for fine control of CLI parameters.
     #[cfg(test)]
     pub async fn set_cli(&mut self, cli: cli_args::Args) {
@@ -293,6 +538,63 @@ impl GlobalStateLock {
         self.cli = cli;
     }
 
+    /// Validate `block` against `previous_block`, consulting the block
+    /// validation cache first and recording the outcome in it afterwards.
+    ///
+    /// The cache is only used if `previous_block` is the predecessor that
+    /// `block` commits to. Otherwise, this is equivalent to [`Block::validate`].
+    ///
+    /// # Locking
+    ///   * Acquires `global_state_lock` for write, briefly, before and after
+    ///     validation. The lock is not held during validation.
+    pub(crate) async fn validate_block(
+        &mut self,
+        block: &Block,
+        previous_block: &Block,
+        now: Timestamp,
+    ) -> std::result::Result<(), BlockValidationError> {
+        let network = self.cli().network;
+        if block.header().prev_block_digest != previous_block.hash() {
+            return block.validate(previous_block, now, network).await;
+        }
+
+        let digest = block.hash();
+        let cached = self
+            .lock_guard_mut()
+            .await
+            .block_validation_cache
+            .get(digest);
+        if let Some(result) = cached {
+            debug!("Using cached validation result for block {digest:x}");
+            return result;
+        }
+
+        let result = block.validate(previous_block, now, network).await;
+        self.lock_guard_mut()
+            .await
+            .block_validation_cache
+            .insert(digest, result);
+
+        result
+    }
+
+    /// Like [`Self::validate_block`] but logs the reason for invalidity and
+    /// returns a bool, as does [`Block::is_valid`].
+    pub(crate) async fn is_valid_block(
+        &mut self,
+        block: &Block,
+        previous_block: &Block,
+        now: Timestamp,
+    ) -> bool {
+        match self.validate_block(block, previous_block, now).await {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("{e}");
+                false
+            }
+        }
+    }
+
     /// stores/records a locally-initiated transaction into the global state.
     pub async fn record_own_transaction(
         &mut self,
@@ -608,6 +910,101 @@ pub struct GlobalState {
     /// The `mining_state` can be updated by main task, mining task, or RPC server.
     pub mining_state: MiningState,
 
+    /// Whether the node sheds load due to resource pressure. Only updated by
+    /// the main task.
+    pub(crate) load_shedding: LoadShedding,
+
+    /// When the node may upgrade the proofs of 3rd party transactions. Can be
+    /// changed by the RPC server.
+    pub(crate) proof_upgrade_schedule: ProofUpgradeSchedule,
+
+    /// Whether the node runs in safe mode, and why. Set once at startup.
+    pub(crate) safe_mode: Option<SafeModeStatus>,
+
+    /// Outcomes of previous block validations, to avoid verifying the same
+    /// block repeatedly.
+    pub(crate) block_validation_cache: BlockValidationCache,
+
+    /// Blocks marked invalid by the operator, and their descendants, which
+    /// are rejected. Only used on RegTest, to simulate forks.
+    pub(crate) block_invalidations: BlockInvalidations,
+
+    /// Signed checkpoints of the canonical chain produced by this node. Only
+    /// updated by the main task.
+    pub(crate) checkpoints: Checkpoints,
+
+    /// Outcome of the most recent checks for new releases. Only updated by
+    /// the main task.
+    pub(crate) update_status: UpdateStatus,
+
+    /// Executables run on events, eg when a new tip is set.
+    pub(crate) plugin_hooks: PluginHooks,
+
+    /// Whether this node is a primary or a standby, and how far it has
+    /// replicated from its primary.
+    pub(crate) replication: ReplicationStatus,
+
+    /// Receiver identifiers watched on behalf of the operator, independently
+    /// of the wallet, and their matches in recent blocks.
+    pub(crate) watch_list: WatchList,
+
+    /// Additions to and removals from the mutator set in recent blocks.
+    pub(crate) mutator_set_growth: MutatorSetGrowthTracker,
+
+    /// Numbered events caused by new tips, alerts, and cancellations, for
+    /// replay to subscribers.
+    pub(crate) event_log: Arc<tokio::sync::Mutex<EventLog>>,
+
+    /// Alerts raised by the configured alert rules, and the operator's
+    /// silences. Rules are only evaluated by the main task.
+    pub(crate) alerts: Alerts,
+
+    /// The wallet's encrypted backup to trusted peers, and the backups held
+    /// for them. Both are empty unless trusted peers are configured. Shared
+    /// with the [`GlobalStateLock`], so that their file I/O does not happen
+    /// while the lock is held.
+    pub(crate) wallet_backup: Arc<tokio::sync::Mutex<WalletBackup>>,
+    pub(crate) held_wallet_backups: Arc<tokio::sync::Mutex<HeldWalletBackups>>,
+
+    /// Progress of applying blocks, shared with the [`GlobalStateLock`] so
+    /// that it can be followed while the lock is held.
+    pub(crate) block_application_progress: BlockApplicationProgressTracker,
+
+    /// Congestion of the channels to the main task. Channels are registered
+    /// when they are created, at startup.
+    pub(crate) channel_metrics: ChannelMetricsRegistry,
+
+    /// Time the main loop spent handling messages, by message type.
+    pub(crate) message_latencies: MessageLatencyRecorder,
+
+    /// Bytes exchanged with peers, by message category and by peer.
+    /// Connections register themselves when they are opened.
+    pub(crate) bandwidth: BandwidthRegistry,
+
+    /// States of the peer connections, and the transitions between them.
+    /// Connections register themselves when they are opened.
+    pub(crate) connection_lifecycles: ConnectionLifecycleRegistry,
+
+    /// States of the supervised tasks. Tasks are registered when they are
+    /// spawned, at startup.
+    pub(crate) task_statuses: TaskStatusRegistry,
+
+    /// Policy consulted before sending funds to a recipient, if any.
+    pub(crate) recipient_policy: Option<Arc<dyn RecipientPolicy>>,
+
+    /// Spending PIN required by the RPC methods that spend funds, if any.
+    /// Shared, so that its audit records can be written without holding the
+    /// global state lock.
+    pub(crate) spending_pin: Arc<SpendingPinGuard>,
+
+    /// Rules by which the composer excludes transactions from its block
+    /// proposals.
+    pub(crate) inclusion_policy: InclusionPolicy,
+
+    /// The proving jobs of own transactions, which can be cancelled until the
+    /// transaction is broadcast.
+    pub(crate) proving_cancellations: ProvingCancellations,
+
     /// Force wallet to maintain its own membership proofs. These membership
     /// proofs will otherwise be read from the archival mutator set.
     #[cfg(test)]
@@ -660,7 +1057,20 @@ impl GlobalState {
         cli: cli_args::Args,
         wallet_state: WalletState,
     ) -> Result<Self> {
-        let archival_state = ArchivalState::new(data_directory.clone(), genesis, cli.network).await;
+        let shared_block_store = match &cli.shared_block_dir {
+            Some(dir) if cli.shared_block_dir_read_only => {
+                Some(SharedBlockStore::open_read_only(dir.clone()).await?)
+            }
+            Some(dir) => Some(SharedBlockStore::open_for_writing(dir.clone()).await?),
+            None => None,
+        };
+        let archival_state = ArchivalState::new_with_shared_block_store(
+            data_directory.clone(),
+            genesis,
+            cli.network,
+            shared_block_store,
+        )
+        .await;
         debug!("Got archival state");
 
         // Get latest block. Use hardcoded genesis block if nothing is in database.
@@ -678,13 +1088,89 @@ impl GlobalState {
             archival_state,
         };
         let chain = BlockchainState::Archival(Box::new(chain));
-        let mempool = Mempool::new(
+        let mut mempool = Mempool::new(
             cli.max_mempool_size,
             cli.proving_capability(),
             chain.light_state(),
+        )
+        .with_orphan_pool_limits(cli.orphan_pool_limits());
+        if cli.max_mempool_spillover_size.as_u64() > 0 {
+            let spillover = MempoolSpillover::new(
+                data_directory.mempool_spillover_dir_path(),
+                cli.max_mempool_spillover_size,
+            )?;
+            mempool = mempool.with_spillover(spillover);
+        }
+
+        let recipient_policy = match &cli.denied_addresses_file {
+            Some(path) => {
+                let denylist = AddressDenylist::read_from_file(path, cli.network)?;
+                info!("Loaded denylist of {} addresses", denylist.len());
+                Some(Arc::new(denylist) as Arc<dyn RecipientPolicy>)
+            }
+            None => None,
+        };
+
+        let spending_pin_key = match &cli.spending_pin_file {
+            Some(path) => {
+                let key = SpendingPinKey::read_from_file(path)?;
+                info!("Spending PIN required for RPC spends");
+                Some(key)
+            }
+            None => None,
+        };
+        let spending_pin = SpendingPinGuard::new(
+            spending_pin_key,
+            data_directory.spending_pin_audit_file_path(),
         );
 
-        Ok(Self::new(wallet_state, chain, net, cli, mempool))
+        let inclusion_policy = InclusionPolicy::from_cli(&cli)?;
+        if inclusion_policy.is_active() {
+            info!(
+                "Composer inclusion policy excludes {} receiver IDs",
+                inclusion_policy.num_excluded_receiver_ids()
+            );
+        }
+
+        // A node that was promoted or demoted keeps its role across restarts.
+        let persisted_role =
+            ReplicationRole::read_from_file(&data_directory.replication_role_file_path()).await?;
+
+        let event_log = EventLog::load(
+            data_directory.wallet_events_file_path(),
+            data_directory.event_cursors_file_path(),
+        )
+        .await?;
+
+        let (wallet_backup, held_wallet_backups) = if cli.wallet_backup_peers.is_empty() {
+            Default::default()
+        } else {
+            let key = WalletBackupKey::new(&wallet_state.wallet_entropy);
+            let wallet_backup =
+                WalletBackup::load(data_directory.wallet_backup_file_path(), &key).await?;
+            let held_wallet_backups =
+                HeldWalletBackups::load(data_directory.held_wallet_backups_dir_path()).await?;
+            info!(
+                "Wallet backup at version {}, holding {} backups for trusted peers",
+                wallet_backup.head().version,
+                held_wallet_backups.len()
+            );
+            (wallet_backup, held_wallet_backups)
+        };
+
+        let mut global_state = Self::new(wallet_state, chain, net, cli, mempool);
+        global_state.recipient_policy = recipient_policy;
+        global_state.spending_pin = Arc::new(spending_pin);
+        global_state.inclusion_policy = inclusion_policy;
+        global_state.event_log = Arc::new(tokio::sync::Mutex::new(event_log));
+        global_state.wallet_backup = Arc::new(tokio::sync::Mutex::new(wallet_backup));
+        global_state.held_wallet_backups = Arc::new(tokio::sync::Mutex::new(held_wallet_backups));
+        if let Some(role) = persisted_role {
+            info!("Replication role is {role}");
+            global_state.replication.role = role;
+        }
+
+        Ok(global_state)
     }
 
     pub fn new(
@@ -694,13 +1180,45 @@ impl GlobalState {
         cli: cli_args::Args,
         mempool: Mempool,
     ) -> Self {
+        let plugin_hooks = PluginHooks::new(&cli);
+        let replication = ReplicationStatus::new(&cli);
+        let connection_lifecycles = ConnectionLifecycleRegistry::new(&cli);
+        let mining_state = MiningState::new(&cli);
+        let proof_upgrade_schedule = ProofUpgradeSchedule::from(&cli);
+        let block_application_progress =
+            BlockApplicationProgressTracker::new(chain.light_state().header().height);
         Self {
             wallet_state,
             chain,
             net,
             cli,
             mempool,
-            mining_state: MiningState::default(),
+            mining_state,
+            load_shedding: LoadShedding::default(),
+            proof_upgrade_schedule,
+            safe_mode: None,
+            block_validation_cache: BlockValidationCache::default(),
+            block_invalidations: BlockInvalidations::default(),
+            checkpoints: Checkpoints::default(),
+            update_status: UpdateStatus::new(VERSION),
+            plugin_hooks,
+            replication,
+            watch_list: WatchList::default(),
+            mutator_set_growth: MutatorSetGrowthTracker::default(),
+            event_log: Default::default(),
+            alerts: Alerts::default(),
+            wallet_backup: Default::default(),
+            held_wallet_backups: Default::default(),
+            block_application_progress,
+            channel_metrics: ChannelMetricsRegistry::default(),
+            message_latencies: MessageLatencyRecorder::default(),
+            bandwidth: BandwidthRegistry::default(),
+            connection_lifecycles,
+            task_statuses: TaskStatusRegistry::default(),
+            recipient_policy: None,
+            spending_pin: Arc::default(),
+            inclusion_policy: InclusionPolicy::default(),
+            proving_cancellations: ProvingCancellations::default(),
             #[cfg(test)]
             force_wallet_membership_proof_maintance: false,
         }
@@ -714,6 +1232,20 @@ impl GlobalState {
             .shuffle_seed(next_block_height)
     }
 
+    /// Make this node a primary or a standby. The role is persisted, so that
+    /// it survives restarts.
+    pub(crate) async fn set_replication_role(&mut self, role: ReplicationRole) -> Result<()> {
+        let path = self
+            .wallet_state
+            .configuration
+            .data_directory()
+            .replication_role_file_path();
+        role.write_to_file(&path).await?;
+        self.replication.role = role;
+
+        Ok(())
+    }
+
     pub async fn get_wallet_status_for_tip(&self) -> WalletStatus {
         let tip_digest = self.chain.light_state().hash();
         let mutator_set_accumulator = self
@@ -726,6 +1258,79 @@ impl GlobalState {
             .await
     }
 
+    /// Report the progress of catching up with the network, stage by stage.
+    pub(crate) async fn sync_status(&self) -> SyncStatus {
+        let tip_height = u64::from(self.chain.light_state().header().height);
+        let progress = self.block_application_progress.current();
+        let anchor = self.net.sync_anchor.as_ref();
+
+        // While syncing, the target is the tip claimed by the peer that
+        // triggered sync mode. Otherwise the node is only aware of its own
+        // tip, and of the tips peers announce as blocks.
+        let target_height = anchor
+            .and(progress.target_height)
+            .map(u64::from)
+            .unwrap_or(tip_height);
+        let headers_target =
+            (anchor.is_some() || !self.net.peer_map.is_empty()).then_some(target_height);
+        let headers = SyncStageProgress::new(
+            SyncStage::Headers,
+            headers_target.unwrap_or(tip_height),
+            headers_target,
+        )
+        .with_peers(anchor.and_then(|anchor| anchor.announced_by));
+
+        // Blocks that do not yet beat the own tip are stored as the anchor's
+        // champion, and count as downloaded.
+        let synced_height = anchor
+            .and_then(|anchor| anchor.champion)
+            .map_or(tip_height, |(height, _)| tip_height.max(height.into()));
+        let blocks = SyncStageProgress::new(SyncStage::Blocks, synced_height, Some(target_height))
+            .with_estimated_completion(anchor.and(progress.estimated_completion))
+            .with_peers(anchor.and(progress.serving_peer));
+
+        let wallet_sync_label = self.wallet_state.wallet_db.get_sync_label();
+        let wallet_height = if wallet_sync_label == self.chain.light_state().hash() {
+            tip_height
+        } else if self.chain.is_archival_node() && wallet_sync_label != Digest::default() {
+            self.chain
+                .archival_state()
+                .get_block_header(wallet_sync_label)
+                .await
+                .map_or(0, |header| header.height.into())
+        } else {
+            0
+        };
+        let wallet = SyncStageProgress::new(SyncStage::Wallet, wallet_height, Some(tip_height));
+
+        // Transactions that this node cannot update stay unsynced until a
+        // peer relays an update, and would keep the stage from completing.
+        let (num_synced_txs, num_syncable_txs) = self.mempool.num_synced_and_syncable_txs();
+        let mempool = SyncStageProgress::new(
+            SyncStage::Mempool,
+            num_synced_txs as u64,
+            Some(num_syncable_txs as u64),
+        );
+
+        SyncStatus {
+            headers,
+            blocks,
+            wallet,
+            mempool,
+        }
+    }
+
+    /// Statistics on the usage of the chain, from the mutator set of the tip
+    /// and the blocks recently applied.
+    pub(crate) fn mutator_set_statistics(&self) -> Result<MutatorSetStatistics> {
+        let tip = self.chain.light_state();
+        let mutator_set = tip.mutator_set_accumulator_after()?;
+
+        Ok(self
+            .mutator_set_growth
+            .statistics(tip.header().height, &mutator_set))
+    }
+
     pub(crate) fn consensus_rule_set(&self) -> ConsensusRuleSet {
         let tip_height = self.chain.light_state().header().height;
         ConsensusRuleSet::infer_from(self.cli().network, tip_height)
@@ -942,6 +1547,10 @@ impl GlobalState {
     /// If the incoming block equals the current tip, this function returns
     /// false.
     pub fn incoming_block_is_more_canonical(&self, incoming_block: &Block) -> bool {
+        if self.block_invalidations.rejects(incoming_block) {
+            return false;
+        }
+
         let winner = Block::fork_choice_rule(self.chain.light_state(), incoming_block);
         winner.hash() != self.chain.light_state().hash()
     }
@@ -1074,9 +1683,26 @@ impl GlobalState {
         self.wallet_state.spendable_inputs(wallet_status, timestamp)
     }
 
+    /// retrieves exactly the wallet's inputs with the given AOCL leaf indices
+    /// as of the present tip, in the given order.
+    ///
+    /// fails if any of the UTXOs is not a synced, unspent UTXO of the wallet,
+    /// or is excluded by [`Self::wallet_spendable_inputs`].
+    pub async fn wallet_inputs_by_aocl_leaf_index(
+        &self,
+        aocl_leaf_indices: &[u64],
+        timestamp: Timestamp,
+    ) -> Result<Vec<TxInput>, api::tx_initiation::error::CreateTxError> {
+        let wallet_status = self.get_wallet_status_for_tip().await;
+        self.wallet_state
+            .inputs_by_aocl_leaf_index(wallet_status, aocl_leaf_indices, timestamp)
+    }
+
+    /// The handshake to send to a new peer. In plausible deniability mode it
+    /// differs between calls, so every connection must use a fresh one.
     pub(crate) fn get_own_handshakedata(&self) -> HandshakeData {
         let listen_port = self.cli().own_listen_port();
-        HandshakeData {
+        let mut handshake_data = HandshakeData {
             tip_header: *self.chain.light_state().header(),
             listen_port,
             network: self.cli().network,
@@ -1085,12 +1711,51 @@ impl GlobalState {
                 panic!(
                 "Must be able to convert own version number to fixed-size string. Got {VERSION}")
             }),
-            // For now, all nodes are archival nodes
-            is_archival_node: self.chain.is_archival_node(),
+            // Headers-only nodes cannot serve old blocks.
+            is_archival_node: self.chain.is_archival_node() && !self.cli().headers_only,
             is_bootstrapper_node: self.cli().bootstrap,
             timestamp: SystemTime::now(),
             extra_data: Default::default(),
+        };
+
+        if !self.cli().disable_peer_compression {
+            handshake_data.add_capability(ZSTD_COMPRESSION_CAPABILITY);
+        }
+        handshake_data.add_capability(HEADER_FIRST_RELAY_CAPABILITY);
+        handshake_data.add_capability(BLOCK_CHUNKS_CAPABILITY);
+        handshake_data.add_capability(MESSAGE_SEQUENCE_CAPABILITY);
+
+        // Pongs reveal the skew of the local clock.
+        if !self.cli().plausible_deniability {
+            handshake_data.add_capability(PING_CAPABILITY);
+        }
+
+        if self.cli().rendezvous {
+            handshake_data.add_capability(RENDEZVOUS_CAPABILITY);
+        }
+        if self.cli().rendezvous_relay {
+            handshake_data.add_capability(RENDEZVOUS_RELAY_CAPABILITY);
+        }
+        if !self.cli().wallet_backup_peers.is_empty() {
+            handshake_data.add_capability(WALLET_BACKUP_CAPABILITY);
+        }
+
+        let tip_height = self.chain.light_state().header().height;
+        for rule_set in fork_signaling::pending_rule_sets(self.cli().network, tip_height) {
+            handshake_data.add_capability(&fork_signaling::signal(rule_set));
+        }
+
+        if self.cli().plausible_deniability {
+            handshake_data.instance_id =
+                plausible_deniability::connection_instance_id(self.net.instance_id);
+            handshake_data.timestamp =
+                plausible_deniability::coarse_timestamp(handshake_data.timestamp);
+            if !self.cli().mine() {
+                handshake_data.tip_header = BlockHeader::genesis(self.cli().network);
+            }
         }
+
+        handshake_data
     }
 
     /// In case the wallet database is corrupted or deleted, this method will restore
@@ -1427,12 +2092,22 @@ impl GlobalState {
                 .expect("Database not in consistent state. Monitored UTXO must have at least one membership proof.");
 
             // request path-to-tip
-            let (backwards, _luca, forwards) = self
+            let (backwards, luca, forwards) = self
                 .chain
                 .archival_state()
                 .find_path(block_hash, tip_hash)
                 .await;
 
+            // The walk needs the bodies of all blocks from the common
+            // ancestor on, which a headers-only node may have pruned.
+            let Some(luca_header) = self.chain.archival_state().get_block_header(luca).await else {
+                bail!("Unknown common ancestor {luca} of membership proof and tip");
+            };
+            self.chain
+                .archival_state()
+                .ensure_bodies_retained_from(luca_header.height)
+                .await?;
+
             // after this point, we may be modifying it.
             let mut monitored_utxo = monitored_utxo.clone();
 
@@ -1451,12 +2126,14 @@ impl GlobalState {
                     break 'outer;
                 }
 
-                let revert_block = self
+                let Some(revert_block) = self
                     .chain
                     .archival_state()
                     .get_block(revert_block_hash)
                     .await?
-                    .unwrap();
+                else {
+                    bail!("Body of block {revert_block_hash} is not available");
+                };
                 let revert_block_parent = self
                     .chain
                     .archival_state()
@@ -1506,12 +2183,14 @@ impl GlobalState {
                     continue;
                 }
 
-                let apply_block = self
+                let Some(apply_block) = self
                     .chain
                     .archival_state()
                     .get_block(apply_block_hash)
                     .await?
-                    .unwrap();
+                else {
+                    bail!("Body of block {apply_block_hash} is not available");
+                };
                 let predecessor_block = self
                     .chain
                     .archival_state()
@@ -1583,6 +2262,190 @@ impl GlobalState {
         Ok(())
     }
 
+    /// Bring the wallet database in line with the archival tip, if its sync
+    /// label disagrees with the tip.
+    ///
+    /// Since the wallet and the block databases are persisted separately, an
+    /// unclean shutdown can leave the wallet synced to some other block. If
+    /// that block is known, the wallet's view is rolled back to the latest
+    /// common ancestor with the tip: expenditures in reverted blocks are
+    /// undone and UTXOs confirmed in them are counted as orphaned. Then the
+    /// blocks up to the tip are scanned for incoming and spent UTXOs. If the
+    /// block is unknown, expenditures in blocks that are not canonical are
+    /// undone, and the canonical blocks after the highest canonical block the
+    /// wallet refers to are scanned again. Either way, the membership
+    /// proofs are restored from the archival mutator set and the wallet is
+    /// persisted.
+    ///
+    /// Does nothing on nodes without an archival state, or if the wallet was
+    /// never synced.
+    pub(crate) async fn reconcile_wallet_with_tip(&mut self) -> Result<WalletReconciliation> {
+        let tip_hash = self.chain.light_state().hash();
+        let wallet_sync_label = self.wallet_state.wallet_db.get_sync_label();
+        if !self.chain.is_archival_node()
+            || wallet_sync_label == tip_hash
+            || wallet_sync_label == Digest::default()
+        {
+            return Ok(WalletReconciliation::Consistent);
+        }
+
+        let ams_sync_label = self
+            .chain
+            .archival_state()
+            .archival_mutator_set
+            .get_sync_label();
+        ensure!(
+            ams_sync_label == tip_hash,
+            "Cannot reconcile wallet: archival mutator set is synced to \
+            {ams_sync_label:x} instead of tip {tip_hash:x}"
+        );
+
+        let sync_label_is_known = self
+            .chain
+            .archival_state()
+            .get_block_header(wallet_sync_label)
+            .await
+            .is_some();
+        let reconciliation = if sync_label_is_known {
+            let (backwards, luca, forwards) = self
+                .chain
+                .archival_state()
+                .find_path(wallet_sync_label, tip_hash)
+                .await;
+
+            let reverted: HashSet<Digest> = backwards.iter().copied().collect();
+            let mut num_unspent = 0;
+            let mut num_orphaned = 0;
+            let num_mutxos = self.wallet_state.wallet_db.monitored_utxos().len().await;
+            for i in 0..num_mutxos {
+                let mutxo = self
+                    .wallet_state
+                    .wallet_db
+                    .monitored_utxo_by_list_index(i)
+                    .await;
+                if mutxo
+                    .spent_in_block
+                    .is_some_and(|(spending_block, _, _)| reverted.contains(&spending_block))
+                {
+                    self.wallet_state.wallet_db.unmark_mutxo_as_spent(i).await;
+                    num_unspent += 1;
+                }
+                if reverted.contains(&mutxo.confirmed_in_block.0) {
+                    num_orphaned += 1;
+                }
+            }
+
+            self.scan_blocks_into_wallet(&forwards).await?;
+
+            WalletReconciliation::Reconciled {
+                wallet_sync_label,
+                tip: tip_hash,
+                luca,
+                num_reverted_blocks: backwards.len(),
+                num_applied_blocks: forwards.len(),
+                num_unspent,
+                num_orphaned,
+            }
+        } else {
+            // The wallet scanned the canonical chain at least up to the
+            // highest canonical block it refers to. Blocks after it may hold
+            // incoming UTXOs, so they are scanned again.
+            let mut num_unspent = 0;
+            let mut num_orphaned = 0;
+            let mut highest_known_canonical = BlockHeight::genesis();
+            let num_mutxos = self.wallet_state.wallet_db.monitored_utxos().len().await;
+            for i in 0..num_mutxos {
+                let mutxo = self
+                    .wallet_state
+                    .wallet_db
+                    .monitored_utxo_by_list_index(i)
+                    .await;
+                if let Some((spending_block, _, spending_height)) = mutxo.spent_in_block {
+                    if self
+                        .chain
+                        .archival_state()
+                        .block_belongs_to_canonical_chain(spending_block)
+                        .await
+                    {
+                        highest_known_canonical = highest_known_canonical.max(spending_height);
+                    } else {
+                        self.wallet_state.wallet_db.unmark_mutxo_as_spent(i).await;
+                        num_unspent += 1;
+                    }
+                }
+                let (confirming_block, _, confirming_height) = mutxo.confirmed_in_block;
+                if self
+                    .chain
+                    .archival_state()
+                    .block_belongs_to_canonical_chain(confirming_block)
+                    .await
+                {
+                    highest_known_canonical = highest_known_canonical.max(confirming_height);
+                } else {
+                    num_orphaned += 1;
+                }
+            }
+
+            let tip_height = self.chain.light_state().header().height;
+            let mut rescanned_blocks = vec![];
+            let mut height = highest_known_canonical.next();
+            while height <= tip_height {
+                let Some(block_hash) = self
+                    .chain
+                    .archival_state()
+                    .archival_block_mmr
+                    .ammr()
+                    .try_get_leaf(height.into())
+                    .await
+                else {
+                    bail!("Cannot reconcile wallet: canonical block at height {height} is missing");
+                };
+                rescanned_blocks.push(block_hash);
+                height = height.next();
+            }
+            self.scan_blocks_into_wallet(&rescanned_blocks).await?;
+
+            WalletReconciliation::UnknownSyncLabel {
+                wallet_sync_label,
+                tip: tip_hash,
+                rescanned_from: highest_known_canonical.next(),
+                num_applied_blocks: rescanned_blocks.len(),
+                num_unspent,
+                num_orphaned,
+            }
+        };
+
+        self.restore_monitored_utxos_from_archival_mutator_set()
+            .await;
+        self.wallet_state.wallet_db.persist().await;
+
+        Ok(reconciliation)
+    }
+
+    /// Scan the given blocks, in order, for incoming and spent UTXOs of the
+    /// wallet.
+    async fn scan_blocks_into_wallet(&mut self, block_hashes: &[Digest]) -> Result<()> {
+        for block_hash in block_hashes {
+            let Some(block) = self.chain.archival_state().get_block(*block_hash).await? else {
+                bail!("Cannot reconcile wallet: block {block_hash:x} is missing");
+            };
+            let Some(parent) = self
+                .chain
+                .archival_state()
+                .get_block(block.header().prev_block_digest)
+                .await?
+            else {
+                bail!("Cannot reconcile wallet: parent of block {block_hash:x} is missing");
+            };
+            let previous_msa = parent.mutator_set_accumulator_after()?;
+            self.wallet_state
+                .update_wallet_state_with_new_block(&previous_msa, &block, false)
+                .await?;
+        }
+
+        Ok(())
+    }
+
     /// Mark as deleted all monitored UTXOs from abandoned chains with a depth
     /// deeper than `block_depth_threshold`. Use
     /// `prune_mutxos_of_unknown_depth = true` to mark MUTXOs from abandoned
@@ -1703,7 +2566,12 @@ impl GlobalState {
     /// # Panics
     ///
     /// - If the stored block is found but does not have a mutator set update.
-    pub(crate) async fn set_tip_to_stored_block(&mut self, block_digest: Digest) -> Result<()> {
+    ///
+    /// Returns a list of update-jobs that should be performed by this client.
+    pub(crate) async fn set_tip_to_stored_block(
+        &mut self,
+        block_digest: Digest,
+    ) -> Result<Vec<MempoolUpdateJob>> {
         // If the node not archival, it cannot sync the wallet. So in this case,
         // abort early.
         ensure!(
@@ -1719,9 +2587,48 @@ impl GlobalState {
             .await?
             .ok_or(anyhow::Error::msg(format!("unknown block {block_digest}")))?;
 
-        let _ = self.set_new_tip_internal(block).await?;
+        self.set_new_tip_internal(block).await
+    }
 
-        Ok(())
+    /// Mark a stored block invalid, so that it and its descendants are
+    /// rejected until [reconsidered](Self::reconsider_block).
+    ///
+    /// Does not change the tip. Returns the most canonical stored block that
+    /// neither is nor descends from a block marked invalid, which the caller
+    /// should set as tip.
+    pub(crate) async fn invalidate_block(&mut self, block_digest: Digest) -> Result<Digest> {
+        ensure!(
+            self.chain.is_archival_node(),
+            "node must be archival in order to invalidate blocks"
+        );
+
+        self.block_invalidations
+            .invalidate(
+                block_digest,
+                self.chain.light_state(),
+                self.chain.archival_state(),
+            )
+            .await
+    }
+
+    /// Remove the mark set by [`Self::invalidate_block`] from a stored block.
+    ///
+    /// Does not change the tip. Returns the most canonical stored block that
+    /// neither is nor descends from a block marked invalid, which the caller
+    /// should set as tip.
+    pub(crate) async fn reconsider_block(&mut self, block_digest: Digest) -> Result<Digest> {
+        ensure!(
+            self.chain.is_archival_node(),
+            "node must be archival in order to reconsider blocks"
+        );
+
+        self.block_invalidations
+            .reconsider(
+                block_digest,
+                self.chain.light_state(),
+                self.chain.archival_state(),
+            )
+            .await
     }
 
     /// Update client's state with a new block.
@@ -1787,12 +2694,34 @@ impl GlobalState {
             .await?;
 
         *self.chain.light_state_mut() = std::sync::Arc::new(new_tip.clone());
+        crash_report::record_tip(new_tip.header().height, new_tip.hash());
 
         // Update mempool with UTXOs from this block. This is done by
         // removing all transaction that became invalid/was mined by this
         // block. Also returns the list of update-jobs that should be
         // performed by this client.
         let (mempool_events, update_jobs) = self.mempool.update_with_block(&new_tip)?;
+        let block_index_sets: HashSet<_> = new_tip
+            .body()
+            .transaction_kernel
+            .inputs
+            .iter()
+            .map(|removal_record| removal_record.absolute_indices)
+            .collect();
+        let confirmed_transactions = mempool_events
+            .iter()
+            .filter_map(|event| match event {
+                MempoolEvent::RemoveTx(kernel)
+                    if !kernel.inputs.is_empty()
+                        && kernel.inputs.iter().all(|removal_record| {
+                            block_index_sets.contains(&removal_record.absolute_indices)
+                        }) =>
+                {
+                    Some(kernel.clone())
+                }
+                _ => None,
+            })
+            .collect_vec();
 
         let parent_ms_accumulator =
             self.chain
@@ -1841,6 +2770,7 @@ impl GlobalState {
                     || self.force_wallet_membership_proof_maintance
             }
         };
+        let num_mutxos_before = self.wallet_state.wallet_db.monitored_utxos().len().await;
         self.wallet_state
             .update_wallet_state_with_new_block(
                 &parent_ms_accumulator.unwrap_or_default(),
@@ -1860,6 +2790,16 @@ impl GlobalState {
             .handle_mempool_events(mempool_events)
             .await;
 
+        self.watch_list.handle_new_tip(&new_tip);
+        self.mutator_set_growth.handle_new_tip(&new_tip);
+        self.block_application_progress
+            .record_applied(new_tip.header().height);
+
+        let events = self
+            .new_tip_events(&new_tip, &confirmed_transactions, num_mutxos_before)
+            .await;
+        self.publish_events(events).await;
+
         // Reset block proposal, as that field pertains to the block that
         // was just set as new tip. Also reset set of exported block proposals.
         self.mining_state.block_proposal = BlockProposal::none();
@@ -1868,6 +2808,125 @@ impl GlobalState {
         Ok(update_jobs)
     }
 
+    /// The events caused by setting `new_tip`, for plugin hooks and the event
+    /// log.
+    ///
+    /// `confirmed_transactions` are the mempool transactions included in the
+    /// new tip. Monitored UTXOs from index `first_new_mutxo_index` onwards were
+    /// received in the new tip.
+    async fn new_tip_events(
+        &self,
+        new_tip: &Block,
+        confirmed_transactions: &[TransactionKernel],
+        first_new_mutxo_index: u64,
+    ) -> Vec<HookEvent> {
+        let block_digest = new_tip.hash();
+        let height = new_tip.header().height;
+        let mut events = vec![HookEvent::NewBlock {
+            block_digest,
+            height,
+            timestamp: new_tip.header().timestamp,
+        }];
+
+        let monitored_utxos = self.wallet_state.wallet_db.monitored_utxos();
+        for i in first_new_mutxo_index..monitored_utxos.len().await {
+            let mutxo = monitored_utxos.get(i).await;
+            events.push(HookEvent::WalletReceive {
+                block_digest,
+                height,
+                aocl_leaf_index: mutxo.aocl_leaf_index,
+                amount: mutxo.utxo.get_native_currency_amount(),
+            });
+        }
+
+        events.extend(
+            confirmed_transactions
+                .iter()
+                .map(|kernel| HookEvent::TxConfirmed {
+                    block_digest,
+                    height,
+                    transaction_id: kernel.txid(),
+                    fee: kernel.fee,
+                }),
+        );
+
+        events
+    }
+
+    /// Pass events to the plugin hooks subscribed to them, and record them in
+    /// the event log.
+    async fn publish_events(&mut self, events: Vec<HookEvent>) {
+        for event in &events {
+            if self.plugin_hooks.subscribes_to(event.kind()) {
+                self.plugin_hooks.dispatch(event);
+            }
+        }
+        self.event_log.lock().await.record(events);
+    }
+
+    /// Evaluate the configured alert rules against the current tip, peer
+    /// count, and mempool, and publish the alerts that fired or whose silence
+    /// lapsed.
+    pub(crate) async fn evaluate_alerts(&mut self, now: Timestamp) {
+        let rules = AlertRules::from(self.cli());
+        if rules.is_empty() {
+            let events = self.alerts.lift_lapsed_silences(now);
+            self.publish_events(events).await;
+            return;
+        }
+
+        let tip_header = *self.chain.light_state().header();
+        let metrics = AlertMetrics {
+            tip_height: tip_header.height,
+            tip_timestamp: tip_header.timestamp,
+            tip_difficulty: tip_header.difficulty,
+            num_peers: self.net.peer_map.len(),
+            mempool_num_txs: self.mempool.len(),
+        };
+        let events = self.alerts.evaluate(&rules, &metrics, now);
+        self.publish_events(events).await;
+    }
+
+    /// Fire an alert raised outside the configured rules, and publish it
+    /// unless it is silenced or active already.
+    pub(crate) async fn raise_alert(&mut self, kind: AlertKind, message: String, now: Timestamp) {
+        let events = self.alerts.raise(kind, message, now).into_iter().collect();
+        self.publish_events(events).await;
+    }
+
+    /// Store recovery data fetched back from a trusted peer, and restore the
+    /// monitored UTXOs it belongs to. `entries` must not be in the wallet's
+    /// incoming secrets file yet, see [`WalletBackup::unknown_to_wallet`].
+    /// Restoration requires an archival mutator set synced to the tip, and is
+    /// otherwise left to the next startup.
+    pub(crate) async fn import_wallet_backup_entries(
+        &mut self,
+        entries: Vec<IncomingUtxoRecoveryData>,
+    ) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let num_imported = entries.len();
+        for entry in entries {
+            self.wallet_state.store_utxo_ms_recovery_data(entry).await?;
+        }
+
+        info!("Imported recovery data of {num_imported} UTXOs from wallet backup");
+        let is_synced = self.chain.is_archival_node()
+            && self
+                .chain
+                .archival_state()
+                .archival_mutator_set
+                .get_sync_label()
+                == self.chain.light_state().hash();
+        if is_synced {
+            self.restore_monitored_utxos_from_recovery_data().await?;
+        }
+
+        Ok(())
+    }
+
     /// resync membership proofs
     pub async fn resync_membership_proofs(&mut self) -> Result<()> {
         // Do not fix memberhip proofs if node is in sync mode, as we would otherwise
@@ -2100,6 +3159,41 @@ impl GlobalState {
         self.wallet_state.handle_mempool_events(events).await;
     }
 
+    /// Cancel an own transaction that has not been broadcast yet: abort its
+    /// proving job, if any, and remove it from the mempool, which releases its
+    /// inputs for other transactions. Publishes a
+    /// [`HookEvent::TxCancelled`] event.
+    ///
+    /// Own transactions are broadcast once they are proven, ie no longer
+    /// backed by a primitive witness. They cannot be cancelled afterwards.
+    pub(crate) async fn cancel_own_transaction(
+        &mut self,
+        transaction_id: TransactionKernelId,
+    ) -> Result<(), api::tx_initiation::error::CancelTxError> {
+        use api::tx_initiation::error::CancelTxError;
+
+        match self.mempool.get_with_priority(transaction_id) {
+            Some((transaction, UpgradePriority::Critical)) => {
+                if !transaction.proof.is_witness() {
+                    return Err(CancelTxError::AlreadyBroadcast);
+                }
+            }
+            _ => return Err(CancelTxError::NotOwnTransaction),
+        }
+
+        if self.proving_cancellations.cancel(transaction_id) {
+            info!("Cancelled proving job of transaction {transaction_id}");
+        }
+        self.mempool_remove(transaction_id).await;
+        self.publish_events(vec![HookEvent::TxCancelled {
+            transaction_id,
+            timestamp: Timestamp::now(),
+        }])
+        .await;
+
+        Ok(())
+    }
+
     /// clears all Tx from mempool and notifies wallet of changes.
     pub async fn mempool_clear(&mut self) {
         let events = self.mempool.clear();
@@ -2221,6 +3315,27 @@ impl GlobalState {
             directory.to_string_lossy()
         );
         let block_file_paths = ArchivalState::read_block_file_names_from_directory(directory)?;
+        self.block_application_progress
+            .start(BlockApplicationSource::DirectoryImport, None);
+        let num_stored_blocks = self
+            .import_block_files(block_file_paths, flush_period, validate_blocks)
+            .await;
+        self.block_application_progress.finish();
+        let num_stored_blocks = num_stored_blocks?;
+        self.flush_databases().await?;
+
+        Ok(num_stored_blocks)
+    }
+
+    /// Store the blocks of the given files to the archival state, as described
+    /// in [`Self::import_blocks_from_directory`]. Returns the number of blocks
+    /// stored.
+    async fn import_block_files(
+        &mut self,
+        block_file_paths: Vec<PathBuf>,
+        flush_period: usize,
+        validate_blocks: bool,
+    ) -> Result<usize> {
         let mut num_stored_blocks = 0;
         let mut predecessor = self.chain.light_state().clone();
         for block_file_path in block_file_paths {
@@ -2290,13 +3405,16 @@ impl GlobalState {
 
                 if flush_period != 0 && num_stored_blocks % flush_period == 0 {
                     self.flush_databases().await?;
-                    info!("Flushed databases after {num_stored_blocks} blocks.");
+                    let progress = self.block_application_progress.current();
+                    info!(
+                        "Flushed databases after {num_stored_blocks} blocks. \
+                        Importing {:.1} blocks/s.",
+                        progress.blocks_per_second
+                    );
                 }
             }
         }
 
-        self.flush_databases().await?;
-
         Ok(num_stored_blocks)
     }
 }
@@ -2700,6 +3818,7 @@ mod tests {
 
     mod set_tip {
         use super::*;
+        use crate::application::plugin_hooks::HookEventKind;
 
         #[apply(shared_tokio_runtime)]
         async fn set_new_tip_clears_block_proposal_related_data() {
@@ -2728,6 +3847,93 @@ mod tests {
                 "Set of exported block proposals must be empty after registering new block"
             );
         }
+
+        #[apply(shared_tokio_runtime)]
+        async fn new_tip_events_report_new_block_and_received_utxos() {
+            let network = Network::Main;
+            let cli = cli_args::Args::default_with_network(network);
+            let mut alice = mock_genesis_global_state(2, WalletEntropy::devnet_wallet(), cli).await;
+            let mut alice = alice.lock_guard_mut().await;
+            let alice_key = alice
+                .wallet_state
+                .wallet_entropy
+                .nth_generation_spending_key(0);
+            let (block1, expected_utxos) =
+                make_mock_block(&Block::genesis(network), None, alice_key, random(), network).await;
+            alice.wallet_state.add_expected_utxos(expected_utxos).await;
+
+            let num_mutxos_before = alice.wallet_state.wallet_db.monitored_utxos().len().await;
+            alice.set_new_tip(block1.clone()).await.unwrap();
+            let num_mutxos_after = alice.wallet_state.wallet_db.monitored_utxos().len().await;
+            assert!(num_mutxos_after > num_mutxos_before);
+
+            let events = alice.new_tip_events(&block1, &[], num_mutxos_before).await;
+            assert_eq!(
+                HookEvent::NewBlock {
+                    block_digest: block1.hash(),
+                    height: block1.header().height,
+                    timestamp: block1.header().timestamp,
+                },
+                events[0]
+            );
+            assert_eq!(
+                (num_mutxos_after - num_mutxos_before) as usize,
+                events[1..]
+                    .iter()
+                    .filter(|event| event.kind() == HookEventKind::WalletReceive)
+                    .count()
+            );
+
+            // setting the tip recorded the same events in the event log.
+            let recorded = alice.event_log.lock().await.replay("test", 0);
+            assert_eq!(
+                events,
+                recorded
+                    .into_iter()
+                    .map(|event| event.event)
+                    .collect::<Vec<_>>()
+            );
+        }
+
+        #[apply(shared_tokio_runtime)]
+        async fn invalidated_blocks_are_left_until_reconsidered() {
+            let network = Network::Main;
+            let mut alice = mock_genesis_global_state(
+                2,
+                WalletEntropy::devnet_wallet(),
+                cli_args::Args::default_with_network(network),
+            )
+            .await;
+            let mut alice = alice.global_state_lock.lock_guard_mut().await;
+
+            let genesis = Block::genesis(network);
+            let a1 = invalid_empty_block(&genesis, network);
+            let a2 = invalid_empty_block(&a1, network);
+            let b1 = invalid_empty_block_with_timestamp(
+                &genesis,
+                a1.header().timestamp + Timestamp::seconds(1),
+                network,
+            );
+            alice.set_new_tip(a1.clone()).await.unwrap();
+            alice.set_new_tip(a2.clone()).await.unwrap();
+            alice.store_block_not_tip(b1.clone()).await.unwrap();
+
+            assert_eq!(b1.hash(), alice.invalidate_block(a1.hash()).await.unwrap());
+            alice.set_tip_to_stored_block(b1.hash()).await.unwrap();
+            assert_eq!(b1.hash(), alice.chain.light_state().hash());
+            assert!(
+                !alice.incoming_block_is_more_canonical(&a2),
+                "descendant of invalidated block must be rejected"
+            );
+
+            assert_eq!(a2.hash(), alice.reconsider_block(a1.hash()).await.unwrap());
+            assert!(alice.incoming_block_is_more_canonical(&a2));
+
+            assert!(
+                alice.invalidate_block(genesis.hash()).await.is_err(),
+                "genesis block cannot be invalidated"
+            );
+        }
     }
 
     #[apply(shared_tokio_runtime)]
@@ -3780,6 +4986,102 @@ mod tests {
                 }
             }
         }
+
+        #[traced_test]
+        #[apply(shared_tokio_runtime)]
+        async fn reconcile_wallet_synced_to_abandoned_or_unknown_block() {
+            let network = Network::Main;
+            let mut rng = rand::rng();
+            let mut alice = mock_genesis_global_state(
+                2,
+                WalletEntropy::devnet_wallet(),
+                cli_args::Args::default_with_network(network),
+            )
+            .await;
+            let mut alice = alice.global_state_lock.lock_guard_mut().await;
+            assert_eq!(
+                WalletReconciliation::Consistent,
+                alice.reconcile_wallet_with_tip().await.unwrap()
+            );
+
+            // Receive two composer UTXOs in block 1a.
+            let alice_key = alice
+                .wallet_state
+                .wallet_entropy
+                .nth_generation_spending_key(0);
+            let genesis_block = alice.chain.archival_state().get_tip().await;
+            let (block_1a, composer_expected_utxos) =
+                make_mock_block(&genesis_block, None, alice_key, rng.random(), network).await;
+            alice
+                .wallet_state
+                .add_expected_utxos(composer_expected_utxos)
+                .await;
+            alice.set_new_tip(block_1a.clone()).await.unwrap();
+
+            // Reorganize to a fork from genesis, and pretend the wallet was
+            // last persisted while synced to block 1a.
+            let bob_key = WalletEntropy::new_random().nth_generation_spending_key(0);
+            let mut tip = genesis_block.clone();
+            for _ in 0..2 {
+                let (next_block, _) =
+                    make_mock_block(&tip, None, bob_key, rng.random(), network).await;
+                alice.set_new_tip(next_block.clone()).await.unwrap();
+                tip = next_block;
+            }
+            alice
+                .wallet_state
+                .wallet_db
+                .set_sync_label(block_1a.hash())
+                .await;
+
+            assert_eq!(
+                WalletReconciliation::Reconciled {
+                    wallet_sync_label: block_1a.hash(),
+                    tip: tip.hash(),
+                    luca: genesis_block.hash(),
+                    num_reverted_blocks: 1,
+                    num_applied_blocks: 2,
+                    num_unspent: 0,
+                    num_orphaned: 2,
+                },
+                alice.reconcile_wallet_with_tip().await.unwrap()
+            );
+            assert_eq!(tip.hash(), alice.wallet_state.wallet_db.get_sync_label());
+            let wallet_status = alice
+                .wallet_state
+                .get_wallet_status(tip.hash(), &tip.mutator_set_accumulator_after().unwrap())
+                .await;
+            assert_eq!(1, wallet_status.synced_unspent.len());
+            assert_eq!(2, wallet_status.unsynced.len());
+
+            // A sync label unknown to the archival state can only be compared
+            // against the canonical chain, which is scanned again from the
+            // highest canonical block the wallet refers to.
+            let unknown_block: Digest = rng.random();
+            alice
+                .wallet_state
+                .wallet_db
+                .set_sync_label(unknown_block)
+                .await;
+            assert_eq!(
+                WalletReconciliation::UnknownSyncLabel {
+                    wallet_sync_label: unknown_block,
+                    tip: tip.hash(),
+                    rescanned_from: BlockHeight::genesis().next(),
+                    num_applied_blocks: 2,
+                    num_unspent: 0,
+                    num_orphaned: 2,
+                },
+                alice.reconcile_wallet_with_tip().await.unwrap()
+            );
+            assert_eq!(tip.hash(), alice.wallet_state.wallet_db.get_sync_label());
+            let wallet_status = alice
+                .wallet_state
+                .get_wallet_status(tip.hash(), &tip.mutator_set_accumulator_after().unwrap())
+                .await;
+            assert_eq!(1, wallet_status.synced_unspent.len());
+            assert_eq!(2, wallet_status.unsynced.len());
+        }
     }
 
     #[traced_test]
@@ -4188,6 +5490,53 @@ mod tests {
         assert_eq!(7, block_2.kernel.body.transaction_kernel.outputs.len());
     }
 
+    #[traced_test]
+    #[apply(shared_tokio_runtime)]
+    async fn block_validation_results_are_cached() {
+        let network = Network::Main;
+        let mut global_state_lock = mock_genesis_global_state(
+            2,
+            WalletEntropy::devnet_wallet(),
+            cli_args::Args::default_with_network(network),
+        )
+        .await;
+        let genesis_block = Block::genesis(network);
+        let now = genesis_block.kernel.header.timestamp + Timestamp::hours(1);
+        let block1 =
+            fake_valid_successor_for_tests(&genesis_block, now, Default::default(), network).await;
+
+        // validating against another block than the committed predecessor
+        // bypasses the cache.
+        assert!(
+            !global_state_lock
+                .is_valid_block(&block1, &block1, now)
+                .await
+        );
+        let metrics = global_state_lock
+            .lock_guard()
+            .await
+            .block_validation_cache
+            .metrics();
+        assert_eq!((0, 0, 0), (metrics.hits, metrics.misses, metrics.len));
+
+        assert!(
+            global_state_lock
+                .is_valid_block(&block1, &genesis_block, now)
+                .await
+        );
+        assert!(
+            global_state_lock
+                .is_valid_block(&block1, &genesis_block, now)
+                .await
+        );
+        let metrics = global_state_lock
+            .lock_guard()
+            .await
+            .block_validation_cache
+            .metrics();
+        assert_eq!((1, 1, 1), (metrics.hits, metrics.misses, metrics.len));
+    }
+
     #[traced_test]
     #[apply(shared_tokio_runtime)]
     async fn mock_global_state_is_valid() {
@@ -0,0 +1,292 @@
+//! Growth of the mutator set over recent blocks.
+//!
+//! Every output of a block adds a commitment to the append-only commitment
+//! list (AOCL) of the mutator set, and every input sets indices in its sliding
+//! window Bloom filter (SWBF). The [`MutatorSetGrowthTracker`] records the
+//! number of additions and removals of each new tip, so that the
+//! [`MutatorSetStatistics`] describing chain usage are available without
+//! reading old blocks.
+
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+use itertools::Itertools;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::protocol::consensus::block::Block;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+use crate::util_types::mutator_set::mutator_set_accumulator::MutatorSetAccumulator;
+use crate::util_types::mutator_set::shared::WINDOW_SIZE;
+
+/// Number of most recent blocks over which growth rates are computed.
+pub(crate) const MUTATOR_SET_STATISTICS_NUM_BLOCKS: usize = 1_000;
+
+/// The additions to and removals from the mutator set of one block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BlockSample {
+    height: BlockHeight,
+    timestamp: Timestamp,
+    num_additions: u64,
+    num_removals: u64,
+}
+
+impl BlockSample {
+    fn new(block: &Block) -> Self {
+        let num_guesser_fee_additions = block
+            .guesser_fee_addition_records()
+            .map(|addition_records| addition_records.len())
+            .unwrap_or_default();
+        let num_additions =
+            block.body().transaction_kernel.outputs.len() + num_guesser_fee_additions;
+
+        Self {
+            height: block.header().height,
+            timestamp: block.header().timestamp,
+            num_additions: num_additions as u64,
+            num_removals: block.body().transaction_kernel.inputs.len() as u64,
+        }
+    }
+}
+
+/// Statistics on the usage of the chain, derived from the mutator set of the
+/// tip and from the blocks leading up to it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MutatorSetStatistics {
+    pub tip_height: BlockHeight,
+
+    /// Number of commitments in the AOCL, ie, number of UTXOs ever created.
+    pub aocl_leaf_count: u64,
+
+    /// Estimated number of UTXOs among which the one being spent by an input
+    /// hides. A removal record does not reveal which AOCL leaf it spends, so
+    /// every leaf is a candidate.
+    pub estimated_anonymity_set_size: u64,
+
+    /// Number of distinct indices set in the active window of the SWBF.
+    pub active_window_num_set_indices: u64,
+
+    /// Share of the active window of the SWBF that is set, between 0 and 1.
+    pub active_window_density: f64,
+
+    /// Number of blocks the rates below are computed over. Only blocks
+    /// applied since the node started count, up to
+    /// [`MUTATOR_SET_STATISTICS_NUM_BLOCKS`].
+    pub num_sampled_blocks: usize,
+
+    /// Average number of additions to the AOCL per block. `None` if no block
+    /// was sampled.
+    pub additions_per_block: Option<f64>,
+
+    /// Average number of removals from the mutator set per block. `None` if
+    /// no block was sampled.
+    pub removals_per_block: Option<f64>,
+
+    /// Number of additions to the AOCL per day. `None` unless the sampled
+    /// blocks span a positive duration.
+    pub aocl_growth_per_day: Option<f64>,
+}
+
+impl Display for MutatorSetStatistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rate = |rate: Option<f64>| rate.map_or("unknown".to_string(), |r| format!("{r:.2}"));
+        writeln!(f, "tip height: {}", self.tip_height)?;
+        writeln!(f, "AOCL leaf count: {}", self.aocl_leaf_count)?;
+        writeln!(
+            f,
+            "estimated anonymity set size: {}",
+            self.estimated_anonymity_set_size
+        )?;
+        writeln!(
+            f,
+            "active window: {} indices set ({:.4}%)",
+            self.active_window_num_set_indices,
+            self.active_window_density * 100.0
+        )?;
+        writeln!(f, "over the last {} blocks:", self.num_sampled_blocks)?;
+        writeln!(
+            f,
+            "  additions per block: {}",
+            rate(self.additions_per_block)
+        )?;
+        writeln!(f, "  removals per block: {}", rate(self.removals_per_block))?;
+        writeln!(
+            f,
+            "  AOCL growth per day: {}",
+            rate(self.aocl_growth_per_day)
+        )
+    }
+}
+
+/// Records the mutator set additions and removals of recent tips.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MutatorSetGrowthTracker {
+    samples: VecDeque<BlockSample>,
+    total_additions: u64,
+    total_removals: u64,
+}
+
+impl MutatorSetGrowthTracker {
+    pub(crate) fn handle_new_tip(&mut self, new_tip: &Block) {
+        self.record(BlockSample::new(new_tip));
+    }
+
+    fn record(&mut self, sample: BlockSample) {
+        // Samples at or above the new tip's height are of blocks that are no
+        // longer canonical.
+        while self
+            .samples
+            .back()
+            .is_some_and(|back| back.height >= sample.height)
+        {
+            self.pop_back();
+        }
+
+        self.total_additions += sample.num_additions;
+        self.total_removals += sample.num_removals;
+        self.samples.push_back(sample);
+
+        while self.samples.len() > MUTATOR_SET_STATISTICS_NUM_BLOCKS {
+            self.pop_front();
+        }
+    }
+
+    fn pop_back(&mut self) {
+        if let Some(sample) = self.samples.pop_back() {
+            self.forget(sample);
+        }
+    }
+
+    fn pop_front(&mut self) {
+        if let Some(sample) = self.samples.pop_front() {
+            self.forget(sample);
+        }
+    }
+
+    fn forget(&mut self, sample: BlockSample) {
+        self.total_additions -= sample.num_additions;
+        self.total_removals -= sample.num_removals;
+    }
+
+    /// The statistics for the tip at `tip_height` whose mutator set is
+    /// `mutator_set`.
+    pub(crate) fn statistics(
+        &self,
+        tip_height: BlockHeight,
+        mutator_set: &MutatorSetAccumulator,
+    ) -> MutatorSetStatistics {
+        let aocl_leaf_count = mutator_set.aocl.num_leafs();
+        let active_window_num_set_indices =
+            mutator_set.swbf_active.sbf.iter().unique().count() as u64;
+
+        let num_sampled_blocks = self.samples.len();
+        let per_block =
+            |total: u64| (num_sampled_blocks > 0).then(|| total as f64 / num_sampled_blocks as f64);
+
+        MutatorSetStatistics {
+            tip_height,
+            aocl_leaf_count,
+            estimated_anonymity_set_size: aocl_leaf_count,
+            active_window_num_set_indices,
+            active_window_density: active_window_num_set_indices as f64 / f64::from(WINDOW_SIZE),
+            num_sampled_blocks,
+            additions_per_block: per_block(self.total_additions),
+            removals_per_block: per_block(self.total_removals),
+            aocl_growth_per_day: self.aocl_growth_per_day(),
+        }
+    }
+
+    /// The additions of all but the first sampled block, over the time since
+    /// the first sampled block.
+    fn aocl_growth_per_day(&self) -> Option<f64> {
+        let (first, last) = (self.samples.front()?, self.samples.back()?);
+        let duration = last
+            .timestamp
+            .to_millis()
+            .checked_sub(first.timestamp.to_millis())?;
+        if duration == 0 {
+            return None;
+        }
+
+        let num_additions = self.total_additions - first.num_additions;
+        let num_days = duration as f64 / Timestamp::days(1).to_millis() as f64;
+        Some(num_additions as f64 / num_days)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    fn sample(height: u64, num_additions: u64, num_removals: u64) -> BlockSample {
+        BlockSample {
+            height: height.into(),
+            timestamp: Timestamp::days(1) * height as usize,
+            num_additions,
+            num_removals,
+        }
+    }
+
+    #[test]
+    fn rates_cover_the_most_recent_blocks() {
+        let mut tracker = MutatorSetGrowthTracker::default();
+        let mutator_set = MutatorSetAccumulator::default();
+        let statistics = tracker.statistics(BlockHeight::genesis(), &mutator_set);
+        assert_eq!(0, statistics.num_sampled_blocks);
+        assert_eq!(None, statistics.additions_per_block);
+        assert_eq!(None, statistics.aocl_growth_per_day);
+        assert_eq!(0.0, statistics.active_window_density);
+
+        let num_blocks = MUTATOR_SET_STATISTICS_NUM_BLOCKS as u64 + 10;
+        for height in 1..=num_blocks {
+            // an addition more than there are removals in every block
+            tracker.record(sample(height, height + 1, height));
+        }
+
+        let statistics = tracker.statistics(num_blocks.into(), &mutator_set);
+        assert_eq!(
+            MUTATOR_SET_STATISTICS_NUM_BLOCKS,
+            statistics.num_sampled_blocks
+        );
+        let removals_per_block = statistics.removals_per_block.unwrap();
+        assert_eq!(
+            removals_per_block + 1.0,
+            statistics.additions_per_block.unwrap()
+        );
+        assert!(removals_per_block > 10.0);
+    }
+
+    #[test]
+    fn samples_of_orphaned_blocks_are_forgotten() {
+        let mut tracker = MutatorSetGrowthTracker::default();
+        tracker.record(sample(1, 2, 0));
+        tracker.record(sample(2, 4, 1));
+        tracker.record(sample(3, 6, 1));
+
+        // reorganization to a competing block of height 2
+        tracker.record(sample(2, 2, 0));
+
+        let statistics = tracker.statistics(2u64.into(), &MutatorSetAccumulator::default());
+        assert_eq!(2, statistics.num_sampled_blocks);
+        assert_eq!(Some(2.0), statistics.additions_per_block);
+        assert_eq!(Some(0.0), statistics.removals_per_block);
+
+        // one day between the two blocks, with the second adding two UTXOs
+        assert_eq!(Some(2.0), statistics.aocl_growth_per_day);
+    }
+
+    #[test]
+    fn density_counts_distinct_indices_in_active_window() {
+        let mut mutator_set = MutatorSetAccumulator::default();
+        mutator_set.swbf_active.sbf = vec![1, 1, 2, WINDOW_SIZE - 1];
+        let statistics =
+            MutatorSetGrowthTracker::default().statistics(BlockHeight::genesis(), &mutator_set);
+        assert_eq!(3, statistics.active_window_num_set_indices);
+        assert_eq!(
+            3.0 / f64::from(WINDOW_SIZE),
+            statistics.active_window_density
+        );
+    }
+}
@@ -1,6 +1,9 @@
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::net::IpAddr;
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::Mutex;
 use std::time::SystemTime;
 
 use anyhow::Result;
@@ -8,14 +11,21 @@ use tasm_lib::prelude::Digest;
 use tasm_lib::twenty_first::util_types::mmr::mmr_accumulator::MmrAccumulator;
 
 use crate::application::config::data_directory::DataDirectory;
-use crate::application::database::create_db_if_missing;
+use crate::application::database::open_files::db_options;
+use crate::application::database::open_files::DbStore;
 use crate::application::database::NeptuneLevelDb;
 use crate::application::database::WriteBatchAsync;
 use crate::protocol::consensus::block::block_height::BlockHeight;
 use crate::protocol::consensus::block::difficulty_control::ProofOfWork;
+use crate::protocol::peer::block_relay::RecentBlockAnnouncements;
+use crate::protocol::peer::fork_signaling::ForkSignals;
 use crate::protocol::peer::peer_info::PeerInfo;
+use crate::protocol::peer::peer_latency;
+use crate::protocol::peer::peer_latency::median_clock_offset_millis;
+use crate::protocol::peer::transaction_announcements::TransactionAnnouncements;
 use crate::protocol::peer::InstanceId;
 use crate::protocol::peer::PeerStanding;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
 use crate::state::database::PeerDatabases;
 
 pub const BANNED_IPS_DB_NAME: &str = "banned_ips";
@@ -38,6 +48,9 @@ pub(crate) struct SyncAnchor {
 
     /// The last time this anchor was either created or updated.
     pub(crate) updated: SystemTime,
+
+    /// The peer whose claimed tip this anchor was created from, if known.
+    pub(crate) announced_by: Option<SocketAddr>,
 }
 
 impl SyncAnchor {
@@ -50,6 +63,7 @@ impl SyncAnchor {
             block_mmr: claimed_block_mmra,
             champion: None,
             updated: SystemTime::now(),
+            announced_by: None,
         }
     }
 
@@ -106,6 +120,34 @@ pub struct NetworkingState {
     ///
     /// Only the peer tasks may update this map.
     disconnection_times: HashMap<InstanceId, SystemTime>,
+
+    /// Addresses at which outgoing connections reached this node itself, for
+    /// example its public address as listed by peers. They are not dialed
+    /// again.
+    ///
+    /// Only the peer tasks may update this set.
+    pub(crate) self_addresses: HashSet<SocketAddr>,
+
+    /// New blocks recently announced by peers, for crediting the peer that
+    /// announced a block first.
+    ///
+    /// Only the peer tasks may update this.
+    pub(crate) recent_block_announcements: RecentBlockAnnouncements,
+
+    /// The peers that announced recent transactions, as a measure of the
+    /// transactions' propagation. Shared with the [`GlobalStateLock`], so
+    /// that peer tasks can record announcements without acquiring the lock.
+    ///
+    /// Only the peer tasks may update this.
+    ///
+    /// [`GlobalStateLock`]: crate::state::GlobalStateLock
+    pub(crate) transaction_announcements: Arc<Mutex<TransactionAnnouncements>>,
+
+    /// The consensus rule sets recently connected peers signaled readiness
+    /// for.
+    ///
+    /// Only the peer tasks may update this.
+    pub(crate) fork_signals: ForkSignals,
 }
 
 impl NetworkingState {
@@ -117,6 +159,10 @@ impl NetworkingState {
             instance_id: rand::random(),
             freeze: false,
             disconnection_times: HashMap::new(),
+            self_addresses: HashSet::new(),
+            recent_block_announcements: RecentBlockAnnouncements::default(),
+            transaction_announcements: Arc::new(Mutex::new(TransactionAnnouncements::default())),
+            fork_signals: ForkSignals::default(),
         }
     }
 
@@ -127,7 +173,7 @@ impl NetworkingState {
 
         let peer_standings = NeptuneLevelDb::<IpAddr, PeerStanding>::new(
             &data_dir.banned_ips_database_dir_path(),
-            &create_db_if_missing(),
+            &db_options(DbStore::PeerStandings),
         )
         .await?;
 
@@ -215,4 +261,32 @@ impl NetworkingState {
     pub(crate) fn last_disconnection_time_of_peer(&self, id: InstanceId) -> Option<SystemTime> {
         self.disconnection_times.get(&id).copied()
     }
+
+    /// The median offset of connected peers' clocks from this node's clock, in
+    /// milliseconds, as measured by pings. Positive if the peers' clocks are
+    /// ahead. Returns `None` if no peer answered a ping yet.
+    ///
+    /// Unlike the offset of any single peer, the median cannot be skewed by a
+    /// minority of peers misreporting their time.
+    pub(crate) fn median_peer_clock_offset_millis(&self) -> Option<i64> {
+        median_clock_offset_millis(
+            self.peer_map
+                .values()
+                .filter_map(|peer_info| peer_info.latency())
+                .map(|latency| latency.clock_offset_millis),
+        )
+    }
+
+    /// The time `now` on this node's clock, adjusted by the median offset of
+    /// connected peers' clocks, within bounds. See
+    /// [`peer_latency::network_adjusted_time`].
+    pub(crate) fn network_adjusted_time(&self, now: Timestamp) -> Timestamp {
+        peer_latency::network_adjusted_time(
+            now,
+            self.peer_map
+                .values()
+                .filter_map(|peer_info| peer_info.latency())
+                .map(|latency| latency.clock_offset_millis),
+        )
+    }
 }
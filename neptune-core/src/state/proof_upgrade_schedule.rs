@@ -0,0 +1,263 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use chrono::NaiveTime;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::application::config::cli_args;
+
+/// A daily time-of-day window, in the node's local time.
+///
+/// Syntax: `HH:MM-HH:MM`, e.g. `01:00-06:00`. The start is inclusive, the end
+/// exclusive. A window whose end precedes its start wraps around midnight, so
+/// `22:00-04:00` covers the night. Serialized in the same syntax.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct UpgradeWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl FromStr for UpgradeWindow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((start, end)) = s.split_once('-') else {
+            return Err(format!("Expected 'HH:MM-HH:MM', got '{s}'"));
+        };
+        let parse = |time: &str| {
+            NaiveTime::parse_from_str(time.trim(), "%H:%M")
+                .map_err(|e| format!("Invalid time of day '{time}': {e}"))
+        };
+
+        let window = Self {
+            start: parse(start)?,
+            end: parse(end)?,
+        };
+        if window.start == window.end {
+            return Err(format!("Window '{s}' is empty"));
+        }
+
+        Ok(window)
+    }
+}
+
+impl TryFrom<String> for UpgradeWindow {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<UpgradeWindow> for String {
+    fn from(window: UpgradeWindow) -> Self {
+        window.to_string()
+    }
+}
+
+impl Display for UpgradeWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+impl UpgradeWindow {
+    /// Whether the window contains the given time of day.
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start < self.end {
+            self.start <= time && time < self.end
+        } else {
+            self.start <= time || time < self.end
+        }
+    }
+}
+
+/// Why transaction-proof upgrading is currently deferred.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ProofUpgradeDeferral {
+    /// Upgrading was paused by the operator.
+    Paused,
+
+    /// The current time of day lies outside all upgrade windows.
+    OutsideWindows,
+
+    /// CPU usage exceeds the configured maximum.
+    CpuLoad {
+        cpu_percent: f32,
+        max_cpu_percent: u8,
+    },
+}
+
+impl Display for ProofUpgradeDeferral {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Paused => write!(f, "paused by operator"),
+            Self::OutsideWindows => write!(f, "outside upgrade windows"),
+            Self::CpuLoad {
+                cpu_percent,
+                max_cpu_percent,
+            } => write!(f, "cpu usage {cpu_percent:.1}% exceeds {max_cpu_percent}%"),
+        }
+    }
+}
+
+/// When the node may spend its prover on upgrading the proofs of 3rd party
+/// transactions.
+///
+/// Set at startup from `--proof-upgrade-window` and `--proof-upgrade-max-cpu`,
+/// and replaceable at runtime via RPC. Runtime changes are not persisted.
+///
+/// Only 3rd party transactions are subject to the schedule. Transactions
+/// initiated by this node's wallet are upgraded regardless.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ProofUpgradeSchedule {
+    /// The windows within which upgrading may start. If empty, upgrading may
+    /// start at any time of day.
+    pub windows: Vec<UpgradeWindow>,
+
+    /// CPU usage, in percent across all cores, at or above which upgrading
+    /// is deferred. If `None`, CPU usage is not considered.
+    pub max_cpu_percent: Option<u8>,
+
+    /// Whether upgrading is paused, regardless of windows and CPU usage.
+    pub paused: bool,
+}
+
+impl From<&cli_args::Args> for ProofUpgradeSchedule {
+    fn from(cli: &cli_args::Args) -> Self {
+        Self {
+            windows: cli.proof_upgrade_window.clone(),
+            max_cpu_percent: cli.proof_upgrade_max_cpu,
+            paused: false,
+        }
+    }
+}
+
+impl ProofUpgradeSchedule {
+    /// Check that the schedule can be followed.
+    pub fn validate(&self) -> Result<(), String> {
+        match self.max_cpu_percent {
+            Some(percent) if !(1..=100).contains(&percent) => Err(format!(
+                "maximum cpu usage must be between 1 and 100 percent, got {percent}"
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the reason for deferring upgrading at local time of day
+    /// `time`, given the most recent CPU usage sample, if any. Returns `None`
+    /// if upgrading may start.
+    ///
+    /// In the absence of a CPU usage sample, CPU usage is not considered.
+    pub fn deferral(
+        &self,
+        time: NaiveTime,
+        cpu_percent: Option<f32>,
+    ) -> Option<ProofUpgradeDeferral> {
+        if self.paused {
+            return Some(ProofUpgradeDeferral::Paused);
+        }
+
+        if !self.windows.is_empty() && !self.windows.iter().any(|w| w.contains(time)) {
+            return Some(ProofUpgradeDeferral::OutsideWindows);
+        }
+
+        match (self.max_cpu_percent, cpu_percent) {
+            (Some(max_cpu_percent), Some(cpu_percent))
+                if cpu_percent >= f32::from(max_cpu_percent) =>
+            {
+                Some(ProofUpgradeDeferral::CpuLoad {
+                    cpu_percent,
+                    max_cpu_percent,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Publicly visible schedule status, as reported by RPC.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProofUpgradeScheduleStatus {
+    /// The schedule in effect.
+    pub schedule: ProofUpgradeSchedule,
+
+    /// Why upgrading is currently deferred, or `None` if the schedule
+    /// permits it.
+    ///
+    /// Upgrading additionally requires `--tx-proof-upgrading`, a node capable
+    /// of producing single proofs, and that the node is neither syncing nor
+    /// shedding load.
+    pub deferral: Option<ProofUpgradeDeferral>,
+
+    /// The most recent CPU usage sample, in percent, if any.
+    pub cpu_percent: Option<f32>,
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    fn time(s: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(s, "%H:%M").unwrap()
+    }
+
+    #[test]
+    fn windows_parse_and_wrap_midnight() {
+        let night: UpgradeWindow = "01:00-06:00".parse().unwrap();
+        assert_eq!("01:00-06:00", night.to_string());
+        assert!(night.contains(time("01:00")));
+        assert!(night.contains(time("05:59")));
+        assert!(!night.contains(time("06:00")));
+        assert!(!night.contains(time("12:00")));
+
+        let wrapping: UpgradeWindow = "22:00-04:00".parse().unwrap();
+        assert!(wrapping.contains(time("23:30")));
+        assert!(wrapping.contains(time("00:00")));
+        assert!(!wrapping.contains(time("04:00")));
+        assert!(!wrapping.contains(time("21:59")));
+
+        for invalid in ["01:00", "1-6", "25:00-06:00", "03:00-03:00"] {
+            assert!(invalid.parse::<UpgradeWindow>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn deferral_considers_pause_windows_and_cpu() {
+        let mut schedule = ProofUpgradeSchedule::default();
+        assert_eq!(None, schedule.deferral(time("12:00"), Some(100.0)));
+
+        schedule.windows = vec!["01:00-06:00".parse().unwrap()];
+        schedule.max_cpu_percent = Some(50);
+        assert_eq!(
+            Some(ProofUpgradeDeferral::OutsideWindows),
+            schedule.deferral(time("12:00"), Some(10.0))
+        );
+        assert_eq!(None, schedule.deferral(time("03:00"), Some(10.0)));
+        assert_eq!(None, schedule.deferral(time("03:00"), None));
+        assert_eq!(
+            Some(ProofUpgradeDeferral::CpuLoad {
+                cpu_percent: 50.0,
+                max_cpu_percent: 50
+            }),
+            schedule.deferral(time("03:00"), Some(50.0))
+        );
+
+        schedule.paused = true;
+        assert_eq!(
+            Some(ProofUpgradeDeferral::Paused),
+            schedule.deferral(time("03:00"), Some(10.0))
+        );
+
+        schedule.max_cpu_percent = Some(0);
+        assert!(schedule.validate().is_err());
+    }
+}
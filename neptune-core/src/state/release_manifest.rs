@@ -0,0 +1,424 @@
+//! Detection of new releases from a signed release manifest.
+//!
+//! If the node is started with
+//! [`release_manifest_url`](crate::application::config::cli_args::Args::release_manifest_url),
+//! it periodically fetches a [`SignedReleaseManifest`] announcing the latest
+//! release for its network. A manifest is only trusted if it is signed by the
+//! release key the operator configured with
+//! [`release_manifest_signer`](crate::application::config::cli_args::Args::release_manifest_signer). The node never updates
+//! itself; it only reports whether an update is available, and whether the
+//! update is required before some block height, eg because of a hard fork.
+
+use std::time::Duration;
+
+use anyhow::bail;
+use anyhow::ensure;
+use anyhow::Context;
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Digest;
+use tasm_lib::prelude::Tip5;
+use tasm_lib::triton_vm::prelude::BFieldCodec;
+use tasm_lib::triton_vm::prelude::BFieldElement;
+use tasm_lib::triton_vm::proof::Claim;
+use tasm_lib::twenty_first::bfe_vec;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+
+use crate::application::config::network::Network;
+use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::protocol::consensus::transaction::lock_script::LockScript;
+use crate::protocol::consensus::transaction::Proof;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+use crate::protocol::proof_abstractions::verifier;
+
+/// Domain separator for release manifest digests, so that a manifest signature
+/// cannot be mistaken for any other use of a hash lock.
+const RELEASE_MANIFEST_DOMAIN_SEPARATOR: u64 = 0x7265_6c65_6173_6531;
+
+/// Maximum size in bytes of a response to a release manifest request,
+/// including headers.
+const MAX_RELEASE_MANIFEST_RESPONSE_SIZE: u64 = 1024 * 1024;
+
+/// Time allowed for fetching a release manifest.
+const RELEASE_MANIFEST_FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An announcement of the latest release for a network.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReleaseManifest {
+    pub network: Network,
+
+    /// Semver version of the release.
+    pub version: String,
+
+    /// If set, nodes running an older version must update before the chain
+    /// reaches this height, eg because a hard fork activates there.
+    pub mandatory_before_height: Option<BlockHeight>,
+
+    pub published: Timestamp,
+}
+
+impl ReleaseManifest {
+    /// The digest that is signed.
+    pub fn digest(&self) -> Digest {
+        let version = self
+            .version
+            .bytes()
+            .map(|byte| BFieldElement::new(byte.into()))
+            .collect::<Vec<_>>();
+        Tip5::hash_varlen(
+            &[
+                bfe_vec![RELEASE_MANIFEST_DOMAIN_SEPARATOR, self.network.id()],
+                version.encode(),
+                self.mandatory_before_height.encode(),
+                self.published.encode(),
+            ]
+            .concat(),
+        )
+    }
+
+    fn public_input(&self) -> Vec<BFieldElement> {
+        self.digest().reversed().values().to_vec()
+    }
+
+    /// Compare the manifest to the running version.
+    fn notice(&self, current_version: &str) -> anyhow::Result<UpdateNotice> {
+        let current = semver::Version::parse(current_version)
+            .with_context(|| format!("invalid version of this node: {current_version}"))?;
+        let latest = semver::Version::parse(&self.version)
+            .with_context(|| format!("invalid version in release manifest: {}", self.version))?;
+
+        if latest <= current {
+            return Ok(UpdateNotice::UpToDate);
+        }
+
+        let version = self.version.clone();
+        Ok(match self.mandatory_before_height {
+            Some(height) => UpdateNotice::Mandatory { version, height },
+            None => UpdateNotice::Available { version },
+        })
+    }
+}
+
+/// A [`ReleaseManifest`] signed by a release key.
+///
+/// Like a [`SignedCheckpoint`](super::checkpoint::SignedCheckpoint), the
+/// signature is a proof of the standard hash lock whose after-image is the
+/// release key, with the manifest digest as public input.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedReleaseManifest {
+    pub manifest: ReleaseManifest,
+    pub signature: Proof,
+}
+
+impl SignedReleaseManifest {
+    /// The claim proven by the signature, if signed by `signer`.
+    fn claim(&self, signer: Digest) -> Claim {
+        let program_digest = LockScript::standard_hash_lock_from_after_image(signer).hash();
+        Claim::new(program_digest).with_input(self.manifest.public_input())
+    }
+
+    /// Verify that the manifest is for `network` and was signed by `signer`.
+    ///
+    /// On networks that use mock proofs, any valid mock proof is accepted.
+    pub async fn verify(&self, network: Network, signer: Digest) -> bool {
+        self.manifest.network == network
+            && verifier::verify(self.claim(signer), self.signature.clone(), network).await
+    }
+}
+
+/// What the node should tell its operator about new releases.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum UpdateNotice {
+    /// No valid manifest has been fetched yet.
+    #[default]
+    Unknown,
+
+    /// The node runs the latest release, or a newer one.
+    UpToDate,
+
+    /// A newer release is available.
+    Available { version: String },
+
+    /// A newer release is available and must be installed before the chain
+    /// reaches `height`.
+    Mandatory {
+        version: String,
+        height: BlockHeight,
+    },
+}
+
+/// Outcome of the most recent release checks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpdateStatus {
+    /// Version of this node.
+    pub current_version: String,
+
+    pub notice: UpdateNotice,
+
+    /// The most recent valid manifest, if any.
+    pub manifest: Option<ReleaseManifest>,
+
+    pub last_checked: Option<Timestamp>,
+
+    /// Why the most recent check failed, if it did. The notice of the last
+    /// successful check, if any, remains in place.
+    pub last_error: Option<String>,
+}
+
+impl UpdateStatus {
+    pub(crate) fn new(current_version: &str) -> Self {
+        Self {
+            current_version: current_version.to_owned(),
+            notice: UpdateNotice::Unknown,
+            manifest: None,
+            last_checked: None,
+            last_error: None,
+        }
+    }
+
+    /// Record the outcome of a release check performed at `now`.
+    pub(crate) fn record_check(
+        &mut self,
+        manifest: anyhow::Result<ReleaseManifest>,
+        now: Timestamp,
+    ) {
+        self.last_checked = Some(now);
+        let manifest = match manifest {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                self.last_error = Some(format!("{e:#}"));
+                return;
+            }
+        };
+
+        match manifest.notice(&self.current_version) {
+            Ok(notice) => {
+                self.notice = notice;
+                self.manifest = Some(manifest);
+                self.last_error = None;
+            }
+            Err(e) => self.last_error = Some(format!("{e:#}")),
+        }
+    }
+}
+
+/// Fetch the release manifest at `url` and verify that it was signed by
+/// `signer`.
+pub(crate) async fn fetch_release_manifest(
+    url: &str,
+    network: Network,
+    signer: Digest,
+) -> anyhow::Result<ReleaseManifest> {
+    let body = tokio::time::timeout(RELEASE_MANIFEST_FETCH_TIMEOUT, http_get(url))
+        .await
+        .context("timed out fetching release manifest")??;
+    let signed: SignedReleaseManifest =
+        serde_json::from_slice(&body).context("failed to parse release manifest")?;
+    ensure!(
+        signed.verify(network, signer).await,
+        "release manifest for network {network} is not signed by the configured release key"
+    );
+
+    Ok(signed.manifest)
+}
+
+/// Split an `http://host[:port][/path]` URL into host, port, and path.
+///
+/// Only plain HTTP is supported. The manifest is authenticated by its
+/// signature, so transport security is not needed.
+fn parse_http_url(url: &str) -> anyhow::Result<(&str, u16, &str)> {
+    let Some(rest) = url.strip_prefix("http://") else {
+        bail!("release manifest URL must start with http://, got {url}");
+    };
+    let (authority, path) = match rest.find('/') {
+        Some(i) => rest.split_at(i),
+        None => (rest, "/"),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse().context("invalid port")?),
+        None => (authority, 80),
+    };
+    ensure!(!host.is_empty(), "release manifest URL has no host: {url}");
+
+    Ok((host, port, path))
+}
+
+/// Fetch the body of a successful HTTP/1.0 `GET` response.
+async fn http_get(url: &str) -> anyhow::Result<Vec<u8>> {
+    let (host, port, path) = parse_http_url(url)?;
+    let mut stream = TcpStream::connect((host, port))
+        .await
+        .with_context(|| format!("failed to connect to {host}:{port}"))?;
+    let request = format!("GET {path} HTTP/1.0\r\nHost: {host}\r\nConnection: close\r\n\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    let mut response = vec![];
+    stream
+        .take(MAX_RELEASE_MANIFEST_RESPONSE_SIZE)
+        .read_to_end(&mut response)
+        .await?;
+
+    http_response_body(response)
+}
+
+/// The body of an HTTP response, if its status is 200 OK.
+fn http_response_body(mut response: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+    const HEADER_TERMINATOR: &[u8] = b"\r\n\r\n";
+    let Some(header_length) = response
+        .windows(HEADER_TERMINATOR.len())
+        .position(|window| window == HEADER_TERMINATOR)
+    else {
+        bail!("malformed HTTP response");
+    };
+
+    let header = String::from_utf8_lossy(&response[..header_length]);
+    let status_line = header.lines().next().unwrap_or_default();
+    let status = status_line.split_whitespace().nth(1);
+    ensure!(
+        status == Some("200"),
+        "release manifest request failed: {status_line}"
+    );
+
+    Ok(response.split_off(header_length + HEADER_TERMINATOR.len()))
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use macro_rules_attr::apply;
+    use rand::random;
+    use tasm_lib::triton_vm::prelude::PublicInput;
+
+    use super::*;
+    use crate::application::triton_vm_job_queue::TritonVmJobQueue;
+    use crate::protocol::consensus::transaction::lock_script::LockScriptAndWitness;
+    use crate::protocol::proof_abstractions::tasm::program::TritonVmProofJobOptions;
+    use crate::tests::shared_tokio_runtime;
+
+    fn manifest(version: &str, mandatory_before_height: Option<u64>) -> ReleaseManifest {
+        ReleaseManifest {
+            network: Network::Main,
+            version: version.to_owned(),
+            mandatory_before_height: mandatory_before_height.map(BlockHeight::from),
+            published: Timestamp::now(),
+        }
+    }
+
+    #[test]
+    fn notice_compares_versions() {
+        assert_eq!(
+            UpdateNotice::UpToDate,
+            manifest("0.3.0", None).notice("0.3.0").unwrap()
+        );
+        assert_eq!(
+            UpdateNotice::UpToDate,
+            manifest("0.2.9", Some(100)).notice("0.3.0").unwrap()
+        );
+        assert_eq!(
+            UpdateNotice::Available {
+                version: "0.3.1".to_owned()
+            },
+            manifest("0.3.1", None).notice("0.3.0").unwrap()
+        );
+        assert_eq!(
+            UpdateNotice::Mandatory {
+                version: "0.4.0".to_owned(),
+                height: 100u64.into()
+            },
+            manifest("0.4.0", Some(100)).notice("0.3.0").unwrap()
+        );
+        assert!(manifest("latest", None).notice("0.3.0").is_err());
+    }
+
+    #[test]
+    fn failed_check_keeps_previous_notice() {
+        let mut status = UpdateStatus::new("0.3.0");
+        status.record_check(Ok(manifest("0.3.1", None)), Timestamp::now());
+        assert!(matches!(status.notice, UpdateNotice::Available { .. }));
+        assert!(status.last_error.is_none());
+
+        status.record_check(Err(anyhow::anyhow!("unreachable")), Timestamp::now());
+        assert!(matches!(status.notice, UpdateNotice::Available { .. }));
+        assert_eq!(Some("unreachable".to_owned()), status.last_error);
+    }
+
+    #[test]
+    fn digest_commits_to_all_fields() {
+        let original = manifest("0.3.1", Some(100));
+        let digest = original.digest();
+        for modified in [
+            ReleaseManifest {
+                network: Network::Testnet(0),
+                ..original.clone()
+            },
+            ReleaseManifest {
+                version: "0.3.2".to_owned(),
+                ..original.clone()
+            },
+            ReleaseManifest {
+                mandatory_before_height: None,
+                ..original.clone()
+            },
+            ReleaseManifest {
+                published: original.published + Timestamp::seconds(1),
+                ..original.clone()
+            },
+        ] {
+            assert_ne!(digest, modified.digest());
+        }
+    }
+
+    #[test]
+    fn http_urls_are_parsed() {
+        assert_eq!(
+            ("example.org", 80, "/"),
+            parse_http_url("http://example.org").unwrap()
+        );
+        assert_eq!(
+            ("example.org", 8080, "/main/manifest.json"),
+            parse_http_url("http://example.org:8080/main/manifest.json").unwrap()
+        );
+        assert!(parse_http_url("https://example.org").is_err());
+        assert!(parse_http_url("http://:80/").is_err());
+    }
+
+    #[test]
+    fn only_successful_http_responses_have_a_body() {
+        let ok = b"HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n{}".to_vec();
+        assert_eq!(b"{}".to_vec(), http_response_body(ok).unwrap());
+
+        let not_found = b"HTTP/1.0 404 Not Found\r\n\r\n".to_vec();
+        assert!(http_response_body(not_found).is_err());
+        assert!(http_response_body(b"garbage".to_vec()).is_err());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn signature_verifies_only_for_release_key() {
+        // a network with real proofs, since mock proofs prove nothing.
+        let network = Network::Main;
+        let secret: Digest = random();
+        let manifest = manifest("0.3.1", None);
+        let signature = LockScriptAndWitness::standard_hash_lock_from_preimage(secret)
+            .prove(
+                PublicInput::new(manifest.public_input()),
+                TritonVmJobQueue::get_instance(),
+                TritonVmProofJobOptions::default_with_network(network),
+            )
+            .await
+            .unwrap();
+        let signed = SignedReleaseManifest {
+            manifest,
+            signature,
+        };
+
+        assert!(signed.verify(network, secret.hash()).await);
+        assert!(!signed.verify(network, random()).await);
+        assert!(!signed.verify(Network::Testnet(0), secret.hash()).await);
+
+        let mut other_version = signed.clone();
+        other_version.manifest.version = "9.9.9".to_owned();
+        assert!(!other_version.verify(network, secret.hash()).await);
+    }
+}
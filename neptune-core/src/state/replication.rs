@@ -0,0 +1,420 @@
+//! Replication of a primary node to a standby node, for high availability.
+//!
+//! A standby is started with
+//! [`standby_of`](crate::application::config::cli_args::Args::standby_of) and
+//! continuously pulls validated blocks and expected UTXOs from its primary, so
+//! that it can take over if the primary fails. Both nodes hold the same
+//! wallet, so only one of them may spend at any time: a standby refuses to
+//! initiate transactions until it is promoted, and promotion first demotes the
+//! primary. Roles are persisted in the data directory, so that a demoted
+//! primary does not resume spending when restarted.
+//!
+//! Primary and standby authenticate each other by proving knowledge of a
+//! shared secret, read from
+//! [`replication_secret_file`](crate::application::config::cli_args::Args::replication_secret_file).
+//! Every message after the handshake is encrypted and authenticated with
+//! session keys derived from the secret and both challenges, see
+//! [`ReplicationSession`].
+
+use std::net::SocketAddr;
+use std::path::Path;
+
+use aead::Aead;
+use aead::Key;
+use aead::KeyInit;
+use aes_gcm::Aes256Gcm;
+use aes_gcm::Nonce;
+use anyhow::anyhow;
+use anyhow::ensure;
+use anyhow::Context;
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Digest;
+use tasm_lib::prelude::Tip5;
+use tasm_lib::triton_vm::prelude::BFieldCodec;
+use tasm_lib::triton_vm::prelude::BFieldElement;
+
+use crate::application::config::cli_args;
+use crate::application::config::network::Network;
+use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::protocol::consensus::block::Block;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+use crate::state::wallet::address::symmetric_key::SymmetricKey;
+use crate::state::wallet::expected_utxo::ExpectedUtxo;
+
+/// Maximum number of blocks sent in response to one [`SyncRequest`].
+///
+/// [`SyncRequest`]: ReplicationMessage::SyncRequest
+pub(crate) const MAX_REPLICATED_BLOCKS_PER_BATCH: usize = 10;
+
+/// Whether a node may spend from its wallet.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display, strum::EnumString,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ReplicationRole {
+    Primary,
+    Standby,
+}
+
+impl ReplicationRole {
+    /// Read the role persisted in the data directory, if any.
+    pub(crate) async fn read_from_file(path: &Path) -> anyhow::Result<Option<Self>> {
+        match tokio::fs::read_to_string(path).await {
+            Ok(contents) => contents
+                .trim()
+                .parse()
+                .map(Some)
+                .with_context(|| format!("Invalid replication role in {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("Could not read {}", path.display())),
+        }
+    }
+
+    pub(crate) async fn write_to_file(self, path: &Path) -> anyhow::Result<()> {
+        tokio::fs::write(path, self.to_string())
+            .await
+            .with_context(|| format!("Could not write {}", path.display()))
+    }
+}
+
+/// The replication state of this node, as reported over RPC.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplicationStatus {
+    pub role: ReplicationRole,
+
+    /// The primary this node replicates from, if it is a standby.
+    pub primary: Option<SocketAddr>,
+
+    /// Height of the last block received from the primary.
+    pub replicated_height: Option<BlockHeight>,
+    pub last_replicated: Option<Timestamp>,
+    pub last_error: Option<String>,
+}
+
+impl ReplicationStatus {
+    pub(crate) fn new(cli: &cli_args::Args) -> Self {
+        let role = if cli.standby_of.is_some() {
+            ReplicationRole::Standby
+        } else {
+            ReplicationRole::Primary
+        };
+
+        Self {
+            role,
+            primary: cli.standby_of,
+            replicated_height: None,
+            last_replicated: None,
+            last_error: None,
+        }
+    }
+
+    /// Whether this node may initiate transactions.
+    pub fn is_spend_enabled(&self) -> bool {
+        self.role == ReplicationRole::Primary
+    }
+
+    pub(crate) fn record_sync(&mut self, result: anyhow::Result<Option<BlockHeight>>) {
+        match result {
+            Ok(height) => {
+                self.replicated_height = height.or(self.replicated_height);
+                self.last_replicated = Some(Timestamp::now());
+                self.last_error = None;
+            }
+            Err(e) => self.last_error = Some(format!("{e:#}")),
+        }
+    }
+}
+
+/// The secret shared by a primary and its standbys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ReplicationSecret(Digest);
+
+impl ReplicationSecret {
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Self {
+        let elements = bytes
+            .iter()
+            .map(|&byte| BFieldElement::new(byte.into()))
+            .collect::<Vec<_>>();
+        Self(Tip5::hash_varlen(&elements))
+    }
+
+    /// Read the secret from a file. Surrounding whitespace is ignored.
+    pub(crate) async fn read_from_file(path: &Path) -> anyhow::Result<Self> {
+        let contents = tokio::fs::read(path)
+            .await
+            .with_context(|| format!("Could not read replication secret {}", path.display()))?;
+        let secret = contents.trim_ascii();
+        ensure!(
+            secret.len() >= 16,
+            "Replication secret in {} must be at least 16 bytes",
+            path.display()
+        );
+
+        Ok(Self::from_bytes(secret))
+    }
+
+    /// Prove knowledge of the secret to the party that sent `challenge`, in
+    /// the given role. The role is included so that a response cannot be
+    /// reflected back to its sender.
+    pub(crate) fn response(&self, role: ReplicationRole, challenge: Digest) -> Digest {
+        let role = BFieldElement::new(match role {
+            ReplicationRole::Primary => 0,
+            ReplicationRole::Standby => 1,
+        });
+        Tip5::hash_varlen(&[self.0.encode(), vec![role], challenge.encode()].concat())
+    }
+
+    /// The key encrypting the messages sent by `sender` in the session
+    /// established with the given challenges.
+    fn session_key(
+        &self,
+        sender: ReplicationRole,
+        standby_challenge: Digest,
+        primary_challenge: Digest,
+    ) -> Key<Aes256Gcm> {
+        let domain = BFieldElement::new(match sender {
+            ReplicationRole::Primary => 2,
+            ReplicationRole::Standby => 3,
+        });
+        let seed = Tip5::hash_varlen(
+            &[
+                self.0.encode(),
+                vec![domain],
+                standby_challenge.encode(),
+                primary_challenge.encode(),
+            ]
+            .concat(),
+        );
+        SymmetricKey::from_seed(seed).secret_key()
+    }
+
+    /// Start the encrypted session that follows a successful handshake, as
+    /// the party in role `own_role`.
+    pub(crate) fn session(
+        &self,
+        own_role: ReplicationRole,
+        standby_challenge: Digest,
+        primary_challenge: Digest,
+    ) -> ReplicationSession {
+        let other_role = match own_role {
+            ReplicationRole::Primary => ReplicationRole::Standby,
+            ReplicationRole::Standby => ReplicationRole::Primary,
+        };
+        ReplicationSession {
+            send_key: self.session_key(own_role, standby_challenge, primary_challenge),
+            receive_key: self.session_key(other_role, standby_challenge, primary_challenge),
+            num_sent: 0,
+            num_received: 0,
+        }
+    }
+}
+
+/// Encrypts and authenticates the frames of a replication connection.
+///
+/// Each direction has its own key. Frames are numbered, and the number is
+/// the nonce, so a frame that was altered, replayed, reordered, or reflected
+/// back to its sender fails to decrypt.
+#[derive(Clone)]
+pub(crate) struct ReplicationSession {
+    send_key: Key<Aes256Gcm>,
+    receive_key: Key<Aes256Gcm>,
+    num_sent: u64,
+    num_received: u64,
+}
+
+impl ReplicationSession {
+    fn nonce(frame_number: u64) -> [u8; 12] {
+        let mut nonce = [0u8; 12];
+        nonce[4..].copy_from_slice(&frame_number.to_be_bytes());
+        nonce
+    }
+
+    /// Encrypt the next frame to send.
+    pub(crate) fn seal(&mut self, plaintext: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let nonce = Self::nonce(self.num_sent);
+        let ciphertext = Aes256Gcm::new(&self.send_key)
+            .encrypt(Nonce::from_slice(&nonce), plaintext)
+            .map_err(|_| anyhow!("could not encrypt replication frame"))?;
+        self.num_sent += 1;
+
+        Ok(ciphertext)
+    }
+
+    /// Decrypt the next frame received. Fails if the frame was not the next
+    /// one sent by the other party.
+    pub(crate) fn open(&mut self, ciphertext: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let nonce = Self::nonce(self.num_received);
+        let plaintext = Aes256Gcm::new(&self.receive_key)
+            .decrypt(Nonce::from_slice(&nonce), ciphertext)
+            .map_err(|_| anyhow!("replication frame failed authentication"))?;
+        self.num_received += 1;
+
+        Ok(plaintext)
+    }
+}
+
+/// Messages exchanged between a standby and its primary.
+///
+/// The standby opens the connection with [`Hello`](Self::Hello), the primary
+/// answers with [`Challenge`](Self::Challenge), and the standby completes the
+/// handshake with [`Authenticate`](Self::Authenticate). After that, the
+/// standby sends requests, each of which the primary answers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum ReplicationMessage {
+    Hello {
+        network: Network,
+        challenge: Digest,
+    },
+    Challenge {
+        challenge: Digest,
+        response: Digest,
+    },
+    Authenticate {
+        response: Digest,
+    },
+
+    /// Request the canonical blocks following the most recent of
+    /// `known_blocks` that is canonical, and the expected UTXOs if the
+    /// standby does not hold as many as the primary.
+    SyncRequest {
+        /// The standby's tip and its most recent ancestors, newest first.
+        known_blocks: Vec<Digest>,
+        num_expected_utxos: u64,
+    },
+    SyncResponse {
+        blocks: Vec<Block>,
+        expected_utxos: Option<Vec<ExpectedUtxo>>,
+    },
+
+    /// Ask the primary to stop spending, before promoting the standby.
+    DemoteRequest,
+    Demoted,
+
+    Refused(String),
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use macro_rules_attr::apply;
+    use rand::random;
+
+    use super::*;
+    use crate::tests::shared::files::unit_test_data_directory;
+    use crate::tests::shared_tokio_runtime;
+
+    #[test]
+    fn responses_depend_on_secret_role_and_challenge() {
+        let secret = ReplicationSecret::from_bytes(b"correct horse battery staple");
+        let other_secret = ReplicationSecret::from_bytes(b"incorrect horse battery staple");
+        let challenge: Digest = random();
+
+        let response = secret.response(ReplicationRole::Standby, challenge);
+        assert_eq!(
+            response,
+            secret.response(ReplicationRole::Standby, challenge)
+        );
+        assert_ne!(
+            response,
+            secret.response(ReplicationRole::Primary, challenge)
+        );
+        assert_ne!(
+            response,
+            secret.response(ReplicationRole::Standby, random())
+        );
+        assert_ne!(
+            response,
+            other_secret.response(ReplicationRole::Standby, challenge)
+        );
+    }
+
+    #[test]
+    fn session_frames_are_authenticated_and_ordered() {
+        let secret = ReplicationSecret::from_bytes(b"correct horse battery staple");
+        let (standby_challenge, primary_challenge) = (random(), random());
+        let mut primary = secret.session(
+            ReplicationRole::Primary,
+            standby_challenge,
+            primary_challenge,
+        );
+        let mut standby = secret.session(
+            ReplicationRole::Standby,
+            standby_challenge,
+            primary_challenge,
+        );
+
+        let first = standby.seal(b"demote").unwrap();
+        let second = standby.seal(b"sync").unwrap();
+        assert_ne!(b"demote".to_vec(), first);
+
+        // Reordered, reflected, and altered frames are rejected.
+        assert!(primary.clone().open(&second).is_err());
+        assert!(standby.clone().open(&first).is_err());
+        let mut altered = first.clone();
+        altered[0] ^= 1;
+        assert!(primary.clone().open(&altered).is_err());
+
+        assert_eq!(b"demote".to_vec(), primary.open(&first).unwrap());
+        assert!(primary.clone().open(&first).is_err(), "replayed");
+        assert_eq!(b"sync".to_vec(), primary.open(&second).unwrap());
+
+        let other_secret = ReplicationSecret::from_bytes(b"incorrect horse battery staple");
+        let mut eavesdropper = other_secret.session(
+            ReplicationRole::Primary,
+            standby_challenge,
+            primary_challenge,
+        );
+        assert!(eavesdropper.open(&first).is_err());
+    }
+
+    #[test]
+    fn only_primaries_are_spend_enabled() {
+        let primary = ReplicationStatus::new(&cli_args::Args::default());
+        assert!(primary.is_spend_enabled());
+
+        let standby = ReplicationStatus::new(&cli_args::Args {
+            standby_of: Some("127.0.0.1:9800".parse().unwrap()),
+            ..Default::default()
+        });
+        assert_eq!(ReplicationRole::Standby, standby.role);
+        assert!(!standby.is_spend_enabled());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn roles_are_persisted() {
+        let data_dir = unit_test_data_directory(Network::Main).unwrap();
+        tokio::fs::create_dir_all(data_dir.root_dir_path())
+            .await
+            .unwrap();
+        let path = data_dir.replication_role_file_path();
+
+        assert_eq!(None, ReplicationRole::read_from_file(&path).await.unwrap());
+        ReplicationRole::Standby.write_to_file(&path).await.unwrap();
+        assert_eq!(
+            Some(ReplicationRole::Standby),
+            ReplicationRole::read_from_file(&path).await.unwrap()
+        );
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn short_secrets_are_rejected() {
+        let data_dir = unit_test_data_directory(Network::Main).unwrap();
+        tokio::fs::create_dir_all(data_dir.root_dir_path())
+            .await
+            .unwrap();
+        let path = data_dir.root_dir_path().join("replication_secret");
+
+        tokio::fs::write(&path, "too short\n").await.unwrap();
+        assert!(ReplicationSecret::read_from_file(&path).await.is_err());
+
+        tokio::fs::write(&path, "long enough to be a secret\n")
+            .await
+            .unwrap();
+        assert_eq!(
+            ReplicationSecret::from_bytes(b"long enough to be a secret"),
+            ReplicationSecret::read_from_file(&path).await.unwrap()
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! Progress of the stages of catching up with the network.
+//!
+//! A node that is behind goes through several stages before it is fully
+//! usable: it learns of the chain to sync towards, downloads and applies the
+//! blocks of that chain, updates the wallet to the new tip, and brings the
+//! transactions in its mempool up to date. A [`SyncStatus`] reports the
+//! progress of each of these stages, and the peers serving them, where a
+//! single "syncing" flag only tells whether blocks are being downloaded.
+
+use std::fmt::Display;
+use std::net::SocketAddr;
+
+use itertools::Itertools;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+
+/// A stage of catching up with the network, in the order in which they
+/// complete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display)]
+pub enum SyncStage {
+    /// Learning the tip of the chain to sync towards. Blocks are not
+    /// announced header by header: a peer's block MMR accumulator commits to
+    /// all of them, so this stage completes once the node has picked the tip
+    /// it syncs towards.
+    Headers,
+
+    /// Downloading and applying the blocks up to that tip.
+    Blocks,
+
+    /// Updating the wallet to the tip.
+    Wallet,
+
+    /// Updating the mempool's transactions to the tip. Only transactions that
+    /// the node updates itself are counted.
+    Mempool,
+}
+
+/// The progress of one [`SyncStage`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncStageProgress {
+    pub stage: SyncStage,
+
+    /// Work done so far: a block height for the headers, blocks, and wallet
+    /// stages, and a number of transactions for the mempool stage.
+    pub current: u64,
+
+    /// The work to be done, in the same unit as `current`. `None` if not yet
+    /// known, eg because no peer is connected.
+    pub target: Option<u64>,
+
+    /// When the stage is expected to complete, if it is in progress and its
+    /// rate is known.
+    pub estimated_completion: Option<Timestamp>,
+
+    /// The peers currently serving this stage. Empty for stages that are
+    /// local to the node.
+    pub peers: Vec<SocketAddr>,
+}
+
+impl SyncStageProgress {
+    pub(crate) fn new(stage: SyncStage, current: u64, target: Option<u64>) -> Self {
+        Self {
+            stage,
+            current,
+            target,
+            estimated_completion: None,
+            peers: vec![],
+        }
+    }
+
+    pub(crate) fn with_estimated_completion(mut self, estimate: Option<Timestamp>) -> Self {
+        self.estimated_completion = estimate.filter(|_| !self.is_complete());
+        self
+    }
+
+    pub(crate) fn with_peers(mut self, peers: impl IntoIterator<Item = SocketAddr>) -> Self {
+        self.peers = peers.into_iter().collect();
+        self
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.target.is_some_and(|target| self.current >= target)
+    }
+
+    /// The share of the work that is done, in percent. `None` if the target
+    /// is not known.
+    pub fn percent(&self) -> Option<f64> {
+        self.target.map(|target| {
+            if target == 0 || self.current >= target {
+                100.0
+            } else {
+                self.current as f64 / target as f64 * 100.0
+            }
+        })
+    }
+}
+
+/// The progress of all [stages](SyncStage) of catching up with the network.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncStatus {
+    pub headers: SyncStageProgress,
+    pub blocks: SyncStageProgress,
+    pub wallet: SyncStageProgress,
+    pub mempool: SyncStageProgress,
+}
+
+impl SyncStatus {
+    /// All stages, in order.
+    pub fn stages(&self) -> [&SyncStageProgress; 4] {
+        [&self.headers, &self.blocks, &self.wallet, &self.mempool]
+    }
+
+    /// The first stage that is not complete, or `None` if the node is in
+    /// sync.
+    pub fn current_stage(&self) -> Option<SyncStage> {
+        self.stages()
+            .into_iter()
+            .find(|progress| !progress.is_complete())
+            .map(|progress| progress.stage)
+    }
+
+    pub fn is_synced(&self) -> bool {
+        self.current_stage().is_none()
+    }
+
+    /// When all stages are expected to complete, if known.
+    pub fn estimated_completion(&self) -> Option<Timestamp> {
+        self.stages()
+            .into_iter()
+            .filter_map(|progress| progress.estimated_completion)
+            .max()
+    }
+}
+
+impl Display for SyncStageProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.target, self.percent()) {
+            (Some(target), Some(percent)) => write!(
+                f,
+                "{}: {}/{target} ({percent:.1}%)",
+                self.stage, self.current
+            )?,
+            _ => write!(f, "{}: unknown target", self.stage)?,
+        }
+        if let Some(estimated_completion) = self.estimated_completion {
+            write!(f, ", done by {}", estimated_completion.standard_format())?;
+        }
+        if !self.peers.is_empty() {
+            write!(f, ", served by {}", self.peers.iter().join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for SyncStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.current_stage() {
+            Some(stage) => writeln!(f, "syncing: {stage}")?,
+            None => writeln!(f, "synced")?,
+        }
+        for progress in self.stages() {
+            writeln!(f, "  {progress}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+    use crate::tests::shared::globalstate::get_dummy_socket_address;
+
+    #[test]
+    fn unknown_targets_are_incomplete() {
+        let progress = SyncStageProgress::new(SyncStage::Headers, 0, None);
+        assert!(!progress.is_complete());
+        assert_eq!(None, progress.percent());
+
+        let progress = SyncStageProgress::new(SyncStage::Mempool, 0, Some(0));
+        assert!(progress.is_complete());
+        assert_eq!(Some(100.0), progress.percent());
+    }
+
+    #[test]
+    fn current_stage_is_first_incomplete_stage() {
+        let peer = get_dummy_socket_address(0);
+        let estimate = Timestamp::now() + Timestamp::minutes(5);
+        let mut status = SyncStatus {
+            headers: SyncStageProgress::new(SyncStage::Headers, 1000, Some(1000))
+                .with_peers([peer]),
+            blocks: SyncStageProgress::new(SyncStage::Blocks, 250, Some(1000))
+                .with_estimated_completion(Some(estimate))
+                .with_peers([peer]),
+            wallet: SyncStageProgress::new(SyncStage::Wallet, 250, Some(250)),
+            mempool: SyncStageProgress::new(SyncStage::Mempool, 3, Some(4)),
+        };
+
+        assert_eq!(Some(SyncStage::Blocks), status.current_stage());
+        assert_eq!(Some(25.0), status.blocks.percent());
+        assert_eq!(Some(75.0), status.mempool.percent());
+        assert_eq!(Some(estimate), status.estimated_completion());
+        assert!(!status.is_synced());
+
+        status.blocks = SyncStageProgress::new(SyncStage::Blocks, 1000, Some(1000))
+            .with_estimated_completion(Some(estimate));
+        assert_eq!(None, status.blocks.estimated_completion);
+        assert_eq!(Some(SyncStage::Mempool), status.current_stage());
+
+        status.mempool.current = 4;
+        assert!(status.is_synced());
+    }
+}
@@ -1,3 +1,4 @@
+pub(crate) mod proving_cancellation;
 pub(crate) mod transaction_details;
 pub(crate) mod transaction_kernel_id;
 pub(crate) mod tx_creation_artifacts;
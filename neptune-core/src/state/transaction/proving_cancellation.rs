@@ -0,0 +1,137 @@
+//! Cancellation of the proving jobs of own transactions.
+//!
+//! Own transactions are initiated with a primitive witness, and proven by the
+//! proof upgrader in the background. While it proves, the upgrader registers
+//! the transaction here, along with a channel that cancels the proving job
+//! when signaled. See
+//! [`TransactionInitiator::cancel`](crate::api::tx_initiation::initiator::TransactionInitiator::cancel).
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+use crate::state::transaction::transaction_kernel_id::TransactionKernelId;
+
+/// Number of cancellations remembered for transactions whose proving job was
+/// not registered yet.
+const MAX_NUM_PENDING_CANCELLATIONS: usize = 1_000;
+
+/// The own transactions being proven, and the channels that cancel their
+/// proving jobs.
+///
+/// A transaction may be cancelled after the upgrader selected it for proving,
+/// but before the job is registered. Such cancellations are remembered, so
+/// that the job is cancelled once registered, and its result discarded.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ProvingCancellations {
+    jobs: HashMap<TransactionKernelId, Arc<watch::Sender<()>>>,
+
+    /// Cancelled transactions whose job has not finished, oldest first.
+    cancelled: VecDeque<TransactionKernelId>,
+}
+
+impl ProvingCancellations {
+    /// Register a proving job for the given transactions. Returns the
+    /// receiver to pass to the job, see
+    /// [`TritonVmProofJobOptions::cancel_job_rx`](crate::protocol::proof_abstractions::tasm::program::TritonVmProofJobOptions::cancel_job_rx).
+    ///
+    /// If any of the transactions was cancelled already, the job is cancelled
+    /// right away.
+    pub(crate) fn register(&mut self, txids: &[TransactionKernelId]) -> watch::Receiver<()> {
+        let (cancel_tx, cancel_rx) = watch::channel(());
+        if txids.iter().any(|txid| self.cancelled.contains(txid)) {
+            // The receiver is alive, so sending cannot fail.
+            let _ = cancel_tx.send(());
+            return cancel_rx;
+        }
+
+        let cancel_tx = Arc::new(cancel_tx);
+        for txid in txids {
+            self.jobs.insert(*txid, cancel_tx.clone());
+        }
+
+        cancel_rx
+    }
+
+    /// Deregister the proving job for the given transactions, once it has
+    /// ended. Returns true iff the job was cancelled, in which case its
+    /// result must be discarded.
+    pub(crate) fn finish(&mut self, txids: &[TransactionKernelId]) -> bool {
+        for txid in txids {
+            self.jobs.remove(txid);
+        }
+
+        let num_cancelled = self.cancelled.len();
+        self.cancelled.retain(|txid| !txids.contains(txid));
+        self.cancelled.len() != num_cancelled
+    }
+
+    /// Cancel the proving job of the transaction, including one that is not
+    /// registered yet. Returns true iff a job was being proven.
+    pub(crate) fn cancel(&mut self, txid: TransactionKernelId) -> bool {
+        if !self.cancelled.contains(&txid) {
+            if self.cancelled.len() >= MAX_NUM_PENDING_CANCELLATIONS {
+                self.cancelled.pop_front();
+            }
+            self.cancelled.push_back(txid);
+        }
+
+        let Some(cancel_tx) = self.jobs.remove(&txid) else {
+            return false;
+        };
+
+        // The job may have ended already, in which case there is no receiver.
+        let _ = cancel_tx.send(());
+        true
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use rand::random;
+
+    use super::*;
+
+    #[test]
+    fn cancelled_jobs_are_signaled_and_reported_when_finished() {
+        let mut cancellations = ProvingCancellations::default();
+        let cancelled: TransactionKernelId = random();
+        let completed: TransactionKernelId = random();
+
+        let cancel_rx = cancellations.register(&[cancelled]);
+        let _other_rx = cancellations.register(&[completed]);
+        assert!(!cancel_rx.has_changed().unwrap());
+
+        assert!(cancellations.cancel(cancelled));
+        assert!(
+            !matches!(cancel_rx.has_changed(), Ok(false)),
+            "job must observe cancellation"
+        );
+        assert!(!cancellations.cancel(cancelled), "can only cancel once");
+
+        assert!(cancellations.finish(&[cancelled]));
+        assert!(!cancellations.finish(&[completed]));
+    }
+
+    #[test]
+    fn jobs_registered_after_cancellation_are_cancelled() {
+        let mut cancellations = ProvingCancellations::default();
+        let txid: TransactionKernelId = random();
+
+        assert!(!cancellations.cancel(txid), "no job was being proven");
+
+        let cancel_rx = cancellations.register(&[txid]);
+        assert!(
+            matches!(cancel_rx.has_changed(), Ok(true)),
+            "job must observe cancellation"
+        );
+        assert!(cancellations.finish(&[txid]));
+
+        let next_rx = cancellations.register(&[txid]);
+        assert!(!next_rx.has_changed().unwrap());
+        assert!(!cancellations.finish(&[txid]));
+    }
+}
@@ -8,12 +8,18 @@ mod addressable_key;
 mod common;
 pub mod encrypted_utxo_notification;
 pub mod generation_address;
+mod payment_request;
 mod receiving_address;
 pub mod symmetric_key;
 
 pub use addressable_key::KeyType;
 pub use addressable_key::SpendingKey;
+pub(crate) use common::receiver_identifier_from_announcement;
+pub use payment_request::PaymentRequest;
+pub use payment_request::PaymentRequestData;
+pub use payment_request::PAYMENT_URI_SCHEME;
 pub use receiving_address::ReceivingAddress;
+pub use symmetric_key::SymmetricAddressData;
 
 #[cfg(test)]
 #[cfg_attr(coverage_nightly, coverage(off))]
@@ -0,0 +1,258 @@
+use anyhow::bail;
+use anyhow::ensure;
+use anyhow::Context;
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::ReceivingAddress;
+use crate::api::amount::Denomination;
+use crate::application::config::network::Network;
+use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
+
+/// URI scheme of payment requests, as in `neptune:<address>?amount=<amount>`.
+pub const PAYMENT_URI_SCHEME: &str = "neptune";
+
+const AMOUNT_PARAM: &str = "amount";
+
+/// a request for payment to an address, optionally of a specific amount.
+///
+/// A payment request is shared with the payer as a URI of the form
+///
+/// ```text
+/// neptune:<bech32m address>[?amount=<decimal amount of coins>]
+/// ```
+///
+/// Only [ReceivingAddress::Generation] addresses can be used, since the
+/// bech32m encoding of symmetric keys reveals the secret key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentRequest {
+    pub address: ReceivingAddress,
+    pub amount: Option<NativeCurrencyAmount>,
+}
+
+impl PaymentRequest {
+    pub fn new(address: ReceivingAddress, amount: Option<NativeCurrencyAmount>) -> Self {
+        Self { address, amount }
+    }
+
+    /// encodes this payment request as a URI.
+    pub fn to_uri(&self, network: Network) -> Result<String> {
+        let address = self.bech32m(network)?;
+        Ok(format!("{PAYMENT_URI_SCHEME}:{address}{}", self.query()))
+    }
+
+    /// encodes this payment request for rendering as a QR code.
+    ///
+    /// Generation addresses are too long to fit in a QR code in byte mode. The
+    /// scheme and address are therefore upper-cased, so that QR encoders can
+    /// use the denser alphanumeric mode for them. If an amount is requested,
+    /// the query part is appended unchanged and the encoder must support
+    /// mixed-mode segments, as most do.
+    ///
+    /// The payload is a valid payment URI, accepted by [Self::from_uri].
+    pub fn to_qr_payload(&self, network: Network) -> Result<String> {
+        let address = self.bech32m(network)?;
+        Ok(format!(
+            "{}:{}{}",
+            PAYMENT_URI_SCHEME.to_uppercase(),
+            address.to_uppercase(),
+            self.query()
+        ))
+    }
+
+    /// parses a payment request from its URI encoding. The scheme and address
+    /// are case-insensitive.
+    pub fn from_uri(uri: &str, network: Network) -> Result<Self> {
+        let Some((scheme, rest)) = uri.split_once(':') else {
+            bail!("payment URI must start with '{PAYMENT_URI_SCHEME}:'");
+        };
+        ensure!(
+            scheme.eq_ignore_ascii_case(PAYMENT_URI_SCHEME),
+            "unsupported payment URI scheme '{scheme}'"
+        );
+
+        let (address, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let address = ReceivingAddress::from_bech32m(&address.to_lowercase(), network)
+            .context("invalid address in payment URI")?;
+
+        let mut amount = None;
+        for param in query.split('&').filter(|p| !p.is_empty()) {
+            let Some((key, value)) = param.split_once('=') else {
+                bail!("malformed payment URI parameter '{param}'");
+            };
+            match key {
+                AMOUNT_PARAM => {
+                    ensure!(amount.is_none(), "duplicate amount in payment URI");
+                    let value = Denomination::Coin
+                        .parse(value)
+                        .context("invalid amount in payment URI")?;
+                    ensure!(
+                        value.is_positive(),
+                        "amount in payment URI must be positive"
+                    );
+                    amount = Some(value);
+                }
+                // unknown parameters are ignored, for forward compatibility.
+                _ => {}
+            }
+        }
+
+        Ok(Self { address, amount })
+    }
+
+    fn bech32m(&self, network: Network) -> Result<String> {
+        ensure!(
+            matches!(self.address, ReceivingAddress::Generation(_)),
+            "payment requests require a generation address"
+        );
+        self.address.to_bech32m(network)
+    }
+
+    fn query(&self) -> String {
+        match self.amount {
+            Some(amount) => format!("?{AMOUNT_PARAM}={}", Denomination::Coin.format(amount)),
+            None => String::new(),
+        }
+    }
+}
+
+/// Data for displaying a payment request in a user interface.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaymentRequestData {
+    /// bech32m encoding of the receiving address
+    pub address: String,
+
+    /// the requested amount, if any
+    pub amount: Option<NativeCurrencyAmount>,
+
+    /// payment URI, see [PaymentRequest::to_uri]
+    pub uri: String,
+
+    /// payload to render as a QR code, see [PaymentRequest::to_qr_payload]
+    pub qr_payload: String,
+}
+
+impl PaymentRequestData {
+    /// fails if the address is not a generation address, or if the requested
+    /// amount is not positive.
+    pub fn new(request: &PaymentRequest, network: Network) -> Result<Self> {
+        ensure!(
+            request.amount.is_none_or(|amount| amount.is_positive()),
+            "requested amount must be positive"
+        );
+        Ok(Self {
+            address: request.bech32m(network)?,
+            amount: request.amount,
+            uri: request.to_uri(network)?,
+            qr_payload: request.to_qr_payload(network)?,
+        })
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use num_traits::Zero;
+    use rand::random;
+
+    use super::*;
+    use crate::state::wallet::address::generation_address::GenerationReceivingAddress;
+    use crate::state::wallet::address::symmetric_key::SymmetricKey;
+
+    fn generation_address() -> ReceivingAddress {
+        GenerationReceivingAddress::derive_from_seed(random()).into()
+    }
+
+    #[test]
+    fn uri_round_trips() {
+        let network = Network::Main;
+        for amount in [
+            None,
+            Some(NativeCurrencyAmount::coins(3)),
+            Some(NativeCurrencyAmount::coins_from_str("0.125").unwrap()),
+        ] {
+            let request = PaymentRequest::new(generation_address(), amount);
+
+            let uri = request.to_uri(network).unwrap();
+            assert!(uri.starts_with("neptune:nolgam1"));
+            assert_eq!(request, PaymentRequest::from_uri(&uri, network).unwrap());
+
+            let qr_payload = request.to_qr_payload(network).unwrap();
+            assert!(qr_payload.starts_with("NEPTUNE:NOLGAM1"));
+            assert_eq!(
+                request,
+                PaymentRequest::from_uri(&qr_payload, network).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn amount_is_displayed_without_trailing_zeros() {
+        let request = PaymentRequest::new(
+            generation_address(),
+            Some(NativeCurrencyAmount::coins_from_str("1.5").unwrap()),
+        );
+        assert!(request
+            .to_uri(Network::Main)
+            .unwrap()
+            .ends_with("?amount=1.5"));
+    }
+
+    #[test]
+    fn qr_payload_without_amount_is_alphanumeric() {
+        // the QR alphanumeric mode character set.
+        let alphanumeric =
+            |c: char| c.is_ascii_digit() || c.is_ascii_uppercase() || " $%*+-./:".contains(c);
+        let request = PaymentRequest::new(generation_address(), None);
+        let payload = request.to_qr_payload(Network::Main).unwrap();
+        assert!(payload.chars().all(alphanumeric));
+    }
+
+    #[test]
+    fn symmetric_addresses_are_rejected() {
+        let address: ReceivingAddress = SymmetricKey::from_seed(random()).into();
+        let request = PaymentRequest::new(address, None);
+        assert!(request.to_uri(Network::Main).is_err());
+        assert!(request.to_qr_payload(Network::Main).is_err());
+    }
+
+    #[test]
+    fn data_requires_positive_amount() {
+        let network = Network::Main;
+        for amount in [
+            NativeCurrencyAmount::zero(),
+            -NativeCurrencyAmount::coins(1),
+        ] {
+            let request = PaymentRequest::new(generation_address(), Some(amount));
+            assert!(PaymentRequestData::new(&request, network).is_err());
+        }
+
+        let request = PaymentRequest::new(generation_address(), None);
+        assert!(PaymentRequestData::new(&request, network).is_ok());
+    }
+
+    #[test]
+    fn invalid_uris_are_rejected() {
+        let network = Network::Main;
+        let uri = PaymentRequest::new(generation_address(), None)
+            .to_uri(network)
+            .unwrap();
+
+        assert!(PaymentRequest::from_uri(&uri.replace("neptune:", "bitcoin:"), network).is_err());
+        assert!(PaymentRequest::from_uri(&uri.replace("neptune:", ""), network).is_err());
+        assert!(PaymentRequest::from_uri(&format!("{uri}?amount=-1"), network).is_err());
+        assert!(PaymentRequest::from_uri(&format!("{uri}?amount=0"), network).is_err());
+        assert!(PaymentRequest::from_uri(&format!("{uri}?amount=x"), network).is_err());
+        assert!(PaymentRequest::from_uri(&format!("{uri}?amount=1&amount=2"), network).is_err());
+        assert!(PaymentRequest::from_uri(&uri, Network::Testnet(0)).is_err());
+
+        let with_unknown_param = format!("{uri}?label=coffee&amount=2");
+        assert_eq!(
+            Some(NativeCurrencyAmount::coins(2)),
+            PaymentRequest::from_uri(&with_unknown_param, network)
+                .unwrap()
+                .amount
+        );
+    }
+}
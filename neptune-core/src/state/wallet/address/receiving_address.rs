@@ -8,6 +8,7 @@ use serde::Deserialize;
 use serde::Serialize;
 use tasm_lib::triton_vm::prelude::Digest;
 
+use super::common;
 use super::generation_address;
 use super::symmetric_key;
 use crate::api::export::KeyType;
@@ -249,6 +250,26 @@ impl ReceivingAddress {
         // turn.
     }
 
+    /// returns true if `encoded` is a valid bech32m address, but for a network
+    /// other than `network`.
+    ///
+    /// useful to tell a user who pasted eg a testnet address into a mainnet
+    /// wallet what went wrong, rather than that the address is invalid.
+    pub fn is_bech32m_for_other_network(encoded: &str, network: Network) -> bool {
+        let hrp_char = common::network_hrp_char(network);
+
+        // all testnets share one prefix, so one of them represents all.
+        [
+            Network::Main,
+            Network::TestnetMock,
+            Network::RegTest,
+            Network::Testnet(0),
+        ]
+        .into_iter()
+        .filter(|other| common::network_hrp_char(*other) != hrp_char)
+        .any(|other| Self::from_bech32m(encoded, other).is_ok())
+    }
+
     /// returns human-readable-prefix (hrp) for a given network
     pub fn get_hrp(&self, network: Network) -> String {
         match self {
@@ -289,3 +289,40 @@ impl SymmetricKey {
         format!("nsymk{}", common::network_hrp_char(network))
     }
 }
+
+/// Data for displaying a symmetric-key address of the own wallet in a user
+/// interface.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymmetricAddressData {
+    /// the derivation index of the key
+    pub derivation_index: u64,
+
+    /// bech32m encoding of the key, see [SymmetricKey::to_bech32m]. Funds can
+    /// be sent to the address with it, but anyone knowing it can spend them, so
+    /// it must never be shared.
+    pub address: String,
+
+    /// bech32m encoding that is safe to display, see
+    /// [SymmetricKey::to_display_bech32m]. It cannot be sent to.
+    pub display_address: String,
+
+    /// whether this is the key handed out for receiving under the
+    /// [rotation policy](crate::state::wallet::symmetric_key_rotation::SymmetricKeyRotationPolicy)
+    pub is_active: bool,
+}
+
+impl SymmetricAddressData {
+    pub fn new(
+        derivation_index: u64,
+        key: &SymmetricKey,
+        is_active: bool,
+        network: Network,
+    ) -> Result<Self> {
+        Ok(Self {
+            derivation_index,
+            address: key.to_bech32m(network)?,
+            display_address: key.to_display_bech32m(network)?,
+            is_active,
+        })
+    }
+}
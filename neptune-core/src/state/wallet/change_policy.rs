@@ -23,6 +23,9 @@ pub enum ChangePolicy {
     /// recover change to the next unused key.
     ///
     /// (of specified key-type, via specified notification medium)
+    ///
+    /// If a symmetric-key rotation policy is configured, symmetric change keys
+    /// are reused until rotated.
     RecoverToNextUnusedKey {
         key_type: KeyType,
         medium: UtxoNotificationMedium,
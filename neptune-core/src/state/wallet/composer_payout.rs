@@ -0,0 +1,202 @@
+//! Off-chain composer payouts, and their export to the recipients.
+//!
+//! A composer that distributes the coinbase to foreign addresses, e.g. a
+//! mining pool paying its members, and notifies the recipients off-chain is
+//! the only party that knows the payouts' UTXOs and sender randomness.
+//! Without them, the recipients cannot claim the payouts. The composer
+//! therefore records the payouts of each block proposal in
+//! [`DataDirectory::composer_payouts_file_path()`] when composing it, before
+//! the proposal can become a block, and exports as [`ComposerPayout`]s the
+//! payouts contained in canonical blocks. Payouts of proposals that never
+//! became blocks are skipped. A recipient imports the [`UtxoTriple`] of a
+//! payout into its wallet, which verifies it against the addition records of
+//! the canonical chain.
+//!
+//! [`DataDirectory::composer_payouts_file_path()`]: crate::application::config::data_directory::DataDirectory::composer_payouts_file_path
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+
+use anyhow::Context;
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Digest;
+use tokio::io::AsyncWriteExt;
+
+use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::protocol::consensus::block::block_selector::BlockSelector;
+use crate::protocol::consensus::transaction::utxo_triple::UtxoTriple;
+use crate::state::GlobalState;
+use crate::util_types::mutator_set::addition_record::AdditionRecord;
+
+/// A coinbase output of a block composed by this node, paid to a foreign
+/// address with off-chain notification.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposerPayout {
+    /// Height of the block whose coinbase contains the payout.
+    pub block_height: BlockHeight,
+
+    /// Digest of the block whose coinbase contains the payout.
+    pub block_digest: Digest,
+
+    /// The UTXO, its sender randomness, and the recipient's receiver digest:
+    /// all the recipient needs to claim it.
+    pub utxo_triple: UtxoTriple,
+}
+
+/// An off-chain payout in a block proposal composed by this node, as recorded
+/// when the proposal is composed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ProposedComposerPayout {
+    /// Height of the proposed block.
+    pub(crate) block_height: BlockHeight,
+
+    /// The UTXO, its sender randomness, and the recipient's receiver digest.
+    pub(crate) utxo_triple: UtxoTriple,
+}
+
+impl ProposedComposerPayout {
+    /// Append the payouts to the file at `path`, and persist them before
+    /// returning.
+    pub(crate) async fn record_all(payouts: &[Self], path: &Path) -> Result<()> {
+        let mut lines = String::new();
+        for payout in payouts {
+            lines.push_str(&serde_json::to_string(payout)?);
+            lines.push('\n');
+        }
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+        file.write_all(lines.as_bytes()).await?;
+        file.sync_all().await?;
+
+        Ok(())
+    }
+
+    /// Read all payouts recorded in the file at `path`. A missing file
+    /// records no payouts.
+    pub(crate) async fn read_all(path: &Path) -> Result<Vec<Self>> {
+        let contents = match tokio::fs::read_to_string(path).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+
+        contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str(line).with_context(|| {
+                    format!("invalid payout in {} on line {}", path.display(), i + 1)
+                })
+            })
+            .collect()
+    }
+
+    /// The given payouts, as read with [`Self::read_all`], that are contained
+    /// in canonical blocks at or above `from_height`, each once.
+    ///
+    /// Payouts of proposals that never became blocks, or whose blocks a
+    /// reorganization orphaned, are skipped. Requires an archival node.
+    pub(crate) async fn mined(
+        gs: &GlobalState,
+        payouts: Vec<Self>,
+        from_height: BlockHeight,
+    ) -> Result<Vec<ComposerPayout>> {
+        anyhow::ensure!(
+            gs.chain.is_archival_node(),
+            "exporting composer payouts requires an archival node"
+        );
+
+        let mut canonical_blocks: HashMap<BlockHeight, Option<(Digest, HashSet<AdditionRecord>)>> =
+            HashMap::new();
+        let mut exported = HashSet::new();
+        let mut mined = vec![];
+        for payout in payouts {
+            if payout.block_height < from_height {
+                continue;
+            }
+
+            if !canonical_blocks.contains_key(&payout.block_height) {
+                let block = match BlockSelector::Height(payout.block_height)
+                    .as_digest(gs)
+                    .await
+                {
+                    Some(digest) => gs.chain.archival_state().get_block(digest).await?,
+                    None => None,
+                };
+                let outputs = block.map(|block| {
+                    let outputs = block.body().transaction_kernel.outputs.iter().copied();
+                    (block.hash(), outputs.collect())
+                });
+                canonical_blocks.insert(payout.block_height, outputs);
+            }
+
+            let Some((block_digest, outputs)) = &canonical_blocks[&payout.block_height] else {
+                continue;
+            };
+            let addition_record = payout.utxo_triple.addition_record();
+            if outputs.contains(&addition_record) && exported.insert(addition_record) {
+                mined.push(ComposerPayout {
+                    block_height: payout.block_height,
+                    block_digest: *block_digest,
+                    utxo_triple: payout.utxo_triple,
+                });
+            }
+        }
+
+        Ok(mined)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use macro_rules_attr::apply;
+    use rand::random;
+
+    use super::*;
+    use crate::application::config::data_directory::DataDirectory;
+    use crate::application::config::network::Network;
+    use crate::tests::shared::files::unit_test_data_directory;
+    use crate::tests::shared_tokio_runtime;
+
+    #[apply(shared_tokio_runtime)]
+    async fn payouts_are_appended_and_read_back() {
+        let data_dir = unit_test_data_directory(Network::Main).unwrap();
+        DataDirectory::create_dir_if_not_exists(&data_dir.wallet_directory_path())
+            .await
+            .unwrap();
+        let path = data_dir.composer_payouts_file_path();
+        assert!(ProposedComposerPayout::read_all(&path)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let payouts = (0..3u64)
+            .map(|height| ProposedComposerPayout {
+                block_height: height.into(),
+                utxo_triple: random(),
+            })
+            .collect::<Vec<_>>();
+        ProposedComposerPayout::record_all(&payouts[..2], &path)
+            .await
+            .unwrap();
+        ProposedComposerPayout::record_all(&payouts[2..], &path)
+            .await
+            .unwrap();
+
+        let read = ProposedComposerPayout::read_all(&path).await.unwrap();
+        assert_eq!(payouts.len(), read.len());
+        for (payout, read) in payouts.iter().zip(&read) {
+            assert_eq!(payout.block_height, read.block_height);
+            assert_eq!(payout.utxo_triple, read.utxo_triple);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Deterministic key and address derivation vectors.
+//!
+//! Third-party wallet implementations can compare their own derivations
+//! against [`DerivationVectors`] produced from the same seed, to verify that
+//! they derive the same keys and addresses as neptune-core.
+//!
+//! Vectors include secret key material. Only derive them from seeds that do
+//! not protect funds, such as [`WalletEntropy::devnet_wallet`].
+use anyhow::ensure;
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Digest;
+use tasm_lib::triton_vm::prelude::BFieldElement;
+
+use super::address::KeyType;
+use super::address::SpendingKey;
+use super::secret_key_material::SecretKeyMaterial;
+use super::wallet_entropy::WalletEntropy;
+use crate::application::config::network::Network;
+
+/// The largest number of indices for which vectors are derived in one go.
+pub const MAX_DERIVATION_VECTOR_INDICES: u64 = 256;
+
+/// The keys and receiving address derived at one index for one key type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DerivationVector {
+    pub key_type: KeyType,
+    pub index: u64,
+
+    /// Fingerprint of the key, announced alongside encrypted UTXO
+    /// notifications so the recipient can recognize them.
+    pub receiver_identifier: BFieldElement,
+
+    /// Secret from which the receiver digest is derived.
+    pub receiver_preimage: Digest,
+
+    /// Hash of the receiver preimage, committed to in the addition records
+    /// of UTXOs sent to the address.
+    pub receiver_digest: Digest,
+
+    /// Post-image of the unlock key, which the lock script checks.
+    pub spending_lock: Digest,
+
+    /// Hash of the lock script of UTXOs sent to the address.
+    pub lock_script_hash: Digest,
+
+    /// The receiving address, bech32m-encoded for the vectors' network.
+    pub address: String,
+}
+
+impl DerivationVector {
+    fn derive(key: SpendingKey, index: u64, network: Network) -> Result<Self> {
+        let address = key.to_address();
+
+        Ok(Self {
+            key_type: KeyType::from(&key),
+            index,
+            receiver_identifier: key.receiver_identifier(),
+            receiver_preimage: key.privacy_preimage(),
+            receiver_digest: address.privacy_digest(),
+            spending_lock: address.spending_lock(),
+            lock_script_hash: address.lock_script_hash(),
+            address: address.to_bech32m(network)?,
+        })
+    }
+}
+
+/// Derivation vectors for all key types at the first indices, from one seed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DerivationVectors {
+    pub network: Network,
+
+    /// The seed, as its BIP-39 mnemonic phrase.
+    pub phrase: Vec<String>,
+
+    /// Vectors ordered by key type, then by index.
+    pub vectors: Vec<DerivationVector>,
+}
+
+impl DerivationVectors {
+    /// Derive the vectors for indices `0..num_indices` of every key type.
+    ///
+    /// Fails if `num_indices` exceeds [`MAX_DERIVATION_VECTOR_INDICES`].
+    pub fn derive(entropy: &WalletEntropy, network: Network, num_indices: u64) -> Result<Self> {
+        ensure!(
+            num_indices <= MAX_DERIVATION_VECTOR_INDICES,
+            "cannot derive vectors for more than {MAX_DERIVATION_VECTOR_INDICES} indices, \
+             got {num_indices}"
+        );
+
+        let mut vectors = vec![];
+        for key_type in KeyType::all_types() {
+            for index in 0..num_indices {
+                let key = match key_type {
+                    KeyType::Generation => entropy.nth_generation_spending_key(index).into(),
+                    KeyType::Symmetric => entropy.nth_symmetric_key(index).into(),
+                };
+                vectors.push(DerivationVector::derive(key, index, network)?);
+            }
+        }
+
+        Ok(Self {
+            network,
+            phrase: SecretKeyMaterial::from(entropy.clone()).to_phrase(),
+            vectors,
+        })
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+    use crate::state::wallet::address::ReceivingAddress;
+
+    #[test]
+    fn vectors_are_deterministic_and_consistent() {
+        let entropy = WalletEntropy::devnet_wallet();
+        let network = Network::Main;
+        let derived = DerivationVectors::derive(&entropy, network, 3).unwrap();
+        assert_eq!(
+            derived,
+            DerivationVectors::derive(&entropy, network, 3).unwrap()
+        );
+        assert_eq!(
+            entropy,
+            WalletEntropy::from_phrase(&derived.phrase).unwrap()
+        );
+
+        let num_key_types = KeyType::all_types().len();
+        assert_eq!(3 * num_key_types, derived.vectors.len());
+        for vector in &derived.vectors {
+            assert_eq!(vector.receiver_preimage.hash(), vector.receiver_digest);
+
+            let address = ReceivingAddress::from_bech32m(&vector.address, network).unwrap();
+            assert_eq!(vector.receiver_identifier, address.receiver_identifier());
+            assert_eq!(vector.lock_script_hash, address.lock_script_hash());
+        }
+
+        let first_generation_address =
+            ReceivingAddress::from(entropy.nth_generation_spending_key(0).to_address());
+        assert_eq!(
+            first_generation_address.to_bech32m(network).unwrap(),
+            derived.vectors[0].address
+        );
+
+        assert!(
+            DerivationVectors::derive(&entropy, network, MAX_DERIVATION_VECTOR_INDICES + 1)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,339 @@
+//! Memos that senders attach to payments, carried in announcements.
+//!
+//! A memo announcement names the recipient by the receiver identifier of
+//! their address, and carries a payload in one of several formats:
+//!
+//! ```text
+//! [MEMO_FLAG, receiver_identifier, format, payload...]
+//! ```
+//!
+//! When a block pays the wallet, memos announced to the receiving key in the
+//! same block are decoded by the wallet's [MemoDecoderRegistry] and attached
+//! to the received UTXOs. Formats without a registered decoder, and payloads
+//! a decoder rejects, are kept as [Memo::Raw].
+//!
+//! Since blocks merge transactions, a memo cannot be traced to the
+//! transaction it came with. If one block pays the same key several times,
+//! all memos to that key are attached to each of the payments.
+//!
+//! Memos are unauthenticated. Receiver identifiers are public, so anyone can
+//! announce a memo to a key in a block that pays it, and it is attached like
+//! the sender's own. A memo must therefore not be trusted to tell what a
+//! payment is for. To bound what such memos cost, payloads longer than
+//! [MAX_MEMO_PAYLOAD_LEN] are ignored, and at most [MAX_NUM_MEMOS_PER_UTXO]
+//! memos are attached to a UTXO.
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::triton_vm::prelude::BFieldCodec;
+use tasm_lib::triton_vm::prelude::BFieldElement;
+
+use super::address::ReceivingAddress;
+use crate::protocol::consensus::transaction::announcement::Announcement;
+use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
+
+/// identifies an [Announcement] as a memo.
+pub const MEMO_FLAG: BFieldElement = BFieldElement::new(77);
+
+/// format of [Memo::AmountHint] payloads
+pub const AMOUNT_HINT_FORMAT: BFieldElement = BFieldElement::new(1);
+
+/// format of [Memo::InvoiceId] payloads
+pub const INVOICE_ID_FORMAT: BFieldElement = BFieldElement::new(2);
+
+/// longest invoice id, in bytes, that is encoded or decoded
+pub const MAX_INVOICE_ID_LEN: usize = 256;
+
+/// longest memo payload, in field elements. announcements with longer payloads
+/// are not recognized as memos.
+pub const MAX_MEMO_PAYLOAD_LEN: usize = MAX_INVOICE_ID_LEN;
+
+/// most memos attached to a single UTXO. further memos are dropped.
+pub const MAX_NUM_MEMOS_PER_UTXO: usize = 8;
+
+/// a decoded memo.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Memo {
+    /// the amount the sender intended to pay, eg to match an invoice
+    AmountHint(NativeCurrencyAmount),
+
+    /// an identifier of the invoice or order being paid
+    InvoiceId(String),
+
+    /// a memo of an unrecognized format, or with a malformed payload
+    Raw {
+        format: BFieldElement,
+        payload: Vec<BFieldElement>,
+    },
+}
+
+impl Memo {
+    /// the format identifier of this memo
+    pub fn format(&self) -> BFieldElement {
+        match self {
+            Self::AmountHint(_) => AMOUNT_HINT_FORMAT,
+            Self::InvoiceId(_) => INVOICE_ID_FORMAT,
+            Self::Raw { format, .. } => *format,
+        }
+    }
+
+    /// the payload of this memo, as carried in an announcement.
+    ///
+    /// Invoice ids longer than [MAX_INVOICE_ID_LEN] bytes are truncated.
+    pub fn payload(&self) -> Vec<BFieldElement> {
+        match self {
+            Self::AmountHint(amount) => amount.encode(),
+            Self::InvoiceId(id) => {
+                let mut end = id.len().min(MAX_INVOICE_ID_LEN);
+                while !id.is_char_boundary(end) {
+                    end -= 1;
+                }
+                id.as_bytes()[..end]
+                    .iter()
+                    .map(|byte| BFieldElement::new(u64::from(*byte)))
+                    .collect()
+            }
+            Self::Raw { payload, .. } => payload.clone(),
+        }
+    }
+
+    /// convert into an [Announcement] addressed to `recipient`, for inclusion
+    /// in a transaction that pays the recipient.
+    pub fn to_announcement(&self, recipient: &ReceivingAddress) -> Announcement {
+        MemoAnnouncement {
+            receiver_identifier: recipient.receiver_identifier(),
+            format: self.format(),
+            payload: self.payload(),
+        }
+        .to_announcement()
+    }
+}
+
+/// a memo as carried in an [Announcement], before decoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoAnnouncement {
+    pub receiver_identifier: BFieldElement,
+    pub format: BFieldElement,
+    pub payload: Vec<BFieldElement>,
+}
+
+impl MemoAnnouncement {
+    /// convert into an [Announcement].
+    pub fn to_announcement(&self) -> Announcement {
+        Announcement::new(
+            [
+                vec![MEMO_FLAG, self.receiver_identifier, self.format],
+                self.payload.clone(),
+            ]
+            .concat(),
+        )
+    }
+
+    /// interpret an [Announcement] as a memo, if it is one and its payload is
+    /// at most [MAX_MEMO_PAYLOAD_LEN] long.
+    pub fn try_from_announcement(announcement: &Announcement) -> Option<Self> {
+        match announcement.message.as_slice() {
+            [flag, receiver_identifier, format, payload @ ..]
+                if *flag == MEMO_FLAG && payload.len() <= MAX_MEMO_PAYLOAD_LEN =>
+            {
+                Some(Self {
+                    receiver_identifier: *receiver_identifier,
+                    format: *format,
+                    payload: payload.to_vec(),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// decodes the payloads of one memo format.
+pub trait MemoDecoder: Send + Sync {
+    /// the format this decoder handles
+    fn format(&self) -> BFieldElement;
+
+    /// decode a payload, or return `None` if it is malformed.
+    fn decode(&self, payload: &[BFieldElement]) -> Option<Memo>;
+}
+
+#[derive(Debug, Clone, Copy)]
+struct AmountHintDecoder;
+
+impl MemoDecoder for AmountHintDecoder {
+    fn format(&self) -> BFieldElement {
+        AMOUNT_HINT_FORMAT
+    }
+
+    fn decode(&self, payload: &[BFieldElement]) -> Option<Memo> {
+        let amount = *NativeCurrencyAmount::decode(payload).ok()?;
+        (!amount.is_negative()).then_some(Memo::AmountHint(amount))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct InvoiceIdDecoder;
+
+impl MemoDecoder for InvoiceIdDecoder {
+    fn format(&self) -> BFieldElement {
+        INVOICE_ID_FORMAT
+    }
+
+    fn decode(&self, payload: &[BFieldElement]) -> Option<Memo> {
+        if payload.len() > MAX_INVOICE_ID_LEN {
+            return None;
+        }
+        let bytes = payload
+            .iter()
+            .map(|element| u8::try_from(element.value()).ok())
+            .collect::<Option<Vec<_>>>()?;
+
+        String::from_utf8(bytes).ok().map(Memo::InvoiceId)
+    }
+}
+
+/// the memo formats the wallet recognizes.
+///
+/// The default registry decodes [Memo::AmountHint] and [Memo::InvoiceId].
+/// Further formats can be added with [Self::register()].
+#[derive(Clone)]
+pub struct MemoDecoderRegistry {
+    decoders: HashMap<BFieldElement, Arc<dyn MemoDecoder>>,
+}
+
+impl Default for MemoDecoderRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            decoders: HashMap::default(),
+        };
+        registry.register(Arc::new(AmountHintDecoder));
+        registry.register(Arc::new(InvoiceIdDecoder));
+
+        registry
+    }
+}
+
+impl Debug for MemoDecoderRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoDecoderRegistry")
+            .field("formats", &self.decoders.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl MemoDecoderRegistry {
+    /// register a decoder, replacing any decoder for the same format.
+    pub fn register(&mut self, decoder: Arc<dyn MemoDecoder>) {
+        self.decoders.insert(decoder.format(), decoder);
+    }
+
+    /// decode a memo, falling back to [Memo::Raw] if its format is not
+    /// registered or its payload is malformed.
+    pub fn decode(&self, memo: &MemoAnnouncement) -> Memo {
+        self.decoders
+            .get(&memo.format)
+            .and_then(|decoder| decoder.decode(&memo.payload))
+            .unwrap_or_else(|| Memo::Raw {
+                format: memo.format,
+                payload: memo.payload.clone(),
+            })
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use tasm_lib::prelude::Digest;
+
+    use super::*;
+    use crate::state::wallet::address::generation_address::GenerationReceivingAddress;
+
+    fn recipient() -> ReceivingAddress {
+        GenerationReceivingAddress::derive_from_seed(Digest::default()).into()
+    }
+
+    fn decode(memo: &Memo) -> Memo {
+        let announcement = memo.to_announcement(&recipient());
+        let memo_announcement = MemoAnnouncement::try_from_announcement(&announcement).unwrap();
+        assert_eq!(
+            recipient().receiver_identifier(),
+            memo_announcement.receiver_identifier
+        );
+
+        MemoDecoderRegistry::default().decode(&memo_announcement)
+    }
+
+    #[test]
+    fn recognized_memos_survive_announcement_roundtrip() {
+        let amount_hint = Memo::AmountHint(NativeCurrencyAmount::coins_from_str("2.5").unwrap());
+        assert_eq!(amount_hint, decode(&amount_hint));
+
+        let invoice_id = Memo::InvoiceId("INV-2026-0042 ✓".to_string());
+        assert_eq!(invoice_id, decode(&invoice_id));
+    }
+
+    #[test]
+    fn unrecognized_and_malformed_memos_are_kept_raw() {
+        let unknown = Memo::Raw {
+            format: BFieldElement::new(1000),
+            payload: vec![BFieldElement::new(1), BFieldElement::new(2)],
+        };
+        assert_eq!(unknown, decode(&unknown));
+
+        let malformed_invoice_id = Memo::Raw {
+            format: INVOICE_ID_FORMAT,
+            payload: vec![BFieldElement::new(1 << 20)],
+        };
+        assert_eq!(malformed_invoice_id, decode(&malformed_invoice_id));
+
+        assert!(
+            MemoAnnouncement::try_from_announcement(&Announcement::new(vec![
+                MEMO_FLAG,
+                BFieldElement::new(3)
+            ]))
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn oversized_payloads_are_not_memos() {
+        let memo = |payload_len| Memo::Raw {
+            format: BFieldElement::new(1000),
+            payload: vec![BFieldElement::new(1); payload_len],
+        };
+
+        let longest = memo(MAX_MEMO_PAYLOAD_LEN).to_announcement(&recipient());
+        assert!(MemoAnnouncement::try_from_announcement(&longest).is_some());
+
+        let oversized = memo(MAX_MEMO_PAYLOAD_LEN + 1).to_announcement(&recipient());
+        assert!(MemoAnnouncement::try_from_announcement(&oversized).is_none());
+    }
+
+    #[test]
+    fn registered_decoders_replace_raw_fallback() {
+        struct LengthDecoder;
+        impl MemoDecoder for LengthDecoder {
+            fn format(&self) -> BFieldElement {
+                BFieldElement::new(1000)
+            }
+
+            fn decode(&self, payload: &[BFieldElement]) -> Option<Memo> {
+                Some(Memo::InvoiceId(format!("#{}", payload.len())))
+            }
+        }
+
+        let memo_announcement = MemoAnnouncement {
+            receiver_identifier: BFieldElement::new(0),
+            format: BFieldElement::new(1000),
+            payload: vec![BFieldElement::new(7); 3],
+        };
+        let mut registry = MemoDecoderRegistry::default();
+        registry.register(Arc::new(LengthDecoder));
+        assert_eq!(
+            Memo::InvoiceId("#3".to_string()),
+            registry.decode(&memo_announcement)
+        );
+    }
+}
@@ -8,6 +8,8 @@ pub(super) mod worker {
     use std::path::PathBuf;
 
     use crate::application::config::data_directory::DataDirectory;
+    use crate::application::database::open_files::db_options;
+    use crate::application::database::open_files::DbStore;
     use crate::application::database::storage::storage_schema::RustyKey;
     use crate::application::database::storage::storage_schema::RustyValue;
     use crate::application::database::NeptuneLevelDb;
@@ -27,10 +29,6 @@ pub(super) mod worker {
             wallet_database_path.exists()
         );
         DataDirectory::create_dir_if_not_exists(&wallet_database_path).await?;
-        NeptuneLevelDb::new(
-            &wallet_database_path,
-            &crate::application::database::create_db_if_missing(),
-        )
-        .await
+        NeptuneLevelDb::new(&wallet_database_path, &db_options(DbStore::Wallet)).await
     }
 }
@@ -1,23 +1,34 @@
 pub mod address;
 pub mod change_policy;
 pub mod coin_with_possible_timelock;
+pub mod composer_payout;
+pub mod derivation_vectors;
 pub(crate) mod expected_utxo;
 pub(crate) mod incoming_utxo;
+pub mod memo;
 pub(crate) mod migrate_db;
 pub(crate) mod monitored_utxo;
+pub(crate) mod pending_transactions;
+pub mod reserve_report;
 pub(crate) mod rusty_wallet_database;
 pub(crate) mod scan_mode_configuration;
 pub mod secret_key_material;
 pub mod sent_transaction;
+pub mod symmetric_key_rotation;
+pub mod sync_reconciliation;
+pub mod timestamp_fuzzing;
 pub mod transaction_input;
 pub mod transaction_output;
 pub(crate) mod unlocked_utxo;
 pub mod utxo_notification;
+pub mod utxo_ownership_proof;
+pub mod wallet_backup;
 pub(crate) mod wallet_configuration;
 pub(crate) mod wallet_db_tables;
 pub mod wallet_entropy;
 pub mod wallet_file;
 pub(crate) mod wallet_state;
+pub mod wallet_stats;
 pub mod wallet_status;
 
 #[cfg(test)]
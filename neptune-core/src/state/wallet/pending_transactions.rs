@@ -0,0 +1,411 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use itertools::Itertools;
+use num_traits::CheckedAdd;
+use num_traits::CheckedSub;
+use num_traits::Zero;
+use tasm_lib::prelude::Tip5;
+
+use super::incoming_utxo::IncomingUtxo;
+use super::wallet_status::WalletStatus;
+use crate::protocol::consensus::transaction::utxo::Utxo;
+use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+use crate::state::transaction::transaction_kernel_id::TransactionKernelId;
+use crate::util_types::mutator_set::addition_record::AdditionRecord;
+use crate::util_types::mutator_set::removal_record::absolute_index_set::AbsoluteIndexSet;
+
+/// An own UTXO that is spent by a pending transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PendingInput {
+    pub(crate) utxo: Utxo,
+    pub(crate) aocl_leaf_index: u64,
+    pub(crate) addition_record: AdditionRecord,
+}
+
+#[derive(Debug, Clone)]
+struct PendingTransaction {
+    /// Order of arrival. Later transactions supersede the earlier ones they
+    /// conflict with, as they do in the mempool.
+    sequence: u64,
+    inputs: HashMap<AbsoluteIndexSet, PendingInput>,
+    outputs: Vec<IncomingUtxo>,
+}
+
+/// The transactions in the mempool that spend from or pay to this wallet, and
+/// how they relate to each other.
+///
+/// Two pending transactions conflict if they spend the same UTXO, such that
+/// at most one of them can be confirmed. A pending transaction depends on
+/// another if it spends one of its outputs, such that it can only be confirmed
+/// after the other. This happens when a reorganization returns a transaction
+/// to the mempool while a transaction spending its outputs is still there.
+///
+/// The *effective* transactions are those that are expected to be confirmed:
+/// of conflicting transactions, only the latest is effective, and transactions
+/// that depend on non-effective transactions are not effective either.
+/// Pending balances, the inputs that are considered spent, and whether a
+/// transaction can be replaced are all derived from the effective
+/// transactions, so they agree with each other. In particular, every UTXO is
+/// accounted for at most once, no matter how many pending transactions spend
+/// or create it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PendingTransactions {
+    next_sequence: u64,
+    transactions: HashMap<TransactionKernelId, PendingTransaction>,
+}
+
+impl PendingTransactions {
+    pub(crate) fn insert(
+        &mut self,
+        txid: TransactionKernelId,
+        inputs: HashMap<AbsoluteIndexSet, PendingInput>,
+        outputs: Vec<IncomingUtxo>,
+    ) {
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        self.transactions.insert(
+            txid,
+            PendingTransaction {
+                sequence,
+                inputs,
+                outputs,
+            },
+        );
+    }
+
+    pub(crate) fn remove(&mut self, txid: TransactionKernelId) {
+        self.transactions.remove(&txid);
+    }
+
+    /// The pending transactions whose outputs are spent by `txid`.
+    fn dependencies(&self, txid: TransactionKernelId) -> Vec<TransactionKernelId> {
+        let Some(transaction) = self.transactions.get(&txid) else {
+            return vec![];
+        };
+        let spent = transaction
+            .inputs
+            .values()
+            .map(|input| input.addition_record)
+            .collect::<HashSet<_>>();
+
+        self.transactions
+            .iter()
+            .filter(|(_, other)| {
+                other
+                    .outputs
+                    .iter()
+                    .any(|output| spent.contains(&output.addition_record()))
+            })
+            .map(|(other_txid, _)| *other_txid)
+            .collect()
+    }
+
+    /// The effective transactions, see the [type docs](Self).
+    fn effective(&self) -> HashSet<TransactionKernelId> {
+        let mut effective = HashSet::new();
+        let mut spent = HashSet::new();
+        for (txid, transaction) in self
+            .transactions
+            .iter()
+            .sorted_by_key(|(_, transaction)| Reverse(transaction.sequence))
+        {
+            if transaction
+                .inputs
+                .keys()
+                .all(|index_set| !spent.contains(index_set))
+            {
+                spent.extend(transaction.inputs.keys().copied());
+                effective.insert(*txid);
+            }
+        }
+
+        loop {
+            let orphaned = effective
+                .iter()
+                .copied()
+                .filter(|txid| {
+                    self.dependencies(*txid)
+                        .iter()
+                        .any(|dependency| !effective.contains(dependency))
+                })
+                .collect_vec();
+            if orphaned.is_empty() {
+                return effective;
+            }
+            for txid in orphaned {
+                effective.remove(&txid);
+            }
+        }
+    }
+
+    /// The own UTXOs spent by effective transactions, along with their
+    /// absolute index sets.
+    pub(crate) fn spent_inputs(&self) -> Vec<(AbsoluteIndexSet, &PendingInput)> {
+        let effective = self.effective();
+        self.transactions
+            .iter()
+            .filter(|(txid, _)| effective.contains(txid))
+            .flat_map(|(_, transaction)| transaction.inputs.iter())
+            .map(|(index_set, input)| (*index_set, input))
+            .collect()
+    }
+
+    /// The own UTXOs created by effective transactions, each listed once.
+    pub(crate) fn received_outputs(&self) -> Vec<&IncomingUtxo> {
+        let effective = self.effective();
+        self.transactions
+            .iter()
+            .filter(|(txid, _)| effective.contains(txid))
+            .flat_map(|(_, transaction)| transaction.outputs.iter())
+            .unique_by(|output| output.addition_record())
+            .collect()
+    }
+
+    /// The amounts received by and spent from this wallet by every pending
+    /// transaction, effective or not.
+    pub(crate) fn balance_effects(
+        &self,
+    ) -> impl Iterator<
+        Item = (
+            TransactionKernelId,
+            NativeCurrencyAmount,
+            NativeCurrencyAmount,
+        ),
+    > + '_ {
+        self.transactions.iter().map(|(txid, transaction)| {
+            let received = transaction
+                .outputs
+                .iter()
+                .map(|output| output.utxo.get_native_currency_amount())
+                .sum();
+            let spent = transaction
+                .inputs
+                .values()
+                .map(|input| input.utxo.get_native_currency_amount())
+                .sum();
+            (*txid, received, spent)
+        })
+    }
+
+    /// Whether transaction `txid` can be replaced by a transaction spending
+    /// the same inputs, e.g., to raise the fee.
+    ///
+    /// This requires that the transaction is effective, that it spends own
+    /// UTXOs, and that no pending transaction depends on it, since the
+    /// replacement would invalidate those.
+    pub(crate) fn is_replaceable(&self, txid: TransactionKernelId) -> bool {
+        let Some(transaction) = self.transactions.get(&txid) else {
+            return false;
+        };
+
+        !transaction.inputs.is_empty()
+            && self.effective().contains(&txid)
+            && self
+                .transactions
+                .keys()
+                .all(|other| !self.dependencies(*other).contains(&txid))
+    }
+
+    /// The balance of the wallet once the effective transactions are
+    /// confirmed, given its confirmed state.
+    ///
+    /// If a `timestamp` is given, UTXOs that are time-locked at that time are
+    /// excluded. Outputs that are confirmed already, and inputs that are
+    /// neither unspent confirmed UTXOs nor pending outputs, e.g., because they
+    /// were spent in a block that the mempool has not caught up with, are not
+    /// counted again.
+    pub(crate) fn unconfirmed_balance(
+        &self,
+        wallet_status: &WalletStatus,
+        timestamp: Option<Timestamp>,
+    ) -> NativeCurrencyAmount {
+        let counts = |utxo: &Utxo| timestamp.is_none_or(|timestamp| utxo.can_spend_at(timestamp));
+
+        let confirmed_leaf_indices = wallet_status
+            .synced_unspent
+            .iter()
+            .map(|(element, _)| element.aocl_leaf_index)
+            .collect::<HashSet<_>>();
+        let confirmed_addition_records = wallet_status
+            .synced_unspent
+            .iter()
+            .map(|(element, msmp)| msmp.addition_record(Tip5::hash(&element.utxo)))
+            .collect::<HashSet<_>>();
+
+        let received = self
+            .received_outputs()
+            .into_iter()
+            .map(|output| (output.addition_record(), &output.utxo))
+            .filter(|(addition_record, _)| !confirmed_addition_records.contains(addition_record))
+            .collect::<HashMap<_, _>>();
+
+        let confirmed_amount = wallet_status
+            .synced_unspent
+            .iter()
+            .map(|(element, _)| &element.utxo)
+            .filter(|utxo| counts(utxo))
+            .map(|utxo| utxo.get_native_currency_amount())
+            .sum::<NativeCurrencyAmount>();
+        let received_amount = received
+            .values()
+            .filter(|utxo| counts(utxo))
+            .map(|utxo| utxo.get_native_currency_amount())
+            .sum::<NativeCurrencyAmount>();
+        let spent_amount = self
+            .spent_inputs()
+            .into_iter()
+            .map(|(_, input)| input)
+            .filter(|input| {
+                confirmed_leaf_indices.contains(&input.aocl_leaf_index)
+                    || received.contains_key(&input.addition_record)
+            })
+            .filter(|input| counts(&input.utxo))
+            .map(|input| input.utxo.get_native_currency_amount())
+            .sum::<NativeCurrencyAmount>();
+
+        confirmed_amount
+            .checked_add(&received_amount)
+            .expect("balance must never overflow")
+            .checked_sub(&spent_amount)
+            .unwrap_or(NativeCurrencyAmount::zero())
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::state::wallet::wallet_status::WalletStatusElement;
+    use crate::util_types::mutator_set::mutator_set_accumulator::MutatorSetAccumulator;
+
+    fn incoming_utxo(coins: u32) -> IncomingUtxo {
+        let mut rng = rand::rng();
+        IncomingUtxo {
+            utxo: Utxo::new_native_currency(rng.random(), NativeCurrencyAmount::coins(coins)),
+            sender_randomness: rng.random(),
+            receiver_preimage: rng.random(),
+            is_guesser_fee: false,
+        }
+    }
+
+    fn spend(utxo: &IncomingUtxo, aocl_leaf_index: u64) -> HashMap<AbsoluteIndexSet, PendingInput> {
+        let index_set = AbsoluteIndexSet::compute(
+            Tip5::hash(&utxo.utxo),
+            utxo.sender_randomness,
+            utxo.receiver_preimage,
+            aocl_leaf_index,
+        );
+        let input = PendingInput {
+            utxo: utxo.utxo.clone(),
+            aocl_leaf_index,
+            addition_record: utxo.addition_record(),
+        };
+        HashMap::from([(index_set, input)])
+    }
+
+    fn wallet_status(confirmed: &[(&IncomingUtxo, u64)]) -> WalletStatus {
+        let msa = MutatorSetAccumulator::default();
+        let synced_unspent = confirmed
+            .iter()
+            .map(|(utxo, aocl_leaf_index)| {
+                let mut msmp = msa.prove(
+                    Tip5::hash(&utxo.utxo),
+                    utxo.sender_randomness,
+                    utxo.receiver_preimage,
+                );
+                msmp.aocl_leaf_index = *aocl_leaf_index;
+                (
+                    WalletStatusElement::new(*aocl_leaf_index, utxo.utxo.clone()),
+                    msmp,
+                )
+            })
+            .collect();
+
+        WalletStatus {
+            synced_unspent,
+            synced_spent: vec![],
+            unsynced: vec![],
+        }
+    }
+
+    fn txid() -> TransactionKernelId {
+        rand::rng().random()
+    }
+
+    #[test]
+    fn conflicting_transactions_are_accounted_once() {
+        let confirmed = incoming_utxo(10);
+        let status = wallet_status(&[(&confirmed, 0)]);
+        let mut pending = PendingTransactions::default();
+
+        let (original, replacement) = (txid(), txid());
+        pending.insert(original, spend(&confirmed, 0), vec![incoming_utxo(4)]);
+        pending.insert(replacement, spend(&confirmed, 0), vec![incoming_utxo(3)]);
+
+        assert_eq!(
+            NativeCurrencyAmount::coins(3),
+            pending.unconfirmed_balance(&status, None)
+        );
+        assert_eq!(1, pending.spent_inputs().len());
+        assert_eq!(1, pending.received_outputs().len());
+        assert!(!pending.is_replaceable(original));
+        assert!(pending.is_replaceable(replacement));
+
+        pending.remove(replacement);
+        assert_eq!(
+            NativeCurrencyAmount::coins(4),
+            pending.unconfirmed_balance(&status, None)
+        );
+        assert!(pending.is_replaceable(original));
+    }
+
+    #[test]
+    fn chained_transactions_are_accounted_once() {
+        let status = wallet_status(&[]);
+        let mut pending = PendingTransactions::default();
+
+        let change = incoming_utxo(5);
+        let (parent, child) = (txid(), txid());
+        pending.insert(parent, spend(&incoming_utxo(9), 3), vec![change.clone()]);
+        pending.insert(child, spend(&change, 7), vec![incoming_utxo(2)]);
+
+        assert_eq!(
+            NativeCurrencyAmount::coins(2),
+            pending.unconfirmed_balance(&status, None)
+        );
+        assert_eq!(vec![parent], pending.dependencies(child));
+        assert!(!pending.is_replaceable(parent));
+        assert!(pending.is_replaceable(child));
+
+        // a newer transaction spending the same output supersedes the child
+        let conflicting_child = txid();
+        pending.insert(conflicting_child, spend(&change, 7), vec![]);
+        assert!(!pending.effective().contains(&child));
+    }
+
+    #[test]
+    fn inputs_spent_in_blocks_are_not_subtracted_again() {
+        let spent_in_block = incoming_utxo(10);
+        let unspent = incoming_utxo(6);
+        let status = wallet_status(&[(&unspent, 1)]);
+        let mut pending = PendingTransactions::default();
+
+        pending.insert(txid(), spend(&spent_in_block, 0), vec![]);
+        assert_eq!(
+            NativeCurrencyAmount::coins(6),
+            pending.unconfirmed_balance(&status, None)
+        );
+
+        let already_confirmed = txid();
+        pending.insert(already_confirmed, HashMap::new(), vec![unspent.clone()]);
+        assert_eq!(
+            NativeCurrencyAmount::coins(6),
+            pending.unconfirmed_balance(&status, None)
+        );
+    }
+}
@@ -0,0 +1,409 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Digest;
+use tasm_lib::prelude::Tip5;
+use tasm_lib::triton_vm::prelude::BFieldCodec;
+use tasm_lib::triton_vm::prelude::BFieldElement;
+use tasm_lib::triton_vm::prelude::PublicInput;
+use tasm_lib::triton_vm::proof::Claim;
+use tasm_lib::twenty_first::bfe_vec;
+
+use super::utxo_ownership_proof::UtxoOwnershipProof;
+use super::utxo_ownership_proof::UtxoOwnershipProofError;
+use super::utxo_ownership_proof::UtxoOwnershipReport;
+use crate::api::tx_initiation::error::CreateProofError;
+use crate::application::config::network::Network;
+use crate::application::triton_vm_job_queue::TritonVmJobQueue;
+use crate::protocol::consensus::transaction::lock_script::LockScriptAndWitness;
+use crate::protocol::consensus::transaction::Proof;
+use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
+use crate::protocol::proof_abstractions::tasm::program::TritonVmProofJobOptions;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+use crate::protocol::proof_abstractions::verifier;
+use crate::state::checkpoint::Checkpoint;
+use crate::util_types::mutator_set::addition_record::AdditionRecord;
+
+/// Domain separator for reserve report digests, so that a report signature
+/// cannot be mistaken for any other use of a lock script, eg a transaction.
+const RESERVE_REPORT_DOMAIN_SEPARATOR: u64 = 0x7273_7276_7270_3031;
+
+/// A proof-of-reserve: the wallet's unspent UTXOs at a block, their
+/// commitments in the mutator set, and their total amount.
+///
+/// The UTXOs and their membership proofs are disclosed through
+/// [`Self::ownership`], so the report carries everything an auditor needs to
+/// check it offline against a [`Checkpoint`] of the chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReserveReport {
+    pub network: Network,
+    pub created_at: Timestamp,
+
+    /// The addition records of the disclosed UTXOs, in the order of
+    /// [`UtxoOwnershipProof::utxos`].
+    pub commitments: Vec<AdditionRecord>,
+
+    /// The claimed sum of the native currency amounts of all disclosed UTXOs.
+    pub total: NativeCurrencyAmount,
+    pub ownership: UtxoOwnershipProof,
+}
+
+/// A reason why a [`SignedReserveReport`] does not verify.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+pub enum ReserveReportError {
+    #[error("report is not for network {0}")]
+    WrongNetwork(Network),
+
+    #[error("report does not match the chain snapshot")]
+    SnapshotMismatch,
+
+    #[error(transparent)]
+    Ownership(#[from] UtxoOwnershipProofError),
+
+    #[error("commitment {index} does not open to the disclosed UTXO")]
+    CommitmentMismatch { index: usize },
+
+    #[error("claimed total {claimed} does not match verified total {verified}")]
+    TotalMismatch {
+        claimed: NativeCurrencyAmount,
+        verified: NativeCurrencyAmount,
+    },
+
+    #[error("no signature for lock script {0}")]
+    MissingSignature(Digest),
+
+    #[error("signature is invalid")]
+    InvalidSignature,
+}
+
+impl ReserveReport {
+    /// Summarize an ownership proof of all unspent UTXOs of a wallet.
+    pub fn new(
+        network: Network,
+        ownership: UtxoOwnershipProof,
+        created_at: Timestamp,
+    ) -> Result<Self, UtxoOwnershipProofError> {
+        let total = ownership.verify()?.total;
+        let commitments = ownership
+            .utxos
+            .iter()
+            .map(|disclosed| {
+                disclosed
+                    .membership_proof
+                    .addition_record(Tip5::hash(&disclosed.utxo))
+            })
+            .collect();
+
+        Ok(Self {
+            network,
+            created_at,
+            commitments,
+            total,
+            ownership,
+        })
+    }
+
+    /// The digest that is signed.
+    pub fn digest(&self) -> Digest {
+        let aocl_leaf_indices = self
+            .ownership
+            .utxos
+            .iter()
+            .map(|disclosed| disclosed.membership_proof.aocl_leaf_index)
+            .collect::<Vec<_>>();
+
+        Tip5::hash_varlen(
+            &[
+                bfe_vec![RESERVE_REPORT_DOMAIN_SEPARATOR, self.network.id()],
+                self.created_at.encode(),
+                self.ownership.block_digest.encode(),
+                self.ownership.block_height.encode(),
+                self.ownership.mutator_set_accumulator.hash().encode(),
+                self.commitments.encode(),
+                aocl_leaf_indices.encode(),
+                self.total.encode(),
+            ]
+            .concat(),
+        )
+    }
+
+    fn public_input(&self) -> Vec<BFieldElement> {
+        self.digest().reversed().values().to_vec()
+    }
+
+    /// Check the report against `snapshot`, a checkpoint of the chain that
+    /// the auditor trusts, and return what the disclosed UTXOs show.
+    fn check(
+        &self,
+        network: Network,
+        snapshot: &Checkpoint,
+    ) -> Result<UtxoOwnershipReport, ReserveReportError> {
+        if self.network != network || snapshot.network != network {
+            return Err(ReserveReportError::WrongNetwork(network));
+        }
+
+        let verified = self.ownership.verify()?;
+        if verified.block_digest != snapshot.block_digest
+            || verified.block_height != snapshot.height
+            || verified.mutator_set_hash != snapshot.mutator_set_hash
+        {
+            return Err(ReserveReportError::SnapshotMismatch);
+        }
+
+        if self.commitments.len() != self.ownership.utxos.len() {
+            return Err(ReserveReportError::CommitmentMismatch {
+                index: self.commitments.len().min(self.ownership.utxos.len()),
+            });
+        }
+        for (index, (commitment, disclosed)) in self
+            .commitments
+            .iter()
+            .zip(&self.ownership.utxos)
+            .enumerate()
+        {
+            if *commitment
+                != disclosed
+                    .membership_proof
+                    .addition_record(Tip5::hash(&disclosed.utxo))
+            {
+                return Err(ReserveReportError::CommitmentMismatch { index });
+            }
+        }
+
+        if self.total != verified.total {
+            return Err(ReserveReportError::TotalMismatch {
+                claimed: self.total,
+                verified: verified.total,
+            });
+        }
+
+        Ok(verified)
+    }
+}
+
+/// A signature of a [`ReserveReport`] by the spending key of some of its
+/// UTXOs: a proof of their lock script, with the report digest as public
+/// input.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockScriptSignature {
+    pub lock_script_hash: Digest,
+    pub proof: Proof,
+}
+
+impl LockScriptSignature {
+    /// The claim proven by the signature.
+    pub fn claim(&self, report: &ReserveReport) -> Claim {
+        Claim::new(self.lock_script_hash).with_input(report.public_input())
+    }
+}
+
+/// A [`ReserveReport`] signed by the wallet that produced it.
+///
+/// The report is signed with the spending keys of its UTXOs: for every lock
+/// script among the disclosed UTXOs, the signatures hold a proof of that lock
+/// script with the report digest as public input, just like a transaction
+/// proves the lock scripts of its inputs. So only whoever can spend the
+/// UTXOs can sign a report disclosing them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedReserveReport {
+    pub report: ReserveReport,
+
+    /// One signature per distinct lock script of the disclosed UTXOs.
+    pub signatures: Vec<LockScriptSignature>,
+}
+
+impl SignedReserveReport {
+    /// Sign `report` with the given lock scripts and their witnesses, which
+    /// must include one for every lock script of the disclosed UTXOs.
+    ///
+    /// Produces a proof per lock script, which takes a while.
+    pub(crate) async fn sign(
+        report: ReserveReport,
+        lock_scripts_and_witnesses: Vec<LockScriptAndWitness>,
+        triton_vm_job_queue: Arc<TritonVmJobQueue>,
+        proof_job_options: TritonVmProofJobOptions,
+    ) -> Result<Self, CreateProofError> {
+        let mut signatures = vec![];
+        for lock_script_and_witness in lock_scripts_and_witnesses {
+            let proof = lock_script_and_witness
+                .prove(
+                    PublicInput::new(report.public_input()),
+                    triton_vm_job_queue.clone(),
+                    proof_job_options.clone(),
+                )
+                .await?;
+            signatures.push(LockScriptSignature {
+                lock_script_hash: lock_script_and_witness.program.hash(),
+                proof,
+            });
+        }
+
+        Ok(Self { report, signatures })
+    }
+
+    /// Verify the report offline against `snapshot`, a checkpoint of the
+    /// chain on `network` that the auditor trusts, eg one they computed
+    /// themselves or collected from several independent nodes.
+    ///
+    /// On success, returns the UTXOs and total the report proves to be under
+    /// the control of whoever signed it, ie under the control of the holder
+    /// of their spending keys.
+    ///
+    /// On networks that use mock proofs, any valid mock signature is accepted.
+    pub async fn verify(
+        &self,
+        network: Network,
+        snapshot: &Checkpoint,
+    ) -> Result<UtxoOwnershipReport, ReserveReportError> {
+        let verified = self.report.check(network, snapshot)?;
+
+        let lock_script_hashes = self
+            .report
+            .ownership
+            .utxos
+            .iter()
+            .map(|disclosed| disclosed.utxo.lock_script_hash())
+            .collect::<HashSet<_>>();
+        for lock_script_hash in &lock_script_hashes {
+            if !self
+                .signatures
+                .iter()
+                .any(|signature| signature.lock_script_hash == *lock_script_hash)
+            {
+                return Err(ReserveReportError::MissingSignature(*lock_script_hash));
+            }
+        }
+
+        for signature in &self.signatures {
+            if !lock_script_hashes.contains(&signature.lock_script_hash)
+                || !verifier::verify(
+                    signature.claim(&self.report),
+                    signature.proof.clone(),
+                    network,
+                )
+                .await
+            {
+                return Err(ReserveReportError::InvalidSignature);
+            }
+        }
+
+        Ok(verified)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use num_traits::Zero;
+    use rand::random;
+
+    use super::*;
+    use crate::protocol::consensus::block::difficulty_control::ProofOfWork;
+    use crate::protocol::consensus::transaction::utxo::Utxo;
+    use crate::state::wallet::utxo_ownership_proof::DisclosedUtxo;
+    use crate::util_types::mutator_set::commit;
+    use crate::util_types::mutator_set::mutator_set_accumulator::MutatorSetAccumulator;
+
+    fn ownership_proof(amounts: &[u32]) -> UtxoOwnershipProof {
+        let mut msa = MutatorSetAccumulator::default();
+        let mut utxos: Vec<DisclosedUtxo> = vec![];
+        let lock_script_hash = random();
+        for amount in amounts {
+            let utxo =
+                Utxo::new_native_currency(lock_script_hash, NativeCurrencyAmount::coins(*amount));
+            let item = Tip5::hash(&utxo);
+            let (sender_randomness, receiver_preimage) = (random(), random::<Digest>());
+            let addition_record = commit(item, sender_randomness, receiver_preimage.hash());
+            let membership_proof = msa.prove(item, sender_randomness, receiver_preimage);
+            for disclosed in &mut utxos {
+                disclosed
+                    .membership_proof
+                    .update_from_addition(Tip5::hash(&disclosed.utxo), &msa, &addition_record)
+                    .unwrap();
+            }
+            msa.add(&addition_record);
+            utxos.push(DisclosedUtxo {
+                utxo,
+                membership_proof,
+            });
+        }
+
+        UtxoOwnershipProof {
+            block_digest: random(),
+            block_height: 7u64.into(),
+            mutator_set_accumulator: msa,
+            utxos,
+        }
+    }
+
+    fn snapshot(network: Network, ownership: &UtxoOwnershipProof) -> Checkpoint {
+        Checkpoint {
+            network,
+            block_digest: ownership.block_digest,
+            height: ownership.block_height,
+            cumulative_proof_of_work: ProofOfWork::zero(),
+            mutator_set_hash: ownership.mutator_set_accumulator.hash(),
+        }
+    }
+
+    #[test]
+    fn report_checks_against_matching_snapshot_only() {
+        let network = Network::Main;
+        let ownership = ownership_proof(&[3, 4]);
+        let snapshot = snapshot(network, &ownership);
+        let report = ReserveReport::new(network, ownership, Timestamp::now()).unwrap();
+
+        let verified = report.check(network, &snapshot).unwrap();
+        assert_eq!(NativeCurrencyAmount::coins(7), verified.total);
+        assert_eq!(2, report.commitments.len());
+
+        assert_eq!(
+            Err(ReserveReportError::WrongNetwork(Network::Testnet(0))),
+            report.check(Network::Testnet(0), &snapshot)
+        );
+
+        let other_block = Checkpoint {
+            block_digest: random(),
+            ..snapshot
+        };
+        assert_eq!(
+            Err(ReserveReportError::SnapshotMismatch),
+            report.check(network, &other_block)
+        );
+    }
+
+    #[test]
+    fn tampered_report_does_not_check() {
+        let network = Network::Main;
+        let ownership = ownership_proof(&[3, 4]);
+        let snapshot = snapshot(network, &ownership);
+        let report = ReserveReport::new(network, ownership, Timestamp::now()).unwrap();
+
+        let mut inflated = report.clone();
+        inflated.total = NativeCurrencyAmount::coins(70);
+        assert_eq!(
+            Err(ReserveReportError::TotalMismatch {
+                claimed: NativeCurrencyAmount::coins(70),
+                verified: NativeCurrencyAmount::coins(7),
+            }),
+            inflated.check(network, &snapshot)
+        );
+        assert_ne!(report.digest(), inflated.digest());
+
+        let mut swapped = report.clone();
+        swapped.commitments.swap(0, 1);
+        assert_eq!(
+            Err(ReserveReportError::CommitmentMismatch { index: 0 }),
+            swapped.check(network, &snapshot)
+        );
+
+        let mut truncated = report;
+        truncated.commitments.pop();
+        assert_eq!(
+            Err(ReserveReportError::CommitmentMismatch { index: 1 }),
+            truncated.check(network, &snapshot)
+        );
+    }
+}
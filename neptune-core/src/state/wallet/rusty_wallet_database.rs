@@ -5,9 +5,12 @@ use tasm_lib::prelude::Tip5;
 use tasm_lib::twenty_first::tip5::digest::Digest;
 
 use super::expected_utxo::ExpectedUtxo;
+use super::memo::Memo;
+use super::memo::MAX_NUM_MEMOS_PER_UTXO;
 use super::migrate_db;
 use super::monitored_utxo::MonitoredUtxo;
 use super::sent_transaction::SentTransaction;
+use super::symmetric_key_rotation::ActiveSymmetricKey;
 use super::wallet_db_tables::WalletDbTables;
 use super::wallet_db_tables::WALLET_DB_SCHEMA_VERSION;
 use crate::api::export::AdditionRecord;
@@ -241,6 +244,21 @@ impl RustyWalletDatabase {
             .await;
     }
 
+    /// Undo the marking of a [`MonitoredUtxo`] as spent, because the spending
+    /// block is no longer canonical.
+    ///
+    /// # Panics
+    ///
+    /// - If index for monitored UTXO is out of range.
+    pub(crate) async fn unmark_mutxo_as_spent(&mut self, mutxo_list_index: Index) {
+        let mut mutxo = self.tables.monitored_utxos.get(mutxo_list_index).await;
+        mutxo.spent_in_block = None;
+        self.tables
+            .monitored_utxos
+            .set(mutxo_list_index, mutxo)
+            .await;
+    }
+
     /// Add a new [`MsMembershipProof`] to a [`MonitoredUtxo`].
     ///
     /// # Panics
@@ -460,6 +478,57 @@ impl RustyWalletDatabase {
         self.tables.symmetric_key_counter.set(counter).await;
     }
 
+    /// retrieve the symmetric key handed out under the key-rotation policy
+    pub(crate) fn get_active_symmetric_key(&self) -> Option<ActiveSymmetricKey> {
+        self.tables.active_symmetric_key.get()
+    }
+
+    /// set the symmetric key handed out under the key-rotation policy
+    pub(crate) async fn set_active_symmetric_key(&mut self, active_key: ActiveSymmetricKey) {
+        self.tables.active_symmetric_key.set(Some(active_key)).await;
+    }
+
+    /// attach memos to the UTXO with the given addition record, skipping
+    /// memos that are already attached, and dropping those beyond
+    /// [MAX_NUM_MEMOS_PER_UTXO].
+    pub(crate) async fn add_utxo_memos(
+        &mut self,
+        addition_record: AdditionRecord,
+        memos: Vec<Memo>,
+    ) {
+        let mut attached = self
+            .tables
+            .utxo_memos
+            .get(&addition_record)
+            .await
+            .unwrap_or_default();
+        let num_attached = attached.len();
+        for memo in memos {
+            if attached.len() >= MAX_NUM_MEMOS_PER_UTXO {
+                break;
+            }
+            if !attached.contains(&memo) {
+                attached.push(memo);
+            }
+        }
+
+        if attached.len() != num_attached {
+            self.tables
+                .utxo_memos
+                .insert(addition_record, attached)
+                .await;
+        }
+    }
+
+    /// retrieve the memos attached to the UTXO with the given addition record
+    pub(crate) async fn utxo_memos(&self, addition_record: &AdditionRecord) -> Vec<Memo> {
+        self.tables
+            .utxo_memos
+            .get(addition_record)
+            .await
+            .unwrap_or_default()
+    }
+
     /// retrieve the database schema version
     pub fn schema_version(&self) -> u16 {
         self.tables.schema_version.get()
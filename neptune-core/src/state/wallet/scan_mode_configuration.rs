@@ -2,6 +2,11 @@ use std::ops::RangeInclusive;
 
 use crate::protocol::consensus::block::block_height::BlockHeight;
 use crate::protocol::consensus::block::Block;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+
+/// Blocks this much older than the wallet's birthday are still scanned, to
+/// tolerate inaccurate clocks of the wallet's creator and of block composers.
+const WALLET_BIRTHDAY_MARGIN: Timestamp = Timestamp::days(1);
 
 /// Configuration settings for Scan Mode.
 ///
@@ -19,6 +24,10 @@ pub(crate) struct ScanModeConfiguration {
     first_block_height: BlockHeight,
     last_block_height: Option<BlockHeight>,
 
+    /// Blocks created before the wallet existed cannot contain UTXOs
+    /// belonging to it, and are not scanned.
+    wallet_birthday: Option<Timestamp>,
+
     /// Relates to the attempted recovery of composer rewards, assuming the user
     ///  - remembers what they set the guesser fraction to, and
     ///  - lost incoming_randomness.dat, and
@@ -37,6 +46,7 @@ impl Default for ScanModeConfiguration {
             num_future_keys: 25,
             first_block_height: BlockHeight::genesis(),
             last_block_height: None,
+            wallet_birthday: None,
             maybe_guesser_fraction: None,
         }
     }
@@ -78,6 +88,11 @@ impl ScanModeConfiguration {
         self.maybe_guesser_fraction = Some(fraction);
     }
 
+    /// Skip scanning blocks created before the given time.
+    pub(crate) fn set_wallet_birthday(&mut self, birthday: Timestamp) {
+        self.wallet_birthday = Some(birthday);
+    }
+
     /// Determine whether to scan a block given its height.
     ///
     /// Marked `pub(crate)` for testing. Not part of the API. Use
@@ -88,10 +103,21 @@ impl ScanModeConfiguration {
             && self.last_block_height.is_none_or(|lbh| lbh >= block_height)
     }
 
+    /// Determine whether to scan a block given its timestamp.
+    ///
+    /// Marked `pub(crate)` for testing. Not part of the API. Use
+    /// [`Self::block_is_in_range`] instead.
+    #[doc(hidden)]
+    pub(crate) fn block_timestamp_is_after_birthday(&self, timestamp: Timestamp) -> bool {
+        self.wallet_birthday
+            .is_none_or(|birthday| birthday <= timestamp + WALLET_BIRTHDAY_MARGIN)
+    }
+
     /// Determine whether to scan the given block.
     pub(crate) fn block_is_in_range(&self, block: &Block) -> bool {
-        let block_height = block.header().height;
-        self.block_height_is_in_range(block_height)
+        let header = block.header();
+        self.block_height_is_in_range(header.height)
+            && self.block_timestamp_is_after_birthday(header.timestamp)
     }
 
     /// How many future keys to scan for.
@@ -0,0 +1,142 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+
+/// Specifies when the symmetric key used for receiving funds is replaced by
+/// its successor.
+///
+/// Without a policy, every request for a symmetric key derives a new one, so
+/// every change output goes to a fresh key. With a policy, the wallet keeps
+/// handing out the same active key until it has been used `max_uses` times or
+/// is older than `max_age`, whichever comes first. Then the successor is
+/// derived and becomes active. Retired keys are never handed out again, but
+/// remain known to the wallet, so UTXOs sent to them are still recognized.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymmetricKeyRotationPolicy {
+    /// Rotate after the active key has been handed out this many times.
+    pub max_uses: Option<u64>,
+
+    /// Rotate once the active key is older than this.
+    pub max_age: Option<Timestamp>,
+}
+
+impl SymmetricKeyRotationPolicy {
+    /// Returns true iff keys are reused until rotated, as opposed to a new key
+    /// being derived on every request.
+    pub fn is_enabled(&self) -> bool {
+        self.max_uses.is_some() || self.max_age.is_some()
+    }
+
+    /// The time at which the active key expires by age, if the policy limits
+    /// the age of keys.
+    fn expiry(&self, active_key: &ActiveSymmetricKey) -> Option<Timestamp> {
+        self.max_age
+            .map(|max_age| active_key.activated_at + max_age)
+    }
+
+    /// Returns true iff the active key must be replaced before it is handed
+    /// out again.
+    pub(crate) fn rotation_is_due(&self, active_key: &ActiveSymmetricKey, now: Timestamp) -> bool {
+        self.max_uses
+            .is_some_and(|max_uses| active_key.num_uses >= max_uses)
+            || self.expiry(active_key).is_some_and(|expiry| now >= expiry)
+    }
+}
+
+/// The symmetric key currently handed out for receiving funds, under a
+/// [`SymmetricKeyRotationPolicy`]. Persisted in the wallet database.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ActiveSymmetricKey {
+    pub(crate) derivation_index: u64,
+
+    /// The number of times this key was handed out.
+    pub(crate) num_uses: u64,
+    pub(crate) activated_at: Timestamp,
+}
+
+impl ActiveSymmetricKey {
+    pub(crate) fn new(derivation_index: u64, activated_at: Timestamp) -> Self {
+        Self {
+            derivation_index,
+            num_uses: 0,
+            activated_at,
+        }
+    }
+}
+
+/// The state of symmetric-key rotation, as reported over RPC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymmetricKeyRotationStatus {
+    pub policy: SymmetricKeyRotationPolicy,
+
+    /// The derivation index of the active key, if a key was handed out under
+    /// the policy.
+    pub active_key_index: Option<u64>,
+
+    /// The number of times the active key was handed out.
+    pub num_uses: u64,
+
+    /// When the active key was derived.
+    pub activated_at: Option<Timestamp>,
+
+    /// When the active key expires by age, if the policy limits the age of
+    /// keys.
+    pub expires_at: Option<Timestamp>,
+
+    /// Whether the active key will be replaced by its successor before it is
+    /// handed out again.
+    pub rotation_is_due: bool,
+
+    /// The number of symmetric keys known to the wallet, including retired
+    /// keys, all of which are scanned for incoming UTXOs.
+    pub num_known_keys: u64,
+}
+
+impl SymmetricKeyRotationStatus {
+    pub(crate) fn new(
+        policy: SymmetricKeyRotationPolicy,
+        active_key: Option<ActiveSymmetricKey>,
+        num_known_keys: u64,
+        now: Timestamp,
+    ) -> Self {
+        Self {
+            policy,
+            active_key_index: active_key.map(|key| key.derivation_index),
+            num_uses: active_key.map(|key| key.num_uses).unwrap_or_default(),
+            activated_at: active_key.map(|key| key.activated_at),
+            expires_at: active_key.and_then(|key| policy.expiry(&key)),
+            rotation_is_due: active_key.is_some_and(|key| policy.rotation_is_due(&key, now)),
+            num_known_keys,
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotation_is_due_after_max_uses_or_max_age() {
+        let activated_at = Timestamp::now();
+        let mut active_key = ActiveSymmetricKey::new(1, activated_at);
+        let policy = SymmetricKeyRotationPolicy {
+            max_uses: Some(2),
+            max_age: Some(Timestamp::days(1)),
+        };
+
+        assert!(!policy.rotation_is_due(&active_key, activated_at));
+        active_key.num_uses = 2;
+        assert!(policy.rotation_is_due(&active_key, activated_at));
+
+        active_key.num_uses = 0;
+        assert!(!policy.rotation_is_due(&active_key, activated_at + Timestamp::hours(23)));
+        assert!(policy.rotation_is_due(&active_key, activated_at + Timestamp::days(1)));
+    }
+
+    #[test]
+    fn default_policy_is_disabled() {
+        assert!(!SymmetricKeyRotationPolicy::default().is_enabled());
+    }
+}
@@ -0,0 +1,99 @@
+use std::fmt;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Digest;
+
+use crate::protocol::consensus::block::block_height::BlockHeight;
+
+/// What the startup reconciliation of the wallet database with the archival
+/// tip found, and what it did about it.
+///
+/// After an unclean shutdown, the wallet database and the block databases may
+/// have been persisted at different blocks, leaving the wallet's sync label
+/// pointing at a block other than the tip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WalletReconciliation {
+    /// The wallet was synced to the tip; nothing was done.
+    Consistent,
+
+    /// The wallet was synced to a known block. Its view was rolled back to the
+    /// common ancestor of that block and the tip, and then forward to the tip.
+    Reconciled {
+        /// The block the wallet was synced to.
+        wallet_sync_label: Digest,
+        tip: Digest,
+
+        /// The latest common ancestor of the wallet's block and the tip.
+        luca: Digest,
+        num_reverted_blocks: usize,
+        num_applied_blocks: usize,
+
+        /// Number of monitored UTXOs whose expenditure was reverted.
+        num_unspent: usize,
+
+        /// Number of monitored UTXOs that were confirmed in a reverted block
+        /// and are therefore not part of the canonical chain.
+        num_orphaned: usize,
+    },
+
+    /// The wallet was synced to a block that the archival state does not
+    /// know, typically because the block databases were not persisted before
+    /// the shutdown. Expenditures and confirmations in blocks that are not
+    /// canonical were rolled back, and the canonical blocks after the highest
+    /// canonical block the wallet refers to were scanned again.
+    UnknownSyncLabel {
+        /// The block the wallet was synced to.
+        wallet_sync_label: Digest,
+        tip: Digest,
+
+        /// The height from which canonical blocks were scanned again.
+        rescanned_from: BlockHeight,
+        num_applied_blocks: usize,
+
+        /// Number of monitored UTXOs whose expenditure was reverted.
+        num_unspent: usize,
+
+        /// Number of monitored UTXOs that were confirmed in a block that is
+        /// not canonical.
+        num_orphaned: usize,
+    },
+}
+
+impl fmt::Display for WalletReconciliation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Consistent => write!(f, "wallet is synced to tip"),
+            Self::Reconciled {
+                wallet_sync_label,
+                tip,
+                luca,
+                num_reverted_blocks,
+                num_applied_blocks,
+                num_unspent,
+                num_orphaned,
+            } => write!(
+                f,
+                "wallet was synced to {wallet_sync_label:x} instead of tip \
+                {tip:x}; reverted {num_reverted_blocks} blocks to common \
+                ancestor {luca:x} and applied {num_applied_blocks} blocks; \
+                {num_unspent} UTXOs are unspent again and {num_orphaned} UTXOs \
+                were orphaned"
+            ),
+            Self::UnknownSyncLabel {
+                wallet_sync_label,
+                tip,
+                rescanned_from,
+                num_applied_blocks,
+                num_unspent,
+                num_orphaned,
+            } => write!(
+                f,
+                "wallet was synced to unknown block {wallet_sync_label:x} \
+                instead of tip {tip:x}; applied {num_applied_blocks} blocks \
+                from height {rescanned_from}; {num_unspent} UTXOs are unspent \
+                again and {num_orphaned} UTXOs were orphaned"
+            ),
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use rand::Rng;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+
+/// Upper bound on both the fuzzing window and the rounding granularity of a
+/// [`TimestampFuzzingPolicy`], such that a fuzzed transaction is at most 12
+/// hours old when it is initiated, far from the mempool's 72-hour age limit.
+pub const MAX_TIMESTAMP_FUZZ: Timestamp = Timestamp::hours(6);
+
+/// Specifies how the timestamp of a transaction initiated by this node is
+/// obscured.
+///
+/// A transaction's timestamp is public and, by default, is the time at which
+/// the transaction was initiated, which can link the transaction to the
+/// activity of its sender. With a policy, the timestamp is instead drawn
+/// uniformly from the `window` preceding the time of initiation, and then
+/// rounded down to a multiple of `granularity`.
+///
+/// Timestamps are only ever moved into the past, since peers reject
+/// transactions timestamped in the future and a transaction can only be
+/// included in a block that is not older than the transaction. A timestamp is
+/// never moved to or before the release date of a time-locked input, which
+/// would make the transaction invalid.
+///
+/// The trade-off is a shorter lifetime: the mempool drops transactions once
+/// they are older than 72 hours, so a transaction that was fuzzed by some
+/// hours is pruned that many hours earlier, if it was not mined by then.
+/// Moreover, timestamps that are all multiples of the same granularity form a
+/// pattern of their own, which is only private if others round alike.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimestampFuzzingPolicy {
+    /// Draw the timestamp from this long a window before initiation. Capped
+    /// at [`MAX_TIMESTAMP_FUZZ`].
+    pub window: Option<Timestamp>,
+
+    /// Round the timestamp down to a multiple of this. Capped at
+    /// [`MAX_TIMESTAMP_FUZZ`].
+    pub granularity: Option<Timestamp>,
+}
+
+impl TimestampFuzzingPolicy {
+    /// Returns true iff timestamps are changed by the policy.
+    pub fn is_enabled(&self) -> bool {
+        self.window.is_some_and(|w| w.to_millis() > 0)
+            || self.granularity.is_some_and(|g| g.to_millis() > 1)
+    }
+
+    /// The timestamp for a transaction initiated at `now` that spends inputs
+    /// released at `not_before`, or not time-locked if `None`.
+    ///
+    /// The result lies in `(not_before, now]`, since time locks only release
+    /// inputs to transactions timestamped strictly after the release date. It
+    /// is `now` if `not_before` is not earlier than `now`.
+    pub(crate) fn fuzz<R: Rng>(
+        &self,
+        now: Timestamp,
+        not_before: Option<Timestamp>,
+        rng: &mut R,
+    ) -> Timestamp {
+        let cap = |t: Option<Timestamp>| {
+            t.map_or(0, |t| t.to_millis().min(MAX_TIMESTAMP_FUZZ.to_millis()))
+        };
+        let window = cap(self.window);
+        let granularity = cap(self.granularity).max(1);
+
+        let now = now.to_millis();
+        let earliest = not_before.map_or(0, |t| t.to_millis().saturating_add(1));
+        if earliest > now {
+            return Timestamp::millis(now);
+        }
+
+        let fuzzed = rng.random_range(now.saturating_sub(window)..=now);
+        let rounded = fuzzed - fuzzed % granularity;
+
+        Timestamp::millis(rounded.max(earliest))
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use proptest::prop_assert;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use test_strategy::proptest;
+
+    use super::*;
+    use crate::protocol::consensus::block::FUTUREDATING_LIMIT;
+    use crate::state::mempool::MEMPOOL_TX_THRESHOLD_AGE_IN_SECS;
+
+    #[test]
+    fn disabled_policy_keeps_timestamp() {
+        let policy = TimestampFuzzingPolicy::default();
+        assert!(!policy.is_enabled());
+
+        let now = Timestamp::now();
+        let mut rng = rand::rng();
+        assert_eq!(now, policy.fuzz(now, None, &mut rng));
+    }
+
+    #[test]
+    fn release_date_after_now_keeps_timestamp() {
+        let policy = TimestampFuzzingPolicy {
+            window: Some(Timestamp::hours(1)),
+            granularity: Some(Timestamp::minutes(10)),
+        };
+        let now = Timestamp::now();
+        let release_date = now + Timestamp::seconds(1);
+        let mut rng = rand::rng();
+        assert_eq!(now, policy.fuzz(now, Some(release_date), &mut rng));
+    }
+
+    #[proptest(cases = 100)]
+    fn fuzzed_timestamp_validates_against_tip(
+        #[strategy(0u64..=24 * 60 * 60 * 1000)] window: u64,
+        #[strategy(0u64..=24 * 60 * 60 * 1000)] granularity: u64,
+        #[strategy(1u64..=60 * 60 * 1000)] released_ago: u64,
+        seed: u64,
+    ) {
+        let policy = TimestampFuzzingPolicy {
+            window: Some(Timestamp::millis(window)),
+            granularity: Some(Timestamp::millis(granularity)),
+        };
+        let now = Timestamp::now();
+        let release_date = Timestamp::millis(now.to_millis() - released_ago);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let fuzzed = policy.fuzz(now, Some(release_date), &mut rng);
+
+        // time-locked inputs remain spendable
+        prop_assert!(fuzzed > release_date);
+
+        // peers accept the transaction into their mempools
+        let max_age = Timestamp::seconds(MEMPOOL_TX_THRESHOLD_AGE_IN_SECS);
+        prop_assert!(fuzzed >= now - max_age);
+        prop_assert!(fuzzed < now + FUTUREDATING_LIMIT);
+        prop_assert!(fuzzed >= now - MAX_TIMESTAMP_FUZZ - MAX_TIMESTAMP_FUZZ);
+
+        // blocks timestamped from now on can include the transaction
+        prop_assert!(fuzzed <= now);
+    }
+
+    #[test]
+    fn fuzzed_timestamp_is_rounded() {
+        let policy = TimestampFuzzingPolicy {
+            window: Some(Timestamp::hours(1)),
+            granularity: Some(Timestamp::minutes(10)),
+        };
+        assert!(policy.is_enabled());
+
+        let now = Timestamp::now();
+        let mut rng = rand::rng();
+        for _ in 0..20 {
+            let fuzzed = policy.fuzz(now, None, &mut rng);
+            assert_eq!(0, fuzzed.to_millis() % Timestamp::minutes(10).to_millis());
+            assert!(fuzzed <= now);
+            assert!(fuzzed >= now - Timestamp::hours(1) - Timestamp::minutes(10));
+        }
+    }
+}
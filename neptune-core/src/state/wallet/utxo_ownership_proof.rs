@@ -0,0 +1,221 @@
+use std::collections::HashSet;
+
+use num_traits::CheckedAdd;
+use num_traits::Zero;
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Digest;
+use tasm_lib::prelude::Tip5;
+
+use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::protocol::consensus::transaction::utxo::Utxo;
+use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+use crate::util_types::mutator_set::ms_membership_proof::MsMembershipProof;
+use crate::util_types::mutator_set::mutator_set_accumulator::MutatorSetAccumulator;
+
+/// A UTXO disclosed in a [`UtxoOwnershipProof`], together with the opening of
+/// its commitment in the mutator set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisclosedUtxo {
+    pub utxo: Utxo,
+
+    /// Contains the sender randomness and the receiver preimage, which open
+    /// the UTXO's addition record, and the authentication paths showing that
+    /// the UTXO was unspent.
+    pub membership_proof: MsMembershipProof,
+}
+
+/// A statement that the wallet controlled a selection of UTXOs at a given
+/// block, which an auditor can verify offline with [`Self::verify`].
+///
+/// Control is demonstrated by knowledge of the receiver preimage, which only
+/// the recipient of a UTXO knows. The spending keys are not disclosed, so the
+/// proof does not enable the auditor to spend the UTXOs. It does enable them
+/// to recognize when the disclosed UTXOs are spent.
+///
+/// The verifier cannot check offline that the mutator set belongs to the
+/// claimed block. Auditors must compare [`UtxoOwnershipReport::block_digest`]
+/// and [`UtxoOwnershipReport::mutator_set_hash`] against a source of chain
+/// data they trust.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UtxoOwnershipProof {
+    pub block_digest: Digest,
+    pub block_height: BlockHeight,
+
+    /// The mutator set after the block.
+    pub mutator_set_accumulator: MutatorSetAccumulator,
+    pub utxos: Vec<DisclosedUtxo>,
+}
+
+/// A reason why a [`UtxoOwnershipProof`] does not verify.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+pub enum UtxoOwnershipProofError {
+    #[error("disclosed UTXO {index} is not an unspent member of the mutator set")]
+    NotUnspent { index: usize },
+
+    #[error("disclosed UTXO {index} duplicates an earlier one")]
+    DuplicateUtxo { index: usize },
+
+    #[error("disclosed amounts overflow")]
+    AmountOverflow,
+}
+
+/// A UTXO that a [`UtxoOwnershipProof`] shows to have been controlled by the
+/// prover.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifiedUtxo {
+    pub aocl_leaf_index: u64,
+    pub lock_script_hash: Digest,
+
+    /// Matches the receiver postimage of the address the UTXO was sent to.
+    pub receiver_digest: Digest,
+    pub amount: NativeCurrencyAmount,
+    pub release_date: Option<Timestamp>,
+}
+
+/// What an auditor learns from a valid [`UtxoOwnershipProof`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UtxoOwnershipReport {
+    pub block_digest: Digest,
+    pub block_height: BlockHeight,
+    pub mutator_set_hash: Digest,
+    pub utxos: Vec<VerifiedUtxo>,
+
+    /// The sum of the native currency amounts of all disclosed UTXOs.
+    pub total: NativeCurrencyAmount,
+}
+
+impl UtxoOwnershipProof {
+    /// Verify that every disclosed UTXO is an unspent member of the mutator
+    /// set, and that no UTXO is disclosed twice.
+    pub fn verify(&self) -> Result<UtxoOwnershipReport, UtxoOwnershipProofError> {
+        let mut aocl_leaf_indices = HashSet::new();
+        let mut utxos = vec![];
+        let mut total = NativeCurrencyAmount::zero();
+        for (
+            index,
+            DisclosedUtxo {
+                utxo,
+                membership_proof,
+            },
+        ) in self.utxos.iter().enumerate()
+        {
+            if !aocl_leaf_indices.insert(membership_proof.aocl_leaf_index) {
+                return Err(UtxoOwnershipProofError::DuplicateUtxo { index });
+            }
+
+            if !self
+                .mutator_set_accumulator
+                .verify(Tip5::hash(utxo), membership_proof)
+            {
+                return Err(UtxoOwnershipProofError::NotUnspent { index });
+            }
+
+            let amount = utxo.get_native_currency_amount();
+            total = total
+                .checked_add(&amount)
+                .ok_or(UtxoOwnershipProofError::AmountOverflow)?;
+            utxos.push(VerifiedUtxo {
+                aocl_leaf_index: membership_proof.aocl_leaf_index,
+                lock_script_hash: utxo.lock_script_hash(),
+                receiver_digest: membership_proof.receiver_preimage.hash(),
+                amount,
+                release_date: utxo.release_date(),
+            });
+        }
+
+        Ok(UtxoOwnershipReport {
+            block_digest: self.block_digest,
+            block_height: self.block_height,
+            mutator_set_hash: self.mutator_set_accumulator.hash(),
+            utxos,
+            total,
+        })
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use rand::random;
+
+    use super::*;
+    use crate::util_types::mutator_set::commit;
+
+    /// Add `utxo` to the mutator set and keep the membership proofs of
+    /// `disclosed` synced.
+    fn add_utxo(msa: &mut MutatorSetAccumulator, disclosed: &mut Vec<DisclosedUtxo>, utxo: Utxo) {
+        let item = Tip5::hash(&utxo);
+        let (sender_randomness, receiver_preimage) = (random(), random::<Digest>());
+        let addition_record = commit(item, sender_randomness, receiver_preimage.hash());
+        let membership_proof = msa.prove(item, sender_randomness, receiver_preimage);
+        for DisclosedUtxo {
+            utxo,
+            membership_proof,
+        } in disclosed.iter_mut()
+        {
+            membership_proof
+                .update_from_addition(Tip5::hash(utxo), msa, &addition_record)
+                .unwrap();
+        }
+        msa.add(&addition_record);
+
+        disclosed.push(DisclosedUtxo {
+            utxo,
+            membership_proof,
+        });
+    }
+
+    #[test]
+    fn ownership_of_unspent_utxos_verifies() {
+        let mut msa = MutatorSetAccumulator::default();
+        let mut disclosed = vec![];
+        let lock_script_hash = random();
+        for amount in [3, 4, 5] {
+            let utxo =
+                Utxo::new_native_currency(lock_script_hash, NativeCurrencyAmount::coins(amount));
+            add_utxo(&mut msa, &mut disclosed, utxo);
+        }
+
+        let mut proof = UtxoOwnershipProof {
+            block_digest: random(),
+            block_height: 3u64.into(),
+            mutator_set_accumulator: msa.clone(),
+            utxos: disclosed.clone(),
+        };
+        let report = proof.verify().unwrap();
+        assert_eq!(NativeCurrencyAmount::coins(12), report.total);
+        assert_eq!(msa.hash(), report.mutator_set_hash);
+        assert_eq!(
+            vec![0, 1, 2],
+            report
+                .utxos
+                .iter()
+                .map(|utxo| utxo.aocl_leaf_index)
+                .collect::<Vec<_>>()
+        );
+
+        proof.utxos.push(disclosed[1].clone());
+        assert_eq!(
+            Err(UtxoOwnershipProofError::DuplicateUtxo { index: 3 }),
+            proof.verify()
+        );
+
+        // A spent UTXO no longer verifies.
+        let spent = &disclosed[0];
+        let removal_record = msa.drop(Tip5::hash(&spent.utxo), &spent.membership_proof);
+        msa.remove(&removal_record);
+        proof.utxos.pop();
+        proof.mutator_set_accumulator = msa;
+        assert_eq!(
+            Err(UtxoOwnershipProofError::NotUnspent { index: 0 }),
+            proof.verify()
+        );
+
+        // Neither does a UTXO that is not the one committed to.
+        proof.utxos[0].utxo =
+            Utxo::new_native_currency(lock_script_hash, NativeCurrencyAmount::coins(30));
+        assert!(proof.verify().is_err());
+    }
+}
@@ -0,0 +1,1541 @@
+//! Encrypted, incremental backups of wallet recovery data, held by trusted
+//! peers.
+//!
+//! The data needed to recover the mutator set membership proofs of received
+//! UTXOs, see [`IncomingUtxoRecoveryData`], cannot be derived from the seed
+//! phrase. A node configured with
+//! [`wallet_backup_peers`](crate::application::config::cli_args::Args::wallet_backup_peers)
+//! replicates it to those peers, and holds their backups in return, so that
+//! it can be fetched back after the data directory is lost. Keys are never
+//! part of a backup.
+//!
+//! A backup is a chain of [`WalletBackupIncrement`]s, each holding the
+//! recovery data received since the previous one, encrypted with a key
+//! derived from the wallet's secret seed. The version of a backup is its
+//! number of increments. Each increment commits to the digest of its
+//! predecessor, and its version and predecessor are authenticated by the
+//! encryption, so a holder can neither reorder, drop, nor alter increments
+//! undetected. Holders know a backup only by its [ID](WalletBackupKey::id),
+//! which reveals nothing about the wallet.
+//!
+//! A node that lost its data directory may start a new backup before the peer
+//! holding the old one is reachable. The two backups then diverge. The node
+//! fetches the peer's backup in full, imports its recovery data, and keeps the
+//! longer of the two backups, sealing the recovery data missing from it on
+//! top. If it keeps its own backup, the peer's copy is replaced by it.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::SeekFrom;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::path::PathBuf;
+
+use aead::Aead;
+use aead::Key;
+use aead::KeyInit;
+use aead::Payload;
+use aes_gcm::Aes256Gcm;
+use aes_gcm::Nonce;
+use anyhow::anyhow;
+use anyhow::ensure;
+use anyhow::Context;
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Digest;
+use tasm_lib::prelude::Tip5;
+use tasm_lib::triton_vm::prelude::BFieldCodec;
+use tasm_lib::triton_vm::prelude::BFieldElement;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncSeekExt;
+use tokio::io::AsyncWriteExt;
+use tokio::io::BufReader;
+use tracing::info;
+use tracing::warn;
+
+use super::address::symmetric_key::SymmetricKey;
+use super::wallet_entropy::WalletEntropy;
+use super::wallet_state::IncomingUtxoRecoveryData;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+
+/// Maximum size in bytes of an increment's ciphertext. Small enough for
+/// [`MAX_NUM_INCREMENTS_PER_MESSAGE`] increments to fit in one peer message.
+pub(crate) const MAX_INCREMENT_CIPHERTEXT_SIZE: usize = 96 * 1024;
+
+/// Maximum number of increments sent in one peer message.
+pub(crate) const MAX_NUM_INCREMENTS_PER_MESSAGE: usize = 8;
+
+/// Maximum number of backups a node holds for its trusted peers.
+pub(crate) const MAX_NUM_HELD_BACKUPS: usize = 16;
+
+/// Maximum number of increments of a single held backup.
+pub(crate) const MAX_NUM_INCREMENTS_PER_BACKUP: u64 = 10_000;
+
+/// Maximum size in bytes of a single held backup, as persisted.
+pub(crate) const MAX_HELD_BACKUP_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Maximum size in bytes of all backups a node holds, as persisted.
+pub(crate) const MAX_TOTAL_HELD_BACKUPS_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Length in bytes of the authentication tag appended to ciphertexts.
+const AUTHENTICATION_TAG_SIZE: usize = 16;
+
+/// Length in bytes of the length prefix of bincode-encoded vectors.
+const VEC_LENGTH_PREFIX_SIZE: usize = 8;
+
+const BACKUP_FILE_EXTENSION: &str = "jsonl";
+
+/// The most recent increment of a backup. The default is the head of the
+/// empty backup.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletBackupHead {
+    /// Number of increments in the backup.
+    pub version: u64,
+
+    /// Digest of the most recent increment.
+    pub digest: Digest,
+}
+
+/// The recovery data received since the previous increment of a backup,
+/// encrypted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct WalletBackupIncrement {
+    pub(crate) version: u64,
+
+    /// Digest of the preceding increment, or the default digest for the first
+    /// increment.
+    pub(crate) previous: Digest,
+    nonce: [u8; 12],
+    ciphertext: Vec<u8>,
+}
+
+impl WalletBackupIncrement {
+    pub(crate) fn digest(&self) -> Digest {
+        let bytes = [self.nonce.as_slice(), &self.ciphertext].concat();
+        Tip5::hash_varlen(
+            &[
+                vec![BFieldElement::new(self.version)],
+                self.previous.encode(),
+                bytes_to_bfes(&bytes),
+            ]
+            .concat(),
+        )
+    }
+
+    pub(crate) fn head(&self) -> WalletBackupHead {
+        WalletBackupHead {
+            version: self.version,
+            digest: self.digest(),
+        }
+    }
+}
+
+/// Length-prefixed, with one element per 4 bytes, so that every element is
+/// canonical.
+fn bytes_to_bfes(bytes: &[u8]) -> Vec<BFieldElement> {
+    let limbs = bytes.chunks(4).map(|chunk| {
+        let mut limb = [0u8; 4];
+        limb[..chunk.len()].copy_from_slice(chunk);
+        BFieldElement::new(u32::from_le_bytes(limb).into())
+    });
+
+    [BFieldElement::new(bytes.len() as u64)]
+        .into_iter()
+        .chain(limbs)
+        .collect()
+}
+
+/// The data authenticated along with an increment's ciphertext.
+fn associated_data(version: u64, previous: Digest) -> Vec<u8> {
+    [version]
+        .into_iter()
+        .chain(previous.values().iter().map(|element| element.value()))
+        .flat_map(u64::to_le_bytes)
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub(crate) enum WalletBackupError {
+    #[error("increment has version {actual}, expected {expected}")]
+    UnexpectedVersion { expected: u64, actual: u64 },
+
+    #[error("increment {0} does not extend the backup")]
+    BrokenChain(u64),
+
+    #[error("increment {0} exceeds {MAX_INCREMENT_CIPHERTEXT_SIZE} bytes")]
+    TooLarge(u64),
+
+    #[error("increment {0} could not be decrypted")]
+    Undecryptable(u64),
+
+    #[error("already holding the maximum of {MAX_NUM_HELD_BACKUPS} backups")]
+    TooManyBackups,
+
+    #[error("backup would exceed the maximum of {MAX_NUM_INCREMENTS_PER_BACKUP} increments")]
+    TooManyIncrements,
+
+    #[error("backup would exceed the maximum of {MAX_HELD_BACKUP_SIZE} bytes")]
+    BackupTooLarge,
+
+    #[error("held backups would exceed the maximum of {MAX_TOTAL_HELD_BACKUPS_SIZE} bytes")]
+    OutOfSpace,
+
+    #[error("could not persist backup: {0}")]
+    Persistence(String),
+}
+
+/// Check that `increments` extend, in order, the backup whose most recent
+/// increment is `head`. Returns the head of the extended backup.
+pub(crate) fn verify_extension(
+    head: WalletBackupHead,
+    increments: &[WalletBackupIncrement],
+) -> Result<WalletBackupHead, WalletBackupError> {
+    let mut head = head;
+    for increment in increments {
+        let expected = head.version + 1;
+        if increment.version != expected {
+            return Err(WalletBackupError::UnexpectedVersion {
+                expected,
+                actual: increment.version,
+            });
+        }
+        if increment.previous != head.digest {
+            return Err(WalletBackupError::BrokenChain(increment.version));
+        }
+        if increment.ciphertext.len() > MAX_INCREMENT_CIPHERTEXT_SIZE {
+            return Err(WalletBackupError::TooLarge(increment.version));
+        }
+        head = increment.head();
+    }
+
+    Ok(head)
+}
+
+/// The key that encrypts a wallet's backup, and the ID under which peers hold
+/// it. Both are derived from the wallet's secret seed, so that they can be
+/// derived again after restoring the wallet from its seed phrase.
+#[derive(Clone)]
+pub(crate) struct WalletBackupKey {
+    id: Digest,
+    cipher_key: Key<Aes256Gcm>,
+}
+
+impl WalletBackupKey {
+    pub(crate) fn new(wallet_entropy: &WalletEntropy) -> Self {
+        let secret = wallet_entropy.wallet_backup_secret();
+        Self {
+            id: secret.hash(),
+            cipher_key: SymmetricKey::from_seed(secret).secret_key(),
+        }
+    }
+
+    /// The ID under which peers hold the backup.
+    pub(crate) fn id(&self) -> Digest {
+        self.id
+    }
+
+    /// Encrypt recovery data into the increment following `previous`.
+    pub(crate) fn seal(
+        &self,
+        previous: WalletBackupHead,
+        entries: &[IncomingUtxoRecoveryData],
+    ) -> anyhow::Result<WalletBackupIncrement> {
+        let version = previous.version + 1;
+        let nonce: [u8; 12] = rand::random();
+        let plaintext = bincode::serialize(entries)?;
+        let ciphertext = Aes256Gcm::new(&self.cipher_key)
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &plaintext,
+                    aad: &associated_data(version, previous.digest),
+                },
+            )
+            .map_err(|_| anyhow!("Could not encrypt wallet backup increment {version}"))?;
+        ensure!(
+            ciphertext.len() <= MAX_INCREMENT_CIPHERTEXT_SIZE,
+            "Wallet backup increment {version} exceeds {MAX_INCREMENT_CIPHERTEXT_SIZE} bytes"
+        );
+
+        Ok(WalletBackupIncrement {
+            version,
+            previous: previous.digest,
+            nonce,
+            ciphertext,
+        })
+    }
+
+    /// Decrypt the recovery data of an increment. Fails if the increment was
+    /// not sealed with this key, or was altered since.
+    pub(crate) fn open(
+        &self,
+        increment: &WalletBackupIncrement,
+    ) -> Result<Vec<IncomingUtxoRecoveryData>, WalletBackupError> {
+        let undecryptable = WalletBackupError::Undecryptable(increment.version);
+        let plaintext = Aes256Gcm::new(&self.cipher_key)
+            .decrypt(
+                Nonce::from_slice(&increment.nonce),
+                Payload {
+                    msg: &increment.ciphertext,
+                    aad: &associated_data(increment.version, increment.previous),
+                },
+            )
+            .map_err(|_| undecryptable.clone())?;
+
+        bincode::deserialize(&plaintext).map_err(|_| undecryptable)
+    }
+}
+
+/// Split recovery data into consecutive groups that each fit in one
+/// increment.
+fn increment_groups(
+    entries: &[IncomingUtxoRecoveryData],
+) -> anyhow::Result<Vec<&[IncomingUtxoRecoveryData]>> {
+    const MAX_PLAINTEXT_SIZE: usize =
+        MAX_INCREMENT_CIPHERTEXT_SIZE - AUTHENTICATION_TAG_SIZE - VEC_LENGTH_PREFIX_SIZE;
+
+    let mut groups = vec![];
+    let mut start = 0;
+    let mut group_size = 0;
+    for (i, entry) in entries.iter().enumerate() {
+        let entry_size = usize::try_from(bincode::serialized_size(entry)?)?;
+        ensure!(
+            entry_size <= MAX_PLAINTEXT_SIZE,
+            "Recovery data of UTXO with AOCL index {} is too large to back up",
+            entry.aocl_index
+        );
+        if group_size + entry_size > MAX_PLAINTEXT_SIZE {
+            groups.push(&entries[start..i]);
+            start = i;
+            group_size = 0;
+        }
+        group_size += entry_size;
+    }
+    if start < entries.len() {
+        groups.push(&entries[start..]);
+    }
+
+    Ok(groups)
+}
+
+/// A backup held by a trusted peer that diverges from the wallet's backup, eg
+/// because this node lost its data directory and started a new backup before
+/// the peer holding the old one was reachable. It is fetched in full, so that
+/// its recovery data is not lost.
+#[derive(Debug, Clone)]
+struct DivergingBackup {
+    peer: SocketAddr,
+
+    /// The head of the backup, as reported by the peer.
+    peer_head: WalletBackupHead,
+    increments: Vec<WalletBackupIncrement>,
+    entries: Vec<IncomingUtxoRecoveryData>,
+
+    /// Total size in bytes of the ciphertexts of the increments.
+    size: u64,
+}
+
+impl DivergingBackup {
+    fn head(&self) -> WalletBackupHead {
+        self.increments
+            .last()
+            .map(WalletBackupIncrement::head)
+            .unwrap_or_default()
+    }
+}
+
+/// The outcome of [`WalletBackup::receive`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ReceivedIncrements {
+    /// Recovery data the backup did not hold before, to be imported into the
+    /// wallet.
+    pub(crate) entries: Vec<IncomingUtxoRecoveryData>,
+
+    /// The version after which to fetch more increments from the peer, if
+    /// any.
+    pub(crate) fetch_after: Option<u64>,
+
+    /// Whether the backup the peer holds diverges from this one, and was
+    /// merged into it, such that the peer's copy is to be replaced.
+    pub(crate) replace: bool,
+}
+
+/// The backup of this node's own wallet, and its replication to the trusted
+/// peers.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WalletBackup {
+    increments: Vec<WalletBackupIncrement>,
+
+    /// Digests of the recovery data entries in the increments.
+    backed_up: HashSet<Digest>,
+
+    /// Where increments are persisted. Nothing is persisted if unset.
+    file: Option<PathBuf>,
+
+    /// Length of the prefix of the wallet's incoming secrets file that was
+    /// read already, and the digests of the recovery data in it.
+    num_read_bytes: u64,
+    known: HashSet<Digest>,
+
+    /// Recovery data read from the wallet's incoming secrets file that is not
+    /// backed up yet.
+    unsealed: Vec<IncomingUtxoRecoveryData>,
+
+    /// The diverging backup being fetched from a trusted peer, if any.
+    diverging: Option<DivergingBackup>,
+
+    /// Heads of diverging backups whose recovery data was merged already.
+    merged: HashSet<Digest>,
+
+    /// Heads of the backup held by trusted peers, as last reported by them.
+    peer_heads: HashMap<SocketAddr, WalletBackupHead>,
+    last_restored: Option<Timestamp>,
+    last_error: Option<String>,
+}
+
+impl WalletBackup {
+    /// Read the increments persisted in the given file, if it exists.
+    ///
+    /// Reading stops at the first increment that does not extend the backup
+    /// or cannot be decrypted, e.g. because it was only partially written.
+    /// The file is then rewritten without it and its successors.
+    pub(crate) async fn load(file: PathBuf, key: &WalletBackupKey) -> anyhow::Result<Self> {
+        let mut backup = Self::default();
+        let increments = read_increments(&file).await?;
+        let num_persisted_increments = increments.len();
+        for increment in increments {
+            let Ok(entries) = verify_extension(backup.head(), std::slice::from_ref(&increment))
+                .and_then(|_| key.open(&increment))
+            else {
+                warn!(
+                    "Discarding wallet backup from increment {} on, in {}",
+                    increment.version,
+                    file.display()
+                );
+                break;
+            };
+            backup.backed_up.extend(entries.iter().map(Tip5::hash));
+            backup.increments.push(increment);
+        }
+
+        if backup.increments.len() < num_persisted_increments {
+            write_increments(&file, &backup.increments).await?;
+        }
+        backup.file = Some(file);
+
+        Ok(backup)
+    }
+
+    pub(crate) fn head(&self) -> WalletBackupHead {
+        self.increments
+            .last()
+            .map(WalletBackupIncrement::head)
+            .unwrap_or_default()
+    }
+
+    /// The head of the backup as of the given version, if it has that many
+    /// increments.
+    pub(crate) fn head_at(&self, version: u64) -> Option<WalletBackupHead> {
+        if version == 0 {
+            return Some(WalletBackupHead::default());
+        }
+
+        let index = usize::try_from(version - 1).ok()?;
+        self.increments.get(index).map(WalletBackupIncrement::head)
+    }
+
+    /// Whether the backup held by a peer diverges from this one, as far as
+    /// can be told from its head. A peer that is ahead may diverge, too,
+    /// which shows once its increments are fetched.
+    pub(crate) fn diverges_from(&self, peer_head: WalletBackupHead) -> bool {
+        peer_head.version <= self.head().version
+            && self.head_at(peer_head.version) != Some(peer_head)
+    }
+
+    /// The increments following the given version, at most as many as fit in
+    /// one peer message.
+    pub(crate) fn increments_after(&self, version: u64) -> Vec<WalletBackupIncrement> {
+        let skip = usize::try_from(version).unwrap_or(usize::MAX);
+        self.increments
+            .iter()
+            .skip(skip)
+            .take(MAX_NUM_INCREMENTS_PER_MESSAGE)
+            .cloned()
+            .collect()
+    }
+
+    /// Read the recovery data appended to the wallet's incoming secrets file
+    /// since it was last read. Only complete lines are read, so that an entry
+    /// that is being written is read in full next time.
+    async fn read_recovery_data(&mut self, incoming_secrets_path: &Path) -> anyhow::Result<()> {
+        let mut file = match tokio::fs::File::open(incoming_secrets_path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Could not read {}", incoming_secrets_path.display()))
+            }
+        };
+        file.seek(SeekFrom::Start(self.num_read_bytes)).await?;
+
+        let mut reader = BufReader::new(file);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let num_bytes = reader.read_line(&mut line).await?;
+            if num_bytes == 0 || !line.ends_with('\n') {
+                break;
+            }
+            let entry: IncomingUtxoRecoveryData = serde_json::from_str(&line)
+                .with_context(|| format!("Could not parse {}", incoming_secrets_path.display()))?;
+            self.num_read_bytes += u64::try_from(num_bytes)?;
+            let digest = Tip5::hash(&entry);
+            if self.known.insert(digest) && !self.backed_up.contains(&digest) {
+                self.unsealed.push(entry);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Seal the recovery data the wallet received since the last increment
+    /// into new increments. The wallet's incoming secrets file is only read
+    /// from where it was read last time. Returns the number of new
+    /// increments.
+    pub(crate) async fn seal(
+        &mut self,
+        key: &WalletBackupKey,
+        incoming_secrets_path: &Path,
+    ) -> anyhow::Result<usize> {
+        self.read_recovery_data(incoming_secrets_path).await?;
+        let entries = std::mem::take(&mut self.unsealed);
+        let sealed = self.seal_new_entries(key, &entries).await;
+        if sealed.is_err() {
+            self.unsealed = entries;
+        }
+
+        sealed
+    }
+
+    /// Seal the recovery data that is not backed up yet into new increments.
+    ///
+    /// `entries` is any of the wallet's recovery data, in any order. Returns
+    /// the number of new increments.
+    async fn seal_new_entries(
+        &mut self,
+        key: &WalletBackupKey,
+        entries: &[IncomingUtxoRecoveryData],
+    ) -> anyhow::Result<usize> {
+        let mut seen = HashSet::new();
+        let new_entries = entries
+            .iter()
+            .filter(|entry| {
+                let digest = Tip5::hash(*entry);
+                !self.backed_up.contains(&digest) && seen.insert(digest)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut head = self.head();
+        let mut increments = vec![];
+        for group in increment_groups(&new_entries)? {
+            let increment = key.seal(head, group)?;
+            head = increment.head();
+            increments.push(increment);
+        }
+
+        let num_increments = increments.len();
+        self.append(increments, &new_entries).await?;
+
+        Ok(num_increments)
+    }
+
+    /// The recovery data the wallet's incoming secrets file does not hold.
+    pub(crate) async fn unknown_to_wallet(
+        &mut self,
+        incoming_secrets_path: &Path,
+        entries: Vec<IncomingUtxoRecoveryData>,
+    ) -> anyhow::Result<Vec<IncomingUtxoRecoveryData>> {
+        self.read_recovery_data(incoming_secrets_path).await?;
+
+        Ok(entries
+            .into_iter()
+            .filter(|entry| !self.known.contains(&Tip5::hash(entry)))
+            .collect())
+    }
+
+    /// Handle increments a trusted peer sent in answer to a fetch.
+    ///
+    /// Increments extending the backup are appended. If they do not extend
+    /// it, the peer's backup diverges, and is fetched in full from its first
+    /// increment on. Once complete, it is merged with the backup, see
+    /// [`Self::merge`].
+    pub(crate) async fn receive(
+        &mut self,
+        key: &WalletBackupKey,
+        peer: SocketAddr,
+        increments: Vec<WalletBackupIncrement>,
+    ) -> anyhow::Result<ReceivedIncrements> {
+        if self
+            .diverging
+            .as_ref()
+            .is_some_and(|diverging| diverging.peer == peer)
+        {
+            return self.receive_diverging(key, increments).await;
+        }
+
+        let version = self.head().version;
+        match self.extend(key, increments).await {
+            Ok(entries) => {
+                // Fetch more as long as the peer is ahead and makes progress.
+                let new_version = self.head().version;
+                let peer_version = self
+                    .peer_head(peer)
+                    .map(|head| head.version)
+                    .unwrap_or_default();
+                let fetch_after =
+                    (new_version > version && peer_version > new_version).then_some(new_version);
+
+                Ok(ReceivedIncrements {
+                    entries,
+                    fetch_after,
+                    replace: false,
+                })
+            }
+            Err(WalletBackupError::BrokenChain(_)) if self.fetch_diverging(peer) => {
+                Ok(ReceivedIncrements {
+                    entries: vec![],
+                    fetch_after: Some(0),
+                    replace: false,
+                })
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Whether a diverging backup is being fetched. No new recovery data is
+    /// sealed meanwhile, as the backup might be replaced.
+    pub(crate) fn is_fetching_diverging_backup(&self) -> bool {
+        self.diverging.is_some()
+    }
+
+    /// Whether the recovery data of the diverging backup with the given head
+    /// was merged into this backup already, such that the peer holding it
+    /// can be sent this backup to replace it.
+    pub(crate) fn has_merged(&self, peer_head: WalletBackupHead) -> bool {
+        self.merged.contains(&peer_head.digest)
+    }
+
+    /// Start fetching the diverging backup the given peer holds, unless
+    /// another one is being fetched, or its recovery data was merged already.
+    /// Returns whether to fetch it.
+    pub(crate) fn fetch_diverging(&mut self, peer: SocketAddr) -> bool {
+        let Some(peer_head) = self.peer_head(peer) else {
+            return false;
+        };
+        if self.diverging.is_some() || self.has_merged(peer_head) {
+            return false;
+        }
+
+        self.diverging = Some(DivergingBackup {
+            peer,
+            peer_head,
+            increments: vec![],
+            entries: vec![],
+            size: 0,
+        });
+
+        true
+    }
+
+    async fn receive_diverging(
+        &mut self,
+        key: &WalletBackupKey,
+        increments: Vec<WalletBackupIncrement>,
+    ) -> anyhow::Result<ReceivedIncrements> {
+        let Some(mut diverging) = self.diverging.take() else {
+            return Ok(ReceivedIncrements::default());
+        };
+
+        let head = diverging.head();
+        let increments = increments
+            .into_iter()
+            .filter(|increment| increment.version > head.version)
+            .collect::<Vec<_>>();
+        ensure!(
+            !increments.is_empty(),
+            "{} stopped serving its wallet backup at version {}",
+            diverging.peer,
+            head.version
+        );
+        verify_extension(head, &increments)?;
+        for increment in increments {
+            diverging.size += u64::try_from(increment.ciphertext.len())?;
+            ensure!(
+                diverging.size <= MAX_HELD_BACKUP_SIZE,
+                "Wallet backup held by {} exceeds {MAX_HELD_BACKUP_SIZE} bytes",
+                diverging.peer
+            );
+            diverging.entries.extend(key.open(&increment)?);
+            diverging.increments.push(increment);
+        }
+
+        let version = diverging.head().version;
+        if version < diverging.peer_head.version {
+            self.diverging = Some(diverging);
+            return Ok(ReceivedIncrements {
+                entries: vec![],
+                fetch_after: Some(version),
+                replace: false,
+            });
+        }
+
+        let peer_head = diverging.peer_head;
+        let entries = self.merge(diverging).await?;
+        Ok(ReceivedIncrements {
+            entries,
+            fetch_after: None,
+            replace: self.has_merged(peer_head),
+        })
+    }
+
+    /// Merge a diverging backup that was fetched in full. The longer of the
+    /// two is kept. The recovery data of the other one is sealed on top of it
+    /// with the wallet's next received UTXOs, as all of it is in the wallet's
+    /// incoming secrets file, once imported. If this backup is kept, the
+    /// diverging one is recorded as merged, see [`Self::has_merged`]. Returns
+    /// the recovery data of the diverging backup that this backup did not
+    /// hold.
+    async fn merge(
+        &mut self,
+        diverging: DivergingBackup,
+    ) -> Result<Vec<IncomingUtxoRecoveryData>, WalletBackupError> {
+        let new_entries = diverging
+            .entries
+            .iter()
+            .filter(|entry| !self.backed_up.contains(&Tip5::hash(*entry)))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let head = diverging.head();
+        if head.version > self.head().version {
+            if let Some(file) = &self.file {
+                write_increments(file, &diverging.increments)
+                    .await
+                    .map_err(|e| WalletBackupError::Persistence(format!("{e:#}")))?;
+            }
+            self.increments = diverging.increments;
+            self.backed_up = diverging.entries.iter().map(Tip5::hash).collect();
+
+            // Read the wallet's incoming secrets file from the start, to seal
+            // the recovery data of the replaced increments again.
+            self.num_read_bytes = 0;
+            self.known.clear();
+            self.unsealed.clear();
+        } else {
+            self.merged.insert(diverging.peer_head.digest);
+        }
+        info!(
+            "Merged diverging wallet backup of version {} held by {}, keeping version {}",
+            head.version,
+            diverging.peer,
+            self.head().version
+        );
+
+        Ok(new_entries)
+    }
+
+    /// Append increments fetched from a peer, after checking that they extend
+    /// the backup and decrypting them. Increments the backup has already are
+    /// skipped. Returns the recovery data of the appended increments.
+    pub(crate) async fn extend(
+        &mut self,
+        key: &WalletBackupKey,
+        increments: Vec<WalletBackupIncrement>,
+    ) -> Result<Vec<IncomingUtxoRecoveryData>, WalletBackupError> {
+        let version = self.head().version;
+        let increments = increments
+            .into_iter()
+            .filter(|increment| increment.version > version)
+            .collect::<Vec<_>>();
+        verify_extension(self.head(), &increments)?;
+
+        let mut entries = vec![];
+        for increment in &increments {
+            entries.extend(key.open(increment)?);
+        }
+        self.append(increments, &entries)
+            .await
+            .map_err(|e| WalletBackupError::Persistence(format!("{e:#}")))?;
+
+        Ok(entries)
+    }
+
+    async fn append(
+        &mut self,
+        increments: Vec<WalletBackupIncrement>,
+        entries: &[IncomingUtxoRecoveryData],
+    ) -> anyhow::Result<()> {
+        if increments.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(file) = &self.file {
+            append_increments(file, &increments).await?;
+        }
+        self.increments.extend(increments);
+        self.backed_up.extend(entries.iter().map(Tip5::hash));
+
+        Ok(())
+    }
+
+    /// Number of recovery data entries in the backup.
+    pub(crate) fn num_entries(&self) -> usize {
+        self.backed_up.len()
+    }
+
+    pub(crate) fn record_peer_head(&mut self, peer: SocketAddr, head: WalletBackupHead) {
+        self.peer_heads.insert(peer, head);
+    }
+
+    pub(crate) fn peer_head(&self, peer: SocketAddr) -> Option<WalletBackupHead> {
+        self.peer_heads.get(&peer).copied()
+    }
+
+    pub(crate) fn record_restored(&mut self, now: Timestamp) {
+        self.last_restored = Some(now);
+        self.last_error = None;
+    }
+
+    pub(crate) fn record_error(&mut self, error: String) {
+        self.last_error = Some(error);
+    }
+
+    pub(crate) fn status(&self, num_held_backups: usize) -> WalletBackupStatus {
+        let head = self.head();
+        let mut peers = self
+            .peer_heads
+            .iter()
+            .map(|(&address, peer_head)| WalletBackupPeer {
+                address,
+                version: peer_head.version,
+                in_sync: *peer_head == head,
+            })
+            .collect::<Vec<_>>();
+        peers.sort_by_key(|peer| peer.address);
+
+        WalletBackupStatus {
+            version: head.version,
+            num_entries: self.num_entries(),
+            peers,
+            num_held_backups,
+            last_restored: self.last_restored,
+            last_error: self.last_error.clone(),
+        }
+    }
+}
+
+/// The state of the wallet's backup to trusted peers, as reported over RPC.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletBackupStatus {
+    /// Number of increments of the wallet's backup.
+    pub version: u64,
+
+    /// Number of received UTXOs whose recovery data is backed up.
+    pub num_entries: usize,
+
+    /// The trusted peers that reported which version of the backup they hold.
+    pub peers: Vec<WalletBackupPeer>,
+
+    /// Number of backups this node holds for its trusted peers.
+    pub num_held_backups: usize,
+
+    /// When recovery data was last fetched back from a trusted peer.
+    pub last_restored: Option<Timestamp>,
+    pub last_error: Option<String>,
+}
+
+/// A trusted peer holding the wallet's backup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletBackupPeer {
+    pub address: SocketAddr,
+
+    /// The version of the backup the peer holds.
+    pub version: u64,
+
+    /// Whether the peer holds the same version as this node.
+    pub in_sync: bool,
+}
+
+/// A backup held for a trusted peer. Only its head and the offsets of its
+/// increments are kept in memory, the increments themselves are served from
+/// disk.
+#[derive(Debug, Clone, Default)]
+struct HeldBackup {
+    head: WalletBackupHead,
+
+    /// Offset of each increment in the backup's file, in order.
+    offsets: Vec<u64>,
+
+    /// Size in bytes of the backup's file.
+    size: u64,
+}
+
+impl HeldBackup {
+    /// Read the backup persisted in the given file. Holders cannot decrypt
+    /// backups, so only the chain is checked. The file is truncated before
+    /// the first increment that does not extend the backup.
+    async fn load(path: &Path) -> anyhow::Result<Self> {
+        let file = tokio::fs::File::open(path)
+            .await
+            .with_context(|| format!("Could not read {}", path.display()))?;
+        let file_size = file.metadata().await?.len();
+
+        let mut backup = Self::default();
+        let mut reader = BufReader::new(file);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let num_bytes = reader.read_line(&mut line).await?;
+            if num_bytes == 0 || !line.ends_with('\n') {
+                break;
+            }
+            let Ok(increment) = serde_json::from_str::<WalletBackupIncrement>(&line) else {
+                break;
+            };
+            if verify_extension(backup.head, std::slice::from_ref(&increment)).is_err() {
+                break;
+            }
+            backup.offsets.push(backup.size);
+            backup.size += u64::try_from(num_bytes)?;
+            backup.head = increment.head();
+        }
+
+        if backup.size < file_size {
+            warn!("Truncating held wallet backup in {}", path.display());
+            truncate(path, backup.size).await?;
+        }
+
+        Ok(backup)
+    }
+}
+
+/// The backups this node holds for its trusted peers, by backup ID.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HeldWalletBackups {
+    backups: HashMap<Digest, HeldBackup>,
+
+    /// Where backups are persisted, one file per backup. No backups are held
+    /// if unset.
+    directory: Option<PathBuf>,
+}
+
+impl HeldWalletBackups {
+    /// Read the backups persisted in the given directory, if it exists.
+    pub(crate) async fn load(directory: PathBuf) -> anyhow::Result<Self> {
+        let mut backups = HashMap::new();
+        if tokio::fs::try_exists(&directory).await? {
+            let mut entries = tokio::fs::read_dir(&directory)
+                .await
+                .with_context(|| format!("Could not read {}", directory.display()))?;
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if path.extension().and_then(|extension| extension.to_str())
+                    != Some(BACKUP_FILE_EXTENSION)
+                {
+                    continue;
+                }
+                let Some(id) = path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .and_then(|stem| Digest::try_from_hex(stem).ok())
+                else {
+                    warn!("Ignoring unexpected file {}", path.display());
+                    continue;
+                };
+
+                backups.insert(id, HeldBackup::load(&path).await?);
+            }
+        }
+
+        Ok(Self {
+            backups,
+            directory: Some(directory),
+        })
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.backups.len()
+    }
+
+    pub(crate) fn head(&self, id: Digest) -> WalletBackupHead {
+        self.backups
+            .get(&id)
+            .map(|backup| backup.head)
+            .unwrap_or_default()
+    }
+
+    /// Total size in bytes of the held backups.
+    fn size(&self) -> u64 {
+        self.backups.values().map(|backup| backup.size).sum()
+    }
+
+    fn file(&self, id: Digest) -> Option<PathBuf> {
+        self.directory.as_ref().map(|directory| {
+            directory
+                .join(id.to_hex())
+                .with_extension(BACKUP_FILE_EXTENSION)
+        })
+    }
+
+    /// The increments of the backup following the given version, at most as
+    /// many as fit in one peer message. They are read from disk.
+    pub(crate) async fn increments_after(
+        &self,
+        id: Digest,
+        version: u64,
+    ) -> anyhow::Result<Vec<WalletBackupIncrement>> {
+        let offset = usize::try_from(version).ok().and_then(|index| {
+            self.backups
+                .get(&id)
+                .and_then(|backup| backup.offsets.get(index))
+        });
+        let (Some(&offset), Some(path)) = (offset, self.file(id)) else {
+            return Ok(vec![]);
+        };
+
+        let mut file = tokio::fs::File::open(&path)
+            .await
+            .with_context(|| format!("Could not read {}", path.display()))?;
+        file.seek(SeekFrom::Start(offset)).await?;
+        let mut lines = BufReader::new(file).lines();
+        let mut increments = vec![];
+        while increments.len() < MAX_NUM_INCREMENTS_PER_MESSAGE {
+            let Some(line) = lines.next_line().await? else {
+                break;
+            };
+            increments.push(serde_json::from_str(&line)?);
+        }
+
+        Ok(increments)
+    }
+
+    /// Append increments to the backup with the given ID. Returns the head of
+    /// the extended backup.
+    pub(crate) async fn store(
+        &mut self,
+        id: Digest,
+        increments: Vec<WalletBackupIncrement>,
+    ) -> Result<WalletBackupHead, WalletBackupError> {
+        if increments.is_empty() {
+            return Ok(self.head(id));
+        }
+        let backup = self.backups.get(&id).cloned().unwrap_or_default();
+
+        self.write(id, backup, increments).await
+    }
+
+    /// Replace the backup with the given ID by the one starting with the
+    /// given increments, eg because the owner merged it into a diverging
+    /// backup. Returns the head of the new backup.
+    pub(crate) async fn replace(
+        &mut self,
+        id: Digest,
+        increments: Vec<WalletBackupIncrement>,
+    ) -> Result<WalletBackupHead, WalletBackupError> {
+        self.write(id, HeldBackup::default(), increments).await
+    }
+
+    /// Append increments to `backup`, and hold the result as the backup with
+    /// the given ID.
+    async fn write(
+        &mut self,
+        id: Digest,
+        mut backup: HeldBackup,
+        increments: Vec<WalletBackupIncrement>,
+    ) -> Result<WalletBackupHead, WalletBackupError> {
+        let (Some(directory), Some(path)) = (self.directory.clone(), self.file(id)) else {
+            return Err(WalletBackupError::Persistence(
+                "no directory for held backups".to_string(),
+            ));
+        };
+        if !self.backups.contains_key(&id) && self.backups.len() >= MAX_NUM_HELD_BACKUPS {
+            return Err(WalletBackupError::TooManyBackups);
+        }
+
+        let head = verify_extension(backup.head, &increments)?;
+        if head.version > MAX_NUM_INCREMENTS_PER_BACKUP {
+            return Err(WalletBackupError::TooManyIncrements);
+        }
+
+        let persistence_error = |e: anyhow::Error| WalletBackupError::Persistence(format!("{e:#}"));
+        let lines = increments
+            .iter()
+            .map(|increment| to_lines(std::slice::from_ref(increment)))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map_err(persistence_error)?;
+        let num_new_bytes = lines.iter().map(|line| line.len() as u64).sum::<u64>();
+        if backup.size + num_new_bytes > MAX_HELD_BACKUP_SIZE {
+            return Err(WalletBackupError::BackupTooLarge);
+        }
+        let held_size = self
+            .backups
+            .get(&id)
+            .map(|held| held.size)
+            .unwrap_or_default();
+        if self.size() - held_size + backup.size + num_new_bytes > MAX_TOTAL_HELD_BACKUPS_SIZE {
+            return Err(WalletBackupError::OutOfSpace);
+        }
+
+        tokio::fs::create_dir_all(&directory)
+            .await
+            .map_err(|e| WalletBackupError::Persistence(e.to_string()))?;
+        write_at(&path, backup.size, &lines.concat())
+            .await
+            .map_err(persistence_error)?;
+
+        for line in lines {
+            backup.offsets.push(backup.size);
+            backup.size += line.len() as u64;
+        }
+        backup.head = head;
+        self.backups.insert(id, backup);
+
+        Ok(head)
+    }
+}
+
+/// Read the increments in a file holding one JSON object per line. Lines
+/// that cannot be parsed end the backup.
+async fn read_increments(path: &Path) -> anyhow::Result<Vec<WalletBackupIncrement>> {
+    let contents = match tokio::fs::read_to_string(path).await {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e).with_context(|| format!("Could not read {}", path.display())),
+    };
+
+    Ok(contents
+        .lines()
+        .map_while(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+async fn write_increments(path: &Path, increments: &[WalletBackupIncrement]) -> anyhow::Result<()> {
+    tokio::fs::write(path, to_lines(increments)?)
+        .await
+        .with_context(|| format!("Could not write {}", path.display()))
+}
+
+async fn append_increments(
+    path: &Path,
+    increments: &[WalletBackupIncrement],
+) -> anyhow::Result<()> {
+    let mut file = tokio::fs::OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path)
+        .await
+        .with_context(|| format!("Could not open {}", path.display()))?;
+    file.write_all(to_lines(increments)?.as_bytes())
+        .await
+        .with_context(|| format!("Could not write {}", path.display()))?;
+    file.flush().await?;
+
+    Ok(())
+}
+
+/// Write `contents` to the file at the given offset, dropping whatever
+/// follows it, eg the remains of a write that failed midway.
+async fn write_at(path: &Path, offset: u64, contents: &str) -> anyhow::Result<()> {
+    let mut file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .await
+        .with_context(|| format!("Could not open {}", path.display()))?;
+    file.set_len(offset).await?;
+    file.seek(SeekFrom::Start(offset)).await?;
+    file.write_all(contents.as_bytes())
+        .await
+        .with_context(|| format!("Could not write {}", path.display()))?;
+    file.flush().await?;
+
+    Ok(())
+}
+
+async fn truncate(path: &Path, size: u64) -> anyhow::Result<()> {
+    tokio::fs::OpenOptions::new()
+        .write(true)
+        .open(path)
+        .await
+        .with_context(|| format!("Could not open {}", path.display()))?
+        .set_len(size)
+        .await
+        .with_context(|| format!("Could not truncate {}", path.display()))
+}
+
+fn to_lines(increments: &[WalletBackupIncrement]) -> anyhow::Result<String> {
+    increments
+        .iter()
+        .map(|increment| serde_json::to_string(increment).map(|line| line + "\n"))
+        .collect::<Result<String, _>>()
+        .map_err(Into::into)
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use macro_rules_attr::apply;
+    use rand::random;
+
+    use super::*;
+    use crate::application::config::network::Network;
+    use crate::protocol::consensus::transaction::utxo::Utxo;
+    use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
+    use crate::tests::shared::files::unit_test_data_directory;
+    use crate::tests::shared_tokio_runtime;
+
+    fn recovery_data(aocl_index: u64) -> IncomingUtxoRecoveryData {
+        IncomingUtxoRecoveryData {
+            utxo: Utxo::new_native_currency(random(), NativeCurrencyAmount::coins(1)),
+            sender_randomness: random(),
+            receiver_preimage: random(),
+            aocl_index,
+        }
+    }
+
+    #[test]
+    fn increments_are_authenticated() {
+        let key = WalletBackupKey::new(&WalletEntropy::new_random());
+        let entries = vec![recovery_data(0), recovery_data(1)];
+        let increment = key.seal(WalletBackupHead::default(), &entries).unwrap();
+        assert_eq!(1, increment.version);
+        assert_eq!(entries, key.open(&increment).unwrap());
+
+        let other_key = WalletBackupKey::new(&WalletEntropy::new_random());
+        assert_ne!(key.id(), other_key.id());
+        assert!(other_key.open(&increment).is_err());
+
+        let mut altered = increment.clone();
+        altered.ciphertext[0] ^= 1;
+        assert!(key.open(&altered).is_err());
+
+        // Version and predecessor are authenticated, too.
+        let mut renumbered = increment.clone();
+        renumbered.version = 2;
+        assert!(key.open(&renumbered).is_err());
+        let mut relinked = increment;
+        relinked.previous = random();
+        assert!(key.open(&relinked).is_err());
+    }
+
+    #[test]
+    fn increments_must_extend_the_backup() {
+        let key = WalletBackupKey::new(&WalletEntropy::new_random());
+        let genesis = WalletBackupHead::default();
+        let first = key.seal(genesis, &[recovery_data(0)]).unwrap();
+        let second = key.seal(first.head(), &[recovery_data(1)]).unwrap();
+        let forked = key.seal(genesis, &[recovery_data(0)]).unwrap();
+
+        assert_eq!(
+            Ok(second.head()),
+            verify_extension(genesis, &[first.clone(), second.clone()])
+        );
+        assert_eq!(
+            Err(WalletBackupError::UnexpectedVersion {
+                expected: 1,
+                actual: 2
+            }),
+            verify_extension(genesis, std::slice::from_ref(&second))
+        );
+        assert_eq!(
+            Err(WalletBackupError::BrokenChain(2)),
+            verify_extension(forked.head(), &[second])
+        );
+    }
+
+    #[test]
+    fn large_recovery_data_is_split_into_increments() {
+        let entries = (0..2_000).map(recovery_data).collect::<Vec<_>>();
+        let groups = increment_groups(&entries).unwrap();
+        assert!(groups.len() > 1);
+        assert_eq!(entries, groups.concat());
+
+        let key = WalletBackupKey::new(&WalletEntropy::new_random());
+        let mut head = WalletBackupHead::default();
+        for group in groups {
+            let increment = key.seal(head, group).unwrap();
+            head = increment.head();
+        }
+        assert!(increment_groups(&[]).unwrap().is_empty());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn backup_can_be_restored_from_holder() {
+        let data_dir = unit_test_data_directory(Network::Main).unwrap();
+        tokio::fs::create_dir_all(data_dir.wallet_directory_path())
+            .await
+            .unwrap();
+        let wallet_entropy = WalletEntropy::new_random();
+        let key = WalletBackupKey::new(&wallet_entropy);
+        let backup_file = data_dir.wallet_backup_file_path();
+        let mut backup = WalletBackup::load(backup_file.clone(), &key).await.unwrap();
+
+        // Recovery data is backed up incrementally.
+        let mut entries = vec![recovery_data(0), recovery_data(1)];
+        assert_eq!(1, backup.seal_new_entries(&key, &entries).await.unwrap());
+        assert_eq!(0, backup.seal_new_entries(&key, &entries).await.unwrap());
+        entries.push(recovery_data(2));
+        assert_eq!(1, backup.seal_new_entries(&key, &entries).await.unwrap());
+        assert_eq!(2, backup.head().version);
+        assert_eq!(3, backup.num_entries());
+
+        let mut holder = HeldWalletBackups::load(data_dir.held_wallet_backups_dir_path())
+            .await
+            .unwrap();
+        assert_eq!(
+            backup.head(),
+            holder
+                .store(key.id(), backup.increments_after(0))
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            Err(WalletBackupError::UnexpectedVersion {
+                expected: 3,
+                actual: 1
+            }),
+            holder.store(key.id(), backup.increments_after(0)).await
+        );
+
+        // Persisted state survives a restart.
+        let backup = WalletBackup::load(backup_file, &key).await.unwrap();
+        assert_eq!(3, backup.num_entries());
+        let holder = HeldWalletBackups::load(data_dir.held_wallet_backups_dir_path())
+            .await
+            .unwrap();
+        assert_eq!(backup.head(), holder.head(key.id()));
+
+        // After losing its data directory, the wallet fetches the backup back.
+        let mut fresh_backup = WalletBackup::default();
+        let restored = fresh_backup
+            .extend(&key, holder.increments_after(key.id(), 0).await.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(entries, restored);
+        assert_eq!(backup.head(), fresh_backup.head());
+
+        // Increments it already has are skipped.
+        assert!(fresh_backup
+            .extend(&key, holder.increments_after(key.id(), 0).await.unwrap())
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn holders_limit_number_of_backups() {
+        let data_dir = unit_test_data_directory(Network::Main).unwrap();
+        let mut holder = HeldWalletBackups::load(data_dir.held_wallet_backups_dir_path())
+            .await
+            .unwrap();
+        for _ in 0..MAX_NUM_HELD_BACKUPS {
+            let key = WalletBackupKey::new(&WalletEntropy::new_random());
+            let increment = key.seal(WalletBackupHead::default(), &[]).unwrap();
+            holder.store(key.id(), vec![increment]).await.unwrap();
+        }
+
+        let key = WalletBackupKey::new(&WalletEntropy::new_random());
+        let increment = key.seal(WalletBackupHead::default(), &[]).unwrap();
+        assert_eq!(
+            Err(WalletBackupError::TooManyBackups),
+            holder.store(key.id(), vec![increment]).await
+        );
+        assert_eq!(MAX_NUM_HELD_BACKUPS, holder.len());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn diverging_backup_is_merged() {
+        let data_dir = unit_test_data_directory(Network::Main).unwrap();
+        tokio::fs::create_dir_all(data_dir.wallet_directory_path())
+            .await
+            .unwrap();
+        let key = WalletBackupKey::new(&WalletEntropy::new_random());
+        let peer = "127.0.0.1:9798".parse().unwrap();
+
+        // The old backup is held by the peer.
+        let old_entries = vec![recovery_data(0), recovery_data(1), recovery_data(2)];
+        let mut old_backup = WalletBackup::default();
+        old_backup
+            .seal_new_entries(&key, &old_entries[..2])
+            .await
+            .unwrap();
+        old_backup
+            .seal_new_entries(&key, &old_entries)
+            .await
+            .unwrap();
+        let mut holder = HeldWalletBackups::load(data_dir.held_wallet_backups_dir_path())
+            .await
+            .unwrap();
+        holder
+            .store(key.id(), old_backup.increments_after(0))
+            .await
+            .unwrap();
+
+        // After losing its data directory, the wallet starts a new backup
+        // before reaching the peer.
+        let new_entry = recovery_data(3);
+        let mut backup = WalletBackup::default();
+        backup
+            .seal_new_entries(&key, std::slice::from_ref(&new_entry))
+            .await
+            .unwrap();
+        backup.record_peer_head(peer, holder.head(key.id()));
+
+        // The peer's backup does not extend the new one, so it is fetched in
+        // full, and replaces the shorter new one.
+        let increments = holder.increments_after(key.id(), 1).await.unwrap();
+        let received = backup.receive(&key, peer, increments).await.unwrap();
+        assert!(received.entries.is_empty());
+        assert_eq!(Some(0), received.fetch_after);
+        assert!(backup.is_fetching_diverging_backup());
+
+        let increments = holder.increments_after(key.id(), 0).await.unwrap();
+        let received = backup.receive(&key, peer, increments).await.unwrap();
+        assert_eq!(old_entries, received.entries);
+        assert_eq!(None, received.fetch_after);
+        assert!(!backup.is_fetching_diverging_backup());
+        assert_eq!(holder.head(key.id()), backup.head());
+
+        // Once the fetched recovery data is imported, the new entry is sealed
+        // on top.
+        let incoming_secrets_path = data_dir.wallet_directory_path().join("incoming_secrets");
+        let lines = [vec![new_entry], old_entries]
+            .concat()
+            .iter()
+            .map(|entry| serde_json::to_string(entry).unwrap() + "\n")
+            .collect::<String>();
+        tokio::fs::write(&incoming_secrets_path, lines)
+            .await
+            .unwrap();
+        assert_eq!(1, backup.seal(&key, &incoming_secrets_path).await.unwrap());
+        assert_eq!(3, backup.head().version);
+        assert_eq!(4, backup.num_entries());
+        assert_eq!(0, backup.seal(&key, &incoming_secrets_path).await.unwrap());
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn shorter_diverging_backup_is_replaced() {
+        let data_dir = unit_test_data_directory(Network::Main).unwrap();
+        let key = WalletBackupKey::new(&WalletEntropy::new_random());
+        let peer = "127.0.0.1:9798".parse().unwrap();
+
+        // The peer holds an old backup of a single increment.
+        let old_entry = recovery_data(0);
+        let mut old_backup = WalletBackup::default();
+        old_backup
+            .seal_new_entries(&key, std::slice::from_ref(&old_entry))
+            .await
+            .unwrap();
+        let mut holder = HeldWalletBackups::load(data_dir.held_wallet_backups_dir_path())
+            .await
+            .unwrap();
+        holder
+            .store(key.id(), old_backup.increments_after(0))
+            .await
+            .unwrap();
+
+        // The new backup is longer, so it is kept, and the peer's copy is
+        // to be replaced.
+        let mut backup = WalletBackup::default();
+        for aocl_index in 1..3 {
+            backup
+                .seal_new_entries(&key, &[recovery_data(aocl_index)])
+                .await
+                .unwrap();
+        }
+        let peer_head = holder.head(key.id());
+        backup.record_peer_head(peer, peer_head);
+        assert!(backup.diverges_from(peer_head));
+        assert!(backup.fetch_diverging(peer));
+
+        let increments = holder.increments_after(key.id(), 0).await.unwrap();
+        let received = backup.receive(&key, peer, increments).await.unwrap();
+        assert_eq!(vec![old_entry], received.entries);
+        assert!(received.replace);
+        assert!(backup.has_merged(peer_head));
+        assert!(!backup.fetch_diverging(peer));
+
+        // Appending to the diverging copy fails, replacing it succeeds.
+        assert_eq!(
+            Err(WalletBackupError::BrokenChain(2)),
+            holder.store(key.id(), backup.increments_after(1)).await
+        );
+        assert_eq!(
+            Ok(backup.head()),
+            holder.replace(key.id(), backup.increments_after(0)).await
+        );
+        assert_eq!(
+            backup.increments_after(0),
+            holder.increments_after(key.id(), 0).await.unwrap()
+        );
+        assert!(!backup.diverges_from(holder.head(key.id())));
+    }
+
+    #[apply(shared_tokio_runtime)]
+    async fn held_backups_are_served_from_disk_and_capped() {
+        let data_dir = unit_test_data_directory(Network::Main).unwrap();
+        let directory = data_dir.held_wallet_backups_dir_path();
+        let mut holder = HeldWalletBackups::load(directory.clone()).await.unwrap();
+        let key = WalletBackupKey::new(&WalletEntropy::new_random());
+        let mut backup = WalletBackup::default();
+        for i in 0..10 {
+            backup
+                .seal_new_entries(&key, &[recovery_data(i)])
+                .await
+                .unwrap();
+        }
+        holder
+            .store(key.id(), backup.increments_after(0))
+            .await
+            .unwrap();
+        holder
+            .store(key.id(), backup.increments_after(8))
+            .await
+            .unwrap();
+        assert_eq!(backup.head(), holder.head(key.id()));
+        assert_eq!(
+            backup.increments_after(3),
+            holder.increments_after(key.id(), 3).await.unwrap()
+        );
+        assert!(holder
+            .increments_after(key.id(), 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // A partially written increment is dropped on load.
+        let file = holder.file(key.id()).unwrap();
+        let mut contents = tokio::fs::read_to_string(&file).await.unwrap();
+        contents.push_str("{\"version\":11");
+        tokio::fs::write(&file, contents).await.unwrap();
+        let mut holder = HeldWalletBackups::load(directory).await.unwrap();
+        assert_eq!(backup.head(), holder.head(key.id()));
+
+        let increment = key.seal(backup.head(), &[recovery_data(10)]).unwrap();
+        holder.backups.get_mut(&key.id()).unwrap().size = MAX_HELD_BACKUP_SIZE;
+        assert_eq!(
+            Err(WalletBackupError::BackupTooLarge),
+            holder.store(key.id(), vec![increment]).await
+        );
+
+        let other_key = WalletBackupKey::new(&WalletEntropy::new_random());
+        let increment = other_key.seal(WalletBackupHead::default(), &[]).unwrap();
+        holder.backups.get_mut(&key.id()).unwrap().size = MAX_TOTAL_HELD_BACKUPS_SIZE;
+        assert_eq!(
+            Err(WalletBackupError::OutOfSpace),
+            holder.store(other_key.id(), vec![increment]).await
+        );
+    }
+}
@@ -3,10 +3,12 @@ use std::path::PathBuf;
 use tracing::info;
 
 use super::scan_mode_configuration::ScanModeConfiguration;
+use super::symmetric_key_rotation::SymmetricKeyRotationPolicy;
 use super::wallet_file::WALLET_INCOMING_SECRETS_FILE_NAME;
 use crate::application::config::cli_args;
 use crate::application::config::data_directory::DataDirectory;
 use crate::application::config::network::Network;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
 
 /// Configuration options for [`WalletState`](super::wallet_state::WalletState).
 ///
@@ -22,6 +24,12 @@ pub(crate) struct WalletConfiguration {
     /// How many mutator set membership proofs to store per monitored UTXO.
     pub(crate) num_mps_per_utxo: usize,
 
+    /// When to replace the symmetric key handed out for receiving funds.
+    pub(crate) symmetric_key_rotation: SymmetricKeyRotationPolicy,
+
+    /// Whether to scan blocks created before the wallet's birthday.
+    ignore_wallet_birthday: bool,
+
     /// data directory configs for neptune-core
     data_directory: DataDirectory,
 
@@ -38,6 +46,8 @@ impl WalletConfiguration {
         Self {
             scan_mode: None,
             num_mps_per_utxo: 0,
+            symmetric_key_rotation: SymmetricKeyRotationPolicy::default(),
+            ignore_wallet_birthday: false,
             data_directory: data_dir.clone(),
             network: Network::Main,
         }
@@ -49,6 +59,8 @@ impl WalletConfiguration {
     /// relevant for wallet state management.
     pub(crate) fn absorb_options(mut self, cli_args: &cli_args::Args) -> Self {
         self.num_mps_per_utxo = cli_args.number_of_mps_per_utxo;
+        self.symmetric_key_rotation = cli_args.symmetric_key_rotation_policy();
+        self.ignore_wallet_birthday = cli_args.ignore_wallet_birthday;
 
         self.scan_mode = match (&cli_args.scan_blocks, cli_args.scan_keys) {
             (None, None) => self.scan_mode,
@@ -89,6 +101,19 @@ impl WalletConfiguration {
         }
     }
 
+    /// Skip scanning blocks created before the wallet's birthday, if scan
+    /// mode is active. Has no effect if the override flag
+    /// `--ignore-wallet-birthday` was set.
+    pub(crate) fn set_wallet_birthday(&mut self, birthday: Timestamp) {
+        if self.ignore_wallet_birthday {
+            return;
+        }
+        if let Some(scan_mode_configuration) = self.scan_mode.as_mut() {
+            info!("Scan mode skips blocks created before wallet birthday {birthday}.");
+            scan_mode_configuration.set_wallet_birthday(birthday);
+        }
+    }
+
     pub(crate) fn incoming_secrets_path(&self) -> PathBuf {
         self.data_directory()
             .wallet_directory_path()
@@ -191,4 +216,31 @@ mod tests {
             );
         }
     }
+    #[test]
+    fn scan_mode_skips_blocks_before_wallet_birthday_unless_ignored() {
+        let network = Network::Main;
+        let data_dir = unit_test_data_directory(network).unwrap();
+        let birthday = Timestamp::now();
+        let long_before = birthday - Timestamp::days(7);
+
+        let cli_args = Args {
+            scan_keys: Some(10),
+            ..Default::default()
+        };
+        let mut configuration = WalletConfiguration::new(&data_dir).absorb_options(&cli_args);
+        configuration.set_wallet_birthday(birthday);
+        let scan_mode = configuration.scan_mode.unwrap();
+        assert!(!scan_mode.block_timestamp_is_after_birthday(long_before));
+        assert!(scan_mode.block_timestamp_is_after_birthday(birthday - Timestamp::hours(1)));
+        assert!(scan_mode.block_timestamp_is_after_birthday(birthday));
+
+        let cli_args = Args {
+            ignore_wallet_birthday: true,
+            ..cli_args
+        };
+        let mut configuration = WalletConfiguration::new(&data_dir).absorb_options(&cli_args);
+        configuration.set_wallet_birthday(birthday);
+        let scan_mode = configuration.scan_mode.unwrap();
+        assert!(scan_mode.block_timestamp_is_after_birthday(long_before));
+    }
 }
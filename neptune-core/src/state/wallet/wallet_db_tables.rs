@@ -3,8 +3,10 @@ use serde::Serialize;
 use twenty_first::prelude::Digest;
 
 use super::expected_utxo::ExpectedUtxo;
+use super::memo::Memo;
 use super::monitored_utxo::MonitoredUtxo;
 use super::sent_transaction::SentTransaction;
+use super::symmetric_key_rotation::ActiveSymmetricKey;
 use crate::api::export::AdditionRecord;
 use crate::application::database::storage::storage_schema::DbtMap;
 use crate::application::database::storage::storage_schema::DbtSingleton;
@@ -161,6 +163,16 @@ pub(super) struct WalletDbTables {
     ///
     /// Length must match [`Self::expected_utxos`].
     pub(super) addition_record_to_expected_utxo: DbtMap<AdditionRecord, Index>,
+
+    /// table number: 14
+    /// The symmetric key currently handed out for receiving funds, if a
+    /// rotation policy is in effect and a key was handed out under it.
+    pub(super) active_symmetric_key: DbtSingleton<Option<ActiveSymmetricKey>>,
+
+    /// table numbers 15 + 16
+    /// Decoded memos announced along with received UTXOs, by the
+    /// [`AdditionRecord`] of the UTXO.
+    pub(super) utxo_memos: DbtMap<AdditionRecord, Vec<Memo>>,
 }
 
 impl WalletDbTables {
@@ -210,6 +222,13 @@ impl WalletDbTables {
             .new_map("addition_record_to_expected_utxo")
             .await;
 
+        let active_symmetric_key = storage
+            .schema
+            .new_singleton::<Option<ActiveSymmetricKey>>("active_symmetric_key")
+            .await;
+
+        let utxo_memos = storage.schema.new_map("utxo_memos").await;
+
         WalletDbTables {
             sync_label,
             monitored_utxos,
@@ -222,6 +241,8 @@ impl WalletDbTables {
             strong_key_to_mutxo,
             index_set_to_mutxo,
             addition_record_to_expected_utxo,
+            active_symmetric_key,
+            utxo_memos,
         }
     }
 
@@ -162,6 +162,27 @@ impl WalletEntropy {
         )
     }
 
+    /// Return the secret used to sign chain checkpoints. Its hash is the
+    /// public key that identifies this node's checkpoints.
+    pub(crate) fn checkpoint_signing_secret(&self) -> Digest {
+        const CHECKPOINT_SIGNING_FLAG: u64 = 0x3c4e2a91d07b6f15u64;
+        Tip5::hash_varlen(
+            &[
+                self.secret_seed.0.encode(),
+                bfe_vec![CHECKPOINT_SIGNING_FLAG],
+            ]
+            .concat(),
+        )
+    }
+
+    /// Return the secret from which the key and identifier of the wallet's
+    /// backups to trusted peers are derived, see
+    /// [`WalletBackupKey`](super::wallet_backup::WalletBackupKey).
+    pub(crate) fn wallet_backup_secret(&self) -> Digest {
+        const WALLET_BACKUP_FLAG: u64 = 0x5b0e7d21c93fa468u64;
+        Tip5::hash_varlen(&[self.secret_seed.0.encode(), bfe_vec![WALLET_BACKUP_FLAG]].concat())
+    }
+
     /// Convert a secret seed phrase (list of 18 valid BIP-39 words) to a
     /// [`WalletEntropy`] object
     pub fn from_phrase(phrase: &[String]) -> Result<Self> {
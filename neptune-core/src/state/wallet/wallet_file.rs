@@ -14,6 +14,7 @@ use zeroize::ZeroizeOnDrop;
 
 use super::secret_key_material::SecretKeyMaterial;
 use super::wallet_entropy::WalletEntropy;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
 
 pub const WALLET_DIRECTORY: &str = "wallet";
 pub const WALLET_SECRET_FILE_NAME: &str = "wallet.dat";
@@ -144,6 +145,14 @@ pub struct WalletFile {
 
     secret_seed: SecretKeyMaterial,
     version: u8,
+
+    /// When the secret seed was generated, if known. No UTXOs can have been
+    /// sent to the wallet before this time, so restores can skip scanning
+    /// older blocks. Absent from wallet files created before birthdays were
+    /// recorded, and from imported wallets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[zeroize(skip)]
+    birthday: Option<Timestamp>,
 }
 
 impl WalletFile {
@@ -152,11 +161,23 @@ impl WalletFile {
             name: STANDARD_WALLET_NAME.to_string(),
             secret_seed,
             version: STANDARD_WALLET_VERSION,
+            birthday: None,
         }
     }
 
     fn new_random() -> Self {
-        Self::new(SecretKeyMaterial(rng().random()))
+        Self::new(SecretKeyMaterial(rng().random())).with_birthday(Timestamp::now())
+    }
+
+    /// Set the time at which the secret seed was generated.
+    pub fn with_birthday(mut self, birthday: Timestamp) -> Self {
+        self.birthday = Some(birthday);
+        self
+    }
+
+    /// The time at which the secret seed was generated, if known.
+    pub fn birthday(&self) -> Option<Timestamp> {
+        self.birthday
     }
 
     pub fn entropy(&self) -> WalletEntropy {
@@ -232,3 +253,33 @@ impl WalletFile {
         fs::write(path.clone(), wallet_as_json).context("Failed to write wallet file to disk")
     }
 }
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_wallets_have_birthday() {
+        let before = Timestamp::now();
+        let wallet_file = WalletFile::new_random();
+        assert!(wallet_file
+            .birthday()
+            .is_some_and(|birthday| before <= birthday));
+
+        let imported_wallet_file = WalletFile::new(wallet_file.secret_key());
+        assert!(imported_wallet_file.birthday().is_none());
+    }
+
+    #[test]
+    fn wallet_files_without_birthday_can_be_read() {
+        let wallet_file = WalletFile::new(SecretKeyMaterial(rng().random()));
+        let json = serde_json::to_string(&wallet_file).unwrap();
+        assert!(!json.contains("birthday"));
+        assert_eq!(wallet_file, serde_json::from_str(&json).unwrap());
+
+        let wallet_file = wallet_file.with_birthday(Timestamp::now());
+        let json = serde_json::to_string(&wallet_file).unwrap();
+        assert_eq!(wallet_file, serde_json::from_str(&json).unwrap());
+    }
+}
@@ -7,7 +7,6 @@ use std::path::PathBuf;
 use anyhow::bail;
 use anyhow::Result;
 use itertools::Itertools;
-use num_traits::CheckedAdd;
 use num_traits::CheckedSub;
 use num_traits::Zero;
 use serde_derive::Deserialize;
@@ -31,21 +30,38 @@ use super::address::generation_address;
 use super::address::symmetric_key;
 use super::address::KeyType;
 use super::address::SpendingKey;
+use super::address::SymmetricAddressData;
 use super::coin_with_possible_timelock::CoinWithPossibleTimeLock;
 use super::expected_utxo::ExpectedUtxo;
 use super::expected_utxo::UtxoNotifier;
 use super::incoming_utxo::IncomingUtxo;
+use super::memo::Memo;
+use super::memo::MemoAnnouncement;
+use super::memo::MemoDecoderRegistry;
+use super::memo::MAX_NUM_MEMOS_PER_UTXO;
+use super::pending_transactions::PendingInput;
+use super::pending_transactions::PendingTransactions;
 use super::rusty_wallet_database::RustyWalletDatabase;
 use super::sent_transaction::SentTransaction;
+use super::symmetric_key_rotation::ActiveSymmetricKey;
+use super::symmetric_key_rotation::SymmetricKeyRotationStatus;
 use super::unlocked_utxo::UnlockedUtxo;
+use super::utxo_ownership_proof::DisclosedUtxo;
+use super::utxo_ownership_proof::UtxoOwnershipProof;
 use super::wallet_configuration::WalletConfiguration;
 use super::wallet_entropy::WalletEntropy;
 use super::wallet_file::WalletFileContext;
+use super::wallet_stats::UtxoSummary;
+use super::wallet_stats::WalletStats;
 use super::wallet_status::WalletStatus;
 use super::wallet_status::WalletStatusElement;
+use crate::api::tx_initiation::error::CreateTxError;
 use crate::application::config::cli_args::Args;
 use crate::application::config::data_directory::DataDirectory;
 use crate::application::config::fee_notification_policy::FeeNotificationPolicy;
+use crate::application::config::network::Network;
+use crate::application::database::open_files::db_options;
+use crate::application::database::open_files::DbStore;
 use crate::application::database::storage::storage_schema::DbtVec;
 use crate::application::database::storage::storage_schema::RustyKey;
 use crate::application::database::storage::storage_schema::RustyValue;
@@ -80,10 +96,9 @@ pub struct WalletState {
     pub wallet_db: RustyWalletDatabase,
     pub wallet_entropy: WalletEntropy,
 
-    /// these two fields are for monitoring wallet-affecting utxos in the mempool.
-    /// key is Tx hash.  for removing watched utxos when a tx is removed from mempool.
-    mempool_spent_utxos: HashMap<TransactionKernelId, HashMap<AbsoluteIndexSet, (Utxo, u64)>>,
-    mempool_unspent_utxos: HashMap<TransactionKernelId, Vec<IncomingUtxo>>,
+    /// Wallet-affecting transactions in the mempool, for monitoring
+    /// unconfirmed UTXOs sent from or to the wallet.
+    pending_transactions: PendingTransactions,
 
     // these fields represent all known keys that have been handed out,
     // ie keys with derivation index in 0..self.spending_key_counter(key_type)
@@ -93,6 +108,9 @@ pub struct WalletState {
 
     /// Tunable options for configuring how the wallet state operates.
     pub(crate) configuration: WalletConfiguration,
+
+    /// Decoders for the memos announced along with received UTXOs.
+    pub(crate) memo_decoders: MemoDecoderRegistry,
 }
 
 /// Contains the cryptographic (non-public) data that is needed to recover the mutator set
@@ -278,6 +296,9 @@ impl WalletState {
             info!("Wallet file present but database absent; wallet may have been imported.");
             configuration.enable_scan_mode();
         }
+        if let Some(birthday) = wallet_file_context.wallet_file.birthday() {
+            configuration.set_wallet_birthday(birthday);
+        }
 
         Self::try_new(configuration, wallet_entropy, genesis).await
     }
@@ -324,11 +345,11 @@ impl WalletState {
         let mut wallet_state = Self {
             wallet_db: rusty_wallet_database,
             wallet_entropy,
-            mempool_spent_utxos: Default::default(),
-            mempool_unspent_utxos: Default::default(),
+            pending_transactions: Default::default(),
             known_generation_keys,
             known_symmetric_keys,
             configuration: configuration.clone(),
+            memo_decoders: MemoDecoderRegistry::default(),
         };
 
         // Generation and Symmetric keys with derivation index 0 are reserved
@@ -417,7 +438,7 @@ impl WalletState {
     }
 
     async fn open_wallet_db(path: &Path) -> anyhow::Result<NeptuneLevelDb<RustyKey, RustyValue>> {
-        NeptuneLevelDb::new(path, &crate::application::database::create_db_if_missing())
+        NeptuneLevelDb::new(path, &db_options(DbStore::Wallet))
             .await
             .map_err(|e| anyhow::anyhow!("Failed to open wallet db at '{}': {}", path.display(), e))
     }
@@ -521,33 +542,38 @@ impl WalletState {
 
                 let tx_id = tx_kernel.txid();
 
-                self.mempool_spent_utxos.insert(tx_id, spent_utxos);
-                self.mempool_unspent_utxos.insert(tx_id, own_utxos);
+                self.pending_transactions
+                    .insert(tx_id, spent_utxos, own_utxos);
             }
             MempoolEvent::RemoveTx(tx_kernel) => {
                 let tx_id = tx_kernel.txid();
                 debug!("handling mempool RemoveTx event.  tx: {}", tx_id);
-                self.mempool_spent_utxos.remove(&tx_id);
-                self.mempool_unspent_utxos.remove(&tx_id);
+                self.pending_transactions.remove(tx_id);
             }
         }
     }
 
     /// Get an iterator over (utxo, aocl_leaf_index) pairs corresponding to
     /// own inputs into transactions that live in the mempool.
-    pub fn mempool_spent_utxos_iter(&self) -> impl Iterator<Item = (&Utxo, &u64)> {
-        self.mempool_spent_utxos
-            .values()
-            .flatten()
-            .map(|(_, (utxo, ali))| (utxo, ali))
+    ///
+    /// Every UTXO is listed once, even if several pending transactions spend
+    /// it. See [`PendingTransactions`].
+    pub fn mempool_spent_utxos_iter(&self) -> impl Iterator<Item = (&Utxo, u64)> {
+        self.pending_transactions
+            .spent_inputs()
+            .into_iter()
+            .map(|(_, input)| (&input.utxo, input.aocl_leaf_index))
     }
 
     /// Get an iterator over (utxo, addition_record) pairs corresponding to
     /// outputs of transactions that live in the mempool.
+    ///
+    /// Every UTXO is listed once, even if several pending transactions create
+    /// it. See [`PendingTransactions`].
     pub fn mempool_unspent_utxos_iter(&self) -> impl Iterator<Item = (&Utxo, AdditionRecord)> {
-        self.mempool_unspent_utxos
-            .values()
-            .flatten()
+        self.pending_transactions
+            .received_outputs()
+            .into_iter()
             .map(|iu| (&iu.utxo, iu.addition_record()))
     }
 
@@ -557,73 +583,39 @@ impl WalletState {
         impl Iterator<Item = (TransactionKernelId, NativeCurrencyAmount)> + '_,
         impl Iterator<Item = (TransactionKernelId, NativeCurrencyAmount)> + '_,
     ) {
-        let outgoing = self.mempool_spent_utxos.iter().map(|(txkid, sender_data)| {
-            (
-                *txkid,
-                sender_data
-                    .values()
-                    .map(|(utxo, __)| utxo.get_native_currency_amount())
-                    .sum::<NativeCurrencyAmount>(),
-            )
-        });
-
         let incoming = self
-            .mempool_unspent_utxos
-            .iter()
-            .map(|(txkid, announced_utxos)| {
-                (
-                    *txkid,
-                    announced_utxos
-                        .iter()
-                        .map(|au| au.utxo.get_native_currency_amount())
-                        .sum::<NativeCurrencyAmount>(),
-                )
-            });
+            .pending_transactions
+            .balance_effects()
+            .map(|(txkid, received, _)| (txkid, received));
+        let outgoing = self
+            .pending_transactions
+            .balance_effects()
+            .map(|(txkid, _, spent)| (txkid, spent));
 
         (incoming, outgoing)
     }
 
+    /// Returns true iff the own transaction with the given id is in the
+    /// mempool and can be replaced by a transaction spending the same inputs,
+    /// e.g., to raise its fee. See [`PendingTransactions`].
+    pub fn mempool_transaction_is_replaceable(&self, txid: TransactionKernelId) -> bool {
+        self.pending_transactions.is_replaceable(txid)
+    }
+
     /// returns unconfirmed, available balance (excludes timelocked utxos)
     pub fn unconfirmed_available_balance(
         &self,
         wallet_status: &WalletStatus,
         timestamp: Timestamp,
     ) -> NativeCurrencyAmount {
-        let amount_spent_by_mempool_transactions = self
-            .mempool_spent_utxos_iter()
-            .map(|(u, _)| u.get_native_currency_amount())
-            .sum();
-        let amount_received_from_mempool_transactions = self
-            .mempool_unspent_utxos_iter()
-            .filter(|(utxo, _)| utxo.can_spend_at(timestamp))
-            .map(|(u, _)| u.get_native_currency_amount())
-            .sum();
-        wallet_status
-            .available_confirmed(timestamp)
-            .checked_add(&amount_received_from_mempool_transactions)
-            .expect("balance must never overflow")
-            .checked_sub(&amount_spent_by_mempool_transactions)
-            .unwrap_or(NativeCurrencyAmount::zero())
+        self.pending_transactions
+            .unconfirmed_balance(wallet_status, Some(timestamp))
     }
 
     /// returns unconfirmed, total balance (includes timelocked utxos)
     pub fn unconfirmed_total_balance(&self, wallet_status: &WalletStatus) -> NativeCurrencyAmount {
-        wallet_status
-            .total_confirmed()
-            .checked_sub(
-                &self
-                    .mempool_spent_utxos_iter()
-                    .map(|(u, _)| u.get_native_currency_amount())
-                    .sum(),
-            )
-            .expect("balance must never be negative")
-            .checked_add(
-                &self
-                    .mempool_unspent_utxos_iter()
-                    .map(|(u, _)| u.get_native_currency_amount())
-                    .sum(),
-            )
-            .expect("balance must never overflow")
+        self.pending_transactions
+            .unconfirmed_balance(wallet_status, None)
     }
 
     /// Returns the number of expected UTXOs in the database.
@@ -708,7 +700,7 @@ impl WalletState {
     async fn scan_for_spent_utxos(
         &self,
         transaction_kernel: &TransactionKernel,
-    ) -> HashMap<AbsoluteIndexSet, (Utxo, u64)> {
+    ) -> HashMap<AbsoluteIndexSet, PendingInput> {
         let mut spent_own_utxos = HashMap::default();
 
         for index_set in transaction_kernel
@@ -716,10 +708,20 @@ impl WalletState {
             .iter()
             .map(|rr| &rr.absolute_indices)
         {
-            if let Some((mutxo, mutxo_list_index)) =
-                self.wallet_db.monitored_utxo_by_index_set(index_set).await
-            {
-                spent_own_utxos.insert(index_set.to_owned(), (mutxo.utxo, mutxo_list_index));
+            if let Some((mutxo, _)) = self.wallet_db.monitored_utxo_by_index_set(index_set).await {
+                let addition_record = commit(
+                    Tip5::hash(&mutxo.utxo),
+                    mutxo.sender_randomness,
+                    mutxo.receiver_preimage.hash(),
+                );
+                spent_own_utxos.insert(
+                    index_set.to_owned(),
+                    PendingInput {
+                        utxo: mutxo.utxo,
+                        aocl_leaf_index: mutxo.aocl_leaf_index,
+                        addition_record,
+                    },
+                );
             }
         }
 
@@ -946,6 +948,16 @@ impl WalletState {
             .find(|k| k.receiver_identifier() == receiver_identifier)
     }
 
+    /// returns the known addressable spending key whose receiver preimage
+    /// hashes to `receiver_digest`, if any.
+    pub(crate) fn find_known_spending_key_for_receiver_digest(
+        &self,
+        receiver_digest: Digest,
+    ) -> Option<SpendingKey> {
+        self.get_all_known_addressable_spending_keys()
+            .find(|k| k.privacy_preimage().hash() == receiver_digest)
+    }
+
     /// returns all base-spending-keys with derivation index less than current counter
     pub fn get_all_known_spending_keys(&self) -> impl Iterator<Item = SpendingKey> + '_ {
         KeyType::all_types()
@@ -1119,6 +1131,97 @@ impl WalletState {
         key
     }
 
+    /// Get a spending key of a given type for receiving funds.
+    ///
+    /// For symmetric keys, the configured
+    /// [`SymmetricKeyRotationPolicy`](super::symmetric_key_rotation::SymmetricKeyRotationPolicy)
+    /// applies. If it is enabled, the active symmetric key is returned, after
+    /// replacing it with its successor if rotation is due. Otherwise, this is
+    /// the same as [`Self::next_unused_spending_key`].
+    ///
+    /// Note that this modifies wallet state.  It is important to write to disk
+    /// afterward to avoid possible funds loss.
+    pub async fn spending_key_for_reception(
+        &mut self,
+        key_type: KeyType,
+        now: Timestamp,
+    ) -> SpendingKey {
+        match key_type {
+            KeyType::Symmetric if self.configuration.symmetric_key_rotation.is_enabled() => {
+                self.active_symmetric_key(now).await.into()
+            }
+            _ => self.next_unused_spending_key(key_type).await,
+        }
+    }
+
+    /// Hand out the active symmetric key, first replacing it with a newly
+    /// derived key if there is none or if rotation is due.
+    ///
+    /// The replaced key remains known to the wallet, so that UTXOs sent to it
+    /// are still recognized.
+    async fn active_symmetric_key(&mut self, now: Timestamp) -> symmetric_key::SymmetricKey {
+        let policy = self.configuration.symmetric_key_rotation;
+        let mut active_key = match self.wallet_db.get_active_symmetric_key() {
+            Some(active_key) if !policy.rotation_is_due(&active_key, now) => active_key,
+            retired_key => {
+                let index = self.spending_key_counter(KeyType::Symmetric);
+                self.next_unused_symmetric_key().await;
+                if let Some(retired_key) = retired_key {
+                    info!(
+                        "Rotating symmetric key {} after {} uses; successor is key {index}",
+                        retired_key.derivation_index, retired_key.num_uses
+                    );
+                }
+                ActiveSymmetricKey::new(index, now)
+            }
+        };
+
+        active_key.num_uses += 1;
+        self.wallet_db.set_active_symmetric_key(active_key).await;
+
+        self.wallet_entropy
+            .nth_symmetric_key(active_key.derivation_index)
+    }
+
+    /// Report the state of symmetric-key rotation.
+    pub(crate) fn symmetric_key_rotation_status(
+        &self,
+        now: Timestamp,
+    ) -> SymmetricKeyRotationStatus {
+        SymmetricKeyRotationStatus::new(
+            self.configuration.symmetric_key_rotation,
+            self.wallet_db.get_active_symmetric_key(),
+            self.spending_key_counter(KeyType::Symmetric),
+            now,
+        )
+    }
+
+    /// Describe the known symmetric keys as addresses, in the order in which
+    /// they were derived.
+    pub(crate) fn symmetric_address_data(
+        &self,
+        network: Network,
+    ) -> Result<Vec<SymmetricAddressData>> {
+        let active_key_index = self
+            .configuration
+            .symmetric_key_rotation
+            .is_enabled()
+            .then(|| self.wallet_db.get_active_symmetric_key())
+            .flatten()
+            .map(|active_key| active_key.derivation_index);
+
+        (0..self.spending_key_counter(KeyType::Symmetric))
+            .map(|index| {
+                SymmetricAddressData::new(
+                    index,
+                    &self.wallet_entropy.nth_symmetric_key(index),
+                    active_key_index == Some(index),
+                    network,
+                )
+            })
+            .collect()
+    }
+
     /// Get the next n generation spending keys (with derivation indices)
     /// without modifying the counter.
     pub(crate) fn get_future_generation_spending_keys(
@@ -1680,6 +1783,8 @@ impl WalletState {
             return Ok(());
         }
 
+        self.record_utxo_memos(tx_kernel, &incoming).await;
+
         let msa_state = previous_mutator_set_accumulator.clone();
 
         // Mutate the monitored UTXOs to account for this block.
@@ -1729,6 +1834,57 @@ impl WalletState {
         Ok(())
     }
 
+    /// Decode the memos announced to own keys in the given transaction, and
+    /// attach them to the incoming UTXOs locked to the same keys, at most
+    /// [MAX_NUM_MEMOS_PER_UTXO] to each.
+    ///
+    /// Memos are unauthenticated, see [memo](super::memo).
+    async fn record_utxo_memos(
+        &mut self,
+        tx_kernel: &TransactionKernel,
+        incoming: &HashMap<AdditionRecord, IncomingUtxo>,
+    ) {
+        if incoming.is_empty() {
+            return;
+        }
+
+        let mut memos_by_addition_record: HashMap<AdditionRecord, Vec<Memo>> = HashMap::new();
+        for memo_announcement in tx_kernel
+            .announcements
+            .iter()
+            .filter_map(MemoAnnouncement::try_from_announcement)
+        {
+            let Some(key) = self
+                .get_all_known_spending_keys()
+                .find(|key| key.receiver_identifier() == memo_announcement.receiver_identifier)
+            else {
+                continue;
+            };
+
+            let lock_script_hash = key.lock_script_hash();
+            let memo = self.memo_decoders.decode(&memo_announcement);
+            for (addition_record, incoming_utxo) in incoming {
+                if incoming_utxo.utxo.lock_script_hash() != lock_script_hash {
+                    continue;
+                }
+                let memos = memos_by_addition_record
+                    .entry(*addition_record)
+                    .or_default();
+                if memos.len() < MAX_NUM_MEMOS_PER_UTXO {
+                    memos.push(memo.clone());
+                }
+            }
+        }
+
+        for (addition_record, memos) in memos_by_addition_record {
+            debug!(
+                "Received {} memo(s) for UTXO {addition_record}",
+                memos.len()
+            );
+            self.wallet_db.add_utxo_memos(addition_record, memos).await;
+        }
+    }
+
     /// writes prepared utxo claim data to disk
     ///
     /// Informs wallet of a Utxo *after* parent Tx is confirmed in a block
@@ -1829,50 +1985,115 @@ impl WalletState {
         timestamp: Timestamp,
     ) -> impl IntoIterator<Item = TxInput> + use<'_> {
         // Build a hashset of all tx inputs presently in the mempool.
-        let index_sets_of_inputs_in_mempool_txs: HashSet<AbsoluteIndexSet> = self
-            .mempool_spent_utxos
-            .iter()
-            .flat_map(|(_txkid, tx_inputs)| tx_inputs.keys())
-            .copied()
-            .collect();
+        let index_sets_of_inputs_in_mempool_txs = self.index_sets_of_inputs_in_mempool_txs();
 
         // filter spendable inputs.
         wallet_status.synced_unspent.into_iter().filter_map(
             move |(wallet_status_element, membership_proof)| {
-                // filter out UTXOs that are still timelocked.
-                if !wallet_status_element.utxo.can_spend_at(timestamp) {
-                    return None;
-                }
+                self.unlock_input(
+                    wallet_status_element,
+                    membership_proof,
+                    timestamp,
+                    &index_sets_of_inputs_in_mempool_txs,
+                )
+                .ok()
+            },
+        )
+    }
 
-                // filter out inputs that are already spent by txs in mempool.
-                let absolute_index_set =
-                    membership_proof.compute_indices(Tip5::hash(&wallet_status_element.utxo));
-                if index_sets_of_inputs_in_mempool_txs.contains(&absolute_index_set) {
-                    return None;
-                }
+    /// Unlock exactly the UTXOs with the given AOCL leaf indices, in the given
+    /// order, for use as transaction inputs.
+    ///
+    /// Returns an error identifying the first UTXO that is not a synced,
+    /// unspent UTXO of this wallet, or that cannot be spent at `timestamp`.
+    pub(crate) fn inputs_by_aocl_leaf_index(
+        &self,
+        mut wallet_status: WalletStatus,
+        aocl_leaf_indices: &[u64],
+        timestamp: Timestamp,
+    ) -> Result<Vec<TxInput>, CreateTxError> {
+        let index_sets_of_inputs_in_mempool_txs = self.index_sets_of_inputs_in_mempool_txs();
 
-                // filter out inputs that we can't spend
-                let Some(spending_key) =
-                    self.find_spending_key_for_utxo(&wallet_status_element.utxo)
-                else {
-                    warn!(
-                        "spending key not found for utxo: {:?}",
-                        wallet_status_element.utxo
-                    );
-                    return None;
-                };
+        let mut inputs = vec![];
+        for (i, &aocl_leaf_index) in aocl_leaf_indices.iter().enumerate() {
+            if aocl_leaf_indices[..i].contains(&aocl_leaf_index) {
+                return Err(CreateTxError::DuplicateInput { aocl_leaf_index });
+            }
 
-                // Create the transaction input object
-                Some(
-                    UnlockedUtxo::unlock(
-                        wallet_status_element.utxo.clone(),
-                        spending_key.lock_script_and_witness(),
-                        membership_proof.clone(),
-                    )
-                    .into(),
-                )
-            },
+            let Some(position) = wallet_status
+                .synced_unspent
+                .iter()
+                .position(|(element, _)| element.aocl_leaf_index == aocl_leaf_index)
+            else {
+                return Err(CreateTxError::UnknownInput { aocl_leaf_index });
+            };
+            let (wallet_status_element, membership_proof) =
+                wallet_status.synced_unspent.swap_remove(position);
+            inputs.push(self.unlock_input(
+                wallet_status_element,
+                membership_proof,
+                timestamp,
+                &index_sets_of_inputs_in_mempool_txs,
+            )?);
+        }
+
+        Ok(inputs)
+    }
+
+    /// The absolute index sets of own UTXOs that are spent by transactions in
+    /// the mempool, and which are therefore not spendable.
+    fn index_sets_of_inputs_in_mempool_txs(&self) -> HashSet<AbsoluteIndexSet> {
+        self.pending_transactions
+            .spent_inputs()
+            .into_iter()
+            .map(|(index_set, _)| index_set)
+            .collect()
+    }
+
+    fn unlock_input(
+        &self,
+        wallet_status_element: WalletStatusElement,
+        membership_proof: MsMembershipProof,
+        timestamp: Timestamp,
+        index_sets_of_inputs_in_mempool_txs: &HashSet<AbsoluteIndexSet>,
+    ) -> Result<TxInput, CreateTxError> {
+        let aocl_leaf_index = wallet_status_element.aocl_leaf_index;
+
+        // filter out UTXOs that are still timelocked.
+        if !wallet_status_element.utxo.can_spend_at(timestamp) {
+            return Err(CreateTxError::TimeLockedInput {
+                aocl_leaf_index,
+                release_date: wallet_status_element
+                    .utxo
+                    .release_date()
+                    .unwrap_or(timestamp),
+            });
+        }
+
+        // filter out inputs that are already spent by txs in mempool.
+        let absolute_index_set =
+            membership_proof.compute_indices(Tip5::hash(&wallet_status_element.utxo));
+        if index_sets_of_inputs_in_mempool_txs.contains(&absolute_index_set) {
+            return Err(CreateTxError::InputSpentInMempool { aocl_leaf_index });
+        }
+
+        // filter out inputs that we can't spend
+        let Some(spending_key) = self.find_spending_key_for_utxo(&wallet_status_element.utxo)
+        else {
+            warn!(
+                "spending key not found for utxo: {:?}",
+                wallet_status_element.utxo
+            );
+            return Err(CreateTxError::NoSpendingKeyForInput { aocl_leaf_index });
+        };
+
+        // Create the transaction input object
+        Ok(UnlockedUtxo::unlock(
+            wallet_status_element.utxo,
+            spending_key.lock_script_and_witness(),
+            membership_proof,
         )
+        .into())
     }
 
     /// Allocate sufficient UTXOs to generate a transaction.
@@ -1968,6 +2189,101 @@ impl WalletState {
         }
         own_coins
     }
+
+    /// Summarize the fragmentation of the wallet's unspent UTXOs, as of the
+    /// given tip.
+    pub(crate) async fn wallet_stats(
+        &self,
+        mutator_set_accumulator: &MutatorSetAccumulator,
+        tip_digest: Digest,
+        now: Timestamp,
+        dust_threshold: NativeCurrencyAmount,
+    ) -> WalletStats {
+        let monitored_utxos = self.wallet_db.monitored_utxos();
+        let mut utxos = vec![];
+
+        let stream = monitored_utxos.stream_values().await;
+        pin_mut!(stream); // needed for iteration
+
+        while let Some(mutxo) = stream.next().await {
+            if mutxo.abandoned_at.is_some() {
+                continue;
+            }
+            let Some(msmp) = mutxo.membership_proof_ref_for_block(tip_digest) else {
+                continue;
+            };
+            if !mutator_set_accumulator.verify(Tip5::hash(&mutxo.utxo), msmp) {
+                continue;
+            }
+            utxos.push(UtxoSummary {
+                aocl_leaf_index: mutxo.aocl_leaf_index,
+                amount: mutxo.utxo.get_native_currency_amount(),
+                confirmed: mutxo.confirmed_in_block.1,
+                is_spendable: mutxo.utxo.can_spend_at(now),
+                membership_proof_size: msmp.encode().len(),
+            });
+        }
+
+        WalletStats::new(&utxos, now, dust_threshold)
+    }
+
+    /// Prove that the wallet controlled the UTXOs with the given AOCL leaf
+    /// indices at the given block, or all UTXOs it controlled there if no
+    /// indices are given.
+    ///
+    /// Requires membership proofs for the block, which the wallet only
+    /// retains for the most recent blocks. `mutator_set_accumulator` must be
+    /// the mutator set after the block.
+    pub(crate) async fn prove_utxo_ownership(
+        &self,
+        block_digest: Digest,
+        block_height: BlockHeight,
+        mutator_set_accumulator: MutatorSetAccumulator,
+        aocl_leaf_indices: &[u64],
+    ) -> Result<UtxoOwnershipProof> {
+        let monitored_utxos = self.wallet_db.monitored_utxos();
+        let mut utxos = vec![];
+
+        let stream = monitored_utxos.stream_values().await;
+        pin_mut!(stream); // needed for iteration
+
+        while let Some(mutxo) = stream.next().await {
+            if mutxo.abandoned_at.is_some()
+                || !aocl_leaf_indices.is_empty()
+                    && !aocl_leaf_indices.contains(&mutxo.aocl_leaf_index)
+            {
+                continue;
+            }
+            let Some(membership_proof) = mutxo.get_membership_proof_for_block(block_digest) else {
+                continue;
+            };
+            if !mutator_set_accumulator.verify(Tip5::hash(&mutxo.utxo), &membership_proof) {
+                continue;
+            }
+            utxos.push(DisclosedUtxo {
+                utxo: mutxo.utxo,
+                membership_proof,
+            });
+        }
+
+        if let Some(missing) = aocl_leaf_indices.iter().find(|index| {
+            !utxos
+                .iter()
+                .any(|disclosed| disclosed.membership_proof.aocl_leaf_index == **index)
+        }) {
+            bail!(
+                "Wallet did not control an unspent UTXO with AOCL leaf index {missing} \
+                 at block {block_digest:x}"
+            );
+        }
+
+        Ok(UtxoOwnershipProof {
+            block_digest,
+            block_height,
+            mutator_set_accumulator,
+            utxos,
+        })
+    }
 }
 
 #[cfg(test)]
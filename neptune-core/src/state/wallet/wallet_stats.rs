@@ -0,0 +1,291 @@
+use std::time::Duration;
+
+use itertools::Itertools;
+use num_traits::Zero;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::consensus::type_scripts::native_currency_amount::NativeCurrencyAmount;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+
+/// Lower bounds, in whole coins, of the value buckets. The first bucket starts
+/// at zero.
+const VALUE_BUCKET_LOWER_BOUNDS_IN_COINS: [u32; 5] = [1, 10, 100, 1_000, 10_000];
+
+/// Upper bounds of the age buckets. The last bucket is unbounded.
+const AGE_BUCKET_UPPER_BOUNDS: [Timestamp; 4] = [
+    Timestamp::days(1),
+    Timestamp::days(7),
+    Timestamp::days(30),
+    Timestamp::days(365),
+];
+
+/// Number of spendable UTXOs above which the wallet is considered
+/// fragmented.
+const FRAGMENTATION_THRESHOLD: usize = 20;
+
+/// Maximum number of inputs in a suggested consolidation, to keep the
+/// consolidating transaction cheap to prove.
+const MAX_CONSOLIDATION_INPUTS: usize = 25;
+
+/// The UTXOs whose value falls in a range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValueBucket {
+    /// Inclusive.
+    pub min_amount: NativeCurrencyAmount,
+
+    /// Exclusive. `None` for the last bucket.
+    pub max_amount: Option<NativeCurrencyAmount>,
+    pub num_utxos: usize,
+    pub total_amount: NativeCurrencyAmount,
+}
+
+/// The UTXOs confirmed within a range of time before now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgeBucket {
+    /// Exclusive. `None` for the last bucket.
+    pub max_age: Option<Duration>,
+    pub num_utxos: usize,
+    pub total_amount: NativeCurrencyAmount,
+}
+
+/// A suggestion to merge small UTXOs into one by sending them to oneself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsolidationPlan {
+    /// AOCL leaf indices of the UTXOs to spend.
+    pub inputs: Vec<u64>,
+    pub total_amount: NativeCurrencyAmount,
+
+    /// Number of unspent UTXOs after the consolidation is confirmed.
+    pub num_utxos_after: usize,
+}
+
+/// Statistics about the fragmentation of the wallet's unspent UTXOs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WalletStats {
+    pub num_utxos: usize,
+    pub total_amount: NativeCurrencyAmount,
+
+    /// In ascending order of value.
+    pub value_buckets: Vec<ValueBucket>,
+
+    /// In ascending order of age.
+    pub age_buckets: Vec<AgeBucket>,
+
+    /// UTXOs worth less than the dust threshold, which may cost more in fees
+    /// to spend than they are worth.
+    pub num_dust_utxos: usize,
+    pub dust_amount: NativeCurrencyAmount,
+
+    /// Average size of the UTXOs' mutator set membership proofs, in
+    /// `BFieldElement`s. Every proof is updated with every block, so the
+    /// wallet's per-block work grows with this size times the number of
+    /// UTXOs.
+    pub average_membership_proof_size: f64,
+
+    /// `None` if the wallet is not fragmented.
+    pub consolidation_plan: Option<ConsolidationPlan>,
+}
+
+/// What [`WalletStats`] needs to know about an unspent UTXO.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct UtxoSummary {
+    pub(crate) aocl_leaf_index: u64,
+    pub(crate) amount: NativeCurrencyAmount,
+    pub(crate) confirmed: Timestamp,
+
+    /// Whether the UTXO is not timelocked.
+    pub(crate) is_spendable: bool,
+    pub(crate) membership_proof_size: usize,
+}
+
+impl WalletStats {
+    pub(crate) fn new(
+        utxos: &[UtxoSummary],
+        now: Timestamp,
+        dust_threshold: NativeCurrencyAmount,
+    ) -> Self {
+        let total_amount = utxos.iter().map(|utxo| utxo.amount).sum();
+
+        let value_bounds = [NativeCurrencyAmount::zero()]
+            .into_iter()
+            .chain(VALUE_BUCKET_LOWER_BOUNDS_IN_COINS.map(NativeCurrencyAmount::coins))
+            .collect_vec();
+        let value_buckets = value_bounds
+            .iter()
+            .enumerate()
+            .map(|(i, &min_amount)| {
+                let max_amount = value_bounds.get(i + 1).copied();
+                let in_bucket = utxos.iter().filter(|utxo| {
+                    min_amount <= utxo.amount && max_amount.is_none_or(|max| utxo.amount < max)
+                });
+                ValueBucket {
+                    min_amount,
+                    max_amount,
+                    num_utxos: in_bucket.clone().count(),
+                    total_amount: in_bucket.map(|utxo| utxo.amount).sum(),
+                }
+            })
+            .collect();
+
+        let age_buckets = (0..=AGE_BUCKET_UPPER_BOUNDS.len())
+            .map(|i| {
+                let min_age = i.checked_sub(1).map(|j| AGE_BUCKET_UPPER_BOUNDS[j]);
+                let max_age = AGE_BUCKET_UPPER_BOUNDS.get(i).copied();
+                let in_bucket = utxos.iter().filter(|utxo| {
+                    let age = Self::age(utxo, now);
+                    min_age.is_none_or(|min| min <= age) && max_age.is_none_or(|max| age < max)
+                });
+                AgeBucket {
+                    max_age: max_age.map(|max| max.as_duration()),
+                    num_utxos: in_bucket.clone().count(),
+                    total_amount: in_bucket.map(|utxo| utxo.amount).sum(),
+                }
+            })
+            .collect();
+
+        let dust = utxos
+            .iter()
+            .filter(|utxo| utxo.amount < dust_threshold)
+            .collect_vec();
+
+        let average_membership_proof_size = if utxos.is_empty() {
+            0.0
+        } else {
+            utxos
+                .iter()
+                .map(|utxo| utxo.membership_proof_size)
+                .sum::<usize>() as f64
+                / utxos.len() as f64
+        };
+
+        Self {
+            num_utxos: utxos.len(),
+            total_amount,
+            value_buckets,
+            age_buckets,
+            num_dust_utxos: dust.len(),
+            dust_amount: dust.iter().map(|utxo| utxo.amount).sum(),
+            average_membership_proof_size,
+            consolidation_plan: Self::consolidation_plan(utxos, dust_threshold),
+        }
+    }
+
+    /// Time since confirmation. Zero for UTXOs confirmed in the future, which
+    /// is possible since block timestamps are set by their composers.
+    fn age(utxo: &UtxoSummary, now: Timestamp) -> Timestamp {
+        if utxo.confirmed < now {
+            now - utxo.confirmed
+        } else {
+            Timestamp::zero()
+        }
+    }
+
+    /// Suggest merging the smallest spendable UTXOs if the wallet holds too
+    /// many of them, or if it holds several dust UTXOs.
+    fn consolidation_plan(
+        utxos: &[UtxoSummary],
+        dust_threshold: NativeCurrencyAmount,
+    ) -> Option<ConsolidationPlan> {
+        let spendable = utxos
+            .iter()
+            .filter(|utxo| utxo.is_spendable)
+            .sorted_by_key(|utxo| utxo.amount)
+            .collect_vec();
+        let num_spendable_dust = spendable
+            .iter()
+            .filter(|utxo| utxo.amount < dust_threshold)
+            .count();
+
+        let num_excess = spendable.len().saturating_sub(FRAGMENTATION_THRESHOLD - 1);
+        let num_inputs = num_excess
+            .max(num_spendable_dust)
+            .min(MAX_CONSOLIDATION_INPUTS);
+        if num_inputs < 2 {
+            return None;
+        }
+
+        let inputs = &spendable[..num_inputs];
+        Some(ConsolidationPlan {
+            inputs: inputs.iter().map(|utxo| utxo.aocl_leaf_index).collect(),
+            total_amount: inputs.iter().map(|utxo| utxo.amount).sum(),
+            num_utxos_after: utxos.len() - num_inputs + 1,
+        })
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    fn utxo(
+        aocl_leaf_index: u64,
+        amount: NativeCurrencyAmount,
+        confirmed: Timestamp,
+    ) -> UtxoSummary {
+        UtxoSummary {
+            aocl_leaf_index,
+            amount,
+            confirmed,
+            is_spendable: true,
+            membership_proof_size: 100 + aocl_leaf_index as usize,
+        }
+    }
+
+    #[test]
+    fn utxos_are_bucketed_by_value_and_age() {
+        let now = Timestamp::now();
+        let dust_threshold =
+            NativeCurrencyAmount::from_nau(NativeCurrencyAmount::coins(1).to_nau() / 10);
+        let utxos = [
+            utxo(
+                0,
+                NativeCurrencyAmount::from_nau(dust_threshold.to_nau() / 10),
+                now,
+            ),
+            utxo(1, NativeCurrencyAmount::coins(5), now - Timestamp::days(2)),
+            utxo(
+                2,
+                NativeCurrencyAmount::coins(50),
+                now - Timestamp::days(400),
+            ),
+        ];
+
+        let stats = WalletStats::new(&utxos, now, dust_threshold);
+        assert_eq!(3, stats.num_utxos);
+        assert_eq!(
+            vec![1, 1, 1, 0, 0, 0],
+            stats
+                .value_buckets
+                .iter()
+                .map(|bucket| bucket.num_utxos)
+                .collect_vec()
+        );
+        assert_eq!(
+            vec![1, 1, 0, 0, 1],
+            stats
+                .age_buckets
+                .iter()
+                .map(|bucket| bucket.num_utxos)
+                .collect_vec()
+        );
+        assert_eq!(1, stats.num_dust_utxos);
+        assert_eq!(101.0, stats.average_membership_proof_size);
+        assert!(stats.consolidation_plan.is_none());
+    }
+
+    #[test]
+    fn fragmented_wallet_gets_consolidation_plan_of_smallest_spendable_utxos() {
+        let now = Timestamp::now();
+        let mut utxos = (0..30)
+            .map(|i| utxo(i, NativeCurrencyAmount::coins(i as u32 + 1), now))
+            .collect_vec();
+        utxos[0].is_spendable = false;
+
+        let stats = WalletStats::new(&utxos, now, NativeCurrencyAmount::zero());
+        let plan = stats.consolidation_plan.unwrap();
+        assert_eq!((1..=10).collect_vec(), plan.inputs);
+        assert_eq!(21, plan.num_utxos_after);
+    }
+}
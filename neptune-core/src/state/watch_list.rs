@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Digest;
+use tasm_lib::triton_vm::prelude::BFieldElement;
+use tracing::info;
+
+use crate::application::config::network::Network;
+use crate::protocol::consensus::block::block_height::BlockHeight;
+use crate::protocol::consensus::block::Block;
+use crate::protocol::consensus::transaction::announcement::Announcement;
+use crate::protocol::proof_abstractions::timestamp::Timestamp;
+use crate::state::wallet::address::receiver_identifier_from_announcement;
+use crate::state::wallet::address::ReceivingAddress;
+
+/// Number of matches retained by the node, most recent last.
+pub(crate) const MAX_NUM_RETAINED_WATCH_MATCHES: usize = 10_000;
+
+/// Something an operator asks the node to watch for in new blocks.
+///
+/// Watching does not involve the wallet and requires no secret material:
+/// new blocks are matched against the receiver identifiers in their
+/// announcements, which are public.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WatchItem {
+    /// The receiver identifier that announcements of UTXOs for some address
+    /// carry.
+    ReceiverIdentifier(BFieldElement),
+
+    /// A bech32m-encoded address. Only its receiver identifier is retained,
+    /// so watching a symmetric address does not store its key.
+    Address(String),
+}
+
+impl WatchItem {
+    fn receiver_identifier(&self, network: Network) -> Result<BFieldElement> {
+        match self {
+            Self::ReceiverIdentifier(receiver_identifier) => Ok(*receiver_identifier),
+            Self::Address(encoded) => {
+                Ok(ReceivingAddress::from_bech32m(encoded, network)?.receiver_identifier())
+            }
+        }
+    }
+}
+
+/// A receiver identifier being watched.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchEntry {
+    pub receiver_identifier: BFieldElement,
+
+    /// Operator-chosen description, reported alongside matches.
+    pub label: String,
+}
+
+/// An announcement in a canonical block that carries a watched receiver
+/// identifier.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchMatch {
+    pub receiver_identifier: BFieldElement,
+    pub label: String,
+    pub block_digest: Digest,
+    pub block_height: BlockHeight,
+    pub block_timestamp: Timestamp,
+
+    /// Index of the matching announcement in the block's transaction kernel.
+    pub announcement_index: usize,
+}
+
+/// The receiver identifiers watched by this node and their matches in recent
+/// canonical blocks. Only updated by the main task and the RPC server. Not
+/// persisted across restarts.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WatchList {
+    entries: HashMap<BFieldElement, WatchEntry>,
+    matches: VecDeque<WatchMatch>,
+}
+
+impl WatchList {
+    /// Start watching `item`, replacing the label if it is already watched.
+    /// Returns the receiver identifier that is matched against new blocks.
+    pub(crate) fn watch(
+        &mut self,
+        item: &WatchItem,
+        label: String,
+        network: Network,
+    ) -> Result<BFieldElement> {
+        let receiver_identifier = item.receiver_identifier(network)?;
+        self.entries.insert(
+            receiver_identifier,
+            WatchEntry {
+                receiver_identifier,
+                label,
+            },
+        );
+
+        Ok(receiver_identifier)
+    }
+
+    /// Stop watching `receiver_identifier`. Matches already recorded are
+    /// retained. Returns true iff it was watched.
+    pub(crate) fn unwatch(&mut self, receiver_identifier: BFieldElement) -> bool {
+        self.entries.remove(&receiver_identifier).is_some()
+    }
+
+    pub(crate) fn entries(&self) -> Vec<WatchEntry> {
+        self.entries.values().cloned().collect()
+    }
+
+    /// Retained matches in blocks at or above `from_height`, oldest first.
+    pub(crate) fn matches(&self, from_height: BlockHeight) -> Vec<WatchMatch> {
+        self.matches
+            .iter()
+            .filter(|watch_match| watch_match.block_height >= from_height)
+            .cloned()
+            .collect()
+    }
+
+    /// Record the matches in a new tip.
+    pub(crate) fn handle_new_tip(&mut self, new_tip: &Block) {
+        self.record_matches(
+            new_tip.hash(),
+            new_tip.header().height,
+            new_tip.header().timestamp,
+            &new_tip.body().transaction_kernel.announcements,
+        );
+    }
+
+    fn record_matches(
+        &mut self,
+        block_digest: Digest,
+        block_height: BlockHeight,
+        block_timestamp: Timestamp,
+        announcements: &[Announcement],
+    ) {
+        // Matches at or above the new tip's height were found in blocks that
+        // are no longer canonical.
+        while self
+            .matches
+            .back()
+            .is_some_and(|watch_match| watch_match.block_height >= block_height)
+        {
+            self.matches.pop_back();
+        }
+
+        if self.entries.is_empty() {
+            return;
+        }
+
+        for (announcement_index, announcement) in announcements.iter().enumerate() {
+            let Ok(receiver_identifier) = receiver_identifier_from_announcement(announcement)
+            else {
+                continue;
+            };
+            let Some(entry) = self.entries.get(&receiver_identifier) else {
+                continue;
+            };
+
+            info!(
+                "Watched receiver identifier {receiver_identifier} ({}) matched announcement \
+                 {announcement_index} in block {block_digest:x} at height {block_height}",
+                entry.label,
+            );
+            self.matches.push_back(WatchMatch {
+                receiver_identifier,
+                label: entry.label.clone(),
+                block_digest,
+                block_height,
+                block_timestamp,
+                announcement_index,
+            });
+        }
+
+        while self.matches.len() > MAX_NUM_RETAINED_WATCH_MATCHES {
+            self.matches.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use rand::random;
+    use tasm_lib::twenty_first::bfe;
+
+    use super::*;
+    use crate::state::wallet::address::generation_address::GenerationSpendingKey;
+
+    fn announcement_for(receiver_identifier: BFieldElement) -> Announcement {
+        Announcement::new(vec![bfe!(79), receiver_identifier, bfe!(1), bfe!(2)])
+    }
+
+    #[test]
+    fn matches_watched_addresses_and_forgets_abandoned_blocks() {
+        let network = Network::Main;
+        let address: ReceivingAddress = GenerationSpendingKey::derive_from_seed(random())
+            .to_address()
+            .into();
+        let item = WatchItem::Address(address.to_bech32m(network).unwrap());
+
+        let mut watch_list = WatchList::default();
+        let receiver_identifier = watch_list
+            .watch(&item, "exchange".to_string(), network)
+            .unwrap();
+        assert_eq!(address.receiver_identifier(), receiver_identifier);
+
+        let announcements = vec![
+            announcement_for(random()),
+            announcement_for(receiver_identifier),
+            Announcement::new(vec![]),
+        ];
+        watch_list.record_matches(random(), 5u64.into(), Timestamp::now(), &announcements);
+        let matches = watch_list.matches(BlockHeight::genesis());
+        assert_eq!(1, matches.len());
+        assert_eq!(1, matches[0].announcement_index);
+        assert_eq!("exchange", matches[0].label);
+
+        // A competing block at the same height replaces the match.
+        watch_list.record_matches(random(), 5u64.into(), Timestamp::now(), &[]);
+        assert!(watch_list.matches(BlockHeight::genesis()).is_empty());
+
+        assert!(watch_list.unwatch(receiver_identifier));
+        watch_list.record_matches(random(), 6u64.into(), Timestamp::now(), &announcements);
+        assert!(watch_list.matches(BlockHeight::genesis()).is_empty());
+    }
+}
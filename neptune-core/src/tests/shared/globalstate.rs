@@ -8,6 +8,7 @@ use tokio::sync::mpsc;
 
 use crate::api::export::Network;
 use crate::application::config::cli_args;
+use crate::application::loops::channel::backpressure::BackpressureSender;
 use crate::application::loops::channel::MainToPeerTask;
 use crate::application::loops::channel::PeerTaskToMain;
 use crate::protocol::consensus::block::Block;
@@ -160,7 +161,7 @@ pub(crate) async fn get_test_genesis_setup(
 ) -> anyhow::Result<(
     broadcast::Sender<MainToPeerTask>,
     broadcast::Receiver<MainToPeerTask>,
-    mpsc::Sender<PeerTaskToMain>,
+    BackpressureSender<PeerTaskToMain>,
     mpsc::Receiver<PeerTaskToMain>,
     GlobalStateLock,
     HandshakeData,
@@ -177,7 +178,7 @@ pub(crate) async fn test_setup_custom_genesis_block(
 ) -> anyhow::Result<(
     broadcast::Sender<MainToPeerTask>,
     broadcast::Receiver<MainToPeerTask>,
-    mpsc::Sender<PeerTaskToMain>,
+    BackpressureSender<PeerTaskToMain>,
     mpsc::Receiver<PeerTaskToMain>,
     GlobalStateLock,
     HandshakeData,
@@ -185,6 +186,7 @@ pub(crate) async fn test_setup_custom_genesis_block(
     let (peer_broadcast_tx, from_main_rx) =
         broadcast::channel::<MainToPeerTask>(PEER_CHANNEL_CAPACITY);
     let (to_main_tx, to_main_rx) = mpsc::channel::<PeerTaskToMain>(PEER_CHANNEL_CAPACITY);
+    let to_main_tx = BackpressureSender::blocking("peer task to main", to_main_tx);
 
     let wallet = WalletEntropy::devnet_wallet();
     let state = mock_genesis_global_state_with_block(peer_count, wallet, cli, custom_genesis).await;
@@ -16,6 +16,7 @@ use tasm_lib::twenty_first::util_types::mmr::shared_basic::leaf_index_to_mt_inde
 use tasm_lib::twenty_first::util_types::mmr::shared_basic::right_lineage_length_from_leaf_index;
 
 use crate::application::database::storage::storage_schema::DbtVec;
+use crate::application::database::storage::storage_schema::ReadCacheMetrics;
 use crate::application::database::storage::storage_vec::traits::*;
 
 /// A Merkle Mountain Range is a datastructure for storing a list of hashes.
@@ -371,6 +372,11 @@ impl ArchivalMmr<DbtVec<Digest>> {
     pub(crate) async fn delete_cache(&mut self) {
         self.digests.delete_cache().await;
     }
+
+    /// The effectiveness of the read cache of the MMR's nodes, if enabled.
+    pub(crate) fn read_cache_metrics(&self) -> Option<ReadCacheMetrics> {
+        self.digests.read_cache_metrics()
+    }
 }
 
 #[cfg(test)]
@@ -11,6 +11,7 @@ pub mod active_window;
 pub mod addition_record;
 pub mod archival_mutator_set;
 pub mod authenticated_item;
+pub mod membership_bundle;
 pub mod mmra_and_membership_proofs;
 pub mod ms_membership_proof;
 #[cfg(any(test, feature = "arbitrary-impls"))]
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::error::Error;
+use std::ops::Range;
 
 use itertools::Itertools;
 use serde::Deserialize;
@@ -11,6 +12,9 @@ use tasm_lib::twenty_first::util_types::mmr::mmr_accumulator::MmrAccumulator;
 
 use super::active_window::ActiveWindow;
 use super::addition_record::AdditionRecord;
+use super::membership_bundle::inactive_chunk_range;
+use super::membership_bundle::BundledUtxo;
+use super::membership_bundle::MembershipBundle;
 use super::ms_membership_proof::MsMembershipProof;
 use super::mutator_set_accumulator::MutatorSetAccumulator;
 use super::removal_record::chunk::Chunk;
@@ -405,6 +409,97 @@ where
         })
     }
 
+    /// Find those of the `remaining` addition records that are in the given
+    /// range of AOCL leafs, and remove them from `remaining`. Returns the found
+    /// addition records along with their AOCL leaf indices.
+    ///
+    /// The range is searched from its end backwards, and is cut off at the
+    /// number of leafs.
+    pub(crate) async fn locate_addition_records(
+        &self,
+        remaining: &mut Vec<AdditionRecord>,
+        leaf_range: Range<u64>,
+    ) -> Vec<(AdditionRecord, u64)> {
+        let search_end = leaf_range.end.min(self.aocl.num_leafs().await);
+        let search_start = leaf_range.start;
+        if remaining.is_empty() || search_start >= search_end {
+            return vec![];
+        }
+
+        let leafs = self
+            .aocl
+            .get_leaf_range_inclusive_async(search_start..=search_end - 1)
+            .await;
+        let mut located = vec![];
+        for (leaf_index, leaf) in (search_start..search_end).zip_eq(leafs).rev() {
+            if let Some(position) = remaining
+                .iter()
+                .position(|record| record.canonical_commitment == leaf)
+            {
+                located.push((remaining.swap_remove(position), leaf_index));
+            }
+        }
+
+        located
+    }
+
+    /// Produce a [`MembershipBundle`] for the given addition records, located
+    /// with [`Self::locate_addition_records`], from which their owner can
+    /// assemble membership proofs valid at the tip identified by `tip_height`
+    /// and `tip_hash`, which must be the block this archival mutator set is
+    /// synced to.
+    ///
+    /// Addition records that are no longer at their AOCL leaf index, e.g.
+    /// because of a reorganization since they were located, are reported as
+    /// missing along with `missing`.
+    pub(crate) async fn membership_bundle(
+        &self,
+        located: Vec<(AdditionRecord, u64)>,
+        mut missing: Vec<AdditionRecord>,
+        tip_height: BlockHeight,
+        tip_hash: Digest,
+    ) -> Result<MembershipBundle, Box<dyn Error>> {
+        let mutator_set_accumulator = self.accumulator().await;
+        let num_inactive_chunks = mutator_set_accumulator.get_batch_index();
+        let num_leafs = self.aocl.num_leafs().await;
+
+        let mut utxos = vec![];
+        let mut chunk_indices = vec![];
+        for (addition_record, aocl_leaf_index) in located {
+            if aocl_leaf_index >= num_leafs
+                || self.aocl.get_leaf_async(aocl_leaf_index).await
+                    != addition_record.canonical_commitment
+            {
+                missing.push(addition_record);
+                continue;
+            }
+
+            utxos.push(BundledUtxo {
+                addition_record,
+                aocl_leaf_index,
+                auth_path_aocl: self.get_aocl_authentication_path(aocl_leaf_index).await?,
+            });
+            chunk_indices.extend(inactive_chunk_range(aocl_leaf_index, num_inactive_chunks));
+        }
+
+        let mut chunks = vec![];
+        for chunk_index in chunk_indices.into_iter().sorted().dedup() {
+            chunks.push((
+                chunk_index,
+                self.get_chunk_and_auth_path(chunk_index).await?,
+            ));
+        }
+
+        Ok(MembershipBundle {
+            tip_height,
+            tip_hash,
+            mutator_set_accumulator,
+            utxos,
+            chunks,
+            missing,
+        })
+    }
+
     /// Revert the `RemovalRecord` by removing the indices that
     /// were inserted by it. These live in either the active window, or
     /// in a relevant chunk.
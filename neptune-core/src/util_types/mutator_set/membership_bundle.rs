@@ -0,0 +1,177 @@
+use itertools::Itertools;
+use serde::Deserialize;
+use serde::Serialize;
+use tasm_lib::prelude::Digest;
+use tasm_lib::twenty_first::util_types::mmr::mmr_membership_proof::MmrMembershipProof;
+
+use super::addition_record::AdditionRecord;
+use super::commit;
+use super::ms_membership_proof::MsMembershipProof;
+use super::mutator_set_accumulator::MutatorSetAccumulator;
+use super::removal_record::absolute_index_set::AbsoluteIndexSet;
+use super::removal_record::chunk::Chunk;
+use super::removal_record::chunk_dictionary::ChunkDictionary;
+use super::shared::BATCH_SIZE;
+use super::shared::CHUNK_SIZE;
+use super::shared::WINDOW_SIZE;
+use crate::protocol::consensus::block::block_height::BlockHeight;
+
+/// Maximum number of addition records a [`MembershipBundle`] can be requested
+/// for. Every UTXO can require up to `WINDOW_SIZE / CHUNK_SIZE` authenticated
+/// chunks, so bundles grow quickly.
+pub const MAX_MEMBERSHIP_BUNDLE_SIZE: usize = 16;
+
+/// Number of most recent AOCL leafs searched for the addition records of a
+/// [`MembershipBundle`]. Older UTXOs are reported as missing, and their
+/// membership proofs must be restored otherwise, e.g. with
+/// `restore_membership_proof_privacy_preserving`.
+pub const MAX_MEMBERSHIP_BUNDLE_SEARCH_DEPTH: u64 = 1 << 20;
+
+/// A UTXO located in the append-only commitment list (AOCL) of the mutator
+/// set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundledUtxo {
+    pub addition_record: AdditionRecord,
+    pub aocl_leaf_index: u64,
+    pub auth_path_aocl: MmrMembershipProof,
+}
+
+/// Everything a wallet needs to spend a set of UTXOs at a given tip, without
+/// having synced the block bodies, as produced by an archival node.
+///
+/// For every requested addition record, the bundle contains its AOCL leaf
+/// index and authentication path, along with all inactive chunks of the
+/// sliding-window Bloom filter that the UTXO's removal indices can fall into.
+/// The archival node thus learns which addition records the wallet is
+/// interested in, but not which chunks its removal records will touch.
+///
+/// Since the archival node does not know the UTXOs' preimages, the membership
+/// proofs are assembled by the wallet with
+/// [`MembershipBundle::membership_proof`]. They are valid against
+/// `mutator_set_accumulator`, i.e., at block `tip_hash`, and must be kept up
+/// to date from there on, like any other membership proof.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MembershipBundle {
+    pub tip_height: BlockHeight,
+    pub tip_hash: Digest,
+    pub mutator_set_accumulator: MutatorSetAccumulator,
+
+    /// The requested UTXOs that were found in the AOCL.
+    pub utxos: Vec<BundledUtxo>,
+
+    /// Authenticated inactive chunks, shared by all UTXOs, sorted by chunk
+    /// index.
+    pub chunks: Vec<(u64, (MmrMembershipProof, Chunk))>,
+
+    /// The requested addition records that are not in the AOCL.
+    pub missing: Vec<AdditionRecord>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum MembershipBundleError {
+    #[error("addition record is not contained in the bundle")]
+    UnknownAdditionRecord,
+
+    #[error("bundle lacks inactive chunk {0}")]
+    MissingChunk(u64),
+
+    #[error("assembled membership proof does not verify against the bundle's mutator set")]
+    InvalidProof,
+}
+
+/// The range of chunk indices that the removal indices of the UTXO at
+/// `aocl_leaf_index` can fall into, restricted to the `num_inactive_chunks`
+/// chunks that have left the active window.
+pub(crate) fn inactive_chunk_range(
+    aocl_leaf_index: u64,
+    num_inactive_chunks: u64,
+) -> std::ops::Range<u64> {
+    let window_start = aocl_leaf_index / u64::from(BATCH_SIZE);
+    let window_end = window_start + u64::from(WINDOW_SIZE / CHUNK_SIZE);
+
+    window_start.min(num_inactive_chunks)..window_end.min(num_inactive_chunks)
+}
+
+impl MembershipBundle {
+    /// Assemble the membership proof for the UTXO with the given preimage.
+    ///
+    /// The proof is verified against the bundle's mutator set before it is
+    /// returned.
+    pub fn membership_proof(
+        &self,
+        item: Digest,
+        sender_randomness: Digest,
+        receiver_preimage: Digest,
+    ) -> Result<MsMembershipProof, MembershipBundleError> {
+        let addition_record = commit(item, sender_randomness, receiver_preimage.hash());
+        let utxo = self
+            .utxos
+            .iter()
+            .find(|utxo| utxo.addition_record == addition_record)
+            .ok_or(MembershipBundleError::UnknownAdditionRecord)?;
+
+        let num_inactive_chunks = self.mutator_set_accumulator.get_batch_index();
+        let chunk_indices = AbsoluteIndexSet::compute(
+            item,
+            sender_randomness,
+            receiver_preimage,
+            utxo.aocl_leaf_index,
+        )
+        .to_array()
+        .into_iter()
+        .map(|index| (index / u128::from(CHUNK_SIZE)) as u64)
+        .filter(|chunk_index| *chunk_index < num_inactive_chunks)
+        .unique()
+        .collect_vec();
+
+        let mut target_chunks = vec![];
+        for chunk_index in chunk_indices {
+            let Ok(position) = self
+                .chunks
+                .binary_search_by_key(&chunk_index, |(index, _)| *index)
+            else {
+                return Err(MembershipBundleError::MissingChunk(chunk_index));
+            };
+            target_chunks.push(self.chunks[position].clone());
+        }
+
+        let membership_proof = MsMembershipProof {
+            sender_randomness,
+            receiver_preimage,
+            auth_path_aocl: utxo.auth_path_aocl.clone(),
+            aocl_leaf_index: utxo.aocl_leaf_index,
+            target_chunks: ChunkDictionary::new(target_chunks),
+        };
+
+        if !self.mutator_set_accumulator.verify(item, &membership_proof) {
+            return Err(MembershipBundleError::InvalidProof);
+        }
+
+        Ok(membership_proof)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inactive_chunk_range_is_bounded_by_window_and_inactive_chunks() {
+        let chunks_per_window = u64::from(WINDOW_SIZE / CHUNK_SIZE);
+
+        assert!(inactive_chunk_range(0, 0).is_empty());
+        assert_eq!(0..3, inactive_chunk_range(0, 3));
+        assert_eq!(
+            0..chunks_per_window,
+            inactive_chunk_range(0, 10 * chunks_per_window)
+        );
+
+        let leaf_index = 5 * u64::from(BATCH_SIZE) + 1;
+        assert_eq!(
+            5..5 + chunks_per_window,
+            inactive_chunk_range(leaf_index, 10 * chunks_per_window)
+        );
+        assert!(inactive_chunk_range(leaf_index, 4).is_empty());
+    }
+}
@@ -3,6 +3,9 @@ use tasm_lib::twenty_first::tip5::digest::Digest;
 use super::active_window::ActiveWindow;
 use super::archival_mutator_set::ArchivalMutatorSet;
 use super::removal_record::chunk::Chunk;
+use crate::application::database::read_cache_budget::read_cache_capacity;
+use crate::application::database::read_cache_budget::CachedTable;
+use crate::application::database::read_cache_budget::TableReadCacheMetrics;
 use crate::application::database::storage::storage_schema::traits::*;
 use crate::application::database::storage::storage_schema::DbtSingleton;
 use crate::application::database::storage::storage_schema::DbtVec;
@@ -31,9 +34,21 @@ impl RustyArchivalMutatorSet {
             crate::LOG_TOKIO_LOCK_EVENT_CB,
         );
 
-        let aocl = storage.schema.new_vec::<Digest>("aocl").await;
-        let swbfi = storage.schema.new_vec::<Digest>("swbfi").await;
-        let chunks = storage.schema.new_vec::<Chunk>("chunks").await;
+        let aocl = storage
+            .schema
+            .new_vec::<Digest>("aocl")
+            .await
+            .with_read_cache(read_cache_capacity(CachedTable::MutatorSetAocl));
+        let swbfi = storage
+            .schema
+            .new_vec::<Digest>("swbfi")
+            .await
+            .with_read_cache(read_cache_capacity(CachedTable::MutatorSetSwbfInactive));
+        let chunks = storage
+            .schema
+            .new_vec::<Chunk>("chunks")
+            .await
+            .with_read_cache(read_cache_capacity(CachedTable::MutatorSetChunks));
         let active_window = storage
             .schema
             .new_singleton::<Vec<u32>>("active_window")
@@ -75,6 +90,32 @@ impl RustyArchivalMutatorSet {
         self.sync_label.set(sync_label).await;
     }
 
+    /// The effectiveness of the read caches of the mutator set's tables.
+    pub(crate) fn read_cache_metrics(&self) -> Vec<TableReadCacheMetrics> {
+        [
+            (
+                CachedTable::MutatorSetChunks,
+                self.ams.chunks.read_cache_metrics(),
+            ),
+            (
+                CachedTable::MutatorSetAocl,
+                self.ams.aocl.read_cache_metrics(),
+            ),
+            (
+                CachedTable::MutatorSetSwbfInactive,
+                self.ams.swbf_inactive.read_cache_metrics(),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(table, metrics)| {
+            Some(TableReadCacheMetrics {
+                table,
+                metrics: metrics?,
+            })
+        })
+        .collect()
+    }
+
     pub async fn restore_or_new(&mut self) {
         // The field `digests` of ArchivalMMR should always have at
         // least one element (a dummy digest), owing to 1-indexation.
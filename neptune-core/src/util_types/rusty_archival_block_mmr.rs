@@ -1,6 +1,9 @@
 use tasm_lib::prelude::Digest;
 
 use super::archival_mmr::ArchivalMmr;
+use crate::application::database::read_cache_budget::read_cache_capacity;
+use crate::application::database::read_cache_budget::CachedTable;
+use crate::application::database::read_cache_budget::TableReadCacheMetrics;
 use crate::application::database::storage::storage_schema::traits::*;
 use crate::application::database::storage::storage_schema::DbtVec;
 use crate::application::database::storage::storage_schema::RustyKey;
@@ -24,7 +27,11 @@ impl RustyArchivalBlockMmr {
 
         // We do not need a sync-label since the last leaf of the MMR will
         // be the sync-label, i.e., the block digest of the latest block added.
-        let abmmr = storage.schema.new_vec::<Digest>("archival_block_mmr").await;
+        let abmmr = storage
+            .schema
+            .new_vec::<Digest>("archival_block_mmr")
+            .await
+            .with_read_cache(read_cache_capacity(CachedTable::ArchivalBlockMmr));
         let abmmr = ArchivalMmr::new(abmmr).await;
 
         Self {
@@ -42,6 +49,14 @@ impl RustyArchivalBlockMmr {
     pub fn ammr_mut(&mut self) -> &mut ArchivalMmr<DbtVec<Digest>> {
         &mut self.ammr
     }
+
+    /// The effectiveness of the read cache of the MMR's nodes, if enabled.
+    pub(crate) fn read_cache_metrics(&self) -> Option<TableReadCacheMetrics> {
+        Some(TableReadCacheMetrics {
+            table: CachedTable::ArchivalBlockMmr,
+            metrics: self.ammr.read_cache_metrics()?,
+        })
+    }
 }
 
 impl StorageWriter for RustyArchivalBlockMmr {
@@ -0,0 +1,104 @@
+use neptune_cash::api::amount::error::AmountError;
+use neptune_cash::api::amount::DenominatedAmount;
+use neptune_cash::api::amount::Denomination;
+use neptune_cash::api::export::NativeCurrencyAmount;
+
+/// test: amounts in every denomination survive formatting and parsing
+/// unchanged.
+#[test]
+pub fn amounts_round_trip_in_every_denomination() -> Result<(), AmountError> {
+    let amounts = [
+        NativeCurrencyAmount::coins(42_000_000),
+        NativeCurrencyAmount::coins(3).half(),
+        -NativeCurrencyAmount::coins(7),
+        Denomination::Nau.from_units(1)?,
+        Denomination::Nau.from_units(-123_456_789)?,
+    ];
+
+    for amount in amounts {
+        for denomination in Denomination::ALL {
+            let formatted = denomination.format(amount);
+            assert_eq!(amount, denomination.parse(&formatted)?, "{formatted}");
+
+            let denominated = DenominatedAmount::new(amount, denomination);
+            assert_eq!(denominated, denominated.to_string().parse()?);
+        }
+    }
+
+    Ok(())
+}
+
+/// test: amounts with and without units parse to the expected values.
+#[test]
+pub fn amounts_parse_with_optional_units() -> Result<(), AmountError> {
+    let one_and_a_half = NativeCurrencyAmount::coins(3).half();
+    for input in [
+        "1.5",
+        "1.5 NPT",
+        "1.5npt",
+        "1500 mNPT",
+        "1500000 uNPT",
+        "1500000µNPT",
+    ] {
+        let parsed: DenominatedAmount = input.parse()?;
+        assert_eq!(one_and_a_half, parsed.amount(), "{input}");
+    }
+
+    assert_eq!(
+        Denomination::Microcoin,
+        "2 uNPT".parse::<DenominatedAmount>()?.denomination()
+    );
+    assert_eq!(
+        "0.0015 NPT",
+        "1.5 mNPT"
+            .parse::<DenominatedAmount>()?
+            .with_denomination(Denomination::Coin)
+            .to_string()
+    );
+
+    Ok(())
+}
+
+/// test: invalid inputs and lossy conversions are rejected with specific
+/// errors rather than rounded.
+#[test]
+pub fn lossy_and_invalid_amounts_are_rejected() -> Result<(), AmountError> {
+    let parse = |s: &str| s.parse::<DenominatedAmount>();
+
+    assert!(matches!(
+        parse("0.5 nau"),
+        Err(AmountError::PrecisionLoss { .. })
+    ));
+    assert!(matches!(
+        parse("0.000000000000000000000000000000001"),
+        Err(AmountError::PrecisionLoss { .. })
+    ));
+    assert!(matches!(
+        parse("1.5 btc"),
+        Err(AmountError::UnknownDenomination(_))
+    ));
+    assert!(matches!(parse("1.2.3"), Err(AmountError::InvalidFormat(_))));
+    assert!(matches!(parse("."), Err(AmountError::InvalidFormat(_))));
+    assert!(matches!(parse(""), Err(AmountError::InvalidFormat(_))));
+    assert!(matches!(parse("42000001"), Err(AmountError::OutOfRange(_))));
+    assert!(matches!(
+        Denomination::Coin.from_units(42_000_001),
+        Err(AmountError::OutOfRange(_))
+    ));
+    assert!(matches!(
+        Denomination::Nau.from_units(i128::MAX),
+        Err(AmountError::OutOfRange(_))
+    ));
+
+    let one_nau = Denomination::Nau.from_units(1)?;
+    assert!(matches!(
+        Denomination::Coin.to_units(one_nau),
+        Err(AmountError::FractionalUnits { .. })
+    ));
+    assert_eq!(
+        1000,
+        Denomination::Millicoin.to_units(NativeCurrencyAmount::coins(1))?
+    );
+
+    Ok(())
+}
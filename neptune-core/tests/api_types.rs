@@ -0,0 +1,261 @@
+mod common;
+
+use common::genesis_node::GenesisNode;
+use common::logging;
+use neptune_cash::api::export::Block;
+use neptune_cash::api::export::KeyType;
+use neptune_cash::api::export::NativeCurrencyAmount;
+use neptune_cash::api::export::Network;
+use neptune_cash::api::export::Timestamp;
+use neptune_cash::api::types::BalanceReport;
+use neptune_cash::api::types::BlockSummary;
+use neptune_cash::api::types::CanonicalBlock;
+use neptune_cash::api::types::CanonicalBlockHeader;
+use neptune_cash::api::types::CanonicalTransactionKernel;
+use neptune_cash::api::types::TransactionSummary;
+use neptune_cash::api::types::API_TYPES_VERSION;
+use num_traits::Zero;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::json;
+
+/// assert that `serialized` deserializes into `T`, and that `T` serializes
+/// back into exactly `serialized`.
+fn assert_compatible<T: Serialize + DeserializeOwned>(serialized: serde_json::Value) {
+    let deserialized: T = serde_json::from_value(serialized.clone()).unwrap();
+    assert_eq!(serialized, serde_json::to_value(deserialized).unwrap());
+}
+
+/// test: the serialized form of the stable api types matches that of
+/// version 1.
+///
+/// If this test fails, the serialized form has changed, which requires a
+/// major bump of `API_TYPES_VERSION` and updated fixtures.
+#[test]
+pub fn api_types_are_compatible_with_version_1() {
+    assert!(API_TYPES_VERSION.starts_with("1."));
+
+    assert_compatible::<BlockSummary>(json!({
+        "digest": "7962e48729acd97e08efa77b5b28d49f2dc0e5609a4f1f1affca5b4549c78e520462a7f955371386",
+        "height": 0,
+        "prev_block_digest": "00000000000000000000000000000000000000000000000000000000000000000000000000000000",
+        "timestamp": 1754049600000u64,
+        "num_inputs": 0,
+        "num_outputs": 4,
+        "num_announcements": 0,
+        "fee": "0",
+        "coinbase": "7326400000000000000000000000000000000",
+    }));
+
+    assert_compatible::<TransactionSummary>(json!({
+        "txid": "7962e48729acd97e08efa77b5b28d49f2dc0e5609a4f1f1affca5b4549c78e520462a7f955371386",
+        "timestamp": 1754049600000u64,
+        "num_inputs": 2,
+        "num_outputs": 2,
+        "num_announcements": 1,
+        "fee": "50000000000000000000000000000",
+        "coinbase": null,
+    }));
+
+    // optional fields may be omitted
+    let without_coinbase: TransactionSummary = serde_json::from_value(json!({
+        "txid": "7962e48729acd97e08efa77b5b28d49f2dc0e5609a4f1f1affca5b4549c78e520462a7f955371386",
+        "timestamp": 1754049600000u64,
+        "num_inputs": 2,
+        "num_outputs": 2,
+        "num_announcements": 1,
+        "fee": "50000000000000000000000000000",
+    }))
+    .unwrap();
+    assert!(without_coinbase.coinbase.is_none());
+
+    let zero_digest =
+        "00000000000000000000000000000000000000000000000000000000000000000000000000000000";
+    let genesis_digest =
+        "7962e48729acd97e08efa77b5b28d49f2dc0e5609a4f1f1affca5b4549c78e520462a7f955371386";
+    let header = json!({
+        "version": 0,
+        "height": 0,
+        "prev_block_digest": zero_digest,
+        "timestamp": 1754049600000u64,
+        "pow": {
+            "root": zero_digest,
+            "path_a": [zero_digest, zero_digest],
+            "path_b": [zero_digest, zero_digest],
+            "nonce": zero_digest,
+        },
+        "cumulative_proof_of_work": "0",
+        "difficulty": "6000",
+        "guesser_receiver_digest": zero_digest,
+        "guesser_lock_script_hash": zero_digest,
+    });
+    assert_compatible::<CanonicalBlockHeader>(header.clone());
+
+    let transaction_kernel = json!({
+        "txid": genesis_digest,
+        "inputs": [{"absolute_indices": ["18446744073709551616", "18446744073709551617"]}],
+        "outputs": [genesis_digest],
+        "announcements": [{"message": ["1", "18446744069414584320"]}],
+        "fee": "50000000000000000000000000000",
+        "coinbase": null,
+        "timestamp": 1754049600000u64,
+        "mutator_set_hash": genesis_digest,
+        "merge_bit": true,
+    });
+    assert_compatible::<CanonicalTransactionKernel>(transaction_kernel.clone());
+
+    assert_compatible::<CanonicalBlock>(json!({
+        "digest": genesis_digest,
+        "header": header,
+        "transaction_kernel": transaction_kernel,
+    }));
+
+    assert_compatible::<BalanceReport>(json!({
+        "confirmed_available": "42000000000000000000000000000000000",
+        "confirmed_total": "48000000000000000000000000000000000",
+        "unconfirmed_available": "33000000000000000000000000000000000",
+        "unconfirmed_total": "39000000000000000000000000000000000",
+    }));
+}
+
+/// test: the canonical encodings of consensus types are obtained from the
+/// internal types.
+///
+/// scenario:
+/// 1. encode the genesis block canonically.
+/// 2. the header and kernel match the internal ones, with digests as hex and
+///    amounts as strings.
+/// 3. the JSON encoding carries exactly the documented fields.
+#[test]
+pub fn canonical_types_convert_from_internal_types() -> anyhow::Result<()> {
+    let genesis = Block::genesis(Network::Main);
+    let canonical = CanonicalBlock::from(&genesis);
+    assert_eq!(genesis.hash().to_hex(), canonical.digest);
+    assert_eq!(
+        CanonicalBlockHeader::from(genesis.header()),
+        canonical.header
+    );
+
+    let header = &canonical.header;
+    assert_eq!(0, header.height);
+    assert_eq!(genesis.header().timestamp.to_millis(), header.timestamp);
+    assert_eq!(
+        genesis.header().prev_block_digest.to_hex(),
+        header.prev_block_digest
+    );
+    assert_eq!(genesis.header().difficulty.to_string(), header.difficulty);
+    assert_eq!(genesis.header().pow.nonce.to_hex(), header.pow.nonce);
+
+    let internal_kernel = &genesis.body().transaction_kernel;
+    let kernel = &canonical.transaction_kernel;
+    assert_eq!(
+        CanonicalTransactionKernel::from(internal_kernel),
+        kernel.clone()
+    );
+    assert!(kernel.inputs.is_empty());
+    assert_eq!(internal_kernel.outputs.len(), kernel.outputs.len());
+    assert_eq!(
+        internal_kernel.outputs[0].canonical_commitment.to_hex(),
+        kernel.outputs[0]
+    );
+    assert_eq!(
+        internal_kernel.coinbase.map(|c| c.to_nau().to_string()),
+        kernel.coinbase
+    );
+    assert_eq!(
+        internal_kernel.mutator_set_hash.to_hex(),
+        kernel.mutator_set_hash
+    );
+
+    let serialized = serde_json::to_value(&canonical)?;
+    let keys = |value: &serde_json::Value| {
+        value
+            .as_object()
+            .map(|object| object.keys().cloned().collect::<Vec<_>>())
+            .unwrap_or_default()
+    };
+    assert_eq!(
+        vec!["digest", "header", "transaction_kernel"],
+        keys(&serialized)
+    );
+    assert_eq!(
+        vec![
+            "cumulative_proof_of_work",
+            "difficulty",
+            "guesser_lock_script_hash",
+            "guesser_receiver_digest",
+            "height",
+            "pow",
+            "prev_block_digest",
+            "timestamp",
+            "version",
+        ],
+        keys(&serialized["header"])
+    );
+    assert_eq!(canonical, serde_json::from_value(serialized)?);
+
+    Ok(())
+}
+
+/// test: the stable api types are obtained from the internal types.
+///
+/// scenario:
+/// 1. summarize the genesis block.
+/// 2. single unconnected node on regtest network
+/// 3. alice mines 3 blocks to her own wallet.
+/// 4. alice sends a payment to herself, and summarizes it and her balances.
+#[tokio::test(flavor = "multi_thread")]
+pub async fn api_types_convert_from_internal_types() -> anyhow::Result<()> {
+    logging::tracing_logger();
+
+    let genesis = Block::genesis(Network::Main);
+    let summary = BlockSummary::from(&genesis);
+    assert_eq!(genesis.hash().to_hex(), summary.digest);
+    assert_eq!(0, summary.height);
+    assert_eq!(genesis.header().timestamp.to_millis(), summary.timestamp);
+
+    // start alice's node, without any peers.
+    let mut alice = GenesisNode::start_default_node().await?;
+    let alice_address = alice
+        .gsl
+        .api_mut()
+        .wallet_mut()
+        .next_receiving_address(KeyType::Generation)
+        .await?;
+    alice
+        .gsl
+        .api_mut()
+        .regtest_mut()
+        .mine_blocks_to_wallet(3, false)
+        .await?;
+
+    let tx_artifacts = alice
+        .gsl
+        .api_mut()
+        .tx_sender_mut()
+        .send(
+            vec![(alice_address, NativeCurrencyAmount::coins_from_str("2.45")?)],
+            Default::default(),
+            NativeCurrencyAmount::zero(),
+            Timestamp::now(),
+        )
+        .await?;
+    let transaction = tx_artifacts.transaction();
+    let summary = TransactionSummary::from(transaction);
+    assert_eq!(transaction.txid().to_string(), summary.txid);
+    assert_eq!(transaction.kernel.inputs.len(), summary.num_inputs);
+    assert_eq!("0", summary.fee);
+
+    let balances = alice.gsl.api().wallet().balances(Timestamp::now()).await;
+    let report = BalanceReport::from(balances);
+    assert_eq!(
+        balances.confirmed_total.to_nau().to_string(),
+        report.confirmed_total
+    );
+    assert_eq!(
+        balances.unconfirmed_available.to_nau().to_string(),
+        report.unconfirmed_available
+    );
+
+    Ok(())
+}
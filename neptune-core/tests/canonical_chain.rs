@@ -0,0 +1,113 @@
+mod common;
+
+use common::genesis_node::GenesisNode;
+use common::logging;
+use futures::StreamExt;
+use neptune_cash::api::chain::error::ChainError;
+use neptune_cash::api::export::BlockHeight;
+
+/// test: walk the canonical chain via the public chain api
+///
+/// scenario:
+/// 1. single unconnected node on regtest network
+/// 2. alice mines 3 blocks to her own wallet.
+/// 3. alice walks the chain by height range and from a digest.
+#[tokio::test(flavor = "multi_thread")]
+pub async fn walk_canonical_chain() -> anyhow::Result<()> {
+    logging::tracing_logger();
+
+    // start alice's node, without any peers.
+    let mut alice = GenesisNode::start_default_node().await?;
+
+    // alice mines 3 blocks to her wallet
+    alice
+        .gsl
+        .api_mut()
+        .regtest_mut()
+        .mine_blocks_to_wallet(3, false)
+        .await?;
+
+    // walk a range extending beyond the tip. iteration stops at the tip.
+    let heights = BlockHeight::genesis()..=BlockHeight::from(10u64);
+    let mut blocks = alice.gsl.api().chain().canonical_blocks(heights).await?;
+
+    let mut digests = vec![];
+    while let Some(block) = blocks.next().await {
+        let block = block?;
+        assert_eq!(BlockHeight::from(digests.len() as u64), block.height());
+        digests.push(block.digest());
+    }
+    assert_eq!(4, digests.len());
+
+    // walk from block 1 to the tip, as a stream.
+    let from_block_1: Vec<_> = alice
+        .gsl
+        .api()
+        .chain()
+        .canonical_blocks_from(digests[1])
+        .await?
+        .into_stream()
+        .map(|block| block.map(|b| b.digest()))
+        .collect()
+        .await;
+    let from_block_1 = from_block_1.into_iter().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(digests[1..], from_block_1);
+
+    // unknown blocks are rejected.
+    let unknown = alice
+        .gsl
+        .api()
+        .chain()
+        .canonical_blocks_from(Default::default())
+        .await;
+    assert!(matches!(unknown, Err(ChainError::UnknownBlock(_))));
+
+    Ok(())
+}
+
+/// test: export chain data to CSV files via the public chain api
+///
+/// scenario:
+/// 1. single unconnected node on regtest network
+/// 2. alice mines 3 blocks to her own wallet.
+/// 3. alice exports a range extending beyond the tip.
+/// 4. each file has a header row and one row per block.
+/// 5. exporting the same range again is refused.
+#[tokio::test(flavor = "multi_thread")]
+pub async fn export_chain_data_to_csv() -> anyhow::Result<()> {
+    logging::tracing_logger();
+
+    // start alice's node, without any peers.
+    let mut alice = GenesisNode::start_default_node().await?;
+
+    // alice mines 3 blocks to her wallet
+    alice
+        .gsl
+        .api_mut()
+        .regtest_mut()
+        .mine_blocks_to_wallet(3, false)
+        .await?;
+
+    let heights = BlockHeight::from(1u64)..=BlockHeight::from(10u64);
+    let report = alice.gsl.api().chain().export_csv(heights.clone()).await?;
+    assert_eq!(3, report.num_blocks);
+    assert_eq!(Some(BlockHeight::from(3u64)), report.last_height);
+    assert_eq!(3, report.files.len());
+    assert!(!report.directory.with_extension("partial").exists());
+
+    for file in &report.files {
+        assert!(file.starts_with(&report.directory));
+        let contents = tokio::fs::read_to_string(file).await?;
+        let rows = contents.lines().collect::<Vec<_>>();
+        assert_eq!(4, rows.len());
+        assert!(rows[0].starts_with("height,"));
+        assert!(rows[1].starts_with("1,"));
+        assert!(rows[3].starts_with("3,"));
+    }
+
+    // earlier exports are not overwritten.
+    let again = alice.gsl.api().chain().export_csv(heights).await;
+    assert!(matches!(again, Err(ChainError::ExportFailed(_))));
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use common::genesis_node::GenesisNode;
+use common::logging;
+use neptune_cash::api::chain::error::ChainError;
+use neptune_cash::api::export::BlockHeight;
+
+/// test: report chain work and audit the fork choice via the public chain api
+///
+/// scenario:
+/// 1. single unconnected node on regtest network
+/// 2. alice mines 3 blocks to her own wallet.
+/// 3. the chain work grows with each block, and totals the ancestors' work
+///    plus the block's own difficulty.
+/// 4. the fork-choice audit reports the tip and no competing tips.
+#[tokio::test(flavor = "multi_thread")]
+pub async fn chain_work_and_fork_choice_audit() -> anyhow::Result<()> {
+    logging::tracing_logger();
+
+    // start alice's node, without any peers.
+    let mut alice = GenesisNode::start_default_node().await?;
+
+    // alice mines 3 blocks to her wallet
+    alice
+        .gsl
+        .api_mut()
+        .regtest_mut()
+        .mine_blocks_to_wallet(3, false)
+        .await?;
+
+    let tip_digest = alice.gsl.lock_guard().await.chain.light_state().hash();
+    let tip_work = alice.gsl.api().chain().chain_work(tip_digest).await?;
+    assert_eq!(BlockHeight::from(3u64), tip_work.height);
+    assert!(tip_work.is_canonical);
+
+    let predecessor_digest = alice
+        .gsl
+        .lock_guard()
+        .await
+        .chain
+        .light_state()
+        .header()
+        .prev_block_digest;
+    let predecessor_work = alice
+        .gsl
+        .api()
+        .chain()
+        .chain_work(predecessor_digest)
+        .await?;
+    assert_eq!(BlockHeight::from(2u64), predecessor_work.height);
+    assert!(predecessor_work.cumulative_proof_of_work < tip_work.cumulative_proof_of_work);
+    assert_eq!(
+        predecessor_work.total_proof_of_work,
+        tip_work.cumulative_proof_of_work
+    );
+
+    // unknown blocks are rejected.
+    let unknown = alice.gsl.api().chain().chain_work(Default::default()).await;
+    assert!(matches!(unknown, Err(ChainError::UnknownBlock(_))));
+
+    // the audit of a single, linear chain finds no competing tips.
+    let audit = alice.gsl.api().chain().fork_choice_audit(10).await?;
+    assert_eq!(tip_work, audit.tip);
+    assert_eq!(10, audit.depth);
+    assert!(audit.competing_tips.is_empty());
+    assert!(audit.sync_target_proof_of_work.is_none());
+
+    Ok(())
+}
@@ -19,8 +19,8 @@ pub async fn custom_coinbase_distribution() {
 
     // random ports to prevent multiple test runs from using same
     // socket.
-    cli_args.peer_port = rng.random_range((1 << 10)..=u16::MAX);
-    cli_args.rpc_port = rng.random_range((1 << 10)..=u16::MAX);
+    cli_args.peer_port = Some(rng.random_range((1 << 10)..=u16::MAX));
+    cli_args.rpc_port = Some(rng.random_range((1 << 10)..=u16::MAX));
 
     // Ensure entire block subsidy goes to composer
     cli_args.guesser_fraction = 0f64;
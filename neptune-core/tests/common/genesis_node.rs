@@ -72,7 +72,7 @@ impl GenesisNode {
                 .expect("Must be able to get socket on local host")
                 .port();
             args.peer_listen_addr = IpAddr::V4(Ipv4Addr::LOCALHOST);
-            args.peer_port = peer_port;
+            args.peer_port = Some(peer_port);
 
             let rpc_listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
                 .await
@@ -81,7 +81,7 @@ impl GenesisNode {
                 .local_addr()
                 .expect("Must be able to get socket on local host")
                 .port();
-            args.rpc_port = rpc_port;
+            args.rpc_port = Some(rpc_port);
         }
 
         // we default proving capability to primitive-witness as lowest common
@@ -133,10 +133,14 @@ impl GenesisNode {
     #[track_caller]
     pub fn instance_args(node_instance: u8, mut args: Args) -> Args {
         let caller = core::panic::Location::caller();
-        args.rpc_port =
-            Self::hash_string_to_port_range(&format!("rpc:{}{}", caller, node_instance));
-        args.peer_port =
-            Self::hash_string_to_port_range(&format!("peer:{}{}", caller, node_instance));
+        args.rpc_port = Some(Self::hash_string_to_port_range(&format!(
+            "rpc:{}{}",
+            caller, node_instance
+        )));
+        args.peer_port = Some(Self::hash_string_to_port_range(&format!(
+            "peer:{}{}",
+            caller, node_instance
+        )));
 
         if let Ok(dd) = Self::integration_test_data_directory(args.network) {
             args.data_dir = Some(dd.root_dir_path());
@@ -168,13 +172,11 @@ impl GenesisNode {
         let mut all_args = vec![];
 
         // fix ports based on the cluster_id
-        base_args.rpc_port = Self::hash_string_to_port_range(&format!("rpc:{}{}", cluster_id, 0));
-        base_args.peer_port = Self::hash_string_to_port_range(&format!("peer:{}{}", cluster_id, 0));
+        let base_rpc_port = Self::hash_string_to_port_range(&format!("rpc:{}{}", cluster_id, 0));
+        let base_peer_port = Self::hash_string_to_port_range(&format!("peer:{}{}", cluster_id, 0));
 
         let peers: Vec<_> = (0..u16::from(num_nodes))
-            .map(|v| {
-                SocketAddr::from_str(&format!("127.0.0.1:{}", base_args.peer_port + v)).unwrap()
-            })
+            .map(|v| SocketAddr::from_str(&format!("127.0.0.1:{}", base_peer_port + v)).unwrap())
             .collect();
 
         for i in 0..num_nodes {
@@ -186,8 +188,8 @@ impl GenesisNode {
                 .filter(|(x, _)| *x != usize::from(i))
                 .map(|(_, s)| s)
                 .collect();
-            args.peer_port = base_args.peer_port + u16::from(i);
-            args.rpc_port = base_args.rpc_port + u16::from(i);
+            args.peer_port = Some(base_peer_port + u16::from(i));
+            args.rpc_port = Some(base_rpc_port + u16::from(i));
             all_args.push(args)
         }
         tracing::debug!("all_args: {:#?}", all_args);
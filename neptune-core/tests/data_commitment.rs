@@ -0,0 +1,123 @@
+mod common;
+
+use common::genesis_node::GenesisNode;
+use common::logging;
+use neptune_cash::api::chain::error::ChainError;
+use neptune_cash::api::chain::DataCommitment;
+use neptune_cash::api::export::BlockHeight;
+use neptune_cash::api::export::NativeCurrencyAmount;
+use neptune_cash::api::export::Timestamp;
+
+/// test: commit to external data and prove its inclusion on-chain
+///
+/// scenario:
+/// 1. single unconnected node on regtest network
+/// 2. alice mines 3 blocks to her own wallet.
+/// 3. alice commits to a document and mines the commitment into block 4.
+/// 4. alice cannot prove the commitment while block 4 is the tip.
+/// 5. alice mines block 5, then proves and verifies the commitment.
+#[tokio::test(flavor = "multi_thread")]
+pub async fn data_commitment_is_provable() -> anyhow::Result<()> {
+    logging::tracing_logger();
+    let timeout_secs = 5;
+
+    // start alice's node, without any peers.
+    let mut alice = GenesisNode::start_default_node().await?;
+
+    // alice mines 3 blocks to her wallet
+    alice
+        .gsl
+        .api_mut()
+        .regtest_mut()
+        .mine_blocks_to_wallet(3, false)
+        .await?;
+
+    // alice commits to a document
+    let commitment = DataCommitment::from_bytes(b"alice's important document");
+    let tx_artifacts = alice
+        .gsl
+        .api_mut()
+        .tx_initiator_mut()
+        .send_data_commitments(
+            [commitment],
+            Default::default(),
+            NativeCurrencyAmount::coins_from_str("0.01")?,
+            Timestamp::now(),
+        )
+        .await?;
+
+    alice
+        .wait_until_tx_in_mempool_has_single_proof(tx_artifacts.transaction().txid(), timeout_secs)
+        .await?;
+
+    // alice mines the commitment into block 4
+    alice
+        .gsl
+        .api_mut()
+        .regtest_mut()
+        .mine_blocks_to_wallet(1, true)
+        .await?;
+
+    // the commitment is found, but block 4 has no successor yet.
+    let heights = BlockHeight::genesis()..=BlockHeight::from(10u64);
+    let result = alice
+        .gsl
+        .api()
+        .chain()
+        .find_data_commitment(commitment, heights.clone())
+        .await;
+    assert!(matches!(result, Err(ChainError::NoSuccessor(_))));
+
+    // alice mines block 5
+    alice
+        .gsl
+        .api_mut()
+        .regtest_mut()
+        .mine_blocks_to_wallet(1, false)
+        .await?;
+
+    let proof = alice
+        .gsl
+        .api()
+        .chain()
+        .find_data_commitment(commitment, heights)
+        .await?
+        .expect("commitment should be on-chain");
+    assert_eq!(BlockHeight::from(4u64), proof.block_height());
+    assert_eq!(BlockHeight::from(5u64), proof.anchor_height());
+
+    // the proof verifies against the anchor's block MMR, but not against
+    // that of an earlier block.
+    let mut anchor = alice
+        .gsl
+        .api()
+        .chain()
+        .canonical_blocks_from(proof.anchor_digest())
+        .await?;
+    let anchor = anchor.next().await.expect("anchor block should exist")?;
+    assert!(proof.verify(&anchor.block().body().block_mmr_accumulator));
+
+    let mut commitment_block = alice
+        .gsl
+        .api()
+        .chain()
+        .canonical_blocks_from(proof.block_digest())
+        .await?;
+    let commitment_block = commitment_block
+        .next()
+        .await
+        .expect("commitment block should exist")?;
+    assert!(!proof.verify(&commitment_block.block().body().block_mmr_accumulator));
+
+    // other data is not on-chain.
+    let other = DataCommitment::from_bytes(b"some other document");
+    assert!(alice
+        .gsl
+        .api()
+        .chain()
+        .find_data_commitment(other, BlockHeight::genesis()..=BlockHeight::from(10u64))
+        .await?
+        .is_none());
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::genesis_node::GenesisNode;
+use common::logging;
+use neptune_cash::api::export::BlockHeight;
+use neptune_cash::api::export::NativeCurrencyAmount;
+use neptune_cash::api::export::Timestamp;
+use neptune_cash::api::wallet::error::WalletError;
+
+/// test: the wallet's balance can be queried as of historical blocks
+///
+/// scenario:
+/// 1. single unconnected node on regtest network
+/// 2. alice mines 3 blocks to her own wallet.
+/// 3. alice's balance as of genesis is zero.
+/// 4. alice's balance as of the tip matches her current balance.
+/// 5. alice's balance as of block 1 lies in between.
+/// 6. alice's balance as of a block above the tip cannot be queried.
+#[tokio::test(flavor = "multi_thread")]
+pub async fn wallet_balance_at_historical_heights() -> anyhow::Result<()> {
+    logging::tracing_logger();
+
+    // start alice's node, without any peers.
+    let mut alice = GenesisNode::start_default_node().await?;
+
+    // alice mines 3 blocks to her wallet
+    alice
+        .gsl
+        .api_mut()
+        .regtest_mut()
+        .mine_blocks_to_wallet(3, false)
+        .await?;
+
+    // alice owned nothing at genesis
+    let at_genesis = alice
+        .gsl
+        .api()
+        .wallet()
+        .balance_at_height(BlockHeight::genesis())
+        .await?;
+    assert_eq!(BlockHeight::genesis(), at_genesis.block_height);
+    assert_eq!(NativeCurrencyAmount::zero(), at_genesis.confirmed_total);
+    assert!(at_genesis.utxos.is_empty());
+
+    // alice's balance as of the tip is her current balance
+    let tip_height = BlockHeight::from(3u64);
+    let at_tip = alice
+        .gsl
+        .api()
+        .wallet()
+        .balance_at_height(tip_height)
+        .await?;
+    let balances = alice.gsl.api().wallet().balances(Timestamp::now()).await;
+    assert_eq!(balances.confirmed_total, at_tip.confirmed_total);
+    assert!(!at_tip.utxos.is_empty());
+
+    // alice's balance grew with every block she mined
+    let at_height_1 = alice
+        .gsl
+        .api()
+        .wallet()
+        .balance_at_height(BlockHeight::from(1u64))
+        .await?;
+    assert!(at_height_1.confirmed_total > NativeCurrencyAmount::zero());
+    assert!(at_height_1.confirmed_total < at_tip.confirmed_total);
+    assert!(at_height_1.utxos.len() < at_tip.utxos.len());
+
+    // blocks above the tip have no balance yet
+    let result = alice
+        .gsl
+        .api()
+        .wallet()
+        .balance_at_height(tip_height.next())
+        .await;
+    assert!(matches!(result, Err(WalletError::HeightAboveTip { .. })));
+
+    Ok(())
+}
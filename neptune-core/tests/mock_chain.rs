@@ -0,0 +1,104 @@
+mod common;
+
+use common::logging;
+use neptune_cash::api::export::Block;
+use neptune_cash::api::export::Network;
+use neptune_cash::api::regtest::error::RegTestError;
+use neptune_cash::api::regtest::MockBlockOptions;
+use neptune_cash::api::regtest::MockChainGenerator;
+use neptune_cash::state::wallet::wallet_entropy::WalletEntropy;
+
+fn generator(seed: [u8; 32]) -> anyhow::Result<MockChainGenerator> {
+    Ok(MockChainGenerator::new(
+        Network::RegTest,
+        WalletEntropy::devnet_wallet(),
+        seed,
+    )?)
+}
+
+/// test: generated chains are valid and reproducible
+///
+/// scenario:
+/// 1. alice and bob each generate 3 blocks on top of genesis, from the same
+///    seed.
+/// 2. their chains are identical.
+/// 3. every block is a valid successor of its predecessor.
+#[tokio::test(flavor = "multi_thread")]
+pub async fn mock_chain_is_valid_and_reproducible() -> anyhow::Result<()> {
+    logging::tracing_logger();
+
+    let network = Network::RegTest;
+    let genesis = Block::genesis(network);
+    let alice = generator([1u8; 32])?.chain(&genesis, 3, MockBlockOptions::default())?;
+    let bob = generator([1u8; 32])?.chain(&genesis, 3, MockBlockOptions::default())?;
+
+    assert_eq!(
+        alice.iter().map(|b| b.hash()).collect::<Vec<_>>(),
+        bob.iter().map(|b| b.hash()).collect::<Vec<_>>()
+    );
+
+    let mut predecessor = &genesis;
+    for block in &alice {
+        let now = block.header().timestamp;
+        assert!(block.is_valid(predecessor, now, network).await);
+        assert!(block.has_proof_of_work(network, predecessor.header()));
+        predecessor = block;
+    }
+
+    Ok(())
+}
+
+/// test: forks and invalid-proof blocks can be generated
+///
+/// scenario:
+/// 1. alice generates 2 blocks on top of genesis.
+/// 2. alice generates a competing block 2 with three nop-transactions.
+/// 3. alice generates a block 3 with an invalid proof.
+/// 4. the fork is valid and differs from the original block 2, while the
+///    invalid-proof block is rejected.
+#[tokio::test(flavor = "multi_thread")]
+pub async fn mock_chain_forks_and_invalid_proofs() -> anyhow::Result<()> {
+    logging::tracing_logger();
+
+    let network = Network::RegTest;
+    let genesis = Block::genesis(network);
+    let mut alice = generator([2u8; 32])?;
+    let chain = alice.chain(&genesis, 2, MockBlockOptions::default())?;
+
+    let fork = alice.successor(
+        &chain[0],
+        MockBlockOptions {
+            num_nop_transactions: 3,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(chain[1].header().height, fork.header().height);
+    assert_ne!(chain[1].hash(), fork.hash());
+    assert!(
+        fork.is_valid(&chain[0], fork.header().timestamp, network)
+            .await
+    );
+
+    let invalid = alice.successor(
+        &chain[1],
+        MockBlockOptions {
+            invalid_proof: true,
+            ..Default::default()
+        },
+    )?;
+    assert!(
+        !invalid
+            .is_valid(&chain[1], invalid.header().timestamp, network)
+            .await
+    );
+
+    Ok(())
+}
+
+/// test: mock chains are refused outside regtest
+#[test]
+pub fn mock_chain_generator_requires_regtest() {
+    let result = MockChainGenerator::new(Network::Main, WalletEntropy::devnet_wallet(), [0u8; 32]);
+
+    assert!(matches!(result, Err(RegTestError::WrongNetwork)));
+}
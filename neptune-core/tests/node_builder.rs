@@ -0,0 +1,57 @@
+mod common;
+
+use common::genesis_node::GenesisNode;
+use common::logging;
+use neptune_cash::api::export::BlockHeight;
+use neptune_cash::api::export::Network;
+use neptune_cash::api::node::NodeBuilder;
+use neptune_cash::application::plugin_hooks::HookEventKind;
+use tarpc::context;
+
+/// test: an embedded node serves its handles and shuts down gracefully
+///
+/// scenario:
+/// 1. alice builds a regtest node without incoming peer connections.
+/// 2. alice mines a block via the public api.
+/// 3. alice's event stream delivers the new block, once.
+/// 4. alice's RPC connection reports the new tip height.
+/// 5. alice shuts the node down, which exits successfully.
+#[tokio::test(flavor = "multi_thread")]
+pub async fn embedded_node_serves_handles_and_shuts_down() -> anyhow::Result<()> {
+    logging::tracing_logger();
+
+    let args = GenesisNode::default_args().await;
+    let node = NodeBuilder::from(args).max_num_peers(0).build().await?;
+    assert_eq!(Network::RegTest, node.global_state_lock().cli().network);
+
+    node.api()
+        .regtest_mut()
+        .mine_blocks_to_wallet(1, false)
+        .await?;
+
+    let mut events = node.events("alice");
+    let new_events =
+        tokio::time::timeout(std::time::Duration::from_secs(10), events.next()).await??;
+    assert!(new_events
+        .iter()
+        .any(|event| event.event.kind() == HookEventKind::NewBlock));
+
+    let mut other_events = node.events("alice");
+    let next_events =
+        tokio::time::timeout(std::time::Duration::from_millis(1500), other_events.next()).await;
+    assert!(next_events.is_err(), "events must be delivered once");
+
+    let rpc = node.rpc_connection().await?;
+    let height = rpc
+        .client
+        .block_height(context::current(), rpc.token)
+        .await??;
+    assert_eq!(BlockHeight::from(1u64), height);
+
+    let shutdown_handle = node.shutdown_handle();
+    let exit_code = node.shutdown().await?;
+    assert_eq!(neptune_cash::SUCCESS_EXIT_CODE, exit_code);
+    assert!(shutdown_handle.shutdown().await.is_err());
+
+    Ok(())
+}
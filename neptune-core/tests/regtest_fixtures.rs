@@ -0,0 +1,69 @@
+mod common;
+
+use common::logging;
+use neptune_cash::api::export::Block;
+use neptune_cash::api::export::NativeCurrencyAmount;
+use neptune_cash::api::export::Network;
+use neptune_cash::api::export::ReceivingAddress;
+use neptune_cash::api::regtest::error::RegTestError;
+use neptune_cash::api::regtest::DevnetPremine;
+use neptune_cash::state::wallet::wallet_entropy::WalletEntropy;
+
+/// test: devnet premine fixture matches the genesis block
+///
+/// scenario:
+/// 1. alice derives the devnet premine fixture for regtest.
+/// 2. every premine UTXO is committed to in the genesis block at its aocl
+///    leaf index.
+/// 3. every premine UTXO is locked to one of the fixture's spending keys,
+///    and its receiver preimage matches its receiver digest.
+/// 4. the fixture's wallet is the devnet wallet, and its first key receives
+///    premine.
+#[tokio::test(flavor = "multi_thread")]
+pub async fn devnet_premine_matches_genesis() -> anyhow::Result<()> {
+    logging::tracing_logger();
+
+    let network = Network::RegTest;
+    let premine = DevnetPremine::new(network)?;
+    assert_eq!(network, premine.network());
+    assert!(!premine.utxos().is_empty());
+    assert!(premine.total_amount() > NativeCurrencyAmount::coins(0));
+
+    let genesis = Block::genesis(network);
+    let genesis_outputs = &genesis.body().transaction_kernel.outputs;
+    let addresses = premine.receiving_addresses();
+    assert_eq!(premine.spending_keys().len(), addresses.len());
+
+    for utxo in premine.utxos() {
+        let leaf_index = usize::try_from(utxo.aocl_leaf_index)?;
+        assert_eq!(genesis_outputs[leaf_index], utxo.addition_record());
+        assert_eq!(
+            utxo.receiver_preimage.hash(),
+            utxo.utxo_triple.receiver_digest
+        );
+        assert!(addresses
+            .iter()
+            .any(|address| address.lock_script_hash() == utxo.utxo_triple.utxo.lock_script_hash()));
+    }
+
+    let devnet_wallet = WalletEntropy::devnet_wallet();
+    assert_eq!(devnet_wallet, premine.wallet_entropy());
+    let first_address =
+        ReceivingAddress::from(devnet_wallet.nth_generation_spending_key(0).to_address());
+    assert!(addresses.contains(&first_address));
+
+    Ok(())
+}
+
+/// test: devnet premine fixture is regtest-only
+///
+/// scenario:
+/// 1. alice derives the devnet premine fixture for mainnet.
+/// 2. the derivation fails with RegTestError::WrongNetwork.
+#[test]
+pub fn devnet_premine_requires_regtest() {
+    assert!(matches!(
+        DevnetPremine::new(Network::Main),
+        Err(RegTestError::WrongNetwork)
+    ));
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::genesis_node::GenesisNode;
+use common::logging;
+use neptune_cash::api::export::KeyType;
+use neptune_cash::api::export::NativeCurrencyAmount;
+use neptune_cash::api::export::Timestamp;
+use neptune_cash::api::tx_initiation::error::SendError;
+use neptune_cash::api::tx_initiation::recipient_policy::AddressDenylist;
+use neptune_cash::application::config::data_directory::DataDirectory;
+use num_traits::Zero;
+
+/// test: sends to a denied address are rejected and audited
+///
+/// scenario:
+/// 1. single unconnected node on regtest network
+/// 2. alice mines 3 blocks to her own wallet.
+/// 3. alice installs a denylist containing one of her own addresses.
+/// 4. alice fails to send to that address, and the denial is audited.
+/// 5. alice removes the denylist and sends successfully.
+#[tokio::test(flavor = "multi_thread")]
+pub async fn denied_recipient_is_rejected() -> anyhow::Result<()> {
+    logging::tracing_logger();
+
+    // start alice's node, without any peers.
+    let mut alice = GenesisNode::start_default_node().await?;
+
+    let denied_address = alice
+        .gsl
+        .api_mut()
+        .wallet_mut()
+        .next_receiving_address(KeyType::Generation)
+        .await?;
+
+    alice
+        .gsl
+        .api_mut()
+        .regtest_mut()
+        .mine_blocks_to_wallet(3, false)
+        .await?;
+
+    // alice denies sending to her address
+    let denylist = AddressDenylist::new([&denied_address]);
+    alice
+        .gsl
+        .api_mut()
+        .tx_initiator_mut()
+        .set_recipient_policy(Some(Arc::new(denylist)))
+        .await;
+
+    let outputs = vec![(
+        denied_address.clone(),
+        NativeCurrencyAmount::coins_from_str("1")?,
+    )];
+    let result = alice
+        .gsl
+        .api_mut()
+        .tx_sender_mut()
+        .send(
+            outputs.clone(),
+            Default::default(),
+            NativeCurrencyAmount::zero(),
+            Timestamp::now(),
+        )
+        .await;
+    assert!(matches!(result, Err(SendError::RecipientDenied(_))));
+
+    // the denial is recorded in the audit file
+    let cli = alice.gsl.cli();
+    let data_directory = DataDirectory::get(cli.data_dir.clone(), cli.network)?;
+    let audit = std::fs::read_to_string(data_directory.send_policy_audit_file_path())?;
+    assert_eq!(1, audit.lines().count());
+
+    // without policy, the send succeeds
+    alice
+        .gsl
+        .api_mut()
+        .tx_initiator_mut()
+        .set_recipient_policy(None)
+        .await;
+    alice
+        .gsl
+        .api_mut()
+        .tx_sender_mut()
+        .send(
+            outputs,
+            Default::default(),
+            NativeCurrencyAmount::zero(),
+            Timestamp::now(),
+        )
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::genesis_node::GenesisNode;
+use common::logging;
+use neptune_cash::api::export::Timestamp;
+use neptune_cash::api::wallet::error::WalletError;
+
+/// test: the wallet accepts valid membership proofs of its own utxos only
+///
+/// scenario:
+/// 1. single unconnected node on regtest network
+/// 2. alice mines 2 blocks to her own wallet.
+/// 3. alice resubmits the membership proof of one of her utxos, which is
+///    accepted and leaves her balance unchanged.
+/// 4. alice submits the membership proof of one utxo for another, which is
+///    rejected.
+#[tokio::test(flavor = "multi_thread")]
+pub async fn wallet_accepts_valid_membership_proofs() -> anyhow::Result<()> {
+    logging::tracing_logger();
+
+    // start alice's node, without any peers.
+    let mut alice = GenesisNode::start_default_node().await?;
+
+    // alice mines 2 blocks to her wallet
+    alice
+        .gsl
+        .api_mut()
+        .regtest_mut()
+        .mine_blocks_to_wallet(2, false)
+        .await?;
+
+    let balances_before = alice.gsl.api().wallet().balances(Timestamp::now()).await;
+    let inputs = alice
+        .gsl
+        .api()
+        .wallet()
+        .spendable_inputs(Timestamp::now())
+        .await;
+    let utxos = inputs.utxos();
+    let membership_proofs = inputs.ms_membership_proofs();
+    assert!(utxos.len() >= 2);
+
+    // alice resubmits the proof of an utxo she already monitors
+    alice
+        .gsl
+        .api_mut()
+        .wallet_mut()
+        .submit_membership_proof(utxos[0].clone(), membership_proofs[0].clone())
+        .await?;
+
+    let balances_after = alice.gsl.api().wallet().balances(Timestamp::now()).await;
+    assert_eq!(
+        balances_before.confirmed_available,
+        balances_after.confirmed_available
+    );
+    assert_eq!(
+        balances_before.confirmed_total,
+        balances_after.confirmed_total
+    );
+
+    // a proof for another utxo is rejected
+    let result = alice
+        .gsl
+        .api_mut()
+        .wallet_mut()
+        .submit_membership_proof(utxos[0].clone(), membership_proofs[1].clone())
+        .await;
+    assert!(matches!(result, Err(WalletError::InvalidMembershipProof)));
+
+    Ok(())
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use clap::Parser;
+use common::genesis_node::GenesisNode;
+use common::logging;
+use neptune_cash::api::export::KeyType;
+use neptune_cash::api::export::NativeCurrencyAmount;
+use neptune_cash::api::export::Network;
+use neptune_cash::api::export::Timestamp;
+use neptune_cash::api::tx_initiation::error::TxInitiationError;
+use neptune_cash::api::tx_initiation::raw_transaction::RawOutput;
+use neptune_cash::api::tx_initiation::raw_transaction::RawTransactionSpec;
+
+/// test: an unfunded send is classified as insufficient funds
+///
+/// scenario:
+/// 1. single unconnected node on regtest network, with an empty wallet.
+/// 2. alice attempts to send 1 coin to herself.
+/// 3. the failure is classified as insufficient funds.
+#[tokio::test(flavor = "multi_thread")]
+pub async fn unfunded_send_is_insufficient_funds() -> anyhow::Result<()> {
+    logging::tracing_logger();
+
+    let mut alice = GenesisNode::start_default_node().await?;
+    let address = alice
+        .gsl
+        .api_mut()
+        .wallet_mut()
+        .next_receiving_address(KeyType::Generation)
+        .await?;
+
+    let result = alice
+        .gsl
+        .api_mut()
+        .tx_sender_mut()
+        .send(
+            vec![(address, NativeCurrencyAmount::coins(1))],
+            Default::default(),
+            NativeCurrencyAmount::zero(),
+            Timestamp::now(),
+        )
+        .await;
+
+    let error = TxInitiationError::from(&result.unwrap_err());
+    assert!(matches!(error, TxInitiationError::InsufficientFunds { .. }));
+
+    Ok(())
+}
+
+/// test: a send paying less than the configured minimum fee is refused
+///
+/// scenario:
+/// 1. single unconnected node on regtest network with `--min-send-fee 1`.
+/// 2. alice mines 3 blocks to her own wallet.
+/// 3. alice attempts to send with a fee of 0.5 coins.
+/// 4. the failure is classified as below the fee floor.
+#[tokio::test(flavor = "multi_thread")]
+pub async fn send_below_min_send_fee_is_refused() -> anyhow::Result<()> {
+    logging::tracing_logger();
+
+    let mut args = GenesisNode::default_args().await;
+    args.update_from(["neptune-core", "--min-send-fee", "1"]);
+    let mut alice = GenesisNode::start_node(args).await?;
+
+    alice
+        .gsl
+        .api_mut()
+        .regtest_mut()
+        .mine_blocks_to_wallet(3, false)
+        .await?;
+    let address = alice
+        .gsl
+        .api_mut()
+        .wallet_mut()
+        .next_receiving_address(KeyType::Generation)
+        .await?;
+
+    let fee = NativeCurrencyAmount::coins_from_str("0.5")?;
+    let result = alice
+        .gsl
+        .api_mut()
+        .tx_sender_mut()
+        .send(
+            vec![(address, NativeCurrencyAmount::coins(1))],
+            Default::default(),
+            fee,
+            Timestamp::now(),
+        )
+        .await;
+
+    assert_eq!(
+        TxInitiationError::BelowFeeFloor {
+            fee,
+            floor: NativeCurrencyAmount::coins(1),
+        },
+        TxInitiationError::from(&result.unwrap_err())
+    );
+
+    Ok(())
+}
+
+/// test: a raw transaction paying to another network's address is refused
+///
+/// scenario:
+/// 1. single unconnected node on regtest network.
+/// 2. alice attempts to send to one of her addresses, encoded for mainnet.
+/// 3. the failure is classified as an address for the wrong network.
+#[tokio::test(flavor = "multi_thread")]
+pub async fn send_to_address_of_other_network_is_refused() -> anyhow::Result<()> {
+    logging::tracing_logger();
+
+    let mut alice = GenesisNode::start_default_node().await?;
+    let network = alice.gsl.cli().network;
+    let mainnet_address = alice
+        .gsl
+        .api_mut()
+        .wallet_mut()
+        .next_receiving_address(KeyType::Generation)
+        .await?
+        .to_bech32m(Network::Main)?;
+
+    let spec = RawTransactionSpec {
+        inputs: vec![0],
+        outputs: vec![RawOutput {
+            address: mainnet_address,
+            amount: "1".to_string(),
+        }],
+        fee: "0".to_string(),
+        timestamp: None,
+        announcements: vec![],
+        change_policy: Default::default(),
+    };
+    let result = alice.gsl.api_mut().tx_sender_mut().send_raw(&spec).await;
+
+    assert_eq!(
+        TxInitiationError::AddressWrongNetwork { index: 0, network },
+        TxInitiationError::from(&result.unwrap_err())
+    );
+
+    Ok(())
+}
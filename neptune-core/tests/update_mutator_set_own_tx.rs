@@ -39,8 +39,8 @@ pub async fn alice_updates_mutator_set_data_on_own_transaction() {
 
         // random ports to prevent multiple test runs from using same
         // socket.
-        cli_args.peer_port = rng.random_range((1 << 10)..=u16::MAX);
-        cli_args.rpc_port = rng.random_range((1 << 10)..=u16::MAX);
+        cli_args.peer_port = Some(rng.random_range((1 << 10)..=u16::MAX));
+        cli_args.rpc_port = Some(rng.random_range((1 << 10)..=u16::MAX));
         let mut alice = GenesisNode::start_node(cli_args).await.unwrap();
 
         // Mine two blocks to get a positive balance
@@ -0,0 +1,112 @@
+mod common;
+
+use common::genesis_node::GenesisNode;
+use common::logging;
+use neptune_cash::api::export::KeyType;
+use neptune_cash::api::export::Memo;
+use neptune_cash::api::export::NativeCurrencyAmount;
+use neptune_cash::api::export::Timestamp;
+use neptune_cash::api::tx_initiation::raw_transaction::RawOutput;
+use neptune_cash::api::tx_initiation::raw_transaction::RawTransactionSpec;
+use tasm_lib::triton_vm::prelude::BFieldElement;
+
+/// test: memos announced along with a payment are decoded by the recipient
+///
+/// scenario:
+/// 1. single unconnected node on regtest network
+/// 2. alice mines 3 blocks to her own wallet, which carry no memos.
+/// 3. alice pays herself, announcing an amount hint, an invoice id, and a
+///    memo of an unknown format to the receiving address.
+/// 4. alice mines the payment into block 4.
+/// 5. the received utxo carries the recognized memos decoded, and the
+///    unknown one raw.
+#[tokio::test(flavor = "multi_thread")]
+pub async fn memos_are_decoded_for_received_utxos() -> anyhow::Result<()> {
+    logging::tracing_logger();
+    let timeout_secs = 5;
+
+    // start alice's node, without any peers.
+    let mut alice = GenesisNode::start_default_node().await?;
+    let network = alice.gsl.cli().network;
+
+    let alice_address = alice
+        .gsl
+        .api_mut()
+        .wallet_mut()
+        .next_receiving_address(KeyType::Generation)
+        .await?;
+
+    // alice mines 3 blocks to her wallet
+    alice
+        .gsl
+        .api_mut()
+        .regtest_mut()
+        .mine_blocks_to_wallet(3, false)
+        .await?;
+    assert!(alice.gsl.api().wallet().utxo_memos().await.is_empty());
+
+    // alice pays herself, with memos
+    let amount = NativeCurrencyAmount::coins(1);
+    let memos = vec![
+        Memo::AmountHint(amount),
+        Memo::InvoiceId("INV-2026-0042".to_string()),
+        Memo::Raw {
+            format: BFieldElement::new(1000),
+            payload: vec![BFieldElement::new(7)],
+        },
+    ];
+    let input = alice
+        .gsl
+        .api()
+        .wallet()
+        .spendable_inputs(Timestamp::now())
+        .await
+        .first()
+        .expect("alice should have a spendable input")
+        .mutator_set_mp()
+        .aocl_leaf_index;
+    let spec = RawTransactionSpec {
+        inputs: vec![input],
+        outputs: vec![RawOutput {
+            address: alice_address.to_bech32m(network)?,
+            amount: "1".to_string(),
+        }],
+        fee: "0.01".to_string(),
+        timestamp: None,
+        announcements: memos
+            .iter()
+            .map(|memo| {
+                memo.to_announcement(&alice_address)
+                    .message
+                    .iter()
+                    .map(|element| element.value())
+                    .collect()
+            })
+            .collect(),
+        change_policy: Default::default(),
+    };
+    let tx_artifacts = alice.gsl.api_mut().tx_sender_mut().send_raw(&spec).await?;
+
+    alice
+        .wait_until_tx_in_mempool_has_single_proof(tx_artifacts.transaction().txid(), timeout_secs)
+        .await?;
+
+    // alice mines the payment into block 4
+    alice
+        .gsl
+        .api_mut()
+        .regtest_mut()
+        .mine_blocks_to_wallet(1, true)
+        .await?;
+
+    // the payment carries the memos, decoded where recognized
+    let utxo_memos = alice.gsl.api().wallet().utxo_memos().await;
+    let payment = utxo_memos
+        .iter()
+        .find(|utxo| utxo.amount == amount)
+        .expect("payment should carry memos");
+    assert_eq!(memos, payment.memos);
+    assert_eq!(4u64, u64::from(payment.confirmed_at));
+
+    Ok(())
+}
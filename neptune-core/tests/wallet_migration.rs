@@ -0,0 +1,139 @@
+mod common;
+
+use common::genesis_node::GenesisNode;
+use common::logging;
+use neptune_cash::api::export::Network;
+use neptune_cash::api::wallet::error::WalletMigrationError;
+use neptune_cash::api::wallet::WalletMigration;
+use neptune_cash::application::config::data_directory::DataDirectory;
+use neptune_cash::state::wallet::wallet_file::WalletFile;
+use neptune_cash::state::wallet::wallet_file::WalletFileContext;
+
+/// creates a wallet secret in a fresh data directory, as `generate-wallet` does.
+async fn data_directory_with_wallet(network: Network) -> anyhow::Result<DataDirectory> {
+    let data_dir = GenesisNode::integration_test_data_directory(network)?;
+    let wallet_dir = data_dir.wallet_directory_path();
+    DataDirectory::create_dir_if_not_exists(&wallet_dir).await?;
+    WalletFileContext::read_from_file_or_create(&wallet_dir)?;
+    Ok(data_dir)
+}
+
+fn read_wallet_file(data_dir: &DataDirectory) -> anyhow::Result<WalletFile> {
+    WalletFile::read_from_file(&WalletFileContext::wallet_secret_path(
+        &data_dir.wallet_directory_path(),
+    ))
+}
+
+/// test: a wallet is migrated into a fresh data directory and verified
+///
+/// scenario:
+/// 1. alice generates a wallet without ever running neptune-core.
+/// 2. alice migrates the wallet into a fresh data directory.
+/// 3. the migrated wallet secret matches the original, the original is left
+///    in place, and the destination is marked as belonging to regtest.
+#[tokio::test(flavor = "multi_thread")]
+pub async fn wallet_is_migrated_into_fresh_data_directory() -> anyhow::Result<()> {
+    logging::tracing_logger();
+
+    let network = Network::RegTest;
+    let from = data_directory_with_wallet(network).await?;
+    let to = GenesisNode::integration_test_data_directory(network)?;
+
+    let report = WalletMigration::new(from.clone(), network, to.clone(), network)
+        .migrate()
+        .await?;
+
+    // wallet.dat plus incoming and outgoing randomness files.
+    assert_eq!(3, report.files_verified);
+    assert!(report.wallet_db.is_none());
+    assert_eq!(to.wallet_directory_path(), report.to_wallet_dir);
+
+    assert_eq!(read_wallet_file(&from)?, read_wallet_file(&to)?);
+    assert_eq!(Some(network), to.recorded_network().await?);
+
+    Ok(())
+}
+
+/// test: migrating a wallet to another network is refused
+///
+/// scenario:
+/// 1. alice generates a regtest wallet.
+/// 2. alice attempts to migrate it into a testnet data directory.
+/// 3. the migration is refused and nothing is written at the destination.
+#[tokio::test(flavor = "multi_thread")]
+pub async fn migration_across_networks_is_refused() -> anyhow::Result<()> {
+    logging::tracing_logger();
+
+    let from = data_directory_with_wallet(Network::RegTest).await?;
+    let to = GenesisNode::integration_test_data_directory(Network::Testnet(0))?;
+
+    let result = WalletMigration::new(from, Network::RegTest, to.clone(), Network::Testnet(0))
+        .migrate()
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(WalletMigrationError::IncompatibleNetworks {
+            from: Network::RegTest,
+            to: Network::Testnet(0),
+        })
+    ));
+    assert!(!to.wallet_directory_path().exists());
+
+    Ok(())
+}
+
+/// test: migration does not overwrite an existing wallet
+///
+/// scenario:
+/// 1. alice and bob each generate a wallet.
+/// 2. alice attempts to migrate her wallet into bob's data directory.
+/// 3. the migration is refused and bob's wallet is unchanged.
+#[tokio::test(flavor = "multi_thread")]
+pub async fn migration_does_not_overwrite_existing_wallet() -> anyhow::Result<()> {
+    logging::tracing_logger();
+
+    let network = Network::RegTest;
+    let alice = data_directory_with_wallet(network).await?;
+    let bob = data_directory_with_wallet(network).await?;
+    let bob_wallet = read_wallet_file(&bob)?;
+
+    let result = WalletMigration::new(alice, network, bob.clone(), network)
+        .migrate()
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(WalletMigrationError::DestinationNotEmpty(_))
+    ));
+    assert_eq!(bob_wallet, read_wallet_file(&bob)?);
+
+    Ok(())
+}
+
+/// test: the wallet of a running node cannot be migrated
+///
+/// scenario:
+/// 1. alice starts her node, which opens the wallet database.
+/// 2. alice attempts to migrate her wallet while the node runs.
+/// 3. the migration is refused because the database is locked, and nothing
+///    is left behind at the destination.
+#[tokio::test(flavor = "multi_thread")]
+pub async fn wallet_of_running_node_is_not_migrated() -> anyhow::Result<()> {
+    logging::tracing_logger();
+
+    let args = GenesisNode::default_args().await;
+    let network = args.network;
+    let from = DataDirectory::get(args.data_dir.clone(), network)?;
+    let _alice = GenesisNode::start_node(args).await?;
+
+    let to = GenesisNode::integration_test_data_directory(network)?;
+    let result = WalletMigration::new(from, network, to.clone(), network)
+        .migrate()
+        .await;
+
+    assert!(matches!(result, Err(WalletMigrationError::Database { .. })));
+    assert!(!to.wallet_directory_path().exists());
+
+    Ok(())
+}
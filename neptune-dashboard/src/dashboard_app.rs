@@ -65,8 +65,16 @@ use crate::utxos_screen::UtxosScreen;
 #[clap(name = "neptune-dashboard", about = "Terminal user interface")]
 pub struct Config {
     /// Sets the neptune-core rpc server localhost port to connect to.
-    #[clap(short, long, default_value = "9799", value_name = "port")]
-    pub port: u16,
+    ///
+    /// Defaults to the default rpc port of --network.
+    #[clap(short, long, value_name = "port")]
+    pub port: Option<u16>,
+
+    /// network of the neptune-core instance to connect to.
+    ///
+    /// Only determines the default --port.
+    #[clap(long, default_value_t)]
+    pub network: Network,
 
     /// neptune-core data directory containing wallet and blockchain state
     #[clap(long)]
@@ -41,7 +41,10 @@ async fn main() {
 
     // Create connection to RPC server
     let args: Config = Config::parse();
-    let server_socket = SocketAddr::new(std::net::IpAddr::V4(Ipv4Addr::LOCALHOST), args.port);
+    let server_socket = SocketAddr::new(
+        std::net::IpAddr::V4(Ipv4Addr::LOCALHOST),
+        args.port.unwrap_or_else(|| args.network.default_rpc_port()),
+    );
     let transport = tarpc::serde_transport::tcp::connect(server_socket, Json::default).await;
     let client = match transport {
         Ok(transp) => {